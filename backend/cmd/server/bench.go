@@ -0,0 +1,452 @@
+package main
+
+import (
+	"bufio"
+	"bytes"
+	"context"
+	"encoding/json"
+	"flag"
+	"fmt"
+	"io"
+	"math"
+	"math/rand/v2"
+	"net/http"
+	"os"
+	"os/signal"
+	"sort"
+	"strings"
+	"sync"
+	"syscall"
+	"time"
+
+	"github.com/tidwall/gjson"
+)
+
+// runBench 向运行中的实例发送合成对话流量（开环：按固定速率发起请求，不等待前一个请求完成），
+// 结束后输出延迟分位数与错误率，例如：
+//
+//	sub2api bench -api-key sk-xxx -model claude-sonnet-4-5 -rps 50 -duration 60s
+//	sub2api bench -api-key sk-xxx -model gpt-5 -api openai -stream -mock-upstream 127.0.0.1:18080
+//
+// -mock-upstream 在本进程内启动模拟上游（Anthropic /v1/messages 与 OpenAI /v1/responses），
+// 将测试账号的 base_url 指向该地址即可在不消耗真实额度的情况下压测网关本身。
+func runBench(args []string) error {
+	fs := flag.NewFlagSet("bench", flag.ContinueOnError)
+	server := fs.String("server", envOr("SUB2API_SERVER", "http://127.0.0.1:8080"), "Server base URL (env SUB2API_SERVER)")
+	apiKey := fs.String("api-key", os.Getenv("SUB2API_API_KEY"), "Gateway API key used for the requests (env SUB2API_API_KEY)")
+	model := fs.String("model", "", "Model to request")
+	api := fs.String("api", "anthropic", "Request format: anthropic (/v1/messages) or openai (/v1/responses)")
+	rps := fs.Float64("rps", 10, "Requests started per second")
+	duration := fs.Duration("duration", 60*time.Second, "How long to generate traffic")
+	concurrency := fs.Int("concurrency", 256, "Maximum in-flight requests; ticks beyond it are counted as dropped")
+	stream := fs.Bool("stream", false, "Send streaming requests and measure time to first token")
+	maxTokens := fs.Int("max-tokens", 256, "max_tokens / max_output_tokens of each request")
+	timeout := fs.Duration("timeout", 2*time.Minute, "Per-request timeout")
+	mockAddr := fs.String("mock-upstream", "", "Also serve a mock upstream on this address, e.g. 127.0.0.1:18080")
+	mockLatency := fs.Duration("mock-latency", 300*time.Millisecond, "Mock upstream: mean response latency")
+	mockErrorRate := fs.Float64("mock-error-rate", 0, "Mock upstream: share of requests answered with 529 overloaded (0-1)")
+	jsonOutput := fs.Bool("json", false, "Print the final report as JSON")
+	if err := fs.Parse(args); err != nil {
+		return err
+	}
+	if *model == "" {
+		return fmt.Errorf("-model is required")
+	}
+	if *apiKey == "" {
+		return fmt.Errorf("-api-key (or SUB2API_API_KEY) is required")
+	}
+	if *rps <= 0 || *duration <= 0 || *concurrency <= 0 || *maxTokens <= 0 {
+		return fmt.Errorf("-rps, -duration, -concurrency and -max-tokens must be positive")
+	}
+	if *mockErrorRate < 0 || *mockErrorRate > 1 {
+		return fmt.Errorf("-mock-error-rate must be between 0 and 1")
+	}
+	var endpoint string
+	switch *api {
+	case "anthropic":
+		endpoint = "/v1/messages"
+	case "openai":
+		endpoint = "/v1/responses"
+	default:
+		return fmt.Errorf("-api must be anthropic or openai")
+	}
+
+	ctx, stop := signal.NotifyContext(context.Background(), os.Interrupt, syscall.SIGTERM)
+	defer stop()
+
+	if *mockAddr != "" {
+		mock, err := startMockUpstream(*mockAddr, *mockLatency, *mockErrorRate)
+		if err != nil {
+			return err
+		}
+		defer func() { _ = mock.Close() }()
+		fmt.Fprintf(os.Stderr, "mock upstream listening on http://%s\n", *mockAddr)
+	}
+
+	b := &bench{
+		url:       strings.TrimRight(*server, "/") + endpoint,
+		apiKey:    *apiKey,
+		model:     *model,
+		openai:    *api == "openai",
+		stream:    *stream,
+		maxTokens: *maxTokens,
+		client: &http.Client{
+			Timeout: *timeout,
+			Transport: &http.Transport{
+				MaxIdleConns:        *concurrency,
+				MaxIdleConnsPerHost: *concurrency,
+				IdleConnTimeout:     90 * time.Second,
+			},
+		},
+		stats: newBenchStats(),
+	}
+	fmt.Fprintf(os.Stderr, "bench: %s %s at %.1f rps for %s (max %d in flight)\n", *model, b.url, *rps, *duration, *concurrency)
+	b.run(ctx, *rps, *duration, *concurrency)
+
+	report := b.stats.report(time.Since(b.stats.start))
+	if *jsonOutput {
+		out, err := json.MarshalIndent(report, "", "  ")
+		if err != nil {
+			return err
+		}
+		fmt.Println(string(out))
+	} else {
+		report.print(os.Stdout)
+	}
+	if report.Succeeded == 0 && report.Sent > 0 {
+		return fmt.Errorf("all %d requests failed", report.Sent)
+	}
+	return nil
+}
+
+type bench struct {
+	url       string
+	apiKey    string
+	model     string
+	openai    bool
+	stream    bool
+	maxTokens int
+	client    *http.Client
+	stats     *benchStats
+}
+
+// run 按固定间隔发起请求，每 10 秒输出一次进度；到时后等待在途请求结束。
+// ctx 取消（Ctrl-C）时立即取消在途请求，不等待单请求超时。
+func (b *bench) run(ctx context.Context, rps float64, duration time.Duration, concurrency int) {
+	runCtx, cancel := context.WithTimeout(ctx, duration)
+	defer cancel()
+
+	interval := time.Duration(float64(time.Second) / rps)
+	ticker := time.NewTicker(interval)
+	defer ticker.Stop()
+	progress := time.NewTicker(10 * time.Second)
+	defer progress.Stop()
+
+	slots := make(chan struct{}, concurrency)
+	var wg sync.WaitGroup
+	b.stats.start = time.Now()
+	for {
+		select {
+		case <-runCtx.Done():
+			wg.Wait()
+			return
+		case <-progress.C:
+			r := b.stats.report(time.Since(b.stats.start))
+			fmt.Fprintf(os.Stderr, "  %4.0fs  sent=%d ok=%d err=%d dropped=%d p50=%.0fms p99=%.0fms\n",
+				r.ElapsedSeconds, r.Sent, r.Succeeded, r.Failed, r.Dropped, r.Latency.P50, r.Latency.P99)
+		case <-ticker.C:
+			select {
+			case slots <- struct{}{}:
+			default:
+				b.stats.drop()
+				continue
+			}
+			wg.Add(1)
+			go func() {
+				defer wg.Done()
+				defer func() { <-slots }()
+				// 在途请求不随压测时长结束而取消，只受单请求超时与 ctx 约束
+				b.stats.record(b.do(ctx))
+			}()
+		}
+	}
+}
+
+// benchCanceled 请求因压测被中断（Ctrl-C）而取消，单独计数，不计入失败
+const benchCanceled = "canceled"
+
+// benchResult 单个请求的结果；status 为 0 表示网络错误或超时
+type benchResult struct {
+	status       int
+	errType      string
+	latency      time.Duration
+	ttft         time.Duration
+	outputTokens int64
+}
+
+func (b *bench) do(ctx context.Context) benchResult {
+	body, _ := json.Marshal(b.payload())
+	req, err := http.NewRequestWithContext(ctx, http.MethodPost, b.url, bytes.NewReader(body))
+	if err != nil {
+		return benchResult{errType: err.Error()}
+	}
+	req.Header.Set("Content-Type", "application/json")
+	req.Header.Set("Authorization", "Bearer "+b.apiKey)
+	if !b.openai {
+		req.Header.Set("anthropic-version", "2023-06-01")
+	}
+
+	start := time.Now()
+	resp, err := b.client.Do(req)
+	if err != nil {
+		return benchResult{errType: benchErrType(ctx, "network"), latency: time.Since(start)}
+	}
+	defer func() { _ = resp.Body.Close() }()
+
+	res := benchResult{status: resp.StatusCode}
+	if resp.StatusCode != http.StatusOK {
+		raw, _ := io.ReadAll(io.LimitReader(resp.Body, 64<<10))
+		res.latency = time.Since(start)
+		res.errType = gjson.GetBytes(raw, "error.type").String()
+		if res.errType == "" {
+			res.errType = http.StatusText(resp.StatusCode)
+		}
+		return res
+	}
+
+	if !b.stream {
+		raw, err := io.ReadAll(resp.Body)
+		res.latency = time.Since(start)
+		if err != nil {
+			res.status, res.errType = 0, benchErrType(ctx, "read_body")
+			return res
+		}
+		res.outputTokens = gjson.GetBytes(raw, "usage.output_tokens").Int()
+		return res
+	}
+
+	// 流式：首个内容增量事件的到达时间为 TTFT，usage 取最后出现的 output_tokens
+	scanner := bufio.NewScanner(resp.Body)
+	scanner.Buffer(make([]byte, 64<<10), 4<<20)
+	for scanner.Scan() {
+		line := scanner.Bytes()
+		if !bytes.HasPrefix(line, []byte("data:")) {
+			continue
+		}
+		data := bytes.TrimSpace(line[len("data:"):])
+		event := gjson.GetBytes(data, "type").String()
+		if res.ttft == 0 && (event == "content_block_delta" || event == "response.output_text.delta") {
+			res.ttft = time.Since(start)
+		}
+		switch event {
+		case "message_delta":
+			res.outputTokens = gjson.GetBytes(data, "usage.output_tokens").Int()
+		case "response.completed":
+			res.outputTokens = gjson.GetBytes(data, "response.usage.output_tokens").Int()
+		case "error", "response.failed":
+			res.status, res.errType = 0, "stream_error"
+		}
+	}
+	res.latency = time.Since(start)
+	if err := scanner.Err(); err != nil && res.errType == "" {
+		res.status, res.errType = 0, benchErrType(ctx, "stream_interrupted")
+	}
+	return res
+}
+
+// benchErrType ctx 已取消时返回 benchCanceled，否则返回 errType
+func benchErrType(ctx context.Context, errType string) string {
+	if ctx.Err() != nil {
+		return benchCanceled
+	}
+	return errType
+}
+
+// benchPrompts 合成对话素材：长度从一句话到多段落不等，模拟真实请求的输入分布
+var benchPrompts = []string{
+	"Summarize the main differences between TCP and UDP in three bullet points.",
+	"Write a short haiku about autumn leaves.",
+	"Explain what a database index is to a junior developer, with an example.",
+	"Translate the following sentence into French and Spanish: The meeting has been moved to Thursday afternoon.",
+	"Review this function and suggest improvements:\n\nfunc sum(xs []int) int {\n\tt := 0\n\tfor i := 0; i < len(xs); i++ {\n\t\tt = t + xs[i]\n\t}\n\treturn t\n}",
+	"I am planning a three-day trip to Kyoto in November. Suggest an itinerary that balances temples, food and walking, and mention anything I should book in advance.",
+	"What are the trade-offs between optimistic and pessimistic locking? Give one scenario where each is the better choice.",
+	"Draft a polite email declining a meeting invitation because of a scheduling conflict, and propose two alternative times.",
+	"List five common causes of memory leaks in long-running services and how to detect each of them.",
+	"Given the CSV header 'date,region,product,units,revenue', write a SQL query that returns the top three products by revenue per region for 2025.",
+}
+
+var benchFollowUps = []string{
+	"Can you make that shorter?",
+	"Now give me a concrete example.",
+	"What would you change if performance mattered most?",
+}
+
+// payload 随机生成 1-3 轮对话
+func (b *bench) payload() map[string]any {
+	turns := 1 + rand.IntN(3)
+	var messages []map[string]string
+	messages = append(messages, map[string]string{"role": "user", "content": benchPrompts[rand.IntN(len(benchPrompts))]})
+	for i := 1; i < turns; i++ {
+		messages = append(messages,
+			map[string]string{"role": "assistant", "content": "Here is a first answer covering the key points you asked about."},
+			map[string]string{"role": "user", "content": benchFollowUps[rand.IntN(len(benchFollowUps))]},
+		)
+	}
+	if b.openai {
+		return map[string]any{
+			"model":             b.model,
+			"input":             messages,
+			"max_output_tokens": b.maxTokens,
+			"stream":            b.stream,
+		}
+	}
+	return map[string]any{
+		"model":      b.model,
+		"messages":   messages,
+		"max_tokens": b.maxTokens,
+		"stream":     b.stream,
+	}
+}
+
+type benchStats struct {
+	mu        sync.Mutex
+	start     time.Time
+	dropped   int
+	canceled  int
+	latencies []time.Duration
+	ttfts     []time.Duration
+	errors    map[string]int
+	succeeded int
+	failed    int
+	tokens    int64
+}
+
+func newBenchStats() *benchStats {
+	return &benchStats{errors: map[string]int{}}
+}
+
+func (s *benchStats) drop() {
+	s.mu.Lock()
+	s.dropped++
+	s.mu.Unlock()
+}
+
+func (s *benchStats) record(r benchResult) {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+	if r.errType == benchCanceled {
+		s.canceled++
+		return
+	}
+	if r.status != http.StatusOK || r.errType != "" {
+		s.failed++
+		key := r.errType
+		if r.status > 0 {
+			key = fmt.Sprintf("HTTP %d %s", r.status, r.errType)
+		}
+		s.errors[key]++
+		return
+	}
+	s.succeeded++
+	s.latencies = append(s.latencies, r.latency)
+	if r.ttft > 0 {
+		s.ttfts = append(s.ttfts, r.ttft)
+	}
+	s.tokens += r.outputTokens
+}
+
+// benchReport 压测结果；延迟只统计成功请求
+type benchReport struct {
+	ElapsedSeconds float64        `json:"elapsed_seconds"`
+	Sent           int            `json:"sent"`
+	Succeeded      int            `json:"succeeded"`
+	Failed         int            `json:"failed"`
+	Dropped        int            `json:"dropped"`
+	Canceled       int            `json:"canceled"`
+	ErrorRate      float64        `json:"error_rate"`
+	ThroughputRPS  float64        `json:"throughput_rps"`
+	OutputTokens   int64          `json:"output_tokens"`
+	Latency        latencySummary `json:"latency"`
+	TTFT           latencySummary `json:"ttft"`
+	Errors         map[string]int `json:"errors,omitempty"`
+}
+
+// latencySummary 延迟分位数（毫秒）
+type latencySummary struct {
+	P50 float64 `json:"p50_ms"`
+	P90 float64 `json:"p90_ms"`
+	P95 float64 `json:"p95_ms"`
+	P99 float64 `json:"p99_ms"`
+	Max float64 `json:"max_ms"`
+}
+
+func (l latencySummary) String() string {
+	return fmt.Sprintf("p50=%.0fms p90=%.0fms p95=%.0fms p99=%.0fms max=%.0fms", l.P50, l.P90, l.P95, l.P99, l.Max)
+}
+
+func (s *benchStats) report(elapsed time.Duration) benchReport {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+	r := benchReport{
+		ElapsedSeconds: elapsed.Seconds(),
+		Sent:           s.succeeded + s.failed,
+		Succeeded:      s.succeeded,
+		Failed:         s.failed,
+		Dropped:        s.dropped,
+		Canceled:       s.canceled,
+		OutputTokens:   s.tokens,
+		Latency:        summarizeLatencies(s.latencies),
+		TTFT:           summarizeLatencies(s.ttfts),
+		Errors:         make(map[string]int, len(s.errors)),
+	}
+	for k, v := range s.errors {
+		r.Errors[k] = v
+	}
+	if r.Sent > 0 {
+		r.ErrorRate = float64(r.Failed) / float64(r.Sent)
+	}
+	if elapsed > 0 {
+		r.ThroughputRPS = float64(r.Succeeded) / elapsed.Seconds()
+	}
+	return r
+}
+
+// summarizeLatencies 最近秩法计算分位数
+func summarizeLatencies(values []time.Duration) latencySummary {
+	if len(values) == 0 {
+		return latencySummary{}
+	}
+	sorted := append([]time.Duration(nil), values...)
+	sort.Slice(sorted, func(i, j int) bool { return sorted[i] < sorted[j] })
+	ms := func(d time.Duration) float64 { return float64(d) / float64(time.Millisecond) }
+	pct := func(p float64) float64 {
+		idx := int(math.Ceil(p*float64(len(sorted)))) - 1
+		return ms(sorted[min(max(idx, 0), len(sorted)-1)])
+	}
+	return latencySummary{P50: pct(0.50), P90: pct(0.90), P95: pct(0.95), P99: pct(0.99), Max: ms(sorted[len(sorted)-1])}
+}
+
+func (r benchReport) print(w io.Writer) {
+	fmt.Fprintf(w, "\nDuration:     %.1fs\n", r.ElapsedSeconds)
+	fmt.Fprintf(w, "Requests:     %d sent, %d ok, %d failed (%.2f%% errors), %d dropped\n", r.Sent, r.Succeeded, r.Failed, r.ErrorRate*100, r.Dropped)
+	if r.Canceled > 0 {
+		fmt.Fprintf(w, "Canceled:     %d in flight when interrupted\n", r.Canceled)
+	}
+	fmt.Fprintf(w, "Throughput:   %.2f req/s, %d output tokens\n", r.ThroughputRPS, r.OutputTokens)
+	fmt.Fprintf(w, "Latency:      %s\n", r.Latency)
+	if r.TTFT.Max > 0 {
+		fmt.Fprintf(w, "First token:  %s\n", r.TTFT)
+	}
+	if len(r.Errors) > 0 {
+		keys := make([]string, 0, len(r.Errors))
+		for k := range r.Errors {
+			keys = append(keys, k)
+		}
+		sort.Slice(keys, func(i, j int) bool { return r.Errors[keys[i]] > r.Errors[keys[j]] })
+		fmt.Fprintln(w, "Errors:")
+		for _, k := range keys {
+			fmt.Fprintf(w, "  %-40s %d\n", k, r.Errors[k])
+		}
+	}
+}
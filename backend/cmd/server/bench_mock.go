@@ -0,0 +1,209 @@
+package main
+
+import (
+	"context"
+	"encoding/json"
+	"fmt"
+	"io"
+	"math/rand/v2"
+	"net"
+	"net/http"
+	"strings"
+	"time"
+
+	"github.com/google/uuid"
+	"github.com/tidwall/gjson"
+)
+
+// mockUpstream 压测用的模拟上游：按 Anthropic Messages / OpenAI Responses 协议返回固定风格的文本，
+// 延迟在 latency 的 50%-150% 之间随机，errorRate 比例的请求返回 529 overloaded。
+type mockUpstream struct {
+	latency   time.Duration
+	errorRate float64
+}
+
+var mockWords = strings.Fields("the gateway forwards each request to an upstream account and streams the answer back " +
+	"while recording usage latency and cost so that capacity planning can rely on realistic numbers")
+
+// startMockUpstream 在 addr 上启动模拟上游，返回的 server 由调用方关闭
+func startMockUpstream(addr string, latency time.Duration, errorRate float64) (*http.Server, error) {
+	ln, err := net.Listen("tcp", addr)
+	if err != nil {
+		return nil, fmt.Errorf("mock upstream: %w", err)
+	}
+	srv := &http.Server{Handler: newMockUpstreamHandler(latency, errorRate), ReadHeaderTimeout: 10 * time.Second}
+	go func() { _ = srv.Serve(ln) }()
+	return srv, nil
+}
+
+// newMockUpstreamHandler 模拟上游的路由：POST /v1/messages 与 POST /v1/responses
+func newMockUpstreamHandler(latency time.Duration, errorRate float64) http.Handler {
+	m := &mockUpstream{latency: latency, errorRate: errorRate}
+	mux := http.NewServeMux()
+	mux.HandleFunc("POST /v1/messages", m.messages)
+	mux.HandleFunc("POST /v1/responses", m.responses)
+	return mux
+}
+
+// mockSleep 等待 d，客户端断开时提前返回 false
+func mockSleep(ctx context.Context, d time.Duration) bool {
+	if d <= 0 {
+		return ctx.Err() == nil
+	}
+	timer := time.NewTimer(d)
+	defer timer.Stop()
+	select {
+	case <-timer.C:
+		return true
+	case <-ctx.Done():
+		return false
+	}
+}
+
+// prepare 读取请求并返回模拟输出；返回 false 时已写入错误响应或客户端已断开
+func (m *mockUpstream) prepare(w http.ResponseWriter, r *http.Request, maxTokensField string) (model string, stream bool, words []string, inputTokens int, ok bool) {
+	body, err := io.ReadAll(io.LimitReader(r.Body, 32<<20))
+	if err != nil {
+		http.Error(w, err.Error(), http.StatusBadRequest)
+		return "", false, nil, 0, false
+	}
+	delay := m.latency/2 + time.Duration(rand.Int64N(int64(m.latency)+1))
+	if rand.Float64() < m.errorRate {
+		if !mockSleep(r.Context(), delay/4) {
+			return "", false, nil, 0, false
+		}
+		w.Header().Set("Content-Type", "application/json")
+		w.WriteHeader(529)
+		_, _ = io.WriteString(w, `{"type":"error","error":{"type":"overloaded_error","message":"mock upstream overloaded"}}`)
+		return "", false, nil, 0, false
+	}
+
+	maxTokens := int(gjson.GetBytes(body, maxTokensField).Int())
+	if maxTokens <= 0 {
+		maxTokens = 256
+	}
+	n := min(20+rand.IntN(100), maxTokens)
+	words = make([]string, n)
+	for i := range words {
+		words[i] = mockWords[rand.IntN(len(mockWords))]
+	}
+	model = gjson.GetBytes(body, "model").String()
+	stream = gjson.GetBytes(body, "stream").Bool()
+	// 非流式请求整体等待 delay；流式请求先等待首 token 延迟，其余时间分摊到各增量事件
+	if stream {
+		delay = delay * 3 / 10
+	}
+	if !mockSleep(r.Context(), delay) {
+		return "", false, nil, 0, false
+	}
+	return model, stream, words, max(len(body)/4, 1), true
+}
+
+func (m *mockUpstream) messages(w http.ResponseWriter, r *http.Request) {
+	model, stream, words, inputTokens, ok := m.prepare(w, r, "max_tokens")
+	if !ok {
+		return
+	}
+	id := "msg_mock_" + strings.ReplaceAll(uuid.NewString(), "-", "")
+	usage := map[string]int{"input_tokens": inputTokens, "output_tokens": len(words)}
+	if !stream {
+		writeMockJSON(w, map[string]any{
+			"id":          id,
+			"type":        "message",
+			"role":        "assistant",
+			"model":       model,
+			"content":     []map[string]string{{"type": "text", "text": strings.Join(words, " ")}},
+			"stop_reason": "end_turn",
+			"usage":       usage,
+		})
+		return
+	}
+
+	send := m.sse(w)
+	send("message_start", map[string]any{"type": "message_start", "message": map[string]any{
+		"id":      id,
+		"type":    "message",
+		"role":    "assistant",
+		"model":   model,
+		"content": []any{},
+		"usage":   map[string]int{"input_tokens": inputTokens, "output_tokens": 1},
+	}})
+	send("content_block_start", map[string]any{"type": "content_block_start", "index": 0, "content_block": map[string]string{"type": "text", "text": ""}})
+	for i, word := range words {
+		if i > 0 {
+			word = " " + word
+			if !mockSleep(r.Context(), m.tokenInterval(len(words))) {
+				return
+			}
+		}
+		send("content_block_delta", map[string]any{"type": "content_block_delta", "index": 0, "delta": map[string]string{"type": "text_delta", "text": word}})
+	}
+	send("content_block_stop", map[string]any{"type": "content_block_stop", "index": 0})
+	send("message_delta", map[string]any{"type": "message_delta", "delta": map[string]any{"stop_reason": "end_turn"}, "usage": map[string]int{"output_tokens": len(words)}})
+	send("message_stop", map[string]any{"type": "message_stop"})
+}
+
+func (m *mockUpstream) responses(w http.ResponseWriter, r *http.Request) {
+	model, stream, words, inputTokens, ok := m.prepare(w, r, "max_output_tokens")
+	if !ok {
+		return
+	}
+	id := "resp_mock_" + strings.ReplaceAll(uuid.NewString(), "-", "")
+	text := strings.Join(words, " ")
+	response := map[string]any{
+		"id":         id,
+		"object":     "response",
+		"created_at": time.Now().Unix(),
+		"status":     "completed",
+		"model":      model,
+		"output": []map[string]any{{
+			"id":      "msg_" + id,
+			"type":    "message",
+			"role":    "assistant",
+			"status":  "completed",
+			"content": []map[string]any{{"type": "output_text", "text": text, "annotations": []any{}}},
+		}},
+		"usage": map[string]int{"input_tokens": inputTokens, "output_tokens": len(words), "total_tokens": inputTokens + len(words)},
+	}
+	if !stream {
+		writeMockJSON(w, response)
+		return
+	}
+
+	send := m.sse(w)
+	send("response.created", map[string]any{"type": "response.created", "response": map[string]any{"id": id, "object": "response", "status": "in_progress", "model": model}})
+	for i, word := range words {
+		if i > 0 {
+			word = " " + word
+			if !mockSleep(r.Context(), m.tokenInterval(len(words))) {
+				return
+			}
+		}
+		send("response.output_text.delta", map[string]any{"type": "response.output_text.delta", "output_index": 0, "content_index": 0, "delta": word})
+	}
+	send("response.completed", map[string]any{"type": "response.completed", "response": response})
+}
+
+// tokenInterval 把 70% 的延迟平均分摊到各个增量事件
+func (m *mockUpstream) tokenInterval(tokens int) time.Duration {
+	return m.latency * 7 / 10 / time.Duration(max(tokens, 1))
+}
+
+func (m *mockUpstream) sse(w http.ResponseWriter) func(event string, data any) {
+	w.Header().Set("Content-Type", "text/event-stream")
+	w.Header().Set("Cache-Control", "no-cache")
+	w.WriteHeader(http.StatusOK)
+	flusher, _ := w.(http.Flusher)
+	return func(event string, data any) {
+		raw, _ := json.Marshal(data)
+		_, _ = fmt.Fprintf(w, "event: %s\ndata: %s\n\n", event, raw)
+		if flusher != nil {
+			flusher.Flush()
+		}
+	}
+}
+
+func writeMockJSON(w http.ResponseWriter, v any) {
+	w.Header().Set("Content-Type", "application/json")
+	_ = json.NewEncoder(w).Encode(v)
+}
@@ -0,0 +1,253 @@
+package main
+
+import (
+	"bufio"
+	"bytes"
+	"context"
+	"encoding/json"
+	"net/http"
+	"net/http/httptest"
+	"strings"
+	"testing"
+	"time"
+
+	"github.com/stretchr/testify/require"
+)
+
+func TestSummarizeLatencies(t *testing.T) {
+	ms := func(values ...int) []time.Duration {
+		out := make([]time.Duration, len(values))
+		for i, v := range values {
+			out[i] = time.Duration(v) * time.Millisecond
+		}
+		return out
+	}
+	oneToHundred := make([]int, 100)
+	for i := range oneToHundred {
+		oneToHundred[i] = i + 1
+	}
+
+	tests := []struct {
+		name   string
+		values []time.Duration
+		want   latencySummary
+	}{
+		{"empty", nil, latencySummary{}},
+		{"single", ms(5), latencySummary{P50: 5, P90: 5, P95: 5, P99: 5, Max: 5}},
+		{"unsorted", ms(30, 10, 20), latencySummary{P50: 20, P90: 30, P95: 30, P99: 30, Max: 30}},
+		{"nearest rank", ms(oneToHundred...), latencySummary{P50: 50, P90: 90, P95: 95, P99: 99, Max: 100}},
+		{"sub millisecond", []time.Duration{500 * time.Microsecond, 1500 * time.Microsecond}, latencySummary{P50: 0.5, P90: 1.5, P95: 1.5, P99: 1.5, Max: 1.5}},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			var input []time.Duration
+			if tt.values != nil {
+				input = append([]time.Duration(nil), tt.values...)
+			}
+			require.Equal(t, tt.want, summarizeLatencies(input))
+			// 不修改调用方的切片
+			require.Equal(t, tt.values, input)
+		})
+	}
+}
+
+func TestBenchStatsReport(t *testing.T) {
+	s := newBenchStats()
+	s.record(benchResult{status: http.StatusOK, latency: 100 * time.Millisecond, ttft: 40 * time.Millisecond, outputTokens: 10})
+	s.record(benchResult{status: http.StatusOK, latency: 300 * time.Millisecond, outputTokens: 5})
+	s.record(benchResult{status: 529, errType: "overloaded_error", latency: time.Second})
+	s.record(benchResult{errType: "network"})
+	s.record(benchResult{errType: benchCanceled})
+	s.drop()
+
+	r := s.report(2 * time.Second)
+	require.Equal(t, benchReport{
+		ElapsedSeconds: 2,
+		Sent:           4,
+		Succeeded:      2,
+		Failed:         2,
+		Dropped:        1,
+		Canceled:       1,
+		ErrorRate:      0.5,
+		ThroughputRPS:  1,
+		OutputTokens:   15,
+		Latency:        latencySummary{P50: 100, P90: 300, P95: 300, P99: 300, Max: 300},
+		TTFT:           latencySummary{P50: 40, P90: 40, P95: 40, P99: 40, Max: 40},
+		Errors:         map[string]int{"HTTP 529 overloaded_error": 1, "network": 1},
+	}, r)
+
+	// 报告中的错误表是副本
+	r.Errors["network"] = 99
+	require.Equal(t, 1, s.report(time.Second).Errors["network"])
+
+	empty := newBenchStats().report(0)
+	require.Zero(t, empty.ErrorRate)
+	require.Zero(t, empty.ThroughputRPS)
+
+	var out bytes.Buffer
+	s.report(2 * time.Second).print(&out)
+	require.Contains(t, out.String(), "4 sent, 2 ok, 2 failed (50.00% errors), 1 dropped")
+	require.Contains(t, out.String(), "Canceled:     1 in flight when interrupted")
+	require.Contains(t, out.String(), "First token:  p50=40ms")
+	require.Contains(t, out.String(), "HTTP 529 overloaded_error")
+}
+
+func postMock(t *testing.T, url, body string) *http.Response {
+	t.Helper()
+	resp, err := http.Post(url, "application/json", strings.NewReader(body))
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = resp.Body.Close() })
+	return resp
+}
+
+func sseEventNames(t *testing.T, resp *http.Response) []string {
+	t.Helper()
+	var names []string
+	scanner := bufio.NewScanner(resp.Body)
+	for scanner.Scan() {
+		if name, ok := strings.CutPrefix(scanner.Text(), "event: "); ok {
+			names = append(names, name)
+		}
+	}
+	require.NoError(t, scanner.Err())
+	return names
+}
+
+func TestMockUpstream_Responses(t *testing.T) {
+	srv := httptest.NewServer(newMockUpstreamHandler(0, 0))
+	defer srv.Close()
+
+	var msg struct {
+		Type       string `json:"type"`
+		Model      string `json:"model"`
+		StopReason string `json:"stop_reason"`
+		Content    []struct {
+			Text string `json:"text"`
+		} `json:"content"`
+		Usage struct {
+			InputTokens  int `json:"input_tokens"`
+			OutputTokens int `json:"output_tokens"`
+		} `json:"usage"`
+	}
+	resp := postMock(t, srv.URL+"/v1/messages", `{"model":"claude-x","max_tokens":3}`)
+	require.Equal(t, http.StatusOK, resp.StatusCode)
+	require.NoError(t, json.NewDecoder(resp.Body).Decode(&msg))
+	require.Equal(t, "message", msg.Type)
+	require.Equal(t, "claude-x", msg.Model)
+	require.Equal(t, "end_turn", msg.StopReason)
+	require.Len(t, msg.Content, 1)
+	require.Len(t, strings.Fields(msg.Content[0].Text), 3)
+	require.Equal(t, 3, msg.Usage.OutputTokens)
+	require.Positive(t, msg.Usage.InputTokens)
+
+	var response struct {
+		Status string `json:"status"`
+		Model  string `json:"model"`
+		Output []struct {
+			Content []struct {
+				Text string `json:"text"`
+			} `json:"content"`
+		} `json:"output"`
+		Usage struct {
+			InputTokens  int `json:"input_tokens"`
+			OutputTokens int `json:"output_tokens"`
+			TotalTokens  int `json:"total_tokens"`
+		} `json:"usage"`
+	}
+	resp = postMock(t, srv.URL+"/v1/responses", `{"model":"gpt-x","max_output_tokens":4}`)
+	require.Equal(t, http.StatusOK, resp.StatusCode)
+	require.NoError(t, json.NewDecoder(resp.Body).Decode(&response))
+	require.Equal(t, "completed", response.Status)
+	require.Equal(t, "gpt-x", response.Model)
+	require.Len(t, strings.Fields(response.Output[0].Content[0].Text), 4)
+	require.Equal(t, 4, response.Usage.OutputTokens)
+	require.Equal(t, response.Usage.InputTokens+response.Usage.OutputTokens, response.Usage.TotalTokens)
+
+	resp = postMock(t, srv.URL+"/v1/messages", `{"model":"claude-x","max_tokens":2,"stream":true}`)
+	require.Equal(t, "text/event-stream", resp.Header.Get("Content-Type"))
+	require.Equal(t, []string{
+		"message_start", "content_block_start", "content_block_delta", "content_block_delta",
+		"content_block_stop", "message_delta", "message_stop",
+	}, sseEventNames(t, resp))
+
+	resp = postMock(t, srv.URL+"/v1/responses", `{"model":"gpt-x","max_output_tokens":2,"stream":true}`)
+	require.Equal(t, []string{
+		"response.created", "response.output_text.delta", "response.output_text.delta", "response.completed",
+	}, sseEventNames(t, resp))
+}
+
+func TestMockUpstream_Overloaded(t *testing.T) {
+	srv := httptest.NewServer(newMockUpstreamHandler(0, 1))
+	defer srv.Close()
+
+	b := &bench{url: srv.URL + "/v1/messages", apiKey: "k", model: "m", maxTokens: 5, client: srv.Client()}
+	res := b.do(context.Background())
+	require.Equal(t, 529, res.status)
+	require.Equal(t, "overloaded_error", res.errType)
+}
+
+func TestBenchDo_AgainstMockUpstream(t *testing.T) {
+	srv := httptest.NewServer(newMockUpstreamHandler(0, 0))
+	defer srv.Close()
+
+	tests := []struct {
+		name     string
+		endpoint string
+		openai   bool
+		stream   bool
+	}{
+		{"anthropic", "/v1/messages", false, false},
+		{"anthropic stream", "/v1/messages", false, true},
+		{"openai", "/v1/responses", true, false},
+		{"openai stream", "/v1/responses", true, true},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			b := &bench{url: srv.URL + tt.endpoint, apiKey: "k", model: "m", openai: tt.openai, stream: tt.stream, maxTokens: 5, client: srv.Client()}
+			res := b.do(context.Background())
+			require.Equal(t, http.StatusOK, res.status)
+			require.Empty(t, res.errType)
+			require.Equal(t, int64(5), res.outputTokens)
+			require.Positive(t, res.latency)
+			if tt.stream {
+				require.Positive(t, res.ttft)
+				require.LessOrEqual(t, res.ttft, res.latency)
+			} else {
+				require.Zero(t, res.ttft)
+			}
+		})
+	}
+}
+
+func TestBenchRun_InFlightOutliveDuration(t *testing.T) {
+	srv := httptest.NewServer(newMockUpstreamHandler(400*time.Millisecond, 0))
+	defer srv.Close()
+
+	b := &bench{url: srv.URL + "/v1/messages", apiKey: "k", model: "m", maxTokens: 5, client: srv.Client(), stats: newBenchStats()}
+	b.run(context.Background(), 20, 150*time.Millisecond, 8)
+
+	// 压测时长结束后在途请求继续完成，不计为取消
+	r := b.stats.report(time.Since(b.stats.start))
+	require.Positive(t, r.Succeeded)
+	require.Zero(t, r.Failed)
+	require.Zero(t, r.Canceled)
+}
+
+func TestBenchRun_CancelAbortsInFlight(t *testing.T) {
+	srv := httptest.NewServer(newMockUpstreamHandler(10*time.Second, 0))
+	defer srv.Close()
+
+	b := &bench{url: srv.URL + "/v1/messages", apiKey: "k", model: "m", maxTokens: 5, client: srv.Client(), stats: newBenchStats()}
+	ctx, cancel := context.WithCancel(context.Background())
+	time.AfterFunc(300*time.Millisecond, cancel)
+
+	start := time.Now()
+	b.run(ctx, 20, time.Minute, 4)
+	// 模拟上游最快 5 秒才响应，Ctrl-C 后应立即返回
+	require.Less(t, time.Since(start), 3*time.Second)
+
+	r := b.stats.report(time.Since(b.stats.start))
+	require.Positive(t, r.Canceled)
+	require.Zero(t, r.Sent)
+	require.Zero(t, r.Failed)
+}
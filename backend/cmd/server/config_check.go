@@ -0,0 +1,55 @@
+package main
+
+import (
+	"flag"
+	"fmt"
+	"os"
+	"strings"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+)
+
+// stringListFlag 可重复的字符串参数，例如 -set a=1 -set b=2
+type stringListFlag []string
+
+func (f *stringListFlag) String() string {
+	return strings.Join(*f, ",")
+}
+
+func (f *stringListFlag) Set(v string) error {
+	*f = append(*f, v)
+	return nil
+}
+
+// runConfigCheck 加载并校验配置后退出，不连接数据库、不启动服务，例如：
+//
+//	sub2api config check
+//	sub2api -config /etc/sub2api/config.yaml -set server.port=9090 config check -strict
+func runConfigCheck(args []string) error {
+	fs := flag.NewFlagSet("config check", flag.ContinueOnError)
+	strict := fs.Bool("strict", false, "Treat unknown config keys as errors")
+	if err := fs.Parse(args); err != nil {
+		return err
+	}
+
+	cfg, report, err := config.Check()
+	if err != nil {
+		return err
+	}
+	if report.ConfigFile != "" {
+		fmt.Fprintf(os.Stderr, "Config file: %s\n", report.ConfigFile)
+	} else {
+		fmt.Fprintln(os.Stderr, "Config file: (none, using defaults and environment)")
+	}
+	for _, key := range report.UnknownKeys {
+		fmt.Fprintf(os.Stderr, "warning: unknown config key %q is ignored\n", key)
+	}
+	if *strict && len(report.UnknownKeys) > 0 {
+		return fmt.Errorf("%d unknown config key(s)", len(report.UnknownKeys))
+	}
+	if strings.TrimSpace(cfg.JWT.Secret) == "" {
+		fmt.Fprintln(os.Stderr, "note: jwt.secret is empty; it will be generated and stored in the database on first start")
+	}
+	fmt.Fprintf(os.Stderr, "Configuration OK (run_mode=%s, server=%s:%d)\n", cfg.RunMode, cfg.Server.Host, cfg.Server.Port)
+	return nil
+}
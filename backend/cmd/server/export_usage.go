@@ -0,0 +1,117 @@
+package main
+
+import (
+	"context"
+	"flag"
+	"fmt"
+	"io"
+	"os"
+	"os/signal"
+	"syscall"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/timezone"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/usagestats"
+	"github.com/Wei-Shaw/sub2api/internal/repository"
+	"github.com/Wei-Shaw/sub2api/internal/service"
+)
+
+// runExportUsage 命令行导出使用记录（与 GET /api/v1/admin/usage/export 等价），例如：
+//
+//	sub2api export-usage -start 2026-01-01 -end 2026-01-31 -format parquet -o usage.parquet
+//	sub2api export-usage -start 2026-01-01 -end 2026-01-31 -upload
+func runExportUsage(args []string) error {
+	fs := flag.NewFlagSet("export-usage", flag.ContinueOnError)
+	startDate := fs.String("start", "", "Start date, inclusive (YYYY-MM-DD, in configured timezone)")
+	endDate := fs.String("end", "", "End date, inclusive (YYYY-MM-DD, in configured timezone)")
+	format := fs.String("format", service.UsageExportFormatCSV, "Output format: csv or parquet")
+	output := fs.String("o", "", "Output file (default: stdout)")
+	upload := fs.Bool("upload", false, "Upload to the configured object storage (storage.*) instead of writing locally")
+	userID := fs.Int64("user-id", 0, "Filter by user ID")
+	apiKeyID := fs.Int64("api-key-id", 0, "Filter by API key ID")
+	accountID := fs.Int64("account-id", 0, "Filter by account ID")
+	groupID := fs.Int64("group-id", 0, "Filter by group ID")
+	model := fs.String("model", "", "Filter by model")
+	if err := fs.Parse(args); err != nil {
+		return err
+	}
+
+	exportFormat, err := service.NormalizeUsageExportFormat(*format)
+	if err != nil {
+		return err
+	}
+	if *startDate == "" || *endDate == "" {
+		return fmt.Errorf("-start and -end are required")
+	}
+
+	cfg, err := config.LoadForBootstrap()
+	if err != nil {
+		return fmt.Errorf("load config: %w", err)
+	}
+	// InitEnt 会初始化时区，日期需在其之后解析
+	client, db, err := repository.InitEnt(cfg)
+	if err != nil {
+		return fmt.Errorf("init database: %w", err)
+	}
+	defer func() { _ = client.Close() }()
+
+	start, err := timezone.ParseInUserLocation("2006-01-02", *startDate, "")
+	if err != nil {
+		return fmt.Errorf("invalid -start: %w", err)
+	}
+	end, err := timezone.ParseInUserLocation("2006-01-02", *endDate, "")
+	if err != nil {
+		return fmt.Errorf("invalid -end: %w", err)
+	}
+	end = end.Add(24*time.Hour - time.Nanosecond)
+	filters := usagestats.UsageLogFilters{
+		UserID:    *userID,
+		APIKeyID:  *apiKeyID,
+		AccountID: *accountID,
+		GroupID:   *groupID,
+		Model:     *model,
+		StartTime: &start,
+		EndTime:   &end,
+	}
+
+	storage, err := repository.NewObjectStorage(cfg)
+	if err != nil {
+		return err
+	}
+	svc := service.NewUsageExportService(repository.NewAdminListRepository(client, db, nil, nil), storage, cfg)
+
+	ctx, stop := signal.NotifyContext(context.Background(), os.Interrupt, syscall.SIGTERM)
+	defer stop()
+
+	if *upload {
+		result, err := svc.ExportToStorage(ctx, filters, exportFormat)
+		if err != nil {
+			return err
+		}
+		fmt.Fprintf(os.Stderr, "Exported %d rows to s3://%s/%s\n", result.Rows, result.Bucket, result.Key)
+		return nil
+	}
+
+	var (
+		w io.Writer = os.Stdout
+		f *os.File
+	)
+	if *output != "" {
+		if f, err = os.Create(*output); err != nil {
+			return err
+		}
+		w = f
+	}
+	rows, err := svc.Export(ctx, filters, exportFormat, w)
+	if f != nil {
+		if closeErr := f.Close(); err == nil {
+			err = closeErr
+		}
+	}
+	if err != nil {
+		return err
+	}
+	fmt.Fprintf(os.Stderr, "Exported %d rows\n", rows)
+	return nil
+}
@@ -0,0 +1,38 @@
+package main
+
+import (
+	"flag"
+	"fmt"
+	"net/http"
+	"os"
+	"time"
+)
+
+// runHealthcheck 请求本机 /health 并以退出码反映结果，供不含 curl/shell 的
+// 最小镜像（scratch/distroless）作为 HEALTHCHECK 使用，例如：
+//
+//	sub2api healthcheck
+//	sub2api healthcheck -url http://127.0.0.1:9090/health -timeout 3s
+func runHealthcheck(args []string) error {
+	port := os.Getenv("SERVER_PORT")
+	if port == "" {
+		port = "8080"
+	}
+	fs := flag.NewFlagSet("healthcheck", flag.ContinueOnError)
+	url := fs.String("url", "http://127.0.0.1:"+port+"/health", "Health endpoint to probe")
+	timeout := fs.Duration("timeout", 5*time.Second, "Request timeout")
+	if err := fs.Parse(args); err != nil {
+		return err
+	}
+
+	client := &http.Client{Timeout: *timeout}
+	resp, err := client.Get(*url)
+	if err != nil {
+		return err
+	}
+	_ = resp.Body.Close()
+	if resp.StatusCode != http.StatusOK {
+		return fmt.Errorf("%s returned %s", *url, resp.Status)
+	}
+	return nil
+}
@@ -0,0 +1,133 @@
+package main
+
+import (
+	"bytes"
+	"context"
+	"encoding/json"
+	"flag"
+	"fmt"
+	"io"
+	"net/http"
+	"os"
+	"os/signal"
+	"strconv"
+	"strings"
+	"syscall"
+	"time"
+)
+
+// runImportSession 从浏览器导出的 Cookie / HAR 导入网页会话账号（调用运行中服务的
+// POST /api/v1/admin/accounts/import-session，解析、探测与创建均在服务端完成），例如：
+//
+//	sub2api import-session -server http://127.0.0.1:8080 -admin-key admin-xxx -file claude.har -group-ids 1,2 -proxy-id 3
+//	sub2api import-session -file cookies.txt -platform gemini -dry-run
+func runImportSession(args []string) error {
+	fs := flag.NewFlagSet("import-session", flag.ContinueOnError)
+	server := fs.String("server", envOr("SUB2API_SERVER", "http://127.0.0.1:8080"), "Server base URL (env SUB2API_SERVER)")
+	adminKey := fs.String("admin-key", os.Getenv("SUB2API_ADMIN_KEY"), "Admin API key (env SUB2API_ADMIN_KEY)")
+	file := fs.String("file", "", "HAR, JSON cookie export, Netscape cookies.txt or raw Cookie header file ('-' for stdin)")
+	platform := fs.String("platform", "", "anthropic, openai or gemini (default: detect from the export)")
+	name := fs.String("name", "", "Account name (default: generated)")
+	notes := fs.String("notes", "", "Account notes")
+	proxyID := fs.Int64("proxy-id", 0, "Proxy ID used for the probe and the account")
+	groupIDs := fs.String("group-ids", "", "Comma separated group IDs to bind")
+	concurrency := fs.Int("concurrency", 1, "Account concurrency")
+	priority := fs.Int("priority", 0, "Account priority")
+	skipProbe := fs.Bool("skip-probe", false, "Create the account without validating the session")
+	dryRun := fs.Bool("dry-run", false, "Parse and probe only, do not create the account")
+	if err := fs.Parse(args); err != nil {
+		return err
+	}
+	if *file == "" {
+		return fmt.Errorf("-file is required")
+	}
+	if *adminKey == "" {
+		return fmt.Errorf("-admin-key (or SUB2API_ADMIN_KEY) is required")
+	}
+
+	var content []byte
+	var err error
+	if *file == "-" {
+		content, err = io.ReadAll(os.Stdin)
+	} else {
+		content, err = os.ReadFile(*file)
+	}
+	if err != nil {
+		return fmt.Errorf("read export: %w", err)
+	}
+
+	payload := map[string]any{
+		"platform":    *platform,
+		"content":     string(content),
+		"name":        *name,
+		"concurrency": *concurrency,
+		"priority":    *priority,
+		"skip_probe":  *skipProbe,
+		"dry_run":     *dryRun,
+	}
+	if *notes != "" {
+		payload["notes"] = *notes
+	}
+	if *proxyID > 0 {
+		payload["proxy_id"] = *proxyID
+	}
+	if *groupIDs != "" {
+		ids := []int64{}
+		for _, raw := range strings.Split(*groupIDs, ",") {
+			id, err := strconv.ParseInt(strings.TrimSpace(raw), 10, 64)
+			if err != nil {
+				return fmt.Errorf("invalid -group-ids: %q", raw)
+			}
+			ids = append(ids, id)
+		}
+		payload["group_ids"] = ids
+	}
+	body, err := json.Marshal(payload)
+	if err != nil {
+		return err
+	}
+
+	ctx, stop := signal.NotifyContext(context.Background(), os.Interrupt, syscall.SIGTERM)
+	defer stop()
+	ctx, cancel := context.WithTimeout(ctx, time.Minute)
+	defer cancel()
+
+	req, err := http.NewRequestWithContext(ctx, http.MethodPost, strings.TrimRight(*server, "/")+"/api/v1/admin/accounts/import-session", bytes.NewReader(body))
+	if err != nil {
+		return err
+	}
+	req.Header.Set("Content-Type", "application/json")
+	req.Header.Set("x-api-key", *adminKey)
+	resp, err := http.DefaultClient.Do(req)
+	if err != nil {
+		return err
+	}
+	defer func() { _ = resp.Body.Close() }()
+
+	var result struct {
+		Code    int             `json:"code"`
+		Message string          `json:"message"`
+		Data    json.RawMessage `json:"data"`
+	}
+	raw, _ := io.ReadAll(io.LimitReader(resp.Body, 1<<20))
+	if err := json.Unmarshal(raw, &result); err != nil {
+		return fmt.Errorf("unexpected response (HTTP %d): %s", resp.StatusCode, strings.TrimSpace(string(raw)))
+	}
+	if resp.StatusCode != http.StatusOK || result.Code != 0 {
+		return fmt.Errorf("import failed (HTTP %d): %s", resp.StatusCode, result.Message)
+	}
+
+	var out bytes.Buffer
+	if err := json.Indent(&out, result.Data, "", "  "); err != nil {
+		return err
+	}
+	fmt.Println(out.String())
+	return nil
+}
+
+func envOr(key, fallback string) string {
+	if v := os.Getenv(key); v != "" {
+		return v
+	}
+	return fallback
+}
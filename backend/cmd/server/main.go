@@ -61,13 +61,99 @@ func main() {
 	// Parse command line flags
 	setupMode := flag.Bool("setup", false, "Run setup wizard in CLI mode")
 	showVersion := flag.Bool("version", false, "Show version information")
+	configFile := flag.String("config", "", "Path to config file (default: search DATA_DIR, /app/data, ., ./config, /etc/sub2api)")
+	var configSets stringListFlag
+	flag.Var(&configSets, "set", "Override a config key, e.g. -set server.port=9090 (repeatable, highest priority)")
 	flag.Parse()
 
+	if err := config.SetCommandLineOverrides(*configFile, configSets); err != nil {
+		log.Fatalf("Invalid command line: %v", err)
+	}
+
 	if *showVersion {
 		log.Printf("Sub2API %s (commit: %s, built: %s)\n", Version, Commit, Date)
 		return
 	}
 
+	// Container health probe: `sub2api healthcheck [-url ...]`
+	if flag.Arg(0) == "healthcheck" {
+		if err := runHealthcheck(flag.Args()[1:]); err != nil {
+			log.Fatalf("Healthcheck failed: %v", err)
+		}
+		return
+	}
+
+	// Worker mode: `sub2api worker` runs background jobs only (no HTTP listener)
+	if flag.Arg(0) == "worker" {
+		if setup.NeedsSetup() {
+			log.Fatalf("Worker cannot start before setup is completed")
+		}
+		// 覆盖配置文件中的 worker.mode（viper 环境变量优先级高于配置文件）
+		if err := os.Setenv("WORKER_MODE", config.WorkerModeWorker); err != nil {
+			log.Fatalf("Failed to set worker mode: %v", err)
+		}
+		runMainServer()
+		return
+	}
+
+	// Config validation: `sub2api config check`
+	if flag.Arg(0) == "config" {
+		if flag.Arg(1) != "check" {
+			log.Fatalf("Unknown config subcommand %q (available: check)", flag.Arg(1))
+		}
+		if err := runConfigCheck(flag.Args()[2:]); err != nil {
+			log.Fatalf("Config check failed: %v", err)
+		}
+		return
+	}
+
+	// Usage export: `sub2api export-usage -start ... -end ...`
+	if flag.Arg(0) == "export-usage" {
+		if err := runExportUsage(flag.Args()[1:]); err != nil {
+			log.Fatalf("Usage export failed: %v", err)
+		}
+		return
+	}
+
+	// Credential re-encryption: `sub2api reencrypt-credentials [-dry-run]`
+	if flag.Arg(0) == "reencrypt-credentials" {
+		if err := runReencryptCredentials(flag.Args()[1:]); err != nil {
+			log.Fatalf("Credential re-encryption failed: %v", err)
+		}
+		return
+	}
+
+	// Browser session import: `sub2api import-session -file session.har ...`
+	if flag.Arg(0) == "import-session" {
+		if err := runImportSession(flag.Args()[1:]); err != nil {
+			log.Fatalf("Session import failed: %v", err)
+		}
+		return
+	}
+
+	// Load testing: `sub2api bench -api-key ... -model ... -rps 50 -duration 60s`
+	if flag.Arg(0) == "bench" {
+		if err := runBench(flag.Args()[1:]); err != nil {
+			log.Fatalf("Bench failed: %v", err)
+		}
+		return
+	}
+
+	if flag.Arg(0) == "replay" {
+		if err := runReplay(flag.Args()[1:]); err != nil {
+			log.Fatalf("Replay failed: %v", err)
+		}
+		return
+	}
+
+	// Standalone mode: `sub2api standalone -config standalone.yaml` (no PostgreSQL / Redis)
+	if flag.Arg(0) == "standalone" {
+		if err := runStandalone(flag.Args()[1:]); err != nil {
+			log.Fatalf("Standalone mode failed: %v", err)
+		}
+		return
+	}
+
 	// CLI setup mode
 	if *setupMode {
 		if err := setup.RunCLI(); err != nil {
@@ -151,6 +237,17 @@ func runMainServer() {
 	}
 	defer app.Cleanup()
 
+	quit := make(chan os.Signal, 1)
+	signal.Notify(quit, syscall.SIGINT, syscall.SIGTERM)
+
+	// worker 进程不监听 HTTP，仅运行后台任务直到收到退出信号
+	if !cfg.Worker.ServesHTTP() {
+		log.Println("Worker started (background jobs only)")
+		<-quit
+		log.Println("Worker exited")
+		return
+	}
+
 	// 启动服务器
 	go func() {
 		if err := app.Server.ListenAndServe(); err != nil && !errors.Is(err, http.ErrServerClosed) {
@@ -158,11 +255,18 @@ func runMainServer() {
 		}
 	}()
 
-	log.Printf("Server started on %s", app.Server.Addr)
+	log.Printf("Server started on %s (worker.mode=%s)", app.Server.Addr, cfg.Worker.Mode)
+
+	if app.GRPC != nil {
+		go func() {
+			if err := app.GRPC.ListenAndServe(); err != nil {
+				log.Fatalf("Failed to start gRPC server: %v", err)
+			}
+		}()
+		log.Printf("gRPC server started on %s", app.GRPC.Addr())
+	}
 
 	// 等待中断信号
-	quit := make(chan os.Signal, 1)
-	signal.Notify(quit, syscall.SIGINT, syscall.SIGTERM)
 	<-quit
 
 	log.Println("Shutting down server...")
@@ -173,6 +277,9 @@ func runMainServer() {
 	if err := app.Server.Shutdown(ctx); err != nil {
 		log.Fatalf("Server forced to shutdown: %v", err)
 	}
+	if app.GRPC != nil {
+		app.GRPC.Shutdown(ctx)
+	}
 
 	log.Println("Server exited")
 }
@@ -0,0 +1,57 @@
+package main
+
+import (
+	"context"
+	"flag"
+	"fmt"
+	"os"
+	"os/signal"
+	"syscall"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/Wei-Shaw/sub2api/internal/repository"
+)
+
+// runReencryptCredentials 加密存量明文凭证，并将旧主密钥密文轮换到当前主密钥，例如：
+//
+//	sub2api reencrypt-credentials -dry-run
+//	sub2api reencrypt-credentials -batch 500
+func runReencryptCredentials(args []string) error {
+	fs := flag.NewFlagSet("reencrypt-credentials", flag.ContinueOnError)
+	batchSize := fs.Int("batch", 200, "Accounts per batch")
+	dryRun := fs.Bool("dry-run", false, "Only count accounts that need re-encryption")
+	if err := fs.Parse(args); err != nil {
+		return err
+	}
+
+	cfg, err := config.LoadForBootstrap()
+	if err != nil {
+		return fmt.Errorf("load config: %w", err)
+	}
+	cipher, err := repository.NewCredentialCipher(cfg)
+	if err != nil {
+		return err
+	}
+	if cipher == nil || !cfg.Security.CredentialEncryption.Enabled {
+		return fmt.Errorf("security.credential_encryption must be enabled with a master_key")
+	}
+	client, db, err := repository.InitEnt(cfg)
+	if err != nil {
+		return fmt.Errorf("init database: %w", err)
+	}
+	defer func() { _ = client.Close() }()
+
+	ctx, stop := signal.NotifyContext(context.Background(), os.Interrupt, syscall.SIGTERM)
+	defer stop()
+
+	result, err := cipher.ReencryptAccounts(ctx, db, *batchSize, *dryRun)
+	if err != nil {
+		return err
+	}
+	if *dryRun {
+		fmt.Fprintf(os.Stderr, "Scanned %d accounts, %d need re-encryption\n", result.Scanned, result.Updated)
+		return nil
+	}
+	fmt.Fprintf(os.Stderr, "Scanned %d accounts, re-encrypted %d with key %s\n", result.Scanned, result.Updated, cfg.Security.CredentialEncryption.MasterKeyID)
+	return nil
+}
@@ -0,0 +1,546 @@
+package main
+
+import (
+	"bufio"
+	"bytes"
+	"context"
+	"encoding/json"
+	"errors"
+	"flag"
+	"fmt"
+	"io"
+	"net/http"
+	"net/url"
+	"os"
+	"os/signal"
+	"sort"
+	"strconv"
+	"strings"
+	"sync"
+	"syscall"
+	"time"
+
+	"github.com/tidwall/gjson"
+)
+
+// runReplay 把归档的请求重放到当前构建或预发环境，并对比响应的结构与关键指标，
+// 用于验证协议转换层重构在真实流量形态下的行为是否一致，例如：
+//
+//	sub2api replay -file capture.jsonl -target http://staging:8080 -api-key sk-xxx
+//	sub2api replay -file capture.jsonl -target http://staging:8080 -api-key sk-xxx -baseline http://prod:8080 -baseline-api-key sk-yyy
+//	sub2api replay -ops-errors -since 24h -admin-key admin-xxx -target http://127.0.0.1:8080 -api-key sk-xxx
+//
+// JSONL 每行一个请求：{"id":"...","path":"/v1/messages","headers":{...},"body":{...},"status":200,"response":...}，
+// 其中 status / response 为录制时的响应（可选，response 可以是 JSON 或 SSE 文本）。
+// 指定 -baseline 时同时重放到基线环境并与之对比，否则与录制的响应对比。
+// -ops-errors 从运维错误日志（请求失败时保存的请求体）读取请求，经管理接口拉取。
+//
+// 对比项：HTTP 状态码、错误类型、输出内容块结构（类型序列与工具名）与停止原因；
+// 文本内容与 token 数受模型随机性影响，只汇总统计不计为差异。
+func runReplay(args []string) error {
+	fs := flag.NewFlagSet("replay", flag.ContinueOnError)
+	file := fs.String("file", "", "JSONL capture to replay ('-' for stdin)")
+	opsErrors := fs.Bool("ops-errors", false, "Replay archived failed requests from the ops error log instead of -file")
+	server := fs.String("server", envOr("SUB2API_SERVER", "http://127.0.0.1:8080"), "Server to read archived requests from with -ops-errors (env SUB2API_SERVER)")
+	adminKey := fs.String("admin-key", os.Getenv("SUB2API_ADMIN_KEY"), "Admin API key for -ops-errors (env SUB2API_ADMIN_KEY)")
+	since := fs.Duration("since", 24*time.Hour, "With -ops-errors: replay requests archived within this window")
+	target := fs.String("target", "", "Base URL of the build under test")
+	apiKey := fs.String("api-key", os.Getenv("SUB2API_API_KEY"), "Gateway API key for -target (env SUB2API_API_KEY)")
+	baseline := fs.String("baseline", "", "Optional base URL of a reference deployment to diff against")
+	baselineKey := fs.String("baseline-api-key", "", "Gateway API key for -baseline (default: -api-key)")
+	concurrency := fs.Int("concurrency", 4, "Requests replayed in parallel")
+	limit := fs.Int("limit", 0, "Replay at most this many requests (0 = all)")
+	timeout := fs.Duration("timeout", 5*time.Minute, "Per-request timeout")
+	out := fs.String("out", "", "Write one JSON result per request to this file")
+	if err := fs.Parse(args); err != nil {
+		return err
+	}
+	if *target == "" || *apiKey == "" {
+		return errors.New("-target and -api-key (or SUB2API_API_KEY) are required")
+	}
+	if (*file == "") == !*opsErrors {
+		return errors.New("exactly one of -file or -ops-errors is required")
+	}
+	if *concurrency <= 0 {
+		return errors.New("-concurrency must be positive")
+	}
+	if *baselineKey == "" {
+		*baselineKey = *apiKey
+	}
+
+	ctx, stop := signal.NotifyContext(context.Background(), os.Interrupt, syscall.SIGTERM)
+	defer stop()
+	client := &http.Client{Timeout: *timeout}
+
+	var records []*replayRecord
+	var err error
+	if *opsErrors {
+		if *adminKey == "" {
+			return errors.New("-admin-key (or SUB2API_ADMIN_KEY) is required with -ops-errors")
+		}
+		records, err = loadOpsErrorRecords(ctx, client, strings.TrimRight(*server, "/"), *adminKey, *since, *limit)
+	} else {
+		records, err = loadReplayFile(*file, *limit)
+	}
+	if err != nil {
+		return err
+	}
+	if len(records) == 0 {
+		return errors.New("no requests to replay")
+	}
+
+	var outFile *os.File
+	if *out != "" {
+		if outFile, err = os.Create(*out); err != nil {
+			return err
+		}
+		defer func() { _ = outFile.Close() }()
+	}
+
+	targetEnv := replayEnv{base: strings.TrimRight(*target, "/"), apiKey: *apiKey}
+	var baselineEnv *replayEnv
+	if *baseline != "" {
+		baselineEnv = &replayEnv{base: strings.TrimRight(*baseline, "/"), apiKey: *baselineKey}
+	}
+	fmt.Fprintf(os.Stderr, "replaying %d requests against %s\n", len(records), targetEnv.base)
+
+	results := make([]*replayResult, len(records))
+	jobs := make(chan int)
+	var wg sync.WaitGroup
+	for range *concurrency {
+		wg.Add(1)
+		go func() {
+			defer wg.Done()
+			for i := range jobs {
+				results[i] = replayOne(ctx, client, records[i], targetEnv, baselineEnv)
+			}
+		}()
+	}
+feed:
+	for i := range records {
+		select {
+		case jobs <- i:
+		case <-ctx.Done():
+			break feed
+		}
+	}
+	close(jobs)
+	wg.Wait()
+
+	report := newReplayReport()
+	for _, r := range results {
+		if r == nil {
+			continue
+		}
+		report.add(r)
+		if outFile != nil {
+			line, _ := json.Marshal(r)
+			if _, err := outFile.Write(append(line, '\n')); err != nil {
+				return err
+			}
+		}
+	}
+	report.print(os.Stdout)
+	if report.mismatched > 0 {
+		return fmt.Errorf("%d of %d replayed requests differ", report.mismatched, report.total)
+	}
+	return nil
+}
+
+// replayRecord 一条待重放的请求（JSONL capture 的一行）
+type replayRecord struct {
+	ID       string            `json:"id"`
+	Method   string            `json:"method"`
+	Path     string            `json:"path"`
+	Headers  map[string]string `json:"headers"`
+	Body     json.RawMessage   `json:"body"`
+	Status   int               `json:"status"`
+	Response json.RawMessage   `json:"response"`
+}
+
+// requestBody body 可以是 JSON 对象，也可以是包含原始请求体的 JSON 字符串
+func (r *replayRecord) requestBody() []byte {
+	if res := gjson.ParseBytes(r.Body); res.Type == gjson.String {
+		return []byte(res.String())
+	}
+	return r.Body
+}
+
+// recorded 录制时的响应；未录制时返回 nil
+func (r *replayRecord) recorded() *replaySummary {
+	if r.Status == 0 {
+		return nil
+	}
+	body := []byte(r.Response)
+	if res := gjson.ParseBytes(r.Response); res.Type == gjson.String {
+		body = []byte(res.String())
+	}
+	return summarizeReplayResponse(r.Status, body)
+}
+
+func loadReplayFile(path string, limit int) ([]*replayRecord, error) {
+	var src io.Reader = os.Stdin
+	if path != "-" {
+		f, err := os.Open(path)
+		if err != nil {
+			return nil, err
+		}
+		defer func() { _ = f.Close() }()
+		src = f
+	}
+	reader := bufio.NewReader(src)
+	var records []*replayRecord
+	for lineNo := 1; limit <= 0 || len(records) < limit; lineNo++ {
+		line, err := reader.ReadBytes('\n')
+		if trimmed := bytes.TrimSpace(line); len(trimmed) > 0 {
+			rec := &replayRecord{}
+			if jsonErr := json.Unmarshal(trimmed, rec); jsonErr != nil {
+				return nil, fmt.Errorf("line %d: %w", lineNo, jsonErr)
+			}
+			if rec.Path == "" || len(rec.Body) == 0 {
+				return nil, fmt.Errorf("line %d: path and body are required", lineNo)
+			}
+			if rec.ID == "" {
+				rec.ID = strconv.Itoa(lineNo)
+			}
+			records = append(records, rec)
+		}
+		if errors.Is(err, io.EOF) {
+			break
+		}
+		if err != nil {
+			return nil, err
+		}
+	}
+	return records, nil
+}
+
+// loadOpsErrorRecords 通过管理接口拉取时间窗口内保存了完整请求体的失败请求
+func loadOpsErrorRecords(ctx context.Context, client *http.Client, server, adminKey string, since time.Duration, limit int) ([]*replayRecord, error) {
+	get := func(path string, query url.Values) (gjson.Result, error) {
+		u := server + "/api/v1/admin/ops" + path
+		if len(query) > 0 {
+			u += "?" + query.Encode()
+		}
+		req, err := http.NewRequestWithContext(ctx, http.MethodGet, u, nil)
+		if err != nil {
+			return gjson.Result{}, err
+		}
+		req.Header.Set("x-api-key", adminKey)
+		resp, err := client.Do(req)
+		if err != nil {
+			return gjson.Result{}, err
+		}
+		defer func() { _ = resp.Body.Close() }()
+		raw, err := io.ReadAll(io.LimitReader(resp.Body, 64<<20))
+		if err != nil {
+			return gjson.Result{}, err
+		}
+		if resp.StatusCode != http.StatusOK || gjson.GetBytes(raw, "code").Int() != 0 {
+			return gjson.Result{}, fmt.Errorf("GET %s failed (HTTP %d): %s", path, resp.StatusCode, gjson.GetBytes(raw, "message").String())
+		}
+		return gjson.GetBytes(raw, "data"), nil
+	}
+
+	end := time.Now().UTC()
+	query := url.Values{
+		"start_time": {end.Add(-since).Format(time.RFC3339)},
+		"end_time":   {end.Format(time.RFC3339)},
+		"page_size":  {"100"},
+	}
+	var records []*replayRecord
+	for page := 1; ; page++ {
+		query.Set("page", strconv.Itoa(page))
+		data, err := get("/request-errors", query)
+		if err != nil {
+			return nil, err
+		}
+		items := data.Get("items").Array()
+		for _, item := range items {
+			id := item.Get("id").String()
+			detail, err := get("/request-errors/"+id, nil)
+			if err != nil {
+				return nil, err
+			}
+			body := detail.Get("request_body").String()
+			if body == "" || detail.Get("request_body_truncated").Bool() {
+				continue
+			}
+			records = append(records, &replayRecord{
+				ID:       "ops-" + id,
+				Path:     detail.Get("request_path").String(),
+				Body:     json.RawMessage(body),
+				Status:   int(detail.Get("status_code").Int()),
+				Response: json.RawMessage(strconv.Quote(detail.Get("error_body").String())),
+			})
+			if limit > 0 && len(records) >= limit {
+				return records, nil
+			}
+		}
+		if len(items) == 0 || page >= int(data.Get("pages").Int()) {
+			return records, nil
+		}
+	}
+}
+
+type replayEnv struct {
+	base   string
+	apiKey string
+}
+
+// replaySummary 一次响应的可比较特征
+type replaySummary struct {
+	Status       int    `json:"status"`
+	ErrorType    string `json:"error_type,omitempty"`
+	Shape        string `json:"shape,omitempty"`
+	StopReason   string `json:"stop_reason,omitempty"`
+	InputTokens  int64  `json:"input_tokens,omitempty"`
+	OutputTokens int64  `json:"output_tokens,omitempty"`
+	TextChars    int    `json:"text_chars,omitempty"`
+	LatencyMs    int64  `json:"latency_ms,omitempty"`
+	Error        string `json:"error,omitempty"`
+}
+
+// replayResult 单个请求的重放结果；Expected 为基线响应或录制的响应（均无时为 nil）
+type replayResult struct {
+	ID       string         `json:"id"`
+	Path     string         `json:"path"`
+	Expected *replaySummary `json:"expected,omitempty"`
+	Target   *replaySummary `json:"target"`
+	Diffs    []string       `json:"diffs,omitempty"`
+}
+
+func replayOne(ctx context.Context, client *http.Client, rec *replayRecord, target replayEnv, baseline *replayEnv) *replayResult {
+	res := &replayResult{ID: rec.ID, Path: rec.Path}
+	if baseline != nil {
+		res.Expected = sendReplay(ctx, client, rec, *baseline)
+	} else {
+		res.Expected = rec.recorded()
+	}
+	res.Target = sendReplay(ctx, client, rec, target)
+	if res.Expected != nil {
+		res.Diffs = diffReplaySummaries(res.Expected, res.Target)
+	}
+	return res
+}
+
+// replaySkippedHeaders 不随重放转发的请求头（认证信息由 -api-key 替换）
+var replaySkippedHeaders = map[string]bool{
+	"authorization":  true,
+	"x-api-key":      true,
+	"cookie":         true,
+	"content-length": true,
+	"host":           true,
+	"connection":     true,
+}
+
+func sendReplay(ctx context.Context, client *http.Client, rec *replayRecord, env replayEnv) *replaySummary {
+	method := rec.Method
+	if method == "" {
+		method = http.MethodPost
+	}
+	req, err := http.NewRequestWithContext(ctx, method, env.base+rec.Path, bytes.NewReader(rec.requestBody()))
+	if err != nil {
+		return &replaySummary{Error: err.Error()}
+	}
+	for k, v := range rec.Headers {
+		if !replaySkippedHeaders[strings.ToLower(k)] {
+			req.Header.Set(k, v)
+		}
+	}
+	req.Header.Set("Content-Type", "application/json")
+	req.Header.Set("Authorization", "Bearer "+env.apiKey)
+	if strings.HasSuffix(rec.Path, "/messages") && req.Header.Get("anthropic-version") == "" {
+		req.Header.Set("anthropic-version", "2023-06-01")
+	}
+
+	start := time.Now()
+	resp, err := client.Do(req)
+	if err != nil {
+		return &replaySummary{Error: err.Error(), LatencyMs: time.Since(start).Milliseconds()}
+	}
+	defer func() { _ = resp.Body.Close() }()
+	body, err := io.ReadAll(resp.Body)
+	summary := summarizeReplayResponse(resp.StatusCode, body)
+	summary.LatencyMs = time.Since(start).Milliseconds()
+	if err != nil {
+		summary.Error = err.Error()
+	}
+	return summary
+}
+
+// summarizeReplayResponse 提取 Anthropic Messages / OpenAI Responses 响应（JSON 或 SSE）的结构特征
+func summarizeReplayResponse(status int, body []byte) *replaySummary {
+	s := &replaySummary{Status: status}
+	trimmed := bytes.TrimSpace(body)
+	if bytes.HasPrefix(trimmed, []byte("event:")) || bytes.HasPrefix(trimmed, []byte("data:")) {
+		summarizeReplayStream(s, trimmed)
+		return s
+	}
+	summarizeReplayJSON(s, gjson.ParseBytes(trimmed))
+	return s
+}
+
+func summarizeReplayJSON(s *replaySummary, doc gjson.Result) {
+	if errType := doc.Get("error.type"); errType.Exists() {
+		s.ErrorType = errType.String()
+		return
+	}
+	var shape []string
+	if content := doc.Get("content"); content.IsArray() {
+		// Anthropic Messages
+		content.ForEach(func(_, block gjson.Result) bool {
+			shape = append(shape, replayBlockShape(block.Get("type").String(), block.Get("name").String()))
+			s.TextChars += len([]rune(block.Get("text").String()))
+			return true
+		})
+		s.StopReason = doc.Get("stop_reason").String()
+	} else if output := doc.Get("output"); output.IsArray() {
+		// OpenAI Responses
+		output.ForEach(func(_, item gjson.Result) bool {
+			shape = append(shape, replayBlockShape(item.Get("type").String(), item.Get("name").String()))
+			item.Get("content").ForEach(func(_, part gjson.Result) bool {
+				s.TextChars += len([]rune(part.Get("text").String()))
+				return true
+			})
+			return true
+		})
+		s.StopReason = doc.Get("status").String()
+	}
+	s.Shape = strings.Join(shape, ",")
+	s.InputTokens = doc.Get("usage.input_tokens").Int()
+	s.OutputTokens = doc.Get("usage.output_tokens").Int()
+}
+
+func summarizeReplayStream(s *replaySummary, body []byte) {
+	var shape []string
+	for _, line := range bytes.Split(body, []byte("\n")) {
+		line = bytes.TrimSpace(line)
+		if !bytes.HasPrefix(line, []byte("data:")) {
+			continue
+		}
+		event := gjson.ParseBytes(bytes.TrimSpace(line[len("data:"):]))
+		switch event.Get("type").String() {
+		case "error", "response.failed":
+			if errType := event.Get("error.type").String(); errType != "" {
+				s.ErrorType = errType
+			} else {
+				s.ErrorType = event.Get("response.error.code").String()
+			}
+		case "message_start":
+			s.InputTokens = event.Get("message.usage.input_tokens").Int()
+		case "content_block_start":
+			block := event.Get("content_block")
+			shape = append(shape, replayBlockShape(block.Get("type").String(), block.Get("name").String()))
+		case "content_block_delta":
+			s.TextChars += len([]rune(event.Get("delta.text").String()))
+		case "message_delta":
+			s.StopReason = event.Get("delta.stop_reason").String()
+			s.OutputTokens = event.Get("usage.output_tokens").Int()
+		case "response.completed", "response.incomplete":
+			// 最终事件携带完整响应，按非流式响应提取
+			summarizeReplayJSON(s, event.Get("response"))
+			return
+		}
+	}
+	s.Shape = strings.Join(shape, ",")
+}
+
+func replayBlockShape(blockType, name string) string {
+	if name != "" {
+		return blockType + ":" + name
+	}
+	return blockType
+}
+
+func diffReplaySummaries(expected, actual *replaySummary) []string {
+	var diffs []string
+	if expected.Error != "" || actual.Error != "" {
+		if expected.Error != actual.Error {
+			diffs = append(diffs, fmt.Sprintf("transport error: %q -> %q", expected.Error, actual.Error))
+		}
+		return diffs
+	}
+	if expected.Status != actual.Status {
+		diffs = append(diffs, fmt.Sprintf("status: %d -> %d", expected.Status, actual.Status))
+	}
+	if expected.ErrorType != actual.ErrorType {
+		diffs = append(diffs, fmt.Sprintf("error type: %q -> %q", expected.ErrorType, actual.ErrorType))
+	}
+	if expected.Shape != actual.Shape {
+		diffs = append(diffs, fmt.Sprintf("shape: [%s] -> [%s]", expected.Shape, actual.Shape))
+	}
+	if expected.StopReason != actual.StopReason {
+		diffs = append(diffs, fmt.Sprintf("stop reason: %q -> %q", expected.StopReason, actual.StopReason))
+	}
+	return diffs
+}
+
+type replayReport struct {
+	total      int
+	compared   int
+	mismatched int
+	byField    map[string]int
+	expected   []time.Duration
+	actual     []time.Duration
+	expTokens  int64
+	actTokens  int64
+	examples   []*replayResult
+}
+
+func newReplayReport() *replayReport {
+	return &replayReport{byField: map[string]int{}}
+}
+
+func (r *replayReport) add(res *replayResult) {
+	r.total++
+	if res.Target.Error == "" {
+		r.actual = append(r.actual, time.Duration(res.Target.LatencyMs)*time.Millisecond)
+		r.actTokens += res.Target.OutputTokens
+	}
+	if res.Expected == nil {
+		return
+	}
+	r.compared++
+	if res.Expected.Error == "" && res.Expected.LatencyMs > 0 {
+		r.expected = append(r.expected, time.Duration(res.Expected.LatencyMs)*time.Millisecond)
+		r.expTokens += res.Expected.OutputTokens
+	}
+	if len(res.Diffs) == 0 {
+		return
+	}
+	r.mismatched++
+	for _, d := range res.Diffs {
+		r.byField[d[:strings.IndexByte(d, ':')]]++
+	}
+	if len(r.examples) < 20 {
+		r.examples = append(r.examples, res)
+	}
+}
+
+func (r *replayReport) print(w io.Writer) {
+	fmt.Fprintf(w, "\nReplayed:   %d requests, %d compared, %d differ\n", r.total, r.compared, r.mismatched)
+	if len(r.expected) > 0 {
+		fmt.Fprintf(w, "Expected:   %s, %d output tokens\n", summarizeLatencies(r.expected), r.expTokens)
+	}
+	fmt.Fprintf(w, "Target:     %s, %d output tokens\n", summarizeLatencies(r.actual), r.actTokens)
+	if r.mismatched == 0 {
+		return
+	}
+	fields := make([]string, 0, len(r.byField))
+	for f := range r.byField {
+		fields = append(fields, f)
+	}
+	sort.Strings(fields)
+	fmt.Fprintln(w, "Differences by field:")
+	for _, f := range fields {
+		fmt.Fprintf(w, "  %-16s %d\n", f, r.byField[f])
+	}
+	fmt.Fprintln(w, "Examples:")
+	for _, ex := range r.examples {
+		fmt.Fprintf(w, "  [%s] %s\n", ex.ID, ex.Path)
+		for _, d := range ex.Diffs {
+			fmt.Fprintf(w, "      %s\n", d)
+		}
+	}
+}
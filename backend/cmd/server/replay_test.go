@@ -0,0 +1,292 @@
+package main
+
+import (
+	"bytes"
+	"context"
+	"encoding/json"
+	"io"
+	"net/http"
+	"net/http/httptest"
+	"os"
+	"path/filepath"
+	"testing"
+
+	"github.com/stretchr/testify/require"
+)
+
+func TestLoadReplayFile(t *testing.T) {
+	valid := `{"id":"a","path":"/v1/messages","body":{"model":"m"},"status":200,"response":{"content":[]}}` + "\n" +
+		"\n" +
+		`{"path":"/v1/responses","body":"{\"model\":\"gpt\"}"}`
+
+	tests := []struct {
+		name    string
+		content string
+		limit   int
+		wantIDs []string
+		wantErr string
+	}{
+		{name: "blank lines and missing trailing newline", content: valid, wantIDs: []string{"a", "3"}},
+		{name: "limit", content: valid, limit: 1, wantIDs: []string{"a"}},
+		{name: "empty file", content: "\n\n"},
+		{name: "invalid json", content: valid + "\n{", wantErr: "line 4"},
+		{name: "missing body", content: `{"path":"/v1/messages"}`, wantErr: "line 1: path and body are required"},
+		{name: "missing path", content: `{"body":{}}`, wantErr: "line 1: path and body are required"},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			path := filepath.Join(t.TempDir(), "capture.jsonl")
+			require.NoError(t, os.WriteFile(path, []byte(tt.content), 0o600))
+			records, err := loadReplayFile(path, tt.limit)
+			if tt.wantErr != "" {
+				require.ErrorContains(t, err, tt.wantErr)
+				return
+			}
+			require.NoError(t, err)
+			ids := make([]string, 0, len(records))
+			for _, rec := range records {
+				ids = append(ids, rec.ID)
+			}
+			require.Equal(t, len(tt.wantIDs), len(ids))
+			if len(tt.wantIDs) > 0 {
+				require.Equal(t, tt.wantIDs, ids)
+			}
+		})
+	}
+
+	_, err := loadReplayFile(filepath.Join(t.TempDir(), "missing.jsonl"), 0)
+	require.Error(t, err)
+}
+
+func TestReplayRecord_BodyAndRecorded(t *testing.T) {
+	tests := []struct {
+		name         string
+		line         string
+		wantBody     string
+		wantRecorded *replaySummary
+	}{
+		{
+			name:     "object body without recording",
+			line:     `{"path":"/v1/messages","body":{"model":"m"}}`,
+			wantBody: `{"model":"m"}`,
+		},
+		{
+			name:         "string body with json response",
+			line:         `{"path":"/v1/messages","body":"{\"model\":\"m\"}","status":200,"response":{"content":[{"type":"text","text":"hi"}],"stop_reason":"end_turn"}}`,
+			wantBody:     `{"model":"m"}`,
+			wantRecorded: &replaySummary{Status: 200, Shape: "text", StopReason: "end_turn", TextChars: 2},
+		},
+		{
+			name:         "sse response stored as string",
+			line:         `{"path":"/v1/messages","body":{},"status":200,"response":"event: message_delta\ndata: {\"type\":\"message_delta\",\"delta\":{\"stop_reason\":\"max_tokens\"},\"usage\":{\"output_tokens\":3}}\n\n"}`,
+			wantBody:     `{}`,
+			wantRecorded: &replaySummary{Status: 200, StopReason: "max_tokens", OutputTokens: 3},
+		},
+		{
+			name:         "error response stored as string",
+			line:         `{"path":"/v1/responses","body":{},"status":429,"response":"{\"error\":{\"type\":\"rate_limit_error\"}}"}`,
+			wantBody:     `{}`,
+			wantRecorded: &replaySummary{Status: 429, ErrorType: "rate_limit_error"},
+		},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			rec := &replayRecord{}
+			require.NoError(t, json.Unmarshal([]byte(tt.line), rec))
+			require.JSONEq(t, tt.wantBody, string(rec.requestBody()))
+			require.Equal(t, tt.wantRecorded, rec.recorded())
+		})
+	}
+}
+
+func TestSummarizeReplayResponse(t *testing.T) {
+	tests := []struct {
+		name   string
+		status int
+		body   string
+		want   replaySummary
+	}{
+		{
+			name:   "anthropic json",
+			status: 200,
+			body:   `{"content":[{"type":"thinking","thinking":"..."},{"type":"text","text":"héllo"},{"type":"tool_use","name":"get_weather","input":{}}],"stop_reason":"tool_use","usage":{"input_tokens":12,"output_tokens":7}}`,
+			want:   replaySummary{Status: 200, Shape: "thinking,text,tool_use:get_weather", StopReason: "tool_use", TextChars: 5, InputTokens: 12, OutputTokens: 7},
+		},
+		{
+			name:   "openai responses json",
+			status: 200,
+			body:   `{"status":"completed","output":[{"type":"reasoning","summary":[]},{"type":"message","content":[{"type":"output_text","text":"hi there"}]},{"type":"function_call","name":"lookup","arguments":"{}"}],"usage":{"input_tokens":3,"output_tokens":4}}`,
+			want:   replaySummary{Status: 200, Shape: "reasoning,message,function_call:lookup", StopReason: "completed", TextChars: 8, InputTokens: 3, OutputTokens: 4},
+		},
+		{
+			name:   "error json",
+			status: 429,
+			body:   `{"type":"error","error":{"type":"rate_limit_error","message":"slow down"}}`,
+			want:   replaySummary{Status: 429, ErrorType: "rate_limit_error"},
+		},
+		{
+			name:   "anthropic stream",
+			status: 200,
+			body: "event: message_start\n" +
+				`data: {"type":"message_start","message":{"usage":{"input_tokens":9,"output_tokens":1}}}` + "\n\n" +
+				"event: content_block_start\n" +
+				`data: {"type":"content_block_start","index":0,"content_block":{"type":"text","text":""}}` + "\n\n" +
+				"event: content_block_delta\n" +
+				`data: {"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"Hello"}}` + "\n\n" +
+				"event: content_block_start\n" +
+				`data: {"type":"content_block_start","index":1,"content_block":{"type":"tool_use","name":"search"}}` + "\n\n" +
+				"event: message_delta\n" +
+				`data: {"type":"message_delta","delta":{"stop_reason":"tool_use"},"usage":{"output_tokens":6}}` + "\n\n",
+			want: replaySummary{Status: 200, Shape: "text,tool_use:search", StopReason: "tool_use", TextChars: 5, InputTokens: 9, OutputTokens: 6},
+		},
+		{
+			name:   "openai stream uses final response",
+			status: 200,
+			body: `data: {"type":"response.created","response":{"status":"in_progress"}}` + "\n\n" +
+				`data: {"type":"response.output_text.delta","delta":"ab"}` + "\n\n" +
+				`data: {"type":"response.completed","response":{"status":"completed","output":[{"type":"message","content":[{"type":"output_text","text":"abc"}]}],"usage":{"input_tokens":2,"output_tokens":1}}}` + "\n\n",
+			want: replaySummary{Status: 200, Shape: "message", StopReason: "completed", TextChars: 3, InputTokens: 2, OutputTokens: 1},
+		},
+		{
+			name:   "anthropic stream error",
+			status: 200,
+			body:   "event: error\n" + `data: {"type":"error","error":{"type":"overloaded_error","message":"busy"}}` + "\n\n",
+			want:   replaySummary{Status: 200, ErrorType: "overloaded_error"},
+		},
+		{
+			name:   "responses stream failed",
+			status: 200,
+			body:   `data: {"type":"response.failed","response":{"error":{"code":"server_error"}}}` + "\n\n",
+			want:   replaySummary{Status: 200, ErrorType: "server_error"},
+		},
+		{
+			name:   "non json body",
+			status: 502,
+			body:   "<html>Bad Gateway</html>",
+			want:   replaySummary{Status: 502},
+		},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			require.Equal(t, tt.want, *summarizeReplayResponse(tt.status, []byte(tt.body)))
+		})
+	}
+}
+
+func TestDiffReplaySummaries(t *testing.T) {
+	tests := []struct {
+		name     string
+		expected replaySummary
+		actual   replaySummary
+		want     []string
+	}{
+		{
+			name:     "identical",
+			expected: replaySummary{Status: 200, Shape: "text", StopReason: "end_turn"},
+			actual:   replaySummary{Status: 200, Shape: "text", StopReason: "end_turn"},
+		},
+		{
+			name:     "metrics are not compared",
+			expected: replaySummary{Status: 200, Shape: "text", InputTokens: 10, OutputTokens: 20, TextChars: 100, LatencyMs: 800},
+			actual:   replaySummary{Status: 200, Shape: "text", InputTokens: 11, OutputTokens: 35, TextChars: 140, LatencyMs: 1200},
+		},
+		{
+			name:     "same transport error",
+			expected: replaySummary{Error: "timeout"},
+			actual:   replaySummary{Error: "timeout"},
+		},
+		{
+			name:     "transport error hides other fields",
+			expected: replaySummary{Status: 200, Shape: "text"},
+			actual:   replaySummary{Error: "connection refused"},
+			want:     []string{`transport error: "" -> "connection refused"`},
+		},
+		{
+			name:     "status and error type",
+			expected: replaySummary{Status: 200, Shape: "text"},
+			actual:   replaySummary{Status: 529, ErrorType: "overloaded_error"},
+			want:     []string{"status: 200 -> 529", `error type: "" -> "overloaded_error"`, "shape: [text] -> []"},
+		},
+		{
+			name:     "shape and stop reason",
+			expected: replaySummary{Status: 200, Shape: "text", StopReason: "end_turn"},
+			actual:   replaySummary{Status: 200, Shape: "text,tool_use:search", StopReason: "tool_use"},
+			want:     []string{"shape: [text] -> [text,tool_use:search]", `stop reason: "end_turn" -> "tool_use"`},
+		},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			require.Equal(t, tt.want, diffReplaySummaries(&tt.expected, &tt.actual))
+		})
+	}
+}
+
+func TestReplayReport(t *testing.T) {
+	r := newReplayReport()
+	r.add(&replayResult{ID: "r1", Path: "/v1/messages",
+		Expected: &replaySummary{Status: 200, LatencyMs: 100, OutputTokens: 5},
+		Target:   &replaySummary{Status: 200, LatencyMs: 120, OutputTokens: 6}})
+	r.add(&replayResult{ID: "r2", Path: "/v1/messages",
+		Expected: &replaySummary{Status: 200, Shape: "text", LatencyMs: 200, OutputTokens: 5},
+		Target:   &replaySummary{Status: 500, LatencyMs: 300, OutputTokens: 4},
+		Diffs:    []string{"status: 200 -> 500", "shape: [text] -> []"}})
+	r.add(&replayResult{ID: "r3", Path: "/v1/responses",
+		Target: &replaySummary{Error: "timeout"}})
+	// 录制的响应没有延迟，不计入基线延迟
+	r.add(&replayResult{ID: "r4", Path: "/v1/responses",
+		Expected: &replaySummary{Status: 200, StopReason: "completed"},
+		Target:   &replaySummary{Status: 200, StopReason: "incomplete", LatencyMs: 50},
+		Diffs:    []string{`stop reason: "completed" -> "incomplete"`}})
+
+	require.Equal(t, 4, r.total)
+	require.Equal(t, 3, r.compared)
+	require.Equal(t, 2, r.mismatched)
+	require.Equal(t, map[string]int{"status": 1, "shape": 1, "stop reason": 1}, r.byField)
+	require.Len(t, r.expected, 2)
+	require.Len(t, r.actual, 3)
+	require.Equal(t, int64(10), r.expTokens)
+	require.Equal(t, int64(10), r.actTokens)
+
+	var out bytes.Buffer
+	r.print(&out)
+	require.Contains(t, out.String(), "Replayed:   4 requests, 3 compared, 2 differ")
+	require.Contains(t, out.String(), "Expected:   p50=100ms")
+	require.Contains(t, out.String(), "Target:     p50=120ms")
+	require.Contains(t, out.String(), "  shape            1")
+	require.Contains(t, out.String(), "[r2] /v1/messages")
+	require.Contains(t, out.String(), "[r4] /v1/responses")
+	require.NotContains(t, out.String(), "[r1]")
+}
+
+func TestReplayOne_AgainstRecording(t *testing.T) {
+	var gotHeader http.Header
+	var gotBody []byte
+	srv := httptest.NewServer(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		gotHeader = r.Header.Clone()
+		gotBody, _ = io.ReadAll(r.Body)
+		w.Header().Set("Content-Type", "application/json")
+		_, _ = io.WriteString(w, `{"content":[{"type":"text","text":"partial"}],"stop_reason":"max_tokens","usage":{"output_tokens":8}}`)
+	}))
+	defer srv.Close()
+
+	rec := &replayRecord{}
+	require.NoError(t, json.Unmarshal([]byte(`{"id":"r1","path":"/v1/messages",
+		"headers":{"Authorization":"Bearer recorded","Cookie":"session=1","anthropic-beta":"tools-2024","X-Api-Key":"recorded"},
+		"body":"{\"model\":\"m\",\"max_tokens\":8}",
+		"status":200,"response":{"content":[{"type":"text","text":"full answer"}],"stop_reason":"end_turn"}}`), rec))
+
+	res := replayOne(context.Background(), srv.Client(), rec, replayEnv{base: srv.URL, apiKey: "sk-target"}, nil)
+
+	require.Equal(t, "Bearer sk-target", gotHeader.Get("Authorization"))
+	require.Empty(t, gotHeader.Get("Cookie"))
+	require.Empty(t, gotHeader.Get("X-Api-Key"))
+	require.Equal(t, "tools-2024", gotHeader.Get("anthropic-beta"))
+	require.Equal(t, "2023-06-01", gotHeader.Get("anthropic-version"))
+	require.JSONEq(t, `{"model":"m","max_tokens":8}`, string(gotBody))
+
+	require.Equal(t, "r1", res.ID)
+	require.Equal(t, "end_turn", res.Expected.StopReason)
+	require.Equal(t, "max_tokens", res.Target.StopReason)
+	require.Equal(t, int64(8), res.Target.OutputTokens)
+	require.Equal(t, []string{`stop reason: "end_turn" -> "max_tokens"`}, res.Diffs)
+}
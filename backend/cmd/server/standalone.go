@@ -0,0 +1,67 @@
+package main
+
+import (
+	"context"
+	"errors"
+	"flag"
+	"log"
+	"net/http"
+	"os"
+	"os/signal"
+	"syscall"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/standalone"
+	"github.com/gin-gonic/gin"
+)
+
+// runStandalone 单用户轻量模式：Key 与账号来自配置文件，使用记录写入本地 JSONL，不需要 PostgreSQL / Redis，例如：
+//
+//	sub2api standalone -config standalone.yaml
+//
+// 配置示例见 deploy/standalone.example.yaml。
+func runStandalone(args []string) error {
+	fs := flag.NewFlagSet("standalone", flag.ContinueOnError)
+	configPath := fs.String("config", "standalone.yaml", "Standalone config file (keys, accounts, usage log)")
+	if err := fs.Parse(args); err != nil {
+		return err
+	}
+
+	cfg, err := standalone.LoadConfig(*configPath)
+	if err != nil {
+		return err
+	}
+	usageLog, err := standalone.OpenUsageLog(cfg.UsageLog)
+	if err != nil {
+		return err
+	}
+	defer func() { _ = usageLog.Close() }()
+
+	gin.SetMode(gin.ReleaseMode)
+	server := &http.Server{
+		Addr:              cfg.Listen,
+		Handler:           standalone.NewServer(cfg, usageLog, nil).Handler(),
+		ReadHeaderTimeout: 30 * time.Second,
+		IdleTimeout:       120 * time.Second,
+	}
+
+	ctx, stop := signal.NotifyContext(context.Background(), os.Interrupt, syscall.SIGTERM)
+	defer stop()
+
+	errCh := make(chan error, 1)
+	go func() { errCh <- server.ListenAndServe() }()
+	log.Printf("Standalone gateway listening on %s (keys=%d, accounts=%d, usage log: %s)", cfg.Listen, len(cfg.Keys), len(cfg.Accounts), cfg.UsageLog)
+
+	select {
+	case err := <-errCh:
+		if !errors.Is(err, http.ErrServerClosed) {
+			return err
+		}
+		return nil
+	case <-ctx.Done():
+	}
+
+	shutdownCtx, cancel := context.WithTimeout(context.Background(), 30*time.Second)
+	defer cancel()
+	return server.Shutdown(shutdownCtx)
+}
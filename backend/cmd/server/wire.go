@@ -14,6 +14,7 @@ import (
 	"github.com/Wei-Shaw/sub2api/internal/handler"
 	"github.com/Wei-Shaw/sub2api/internal/repository"
 	"github.com/Wei-Shaw/sub2api/internal/server"
+	"github.com/Wei-Shaw/sub2api/internal/server/grpcapi"
 	"github.com/Wei-Shaw/sub2api/internal/server/middleware"
 	"github.com/Wei-Shaw/sub2api/internal/service"
 
@@ -23,6 +24,7 @@ import (
 
 type Application struct {
 	Server  *http.Server
+	GRPC    *grpcapi.Server
 	Cleanup func()
 }
 
@@ -47,7 +49,7 @@ func initializeApplication(buildInfo handler.BuildInfo) (*Application, error) {
 		provideCleanup,
 
 		// Application struct
-		wire.Struct(new(Application), "Server", "Cleanup"),
+		wire.Struct(new(Application), "Server", "GRPC", "Cleanup"),
 	)
 	return nil, nil
 }
@@ -61,7 +63,9 @@ func provideServiceBuildInfo(buildInfo handler.BuildInfo) service.BuildInfo {
 
 func provideCleanup(
 	entClient *ent.Client,
+	readDB *repository.ReadDB,
 	rdb *redis.Client,
+	leaderElector *service.LeaderElector,
 	opsMetricsCollector *service.OpsMetricsCollector,
 	opsAggregation *service.OpsAggregationService,
 	opsAlertEvaluator *service.OpsAlertEvaluatorService,
@@ -73,12 +77,20 @@ func provideCleanup(
 	tokenRefresh *service.TokenRefreshService,
 	accountExpiry *service.AccountExpiryService,
 	subscriptionExpiry *service.SubscriptionExpiryService,
+	trash *service.TrashService,
 	usageCleanup *service.UsageCleanupService,
 	idempotencyCleanup *service.IdempotencyCleanupService,
 	pricing *service.PricingService,
 	emailQueue *service.EmailQueueService,
+	analyticsTee *service.AnalyticsTeeService,
+	eventExport *service.EventExportService,
+	usageAnalytics *service.UsageAnalyticsService,
+	jobQueue *service.JobQueueService,
+	cronJobs *service.CronJobService,
+	telegram *service.TelegramService,
 	billingCache *service.BillingCacheService,
 	usageRecordWorkerPool *service.UsageRecordWorkerPool,
+	requestLog *service.RequestLogService,
 	subscriptionService *service.SubscriptionService,
 	oauth *service.OAuthService,
 	openaiOAuth *service.OpenAIOAuthService,
@@ -166,6 +178,18 @@ func provideCleanup(
 				subscriptionExpiry.Stop()
 				return nil
 			}},
+			{"CronJobService", func() error {
+				cronJobs.Stop()
+				return nil
+			}},
+			{"TelegramService", func() error {
+				telegram.Stop()
+				return nil
+			}},
+			{"TrashService", func() error {
+				trash.Stop()
+				return nil
+			}},
 			{"SubscriptionService", func() error {
 				if subscriptionService != nil {
 					subscriptionService.Stop()
@@ -180,10 +204,30 @@ func provideCleanup(
 				emailQueue.Stop()
 				return nil
 			}},
+			{"AnalyticsTeeService", func() error {
+				analyticsTee.Stop()
+				return nil
+			}},
+			{"EventExportService", func() error {
+				eventExport.Stop()
+				return nil
+			}},
+			{"UsageAnalyticsService", func() error {
+				usageAnalytics.Stop()
+				return nil
+			}},
+			{"JobQueueService", func() error {
+				jobQueue.Stop()
+				return nil
+			}},
 			{"BillingCacheService", func() error {
 				billingCache.Stop()
 				return nil
 			}},
+			{"RequestLogService", func() error {
+				requestLog.Stop()
+				return nil
+			}},
 			{"UsageRecordWorkerPool", func() error {
 				if usageRecordWorkerPool != nil {
 					usageRecordWorkerPool.Stop()
@@ -206,9 +250,16 @@ func provideCleanup(
 				antigravityOAuth.Stop()
 				return nil
 			}},
+			{"LeaderElector", func() error {
+				leaderElector.Stop()
+				return nil
+			}},
 			{"Redis", func() error {
 				return rdb.Close()
 			}},
+			{"ReadDB", func() error {
+				return readDB.Close()
+			}},
 			{"Ent", func() error {
 				return entClient.Close()
 			}},
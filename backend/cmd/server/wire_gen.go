@@ -14,6 +14,7 @@ import (
 	"github.com/Wei-Shaw/sub2api/internal/handler/admin"
 	"github.com/Wei-Shaw/sub2api/internal/repository"
 	"github.com/Wei-Shaw/sub2api/internal/server"
+	"github.com/Wei-Shaw/sub2api/internal/server/grpcapi"
 	"github.com/Wei-Shaw/sub2api/internal/server/middleware"
 	"github.com/Wei-Shaw/sub2api/internal/service"
 	"github.com/redis/go-redis/v9"
@@ -42,6 +43,10 @@ func initializeApplication(buildInfo handler.BuildInfo) (*Application, error) {
 	if err != nil {
 		return nil, err
 	}
+	readDB, err := repository.ProvideReadDB(configConfig, db)
+	if err != nil {
+		return nil, err
+	}
 	userRepository := repository.NewUserRepository(client, db)
 	redeemCodeRepository := repository.NewRedeemCodeRepository(client)
 	redisClient := repository.ProvideRedis(configConfig)
@@ -52,12 +57,19 @@ func initializeApplication(buildInfo handler.BuildInfo) (*Application, error) {
 	emailService := service.NewEmailService(settingRepository, emailCache)
 	turnstileVerifier := repository.NewTurnstileVerifier()
 	turnstileService := service.NewTurnstileService(settingService, turnstileVerifier)
-	emailQueueService := service.ProvideEmailQueueService(emailService)
+	jobQueue := repository.NewJobQueue(redisClient, configConfig)
+	jobQueueService := service.ProvideJobQueueService(jobQueue, configConfig)
+	emailQueueService := service.ProvideEmailQueueService(emailService, jobQueueService)
+	notificationRepository := repository.NewNotificationRepository(db)
+	notificationService := service.ProvideNotificationService(notificationRepository, settingRepository, emailQueueService, configConfig)
 	promoCodeRepository := repository.NewPromoCodeRepository(client)
 	billingCache := repository.NewBillingCache(redisClient)
 	userSubscriptionRepository := repository.NewUserSubscriptionRepository(client)
-	billingCacheService := service.NewBillingCacheService(billingCache, userRepository, userSubscriptionRepository, configConfig)
 	apiKeyRepository := repository.NewAPIKeyRepository(client)
+	spendingCapRepository := repository.NewSpendingCapRepository(db)
+	spendingCapCache := repository.NewSpendingCapCache(redisClient)
+	spendingCapService := service.NewSpendingCapService(spendingCapRepository, spendingCapCache, apiKeyRepository, userRepository, notificationService)
+	billingCacheService := service.NewBillingCacheService(billingCache, userRepository, userSubscriptionRepository, configConfig, spendingCapService)
 	groupRepository := repository.NewGroupRepository(client, db)
 	userGroupRateRepository := repository.NewUserGroupRateRepository(db)
 	apiKeyCache := repository.NewAPIKeyCache(redisClient)
@@ -65,7 +77,9 @@ func initializeApplication(buildInfo handler.BuildInfo) (*Application, error) {
 	apiKeyAuthCacheInvalidator := service.ProvideAPIKeyAuthCacheInvalidator(apiKeyService)
 	promoService := service.NewPromoService(promoCodeRepository, userRepository, billingCacheService, client, apiKeyAuthCacheInvalidator)
 	authService := service.NewAuthService(userRepository, redeemCodeRepository, refreshTokenCache, configConfig, settingService, emailService, turnstileService, emailQueueService, promoService)
-	userService := service.NewUserService(userRepository, apiKeyAuthCacheInvalidator, billingCache)
+	adminUserRepository := repository.NewAdminUserRepository(db)
+	adminUserService := service.NewAdminUserService(adminUserRepository, userRepository, apiKeyAuthCacheInvalidator, notificationService, configConfig)
+	userService := service.ProvideUserService(userRepository, apiKeyAuthCacheInvalidator, billingCache, adminUserService)
 	subscriptionService := service.NewSubscriptionService(groupRepository, userSubscriptionRepository, billingCacheService, client, configConfig)
 	redeemCache := repository.NewRedeemCache(redisClient)
 	redeemService := service.NewRedeemService(redeemCodeRepository, userRepository, subscriptionService, redeemCache, billingCacheService, client, apiKeyAuthCacheInvalidator)
@@ -74,11 +88,23 @@ func initializeApplication(buildInfo handler.BuildInfo) (*Application, error) {
 		return nil, err
 	}
 	totpCache := repository.NewTotpCache(redisClient)
-	totpService := service.NewTotpService(userRepository, secretEncryptor, totpCache, settingService, emailService, emailQueueService)
-	authHandler := handler.NewAuthHandler(configConfig, authService, userService, settingService, promoService, redeemService, totpService)
+	totpBackupCodeRepository := repository.NewTotpBackupCodeRepository(db)
+	totpService := service.NewTotpService(userRepository, secretEncryptor, totpCache, totpBackupCodeRepository, settingService, emailService, emailQueueService)
+	webSessionStore := repository.NewWebSessionStore(redisClient)
+	webSessionService := service.NewWebSessionService(webSessionStore, configConfig)
+	authHandler := handler.NewAuthHandler(configConfig, authService, userService, settingService, promoService, redeemService, totpService, webSessionService)
 	userHandler := handler.NewUserHandler(userService)
-	apiKeyHandler := handler.NewAPIKeyHandler(apiKeyService)
-	usageLogRepository := repository.NewUsageLogRepository(client, db)
+	apiKeyRotationRepository := repository.NewAPIKeyRotationRepository(db)
+	apiKeyRotationService := service.NewAPIKeyRotationService(apiKeyRepository, apiKeyRotationRepository, apiKeyService, configConfig)
+	apiKeyHandler := handler.NewAPIKeyHandler(apiKeyService, apiKeyRotationService)
+	apiKeyPolicyRepository := repository.NewAPIKeyPolicyRepository(db)
+	upstreamHeaderAllowlist := service.ProvideUpstreamHeaderAllowlist(configConfig)
+	logMaskingProfiles, err := service.ProvideLogMaskingProfiles(configConfig)
+	if err != nil {
+		return nil, err
+	}
+	apiKeyPolicyService := service.NewAPIKeyPolicyService(apiKeyPolicyRepository, apiKeyRepository, groupRepository, upstreamHeaderAllowlist, logMaskingProfiles)
+	usageLogRepository := repository.NewUsageLogRepository(client, db, readDB)
 	usageService := service.NewUsageService(usageLogRepository, userRepository, client, apiKeyAuthCacheInvalidator)
 	usageHandler := handler.NewUsageHandler(usageService, apiKeyService)
 	redeemHandler := handler.NewRedeemHandler(redeemService)
@@ -90,21 +116,28 @@ func initializeApplication(buildInfo handler.BuildInfo) (*Application, error) {
 	dashboardAggregationRepository := repository.NewDashboardAggregationRepository(db)
 	dashboardStatsCache := repository.NewDashboardCache(redisClient, configConfig)
 	dashboardService := service.NewDashboardService(usageLogRepository, dashboardAggregationRepository, dashboardStatsCache, configConfig)
+	leaderLock := repository.NewLeaderLock(redisClient)
+	leaderElector := service.ProvideLeaderElector(leaderLock, configConfig)
 	timingWheelService, err := service.ProvideTimingWheelService()
 	if err != nil {
 		return nil, err
 	}
-	dashboardAggregationService := service.ProvideDashboardAggregationService(dashboardAggregationRepository, timingWheelService, configConfig)
+	dashboardAggregationService := service.ProvideDashboardAggregationService(dashboardAggregationRepository, timingWheelService, jobQueueService, leaderElector, configConfig)
 	dashboardHandler := admin.NewDashboardHandler(dashboardService, dashboardAggregationService)
 	schedulerCache := repository.NewSchedulerCache(redisClient)
-	accountRepository := repository.NewAccountRepository(client, db, schedulerCache)
+	credentialCipher, err := repository.NewCredentialCipher(configConfig)
+	if err != nil {
+		return nil, err
+	}
+	accountRepository := repository.NewAccountRepository(client, db, schedulerCache, credentialCipher)
 	soraAccountRepository := repository.NewSoraAccountRepository(db)
 	proxyRepository := repository.NewProxyRepository(client, db)
 	proxyExitInfoProber := repository.NewProxyExitInfoProber(configConfig)
 	proxyLatencyCache := repository.NewProxyLatencyCache(redisClient)
 	adminService := service.NewAdminService(userRepository, groupRepository, accountRepository, soraAccountRepository, proxyRepository, apiKeyRepository, redeemCodeRepository, userGroupRateRepository, billingCacheService, proxyExitInfoProber, proxyLatencyCache, apiKeyAuthCacheInvalidator)
 	concurrencyCache := repository.ProvideConcurrencyCache(redisClient, configConfig)
-	concurrencyService := service.ProvideConcurrencyService(concurrencyCache, accountRepository, configConfig)
+	accountStateLocker := repository.NewAccountStateLocker(redisClient)
+	concurrencyService := service.ProvideConcurrencyService(concurrencyCache, accountRepository, configConfig, accountStateLocker)
 	adminUserHandler := admin.NewUserHandler(adminService, concurrencyService)
 	groupHandler := admin.NewGroupHandler(adminService)
 	claudeOAuthClient := repository.NewClaudeOAuthClient()
@@ -121,8 +154,12 @@ func initializeApplication(buildInfo handler.BuildInfo) (*Application, error) {
 	timeoutCounterCache := repository.NewTimeoutCounterCache(redisClient)
 	geminiTokenCache := repository.NewGeminiTokenCache(redisClient)
 	compositeTokenCacheInvalidator := service.NewCompositeTokenCacheInvalidator(geminiTokenCache)
-	rateLimitService := service.ProvideRateLimitService(accountRepository, usageLogRepository, configConfig, geminiQuotaService, tempUnschedCache, timeoutCounterCache, settingService, compositeTokenCacheInvalidator)
-	httpUpstream := repository.NewHTTPUpstream(configConfig)
+	copilotAuthClient := repository.NewCopilotAuthClient()
+	copilotService := service.NewCopilotService(copilotAuthClient, proxyRepository, accountRepository, geminiTokenCache)
+	challengeHookClient := repository.NewChallengeHookClient()
+	challengeHookService := service.NewChallengeHookService(accountRepository, challengeHookClient, configConfig)
+	rateLimitService := service.ProvideRateLimitService(accountRepository, usageLogRepository, configConfig, geminiQuotaService, tempUnschedCache, timeoutCounterCache, settingService, compositeTokenCacheInvalidator, challengeHookService, accountStateLocker)
+	httpUpstream := repository.NewHTTPUpstream(configConfig, upstreamHeaderAllowlist)
 	claudeUsageFetcher := repository.NewClaudeUsageFetcher(httpUpstream)
 	antigravityQuotaFetcher := service.NewAntigravityQuotaFetcher(proxyRepository)
 	usageCache := service.NewUsageCache()
@@ -146,7 +183,7 @@ func initializeApplication(buildInfo handler.BuildInfo) (*Application, error) {
 	proxyHandler := admin.NewProxyHandler(adminService)
 	adminRedeemHandler := admin.NewRedeemHandler(adminService)
 	promoHandler := admin.NewPromoHandler(promoService)
-	opsRepository := repository.NewOpsRepository(db)
+	opsRepository := repository.NewOpsRepository(db, readDB)
 	pricingRemoteClient := repository.ProvidePricingRemoteClient(configConfig)
 	pricingService, err := service.ProvidePricingService(configConfig, pricingRemoteClient)
 	if err != nil {
@@ -157,13 +194,41 @@ func initializeApplication(buildInfo handler.BuildInfo) (*Application, error) {
 	deferredService := service.ProvideDeferredService(accountRepository, timingWheelService)
 	claudeTokenProvider := service.NewClaudeTokenProvider(accountRepository, geminiTokenCache, oAuthService)
 	digestSessionStore := service.NewDigestSessionStore()
-	gatewayService := service.NewGatewayService(accountRepository, groupRepository, usageLogRepository, userRepository, userSubscriptionRepository, userGroupRateRepository, gatewayCache, configConfig, schedulerSnapshotService, concurrencyService, billingService, rateLimitService, billingCacheService, identityService, httpUpstream, deferredService, claudeTokenProvider, sessionLimitCache, digestSessionStore)
+	accountLatencyTracker := service.NewAccountLatencyTracker()
+	accountQuotaBudgetTracker := service.NewAccountQuotaBudgetTracker(usageLogRepository)
+	liveUsageHub := service.NewLiveUsageHub()
+	analyticsSinkClient := repository.NewAnalyticsSinkClient()
+	analyticsTeeService := service.ProvideAnalyticsTeeService(liveUsageHub, jobQueueService, analyticsSinkClient, configConfig)
+	accountShardRepository := repository.NewAccountShardRepository(db)
+	accountShardService := service.NewAccountShardService(accountShardRepository)
+	eventOutboxRepository := repository.NewEventOutboxRepository(db)
+	eventPublisher := repository.NewEventPublisher(configConfig)
+	eventExportService := service.ProvideEventExportService(eventOutboxRepository, eventPublisher, configConfig)
+	usageAnalyticsStore := repository.NewClickHouseUsageStore(configConfig)
+	usageAnalyticsService := service.ProvideUsageAnalyticsService(usageAnalyticsStore, jobQueueService, configConfig)
+	gatewayService := service.NewGatewayService(accountRepository, groupRepository, usageLogRepository, userRepository, userSubscriptionRepository, userGroupRateRepository, gatewayCache, configConfig, schedulerSnapshotService, concurrencyService, billingService, rateLimitService, billingCacheService, identityService, httpUpstream, deferredService, claudeTokenProvider, sessionLimitCache, digestSessionStore, accountLatencyTracker, accountQuotaBudgetTracker, liveUsageHub, accountShardService, eventExportService, usageAnalyticsService)
 	openAITokenProvider := service.NewOpenAITokenProvider(accountRepository, geminiTokenCache, openAIOAuthService)
-	openAIGatewayService := service.NewOpenAIGatewayService(accountRepository, usageLogRepository, userRepository, userSubscriptionRepository, gatewayCache, configConfig, schedulerSnapshotService, concurrencyService, billingService, rateLimitService, billingCacheService, httpUpstream, deferredService, openAITokenProvider)
-	geminiMessagesCompatService := service.NewGeminiMessagesCompatService(accountRepository, groupRepository, gatewayCache, schedulerSnapshotService, geminiTokenProvider, rateLimitService, httpUpstream, antigravityGatewayService, configConfig)
-	opsSystemLogSink := service.ProvideOpsSystemLogSink(opsRepository)
-	opsService := service.NewOpsService(opsRepository, settingRepository, configConfig, accountRepository, userRepository, concurrencyService, gatewayService, openAIGatewayService, geminiMessagesCompatService, antigravityGatewayService, opsSystemLogSink)
-	settingHandler := admin.NewSettingHandler(settingService, emailService, turnstileService, opsService)
+	openAIGatewayService := service.NewOpenAIGatewayService(accountRepository, usageLogRepository, userRepository, userSubscriptionRepository, gatewayCache, configConfig, schedulerSnapshotService, concurrencyService, billingService, rateLimitService, billingCacheService, httpUpstream, deferredService, openAITokenProvider, accountLatencyTracker, accountQuotaBudgetTracker, liveUsageHub, copilotService, accountShardService, eventExportService, usageAnalyticsService)
+	geminiMessagesCompatService := service.NewGeminiMessagesCompatService(accountRepository, groupRepository, gatewayCache, schedulerSnapshotService, geminiTokenProvider, rateLimitService, httpUpstream, antigravityGatewayService, configConfig, accountShardService)
+	logStreamHub := service.NewLogStreamHub(logMaskingProfiles)
+	opsSystemLogSink := service.ProvideOpsSystemLogSink(opsRepository, logStreamHub, logMaskingProfiles)
+	opsService := service.NewOpsService(opsRepository, settingRepository, configConfig, accountRepository, userRepository, concurrencyService, gatewayService, openAIGatewayService, geminiMessagesCompatService, antigravityGatewayService, opsSystemLogSink, logMaskingProfiles)
+	trafficMirrorRepository := repository.NewTrafficMirrorRepository(db)
+	featureFlagRepository := repository.NewFeatureFlagRepository(db)
+	featureFlagCache := repository.NewFeatureFlagCache(redisClient)
+	featureFlagService := service.NewFeatureFlagService(featureFlagRepository, featureFlagCache, configConfig)
+	trafficMirrorService := service.NewTrafficMirrorService(trafficMirrorRepository, accountRepository, gatewayService, openAIGatewayService, geminiMessagesCompatService, antigravityGatewayService, featureFlagService)
+	modelCanaryRouteRepository := repository.NewModelCanaryRouteRepository(db)
+	modelCanaryStatsCache := repository.NewModelCanaryStatsCache(redisClient)
+	modelCanaryService := service.NewModelCanaryService(modelCanaryRouteRepository, modelCanaryStatsCache)
+	semanticCacheRepository := repository.NewSemanticCacheRepository(db)
+	semanticCacheEmbedder := repository.NewSemanticCacheEmbedder()
+	semanticCacheService := service.NewSemanticCacheService(semanticCacheRepository, semanticCacheEmbedder, featureFlagService, configConfig)
+	moderationClient := repository.NewModerationClient()
+	moderationService := service.NewModerationService(moderationClient, configConfig)
+	accountHealthRepository := repository.NewAccountHealthRepository(db)
+	accountHealthService := service.NewAccountHealthService(accountHealthRepository, accountRepository, accountQuotaBudgetTracker)
+	settingHandler := admin.NewSettingHandler(settingService, emailService, turnstileService, opsService, eventExportService)
 	opsHandler := admin.NewOpsHandler(opsService)
 	updateCache := repository.NewUpdateCache(redisClient)
 	gitHubReleaseClient := repository.ProvideGitHubReleaseClient(configConfig)
@@ -174,7 +239,7 @@ func initializeApplication(buildInfo handler.BuildInfo) (*Application, error) {
 	systemHandler := handler.ProvideSystemHandler(updateService, systemOperationLockService)
 	adminSubscriptionHandler := admin.NewSubscriptionHandler(subscriptionService)
 	usageCleanupRepository := repository.NewUsageCleanupRepository(client, db)
-	usageCleanupService := service.ProvideUsageCleanupService(usageCleanupRepository, timingWheelService, dashboardAggregationService, configConfig)
+	usageCleanupService := service.ProvideUsageCleanupService(usageCleanupRepository, timingWheelService, dashboardAggregationService, leaderElector, configConfig)
 	adminUsageHandler := admin.NewUsageHandler(usageService, apiKeyService, adminService, usageCleanupService)
 	userAttributeDefinitionRepository := repository.NewUserAttributeDefinitionRepository(client)
 	userAttributeValueRepository := repository.NewUserAttributeValueRepository(client)
@@ -184,36 +249,112 @@ func initializeApplication(buildInfo handler.BuildInfo) (*Application, error) {
 	errorPassthroughCache := repository.NewErrorPassthroughCache(redisClient)
 	errorPassthroughService := service.NewErrorPassthroughService(errorPassthroughRepository, errorPassthroughCache)
 	errorPassthroughHandler := admin.NewErrorPassthroughHandler(errorPassthroughService)
-	adminHandlers := handler.ProvideAdminHandlers(dashboardHandler, adminUserHandler, groupHandler, accountHandler, adminAnnouncementHandler, oAuthHandler, openAIOAuthHandler, geminiOAuthHandler, antigravityOAuthHandler, proxyHandler, adminRedeemHandler, promoHandler, settingHandler, opsHandler, systemHandler, adminSubscriptionHandler, adminUsageHandler, userAttributeHandler, errorPassthroughHandler)
+	webSessionHandler := admin.NewWebSessionHandler(webSessionService)
+	trashRepository := repository.NewTrashRepository(db)
+	trashService := service.ProvideTrashService(trashRepository, apiKeyService, leaderElector, configConfig)
+	requestLogRepository := repository.NewRequestLogRepository(db)
+	requestLogService := service.ProvideRequestLogService(requestLogRepository, configConfig)
+	cronJobStateRepository := repository.NewCronJobStateRepository(db)
+	cronJobLocker := repository.NewCronJobLocker(redisClient)
+	objectStorage, err := repository.NewObjectStorage(configConfig)
+	if err != nil {
+		return nil, err
+	}
+	statementRepository := repository.NewStatementRepository(db)
+	statementService := service.NewStatementService(statementRepository, userRepository, objectStorage, notificationService, configConfig)
+	telegramClient := repository.NewTelegramClient(configConfig)
+	telegramStateCache := repository.NewTelegramStateCache(redisClient)
+	telegramService := service.ProvideTelegramService(telegramClient, telegramStateCache, cronJobLocker, accountRepository, adminService, dashboardService, accountQuotaBudgetTracker, configConfig)
+	conversationRepository := repository.NewConversationRepository(db)
+	conversationService := service.NewConversationService(conversationRepository, configConfig)
+	gatewayFileRepository := repository.NewGatewayFileRepository(db)
+	remoteImageFetcher := repository.NewRemoteImageFetcher(configConfig)
+	remoteImageCache := repository.NewRemoteImageCache(redisClient)
+	attachmentService := service.NewAttachmentService(gatewayFileRepository, objectStorage, remoteImageFetcher, remoteImageCache, billingService, configConfig)
+	promptTemplateRepository := repository.NewPromptTemplateRepository(db)
+	promptTemplateService := service.NewPromptTemplateService(promptTemplateRepository, configConfig)
+	cronJobService := service.ProvideCronJobService(cronJobStateRepository, cronJobLocker, dashboardAggregationService, trashService, requestLogService, statementService, notificationService, telegramService, conversationService, attachmentService, configConfig)
+	trashHandler := admin.NewTrashHandler(trashService)
+	adminListRepository := repository.NewAdminListRepository(client, db, readDB, credentialCipher)
+	adminListService := service.NewAdminListService(adminListRepository)
+	listHandler := admin.NewListHandler(adminListService)
+	jobQueueHandler := admin.NewJobQueueHandler(jobQueueService)
+	cronJobHandler := admin.NewCronJobHandler(cronJobService)
+	usageExportService := service.NewUsageExportService(adminListRepository, objectStorage, configConfig)
+	usageExportHandler := admin.NewUsageExportHandler(usageExportService)
+	apiKeyPolicyHandler := admin.NewAPIKeyPolicyHandler(apiKeyPolicyService)
+	trafficMirrorHandler := admin.NewTrafficMirrorHandler(trafficMirrorService)
+	modelCanaryHandler := admin.NewModelCanaryHandler(modelCanaryService)
+	accountHealthHandler := admin.NewAccountHealthHandler(accountHealthService)
+	liveUsageHandler := admin.NewLiveUsageHandler(liveUsageHub, logStreamHub)
+	requestLogHandler := admin.NewRequestLogHandler(requestLogService)
+	dataSubjectRepository := repository.NewDataSubjectRepository(db)
+	dataSubjectService := service.NewDataSubjectService(dataSubjectRepository, objectStorage, jobQueueService)
+	dataSubjectHandler := admin.NewDataSubjectHandler(dataSubjectService)
+	featureFlagHandler := admin.NewFeatureFlagHandler(featureFlagService)
+	maintenanceService := service.NewMaintenanceService(settingRepository)
+	maintenanceHandler := admin.NewMaintenanceHandler(maintenanceService)
+	spendingCapHandler := admin.NewSpendingCapHandler(spendingCapService)
+	creditHoldCache := repository.NewCreditHoldCache(redisClient)
+	prepaidCreditService := service.NewPrepaidCreditService(creditHoldCache, billingCacheService, billingService, apiKeyRepository, apiKeyAuthCacheInvalidator, configConfig)
+	prepaidCreditHandler := admin.NewPrepaidCreditHandler(prepaidCreditService)
+	statementHandler := admin.NewStatementHandler(statementService)
+	paymentRepository := repository.NewPaymentRepository(db)
+	stripeClient := repository.NewStripeClient(configConfig)
+	paymentService := service.NewPaymentService(paymentRepository, userRepository, stripeClient, billingCacheService, apiKeyAuthCacheInvalidator, configConfig)
+	adminPaymentHandler := admin.NewPaymentHandler(paymentService)
+	staffHandler := admin.NewStaffHandler(adminUserService)
+	teamRepository := repository.NewTeamRepository(db)
+	teamService := service.NewTeamService(teamRepository, apiKeyRepository, spendingCapService)
+	adminTeamHandler := admin.NewTeamHandler(teamService)
+	adminNotificationHandler := admin.NewNotificationHandler(notificationService)
+	copilotHandler := admin.NewCopilotHandler(copilotService)
+	sessionImportService := service.NewSessionImportService(adminService, httpUpstream)
+	sessionImportHandler := admin.NewSessionImportHandler(sessionImportService)
+	challengeHandler := admin.NewChallengeHandler(challengeHookService)
+	accountShardHandler := admin.NewAccountShardHandler(accountShardService)
+	graphQLHandler := admin.NewGraphQLHandler(adminService, dashboardService, opsService)
+	routingRuleRepository := repository.NewRoutingRuleRepository(db)
+	routingRuleService := service.NewRoutingRuleService(routingRuleRepository, groupRepository, schedulerSnapshotService)
+	routingRuleHandler := admin.NewRoutingRuleHandler(routingRuleService)
+	adminHandlers := handler.ProvideAdminHandlers(dashboardHandler, adminUserHandler, groupHandler, accountHandler, adminAnnouncementHandler, oAuthHandler, openAIOAuthHandler, geminiOAuthHandler, antigravityOAuthHandler, proxyHandler, adminRedeemHandler, promoHandler, settingHandler, opsHandler, systemHandler, adminSubscriptionHandler, adminUsageHandler, userAttributeHandler, errorPassthroughHandler, webSessionHandler, trashHandler, listHandler, jobQueueHandler, cronJobHandler, usageExportHandler, apiKeyPolicyHandler, trafficMirrorHandler, modelCanaryHandler, accountHealthHandler, liveUsageHandler, requestLogHandler, dataSubjectHandler, featureFlagHandler, maintenanceHandler, spendingCapHandler, prepaidCreditHandler, statementHandler, adminPaymentHandler, staffHandler, adminTeamHandler, adminNotificationHandler, copilotHandler, sessionImportHandler, challengeHandler, accountShardHandler, graphQLHandler, routingRuleHandler)
 	usageRecordWorkerPool := service.NewUsageRecordWorkerPool(configConfig)
-	gatewayHandler := handler.NewGatewayHandler(gatewayService, geminiMessagesCompatService, antigravityGatewayService, userService, concurrencyService, billingCacheService, usageService, apiKeyService, usageRecordWorkerPool, errorPassthroughService, configConfig)
-	openAIGatewayHandler := handler.NewOpenAIGatewayHandler(openAIGatewayService, concurrencyService, billingCacheService, apiKeyService, usageRecordWorkerPool, errorPassthroughService, configConfig)
+	gatewayHandler := handler.NewGatewayHandler(gatewayService, geminiMessagesCompatService, antigravityGatewayService, userService, concurrencyService, billingCacheService, usageService, apiKeyService, usageRecordWorkerPool, errorPassthroughService, trafficMirrorService, modelCanaryService, semanticCacheService, moderationService, conversationService, attachmentService, promptTemplateService, configConfig)
+	openAIGatewayHandler := handler.NewOpenAIGatewayHandler(openAIGatewayService, concurrencyService, billingCacheService, apiKeyService, usageRecordWorkerPool, errorPassthroughService, trafficMirrorService, modelCanaryService, semanticCacheService, moderationService, attachmentService, promptTemplateService, configConfig)
 	soraSDKClient := service.ProvideSoraSDKClient(configConfig, httpUpstream, openAITokenProvider, accountRepository, soraAccountRepository)
-	soraMediaStorage := service.ProvideSoraMediaStorage(configConfig)
+	soraMediaStorage := service.ProvideSoraMediaStorage(configConfig, objectStorage)
 	soraGatewayService := service.NewSoraGatewayService(soraSDKClient, soraMediaStorage, rateLimitService, configConfig)
 	soraGatewayHandler := handler.NewSoraGatewayHandler(gatewayService, soraGatewayService, concurrencyService, billingCacheService, usageRecordWorkerPool, configConfig)
 	handlerSettingHandler := handler.ProvideSettingHandler(settingService, buildInfo)
 	totpHandler := handler.NewTotpHandler(totpService)
+	paymentHandler := handler.NewPaymentHandler(paymentService)
+	teamHandler := handler.NewTeamHandler(teamService)
+	notificationHandler := handler.NewNotificationHandler(notificationService)
 	idempotencyCoordinator := service.ProvideIdempotencyCoordinator(idempotencyRepository, configConfig)
-	idempotencyCleanupService := service.ProvideIdempotencyCleanupService(idempotencyRepository, configConfig)
-	handlers := handler.ProvideHandlers(authHandler, userHandler, apiKeyHandler, usageHandler, redeemHandler, subscriptionHandler, announcementHandler, adminHandlers, gatewayHandler, openAIGatewayHandler, soraGatewayHandler, handlerSettingHandler, totpHandler, idempotencyCoordinator, idempotencyCleanupService)
-	jwtAuthMiddleware := middleware.NewJWTAuthMiddleware(authService, userService)
-	adminAuthMiddleware := middleware.NewAdminAuthMiddleware(authService, userService, settingService)
-	apiKeyAuthMiddleware := middleware.NewAPIKeyAuthMiddleware(apiKeyService, subscriptionService, configConfig)
-	engine := server.ProvideRouter(configConfig, handlers, jwtAuthMiddleware, adminAuthMiddleware, apiKeyAuthMiddleware, apiKeyService, subscriptionService, opsService, settingService, redisClient)
+	idempotencyCleanupService := service.ProvideIdempotencyCleanupService(idempotencyRepository, leaderElector, configConfig)
+	handlers := handler.ProvideHandlers(authHandler, userHandler, apiKeyHandler, usageHandler, redeemHandler, subscriptionHandler, announcementHandler, adminHandlers, gatewayHandler, openAIGatewayHandler, soraGatewayHandler, handlerSettingHandler, totpHandler, paymentHandler, teamHandler, notificationHandler, idempotencyCoordinator, idempotencyCleanupService)
+	jwtAuthMiddleware := middleware.NewJWTAuthMiddleware(authService, userService, webSessionService)
+	adminAuthMiddleware := middleware.NewAdminAuthMiddleware(authService, userService, settingService, webSessionService, adminUserService)
+	apiKeyAuthMiddleware := middleware.NewAPIKeyAuthMiddleware(apiKeyService, subscriptionService, apiKeyPolicyService, billingCacheService, configConfig)
+	engine := server.ProvideRouter(configConfig, handlers, jwtAuthMiddleware, adminAuthMiddleware, apiKeyAuthMiddleware, apiKeyService, subscriptionService, apiKeyPolicyService, opsService, requestLogService, settingService, maintenanceService, prepaidCreditService, routingRuleService, redisClient)
 	httpServer := server.ProvideHTTPServer(configConfig, engine)
+	grpcapiServer := server.ProvideGRPCServer(configConfig, engine)
 	opsMetricsCollector := service.ProvideOpsMetricsCollector(opsRepository, settingRepository, accountRepository, concurrencyService, db, redisClient, configConfig)
 	opsAggregationService := service.ProvideOpsAggregationService(opsRepository, settingRepository, db, redisClient, configConfig)
-	opsAlertEvaluatorService := service.ProvideOpsAlertEvaluatorService(opsService, opsRepository, emailService, redisClient, configConfig)
+	discordWebhookClient := repository.NewDiscordWebhookClient()
+	webhookDeliveryService := service.NewWebhookDeliveryService(discordWebhookClient, jobQueueService)
+	opsAlertEvaluatorService := service.ProvideOpsAlertEvaluatorService(opsService, opsRepository, emailService, webhookDeliveryService, redisClient, eventExportService, configConfig)
 	opsCleanupService := service.ProvideOpsCleanupService(opsRepository, db, redisClient, configConfig)
-	opsScheduledReportService := service.ProvideOpsScheduledReportService(opsService, userService, emailService, redisClient, configConfig)
-	soraMediaCleanupService := service.ProvideSoraMediaCleanupService(soraMediaStorage, configConfig)
-	tokenRefreshService := service.ProvideTokenRefreshService(accountRepository, soraAccountRepository, oAuthService, openAIOAuthService, geminiOAuthService, antigravityOAuthService, compositeTokenCacheInvalidator, schedulerCache, configConfig)
-	accountExpiryService := service.ProvideAccountExpiryService(accountRepository)
-	subscriptionExpiryService := service.ProvideSubscriptionExpiryService(userSubscriptionRepository)
-	v := provideCleanup(client, redisClient, opsMetricsCollector, opsAggregationService, opsAlertEvaluatorService, opsCleanupService, opsScheduledReportService, opsSystemLogSink, soraMediaCleanupService, schedulerSnapshotService, tokenRefreshService, accountExpiryService, subscriptionExpiryService, usageCleanupService, idempotencyCleanupService, pricingService, emailQueueService, billingCacheService, usageRecordWorkerPool, subscriptionService, oAuthService, openAIOAuthService, geminiOAuthService, antigravityOAuthService)
+	opsScheduledReportService := service.ProvideOpsScheduledReportService(opsService, userService, emailService, webhookDeliveryService, redisClient, configConfig)
+	soraMediaCleanupService := service.ProvideSoraMediaCleanupService(soraMediaStorage, leaderElector, configConfig)
+	sessionRefreshHookClient := repository.NewSessionRefreshHookClient()
+	tokenRefreshService := service.ProvideTokenRefreshService(accountRepository, soraAccountRepository, oAuthService, openAIOAuthService, geminiOAuthService, antigravityOAuthService, copilotService, sessionRefreshHookClient, compositeTokenCacheInvalidator, schedulerCache, leaderElector, configConfig)
+	accountExpiryService := service.ProvideAccountExpiryService(accountRepository, leaderElector, configConfig)
+	subscriptionExpiryService := service.ProvideSubscriptionExpiryService(userSubscriptionRepository, leaderElector, configConfig)
+	v := provideCleanup(client, readDB, redisClient, leaderElector, opsMetricsCollector, opsAggregationService, opsAlertEvaluatorService, opsCleanupService, opsScheduledReportService, opsSystemLogSink, soraMediaCleanupService, schedulerSnapshotService, tokenRefreshService, accountExpiryService, subscriptionExpiryService, trashService, usageCleanupService, idempotencyCleanupService, pricingService, emailQueueService, analyticsTeeService, eventExportService, usageAnalyticsService, jobQueueService, cronJobService, telegramService, billingCacheService, usageRecordWorkerPool, requestLogService, subscriptionService, oAuthService, openAIOAuthService, geminiOAuthService, antigravityOAuthService)
 	application := &Application{
 		Server:  httpServer,
+		GRPC:    grpcapiServer,
 		Cleanup: v,
 	}
 	return application, nil
@@ -223,6 +364,7 @@ func initializeApplication(buildInfo handler.BuildInfo) (*Application, error) {
 
 type Application struct {
 	Server  *http.Server
+	GRPC    *grpcapi.Server
 	Cleanup func()
 }
 
@@ -235,7 +377,9 @@ func provideServiceBuildInfo(buildInfo handler.BuildInfo) service.BuildInfo {
 
 func provideCleanup(
 	entClient *ent.Client,
+	readDB *repository.ReadDB,
 	rdb *redis.Client,
+	leaderElector *service.LeaderElector,
 	opsMetricsCollector *service.OpsMetricsCollector,
 	opsAggregation *service.OpsAggregationService,
 	opsAlertEvaluator *service.OpsAlertEvaluatorService,
@@ -247,12 +391,20 @@ func provideCleanup(
 	tokenRefresh *service.TokenRefreshService,
 	accountExpiry *service.AccountExpiryService,
 	subscriptionExpiry *service.SubscriptionExpiryService,
+	trash *service.TrashService,
 	usageCleanup *service.UsageCleanupService,
 	idempotencyCleanup *service.IdempotencyCleanupService,
 	pricing *service.PricingService,
 	emailQueue *service.EmailQueueService,
+	analyticsTee *service.AnalyticsTeeService,
+	eventExport *service.EventExportService,
+	usageAnalytics *service.UsageAnalyticsService,
+	jobQueue *service.JobQueueService,
+	cronJobs *service.CronJobService,
+	telegram *service.TelegramService,
 	billingCache *service.BillingCacheService,
 	usageRecordWorkerPool *service.UsageRecordWorkerPool,
+	requestLog *service.RequestLogService,
 	subscriptionService *service.SubscriptionService,
 	oauth *service.OAuthService,
 	openaiOAuth *service.OpenAIOAuthService,
@@ -339,6 +491,18 @@ func provideCleanup(
 				subscriptionExpiry.Stop()
 				return nil
 			}},
+			{"CronJobService", func() error {
+				cronJobs.Stop()
+				return nil
+			}},
+			{"TelegramService", func() error {
+				telegram.Stop()
+				return nil
+			}},
+			{"TrashService", func() error {
+				trash.Stop()
+				return nil
+			}},
 			{"SubscriptionService", func() error {
 				if subscriptionService != nil {
 					subscriptionService.Stop()
@@ -353,10 +517,30 @@ func provideCleanup(
 				emailQueue.Stop()
 				return nil
 			}},
+			{"AnalyticsTeeService", func() error {
+				analyticsTee.Stop()
+				return nil
+			}},
+			{"EventExportService", func() error {
+				eventExport.Stop()
+				return nil
+			}},
+			{"UsageAnalyticsService", func() error {
+				usageAnalytics.Stop()
+				return nil
+			}},
+			{"JobQueueService", func() error {
+				jobQueue.Stop()
+				return nil
+			}},
 			{"BillingCacheService", func() error {
 				billingCache.Stop()
 				return nil
 			}},
+			{"RequestLogService", func() error {
+				requestLog.Stop()
+				return nil
+			}},
 			{"UsageRecordWorkerPool", func() error {
 				if usageRecordWorkerPool != nil {
 					usageRecordWorkerPool.Stop()
@@ -379,9 +563,16 @@ func provideCleanup(
 				antigravityOAuth.Stop()
 				return nil
 			}},
+			{"LeaderElector", func() error {
+				leaderElector.Stop()
+				return nil
+			}},
 			{"Redis", func() error {
 				return rdb.Close()
 			}},
+			{"ReadDB", func() error {
+				return readDB.Close()
+			}},
 			{"Ent", func() error {
 				return entClient.Close()
 			}},
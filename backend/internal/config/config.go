@@ -2,15 +2,22 @@
 package config
 
 import (
+	"context"
 	"crypto/rand"
 	"encoding/hex"
 	"fmt"
 	"log/slog"
+	"net"
 	"net/url"
 	"os"
+	"regexp"
+	"sort"
 	"strings"
 	"time"
 
+	"github.com/Wei-Shaw/sub2api/internal/pkg/secrets"
+	"github.com/mitchellh/mapstructure"
+	"github.com/robfig/cron/v3"
 	"github.com/spf13/viper"
 )
 
@@ -19,6 +26,22 @@ const (
 	RunModeSimple   = "simple"
 )
 
+// 后台任务运行角色
+const (
+	// WorkerModeEmbedded API 与后台任务运行在同一进程（默认）
+	WorkerModeEmbedded = "embedded"
+	// WorkerModeAPI 仅处理 HTTP 请求，后台任务交给独立 worker 进程
+	WorkerModeAPI = "api"
+	// WorkerModeWorker 仅运行后台任务，不监听 HTTP（sub2api worker）
+	WorkerModeWorker = "worker"
+)
+
+// CronJobScheduleParser 定时任务使用的 5 段 cron 表达式解析器（分 时 日 月 周）
+var CronJobScheduleParser = cron.NewParser(cron.Minute | cron.Hour | cron.Dom | cron.Month | cron.Dow | cron.Descriptor)
+
+// upstreamHeaderAllowlistPattern gateway.upstream_header_allowlist 条目格式（小写后）
+var upstreamHeaderAllowlistPattern = regexp.MustCompile(`^[a-z0-9][a-z0-9-]*\*?$`)
+
 // 使用量记录队列溢出策略
 const (
 	UsageRecordOverflowPolicyDrop   = "drop"
@@ -49,6 +72,7 @@ type Config struct {
 	Log                     LogConfig                     `mapstructure:"log"`
 	CORS                    CORSConfig                    `mapstructure:"cors"`
 	Security                SecurityConfig                `mapstructure:"security"`
+	Secrets                 SecretsConfig                 `mapstructure:"secrets"`
 	Billing                 BillingConfig                 `mapstructure:"billing"`
 	Turnstile               TurnstileConfig               `mapstructure:"turnstile"`
 	Database                DatabaseConfig                `mapstructure:"database"`
@@ -56,12 +80,30 @@ type Config struct {
 	Ops                     OpsConfig                     `mapstructure:"ops"`
 	JWT                     JWTConfig                     `mapstructure:"jwt"`
 	Totp                    TotpConfig                    `mapstructure:"totp"`
+	AdminUsers              AdminUsersConfig              `mapstructure:"admin_users"`
+	Notifications           NotificationsConfig           `mapstructure:"notifications"`
+	WebSession              WebSessionConfig              `mapstructure:"web_session"`
 	LinuxDo                 LinuxDoConnectConfig          `mapstructure:"linuxdo_connect"`
 	Default                 DefaultConfig                 `mapstructure:"default"`
 	RateLimit               RateLimitConfig               `mapstructure:"rate_limit"`
 	Pricing                 PricingConfig                 `mapstructure:"pricing"`
 	Gateway                 GatewayConfig                 `mapstructure:"gateway"`
 	APIKeyAuth              APIKeyAuthCacheConfig         `mapstructure:"api_key_auth_cache"`
+	APIKeyRotation          APIKeyRotationConfig          `mapstructure:"api_key_rotation"`
+	Trash                   TrashConfig                   `mapstructure:"trash"`
+	RequestLog              RequestLogConfig              `mapstructure:"request_log"`
+	JobQueue                JobQueueConfig                `mapstructure:"job_queue"`
+	AnalyticsTee            AnalyticsTeeConfig            `mapstructure:"analytics_tee"`
+	EventExport             EventExportConfig             `mapstructure:"event_export"`
+	ClickHouse              ClickHouseConfig              `mapstructure:"clickhouse"`
+	APIDocs                 APIDocsConfig                 `mapstructure:"api_docs"`
+	GRPC                    GRPCConfig                    `mapstructure:"grpc"`
+	Worker                  WorkerConfig                  `mapstructure:"worker"`
+	CronJobs                CronJobsConfig                `mapstructure:"cron_jobs"`
+	UsageExport             UsageExportConfig             `mapstructure:"usage_export"`
+	Statements              StatementsConfig              `mapstructure:"statements"`
+	Payments                PaymentsConfig                `mapstructure:"payments"`
+	Storage                 StorageConfig                 `mapstructure:"storage"`
 	SubscriptionCache       SubscriptionCacheConfig       `mapstructure:"subscription_cache"`
 	SubscriptionMaintenance SubscriptionMaintenanceConfig `mapstructure:"subscription_maintenance"`
 	Dashboard               DashboardCacheConfig          `mapstructure:"dashboard_cache"`
@@ -69,6 +111,14 @@ type Config struct {
 	UsageCleanup            UsageCleanupConfig            `mapstructure:"usage_cleanup"`
 	Concurrency             ConcurrencyConfig             `mapstructure:"concurrency"`
 	TokenRefresh            TokenRefreshConfig            `mapstructure:"token_refresh"`
+	ChallengeHook           ChallengeHookConfig           `mapstructure:"challenge_hook"`
+	SemanticCache           SemanticCacheConfig           `mapstructure:"semantic_cache"`
+	Conversation            ConversationConfig            `mapstructure:"conversation"`
+	Attachments             AttachmentsConfig             `mapstructure:"attachments"`
+	PromptTemplates         PromptTemplatesConfig         `mapstructure:"prompt_templates"`
+	Moderation              ModerationConfig              `mapstructure:"moderation"`
+	LogMasking              LogMaskingConfig              `mapstructure:"log_masking"`
+	FaultInjection          FaultInjectionConfig          `mapstructure:"fault_injection"`
 	Sora                    SoraConfig                    `mapstructure:"sora"`
 	RunMode                 string                        `mapstructure:"run_mode" yaml:"run_mode"`
 	Timezone                string                        `mapstructure:"timezone"` // e.g. "Asia/Shanghai", "UTC"
@@ -191,6 +241,259 @@ type TokenRefreshConfig struct {
 	RetryBackoffSeconds int `mapstructure:"retry_backoff_seconds"`
 	// 是否允许 OpenAI 刷新器同步覆盖关联的 Sora 账号 token（默认关闭）
 	SyncLinkedSoraAccounts bool `mapstructure:"sync_linked_sora_accounts"`
+	// 外部会话刷新服务（无头浏览器等），用于只能通过浏览器流程续期的网页会话账号
+	SessionHook SessionRefreshHookConfig `mapstructure:"session_hook"`
+}
+
+// SessionRefreshHookConfig 外部会话刷新服务配置。
+// 刷新周期内将账号凭证 POST 给外部服务，由其驱动无头浏览器完成续期并返回新凭证。
+type SessionRefreshHookConfig struct {
+	Enabled bool `mapstructure:"enabled"`
+	// 外部刷新服务地址
+	URL string `mapstructure:"url"`
+	// 以 Authorization: Bearer 发送给外部服务
+	AuthToken string `mapstructure:"auth_token"`
+	// 单次刷新超时（秒），浏览器流程通常较慢
+	TimeoutSeconds int `mapstructure:"timeout_seconds"`
+	// 凭证中没有过期时间时的固定刷新间隔（小时），0 表示只按过期时间刷新
+	IntervalHours float64 `mapstructure:"interval_hours"`
+	// 交给外部服务刷新的账号类型；已有内置刷新器的账号仍由内置刷新器处理
+	AccountTypes []string `mapstructure:"account_types"`
+}
+
+// ChallengeHookConfig 上游人机验证（Cloudflare / CAPTCHA 等）处理钩子配置。
+// 账号遇到验证时暂停调度并通知外部服务（Webhook 或验证码求解服务），由其回报结果后自动恢复。
+type ChallengeHookConfig struct {
+	Enabled bool `mapstructure:"enabled"`
+	// 外部服务地址，收到 POST 的验证上下文
+	URL string `mapstructure:"url"`
+	// 以 Authorization: Bearer 发送给外部服务
+	AuthToken string `mapstructure:"auth_token"`
+	// 调用外部服务的超时（秒）；同步返回结果的求解服务需要更长时间
+	TimeoutSeconds int `mapstructure:"timeout_seconds"`
+	// 等待处理结果期间账号暂停调度的时长（分钟），超时未回报则自动恢复调度
+	HoldMinutes int `mapstructure:"hold_minutes"`
+	// 回调地址前缀（本服务对外可访问的地址），用于生成通知中的 callback_url；为空时不生成
+	CallbackBaseURL string `mapstructure:"callback_base_url"`
+}
+
+// SemanticCacheConfig 语义（向量相似度）响应缓存配置。
+// 仅对通过 semantic_cache 功能开关定向开启的 API Key 生效，依赖 PostgreSQL pgvector 扩展。
+type SemanticCacheConfig struct {
+	Enabled bool `mapstructure:"enabled"`
+	// OpenAI 兼容的 embeddings 接口地址，如 https://api.openai.com/v1/embeddings
+	EmbeddingURL    string `mapstructure:"embedding_url"`
+	EmbeddingAPIKey string `mapstructure:"embedding_api_key"`
+	EmbeddingModel  string `mapstructure:"embedding_model"`
+	// 生成向量的超时（秒），超时按未命中处理
+	TimeoutSeconds int `mapstructure:"timeout_seconds"`
+	// 余弦相似度不低于该值时返回缓存响应（0-1）
+	SimilarityThreshold float64 `mapstructure:"similarity_threshold"`
+	// 缓存条目有效期（小时）
+	TTLHours int `mapstructure:"ttl_hours"`
+	// 超过该长度（字符）的提问不参与缓存
+	MaxPromptChars int `mapstructure:"max_prompt_chars"`
+	// 超过该大小（字节）的响应不写入缓存
+	MaxResponseBytes int `mapstructure:"max_response_bytes"`
+}
+
+// ConversationConfig 服务端会话持久化配置。
+// 客户端在 /v1/messages 请求中携带 conversation_id 后只需发送新消息，历史由网关从数据库补全。
+type ConversationConfig struct {
+	Enabled bool `mapstructure:"enabled"`
+	// 补全后最多保留的消息条数，超出时按整轮丢弃最早的消息
+	MaxMessages int `mapstructure:"max_messages"`
+	// 补全后消息内容的最大总长度（JSON 字节），超出时同样按整轮丢弃最早的消息
+	MaxContextBytes int `mapstructure:"max_context_bytes"`
+	// 超过该大小（字节）的响应无法完整记录，本轮不写入会话
+	MaxResponseBytes int `mapstructure:"max_response_bytes"`
+	// 会话最后一次更新后的保留天数
+	TTLDays int `mapstructure:"ttl_days"`
+}
+
+// AttachmentsConfig 多模态文件附件配置。
+// 消息中可按文件 ID 引用通过 /v1/files 上传的文件（存储在对象存储），或以 multipart/form-data
+// 随请求直接上传文件，网关按上游要求转换为内联 base64 内容块后转发。
+type AttachmentsConfig struct {
+	Enabled bool `mapstructure:"enabled"`
+	// 单个文件大小上限（字节），模型规则可进一步收紧
+	MaxFileBytes int64 `mapstructure:"max_file_bytes"`
+	// 单个请求内联的文件总大小上限（字节）
+	MaxRequestBytes int64 `mapstructure:"max_request_bytes"`
+	// 通过 /v1/files 上传的文件保留小时数
+	TTLHours int `mapstructure:"ttl_hours"`
+	// Rules: 按模型限制允许的文件类型与大小，取第一条同时匹配模型与类型的规则；
+	// 为空时使用内置规则（Claude / OpenAI 官方限制），没有匹配规则的文件会被拒绝
+	Rules []AttachmentRule `mapstructure:"rules"`
+	// RemoteImages 远程图片 URL 抓取
+	RemoteImages AttachmentRemoteImagesConfig `mapstructure:"remote_images"`
+	// Preprocess PDF / DOCX 文本提取
+	Preprocess AttachmentPreprocessConfig `mapstructure:"preprocess"`
+}
+
+// PromptTemplatesConfig 提示词模板配置。
+// 用户通过 /v1/prompt-templates 管理带变量的命名模板（每次修改生成新版本），
+// 请求中以 template_id + template_variables 引用模板，由网关渲染后拼接到 system 与 messages 之前。
+type PromptTemplatesConfig struct {
+	Enabled bool `mapstructure:"enabled"`
+	// 每个用户最多创建的模板数
+	MaxTemplatesPerUser int `mapstructure:"max_templates_per_user"`
+	// 单个模板版本内容的最大长度（JSON 字节）
+	MaxTemplateBytes int `mapstructure:"max_template_bytes"`
+}
+
+// 内容审核服务商类型
+const (
+	// ModerationProviderLocal 本地规则引擎（正则 + 内置越狱话术检测），不产生网络调用
+	ModerationProviderLocal = "local"
+	// ModerationProviderOpenAI OpenAI 兼容的 moderations 接口（如 omni-moderation-latest）
+	ModerationProviderOpenAI = "openai"
+	// ModerationProviderHTTP 外部审核服务：POST {"input":[...]}，返回 {"flagged":bool,"categories":[...]}
+	ModerationProviderHTTP = "http"
+)
+
+// ModerationConfig 入站内容审核服务商配置。
+// API Key / 分组策略的 moderation.providers 按名称引用这里的服务商，按顺序调用，失败或超时时回退到下一个。
+// 未配置名为 local 的服务商时，内置一个仅启用越狱话术检测的 local 服务商。
+type ModerationConfig struct {
+	Providers map[string]ModerationProviderConfig `mapstructure:"providers"`
+}
+
+// ModerationProviderConfig 单个审核服务商
+type ModerationProviderConfig struct {
+	// 类型：local / openai / http
+	Type string `mapstructure:"type"`
+	// openai：moderations 接口地址，如 https://api.openai.com/v1/moderations；http：外部服务地址
+	URL string `mapstructure:"url"`
+	// 以 Authorization: Bearer 发送
+	APIKey string `mapstructure:"api_key"`
+	// openai 类型使用的审核模型，为空时由上游决定
+	Model string `mapstructure:"model"`
+	// 单次调用的延迟预算（毫秒），超时视为该服务商失败并回退到下一个
+	TimeoutMS int `mapstructure:"timeout_ms"`
+	// local 类型的规则：类别名 -> RE2 正则，命中即判定违规
+	Patterns map[string]string `mapstructure:"patterns"`
+	// local 类型是否启用内置越狱话术检测
+	Jailbreak bool `mapstructure:"jailbreak"`
+}
+
+// 日志脱敏内置的证件号类型
+const (
+	// LogMaskCNID 中国居民身份证号（18 位，校验码校验）
+	LogMaskCNID = "cn_id"
+	// LogMaskUSSSN 美国社会安全号（123-45-6789）
+	LogMaskUSSSN = "us_ssn"
+	// LogMaskUKNINO 英国国民保险号（AB123456C）
+	LogMaskUKNINO = "uk_nino"
+)
+
+// LogMaskingConfig 日志脱敏配置。
+// 对写入运维错误日志（请求体、错误响应、上游错误事件）与系统日志索引的提示词内容按配置档脱敏；
+// API Key / 分组策略的 masking_profile 指定请求使用的配置档，系统日志无法关联请求归属，始终使用默认配置档。
+type LogMaskingConfig struct {
+	// 未指定 masking_profile 时使用的配置档名称，为空表示不脱敏
+	DefaultProfile string                             `mapstructure:"default_profile"`
+	Profiles       map[string]LogMaskingProfileConfig `mapstructure:"profiles"`
+}
+
+// LogMaskingProfileConfig 单个脱敏配置档
+type LogMaskingProfileConfig struct {
+	// 银行卡号（13-19 位数字，允许空格 / 连字符分隔，Luhn 校验）
+	CreditCards bool `mapstructure:"credit_cards"`
+	// 证件号类型：cn_id / us_ssn / uk_nino
+	NationalIDs []string `mapstructure:"national_ids"`
+	// 自定义规则：规则名 -> RE2 正则，命中内容替换为 [规则名大写]
+	Patterns map[string]string `mapstructure:"patterns"`
+}
+
+// 故障注入类型
+const (
+	FaultLatency         = "latency"
+	FaultError           = "error"
+	FaultConnectionError = "connection_error"
+	FaultDropStream      = "drop_stream"
+	FaultMalformedChunk  = "malformed_chunk"
+)
+
+// FaultInjectionConfig 上游故障注入配置（仅用于测试环境的混沌测试）。
+// 启用后所有上游请求按规则随机注入延迟、错误状态码、连接错误、中途断流或畸形 SSE 数据块，
+// 用于验证重试、故障转移与客户端的容错行为，不应在生产环境启用。
+type FaultInjectionConfig struct {
+	Enabled bool `mapstructure:"enabled"`
+	// Rules 按顺序匹配；latency 规则命中后继续匹配，其余类型取第一条命中的规则
+	Rules []FaultInjectionRule `mapstructure:"rules"`
+}
+
+// FaultInjectionRule 单条故障注入规则
+type FaultInjectionRule struct {
+	// Fault: latency / error / connection_error / drop_stream / malformed_chunk
+	Fault string `mapstructure:"fault"`
+	// Rate: 匹配请求的注入概率 (0, 1]
+	Rate float64 `mapstructure:"rate"`
+	// AccountIDs: 仅对这些账号生效，为空匹配所有账号
+	AccountIDs []int64 `mapstructure:"account_ids"`
+	// URLContains: 仅对 URL 包含该子串的请求生效（如 api.anthropic.com、/v1/responses），为空匹配所有请求
+	URLContains string `mapstructure:"url_contains"`
+	// LatencyMS / LatencyJitterMS: latency 注入的固定延迟与随机抖动上限（毫秒）
+	LatencyMS       int `mapstructure:"latency_ms"`
+	LatencyJitterMS int `mapstructure:"latency_jitter_ms"`
+	// StatusCode / Body: error 注入的状态码与响应体（为空时生成 Anthropic 风格的错误 JSON）
+	StatusCode int    `mapstructure:"status_code"`
+	Body       string `mapstructure:"body"`
+	// RetryAfterSeconds: error 注入时附带的 Retry-After 头，0 表示不附带
+	RetryAfterSeconds int `mapstructure:"retry_after_seconds"`
+	// AfterBytes: drop_stream / malformed_chunk 在转发多少字节响应体后注入
+	AfterBytes int `mapstructure:"after_bytes"`
+}
+
+// AttachmentPreprocessConfig 文档预处理配置。
+// 启用后网关在转发前提取 PDF / DOCX 附件的文本（视觉模型附带文档内的图片），
+// 按分块大小拆分为多个文本块，并按目标模型的上下文窗口截断。
+type AttachmentPreprocessConfig struct {
+	Enabled bool `mapstructure:"enabled"`
+	// 仍以原生 PDF 转发的模型（支持末尾 * 通配）；DOCX 始终提取
+	NativePDFModels []string `mapstructure:"native_pdf_models"`
+	// 是否提取文档中的图片（仅对规则允许图片的模型生效）
+	ExtractImages bool `mapstructure:"extract_images"`
+	// 单个请求最多附带的提取图片数
+	MaxImages int `mapstructure:"max_images"`
+	// 单个 PDF 最多处理的页数
+	MaxPages int `mapstructure:"max_pages"`
+	// 每个文本块的大小（估算 token 数）
+	ChunkTokens int `mapstructure:"chunk_tokens"`
+	// 提取文本最多占用目标模型上下文窗口的比例（0-1]
+	ContextRatio float64 `mapstructure:"context_ratio"`
+	// 模型上下文窗口未知时单个请求提取文本的 token 上限
+	MaxTextTokens int `mapstructure:"max_text_tokens"`
+}
+
+// AttachmentRemoteImagesConfig 远程图片 URL 抓取配置。
+// 启用后网关下载消息中以 URL 引用的图片并内联为 base64（部分上游只接受内联数据），
+// 连接层拒绝解析到内网、回环、链路本地等保留地址的目标。
+type AttachmentRemoteImagesConfig struct {
+	Enabled bool `mapstructure:"enabled"`
+	// 是否允许 http:// 地址（默认仅 https）
+	AllowInsecureHTTP bool `mapstructure:"allow_insecure_http"`
+	// 允许的主机（支持 *.example.com 通配），为空表示任意公网主机
+	AllowedHosts []string `mapstructure:"allowed_hosts"`
+	// 单张图片大小上限（字节）
+	MaxBytes int64 `mapstructure:"max_bytes"`
+	// 单个请求最多抓取的图片数
+	MaxImagesPerRequest int `mapstructure:"max_images_per_request"`
+	// 单张图片下载超时（秒）
+	TimeoutSeconds int `mapstructure:"timeout_seconds"`
+	// 下载结果在 Redis 中的缓存时间（秒），0 表示不缓存
+	CacheTTLSeconds int `mapstructure:"cache_ttl_seconds"`
+}
+
+// AttachmentRule 单条附件限制规则
+type AttachmentRule struct {
+	// Models: 匹配的模型（支持末尾 * 通配），为空匹配所有模型
+	Models []string `mapstructure:"models"`
+	// MediaTypes: 允许的媒体类型，支持 image/* 形式
+	MediaTypes []string `mapstructure:"media_types"`
+	// MaxFileBytes: 单个文件大小上限（字节），0 沿用 max_file_bytes
+	MaxFileBytes int64 `mapstructure:"max_file_bytes"`
 }
 
 type PricingConfig struct {
@@ -241,6 +544,189 @@ type SecurityConfig struct {
 	CSP             CSPConfig            `mapstructure:"csp"`
 	ProxyFallback   ProxyFallbackConfig  `mapstructure:"proxy_fallback"`
 	ProxyProbe      ProxyProbeConfig     `mapstructure:"proxy_probe"`
+
+	// CredentialEncryption 上游账号凭证（token / cookie / API key）落库加密
+	CredentialEncryption CredentialEncryptionConfig `mapstructure:"credential_encryption"`
+}
+
+// CredentialEncryptionConfig 凭证信封加密配置。
+// 每个凭证值使用独立数据密钥加密，数据密钥由主密钥包装；
+// 轮换时把新密钥设为 master_key，旧密钥移入 previous_keys，再执行 reencrypt-credentials。
+type CredentialEncryptionConfig struct {
+	// Enabled 为 true 时新写入的凭证加密；为 false 但配置了密钥时仍可解密已加密的数据
+	Enabled bool `mapstructure:"enabled"`
+	// MasterKey 主密钥（64 位 hex，即 32 字节），建议通过环境变量 SECURITY_CREDENTIAL_ENCRYPTION_MASTER_KEY 注入
+	MasterKey string `mapstructure:"master_key"`
+	// MasterKeyID 主密钥标识，写入密文以便轮换后定位解密密钥
+	MasterKeyID string `mapstructure:"master_key_id"`
+	// PreviousKeys 已退役的主密钥（key_id -> hex），仅用于解密
+	PreviousKeys map[string]string `mapstructure:"previous_keys"`
+}
+
+// SecretsConfig 外部密钥后端配置。
+// 启用后，配置项与账号凭证字段可写成 secret://<path>#<field> 引用，启动时（凭证在读取时）从后端解析。
+type SecretsConfig struct {
+	// Provider 密钥后端：空（关闭）/ vault / file / exec
+	Provider string `mapstructure:"provider"`
+	// CacheTTLSeconds 解析结果缓存时间，过期后重新拉取以感知后端轮换（<= 0 表示不过期）
+	CacheTTLSeconds int                `mapstructure:"cache_ttl_seconds"`
+	Vault           SecretsVaultConfig `mapstructure:"vault"`
+	File            SecretsFileConfig  `mapstructure:"file"`
+	Exec            SecretsExecConfig  `mapstructure:"exec"`
+}
+
+type SecretsVaultConfig struct {
+	Address string `mapstructure:"address"`
+	// Token 为空时读取 token_file，再回退到环境变量 VAULT_TOKEN
+	Token          string `mapstructure:"token"`
+	TokenFile      string `mapstructure:"token_file"`
+	Mount          string `mapstructure:"mount"`
+	Namespace      string `mapstructure:"namespace"`
+	TimeoutSeconds int    `mapstructure:"timeout_seconds"`
+}
+
+type SecretsFileConfig struct {
+	// Dir 密钥挂载目录（Kubernetes Secret / Secret Manager CSI 驱动）
+	Dir string `mapstructure:"dir"`
+}
+
+type SecretsExecConfig struct {
+	// Command 命令及参数，密钥路径作为最后一个参数追加
+	Command        []string `mapstructure:"command"`
+	TimeoutSeconds int      `mapstructure:"timeout_seconds"`
+}
+
+// NewResolver 根据配置创建密钥解析器；未配置后端时返回 nil。
+func (c SecretsConfig) NewResolver() (*secrets.Resolver, error) {
+	var (
+		provider secrets.Provider
+		err      error
+	)
+	switch strings.ToLower(strings.TrimSpace(c.Provider)) {
+	case "":
+		return nil, nil
+	case "vault":
+		token := strings.TrimSpace(c.Vault.Token)
+		if token == "" && strings.TrimSpace(c.Vault.TokenFile) == "" {
+			token = strings.TrimSpace(os.Getenv("VAULT_TOKEN"))
+		}
+		address := strings.TrimSpace(c.Vault.Address)
+		if address == "" {
+			address = strings.TrimSpace(os.Getenv("VAULT_ADDR"))
+		}
+		provider, err = secrets.NewVaultProvider(secrets.VaultConfig{
+			Address:   address,
+			Token:     token,
+			TokenFile: strings.TrimSpace(c.Vault.TokenFile),
+			Mount:     strings.TrimSpace(c.Vault.Mount),
+			Namespace: strings.TrimSpace(c.Vault.Namespace),
+			Timeout:   time.Duration(c.Vault.TimeoutSeconds) * time.Second,
+		})
+	case "file":
+		provider, err = secrets.NewFileProvider(c.File.Dir)
+	case "exec":
+		provider, err = secrets.NewExecProvider(c.Exec.Command, time.Duration(c.Exec.TimeoutSeconds)*time.Second)
+	default:
+		return nil, fmt.Errorf("secrets.provider must be one of vault/file/exec, got %q", c.Provider)
+	}
+	if err != nil {
+		return nil, err
+	}
+	return secrets.NewResolver(provider, time.Duration(c.CacheTTLSeconds)*time.Second), nil
+}
+
+// resolveSecretRefs 将启动期敏感配置中的 secret:// 引用替换为实际值。
+// 数据库 / Redis 密码等在启动时解析一次，后端轮换后需重启进程生效。
+func resolveSecretRefs(cfg *Config) error {
+	targets := map[string]*string{
+		"database.password":                         &cfg.Database.Password,
+		"redis.password":                            &cfg.Redis.Password,
+		"jwt.secret":                                &cfg.JWT.Secret,
+		"totp.encryption_key":                       &cfg.Totp.EncryptionKey,
+		"security.credential_encryption.master_key": &cfg.Security.CredentialEncryption.MasterKey,
+		"linuxdo_connect.client_secret":             &cfg.LinuxDo.ClientSecret,
+		"gemini.oauth.client_secret":                &cfg.Gemini.OAuth.ClientSecret,
+		"storage.secret_access_key":                 &cfg.Storage.SecretAccessKey,
+		"storage.session_token":                     &cfg.Storage.SessionToken,
+		"payments.stripe.secret_key":                &cfg.Payments.Stripe.SecretKey,
+		"payments.stripe.webhook_secret":            &cfg.Payments.Stripe.WebhookSecret,
+		"notifications.telegram.bot_token":          &cfg.Notifications.Telegram.BotToken,
+	}
+	var refs []string
+	for key, ptr := range targets {
+		if secrets.IsRef(*ptr) {
+			refs = append(refs, key)
+		}
+	}
+	previousKeys := cfg.Security.CredentialEncryption.PreviousKeys
+	var previousRefs []string
+	for id, value := range previousKeys {
+		if secrets.IsRef(value) {
+			previousRefs = append(previousRefs, id)
+		}
+	}
+	if len(refs) == 0 && len(previousRefs) == 0 {
+		return nil
+	}
+	sort.Strings(refs)
+
+	resolver, err := cfg.Secrets.NewResolver()
+	if err != nil {
+		return err
+	}
+	if resolver == nil {
+		return fmt.Errorf("secret:// references require secrets.provider to be configured")
+	}
+	ctx, cancel := context.WithTimeout(context.Background(), time.Minute)
+	defer cancel()
+	for _, key := range refs {
+		value, err := resolver.Resolve(ctx, *targets[key])
+		if err != nil {
+			return fmt.Errorf("resolve %s: %w", key, err)
+		}
+		*targets[key] = value
+	}
+	for _, id := range previousRefs {
+		value, err := resolver.Resolve(ctx, previousKeys[id])
+		if err != nil {
+			return fmt.Errorf("resolve security.credential_encryption.previous_keys.%s: %w", id, err)
+		}
+		previousKeys[id] = value
+	}
+	return nil
+}
+
+// Keys 解析主密钥与历史密钥（key_id -> 32 字节密钥）；未配置主密钥时返回 nil。
+func (c CredentialEncryptionConfig) Keys() (map[string][]byte, error) {
+	if strings.TrimSpace(c.MasterKey) == "" {
+		return nil, nil
+	}
+	activeID := strings.TrimSpace(c.MasterKeyID)
+	if activeID == "" || strings.Contains(activeID, ":") {
+		return nil, fmt.Errorf("security.credential_encryption.master_key_id must be non-empty and must not contain ':'")
+	}
+	keys := make(map[string][]byte, len(c.PreviousKeys)+1)
+	parse := func(field, id, value string) error {
+		key, err := hex.DecodeString(strings.TrimSpace(value))
+		if err != nil || len(key) != 32 {
+			return fmt.Errorf("%s must be 64 hex characters (32 bytes)", field)
+		}
+		keys[id] = key
+		return nil
+	}
+	for id, value := range c.PreviousKeys {
+		id = strings.TrimSpace(id)
+		if id == "" || id == activeID || strings.Contains(id, ":") {
+			return nil, fmt.Errorf("security.credential_encryption.previous_keys has invalid key id %q", id)
+		}
+		if err := parse("security.credential_encryption.previous_keys."+id, id, value); err != nil {
+			return nil, err
+		}
+	}
+	if err := parse("security.credential_encryption.master_key", activeID, c.MasterKey); err != nil {
+		return nil, err
+	}
+	return keys, nil
 }
 
 type URLAllowlistConfig struct {
@@ -276,6 +762,29 @@ type ProxyProbeConfig struct {
 
 type BillingConfig struct {
 	CircuitBreaker CircuitBreakerConfig `mapstructure:"circuit_breaker"`
+	PrepaidHold    PrepaidHoldConfig    `mapstructure:"prepaid_hold"`
+	// EstimatedUsageMargin 上游未返回用量（网页会话按本地分词器估算）时计费 token 的不确定性系数：
+	// 计费 token = 估算值 × (1 + margin)；0 表示按估算值计费，负值表示让利。取值 -0.5 ~ 1
+	EstimatedUsageMargin float64           `mapstructure:"estimated_usage_margin"`
+	LimitDryRun          LimitDryRunConfig `mapstructure:"limit_dry_run"`
+}
+
+// LimitDryRunConfig 限额演练模式：超限时照常放行，只记录本应拒绝的请求（日志及 Redis 计数），
+// 用于调整限额前按真实流量评估影响
+type LimitDryRunConfig struct {
+	// SubscriptionLimits 分组订阅的日 / 周 / 月限额
+	SubscriptionLimits bool `mapstructure:"subscription_limits"`
+	// APIKeyQuota API Key 总额度：额度用尽时不拒绝，也不标记为 quota_exhausted
+	APIKeyQuota bool `mapstructure:"api_key_quota"`
+}
+
+// PrepaidHoldConfig 预付费额度预占：请求前按预估费用原子预占余额 / API Key 额度，余额不足时直接拒绝
+type PrepaidHoldConfig struct {
+	Enabled bool `mapstructure:"enabled"`
+	// DefaultOutputTokens 请求未指定 max_tokens 时用于预估的输出 token 数（不超过模型输出上限）
+	DefaultOutputTokens int `mapstructure:"default_output_tokens"`
+	// HoldTTLSeconds 预占最长保留时间，进程异常退出时预占到期自动释放
+	HoldTTLSeconds int `mapstructure:"hold_ttl_seconds"`
 }
 
 type CircuitBreakerConfig struct {
@@ -288,6 +797,10 @@ type CircuitBreakerConfig struct {
 type ConcurrencyConfig struct {
 	// PingInterval: 并发等待期间的 SSE ping 间隔（秒）
 	PingInterval int `mapstructure:"ping_interval"`
+	// FairShare: 多个用户排队等待同一账号时按用户公平调度（加权 Deficit Round Robin，权重见 API Key 策略 fair_share_weight）
+	FairShare bool `mapstructure:"fair_share"`
+	// AdmissionMaxWait: 账号等待队列准入上限（秒），按账号当前吞吐估算的排队时间超过该值时直接返回 429；0 表示不启用
+	AdmissionMaxWait int `mapstructure:"admission_max_wait"`
 }
 
 // SoraConfig 直连 Sora 配置
@@ -326,6 +839,7 @@ type SoraCurlCFFISidecarConfig struct {
 
 // SoraStorageConfig 媒体存储配置
 type SoraStorageConfig struct {
+	// Type local（本地磁盘）或 s3（写入 storage 配置的对象存储）
 	Type                   string                   `mapstructure:"type"`
 	LocalPath              string                   `mapstructure:"local_path"`
 	FallbackToUpstream     bool                     `mapstructure:"fallback_to_upstream"`
@@ -350,6 +864,9 @@ type GatewayConfig struct {
 	ResponseHeaderTimeout int `mapstructure:"response_header_timeout"`
 	// 请求体最大字节数，用于网关请求体大小限制
 	MaxBodySize int64 `mapstructure:"max_body_size"`
+	// BodyLimits: 按接口路径覆盖请求体上限，取路径前缀最长的一条规则；
+	// 仍受 server.max_request_body_size 全局上限约束
+	BodyLimits []GatewayBodyLimitRule `mapstructure:"body_limits"`
 	// 非流式上游响应体读取上限（字节），用于防止无界读取导致内存放大
 	UpstreamResponseReadMaxBytes int64 `mapstructure:"upstream_response_read_max_bytes"`
 	// 代理探测响应体读取上限（字节）
@@ -383,6 +900,10 @@ type GatewayConfig struct {
 	// 超过此时间未使用的客户端会被标记为可回收
 	// 建议值：根据用户访问频率设置，一般 10-30 分钟
 	ClientIdleTTLSeconds int `mapstructure:"client_idle_ttl_seconds"`
+	// HTTPClient: 上游 HTTP 客户端调优（HTTP/2、keep-alive、DNS 缓存/覆盖、happy eyeballs、按平台覆盖连接池）
+	HTTPClient GatewayHTTPClientConfig `mapstructure:"http_client"`
+	// ResponseCompression: 网关响应压缩（按 Accept-Encoding 协商 gzip/br/zstd）
+	ResponseCompression GatewayResponseCompressionConfig `mapstructure:"response_compression"`
 	// ConcurrencySlotTTLMinutes: 并发槽位过期时间（分钟）
 	// 应大于最长 LLM 请求时间，防止请求完成前槽位过期
 	ConcurrencySlotTTLMinutes int `mapstructure:"concurrency_slot_ttl_minutes"`
@@ -393,8 +914,13 @@ type GatewayConfig struct {
 
 	// StreamDataIntervalTimeout: 流数据间隔超时（秒），0表示禁用
 	StreamDataIntervalTimeout int `mapstructure:"stream_data_interval_timeout"`
-	// StreamKeepaliveInterval: 流式 keepalive 间隔（秒），0表示禁用
+	// StreamKeepaliveInterval: 流式 keepalive 间隔（秒），超过该间隔未向客户端写入数据时发送 `: ping` 注释，0表示禁用
 	StreamKeepaliveInterval int `mapstructure:"stream_keepalive_interval"`
+	// CancelUpstreamOnClientDisconnect: 流式客户端断开后立即断开上游连接并按已输出内容估算 usage 计费；
+	// false 时继续读取上游直至结束以获取完整 usage（上游会继续生成并消耗账号额度）
+	CancelUpstreamOnClientDisconnect bool `mapstructure:"cancel_upstream_on_client_disconnect"`
+	// UpstreamTimeouts: 上游请求分阶段超时（连接/首 token/总时长），支持按平台与模型覆盖
+	UpstreamTimeouts GatewayUpstreamTimeoutsConfig `mapstructure:"upstream_timeouts"`
 	// MaxLineSize: 上游 SSE 单行最大字节数（0使用默认值）
 	MaxLineSize int `mapstructure:"max_line_size"`
 
@@ -409,6 +935,30 @@ type GatewayConfig struct {
 	// 是否允许对部分 400 错误触发 failover（默认关闭以避免改变语义）
 	FailoverOn400 bool `mapstructure:"failover_on_400"`
 
+	// CountTokensMode: /v1/messages/count_tokens 计算方式（auto/local/upstream）
+	// auto: 优先本地估算，包含无法本地估算的内容或请求携带 precise=true 时转发上游
+	CountTokensMode string `mapstructure:"count_tokens_mode"`
+
+	// RequestValidation: 请求体结构校验（off/lenient/strict），校验失败返回带字段路径的 400
+	// lenient: 校验已知字段的类型与取值，忽略未知字段；strict: 额外拒绝未知的顶层 / 消息字段
+	RequestValidation string `mapstructure:"request_validation"`
+
+	// DefaultMaxTokens: Anthropic 请求未携带 max_tokens 时填充的默认值（上游要求必填）；0 表示不填充
+	// API Key / 分组策略中的 max_tokens.default 优先
+	DefaultMaxTokens int `mapstructure:"default_max_tokens"`
+
+	// CompletionsMaxChoices: /v1/completions 的 n 上限（仅该端点模拟 n > 1）；n > 1 时并发发起 n 次上游请求后合并
+	// （受用户并发限制与配额约束），失败的 choice 不返回且不计费
+	CompletionsMaxChoices int `mapstructure:"completions_max_choices"`
+
+	// FinishReasonOverrides: 按来源覆盖上游停止原因到 OpenAI finish_reason 的内置映射
+	// 一级键为来源（anthropic / gemini / openai），二级键为上游停止原因（不区分大小写），值为 stop / length / tool_calls / content_filter
+	FinishReasonOverrides map[string]map[string]string `mapstructure:"finish_reason_overrides"`
+
+	// UpstreamHeaderAllowlist: API Key / 分组策略 upstream_tags.headers 允许设置的请求头名称（不区分大小写，以 * 结尾表示前缀匹配）
+	// 为空表示不允许设置任何自定义请求头；认证与协议相关的保留请求头始终不允许
+	UpstreamHeaderAllowlist []string `mapstructure:"upstream_header_allowlist"`
+
 	// Sora 专用配置
 	// SoraMaxBodySize: Sora 请求体最大字节数（0 表示使用 gateway.max_body_size）
 	SoraMaxBodySize int64 `mapstructure:"sora_max_body_size"`
@@ -483,6 +1033,83 @@ type GatewayUsageRecordConfig struct {
 	AutoScaleCooldownSeconds int `mapstructure:"auto_scale_cooldown_seconds"`
 }
 
+// GatewayHTTPClientConfig 上游 HTTP 客户端调优配置
+// DNS 缓存与覆盖只作用于直连和连接 HTTP 代理服务器本身；经代理访问的目标主机由代理解析
+type GatewayHTTPClientConfig struct {
+	// HTTP2: 是否尝试 HTTP/2（配置代理或自定义拨号后 Go 默认不再尝试 h2）
+	HTTP2 bool `mapstructure:"http2"`
+	// KeepAliveSeconds: TCP keep-alive 探测间隔（秒），0 使用 Go 默认值，-1 关闭
+	KeepAliveSeconds int `mapstructure:"keep_alive_seconds"`
+	// DisableKeepAlives: 关闭 HTTP 连接复用（每个请求新建连接，仅用于排查问题）
+	DisableKeepAlives bool `mapstructure:"disable_keep_alives"`
+	// HappyEyeballs: 双栈主机并行尝试 IPv6/IPv4（RFC 6555），关闭后按解析顺序逐个尝试
+	HappyEyeballs bool `mapstructure:"happy_eyeballs"`
+	// FallbackDelayMs: happy eyeballs 启动备用地址族前的等待时间（毫秒）
+	FallbackDelayMs int `mapstructure:"fallback_delay_ms"`
+	// DNSCacheTTLSeconds: DNS 解析结果缓存时间（秒），0 表示不缓存
+	DNSCacheTTLSeconds int `mapstructure:"dns_cache_ttl_seconds"`
+	// DNSOverrides: 固定解析（类似 curl --resolve），优先于 DNS 与缓存
+	DNSOverrides []GatewayDNSOverride `mapstructure:"dns_overrides"`
+	// Adapters: 按平台（anthropic/openai/gemini/antigravity/sora）覆盖连接池与协议参数，0 / 未设置沿用全局值
+	Adapters map[string]GatewayHTTPClientAdapterConfig `mapstructure:"adapters"`
+}
+
+// GatewayResponseCompressionConfig 网关响应压缩配置
+// 只压缩 JSON/文本响应；上游已压缩（带 Content-Encoding）的响应原样透传
+type GatewayResponseCompressionConfig struct {
+	Enabled bool `mapstructure:"enabled"`
+	// Algorithms: 支持的算法及服务端偏好顺序（gzip/br/zstd），客户端 q 值相同时按此顺序选择
+	Algorithms []string `mapstructure:"algorithms"`
+	// MinSizeBytes: 小于该大小的响应不压缩
+	MinSizeBytes int `mapstructure:"min_size_bytes"`
+	// SSE: 是否压缩 SSE 流（每个事件写出后立即 flush 压缩器，保证事件不被缓冲）
+	SSE bool `mapstructure:"sse"`
+}
+
+// GatewayDNSOverride 单条固定解析
+type GatewayDNSOverride struct {
+	Host    string `mapstructure:"host"`
+	Address string `mapstructure:"address"`
+}
+
+// GatewayHTTPClientAdapterConfig 单个平台的上游客户端覆盖参数
+type GatewayHTTPClientAdapterConfig struct {
+	HTTP2                  *bool `mapstructure:"http2"`
+	MaxIdleConnsPerHost    int   `mapstructure:"max_idle_conns_per_host"`
+	MaxConnsPerHost        int   `mapstructure:"max_conns_per_host"`
+	IdleConnTimeoutSeconds int   `mapstructure:"idle_conn_timeout_seconds"`
+}
+
+// GatewayUpstreamTimeoutsConfig 上游请求分阶段超时配置（秒，0 表示不限制）
+type GatewayUpstreamTimeoutsConfig struct {
+	// ConnectSeconds: 建立上游连接（含代理与 TLS 握手）的超时
+	ConnectSeconds int `mapstructure:"connect_seconds"`
+	// FirstTokenSeconds: 从发出请求到收到首个响应体字节的超时
+	FirstTokenSeconds int `mapstructure:"first_token_seconds"`
+	// TotalSeconds: 整个上游请求（含流式读取）的超时
+	TotalSeconds int `mapstructure:"total_seconds"`
+	// Rules: 按平台/模型覆盖默认值，按顺序取第一条匹配的规则，规则中为 0 的字段沿用默认值
+	Rules []GatewayUpstreamTimeoutRule `mapstructure:"rules"`
+}
+
+// GatewayUpstreamTimeoutRule 单条上游超时覆盖规则
+type GatewayUpstreamTimeoutRule struct {
+	// Platform: 匹配的平台（anthropic/openai/gemini/antigravity/sora），为空匹配所有平台
+	Platform string `mapstructure:"platform"`
+	// Models: 匹配的模型（支持末尾 * 通配），为空匹配所有模型
+	Models            []string `mapstructure:"models"`
+	ConnectSeconds    int      `mapstructure:"connect_seconds"`
+	FirstTokenSeconds int      `mapstructure:"first_token_seconds"`
+	TotalSeconds      int      `mapstructure:"total_seconds"`
+}
+
+// GatewayBodyLimitRule 单条接口请求体上限规则
+type GatewayBodyLimitRule struct {
+	// Path: 请求路径前缀（如 /v1/messages/count_tokens）
+	Path     string `mapstructure:"path"`
+	MaxBytes int64  `mapstructure:"max_bytes"`
+}
+
 // SoraModelFiltersConfig Sora 模型过滤配置
 type SoraModelFiltersConfig struct {
 	// HidePromptEnhance 是否隐藏 prompt-enhance 模型
@@ -511,6 +1138,20 @@ type TLSProfileConfig struct {
 	Curves []uint16 `mapstructure:"curves"`
 	// PointFormats: 点格式列表（空则使用内置默认值）
 	PointFormats []uint8 `mapstructure:"point_formats"`
+	// ClientHello: 浏览器 ClientHello 预设（chrome/firefox/safari/edge/ios），设置后忽略上面三项与 GREASE
+	ClientHello string `mapstructure:"client_hello"`
+	// HTTP2: 以浏览器的 HTTP/2 SETTINGS 建立连接（上游需支持 h2）
+	HTTP2 TLSHTTP2Config `mapstructure:"http2"`
+}
+
+// TLSHTTP2Config HTTP/2 连接参数（SETTINGS 帧与初始窗口，0 表示使用 Go 默认值）
+type TLSHTTP2Config struct {
+	Enabled              bool   `mapstructure:"enabled"`
+	HeaderTableSize      uint32 `mapstructure:"header_table_size"`
+	InitialWindowSize    uint32 `mapstructure:"initial_window_size"`
+	ConnectionWindowSize uint32 `mapstructure:"connection_window_size"`
+	MaxFrameSize         uint32 `mapstructure:"max_frame_size"`
+	MaxHeaderListSize    uint32 `mapstructure:"max_header_list_size"`
 }
 
 // GatewaySchedulingConfig accounts scheduling configuration.
@@ -526,6 +1167,9 @@ type GatewaySchedulingConfig struct {
 	// 兜底层账户选择策略: "last_used"(按最后使用时间排序，默认) 或 "random"(随机)
 	FallbackSelectionMode string `mapstructure:"fallback_selection_mode"`
 
+	// 负载感知层账户选择策略: "load"(优先级 → 负载率 → LRU，默认) 或 "latency"(优先级 → 延迟 → 负载率 → LRU)
+	SelectionStrategy string `mapstructure:"selection_strategy"`
+
 	// 负载计算
 	LoadBatchEnabled bool `mapstructure:"load_batch_enabled"`
 
@@ -578,6 +1222,43 @@ type DatabaseConfig struct {
 	ConnMaxLifetimeMinutes int `mapstructure:"conn_max_lifetime_minutes"`
 	// ConnMaxIdleTimeMinutes: 空闲连接最大存活时间，及时释放不活跃连接
 	ConnMaxIdleTimeMinutes int `mapstructure:"conn_max_idle_time_minutes"`
+	// Replicas: 只读副本，用量查询、仪表盘聚合与列表接口优先走副本，写入始终走主库
+	Replicas []DatabaseReplicaConfig `mapstructure:"replicas"`
+	// ReplicaMaxLagSeconds: 副本复制延迟超过该值时暂停向其路由，回落到其他副本或主库
+	ReplicaMaxLagSeconds int `mapstructure:"replica_max_lag_seconds"`
+}
+
+// DatabaseReplicaConfig 只读副本连接配置，未设置的字段沿用主库配置
+type DatabaseReplicaConfig struct {
+	Host     string `mapstructure:"host"`
+	Port     int    `mapstructure:"port"`
+	User     string `mapstructure:"user"`
+	Password string `mapstructure:"password"`
+	DBName   string `mapstructure:"dbname"`
+	SSLMode  string `mapstructure:"sslmode"`
+}
+
+// ReplicaConfig 以主库配置为基础合并副本连接参数
+func (d *DatabaseConfig) ReplicaConfig(r DatabaseReplicaConfig) DatabaseConfig {
+	merged := *d
+	merged.Replicas = nil
+	merged.Host = r.Host
+	if r.Port > 0 {
+		merged.Port = r.Port
+	}
+	if r.User != "" {
+		merged.User = r.User
+	}
+	if r.Password != "" {
+		merged.Password = r.Password
+	}
+	if r.DBName != "" {
+		merged.DBName = r.DBName
+	}
+	if r.SSLMode != "" {
+		merged.SSLMode = r.SSLMode
+	}
+	return merged
 }
 
 func (d *DatabaseConfig) DSN() string {
@@ -692,6 +1373,308 @@ type JWTConfig struct {
 	RefreshWindowMinutes int `mapstructure:"refresh_window_minutes"`
 }
 
+// APIKeyRotationConfig API Key 轮换配置
+type APIKeyRotationConfig struct {
+	// DefaultGraceHours 轮换后旧 Key 默认继续有效的小时数
+	DefaultGraceHours int `mapstructure:"default_grace_hours"`
+	// MaxGraceHours 允许的最大宽限期（小时）
+	MaxGraceHours int `mapstructure:"max_grace_hours"`
+}
+
+// TrashConfig 回收站配置（已软删除的 API Key 与上游账号）
+type TrashConfig struct {
+	// RetentionDays 软删除后保留的天数，超过后由后台任务彻底清除；0 表示永不清除
+	RetentionDays int `mapstructure:"retention_days"`
+	// PurgeIntervalMinutes 清除任务的执行间隔（分钟）
+	PurgeIntervalMinutes int `mapstructure:"purge_interval_minutes"`
+}
+
+// RequestLogConfig 网关请求日志配置（管理后台按 Key/模型/状态/Trace ID 检索）
+type RequestLogConfig struct {
+	// Enabled 是否记录网关请求日志
+	Enabled bool `mapstructure:"enabled"`
+	// RetentionDays 保留天数，超过后由 request_log_purge 定时任务清除（1-30）
+	RetentionDays int `mapstructure:"retention_days"`
+	// QueueSize 异步写入队列容量，队列满时丢弃新记录而不阻塞请求
+	QueueSize int `mapstructure:"queue_size"`
+	// BatchSize 单次批量写入的最大条数
+	BatchSize int `mapstructure:"batch_size"`
+}
+
+// JobQueueConfig 基于 Redis Streams 的内部任务队列配置
+type JobQueueConfig struct {
+	// Enabled 是否启用持久化任务队列（邮件、仪表盘回填、数据主体请求、出站 Webhook、用量分析写入）；
+	// 默认关闭，各模块保持进程内处理
+	Enabled bool `mapstructure:"enabled"`
+	// KeyPrefix Stream key 前缀（多环境共用 Redis 时用于隔离）
+	KeyPrefix string `mapstructure:"key_prefix"`
+	// ConsumerName 当前实例的消费者名称（为空时使用 hostname-pid）
+	ConsumerName string `mapstructure:"consumer_name"`
+	// BlockSeconds 单次阻塞读取的最长等待时间（秒）
+	BlockSeconds int `mapstructure:"block_seconds"`
+	// ClaimIdleSeconds 任务被取走后超过该时间未确认，视为卡住并由其他消费者重新认领（秒）
+	ClaimIdleSeconds int `mapstructure:"claim_idle_seconds"`
+	// MaxAttempts 最大投递次数，超过后转入死信队列
+	MaxAttempts int `mapstructure:"max_attempts"`
+	// MaxLen 每个 Stream 保留的最大消息数（近似裁剪）
+	MaxLen int64 `mapstructure:"max_len"`
+}
+
+// AnalyticsTeeConfig 请求摘要事件旁路转发配置。
+// 请求完成后将摘要（模型、Token、费用、耗时、状态码等，不含请求/响应内容）批量写入任务队列，
+// 由队列消费者投递到外部分析系统，投递失败按任务队列语义重试，不影响请求延迟。
+type AnalyticsTeeConfig struct {
+	Enabled bool `mapstructure:"enabled"`
+	// URL 接收事件的 HTTP 地址，收到 POST {"events": [...]}
+	URL string `mapstructure:"url"`
+	// AuthToken 以 Authorization: Bearer 发送给接收端
+	AuthToken string `mapstructure:"auth_token"`
+	// TimeoutSeconds 单次投递超时（秒）
+	TimeoutSeconds int `mapstructure:"timeout_seconds"`
+	// BatchSize 单批最多事件数
+	BatchSize int `mapstructure:"batch_size"`
+	// FlushIntervalMs 未攒满一批时的最长等待时间（毫秒），决定事件到达外部系统的延迟
+	FlushIntervalMs int `mapstructure:"flush_interval_ms"`
+	// BufferSize 进程内事件缓冲，入队不及时时丢弃新事件而不阻塞网关
+	BufferSize int `mapstructure:"buffer_size"`
+	// Workers 投递并发数（任务队列消费者数量）
+	Workers int `mapstructure:"workers"`
+	// SampleRate 采样比例 (0, 1]，0 或 1 表示全量
+	SampleRate float64 `mapstructure:"sample_rate"`
+	// ErrorsOnly 仅转发失败请求（状态码 >= 400）
+	ErrorsOnly bool `mapstructure:"errors_only"`
+}
+
+// EventExportConfig 事件导出配置（用量、审计、告警事件发布到 Kafka / NATS）。
+// 事件先写入 event_outbox 表，再由后台转发协程批量发布并确认，保证至少一次投递；
+// 下游应按事件 id 去重。
+type EventExportConfig struct {
+	Enabled bool `mapstructure:"enabled"`
+	// Driver 发布方式: kafka/nats
+	Driver string `mapstructure:"driver"`
+	// Kafka 通过 Kafka REST Proxy（v2 API）发布
+	Kafka EventExportKafkaConfig `mapstructure:"kafka"`
+	// NATS 通过 NATS 核心协议发布，可选等待 JetStream 确认
+	NATS EventExportNATSConfig `mapstructure:"nats"`
+	// Topics 事件类型到 topic / subject 的映射，留空表示不导出该类事件
+	Topics EventExportTopicsConfig `mapstructure:"topics"`
+	// BatchSize 单次从 outbox 认领并发布的最大事件数
+	BatchSize int `mapstructure:"batch_size"`
+	// PollIntervalMs outbox 轮询间隔（毫秒）
+	PollIntervalMs int `mapstructure:"poll_interval_ms"`
+	// TimeoutSeconds 单批发布超时（秒），同时作为认领租约的基准
+	TimeoutSeconds int `mapstructure:"timeout_seconds"`
+	// RetentionHours 已发布事件在 outbox 中的保留时间（小时）
+	RetentionHours int `mapstructure:"retention_hours"`
+}
+
+// EventExportKafkaConfig Kafka REST Proxy 配置
+type EventExportKafkaConfig struct {
+	// RESTURL REST Proxy 地址，如 http://kafka-rest:8082
+	RESTURL string `mapstructure:"rest_url"`
+	// AuthToken 以 Authorization: Bearer 发送（可选）
+	AuthToken string `mapstructure:"auth_token"`
+}
+
+// EventExportNATSConfig NATS 配置
+type EventExportNATSConfig struct {
+	// URL 服务地址，如 nats://nats:4222
+	URL      string `mapstructure:"url"`
+	Token    string `mapstructure:"token"`
+	User     string `mapstructure:"user"`
+	Password string `mapstructure:"password"`
+	// JetStream 等待 JetStream 持久化确认（subject 需已绑定 stream），并以 Nats-Msg-Id 去重
+	JetStream bool `mapstructure:"jetstream"`
+}
+
+// EventExportTopicsConfig 事件类型到 topic / subject 的映射
+type EventExportTopicsConfig struct {
+	Usage string `mapstructure:"usage"`
+	Audit string `mapstructure:"audit"`
+	Alert string `mapstructure:"alert"`
+}
+
+// ClickHouseConfig 用量明细写入 ClickHouse 的配置。
+// Postgres 仍保存事务数据（usage_logs 及计费）；ClickHouse 用于高并发下的用量分析查询。
+// 写入在进程内攒批后通过 HTTP 接口批量插入，ClickHouse 不可用时在内存中缓冲并重试。
+type ClickHouseConfig struct {
+	Enabled bool `mapstructure:"enabled"`
+	// URL HTTP 接口地址，如 http://clickhouse:8123
+	URL string `mapstructure:"url"`
+	// Database 数据库名（不存在时自动创建）
+	Database string `mapstructure:"database"`
+	Username string `mapstructure:"username"`
+	Password string `mapstructure:"password"`
+	// BatchSize 单次插入的最大行数
+	BatchSize int `mapstructure:"batch_size"`
+	// FlushIntervalMs 未攒满一批时的最长等待时间（毫秒）
+	FlushIntervalMs int `mapstructure:"flush_interval_ms"`
+	// BufferSize 进程内待写入队列容量，写满时丢弃新记录而不阻塞网关
+	BufferSize int `mapstructure:"buffer_size"`
+	// MaxPendingRows 写入失败后在内存中保留待重试的最大行数，超出时丢弃最早的批次
+	MaxPendingRows int `mapstructure:"max_pending_rows"`
+	// TimeoutSeconds 单次插入超时（秒）
+	TimeoutSeconds int `mapstructure:"timeout_seconds"`
+}
+
+// APIDocsConfig OpenAPI 文档与 Swagger UI 配置。
+// /openapi.json 由运行时路由表生成，与实际注册的接口保持一致；/docs 页面从 CDN 加载 Swagger UI 静态资源。
+type APIDocsConfig struct {
+	Enabled bool `mapstructure:"enabled"`
+	// SwaggerUIURL swagger-ui-dist 资源地址（需提供 swagger-ui.css 与 swagger-ui-bundle.js），可替换为内网镜像
+	SwaggerUIURL string `mapstructure:"swagger_ui_url"`
+}
+
+// GRPCConfig 网关 gRPC 接口配置（契约见 backend/proto/gateway/v1/gateway.proto）。
+// gRPC 请求在进程内转交给 HTTP 网关路由处理，认证、调度、计费与 HTTP 接口完全一致。
+type GRPCConfig struct {
+	Enabled bool   `mapstructure:"enabled"`
+	Host    string `mapstructure:"host"`
+	Port    int    `mapstructure:"port"`
+}
+
+// Address 监听地址
+func (g *GRPCConfig) Address() string {
+	return fmt.Sprintf("%s:%d", g.Host, g.Port)
+}
+
+// WorkerConfig 后台任务运行时配置
+//
+// 长耗时的后台任务（Token 刷新、预聚合、过期/清理任务、任务队列消费等）可以与 API 同进程运行，
+// 也可以拆分到独立的 `sub2api worker` 进程，让 API 副本专注于请求延迟。
+type WorkerConfig struct {
+	// Mode 运行角色: embedded/api/worker
+	Mode string `mapstructure:"mode"`
+	// LeaderElection 多个进程运行后台任务时的选主配置
+	LeaderElection WorkerLeaderElectionConfig `mapstructure:"leader_election"`
+}
+
+// WorkerLeaderElectionConfig 后台单例任务选主配置
+//
+// 多个 embedded/worker 进程同时运行时，Token 刷新、预聚合、过期检查与清理等周期任务
+// 只在持有 Redis 租约的实例上执行，leader 失联后租约过期由其他实例接管。
+type WorkerLeaderElectionConfig struct {
+	// Enabled 是否启用选主；单实例部署可关闭
+	Enabled bool `mapstructure:"enabled"`
+	// LeaseSeconds 租约时长（秒），leader 每 1/3 租约续期一次，失联后最长该时间内完成切换
+	LeaseSeconds int `mapstructure:"lease_seconds"`
+}
+
+// RunsBackgroundJobs 当前进程是否运行后台任务
+func (c WorkerConfig) RunsBackgroundJobs() bool {
+	return c.Mode != WorkerModeAPI
+}
+
+// ServesHTTP 当前进程是否监听 HTTP
+func (c WorkerConfig) ServesHTTP() bool {
+	return c.Mode != WorkerModeWorker
+}
+
+// CronJobsConfig 定时任务（cron 表达式调度）配置
+//
+// 多副本部署时通过 Redis 锁保证同一次调度只在一个实例上执行，
+// 每个任务的最近执行状态持久化在数据库中，重启后会补跑停机期间错过的一次调度。
+type CronJobsConfig struct {
+	// Enabled 是否启用定时任务调度
+	Enabled bool `mapstructure:"enabled"`
+	// JitterSeconds 触发后随机延迟的上限（秒），用于打散多副本/多任务的同时触发；0 表示不延迟
+	JitterSeconds int `mapstructure:"jitter_seconds"`
+	// Tasks 按任务名覆盖内置默认值，例如 tasks.dashboard_recompute.schedule
+	Tasks map[string]CronTaskConfig `mapstructure:"tasks"`
+}
+
+// CronTaskConfig 单个定时任务的覆盖配置（未设置的字段使用内置默认值）
+type CronTaskConfig struct {
+	// Enabled 是否启用该任务
+	Enabled *bool `mapstructure:"enabled"`
+	// Schedule 5 段 cron 表达式（分 时 日 月 周），按 timezone 解释
+	Schedule string `mapstructure:"schedule"`
+	// JitterSeconds 覆盖全局随机延迟上限
+	JitterSeconds *int `mapstructure:"jitter_seconds"`
+}
+
+// UsageExportConfig 使用记录导出（CSV / Parquet）配置
+type UsageExportConfig struct {
+	// MaxRangeDays 单次导出允许的最大时间跨度（天）
+	MaxRangeDays int `mapstructure:"max_range_days"`
+	// BatchSize 每批从数据库读取的行数（同时作为 Parquet 行组大小）
+	BatchSize int `mapstructure:"batch_size"`
+	// StoragePrefix 上传到对象存储（storage）时的 key 前缀，例如 usage-exports/
+	StoragePrefix string `mapstructure:"storage_prefix"`
+}
+
+// StatementsConfig 月度账单（JSON / CSV / PDF）配置；文件写入对象存储，未配置存储时不生成
+type StatementsConfig struct {
+	// StoragePrefix 账单文件在对象存储中的 key 前缀，例如 statements/
+	StoragePrefix string `mapstructure:"storage_prefix"`
+	// IssuerName 账单抬头中的出具方名称
+	IssuerName string `mapstructure:"issuer_name"`
+}
+
+// PaymentsConfig 在线支付（自助充值）配置
+type PaymentsConfig struct {
+	Stripe StripeConfig `mapstructure:"stripe"`
+}
+
+// StripeConfig Stripe Checkout 充值：用户下单跳转 Stripe 支付，Webhook 回调后为账户余额入账
+type StripeConfig struct {
+	Enabled bool `mapstructure:"enabled"`
+	// SecretKey Stripe API 密钥（sk_live_... / sk_test_...）
+	SecretKey string `mapstructure:"secret_key"`
+	// WebhookSecret Webhook 签名密钥（whsec_...），用于校验 Stripe-Signature
+	WebhookSecret string `mapstructure:"webhook_secret"`
+	// Currency 收款币种（ISO 4217），例如 usd、jpy；金额按币种的最小单位换算
+	Currency string `mapstructure:"currency"`
+	// CreditsPerUnit 每 1 单位币种兑换的余额（USD），例如 CNY 收款时可设置为汇率
+	CreditsPerUnit float64 `mapstructure:"credits_per_unit"`
+	// MinAmount / MaxAmount 单笔充值金额范围（按收款币种）
+	MinAmount float64 `mapstructure:"min_amount"`
+	MaxAmount float64 `mapstructure:"max_amount"`
+	// SuccessURL / CancelURL 支付完成或取消后跳转的前端地址；SuccessURL 可包含 {CHECKOUT_SESSION_ID}
+	SuccessURL string `mapstructure:"success_url"`
+	CancelURL  string `mapstructure:"cancel_url"`
+	// WebhookToleranceSeconds Webhook 签名时间戳允许的最大偏差，防止重放
+	WebhookToleranceSeconds int `mapstructure:"webhook_tolerance_seconds"`
+	// APIBase Stripe API 地址，仅测试时需要修改
+	APIBase string `mapstructure:"api_base"`
+}
+
+// StorageConfig 对象存储配置（S3 / MinIO / R2 等兼容存储）
+// 用于导出文件、Sora 媒体等原本写入本地磁盘的数据，多实例部署时共享同一存储桶。
+type StorageConfig struct {
+	Enabled bool `mapstructure:"enabled"`
+	// Endpoint 服务地址，为空时使用 AWS S3（https://s3.<region>.amazonaws.com）
+	Endpoint string `mapstructure:"endpoint"`
+	Region   string `mapstructure:"region"`
+	Bucket   string `mapstructure:"bucket"`
+	// Prefix 本部署所有对象的 key 前缀，用于多个部署共享存储桶，例如 prod/
+	Prefix          string `mapstructure:"prefix"`
+	AccessKeyID     string `mapstructure:"access_key_id"`
+	SecretAccessKey string `mapstructure:"secret_access_key"`
+	// SessionToken 临时凭证（STS）的会话令牌，可选
+	SessionToken string `mapstructure:"session_token"`
+	// UsePathStyle 使用 endpoint/bucket/key 形式访问（MinIO 通常需要开启）
+	UsePathStyle bool `mapstructure:"use_path_style"`
+}
+
+// WebSessionConfig 管理后台 Cookie 会话配置（会话数据存储在 Redis）
+type WebSessionConfig struct {
+	// Enabled 是否在登录时下发会话 Cookie，并允许中间件通过 Cookie 认证
+	Enabled bool `mapstructure:"enabled"`
+	// CookieName 会话 Cookie 名称
+	CookieName string `mapstructure:"cookie_name"`
+	// CookieDomain Cookie 作用域（为空表示仅当前 host）
+	CookieDomain string `mapstructure:"cookie_domain"`
+	// Secure 是否仅通过 HTTPS 发送 Cookie（生产环境应开启）
+	Secure bool `mapstructure:"secure"`
+	// SameSite Cookie 的 SameSite 策略: strict/lax（不允许 none，跨站请求会携带 Cookie）
+	SameSite string `mapstructure:"same_site"`
+	// IdleTimeoutMinutes 滑动过期时间（分钟），每次访问会刷新
+	IdleTimeoutMinutes int `mapstructure:"idle_timeout_minutes"`
+	// AbsoluteTimeoutHours 会话最长存活时间（小时），滑动续期不会超过该上限
+	AbsoluteTimeoutHours int `mapstructure:"absolute_timeout_hours"`
+}
+
 // TotpConfig TOTP 双因素认证配置
 type TotpConfig struct {
 	// EncryptionKey 用于加密 TOTP 密钥的 AES-256 密钥（32 字节 hex 编码）
@@ -700,6 +1683,48 @@ type TotpConfig struct {
 	// EncryptionKeyConfigured 标记加密密钥是否为手动配置（非自动生成）
 	// 只有手动配置了密钥才允许在管理后台启用 TOTP 功能
 	EncryptionKeyConfigured bool `mapstructure:"-"`
+	// RequireForAdmins 强制管理员账号启用 2FA
+	// 开启后未绑定 TOTP 的管理员只能访问 2FA 绑定相关接口，无法进入管理后台
+	RequireForAdmins bool `mapstructure:"require_for_admins"`
+}
+
+// AdminUsersConfig 后台账号（超级管理员 / 财务 / 账号运维 / 只读审计）的密码策略
+type AdminUsersConfig struct {
+	// PasswordMinLength 密码最小长度
+	PasswordMinLength int `mapstructure:"password_min_length"`
+	// PasswordRequireUpper / Lower / Digit / Symbol 密码必须包含的字符类别
+	PasswordRequireUpper  bool `mapstructure:"password_require_upper"`
+	PasswordRequireLower  bool `mapstructure:"password_require_lower"`
+	PasswordRequireDigit  bool `mapstructure:"password_require_digit"`
+	PasswordRequireSymbol bool `mapstructure:"password_require_symbol"`
+	// PasswordMaxAgeDays 密码有效期（天），到期后必须修改密码才能进入管理后台；0 表示不过期
+	PasswordMaxAgeDays int `mapstructure:"password_max_age_days"`
+}
+
+// NotificationsConfig 邮件通知（额度告警、Key 到期提醒、后台账号邀请、月度账单）
+type NotificationsConfig struct {
+	// KeyExpiryWarnDays API Key 到期前多少天发送提醒；0 表示不提醒
+	KeyExpiryWarnDays int `mapstructure:"key_expiry_warn_days"`
+	// LogRetentionDays 发送记录保留天数
+	LogRetentionDays int `mapstructure:"log_retention_days"`
+	// Telegram 运维告警与命令机器人
+	Telegram TelegramConfig `mapstructure:"telegram"`
+}
+
+// TelegramConfig Telegram 机器人：向白名单会话推送严重告警（账号全部不可用、配额耗尽），
+// 并响应这些会话中的运维命令（/status、/disable、/usage）。
+type TelegramConfig struct {
+	Enabled bool `mapstructure:"enabled"`
+	// BotToken BotFather 签发的机器人 Token
+	BotToken string `mapstructure:"bot_token"`
+	// ChatIDs 会话 ID 白名单：告警推送到这些会话，且只响应这些会话中的命令
+	ChatIDs []int64 `mapstructure:"chat_ids"`
+	// AlertCooldownMinutes 同一告警在冷却期内只推送一次（多实例共享）
+	AlertCooldownMinutes int `mapstructure:"alert_cooldown_minutes"`
+	// PollTimeoutSeconds getUpdates 长轮询超时
+	PollTimeoutSeconds int `mapstructure:"poll_timeout_seconds"`
+	// APIBase Bot API 地址，使用自建 Bot API 服务或测试时修改
+	APIBase string `mapstructure:"api_base"`
 }
 
 type TurnstileConfig struct {
@@ -811,6 +1836,42 @@ func Load() (*Config, error) {
 	return load(false)
 }
 
+// LoadReport 配置加载过程中的诊断信息
+type LoadReport struct {
+	// ConfigFile 实际读取的配置文件，为空表示仅使用默认值与环境变量
+	ConfigFile string
+	// UnknownKeys 配置中存在但不对应任何配置项的键（通常是拼写错误或已废弃的配置）
+	UnknownKeys []string
+}
+
+// Check 按启动流程加载并校验配置，返回诊断信息，不启动任何服务（sub2api config check）。
+func Check() (*Config, *LoadReport, error) {
+	return loadWithReport(true)
+}
+
+// 命令行层配置（优先级：默认值 < 配置文件 < 环境变量 < 命令行）
+var commandLine struct {
+	configFile string
+	overrides  map[string]string
+}
+
+// SetCommandLineOverrides 设置命令行层配置：configFile 显式指定配置文件，
+// sets 为 key=value 形式的覆盖项（key 使用点分路径，例如 server.port=9090）。
+func SetCommandLineOverrides(configFile string, sets []string) error {
+	overrides := make(map[string]string, len(sets))
+	for _, item := range sets {
+		key, value, ok := strings.Cut(item, "=")
+		key = strings.ToLower(strings.TrimSpace(key))
+		if !ok || key == "" {
+			return fmt.Errorf("invalid -set %q: expected key=value", item)
+		}
+		overrides[key] = value
+	}
+	commandLine.configFile = strings.TrimSpace(configFile)
+	commandLine.overrides = overrides
+	return nil
+}
+
 // LoadForBootstrap 读取启动阶段配置。
 //
 // 启动阶段允许 jwt.secret 先留空，后续由数据库初始化流程补齐并再次完整校验。
@@ -819,8 +1880,22 @@ func LoadForBootstrap() (*Config, error) {
 }
 
 func load(allowMissingJWTSecret bool) (*Config, error) {
+	cfg, report, err := loadWithReport(allowMissingJWTSecret)
+	if err != nil {
+		return nil, err
+	}
+	for _, key := range report.UnknownKeys {
+		slog.Warn("unknown config key ignored", "key", key, "hint", suggestConfigKey(key))
+	}
+	return cfg, nil
+}
+
+func loadWithReport(allowMissingJWTSecret bool) (*Config, *LoadReport, error) {
 	viper.SetConfigName("config")
 	viper.SetConfigType("yaml")
+	if commandLine.configFile != "" {
+		viper.SetConfigFile(commandLine.configFile)
+	}
 
 	// Add config paths in priority order
 	// 1. DATA_DIR environment variable (highest priority)
@@ -844,24 +1919,42 @@ func load(allowMissingJWTSecret bool) (*Config, error) {
 	setDefaults()
 
 	if err := viper.ReadInConfig(); err != nil {
-		if _, ok := err.(viper.ConfigFileNotFoundError); !ok {
-			return nil, fmt.Errorf("read config error: %w", err)
+		if _, ok := err.(viper.ConfigFileNotFoundError); !ok || commandLine.configFile != "" {
+			return nil, nil, fmt.Errorf("read config error: %w", err)
 		}
 		// 配置文件不存在时使用默认值
 	}
 
+	// 命令行覆盖项优先级最高；未知的 key 直接报错，避免拼写错误被静默忽略
+	for key, value := range commandLine.overrides {
+		if !isKnownConfigKey(key) {
+			return nil, nil, fmt.Errorf("unknown config key %q in -set; %s", key, suggestConfigKey(key))
+		}
+		viper.Set(key, value)
+	}
+
+	report := &LoadReport{ConfigFile: viper.ConfigFileUsed()}
 	var cfg Config
-	if err := viper.Unmarshal(&cfg); err != nil {
-		return nil, fmt.Errorf("unmarshal config error: %w", err)
+	var md mapstructure.Metadata
+	if err := viper.Unmarshal(&cfg, func(dc *mapstructure.DecoderConfig) { dc.Metadata = &md }); err != nil {
+		return nil, nil, fmt.Errorf("unmarshal config error: %w", err)
+	}
+	report.UnknownKeys = unknownConfigKeys(md.Unused)
+	if err := resolveSecretRefs(&cfg); err != nil {
+		return nil, nil, fmt.Errorf("resolve secrets error: %w", err)
 	}
 
 	cfg.RunMode = NormalizeRunMode(cfg.RunMode)
+	cfg.Worker.Mode = strings.ToLower(strings.TrimSpace(cfg.Worker.Mode))
 	cfg.Server.Mode = strings.ToLower(strings.TrimSpace(cfg.Server.Mode))
 	if cfg.Server.Mode == "" {
 		cfg.Server.Mode = "debug"
 	}
 	cfg.Server.FrontendURL = strings.TrimSpace(cfg.Server.FrontendURL)
 	cfg.JWT.Secret = strings.TrimSpace(cfg.JWT.Secret)
+	cfg.WebSession.CookieName = strings.TrimSpace(cfg.WebSession.CookieName)
+	cfg.WebSession.CookieDomain = strings.TrimSpace(cfg.WebSession.CookieDomain)
+	cfg.WebSession.SameSite = strings.ToLower(strings.TrimSpace(cfg.WebSession.SameSite))
 	cfg.LinuxDo.ClientID = strings.TrimSpace(cfg.LinuxDo.ClientID)
 	cfg.LinuxDo.ClientSecret = strings.TrimSpace(cfg.LinuxDo.ClientSecret)
 	cfg.LinuxDo.AuthorizeURL = strings.TrimSpace(cfg.LinuxDo.AuthorizeURL)
@@ -885,13 +1978,14 @@ func load(allowMissingJWTSecret bool) (*Config, error) {
 	cfg.Log.Environment = strings.TrimSpace(cfg.Log.Environment)
 	cfg.Log.StacktraceLevel = strings.ToLower(strings.TrimSpace(cfg.Log.StacktraceLevel))
 	cfg.Log.Output.FilePath = strings.TrimSpace(cfg.Log.Output.FilePath)
+	cfg.Gateway.FinishReasonOverrides = normalizeFinishReasonOverrides(cfg.Gateway.FinishReasonOverrides)
 
 	// Auto-generate TOTP encryption key if not set (32 bytes = 64 hex chars for AES-256)
 	cfg.Totp.EncryptionKey = strings.TrimSpace(cfg.Totp.EncryptionKey)
 	if cfg.Totp.EncryptionKey == "" {
 		key, err := generateJWTSecret(32) // Reuse the same random generation function
 		if err != nil {
-			return nil, fmt.Errorf("generate totp encryption key error: %w", err)
+			return nil, nil, fmt.Errorf("generate totp encryption key error: %w", err)
 		}
 		cfg.Totp.EncryptionKey = key
 		cfg.Totp.EncryptionKeyConfigured = false
@@ -907,7 +2001,7 @@ func load(allowMissingJWTSecret bool) (*Config, error) {
 	}
 
 	if err := cfg.Validate(); err != nil {
-		return nil, fmt.Errorf("validate config error: %w", err)
+		return nil, nil, fmt.Errorf("validate config error: %w", err)
 	}
 
 	if allowMissingJWTSecret && originalJWTSecret == "" {
@@ -931,7 +2025,7 @@ func load(allowMissingJWTSecret bool) (*Config, error) {
 		)
 	}
 
-	return &cfg, nil
+	return &cfg, report, nil
 }
 
 func setDefaults() {
@@ -1001,12 +2095,33 @@ func setDefaults() {
 	viper.SetDefault("security.csp.enabled", true)
 	viper.SetDefault("security.csp.policy", DefaultCSPPolicy)
 	viper.SetDefault("security.proxy_probe.insecure_skip_verify", false)
+	viper.SetDefault("security.credential_encryption.enabled", false)
+	viper.SetDefault("security.credential_encryption.master_key", "")
+	viper.SetDefault("security.credential_encryption.master_key_id", "k1")
+
+	// Secrets
+	viper.SetDefault("secrets.provider", "")
+	viper.SetDefault("secrets.cache_ttl_seconds", 300)
+	viper.SetDefault("secrets.vault.address", "")
+	viper.SetDefault("secrets.vault.token", "")
+	viper.SetDefault("secrets.vault.token_file", "")
+	viper.SetDefault("secrets.vault.mount", "secret")
+	viper.SetDefault("secrets.vault.namespace", "")
+	viper.SetDefault("secrets.vault.timeout_seconds", 10)
+	viper.SetDefault("secrets.file.dir", "")
+	viper.SetDefault("secrets.exec.timeout_seconds", 15)
 
 	// Billing
 	viper.SetDefault("billing.circuit_breaker.enabled", true)
 	viper.SetDefault("billing.circuit_breaker.failure_threshold", 5)
 	viper.SetDefault("billing.circuit_breaker.reset_timeout_seconds", 30)
 	viper.SetDefault("billing.circuit_breaker.half_open_requests", 3)
+	viper.SetDefault("billing.prepaid_hold.enabled", false)
+	viper.SetDefault("billing.prepaid_hold.default_output_tokens", 4096)
+	viper.SetDefault("billing.prepaid_hold.hold_ttl_seconds", 900)
+	viper.SetDefault("billing.estimated_usage_margin", 0.0)
+	viper.SetDefault("billing.limit_dry_run.subscription_limits", false)
+	viper.SetDefault("billing.limit_dry_run.api_key_quota", false)
 
 	// Turnstile
 	viper.SetDefault("turnstile.required", false)
@@ -1038,6 +2153,7 @@ func setDefaults() {
 	viper.SetDefault("database.max_idle_conns", 128)
 	viper.SetDefault("database.conn_max_lifetime_minutes", 30)
 	viper.SetDefault("database.conn_max_idle_time_minutes", 5)
+	viper.SetDefault("database.replica_max_lag_seconds", 10)
 
 	// Redis
 	viper.SetDefault("redis.host", "localhost")
@@ -1074,6 +2190,150 @@ func setDefaults() {
 
 	// TOTP
 	viper.SetDefault("totp.encryption_key", "")
+	viper.SetDefault("totp.require_for_admins", false)
+
+	// Admin Users
+	viper.SetDefault("admin_users.password_min_length", 12)
+	viper.SetDefault("admin_users.password_require_upper", true)
+	viper.SetDefault("admin_users.password_require_lower", true)
+	viper.SetDefault("admin_users.password_require_digit", true)
+	viper.SetDefault("admin_users.password_require_symbol", false)
+	viper.SetDefault("admin_users.password_max_age_days", 90)
+
+	// Notifications
+	viper.SetDefault("notifications.key_expiry_warn_days", 7)
+	viper.SetDefault("notifications.log_retention_days", 90)
+	viper.SetDefault("notifications.telegram.enabled", false)
+	viper.SetDefault("notifications.telegram.bot_token", "")
+	viper.SetDefault("notifications.telegram.chat_ids", []int64{})
+	viper.SetDefault("notifications.telegram.alert_cooldown_minutes", 30)
+	viper.SetDefault("notifications.telegram.poll_timeout_seconds", 25)
+	viper.SetDefault("notifications.telegram.api_base", "https://api.telegram.org")
+
+	// API Key Rotation
+	viper.SetDefault("api_key_rotation.default_grace_hours", 24)
+	viper.SetDefault("api_key_rotation.max_grace_hours", 720) // 30天
+
+	// Trash
+	viper.SetDefault("trash.retention_days", 30)
+	viper.SetDefault("trash.purge_interval_minutes", 60)
+
+	// Request log
+	viper.SetDefault("request_log.enabled", true)
+	viper.SetDefault("request_log.retention_days", 7)
+	viper.SetDefault("request_log.queue_size", 8192)
+	viper.SetDefault("request_log.batch_size", 200)
+
+	// Job Queue
+	viper.SetDefault("job_queue.enabled", false)
+	viper.SetDefault("job_queue.key_prefix", "jobs:")
+	viper.SetDefault("job_queue.consumer_name", "")
+	viper.SetDefault("job_queue.block_seconds", 5)
+	viper.SetDefault("job_queue.claim_idle_seconds", 60)
+	viper.SetDefault("job_queue.max_attempts", 5)
+	viper.SetDefault("job_queue.max_len", 100000)
+
+	// Analytics tee
+	viper.SetDefault("analytics_tee.enabled", false)
+	viper.SetDefault("analytics_tee.url", "")
+	viper.SetDefault("analytics_tee.auth_token", "")
+	viper.SetDefault("analytics_tee.timeout_seconds", 10)
+	viper.SetDefault("analytics_tee.batch_size", 200)
+	viper.SetDefault("analytics_tee.flush_interval_ms", 1000)
+	viper.SetDefault("analytics_tee.buffer_size", 8192)
+	viper.SetDefault("analytics_tee.workers", 2)
+	viper.SetDefault("analytics_tee.sample_rate", 0)
+	viper.SetDefault("analytics_tee.errors_only", false)
+
+	// Event export
+	viper.SetDefault("event_export.enabled", false)
+	viper.SetDefault("event_export.driver", "kafka")
+	viper.SetDefault("event_export.kafka.rest_url", "")
+	viper.SetDefault("event_export.kafka.auth_token", "")
+	viper.SetDefault("event_export.nats.url", "")
+	viper.SetDefault("event_export.nats.token", "")
+	viper.SetDefault("event_export.nats.user", "")
+	viper.SetDefault("event_export.nats.password", "")
+	viper.SetDefault("event_export.nats.jetstream", false)
+	viper.SetDefault("event_export.topics.usage", "sub2api.usage")
+	viper.SetDefault("event_export.topics.audit", "sub2api.audit")
+	viper.SetDefault("event_export.topics.alert", "sub2api.alert")
+	viper.SetDefault("event_export.batch_size", 100)
+	viper.SetDefault("event_export.poll_interval_ms", 1000)
+	viper.SetDefault("event_export.timeout_seconds", 10)
+	viper.SetDefault("event_export.retention_hours", 24)
+
+	// ClickHouse
+	viper.SetDefault("clickhouse.enabled", false)
+	viper.SetDefault("clickhouse.url", "")
+	viper.SetDefault("clickhouse.database", "sub2api")
+	viper.SetDefault("clickhouse.username", "default")
+	viper.SetDefault("clickhouse.password", "")
+	viper.SetDefault("clickhouse.batch_size", 5000)
+	viper.SetDefault("clickhouse.flush_interval_ms", 2000)
+	viper.SetDefault("clickhouse.buffer_size", 50000)
+	viper.SetDefault("clickhouse.max_pending_rows", 500000)
+	viper.SetDefault("clickhouse.timeout_seconds", 30)
+
+	// API Docs
+	viper.SetDefault("api_docs.enabled", true)
+	viper.SetDefault("api_docs.swagger_ui_url", "https://cdn.jsdelivr.net/npm/swagger-ui-dist@5")
+
+	// gRPC
+	viper.SetDefault("grpc.enabled", false)
+	viper.SetDefault("grpc.host", "0.0.0.0")
+	viper.SetDefault("grpc.port", 9090)
+
+	// Worker
+	viper.SetDefault("worker.mode", WorkerModeEmbedded)
+	viper.SetDefault("worker.leader_election.enabled", true)
+	viper.SetDefault("worker.leader_election.lease_seconds", 30)
+
+	// Cron Jobs
+	viper.SetDefault("cron_jobs.enabled", true)
+	viper.SetDefault("cron_jobs.jitter_seconds", 30)
+
+	// Usage Export
+	viper.SetDefault("usage_export.max_range_days", 93)
+	viper.SetDefault("usage_export.batch_size", 5000)
+	viper.SetDefault("usage_export.storage_prefix", "usage-exports/")
+
+	// Statements
+	viper.SetDefault("statements.storage_prefix", "statements/")
+	viper.SetDefault("statements.issuer_name", "Sub2API")
+
+	// Payments
+	viper.SetDefault("payments.stripe.enabled", false)
+	viper.SetDefault("payments.stripe.secret_key", "")
+	viper.SetDefault("payments.stripe.webhook_secret", "")
+	viper.SetDefault("payments.stripe.currency", "usd")
+	viper.SetDefault("payments.stripe.credits_per_unit", 1.0)
+	viper.SetDefault("payments.stripe.min_amount", 5.0)
+	viper.SetDefault("payments.stripe.max_amount", 1000.0)
+	viper.SetDefault("payments.stripe.success_url", "")
+	viper.SetDefault("payments.stripe.cancel_url", "")
+	viper.SetDefault("payments.stripe.webhook_tolerance_seconds", 300)
+	viper.SetDefault("payments.stripe.api_base", "https://api.stripe.com")
+
+	// Object Storage
+	viper.SetDefault("storage.enabled", false)
+	viper.SetDefault("storage.endpoint", "")
+	viper.SetDefault("storage.region", "us-east-1")
+	viper.SetDefault("storage.bucket", "")
+	viper.SetDefault("storage.prefix", "")
+	viper.SetDefault("storage.access_key_id", "")
+	viper.SetDefault("storage.secret_access_key", "")
+	viper.SetDefault("storage.session_token", "")
+	viper.SetDefault("storage.use_path_style", false)
+
+	// Web Session
+	viper.SetDefault("web_session.enabled", false)
+	viper.SetDefault("web_session.cookie_name", "sub2api_session")
+	viper.SetDefault("web_session.cookie_domain", "")
+	viper.SetDefault("web_session.secure", true)
+	viper.SetDefault("web_session.same_site", "strict")
+	viper.SetDefault("web_session.idle_timeout_minutes", 120)  // 2小时无操作过期
+	viper.SetDefault("web_session.absolute_timeout_hours", 168) // 最长7天
 
 	// Default
 	// Admin credentials are created via the setup flow (web wizard / CLI / AUTO_SETUP).
@@ -1152,13 +2412,16 @@ func setDefaults() {
 	viper.SetDefault("gateway.log_upstream_error_body", true)
 	viper.SetDefault("gateway.log_upstream_error_body_max_bytes", 2048)
 	viper.SetDefault("gateway.inject_beta_for_apikey", false)
+	viper.SetDefault("gateway.count_tokens_mode", "auto")
+	viper.SetDefault("gateway.request_validation", "lenient")
+	viper.SetDefault("gateway.default_max_tokens", 4096)
+	viper.SetDefault("gateway.completions_max_choices", 8)
 	viper.SetDefault("gateway.failover_on_400", false)
 	viper.SetDefault("gateway.max_account_switches", 10)
 	viper.SetDefault("gateway.max_account_switches_gemini", 3)
 	viper.SetDefault("gateway.force_codex_cli", false)
 	viper.SetDefault("gateway.openai_passthrough_allow_timeout_headers", false)
 	viper.SetDefault("gateway.antigravity_fallback_cooldown_minutes", 1)
-	viper.SetDefault("gateway.antigravity_extra_retries", 10)
 	viper.SetDefault("gateway.max_body_size", int64(100*1024*1024))
 	viper.SetDefault("gateway.upstream_response_read_max_bytes", int64(8*1024*1024))
 	viper.SetDefault("gateway.proxy_probe_response_read_max_bytes", int64(1024*1024))
@@ -1178,15 +2441,30 @@ func setDefaults() {
 	viper.SetDefault("gateway.idle_conn_timeout_seconds", 90) // 空闲连接超时（秒）
 	viper.SetDefault("gateway.max_upstream_clients", 5000)
 	viper.SetDefault("gateway.client_idle_ttl_seconds", 900)
+	viper.SetDefault("gateway.response_compression.enabled", false)
+	viper.SetDefault("gateway.response_compression.algorithms", []string{"zstd", "br", "gzip"})
+	viper.SetDefault("gateway.response_compression.min_size_bytes", 1024)
+	viper.SetDefault("gateway.response_compression.sse", false)
+	viper.SetDefault("gateway.http_client.http2", true)
+	viper.SetDefault("gateway.http_client.keep_alive_seconds", 30)
+	viper.SetDefault("gateway.http_client.disable_keep_alives", false)
+	viper.SetDefault("gateway.http_client.happy_eyeballs", true)
+	viper.SetDefault("gateway.http_client.fallback_delay_ms", 300)
+	viper.SetDefault("gateway.http_client.dns_cache_ttl_seconds", 60)
 	viper.SetDefault("gateway.concurrency_slot_ttl_minutes", 30) // 并发槽位过期时间（支持超长请求）
 	viper.SetDefault("gateway.stream_data_interval_timeout", 180)
 	viper.SetDefault("gateway.stream_keepalive_interval", 10)
+	viper.SetDefault("gateway.cancel_upstream_on_client_disconnect", true)
+	viper.SetDefault("gateway.upstream_timeouts.connect_seconds", 30)
+	viper.SetDefault("gateway.upstream_timeouts.first_token_seconds", 0)
+	viper.SetDefault("gateway.upstream_timeouts.total_seconds", 0)
 	viper.SetDefault("gateway.max_line_size", 40*1024*1024)
 	viper.SetDefault("gateway.scheduling.sticky_session_max_waiting", 3)
 	viper.SetDefault("gateway.scheduling.sticky_session_wait_timeout", 120*time.Second)
 	viper.SetDefault("gateway.scheduling.fallback_wait_timeout", 30*time.Second)
 	viper.SetDefault("gateway.scheduling.fallback_max_waiting", 100)
 	viper.SetDefault("gateway.scheduling.fallback_selection_mode", "last_used")
+	viper.SetDefault("gateway.scheduling.selection_strategy", "load")
 	viper.SetDefault("gateway.scheduling.load_batch_enabled", true)
 	viper.SetDefault("gateway.scheduling.slot_cleanup_interval", 30*time.Second)
 	viper.SetDefault("gateway.scheduling.db_fallback_enabled", true)
@@ -1217,6 +2495,8 @@ func setDefaults() {
 	// TLS指纹伪装配置（默认关闭，需要账号级别单独启用）
 	viper.SetDefault("gateway.tls_fingerprint.enabled", true)
 	viper.SetDefault("concurrency.ping_interval", 10)
+	viper.SetDefault("concurrency.fair_share", true)
+	viper.SetDefault("concurrency.admission_max_wait", 30)
 
 	// Sora 直连配置
 	viper.SetDefault("sora.client.base_url", "https://sora.chatgpt.com/backend")
@@ -1257,6 +2537,67 @@ func setDefaults() {
 	viper.SetDefault("token_refresh.max_retries", 3)                   // 最多重试3次
 	viper.SetDefault("token_refresh.retry_backoff_seconds", 2)         // 重试退避基础2秒
 	viper.SetDefault("token_refresh.sync_linked_sora_accounts", false) // 默认不跨平台覆盖 Sora token
+	viper.SetDefault("token_refresh.session_hook.enabled", false)
+	viper.SetDefault("token_refresh.session_hook.url", "")
+	viper.SetDefault("token_refresh.session_hook.auth_token", "")
+	viper.SetDefault("token_refresh.session_hook.timeout_seconds", 120)
+	viper.SetDefault("token_refresh.session_hook.interval_hours", 12)
+	viper.SetDefault("token_refresh.session_hook.account_types", []string{"web-session"})
+
+	// Challenge hook
+	viper.SetDefault("challenge_hook.enabled", false)
+	viper.SetDefault("challenge_hook.url", "")
+	viper.SetDefault("challenge_hook.auth_token", "")
+	viper.SetDefault("challenge_hook.timeout_seconds", 30)
+	viper.SetDefault("challenge_hook.hold_minutes", 30)
+	viper.SetDefault("challenge_hook.callback_base_url", "")
+
+	// Semantic cache
+	viper.SetDefault("semantic_cache.enabled", false)
+	viper.SetDefault("semantic_cache.embedding_url", "")
+	viper.SetDefault("semantic_cache.embedding_api_key", "")
+	viper.SetDefault("semantic_cache.embedding_model", "text-embedding-3-small")
+	viper.SetDefault("semantic_cache.timeout_seconds", 5)
+	viper.SetDefault("semantic_cache.similarity_threshold", 0.95)
+	viper.SetDefault("semantic_cache.ttl_hours", 24)
+	viper.SetDefault("semantic_cache.max_prompt_chars", 8000)
+	viper.SetDefault("semantic_cache.max_response_bytes", 256*1024)
+
+	// Conversation persistence
+	viper.SetDefault("conversation.enabled", false)
+	viper.SetDefault("conversation.max_messages", 200)
+	viper.SetDefault("conversation.max_context_bytes", 2*1024*1024)
+	viper.SetDefault("conversation.max_response_bytes", 2*1024*1024)
+	viper.SetDefault("conversation.ttl_days", 30)
+
+	// Attachments
+	viper.SetDefault("attachments.enabled", false)
+	viper.SetDefault("attachments.max_file_bytes", 32*1024*1024)
+	viper.SetDefault("attachments.max_request_bytes", 64*1024*1024)
+	viper.SetDefault("attachments.ttl_hours", 168)
+	viper.SetDefault("attachments.remote_images.enabled", false)
+	viper.SetDefault("attachments.remote_images.allow_insecure_http", false)
+	viper.SetDefault("attachments.remote_images.allowed_hosts", []string{})
+	viper.SetDefault("attachments.remote_images.max_bytes", 10*1024*1024)
+	viper.SetDefault("attachments.remote_images.max_images_per_request", 20)
+	viper.SetDefault("attachments.remote_images.timeout_seconds", 10)
+	viper.SetDefault("attachments.remote_images.cache_ttl_seconds", 300)
+	viper.SetDefault("attachments.preprocess.enabled", false)
+	viper.SetDefault("attachments.preprocess.native_pdf_models", []string{})
+	viper.SetDefault("attachments.preprocess.extract_images", true)
+	viper.SetDefault("attachments.preprocess.max_images", 10)
+	viper.SetDefault("attachments.preprocess.max_pages", 200)
+	viper.SetDefault("attachments.preprocess.chunk_tokens", 2000)
+	viper.SetDefault("attachments.preprocess.context_ratio", 0.5)
+	viper.SetDefault("attachments.preprocess.max_text_tokens", 100000)
+
+	// Prompt templates
+	viper.SetDefault("prompt_templates.enabled", false)
+	viper.SetDefault("prompt_templates.max_templates_per_user", 100)
+	viper.SetDefault("prompt_templates.max_template_bytes", 64*1024)
+
+	// Fault injection (chaos testing only)
+	viper.SetDefault("fault_injection.enabled", false)
 
 	// Gemini OAuth - configure via environment variables or config file
 	// GEMINI_OAUTH_CLIENT_ID and GEMINI_OAUTH_CLIENT_SECRET
@@ -1390,9 +2731,350 @@ func (c *Config) Validate() error {
 	if c.JWT.RefreshWindowMinutes < 0 {
 		return fmt.Errorf("jwt.refresh_window_minutes must be non-negative")
 	}
+	if c.APIKeyRotation.MaxGraceHours < 0 {
+		return fmt.Errorf("api_key_rotation.max_grace_hours must be non-negative")
+	}
+	if c.APIKeyRotation.DefaultGraceHours < 0 || c.APIKeyRotation.DefaultGraceHours > c.APIKeyRotation.MaxGraceHours {
+		return fmt.Errorf("api_key_rotation.default_grace_hours must be between 0 and api_key_rotation.max_grace_hours")
+	}
+	if c.Trash.RetentionDays < 0 {
+		return fmt.Errorf("trash.retention_days must be non-negative")
+	}
+	if c.Trash.PurgeIntervalMinutes <= 0 {
+		return fmt.Errorf("trash.purge_interval_minutes must be positive")
+	}
+	if c.RequestLog.RetentionDays < 1 || c.RequestLog.RetentionDays > 30 {
+		return fmt.Errorf("request_log.retention_days must be between 1 and 30")
+	}
+	if c.RequestLog.QueueSize <= 0 {
+		return fmt.Errorf("request_log.queue_size must be positive")
+	}
+	if c.RequestLog.BatchSize <= 0 {
+		return fmt.Errorf("request_log.batch_size must be positive")
+	}
+	switch c.Worker.Mode {
+	case WorkerModeEmbedded, WorkerModeAPI, WorkerModeWorker:
+	default:
+		return fmt.Errorf("worker.mode must be one of: embedded/api/worker")
+	}
+	if c.Worker.LeaderElection.Enabled && c.Worker.LeaderElection.LeaseSeconds < 3 {
+		return fmt.Errorf("worker.leader_election.lease_seconds must be at least 3")
+	}
+	if c.Worker.Mode == WorkerModeAPI && !c.JobQueue.Enabled {
+		// API 副本依赖任务队列把邮件等异步任务交给 worker
+		return fmt.Errorf("job_queue.enabled must be true when worker.mode=api")
+	}
+	if c.CronJobs.JitterSeconds < 0 {
+		return fmt.Errorf("cron_jobs.jitter_seconds must be non-negative")
+	}
+	for name, task := range c.CronJobs.Tasks {
+		if strings.TrimSpace(task.Schedule) != "" {
+			if _, err := CronJobScheduleParser.Parse(strings.TrimSpace(task.Schedule)); err != nil {
+				return fmt.Errorf("cron_jobs.tasks.%s.schedule is invalid: %w", name, err)
+			}
+		}
+		if task.JitterSeconds != nil && *task.JitterSeconds < 0 {
+			return fmt.Errorf("cron_jobs.tasks.%s.jitter_seconds must be non-negative", name)
+		}
+	}
+	if c.UsageExport.MaxRangeDays <= 0 {
+		return fmt.Errorf("usage_export.max_range_days must be positive")
+	}
+	if c.UsageExport.BatchSize < 100 || c.UsageExport.BatchSize > 50000 {
+		return fmt.Errorf("usage_export.batch_size must be between 100 and 50000")
+	}
+	if c.Storage.Enabled {
+		if strings.TrimSpace(c.Storage.Bucket) == "" {
+			return fmt.Errorf("storage.bucket is required when storage.enabled=true")
+		}
+		if endpoint := strings.TrimSpace(c.Storage.Endpoint); endpoint != "" {
+			if u, err := url.Parse(endpoint); err != nil || u.Host == "" || (u.Scheme != "http" && u.Scheme != "https") {
+				return fmt.Errorf("storage.endpoint must be an absolute http(s) URL")
+			}
+		}
+		if (c.Storage.AccessKeyID == "") != (c.Storage.SecretAccessKey == "") {
+			return fmt.Errorf("storage.access_key_id and storage.secret_access_key must be set together")
+		}
+	}
+	if c.JobQueue.Enabled {
+		if c.JobQueue.BlockSeconds <= 0 {
+			return fmt.Errorf("job_queue.block_seconds must be positive")
+		}
+		if c.JobQueue.ClaimIdleSeconds <= c.JobQueue.BlockSeconds {
+			return fmt.Errorf("job_queue.claim_idle_seconds must be greater than job_queue.block_seconds")
+		}
+		if c.JobQueue.MaxAttempts <= 0 {
+			return fmt.Errorf("job_queue.max_attempts must be positive")
+		}
+		if c.JobQueue.MaxLen < 0 {
+			return fmt.Errorf("job_queue.max_len must be non-negative")
+		}
+	}
+	if tee := c.AnalyticsTee; tee.Enabled {
+		if !c.JobQueue.Enabled {
+			// 投递与重试依赖任务队列，避免在请求路径上直接调用外部系统
+			return fmt.Errorf("job_queue.enabled must be true when analytics_tee.enabled=true")
+		}
+		if u, err := url.Parse(strings.TrimSpace(tee.URL)); err != nil || u.Host == "" || (u.Scheme != "http" && u.Scheme != "https") {
+			return fmt.Errorf("analytics_tee.url must be an absolute http(s) URL")
+		}
+		if tee.TimeoutSeconds <= 0 || tee.BatchSize <= 0 || tee.FlushIntervalMs <= 0 || tee.BufferSize <= 0 || tee.Workers <= 0 {
+			return fmt.Errorf("analytics_tee.timeout_seconds, batch_size, flush_interval_ms, buffer_size and workers must be positive")
+		}
+		if tee.SampleRate < 0 || tee.SampleRate > 1 {
+			return fmt.Errorf("analytics_tee.sample_rate must be within [0, 1]")
+		}
+	}
+	if ex := c.EventExport; ex.Enabled {
+		switch ex.Driver {
+		case "kafka":
+			if u, err := url.Parse(strings.TrimSpace(ex.Kafka.RESTURL)); err != nil || u.Host == "" || (u.Scheme != "http" && u.Scheme != "https") {
+				return fmt.Errorf("event_export.kafka.rest_url must be an absolute http(s) URL")
+			}
+		case "nats":
+			if u, err := url.Parse(strings.TrimSpace(ex.NATS.URL)); err != nil || u.Host == "" || u.Scheme != "nats" {
+				return fmt.Errorf("event_export.nats.url must be a nats://host:port URL")
+			}
+		default:
+			return fmt.Errorf("event_export.driver must be one of: kafka, nats")
+		}
+		if ex.BatchSize <= 0 || ex.BatchSize > 1000 {
+			return fmt.Errorf("event_export.batch_size must be between 1 and 1000")
+		}
+		if ex.PollIntervalMs <= 0 || ex.TimeoutSeconds <= 0 || ex.RetentionHours <= 0 {
+			return fmt.Errorf("event_export.poll_interval_ms, timeout_seconds and retention_hours must be positive")
+		}
+	}
+	if ch := c.ClickHouse; ch.Enabled {
+		if u, err := url.Parse(strings.TrimSpace(ch.URL)); err != nil || u.Host == "" || (u.Scheme != "http" && u.Scheme != "https") {
+			return fmt.Errorf("clickhouse.url must be an absolute http(s) URL")
+		}
+		if !isValidClickHouseIdentifier(ch.Database) {
+			return fmt.Errorf("clickhouse.database must contain only letters, digits and underscores")
+		}
+		if ch.BatchSize <= 0 || ch.FlushIntervalMs <= 0 || ch.BufferSize <= 0 || ch.TimeoutSeconds <= 0 {
+			return fmt.Errorf("clickhouse.batch_size, flush_interval_ms, buffer_size and timeout_seconds must be positive")
+		}
+		if ch.MaxPendingRows < ch.BatchSize {
+			return fmt.Errorf("clickhouse.max_pending_rows must be at least clickhouse.batch_size")
+		}
+	}
+	if docs := c.APIDocs; docs.Enabled {
+		if u, err := url.Parse(strings.TrimSpace(docs.SwaggerUIURL)); err != nil || u.Host == "" || (u.Scheme != "http" && u.Scheme != "https") {
+			return fmt.Errorf("api_docs.swagger_ui_url must be an absolute http(s) URL")
+		}
+	}
+	if c.GRPC.Enabled {
+		if c.GRPC.Port <= 0 || c.GRPC.Port > 65535 {
+			return fmt.Errorf("grpc.port must be between 1 and 65535")
+		}
+		if c.GRPC.Port == c.Server.Port {
+			return fmt.Errorf("grpc.port must differ from server.port")
+		}
+	}
+	if c.Totp.RequireForAdmins && !c.Totp.EncryptionKeyConfigured {
+		// 随机生成的密钥在重启后会变化，导致管理员被锁在 2FA 之外
+		return fmt.Errorf("totp.encryption_key is required when totp.require_for_admins=true")
+	}
+	if c.AdminUsers.PasswordMinLength < 8 || c.AdminUsers.PasswordMinLength > 72 {
+		// bcrypt 只使用前 72 字节
+		return fmt.Errorf("admin_users.password_min_length must be between 8 and 72")
+	}
+	if c.AdminUsers.PasswordMaxAgeDays < 0 {
+		return fmt.Errorf("admin_users.password_max_age_days must be non-negative")
+	}
+	if c.Notifications.KeyExpiryWarnDays < 0 || c.Notifications.KeyExpiryWarnDays > 90 {
+		return fmt.Errorf("notifications.key_expiry_warn_days must be between 0 and 90")
+	}
+	if c.Notifications.LogRetentionDays <= 0 {
+		return fmt.Errorf("notifications.log_retention_days must be positive")
+	}
+	if tg := c.Notifications.Telegram; tg.Enabled {
+		if strings.TrimSpace(tg.BotToken) == "" {
+			return fmt.Errorf("notifications.telegram.bot_token is required when notifications.telegram.enabled=true")
+		}
+		if len(tg.ChatIDs) == 0 {
+			return fmt.Errorf("notifications.telegram.chat_ids must not be empty when notifications.telegram.enabled=true")
+		}
+		if tg.AlertCooldownMinutes <= 0 {
+			return fmt.Errorf("notifications.telegram.alert_cooldown_minutes must be positive")
+		}
+		// Telegram 长轮询上限为 50 秒
+		if tg.PollTimeoutSeconds <= 0 || tg.PollTimeoutSeconds > 50 {
+			return fmt.Errorf("notifications.telegram.poll_timeout_seconds must be between 1 and 50")
+		}
+	}
+	if hook := c.TokenRefresh.SessionHook; hook.Enabled {
+		if strings.TrimSpace(hook.URL) == "" {
+			return fmt.Errorf("token_refresh.session_hook.url is required when token_refresh.session_hook.enabled=true")
+		}
+		if hook.TimeoutSeconds <= 0 {
+			return fmt.Errorf("token_refresh.session_hook.timeout_seconds must be positive")
+		}
+		if hook.IntervalHours < 0 {
+			return fmt.Errorf("token_refresh.session_hook.interval_hours must be non-negative")
+		}
+	}
+	if hook := c.ChallengeHook; hook.Enabled {
+		if strings.TrimSpace(hook.URL) == "" {
+			return fmt.Errorf("challenge_hook.url is required when challenge_hook.enabled=true")
+		}
+		if hook.TimeoutSeconds <= 0 {
+			return fmt.Errorf("challenge_hook.timeout_seconds must be positive")
+		}
+		if hook.HoldMinutes <= 0 {
+			return fmt.Errorf("challenge_hook.hold_minutes must be positive")
+		}
+	}
+	if sc := c.SemanticCache; sc.Enabled {
+		if strings.TrimSpace(sc.EmbeddingURL) == "" || strings.TrimSpace(sc.EmbeddingModel) == "" {
+			return fmt.Errorf("semantic_cache.embedding_url and semantic_cache.embedding_model are required when semantic_cache.enabled=true")
+		}
+		if sc.SimilarityThreshold <= 0 || sc.SimilarityThreshold > 1 {
+			return fmt.Errorf("semantic_cache.similarity_threshold must be within (0, 1]")
+		}
+		if sc.TimeoutSeconds <= 0 || sc.TTLHours <= 0 || sc.MaxPromptChars <= 0 || sc.MaxResponseBytes <= 0 {
+			return fmt.Errorf("semantic_cache.timeout_seconds, ttl_hours, max_prompt_chars and max_response_bytes must be positive")
+		}
+	}
+	if conv := c.Conversation; conv.Enabled {
+		if conv.MaxMessages <= 0 || conv.MaxContextBytes <= 0 || conv.MaxResponseBytes <= 0 || conv.TTLDays <= 0 {
+			return fmt.Errorf("conversation.max_messages, max_context_bytes, max_response_bytes and ttl_days must be positive")
+		}
+	}
+	if att := c.Attachments; att.Enabled {
+		if att.MaxFileBytes <= 0 || att.MaxRequestBytes <= 0 || att.TTLHours <= 0 {
+			return fmt.Errorf("attachments.max_file_bytes, max_request_bytes and ttl_hours must be positive")
+		}
+		for i, rule := range att.Rules {
+			if len(rule.MediaTypes) == 0 {
+				return fmt.Errorf("attachments.rules[%d].media_types is required", i)
+			}
+			if rule.MaxFileBytes < 0 {
+				return fmt.Errorf("attachments.rules[%d].max_file_bytes must be non-negative", i)
+			}
+		}
+		if ri := att.RemoteImages; ri.Enabled {
+			if ri.MaxBytes <= 0 || ri.MaxImagesPerRequest <= 0 || ri.TimeoutSeconds <= 0 {
+				return fmt.Errorf("attachments.remote_images.max_bytes, max_images_per_request and timeout_seconds must be positive")
+			}
+			if ri.CacheTTLSeconds < 0 {
+				return fmt.Errorf("attachments.remote_images.cache_ttl_seconds must be non-negative")
+			}
+		}
+		if pp := att.Preprocess; pp.Enabled {
+			if pp.MaxPages <= 0 || pp.ChunkTokens <= 0 || pp.MaxTextTokens <= 0 {
+				return fmt.Errorf("attachments.preprocess.max_pages, chunk_tokens and max_text_tokens must be positive")
+			}
+			if pp.MaxImages < 0 {
+				return fmt.Errorf("attachments.preprocess.max_images must be non-negative")
+			}
+			if pp.ContextRatio <= 0 || pp.ContextRatio > 1 {
+				return fmt.Errorf("attachments.preprocess.context_ratio must be in (0, 1]")
+			}
+		}
+	}
+	if name := c.LogMasking.DefaultProfile; name != "" {
+		if _, ok := c.LogMasking.Profiles[name]; !ok {
+			return fmt.Errorf("log_masking.default_profile %q is not defined in log_masking.profiles", name)
+		}
+	}
+	for name, profile := range c.LogMasking.Profiles {
+		for _, idType := range profile.NationalIDs {
+			switch idType {
+			case LogMaskCNID, LogMaskUSSSN, LogMaskUKNINO:
+			default:
+				return fmt.Errorf("log_masking.profiles.%s.national_ids contains unknown type %q (supported: cn_id/us_ssn/uk_nino)", name, idType)
+			}
+		}
+		for rule, pattern := range profile.Patterns {
+			if _, err := regexp.Compile(pattern); err != nil {
+				return fmt.Errorf("log_masking.profiles.%s.patterns.%s is not a valid regex: %w", name, rule, err)
+			}
+		}
+	}
+	for name, provider := range c.Moderation.Providers {
+		switch provider.Type {
+		case ModerationProviderLocal:
+			for category, pattern := range provider.Patterns {
+				if _, err := regexp.Compile(pattern); err != nil {
+					return fmt.Errorf("moderation.providers.%s.patterns.%s is not a valid regex: %w", name, category, err)
+				}
+			}
+		case ModerationProviderOpenAI, ModerationProviderHTTP:
+			if strings.TrimSpace(provider.URL) == "" {
+				return fmt.Errorf("moderation.providers.%s.url is required for type %s", name, provider.Type)
+			}
+		default:
+			return fmt.Errorf("moderation.providers.%s.type must be one of local/openai/http", name)
+		}
+		if provider.TimeoutMS < 0 {
+			return fmt.Errorf("moderation.providers.%s.timeout_ms must be non-negative", name)
+		}
+	}
+	if pt := c.PromptTemplates; pt.Enabled {
+		if pt.MaxTemplatesPerUser <= 0 || pt.MaxTemplateBytes <= 0 {
+			return fmt.Errorf("prompt_templates.max_templates_per_user and max_template_bytes must be positive")
+		}
+	}
+	if fi := c.FaultInjection; fi.Enabled {
+		for i, rule := range fi.Rules {
+			if rule.Rate <= 0 || rule.Rate > 1 {
+				return fmt.Errorf("fault_injection.rules[%d].rate must be in (0, 1]", i)
+			}
+			if rule.LatencyMS < 0 || rule.LatencyJitterMS < 0 || rule.AfterBytes < 0 || rule.RetryAfterSeconds < 0 {
+				return fmt.Errorf("fault_injection.rules[%d]: latency_ms, latency_jitter_ms, after_bytes and retry_after_seconds must be non-negative", i)
+			}
+			switch rule.Fault {
+			case FaultLatency:
+				if rule.LatencyMS+rule.LatencyJitterMS <= 0 {
+					return fmt.Errorf("fault_injection.rules[%d].latency_ms is required for latency faults", i)
+				}
+			case FaultError:
+				if rule.StatusCode < 400 || rule.StatusCode > 599 {
+					return fmt.Errorf("fault_injection.rules[%d].status_code must be between 400 and 599", i)
+				}
+			case FaultConnectionError, FaultDropStream, FaultMalformedChunk:
+			default:
+				return fmt.Errorf("fault_injection.rules[%d].fault must be one of: latency/error/connection_error/drop_stream/malformed_chunk", i)
+			}
+		}
+	}
+	if c.WebSession.Enabled {
+		if c.WebSession.CookieName == "" {
+			return fmt.Errorf("web_session.cookie_name is required when web_session.enabled=true")
+		}
+		switch c.WebSession.SameSite {
+		case "strict", "lax":
+		case "none":
+			return fmt.Errorf("web_session.same_site=none is not allowed: cross-site requests would carry the session cookie")
+		default:
+			return fmt.Errorf("web_session.same_site must be one of: strict/lax")
+		}
+		if c.WebSession.IdleTimeoutMinutes <= 0 {
+			return fmt.Errorf("web_session.idle_timeout_minutes must be positive")
+		}
+		if c.WebSession.AbsoluteTimeoutHours <= 0 {
+			return fmt.Errorf("web_session.absolute_timeout_hours must be positive")
+		}
+		if c.WebSession.IdleTimeoutMinutes > c.WebSession.AbsoluteTimeoutHours*60 {
+			return fmt.Errorf("web_session.idle_timeout_minutes must be <= web_session.absolute_timeout_hours*60")
+		}
+	}
 	if c.Security.CSP.Enabled && strings.TrimSpace(c.Security.CSP.Policy) == "" {
 		return fmt.Errorf("security.csp.policy is required when CSP is enabled")
 	}
+	switch strings.ToLower(strings.TrimSpace(c.Secrets.Provider)) {
+	case "", "vault", "file", "exec":
+	default:
+		return fmt.Errorf("secrets.provider must be one of vault/file/exec")
+	}
+	if c.Security.CredentialEncryption.Enabled && strings.TrimSpace(c.Security.CredentialEncryption.MasterKey) == "" {
+		return fmt.Errorf("security.credential_encryption.master_key is required when credential encryption is enabled")
+	}
+	if _, err := c.Security.CredentialEncryption.Keys(); err != nil {
+		return err
+	}
 	if c.LinuxDo.Enabled {
 		if strings.TrimSpace(c.LinuxDo.ClientID) == "" {
 			return fmt.Errorf("linuxdo_connect.client_id is required when linuxdo_connect.enabled=true")
@@ -1459,6 +3141,37 @@ func (c *Config) Validate() error {
 			return fmt.Errorf("billing.circuit_breaker.half_open_requests must be positive")
 		}
 	}
+	if c.Billing.PrepaidHold.Enabled {
+		if c.Billing.PrepaidHold.DefaultOutputTokens <= 0 {
+			return fmt.Errorf("billing.prepaid_hold.default_output_tokens must be positive")
+		}
+		if c.Billing.PrepaidHold.HoldTTLSeconds <= 0 {
+			return fmt.Errorf("billing.prepaid_hold.hold_ttl_seconds must be positive")
+		}
+	}
+	if c.Billing.EstimatedUsageMargin < -0.5 || c.Billing.EstimatedUsageMargin > 1 {
+		return fmt.Errorf("billing.estimated_usage_margin must be between -0.5 and 1")
+	}
+	if stripe := c.Payments.Stripe; stripe.Enabled {
+		if strings.TrimSpace(stripe.SecretKey) == "" || strings.TrimSpace(stripe.WebhookSecret) == "" {
+			return fmt.Errorf("payments.stripe.secret_key and payments.stripe.webhook_secret are required when payments.stripe.enabled=true")
+		}
+		if strings.TrimSpace(stripe.SuccessURL) == "" || strings.TrimSpace(stripe.CancelURL) == "" {
+			return fmt.Errorf("payments.stripe.success_url and payments.stripe.cancel_url are required when payments.stripe.enabled=true")
+		}
+		if len(strings.TrimSpace(stripe.Currency)) != 3 {
+			return fmt.Errorf("payments.stripe.currency must be a 3-letter ISO currency code")
+		}
+		if stripe.CreditsPerUnit <= 0 {
+			return fmt.Errorf("payments.stripe.credits_per_unit must be positive")
+		}
+		if stripe.MinAmount <= 0 || stripe.MaxAmount < stripe.MinAmount {
+			return fmt.Errorf("payments.stripe.min_amount must be positive and not exceed payments.stripe.max_amount")
+		}
+		if stripe.WebhookToleranceSeconds <= 0 {
+			return fmt.Errorf("payments.stripe.webhook_tolerance_seconds must be positive")
+		}
+	}
 	if c.Database.MaxOpenConns <= 0 {
 		return fmt.Errorf("database.max_open_conns must be positive")
 	}
@@ -1474,6 +3187,17 @@ func (c *Config) Validate() error {
 	if c.Database.ConnMaxIdleTimeMinutes < 0 {
 		return fmt.Errorf("database.conn_max_idle_time_minutes must be non-negative")
 	}
+	if len(c.Database.Replicas) > 0 && c.Database.ReplicaMaxLagSeconds <= 0 {
+		return fmt.Errorf("database.replica_max_lag_seconds must be positive when database.replicas is set")
+	}
+	for i, replica := range c.Database.Replicas {
+		if strings.TrimSpace(replica.Host) == "" {
+			return fmt.Errorf("database.replicas[%d].host is required", i)
+		}
+		if replica.Port < 0 {
+			return fmt.Errorf("database.replicas[%d].port must be non-negative", i)
+		}
+	}
 	if c.Redis.DialTimeoutSeconds <= 0 {
 		return fmt.Errorf("redis.dial_timeout_seconds must be positive")
 	}
@@ -1615,6 +3339,14 @@ func (c *Config) Validate() error {
 	if c.Gateway.MaxBodySize <= 0 {
 		return fmt.Errorf("gateway.max_body_size must be positive")
 	}
+	for i, rule := range c.Gateway.BodyLimits {
+		if !strings.HasPrefix(rule.Path, "/") {
+			return fmt.Errorf("gateway.body_limits[%d].path must start with /", i)
+		}
+		if rule.MaxBytes <= 0 {
+			return fmt.Errorf("gateway.body_limits[%d].max_bytes must be positive", i)
+		}
+	}
 	if c.Gateway.UpstreamResponseReadMaxBytes <= 0 {
 		return fmt.Errorf("gateway.upstream_response_read_max_bytes must be positive")
 	}
@@ -1633,6 +3365,45 @@ func (c *Config) Validate() error {
 	if c.Gateway.SoraMediaSignedURLTTLSeconds < 0 {
 		return fmt.Errorf("gateway.sora_media_signed_url_ttl_seconds must be non-negative")
 	}
+	if c.Gateway.DefaultMaxTokens < 0 {
+		return fmt.Errorf("gateway.default_max_tokens must be non-negative")
+	}
+	if c.Gateway.CompletionsMaxChoices < 1 {
+		return fmt.Errorf("gateway.completions_max_choices must be positive")
+	}
+	for source, reasons := range c.Gateway.FinishReasonOverrides {
+		switch strings.ToLower(strings.TrimSpace(source)) {
+		case "anthropic", "gemini", "openai":
+		default:
+			return fmt.Errorf("gateway.finish_reason_overrides: unknown source %q (anthropic/gemini/openai)", source)
+		}
+		for reason, finish := range reasons {
+			switch strings.ToLower(strings.TrimSpace(finish)) {
+			case "stop", "length", "tool_calls", "content_filter":
+			default:
+				return fmt.Errorf("gateway.finish_reason_overrides.%s.%s must be one of stop/length/tool_calls/content_filter", source, reason)
+			}
+		}
+	}
+	for _, entry := range c.Gateway.UpstreamHeaderAllowlist {
+		if !upstreamHeaderAllowlistPattern.MatchString(strings.ToLower(strings.TrimSpace(entry))) {
+			return fmt.Errorf("gateway.upstream_header_allowlist: invalid header name %q (letters, digits and '-', optional trailing '*')", entry)
+		}
+	}
+	if mode := strings.TrimSpace(strings.ToLower(c.Gateway.CountTokensMode)); mode != "" {
+		switch mode {
+		case "auto", "local", "upstream":
+		default:
+			return fmt.Errorf("gateway.count_tokens_mode must be one of: auto/local/upstream")
+		}
+	}
+	if mode := strings.TrimSpace(strings.ToLower(c.Gateway.RequestValidation)); mode != "" {
+		switch mode {
+		case "off", "lenient", "strict":
+		default:
+			return fmt.Errorf("gateway.request_validation must be one of: off/lenient/strict")
+		}
+	}
 	if mode := strings.TrimSpace(strings.ToLower(c.Gateway.SoraStreamMode)); mode != "" {
 		switch mode {
 		case "force", "error":
@@ -1698,8 +3469,14 @@ func (c *Config) Validate() error {
 			return fmt.Errorf("sora.storage.cleanup.retention_days must be non-negative")
 		}
 	}
-	if storageType := strings.TrimSpace(strings.ToLower(c.Sora.Storage.Type)); storageType != "" && storageType != "local" {
-		return fmt.Errorf("sora.storage.type must be 'local'")
+	switch storageType := strings.TrimSpace(strings.ToLower(c.Sora.Storage.Type)); storageType {
+	case "", "local":
+	case "s3":
+		if !c.Storage.Enabled {
+			return fmt.Errorf("storage.enabled must be true when sora.storage.type=s3")
+		}
+	default:
+		return fmt.Errorf("sora.storage.type must be 'local' or 's3'")
 	}
 	if strings.TrimSpace(c.Gateway.ConnectionPoolIsolation) != "" {
 		switch c.Gateway.ConnectionPoolIsolation {
@@ -1730,6 +3507,36 @@ func (c *Config) Validate() error {
 	if c.Gateway.ClientIdleTTLSeconds <= 0 {
 		return fmt.Errorf("gateway.client_idle_ttl_seconds must be positive")
 	}
+	if hc := c.Gateway.HTTPClient; hc.KeepAliveSeconds < -1 || hc.FallbackDelayMs < 0 || hc.DNSCacheTTLSeconds < 0 {
+		return fmt.Errorf("gateway.http_client.keep_alive_seconds must be >= -1, fallback_delay_ms and dns_cache_ttl_seconds must be non-negative")
+	}
+	if c.Gateway.ResponseCompression.MinSizeBytes < 0 {
+		return fmt.Errorf("gateway.response_compression.min_size_bytes must be non-negative")
+	}
+	for _, algorithm := range c.Gateway.ResponseCompression.Algorithms {
+		switch strings.ToLower(strings.TrimSpace(algorithm)) {
+		case "gzip", "br", "zstd":
+		default:
+			return fmt.Errorf("gateway.response_compression.algorithms contains unsupported algorithm %q (gzip/br/zstd)", algorithm)
+		}
+	}
+	for i, override := range c.Gateway.HTTPClient.DNSOverrides {
+		if strings.TrimSpace(override.Host) == "" || net.ParseIP(strings.TrimSpace(override.Address)) == nil {
+			return fmt.Errorf("gateway.http_client.dns_overrides[%d] requires host and an IP address", i)
+		}
+	}
+	for name, adapter := range c.Gateway.HTTPClient.Adapters {
+		if adapter.MaxIdleConnsPerHost < 0 || adapter.MaxConnsPerHost < 0 || adapter.IdleConnTimeoutSeconds < 0 {
+			return fmt.Errorf("gateway.http_client.adapters.%s values must be non-negative", name)
+		}
+	}
+	for name, profile := range c.Gateway.TLSFingerprint.Profiles {
+		switch profile.ClientHello {
+		case "", "chrome", "firefox", "safari", "edge", "ios":
+		default:
+			return fmt.Errorf("gateway.tls_fingerprint.profiles.%s.client_hello must be one of: chrome/firefox/safari/edge/ios", name)
+		}
+	}
 	if c.Gateway.ConcurrencySlotTTLMinutes <= 0 {
 		return fmt.Errorf("gateway.concurrency_slot_ttl_minutes must be positive")
 	}
@@ -1747,6 +3554,9 @@ func (c *Config) Validate() error {
 		(c.Gateway.StreamKeepaliveInterval < 5 || c.Gateway.StreamKeepaliveInterval > 30) {
 		return fmt.Errorf("gateway.stream_keepalive_interval must be 0 or between 5-30 seconds")
 	}
+	if err := c.Gateway.UpstreamTimeouts.validate(); err != nil {
+		return err
+	}
 	if c.Gateway.MaxLineSize < 0 {
 		return fmt.Errorf("gateway.max_line_size must be non-negative")
 	}
@@ -1829,6 +3639,11 @@ func (c *Config) Validate() error {
 	if c.Gateway.Scheduling.FallbackMaxWaiting <= 0 {
 		return fmt.Errorf("gateway.scheduling.fallback_max_waiting must be positive")
 	}
+	switch c.Gateway.Scheduling.SelectionStrategy {
+	case "", "load", "latency":
+	default:
+		return fmt.Errorf("gateway.scheduling.selection_strategy must be one of: load/latency")
+	}
 	if c.Gateway.Scheduling.SlotCleanupInterval < 0 {
 		return fmt.Errorf("gateway.scheduling.slot_cleanup_interval must be non-negative")
 	}
@@ -1879,6 +3694,9 @@ func (c *Config) Validate() error {
 	if c.Concurrency.PingInterval < 5 || c.Concurrency.PingInterval > 30 {
 		return fmt.Errorf("concurrency.ping_interval must be between 5-30 seconds")
 	}
+	if c.Concurrency.AdmissionMaxWait < 0 {
+		return fmt.Errorf("concurrency.admission_max_wait must be non-negative")
+	}
 	return nil
 }
 
@@ -1897,6 +3715,22 @@ func normalizeStringSlice(values []string) []string {
 	return normalized
 }
 
+// normalizeFinishReasonOverrides 将 gateway.finish_reason_overrides 的来源、停止原因与 finish_reason 统一为小写
+func normalizeFinishReasonOverrides(overrides map[string]map[string]string) map[string]map[string]string {
+	if len(overrides) == 0 {
+		return overrides
+	}
+	normalized := make(map[string]map[string]string, len(overrides))
+	for source, reasons := range overrides {
+		table := make(map[string]string, len(reasons))
+		for reason, finish := range reasons {
+			table[strings.ToLower(strings.TrimSpace(reason))] = strings.ToLower(strings.TrimSpace(finish))
+		}
+		normalized[strings.ToLower(strings.TrimSpace(source))] = table
+	}
+	return normalized
+}
+
 func isWeakJWTSecret(secret string) bool {
 	lower := strings.ToLower(strings.TrimSpace(secret))
 	if lower == "" {
@@ -2017,6 +3851,19 @@ func isHTTPScheme(scheme string) bool {
 	return strings.EqualFold(scheme, "http") || strings.EqualFold(scheme, "https")
 }
 
+// isValidClickHouseIdentifier 数据库名会直接拼入 DDL，仅允许字母、数字与下划线
+func isValidClickHouseIdentifier(name string) bool {
+	if name == "" || len(name) > 64 {
+		return false
+	}
+	for _, r := range name {
+		if (r < 'a' || r > 'z') && (r < 'A' || r > 'Z') && (r < '0' || r > '9') && r != '_' {
+			return false
+		}
+	}
+	return true
+}
+
 func warnIfInsecureURL(field, raw string) {
 	u, err := url.Parse(strings.TrimSpace(raw))
 	if err != nil {
@@ -2026,3 +3873,23 @@ func warnIfInsecureURL(field, raw string) {
 		slog.Warn("url uses http scheme; use https in production to avoid token leakage", "field", field)
 	}
 }
+
+func (c GatewayUpstreamTimeoutsConfig) validate() error {
+	if c.ConnectSeconds < 0 || c.FirstTokenSeconds < 0 || c.TotalSeconds < 0 {
+		return fmt.Errorf("gateway.upstream_timeouts timeouts must be non-negative")
+	}
+	for i, rule := range c.Rules {
+		if rule.ConnectSeconds < 0 || rule.FirstTokenSeconds < 0 || rule.TotalSeconds < 0 {
+			return fmt.Errorf("gateway.upstream_timeouts.rules[%d] timeouts must be non-negative", i)
+		}
+		if strings.TrimSpace(rule.Platform) == "" && len(rule.Models) == 0 {
+			return fmt.Errorf("gateway.upstream_timeouts.rules[%d] must set platform or models", i)
+		}
+		for _, model := range rule.Models {
+			if strings.TrimSpace(model) == "" {
+				return fmt.Errorf("gateway.upstream_timeouts.rules[%d].models must not contain empty entries", i)
+			}
+		}
+	}
+	return nil
+}
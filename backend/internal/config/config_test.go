@@ -1,6 +1,9 @@
 package config
 
 import (
+	"os"
+	"path/filepath"
+	"reflect"
 	"strings"
 	"testing"
 	"time"
@@ -147,6 +150,22 @@ func TestLoadDefaultSecurityToggles(t *testing.T) {
 	}
 }
 
+func TestLoadDefaultWebSessionDisabled(t *testing.T) {
+	resetViperWithJWTSecret(t)
+
+	cfg, err := Load()
+	if err != nil {
+		t.Fatalf("Load() error: %v", err)
+	}
+
+	if cfg.WebSession.Enabled {
+		t.Fatalf("WebSession.Enabled = true, want false")
+	}
+	if cfg.WebSession.SameSite != "strict" {
+		t.Fatalf("WebSession.SameSite = %q, want %q", cfg.WebSession.SameSite, "strict")
+	}
+}
+
 func TestLoadDefaultServerMode(t *testing.T) {
 	resetViperWithJWTSecret(t)
 
@@ -993,6 +1012,18 @@ func TestValidateConfigErrors(t *testing.T) {
 			mutate:  func(c *Config) { c.Gateway.StreamKeepaliveInterval = 4 },
 			wantErr: "gateway.stream_keepalive_interval",
 		},
+		{
+			name:    "gateway upstream timeouts negative",
+			mutate:  func(c *Config) { c.Gateway.UpstreamTimeouts.FirstTokenSeconds = -1 },
+			wantErr: "gateway.upstream_timeouts",
+		},
+		{
+			name: "gateway upstream timeout rule without matcher",
+			mutate: func(c *Config) {
+				c.Gateway.UpstreamTimeouts.Rules = []GatewayUpstreamTimeoutRule{{TotalSeconds: 60}}
+			},
+			wantErr: "gateway.upstream_timeouts.rules[0]",
+		},
 		{
 			name:    "gateway stream data interval range",
 			mutate:  func(c *Config) { c.Gateway.StreamDataIntervalTimeout = 5 },
@@ -1160,6 +1191,42 @@ func TestValidateConfigErrors(t *testing.T) {
 			mutate:  func(c *Config) { c.Ops.Cleanup.MinuteMetricsRetentionDays = -1 },
 			wantErr: "ops.cleanup.minute_metrics_retention_days",
 		},
+		{
+			name:    "request log retention",
+			mutate:  func(c *Config) { c.RequestLog.RetentionDays = 31 },
+			wantErr: "request_log.retention_days",
+		},
+		{
+			name:    "request log batch size",
+			mutate:  func(c *Config) { c.RequestLog.BatchSize = 0 },
+			wantErr: "request_log.batch_size",
+		},
+		{
+			name:    "credential encryption without master key",
+			mutate:  func(c *Config) { c.Security.CredentialEncryption.Enabled = true },
+			wantErr: "security.credential_encryption.master_key",
+		},
+		{
+			name: "credential encryption previous key",
+			mutate: func(c *Config) {
+				c.Security.CredentialEncryption.MasterKey = strings.Repeat("ab", 32)
+				c.Security.CredentialEncryption.PreviousKeys = map[string]string{"k0": "not-hex"}
+			},
+			wantErr: "security.credential_encryption.previous_keys.k0",
+		},
+		{
+			name:    "secrets provider",
+			mutate:  func(c *Config) { c.Secrets.Provider = "keychain" },
+			wantErr: "secrets.provider",
+		},
+		{
+			name: "web session same_site none rejected",
+			mutate: func(c *Config) {
+				c.WebSession.Enabled = true
+				c.WebSession.SameSite = "none"
+			},
+			wantErr: "web_session.same_site=none is not allowed",
+		},
 	}
 
 	for _, tt := range cases {
@@ -1404,3 +1471,448 @@ func TestLoad_DefaultGatewayUsageRecordConfig(t *testing.T) {
 		t.Fatalf("auto_scale_cooldown_seconds = %d, want 10", cfg.Gateway.UsageRecord.AutoScaleCooldownSeconds)
 	}
 }
+
+func TestLoad_WorkerModeFromEnv(t *testing.T) {
+	resetViperWithJWTSecret(t)
+	t.Setenv("WORKER_MODE", " Worker ")
+
+	cfg, err := Load()
+	if err != nil {
+		t.Fatalf("Load() error: %v", err)
+	}
+	if cfg.Worker.Mode != WorkerModeWorker {
+		t.Fatalf("worker.mode = %q, want %q", cfg.Worker.Mode, WorkerModeWorker)
+	}
+	if cfg.Worker.ServesHTTP() {
+		t.Fatalf("worker mode should not serve HTTP")
+	}
+	if !cfg.Worker.RunsBackgroundJobs() {
+		t.Fatalf("worker mode should run background jobs")
+	}
+}
+
+func TestLoadDefaultJobQueueDisabled(t *testing.T) {
+	resetViperWithJWTSecret(t)
+
+	cfg, err := Load()
+	if err != nil {
+		t.Fatalf("Load() error: %v", err)
+	}
+	if cfg.JobQueue.Enabled {
+		t.Fatalf("JobQueue.Enabled = true, want false by default")
+	}
+}
+
+func TestValidateWorkerModeAPIRequiresJobQueue(t *testing.T) {
+	resetViperWithJWTSecret(t)
+
+	cfg, err := Load()
+	if err != nil {
+		t.Fatalf("Load() error: %v", err)
+	}
+
+	cfg.Worker.Mode = WorkerModeAPI
+	cfg.JobQueue.Enabled = false
+	err = cfg.Validate()
+	if err == nil || !strings.Contains(err.Error(), "job_queue.enabled must be true when worker.mode=api") {
+		t.Fatalf("Validate() error = %v, want job_queue enabled error", err)
+	}
+
+	cfg.Worker.Mode = "sidecar"
+	err = cfg.Validate()
+	if err == nil || !strings.Contains(err.Error(), "worker.mode must be one of") {
+		t.Fatalf("Validate() error = %v, want worker.mode error", err)
+	}
+}
+
+func TestValidateObjectStorage(t *testing.T) {
+	resetViperWithJWTSecret(t)
+
+	cfg, err := Load()
+	if err != nil {
+		t.Fatalf("Load() error: %v", err)
+	}
+	if cfg.Storage.Enabled || cfg.UsageExport.StoragePrefix != "usage-exports/" {
+		t.Fatalf("unexpected storage defaults: %+v / %q", cfg.Storage, cfg.UsageExport.StoragePrefix)
+	}
+
+	cfg.Sora.Storage.Type = "s3"
+	err = cfg.Validate()
+	if err == nil || !strings.Contains(err.Error(), "storage.enabled must be true when sora.storage.type=s3") {
+		t.Fatalf("Validate() error = %v, want storage.enabled error", err)
+	}
+
+	cfg.Storage.Enabled = true
+	err = cfg.Validate()
+	if err == nil || !strings.Contains(err.Error(), "storage.bucket is required") {
+		t.Fatalf("Validate() error = %v, want storage.bucket error", err)
+	}
+
+	cfg.Storage.Bucket = "sub2api"
+	cfg.Storage.Endpoint = "minio:9000"
+	err = cfg.Validate()
+	if err == nil || !strings.Contains(err.Error(), "storage.endpoint must be an absolute http(s) URL") {
+		t.Fatalf("Validate() error = %v, want storage.endpoint error", err)
+	}
+
+	cfg.Storage.Endpoint = "http://minio:9000"
+	if err := cfg.Validate(); err != nil {
+		t.Fatalf("Validate() error: %v", err)
+	}
+}
+
+func TestValidateStripePayments(t *testing.T) {
+	resetViperWithJWTSecret(t)
+
+	cfg, err := Load()
+	if err != nil {
+		t.Fatalf("Load() error: %v", err)
+	}
+	if cfg.Payments.Stripe.Enabled || cfg.Payments.Stripe.Currency != "usd" || cfg.Payments.Stripe.CreditsPerUnit != 1 {
+		t.Fatalf("unexpected stripe defaults: %+v", cfg.Payments.Stripe)
+	}
+
+	cfg.Payments.Stripe.Enabled = true
+	err = cfg.Validate()
+	if err == nil || !strings.Contains(err.Error(), "payments.stripe.secret_key and payments.stripe.webhook_secret are required") {
+		t.Fatalf("Validate() error = %v, want stripe secret error", err)
+	}
+
+	cfg.Payments.Stripe.SecretKey = "sk_test_123"
+	cfg.Payments.Stripe.WebhookSecret = "whsec_123"
+	err = cfg.Validate()
+	if err == nil || !strings.Contains(err.Error(), "payments.stripe.success_url and payments.stripe.cancel_url are required") {
+		t.Fatalf("Validate() error = %v, want stripe redirect url error", err)
+	}
+
+	cfg.Payments.Stripe.SuccessURL = "https://example.com/billing?session={CHECKOUT_SESSION_ID}"
+	cfg.Payments.Stripe.CancelURL = "https://example.com/billing"
+	cfg.Payments.Stripe.MaxAmount = 1
+	err = cfg.Validate()
+	if err == nil || !strings.Contains(err.Error(), "payments.stripe.min_amount") {
+		t.Fatalf("Validate() error = %v, want stripe amount range error", err)
+	}
+
+	cfg.Payments.Stripe.MaxAmount = 1000
+	if err := cfg.Validate(); err != nil {
+		t.Fatalf("Validate() error: %v", err)
+	}
+}
+
+func TestValidateAdminUsersPasswordPolicy(t *testing.T) {
+	resetViperWithJWTSecret(t)
+
+	cfg, err := Load()
+	if err != nil {
+		t.Fatalf("Load() error: %v", err)
+	}
+	if cfg.AdminUsers.PasswordMinLength != 12 || cfg.AdminUsers.PasswordMaxAgeDays != 90 || !cfg.AdminUsers.PasswordRequireDigit {
+		t.Fatalf("unexpected admin_users defaults: %+v", cfg.AdminUsers)
+	}
+
+	cfg.AdminUsers.PasswordMinLength = 6
+	err = cfg.Validate()
+	if err == nil || !strings.Contains(err.Error(), "admin_users.password_min_length") {
+		t.Fatalf("Validate() error = %v, want password_min_length error", err)
+	}
+
+	cfg.AdminUsers.PasswordMinLength = 12
+	cfg.AdminUsers.PasswordMaxAgeDays = -1
+	err = cfg.Validate()
+	if err == nil || !strings.Contains(err.Error(), "admin_users.password_max_age_days") {
+		t.Fatalf("Validate() error = %v, want password_max_age_days error", err)
+	}
+}
+
+func TestValidateNotificationsConfig(t *testing.T) {
+	resetViperWithJWTSecret(t)
+
+	cfg, err := Load()
+	if err != nil {
+		t.Fatalf("Load() error: %v", err)
+	}
+	if cfg.Notifications.KeyExpiryWarnDays != 7 || cfg.Notifications.LogRetentionDays != 90 {
+		t.Fatalf("unexpected notifications defaults: %+v", cfg.Notifications)
+	}
+
+	cfg.Notifications.KeyExpiryWarnDays = -1
+	err = cfg.Validate()
+	if err == nil || !strings.Contains(err.Error(), "notifications.key_expiry_warn_days") {
+		t.Fatalf("Validate() error = %v, want key_expiry_warn_days error", err)
+	}
+
+	cfg.Notifications.KeyExpiryWarnDays = 0
+	cfg.Notifications.LogRetentionDays = 0
+	err = cfg.Validate()
+	if err == nil || !strings.Contains(err.Error(), "notifications.log_retention_days") {
+		t.Fatalf("Validate() error = %v, want log_retention_days error", err)
+	}
+}
+
+func TestValidateTelegramConfig(t *testing.T) {
+	resetViperWithJWTSecret(t)
+
+	cfg, err := Load()
+	if err != nil {
+		t.Fatalf("Load() error: %v", err)
+	}
+	tg := cfg.Notifications.Telegram
+	if tg.Enabled || tg.AlertCooldownMinutes != 30 || tg.PollTimeoutSeconds != 25 || tg.APIBase != "https://api.telegram.org" {
+		t.Fatalf("unexpected telegram defaults: %+v", tg)
+	}
+
+	cfg.Notifications.Telegram.Enabled = true
+	err = cfg.Validate()
+	if err == nil || !strings.Contains(err.Error(), "notifications.telegram.bot_token") {
+		t.Fatalf("Validate() error = %v, want bot_token error", err)
+	}
+
+	cfg.Notifications.Telegram.BotToken = "123:abc"
+	err = cfg.Validate()
+	if err == nil || !strings.Contains(err.Error(), "notifications.telegram.chat_ids") {
+		t.Fatalf("Validate() error = %v, want chat_ids error", err)
+	}
+
+	cfg.Notifications.Telegram.ChatIDs = []int64{-1001234567890}
+	cfg.Notifications.Telegram.PollTimeoutSeconds = 60
+	err = cfg.Validate()
+	if err == nil || !strings.Contains(err.Error(), "notifications.telegram.poll_timeout_seconds") {
+		t.Fatalf("Validate() error = %v, want poll_timeout_seconds error", err)
+	}
+
+	cfg.Notifications.Telegram.PollTimeoutSeconds = 25
+	if err := cfg.Validate(); err != nil {
+		t.Fatalf("Validate() error: %v", err)
+	}
+}
+
+func TestResolveSecretRefsFromFileProvider(t *testing.T) {
+	dir := t.TempDir()
+	if err := os.MkdirAll(filepath.Join(dir, "sub2api"), 0o755); err != nil {
+		t.Fatal(err)
+	}
+	if err := os.WriteFile(filepath.Join(dir, "sub2api", "db"), []byte(`{"password":"db-pass"}`), 0o600); err != nil {
+		t.Fatal(err)
+	}
+	if err := os.WriteFile(filepath.Join(dir, "sub2api", "redis"), []byte("redis-pass\n"), 0o600); err != nil {
+		t.Fatal(err)
+	}
+
+	cfg := &Config{}
+	cfg.Secrets.Provider = "file"
+	cfg.Secrets.File.Dir = dir
+	cfg.Database.Password = "secret://sub2api/db#password"
+	cfg.Redis.Password = "secret://sub2api/redis"
+	cfg.JWT.Secret = "plain-value"
+	if err := resolveSecretRefs(cfg); err != nil {
+		t.Fatalf("resolveSecretRefs() error: %v", err)
+	}
+	if cfg.Database.Password != "db-pass" || cfg.Redis.Password != "redis-pass" || cfg.JWT.Secret != "plain-value" {
+		t.Fatalf("unexpected resolved values: db=%q redis=%q jwt=%q", cfg.Database.Password, cfg.Redis.Password, cfg.JWT.Secret)
+	}
+
+	missing := &Config{}
+	missing.Database.Password = "secret://sub2api/db#password"
+	if err := resolveSecretRefs(missing); err == nil || !strings.Contains(err.Error(), "secrets.provider") {
+		t.Fatalf("expected error without provider, got %v", err)
+	}
+}
+
+func TestLoadCommandLineOverridesTakePrecedence(t *testing.T) {
+	resetViperWithJWTSecret(t)
+	t.Setenv("SERVER_PORT", "7000")
+	t.Cleanup(func() { _ = SetCommandLineOverrides("", nil) })
+
+	if err := SetCommandLineOverrides("", []string{" Server.Port =9191"}); err != nil {
+		t.Fatalf("SetCommandLineOverrides() error: %v", err)
+	}
+	cfg, err := Load()
+	if err != nil {
+		t.Fatalf("Load() error: %v", err)
+	}
+	if cfg.Server.Port != 9191 {
+		t.Fatalf("Server.Port = %d, want 9191 (command line > env)", cfg.Server.Port)
+	}
+
+	resetViperWithJWTSecret(t)
+	if err := SetCommandLineOverrides("", []string{"server.prot=1"}); err != nil {
+		t.Fatalf("SetCommandLineOverrides() error: %v", err)
+	}
+	_, err = Load()
+	if err == nil || !strings.Contains(err.Error(), `did you mean "server.port"`) {
+		t.Fatalf("Load() error = %v, want suggestion for server.port", err)
+	}
+
+	if err := SetCommandLineOverrides("", []string{"novalue"}); err == nil {
+		t.Fatalf("SetCommandLineOverrides() should reject entries without '='")
+	}
+}
+
+func TestLoadExplicitConfigFileAndUnknownKeys(t *testing.T) {
+	resetViperWithJWTSecret(t)
+	t.Cleanup(func() { _ = SetCommandLineOverrides("", nil) })
+
+	path := filepath.Join(t.TempDir(), "custom.yaml")
+	content := "server:\n  port: 8181\n  prot: 1\nsecurity:\n  credential_encryption:\n    previous_keys:\n      k0: \"\"\n"
+	if err := os.WriteFile(path, []byte(content), 0o600); err != nil {
+		t.Fatal(err)
+	}
+	if err := SetCommandLineOverrides(path, nil); err != nil {
+		t.Fatal(err)
+	}
+	cfg, report, err := Check()
+	if err != nil {
+		t.Fatalf("Check() error: %v", err)
+	}
+	if cfg.Server.Port != 8181 || report.ConfigFile != path {
+		t.Fatalf("unexpected result: port=%d file=%q", cfg.Server.Port, report.ConfigFile)
+	}
+	if len(report.UnknownKeys) != 1 || report.UnknownKeys[0] != "server.prot" {
+		t.Fatalf("UnknownKeys = %v, want [server.prot]", report.UnknownKeys)
+	}
+
+	resetViperWithJWTSecret(t)
+	if err := SetCommandLineOverrides(filepath.Join(t.TempDir(), "missing.yaml"), nil); err != nil {
+		t.Fatal(err)
+	}
+	if _, err := Load(); err == nil {
+		t.Fatalf("Load() should fail when the explicit config file is missing")
+	}
+}
+
+func TestValidateFinishReasonOverrides(t *testing.T) {
+	resetViperWithJWTSecret(t)
+
+	cfg, err := Load()
+	if err != nil {
+		t.Fatalf("Load() error: %v", err)
+	}
+
+	cfg.Gateway.FinishReasonOverrides = map[string]map[string]string{"gemini": {"recitation": "stop"}}
+	if err := cfg.Validate(); err != nil {
+		t.Fatalf("Validate() unexpected error: %v", err)
+	}
+
+	cfg.Gateway.FinishReasonOverrides = map[string]map[string]string{"gemini": {"recitation": "blocked"}}
+	err = cfg.Validate()
+	if err == nil || !strings.Contains(err.Error(), "gateway.finish_reason_overrides.gemini.recitation") {
+		t.Fatalf("Validate() expected finish_reason_overrides value error, got: %v", err)
+	}
+
+	cfg.Gateway.FinishReasonOverrides = map[string]map[string]string{"mistral": {"error": "stop"}}
+	err = cfg.Validate()
+	if err == nil || !strings.Contains(err.Error(), "unknown source") {
+		t.Fatalf("Validate() expected finish_reason_overrides source error, got: %v", err)
+	}
+}
+
+func TestNormalizeFinishReasonOverrides(t *testing.T) {
+	got := normalizeFinishReasonOverrides(map[string]map[string]string{
+		" Gemini ": {"RECITATION": " Stop "},
+		"openai":   {"Error": "LENGTH"},
+	})
+	want := map[string]map[string]string{
+		"gemini": {"recitation": "stop"},
+		"openai": {"error": "length"},
+	}
+	if !reflect.DeepEqual(got, want) {
+		t.Fatalf("normalizeFinishReasonOverrides() = %v, want %v", got, want)
+	}
+	if normalizeFinishReasonOverrides(nil) != nil {
+		t.Fatalf("normalizeFinishReasonOverrides(nil) should stay nil")
+	}
+}
+
+func TestValidateUpstreamHeaderAllowlist(t *testing.T) {
+	resetViperWithJWTSecret(t)
+
+	cfg, err := Load()
+	if err != nil {
+		t.Fatalf("Load() error: %v", err)
+	}
+
+	cfg.Gateway.UpstreamHeaderAllowlist = []string{"X-Reseller-*", "x-tenant-id"}
+	if err := cfg.Validate(); err != nil {
+		t.Fatalf("Validate() unexpected error: %v", err)
+	}
+
+	for _, entry := range []string{"*", "x tenant", "x-*-id", "x_tenant"} {
+		cfg.Gateway.UpstreamHeaderAllowlist = []string{entry}
+		err = cfg.Validate()
+		if err == nil || !strings.Contains(err.Error(), "gateway.upstream_header_allowlist") {
+			t.Fatalf("Validate() expected upstream_header_allowlist error for %q, got: %v", entry, err)
+		}
+	}
+}
+
+func TestValidateModerationProviders(t *testing.T) {
+	resetViperWithJWTSecret(t)
+
+	cfg, err := Load()
+	if err != nil {
+		t.Fatalf("Load() error: %v", err)
+	}
+
+	cfg.Moderation.Providers = map[string]ModerationProviderConfig{
+		"rules":  {Type: ModerationProviderLocal, Patterns: map[string]string{"secrets": `sk-[A-Za-z0-9]{20,}`}},
+		"openai": {Type: ModerationProviderOpenAI, URL: "https://api.openai.com/v1/moderations", TimeoutMS: 800},
+	}
+	if err := cfg.Validate(); err != nil {
+		t.Fatalf("Validate() unexpected error: %v", err)
+	}
+
+	cfg.Moderation.Providers = map[string]ModerationProviderConfig{"ext": {Type: ModerationProviderHTTP}}
+	err = cfg.Validate()
+	if err == nil || !strings.Contains(err.Error(), "moderation.providers.ext.url") {
+		t.Fatalf("Validate() expected moderation url error, got: %v", err)
+	}
+
+	cfg.Moderation.Providers = map[string]ModerationProviderConfig{"rules": {Type: ModerationProviderLocal, Patterns: map[string]string{"bad": "(unclosed"}}}
+	err = cfg.Validate()
+	if err == nil || !strings.Contains(err.Error(), "moderation.providers.rules.patterns.bad") {
+		t.Fatalf("Validate() expected moderation pattern error, got: %v", err)
+	}
+
+	cfg.Moderation.Providers = map[string]ModerationProviderConfig{"x": {Type: "azure"}}
+	err = cfg.Validate()
+	if err == nil || !strings.Contains(err.Error(), "moderation.providers.x.type") {
+		t.Fatalf("Validate() expected moderation type error, got: %v", err)
+	}
+}
+
+func TestValidateLogMasking(t *testing.T) {
+	resetViperWithJWTSecret(t)
+
+	cfg, err := Load()
+	if err != nil {
+		t.Fatalf("Load() error: %v", err)
+	}
+
+	cfg.LogMasking = LogMaskingConfig{
+		DefaultProfile: "pii",
+		Profiles: map[string]LogMaskingProfileConfig{
+			"pii": {CreditCards: true, NationalIDs: []string{LogMaskCNID, LogMaskUSSSN}, Patterns: map[string]string{"email": `\S+@\S+`}},
+		},
+	}
+	if err := cfg.Validate(); err != nil {
+		t.Fatalf("Validate() unexpected error: %v", err)
+	}
+
+	cfg.LogMasking.DefaultProfile = "strict"
+	err = cfg.Validate()
+	if err == nil || !strings.Contains(err.Error(), "log_masking.default_profile") {
+		t.Fatalf("Validate() expected default_profile error, got: %v", err)
+	}
+
+	cfg.LogMasking = LogMaskingConfig{Profiles: map[string]LogMaskingProfileConfig{"pii": {NationalIDs: []string{"passport"}}}}
+	err = cfg.Validate()
+	if err == nil || !strings.Contains(err.Error(), "log_masking.profiles.pii.national_ids") {
+		t.Fatalf("Validate() expected national_ids error, got: %v", err)
+	}
+
+	cfg.LogMasking = LogMaskingConfig{Profiles: map[string]LogMaskingProfileConfig{"pii": {Patterns: map[string]string{"bad": "(unclosed"}}}}
+	err = cfg.Validate()
+	if err == nil || !strings.Contains(err.Error(), "log_masking.profiles.pii.patterns.bad") {
+		t.Fatalf("Validate() expected pattern error, got: %v", err)
+	}
+}
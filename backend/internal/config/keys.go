@@ -0,0 +1,111 @@
+package config
+
+import (
+	"fmt"
+	"reflect"
+	"sort"
+	"strings"
+	"sync"
+)
+
+var (
+	configKeysOnce sync.Once
+	configKeys     []string
+	// configMapKeys 值为 map 的配置项，其下任意子键均合法（例如 security.credential_encryption.previous_keys）
+	configMapKeys []string
+)
+
+// configKeyPaths 通过 mapstructure 标签枚举所有配置项的点分路径
+func configKeyPaths() ([]string, []string) {
+	configKeysOnce.Do(func() {
+		walkConfigKeys(reflect.TypeOf(Config{}), "")
+		sort.Strings(configKeys)
+		sort.Strings(configMapKeys)
+	})
+	return configKeys, configMapKeys
+}
+
+func walkConfigKeys(t reflect.Type, prefix string) {
+	for i := 0; i < t.NumField(); i++ {
+		f := t.Field(i)
+		tag := strings.Split(f.Tag.Get("mapstructure"), ",")[0]
+		if tag == "" || tag == "-" || !f.IsExported() {
+			continue
+		}
+		key := prefix + tag
+		ft := f.Type
+		for ft.Kind() == reflect.Pointer {
+			ft = ft.Elem()
+		}
+		switch ft.Kind() {
+		case reflect.Struct:
+			walkConfigKeys(ft, key+".")
+		case reflect.Map:
+			configMapKeys = append(configMapKeys, key)
+		default:
+			configKeys = append(configKeys, key)
+		}
+	}
+}
+
+func isKnownConfigKey(key string) bool {
+	keys, mapKeys := configKeyPaths()
+	if i := sort.SearchStrings(keys, key); i < len(keys) && keys[i] == key {
+		return true
+	}
+	for _, m := range mapKeys {
+		if key == m || strings.HasPrefix(key, m+".") {
+			return true
+		}
+	}
+	return false
+}
+
+// unknownConfigKeys 过滤 mapstructure 未使用的键，map 类型配置项的子键不算未知
+func unknownConfigKeys(unused []string) []string {
+	out := make([]string, 0, len(unused))
+	for _, key := range unused {
+		key = strings.ToLower(key)
+		if !isKnownConfigKey(key) {
+			out = append(out, key)
+		}
+	}
+	sort.Strings(out)
+	return out
+}
+
+// suggestConfigKey 给出最接近的合法配置项，用于可操作的错误提示
+func suggestConfigKey(key string) string {
+	keys, _ := configKeyPaths()
+	best, bestDist := "", -1
+	for _, candidate := range keys {
+		d := levenshtein(key, candidate)
+		if bestDist < 0 || d < bestDist {
+			best, bestDist = candidate, d
+		}
+	}
+	if best != "" && bestDist <= max(2, len(key)/4) {
+		return fmt.Sprintf("did you mean %q?", best)
+	}
+	return "see deploy/config.example.yaml for available keys"
+}
+
+func levenshtein(a, b string) int {
+	prev := make([]int, len(b)+1)
+	cur := make([]int, len(b)+1)
+	for j := range prev {
+		prev[j] = j
+	}
+	for i := 1; i <= len(a); i++ {
+		cur[0] = i
+		for j := 1; j <= len(b); j++ {
+			cost := 1
+			if a[i-1] == b[j-1] {
+				cost = 0
+			}
+			cur[j] = min(prev[j]+1, cur[j-1]+1, prev[j-1]+cost)
+		}
+		prev, cur = cur, prev
+	}
+	return prev[len(b)]
+}
@@ -14,6 +14,11 @@ const (
 const (
 	RoleAdmin = "admin"
 	RoleUser  = "user"
+
+	// 后台细分角色
+	RoleBilling  = "billing"  // 财务：余额、卡密、订阅、支付与账单
+	RoleAccounts = "accounts" // 账号运维：上游账号、分组与代理
+	RoleAuditor  = "auditor"  // 只读审计
 )
 
 // Platform constants
@@ -31,6 +36,8 @@ const (
 	AccountTypeSetupToken = "setup-token" // Setup Token类型账号（inference only scope）
 	AccountTypeAPIKey     = "apikey"      // API Key类型账号
 	AccountTypeUpstream   = "upstream"    // 上游透传类型账号（通过 Base URL + API Key 连接上游）
+	AccountTypeWebSession = "web-session" // 网页会话类型账号（通过网页登录 Cookie 驱动官方网页对话接口）
+	AccountTypeCopilot    = "copilot"     // GitHub Copilot 订阅账号（设备码登录，GitHub token 换取短期 Copilot token）
 )
 
 // Redeem type constants
@@ -0,0 +1,86 @@
+package admin
+
+import (
+	"strconv"
+
+	"github.com/Wei-Shaw/sub2api/internal/pkg/response"
+	"github.com/Wei-Shaw/sub2api/internal/service"
+	"github.com/gin-gonic/gin"
+)
+
+// AccountHealthHandler 处理账号延迟与成功率分析的 HTTP 请求
+type AccountHealthHandler struct {
+	service *service.AccountHealthService
+}
+
+// NewAccountHealthHandler 创建账号健康度分析处理器
+func NewAccountHealthHandler(service *service.AccountHealthService) *AccountHealthHandler {
+	return &AccountHealthHandler{service: service}
+}
+
+// List 获取所有账号在最近 hours 小时内的健康度汇总（成功率低的在前）
+// GET /api/v1/admin/account-health?hours=24
+func (h *AccountHealthHandler) List(c *gin.Context) {
+	hours, ok := parseAccountHealthHours(c)
+	if !ok {
+		return
+	}
+
+	summaries, err := h.service.ListSummaries(c.Request.Context(), hours)
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, summaries)
+}
+
+// GetByAccount 获取单个账号的小时级延迟分位数、成功率与吞吐时间序列
+// GET /api/v1/admin/account-health/:id?hours=24
+func (h *AccountHealthHandler) GetByAccount(c *gin.Context) {
+	accountID, err := strconv.ParseInt(c.Param("id"), 10, 64)
+	if err != nil || accountID <= 0 {
+		response.BadRequest(c, "Invalid account ID")
+		return
+	}
+	hours, ok := parseAccountHealthHours(c)
+	if !ok {
+		return
+	}
+
+	buckets, err := h.service.GetAccountSeries(c.Request.Context(), accountID, hours)
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, buckets)
+}
+
+// GetQuota 获取账号当前的配额预算：各窗口用量、预测剩余额度与预计耗尽时间
+// GET /api/v1/admin/account-health/:id/quota
+func (h *AccountHealthHandler) GetQuota(c *gin.Context) {
+	accountID, err := strconv.ParseInt(c.Param("id"), 10, 64)
+	if err != nil || accountID <= 0 {
+		response.BadRequest(c, "Invalid account ID")
+		return
+	}
+
+	status, err := h.service.GetQuotaStatus(c.Request.Context(), accountID)
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, status)
+}
+
+func parseAccountHealthHours(c *gin.Context) (int, bool) {
+	raw := c.Query("hours")
+	if raw == "" {
+		return 0, true
+	}
+	hours, err := strconv.Atoi(raw)
+	if err != nil {
+		response.BadRequest(c, "Invalid hours")
+		return 0, false
+	}
+	return hours, true
+}
@@ -0,0 +1,308 @@
+package admin
+
+import (
+	"context"
+	"strconv"
+
+	"github.com/Wei-Shaw/sub2api/internal/pkg/response"
+	"github.com/Wei-Shaw/sub2api/internal/service"
+	"github.com/gin-gonic/gin"
+)
+
+// AccountShardHandler 处理账号分片与分片路由规则的 HTTP 请求
+type AccountShardHandler struct {
+	service *service.AccountShardService
+}
+
+// NewAccountShardHandler 创建账号分片处理器
+func NewAccountShardHandler(service *service.AccountShardService) *AccountShardHandler {
+	return &AccountShardHandler{service: service}
+}
+
+// CreateAccountShardRequest 创建分片请求
+type CreateAccountShardRequest struct {
+	Name        string `json:"name" binding:"required"`
+	Description string `json:"description"`
+}
+
+// UpdateAccountShardRequest 更新分片请求（部分更新）
+type UpdateAccountShardRequest struct {
+	Name        *string `json:"name"`
+	Description *string `json:"description"`
+}
+
+// IsolateAccountShardRequest 隔离分片请求
+type IsolateAccountShardRequest struct {
+	Reason string `json:"reason"`
+}
+
+// AccountShardMembersRequest 分片成员变更请求
+type AccountShardMembersRequest struct {
+	AccountIDs []int64 `json:"account_ids" binding:"required"`
+}
+
+// AccountShardRuleRequest 创建 / 更新路由规则请求
+type AccountShardRuleRequest struct {
+	ShardID      int64  `json:"shard_id" binding:"required"`
+	Priority     int    `json:"priority"`
+	APIKeyID     *int64 `json:"api_key_id"`
+	GroupID      *int64 `json:"group_id"`
+	ModelPattern string `json:"model_pattern"`
+	Enabled      *bool  `json:"enabled"`
+}
+
+// List 获取所有分片
+// GET /api/v1/admin/account-shards
+func (h *AccountShardHandler) List(c *gin.Context) {
+	shards, err := h.service.ListShards(c.Request.Context())
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, shards)
+}
+
+// GetByID 根据 ID 获取分片
+// GET /api/v1/admin/account-shards/:id
+func (h *AccountShardHandler) GetByID(c *gin.Context) {
+	id, ok := parseAccountShardID(c, "Invalid shard ID")
+	if !ok {
+		return
+	}
+
+	shard, err := h.service.GetShard(c.Request.Context(), id)
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, shard)
+}
+
+// Create 创建分片
+// POST /api/v1/admin/account-shards
+func (h *AccountShardHandler) Create(c *gin.Context) {
+	var req CreateAccountShardRequest
+	if err := c.ShouldBindJSON(&req); err != nil {
+		response.BadRequest(c, "Invalid request: "+err.Error())
+		return
+	}
+
+	shard := &service.AccountShard{Name: req.Name, Description: req.Description}
+	if err := h.service.CreateShard(c.Request.Context(), shard); err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Created(c, shard)
+}
+
+// Update 更新分片名称与描述
+// PUT /api/v1/admin/account-shards/:id
+func (h *AccountShardHandler) Update(c *gin.Context) {
+	id, ok := parseAccountShardID(c, "Invalid shard ID")
+	if !ok {
+		return
+	}
+
+	var req UpdateAccountShardRequest
+	if err := c.ShouldBindJSON(&req); err != nil {
+		response.BadRequest(c, "Invalid request: "+err.Error())
+		return
+	}
+
+	shard, err := h.service.GetShard(c.Request.Context(), id)
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	if req.Name != nil {
+		shard.Name = *req.Name
+	}
+	if req.Description != nil {
+		shard.Description = *req.Description
+	}
+
+	if err := h.service.UpdateShard(c.Request.Context(), shard); err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, shard)
+}
+
+// Delete 删除分片（成员账号回到默认池，相关规则一并删除）
+// DELETE /api/v1/admin/account-shards/:id
+func (h *AccountShardHandler) Delete(c *gin.Context) {
+	id, ok := parseAccountShardID(c, "Invalid shard ID")
+	if !ok {
+		return
+	}
+
+	if err := h.service.DeleteShard(c.Request.Context(), id); err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, gin.H{"message": "Account shard deleted successfully"})
+}
+
+// Isolate 隔离分片，分片内账号立即停止参与调度
+// POST /api/v1/admin/account-shards/:id/isolate
+func (h *AccountShardHandler) Isolate(c *gin.Context) {
+	id, ok := parseAccountShardID(c, "Invalid shard ID")
+	if !ok {
+		return
+	}
+
+	var req IsolateAccountShardRequest
+	if c.Request.ContentLength > 0 {
+		if err := c.ShouldBindJSON(&req); err != nil {
+			response.BadRequest(c, "Invalid request: "+err.Error())
+			return
+		}
+	}
+
+	shard, err := h.service.SetIsolated(c.Request.Context(), id, true, req.Reason)
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, shard)
+}
+
+// Restore 解除分片隔离
+// POST /api/v1/admin/account-shards/:id/restore
+func (h *AccountShardHandler) Restore(c *gin.Context) {
+	id, ok := parseAccountShardID(c, "Invalid shard ID")
+	if !ok {
+		return
+	}
+
+	shard, err := h.service.SetIsolated(c.Request.Context(), id, false, "")
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, shard)
+}
+
+// AddMembers 将账号移入分片（已属于其他分片的账号会被移出原分片）
+// POST /api/v1/admin/account-shards/:id/accounts
+func (h *AccountShardHandler) AddMembers(c *gin.Context) {
+	h.changeMembers(c, h.service.AddMembers)
+}
+
+// RemoveMembers 将账号移出分片
+// DELETE /api/v1/admin/account-shards/:id/accounts
+func (h *AccountShardHandler) RemoveMembers(c *gin.Context) {
+	h.changeMembers(c, h.service.RemoveMembers)
+}
+
+func (h *AccountShardHandler) changeMembers(c *gin.Context, apply func(ctx context.Context, shardID int64, accountIDs []int64) (int64, error)) {
+	id, ok := parseAccountShardID(c, "Invalid shard ID")
+	if !ok {
+		return
+	}
+
+	var req AccountShardMembersRequest
+	if err := c.ShouldBindJSON(&req); err != nil {
+		response.BadRequest(c, "Invalid request: "+err.Error())
+		return
+	}
+
+	affected, err := apply(c.Request.Context(), id, req.AccountIDs)
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, gin.H{"affected": affected})
+}
+
+// ListRules 获取所有路由规则（按匹配顺序）
+// GET /api/v1/admin/account-shard-rules
+func (h *AccountShardHandler) ListRules(c *gin.Context) {
+	rules, err := h.service.ListRules(c.Request.Context())
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, rules)
+}
+
+// CreateRule 创建路由规则
+// POST /api/v1/admin/account-shard-rules
+func (h *AccountShardHandler) CreateRule(c *gin.Context) {
+	var req AccountShardRuleRequest
+	if err := c.ShouldBindJSON(&req); err != nil {
+		response.BadRequest(c, "Invalid request: "+err.Error())
+		return
+	}
+
+	rule := &service.AccountShardRule{}
+	applyAccountShardRuleRequest(rule, &req)
+	if err := h.service.CreateRule(c.Request.Context(), rule); err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Created(c, rule)
+}
+
+// UpdateRule 更新路由规则（整体替换）
+// PUT /api/v1/admin/account-shard-rules/:id
+func (h *AccountShardHandler) UpdateRule(c *gin.Context) {
+	id, ok := parseAccountShardID(c, "Invalid shard rule ID")
+	if !ok {
+		return
+	}
+
+	var req AccountShardRuleRequest
+	if err := c.ShouldBindJSON(&req); err != nil {
+		response.BadRequest(c, "Invalid request: "+err.Error())
+		return
+	}
+
+	rule, err := h.service.GetRule(c.Request.Context(), id)
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	applyAccountShardRuleRequest(rule, &req)
+	if err := h.service.UpdateRule(c.Request.Context(), rule); err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, rule)
+}
+
+// DeleteRule 删除路由规则
+// DELETE /api/v1/admin/account-shard-rules/:id
+func (h *AccountShardHandler) DeleteRule(c *gin.Context) {
+	id, ok := parseAccountShardID(c, "Invalid shard rule ID")
+	if !ok {
+		return
+	}
+
+	if err := h.service.DeleteRule(c.Request.Context(), id); err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, gin.H{"message": "Account shard rule deleted successfully"})
+}
+
+func applyAccountShardRuleRequest(rule *service.AccountShardRule, req *AccountShardRuleRequest) {
+	rule.ShardID = req.ShardID
+	rule.Priority = req.Priority
+	rule.APIKeyID = req.APIKeyID
+	rule.GroupID = req.GroupID
+	rule.ModelPattern = req.ModelPattern
+	rule.Enabled = true
+	if req.Enabled != nil {
+		rule.Enabled = *req.Enabled
+	}
+}
+
+func parseAccountShardID(c *gin.Context, message string) (int64, bool) {
+	id, err := strconv.ParseInt(c.Param("id"), 10, 64)
+	if err != nil || id <= 0 {
+		response.BadRequest(c, message)
+		return 0, false
+	}
+	return id, true
+}
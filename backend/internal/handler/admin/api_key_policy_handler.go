@@ -0,0 +1,93 @@
+package admin
+
+import (
+	"strconv"
+
+	"github.com/Wei-Shaw/sub2api/internal/pkg/response"
+	"github.com/Wei-Shaw/sub2api/internal/service"
+	"github.com/gin-gonic/gin"
+)
+
+// APIKeyPolicyHandler API Key / 分组级网关策略管理
+type APIKeyPolicyHandler struct {
+	policyService *service.APIKeyPolicyService
+}
+
+// NewAPIKeyPolicyHandler 创建 API Key 策略处理器
+func NewAPIKeyPolicyHandler(policyService *service.APIKeyPolicyService) *APIKeyPolicyHandler {
+	return &APIKeyPolicyHandler{policyService: policyService}
+}
+
+// Get 返回 API Key 的策略（未配置时返回默认值）
+// GET /api/v1/admin/api-keys/:id/policy
+func (h *APIKeyPolicyHandler) Get(c *gin.Context) {
+	apiKeyID, err := strconv.ParseInt(c.Param("id"), 10, 64)
+	if err != nil {
+		response.BadRequest(c, "Invalid API key ID")
+		return
+	}
+	policy, err := h.policyService.Get(c.Request.Context(), apiKeyID)
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, policy)
+}
+
+// Update 整体覆盖 API Key 的策略
+// PUT /api/v1/admin/api-keys/:id/policy
+func (h *APIKeyPolicyHandler) Update(c *gin.Context) {
+	apiKeyID, err := strconv.ParseInt(c.Param("id"), 10, 64)
+	if err != nil {
+		response.BadRequest(c, "Invalid API key ID")
+		return
+	}
+	var req service.APIKeyPolicy
+	if err := c.ShouldBindJSON(&req); err != nil {
+		response.BadRequest(c, "Invalid request: "+err.Error())
+		return
+	}
+	policy, err := h.policyService.Update(c.Request.Context(), apiKeyID, &req)
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, policy)
+}
+
+// GetGroup 返回分组策略（作为分组内 API Key 的默认策略）
+// GET /api/v1/admin/groups/:id/policy
+func (h *APIKeyPolicyHandler) GetGroup(c *gin.Context) {
+	groupID, err := strconv.ParseInt(c.Param("id"), 10, 64)
+	if err != nil {
+		response.BadRequest(c, "Invalid group ID")
+		return
+	}
+	policy, err := h.policyService.GetGroup(c.Request.Context(), groupID)
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, policy)
+}
+
+// UpdateGroup 整体覆盖分组策略
+// PUT /api/v1/admin/groups/:id/policy
+func (h *APIKeyPolicyHandler) UpdateGroup(c *gin.Context) {
+	groupID, err := strconv.ParseInt(c.Param("id"), 10, 64)
+	if err != nil {
+		response.BadRequest(c, "Invalid group ID")
+		return
+	}
+	var req service.APIKeyPolicy
+	if err := c.ShouldBindJSON(&req); err != nil {
+		response.BadRequest(c, "Invalid request: "+err.Error())
+		return
+	}
+	policy, err := h.policyService.UpdateGroup(c.Request.Context(), groupID, &req)
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, policy)
+}
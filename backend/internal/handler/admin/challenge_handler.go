@@ -0,0 +1,49 @@
+package admin
+
+import (
+	"strconv"
+
+	"github.com/Wei-Shaw/sub2api/internal/pkg/response"
+	"github.com/Wei-Shaw/sub2api/internal/service"
+
+	"github.com/gin-gonic/gin"
+)
+
+// ChallengeHandler handles results reported back by the upstream challenge hook
+type ChallengeHandler struct {
+	challengeHookService *service.ChallengeHookService
+}
+
+// NewChallengeHandler creates a new challenge handler
+func NewChallengeHandler(challengeHookService *service.ChallengeHookService) *ChallengeHandler {
+	return &ChallengeHandler{challengeHookService: challengeHookService}
+}
+
+// ResolveChallengeRequest represents the result of an asynchronous challenge
+type ResolveChallengeRequest struct {
+	ChallengeID string         `json:"challenge_id"`
+	Success     bool           `json:"success"`
+	Credentials map[string]any `json:"credentials"`
+	Message     string         `json:"message"`
+}
+
+// Resolve resumes the account on success, or marks it as errored on failure
+// POST /api/v1/admin/accounts/:id/challenge/resolve
+func (h *ChallengeHandler) Resolve(c *gin.Context) {
+	accountID, err := strconv.ParseInt(c.Param("id"), 10, 64)
+	if err != nil {
+		response.BadRequest(c, "Invalid account ID")
+		return
+	}
+	var req ResolveChallengeRequest
+	if err := c.ShouldBindJSON(&req); err != nil {
+		response.BadRequest(c, "Invalid request: "+err.Error())
+		return
+	}
+
+	if err := h.challengeHookService.Resolve(c.Request.Context(), accountID, req.ChallengeID, req.Success, req.Credentials, req.Message); err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, gin.H{"message": "Challenge resolved"})
+}
@@ -0,0 +1,81 @@
+package admin
+
+import (
+	"strconv"
+
+	"github.com/Wei-Shaw/sub2api/internal/pkg/response"
+	"github.com/Wei-Shaw/sub2api/internal/service"
+
+	"github.com/gin-gonic/gin"
+)
+
+// CopilotHandler handles GitHub Copilot device login and model listing
+type CopilotHandler struct {
+	copilotService *service.CopilotService
+}
+
+// NewCopilotHandler creates a new Copilot handler
+func NewCopilotHandler(copilotService *service.CopilotService) *CopilotHandler {
+	return &CopilotHandler{copilotService: copilotService}
+}
+
+// CopilotDeviceCodeRequest represents the request for starting a device login
+type CopilotDeviceCodeRequest struct {
+	ProxyID *int64 `json:"proxy_id"`
+}
+
+// StartDeviceAuth starts a GitHub device login for a Copilot account
+// POST /api/v1/admin/copilot/device-code
+func (h *CopilotHandler) StartDeviceAuth(c *gin.Context) {
+	var req CopilotDeviceCodeRequest
+	if err := c.ShouldBindJSON(&req); err != nil {
+		// Allow empty body
+		req = CopilotDeviceCodeRequest{}
+	}
+
+	result, err := h.copilotService.StartDeviceAuth(c.Request.Context(), req.ProxyID)
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, result)
+}
+
+// CopilotPollRequest represents the request for polling a device login
+type CopilotPollRequest struct {
+	SessionID string `json:"session_id" binding:"required"`
+}
+
+// PollDeviceAuth polls a device login; once authorized the returned credentials can be used to create the account
+// POST /api/v1/admin/copilot/poll
+func (h *CopilotHandler) PollDeviceAuth(c *gin.Context) {
+	var req CopilotPollRequest
+	if err := c.ShouldBindJSON(&req); err != nil {
+		response.BadRequest(c, "Invalid request: "+err.Error())
+		return
+	}
+
+	result, err := h.copilotService.PollDeviceAuth(c.Request.Context(), req.SessionID)
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, result)
+}
+
+// ListModels lists the models available to a Copilot account
+// GET /api/v1/admin/copilot/accounts/:id/models
+func (h *CopilotHandler) ListModels(c *gin.Context) {
+	accountID, err := strconv.ParseInt(c.Param("id"), 10, 64)
+	if err != nil {
+		response.BadRequest(c, "Invalid account ID")
+		return
+	}
+
+	models, err := h.copilotService.ListModels(c.Request.Context(), accountID)
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, models)
+}
@@ -0,0 +1,38 @@
+package admin
+
+import (
+	"github.com/Wei-Shaw/sub2api/internal/pkg/response"
+	"github.com/Wei-Shaw/sub2api/internal/service"
+	"github.com/gin-gonic/gin"
+)
+
+// CronJobHandler 定时任务查看与手动触发
+type CronJobHandler struct {
+	cronJobs *service.CronJobService
+}
+
+// NewCronJobHandler 创建定时任务处理器
+func NewCronJobHandler(cronJobs *service.CronJobService) *CronJobHandler {
+	return &CronJobHandler{cronJobs: cronJobs}
+}
+
+// List 返回所有定时任务的调度表达式、下次执行时间与最近执行状态
+// GET /api/v1/admin/jobs/schedules
+func (h *CronJobHandler) List(c *gin.Context) {
+	jobs, err := h.cronJobs.List(c.Request.Context())
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, jobs)
+}
+
+// Run 立即异步执行一次定时任务
+// POST /api/v1/admin/jobs/schedules/:name/run
+func (h *CronJobHandler) Run(c *gin.Context) {
+	if err := h.cronJobs.RunNow(c.Param("name")); err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, gin.H{"message": "Job triggered"})
+}
@@ -0,0 +1,102 @@
+package admin
+
+import (
+	"net/http"
+	"path"
+	"strconv"
+
+	"github.com/Wei-Shaw/sub2api/internal/pkg/pagination"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/response"
+	middleware2 "github.com/Wei-Shaw/sub2api/internal/server/middleware"
+	"github.com/Wei-Shaw/sub2api/internal/service"
+	"github.com/gin-gonic/gin"
+)
+
+// DataSubjectHandler 数据主体（用户 / API Key）数据导出与永久删除
+type DataSubjectHandler struct {
+	dataSubjectService *service.DataSubjectService
+}
+
+// NewDataSubjectHandler 创建数据主体请求处理器
+func NewDataSubjectHandler(dataSubjectService *service.DataSubjectService) *DataSubjectHandler {
+	return &DataSubjectHandler{dataSubjectService: dataSubjectService}
+}
+
+// CreateDataSubjectRequest 创建数据主体请求
+type CreateDataSubjectRequest struct {
+	Kind        string `json:"kind" binding:"required,oneof=export delete"`
+	SubjectType string `json:"subject_type" binding:"required,oneof=user api_key"`
+	SubjectID   int64  `json:"subject_id" binding:"required,gt=0"`
+	Reason      string `json:"reason" binding:"max=1000"`
+}
+
+// Create 创建导出或删除请求，异步执行
+// POST /api/v1/admin/data-subject-requests
+func (h *DataSubjectHandler) Create(c *gin.Context) {
+	subject, ok := middleware2.GetAuthSubjectFromContext(c)
+	if !ok || subject.UserID <= 0 {
+		response.Unauthorized(c, "Unauthorized")
+		return
+	}
+	var req CreateDataSubjectRequest
+	if err := c.ShouldBindJSON(&req); err != nil {
+		response.BadRequest(c, "Invalid request: "+err.Error())
+		return
+	}
+
+	item, err := h.dataSubjectService.CreateRequest(c.Request.Context(), req.Kind, req.SubjectType, req.SubjectID, req.Reason, subject.UserID)
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, item)
+}
+
+// List 列出数据主体请求
+// GET /api/v1/admin/data-subject-requests
+func (h *DataSubjectHandler) List(c *gin.Context) {
+	page, pageSize := response.ParsePagination(c)
+	params := pagination.PaginationParams{Page: page, PageSize: pageSize}
+	items, result, err := h.dataSubjectService.List(c.Request.Context(), params)
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Paginated(c, items, result.Total, page, pageSize)
+}
+
+// Get 查询请求状态与完成报告
+// GET /api/v1/admin/data-subject-requests/:id
+func (h *DataSubjectHandler) Get(c *gin.Context) {
+	id, err := strconv.ParseInt(c.Param("id"), 10, 64)
+	if err != nil {
+		response.BadRequest(c, "Invalid request ID")
+		return
+	}
+	item, err := h.dataSubjectService.Get(c.Request.Context(), id)
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, item)
+}
+
+// Download 下载导出结果（JSON Lines，每行 {"table": ..., "row": {...}}）
+// GET /api/v1/admin/data-subject-requests/:id/download
+func (h *DataSubjectHandler) Download(c *gin.Context) {
+	id, err := strconv.ParseInt(c.Param("id"), 10, 64)
+	if err != nil {
+		response.BadRequest(c, "Invalid request ID")
+		return
+	}
+	item, obj, err := h.dataSubjectService.OpenExport(c.Request.Context(), id)
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	defer func() { _ = obj.Body.Close() }()
+
+	c.Header("Content-Disposition", "attachment; filename="+path.Base(item.ExportKey))
+	c.Header("Cache-Control", "no-store")
+	c.DataFromReader(http.StatusOK, obj.Size, "application/x-ndjson", obj.Body, nil)
+}
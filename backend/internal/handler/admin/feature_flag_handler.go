@@ -0,0 +1,102 @@
+package admin
+
+import (
+	"strconv"
+
+	"github.com/Wei-Shaw/sub2api/internal/pkg/response"
+	middleware2 "github.com/Wei-Shaw/sub2api/internal/server/middleware"
+	"github.com/Wei-Shaw/sub2api/internal/service"
+	"github.com/gin-gonic/gin"
+)
+
+// FeatureFlagHandler 运行时功能开关管理
+type FeatureFlagHandler struct {
+	featureFlagService *service.FeatureFlagService
+}
+
+// NewFeatureFlagHandler 创建功能开关处理器
+func NewFeatureFlagHandler(featureFlagService *service.FeatureFlagService) *FeatureFlagHandler {
+	return &FeatureFlagHandler{featureFlagService: featureFlagService}
+}
+
+// UpsertFeatureFlagRequest 创建或更新功能开关
+type UpsertFeatureFlagRequest struct {
+	Description    string   `json:"description" binding:"max=1000"`
+	Enabled        bool     `json:"enabled"`
+	RolloutPercent *int     `json:"rollout_percent"`
+	Environments   []string `json:"environments"`
+	UserIDs        []int64  `json:"user_ids"`
+	GroupIDs       []int64  `json:"group_ids"`
+	APIKeyIDs      []int64  `json:"api_key_ids"`
+}
+
+// List 列出内置与已配置的功能开关
+// GET /api/v1/admin/feature-flags
+func (h *FeatureFlagHandler) List(c *gin.Context) {
+	items, err := h.featureFlagService.List(c.Request.Context())
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, items)
+}
+
+// Upsert 创建或更新功能开关，立即对所有实例生效
+// PUT /api/v1/admin/feature-flags/:key
+func (h *FeatureFlagHandler) Upsert(c *gin.Context) {
+	var req UpsertFeatureFlagRequest
+	if err := c.ShouldBindJSON(&req); err != nil {
+		response.BadRequest(c, "Invalid request: "+err.Error())
+		return
+	}
+	flag := &service.FeatureFlag{
+		Key:            c.Param("key"),
+		Description:    req.Description,
+		Enabled:        req.Enabled,
+		RolloutPercent: 100,
+		Environments:   req.Environments,
+		UserIDs:        req.UserIDs,
+		GroupIDs:       req.GroupIDs,
+		APIKeyIDs:      req.APIKeyIDs,
+	}
+	if req.RolloutPercent != nil {
+		flag.RolloutPercent = *req.RolloutPercent
+	}
+	if subject, ok := middleware2.GetAuthSubjectFromContext(c); ok && subject.UserID > 0 {
+		id := subject.UserID
+		flag.UpdatedBy = &id
+	}
+	if err := h.featureFlagService.Upsert(c.Request.Context(), flag); err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, flag)
+}
+
+// Delete 删除功能开关配置，恢复内置默认值
+// DELETE /api/v1/admin/feature-flags/:key
+func (h *FeatureFlagHandler) Delete(c *gin.Context) {
+	if err := h.featureFlagService.Delete(c.Request.Context(), c.Param("key")); err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, gin.H{"deleted": true})
+}
+
+// Evaluate 查看功能开关对指定主体的判定结果（本实例当前缓存）
+// GET /api/v1/admin/feature-flags/:key/evaluate?user_id=&group_id=&api_key_id=
+func (h *FeatureFlagHandler) Evaluate(c *gin.Context) {
+	var subject service.FeatureFlagSubject
+	for name, dst := range map[string]*int64{"user_id": &subject.UserID, "group_id": &subject.GroupID, "api_key_id": &subject.APIKeyID} {
+		if raw := c.Query(name); raw != "" {
+			v, err := strconv.ParseInt(raw, 10, 64)
+			if err != nil {
+				response.BadRequest(c, "Invalid "+name)
+				return
+			}
+			*dst = v
+		}
+	}
+	key := c.Param("key")
+	response.Success(c, gin.H{"key": key, "enabled": h.featureFlagService.IsEnabled(key, subject)})
+}
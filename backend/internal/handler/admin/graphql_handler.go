@@ -0,0 +1,41 @@
+package admin
+
+import (
+	"encoding/json"
+	"net/http"
+
+	"github.com/Wei-Shaw/sub2api/internal/pkg/graphql"
+	"github.com/Wei-Shaw/sub2api/internal/service"
+
+	"github.com/gin-gonic/gin"
+)
+
+// GraphQLHandler 管理后台 GraphQL 查询接口：仪表盘可在一次请求中获取账号、用户、
+// API Key、用量汇总与告警等嵌套视图，嵌套字段按层批量加载
+type GraphQLHandler struct {
+	schema *graphql.Schema
+}
+
+// NewGraphQLHandler 创建 GraphQL 处理器
+func NewGraphQLHandler(adminService service.AdminService, dashboardService *service.DashboardService, opsService *service.OpsService) *GraphQLHandler {
+	return &GraphQLHandler{schema: newAdminGraphQLSchema(adminService, dashboardService, opsService)}
+}
+
+// Query 执行 GraphQL 查询（仅支持 query 操作）
+// POST /api/v1/admin/graphql
+func (h *GraphQLHandler) Query(c *gin.Context) {
+	var req graphql.Request
+	dec := json.NewDecoder(c.Request.Body)
+	dec.UseNumber()
+	if err := dec.Decode(&req); err != nil {
+		c.JSON(http.StatusBadRequest, &graphql.Result{Errors: []*graphql.Error{{Message: "Invalid request body: " + err.Error()}}})
+		return
+	}
+	c.JSON(http.StatusOK, h.schema.Execute(c.Request.Context(), req))
+}
+
+// Schema 返回 schema 的 SDL 描述
+// GET /api/v1/admin/graphql/schema
+func (h *GraphQLHandler) Schema(c *gin.Context) {
+	c.String(http.StatusOK, h.schema.SDL())
+}
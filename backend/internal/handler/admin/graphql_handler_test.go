@@ -0,0 +1,99 @@
+package admin
+
+import (
+	"bytes"
+	"encoding/json"
+	"net/http"
+	"net/http/httptest"
+	"testing"
+
+	"github.com/gin-gonic/gin"
+	"github.com/stretchr/testify/require"
+)
+
+func setupGraphQLRouter() (*gin.Engine, *stubAdminService) {
+	gin.SetMode(gin.TestMode)
+	router := gin.New()
+	adminSvc := newStubAdminService()
+	h := NewGraphQLHandler(adminSvc, nil, nil)
+	router.POST("/api/v1/admin/graphql", h.Query)
+	router.GET("/api/v1/admin/graphql/schema", h.Schema)
+	return router, adminSvc
+}
+
+func postGraphQL(t *testing.T, router *gin.Engine, body any) (int, map[string]any) {
+	t.Helper()
+	payload, err := json.Marshal(body)
+	require.NoError(t, err)
+	rec := httptest.NewRecorder()
+	req := httptest.NewRequest(http.MethodPost, "/api/v1/admin/graphql", bytes.NewReader(payload))
+	req.Header.Set("Content-Type", "application/json")
+	router.ServeHTTP(rec, req)
+	var out map[string]any
+	require.NoError(t, json.Unmarshal(rec.Body.Bytes(), &out))
+	return rec.Code, out
+}
+
+func TestGraphQLHandler_NestedQuery(t *testing.T) {
+	router, adminSvc := setupGraphQLRouter()
+	proxyID := adminSvc.proxies[0].ID
+	groupID := adminSvc.groups[0].ID
+	adminSvc.accounts[0].ProxyID = &proxyID
+	adminSvc.apiKeys[0].GroupID = &groupID
+
+	code, out := postGraphQL(t, router, map[string]any{
+		"query": `query ($uid: Int!) {
+			accounts(page_size: 5) { total items { name proxy { host port } } }
+			api_keys(user_id: $uid) { items { name user { email } group { id name } } }
+		}`,
+		"variables": map[string]any{"uid": 1},
+	})
+	require.Equal(t, http.StatusOK, code)
+	require.Nil(t, out["errors"])
+	require.Equal(t, map[string]any{
+		"accounts": map[string]any{
+			"total": float64(1),
+			"items": []any{map[string]any{"name": "account", "proxy": map[string]any{"host": "127.0.0.1", "port": float64(8080)}}},
+		},
+		"api_keys": map[string]any{
+			"items": []any{map[string]any{
+				"name":  "test",
+				"user":  map[string]any{"email": "user@example.com"},
+				"group": map[string]any{"id": float64(groupID), "name": "group"},
+			}},
+		},
+	}, out["data"])
+}
+
+func TestGraphQLHandler_Errors(t *testing.T) {
+	router, _ := setupGraphQLRouter()
+
+	code, out := postGraphQL(t, router, map[string]any{"query": `{ api_keys { total } }`})
+	require.Equal(t, http.StatusOK, code)
+	errs := out["errors"].([]any)
+	require.Len(t, errs, 1)
+	require.Equal(t, "exactly one of user_id or group_id is required", errs[0].(map[string]any)["message"])
+
+	code, out = postGraphQL(t, router, map[string]any{"query": `{ accounts { items { credentials } } }`})
+	require.Equal(t, http.StatusOK, code)
+	require.Nil(t, out["data"])
+	require.Contains(t, out["errors"].([]any)[0].(map[string]any)["message"], `Cannot query field "credentials"`)
+
+	rec := httptest.NewRecorder()
+	router.ServeHTTP(rec, httptest.NewRequest(http.MethodPost, "/api/v1/admin/graphql", bytes.NewReader([]byte("{"))))
+	require.Equal(t, http.StatusBadRequest, rec.Code)
+}
+
+func TestGraphQLHandler_Schema(t *testing.T) {
+	router, _ := setupGraphQLRouter()
+	rec := httptest.NewRecorder()
+	router.ServeHTTP(rec, httptest.NewRequest(http.MethodGet, "/api/v1/admin/graphql/schema", nil))
+
+	require.Equal(t, http.StatusOK, rec.Code)
+	sdl := rec.Body.String()
+	require.Contains(t, sdl, "type Query {")
+	require.Contains(t, sdl, "  accounts(page: Int = 1, page_size: Int = 20, platform: String, type: String, status: String, search: String, group_id: Int): AccountPage!\n")
+	require.Contains(t, sdl, "  proxy: Proxy\n")
+	require.NotContains(t, sdl, "password")
+	require.NotContains(t, sdl, "credentials")
+}
@@ -0,0 +1,509 @@
+package admin
+
+import (
+	"context"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/handler/dto"
+	infraerrors "github.com/Wei-Shaw/sub2api/internal/pkg/errors"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/graphql"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/usagestats"
+	"github.com/Wei-Shaw/sub2api/internal/service"
+)
+
+const (
+	graphQLDefaultPageSize = 20
+	graphQLMaxPageSize     = 100
+	graphQLMaxAlertEvents  = 500
+	// graphQLMaxDepth 后台最深的选择为 api_keys.items.user.usage 的字段（第 5 层），留一层余量
+	graphQLMaxDepth = 6
+)
+
+// graphQLPage 分页查询结果
+type graphQLPage struct {
+	Items    any   `json:"items"`
+	Total    int64 `json:"total"`
+	Page     int   `json:"page"`
+	PageSize int   `json:"page_size"`
+}
+
+// adminGraphQLResolvers 根查询与嵌套字段的解析器；嵌套字段均为批量解析，
+// 同一层级的所有父对象只触发一次（去重后的）查询
+type adminGraphQLResolvers struct {
+	admin     service.AdminService
+	dashboard *service.DashboardService
+	ops       *service.OpsService
+}
+
+func newAdminGraphQLSchema(adminService service.AdminService, dashboardService *service.DashboardService, opsService *service.OpsService) *graphql.Schema {
+	r := &adminGraphQLResolvers{admin: adminService, dashboard: dashboardService, ops: opsService}
+
+	proxyType := graphql.ObjectOf("Proxy", "代理", dto.Proxy{})
+	groupType := graphql.ObjectOf("Group", "分组", dto.Group{})
+	userUsageType := graphql.ObjectOf("UserUsage", "用户费用汇总（今日 / 累计，实际扣除）", usagestats.BatchUserUsageStats{})
+	apiKeyUsageType := graphql.ObjectOf("APIKeyUsage", "API Key 费用汇总（今日 / 累计，实际扣除）", usagestats.BatchAPIKeyUsageStats{})
+	userType := graphql.ObjectOf("User", "用户", dto.AdminUser{}).AddFields(
+		&graphql.Field{Name: "usage", Type: userUsageType, Batch: r.userUsage},
+	)
+	apiKeyType := graphql.ObjectOf("APIKey", "API Key", dto.APIKey{}).AddFields(
+		&graphql.Field{Name: "user", Type: userType, Batch: r.apiKeyUser},
+		&graphql.Field{Name: "group", Type: groupType, Batch: r.apiKeyGroup},
+		&graphql.Field{Name: "usage", Type: apiKeyUsageType, Batch: r.apiKeyUsage},
+	)
+	accountType := graphql.ObjectOf("Account", "上游账号（不含凭证）", dto.Account{}).AddFields(
+		&graphql.Field{Name: "proxy", Type: proxyType, Batch: r.accountProxy},
+		&graphql.Field{Name: "groups", Type: graphql.ListOf(graphql.NonNullOf(groupType))},
+	)
+	alertRuleType := graphql.ObjectOf("AlertRule", "告警规则", service.OpsAlertRule{})
+	alertEventType := graphql.ObjectOf("AlertEvent", "告警事件", service.OpsAlertEvent{}).AddFields(
+		&graphql.Field{Name: "rule", Type: alertRuleType, Batch: r.alertEventRule},
+	)
+	dashboardType := graphql.ObjectOf("DashboardStats", "仪表盘概览", usagestats.DashboardStats{})
+	trendPointType := graphql.ObjectOf("TrendPoint", "用量趋势数据点", usagestats.TrendDataPoint{})
+	modelStatType := graphql.ObjectOf("ModelStat", "按模型的用量统计", usagestats.ModelStat{})
+
+	timeRangeArgs := []*graphql.Argument{
+		{Name: "start", Type: graphql.NonNullOf(graphql.Time), Description: "起始时间（含）"},
+		{Name: "end", Type: graphql.NonNullOf(graphql.Time), Description: "结束时间（不含）"},
+		{Name: "user_id", Type: graphql.Int},
+		{Name: "api_key_id", Type: graphql.Int},
+		{Name: "account_id", Type: graphql.Int},
+		{Name: "group_id", Type: graphql.Int},
+	}
+	trendArgs := append(append([]*graphql.Argument{}, timeRangeArgs...),
+		&graphql.Argument{Name: "granularity", Type: graphql.String, Default: "day", Description: "day 或 hour"},
+		&graphql.Argument{Name: "model", Type: graphql.String},
+	)
+	idArg := []*graphql.Argument{{Name: "id", Type: graphql.NonNullOf(graphql.Int)}}
+
+	query := graphql.NewObject("Query", "管理后台只读查询",
+		&graphql.Field{Name: "dashboard", Type: dashboardType, Resolve: r.dashboardStats},
+		&graphql.Field{
+			Name:    "usage_trend",
+			Type:    graphql.NonNullOf(graphql.ListOf(graphql.NonNullOf(trendPointType))),
+			Args:    trendArgs,
+			Resolve: r.usageTrend,
+		},
+		&graphql.Field{
+			Name:    "model_stats",
+			Type:    graphql.NonNullOf(graphql.ListOf(graphql.NonNullOf(modelStatType))),
+			Args:    timeRangeArgs,
+			Resolve: r.modelStats,
+		},
+		&graphql.Field{
+			Name: "accounts",
+			Type: graphql.NonNullOf(graphQLPageType("AccountPage", accountType)),
+			Args: graphQLPageArgs(
+				&graphql.Argument{Name: "platform", Type: graphql.String},
+				&graphql.Argument{Name: "type", Type: graphql.String},
+				&graphql.Argument{Name: "status", Type: graphql.String},
+				&graphql.Argument{Name: "search", Type: graphql.String},
+				&graphql.Argument{Name: "group_id", Type: graphql.Int},
+			),
+			Resolve: r.accounts,
+		},
+		&graphql.Field{Name: "account", Type: accountType, Args: idArg, Resolve: r.account},
+		&graphql.Field{
+			Name: "users",
+			Type: graphql.NonNullOf(graphQLPageType("UserPage", userType)),
+			Args: graphQLPageArgs(
+				&graphql.Argument{Name: "status", Type: graphql.String},
+				&graphql.Argument{Name: "role", Type: graphql.String},
+				&graphql.Argument{Name: "search", Type: graphql.String},
+			),
+			Resolve: r.users,
+		},
+		&graphql.Field{Name: "user", Type: userType, Args: idArg, Resolve: r.user},
+		&graphql.Field{
+			Name: "groups",
+			Type: graphql.NonNullOf(graphQLPageType("GroupPage", groupType)),
+			Args: graphQLPageArgs(
+				&graphql.Argument{Name: "platform", Type: graphql.String},
+				&graphql.Argument{Name: "status", Type: graphql.String},
+				&graphql.Argument{Name: "search", Type: graphql.String},
+				&graphql.Argument{Name: "is_exclusive", Type: graphql.Boolean},
+			),
+			Resolve: r.groups,
+		},
+		&graphql.Field{Name: "group", Type: groupType, Args: idArg, Resolve: r.group},
+		&graphql.Field{
+			Name:        "api_keys",
+			Description: "按用户或分组列出 API Key（user_id 与 group_id 二选一）",
+			Type:        graphql.NonNullOf(graphQLPageType("APIKeyPage", apiKeyType)),
+			Args: graphQLPageArgs(
+				&graphql.Argument{Name: "user_id", Type: graphql.Int},
+				&graphql.Argument{Name: "group_id", Type: graphql.Int},
+			),
+			Resolve: r.apiKeys,
+		},
+		&graphql.Field{
+			Name: "alert_events",
+			Type: graphql.NonNullOf(graphql.ListOf(graphql.NonNullOf(alertEventType))),
+			Args: []*graphql.Argument{
+				{Name: "limit", Type: graphql.Int, Default: int64(20)},
+				{Name: "status", Type: graphql.String},
+				{Name: "severity", Type: graphql.String},
+			},
+			Resolve: r.alertEvents,
+		},
+		&graphql.Field{
+			Name:    "alert_rules",
+			Type:    graphql.NonNullOf(graphql.ListOf(graphql.NonNullOf(alertRuleType))),
+			Resolve: r.alertRules,
+		},
+	)
+
+	schema := graphql.NewSchema(query)
+	schema.MaxDepth = graphQLMaxDepth
+	schema.ErrorPresenter = infraerrors.Message
+	return schema
+}
+
+func graphQLPageType(name string, item *graphql.Object) *graphql.Object {
+	return graphql.NewObject(name, "",
+		&graphql.Field{Name: "items", Type: graphql.NonNullOf(graphql.ListOf(graphql.NonNullOf(item)))},
+		&graphql.Field{Name: "total", Type: graphql.NonNullOf(graphql.Int)},
+		&graphql.Field{Name: "page", Type: graphql.NonNullOf(graphql.Int)},
+		&graphql.Field{Name: "page_size", Type: graphql.NonNullOf(graphql.Int)},
+	)
+}
+
+func graphQLPageArgs(extra ...*graphql.Argument) []*graphql.Argument {
+	return append([]*graphql.Argument{
+		{Name: "page", Type: graphql.Int, Default: int64(1)},
+		{Name: "page_size", Type: graphql.Int, Default: int64(graphQLDefaultPageSize)},
+	}, extra...)
+}
+
+func graphQLPageParams(args graphql.Args) (page, pageSize int) {
+	page, pageSize = int(args.Int("page")), int(args.Int("page_size"))
+	if page < 1 {
+		page = 1
+	}
+	if pageSize < 1 {
+		pageSize = graphQLDefaultPageSize
+	}
+	if pageSize > graphQLMaxPageSize {
+		pageSize = graphQLMaxPageSize
+	}
+	return page, pageSize
+}
+
+// ---- 根查询 ----
+
+func (r *adminGraphQLResolvers) dashboardStats(ctx context.Context, _ any, _ graphql.Args) (any, error) {
+	return r.dashboard.GetDashboardStats(ctx)
+}
+
+func (r *adminGraphQLResolvers) usageTrend(ctx context.Context, _ any, args graphql.Args) (any, error) {
+	granularity := args.String("granularity")
+	if granularity != "day" && granularity != "hour" {
+		return nil, infraerrors.BadRequest("INVALID_GRANULARITY", "granularity must be day or hour")
+	}
+	return r.dashboard.GetUsageTrendWithFilters(ctx, args.Time("start"), args.Time("end"), granularity,
+		args.Int("user_id"), args.Int("api_key_id"), args.Int("account_id"), args.Int("group_id"), args.String("model"), nil, nil)
+}
+
+func (r *adminGraphQLResolvers) modelStats(ctx context.Context, _ any, args graphql.Args) (any, error) {
+	return r.dashboard.GetModelStatsWithFilters(ctx, args.Time("start"), args.Time("end"),
+		args.Int("user_id"), args.Int("api_key_id"), args.Int("account_id"), args.Int("group_id"), nil, nil)
+}
+
+func (r *adminGraphQLResolvers) accounts(ctx context.Context, _ any, args graphql.Args) (any, error) {
+	page, pageSize := graphQLPageParams(args)
+	accounts, total, err := r.admin.ListAccounts(ctx, page, pageSize, args.String("platform"), args.String("type"), args.String("status"), args.String("search"), args.Int("group_id"))
+	if err != nil {
+		return nil, err
+	}
+	items := make([]*dto.Account, 0, len(accounts))
+	for i := range accounts {
+		items = append(items, dto.AccountFromService(&accounts[i]))
+	}
+	return &graphQLPage{Items: items, Total: total, Page: page, PageSize: pageSize}, nil
+}
+
+func (r *adminGraphQLResolvers) account(ctx context.Context, _ any, args graphql.Args) (any, error) {
+	account, err := r.admin.GetAccount(ctx, args.Int("id"))
+	if err != nil {
+		return nil, err
+	}
+	return dto.AccountFromService(account), nil
+}
+
+func (r *adminGraphQLResolvers) users(ctx context.Context, _ any, args graphql.Args) (any, error) {
+	page, pageSize := graphQLPageParams(args)
+	filters := service.UserListFilters{Status: args.String("status"), Role: args.String("role"), Search: args.String("search")}
+	users, total, err := r.admin.ListUsers(ctx, page, pageSize, filters)
+	if err != nil {
+		return nil, err
+	}
+	items := make([]*dto.AdminUser, 0, len(users))
+	for i := range users {
+		items = append(items, dto.UserFromServiceAdmin(&users[i]))
+	}
+	return &graphQLPage{Items: items, Total: total, Page: page, PageSize: pageSize}, nil
+}
+
+func (r *adminGraphQLResolvers) user(ctx context.Context, _ any, args graphql.Args) (any, error) {
+	user, err := r.admin.GetUser(ctx, args.Int("id"))
+	if err != nil {
+		return nil, err
+	}
+	return dto.UserFromServiceAdmin(user), nil
+}
+
+func (r *adminGraphQLResolvers) groups(ctx context.Context, _ any, args graphql.Args) (any, error) {
+	page, pageSize := graphQLPageParams(args)
+	groups, total, err := r.admin.ListGroups(ctx, page, pageSize, args.String("platform"), args.String("status"), args.String("search"), args.Bool("is_exclusive"))
+	if err != nil {
+		return nil, err
+	}
+	items := make([]*dto.AdminGroup, 0, len(groups))
+	for i := range groups {
+		items = append(items, dto.GroupFromServiceAdmin(&groups[i]))
+	}
+	return &graphQLPage{Items: items, Total: total, Page: page, PageSize: pageSize}, nil
+}
+
+func (r *adminGraphQLResolvers) group(ctx context.Context, _ any, args graphql.Args) (any, error) {
+	group, err := r.admin.GetGroup(ctx, args.Int("id"))
+	if err != nil {
+		return nil, err
+	}
+	return dto.GroupFromServiceAdmin(group), nil
+}
+
+func (r *adminGraphQLResolvers) apiKeys(ctx context.Context, _ any, args graphql.Args) (any, error) {
+	if args.Has("user_id") == args.Has("group_id") {
+		return nil, infraerrors.BadRequest("INVALID_ARGUMENT", "exactly one of user_id or group_id is required")
+	}
+	page, pageSize := graphQLPageParams(args)
+	var (
+		keys  []service.APIKey
+		total int64
+		err   error
+	)
+	if args.Has("user_id") {
+		keys, total, err = r.admin.GetUserAPIKeys(ctx, args.Int("user_id"), page, pageSize)
+	} else {
+		keys, total, err = r.admin.GetGroupAPIKeys(ctx, args.Int("group_id"), page, pageSize)
+	}
+	if err != nil {
+		return nil, err
+	}
+	items := make([]*dto.APIKey, 0, len(keys))
+	for i := range keys {
+		items = append(items, dto.APIKeyFromService(&keys[i]))
+	}
+	return &graphQLPage{Items: items, Total: total, Page: page, PageSize: pageSize}, nil
+}
+
+func (r *adminGraphQLResolvers) requireOps(ctx context.Context) error {
+	if r.ops == nil {
+		return infraerrors.ServiceUnavailable("OPS_UNAVAILABLE", "Ops service not available")
+	}
+	return r.ops.RequireMonitoringEnabled(ctx)
+}
+
+func (r *adminGraphQLResolvers) alertEvents(ctx context.Context, _ any, args graphql.Args) (any, error) {
+	if err := r.requireOps(ctx); err != nil {
+		return nil, err
+	}
+	limit := int(args.Int("limit"))
+	if limit <= 0 || limit > graphQLMaxAlertEvents {
+		return nil, infraerrors.BadRequest("INVALID_LIMIT", "limit must be between 1 and 500")
+	}
+	return r.ops.ListAlertEvents(ctx, &service.OpsAlertEventFilter{
+		Limit:    limit,
+		Status:   args.String("status"),
+		Severity: args.String("severity"),
+	})
+}
+
+func (r *adminGraphQLResolvers) alertRules(ctx context.Context, _ any, _ graphql.Args) (any, error) {
+	if err := r.requireOps(ctx); err != nil {
+		return nil, err
+	}
+	return r.ops.ListAlertRules(ctx)
+}
+
+// ---- 批量嵌套字段 ----
+
+// uniqueIDs 去重并跳过非正数 ID
+func uniqueIDs(ids []int64) []int64 {
+	seen := make(map[int64]struct{}, len(ids))
+	out := make([]int64, 0, len(ids))
+	for _, id := range ids {
+		if id <= 0 {
+			continue
+		}
+		if _, ok := seen[id]; !ok {
+			seen[id] = struct{}{}
+			out = append(out, id)
+		}
+	}
+	return out
+}
+
+func (r *adminGraphQLResolvers) userUsage(ctx context.Context, sources []any, _ graphql.Args) ([]any, error) {
+	ids := make([]int64, len(sources))
+	for i, src := range sources {
+		if u, ok := src.(*dto.AdminUser); ok {
+			ids[i] = u.ID
+		}
+	}
+	stats, err := r.dashboard.GetBatchUserUsageStats(ctx, uniqueIDs(ids), time.Time{}, time.Time{})
+	if err != nil {
+		return nil, err
+	}
+	out := make([]any, len(sources))
+	for i, id := range ids {
+		if s, ok := stats[id]; ok {
+			out[i] = s
+		}
+	}
+	return out, nil
+}
+
+func (r *adminGraphQLResolvers) apiKeyUsage(ctx context.Context, sources []any, _ graphql.Args) ([]any, error) {
+	ids := make([]int64, len(sources))
+	for i, src := range sources {
+		if k, ok := src.(*dto.APIKey); ok {
+			ids[i] = k.ID
+		}
+	}
+	stats, err := r.dashboard.GetBatchAPIKeyUsageStats(ctx, uniqueIDs(ids), time.Time{}, time.Time{})
+	if err != nil {
+		return nil, err
+	}
+	out := make([]any, len(sources))
+	for i, id := range ids {
+		if s, ok := stats[id]; ok {
+			out[i] = s
+		}
+	}
+	return out, nil
+}
+
+// apiKeyUser 没有批量查询用户的接口，按去重后的 user_id 逐个加载
+func (r *adminGraphQLResolvers) apiKeyUser(ctx context.Context, sources []any, _ graphql.Args) ([]any, error) {
+	ids := make([]int64, len(sources))
+	for i, src := range sources {
+		if k, ok := src.(*dto.APIKey); ok {
+			ids[i] = k.UserID
+		}
+	}
+	users := make(map[int64]*dto.AdminUser)
+	for _, id := range uniqueIDs(ids) {
+		user, err := r.admin.GetUser(ctx, id)
+		if err != nil {
+			if infraerrors.IsNotFound(err) {
+				continue
+			}
+			return nil, err
+		}
+		users[id] = dto.UserFromServiceAdmin(user)
+	}
+	out := make([]any, len(sources))
+	for i, id := range ids {
+		if u, ok := users[id]; ok {
+			out[i] = u
+		}
+	}
+	return out, nil
+}
+
+// apiKeyGroup 优先使用 API Key 已预加载的分组，其余按去重后的 group_id 加载
+func (r *adminGraphQLResolvers) apiKeyGroup(ctx context.Context, sources []any, _ graphql.Args) ([]any, error) {
+	out := make([]any, len(sources))
+	groups := make(map[int64]*dto.Group)
+	var missing []int64
+	for _, src := range sources {
+		k, ok := src.(*dto.APIKey)
+		if !ok || k.GroupID == nil {
+			continue
+		}
+		if k.Group != nil {
+			groups[*k.GroupID] = k.Group
+		} else {
+			missing = append(missing, *k.GroupID)
+		}
+	}
+	for _, id := range uniqueIDs(missing) {
+		if _, ok := groups[id]; ok {
+			continue
+		}
+		group, err := r.admin.GetGroup(ctx, id)
+		if err != nil {
+			if infraerrors.IsNotFound(err) {
+				continue
+			}
+			return nil, err
+		}
+		groups[id] = dto.GroupFromService(group)
+	}
+	for i, src := range sources {
+		if k, ok := src.(*dto.APIKey); ok && k.GroupID != nil {
+			if g, ok := groups[*k.GroupID]; ok {
+				out[i] = g
+			}
+		}
+	}
+	return out, nil
+}
+
+// accountProxy 优先使用已预加载的代理，其余通过一次 GetProxiesByIDs 加载
+func (r *adminGraphQLResolvers) accountProxy(ctx context.Context, sources []any, _ graphql.Args) ([]any, error) {
+	proxies := make(map[int64]*dto.Proxy)
+	var missing []int64
+	for _, src := range sources {
+		a, ok := src.(*dto.Account)
+		if !ok || a.ProxyID == nil {
+			continue
+		}
+		if a.Proxy != nil {
+			proxies[*a.ProxyID] = a.Proxy
+		} else {
+			missing = append(missing, *a.ProxyID)
+		}
+	}
+	if ids := uniqueIDs(missing); len(ids) > 0 {
+		loaded, err := r.admin.GetProxiesByIDs(ctx, ids)
+		if err != nil {
+			return nil, err
+		}
+		for i := range loaded {
+			proxies[loaded[i].ID] = dto.ProxyFromService(&loaded[i])
+		}
+	}
+	out := make([]any, len(sources))
+	for i, src := range sources {
+		if a, ok := src.(*dto.Account); ok && a.ProxyID != nil {
+			if p, ok := proxies[*a.ProxyID]; ok {
+				out[i] = p
+			}
+		}
+	}
+	return out, nil
+}
+
+// alertEventRule 规则数量很少，一次加载全部规则
+func (r *adminGraphQLResolvers) alertEventRule(ctx context.Context, sources []any, _ graphql.Args) ([]any, error) {
+	rules, err := r.ops.ListAlertRules(ctx)
+	if err != nil {
+		return nil, err
+	}
+	byID := make(map[int64]*service.OpsAlertRule, len(rules))
+	for _, rule := range rules {
+		byID[rule.ID] = rule
+	}
+	out := make([]any, len(sources))
+	for i, src := range sources {
+		if ev, ok := src.(*service.OpsAlertEvent); ok {
+			if rule, ok := byID[ev.RuleID]; ok {
+				out[i] = rule
+			}
+		}
+	}
+	return out, nil
+}
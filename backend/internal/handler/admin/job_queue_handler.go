@@ -0,0 +1,31 @@
+package admin
+
+import (
+	"github.com/Wei-Shaw/sub2api/internal/pkg/response"
+	"github.com/Wei-Shaw/sub2api/internal/service"
+	"github.com/gin-gonic/gin"
+)
+
+// JobQueueHandler 后台任务队列监控
+type JobQueueHandler struct {
+	jobQueue *service.JobQueueService
+}
+
+// NewJobQueueHandler 创建任务队列监控处理器
+func NewJobQueueHandler(jobQueue *service.JobQueueService) *JobQueueHandler {
+	return &JobQueueHandler{jobQueue: jobQueue}
+}
+
+// Queues 返回各主题的队列深度、待确认数、积压与死信数量
+// GET /api/v1/admin/jobs/queues
+func (h *JobQueueHandler) Queues(c *gin.Context) {
+	stats, err := h.jobQueue.Stats(c.Request.Context())
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, gin.H{
+		"enabled": h.jobQueue.Enabled(),
+		"queues":  stats,
+	})
+}
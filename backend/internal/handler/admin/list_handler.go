@@ -0,0 +1,159 @@
+package admin
+
+import (
+	"strconv"
+	"strings"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/handler/dto"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/response"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/timezone"
+	"github.com/Wei-Shaw/sub2api/internal/service"
+	"github.com/gin-gonic/gin"
+)
+
+// ListHandler 管理后台列表的游标分页接口
+//
+// 请求携带 cursor 参数（首页传空字符串）时使用游标分页：
+//   - cursor: 上一页返回的 next_cursor
+//   - limit / page_size: 每页数量（最大 100）
+//   - sort_by / sort_order: 排序字段与方向（默认 id desc）
+//
+// 响应格式为 {items, next_cursor, has_more}，不返回总数。
+type ListHandler struct {
+	listService *service.AdminListService
+}
+
+// NewListHandler 创建游标分页列表处理器
+func NewListHandler(listService *service.AdminListService) *ListHandler {
+	return &ListHandler{listService: listService}
+}
+
+// Accounts 游标分页列出账号
+// GET /api/v1/admin/accounts?cursor=
+func (h *ListHandler) Accounts(c *gin.Context) {
+	filter := service.AccountListFilter{
+		Platform: c.Query("platform"),
+		Type:     c.Query("type"),
+		Status:   c.Query("status"),
+		Search:   normalizeListSearch(c.Query("search")),
+	}
+	if groupIDStr := c.Query("group"); groupIDStr != "" {
+		groupID, err := strconv.ParseInt(groupIDStr, 10, 64)
+		if err != nil {
+			response.BadRequest(c, "Invalid group")
+			return
+		}
+		filter.GroupID = groupID
+	}
+	var ok bool
+	if filter.CreatedFrom, filter.CreatedTo, ok = parseListDateRange(c); !ok {
+		return
+	}
+
+	accounts, result, err := h.listService.ListAccounts(c.Request.Context(), filter, response.ParseCursorPagination(c))
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+
+	out := make([]dto.Account, 0, len(accounts))
+	for i := range accounts {
+		out = append(out, *dto.AccountFromService(&accounts[i]))
+	}
+	response.CursorPaginated(c, out, result.NextCursor, result.HasMore)
+}
+
+// APIKeys 游标分页列出全部用户的 API Key
+// GET /api/v1/admin/api-keys
+func (h *ListHandler) APIKeys(c *gin.Context) {
+	filter := service.APIKeyListFilter{
+		Status: c.Query("status"),
+		Search: normalizeListSearch(c.Query("search")),
+	}
+	if userIDStr := c.Query("user_id"); userIDStr != "" {
+		userID, err := strconv.ParseInt(userIDStr, 10, 64)
+		if err != nil {
+			response.BadRequest(c, "Invalid user_id")
+			return
+		}
+		filter.UserID = userID
+	}
+	if groupIDStr := c.Query("group_id"); groupIDStr != "" {
+		groupID, err := strconv.ParseInt(groupIDStr, 10, 64)
+		if err != nil {
+			response.BadRequest(c, "Invalid group_id")
+			return
+		}
+		filter.GroupID = groupID
+	}
+	var ok bool
+	if filter.CreatedFrom, filter.CreatedTo, ok = parseListDateRange(c); !ok {
+		return
+	}
+
+	keys, result, err := h.listService.ListAPIKeys(c.Request.Context(), filter, response.ParseCursorPagination(c))
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+
+	out := make([]dto.APIKey, 0, len(keys))
+	for i := range keys {
+		out = append(out, *dto.APIKeyFromService(&keys[i]))
+	}
+	response.CursorPaginated(c, out, result.NextCursor, result.HasMore)
+}
+
+// UsageLogs 游标分页列出使用记录（过滤参数与 offset 分页列表一致）
+// GET /api/v1/admin/usage?cursor=
+func (h *ListHandler) UsageLogs(c *gin.Context) {
+	filters, ok := parseUsageLogFilters(c)
+	if !ok {
+		return
+	}
+
+	records, result, err := h.listService.ListUsageLogs(c.Request.Context(), filters, response.ParseCursorPagination(c))
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+
+	out := make([]dto.AdminUsageLog, 0, len(records))
+	for i := range records {
+		out = append(out, *dto.UsageLogFromServiceAdmin(&records[i]))
+	}
+	response.CursorPaginated(c, out, result.NextCursor, result.HasMore)
+}
+
+func normalizeListSearch(search string) string {
+	search = strings.TrimSpace(search)
+	if len(search) > 100 {
+		search = search[:100]
+	}
+	return search
+}
+
+// parseListDateRange 解析 start_date / end_date（YYYY-MM-DD，按 timezone 参数解释），作用于创建时间
+func parseListDateRange(c *gin.Context) (*time.Time, *time.Time, bool) {
+	var startTime, endTime *time.Time
+	userTZ := c.Query("timezone")
+	if startDateStr := c.Query("start_date"); startDateStr != "" {
+		t, err := timezone.ParseInUserLocation("2006-01-02", startDateStr, userTZ)
+		if err != nil {
+			response.BadRequest(c, "Invalid start_date format, use YYYY-MM-DD")
+			return nil, nil, false
+		}
+		startTime = &t
+	}
+	if endDateStr := c.Query("end_date"); endDateStr != "" {
+		t, err := timezone.ParseInUserLocation("2006-01-02", endDateStr, userTZ)
+		if err != nil {
+			response.BadRequest(c, "Invalid end_date format, use YYYY-MM-DD")
+			return nil, nil, false
+		}
+		t = t.Add(24*time.Hour - time.Nanosecond)
+		endTime = &t
+	}
+	return startTime, endTime, true
+}
@@ -0,0 +1,209 @@
+package admin
+
+import (
+	"context"
+	"encoding/json"
+	"errors"
+	"net/http"
+	"strconv"
+	"strings"
+	"sync"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/pkg/logger"
+	"github.com/Wei-Shaw/sub2api/internal/service"
+	"github.com/gin-gonic/gin"
+	"github.com/gorilla/websocket"
+)
+
+const (
+	// liveLogsWSFlushInterval 日志按批推送，避免日志密集时逐条写帧
+	liveLogsWSFlushInterval = 500 * time.Millisecond
+	// liveLogsWSMaxBatch 单批最多推送的日志条数，超出部分留到下一批
+	liveLogsWSMaxBatch = 200
+	// liveLogsWSMaxQueryLen q 参数最大长度
+	liveLogsWSMaxQueryLen = 256
+)
+
+// LogsWSHandler 通过 WebSocket 实时推送服务端结构化日志，按系统日志索引相同的规则脱敏。
+// 过滤与限速在服务端完成，查询参数：
+//   - level：最低级别 debug / info（默认）/ warn / error；低于全局日志级别的日志不会产生
+//   - component：逗号分隔的组件名子串
+//   - q：消息子串
+//   - rate：每秒最多推送条数（默认 50，上限 500），超出部分丢弃并在下一批的 rate_limited 中计数
+//
+// GET /api/v1/admin/ws/logs
+func (h *LiveUsageHandler) LogsWSHandler(c *gin.Context) {
+	if h == nil || h.logStream == nil {
+		c.JSON(http.StatusServiceUnavailable, gin.H{"error": "log stream not initialized"})
+		return
+	}
+	filter, err := parseLogStreamFilter(c)
+	if err != nil {
+		c.JSON(http.StatusBadRequest, gin.H{"error": err.Error()})
+		return
+	}
+
+	clientIP := requestClientIP(c.Request)
+	// 与 Ops 实时 QPS、实时请求流共享连接数上限
+	if !tryAcquireOpsWSTotalSlot(opsWSLimits.MaxConns) {
+		logger.LegacyPrintf("handler.admin.live_logs_ws", "[LiveLogsWS] connection limit reached: %d/%d", wsConnCount.Load(), opsWSLimits.MaxConns)
+		c.JSON(http.StatusServiceUnavailable, gin.H{"error": "too many connections"})
+		return
+	}
+	defer func() {
+		if wsConnCount.Add(-1) == 0 {
+			scheduleQPSWSIdleStop()
+		}
+	}()
+	if opsWSLimits.MaxConnsPerIP > 0 && clientIP != "" {
+		if !tryAcquireOpsWSIPSlot(clientIP, opsWSLimits.MaxConnsPerIP) {
+			logger.LegacyPrintf("handler.admin.live_logs_ws", "[LiveLogsWS] per-ip connection limit reached: ip=%s limit=%d", clientIP, opsWSLimits.MaxConnsPerIP)
+			c.JSON(http.StatusServiceUnavailable, gin.H{"error": "too many connections"})
+			return
+		}
+		defer releaseOpsWSIPSlot(clientIP)
+	}
+
+	conn, err := upgrader.Upgrade(c.Writer, c.Request, nil)
+	if err != nil {
+		logger.LegacyPrintf("handler.admin.live_logs_ws", "[LiveLogsWS] upgrade failed: %v", err)
+		return
+	}
+	defer func() {
+		_ = conn.Close()
+	}()
+
+	sub := h.logStream.Subscribe(filter, 0)
+	defer sub.Close()
+
+	handleLiveLogsWebSocket(c.Request.Context(), conn, sub)
+}
+
+func parseLogStreamFilter(c *gin.Context) (service.LogStreamFilter, error) {
+	var filter service.LogStreamFilter
+	if level := strings.ToLower(strings.TrimSpace(c.Query("level"))); level != "" {
+		if !service.ValidLogStreamLevel(level) {
+			return filter, errors.New("invalid level")
+		}
+		filter.MinLevel = level
+	}
+	filter.Components = splitLiveUsageList(c.Query("component"))
+	filter.Query = strings.TrimSpace(c.Query("q"))
+	if len(filter.Query) > liveLogsWSMaxQueryLen {
+		return filter, errors.New("q is too long")
+	}
+	if raw := strings.TrimSpace(c.Query("rate")); raw != "" {
+		rate, err := strconv.Atoi(raw)
+		if err != nil || rate <= 0 || rate > service.LogStreamMaxRate {
+			return filter, errors.New("invalid rate")
+		}
+		filter.RatePerSecond = rate
+	}
+	return filter, nil
+}
+
+func handleLiveLogsWebSocket(parentCtx context.Context, conn *websocket.Conn, sub *service.LogStreamSubscription) {
+	ctx, cancel := context.WithCancel(parentCtx)
+	defer cancel()
+
+	var closeOnce sync.Once
+	closeConn := func() {
+		closeOnce.Do(func() {
+			_ = conn.Close()
+		})
+	}
+
+	// 客户端不发送业务消息，读循环只处理 Pong/Close 控制帧
+	var wg sync.WaitGroup
+	wg.Add(1)
+	go func() {
+		defer wg.Done()
+		defer cancel()
+
+		conn.SetReadLimit(qpsWSMaxReadBytes)
+		if err := conn.SetReadDeadline(time.Now().Add(qpsWSPongWait)); err != nil {
+			return
+		}
+		conn.SetPongHandler(func(string) error {
+			return conn.SetReadDeadline(time.Now().Add(qpsWSPongWait))
+		})
+		for {
+			if _, _, err := conn.ReadMessage(); err != nil {
+				if websocket.IsUnexpectedCloseError(err, websocket.CloseNormalClosure, websocket.CloseGoingAway, websocket.CloseNoStatusReceived) {
+					logger.LegacyPrintf("handler.admin.live_logs_ws", "[LiveLogsWS] read failed: %v", err)
+				}
+				return
+			}
+		}
+	}()
+
+	flushTicker := time.NewTicker(liveLogsWSFlushInterval)
+	defer flushTicker.Stop()
+	pingTicker := time.NewTicker(qpsWSPingInterval)
+	defer pingTicker.Stop()
+
+	writeWithTimeout := func(messageType int, data []byte) error {
+		if err := conn.SetWriteDeadline(time.Now().Add(qpsWSWriteTimeout)); err != nil {
+			return err
+		}
+		return conn.WriteMessage(messageType, data)
+	}
+	stop := func() {
+		cancel()
+		closeConn()
+		wg.Wait()
+	}
+
+	batch := make([]*service.LogStreamEntry, 0, liveLogsWSMaxBatch)
+	events := sub.Events()
+	for {
+		select {
+		case ev, ok := <-events:
+			if !ok {
+				stop()
+				return
+			}
+			batch = append(batch, h.logStream.NewEntry(ev))
+			if len(batch) >= liveLogsWSMaxBatch {
+				events = nil // 批次已满，暂停读取直到下一次推送（积压由订阅缓冲承接）
+			}
+
+		case <-flushTicker.C:
+			events = sub.Events()
+			dropped, rateLimited := sub.TakeDropped()
+			if len(batch) == 0 && dropped == 0 && rateLimited == 0 {
+				continue
+			}
+			msg, err := json.Marshal(gin.H{
+				"type":         "logs",
+				"timestamp":    time.Now().UTC().Format(time.RFC3339),
+				"data":         batch,
+				"dropped":      dropped,
+				"rate_limited": rateLimited,
+			})
+			if err != nil {
+				logger.LegacyPrintf("handler.admin.live_logs_ws", "[LiveLogsWS] marshal failed: %v", err)
+				batch = batch[:0]
+				continue
+			}
+			if err := writeWithTimeout(websocket.TextMessage, msg); err != nil {
+				logger.LegacyPrintf("handler.admin.live_logs_ws", "[LiveLogsWS] write failed: %v", err)
+				stop()
+				return
+			}
+			batch = batch[:0]
+
+		case <-pingTicker.C:
+			if err := writeWithTimeout(websocket.PingMessage, nil); err != nil {
+				stop()
+				return
+			}
+
+		case <-ctx.Done():
+			_ = writeWithTimeout(websocket.CloseMessage, websocket.FormatCloseMessage(websocket.CloseNormalClosure, ""))
+			stop()
+			return
+		}
+	}
+}
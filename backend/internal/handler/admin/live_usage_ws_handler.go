@@ -0,0 +1,250 @@
+package admin
+
+import (
+	"context"
+	"encoding/json"
+	"errors"
+	"fmt"
+	"net/http"
+	"strconv"
+	"strings"
+	"sync"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/pkg/logger"
+	"github.com/Wei-Shaw/sub2api/internal/service"
+	"github.com/gin-gonic/gin"
+	"github.com/gorilla/websocket"
+)
+
+const (
+	// liveUsageWSFlushInterval 事件按批推送，避免高流量时逐条写帧
+	liveUsageWSFlushInterval = 500 * time.Millisecond
+	// liveUsageWSMaxBatch 单批最多推送的事件数，超出部分留到下一批
+	liveUsageWSMaxBatch = 200
+)
+
+// LiveUsageHandler 实时请求流与实时日志（WebSocket）
+type LiveUsageHandler struct {
+	hub       *service.LiveUsageHub
+	logStream *service.LogStreamHub
+}
+
+// NewLiveUsageHandler 创建实时请求流处理器
+func NewLiveUsageHandler(hub *service.LiveUsageHub, logStream *service.LogStreamHub) *LiveUsageHandler {
+	return &LiveUsageHandler{hub: hub, logStream: logStream}
+}
+
+// LiveWSHandler 通过 WebSocket 实时推送请求事件（模型、Key、Token、延迟、状态）。
+// 过滤与采样在服务端完成，查询参数：
+//   - model / platform：逗号分隔
+//   - user_id / api_key_id / account_id：逗号分隔的 ID
+//   - errors_only=true：仅推送错误请求
+//   - sample_rate：采样比例 (0, 1]
+//
+// GET /api/v1/admin/ws/live
+func (h *LiveUsageHandler) LiveWSHandler(c *gin.Context) {
+	if h == nil || h.hub == nil {
+		c.JSON(http.StatusServiceUnavailable, gin.H{"error": "live usage feed not initialized"})
+		return
+	}
+	filter, err := parseLiveUsageFilter(c)
+	if err != nil {
+		c.JSON(http.StatusBadRequest, gin.H{"error": err.Error()})
+		return
+	}
+
+	clientIP := requestClientIP(c.Request)
+	// 与 Ops 实时 QPS 共享连接数上限
+	if !tryAcquireOpsWSTotalSlot(opsWSLimits.MaxConns) {
+		logger.LegacyPrintf("handler.admin.live_usage_ws", "[LiveUsageWS] connection limit reached: %d/%d", wsConnCount.Load(), opsWSLimits.MaxConns)
+		c.JSON(http.StatusServiceUnavailable, gin.H{"error": "too many connections"})
+		return
+	}
+	defer func() {
+		if wsConnCount.Add(-1) == 0 {
+			scheduleQPSWSIdleStop()
+		}
+	}()
+	if opsWSLimits.MaxConnsPerIP > 0 && clientIP != "" {
+		if !tryAcquireOpsWSIPSlot(clientIP, opsWSLimits.MaxConnsPerIP) {
+			logger.LegacyPrintf("handler.admin.live_usage_ws", "[LiveUsageWS] per-ip connection limit reached: ip=%s limit=%d", clientIP, opsWSLimits.MaxConnsPerIP)
+			c.JSON(http.StatusServiceUnavailable, gin.H{"error": "too many connections"})
+			return
+		}
+		defer releaseOpsWSIPSlot(clientIP)
+	}
+
+	conn, err := upgrader.Upgrade(c.Writer, c.Request, nil)
+	if err != nil {
+		logger.LegacyPrintf("handler.admin.live_usage_ws", "[LiveUsageWS] upgrade failed: %v", err)
+		return
+	}
+	defer func() {
+		_ = conn.Close()
+	}()
+
+	sub := h.hub.Subscribe(filter, 0)
+	defer sub.Close()
+
+	handleLiveUsageWebSocket(c.Request.Context(), conn, sub)
+}
+
+func parseLiveUsageFilter(c *gin.Context) (service.LiveUsageFilter, error) {
+	var filter service.LiveUsageFilter
+	var err error
+	filter.Models = splitLiveUsageList(c.Query("model"))
+	filter.Platforms = splitLiveUsageList(c.Query("platform"))
+	if filter.UserIDs, err = parseLiveUsageIDs(c.Query("user_id"), "user_id"); err != nil {
+		return filter, err
+	}
+	if filter.APIKeyIDs, err = parseLiveUsageIDs(c.Query("api_key_id"), "api_key_id"); err != nil {
+		return filter, err
+	}
+	if filter.AccountIDs, err = parseLiveUsageIDs(c.Query("account_id"), "account_id"); err != nil {
+		return filter, err
+	}
+	if raw := strings.TrimSpace(c.Query("errors_only")); raw != "" {
+		if filter.ErrorsOnly, err = strconv.ParseBool(raw); err != nil {
+			return filter, errors.New("invalid errors_only")
+		}
+	}
+	if raw := strings.TrimSpace(c.Query("sample_rate")); raw != "" {
+		rate, err := strconv.ParseFloat(raw, 64)
+		if err != nil || rate <= 0 || rate > 1 {
+			return filter, errors.New("invalid sample_rate")
+		}
+		filter.SampleRate = rate
+	}
+	return filter, nil
+}
+
+func splitLiveUsageList(raw string) []string {
+	var out []string
+	for _, item := range strings.Split(raw, ",") {
+		if item = strings.TrimSpace(item); item != "" {
+			out = append(out, item)
+		}
+	}
+	return out
+}
+
+func parseLiveUsageIDs(raw, name string) ([]int64, error) {
+	items := splitLiveUsageList(raw)
+	if len(items) == 0 {
+		return nil, nil
+	}
+	ids := make([]int64, 0, len(items))
+	for _, item := range items {
+		id, err := strconv.ParseInt(item, 10, 64)
+		if err != nil || id <= 0 {
+			return nil, fmt.Errorf("invalid %s", name)
+		}
+		ids = append(ids, id)
+	}
+	return ids, nil
+}
+
+func handleLiveUsageWebSocket(parentCtx context.Context, conn *websocket.Conn, sub *service.LiveUsageSubscription) {
+	ctx, cancel := context.WithCancel(parentCtx)
+	defer cancel()
+
+	var closeOnce sync.Once
+	closeConn := func() {
+		closeOnce.Do(func() {
+			_ = conn.Close()
+		})
+	}
+
+	// 客户端不发送业务消息，读循环只处理 Pong/Close 控制帧
+	var wg sync.WaitGroup
+	wg.Add(1)
+	go func() {
+		defer wg.Done()
+		defer cancel()
+
+		conn.SetReadLimit(qpsWSMaxReadBytes)
+		if err := conn.SetReadDeadline(time.Now().Add(qpsWSPongWait)); err != nil {
+			return
+		}
+		conn.SetPongHandler(func(string) error {
+			return conn.SetReadDeadline(time.Now().Add(qpsWSPongWait))
+		})
+		for {
+			if _, _, err := conn.ReadMessage(); err != nil {
+				if websocket.IsUnexpectedCloseError(err, websocket.CloseNormalClosure, websocket.CloseGoingAway, websocket.CloseNoStatusReceived) {
+					logger.LegacyPrintf("handler.admin.live_usage_ws", "[LiveUsageWS] read failed: %v", err)
+				}
+				return
+			}
+		}
+	}()
+
+	flushTicker := time.NewTicker(liveUsageWSFlushInterval)
+	defer flushTicker.Stop()
+	pingTicker := time.NewTicker(qpsWSPingInterval)
+	defer pingTicker.Stop()
+
+	writeWithTimeout := func(messageType int, data []byte) error {
+		if err := conn.SetWriteDeadline(time.Now().Add(qpsWSWriteTimeout)); err != nil {
+			return err
+		}
+		return conn.WriteMessage(messageType, data)
+	}
+	stop := func() {
+		cancel()
+		closeConn()
+		wg.Wait()
+	}
+
+	batch := make([]*service.LiveUsageEvent, 0, liveUsageWSMaxBatch)
+	events := sub.Events()
+	for {
+		select {
+		case ev, ok := <-events:
+			if !ok {
+				stop()
+				return
+			}
+			batch = append(batch, ev)
+			if len(batch) >= liveUsageWSMaxBatch {
+				events = nil // 批次已满，暂停读取直到下一次推送（积压由订阅缓冲承接）
+			}
+
+		case <-flushTicker.C:
+			events = sub.Events()
+			dropped := sub.TakeDropped()
+			if len(batch) == 0 && dropped == 0 {
+				continue
+			}
+			msg, err := json.Marshal(gin.H{
+				"type":      "usage_events",
+				"timestamp": time.Now().UTC().Format(time.RFC3339),
+				"data":      batch,
+				"dropped":   dropped,
+			})
+			if err != nil {
+				logger.LegacyPrintf("handler.admin.live_usage_ws", "[LiveUsageWS] marshal failed: %v", err)
+				batch = batch[:0]
+				continue
+			}
+			if err := writeWithTimeout(websocket.TextMessage, msg); err != nil {
+				logger.LegacyPrintf("handler.admin.live_usage_ws", "[LiveUsageWS] write failed: %v", err)
+				stop()
+				return
+			}
+			batch = batch[:0]
+
+		case <-pingTicker.C:
+			if err := writeWithTimeout(websocket.PingMessage, nil); err != nil {
+				stop()
+				return
+			}
+
+		case <-ctx.Done():
+			_ = writeWithTimeout(websocket.CloseMessage, websocket.FormatCloseMessage(websocket.CloseNormalClosure, ""))
+			stop()
+			return
+		}
+	}
+}
@@ -0,0 +1,72 @@
+package admin
+
+import (
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/pkg/response"
+	middleware2 "github.com/Wei-Shaw/sub2api/internal/server/middleware"
+	"github.com/Wei-Shaw/sub2api/internal/service"
+	"github.com/gin-gonic/gin"
+)
+
+// MaintenanceHandler 网关维护模式管理
+type MaintenanceHandler struct {
+	maintenanceService *service.MaintenanceService
+}
+
+// NewMaintenanceHandler 创建维护模式处理器
+func NewMaintenanceHandler(maintenanceService *service.MaintenanceService) *MaintenanceHandler {
+	return &MaintenanceHandler{maintenanceService: maintenanceService}
+}
+
+// UpdateMaintenanceRequest 更新维护模式请求
+type UpdateMaintenanceRequest struct {
+	Enabled      bool       `json:"enabled"`
+	Message      string     `json:"message" binding:"max=1000"`
+	StartsAt     *time.Time `json:"starts_at"`
+	EndsAt       *time.Time `json:"ends_at"`
+	AllowedPaths []string   `json:"allowed_paths" binding:"max=50"`
+}
+
+// MaintenanceStatusResponse 维护模式配置及当前是否处于维护窗口
+type MaintenanceStatusResponse struct {
+	*service.MaintenanceSettings
+	Active bool `json:"active"`
+}
+
+// Get 获取维护模式配置
+// GET /api/v1/admin/maintenance
+func (h *MaintenanceHandler) Get(c *gin.Context) {
+	settings, err := h.maintenanceService.GetSettings(c.Request.Context())
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, MaintenanceStatusResponse{MaintenanceSettings: settings, Active: settings.ActiveAt(time.Now())})
+}
+
+// Update 更新维护模式配置
+// PUT /api/v1/admin/maintenance
+func (h *MaintenanceHandler) Update(c *gin.Context) {
+	var req UpdateMaintenanceRequest
+	if err := c.ShouldBindJSON(&req); err != nil {
+		response.BadRequest(c, "Invalid request: "+err.Error())
+		return
+	}
+	settings := &service.MaintenanceSettings{
+		Enabled:      req.Enabled,
+		Message:      req.Message,
+		StartsAt:     req.StartsAt,
+		EndsAt:       req.EndsAt,
+		AllowedPaths: req.AllowedPaths,
+	}
+	if subject, ok := middleware2.GetAuthSubjectFromContext(c); ok && subject.UserID > 0 {
+		id := subject.UserID
+		settings.UpdatedBy = &id
+	}
+	if err := h.maintenanceService.UpdateSettings(c.Request.Context(), settings); err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, MaintenanceStatusResponse{MaintenanceSettings: settings, Active: settings.ActiveAt(time.Now())})
+}
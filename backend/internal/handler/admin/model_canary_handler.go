@@ -0,0 +1,176 @@
+package admin
+
+import (
+	"strconv"
+
+	"github.com/Wei-Shaw/sub2api/internal/pkg/response"
+	"github.com/Wei-Shaw/sub2api/internal/service"
+	"github.com/gin-gonic/gin"
+)
+
+// ModelCanaryHandler 处理金丝雀路由的 HTTP 请求
+type ModelCanaryHandler struct {
+	service *service.ModelCanaryService
+}
+
+// NewModelCanaryHandler 创建金丝雀路由处理器
+func NewModelCanaryHandler(service *service.ModelCanaryService) *ModelCanaryHandler {
+	return &ModelCanaryHandler{service: service}
+}
+
+// CreateModelCanaryRouteRequest 创建路由请求
+type CreateModelCanaryRouteRequest struct {
+	Alias   string                      `json:"alias" binding:"required"`
+	Enabled *bool                       `json:"enabled"`
+	Targets []service.ModelCanaryTarget `json:"targets" binding:"required"`
+}
+
+// UpdateModelCanaryRouteRequest 更新路由请求（部分更新；targets 整体替换，调整权重时传入完整目标列表）
+type UpdateModelCanaryRouteRequest struct {
+	Alias   *string                     `json:"alias"`
+	Enabled *bool                       `json:"enabled"`
+	Targets []service.ModelCanaryTarget `json:"targets"`
+}
+
+// List 获取所有路由
+// GET /api/v1/admin/canary-routes
+func (h *ModelCanaryHandler) List(c *gin.Context) {
+	routes, err := h.service.List(c.Request.Context())
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, routes)
+}
+
+// GetByID 根据 ID 获取路由
+// GET /api/v1/admin/canary-routes/:id
+func (h *ModelCanaryHandler) GetByID(c *gin.Context) {
+	id, ok := parseModelCanaryRouteID(c)
+	if !ok {
+		return
+	}
+
+	route, err := h.service.GetByID(c.Request.Context(), id)
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, route)
+}
+
+// Create 创建路由
+// POST /api/v1/admin/canary-routes
+func (h *ModelCanaryHandler) Create(c *gin.Context) {
+	var req CreateModelCanaryRouteRequest
+	if err := c.ShouldBindJSON(&req); err != nil {
+		response.BadRequest(c, "Invalid request: "+err.Error())
+		return
+	}
+
+	route := &service.ModelCanaryRoute{
+		Alias:   req.Alias,
+		Enabled: true,
+		Targets: req.Targets,
+	}
+	if req.Enabled != nil {
+		route.Enabled = *req.Enabled
+	}
+
+	if err := h.service.Create(c.Request.Context(), route); err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Created(c, route)
+}
+
+// Update 更新路由（支持部分更新，权重调整即时生效）
+// PUT /api/v1/admin/canary-routes/:id
+func (h *ModelCanaryHandler) Update(c *gin.Context) {
+	id, ok := parseModelCanaryRouteID(c)
+	if !ok {
+		return
+	}
+
+	var req UpdateModelCanaryRouteRequest
+	if err := c.ShouldBindJSON(&req); err != nil {
+		response.BadRequest(c, "Invalid request: "+err.Error())
+		return
+	}
+
+	route, err := h.service.GetByID(c.Request.Context(), id)
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	if req.Alias != nil {
+		route.Alias = *req.Alias
+	}
+	if req.Enabled != nil {
+		route.Enabled = *req.Enabled
+	}
+	if req.Targets != nil {
+		route.Targets = req.Targets
+	}
+
+	if err := h.service.Update(c.Request.Context(), route); err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, route)
+}
+
+// Delete 删除路由及其统计
+// DELETE /api/v1/admin/canary-routes/:id
+func (h *ModelCanaryHandler) Delete(c *gin.Context) {
+	id, ok := parseModelCanaryRouteID(c)
+	if !ok {
+		return
+	}
+
+	if err := h.service.Delete(c.Request.Context(), id); err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, gin.H{"message": "Canary route deleted successfully"})
+}
+
+// GetStats 获取各目标的请求数、成功率与平均耗时
+// GET /api/v1/admin/canary-routes/:id/stats
+func (h *ModelCanaryHandler) GetStats(c *gin.Context) {
+	id, ok := parseModelCanaryRouteID(c)
+	if !ok {
+		return
+	}
+
+	stats, err := h.service.GetStats(c.Request.Context(), id)
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, stats)
+}
+
+// ResetStats 清空路由统计
+// DELETE /api/v1/admin/canary-routes/:id/stats
+func (h *ModelCanaryHandler) ResetStats(c *gin.Context) {
+	id, ok := parseModelCanaryRouteID(c)
+	if !ok {
+		return
+	}
+
+	if err := h.service.ResetStats(c.Request.Context(), id); err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, gin.H{"message": "Canary stats reset successfully"})
+}
+
+func parseModelCanaryRouteID(c *gin.Context) (int64, bool) {
+	id, err := strconv.ParseInt(c.Param("id"), 10, 64)
+	if err != nil || id <= 0 {
+		response.BadRequest(c, "Invalid canary route ID")
+		return 0, false
+	}
+	return id, true
+}
@@ -0,0 +1,50 @@
+package admin
+
+import (
+	"strconv"
+	"strings"
+
+	"github.com/Wei-Shaw/sub2api/internal/pkg/pagination"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/response"
+	"github.com/Wei-Shaw/sub2api/internal/service"
+	"github.com/gin-gonic/gin"
+)
+
+// NotificationHandler 通知邮件发送记录查询，用于排查投递问题
+type NotificationHandler struct {
+	notificationService *service.NotificationService
+}
+
+// NewNotificationHandler 创建通知处理器
+func NewNotificationHandler(notificationService *service.NotificationService) *NotificationHandler {
+	return &NotificationHandler{notificationService: notificationService}
+}
+
+// ListLogs 分页列出发送记录，可按 user_id、recipient、kind、status 过滤
+// GET /api/v1/admin/notifications/logs
+func (h *NotificationHandler) ListLogs(c *gin.Context) {
+	page, pageSize := response.ParsePagination(c)
+	filter := service.NotificationLogFilter{
+		Recipient: strings.TrimSpace(c.Query("recipient")),
+		Kind:      strings.TrimSpace(c.Query("kind")),
+		Status:    strings.TrimSpace(c.Query("status")),
+	}
+	if raw := c.Query("user_id"); raw != "" {
+		id, err := strconv.ParseInt(raw, 10, 64)
+		if err != nil || id <= 0 {
+			response.BadRequest(c, "Invalid user_id")
+			return
+		}
+		filter.UserID = id
+	}
+
+	items, paginationResult, err := h.notificationService.ListLogs(c.Request.Context(), filter, pagination.PaginationParams{
+		Page:     page,
+		PageSize: pageSize,
+	})
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Paginated(c, items, paginationResult.Total, page, pageSize)
+}
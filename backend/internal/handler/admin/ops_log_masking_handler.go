@@ -0,0 +1,45 @@
+package admin
+
+import (
+	"github.com/Wei-Shaw/sub2api/internal/pkg/response"
+	"github.com/gin-gonic/gin"
+)
+
+// opsLogMaskingPreviewMaxChars 预览文本的最大长度（字节）
+const opsLogMaskingPreviewMaxChars = 64 * 1024
+
+type opsLogMaskingPreviewRequest struct {
+	// Profile 配置档名称，为空时使用默认配置档
+	Profile string `json:"profile"`
+	Text    string `json:"text" binding:"required"`
+}
+
+// ListLogMaskingProfiles returns the configured log masking profiles.
+// GET /api/v1/admin/ops/log-masking/profiles
+func (h *OpsHandler) ListLogMaskingProfiles(c *gin.Context) {
+	names, defaultProfile := h.opsService.LogMasking().Names()
+	response.Success(c, gin.H{
+		"profiles":        names,
+		"default_profile": defaultProfile,
+	})
+}
+
+// PreviewLogMasking shows what the given text would look like in logs after masking.
+// POST /api/v1/admin/ops/log-masking/preview
+func (h *OpsHandler) PreviewLogMasking(c *gin.Context) {
+	var req opsLogMaskingPreviewRequest
+	if err := c.ShouldBindJSON(&req); err != nil {
+		response.BadRequest(c, "Invalid request: "+err.Error())
+		return
+	}
+	if len(req.Text) > opsLogMaskingPreviewMaxChars {
+		response.BadRequest(c, "text is too long")
+		return
+	}
+	preview, err := h.opsService.LogMasking().Preview(req.Profile, req.Text)
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, preview)
+}
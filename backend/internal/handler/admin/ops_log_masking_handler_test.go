@@ -0,0 +1,67 @@
+package admin
+
+import (
+	"bytes"
+	"encoding/json"
+	"net/http"
+	"net/http/httptest"
+	"testing"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/Wei-Shaw/sub2api/internal/service"
+	"github.com/gin-gonic/gin"
+)
+
+func newOpsLogMaskingTestRouter(t *testing.T, cfg config.LogMaskingConfig) *gin.Engine {
+	t.Helper()
+	profiles, err := service.NewLogMaskingProfiles(cfg)
+	if err != nil {
+		t.Fatalf("NewLogMaskingProfiles: %v", err)
+	}
+	gin.SetMode(gin.TestMode)
+	r := gin.New()
+	h := NewOpsHandler(service.NewOpsService(nil, nil, nil, nil, nil, nil, nil, nil, nil, nil, nil, profiles))
+	r.GET("/log-masking/profiles", h.ListLogMaskingProfiles)
+	r.POST("/log-masking/preview", h.PreviewLogMasking)
+	return r
+}
+
+func TestOpsLogMaskingHandler_Preview(t *testing.T) {
+	r := newOpsLogMaskingTestRouter(t, config.LogMaskingConfig{
+		DefaultProfile: "pii",
+		Profiles: map[string]config.LogMaskingProfileConfig{
+			"pii": {CreditCards: true},
+		},
+	})
+
+	w := httptest.NewRecorder()
+	body := []byte(`{"text":"card 4111 1111 1111 1111"}`)
+	r.ServeHTTP(w, httptest.NewRequest(http.MethodPost, "/log-masking/preview", bytes.NewReader(body)))
+	if w.Code != http.StatusOK {
+		t.Fatalf("status=%d, want 200, body=%s", w.Code, w.Body.String())
+	}
+	var resp responseEnvelope
+	if err := json.Unmarshal(w.Body.Bytes(), &resp); err != nil {
+		t.Fatalf("unmarshal response: %v", err)
+	}
+	var preview service.LogMaskingPreview
+	if err := json.Unmarshal(resp.Data, &preview); err != nil {
+		t.Fatalf("unmarshal preview: %v", err)
+	}
+	if preview.Profile != "pii" || preview.Masked != "card [CREDIT_CARD]" || preview.Matches["credit_card"] != 1 {
+		t.Fatalf("unexpected preview: %+v", preview)
+	}
+
+	w = httptest.NewRecorder()
+	body = []byte(`{"profile":"missing","text":"x"}`)
+	r.ServeHTTP(w, httptest.NewRequest(http.MethodPost, "/log-masking/preview", bytes.NewReader(body)))
+	if w.Code != http.StatusNotFound {
+		t.Fatalf("status=%d, want 404", w.Code)
+	}
+
+	w = httptest.NewRecorder()
+	r.ServeHTTP(w, httptest.NewRequest(http.MethodGet, "/log-masking/profiles", nil))
+	if w.Code != http.StatusOK || !bytes.Contains(w.Body.Bytes(), []byte(`"default_profile":"pii"`)) {
+		t.Fatalf("status=%d body=%s", w.Code, w.Body.String())
+	}
+}
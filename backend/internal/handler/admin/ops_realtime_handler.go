@@ -100,6 +100,50 @@ func (h *OpsHandler) GetUserConcurrencyStats(c *gin.Context) {
 	response.Success(c, payload)
 }
 
+// GetFairShareStats returns per-user fair-share scheduling stats for account wait queues.
+// GET /api/v1/admin/ops/fair-share
+//
+// Stats are collected in-process; with multiple instances each instance reports its own queues.
+func (h *OpsHandler) GetFairShareStats(c *gin.Context) {
+	if h.opsService == nil {
+		response.Error(c, http.StatusServiceUnavailable, "Ops service not available")
+		return
+	}
+
+	users, err := h.opsService.GetFairShareStats(c.Request.Context())
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, gin.H{
+		"users":     users,
+		"timestamp": time.Now().UTC(),
+	})
+}
+
+// GetUpstreamPoolStats returns upstream HTTP client pool stats (clients, in-flight requests,
+// connection reuse, dials and DNS cache hits), broken down by platform.
+// GET /api/v1/admin/ops/upstream-pool
+//
+// Stats are collected in-process; with multiple instances each instance reports its own pools.
+func (h *OpsHandler) GetUpstreamPoolStats(c *gin.Context) {
+	if h.opsService == nil {
+		response.Error(c, http.StatusServiceUnavailable, "Ops service not available")
+		return
+	}
+
+	stats, ok, err := h.opsService.GetUpstreamPoolStats(c.Request.Context())
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, gin.H{
+		"enabled":   ok,
+		"pool":      stats,
+		"timestamp": time.Now().UTC(),
+	})
+}
+
 // GetAccountAvailability returns account availability statistics.
 // GET /api/v1/admin/ops/account-availability
 //
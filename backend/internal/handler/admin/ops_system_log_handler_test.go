@@ -47,7 +47,7 @@ func TestOpsSystemLogHandler_ListUnavailable(t *testing.T) {
 }
 
 func TestOpsSystemLogHandler_ListInvalidUserID(t *testing.T) {
-	svc := service.NewOpsService(nil, nil, nil, nil, nil, nil, nil, nil, nil, nil, nil)
+	svc := service.NewOpsService(nil, nil, nil, nil, nil, nil, nil, nil, nil, nil, nil, nil)
 	h := NewOpsHandler(svc)
 	r := newOpsSystemLogTestRouter(h, false)
 
@@ -60,7 +60,7 @@ func TestOpsSystemLogHandler_ListInvalidUserID(t *testing.T) {
 }
 
 func TestOpsSystemLogHandler_ListInvalidAccountID(t *testing.T) {
-	svc := service.NewOpsService(nil, nil, nil, nil, nil, nil, nil, nil, nil, nil, nil)
+	svc := service.NewOpsService(nil, nil, nil, nil, nil, nil, nil, nil, nil, nil, nil, nil)
 	h := NewOpsHandler(svc)
 	r := newOpsSystemLogTestRouter(h, false)
 
@@ -75,7 +75,7 @@ func TestOpsSystemLogHandler_ListInvalidAccountID(t *testing.T) {
 func TestOpsSystemLogHandler_ListMonitoringDisabled(t *testing.T) {
 	svc := service.NewOpsService(nil, nil, &config.Config{
 		Ops: config.OpsConfig{Enabled: false},
-	}, nil, nil, nil, nil, nil, nil, nil, nil)
+	}, nil, nil, nil, nil, nil, nil, nil, nil, nil)
 	h := NewOpsHandler(svc)
 	r := newOpsSystemLogTestRouter(h, false)
 
@@ -88,7 +88,7 @@ func TestOpsSystemLogHandler_ListMonitoringDisabled(t *testing.T) {
 }
 
 func TestOpsSystemLogHandler_ListSuccess(t *testing.T) {
-	svc := service.NewOpsService(nil, nil, nil, nil, nil, nil, nil, nil, nil, nil, nil)
+	svc := service.NewOpsService(nil, nil, nil, nil, nil, nil, nil, nil, nil, nil, nil, nil)
 	h := NewOpsHandler(svc)
 	r := newOpsSystemLogTestRouter(h, false)
 
@@ -109,7 +109,7 @@ func TestOpsSystemLogHandler_ListSuccess(t *testing.T) {
 }
 
 func TestOpsSystemLogHandler_CleanupUnauthorized(t *testing.T) {
-	svc := service.NewOpsService(nil, nil, nil, nil, nil, nil, nil, nil, nil, nil, nil)
+	svc := service.NewOpsService(nil, nil, nil, nil, nil, nil, nil, nil, nil, nil, nil, nil)
 	h := NewOpsHandler(svc)
 	r := newOpsSystemLogTestRouter(h, false)
 
@@ -123,7 +123,7 @@ func TestOpsSystemLogHandler_CleanupUnauthorized(t *testing.T) {
 }
 
 func TestOpsSystemLogHandler_CleanupInvalidPayload(t *testing.T) {
-	svc := service.NewOpsService(nil, nil, nil, nil, nil, nil, nil, nil, nil, nil, nil)
+	svc := service.NewOpsService(nil, nil, nil, nil, nil, nil, nil, nil, nil, nil, nil, nil)
 	h := NewOpsHandler(svc)
 	r := newOpsSystemLogTestRouter(h, true)
 
@@ -137,7 +137,7 @@ func TestOpsSystemLogHandler_CleanupInvalidPayload(t *testing.T) {
 }
 
 func TestOpsSystemLogHandler_CleanupInvalidTime(t *testing.T) {
-	svc := service.NewOpsService(nil, nil, nil, nil, nil, nil, nil, nil, nil, nil, nil)
+	svc := service.NewOpsService(nil, nil, nil, nil, nil, nil, nil, nil, nil, nil, nil, nil)
 	h := NewOpsHandler(svc)
 	r := newOpsSystemLogTestRouter(h, true)
 
@@ -151,7 +151,7 @@ func TestOpsSystemLogHandler_CleanupInvalidTime(t *testing.T) {
 }
 
 func TestOpsSystemLogHandler_CleanupInvalidEndTime(t *testing.T) {
-	svc := service.NewOpsService(nil, nil, nil, nil, nil, nil, nil, nil, nil, nil, nil)
+	svc := service.NewOpsService(nil, nil, nil, nil, nil, nil, nil, nil, nil, nil, nil, nil)
 	h := NewOpsHandler(svc)
 	r := newOpsSystemLogTestRouter(h, true)
 
@@ -165,7 +165,7 @@ func TestOpsSystemLogHandler_CleanupInvalidEndTime(t *testing.T) {
 }
 
 func TestOpsSystemLogHandler_CleanupServiceUnavailable(t *testing.T) {
-	svc := service.NewOpsService(nil, nil, nil, nil, nil, nil, nil, nil, nil, nil, nil)
+	svc := service.NewOpsService(nil, nil, nil, nil, nil, nil, nil, nil, nil, nil, nil, nil)
 	h := NewOpsHandler(svc)
 	r := newOpsSystemLogTestRouter(h, true)
 
@@ -181,7 +181,7 @@ func TestOpsSystemLogHandler_CleanupServiceUnavailable(t *testing.T) {
 func TestOpsSystemLogHandler_CleanupMonitoringDisabled(t *testing.T) {
 	svc := service.NewOpsService(nil, nil, &config.Config{
 		Ops: config.OpsConfig{Enabled: false},
-	}, nil, nil, nil, nil, nil, nil, nil, nil)
+	}, nil, nil, nil, nil, nil, nil, nil, nil, nil)
 	h := NewOpsHandler(svc)
 	r := newOpsSystemLogTestRouter(h, true)
 
@@ -195,8 +195,8 @@ func TestOpsSystemLogHandler_CleanupMonitoringDisabled(t *testing.T) {
 }
 
 func TestOpsSystemLogHandler_Health(t *testing.T) {
-	sink := service.NewOpsSystemLogSink(nil)
-	svc := service.NewOpsService(nil, nil, nil, nil, nil, nil, nil, nil, nil, nil, sink)
+	sink := service.NewOpsSystemLogSink(nil, nil)
+	svc := service.NewOpsService(nil, nil, nil, nil, nil, nil, nil, nil, nil, nil, sink, nil)
 	h := NewOpsHandler(svc)
 	r := newOpsSystemLogTestRouter(h, false)
 
@@ -221,7 +221,7 @@ func TestOpsSystemLogHandler_HealthUnavailableAndMonitoringDisabled(t *testing.T
 
 	svc := service.NewOpsService(nil, nil, &config.Config{
 		Ops: config.OpsConfig{Enabled: false},
-	}, nil, nil, nil, nil, nil, nil, nil, nil)
+	}, nil, nil, nil, nil, nil, nil, nil, nil, nil)
 	h = NewOpsHandler(svc)
 	r = newOpsSystemLogTestRouter(h, false)
 	w = httptest.NewRecorder()
@@ -0,0 +1,66 @@
+package admin
+
+import (
+	"strconv"
+
+	"github.com/Wei-Shaw/sub2api/internal/pkg/response"
+	middleware2 "github.com/Wei-Shaw/sub2api/internal/server/middleware"
+	"github.com/Wei-Shaw/sub2api/internal/service"
+	"github.com/gin-gonic/gin"
+)
+
+// PrepaidCreditHandler API Key 预付费额度管理（用户余额充值见 UserHandler.UpdateBalance）
+type PrepaidCreditHandler struct {
+	prepaidCreditService *service.PrepaidCreditService
+}
+
+// NewPrepaidCreditHandler 创建预付费额度处理器
+func NewPrepaidCreditHandler(prepaidCreditService *service.PrepaidCreditService) *PrepaidCreditHandler {
+	return &PrepaidCreditHandler{prepaidCreditService: prepaidCreditService}
+}
+
+// TopUpCreditRequest API Key 额度充值请求
+type TopUpCreditRequest struct {
+	Amount float64 `json:"amount" binding:"required,gt=0"`
+}
+
+// Get 获取 API Key 的额度、已用、预占与可用额度
+// GET /api/v1/admin/api-keys/:id/credits
+func (h *PrepaidCreditHandler) Get(c *gin.Context) {
+	apiKeyID, err := strconv.ParseInt(c.Param("id"), 10, 64)
+	if err != nil || apiKeyID <= 0 {
+		response.BadRequest(c, "Invalid API key ID")
+		return
+	}
+	credit, err := h.prepaidCreditService.GetAPIKeyCredit(c.Request.Context(), apiKeyID)
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, credit)
+}
+
+// TopUp 为 API Key 充值预付费额度
+// POST /api/v1/admin/api-keys/:id/credits
+func (h *PrepaidCreditHandler) TopUp(c *gin.Context) {
+	apiKeyID, err := strconv.ParseInt(c.Param("id"), 10, 64)
+	if err != nil || apiKeyID <= 0 {
+		response.BadRequest(c, "Invalid API key ID")
+		return
+	}
+	var req TopUpCreditRequest
+	if err := c.ShouldBindJSON(&req); err != nil {
+		response.BadRequest(c, "Invalid request: "+err.Error())
+		return
+	}
+	var operatorID int64
+	if subject, ok := middleware2.GetAuthSubjectFromContext(c); ok {
+		operatorID = subject.UserID
+	}
+	credit, err := h.prepaidCreditService.TopUpAPIKey(c.Request.Context(), apiKeyID, req.Amount, operatorID)
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, credit)
+}
@@ -0,0 +1,79 @@
+package admin
+
+import (
+	"strconv"
+	"strings"
+
+	"github.com/Wei-Shaw/sub2api/internal/pkg/response"
+	"github.com/Wei-Shaw/sub2api/internal/service"
+	"github.com/gin-gonic/gin"
+)
+
+// RequestLogHandler 网关请求日志检索
+type RequestLogHandler struct {
+	requestLogService *service.RequestLogService
+}
+
+// NewRequestLogHandler 创建请求日志处理器
+func NewRequestLogHandler(requestLogService *service.RequestLogService) *RequestLogHandler {
+	return &RequestLogHandler{requestLogService: requestLogService}
+}
+
+// Search 按 Key、模型、状态码、错误分类、Trace ID 与时间范围检索请求日志（仅保留期内）。
+// 查询参数：api_key_id / user_id / account_id、model、status_codes（逗号分隔）、
+// errors_only、error_class、trace_id、start_time / end_time 或 time_range（默认 1h）
+//
+// GET /api/v1/admin/request-logs
+func (h *RequestLogHandler) Search(c *gin.Context) {
+	page, pageSize := response.ParsePagination(c)
+	filter := service.RequestLogFilter{
+		Model:      strings.TrimSpace(c.Query("model")),
+		ErrorClass: strings.TrimSpace(c.Query("error_class")),
+		TraceID:    strings.TrimSpace(c.Query("trace_id")),
+		Page:       page,
+		PageSize:   pageSize,
+	}
+
+	var err error
+	if filter.StartTime, filter.EndTime, err = parseOpsTimeRange(c, "1h"); err != nil {
+		response.BadRequest(c, err.Error())
+		return
+	}
+	for name, dst := range map[string]**int64{
+		"api_key_id": &filter.APIKeyID,
+		"user_id":    &filter.UserID,
+		"account_id": &filter.AccountID,
+	} {
+		raw := strings.TrimSpace(c.Query(name))
+		if raw == "" {
+			continue
+		}
+		id, err := strconv.ParseInt(raw, 10, 64)
+		if err != nil || id <= 0 {
+			response.BadRequest(c, "Invalid "+name)
+			return
+		}
+		*dst = &id
+	}
+	for _, raw := range splitLiveUsageList(c.Query("status_codes")) {
+		code, err := strconv.Atoi(raw)
+		if err != nil || code < 100 || code > 599 {
+			response.BadRequest(c, "Invalid status_codes")
+			return
+		}
+		filter.StatusCodes = append(filter.StatusCodes, code)
+	}
+	if raw := strings.TrimSpace(c.Query("errors_only")); raw != "" {
+		if filter.ErrorsOnly, err = strconv.ParseBool(raw); err != nil {
+			response.BadRequest(c, "Invalid errors_only")
+			return
+		}
+	}
+
+	items, total, err := h.requestLogService.Search(c.Request.Context(), filter)
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Paginated(c, items, total, page, pageSize)
+}
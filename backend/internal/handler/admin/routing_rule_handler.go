@@ -0,0 +1,207 @@
+package admin
+
+import (
+	"strconv"
+
+	"github.com/Wei-Shaw/sub2api/internal/pkg/response"
+	"github.com/Wei-Shaw/sub2api/internal/service"
+	"github.com/gin-gonic/gin"
+)
+
+// RoutingRuleHandler 处理声明式路由规则与 API Key 标签的 HTTP 请求
+type RoutingRuleHandler struct {
+	service *service.RoutingRuleService
+}
+
+// NewRoutingRuleHandler 创建路由规则处理器
+func NewRoutingRuleHandler(service *service.RoutingRuleService) *RoutingRuleHandler {
+	return &RoutingRuleHandler{service: service}
+}
+
+// RoutingRuleRequest 创建 / 更新路由规则请求
+type RoutingRuleRequest struct {
+	Name             string                    `json:"name" binding:"required"`
+	Description      string                    `json:"description"`
+	Priority         int                       `json:"priority"`
+	Enabled          *bool                     `json:"enabled"`
+	Conditions       service.RoutingConditions `json:"conditions"`
+	TargetGroupID    *int64                    `json:"target_group_id"`
+	FallbackGroupIDs []int64                   `json:"fallback_group_ids"`
+	ParamOverrides   map[string]any            `json:"param_overrides"`
+}
+
+// APIKeyTagsRequest 设置 API Key 标签请求
+type APIKeyTagsRequest struct {
+	Tags []string `json:"tags"`
+}
+
+// RoutingEvaluateResponse 规则试算结果
+type RoutingEvaluateResponse struct {
+	Matched  bool                     `json:"matched"`
+	Decision *service.RoutingDecision `json:"decision,omitempty"`
+}
+
+// List 获取所有路由规则（按匹配顺序）
+// GET /api/v1/admin/routing-rules
+func (h *RoutingRuleHandler) List(c *gin.Context) {
+	rules, err := h.service.List(c.Request.Context())
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, rules)
+}
+
+// GetByID 根据 ID 获取路由规则
+// GET /api/v1/admin/routing-rules/:id
+func (h *RoutingRuleHandler) GetByID(c *gin.Context) {
+	id, ok := parseRoutingID(c, "Invalid routing rule ID")
+	if !ok {
+		return
+	}
+
+	rule, err := h.service.GetByID(c.Request.Context(), id)
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, rule)
+}
+
+// Create 创建路由规则
+// POST /api/v1/admin/routing-rules
+func (h *RoutingRuleHandler) Create(c *gin.Context) {
+	var req RoutingRuleRequest
+	if err := c.ShouldBindJSON(&req); err != nil {
+		response.BadRequest(c, "Invalid request: "+err.Error())
+		return
+	}
+
+	rule := &service.RoutingRule{}
+	applyRoutingRuleRequest(rule, &req)
+	if err := h.service.Create(c.Request.Context(), rule); err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Created(c, rule)
+}
+
+// Update 更新路由规则（整体替换）
+// PUT /api/v1/admin/routing-rules/:id
+func (h *RoutingRuleHandler) Update(c *gin.Context) {
+	id, ok := parseRoutingID(c, "Invalid routing rule ID")
+	if !ok {
+		return
+	}
+
+	var req RoutingRuleRequest
+	if err := c.ShouldBindJSON(&req); err != nil {
+		response.BadRequest(c, "Invalid request: "+err.Error())
+		return
+	}
+
+	rule, err := h.service.GetByID(c.Request.Context(), id)
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	applyRoutingRuleRequest(rule, &req)
+	if err := h.service.Update(c.Request.Context(), rule); err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, rule)
+}
+
+// Delete 删除路由规则
+// DELETE /api/v1/admin/routing-rules/:id
+func (h *RoutingRuleHandler) Delete(c *gin.Context) {
+	id, ok := parseRoutingID(c, "Invalid routing rule ID")
+	if !ok {
+		return
+	}
+
+	if err := h.service.Delete(c.Request.Context(), id); err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, gin.H{"message": "Routing rule deleted successfully"})
+}
+
+// Evaluate 按给定的分组、标签、模型、提示词长度、请求头与时间试算命中的规则和最终分组
+// POST /api/v1/admin/routing-rules/evaluate
+func (h *RoutingRuleHandler) Evaluate(c *gin.Context) {
+	var req service.RoutingEvaluateInput
+	if err := c.ShouldBindJSON(&req); err != nil {
+		response.BadRequest(c, "Invalid request: "+err.Error())
+		return
+	}
+
+	decision, err := h.service.Evaluate(c.Request.Context(), &req)
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, RoutingEvaluateResponse{Matched: decision != nil, Decision: decision})
+}
+
+// GetKeyTags 获取 API Key 标签
+// GET /api/v1/admin/api-keys/:id/tags
+func (h *RoutingRuleHandler) GetKeyTags(c *gin.Context) {
+	id, ok := parseRoutingID(c, "Invalid API key ID")
+	if !ok {
+		return
+	}
+
+	tags, err := h.service.GetKeyTags(c.Request.Context(), id)
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, gin.H{"tags": tags})
+}
+
+// UpdateKeyTags 整体替换 API Key 标签
+// PUT /api/v1/admin/api-keys/:id/tags
+func (h *RoutingRuleHandler) UpdateKeyTags(c *gin.Context) {
+	id, ok := parseRoutingID(c, "Invalid API key ID")
+	if !ok {
+		return
+	}
+
+	var req APIKeyTagsRequest
+	if err := c.ShouldBindJSON(&req); err != nil {
+		response.BadRequest(c, "Invalid request: "+err.Error())
+		return
+	}
+
+	tags, err := h.service.SetKeyTags(c.Request.Context(), id, req.Tags)
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, gin.H{"tags": tags})
+}
+
+func applyRoutingRuleRequest(rule *service.RoutingRule, req *RoutingRuleRequest) {
+	rule.Name = req.Name
+	rule.Description = req.Description
+	rule.Priority = req.Priority
+	rule.Conditions = req.Conditions
+	rule.TargetGroupID = req.TargetGroupID
+	rule.FallbackGroupIDs = req.FallbackGroupIDs
+	rule.ParamOverrides = req.ParamOverrides
+	rule.Enabled = true
+	if req.Enabled != nil {
+		rule.Enabled = *req.Enabled
+	}
+}
+
+func parseRoutingID(c *gin.Context, message string) (int64, bool) {
+	id, err := strconv.ParseInt(c.Param("id"), 10, 64)
+	if err != nil || id <= 0 {
+		response.BadRequest(c, message)
+		return 0, false
+	}
+	return id, true
+}
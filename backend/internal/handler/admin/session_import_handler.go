@@ -0,0 +1,72 @@
+package admin
+
+import (
+	"github.com/Wei-Shaw/sub2api/internal/handler/dto"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/response"
+	"github.com/Wei-Shaw/sub2api/internal/service"
+
+	"github.com/gin-gonic/gin"
+)
+
+// SessionImportHandler handles importing web session accounts from browser exports
+type SessionImportHandler struct {
+	sessionImportService *service.SessionImportService
+}
+
+// NewSessionImportHandler creates a new session import handler
+func NewSessionImportHandler(sessionImportService *service.SessionImportService) *SessionImportHandler {
+	return &SessionImportHandler{sessionImportService: sessionImportService}
+}
+
+// SessionImportRequest represents a browser cookie / HAR import request
+type SessionImportRequest struct {
+	Platform    string  `json:"platform" binding:"omitempty,oneof=anthropic openai gemini"`
+	Content     string  `json:"content" binding:"required"`
+	Name        string  `json:"name"`
+	Notes       *string `json:"notes"`
+	ProxyID     *int64  `json:"proxy_id"`
+	GroupIDs    []int64 `json:"group_ids"`
+	Concurrency int     `json:"concurrency"`
+	Priority    int     `json:"priority"`
+	SkipProbe   bool    `json:"skip_probe"`
+	DryRun      bool    `json:"dry_run"`
+}
+
+// SessionImportResponse represents the import result; credentials are never echoed back
+type SessionImportResponse struct {
+	*service.SessionImportResult
+	Account *dto.Account `json:"account,omitempty"`
+}
+
+// Import parses a browser export, probes the session and creates the account
+// POST /api/v1/admin/accounts/import-session
+func (h *SessionImportHandler) Import(c *gin.Context) {
+	var req SessionImportRequest
+	if err := c.ShouldBindJSON(&req); err != nil {
+		response.BadRequest(c, "Invalid request: "+err.Error())
+		return
+	}
+
+	result, err := h.sessionImportService.Import(c.Request.Context(), &service.SessionImportInput{
+		Platform:    req.Platform,
+		Content:     req.Content,
+		Name:        req.Name,
+		Notes:       req.Notes,
+		ProxyID:     req.ProxyID,
+		GroupIDs:    req.GroupIDs,
+		Concurrency: req.Concurrency,
+		Priority:    req.Priority,
+		SkipProbe:   req.SkipProbe,
+		DryRun:      req.DryRun,
+	})
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+
+	resp := SessionImportResponse{SessionImportResult: result}
+	if result.Account != nil {
+		resp.Account = dto.AccountFromService(result.Account)
+	}
+	response.Success(c, resp)
+}
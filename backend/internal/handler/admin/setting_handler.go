@@ -20,15 +20,17 @@ type SettingHandler struct {
 	emailService     *service.EmailService
 	turnstileService *service.TurnstileService
 	opsService       *service.OpsService
+	eventExport      *service.EventExportService
 }
 
 // NewSettingHandler 创建系统设置处理器
-func NewSettingHandler(settingService *service.SettingService, emailService *service.EmailService, turnstileService *service.TurnstileService, opsService *service.OpsService) *SettingHandler {
+func NewSettingHandler(settingService *service.SettingService, emailService *service.EmailService, turnstileService *service.TurnstileService, opsService *service.OpsService, eventExport *service.EventExportService) *SettingHandler {
 	return &SettingHandler{
 		settingService:   settingService,
 		emailService:     emailService,
 		turnstileService: turnstileService,
 		opsService:       opsService,
+		eventExport:      eventExport,
 	}
 }
 
@@ -434,12 +436,20 @@ func (h *SettingHandler) auditSettingsUpdate(c *gin.Context, before *service.Sys
 
 	subject, _ := middleware.GetAuthSubjectFromContext(c)
 	role, _ := middleware.GetUserRoleFromContext(c)
+	now := time.Now().UTC()
 	log.Printf("AUDIT: settings updated at=%s user_id=%d role=%s changed=%v",
-		time.Now().UTC().Format(time.RFC3339),
+		now.Format(time.RFC3339),
 		subject.UserID,
 		role,
 		changed,
 	)
+	h.eventExport.RecordAudit(c.Request.Context(), &service.EventExportAudit{
+		Action:      "settings.update",
+		ActorUserID: subject.UserID,
+		ActorRole:   role,
+		Changes:     changed,
+		At:          now,
+	})
 }
 
 func diffSettings(before *service.SystemSettings, after *service.SystemSettings, req UpdateSettingsRequest) []string {
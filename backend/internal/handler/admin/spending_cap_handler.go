@@ -0,0 +1,144 @@
+package admin
+
+import (
+	"strconv"
+	"strings"
+
+	"github.com/Wei-Shaw/sub2api/internal/pkg/pagination"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/response"
+	middleware2 "github.com/Wei-Shaw/sub2api/internal/server/middleware"
+	"github.com/Wei-Shaw/sub2api/internal/service"
+	"github.com/gin-gonic/gin"
+)
+
+// SpendingCapHandler API Key / 用户 / 团队消费硬上限管理
+type SpendingCapHandler struct {
+	spendingCapService *service.SpendingCapService
+}
+
+// NewSpendingCapHandler 创建消费上限处理器
+func NewSpendingCapHandler(spendingCapService *service.SpendingCapService) *SpendingCapHandler {
+	return &SpendingCapHandler{spendingCapService: spendingCapService}
+}
+
+// UpsertSpendingCapRequest 设置消费上限请求
+type UpsertSpendingCapRequest struct {
+	Period   string  `json:"period" binding:"required,oneof=daily weekly monthly"`
+	LimitUSD float64 `json:"limit_usd" binding:"required,gt=0"`
+	// DryRun 演练模式：只记录本应拒绝的请求，不实际拒绝或暂停
+	DryRun bool `json:"dry_run"`
+}
+
+// List 分页列出消费上限（可按作用范围、是否暂停过滤）
+// GET /api/v1/admin/spending-caps
+func (h *SpendingCapHandler) List(c *gin.Context) {
+	page, pageSize := response.ParsePagination(c)
+	scopeType := strings.TrimSpace(c.Query("scope_type"))
+	suspendedOnly, _ := strconv.ParseBool(c.Query("suspended"))
+
+	items, paginationResult, err := h.spendingCapService.List(c.Request.Context(), scopeType, suspendedOnly, pagination.PaginationParams{
+		Page:     page,
+		PageSize: pageSize,
+	})
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Paginated(c, items, paginationResult.Total, page, pageSize)
+}
+
+// Get 获取消费上限及当前周期消费
+// GET /api/v1/admin/spending-caps/:scope/:id
+func (h *SpendingCapHandler) Get(c *gin.Context) {
+	scopeType, scopeID, ok := parseSpendingCapScope(c)
+	if !ok {
+		return
+	}
+	status, err := h.spendingCapService.Get(c.Request.Context(), scopeType, scopeID)
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, status)
+}
+
+// Upsert 创建或更新消费上限（同时清除暂停状态）；dry_run=true 时以演练模式评估新上限
+// PUT /api/v1/admin/spending-caps/:scope/:id
+func (h *SpendingCapHandler) Upsert(c *gin.Context) {
+	scopeType, scopeID, ok := parseSpendingCapScope(c)
+	if !ok {
+		return
+	}
+	var req UpsertSpendingCapRequest
+	if err := c.ShouldBindJSON(&req); err != nil {
+		response.BadRequest(c, "Invalid request: "+err.Error())
+		return
+	}
+	spendingCap := &service.SpendingCap{
+		ScopeType: scopeType,
+		ScopeID:   scopeID,
+		Period:    req.Period,
+		LimitUSD:  req.LimitUSD,
+		DryRun:    req.DryRun,
+	}
+	if subject, ok := middleware2.GetAuthSubjectFromContext(c); ok && subject.UserID > 0 {
+		id := subject.UserID
+		spendingCap.UpdatedBy = &id
+	}
+	status, err := h.spendingCapService.Upsert(c.Request.Context(), spendingCap)
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, status)
+}
+
+// Delete 删除消费上限
+// DELETE /api/v1/admin/spending-caps/:scope/:id
+func (h *SpendingCapHandler) Delete(c *gin.Context) {
+	scopeType, scopeID, ok := parseSpendingCapScope(c)
+	if !ok {
+		return
+	}
+	if err := h.spendingCapService.Delete(c.Request.Context(), scopeType, scopeID); err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, gin.H{"message": "Spending cap deleted successfully"})
+}
+
+// Lift 解除当前周期的暂停，本周期内不再强制上限
+// POST /api/v1/admin/spending-caps/:scope/:id/lift
+func (h *SpendingCapHandler) Lift(c *gin.Context) {
+	scopeType, scopeID, ok := parseSpendingCapScope(c)
+	if !ok {
+		return
+	}
+	var operatorID int64
+	if subject, ok := middleware2.GetAuthSubjectFromContext(c); ok {
+		operatorID = subject.UserID
+	}
+	status, err := h.spendingCapService.Lift(c.Request.Context(), scopeType, scopeID, operatorID)
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, status)
+}
+
+func parseSpendingCapScope(c *gin.Context) (string, int64, bool) {
+	scopeType := c.Param("scope")
+	switch scopeType {
+	case service.SpendingCapScopeAPIKey, service.SpendingCapScopeUser,
+		service.SpendingCapScopeTeam, service.SpendingCapScopeTeamMember:
+	default:
+		response.BadRequest(c, "Invalid scope, must be api_key, user, team or team_member")
+		return "", 0, false
+	}
+	scopeID, err := strconv.ParseInt(c.Param("id"), 10, 64)
+	if err != nil || scopeID <= 0 {
+		response.BadRequest(c, "Invalid scope ID")
+		return "", 0, false
+	}
+	return scopeType, scopeID, true
+}
@@ -0,0 +1,144 @@
+package admin
+
+import (
+	"strconv"
+
+	"github.com/Wei-Shaw/sub2api/internal/handler/dto"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/response"
+	middleware2 "github.com/Wei-Shaw/sub2api/internal/server/middleware"
+	"github.com/Wei-Shaw/sub2api/internal/service"
+	"github.com/gin-gonic/gin"
+)
+
+// StaffHandler 后台账号（超级管理员 / 财务 / 账号运维 / 只读审计）管理
+type StaffHandler struct {
+	adminUserService *service.AdminUserService
+}
+
+// NewStaffHandler 创建后台账号处理器
+func NewStaffHandler(adminUserService *service.AdminUserService) *StaffHandler {
+	return &StaffHandler{adminUserService: adminUserService}
+}
+
+// CreateStaffRequest 创建后台账号请求；password 为临时密码，首次登录后必须修改
+type CreateStaffRequest struct {
+	Email    string `json:"email" binding:"required,email"`
+	Username string `json:"username"`
+	Password string `json:"password" binding:"required"`
+	Role     string `json:"role" binding:"required,oneof=admin billing accounts auditor"`
+}
+
+// UpdateStaffRequest 修改后台账号请求
+type UpdateStaffRequest struct {
+	Username *string `json:"username"`
+	Role     *string `json:"role" binding:"omitempty,oneof=admin billing accounts auditor"`
+	Status   *string `json:"status" binding:"omitempty,oneof=active disabled"`
+}
+
+// ResetStaffPasswordRequest 重置后台账号密码请求
+type ResetStaffPasswordRequest struct {
+	Password string `json:"password" binding:"required"`
+}
+
+// List 列出后台账号及其密码状态
+// GET /api/v1/admin/staff
+func (h *StaffHandler) List(c *gin.Context) {
+	items, err := h.adminUserService.List(c.Request.Context())
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, items)
+}
+
+// Create 创建后台账号
+// POST /api/v1/admin/staff
+func (h *StaffHandler) Create(c *gin.Context) {
+	var req CreateStaffRequest
+	if err := c.ShouldBindJSON(&req); err != nil {
+		response.BadRequest(c, "Invalid request: "+err.Error())
+		return
+	}
+	user, err := h.adminUserService.Create(c.Request.Context(), &service.CreateAdminUserInput{
+		Email:    req.Email,
+		Username: req.Username,
+		Password: req.Password,
+		Role:     req.Role,
+	})
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Created(c, dto.UserFromServiceAdmin(user))
+}
+
+// Update 修改后台账号的用户名、角色或状态
+// PUT /api/v1/admin/staff/:id
+func (h *StaffHandler) Update(c *gin.Context) {
+	subject, id, ok := parseStaffTarget(c)
+	if !ok {
+		return
+	}
+	var req UpdateStaffRequest
+	if err := c.ShouldBindJSON(&req); err != nil {
+		response.BadRequest(c, "Invalid request: "+err.Error())
+		return
+	}
+	user, err := h.adminUserService.Update(c.Request.Context(), subject.UserID, id, &service.UpdateAdminUserInput{
+		Username: req.Username,
+		Role:     req.Role,
+		Status:   req.Status,
+	})
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, dto.UserFromServiceAdmin(user))
+}
+
+// ResetPassword 为后台账号设置临时密码，旧登录态立即失效
+// POST /api/v1/admin/staff/:id/reset-password
+func (h *StaffHandler) ResetPassword(c *gin.Context) {
+	subject, id, ok := parseStaffTarget(c)
+	if !ok {
+		return
+	}
+	var req ResetStaffPasswordRequest
+	if err := c.ShouldBindJSON(&req); err != nil {
+		response.BadRequest(c, "Invalid request: "+err.Error())
+		return
+	}
+	if err := h.adminUserService.ResetPassword(c.Request.Context(), subject.UserID, id, req.Password); err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, gin.H{"message": "Password reset, the user must change it on next sign-in"})
+}
+
+// Revoke 收回后台权限，账号保留为普通用户
+// DELETE /api/v1/admin/staff/:id
+func (h *StaffHandler) Revoke(c *gin.Context) {
+	subject, id, ok := parseStaffTarget(c)
+	if !ok {
+		return
+	}
+	if err := h.adminUserService.Revoke(c.Request.Context(), subject.UserID, id); err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, gin.H{"message": "Admin access revoked"})
+}
+
+func parseStaffTarget(c *gin.Context) (middleware2.AuthSubject, int64, bool) {
+	subject, ok := middleware2.GetAuthSubjectFromContext(c)
+	if !ok {
+		response.Unauthorized(c, "User not found in context")
+		return subject, 0, false
+	}
+	id, err := strconv.ParseInt(c.Param("id"), 10, 64)
+	if err != nil || id <= 0 {
+		response.BadRequest(c, "Invalid user ID")
+		return subject, 0, false
+	}
+	return subject, id, true
+}
@@ -0,0 +1,122 @@
+package admin
+
+import (
+	"net/http"
+	"path"
+	"strconv"
+	"strings"
+
+	"github.com/Wei-Shaw/sub2api/internal/pkg/pagination"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/response"
+	"github.com/Wei-Shaw/sub2api/internal/service"
+	"github.com/gin-gonic/gin"
+)
+
+// StatementHandler 月度账单查询、手动生成与下载
+type StatementHandler struct {
+	statementService *service.StatementService
+}
+
+// NewStatementHandler 创建月度账单处理器
+func NewStatementHandler(statementService *service.StatementService) *StatementHandler {
+	return &StatementHandler{statementService: statementService}
+}
+
+// GenerateStatementRequest 手动生成账单请求
+type GenerateStatementRequest struct {
+	// Month 账单月份（YYYY-MM）
+	Month string `json:"month" binding:"required"`
+	// UserID 指定用户时同步生成并返回该用户账单，否则在后台生成当月所有用户的账单
+	UserID int64 `json:"user_id" binding:"omitempty,gt=0"`
+}
+
+// List 分页列出账单（可按用户、月份过滤）
+// GET /api/v1/admin/statements
+func (h *StatementHandler) List(c *gin.Context) {
+	page, pageSize := response.ParsePagination(c)
+	var userID int64
+	if v := strings.TrimSpace(c.Query("user_id")); v != "" {
+		id, err := strconv.ParseInt(v, 10, 64)
+		if err != nil || id <= 0 {
+			response.BadRequest(c, "Invalid user_id")
+			return
+		}
+		userID = id
+	}
+
+	items, paginationResult, err := h.statementService.List(c.Request.Context(), userID, c.Query("month"), pagination.PaginationParams{
+		Page:     page,
+		PageSize: pageSize,
+	})
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Paginated(c, items, paginationResult.Total, page, pageSize)
+}
+
+// Generate 手动生成（或重新生成）账单
+// POST /api/v1/admin/statements/generate
+func (h *StatementHandler) Generate(c *gin.Context) {
+	var req GenerateStatementRequest
+	if err := c.ShouldBindJSON(&req); err != nil {
+		response.BadRequest(c, "Invalid request: "+err.Error())
+		return
+	}
+	periodStart, err := service.ParseStatementMonth(req.Month)
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+
+	if req.UserID > 0 {
+		statement, err := h.statementService.Generate(c.Request.Context(), req.UserID, periodStart)
+		if err != nil {
+			response.ErrorFrom(c, err)
+			return
+		}
+		response.Success(c, statement)
+		return
+	}
+	if err := h.statementService.GenerateMonthAsync(periodStart); err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, gin.H{"month": periodStart.Format("2006-01"), "status": "started"})
+}
+
+// Get 获取账单汇总
+// GET /api/v1/admin/statements/:id
+func (h *StatementHandler) Get(c *gin.Context) {
+	id, err := strconv.ParseInt(c.Param("id"), 10, 64)
+	if err != nil {
+		response.BadRequest(c, "Invalid statement ID")
+		return
+	}
+	statement, err := h.statementService.Get(c.Request.Context(), id)
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, statement)
+}
+
+// Download 下载账单文件，format 为 pdf（默认）、csv 或 json
+// GET /api/v1/admin/statements/:id/download?format=pdf
+func (h *StatementHandler) Download(c *gin.Context) {
+	id, err := strconv.ParseInt(c.Param("id"), 10, 64)
+	if err != nil {
+		response.BadRequest(c, "Invalid statement ID")
+		return
+	}
+	key, obj, err := h.statementService.Open(c.Request.Context(), id, c.DefaultQuery("format", service.StatementFormatPDF))
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	defer func() { _ = obj.Body.Close() }()
+
+	c.Header("Content-Disposition", "attachment; filename="+path.Base(path.Dir(key))+"_"+path.Base(key))
+	c.Header("Cache-Control", "no-store")
+	c.DataFromReader(http.StatusOK, obj.Size, obj.ContentType, obj.Body, nil)
+}
@@ -0,0 +1,85 @@
+package admin
+
+import (
+	"strconv"
+
+	"github.com/Wei-Shaw/sub2api/internal/pkg/pagination"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/response"
+	"github.com/Wei-Shaw/sub2api/internal/service"
+	"github.com/gin-gonic/gin"
+)
+
+// TeamHandler 团队 API Key 查看（预算与子额度通过 spending-caps 接口调整）
+type TeamHandler struct {
+	teamService *service.TeamService
+}
+
+// NewTeamHandler 创建团队处理器
+func NewTeamHandler(teamService *service.TeamService) *TeamHandler {
+	return &TeamHandler{teamService: teamService}
+}
+
+// List 分页列出团队，可按 user_id 过滤
+// GET /api/v1/admin/teams
+func (h *TeamHandler) List(c *gin.Context) {
+	page, pageSize := response.ParsePagination(c)
+	var userID int64
+	if raw := c.Query("user_id"); raw != "" {
+		id, err := strconv.ParseInt(raw, 10, 64)
+		if err != nil || id <= 0 {
+			response.BadRequest(c, "Invalid user_id")
+			return
+		}
+		userID = id
+	}
+
+	items, paginationResult, err := h.teamService.List(c.Request.Context(), userID, pagination.PaginationParams{
+		Page:     page,
+		PageSize: pageSize,
+	})
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Paginated(c, items, paginationResult.Total, page, pageSize)
+}
+
+// Get 获取团队成员、预算及子额度
+// GET /api/v1/admin/teams/:id
+func (h *TeamHandler) Get(c *gin.Context) {
+	teamID, ok := parseTeamID(c)
+	if !ok {
+		return
+	}
+	detail, err := h.teamService.Get(c.Request.Context(), 0, teamID)
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, detail)
+}
+
+// Usage 按成员统计团队用量
+// GET /api/v1/admin/teams/:id/usage
+func (h *TeamHandler) Usage(c *gin.Context) {
+	teamID, ok := parseTeamID(c)
+	if !ok {
+		return
+	}
+	start, end := parseTimeRange(c)
+	report, err := h.teamService.Usage(c.Request.Context(), 0, teamID, start, end)
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, report)
+}
+
+func parseTeamID(c *gin.Context) (int64, bool) {
+	teamID, err := strconv.ParseInt(c.Param("id"), 10, 64)
+	if err != nil || teamID <= 0 {
+		response.BadRequest(c, "Invalid team ID")
+		return 0, false
+	}
+	return teamID, true
+}
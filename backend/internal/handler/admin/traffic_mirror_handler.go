@@ -0,0 +1,235 @@
+package admin
+
+import (
+	"strconv"
+
+	"github.com/Wei-Shaw/sub2api/internal/pkg/response"
+	"github.com/Wei-Shaw/sub2api/internal/service"
+	"github.com/gin-gonic/gin"
+)
+
+// TrafficMirrorHandler 处理流量镜像规则与样本的 HTTP 请求
+type TrafficMirrorHandler struct {
+	service *service.TrafficMirrorService
+}
+
+// NewTrafficMirrorHandler 创建流量镜像处理器
+func NewTrafficMirrorHandler(service *service.TrafficMirrorService) *TrafficMirrorHandler {
+	return &TrafficMirrorHandler{service: service}
+}
+
+// CreateTrafficMirrorRuleRequest 创建规则请求
+type CreateTrafficMirrorRuleRequest struct {
+	Name            string  `json:"name" binding:"required"`
+	Enabled         *bool   `json:"enabled"`
+	GroupID         *int64  `json:"group_id"`
+	ModelPattern    string  `json:"model_pattern"`
+	SampleRate      float64 `json:"sample_rate"`
+	TargetAccountID int64   `json:"target_account_id" binding:"required"`
+	TargetModel     string  `json:"target_model"`
+}
+
+// UpdateTrafficMirrorRuleRequest 更新规则请求（部分更新，所有字段可选）
+type UpdateTrafficMirrorRuleRequest struct {
+	Name            *string  `json:"name"`
+	Enabled         *bool    `json:"enabled"`
+	GroupID         *int64   `json:"group_id"`
+	ModelPattern    *string  `json:"model_pattern"`
+	SampleRate      *float64 `json:"sample_rate"`
+	TargetAccountID *int64   `json:"target_account_id"`
+	TargetModel     *string  `json:"target_model"`
+}
+
+// ListRules 获取所有规则
+// GET /api/v1/admin/traffic-mirror/rules
+func (h *TrafficMirrorHandler) ListRules(c *gin.Context) {
+	rules, err := h.service.ListRules(c.Request.Context())
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, rules)
+}
+
+// GetRule 根据 ID 获取规则
+// GET /api/v1/admin/traffic-mirror/rules/:id
+func (h *TrafficMirrorHandler) GetRule(c *gin.Context) {
+	id, err := strconv.ParseInt(c.Param("id"), 10, 64)
+	if err != nil {
+		response.BadRequest(c, "Invalid rule ID")
+		return
+	}
+
+	rule, err := h.service.GetRule(c.Request.Context(), id)
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, rule)
+}
+
+// CreateRule 创建规则
+// POST /api/v1/admin/traffic-mirror/rules
+func (h *TrafficMirrorHandler) CreateRule(c *gin.Context) {
+	var req CreateTrafficMirrorRuleRequest
+	if err := c.ShouldBindJSON(&req); err != nil {
+		response.BadRequest(c, "Invalid request: "+err.Error())
+		return
+	}
+
+	rule := &service.TrafficMirrorRule{
+		Name:            req.Name,
+		Enabled:         true,
+		GroupID:         req.GroupID,
+		ModelPattern:    req.ModelPattern,
+		SampleRate:      req.SampleRate,
+		TargetAccountID: req.TargetAccountID,
+		TargetModel:     req.TargetModel,
+	}
+	if req.Enabled != nil {
+		rule.Enabled = *req.Enabled
+	}
+
+	if err := h.service.CreateRule(c.Request.Context(), rule); err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Created(c, rule)
+}
+
+// UpdateRule 更新规则（支持部分更新）
+// PUT /api/v1/admin/traffic-mirror/rules/:id
+func (h *TrafficMirrorHandler) UpdateRule(c *gin.Context) {
+	id, err := strconv.ParseInt(c.Param("id"), 10, 64)
+	if err != nil {
+		response.BadRequest(c, "Invalid rule ID")
+		return
+	}
+
+	var req UpdateTrafficMirrorRuleRequest
+	if err := c.ShouldBindJSON(&req); err != nil {
+		response.BadRequest(c, "Invalid request: "+err.Error())
+		return
+	}
+
+	rule, err := h.service.GetRule(c.Request.Context(), id)
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+
+	if req.Name != nil {
+		rule.Name = *req.Name
+	}
+	if req.Enabled != nil {
+		rule.Enabled = *req.Enabled
+	}
+	if req.GroupID != nil {
+		// group_id <= 0 表示取消分组限定
+		rule.GroupID = req.GroupID
+	}
+	if req.ModelPattern != nil {
+		rule.ModelPattern = *req.ModelPattern
+	}
+	if req.SampleRate != nil {
+		rule.SampleRate = *req.SampleRate
+	}
+	if req.TargetAccountID != nil {
+		rule.TargetAccountID = *req.TargetAccountID
+	}
+	if req.TargetModel != nil {
+		rule.TargetModel = *req.TargetModel
+	}
+
+	if err := h.service.UpdateRule(c.Request.Context(), rule); err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, rule)
+}
+
+// DeleteRule 删除规则及其样本
+// DELETE /api/v1/admin/traffic-mirror/rules/:id
+func (h *TrafficMirrorHandler) DeleteRule(c *gin.Context) {
+	id, err := strconv.ParseInt(c.Param("id"), 10, 64)
+	if err != nil {
+		response.BadRequest(c, "Invalid rule ID")
+		return
+	}
+
+	if err := h.service.DeleteRule(c.Request.Context(), id); err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, gin.H{"message": "Rule deleted successfully"})
+}
+
+// ListSamples 分页列出镜像样本（不含输出内容）
+// GET /api/v1/admin/traffic-mirror/samples?rule_id=
+func (h *TrafficMirrorHandler) ListSamples(c *gin.Context) {
+	page, pageSize := response.ParsePagination(c)
+
+	var ruleID int64
+	if raw := c.Query("rule_id"); raw != "" {
+		v, err := strconv.ParseInt(raw, 10, 64)
+		if err != nil || v <= 0 {
+			response.BadRequest(c, "Invalid rule_id")
+			return
+		}
+		ruleID = v
+	}
+
+	samples, total, err := h.service.ListSamples(c.Request.Context(), ruleID, page, pageSize)
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Paginated(c, samples, total, page, pageSize)
+}
+
+// GetSample 获取样本详情（含主请求与镜像请求输出）
+// GET /api/v1/admin/traffic-mirror/samples/:id
+func (h *TrafficMirrorHandler) GetSample(c *gin.Context) {
+	id, err := strconv.ParseInt(c.Param("id"), 10, 64)
+	if err != nil {
+		response.BadRequest(c, "Invalid sample ID")
+		return
+	}
+
+	sample, err := h.service.GetSample(c.Request.Context(), id)
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, sample)
+}
+
+// Report 按规则汇总主请求与镜像请求的对比结果（错误率、延迟分位、输出结构与停止原因一致率等）
+// GET /api/v1/admin/traffic-mirror/report?rule_id=&time_range=24h|start_time=&end_time=
+func (h *TrafficMirrorHandler) Report(c *gin.Context) {
+	var ruleID int64
+	if raw := c.Query("rule_id"); raw != "" {
+		v, err := strconv.ParseInt(raw, 10, 64)
+		if err != nil || v <= 0 {
+			response.BadRequest(c, "Invalid rule_id")
+			return
+		}
+		ruleID = v
+	}
+	startTime, endTime, err := parseOpsTimeRange(c, "24h")
+	if err != nil {
+		response.BadRequest(c, err.Error())
+		return
+	}
+
+	reports, err := h.service.Report(c.Request.Context(), ruleID, startTime, endTime)
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, gin.H{
+		"start_time": startTime,
+		"end_time":   endTime,
+		"rules":      reports,
+	})
+}
@@ -0,0 +1,61 @@
+package admin
+
+import (
+	"net/http"
+
+	"github.com/Wei-Shaw/sub2api/internal/pkg/logger"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/response"
+	"github.com/Wei-Shaw/sub2api/internal/service"
+	"github.com/gin-gonic/gin"
+)
+
+// UsageExportHandler 使用记录导出（CSV / Parquet）
+type UsageExportHandler struct {
+	exportService *service.UsageExportService
+}
+
+// NewUsageExportHandler 创建使用记录导出处理器
+func NewUsageExportHandler(exportService *service.UsageExportService) *UsageExportHandler {
+	return &UsageExportHandler{exportService: exportService}
+}
+
+// Export 按过滤条件导出使用记录
+// GET /api/v1/admin/usage/export?format=csv|parquet&destination=download|storage&start_date=&end_date=
+//
+// 过滤参数与使用记录列表一致，start_date / end_date 必填。
+// destination=storage（兼容 s3）时导出文件直接上传到配置的对象存储，响应返回对象 key；否则以附件形式流式下载。
+func (h *UsageExportHandler) Export(c *gin.Context) {
+	filters, ok := parseUsageLogFilters(c)
+	if !ok {
+		return
+	}
+	format, err := service.NormalizeUsageExportFormat(c.Query("format"))
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	if err := h.exportService.ValidateRange(filters); err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+
+	if destination := c.Query("destination"); destination == "storage" || destination == "s3" {
+		result, err := h.exportService.ExportToStorage(c.Request.Context(), filters, format)
+		if err != nil {
+			response.ErrorFrom(c, err)
+			return
+		}
+		response.Success(c, result)
+		return
+	}
+
+	c.Header("Content-Type", service.UsageExportContentType(format))
+	c.Header("Content-Disposition", "attachment; filename="+service.UsageExportFileName(filters, format))
+	c.Header("Cache-Control", "no-store")
+	c.Status(http.StatusOK)
+	if _, err := h.exportService.Export(c.Request.Context(), filters, format, c.Writer); err != nil {
+		// 响应头已发送，只能中断连接，客户端会收到不完整的文件
+		logger.LegacyPrintf("handler.admin.usage_export", "[UsageExport] export failed: %v", err)
+		c.Abort()
+	}
+}
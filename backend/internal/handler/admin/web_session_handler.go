@@ -0,0 +1,74 @@
+package admin
+
+import (
+	"strconv"
+	"strings"
+
+	"github.com/Wei-Shaw/sub2api/internal/pkg/response"
+	"github.com/Wei-Shaw/sub2api/internal/service"
+	"github.com/gin-gonic/gin"
+)
+
+// WebSessionHandler 管理用户的后台 Cookie 会话
+type WebSessionHandler struct {
+	webSessionService *service.WebSessionService
+}
+
+// NewWebSessionHandler 创建会话管理处理器
+func NewWebSessionHandler(webSessionService *service.WebSessionService) *WebSessionHandler {
+	return &WebSessionHandler{webSessionService: webSessionService}
+}
+
+// ListUserSessions 列出用户的活跃会话
+// GET /api/v1/admin/users/:id/sessions
+func (h *WebSessionHandler) ListUserSessions(c *gin.Context) {
+	userID, err := strconv.ParseInt(c.Param("id"), 10, 64)
+	if err != nil {
+		response.BadRequest(c, "Invalid user ID")
+		return
+	}
+
+	sessions, err := h.webSessionService.ListForUser(c.Request.Context(), userID)
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, sessions)
+}
+
+// RevokeUserSessions 立即撤销用户的全部会话
+// DELETE /api/v1/admin/users/:id/sessions
+func (h *WebSessionHandler) RevokeUserSessions(c *gin.Context) {
+	userID, err := strconv.ParseInt(c.Param("id"), 10, 64)
+	if err != nil {
+		response.BadRequest(c, "Invalid user ID")
+		return
+	}
+
+	if err := h.webSessionService.RevokeAllForUser(c.Request.Context(), userID); err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, gin.H{"message": "All sessions revoked"})
+}
+
+// RevokeUserSession 撤销用户的指定会话
+// DELETE /api/v1/admin/users/:id/sessions/:session_id
+func (h *WebSessionHandler) RevokeUserSession(c *gin.Context) {
+	userID, err := strconv.ParseInt(c.Param("id"), 10, 64)
+	if err != nil {
+		response.BadRequest(c, "Invalid user ID")
+		return
+	}
+	sessionID := strings.TrimSpace(c.Param("session_id"))
+	if sessionID == "" {
+		response.BadRequest(c, "Invalid session ID")
+		return
+	}
+
+	if err := h.webSessionService.RevokeByID(c.Request.Context(), userID, sessionID); err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, gin.H{"message": "Session revoked"})
+}
@@ -17,13 +17,15 @@ import (
 
 // APIKeyHandler handles API key-related requests
 type APIKeyHandler struct {
-	apiKeyService *service.APIKeyService
+	apiKeyService   *service.APIKeyService
+	rotationService *service.APIKeyRotationService
 }
 
 // NewAPIKeyHandler creates a new APIKeyHandler
-func NewAPIKeyHandler(apiKeyService *service.APIKeyService) *APIKeyHandler {
+func NewAPIKeyHandler(apiKeyService *service.APIKeyService, rotationService *service.APIKeyRotationService) *APIKeyHandler {
 	return &APIKeyHandler{
-		apiKeyService: apiKeyService,
+		apiKeyService:   apiKeyService,
+		rotationService: rotationService,
 	}
 }
 
@@ -224,6 +226,95 @@ func (h *APIKeyHandler) Delete(c *gin.Context) {
 	response.Success(c, gin.H{"message": "API key deleted successfully"})
 }
 
+// RotateAPIKeyRequest 轮换 API Key 请求
+type RotateAPIKeyRequest struct {
+	GraceHours *int `json:"grace_hours"` // 旧 Key 宽限期（小时），nil 使用默认值
+}
+
+// RotateAPIKeyResponse 轮换 API Key 响应
+type RotateAPIKeyResponse struct {
+	OldKey     *dto.APIKey `json:"old_key"`
+	NewKey     *dto.APIKey `json:"new_key"`
+	GraceUntil time.Time   `json:"grace_until"`
+}
+
+// Rotate 签发替换 Key，旧 Key 在宽限期内继续有效
+// POST /api/v1/keys/:id/rotate
+func (h *APIKeyHandler) Rotate(c *gin.Context) {
+	subject, ok := middleware2.GetAuthSubjectFromContext(c)
+	if !ok {
+		response.Unauthorized(c, "User not authenticated")
+		return
+	}
+
+	keyID, err := strconv.ParseInt(c.Param("id"), 10, 64)
+	if err != nil {
+		response.BadRequest(c, "Invalid key ID")
+		return
+	}
+
+	var req RotateAPIKeyRequest
+	if err := c.ShouldBindJSON(&req); err != nil {
+		// Allow empty body (use default grace period)
+		req = RotateAPIKeyRequest{}
+	}
+
+	executeUserIdempotentJSON(c, "user.api_keys.rotate", gin.H{"id": keyID, "grace_hours": req.GraceHours}, service.DefaultWriteIdempotencyTTL(), func(ctx context.Context) (any, error) {
+		result, err := h.rotationService.Rotate(ctx, subject.UserID, keyID, req.GraceHours)
+		if err != nil {
+			return nil, err
+		}
+		return RotateAPIKeyResponse{
+			OldKey:     dto.APIKeyFromService(result.OldKey),
+			NewKey:     dto.APIKeyFromService(result.NewKey),
+			GraceUntil: result.Rotation.GraceUntil,
+		}, nil
+	})
+}
+
+// APIKeyRotationStatusResponse 轮换状态响应
+type APIKeyRotationStatusResponse struct {
+	OldAPIKeyID   int64                       `json:"old_api_key_id"`
+	NewAPIKeyID   int64                       `json:"new_api_key_id"`
+	RotatedAt     time.Time                   `json:"rotated_at"`
+	GraceUntil    time.Time                   `json:"grace_until"`
+	InGracePeriod bool                        `json:"in_grace_period"`
+	OldKeyUsage   *service.APIKeyVariantUsage `json:"old_key_usage"`
+	NewKeyUsage   *service.APIKeyVariantUsage `json:"new_key_usage"`
+}
+
+// GetRotationStatus 查看最近一次轮换的迁移进度（新旧 Key 各自的请求量）
+// GET /api/v1/keys/:id/rotation
+func (h *APIKeyHandler) GetRotationStatus(c *gin.Context) {
+	subject, ok := middleware2.GetAuthSubjectFromContext(c)
+	if !ok {
+		response.Unauthorized(c, "User not authenticated")
+		return
+	}
+
+	keyID, err := strconv.ParseInt(c.Param("id"), 10, 64)
+	if err != nil {
+		response.BadRequest(c, "Invalid key ID")
+		return
+	}
+
+	status, err := h.rotationService.GetStatus(c.Request.Context(), subject.UserID, keyID)
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+
+	response.Success(c, APIKeyRotationStatusResponse{
+		OldAPIKeyID:   status.Rotation.OldAPIKeyID,
+		NewAPIKeyID:   status.Rotation.NewAPIKeyID,
+		RotatedAt:     status.Rotation.CreatedAt,
+		GraceUntil:    status.Rotation.GraceUntil,
+		InGracePeriod: status.InGracePeriod,
+		OldKeyUsage:   status.OldUsage,
+		NewKeyUsage:   status.NewUsage,
+	})
+}
+
 // GetAvailableGroups 获取用户可以绑定的分组列表
 // GET /api/v1/groups/available
 func (h *APIKeyHandler) GetAvailableGroups(c *gin.Context) {
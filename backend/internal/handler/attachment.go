@@ -0,0 +1,152 @@
+package handler
+
+import (
+	"net/http"
+	"strconv"
+	"strings"
+
+	pkgerrors "github.com/Wei-Shaw/sub2api/internal/pkg/errors"
+	middleware2 "github.com/Wei-Shaw/sub2api/internal/server/middleware"
+	"github.com/Wei-Shaw/sub2api/internal/service"
+	"github.com/gin-gonic/gin"
+	"go.uber.org/zap"
+)
+
+// applyAttachments 解析 multipart/form-data 请求，并把消息中引用的文件替换为内联内容块。
+//
+// 返回处理后的 JSON 请求体以及是否继续处理；出错时已写入错误响应。
+func applyAttachments(
+	c *gin.Context,
+	svc *service.AttachmentService,
+	format service.AttachmentFormat,
+	apiKey *service.APIKey,
+	body []byte,
+	reqLog *zap.Logger,
+	errorResponse func(*gin.Context, int, string, string),
+) ([]byte, bool) {
+	var uploads map[string]*service.Attachment
+	if strings.HasPrefix(c.ContentType(), "multipart/") {
+		payload, parts, err := svc.ParseMultipart(c.GetHeader("Content-Type"), body)
+		if err != nil {
+			status, errType, message := attachmentErrorDetails(err)
+			errorResponse(c, status, errType, message)
+			return body, false
+		}
+		body, uploads = payload, parts
+	}
+	resolved, err := svc.Resolve(c.Request.Context(), format, apiKey.UserID, body, uploads)
+	if err != nil {
+		status, errType, message := attachmentErrorDetails(err)
+		if status >= http.StatusInternalServerError {
+			reqLog.Error("gateway.attachment_resolve_failed", zap.Error(err))
+		}
+		errorResponse(c, status, errType, message)
+		return body, false
+	}
+	return resolved, true
+}
+
+func attachmentErrorDetails(err error) (int, string, string) {
+	status := pkgerrors.Code(err)
+	switch {
+	case status == http.StatusNotFound:
+		return status, "not_found_error", pkgerrors.Message(err)
+	case status == http.StatusServiceUnavailable:
+		return status, "api_error", pkgerrors.Message(err)
+	case status >= http.StatusInternalServerError:
+		return http.StatusInternalServerError, "api_error", "File storage error"
+	default:
+		return status, "invalid_request_error", pkgerrors.Message(err)
+	}
+}
+
+// UploadFile 上传文件，返回可在消息中引用的文件 ID
+// POST /v1/files (multipart/form-data, field "file")
+func (h *GatewayHandler) UploadFile(c *gin.Context) {
+	subject, ok := middleware2.GetAuthSubjectFromContext(c)
+	if !ok {
+		h.errorResponse(c, http.StatusInternalServerError, "api_error", "User context not found")
+		return
+	}
+	fileHeader, err := c.FormFile("file")
+	if err != nil {
+		if maxErr, ok := extractMaxBytesError(err); ok {
+			h.errorResponse(c, http.StatusRequestEntityTooLarge, "invalid_request_error", buildBodyTooLargeMessage(maxErr.Limit))
+			return
+		}
+		h.errorResponse(c, http.StatusBadRequest, "invalid_request_error", "multipart field \"file\" is required")
+		return
+	}
+	src, err := fileHeader.Open()
+	if err != nil {
+		h.errorResponse(c, http.StatusBadRequest, "invalid_request_error", "Failed to read uploaded file")
+		return
+	}
+	defer func() { _ = src.Close() }()
+
+	var apiKeyID *int64
+	if apiKey, ok := middleware2.GetAPIKeyFromContext(c); ok {
+		apiKeyID = &apiKey.ID
+	}
+	file, err := h.attachmentService.Upload(c.Request.Context(), subject.UserID, apiKeyID, fileHeader.Filename, fileHeader.Header.Get("Content-Type"), src)
+	if err != nil {
+		status, errType, message := attachmentErrorDetails(err)
+		if status >= http.StatusInternalServerError {
+			requestLogger(c, "handler.gateway.files").Error("gateway.attachment_upload_failed", zap.Error(err))
+		}
+		h.errorResponse(c, status, errType, message)
+		return
+	}
+	c.JSON(http.StatusCreated, file)
+}
+
+// ListFiles 按上传时间倒序列出未过期的文件
+// GET /v1/files?limit=20
+func (h *GatewayHandler) ListFiles(c *gin.Context) {
+	subject, ok := middleware2.GetAuthSubjectFromContext(c)
+	if !ok {
+		h.errorResponse(c, http.StatusInternalServerError, "api_error", "User context not found")
+		return
+	}
+	limit, _ := strconv.Atoi(c.Query("limit"))
+	files, err := h.attachmentService.List(c.Request.Context(), subject.UserID, limit)
+	if err != nil {
+		status, errType, message := attachmentErrorDetails(err)
+		h.errorResponse(c, status, errType, message)
+		return
+	}
+	c.JSON(http.StatusOK, gin.H{"data": files})
+}
+
+// GetFile 查询文件元数据
+// GET /v1/files/:id
+func (h *GatewayHandler) GetFile(c *gin.Context) {
+	subject, ok := middleware2.GetAuthSubjectFromContext(c)
+	if !ok {
+		h.errorResponse(c, http.StatusInternalServerError, "api_error", "User context not found")
+		return
+	}
+	file, err := h.attachmentService.Get(c.Request.Context(), subject.UserID, c.Param("id"))
+	if err != nil {
+		status, errType, message := attachmentErrorDetails(err)
+		h.errorResponse(c, status, errType, message)
+		return
+	}
+	c.JSON(http.StatusOK, file)
+}
+
+// DeleteFile 删除文件
+// DELETE /v1/files/:id
+func (h *GatewayHandler) DeleteFile(c *gin.Context) {
+	subject, ok := middleware2.GetAuthSubjectFromContext(c)
+	if !ok {
+		h.errorResponse(c, http.StatusInternalServerError, "api_error", "User context not found")
+		return
+	}
+	if err := h.attachmentService.Delete(c.Request.Context(), subject.UserID, c.Param("id")); err != nil {
+		status, errType, message := attachmentErrorDetails(err)
+		h.errorResponse(c, status, errType, message)
+		return
+	}
+	c.Status(http.StatusNoContent)
+}
@@ -23,10 +23,11 @@ type AuthHandler struct {
 	promoService  *service.PromoService
 	redeemService *service.RedeemService
 	totpService   *service.TotpService
+	webSessionSvc *service.WebSessionService
 }
 
 // NewAuthHandler creates a new AuthHandler
-func NewAuthHandler(cfg *config.Config, authService *service.AuthService, userService *service.UserService, settingService *service.SettingService, promoService *service.PromoService, redeemService *service.RedeemService, totpService *service.TotpService) *AuthHandler {
+func NewAuthHandler(cfg *config.Config, authService *service.AuthService, userService *service.UserService, settingService *service.SettingService, promoService *service.PromoService, redeemService *service.RedeemService, totpService *service.TotpService, webSessionService *service.WebSessionService) *AuthHandler {
 	return &AuthHandler{
 		cfg:           cfg,
 		authService:   authService,
@@ -35,6 +36,7 @@ func NewAuthHandler(cfg *config.Config, authService *service.AuthService, userSe
 		promoService:  promoService,
 		redeemService: redeemService,
 		totpService:   totpService,
+		webSessionSvc: webSessionService,
 	}
 }
 
@@ -78,7 +80,10 @@ type AuthResponse struct {
 
 // respondWithTokenPair 生成 Token 对并返回认证响应
 // 如果 Token 对生成失败，回退到只返回 Access Token（向后兼容）
+// 启用 Cookie 会话时，同时下发 HttpOnly 会话 Cookie
 func (h *AuthHandler) respondWithTokenPair(c *gin.Context, user *service.User) {
+	h.issueWebSession(c, user)
+
 	tokenPair, err := h.authService.GenerateTokenPair(c.Request.Context(), user, "")
 	if err != nil {
 		slog.Error("failed to generate token pair", "error", err, "user_id", user.ID)
@@ -104,6 +109,19 @@ func (h *AuthHandler) respondWithTokenPair(c *gin.Context, user *service.User) {
 	})
 }
 
+// issueWebSession 创建 Cookie 会话（失败时仅记录日志，不影响 Token 登录）
+func (h *AuthHandler) issueWebSession(c *gin.Context, user *service.User) {
+	if !h.webSessionSvc.Enabled() {
+		return
+	}
+	token, _, err := h.webSessionSvc.Create(c.Request.Context(), user, ip.GetClientIP(c), c.GetHeader("User-Agent"))
+	if err != nil {
+		slog.Error("failed to create web session", "error", err, "user_id", user.ID)
+		return
+	}
+	middleware2.SetWebSessionCookie(c, h.webSessionSvc, token)
+}
+
 // Register handles user registration
 // POST /api/v1/auth/register
 func (h *AuthHandler) Register(c *gin.Context) {
@@ -179,7 +197,7 @@ func (h *AuthHandler) Login(c *gin.Context) {
 	_ = token // token 由 authService.Login 返回但此处由 respondWithTokenPair 重新生成
 
 	// Check if TOTP 2FA is enabled for this user
-	if h.totpService != nil && h.settingSvc.IsTotpEnabled(c.Request.Context()) && user.TotpEnabled {
+	if h.totpService != nil && h.totpService.RequiresLogin2FA(c.Request.Context(), user) {
 		// Create a temporary login session for 2FA
 		tempToken, err := h.totpService.CreateLoginSession(c.Request.Context(), user.ID, user.Email)
 		if err != nil {
@@ -206,9 +224,11 @@ type TotpLoginResponse struct {
 }
 
 // Login2FARequest represents the 2FA login request
+// totp_code 与 backup_code 二选一
 type Login2FARequest struct {
-	TempToken string `json:"temp_token" binding:"required"`
-	TotpCode  string `json:"totp_code" binding:"required,len=6"`
+	TempToken  string `json:"temp_token" binding:"required"`
+	TotpCode   string `json:"totp_code" binding:"omitempty,len=6"`
+	BackupCode string `json:"backup_code"`
 }
 
 // Login2FA completes the login with 2FA verification
@@ -220,9 +240,15 @@ func (h *AuthHandler) Login2FA(c *gin.Context) {
 		return
 	}
 
+	if req.TotpCode == "" && strings.TrimSpace(req.BackupCode) == "" {
+		response.BadRequest(c, "totp_code or backup_code is required")
+		return
+	}
+
 	slog.Debug("login_2fa_request",
 		"temp_token_len", len(req.TempToken),
-		"totp_code_len", len(req.TotpCode))
+		"totp_code_len", len(req.TotpCode),
+		"backup_code", req.BackupCode != "")
 
 	// Get the login session
 	session, err := h.totpService.GetLoginSession(c.Request.Context(), req.TempToken)
@@ -242,12 +268,18 @@ func (h *AuthHandler) Login2FA(c *gin.Context) {
 		"user_id", session.UserID,
 		"email", session.Email)
 
-	// Verify the TOTP code
-	if err := h.totpService.VerifyCode(c.Request.Context(), session.UserID, req.TotpCode); err != nil {
+	// Verify the TOTP code (or a one-time backup code)
+	var verifyErr error
+	if req.TotpCode != "" {
+		verifyErr = h.totpService.VerifyCode(c.Request.Context(), session.UserID, req.TotpCode)
+	} else {
+		verifyErr = h.totpService.VerifyBackupCode(c.Request.Context(), session.UserID, req.BackupCode)
+	}
+	if verifyErr != nil {
 		slog.Debug("login_2fa_verify_failed",
 			"user_id", session.UserID,
-			"error", err)
-		response.ErrorFrom(c, err)
+			"error", verifyErr)
+		response.ErrorFrom(c, verifyErr)
 		return
 	}
 
@@ -562,6 +594,14 @@ func (h *AuthHandler) Logout(c *gin.Context) {
 		}
 	}
 
+	// 撤销当前 Cookie 会话
+	if sessionToken := middleware2.WebSessionTokenFromCookie(c, h.webSessionSvc); sessionToken != "" {
+		if err := h.webSessionSvc.Revoke(c.Request.Context(), sessionToken); err != nil {
+			slog.Debug("failed to revoke web session", "error", err)
+		}
+	}
+	middleware2.ClearWebSessionCookie(c, h.webSessionSvc)
+
 	response.Success(c, LogoutResponse{
 		Message: "Logged out successfully",
 	})
@@ -586,6 +626,12 @@ func (h *AuthHandler) RevokeAllSessions(c *gin.Context) {
 		response.InternalError(c, "Failed to revoke sessions")
 		return
 	}
+	if err := h.webSessionSvc.RevokeAllForUser(c.Request.Context(), subject.UserID); err != nil {
+		slog.Error("failed to revoke web sessions", "user_id", subject.UserID, "error", err)
+		response.InternalError(c, "Failed to revoke sessions")
+		return
+	}
+	middleware2.ClearWebSessionCookie(c, h.webSessionSvc)
 
 	response.Success(c, RevokeAllSessionsResponse{
 		Message: "All sessions have been revoked. Please log in again.",
@@ -0,0 +1,155 @@
+package handler
+
+import (
+	"net/http"
+	"strconv"
+
+	pkgerrors "github.com/Wei-Shaw/sub2api/internal/pkg/errors"
+	middleware2 "github.com/Wei-Shaw/sub2api/internal/server/middleware"
+	"github.com/Wei-Shaw/sub2api/internal/service"
+	"github.com/gin-gonic/gin"
+	"go.uber.org/zap"
+)
+
+// applyConversation 请求携带 conversation_id 时从服务端会话补全历史消息。
+//
+// 返回补全后的请求体、本轮会话（未携带时为 nil）以及是否继续处理；出错时已写入错误响应。
+func applyConversation(
+	c *gin.Context,
+	svc *service.ConversationService,
+	apiKey *service.APIKey,
+	body []byte,
+	reqLog *zap.Logger,
+	errorResponse func(*gin.Context, int, string, string),
+) ([]byte, *service.ConversationTurn, bool) {
+	body, turn, err := svc.Prepare(c, apiKey, body)
+	if err != nil {
+		status, errType, message := conversationErrorDetails(err)
+		if status >= http.StatusInternalServerError {
+			reqLog.Error("gateway.conversation_prepare_failed", zap.Error(err))
+		}
+		errorResponse(c, status, errType, message)
+		return body, nil, false
+	}
+	if turn != nil {
+		reqLog.Debug("gateway.conversation_restored", zap.String("conversation_id", turn.ID()))
+	}
+	return body, turn, true
+}
+
+func conversationErrorDetails(err error) (int, string, string) {
+	status := pkgerrors.Code(err)
+	switch {
+	case status == http.StatusNotFound:
+		return status, "not_found_error", pkgerrors.Message(err)
+	case status >= http.StatusInternalServerError:
+		return http.StatusInternalServerError, "api_error", "Conversation storage error"
+	default:
+		return status, "invalid_request_error", pkgerrors.Message(err)
+	}
+}
+
+// ListConversations 按最近更新时间列出根会话（分支通过 tree 接口查询）
+// GET /v1/conversations?limit=20
+func (h *GatewayHandler) ListConversations(c *gin.Context) {
+	subject, ok := middleware2.GetAuthSubjectFromContext(c)
+	if !ok {
+		h.errorResponse(c, http.StatusInternalServerError, "api_error", "User context not found")
+		return
+	}
+	limit, _ := strconv.Atoi(c.Query("limit"))
+	conversations, err := h.conversationService.List(c.Request.Context(), subject.UserID, limit)
+	if err != nil {
+		status, errType, message := conversationErrorDetails(err)
+		h.errorResponse(c, status, errType, message)
+		return
+	}
+	c.JSON(http.StatusOK, gin.H{"data": conversations})
+}
+
+// GetConversation 查询服务端会话及其消息
+// GET /v1/conversations/:id
+func (h *GatewayHandler) GetConversation(c *gin.Context) {
+	subject, ok := middleware2.GetAuthSubjectFromContext(c)
+	if !ok {
+		h.errorResponse(c, http.StatusInternalServerError, "api_error", "User context not found")
+		return
+	}
+	conv, messages, err := h.conversationService.Get(c.Request.Context(), subject.UserID, c.Param("id"))
+	if err != nil {
+		status, errType, message := conversationErrorDetails(err)
+		h.errorResponse(c, status, errType, message)
+		return
+	}
+	c.JSON(http.StatusOK, gin.H{
+		"conversation": conv,
+		"messages":     messages,
+	})
+}
+
+// DeleteConversation 删除服务端会话
+// DELETE /v1/conversations/:id
+func (h *GatewayHandler) DeleteConversation(c *gin.Context) {
+	subject, ok := middleware2.GetAuthSubjectFromContext(c)
+	if !ok {
+		h.errorResponse(c, http.StatusInternalServerError, "api_error", "User context not found")
+		return
+	}
+	if err := h.conversationService.Delete(c.Request.Context(), subject.UserID, c.Param("id")); err != nil {
+		status, errType, message := conversationErrorDetails(err)
+		h.errorResponse(c, status, errType, message)
+		return
+	}
+	c.Status(http.StatusNoContent)
+}
+
+type forkConversationRequest struct {
+	// Seq 分叉点：新会话继承第 1..seq 条消息
+	Seq int `json:"seq" binding:"required"`
+	// ID 新会话 ID，为空时自动生成
+	ID string `json:"id"`
+}
+
+// ForkConversation 从会话的任意一条消息处分叉出新会话
+// POST /v1/conversations/:id/fork
+func (h *GatewayHandler) ForkConversation(c *gin.Context) {
+	subject, ok := middleware2.GetAuthSubjectFromContext(c)
+	if !ok {
+		h.errorResponse(c, http.StatusInternalServerError, "api_error", "User context not found")
+		return
+	}
+	apiKey, _ := middleware2.GetAPIKeyFromContext(c)
+	var req forkConversationRequest
+	if err := c.ShouldBindJSON(&req); err != nil {
+		h.errorResponse(c, http.StatusBadRequest, "invalid_request_error", "Invalid request: "+err.Error())
+		return
+	}
+	var apiKeyID int64
+	if apiKey != nil {
+		apiKeyID = apiKey.ID
+	}
+	conv, err := h.conversationService.Fork(c.Request.Context(), subject.UserID, apiKeyID, c.Param("id"), req.Seq, req.ID)
+	if err != nil {
+		status, errType, message := conversationErrorDetails(err)
+		h.errorResponse(c, status, errType, message)
+		return
+	}
+	c.JSON(http.StatusCreated, conv)
+}
+
+// ConversationTree 查询会话所在的整棵分支树（根会话在前）
+// GET /v1/conversations/:id/tree
+func (h *GatewayHandler) ConversationTree(c *gin.Context) {
+	subject, ok := middleware2.GetAuthSubjectFromContext(c)
+	if !ok {
+		h.errorResponse(c, http.StatusInternalServerError, "api_error", "User context not found")
+		return
+	}
+	tree, err := h.conversationService.Tree(c.Request.Context(), subject.UserID, c.Param("id"))
+	if err != nil {
+		status, errType, message := conversationErrorDetails(err)
+		h.errorResponse(c, status, errType, message)
+		return
+	}
+	c.JSON(http.StatusOK, gin.H{"data": tree})
+}
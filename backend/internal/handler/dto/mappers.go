@@ -206,11 +206,6 @@ func AccountFromServiceShallow(a *service.Account) *Account {
 		if idleTimeout := a.GetSessionIdleTimeoutMinutes(); idleTimeout > 0 {
 			out.SessionIdleTimeoutMin = &idleTimeout
 		}
-		// TLS指纹伪装开关
-		if a.IsTLSFingerprintEnabled() {
-			enabled := true
-			out.EnableTLSFingerprint = &enabled
-		}
 		// 会话ID伪装开关
 		if a.IsSessionIDMaskingEnabled() {
 			enabled := true
@@ -225,6 +220,15 @@ func AccountFromServiceShallow(a *service.Account) *Account {
 		}
 	}
 
+	// TLS指纹伪装开关与模板（Anthropic OAuth/SetupToken、网页会话账号，或显式指定模板的账号）
+	if a.IsTLSFingerprintEnabled() {
+		enabled := true
+		out.EnableTLSFingerprint = &enabled
+	}
+	if profile := a.GetTLSFingerprintProfileOverride(); profile != "" {
+		out.TLSFingerprintProfile = &profile
+	}
+
 	return out
 }
 
@@ -399,6 +403,7 @@ func usageLogFromServiceUser(l *service.UsageLog) UsageLog {
 		OutputTokens:          l.OutputTokens,
 		CacheCreationTokens:   l.CacheCreationTokens,
 		CacheReadTokens:       l.CacheReadTokens,
+		ReasoningTokens:       l.ReasoningTokens,
 		CacheCreation5mTokens: l.CacheCreation5mTokens,
 		CacheCreation1hTokens: l.CacheCreation1hTokens,
 		InputCost:             l.InputCost,
@@ -417,6 +422,8 @@ func usageLogFromServiceUser(l *service.UsageLog) UsageLog {
 		MediaType:             l.MediaType,
 		UserAgent:             l.UserAgent,
 		CacheTTLOverridden:    l.CacheTTLOverridden,
+		SystemFingerprint:     l.SystemFingerprint,
+		UsageEstimated:        l.UsageEstimated,
 		CreatedAt:             l.CreatedAt,
 		User:                  UserFromServiceShallow(l.User),
 		APIKey:                APIKeyFromService(l.APIKey),
@@ -445,6 +452,7 @@ func UsageLogFromServiceAdmin(l *service.UsageLog) *AdminUsageLog {
 		UsageLog:              usageLogFromServiceUser(l),
 		AccountRateMultiplier: l.AccountRateMultiplier,
 		IPAddress:             l.IPAddress,
+		PromptFirewall:        l.PromptFirewall,
 		Account:               AccountSummaryFromService(l.Account),
 	}
 }
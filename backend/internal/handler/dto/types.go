@@ -148,9 +148,10 @@ type Account struct {
 	MaxSessions           *int `json:"max_sessions,omitempty"`
 	SessionIdleTimeoutMin *int `json:"session_idle_timeout_minutes,omitempty"`
 
-	// TLS指纹伪装（仅 Anthropic OAuth/SetupToken 账号有效）
+	// TLS指纹伪装（Anthropic OAuth/SetupToken 与网页会话账号可开关；其他账号指定模板即启用）
 	// 从 extra 字段提取，方便前端显示和编辑
-	EnableTLSFingerprint *bool `json:"enable_tls_fingerprint,omitempty"`
+	EnableTLSFingerprint  *bool   `json:"enable_tls_fingerprint,omitempty"`
+	TLSFingerprintProfile *string `json:"tls_fingerprint_profile,omitempty"`
 
 	// 会话ID伪装（仅 Anthropic OAuth/SetupToken 账号有效）
 	// 启用后将在15分钟内固定 metadata.user_id 中的 session ID
@@ -266,6 +267,8 @@ type UsageLog struct {
 	OutputTokens        int `json:"output_tokens"`
 	CacheCreationTokens int `json:"cache_creation_tokens"`
 	CacheReadTokens     int `json:"cache_read_tokens"`
+	// ReasoningTokens 输出中的推理 token 数（已计入 output_tokens）
+	ReasoningTokens int `json:"reasoning_tokens,omitempty"`
 
 	CacheCreation5mTokens int `json:"cache_creation_5m_tokens"`
 	CacheCreation1hTokens int `json:"cache_creation_1h_tokens"`
@@ -294,6 +297,12 @@ type UsageLog struct {
 	// Cache TTL Override 标记
 	CacheTTLOverridden bool `json:"cache_ttl_overridden"`
 
+	// SystemFingerprint 上游 + 模型 + 适配器版本指纹，变化说明后端发生了变化
+	SystemFingerprint *string `json:"system_fingerprint,omitempty"`
+
+	// UsageEstimated 上游未返回用量，token 数为本地估算值
+	UsageEstimated bool `json:"usage_estimated"`
+
 	CreatedAt time.Time `json:"created_at"`
 
 	User         *User             `json:"user,omitempty"`
@@ -312,6 +321,9 @@ type AdminUsageLog struct {
 	// IPAddress 用户请求 IP（仅管理员可见）
 	IPAddress *string `json:"ip_address,omitempty"`
 
+	// PromptFirewall 提示词防火墙处理结果（仅管理员可见）
+	PromptFirewall *string `json:"prompt_firewall,omitempty"`
+
 	// Account 最小账号信息（避免泄露敏感字段）
 	Account *AccountSummary `json:"account,omitempty"`
 }
@@ -0,0 +1,398 @@
+package handler
+
+import (
+	"bufio"
+	"bytes"
+	"encoding/json"
+	"net"
+	"net/http"
+	"runtime/debug"
+	"strconv"
+	"strings"
+	"sync"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/logger"
+	middleware2 "github.com/Wei-Shaw/sub2api/internal/server/middleware"
+	"github.com/Wei-Shaw/sub2api/internal/service"
+	"github.com/gin-gonic/gin"
+	"github.com/tidwall/gjson"
+	"github.com/tidwall/sjson"
+	"go.uber.org/zap"
+)
+
+// Completions 旧版文本补全接口：提示词包装为单轮对话后交给 Messages 处理（调度、限流、计费与 /v1/messages 一致），
+// 响应再转换为 text_completion 格式。n > 1 由网关扇出模拟（见 completionsFanOut），仅本端点支持。
+// POST /v1/completions
+func (h *GatewayHandler) Completions(c *gin.Context) {
+	body, err := middleware2.ReadRequestBody(c.Request)
+	if err != nil {
+		if maxErr, ok := extractMaxBytesError(err); ok {
+			openAIErrorResponse(c, http.StatusRequestEntityTooLarge, "invalid_request_error", buildBodyTooLargeMessage(maxErr.Limit))
+			return
+		}
+		openAIErrorResponse(c, http.StatusBadRequest, "invalid_request_error", "Failed to read request body")
+		return
+	}
+	if len(body) == 0 {
+		openAIErrorResponse(c, http.StatusBadRequest, "invalid_request_error", "Request body is empty")
+		return
+	}
+
+	req, messagesBody, schemaErr := service.ConvertCompletionRequest(body, completionsMaxChoices(h.cfg), finishReasonOverrides(h.cfg))
+	if schemaErr != nil {
+		openAISchemaErrorResponse(c, schemaErr)
+		return
+	}
+	c.Request.Header.Set("Content-Type", "application/json")
+	if req.Logprobs {
+		c.Header(service.LogprobsHeader, service.LogprobsEmulated)
+	}
+	if req.N > 1 {
+		h.completionsFanOut(c, req, messagesBody)
+		return
+	}
+	middleware2.SetRequestBody(c.Request, messagesBody)
+
+	writer := &completionResponseWriter{ResponseWriter: c.Writer, req: req, stream: req.NewStream(time.Now().Unix())}
+	c.Writer = writer
+	defer func() { c.Writer = writer.ResponseWriter }()
+
+	h.Messages(c)
+	writer.finish()
+}
+
+func completionsMaxChoices(cfg *config.Config) int {
+	if cfg == nil || cfg.Gateway.CompletionsMaxChoices < 1 {
+		return 1
+	}
+	return cfg.Gateway.CompletionsMaxChoices
+}
+
+func finishReasonOverrides(cfg *config.Config) map[string]map[string]string {
+	if cfg == nil {
+		return nil
+	}
+	return cfg.Gateway.FinishReasonOverrides
+}
+
+// completionsFanOut n > 1 时并发发起 n 次非流式 Messages 请求并合并 choices。
+// 只有 /v1/completions 模拟 n > 1；/v1/messages、/v1/chat/completions 等端点不做扇出。
+// 每次请求与普通 /v1/messages 请求一样经过完整的 Messages 流程：各自占用一个用户并发槽位与账号槽位、
+// 认领预占中间件按 n 份预占的其中一份（随该次扣费释放），并单独计费。
+// 并发度不超过用户并发上限，单个请求失败不会取消其余请求（已完成的请求已计费）。
+// 请求 stream 时在全部结果返回后以流式 chunk 输出。
+func (h *GatewayHandler) completionsFanOut(c *gin.Context, req *service.CompletionRequest, messagesBody []byte) {
+	body, err := sjson.DeleteBytes(messagesBody, "stream")
+	if err != nil {
+		openAIErrorResponse(c, http.StatusInternalServerError, "api_error", "Failed to build upstream request")
+		return
+	}
+	parallel := req.N
+	if subject, ok := middleware2.GetAuthSubjectFromContext(c); ok && subject.Concurrency > 0 {
+		parallel = min(parallel, subject.Concurrency)
+	}
+
+	ctx := c.Request.Context()
+	results := make([]*completionCapture, req.N)
+	var wg sync.WaitGroup
+	sem := make(chan struct{}, parallel)
+	for i := range req.N {
+		sem <- struct{}{}
+		if ctx.Err() != nil {
+			break
+		}
+		// Copy 在当前 goroutine 中复制 Keys，子请求不与父请求共享可写状态
+		sub := c.Copy()
+		wg.Add(1)
+		go func() {
+			defer wg.Done()
+			defer func() { <-sem }()
+			results[i] = h.runCompletionChoice(sub, body)
+		}()
+	}
+	wg.Wait()
+	if ctx.Err() != nil {
+		// 客户端已断开
+		return
+	}
+	writeCompletionFanOut(c, req, results)
+}
+
+// writeCompletionFanOut 合并扇出结果。
+//
+// 部分成功：返回 200 与成功的 choices（保留原 index，choices 数少于 n），usage 只统计返回的 choices（与计费一致），
+// 失败的 index 写入 X-Sub2API-Failed-Choices 响应头，客户端需据此判断结果不完整。
+// 全部失败：返回第一个失败请求的错误状态码与错误体（未写出响应的按 502）。
+func writeCompletionFanOut(c *gin.Context, req *service.CompletionRequest, results []*completionCapture) {
+	choices := make([]service.CompletionChoiceBody, 0, len(results))
+	var failed []string
+	var firstErr *completionCapture
+	for i, capture := range results {
+		if capture.status < http.StatusOK || capture.status >= http.StatusMultipleChoices {
+			failed = append(failed, strconv.Itoa(i))
+			if firstErr == nil {
+				firstErr = capture
+			}
+			continue
+		}
+		choices = append(choices, service.CompletionChoiceBody{Index: i, Body: capture.body.Bytes()})
+	}
+	if len(choices) == 0 {
+		status := firstErr.status
+		if status == 0 {
+			status = http.StatusBadGateway
+		}
+		c.Data(status, "application/json; charset=utf-8", completionErrorFromMessages(status, firstErr.body.Bytes()))
+		return
+	}
+	if len(failed) > 0 {
+		c.Header(service.CompletionsFailedChoicesHeader, strings.Join(failed, ","))
+	}
+	// 各次请求可能调度到不同上游，响应头与指纹以第一个成功的 choice 为准
+	first := results[choices[0].Index]
+	for _, key := range []string{service.SystemFingerprintHeader, service.SamplingAdjustedHeader} {
+		if v := first.header.Get(key); v != "" {
+			c.Header(key, v)
+		}
+	}
+	req.SystemFingerprint = first.header.Get(service.SystemFingerprintHeader)
+	out, err := req.MergeCompletions(choices, time.Now().Unix(), req.Stream)
+	if err != nil {
+		openAIErrorResponse(c, http.StatusBadGateway, "upstream_error", "Invalid upstream response")
+		return
+	}
+	contentType := "application/json; charset=utf-8"
+	if req.Stream {
+		contentType = "text/event-stream"
+	}
+	c.Data(http.StatusOK, contentType, out)
+}
+
+// runCompletionChoice 在父请求的 Context 副本上执行一次 Messages 请求并捕获响应
+func (h *GatewayHandler) runCompletionChoice(sub *gin.Context, body []byte) (capture *completionCapture) {
+	capture = &completionCapture{header: make(http.Header)}
+	defer func() {
+		if p := recover(); p != nil {
+			logger.L().With(
+				zap.String("component", "handler.gateway.completions"),
+				zap.Any("panic", p),
+				zap.ByteString("stack", debug.Stack()),
+			).Error("gateway.completions_choice_panic")
+			capture = &completionCapture{header: make(http.Header), status: http.StatusInternalServerError}
+		}
+	}()
+	sub.Writer = capture
+	sub.Request = sub.Request.Clone(sub.Request.Context())
+	middleware2.SetRequestBody(sub.Request, body)
+	h.Messages(sub)
+	return capture
+}
+
+// completionCapture 缓存 n > 1 扇出时单次 Messages 请求的响应（不直接写出到客户端连接）
+type completionCapture struct {
+	header  http.Header
+	status  int
+	written bool
+	body    bytes.Buffer
+}
+
+var _ gin.ResponseWriter = (*completionCapture)(nil)
+
+func (w *completionCapture) Header() http.Header {
+	return w.header
+}
+
+// WriteHeader 与 gin 一致：写出响应体前可覆盖状态码
+func (w *completionCapture) WriteHeader(code int) {
+	if code > 0 && !w.written {
+		w.status = code
+	}
+}
+
+func (w *completionCapture) Write(data []byte) (int, error) {
+	w.WriteHeaderNow()
+	return w.body.Write(data)
+}
+
+func (w *completionCapture) WriteString(s string) (int, error) {
+	return w.Write([]byte(s))
+}
+
+func (w *completionCapture) Status() int {
+	if w.status == 0 {
+		return http.StatusOK
+	}
+	return w.status
+}
+
+func (w *completionCapture) Size() int {
+	if !w.written {
+		return -1
+	}
+	return w.body.Len()
+}
+
+func (w *completionCapture) Written() bool {
+	return w.written
+}
+
+func (w *completionCapture) WriteHeaderNow() {
+	if w.status == 0 {
+		w.status = http.StatusOK
+	}
+	w.written = true
+}
+
+func (w *completionCapture) Flush() {}
+
+func (w *completionCapture) Hijack() (net.Conn, *bufio.ReadWriter, error) {
+	return nil, nil, http.ErrNotSupported
+}
+
+func (w *completionCapture) CloseNotify() <-chan bool {
+	return make(chan bool)
+}
+
+func (w *completionCapture) Pusher() http.Pusher {
+	return nil
+}
+
+// openAIErrorResponse 返回 OpenAI 格式的错误响应
+func openAIErrorResponse(c *gin.Context, status int, errType, message string) {
+	c.JSON(status, gin.H{
+		"error": gin.H{
+			"type":    errType,
+			"message": message,
+		},
+	})
+}
+
+// completionResponseWriter 把 Messages 写出的响应转换为 Completions 格式：
+// 2xx SSE 响应逐段转换后立即写出，其余响应（非流式结果、错误）缓存到 finish 时整体转换
+type completionResponseWriter struct {
+	gin.ResponseWriter
+	req    *service.CompletionRequest
+	stream *service.CompletionStream
+
+	status    int
+	streaming bool
+	body      bytes.Buffer
+}
+
+func (w *completionResponseWriter) WriteHeader(code int) {
+	if w.status == 0 {
+		w.status = code
+	}
+}
+
+// WriteHeaderNow SSE 响应在此提交响应头，其余响应延迟到 finish
+func (w *completionResponseWriter) WriteHeaderNow() {
+	w.startStreaming()
+}
+
+func (w *completionResponseWriter) Write(data []byte) (int, error) {
+	if w.status == 0 {
+		w.status = http.StatusOK
+	}
+	if !w.startStreaming() {
+		return w.body.Write(data)
+	}
+	if out := w.stream.Write(data); len(out) > 0 {
+		if _, err := w.ResponseWriter.Write(out); err != nil {
+			return 0, err
+		}
+	}
+	return len(data), nil
+}
+
+func (w *completionResponseWriter) WriteString(s string) (int, error) {
+	return w.Write([]byte(s))
+}
+
+func (w *completionResponseWriter) Status() int {
+	if w.status != 0 {
+		return w.status
+	}
+	return w.ResponseWriter.Status()
+}
+
+func (w *completionResponseWriter) Written() bool {
+	return w.status != 0 || w.ResponseWriter.Written()
+}
+
+func (w *completionResponseWriter) Flush() {
+	if w.streaming {
+		w.ResponseWriter.Flush()
+	}
+}
+
+// startStreaming 响应为 2xx SSE 时提交响应头并进入流式转换，返回是否处于流式转换
+func (w *completionResponseWriter) startStreaming() bool {
+	if w.streaming {
+		return true
+	}
+	status := w.Status()
+	if status >= http.StatusMultipleChoices || !strings.HasPrefix(w.Header().Get("Content-Type"), "text/event-stream") {
+		return false
+	}
+	w.streaming = true
+	w.req.SystemFingerprint = w.Header().Get(service.SystemFingerprintHeader)
+	w.Header().Del("Content-Length")
+	w.ResponseWriter.WriteHeader(status)
+	w.ResponseWriter.WriteHeaderNow()
+	return true
+}
+
+// finish Messages 处理结束后写出缓存的响应
+func (w *completionResponseWriter) finish() {
+	if w.streaming {
+		if out := w.stream.Abort("stream ended before completion"); len(out) > 0 {
+			_, _ = w.ResponseWriter.Write(out)
+			w.ResponseWriter.Flush()
+		}
+		return
+	}
+	if w.status == 0 && w.body.Len() == 0 {
+		return
+	}
+	status := w.Status()
+	header := w.Header()
+	header.Del("Content-Length")
+	header.Set("Content-Type", "application/json; charset=utf-8")
+
+	var out []byte
+	if status < http.StatusMultipleChoices {
+		w.req.SystemFingerprint = header.Get(service.SystemFingerprintHeader)
+		converted, err := w.req.MessagesToCompletion(w.body.Bytes(), time.Now().Unix())
+		if err != nil {
+			status = http.StatusBadGateway
+			converted = completionErrorBody("upstream_error", "Invalid upstream response")
+		}
+		out = converted
+	} else {
+		out = completionErrorFromMessages(status, w.body.Bytes())
+	}
+	w.ResponseWriter.WriteHeader(status)
+	_, _ = w.ResponseWriter.Write(out)
+}
+
+// completionErrorFromMessages 将 Messages 返回的 Anthropic 格式错误（{"type":"error","error":{...}}）转换为 OpenAI 格式
+func completionErrorFromMessages(status int, body []byte) []byte {
+	parsed := gjson.ParseBytes(body)
+	errType := parsed.Get("error.type").String()
+	if errType == "" {
+		errType = "api_error"
+	}
+	message := parsed.Get("error.message").String()
+	if message == "" {
+		message = http.StatusText(status)
+	}
+	return completionErrorBody(errType, message)
+}
+
+func completionErrorBody(errType, message string) []byte {
+	body, _ := json.Marshal(gin.H{"error": gin.H{"type": errType, "message": message}})
+	return body
+}
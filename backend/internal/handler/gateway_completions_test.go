@@ -0,0 +1,142 @@
+package handler
+
+import (
+	"net/http"
+	"net/http/httptest"
+	"strings"
+	"testing"
+
+	"github.com/Wei-Shaw/sub2api/internal/service"
+	"github.com/gin-gonic/gin"
+	"github.com/stretchr/testify/require"
+	"github.com/tidwall/gjson"
+)
+
+func newCompletionTestContext(t *testing.T, body string) (*gin.Context, *httptest.ResponseRecorder, *completionResponseWriter) {
+	t.Helper()
+	gin.SetMode(gin.TestMode)
+	rec := httptest.NewRecorder()
+	c, _ := gin.CreateTestContext(rec)
+	c.Request = httptest.NewRequest(http.MethodPost, "/v1/completions", nil)
+
+	req, _, schemaErr := service.ConvertCompletionRequest([]byte(body), 1, nil)
+	require.Nil(t, schemaErr)
+	writer := &completionResponseWriter{ResponseWriter: c.Writer, req: req, stream: req.NewStream(1)}
+	c.Writer = writer
+	return c, rec, writer
+}
+
+func TestCompletionResponseWriter_ConvertsJSON(t *testing.T) {
+	c, rec, writer := newCompletionTestContext(t, `{"model":"m","prompt":"hi"}`)
+	c.Header("Content-Length", "999")
+	c.JSON(http.StatusOK, gin.H{"id": "msg_1", "content": []gin.H{{"type": "text", "text": "there"}}, "stop_reason": "end_turn", "usage": gin.H{"input_tokens": 3, "output_tokens": 1}})
+	require.Empty(t, rec.Body.String())
+	writer.finish()
+
+	require.Equal(t, http.StatusOK, rec.Code)
+	require.Empty(t, rec.Header().Get("Content-Length"))
+	require.Equal(t, "text_completion", gjson.Get(rec.Body.String(), "object").String())
+	require.Equal(t, "there", gjson.Get(rec.Body.String(), "choices.0.text").String())
+	require.Equal(t, int64(4), gjson.Get(rec.Body.String(), "usage.total_tokens").Int())
+}
+
+func TestCompletionResponseWriter_ConvertsError(t *testing.T) {
+	c, rec, writer := newCompletionTestContext(t, `{"model":"m","prompt":"hi","stream":true}`)
+	(&GatewayHandler{}).errorResponse(c, http.StatusTooManyRequests, "rate_limit_error", "slow down")
+	require.True(t, c.Writer.Written())
+	writer.finish()
+
+	require.Equal(t, http.StatusTooManyRequests, rec.Code)
+	require.False(t, gjson.Get(rec.Body.String(), "type").Exists())
+	require.Equal(t, "rate_limit_error", gjson.Get(rec.Body.String(), "error.type").String())
+	require.Equal(t, "slow down", gjson.Get(rec.Body.String(), "error.message").String())
+}
+
+func TestCompletionResponseWriter_StreamsSSE(t *testing.T) {
+	c, rec, writer := newCompletionTestContext(t, `{"model":"m","prompt":"hi","stream":true}`)
+	c.Header("Content-Type", "text/event-stream")
+	c.Status(http.StatusOK)
+	_, err := c.Writer.WriteString("event: message_start\ndata: {\"type\":\"message_start\",\"message\":{\"id\":\"msg_1\"}}\n\n" +
+		"event: content_block_delta\ndata: {\"type\":\"content_block_delta\",\"delta\":{\"type\":\"text_delta\",\"text\":\"yo\"}}\n\n")
+	require.NoError(t, err)
+	c.Writer.Flush()
+	// 流式转换立即写出，不等待 finish
+	require.Contains(t, rec.Body.String(), `"text":"yo"`)
+
+	writer.finish()
+	require.Equal(t, http.StatusOK, rec.Code)
+	require.True(t, strings.HasSuffix(rec.Body.String(), "\n\n"))
+	require.Contains(t, rec.Body.String(), "stream ended before completion")
+}
+
+func newCompletionCapture(status int, header http.Header, body string) *completionCapture {
+	capture := &completionCapture{header: header, status: status}
+	if capture.header == nil {
+		capture.header = make(http.Header)
+	}
+	capture.body.WriteString(body)
+	return capture
+}
+
+func TestWriteCompletionFanOut_PartialFailure(t *testing.T) {
+	gin.SetMode(gin.TestMode)
+	req, _, schemaErr := service.ConvertCompletionRequest([]byte(`{"model":"m","prompt":"hi","n":3}`), 3, nil)
+	require.Nil(t, schemaErr)
+
+	rec := httptest.NewRecorder()
+	c, _ := gin.CreateTestContext(rec)
+	writeCompletionFanOut(c, req, []*completionCapture{
+		newCompletionCapture(http.StatusTooManyRequests, nil, `{"type":"error","error":{"type":"rate_limit_error","message":"slow down"}}`),
+		newCompletionCapture(http.StatusOK, http.Header{service.SystemFingerprintHeader: {"fp_second"}}, `{"id":"msg_2","content":[{"type":"text","text":"two"}],"stop_reason":"end_turn","usage":{"input_tokens":3,"output_tokens":1}}`),
+		newCompletionCapture(http.StatusOK, nil, `{"id":"msg_3","content":[{"type":"text","text":"three"}],"stop_reason":"end_turn","usage":{"input_tokens":3,"output_tokens":2}}`),
+	})
+
+	// 成功的 choices 保留原 index，usage 只统计返回的 choices
+	require.Equal(t, http.StatusOK, rec.Code)
+	require.Equal(t, "0", rec.Header().Get(service.CompletionsFailedChoicesHeader))
+	require.Equal(t, "fp_second", rec.Header().Get(service.SystemFingerprintHeader))
+	require.Equal(t, `["two","three"]`, gjson.Get(rec.Body.String(), "choices.#.text").Raw)
+	require.Equal(t, `[1,2]`, gjson.Get(rec.Body.String(), "choices.#.index").Raw)
+	require.Equal(t, int64(9), gjson.Get(rec.Body.String(), "usage.total_tokens").Int())
+
+	// 全部失败时返回第一个错误
+	rec = httptest.NewRecorder()
+	c, _ = gin.CreateTestContext(rec)
+	writeCompletionFanOut(c, req, []*completionCapture{
+		newCompletionCapture(http.StatusTooManyRequests, nil, `{"type":"error","error":{"type":"rate_limit_error","message":"slow down"}}`),
+		newCompletionCapture(0, nil, ""),
+	})
+	require.Equal(t, http.StatusTooManyRequests, rec.Code)
+	require.Equal(t, "rate_limit_error", gjson.Get(rec.Body.String(), "error.type").String())
+	require.Empty(t, rec.Header().Get(service.CompletionsFailedChoicesHeader))
+}
+
+func TestCompletionCapture_CopiedContext(t *testing.T) {
+	gin.SetMode(gin.TestMode)
+	rec := httptest.NewRecorder()
+	c, _ := gin.CreateTestContext(rec)
+	c.Request = httptest.NewRequest(http.MethodPost, "/v1/completions", nil)
+	c.Set("api_key", "parent")
+
+	// 子请求在父 Context 副本上执行：读取父请求的 Keys，响应写入 capture 而不是客户端连接
+	sub := c.Copy()
+	capture := &completionCapture{header: make(http.Header)}
+	sub.Writer = capture
+	require.Equal(t, "parent", sub.MustGet("api_key"))
+	sub.Set("api_key", "child")
+	require.Equal(t, "parent", c.MustGet("api_key"))
+
+	require.False(t, sub.Writer.Written())
+	require.Equal(t, -1, sub.Writer.Size())
+	sub.Status(http.StatusTooManyRequests)
+	sub.JSON(http.StatusBadGateway, gin.H{"type": "error"})
+	require.True(t, sub.Writer.Written())
+	require.Equal(t, http.StatusBadGateway, capture.status)
+	require.JSONEq(t, `{"type":"error"}`, capture.body.String())
+
+	// 写出后状态码不再变化
+	sub.Status(http.StatusOK)
+	require.Equal(t, http.StatusBadGateway, sub.Writer.Status())
+	require.Empty(t, rec.Body.String())
+	require.False(t, c.Writer.Written())
+}
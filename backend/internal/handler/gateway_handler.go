@@ -6,7 +6,6 @@ import (
 	"encoding/json"
 	"errors"
 	"fmt"
-	"io"
 	"net/http"
 	"strings"
 	"time"
@@ -38,6 +37,13 @@ type GatewayHandler struct {
 	apiKeyService             *service.APIKeyService
 	usageRecordWorkerPool     *service.UsageRecordWorkerPool
 	errorPassthroughService   *service.ErrorPassthroughService
+	trafficMirrorService      *service.TrafficMirrorService
+	modelCanaryService        *service.ModelCanaryService
+	semanticCacheService      *service.SemanticCacheService
+	moderationService         *service.ModerationService
+	conversationService       *service.ConversationService
+	attachmentService         *service.AttachmentService
+	promptTemplateService     *service.PromptTemplateService
 	concurrencyHelper         *ConcurrencyHelper
 	maxAccountSwitches        int
 	maxAccountSwitchesGemini  int
@@ -56,6 +62,13 @@ func NewGatewayHandler(
 	apiKeyService *service.APIKeyService,
 	usageRecordWorkerPool *service.UsageRecordWorkerPool,
 	errorPassthroughService *service.ErrorPassthroughService,
+	trafficMirrorService *service.TrafficMirrorService,
+	modelCanaryService *service.ModelCanaryService,
+	semanticCacheService *service.SemanticCacheService,
+	moderationService *service.ModerationService,
+	conversationService *service.ConversationService,
+	attachmentService *service.AttachmentService,
+	promptTemplateService *service.PromptTemplateService,
 	cfg *config.Config,
 ) *GatewayHandler {
 	pingInterval := time.Duration(0)
@@ -80,6 +93,13 @@ func NewGatewayHandler(
 		apiKeyService:             apiKeyService,
 		usageRecordWorkerPool:     usageRecordWorkerPool,
 		errorPassthroughService:   errorPassthroughService,
+		trafficMirrorService:      trafficMirrorService,
+		modelCanaryService:        modelCanaryService,
+		semanticCacheService:      semanticCacheService,
+		moderationService:         moderationService,
+		conversationService:       conversationService,
+		attachmentService:         attachmentService,
+		promptTemplateService:     promptTemplateService,
 		concurrencyHelper:         NewConcurrencyHelper(concurrencyService, SSEPingFormatClaude, pingInterval),
 		maxAccountSwitches:        maxAccountSwitches,
 		maxAccountSwitchesGemini:  maxAccountSwitchesGemini,
@@ -111,7 +131,7 @@ func (h *GatewayHandler) Messages(c *gin.Context) {
 	)
 
 	// 读取请求体
-	body, err := io.ReadAll(c.Request.Body)
+	body, err := middleware2.ReadRequestBody(c.Request)
 	if err != nil {
 		if maxErr, ok := extractMaxBytesError(err); ok {
 			h.errorResponse(c, http.StatusRequestEntityTooLarge, "invalid_request_error", buildBodyTooLargeMessage(maxErr.Limit))
@@ -126,8 +146,45 @@ func (h *GatewayHandler) Messages(c *gin.Context) {
 		return
 	}
 
+	// 多模态附件：解析 multipart 请求并把文件引用转换为内联 image / document 内容块
+	body, ok = applyAttachments(c, h.attachmentService, service.AttachmentFormatAnthropic, apiKey, body, reqLog, h.errorResponse)
+	if !ok {
+		return
+	}
+
+	// 请求体结构校验（gateway.request_validation），返回带字段路径的错误
+	if schemaErr := service.AnthropicMessagesSchema.Validate(body, requestValidationMode(h.cfg)); schemaErr != nil {
+		h.errorResponse(c, http.StatusBadRequest, "invalid_request_error", schemaErr.Message)
+		return
+	}
+
+	// 提示词模板：携带 template_id 时渲染模板并拼接到 system / messages 之前
+	body, ok = applyPromptTemplate(c, h.promptTemplateService, service.PromptFormatAnthropic, apiKey, body, reqLog, h.errorResponse)
+	if !ok {
+		return
+	}
+
+	// 将 OpenAI 风格的 reasoning_effort / reasoning.effort 转换为 Anthropic thinking 参数
+	body = service.TranslateClaudeReasoningParams(body)
+
 	setOpsRequestContext(c, "", false, body)
 
+	// 提示词防火墙与内容审核：检查客户端原始提示词（系统提示词模板注入之前）
+	body, firewallDecision, ok := applyPromptFirewall(c, h.moderationService, body, service.PromptFormatAnthropic, reqLog, h.errorResponse)
+	if !ok {
+		return
+	}
+
+	// 金丝雀路由：模型别名按权重分流到不同目标模型
+	body, finishCanary := applyModelCanary(c, h.modelCanaryService, body, reqLog)
+	defer finishCanary()
+
+	// 服务端会话：携带 conversation_id 时补全历史消息（需在解析与 max_tokens 校验之前）
+	body, conversationTurn, ok := applyConversation(c, h.conversationService, apiKey, body, reqLog, h.errorResponse)
+	if !ok {
+		return
+	}
+
 	parsedReq, err := service.ParseGatewayRequest(body, domain.PlatformAnthropic)
 	if err != nil {
 		h.errorResponse(c, http.StatusBadRequest, "invalid_request_error", "Failed to parse request body")
@@ -148,6 +205,16 @@ func (h *GatewayHandler) Messages(c *gin.Context) {
 	SetClaudeCodeClientContext(c, body)
 	isClaudeCodeClient := service.IsClaudeCodeClient(c.Request.Context())
 
+	// 按 API Key / 分组策略注入系统提示词模板（客户端识别基于原始请求体，注入后重新解析）
+	if injected, ok := service.ApplyClaudeSystemPrompt(body, service.APIKeyPolicyFromContext(c.Request.Context()).SystemPrompt); ok {
+		reparsed, err := service.ParseGatewayRequest(injected, domain.PlatformAnthropic)
+		if err != nil {
+			h.errorResponse(c, http.StatusBadRequest, "invalid_request_error", "Failed to parse request body")
+			return
+		}
+		body, parsedReq = injected, reparsed
+	}
+
 	// 在请求上下文中记录 thinking 状态，供 Antigravity 最终模型 key 推导/模型维度限流使用
 	c.Request = c.Request.WithContext(context.WithValue(c.Request.Context(), ctxkey.ThinkingEnabled, parsedReq.ThinkingEnabled))
 
@@ -159,6 +226,23 @@ func (h *GatewayHandler) Messages(c *gin.Context) {
 		return
 	}
 
+	// 按 API Key / 分组策略填充或下调 max_tokens，并校验上下文窗口
+	adjusted, changed, err := h.gatewayService.ApplyMaxTokensPolicy(c.Request.Context(), body, reqModel, c.GetHeader("anthropic-beta"))
+	if err != nil {
+		reqLog.Info("gateway.max_tokens_rejected", zap.Error(err))
+		h.errorResponse(c, http.StatusBadRequest, "invalid_request_error", pkgerrors.Message(err))
+		return
+	}
+	if changed {
+		reparsed, err := service.ParseGatewayRequest(adjusted, domain.PlatformAnthropic)
+		if err != nil {
+			h.errorResponse(c, http.StatusBadRequest, "invalid_request_error", "Failed to parse request body")
+			return
+		}
+		body, parsedReq = adjusted, reparsed
+		setOpsRequestContext(c, reqModel, reqStream, body)
+	}
+
 	// Track if we've started streaming (for error handling)
 	streamStarted := false
 
@@ -170,6 +254,13 @@ func (h *GatewayHandler) Messages(c *gin.Context) {
 	// 获取订阅信息（可能为nil）- 提前获取用于后续检查
 	subscription, _ := middleware2.GetSubscriptionFromContext(c)
 
+	// 语义缓存：开启的 API Key 命中相似问题时直接返回缓存响应（不占并发槽位、不转发、不计费）
+	semanticCache, served := h.semanticCacheService.Lookup(c, service.SemanticCacheTypeMessages, apiKey, reqModel, reqStream, body)
+	if served {
+		conversationTurn.Complete()
+		return
+	}
+
 	// 0. 检查wait队列是否已满
 	maxWait := service.CalculateMaxWait(subject.Concurrency)
 	canWait, err := h.concurrencyHelper.IncrementWaitCount(c.Request.Context(), subject.UserID, maxWait)
@@ -224,6 +315,7 @@ func (h *GatewayHandler) Messages(c *gin.Context) {
 		UserAgent: c.GetHeader("User-Agent"),
 		APIKeyID:  apiKey.ID,
 	}
+	parsedReq.ConversationKey = conversationTurn.SessionKey()
 	sessionHash := h.gatewayService.GenerateSessionHash(parsedReq)
 
 	// 获取平台：优先使用强制平台（/antigravity 路由，中间件已设置 request.Context），否则使用分组平台
@@ -255,6 +347,9 @@ func (h *GatewayHandler) Messages(c *gin.Context) {
 	// 判断是否真的绑定了粘性会话：有 sessionKey 且已经绑定到某个账号
 	hasBoundSession := sessionKey != "" && sessionBoundAccountID > 0
 
+	// 流量镜像：抽中的请求在主请求成功后异步复制到规则指定的账号/模型，不影响客户端响应
+	mirror := h.trafficMirrorService.Start(c, service.TrafficMirrorTypeMessages, apiKey, reqModel, body)
+
 	if platform == service.PlatformGemini {
 		fs := NewFailoverState(h.maxAccountSwitchesGemini, hasBoundSession)
 
@@ -282,7 +377,7 @@ func (h *GatewayHandler) Messages(c *gin.Context) {
 					return
 				default: // FailoverExhausted
 					if fs.LastFailoverErr != nil {
-						h.handleFailoverExhausted(c, fs.LastFailoverErr, service.PlatformGemini, streamStarted)
+						h.handleFailoverExhausted(c, fs.LastFailoverErr, fs.FailedAccountIDs, service.PlatformGemini, streamStarted)
 					} else {
 						h.handleFailoverExhaustedSimple(c, 502, streamStarted)
 					}
@@ -361,8 +456,9 @@ func (h *GatewayHandler) Messages(c *gin.Context) {
 			accountReleaseFunc = wrapReleaseOnDone(c.Request.Context(), accountReleaseFunc)
 
 			// 转发请求 - 根据账号平台分流
+			c.Header(service.SystemFingerprintHeader, service.SystemFingerprint(account, reqModel))
 			var result *service.ForwardResult
-			requestCtx := c.Request.Context()
+			requestCtx := service.WithUpstreamTimeouts(service.WithUpstreamAdapter(c.Request.Context(), account.Platform), service.ResolveUpstreamTimeouts(h.cfg, account.Platform, reqModel))
 			if fs.SwitchCount > 0 {
 				requestCtx = context.WithValue(requestCtx, ctxkey.AccountSwitchCount, fs.SwitchCount)
 			}
@@ -382,7 +478,7 @@ func (h *GatewayHandler) Messages(c *gin.Context) {
 					case FailoverContinue:
 						continue
 					case FailoverExhausted:
-						h.handleFailoverExhausted(c, fs.LastFailoverErr, service.PlatformGemini, streamStarted)
+						h.handleFailoverExhausted(c, fs.LastFailoverErr, fs.FailedAccountIDs, service.PlatformGemini, streamStarted)
 						return
 					case FailoverCanceled:
 						return
@@ -397,9 +493,14 @@ func (h *GatewayHandler) Messages(c *gin.Context) {
 				return
 			}
 
+			mirror.Complete(account.ID, result.Duration)
+			semanticCache.Complete()
+			conversationTurn.Complete()
+
 			// 捕获请求信息（用于异步记录，避免在 goroutine 中访问 gin.Context）
 			userAgent := c.GetHeader("User-Agent")
 			clientIP := ip.GetClientIP(c)
+			creditHold := service.ClaimCreditHold(c.Request.Context())
 
 			// 使用量记录通过有界 worker 池提交，避免请求热路径创建无界 goroutine。
 			h.submitUsageRecordTask(func(ctx context.Context) {
@@ -412,7 +513,9 @@ func (h *GatewayHandler) Messages(c *gin.Context) {
 					UserAgent:         userAgent,
 					IPAddress:         clientIP,
 					ForceCacheBilling: fs.ForceCacheBilling,
+					PromptFirewall:    firewallDecision,
 					APIKeyService:     h.apiKeyService,
+					CreditHold:        creditHold,
 				}); err != nil {
 					logger.L().With(
 						zap.String("component", "handler.gateway.messages"),
@@ -465,7 +568,7 @@ func (h *GatewayHandler) Messages(c *gin.Context) {
 					return
 				default: // FailoverExhausted
 					if fs.LastFailoverErr != nil {
-						h.handleFailoverExhausted(c, fs.LastFailoverErr, platform, streamStarted)
+						h.handleFailoverExhausted(c, fs.LastFailoverErr, fs.FailedAccountIDs, platform, streamStarted)
 					} else {
 						h.handleFailoverExhaustedSimple(c, 502, streamStarted)
 					}
@@ -544,8 +647,9 @@ func (h *GatewayHandler) Messages(c *gin.Context) {
 			accountReleaseFunc = wrapReleaseOnDone(c.Request.Context(), accountReleaseFunc)
 
 			// 转发请求 - 根据账号平台分流
+			c.Header(service.SystemFingerprintHeader, service.SystemFingerprint(account, reqModel))
 			var result *service.ForwardResult
-			requestCtx := c.Request.Context()
+			requestCtx := service.WithUpstreamTimeouts(service.WithUpstreamAdapter(c.Request.Context(), account.Platform), service.ResolveUpstreamTimeouts(h.cfg, account.Platform, reqModel))
 			if fs.SwitchCount > 0 {
 				requestCtx = context.WithValue(requestCtx, ctxkey.AccountSwitchCount, fs.SwitchCount)
 			}
@@ -608,7 +712,7 @@ func (h *GatewayHandler) Messages(c *gin.Context) {
 					case FailoverContinue:
 						continue
 					case FailoverExhausted:
-						h.handleFailoverExhausted(c, fs.LastFailoverErr, account.Platform, streamStarted)
+						h.handleFailoverExhausted(c, fs.LastFailoverErr, fs.FailedAccountIDs, account.Platform, streamStarted)
 						return
 					case FailoverCanceled:
 						return
@@ -623,9 +727,14 @@ func (h *GatewayHandler) Messages(c *gin.Context) {
 				return
 			}
 
+			mirror.Complete(account.ID, result.Duration)
+			semanticCache.Complete()
+			conversationTurn.Complete()
+
 			// 捕获请求信息（用于异步记录，避免在 goroutine 中访问 gin.Context）
 			userAgent := c.GetHeader("User-Agent")
 			clientIP := ip.GetClientIP(c)
+			creditHold := service.ClaimCreditHold(c.Request.Context())
 
 			// 使用量记录通过有界 worker 池提交，避免请求热路径创建无界 goroutine。
 			h.submitUsageRecordTask(func(ctx context.Context) {
@@ -638,7 +747,9 @@ func (h *GatewayHandler) Messages(c *gin.Context) {
 					UserAgent:         userAgent,
 					IPAddress:         clientIP,
 					ForceCacheBilling: fs.ForceCacheBilling,
+					PromptFirewall:    firewallDecision,
 					APIKeyService:     h.apiKeyService,
+					CreditHold:        creditHold,
 				}); err != nil {
 					logger.L().With(
 						zap.String("component", "handler.gateway.messages"),
@@ -687,15 +798,20 @@ func (h *GatewayHandler) Models(c *gin.Context) {
 	// Get available models from account configurations (without platform filter)
 	availableModels := h.gatewayService.GetAvailableModels(c.Request.Context(), groupID, "")
 
+	capabilities := modelCapabilities{Logprobs: service.LogprobsCapability(platform)}
+
 	if len(availableModels) > 0 {
 		// Build model list from whitelist
-		models := make([]claude.Model, 0, len(availableModels))
+		models := make([]claudeModelEntry, 0, len(availableModels))
 		for _, modelID := range availableModels {
-			models = append(models, claude.Model{
-				ID:          modelID,
-				Type:        "model",
-				DisplayName: modelID,
-				CreatedAt:   "2024-01-01T00:00:00Z",
+			models = append(models, claudeModelEntry{
+				Model: claude.Model{
+					ID:          modelID,
+					Type:        "model",
+					DisplayName: modelID,
+					CreatedAt:   "2024-01-01T00:00:00Z",
+				},
+				Capabilities: capabilities,
 			})
 		}
 		c.JSON(http.StatusOK, gin.H{
@@ -707,19 +823,43 @@ func (h *GatewayHandler) Models(c *gin.Context) {
 
 	// Fallback to default models
 	if platform == "openai" {
+		models := make([]openAIModelEntry, 0, len(openai.DefaultModels))
+		for _, model := range openai.DefaultModels {
+			models = append(models, openAIModelEntry{Model: model, Capabilities: capabilities})
+		}
 		c.JSON(http.StatusOK, gin.H{
 			"object": "list",
-			"data":   openai.DefaultModels,
+			"data":   models,
 		})
 		return
 	}
 
+	models := make([]claudeModelEntry, 0, len(claude.DefaultModels))
+	for _, model := range claude.DefaultModels {
+		models = append(models, claudeModelEntry{Model: model, Capabilities: capabilities})
+	}
 	c.JSON(http.StatusOK, gin.H{
 		"object": "list",
-		"data":   claude.DefaultModels,
+		"data":   models,
 	})
 }
 
+// modelCapabilities /v1/models 条目附带的能力标记，供评测等工具判断可用参数
+type modelCapabilities struct {
+	// Logprobs passthrough：透传上游 logprobs；emulated：返回标记为 emulated 的空结构
+	Logprobs string `json:"logprobs"`
+}
+
+type claudeModelEntry struct {
+	claude.Model
+	Capabilities modelCapabilities `json:"capabilities"`
+}
+
+type openAIModelEntry struct {
+	openai.Model
+	Capabilities modelCapabilities `json:"capabilities"`
+}
+
 // AntigravityModels 返回 Antigravity 支持的全部模型
 // GET /antigravity/models
 func (h *GatewayHandler) AntigravityModels(c *gin.Context) {
@@ -890,11 +1030,12 @@ func (h *GatewayHandler) calculateSubscriptionRemaining(group *service.Group, su
 
 // handleConcurrencyError handles concurrency-related errors with proper 429 response
 func (h *GatewayHandler) handleConcurrencyError(c *gin.Context, err error, slotType string, streamStarted bool) {
+	setConcurrencyRetryAfter(c, err, streamStarted)
 	h.handleStreamingAwareError(c, http.StatusTooManyRequests, "rate_limit_error",
 		fmt.Sprintf("Concurrency limit exceeded for %s, please retry later", slotType), streamStarted)
 }
 
-func (h *GatewayHandler) handleFailoverExhausted(c *gin.Context, failoverErr *service.UpstreamFailoverError, platform string, streamStarted bool) {
+func (h *GatewayHandler) handleFailoverExhausted(c *gin.Context, failoverErr *service.UpstreamFailoverError, failedAccountIDs map[int64]struct{}, platform string, streamStarted bool) {
 	statusCode := failoverErr.StatusCode
 	responseBody := failoverErr.ResponseBody
 
@@ -917,37 +1058,28 @@ func (h *GatewayHandler) handleFailoverExhausted(c *gin.Context, failoverErr *se
 				c.Set(service.OpsSkipPassthroughKey, true)
 			}
 
+			setGatewayRetryAfter(c, h.gatewayService, respCode, failedAccountIDs, streamStarted)
 			h.handleStreamingAwareError(c, respCode, "upstream_error", msg, streamStarted)
 			return
 		}
 	}
 
 	// 使用默认的错误映射
-	status, errType, errMsg := h.mapUpstreamError(statusCode)
+	status, errType, errMsg := h.mapUpstreamError(statusCode, responseBody)
+	setGatewayRetryAfter(c, h.gatewayService, status, failedAccountIDs, streamStarted)
 	h.handleStreamingAwareError(c, status, errType, errMsg, streamStarted)
 }
 
 // handleFailoverExhaustedSimple 简化版本，用于没有响应体的情况
 func (h *GatewayHandler) handleFailoverExhaustedSimple(c *gin.Context, statusCode int, streamStarted bool) {
-	status, errType, errMsg := h.mapUpstreamError(statusCode)
+	status, errType, errMsg := h.mapUpstreamError(statusCode, nil)
 	h.handleStreamingAwareError(c, status, errType, errMsg, streamStarted)
 }
 
-func (h *GatewayHandler) mapUpstreamError(statusCode int) (int, string, string) {
-	switch statusCode {
-	case 401:
-		return http.StatusBadGateway, "upstream_error", "Upstream authentication failed, please contact administrator"
-	case 403:
-		return http.StatusBadGateway, "upstream_error", "Upstream access forbidden, please contact administrator"
-	case 429:
-		return http.StatusTooManyRequests, "rate_limit_error", "Upstream rate limit exceeded, please retry later"
-	case 529:
-		return http.StatusServiceUnavailable, "overloaded_error", "Upstream service overloaded, please retry later"
-	case 500, 502, 503, 504:
-		return http.StatusBadGateway, "upstream_error", "Upstream service temporarily unavailable"
-	default:
-		return http.StatusBadGateway, "upstream_error", "Upstream request failed"
-	}
+// mapUpstreamError 按统一的上游错误分类映射状态码、错误类型与消息
+func (h *GatewayHandler) mapUpstreamError(statusCode int, responseBody []byte) (int, string, string) {
+	mapped := service.MapUpstreamError(service.ClassifyUpstreamError(statusCode, responseBody), service.UpstreamErrorFormatAnthropic)
+	return mapped.Status, mapped.Type, mapped.Message
 }
 
 // handleStreamingAwareError handles errors that may occur after streaming has started
@@ -1026,7 +1158,7 @@ func (h *GatewayHandler) CountTokens(c *gin.Context) {
 	)
 
 	// 读取请求体
-	body, err := io.ReadAll(c.Request.Body)
+	body, err := middleware2.ReadRequestBody(c.Request)
 	if err != nil {
 		if maxErr, ok := extractMaxBytesError(err); ok {
 			h.errorResponse(c, http.StatusRequestEntityTooLarge, "invalid_request_error", buildBodyTooLargeMessage(maxErr.Limit))
@@ -1041,9 +1173,20 @@ func (h *GatewayHandler) CountTokens(c *gin.Context) {
 		return
 	}
 
+	if schemaErr := service.AnthropicMessagesSchema.Validate(body, requestValidationMode(h.cfg)); schemaErr != nil {
+		h.errorResponse(c, http.StatusBadRequest, "invalid_request_error", schemaErr.Message)
+		return
+	}
+
 	// 检查是否为 Claude Code 客户端，设置到 context 中
 	SetClaudeCodeClientContext(c, body)
 
+	// 将 OpenAI 风格的 reasoning_effort / reasoning.effort 转换为 Anthropic thinking 参数
+	body = service.TranslateClaudeReasoningParams(body)
+
+	// 与 Messages 保持一致地注入系统提示词模板，使计数包含注入内容
+	body, _ = service.ApplyClaudeSystemPrompt(body, service.APIKeyPolicyFromContext(c.Request.Context()).SystemPrompt)
+
 	setOpsRequestContext(c, "", false, body)
 
 	parsedReq, err := service.ParseGatewayRequest(body, domain.PlatformAnthropic)
@@ -1074,6 +1217,15 @@ func (h *GatewayHandler) CountTokens(c *gin.Context) {
 		return
 	}
 
+	// 本地估算：无需占用上游账号；需要精确结果或包含无法本地估算的内容时才转发上游
+	mode := h.gatewayService.CountTokensMode()
+	estimated, reliable := service.EstimateClaudeInputTokens(body)
+	precise := c.Query("precise") == "true"
+	if mode == service.CountTokensModeLocal || (mode == service.CountTokensModeAuto && reliable && !precise) {
+		c.JSON(http.StatusOK, gin.H{"input_tokens": estimated})
+		return
+	}
+
 	// 计算粘性会话 hash
 	parsedReq.SessionContext = &service.SessionContext{
 		ClientIP:  ip.GetClientIP(c),
@@ -1086,6 +1238,11 @@ func (h *GatewayHandler) CountTokens(c *gin.Context) {
 	account, err := h.gatewayService.SelectAccountForModel(c.Request.Context(), apiKey.GroupID, sessionHash, parsedReq.Model)
 	if err != nil {
 		reqLog.Warn("gateway.count_tokens_select_account_failed", zap.Error(err))
+		// auto 模式下无可用账号时降级为本地估算，而不是直接失败
+		if mode == service.CountTokensModeAuto {
+			c.JSON(http.StatusOK, gin.H{"input_tokens": estimated})
+			return
+		}
 		h.errorResponse(c, http.StatusServiceUnavailable, "api_error", "Service temporarily unavailable")
 		return
 	}
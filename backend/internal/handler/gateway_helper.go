@@ -3,13 +3,17 @@ package handler
 import (
 	"context"
 	"encoding/json"
+	"errors"
 	"fmt"
+	"math"
 	"math/rand/v2"
 	"net/http"
+	"strconv"
 	"strings"
 	"sync"
 	"time"
 
+	middleware2 "github.com/Wei-Shaw/sub2api/internal/server/middleware"
 	"github.com/Wei-Shaw/sub2api/internal/service"
 
 	"github.com/gin-gonic/gin"
@@ -89,9 +93,14 @@ const (
 type ConcurrencyError struct {
 	SlotType  string
 	IsTimeout bool
+	// RetryAfter 准入控制拒绝时按当前吞吐估算的重试等待时间
+	RetryAfter time.Duration
 }
 
 func (e *ConcurrencyError) Error() string {
+	if e.RetryAfter > 0 {
+		return fmt.Sprintf("estimated wait for %s concurrency slot exceeds admission limit", e.SlotType)
+	}
 	if e.IsTimeout {
 		return fmt.Sprintf("timeout waiting for %s concurrency slot", e.SlotType)
 	}
@@ -238,11 +247,28 @@ func (h *ConcurrencyHelper) waitForSlotWithPingTimeout(c *gin.Context, slotType
 	ctx, cancel := context.WithTimeout(c.Request.Context(), timeout)
 	defer cancel()
 
+	// 账号槽位等待按用户公平调度：仅轮到的请求尝试获取槽位，未启用时 ticket 为 nil（始终轮到）
+	var ticket *service.FairShareTicket
+	if slotType == "account" {
+		if subject, ok := middleware2.GetAuthSubjectFromContext(c); ok {
+			weight := service.APIKeyPolicyFromContext(ctx).FairShareWeight
+			ticket = h.concurrencyService.EnterFairShare(id, subject.UserID, weight)
+			defer ticket.Leave()
+		}
+	}
+
 	acquireSlot := func() (*service.AcquireResult, error) {
 		if slotType == "user" {
 			return h.concurrencyService.AcquireUserSlot(ctx, id, maxConcurrency)
 		}
-		return h.concurrencyService.AcquireAccountSlot(ctx, id, maxConcurrency)
+		if !ticket.IsTurn() {
+			return &service.AcquireResult{}, nil
+		}
+		result, err := h.concurrencyService.AcquireAccountSlot(ctx, id, maxConcurrency)
+		if err == nil && result.Acquired {
+			ticket.Acquired()
+		}
+		return result, err
 	}
 
 	if tryImmediate {
@@ -255,6 +281,15 @@ func (h *ConcurrencyHelper) waitForSlotWithPingTimeout(c *gin.Context, slotType
 		}
 	}
 
+	// 准入控制：按账号当前吞吐估算的排队时间超过上限时直接拒绝，不进入等待
+	if slotType == "account" {
+		admission, retryAfter, ok := h.concurrencyService.EnterAdmission(id, timeout)
+		if !ok {
+			return nil, &ConcurrencyError{SlotType: slotType, RetryAfter: retryAfter}
+		}
+		defer admission.Leave()
+	}
+
 	// Determine if ping is needed (streaming + ping format defined)
 	needPing := isStream && h.pingFormat != ""
 
@@ -301,6 +336,16 @@ func (h *ConcurrencyHelper) waitForSlotWithPingTimeout(c *gin.Context, slotType
 			}
 			flusher.Flush()
 
+		case <-ticket.Turn():
+			// 轮到本请求（前一个请求已获得槽位或放弃排队），立即尝试
+			result, err := acquireSlot()
+			if err != nil {
+				return nil, err
+			}
+			if result.Acquired {
+				return result.ReleaseFunc, nil
+			}
+
 		case <-timer.C:
 			// Try to acquire slot
 			result, err := acquireSlot()
@@ -322,6 +367,31 @@ func (h *ConcurrencyHelper) AcquireAccountSlotWithWaitTimeout(c *gin.Context, ac
 	return h.waitForSlotWithPingTimeout(c, "account", accountID, maxConcurrency, timeout, isStream, streamStarted, true)
 }
 
+// setConcurrencyRetryAfter 准入控制拒绝时设置 Retry-After 响应头（秒，向上取整）；流已开始时无法设置响应头
+func setConcurrencyRetryAfter(c *gin.Context, err error, streamStarted bool) {
+	var concurrencyErr *ConcurrencyError
+	if streamStarted || !errors.As(err, &concurrencyErr) || concurrencyErr.RetryAfter <= 0 {
+		return
+	}
+	c.Header("Retry-After", strconv.Itoa(int(math.Ceil(concurrencyErr.RetryAfter.Seconds()))))
+}
+
+// retryAfterEstimator 估算失败账号中最早恢复可用的等待时长
+type retryAfterEstimator interface {
+	RetryAfter(ctx context.Context, accountIDs map[int64]struct{}) (time.Duration, bool)
+}
+
+// setGatewayRetryAfter 换号耗尽后返回限流/过载错误时，按网关记录的账号限流窗口设置 Retry-After 与 X-RateLimit-Reset-Requests
+// （而非透传单个上游账号的原始限流头）；流已开始时无法设置响应头
+func setGatewayRetryAfter(c *gin.Context, estimator retryAfterEstimator, status int, failedAccountIDs map[int64]struct{}, streamStarted bool) {
+	if streamStarted || estimator == nil || (status != http.StatusTooManyRequests && status != http.StatusServiceUnavailable) {
+		return
+	}
+	if wait, ok := estimator.RetryAfter(c.Request.Context(), failedAccountIDs); ok {
+		service.SetGatewayRetryAfterHeaders(c.Writer.Header(), wait)
+	}
+}
+
 // nextBackoff 计算下一次退避时间
 // 性能优化：使用指数退避 + 随机抖动，避免惊群效应
 // current: 当前退避时间
@@ -267,3 +267,29 @@ type helperConcurrencyCacheStubWithError struct {
 func (s *helperConcurrencyCacheStubWithError) AcquireAccountSlot(ctx context.Context, accountID int64, maxConcurrency int, requestID string) (bool, error) {
 	return false, s.err
 }
+
+func TestWaitForSlotWithPingTimeout_AdmissionRejectsWithRetryAfter(t *testing.T) {
+	cache := &helperConcurrencyCacheStub{
+		accountSeq: []bool{true, false},
+	}
+	concurrency := service.NewConcurrencyService(cache)
+	concurrency.EnableAdmissionControl(500 * time.Millisecond)
+
+	// 一次完成记录：吞吐约 1 req/s，估算排队 1s 超过 500ms 上限
+	result, err := concurrency.AcquireAccountSlot(context.Background(), 401, 1)
+	require.NoError(t, err)
+	result.ReleaseFunc()
+
+	helper := NewConcurrencyHelper(concurrency, SSEPingFormatNone, 5*time.Millisecond)
+	c, _ := newHelperTestContext(http.MethodPost, "/v1/messages")
+	streamStarted := false
+	release, err := helper.AcquireAccountSlotWithWaitTimeout(c, 401, 1, time.Second, false, &streamStarted)
+	require.Nil(t, release)
+	var cErr *ConcurrencyError
+	require.ErrorAs(t, err, &cErr)
+	require.False(t, cErr.IsTimeout)
+	require.Equal(t, time.Second, cErr.RetryAfter)
+
+	setConcurrencyRetryAfter(c, err, streamStarted)
+	require.Equal(t, "1", c.Writer.Header().Get("Retry-After"))
+}
@@ -7,7 +7,6 @@ import (
 	"encoding/hex"
 	"encoding/json"
 	"errors"
-	"io"
 	"net/http"
 	"regexp"
 	"strings"
@@ -168,7 +167,7 @@ func (h *GatewayHandler) GeminiV1BetaModels(c *gin.Context) {
 	stream := action == "streamGenerateContent"
 	reqLog = reqLog.With(zap.String("model", modelName), zap.String("action", action), zap.Bool("stream", stream))
 
-	body, err := io.ReadAll(c.Request.Body)
+	body, err := middleware.ReadRequestBody(c.Request)
 	if err != nil {
 		if maxErr, ok := extractMaxBytesError(err); ok {
 			googleError(c, http.StatusRequestEntityTooLarge, buildBodyTooLargeMessage(maxErr.Limit))
@@ -181,6 +180,12 @@ func (h *GatewayHandler) GeminiV1BetaModels(c *gin.Context) {
 		googleError(c, http.StatusBadRequest, "Request body is empty")
 		return
 	}
+	if action == "generateContent" || action == "streamGenerateContent" {
+		if schemaErr := service.GeminiGenerateContentSchema.Validate(body, requestValidationMode(h.cfg)); schemaErr != nil {
+			googleError(c, http.StatusBadRequest, schemaErr.Message)
+			return
+		}
+	}
 
 	setOpsRequestContext(c, modelName, stream, body)
 
@@ -438,6 +443,7 @@ func (h *GatewayHandler) GeminiV1BetaModels(c *gin.Context) {
 			)
 			if err != nil {
 				reqLog.Warn("gemini.account_slot_acquire_failed", zap.Int64("account_id", account.ID), zap.Error(err))
+				setConcurrencyRetryAfter(c, err, streamStarted)
 				googleError(c, http.StatusTooManyRequests, err.Error())
 				return
 			}
@@ -453,8 +459,9 @@ func (h *GatewayHandler) GeminiV1BetaModels(c *gin.Context) {
 		accountReleaseFunc = wrapReleaseOnDone(c.Request.Context(), accountReleaseFunc)
 
 		// 5) forward (根据平台分流)
+		c.Header(service.SystemFingerprintHeader, service.SystemFingerprint(account, modelName))
 		var result *service.ForwardResult
-		requestCtx := c.Request.Context()
+		requestCtx := service.WithUpstreamTimeouts(service.WithUpstreamAdapter(c.Request.Context(), account.Platform), service.ResolveUpstreamTimeouts(h.cfg, account.Platform, modelName))
 		if fs.SwitchCount > 0 {
 			requestCtx = context.WithValue(requestCtx, ctxkey.AccountSwitchCount, fs.SwitchCount)
 		}
@@ -488,6 +495,7 @@ func (h *GatewayHandler) GeminiV1BetaModels(c *gin.Context) {
 		// 捕获请求信息（用于异步记录，避免在 goroutine 中访问 gin.Context）
 		userAgent := c.GetHeader("User-Agent")
 		clientIP := ip.GetClientIP(c)
+		creditHold := service.ClaimCreditHold(c.Request.Context())
 
 		// 保存 Gemini 内容摘要会话（用于 Fallback 匹配）
 		if useDigestFallback && geminiDigestChain != "" && geminiPrefixHash != "" {
@@ -518,6 +526,7 @@ func (h *GatewayHandler) GeminiV1BetaModels(c *gin.Context) {
 				LongContextMultiplier: 2.0,    // 超出部分双倍计费
 				ForceCacheBilling:     fs.ForceCacheBilling,
 				APIKeyService:         h.apiKeyService,
+				CreditHold:            creditHold,
 			}); err != nil {
 				logger.L().With(
 					zap.String("component", "handler.gemini_v1beta.models"),
@@ -25,6 +25,34 @@ type AdminHandlers struct {
 	Usage            *admin.UsageHandler
 	UserAttribute    *admin.UserAttributeHandler
 	ErrorPassthrough *admin.ErrorPassthroughHandler
+	WebSession       *admin.WebSessionHandler
+	Trash            *admin.TrashHandler
+	List             *admin.ListHandler
+	JobQueue         *admin.JobQueueHandler
+	CronJob          *admin.CronJobHandler
+	UsageExport      *admin.UsageExportHandler
+	APIKeyPolicy     *admin.APIKeyPolicyHandler
+	TrafficMirror    *admin.TrafficMirrorHandler
+	ModelCanary      *admin.ModelCanaryHandler
+	AccountHealth    *admin.AccountHealthHandler
+	LiveUsage        *admin.LiveUsageHandler
+	RequestLog       *admin.RequestLogHandler
+	DataSubject      *admin.DataSubjectHandler
+	FeatureFlag      *admin.FeatureFlagHandler
+	Maintenance      *admin.MaintenanceHandler
+	SpendingCap      *admin.SpendingCapHandler
+	PrepaidCredit    *admin.PrepaidCreditHandler
+	Statement        *admin.StatementHandler
+	Payment          *admin.PaymentHandler
+	Staff            *admin.StaffHandler
+	Team             *admin.TeamHandler
+	Notification     *admin.NotificationHandler
+	Copilot          *admin.CopilotHandler
+	SessionImport    *admin.SessionImportHandler
+	Challenge        *admin.ChallengeHandler
+	AccountShard     *admin.AccountShardHandler
+	GraphQL          *admin.GraphQLHandler
+	RoutingRule      *admin.RoutingRuleHandler
 }
 
 // Handlers contains all HTTP handlers
@@ -42,6 +70,9 @@ type Handlers struct {
 	SoraGateway   *SoraGatewayHandler
 	Setting       *SettingHandler
 	Totp          *TotpHandler
+	Payment       *PaymentHandler
+	Team          *TeamHandler
+	Notification  *NotificationHandler
 }
 
 // BuildInfo contains build-time information
@@ -0,0 +1,31 @@
+package handler
+
+import (
+	"net/http"
+
+	"github.com/Wei-Shaw/sub2api/internal/service"
+	"github.com/gin-gonic/gin"
+	"go.uber.org/zap"
+)
+
+// applyModelCanary 请求模型命中金丝雀路由时按权重改写为目标模型。
+//
+// 返回的 finish 需在请求结束时调用（defer），记录目标的成功率与耗时；
+// 仅统计已进入账号调度的请求，参数校验、并发限制等网关侧拒绝不计入目标统计。
+func applyModelCanary(c *gin.Context, svc *service.ModelCanaryService, body []byte, reqLog *zap.Logger) ([]byte, func()) {
+	routed, decision := svc.Route(c.Request.Context(), body)
+	if decision == nil {
+		return body, func() {}
+	}
+	reqLog.Debug("gateway.model_canary_routed",
+		zap.Int64("canary_route_id", decision.RouteID),
+		zap.String("canary_target", decision.Target),
+		zap.String("upstream_model", decision.Model),
+	)
+	return routed, func() {
+		if _, selected := c.Get(opsAccountIDKey); !selected {
+			return
+		}
+		decision.Finish(c.Writer.Status() < http.StatusBadRequest)
+	}
+}
@@ -0,0 +1,64 @@
+package handler
+
+import (
+	"github.com/Wei-Shaw/sub2api/internal/pkg/response"
+	middleware2 "github.com/Wei-Shaw/sub2api/internal/server/middleware"
+	"github.com/Wei-Shaw/sub2api/internal/service"
+
+	"github.com/gin-gonic/gin"
+)
+
+// NotificationHandler handles the current user's email notification preferences
+type NotificationHandler struct {
+	notificationService *service.NotificationService
+}
+
+// NewNotificationHandler creates a new NotificationHandler
+func NewNotificationHandler(notificationService *service.NotificationService) *NotificationHandler {
+	return &NotificationHandler{notificationService: notificationService}
+}
+
+// UpdateNotificationPreferencesRequest maps notification kind to whether emails are wanted
+type UpdateNotificationPreferencesRequest struct {
+	Email map[string]bool `json:"email" binding:"required"`
+}
+
+// GetPreferences returns the user's preference for each notification kind
+// GET /api/v1/user/notification-preferences
+func (h *NotificationHandler) GetPreferences(c *gin.Context) {
+	subject, ok := middleware2.GetAuthSubjectFromContext(c)
+	if !ok {
+		response.Unauthorized(c, "User not authenticated")
+		return
+	}
+
+	prefs, err := h.notificationService.Preferences(c.Request.Context(), subject.UserID)
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, prefs)
+}
+
+// UpdatePreferences turns email notifications on or off per kind
+// PUT /api/v1/user/notification-preferences
+func (h *NotificationHandler) UpdatePreferences(c *gin.Context) {
+	subject, ok := middleware2.GetAuthSubjectFromContext(c)
+	if !ok {
+		response.Unauthorized(c, "User not authenticated")
+		return
+	}
+
+	var req UpdateNotificationPreferencesRequest
+	if err := c.ShouldBindJSON(&req); err != nil {
+		response.BadRequest(c, "Invalid request: "+err.Error())
+		return
+	}
+
+	prefs, err := h.notificationService.UpdatePreferences(c.Request.Context(), subject.UserID, req.Email)
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, prefs)
+}
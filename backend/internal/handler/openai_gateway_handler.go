@@ -5,12 +5,12 @@ import (
 	"encoding/json"
 	"errors"
 	"fmt"
-	"io"
 	"net/http"
 	"strings"
 	"time"
 
 	"github.com/Wei-Shaw/sub2api/internal/config"
+	pkgerrors "github.com/Wei-Shaw/sub2api/internal/pkg/errors"
 	"github.com/Wei-Shaw/sub2api/internal/pkg/ip"
 	"github.com/Wei-Shaw/sub2api/internal/pkg/logger"
 	middleware2 "github.com/Wei-Shaw/sub2api/internal/server/middleware"
@@ -28,8 +28,15 @@ type OpenAIGatewayHandler struct {
 	apiKeyService           *service.APIKeyService
 	usageRecordWorkerPool   *service.UsageRecordWorkerPool
 	errorPassthroughService *service.ErrorPassthroughService
+	trafficMirrorService    *service.TrafficMirrorService
+	modelCanaryService      *service.ModelCanaryService
+	semanticCacheService    *service.SemanticCacheService
+	moderationService       *service.ModerationService
+	attachmentService       *service.AttachmentService
+	promptTemplateService   *service.PromptTemplateService
 	concurrencyHelper       *ConcurrencyHelper
 	maxAccountSwitches      int
+	cfg                     *config.Config
 }
 
 // NewOpenAIGatewayHandler creates a new OpenAIGatewayHandler
@@ -40,6 +47,12 @@ func NewOpenAIGatewayHandler(
 	apiKeyService *service.APIKeyService,
 	usageRecordWorkerPool *service.UsageRecordWorkerPool,
 	errorPassthroughService *service.ErrorPassthroughService,
+	trafficMirrorService *service.TrafficMirrorService,
+	modelCanaryService *service.ModelCanaryService,
+	semanticCacheService *service.SemanticCacheService,
+	moderationService *service.ModerationService,
+	attachmentService *service.AttachmentService,
+	promptTemplateService *service.PromptTemplateService,
 	cfg *config.Config,
 ) *OpenAIGatewayHandler {
 	pingInterval := time.Duration(0)
@@ -56,8 +69,15 @@ func NewOpenAIGatewayHandler(
 		apiKeyService:           apiKeyService,
 		usageRecordWorkerPool:   usageRecordWorkerPool,
 		errorPassthroughService: errorPassthroughService,
+		trafficMirrorService:    trafficMirrorService,
+		modelCanaryService:      modelCanaryService,
+		semanticCacheService:    semanticCacheService,
+		moderationService:       moderationService,
+		attachmentService:       attachmentService,
+		promptTemplateService:   promptTemplateService,
 		concurrencyHelper:       NewConcurrencyHelper(concurrencyService, SSEPingFormatComment, pingInterval),
 		maxAccountSwitches:      maxAccountSwitches,
+		cfg:                     cfg,
 	}
 }
 
@@ -87,7 +107,7 @@ func (h *OpenAIGatewayHandler) Responses(c *gin.Context) {
 	)
 
 	// Read request body
-	body, err := io.ReadAll(c.Request.Body)
+	body, err := middleware2.ReadRequestBody(c.Request)
 	if err != nil {
 		if maxErr, ok := extractMaxBytesError(err); ok {
 			h.errorResponse(c, http.StatusRequestEntityTooLarge, "invalid_request_error", buildBodyTooLargeMessage(maxErr.Limit))
@@ -102,6 +122,24 @@ func (h *OpenAIGatewayHandler) Responses(c *gin.Context) {
 		return
 	}
 
+	// 多模态附件：解析 multipart 请求并把文件引用转换为内联 input_image / input_file
+	body, ok = applyAttachments(c, h.attachmentService, service.AttachmentFormatOpenAIResponses, apiKey, body, reqLog, h.errorResponse)
+	if !ok {
+		return
+	}
+
+	// 请求体结构校验（gateway.request_validation），返回带 param 的 OpenAI 格式错误
+	if schemaErr := service.OpenAIResponsesSchema.Validate(body, requestValidationMode(h.cfg)); schemaErr != nil {
+		openAISchemaErrorResponse(c, schemaErr)
+		return
+	}
+
+	// 提示词模板：携带 template_id 时渲染模板并拼接到 instructions / input 之前
+	body, ok = applyPromptTemplate(c, h.promptTemplateService, service.PromptFormatOpenAIResponses, apiKey, body, reqLog, h.errorResponse)
+	if !ok {
+		return
+	}
+
 	setOpsRequestContext(c, "", false, body)
 
 	// 校验请求体 JSON 合法性
@@ -110,6 +148,10 @@ func (h *OpenAIGatewayHandler) Responses(c *gin.Context) {
 		return
 	}
 
+	// 金丝雀路由：模型别名按权重分流到不同目标模型
+	body, finishCanary := applyModelCanary(c, h.modelCanaryService, body, reqLog)
+	defer finishCanary()
+
 	// 使用 gjson 只读提取字段做校验，避免完整 Unmarshal
 	modelResult := gjson.GetBytes(body, "model")
 	if !modelResult.Exists() || modelResult.Type != gjson.String || modelResult.String() == "" {
@@ -128,6 +170,19 @@ func (h *OpenAIGatewayHandler) Responses(c *gin.Context) {
 
 	setOpsRequestContext(c, reqModel, reqStream, body)
 
+	body, firewallDecision, ok := applyPromptFirewall(c, h.moderationService, body, service.PromptFormatOpenAIResponses, reqLog, h.errorResponse)
+	if !ok {
+		return
+	}
+
+	// 按 API Key / 分组策略填充或下调 max_output_tokens，并校验输入 token 上限
+	body, _, err = h.gatewayService.ApplyMaxTokensPolicy(c.Request.Context(), body, reqModel)
+	if err != nil {
+		reqLog.Info("openai.max_tokens_rejected", zap.Error(err))
+		h.errorResponse(c, http.StatusBadRequest, "invalid_request_error", pkgerrors.Message(err))
+		return
+	}
+
 	// 提前校验 function_call_output 是否具备可关联上下文，避免上游 400。
 	// 要求 previous_response_id，或 input 内存在带 call_id 的 tool_call/function_call，
 	// 或带 id 且与 call_id 匹配的 item_reference。
@@ -173,6 +228,12 @@ func (h *OpenAIGatewayHandler) Responses(c *gin.Context) {
 	service.SetOpsLatencyMs(c, service.OpsAuthLatencyMsKey, time.Since(requestStart).Milliseconds())
 	routingStart := time.Now()
 
+	// 语义缓存：开启的 API Key 命中相似问题时直接返回缓存响应（不占并发槽位、不转发、不计费）
+	semanticCache, served := h.semanticCacheService.Lookup(c, service.SemanticCacheTypeOpenAI, apiKey, reqModel, reqStream, body)
+	if served {
+		return
+	}
+
 	// 0. 先尝试直接抢占用户槽位（快速路径）
 	userReleaseFunc, userAcquired, err := h.concurrencyHelper.TryAcquireUserSlot(c.Request.Context(), subject.UserID, subject.Concurrency)
 	if err != nil {
@@ -233,6 +294,9 @@ func (h *OpenAIGatewayHandler) Responses(c *gin.Context) {
 	// Generate session hash (header first; fallback to prompt_cache_key)
 	sessionHash := h.gatewayService.GenerateSessionHash(c, body)
 
+	// 流量镜像：抽中的请求在主请求成功后异步复制到规则指定的账号/模型，不影响客户端响应
+	mirror := h.trafficMirrorService.Start(c, service.TrafficMirrorTypeOpenAI, apiKey, reqModel, body)
+
 	maxAccountSwitches := h.maxAccountSwitches
 	switchCount := 0
 	failedAccountIDs := make(map[int64]struct{})
@@ -252,7 +316,7 @@ func (h *OpenAIGatewayHandler) Responses(c *gin.Context) {
 				return
 			}
 			if lastFailoverErr != nil {
-				h.handleFailoverExhausted(c, lastFailoverErr, streamStarted)
+				h.handleFailoverExhausted(c, lastFailoverErr, failedAccountIDs, streamStarted)
 			} else {
 				h.handleFailoverExhaustedSimple(c, 502, streamStarted)
 			}
@@ -336,7 +400,9 @@ func (h *OpenAIGatewayHandler) Responses(c *gin.Context) {
 		// Forward request
 		service.SetOpsLatencyMs(c, service.OpsRoutingLatencyMsKey, time.Since(routingStart).Milliseconds())
 		forwardStart := time.Now()
-		result, err := h.gatewayService.Forward(c.Request.Context(), c, account, body)
+		c.Header(service.SystemFingerprintHeader, service.SystemFingerprint(account, reqModel))
+		forwardCtx := service.WithUpstreamTimeouts(service.WithUpstreamAdapter(c.Request.Context(), account.Platform), service.ResolveUpstreamTimeouts(h.cfg, account.Platform, reqModel))
+		result, err := h.gatewayService.Forward(forwardCtx, c, account, body)
 		forwardDurationMs := time.Since(forwardStart).Milliseconds()
 		if accountReleaseFunc != nil {
 			accountReleaseFunc()
@@ -356,7 +422,7 @@ func (h *OpenAIGatewayHandler) Responses(c *gin.Context) {
 				failedAccountIDs[account.ID] = struct{}{}
 				lastFailoverErr = failoverErr
 				if switchCount >= maxAccountSwitches {
-					h.handleFailoverExhausted(c, failoverErr, streamStarted)
+					h.handleFailoverExhausted(c, failoverErr, failedAccountIDs, streamStarted)
 					return
 				}
 				switchCount++
@@ -377,21 +443,27 @@ func (h *OpenAIGatewayHandler) Responses(c *gin.Context) {
 			return
 		}
 
+		mirror.Complete(account.ID, result.Duration)
+		semanticCache.Complete()
+
 		// 捕获请求信息（用于异步记录，避免在 goroutine 中访问 gin.Context）
 		userAgent := c.GetHeader("User-Agent")
 		clientIP := ip.GetClientIP(c)
+		creditHold := service.ClaimCreditHold(c.Request.Context())
 
 		// 使用量记录通过有界 worker 池提交，避免请求热路径创建无界 goroutine。
 		h.submitUsageRecordTask(func(ctx context.Context) {
 			if err := h.gatewayService.RecordUsage(ctx, &service.OpenAIRecordUsageInput{
-				Result:        result,
-				APIKey:        apiKey,
-				User:          apiKey.User,
-				Account:       account,
-				Subscription:  subscription,
-				UserAgent:     userAgent,
-				IPAddress:     clientIP,
-				APIKeyService: h.apiKeyService,
+				Result:         result,
+				APIKey:         apiKey,
+				User:           apiKey.User,
+				Account:        account,
+				Subscription:   subscription,
+				UserAgent:      userAgent,
+				IPAddress:      clientIP,
+				PromptFirewall: firewallDecision,
+				APIKeyService:  h.apiKeyService,
+				CreditHold:     creditHold,
 			}); err != nil {
 				logger.L().With(
 					zap.String("component", "handler.openai_gateway.responses"),
@@ -449,11 +521,12 @@ func (h *OpenAIGatewayHandler) submitUsageRecordTask(task service.UsageRecordTas
 
 // handleConcurrencyError handles concurrency-related errors with proper 429 response
 func (h *OpenAIGatewayHandler) handleConcurrencyError(c *gin.Context, err error, slotType string, streamStarted bool) {
+	setConcurrencyRetryAfter(c, err, streamStarted)
 	h.handleStreamingAwareError(c, http.StatusTooManyRequests, "rate_limit_error",
 		fmt.Sprintf("Concurrency limit exceeded for %s, please retry later", slotType), streamStarted)
 }
 
-func (h *OpenAIGatewayHandler) handleFailoverExhausted(c *gin.Context, failoverErr *service.UpstreamFailoverError, streamStarted bool) {
+func (h *OpenAIGatewayHandler) handleFailoverExhausted(c *gin.Context, failoverErr *service.UpstreamFailoverError, failedAccountIDs map[int64]struct{}, streamStarted bool) {
 	statusCode := failoverErr.StatusCode
 	responseBody := failoverErr.ResponseBody
 
@@ -476,37 +549,28 @@ func (h *OpenAIGatewayHandler) handleFailoverExhausted(c *gin.Context, failoverE
 				c.Set(service.OpsSkipPassthroughKey, true)
 			}
 
+			setGatewayRetryAfter(c, h.gatewayService, respCode, failedAccountIDs, streamStarted)
 			h.handleStreamingAwareError(c, respCode, "upstream_error", msg, streamStarted)
 			return
 		}
 	}
 
 	// 使用默认的错误映射
-	status, errType, errMsg := h.mapUpstreamError(statusCode)
+	status, errType, errMsg := h.mapUpstreamError(statusCode, responseBody)
+	setGatewayRetryAfter(c, h.gatewayService, status, failedAccountIDs, streamStarted)
 	h.handleStreamingAwareError(c, status, errType, errMsg, streamStarted)
 }
 
 // handleFailoverExhaustedSimple 简化版本，用于没有响应体的情况
 func (h *OpenAIGatewayHandler) handleFailoverExhaustedSimple(c *gin.Context, statusCode int, streamStarted bool) {
-	status, errType, errMsg := h.mapUpstreamError(statusCode)
+	status, errType, errMsg := h.mapUpstreamError(statusCode, nil)
 	h.handleStreamingAwareError(c, status, errType, errMsg, streamStarted)
 }
 
-func (h *OpenAIGatewayHandler) mapUpstreamError(statusCode int) (int, string, string) {
-	switch statusCode {
-	case 401:
-		return http.StatusBadGateway, "upstream_error", "Upstream authentication failed, please contact administrator"
-	case 403:
-		return http.StatusBadGateway, "upstream_error", "Upstream access forbidden, please contact administrator"
-	case 429:
-		return http.StatusTooManyRequests, "rate_limit_error", "Upstream rate limit exceeded, please retry later"
-	case 529:
-		return http.StatusServiceUnavailable, "upstream_error", "Upstream service overloaded, please retry later"
-	case 500, 502, 503, 504:
-		return http.StatusBadGateway, "upstream_error", "Upstream service temporarily unavailable"
-	default:
-		return http.StatusBadGateway, "upstream_error", "Upstream request failed"
-	}
+// mapUpstreamError 按统一的上游错误分类映射状态码、错误类型与消息
+func (h *OpenAIGatewayHandler) mapUpstreamError(statusCode int, responseBody []byte) (int, string, string) {
+	mapped := service.MapUpstreamError(service.ClassifyUpstreamError(statusCode, responseBody), service.UpstreamErrorFormatOpenAI)
+	return mapped.Status, mapped.Type, mapped.Message
 }
 
 // handleStreamingAwareError handles errors that may occur after streaming has started
@@ -272,7 +272,7 @@ func setOpsRequestContext(c *gin.Context, model string, stream bool, requestBody
 	}
 }
 
-func attachOpsRequestBodyToEntry(c *gin.Context, entry *service.OpsInsertErrorLogInput) {
+func attachOpsRequestBodyToEntry(c *gin.Context, entry *service.OpsInsertErrorLogInput, masker *service.LogMasker) {
 	if c == nil || entry == nil {
 		return
 	}
@@ -284,10 +284,43 @@ func attachOpsRequestBodyToEntry(c *gin.Context, entry *service.OpsInsertErrorLo
 	if !ok || len(raw) == 0 {
 		return
 	}
+	raw = masker.MaskJSON(raw)
 	entry.RequestBodyJSON, entry.RequestBodyTruncated, entry.RequestBodyBytes = service.PrepareOpsRequestBodyForQueue(raw)
 	opsErrorLogSanitized.Add(1)
 }
 
+// maskOpsErrorLogEntry 按请求的日志脱敏配置档处理错误信息与上游错误事件中可能回显的提示词内容
+// （请求体在 attachOpsRequestBodyToEntry 中处理）。上游错误事件为共享指针，脱敏写入副本。
+func maskOpsErrorLogEntry(entry *service.OpsInsertErrorLogInput, masker *service.LogMasker) {
+	if masker == nil || entry == nil {
+		return
+	}
+	entry.ErrorMessage = masker.Mask(entry.ErrorMessage)
+	entry.ErrorBody = string(masker.MaskJSON([]byte(entry.ErrorBody)))
+	if entry.UpstreamErrorMessage != nil {
+		msg := masker.Mask(*entry.UpstreamErrorMessage)
+		entry.UpstreamErrorMessage = &msg
+	}
+	if entry.UpstreamErrorDetail != nil {
+		detail := string(masker.MaskJSON([]byte(*entry.UpstreamErrorDetail)))
+		entry.UpstreamErrorDetail = &detail
+	}
+	if len(entry.UpstreamErrors) > 0 {
+		events := make([]*service.OpsUpstreamErrorEvent, 0, len(entry.UpstreamErrors))
+		for _, ev := range entry.UpstreamErrors {
+			if ev == nil {
+				continue
+			}
+			out := *ev
+			out.Message = masker.Mask(out.Message)
+			out.Detail = string(masker.MaskJSON([]byte(out.Detail)))
+			out.UpstreamRequestBody = string(masker.MaskJSON([]byte(out.UpstreamRequestBody)))
+			events = append(events, &out)
+		}
+		entry.UpstreamErrors = events
+	}
+}
+
 func setOpsSelectedAccount(c *gin.Context, accountID int64, platform ...string) {
 	if c == nil || accountID <= 0 {
 		return
@@ -567,7 +600,9 @@ func OpsErrorLoggerMiddleware(ops *service.OpsService) gin.HandlerFunc {
 
 			// Store request headers/body only when an upstream error occurred to keep overhead minimal.
 			entry.RequestHeadersJSON = extractOpsRetryRequestHeaders(c)
-			attachOpsRequestBodyToEntry(c, entry)
+			masker := ops.LogMasking().For(c.Request.Context())
+			attachOpsRequestBodyToEntry(c, entry, masker)
+			maskOpsErrorLogEntry(entry, masker)
 
 			// Skip logging if a passthrough rule with skip_monitoring=true matched.
 			if v, ok := c.Get(service.OpsSkipPassthroughKey); ok {
@@ -743,7 +778,9 @@ func OpsErrorLoggerMiddleware(ops *service.OpsService) gin.HandlerFunc {
 		// Persist only a minimal, whitelisted set of request headers to improve retry fidelity.
 		// Do NOT store Authorization/Cookie/etc.
 		entry.RequestHeadersJSON = extractOpsRetryRequestHeaders(c)
-		attachOpsRequestBodyToEntry(c, entry)
+		masker := ops.LogMasking().For(c.Request.Context())
+		attachOpsRequestBodyToEntry(c, entry, masker)
+		maskOpsErrorLogEntry(entry, masker)
 
 		enqueueOpsErrorLog(ops, entry)
 	}
@@ -55,7 +55,7 @@ func TestAttachOpsRequestBodyToEntry_SanitizeAndTrim(t *testing.T) {
 	setOpsRequestContext(c, "claude-3", false, raw)
 
 	entry := &service.OpsInsertErrorLogInput{}
-	attachOpsRequestBodyToEntry(c, entry)
+	attachOpsRequestBodyToEntry(c, entry, nil)
 
 	require.NotNil(t, entry.RequestBodyBytes)
 	require.Equal(t, len(raw), *entry.RequestBodyBytes)
@@ -77,7 +77,7 @@ func TestAttachOpsRequestBodyToEntry_InvalidJSONKeepsSize(t *testing.T) {
 	setOpsRequestContext(c, "claude-3", false, raw)
 
 	entry := &service.OpsInsertErrorLogInput{}
-	attachOpsRequestBodyToEntry(c, entry)
+	attachOpsRequestBodyToEntry(c, entry, nil)
 
 	require.Nil(t, entry.RequestBodyJSON)
 	require.NotNil(t, entry.RequestBodyBytes)
@@ -96,7 +96,7 @@ func TestEnqueueOpsErrorLog_QueueFullDrop(t *testing.T) {
 	opsErrorLogQueue = make(chan opsErrorLogJob, 1)
 	opsErrorLogMu.Unlock()
 
-	ops := service.NewOpsService(nil, nil, nil, nil, nil, nil, nil, nil, nil, nil, nil)
+	ops := service.NewOpsService(nil, nil, nil, nil, nil, nil, nil, nil, nil, nil, nil, nil)
 	entry := &service.OpsInsertErrorLogInput{ErrorPhase: "upstream", ErrorType: "upstream_error"}
 
 	enqueueOpsErrorLog(ops, entry)
@@ -112,28 +112,28 @@ func TestAttachOpsRequestBodyToEntry_EarlyReturnBranches(t *testing.T) {
 	gin.SetMode(gin.TestMode)
 
 	entry := &service.OpsInsertErrorLogInput{}
-	attachOpsRequestBodyToEntry(nil, entry)
-	attachOpsRequestBodyToEntry(&gin.Context{}, nil)
+	attachOpsRequestBodyToEntry(nil, entry, nil)
+	attachOpsRequestBodyToEntry(&gin.Context{}, nil, nil)
 
 	rec := httptest.NewRecorder()
 	c, _ := gin.CreateTestContext(rec)
 	c.Request = httptest.NewRequest(http.MethodPost, "/v1/messages", nil)
 
 	// 无请求体 key
-	attachOpsRequestBodyToEntry(c, entry)
+	attachOpsRequestBodyToEntry(c, entry, nil)
 	require.Nil(t, entry.RequestBodyJSON)
 	require.Nil(t, entry.RequestBodyBytes)
 	require.False(t, entry.RequestBodyTruncated)
 
 	// 错误类型
 	c.Set(opsRequestBodyKey, "not-bytes")
-	attachOpsRequestBodyToEntry(c, entry)
+	attachOpsRequestBodyToEntry(c, entry, nil)
 	require.Nil(t, entry.RequestBodyJSON)
 	require.Nil(t, entry.RequestBodyBytes)
 
 	// 空 bytes
 	c.Set(opsRequestBodyKey, []byte{})
-	attachOpsRequestBodyToEntry(c, entry)
+	attachOpsRequestBodyToEntry(c, entry, nil)
 	require.Nil(t, entry.RequestBodyJSON)
 	require.Nil(t, entry.RequestBodyBytes)
 
@@ -143,7 +143,7 @@ func TestAttachOpsRequestBodyToEntry_EarlyReturnBranches(t *testing.T) {
 func TestEnqueueOpsErrorLog_EarlyReturnBranches(t *testing.T) {
 	resetOpsErrorLoggerStateForTest(t)
 
-	ops := service.NewOpsService(nil, nil, nil, nil, nil, nil, nil, nil, nil, nil, nil)
+	ops := service.NewOpsService(nil, nil, nil, nil, nil, nil, nil, nil, nil, nil, nil, nil)
 	entry := &service.OpsInsertErrorLogInput{ErrorPhase: "upstream", ErrorType: "upstream_error"}
 
 	// nil 入参分支
@@ -0,0 +1,102 @@
+package handler
+
+import (
+	"io"
+	"net/http"
+
+	infraerrors "github.com/Wei-Shaw/sub2api/internal/pkg/errors"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/logger"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/pagination"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/response"
+	middleware2 "github.com/Wei-Shaw/sub2api/internal/server/middleware"
+	"github.com/Wei-Shaw/sub2api/internal/service"
+
+	"github.com/gin-gonic/gin"
+)
+
+// stripeWebhookMaxBytes Stripe 事件体上限
+const stripeWebhookMaxBytes = 1 << 20
+
+// PaymentHandler handles self-service credit purchases
+type PaymentHandler struct {
+	paymentService *service.PaymentService
+}
+
+// NewPaymentHandler creates a new PaymentHandler
+func NewPaymentHandler(paymentService *service.PaymentService) *PaymentHandler {
+	return &PaymentHandler{paymentService: paymentService}
+}
+
+// CreateCheckoutRequest represents the checkout request payload
+type CreateCheckoutRequest struct {
+	// Amount 充值金额（按配置的收款币种）
+	Amount float64 `json:"amount" binding:"required,gt=0"`
+}
+
+// CreateCheckout creates a Stripe Checkout session for a balance top-up
+// POST /api/v1/payments/checkout
+func (h *PaymentHandler) CreateCheckout(c *gin.Context) {
+	subject, ok := middleware2.GetAuthSubjectFromContext(c)
+	if !ok {
+		response.Unauthorized(c, "User not authenticated")
+		return
+	}
+
+	var req CreateCheckoutRequest
+	if err := c.ShouldBindJSON(&req); err != nil {
+		response.BadRequest(c, "Invalid request: "+err.Error())
+		return
+	}
+
+	order, err := h.paymentService.CreateCheckout(c.Request.Context(), subject.UserID, req.Amount)
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, order)
+}
+
+// ListOrders returns the current user's payment orders
+// GET /api/v1/payments/orders
+func (h *PaymentHandler) ListOrders(c *gin.Context) {
+	subject, ok := middleware2.GetAuthSubjectFromContext(c)
+	if !ok {
+		response.Unauthorized(c, "User not authenticated")
+		return
+	}
+
+	page, pageSize := response.ParsePagination(c)
+	orders, result, err := h.paymentService.ListUserOrders(c.Request.Context(), subject.UserID, pagination.PaginationParams{
+		Page:     page,
+		PageSize: pageSize,
+	})
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Paginated(c, orders, result.Total, page, pageSize)
+}
+
+// StripeWebhook receives Stripe events (no user auth; verified by Stripe-Signature).
+// 非 2xx 响应会触发 Stripe 重试，因此只有签名或报文错误返回 4xx。
+// POST /api/v1/payments/stripe/webhook
+func (h *PaymentHandler) StripeWebhook(c *gin.Context) {
+	payload, err := io.ReadAll(io.LimitReader(c.Request.Body, stripeWebhookMaxBytes+1))
+	if err != nil || len(payload) > stripeWebhookMaxBytes {
+		c.JSON(http.StatusBadRequest, gin.H{"error": "invalid payload"})
+		return
+	}
+
+	if err := h.paymentService.HandleStripeWebhook(c.Request.Context(), payload, c.GetHeader("Stripe-Signature")); err != nil {
+		status := infraerrors.Code(err)
+		if status < http.StatusBadRequest {
+			status = http.StatusInternalServerError
+		}
+		if status >= http.StatusInternalServerError {
+			logger.LegacyPrintf("handler.payment", "[Payment] stripe webhook failed: %v", err)
+		}
+		c.JSON(status, gin.H{"error": infraerrors.Message(err)})
+		return
+	}
+	c.JSON(http.StatusOK, gin.H{"received": true})
+}
@@ -0,0 +1,58 @@
+package handler
+
+import (
+	"net/http"
+
+	"github.com/Wei-Shaw/sub2api/internal/service"
+	"github.com/gin-gonic/gin"
+	"go.uber.org/zap"
+)
+
+// applyPromptFirewall 按 API Key / 分组策略检查入站提示词：先执行防火墙规则，再按顺序调用内容审核服务商。
+//
+// 返回（可能经过 sanitize 改写的）请求体、写入使用记录的决策摘要，以及是否继续处理；
+// 命中 block 时已写入 400 响应（审核服务商全部不可用且策略要求拒绝时为 503），被拦截的请求由 ops 错误日志记录。
+func applyPromptFirewall(
+	c *gin.Context,
+	moderation *service.ModerationService,
+	body []byte,
+	format string,
+	reqLog *zap.Logger,
+	errorResponse func(*gin.Context, int, string, string),
+) ([]byte, string, bool) {
+	policy := service.APIKeyPolicyFromContext(c.Request.Context())
+	var firewallSummary string
+	if decision := policy.Firewall.Inspect(body, format); decision != nil {
+		firewallSummary = decision.Summary()
+		if decision.Action == service.PromptFirewallActionBlock {
+			reqLog.Warn("gateway.prompt_firewall_blocked", zap.String("decision", firewallSummary))
+			errorResponse(c, http.StatusBadRequest, "invalid_request_error", "Request blocked by prompt policy")
+			return body, firewallSummary, false
+		}
+		reqLog.Info("gateway.prompt_firewall_matched", zap.String("decision", firewallSummary))
+		body = decision.Body
+	}
+
+	verdict := moderation.Check(c.Request.Context(), policy.Moderation, body, format)
+	if verdict == nil {
+		return body, firewallSummary, true
+	}
+	if len(verdict.Failures) > 0 {
+		reqLog.Warn("gateway.moderation_provider_failed", zap.Strings("failures", verdict.Failures))
+	}
+	moderationSummary := verdict.Summary()
+	summary := service.MergePromptCheckSummaries(firewallSummary, moderationSummary)
+	switch {
+	case verdict.Action == service.PromptFirewallActionBlock && verdict.Unavailable:
+		reqLog.Warn("gateway.moderation_unavailable_blocked", zap.String("decision", moderationSummary))
+		errorResponse(c, http.StatusServiceUnavailable, "api_error", "Moderation service unavailable, please retry later")
+		return body, summary, false
+	case verdict.Action == service.PromptFirewallActionBlock:
+		reqLog.Warn("gateway.moderation_blocked", zap.String("decision", moderationSummary))
+		errorResponse(c, http.StatusBadRequest, "invalid_request_error", "Request blocked by prompt policy")
+		return body, summary, false
+	case moderationSummary != "":
+		reqLog.Info("gateway.moderation_flagged", zap.String("decision", moderationSummary))
+	}
+	return body, summary, true
+}
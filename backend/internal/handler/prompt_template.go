@@ -0,0 +1,196 @@
+package handler
+
+import (
+	"net/http"
+	"strconv"
+
+	pkgerrors "github.com/Wei-Shaw/sub2api/internal/pkg/errors"
+	middleware2 "github.com/Wei-Shaw/sub2api/internal/server/middleware"
+	"github.com/Wei-Shaw/sub2api/internal/service"
+	"github.com/gin-gonic/gin"
+	"go.uber.org/zap"
+)
+
+// applyPromptTemplate 请求携带 template_id 时在服务端渲染提示词模板。
+//
+// 返回处理后的请求体以及是否继续处理；出错时已写入错误响应。
+func applyPromptTemplate(
+	c *gin.Context,
+	svc *service.PromptTemplateService,
+	format string,
+	apiKey *service.APIKey,
+	body []byte,
+	reqLog *zap.Logger,
+	errorResponse func(*gin.Context, int, string, string),
+) ([]byte, bool) {
+	rendered, err := svc.Apply(c.Request.Context(), format, apiKey.UserID, body)
+	if err != nil {
+		status, errType, message := promptTemplateErrorDetails(err)
+		if status >= http.StatusInternalServerError {
+			reqLog.Error("gateway.prompt_template_apply_failed", zap.Error(err))
+		}
+		errorResponse(c, status, errType, message)
+		return body, false
+	}
+	return rendered, true
+}
+
+func promptTemplateErrorDetails(err error) (int, string, string) {
+	status := pkgerrors.Code(err)
+	switch {
+	case status == http.StatusNotFound:
+		return status, "not_found_error", pkgerrors.Message(err)
+	case status >= http.StatusInternalServerError:
+		return http.StatusInternalServerError, "api_error", "Prompt template storage error"
+	default:
+		return status, "invalid_request_error", pkgerrors.Message(err)
+	}
+}
+
+// promptTemplateRequest 创建 / 更新模板的请求体；name 仅在创建时使用
+type promptTemplateRequest struct {
+	Name        string                           `json:"name"`
+	Description *string                          `json:"description"`
+	System      string                           `json:"system"`
+	Messages    []service.PromptTemplateMessage  `json:"messages"`
+	Variables   []service.PromptTemplateVariable `json:"variables"`
+}
+
+func (r *promptTemplateRequest) input() *service.PromptTemplateInput {
+	return &service.PromptTemplateInput{
+		Name:        r.Name,
+		Description: r.Description,
+		System:      r.System,
+		Messages:    r.Messages,
+		Variables:   r.Variables,
+	}
+}
+
+// CreatePromptTemplate 创建提示词模板（版本 1）
+// POST /v1/prompt-templates
+func (h *GatewayHandler) CreatePromptTemplate(c *gin.Context) {
+	subject, ok := middleware2.GetAuthSubjectFromContext(c)
+	if !ok {
+		h.errorResponse(c, http.StatusInternalServerError, "api_error", "User context not found")
+		return
+	}
+	var req promptTemplateRequest
+	if err := c.ShouldBindJSON(&req); err != nil {
+		h.errorResponse(c, http.StatusBadRequest, "invalid_request_error", "Invalid request: "+err.Error())
+		return
+	}
+	tmpl, version, err := h.promptTemplateService.Create(c.Request.Context(), subject.UserID, req.input())
+	if err != nil {
+		status, errType, message := promptTemplateErrorDetails(err)
+		h.errorResponse(c, status, errType, message)
+		return
+	}
+	c.JSON(http.StatusCreated, gin.H{
+		"template": tmpl,
+		"version":  version,
+	})
+}
+
+// ListPromptTemplates 按最近更新时间列出提示词模板
+// GET /v1/prompt-templates
+func (h *GatewayHandler) ListPromptTemplates(c *gin.Context) {
+	subject, ok := middleware2.GetAuthSubjectFromContext(c)
+	if !ok {
+		h.errorResponse(c, http.StatusInternalServerError, "api_error", "User context not found")
+		return
+	}
+	templates, err := h.promptTemplateService.List(c.Request.Context(), subject.UserID)
+	if err != nil {
+		status, errType, message := promptTemplateErrorDetails(err)
+		h.errorResponse(c, status, errType, message)
+		return
+	}
+	c.JSON(http.StatusOK, gin.H{"data": templates})
+}
+
+// GetPromptTemplate 按 ID 或名称查询模板及其内容（默认最新版本）
+// GET /v1/prompt-templates/:id?version=N
+func (h *GatewayHandler) GetPromptTemplate(c *gin.Context) {
+	subject, ok := middleware2.GetAuthSubjectFromContext(c)
+	if !ok {
+		h.errorResponse(c, http.StatusInternalServerError, "api_error", "User context not found")
+		return
+	}
+	version := 0
+	if raw := c.Query("version"); raw != "" {
+		v, err := strconv.Atoi(raw)
+		if err != nil || v <= 0 {
+			h.errorResponse(c, http.StatusBadRequest, "invalid_request_error", "version must be a positive integer")
+			return
+		}
+		version = v
+	}
+	tmpl, v, err := h.promptTemplateService.Get(c.Request.Context(), subject.UserID, c.Param("id"), version)
+	if err != nil {
+		status, errType, message := promptTemplateErrorDetails(err)
+		h.errorResponse(c, status, errType, message)
+		return
+	}
+	c.JSON(http.StatusOK, gin.H{
+		"template": tmpl,
+		"version":  v,
+	})
+}
+
+// UpdatePromptTemplate 发布模板的新版本（历史版本保留）
+// PUT /v1/prompt-templates/:id
+func (h *GatewayHandler) UpdatePromptTemplate(c *gin.Context) {
+	subject, ok := middleware2.GetAuthSubjectFromContext(c)
+	if !ok {
+		h.errorResponse(c, http.StatusInternalServerError, "api_error", "User context not found")
+		return
+	}
+	var req promptTemplateRequest
+	if err := c.ShouldBindJSON(&req); err != nil {
+		h.errorResponse(c, http.StatusBadRequest, "invalid_request_error", "Invalid request: "+err.Error())
+		return
+	}
+	tmpl, version, err := h.promptTemplateService.Update(c.Request.Context(), subject.UserID, c.Param("id"), req.input())
+	if err != nil {
+		status, errType, message := promptTemplateErrorDetails(err)
+		h.errorResponse(c, status, errType, message)
+		return
+	}
+	c.JSON(http.StatusOK, gin.H{
+		"template": tmpl,
+		"version":  version,
+	})
+}
+
+// DeletePromptTemplate 删除模板及其全部版本
+// DELETE /v1/prompt-templates/:id
+func (h *GatewayHandler) DeletePromptTemplate(c *gin.Context) {
+	subject, ok := middleware2.GetAuthSubjectFromContext(c)
+	if !ok {
+		h.errorResponse(c, http.StatusInternalServerError, "api_error", "User context not found")
+		return
+	}
+	if err := h.promptTemplateService.Delete(c.Request.Context(), subject.UserID, c.Param("id")); err != nil {
+		status, errType, message := promptTemplateErrorDetails(err)
+		h.errorResponse(c, status, errType, message)
+		return
+	}
+	c.Status(http.StatusNoContent)
+}
+
+// ListPromptTemplateVersions 列出模板的全部版本（最新在前）
+// GET /v1/prompt-templates/:id/versions
+func (h *GatewayHandler) ListPromptTemplateVersions(c *gin.Context) {
+	subject, ok := middleware2.GetAuthSubjectFromContext(c)
+	if !ok {
+		h.errorResponse(c, http.StatusInternalServerError, "api_error", "User context not found")
+		return
+	}
+	versions, err := h.promptTemplateService.Versions(c.Request.Context(), subject.UserID, c.Param("id"))
+	if err != nil {
+		status, errType, message := promptTemplateErrorDetails(err)
+		h.errorResponse(c, status, errType, message)
+		return
+	}
+	c.JSON(http.StatusOK, gin.H{"data": versions})
+}
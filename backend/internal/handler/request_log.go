@@ -0,0 +1,63 @@
+package handler
+
+import (
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/pkg/ctxkey"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/ip"
+	middleware2 "github.com/Wei-Shaw/sub2api/internal/server/middleware"
+	"github.com/Wei-Shaw/sub2api/internal/service"
+	"github.com/gin-gonic/gin"
+)
+
+// RequestLogMiddleware 在网关请求结束后记录一条请求日志（异步批量写入，供管理后台检索）
+func RequestLogMiddleware(svc *service.RequestLogService) gin.HandlerFunc {
+	return func(c *gin.Context) {
+		if svc == nil {
+			c.Next()
+			return
+		}
+		start := time.Now()
+		c.Next()
+
+		entry := &service.RequestLogEntry{
+			CreatedAt:  start,
+			Method:     c.Request.Method,
+			Path:       c.Request.URL.Path,
+			StatusCode: c.Writer.Status(),
+			DurationMs: int(time.Since(start).Milliseconds()),
+			ClientIP:   ip.GetClientIP(c),
+		}
+		ctx := c.Request.Context()
+		entry.TraceID, _ = ctx.Value(ctxkey.RequestID).(string)
+		entry.ClientRequestID, _ = ctx.Value(ctxkey.ClientRequestID).(string)
+
+		apiKey, _ := middleware2.GetAPIKeyFromContext(c)
+		if apiKey != nil {
+			entry.APIKeyID = &apiKey.ID
+			entry.UserID = &apiKey.UserID
+			entry.GroupID = apiKey.GroupID
+		}
+		entry.Platform = resolveOpsPlatform(apiKey, guessPlatformFromPath(entry.Path))
+
+		if v, ok := c.Get(opsModelKey); ok {
+			entry.Model, _ = v.(string)
+		}
+		if v, ok := c.Get(opsStreamKey); ok {
+			entry.Stream, _ = v.(bool)
+		}
+		if v, ok := c.Get(opsAccountIDKey); ok {
+			if id, ok := v.(int64); ok && id > 0 {
+				entry.AccountID = &id
+			}
+		}
+		if v, ok := c.Get(service.RequestLogErrorClassKey); ok {
+			entry.ErrorClass, _ = v.(string)
+		}
+		if entry.ErrorClass == "" {
+			entry.ErrorClass = service.RequestLogErrorClassForStatus(entry.StatusCode)
+		}
+
+		svc.Record(entry)
+	}
+}
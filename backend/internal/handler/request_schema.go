@@ -0,0 +1,37 @@
+package handler
+
+import (
+	"net/http"
+	"strings"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/Wei-Shaw/sub2api/internal/service"
+	"github.com/gin-gonic/gin"
+)
+
+// requestValidationMode 请求体结构校验模式（gateway.request_validation），未配置时为 lenient
+func requestValidationMode(cfg *config.Config) string {
+	if cfg == nil {
+		return service.RequestValidationLenient
+	}
+	if mode := strings.ToLower(strings.TrimSpace(cfg.Gateway.RequestValidation)); mode != "" {
+		return mode
+	}
+	return service.RequestValidationLenient
+}
+
+// openAISchemaErrorResponse 返回 OpenAI 格式的字段级错误（error.param 指向出错字段）
+func openAISchemaErrorResponse(c *gin.Context, err *service.RequestSchemaError) {
+	var param any
+	if err.Param != "" {
+		param = err.Param
+	}
+	c.JSON(http.StatusBadRequest, gin.H{
+		"error": gin.H{
+			"type":    "invalid_request_error",
+			"message": err.Message,
+			"param":   param,
+			"code":    err.Code,
+		},
+	})
+}
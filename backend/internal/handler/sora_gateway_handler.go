@@ -7,7 +7,6 @@ import (
 	"encoding/json"
 	"errors"
 	"fmt"
-	"io"
 	"net/http"
 	"os"
 	"path"
@@ -110,7 +109,7 @@ func (h *SoraGatewayHandler) ChatCompletions(c *gin.Context) {
 		zap.Any("group_id", apiKey.GroupID),
 	)
 
-	body, err := io.ReadAll(c.Request.Body)
+	body, err := middleware2.ReadRequestBody(c.Request)
 	if err != nil {
 		if maxErr, ok := extractMaxBytesError(err); ok {
 			h.errorResponse(c, http.StatusRequestEntityTooLarge, "invalid_request_error", buildBodyTooLargeMessage(maxErr.Limit))
@@ -402,6 +401,7 @@ func (h *SoraGatewayHandler) ChatCompletions(c *gin.Context) {
 
 		userAgent := c.GetHeader("User-Agent")
 		clientIP := ip.GetClientIP(c)
+		creditHold := service.ClaimCreditHold(c.Request.Context())
 
 		// 使用量记录通过有界 worker 池提交，避免请求热路径创建无界 goroutine。
 		h.submitUsageRecordTask(func(ctx context.Context) {
@@ -413,6 +413,7 @@ func (h *SoraGatewayHandler) ChatCompletions(c *gin.Context) {
 				Subscription: subscription,
 				UserAgent:    userAgent,
 				IPAddress:    clientIP,
+				CreditHold:   creditHold,
 			}); err != nil {
 				logger.L().With(
 					zap.String("component", "handler.sora_gateway.chat_completions"),
@@ -468,6 +469,7 @@ func (h *SoraGatewayHandler) submitUsageRecordTask(task service.UsageRecordTask)
 }
 
 func (h *SoraGatewayHandler) handleConcurrencyError(c *gin.Context, err error, slotType string, streamStarted bool) {
+	setConcurrencyRetryAfter(c, err, streamStarted)
 	h.handleStreamingAwareError(c, http.StatusTooManyRequests, "rate_limit_error",
 		fmt.Sprintf("Concurrency limit exceeded for %s, please retry later", slotType), streamStarted)
 }
@@ -656,6 +658,10 @@ func (h *SoraGatewayHandler) proxySoraMedia(c *gin.Context, requireSignature boo
 			return
 		}
 	}
+	if mediaStorage := h.soraGatewayService.MediaStorage(); mediaStorage.UsesObjectStorage() {
+		h.serveSoraMediaObject(c, mediaStorage, cleaned)
+		return
+	}
 	if strings.TrimSpace(h.soraMediaRoot) == "" {
 		c.JSON(http.StatusServiceUnavailable, gin.H{
 			"error": gin.H{
@@ -678,3 +684,28 @@ func (h *SoraGatewayHandler) proxySoraMedia(c *gin.Context, requireSignature boo
 	}
 	c.File(localPath)
 }
+
+// serveSoraMediaObject 从对象存储读取并转发媒体
+func (h *SoraGatewayHandler) serveSoraMediaObject(c *gin.Context, mediaStorage *service.SoraMediaStorage, relative string) {
+	obj, err := mediaStorage.OpenObject(c.Request.Context(), relative)
+	if err != nil {
+		if errors.Is(err, service.ErrObjectNotFound) {
+			c.Status(http.StatusNotFound)
+			return
+		}
+		logger.L().Warn("sora.media_object_read_failed", zap.String("path", relative), zap.Error(err))
+		c.Status(http.StatusBadGateway)
+		return
+	}
+	defer func() { _ = obj.Body.Close() }()
+
+	contentType := obj.ContentType
+	if contentType == "" {
+		contentType = "application/octet-stream"
+	}
+	extraHeaders := map[string]string{}
+	if !obj.LastModified.IsZero() {
+		extraHeaders["Last-Modified"] = obj.LastModified.UTC().Format(http.TimeFormat)
+	}
+	c.DataFromReader(http.StatusOK, obj.Size, contentType, obj.Body, extraHeaders)
+}
@@ -402,7 +402,7 @@ func TestSoraGatewayHandler_ChatCompletions(t *testing.T) {
 	deferredService := service.NewDeferredService(accountRepo, nil, 0)
 	billingService := service.NewBillingService(cfg, nil)
 	concurrencyService := service.NewConcurrencyService(testutil.StubConcurrencyCache{})
-	billingCacheService := service.NewBillingCacheService(nil, nil, nil, cfg)
+	billingCacheService := service.NewBillingCacheService(nil, nil, nil, cfg, nil)
 	t.Cleanup(func() {
 		billingCacheService.Stop()
 	})
@@ -427,6 +427,12 @@ func TestSoraGatewayHandler_ChatCompletions(t *testing.T) {
 		nil,
 		testutil.StubSessionLimitCache{},
 		nil,
+		nil,
+		nil,
+		nil,
+		nil,
+		nil,
+		nil,
 	)
 
 	soraClient := &stubSoraClient{imageURLs: []string{"https://example.com/a.png"}}
@@ -0,0 +1,246 @@
+package handler
+
+import (
+	"strconv"
+
+	"github.com/Wei-Shaw/sub2api/internal/pkg/pagination"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/response"
+	middleware2 "github.com/Wei-Shaw/sub2api/internal/server/middleware"
+	"github.com/Wei-Shaw/sub2api/internal/service"
+
+	"github.com/gin-gonic/gin"
+)
+
+// TeamHandler handles API key teams with shared budgets
+type TeamHandler struct {
+	teamService *service.TeamService
+}
+
+// NewTeamHandler creates a new TeamHandler
+func NewTeamHandler(teamService *service.TeamService) *TeamHandler {
+	return &TeamHandler{teamService: teamService}
+}
+
+// CreateTeamRequest represents the create team request payload
+type CreateTeamRequest struct {
+	Name   string `json:"name" binding:"required"`
+	Period string `json:"period" binding:"omitempty,oneof=daily weekly monthly"`
+}
+
+// UpdateTeamRequest represents the update team request payload
+type UpdateTeamRequest struct {
+	Name   *string `json:"name"`
+	Period *string `json:"period" binding:"omitempty,oneof=daily weekly monthly"`
+}
+
+// SetTeamBudgetRequest represents the team budget payload; 0 removes the budget
+type SetTeamBudgetRequest struct {
+	LimitUSD *float64 `json:"limit_usd" binding:"required,gte=0"`
+}
+
+// UpsertTeamMemberRequest represents the team member payload
+type UpsertTeamMemberRequest struct {
+	MemberName string `json:"member_name"`
+	// LimitUSD 成员子额度；省略时不修改，0 表示移除
+	LimitUSD *float64 `json:"limit_usd" binding:"omitempty,gte=0"`
+}
+
+// List returns the current user's teams
+// GET /api/v1/teams
+func (h *TeamHandler) List(c *gin.Context) {
+	subject, ok := middleware2.GetAuthSubjectFromContext(c)
+	if !ok {
+		response.Unauthorized(c, "User not authenticated")
+		return
+	}
+
+	page, pageSize := response.ParsePagination(c)
+	teams, result, err := h.teamService.List(c.Request.Context(), subject.UserID, pagination.PaginationParams{
+		Page:     page,
+		PageSize: pageSize,
+	})
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Paginated(c, teams, result.Total, page, pageSize)
+}
+
+// Create creates a team
+// POST /api/v1/teams
+func (h *TeamHandler) Create(c *gin.Context) {
+	subject, ok := middleware2.GetAuthSubjectFromContext(c)
+	if !ok {
+		response.Unauthorized(c, "User not authenticated")
+		return
+	}
+
+	var req CreateTeamRequest
+	if err := c.ShouldBindJSON(&req); err != nil {
+		response.BadRequest(c, "Invalid request: "+err.Error())
+		return
+	}
+
+	team, err := h.teamService.Create(c.Request.Context(), subject.UserID, req.Name, req.Period)
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Created(c, team)
+}
+
+// Get returns a team with its members, budget and per-member limits
+// GET /api/v1/teams/:id
+func (h *TeamHandler) Get(c *gin.Context) {
+	subject, teamID, ok := parseTeamTarget(c)
+	if !ok {
+		return
+	}
+
+	detail, err := h.teamService.Get(c.Request.Context(), subject.UserID, teamID)
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, detail)
+}
+
+// Update renames a team or changes its budget period
+// PUT /api/v1/teams/:id
+func (h *TeamHandler) Update(c *gin.Context) {
+	subject, teamID, ok := parseTeamTarget(c)
+	if !ok {
+		return
+	}
+
+	var req UpdateTeamRequest
+	if err := c.ShouldBindJSON(&req); err != nil {
+		response.BadRequest(c, "Invalid request: "+err.Error())
+		return
+	}
+
+	team, err := h.teamService.Update(c.Request.Context(), subject.UserID, teamID, req.Name, req.Period)
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, team)
+}
+
+// Delete deletes a team; member keys are kept
+// DELETE /api/v1/teams/:id
+func (h *TeamHandler) Delete(c *gin.Context) {
+	subject, teamID, ok := parseTeamTarget(c)
+	if !ok {
+		return
+	}
+
+	if err := h.teamService.Delete(c.Request.Context(), subject.UserID, teamID); err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, gin.H{"message": "Team deleted successfully"})
+}
+
+// SetBudget sets the shared team budget
+// PUT /api/v1/teams/:id/budget
+func (h *TeamHandler) SetBudget(c *gin.Context) {
+	subject, teamID, ok := parseTeamTarget(c)
+	if !ok {
+		return
+	}
+
+	var req SetTeamBudgetRequest
+	if err := c.ShouldBindJSON(&req); err != nil {
+		response.BadRequest(c, "Invalid request: "+err.Error())
+		return
+	}
+
+	status, err := h.teamService.SetBudget(c.Request.Context(), subject.UserID, teamID, *req.LimitUSD, subject.UserID)
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, status)
+}
+
+// UpsertMember adds one of the user's API keys to the team, or updates its member name and sub-limit
+// PUT /api/v1/teams/:id/members/:key_id
+func (h *TeamHandler) UpsertMember(c *gin.Context) {
+	subject, teamID, ok := parseTeamTarget(c)
+	if !ok {
+		return
+	}
+	keyID, err := strconv.ParseInt(c.Param("key_id"), 10, 64)
+	if err != nil || keyID <= 0 {
+		response.BadRequest(c, "Invalid key ID")
+		return
+	}
+
+	var req UpsertTeamMemberRequest
+	if err := c.ShouldBindJSON(&req); err != nil {
+		response.BadRequest(c, "Invalid request: "+err.Error())
+		return
+	}
+
+	member, err := h.teamService.UpsertMember(c.Request.Context(), subject.UserID, teamID, keyID, service.UpsertTeamMemberInput{
+		MemberName: req.MemberName,
+		LimitUSD:   req.LimitUSD,
+	}, subject.UserID)
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, member)
+}
+
+// RemoveMember removes an API key from the team
+// DELETE /api/v1/teams/:id/members/:key_id
+func (h *TeamHandler) RemoveMember(c *gin.Context) {
+	subject, teamID, ok := parseTeamTarget(c)
+	if !ok {
+		return
+	}
+	keyID, err := strconv.ParseInt(c.Param("key_id"), 10, 64)
+	if err != nil || keyID <= 0 {
+		response.BadRequest(c, "Invalid key ID")
+		return
+	}
+
+	if err := h.teamService.RemoveMember(c.Request.Context(), subject.UserID, teamID, keyID); err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, gin.H{"message": "Member removed successfully"})
+}
+
+// Usage returns usage attributed to each team member
+// GET /api/v1/teams/:id/usage?start_date=2006-01-02&end_date=2006-01-02&timezone=Asia/Shanghai
+func (h *TeamHandler) Usage(c *gin.Context) {
+	subject, teamID, ok := parseTeamTarget(c)
+	if !ok {
+		return
+	}
+
+	start, end := parseUserTimeRange(c)
+	report, err := h.teamService.Usage(c.Request.Context(), subject.UserID, teamID, start, end)
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+	response.Success(c, report)
+}
+
+func parseTeamTarget(c *gin.Context) (middleware2.AuthSubject, int64, bool) {
+	subject, ok := middleware2.GetAuthSubjectFromContext(c)
+	if !ok {
+		response.Unauthorized(c, "User not authenticated")
+		return subject, 0, false
+	}
+	teamID, err := strconv.ParseInt(c.Param("id"), 10, 64)
+	if err != nil || teamID <= 0 {
+		response.BadRequest(c, "Invalid team ID")
+		return subject, 0, false
+	}
+	return subject, teamID, true
+}
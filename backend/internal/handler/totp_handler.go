@@ -22,9 +22,11 @@ func NewTotpHandler(totpService *service.TotpService) *TotpHandler {
 
 // TotpStatusResponse represents the TOTP status response
 type TotpStatusResponse struct {
-	Enabled        bool   `json:"enabled"`
-	EnabledAt      *int64 `json:"enabled_at,omitempty"` // Unix timestamp
-	FeatureEnabled bool   `json:"feature_enabled"`
+	Enabled              bool   `json:"enabled"`
+	EnabledAt            *int64 `json:"enabled_at,omitempty"` // Unix timestamp
+	FeatureEnabled       bool   `json:"feature_enabled"`
+	Required             bool   `json:"required"`
+	BackupCodesRemaining int    `json:"backup_codes_remaining"`
 }
 
 // GetStatus returns the TOTP status for the current user
@@ -43,8 +45,10 @@ func (h *TotpHandler) GetStatus(c *gin.Context) {
 	}
 
 	resp := TotpStatusResponse{
-		Enabled:        status.Enabled,
-		FeatureEnabled: status.FeatureEnabled,
+		Enabled:              status.Enabled,
+		FeatureEnabled:       status.FeatureEnabled,
+		Required:             status.Required,
+		BackupCodesRemaining: status.BackupCodesRemaining,
 	}
 
 	if status.EnabledAt != nil {
@@ -119,12 +123,48 @@ func (h *TotpHandler) Enable(c *gin.Context) {
 		return
 	}
 
-	if err := h.totpService.CompleteSetup(c.Request.Context(), subject.UserID, req.TotpCode, req.SetupToken); err != nil {
+	backupCodes, err := h.totpService.CompleteSetup(c.Request.Context(), subject.UserID, req.TotpCode, req.SetupToken)
+	if err != nil {
 		response.ErrorFrom(c, err)
 		return
 	}
 
-	response.Success(c, gin.H{"success": true})
+	response.Success(c, TotpBackupCodesResponse{Success: true, BackupCodes: backupCodes})
+}
+
+// TotpBackupCodesResponse 返回一次性展示的备用恢复码
+type TotpBackupCodesResponse struct {
+	Success     bool     `json:"success"`
+	BackupCodes []string `json:"backup_codes"`
+}
+
+// TotpRegenerateBackupCodesRequest 重新生成备用码请求
+type TotpRegenerateBackupCodesRequest struct {
+	TotpCode string `json:"totp_code" binding:"required,len=6"`
+}
+
+// RegenerateBackupCodes 重新生成备用码（旧备用码立即失效）
+// POST /api/v1/user/totp/backup-codes
+func (h *TotpHandler) RegenerateBackupCodes(c *gin.Context) {
+	subject, ok := middleware2.GetAuthSubjectFromContext(c)
+	if !ok {
+		response.Unauthorized(c, "User not authenticated")
+		return
+	}
+
+	var req TotpRegenerateBackupCodesRequest
+	if err := c.ShouldBindJSON(&req); err != nil {
+		response.BadRequest(c, "Invalid request: "+err.Error())
+		return
+	}
+
+	backupCodes, err := h.totpService.RegenerateBackupCodes(c.Request.Context(), subject.UserID, req.TotpCode)
+	if err != nil {
+		response.ErrorFrom(c, err)
+		return
+	}
+
+	response.Success(c, TotpBackupCodesResponse{Success: true, BackupCodes: backupCodes})
 }
 
 // TotpDisableRequest represents the request to disable TOTP
@@ -28,6 +28,34 @@ func ProvideAdminHandlers(
 	usageHandler *admin.UsageHandler,
 	userAttributeHandler *admin.UserAttributeHandler,
 	errorPassthroughHandler *admin.ErrorPassthroughHandler,
+	webSessionHandler *admin.WebSessionHandler,
+	trashHandler *admin.TrashHandler,
+	listHandler *admin.ListHandler,
+	jobQueueHandler *admin.JobQueueHandler,
+	cronJobHandler *admin.CronJobHandler,
+	usageExportHandler *admin.UsageExportHandler,
+	apiKeyPolicyHandler *admin.APIKeyPolicyHandler,
+	trafficMirrorHandler *admin.TrafficMirrorHandler,
+	modelCanaryHandler *admin.ModelCanaryHandler,
+	accountHealthHandler *admin.AccountHealthHandler,
+	liveUsageHandler *admin.LiveUsageHandler,
+	requestLogHandler *admin.RequestLogHandler,
+	dataSubjectHandler *admin.DataSubjectHandler,
+	featureFlagHandler *admin.FeatureFlagHandler,
+	maintenanceHandler *admin.MaintenanceHandler,
+	spendingCapHandler *admin.SpendingCapHandler,
+	prepaidCreditHandler *admin.PrepaidCreditHandler,
+	statementHandler *admin.StatementHandler,
+	adminPaymentHandler *admin.PaymentHandler,
+	staffHandler *admin.StaffHandler,
+	adminTeamHandler *admin.TeamHandler,
+	adminNotificationHandler *admin.NotificationHandler,
+	copilotHandler *admin.CopilotHandler,
+	sessionImportHandler *admin.SessionImportHandler,
+	challengeHandler *admin.ChallengeHandler,
+	accountShardHandler *admin.AccountShardHandler,
+	graphQLHandler *admin.GraphQLHandler,
+	routingRuleHandler *admin.RoutingRuleHandler,
 ) *AdminHandlers {
 	return &AdminHandlers{
 		Dashboard:        dashboardHandler,
@@ -49,6 +77,34 @@ func ProvideAdminHandlers(
 		Usage:            usageHandler,
 		UserAttribute:    userAttributeHandler,
 		ErrorPassthrough: errorPassthroughHandler,
+		WebSession:       webSessionHandler,
+		Trash:            trashHandler,
+		List:             listHandler,
+		JobQueue:         jobQueueHandler,
+		CronJob:          cronJobHandler,
+		UsageExport:      usageExportHandler,
+		APIKeyPolicy:     apiKeyPolicyHandler,
+		TrafficMirror:    trafficMirrorHandler,
+		ModelCanary:      modelCanaryHandler,
+		AccountHealth:    accountHealthHandler,
+		LiveUsage:        liveUsageHandler,
+		RequestLog:       requestLogHandler,
+		DataSubject:      dataSubjectHandler,
+		FeatureFlag:      featureFlagHandler,
+		Maintenance:      maintenanceHandler,
+		SpendingCap:      spendingCapHandler,
+		PrepaidCredit:    prepaidCreditHandler,
+		Statement:        statementHandler,
+		Payment:          adminPaymentHandler,
+		Staff:            staffHandler,
+		Team:             adminTeamHandler,
+		Notification:     adminNotificationHandler,
+		Copilot:          copilotHandler,
+		SessionImport:    sessionImportHandler,
+		Challenge:        challengeHandler,
+		AccountShard:     accountShardHandler,
+		GraphQL:          graphQLHandler,
+		RoutingRule:      routingRuleHandler,
 	}
 }
 
@@ -77,6 +133,9 @@ func ProvideHandlers(
 	soraGatewayHandler *SoraGatewayHandler,
 	settingHandler *SettingHandler,
 	totpHandler *TotpHandler,
+	paymentHandler *PaymentHandler,
+	teamHandler *TeamHandler,
+	notificationHandler *NotificationHandler,
 	_ *service.IdempotencyCoordinator,
 	_ *service.IdempotencyCleanupService,
 ) *Handlers {
@@ -94,6 +153,9 @@ func ProvideHandlers(
 		SoraGateway:   soraGatewayHandler,
 		Setting:       settingHandler,
 		Totp:          totpHandler,
+		Payment:       paymentHandler,
+		Team:          teamHandler,
+		Notification:  notificationHandler,
 	}
 }
 
@@ -111,6 +173,9 @@ var ProviderSet = wire.NewSet(
 	NewOpenAIGatewayHandler,
 	NewSoraGatewayHandler,
 	NewTotpHandler,
+	NewPaymentHandler,
+	NewTeamHandler,
+	NewNotificationHandler,
 	ProvideSettingHandler,
 
 	// Admin handlers
@@ -133,6 +198,34 @@ var ProviderSet = wire.NewSet(
 	admin.NewUsageHandler,
 	admin.NewUserAttributeHandler,
 	admin.NewErrorPassthroughHandler,
+	admin.NewWebSessionHandler,
+	admin.NewTrashHandler,
+	admin.NewListHandler,
+	admin.NewJobQueueHandler,
+	admin.NewCronJobHandler,
+	admin.NewUsageExportHandler,
+	admin.NewAPIKeyPolicyHandler,
+	admin.NewTrafficMirrorHandler,
+	admin.NewModelCanaryHandler,
+	admin.NewAccountHealthHandler,
+	admin.NewLiveUsageHandler,
+	admin.NewRequestLogHandler,
+	admin.NewDataSubjectHandler,
+	admin.NewFeatureFlagHandler,
+	admin.NewMaintenanceHandler,
+	admin.NewSpendingCapHandler,
+	admin.NewPrepaidCreditHandler,
+	admin.NewStatementHandler,
+	admin.NewPaymentHandler,
+	admin.NewStaffHandler,
+	admin.NewTeamHandler,
+	admin.NewNotificationHandler,
+	admin.NewCopilotHandler,
+	admin.NewSessionImportHandler,
+	admin.NewChallengeHandler,
+	admin.NewAccountShardHandler,
+	admin.NewGraphQLHandler,
+	admin.NewRoutingRuleHandler,
 
 	// AdminHandlers and Handlers constructors
 	ProvideAdminHandlers,
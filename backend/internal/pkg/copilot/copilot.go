@@ -0,0 +1,111 @@
+// Package copilot provides constants and types for GitHub Copilot subscription integration.
+package copilot
+
+import (
+	"net/http"
+	"time"
+)
+
+// GitHub 设备码登录与 Copilot token 交换（与 VS Code Copilot Chat 插件一致）
+const (
+	// ClientID GitHub Copilot 插件的 OAuth App Client ID
+	ClientID = "Iv1.b507a08c87ecfe98"
+	Scope    = "read:user"
+
+	DeviceCodeURL  = "https://github.com/login/device/code"
+	AccessTokenURL = "https://github.com/login/oauth/access_token"
+	// TokenExchangeURL 用 GitHub token 换取短期 Copilot token
+	TokenExchangeURL = "https://api.github.com/copilot_internal/v2/token"
+
+	// DefaultAPIBaseURL token 交换结果未返回 endpoints.api 时使用
+	DefaultAPIBaseURL = "https://api.githubcopilot.com"
+
+	EditorVersion       = "vscode/1.99.3"
+	EditorPluginVersion = "copilot-chat/0.26.7"
+	IntegrationID       = "vscode-chat"
+	UserAgent           = "GitHubCopilotChat/0.26.7"
+	APIVersion          = "2025-04-01"
+
+	DeviceGrantType  = "urn:ietf:params:oauth:grant-type:device_code"
+	RefreshGrantType = "refresh_token"
+
+	// DeviceSessionTTL 设备码登录会话的最长保留时间（GitHub 设备码默认 15 分钟过期）
+	DeviceSessionTTL = 15 * time.Minute
+)
+
+// 轮询 access_token 时 GitHub 返回的 error 字段
+const (
+	ErrAuthorizationPending = "authorization_pending"
+	ErrSlowDown             = "slow_down"
+	ErrExpiredToken         = "expired_token"
+	ErrAccessDenied         = "access_denied"
+)
+
+// DeviceCodeResponse 设备码申请结果
+type DeviceCodeResponse struct {
+	DeviceCode      string `json:"device_code"`
+	UserCode        string `json:"user_code"`
+	VerificationURI string `json:"verification_uri"`
+	ExpiresIn       int64  `json:"expires_in"`
+	Interval        int64  `json:"interval"`
+}
+
+// AccessTokenResponse 设备码轮询 / refresh_token 刷新结果；Error 非空表示尚未授权或失败
+type AccessTokenResponse struct {
+	AccessToken           string `json:"access_token"`
+	TokenType             string `json:"token_type"`
+	Scope                 string `json:"scope"`
+	RefreshToken          string `json:"refresh_token,omitempty"`
+	ExpiresIn             int64  `json:"expires_in,omitempty"`
+	RefreshTokenExpiresIn int64  `json:"refresh_token_expires_in,omitempty"`
+	Error                 string `json:"error,omitempty"`
+	ErrorDescription      string `json:"error_description,omitempty"`
+	Interval              int64  `json:"interval,omitempty"`
+}
+
+// Token copilot_internal/v2/token 返回的短期 token
+type Token struct {
+	Token     string `json:"token"`
+	ExpiresAt int64  `json:"expires_at"`
+	RefreshIn int64  `json:"refresh_in"`
+	SKU       string `json:"sku"`
+	Endpoints struct {
+		API string `json:"api"`
+	} `json:"endpoints"`
+}
+
+// APIBaseURL 返回 token 对应的 API 地址（个人 / 商业 / 企业版各不相同）
+func (t *Token) APIBaseURL() string {
+	if t == nil || t.Endpoints.API == "" {
+		return DefaultAPIBaseURL
+	}
+	return t.Endpoints.API
+}
+
+// Model /models 返回的模型信息
+type Model struct {
+	ID                 string `json:"id"`
+	Name               string `json:"name"`
+	Vendor             string `json:"vendor"`
+	Version            string `json:"version"`
+	Preview            bool   `json:"preview"`
+	ModelPickerEnabled bool   `json:"model_picker_enabled"`
+	Capabilities       struct {
+		Type     string `json:"type"`
+		Family   string `json:"family"`
+		Supports struct {
+			ToolCalls bool `json:"tool_calls"`
+			Streaming bool `json:"streaming"`
+			Vision    bool `json:"vision"`
+		} `json:"supports"`
+	} `json:"capabilities"`
+}
+
+// SetClientHeaders 写入与 VS Code Copilot Chat 插件一致的客户端标识头
+func SetClientHeaders(h http.Header) {
+	h.Set("User-Agent", UserAgent)
+	h.Set("Editor-Version", EditorVersion)
+	h.Set("Editor-Plugin-Version", EditorPluginVersion)
+	h.Set("Copilot-Integration-Id", IntegrationID)
+	h.Set("X-GitHub-Api-Version", APIVersion)
+}
@@ -37,6 +37,15 @@ const (
 	// Group 认证后的分组信息，由 API Key 认证中间件设置
 	Group Key = "ctx_group"
 
+	// APIKeyPolicy 当前 API Key 的请求/响应处理策略，由 API Key 认证中间件设置
+	APIKeyPolicy Key = "ctx_api_key_policy"
+
+	// APIKeyID 当前请求的 API Key ID（int64），由 API Key 认证中间件设置，用于账号分片路由
+	APIKeyID Key = "ctx_api_key_id"
+
+	// CreditHold 当前请求的预付费额度预占（[]*service.CreditHold，每次上游调用一份），由预占中间件设置，记录用量时释放
+	CreditHold Key = "ctx_credit_hold"
+
 	// IsMaxTokensOneHaikuRequest 标识当前请求是否为 max_tokens=1 + haiku 模型的探测请求
 	// 用于 ClaudeCodeOnly 验证绕过（绕过 system prompt 检查，但仍需验证 User-Agent）
 	IsMaxTokensOneHaikuRequest Key = "ctx_is_max_tokens_one_haiku"
@@ -0,0 +1,224 @@
+// Package docx 从 Word（.docx，Office Open XML）文档中提取正文文本与内嵌图片。
+//
+// 只读取 word/document.xml 的段落、表格与图片引用，不处理页眉页脚、批注与修订记录。
+package docx
+
+import (
+	"archive/zip"
+	"bytes"
+	"encoding/xml"
+	"errors"
+	"fmt"
+	"io"
+	"path"
+	"strings"
+)
+
+// ErrInvalid 不是有效的 docx 文件
+var ErrInvalid = errors.New("docx: not a valid Word document")
+
+// 解压上限，防止压缩炸弹
+const (
+	maxDocumentXMLBytes = 64 << 20
+	maxImageBytes       = 20 << 20
+)
+
+var imageTypes = map[string]string{
+	".png":  "image/png",
+	".jpg":  "image/jpeg",
+	".jpeg": "image/jpeg",
+	".gif":  "image/gif",
+	".webp": "image/webp",
+}
+
+// Image 正文引用的图片（按出现顺序，同一图片只返回一次）
+type Image struct {
+	Name      string
+	MediaType string
+	Data      []byte
+}
+
+// Document 提取结果；表格每行输出为一个以 " | " 分隔单元格的段落
+type Document struct {
+	Paragraphs []string
+	Images     []Image
+}
+
+// Extract 提取正文段落；withImages 为 true 时同时读取 PNG / JPEG / GIF / WebP 图片
+func Extract(data []byte, withImages bool) (*Document, error) {
+	zr, err := zip.NewReader(bytes.NewReader(data), int64(len(data)))
+	if err != nil {
+		return nil, ErrInvalid
+	}
+	files := make(map[string]*zip.File, len(zr.File))
+	for _, f := range zr.File {
+		files[strings.TrimPrefix(f.Name, "/")] = f
+	}
+	mainPart, ok := files["word/document.xml"]
+	if !ok {
+		return nil, ErrInvalid
+	}
+	body, err := readZipFile(mainPart, maxDocumentXMLBytes)
+	if err != nil {
+		return nil, err
+	}
+
+	doc, embeds, err := parseBody(body)
+	if err != nil {
+		return nil, err
+	}
+	if !withImages || len(embeds) == 0 {
+		return doc, nil
+	}
+
+	rels := map[string]string{}
+	if f, ok := files["word/_rels/document.xml.rels"]; ok {
+		if raw, err := readZipFile(f, maxDocumentXMLBytes); err == nil {
+			rels = parseRelationships(raw)
+		}
+	}
+	seen := map[string]bool{}
+	for _, id := range embeds {
+		target, ok := rels[id]
+		if !ok || seen[target] {
+			continue
+		}
+		seen[target] = true
+		mediaType, ok := imageTypes[strings.ToLower(path.Ext(target))]
+		f, exists := files[target]
+		if !ok || !exists || f.UncompressedSize64 > maxImageBytes {
+			continue
+		}
+		img, err := readZipFile(f, maxImageBytes)
+		if err != nil {
+			continue
+		}
+		doc.Images = append(doc.Images, Image{Name: path.Base(target), MediaType: mediaType, Data: img})
+	}
+	return doc, nil
+}
+
+func readZipFile(f *zip.File, limit int64) ([]byte, error) {
+	rc, err := f.Open()
+	if err != nil {
+		return nil, fmt.Errorf("docx: open %s: %w", f.Name, err)
+	}
+	defer func() { _ = rc.Close() }()
+	data, err := io.ReadAll(io.LimitReader(rc, limit+1))
+	if err != nil {
+		return nil, fmt.Errorf("docx: read %s: %w", f.Name, err)
+	}
+	if int64(len(data)) > limit {
+		return nil, fmt.Errorf("docx: %s exceeds size limit", f.Name)
+	}
+	return data, nil
+}
+
+// parseBody 按文档顺序收集段落文本与图片关系 ID（a:blip r:embed）
+func parseBody(body []byte) (*Document, []string, error) {
+	dec := xml.NewDecoder(bytes.NewReader(body))
+	doc := &Document{}
+	var embeds []string
+	var para strings.Builder
+	var row, cell []string
+	tableDepth, inText := 0, false
+
+	flush := func() {
+		text := strings.TrimSpace(para.String())
+		para.Reset()
+		if text == "" {
+			return
+		}
+		if tableDepth > 0 {
+			cell = append(cell, text)
+			return
+		}
+		doc.Paragraphs = append(doc.Paragraphs, text)
+	}
+
+	for {
+		tok, err := dec.Token()
+		if err == io.EOF {
+			break
+		}
+		if err != nil {
+			return nil, nil, ErrInvalid
+		}
+		switch t := tok.(type) {
+		case xml.StartElement:
+			switch t.Name.Local {
+			case "t":
+				inText = true
+			case "tab":
+				para.WriteByte('\t')
+			case "br", "cr":
+				para.WriteByte('\n')
+			case "tbl":
+				flush()
+				tableDepth++
+			case "blip":
+				for _, attr := range t.Attr {
+					if attr.Name.Local == "embed" && attr.Value != "" {
+						embeds = append(embeds, attr.Value)
+					}
+				}
+			}
+		case xml.EndElement:
+			switch t.Name.Local {
+			case "t":
+				inText = false
+			case "p":
+				flush()
+			case "tc":
+				if tableDepth > 0 {
+					row = append(row, strings.Join(cell, " "))
+					cell = cell[:0]
+				}
+			case "tr":
+				if tableDepth > 0 && strings.TrimSpace(strings.Join(row, "")) != "" {
+					doc.Paragraphs = append(doc.Paragraphs, strings.Join(row, " | "))
+				}
+				row = row[:0]
+			case "tbl":
+				if tableDepth > 0 {
+					tableDepth--
+				}
+			}
+		case xml.CharData:
+			if inText {
+				para.Write(t)
+			}
+		}
+	}
+	return doc, embeds, nil
+}
+
+type relationships struct {
+	Items []struct {
+		ID         string `xml:"Id,attr"`
+		Target     string `xml:"Target,attr"`
+		TargetMode string `xml:"TargetMode,attr"`
+	} `xml:"Relationship"`
+}
+
+// parseRelationships 返回关系 ID 到压缩包内路径的映射（忽略外部链接）
+func parseRelationships(raw []byte) map[string]string {
+	var rels relationships
+	out := map[string]string{}
+	if err := xml.Unmarshal(raw, &rels); err != nil {
+		return out
+	}
+	for _, r := range rels.Items {
+		if strings.EqualFold(r.TargetMode, "External") {
+			continue
+		}
+		target := r.Target
+		if strings.HasPrefix(target, "/") {
+			target = strings.TrimPrefix(target, "/")
+		} else {
+			target = path.Join("word", target)
+		}
+		out[r.ID] = target
+	}
+	return out
+}
@@ -0,0 +1,75 @@
+//go:build unit
+
+package docx
+
+import (
+	"archive/zip"
+	"bytes"
+	"testing"
+
+	"github.com/stretchr/testify/require"
+)
+
+func buildDocx(t *testing.T, files map[string]string) []byte {
+	t.Helper()
+	var buf bytes.Buffer
+	zw := zip.NewWriter(&buf)
+	for name, content := range files {
+		w, err := zw.Create(name)
+		require.NoError(t, err)
+		_, err = w.Write([]byte(content))
+		require.NoError(t, err)
+	}
+	require.NoError(t, zw.Close())
+	return buf.Bytes()
+}
+
+func TestExtract(t *testing.T) {
+	document := `<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"
+  xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main"
+  xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships">
+<w:body>
+  <w:p><w:r><w:t>Quarterly </w:t></w:r><w:r><w:t>report</w:t></w:r></w:p>
+  <w:p><w:r><w:t>a</w:t><w:tab/><w:t>b</w:t></w:r></w:p>
+  <w:p></w:p>
+  <w:tbl>
+    <w:tr><w:tc><w:p><w:r><w:t>Item</w:t></w:r></w:p></w:tc><w:tc><w:p><w:r><w:t>Cost</w:t></w:r></w:p></w:tc></w:tr>
+    <w:tr><w:tc><w:p><w:r><w:t>GPU</w:t></w:r></w:p></w:tc><w:tc><w:p><w:r><w:t>12</w:t></w:r></w:p></w:tc></w:tr>
+  </w:tbl>
+  <w:p><w:r><w:drawing><a:blip r:embed="rId5"/></w:drawing></w:r><w:r><w:delText>removed</w:delText></w:r></w:p>
+  <w:p><w:r><w:drawing><a:blip r:embed="rId6"/></w:drawing></w:r></w:p>
+</w:body>
+</w:document>`
+	rels := `<?xml version="1.0" encoding="UTF-8"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
+  <Relationship Id="rId5" Type="image" Target="media/image1.png"/>
+  <Relationship Id="rId6" Type="image" Target="media/image2.emf"/>
+  <Relationship Id="rId7" Type="hyperlink" Target="https://example.com" TargetMode="External"/>
+</Relationships>`
+	data := buildDocx(t, map[string]string{
+		"word/document.xml":            document,
+		"word/_rels/document.xml.rels": rels,
+		"word/media/image1.png":        "png-bytes",
+		"word/media/image2.emf":        "emf-bytes",
+	})
+
+	doc, err := Extract(data, true)
+	require.NoError(t, err)
+	require.Equal(t, []string{"Quarterly report", "a\tb", "Item | Cost", "GPU | 12"}, doc.Paragraphs)
+	require.Len(t, doc.Images, 1)
+	require.Equal(t, "image/png", doc.Images[0].MediaType)
+	require.Equal(t, []byte("png-bytes"), doc.Images[0].Data)
+
+	doc, err = Extract(data, false)
+	require.NoError(t, err)
+	require.Empty(t, doc.Images)
+}
+
+func TestExtract_Invalid(t *testing.T) {
+	_, err := Extract([]byte("plain text"), false)
+	require.ErrorIs(t, err, ErrInvalid)
+
+	_, err = Extract(buildDocx(t, map[string]string{"xl/workbook.xml": "<workbook/>"}), false)
+	require.ErrorIs(t, err, ErrInvalid)
+}
@@ -0,0 +1,196 @@
+// Package envelope 提供信封加密：每个值使用随机数据密钥（DEK）以 AES-256-GCM 加密，
+// DEK 再由主密钥（KEK）包装后与密文一起保存。
+//
+// 密文格式（ASCII，可直接存入 JSON 字符串）：
+//
+//	enc:v1:<key-id>:<base64(wrapped DEK)>:<base64(nonce + ciphertext)>
+//
+// 主密钥轮换：新主密钥设为 active，旧主密钥保留在 Keyring 中用于解密，
+// 再将 NeedsRewrap 为 true 的值重新加密即可。
+package envelope
+
+import (
+	"crypto/aes"
+	"crypto/cipher"
+	"crypto/rand"
+	"encoding/base64"
+	"errors"
+	"fmt"
+	"io"
+	"strings"
+)
+
+const (
+	prefix     = "enc:v1:"
+	dekSize    = 32
+	masterSize = 32
+)
+
+var (
+	// ErrUnknownKey 密文使用的主密钥不在 Keyring 中
+	ErrUnknownKey = errors.New("envelope: unknown master key")
+	// ErrMalformed 密文格式无效
+	ErrMalformed = errors.New("envelope: malformed ciphertext")
+)
+
+// KeyWrapper 包装/解包数据密钥。本地主密钥由 Keyring 实现；接入 KMS 时实现此接口即可。
+type KeyWrapper interface {
+	// ActiveKeyID 新加密使用的主密钥 ID
+	ActiveKeyID() string
+	Wrap(dek []byte) ([]byte, error)
+	Unwrap(keyID string, wrapped []byte) ([]byte, error)
+}
+
+// Keyring 本地主密钥集合：active 用于加密，其余仅用于解密
+type Keyring struct {
+	active string
+	keys   map[string]cipher.AEAD
+}
+
+// NewKeyring 创建主密钥集合；keys 为 key-id 到 32 字节主密钥的映射，必须包含 activeID
+func NewKeyring(activeID string, keys map[string][]byte) (*Keyring, error) {
+	if activeID == "" || strings.Contains(activeID, ":") {
+		return nil, fmt.Errorf("envelope: invalid active key id %q", activeID)
+	}
+	kr := &Keyring{active: activeID, keys: make(map[string]cipher.AEAD, len(keys))}
+	for id, key := range keys {
+		if id == "" || strings.Contains(id, ":") {
+			return nil, fmt.Errorf("envelope: invalid key id %q", id)
+		}
+		if len(key) != masterSize {
+			return nil, fmt.Errorf("envelope: master key %q must be %d bytes, got %d", id, masterSize, len(key))
+		}
+		aead, err := newGCM(key)
+		if err != nil {
+			return nil, err
+		}
+		kr.keys[id] = aead
+	}
+	if _, ok := kr.keys[activeID]; !ok {
+		return nil, fmt.Errorf("envelope: active key %q not provided", activeID)
+	}
+	return kr, nil
+}
+
+// ActiveKeyID 实现 KeyWrapper
+func (k *Keyring) ActiveKeyID() string {
+	return k.active
+}
+
+// Wrap 实现 KeyWrapper
+func (k *Keyring) Wrap(dek []byte) ([]byte, error) {
+	return seal(k.keys[k.active], dek)
+}
+
+// Unwrap 实现 KeyWrapper
+func (k *Keyring) Unwrap(keyID string, wrapped []byte) ([]byte, error) {
+	aead, ok := k.keys[keyID]
+	if !ok {
+		return nil, fmt.Errorf("%w: %s", ErrUnknownKey, keyID)
+	}
+	return open(aead, wrapped)
+}
+
+// IsEncrypted 判断字符串是否为信封密文
+func IsEncrypted(s string) bool {
+	return strings.HasPrefix(s, prefix)
+}
+
+// KeyID 返回密文使用的主密钥 ID，非密文返回空字符串
+func KeyID(s string) string {
+	if !IsEncrypted(s) {
+		return ""
+	}
+	rest := s[len(prefix):]
+	if i := strings.IndexByte(rest, ':'); i > 0 {
+		return rest[:i]
+	}
+	return ""
+}
+
+// NeedsRewrap 值为明文或使用了非 active 主密钥时返回 true
+func NeedsRewrap(w KeyWrapper, s string) bool {
+	return KeyID(s) != w.ActiveKeyID()
+}
+
+// Encrypt 使用新的数据密钥加密明文
+func Encrypt(w KeyWrapper, plaintext string) (string, error) {
+	dek := make([]byte, dekSize)
+	if _, err := io.ReadFull(rand.Reader, dek); err != nil {
+		return "", fmt.Errorf("envelope: generate data key: %w", err)
+	}
+	aead, err := newGCM(dek)
+	if err != nil {
+		return "", err
+	}
+	payload, err := seal(aead, []byte(plaintext))
+	if err != nil {
+		return "", err
+	}
+	wrapped, err := w.Wrap(dek)
+	if err != nil {
+		return "", fmt.Errorf("envelope: wrap data key: %w", err)
+	}
+	return prefix + w.ActiveKeyID() + ":" +
+		base64.StdEncoding.EncodeToString(wrapped) + ":" +
+		base64.StdEncoding.EncodeToString(payload), nil
+}
+
+// Decrypt 解密信封密文
+func Decrypt(w KeyWrapper, s string) (string, error) {
+	if !IsEncrypted(s) {
+		return "", ErrMalformed
+	}
+	parts := strings.Split(s[len(prefix):], ":")
+	if len(parts) != 3 {
+		return "", ErrMalformed
+	}
+	wrapped, err := base64.StdEncoding.DecodeString(parts[1])
+	if err != nil {
+		return "", ErrMalformed
+	}
+	payload, err := base64.StdEncoding.DecodeString(parts[2])
+	if err != nil {
+		return "", ErrMalformed
+	}
+	dek, err := w.Unwrap(parts[0], wrapped)
+	if err != nil {
+		return "", err
+	}
+	aead, err := newGCM(dek)
+	if err != nil {
+		return "", err
+	}
+	plaintext, err := open(aead, payload)
+	if err != nil {
+		return "", err
+	}
+	return string(plaintext), nil
+}
+
+func newGCM(key []byte) (cipher.AEAD, error) {
+	block, err := aes.NewCipher(key)
+	if err != nil {
+		return nil, fmt.Errorf("envelope: create cipher: %w", err)
+	}
+	return cipher.NewGCM(block)
+}
+
+func seal(aead cipher.AEAD, plaintext []byte) ([]byte, error) {
+	nonce := make([]byte, aead.NonceSize(), aead.NonceSize()+len(plaintext)+aead.Overhead())
+	if _, err := io.ReadFull(rand.Reader, nonce); err != nil {
+		return nil, fmt.Errorf("envelope: generate nonce: %w", err)
+	}
+	return aead.Seal(nonce, nonce, plaintext, nil), nil
+}
+
+func open(aead cipher.AEAD, data []byte) ([]byte, error) {
+	if len(data) < aead.NonceSize() {
+		return nil, ErrMalformed
+	}
+	plaintext, err := aead.Open(nil, data[:aead.NonceSize()], data[aead.NonceSize():], nil)
+	if err != nil {
+		return nil, fmt.Errorf("envelope: decrypt: %w", err)
+	}
+	return plaintext, nil
+}
@@ -0,0 +1,90 @@
+package envelope
+
+import (
+	"bytes"
+	"testing"
+
+	"github.com/stretchr/testify/require"
+)
+
+func testKey(b byte) []byte {
+	return bytes.Repeat([]byte{b}, 32)
+}
+
+func TestEncryptDecryptRoundTrip(t *testing.T) {
+	kr, err := NewKeyring("k1", map[string][]byte{"k1": testKey(1)})
+	require.NoError(t, err)
+
+	enc, err := Encrypt(kr, "sk-ant-secret")
+	require.NoError(t, err)
+	require.True(t, IsEncrypted(enc))
+	require.Equal(t, "k1", KeyID(enc))
+	require.NotContains(t, enc, "sk-ant-secret")
+
+	// 每次加密使用新的数据密钥与 nonce
+	enc2, err := Encrypt(kr, "sk-ant-secret")
+	require.NoError(t, err)
+	require.NotEqual(t, enc, enc2)
+
+	plain, err := Decrypt(kr, enc)
+	require.NoError(t, err)
+	require.Equal(t, "sk-ant-secret", plain)
+}
+
+func TestKeyRotation(t *testing.T) {
+	old, err := NewKeyring("k1", map[string][]byte{"k1": testKey(1)})
+	require.NoError(t, err)
+	enc, err := Encrypt(old, "refresh-token")
+	require.NoError(t, err)
+
+	rotated, err := NewKeyring("k2", map[string][]byte{"k1": testKey(1), "k2": testKey(2)})
+	require.NoError(t, err)
+	require.True(t, NeedsRewrap(rotated, enc))
+	require.True(t, NeedsRewrap(rotated, "plaintext"))
+
+	plain, err := Decrypt(rotated, enc)
+	require.NoError(t, err)
+	require.Equal(t, "refresh-token", plain)
+
+	reenc, err := Encrypt(rotated, plain)
+	require.NoError(t, err)
+	require.False(t, NeedsRewrap(rotated, reenc))
+
+	// 旧主密钥移除后无法解密旧密文
+	onlyNew, err := NewKeyring("k2", map[string][]byte{"k2": testKey(2)})
+	require.NoError(t, err)
+	_, err = Decrypt(onlyNew, enc)
+	require.ErrorIs(t, err, ErrUnknownKey)
+}
+
+func TestDecryptRejectsTampering(t *testing.T) {
+	kr, err := NewKeyring("k1", map[string][]byte{"k1": testKey(1)})
+	require.NoError(t, err)
+
+	_, err = Decrypt(kr, "plaintext")
+	require.ErrorIs(t, err, ErrMalformed)
+	_, err = Decrypt(kr, "enc:v1:k1:abc")
+	require.ErrorIs(t, err, ErrMalformed)
+
+	enc, err := Encrypt(kr, "value")
+	require.NoError(t, err)
+	tampered := enc[:len(enc)-4] + "AAA="
+	_, err = Decrypt(kr, tampered)
+	require.Error(t, err)
+
+	other, err := NewKeyring("k1", map[string][]byte{"k1": testKey(9)})
+	require.NoError(t, err)
+	_, err = Decrypt(other, enc)
+	require.Error(t, err)
+}
+
+func TestNewKeyringValidation(t *testing.T) {
+	_, err := NewKeyring("", map[string][]byte{"k1": testKey(1)})
+	require.Error(t, err)
+	_, err = NewKeyring("k1", map[string][]byte{"k1": testKey(1)[:16]})
+	require.Error(t, err)
+	_, err = NewKeyring("k2", map[string][]byte{"k1": testKey(1)})
+	require.Error(t, err)
+	_, err = NewKeyring("a:b", map[string][]byte{"a:b": testKey(1)})
+	require.Error(t, err)
+}
@@ -0,0 +1,605 @@
+package graphql
+
+import (
+	"bytes"
+	"context"
+	"encoding/json"
+	"fmt"
+	"reflect"
+	"strconv"
+	"strings"
+)
+
+// Request GraphQL HTTP 请求体
+type Request struct {
+	Query         string         `json:"query"`
+	OperationName string         `json:"operationName,omitempty"`
+	Variables     map[string]any `json:"variables,omitempty"`
+}
+
+// Location 错误在查询文本中的位置（从 1 开始）
+type Location struct {
+	Line   int `json:"line"`
+	Column int `json:"column"`
+}
+
+// Error GraphQL 错误。Path 为字段的响应路径（不含列表下标：同层数据批量解析，错误作用于整层）
+type Error struct {
+	Message   string     `json:"message"`
+	Locations []Location `json:"locations,omitempty"`
+	Path      []string   `json:"path,omitempty"`
+
+	offset int
+}
+
+func (e *Error) Error() string { return e.Message }
+
+// Result GraphQL 响应；请求未进入执行阶段（语法/校验错误）时没有 data
+type Result struct {
+	Data   any      `json:"data,omitempty"`
+	Errors []*Error `json:"errors,omitempty"`
+}
+
+func errorResult(query string, err error) *Result {
+	gqlErr, ok := err.(*Error)
+	if !ok {
+		gqlErr = &Error{Message: err.Error()}
+	}
+	if gqlErr.offset > 0 || strings.HasPrefix(gqlErr.Message, "Syntax Error") {
+		gqlErr.Locations = []Location{locate(query, gqlErr.offset)}
+	}
+	return &Result{Errors: []*Error{gqlErr}}
+}
+
+func locate(src string, offset int) Location {
+	if offset > len(src) {
+		offset = len(src)
+	}
+	line := strings.Count(src[:offset], "\n") + 1
+	col := offset - strings.LastIndex(src[:offset], "\n")
+	return Location{Line: line, Column: col}
+}
+
+// Execute 解析、校验并执行查询。仅支持 query 操作
+func (s *Schema) Execute(ctx context.Context, req Request) *Result {
+	doc, err := parse(req.Query)
+	if err != nil {
+		return errorResult(req.Query, err)
+	}
+	op, err := doc.operation(req.OperationName)
+	if err != nil {
+		return errorResult(req.Query, err)
+	}
+	if op.kind != "query" {
+		return errorResult(req.Query, fmt.Errorf("%s operations are not supported", op.kind))
+	}
+	vars, err := coerceVariables(op, req.Variables)
+	if err != nil {
+		return errorResult(req.Query, err)
+	}
+	e := &executor{ctx: ctx, schema: s, fragments: doc.fragments, vars: vars}
+	if err := e.validate(s.Query, op.selections, 1, map[string]bool{}); err != nil {
+		return errorResult(req.Query, err)
+	}
+	data := e.executeSelectionSet(s.Query, []any{nil}, op.selections, nil)
+	return &Result{Data: data[0], Errors: e.errors}
+}
+
+func (d *document) operation(name string) (*operation, error) {
+	if name == "" {
+		if len(d.operations) > 1 {
+			return nil, &Error{Message: "Must provide operation name if query contains multiple operations."}
+		}
+		return d.operations[0], nil
+	}
+	for _, op := range d.operations {
+		if op.name == name {
+			return op, nil
+		}
+	}
+	return nil, &Error{Message: fmt.Sprintf("Unknown operation named %q.", name)}
+}
+
+func coerceVariables(op *operation, provided map[string]any) (map[string]any, error) {
+	vars := map[string]any{}
+	for _, def := range op.vars {
+		v, ok := provided[def.name]
+		if !ok && def.defaultValue != nil {
+			v, ok = valueOf(def.defaultValue, nil), true
+		}
+		if (!ok || v == nil) && strings.HasSuffix(def.typ, "!") {
+			return nil, &Error{Message: fmt.Sprintf("Variable \"$%s\" of required type %q was not provided.", def.name, def.typ)}
+		}
+		if ok {
+			vars[def.name] = v
+		}
+	}
+	return vars, nil
+}
+
+// valueOf 将 AST 值转换为 Go 值；变量从 vars 读取
+func valueOf(v *value, vars map[string]any) any {
+	switch v.kind {
+	case valueVariable:
+		return vars[v.raw]
+	case valueInt:
+		if n, err := strconv.ParseInt(v.raw, 10, 64); err == nil {
+			return n
+		}
+		f, _ := strconv.ParseFloat(v.raw, 64)
+		return f
+	case valueFloat:
+		f, _ := strconv.ParseFloat(v.raw, 64)
+		return f
+	case valueString, valueEnum:
+		return v.raw
+	case valueBoolean:
+		return v.raw == "true"
+	case valueList:
+		out := make([]any, len(v.list))
+		for i, item := range v.list {
+			out[i] = valueOf(item, vars)
+		}
+		return out
+	case valueObject:
+		out := make(map[string]any, len(v.fields))
+		for _, f := range v.fields {
+			out[f.name] = valueOf(f.value, vars)
+		}
+		return out
+	}
+	return nil
+}
+
+type executor struct {
+	ctx        context.Context
+	schema     *Schema
+	fragments  map[string]*fragmentDef
+	vars       map[string]any
+	errors     []*Error
+	complexity int
+}
+
+func (e *executor) addError(path []string, msg string) {
+	e.errors = append(e.errors, &Error{Message: msg, Path: path})
+}
+
+func (e *executor) presentError(err error) string {
+	if gqlErr, ok := err.(*Error); ok {
+		return gqlErr.Message
+	}
+	if e.schema.ErrorPresenter != nil {
+		return e.schema.ErrorPresenter(err)
+	}
+	return err.Error()
+}
+
+// addComplexity 累加查询复杂度；片段每次展开都会计入，超限即停止校验（同时限制重复展开片段的校验开销）
+func (e *executor) addComplexity(cost int) error {
+	e.complexity += cost
+	if e.schema.MaxComplexity > 0 && e.complexity > e.schema.MaxComplexity {
+		return &Error{Message: fmt.Sprintf("Query exceeds maximum complexity of %d.", e.schema.MaxComplexity)}
+	}
+	return nil
+}
+
+// validate 在执行前校验字段、参数、片段、查询深度与复杂度
+func (e *executor) validate(obj *Object, sels []selection, depth int, spreading map[string]bool) error {
+	if depth > e.schema.MaxDepth {
+		return &Error{Message: fmt.Sprintf("Query exceeds maximum depth of %d.", e.schema.MaxDepth)}
+	}
+	for _, sel := range sels {
+		switch s := sel.(type) {
+		case *field:
+			if s.name == "__typename" {
+				if len(s.selections) > 0 {
+					return &Error{Message: `Field "__typename" must not have a selection since type "String!" has no subfields.`}
+				}
+				if err := e.addComplexity(1); err != nil {
+					return err
+				}
+				continue
+			}
+			f := obj.Field(s.name)
+			if f == nil {
+				return &Error{Message: fmt.Sprintf("Cannot query field %q on type %q.", s.name, obj.Name)}
+			}
+			cost := 1
+			if f.Resolve != nil || f.Batch != nil {
+				cost = ResolverCost
+			}
+			if err := e.addComplexity(cost); err != nil {
+				return err
+			}
+			for _, a := range s.args {
+				if f.arg(a.name) == nil {
+					return &Error{Message: fmt.Sprintf("Unknown argument %q on field \"%s.%s\".", a.name, obj.Name, f.Name)}
+				}
+			}
+			child, isObject := namedType(f.Type).(*Object)
+			switch {
+			case isObject && len(s.selections) == 0:
+				return &Error{Message: fmt.Sprintf("Field %q of type %q must have a selection of subfields.", s.name, f.Type.String())}
+			case !isObject && len(s.selections) > 0:
+				return &Error{Message: fmt.Sprintf("Field %q must not have a selection since type %q has no subfields.", s.name, f.Type.String())}
+			case isObject:
+				if err := e.validate(child, s.selections, depth+1, spreading); err != nil {
+					return err
+				}
+			}
+		case *fragmentSpread:
+			frag := e.fragments[s.name]
+			if frag == nil {
+				return &Error{Message: fmt.Sprintf("Unknown fragment %q.", s.name)}
+			}
+			if spreading[s.name] {
+				return &Error{Message: fmt.Sprintf("Cannot spread fragment %q within itself.", s.name)}
+			}
+			if frag.typeCondition != obj.Name {
+				return &Error{Message: fmt.Sprintf("Fragment %q cannot be spread here as objects of type %q can never be of type %q.", s.name, obj.Name, frag.typeCondition)}
+			}
+			spreading[s.name] = true
+			err := e.validate(obj, frag.selections, depth, spreading)
+			delete(spreading, s.name)
+			if err != nil {
+				return err
+			}
+		case *inlineFragment:
+			if s.typeCondition != "" && s.typeCondition != obj.Name {
+				return &Error{Message: fmt.Sprintf("Fragment cannot be spread here as objects of type %q can never be of type %q.", obj.Name, s.typeCondition)}
+			}
+			if err := e.validate(obj, s.selections, depth, spreading); err != nil {
+				return err
+			}
+		}
+	}
+	return nil
+}
+
+// fieldGroup 同一响应键下合并的字段
+type fieldGroup struct {
+	key    string
+	name   string
+	fields []*field
+}
+
+func (e *executor) collectFields(sels []selection, groups []*fieldGroup, visited map[string]bool) []*fieldGroup {
+	for _, sel := range sels {
+		switch s := sel.(type) {
+		case *field:
+			if !e.included(s.directives) {
+				continue
+			}
+			key := s.responseKey()
+			found := false
+			for _, g := range groups {
+				if g.key == key {
+					g.fields = append(g.fields, s)
+					found = true
+					break
+				}
+			}
+			if !found {
+				groups = append(groups, &fieldGroup{key: key, name: s.name, fields: []*field{s}})
+			}
+		case *fragmentSpread:
+			if visited[s.name] || !e.included(s.directives) {
+				continue
+			}
+			visited[s.name] = true
+			groups = e.collectFields(e.fragments[s.name].selections, groups, visited)
+		case *inlineFragment:
+			if e.included(s.directives) {
+				groups = e.collectFields(s.selections, groups, visited)
+			}
+		}
+	}
+	return groups
+}
+
+// included 处理 @skip(if:) 与 @include(if:)
+func (e *executor) included(dirs []*directive) bool {
+	for _, d := range dirs {
+		var cond bool
+		for _, a := range d.args {
+			if a.name == "if" {
+				cond, _ = valueOf(a.value, e.vars).(bool)
+			}
+		}
+		if (d.name == "skip" && cond) || (d.name == "include" && !cond) {
+			return false
+		}
+	}
+	return true
+}
+
+// executeSelectionSet 对同一层级的所有 source 执行选择集；每个字段只解析一次（批量）
+func (e *executor) executeSelectionSet(obj *Object, sources []any, sels []selection, path []string) []any {
+	results := make([]*orderedMap, len(sources))
+	for i := range results {
+		results[i] = &orderedMap{}
+	}
+	for _, g := range e.collectFields(sels, nil, map[string]bool{}) {
+		fieldPath := append(path[:len(path):len(path)], g.key)
+		if g.name == "__typename" {
+			for _, r := range results {
+				r.set(g.key, obj.Name)
+			}
+			continue
+		}
+		f := obj.Field(g.name)
+		completed, err := e.resolveField(f, sources, g.fields[0].args)
+		if err != nil {
+			e.addError(fieldPath, e.presentError(err))
+			completed = make([]any, len(sources))
+		} else {
+			var subSels []selection
+			for _, af := range g.fields {
+				subSels = append(subSels, af.selections...)
+			}
+			completed = e.complete(f.Type, completed, subSels, fieldPath)
+		}
+		for i, r := range results {
+			r.set(g.key, completed[i])
+		}
+	}
+	out := make([]any, len(results))
+	for i, r := range results {
+		out[i] = r
+	}
+	return out
+}
+
+func (e *executor) resolveField(f *Field, sources []any, astArgs []*argument) ([]any, error) {
+	args, err := e.coerceArgs(f, astArgs)
+	if err != nil {
+		return nil, err
+	}
+	if len(sources) == 0 {
+		return nil, nil
+	}
+	switch {
+	case f.Batch != nil:
+		out, err := f.Batch(e.ctx, sources, args)
+		if err != nil {
+			return nil, err
+		}
+		if len(out) != len(sources) {
+			return nil, fmt.Errorf("batch resolver for %q returned %d values for %d sources", f.Name, len(out), len(sources))
+		}
+		return out, nil
+	case f.Resolve != nil:
+		out := make([]any, len(sources))
+		for i, src := range sources {
+			v, err := f.Resolve(e.ctx, src, args)
+			if err != nil {
+				return nil, err
+			}
+			out[i] = v
+		}
+		return out, nil
+	default:
+		out := make([]any, len(sources))
+		for i, src := range sources {
+			out[i] = defaultResolve(src, f.Name)
+		}
+		return out, nil
+	}
+}
+
+func (e *executor) coerceArgs(f *Field, astArgs []*argument) (Args, error) {
+	provided := make(map[string]*value, len(astArgs))
+	for _, a := range astArgs {
+		provided[a.name] = a.value
+	}
+	args := Args{}
+	for _, def := range f.Args {
+		var v any
+		if raw, ok := provided[def.Name]; ok {
+			v = valueOf(raw, e.vars)
+		}
+		if v == nil {
+			if def.Default != nil {
+				args[def.Name] = def.Default
+				continue
+			}
+			if _, required := def.Type.(*NonNull); required {
+				return nil, &Error{Message: fmt.Sprintf("Argument %q of required type %q was not provided.", def.Name, def.Type.String())}
+			}
+			continue
+		}
+		cv, err := coerceInput(def.Type, v)
+		if err != nil {
+			return nil, &Error{Message: fmt.Sprintf("Argument %q has invalid value: %s", def.Name, err.Error())}
+		}
+		args[def.Name] = cv
+	}
+	return args, nil
+}
+
+func coerceInput(t Type, v any) (any, error) {
+	switch tt := t.(type) {
+	case *NonNull:
+		if v == nil {
+			return nil, fmt.Errorf("expected non-null %s", tt.String())
+		}
+		return coerceInput(tt.Of, v)
+	case *List:
+		if v == nil {
+			return nil, nil
+		}
+		items, ok := v.([]any)
+		if !ok {
+			items = []any{v}
+		}
+		out := make([]any, len(items))
+		for i, item := range items {
+			cv, err := coerceInput(tt.Of, item)
+			if err != nil {
+				return nil, err
+			}
+			out[i] = cv
+		}
+		return out, nil
+	case *Scalar:
+		if v == nil {
+			return nil, nil
+		}
+		cv, ok := tt.Parse(v)
+		if !ok {
+			return nil, fmt.Errorf("expected %s", tt.Name)
+		}
+		return cv, nil
+	}
+	return nil, fmt.Errorf("unsupported input type %s", t.String())
+}
+
+// complete 按字段类型补全同一层级的解析结果
+func (e *executor) complete(t Type, values []any, sels []selection, path []string) []any {
+	switch tt := t.(type) {
+	case *NonNull:
+		out := e.complete(tt.Of, values, sels, path)
+		for _, v := range out {
+			if v == nil {
+				e.addError(path, fmt.Sprintf("Cannot return null for non-nullable field of type %q.", tt.String()))
+				break
+			}
+		}
+		return out
+
+	case *Scalar:
+		out := make([]any, len(values))
+		reported := false
+		for i, v := range values {
+			v = deref(v)
+			if v == nil {
+				continue
+			}
+			sv, ok := tt.Serialize(v)
+			if !ok && !reported {
+				e.addError(path, fmt.Sprintf("%s cannot represent value of type %T.", tt.Name, v))
+				reported = true
+			}
+			out[i] = sv
+		}
+		return out
+
+	case *List:
+		out := make([]any, len(values))
+		counts := make([]int, len(values))
+		var flat []any
+		for i, v := range values {
+			counts[i] = -1
+			rv := reflect.ValueOf(v)
+			for rv.IsValid() && (rv.Kind() == reflect.Pointer || rv.Kind() == reflect.Interface) && !rv.IsNil() {
+				rv = rv.Elem()
+			}
+			if !rv.IsValid() || ((rv.Kind() == reflect.Pointer || rv.Kind() == reflect.Interface) && rv.IsNil()) {
+				continue
+			}
+			if rv.Kind() != reflect.Slice && rv.Kind() != reflect.Array {
+				e.addError(path, fmt.Sprintf("Expected a list, got %T.", v))
+				continue
+			}
+			counts[i] = rv.Len()
+			for j := 0; j < rv.Len(); j++ {
+				flat = append(flat, rv.Index(j).Interface())
+			}
+		}
+		inner := e.complete(tt.Of, flat, sels, path)
+		pos := 0
+		for i, n := range counts {
+			if n < 0 {
+				continue
+			}
+			out[i] = inner[pos : pos+n : pos+n]
+			pos += n
+		}
+		return out
+
+	case *Object:
+		out := make([]any, len(values))
+		var idx []int
+		var sources []any
+		for i, v := range values {
+			if isNil(v) {
+				continue
+			}
+			idx = append(idx, i)
+			sources = append(sources, v)
+		}
+		if len(sources) == 0 {
+			return out
+		}
+		for k, r := range e.executeSelectionSet(tt, sources, sels, path) {
+			out[idx[k]] = r
+		}
+		return out
+	}
+	return make([]any, len(values))
+}
+
+func deref(v any) any {
+	rv := reflect.ValueOf(v)
+	for rv.IsValid() && rv.Kind() == reflect.Pointer {
+		if rv.IsNil() {
+			return nil
+		}
+		rv = rv.Elem()
+	}
+	if !rv.IsValid() {
+		return nil
+	}
+	return rv.Interface()
+}
+
+func isNil(v any) bool {
+	if v == nil {
+		return true
+	}
+	rv := reflect.ValueOf(v)
+	switch rv.Kind() {
+	case reflect.Pointer, reflect.Map, reflect.Interface:
+		return rv.IsNil()
+	}
+	return false
+}
+
+// orderedMap 按选择集顺序输出字段
+type orderedMap struct {
+	keys   []string
+	values map[string]any
+}
+
+func (m *orderedMap) set(key string, v any) {
+	if m.values == nil {
+		m.values = map[string]any{}
+	}
+	if _, ok := m.values[key]; !ok {
+		m.keys = append(m.keys, key)
+	}
+	m.values[key] = v
+}
+
+func (m *orderedMap) MarshalJSON() ([]byte, error) {
+	var buf bytes.Buffer
+	_ = buf.WriteByte('{')
+	for i, k := range m.keys {
+		if i > 0 {
+			_ = buf.WriteByte(',')
+		}
+		key, err := json.Marshal(k)
+		if err != nil {
+			return nil, err
+		}
+		val, err := json.Marshal(m.values[k])
+		if err != nil {
+			return nil, err
+		}
+		_, _ = buf.Write(key)
+		_ = buf.WriteByte(':')
+		_, _ = buf.Write(val)
+	}
+	_ = buf.WriteByte('}')
+	return buf.Bytes(), nil
+}
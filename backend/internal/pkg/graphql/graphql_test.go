@@ -0,0 +1,231 @@
+//go:build unit
+
+package graphql
+
+import (
+	"context"
+	"encoding/json"
+	"errors"
+	"testing"
+	"time"
+
+	"github.com/stretchr/testify/require"
+)
+
+type testOwner struct {
+	ID   int64  `json:"id"`
+	Name string `json:"name"`
+}
+
+type testWidget struct {
+	ID        int64     `json:"id"`
+	Name      string    `json:"name"`
+	Price     *float64  `json:"price"`
+	Tags      []string  `json:"tags"`
+	OwnerID   int64     `json:"owner_id"`
+	CreatedAt time.Time `json:"created_at"`
+	Secret    string    `json:"-"`
+	Extra     map[string]any
+}
+
+type testFixture struct {
+	schema      *Schema
+	batchCalls  int
+	batchSizes  []int
+	lastArgs    Args
+	resolverErr error
+}
+
+func newTestFixture() *testFixture {
+	fx := &testFixture{}
+	price := 9.5
+	widgets := []*testWidget{
+		{ID: 1, Name: "alpha", Price: &price, Tags: []string{"a", "b"}, OwnerID: 10, CreatedAt: time.Date(2026, 1, 2, 3, 4, 5, 0, time.UTC)},
+		{ID: 2, Name: "beta", OwnerID: 20},
+		{ID: 3, Name: "gamma", OwnerID: 10},
+	}
+	owners := map[int64]*testOwner{10: {ID: 10, Name: "ann"}, 20: {ID: 20, Name: "bob"}}
+
+	owner := ObjectOf("Owner", "", testOwner{})
+	widget := ObjectOf("Widget", "A widget", testWidget{}).AddFields(&Field{
+		Name: "owner",
+		Type: owner,
+		Batch: func(ctx context.Context, sources []any, args Args) ([]any, error) {
+			fx.batchCalls++
+			fx.batchSizes = append(fx.batchSizes, len(sources))
+			out := make([]any, len(sources))
+			for i, src := range sources {
+				out[i] = owners[src.(*testWidget).OwnerID]
+			}
+			return out, nil
+		},
+	}, &Field{
+		Name: "broken",
+		Type: String,
+		Resolve: func(ctx context.Context, source any, args Args) (any, error) {
+			return nil, fx.resolverErr
+		},
+	})
+	query := NewObject("Query", "",
+		&Field{
+			Name: "widgets",
+			Type: NonNullOf(ListOf(NonNullOf(widget))),
+			Args: []*Argument{{Name: "limit", Type: Int, Default: int64(10)}, {Name: "since", Type: Time}},
+			Resolve: func(ctx context.Context, source any, args Args) (any, error) {
+				fx.lastArgs = args
+				n := int(args.Int("limit"))
+				if n > len(widgets) {
+					n = len(widgets)
+				}
+				return widgets[:n], nil
+			},
+		},
+		&Field{
+			Name: "widget",
+			Type: widget,
+			Args: []*Argument{{Name: "id", Type: NonNullOf(Int)}},
+			Resolve: func(ctx context.Context, source any, args Args) (any, error) {
+				for _, w := range widgets {
+					if w.ID == args.Int("id") {
+						return w, nil
+					}
+				}
+				return nil, nil
+			},
+		},
+	)
+	fx.schema = NewSchema(query)
+	return fx
+}
+
+func marshal(t *testing.T, r *Result) string {
+	t.Helper()
+	data, err := json.Marshal(r)
+	require.NoError(t, err)
+	return string(data)
+}
+
+func TestExecute_NestedBatchAndOrder(t *testing.T) {
+	fx := newTestFixture()
+	res := fx.schema.Execute(context.Background(), Request{Query: `
+		query List {
+			widgets { name id owner { name } price tags created_at }
+			single: widget(id: 2) { __typename name owner { id } }
+		}`})
+
+	require.Empty(t, res.Errors)
+	require.Equal(t,
+		`{"data":{"widgets":[`+
+			`{"name":"alpha","id":1,"owner":{"name":"ann"},"price":9.5,"tags":["a","b"],"created_at":"2026-01-02T03:04:05Z"},`+
+			`{"name":"beta","id":2,"owner":{"name":"bob"},"price":null,"tags":[],"created_at":"0001-01-01T00:00:00Z"},`+
+			`{"name":"gamma","id":3,"owner":{"name":"ann"},"price":null,"tags":[],"created_at":"0001-01-01T00:00:00Z"}],`+
+			`"single":{"__typename":"Widget","name":"beta","owner":{"id":20}}}}`,
+		marshal(t, res))
+	// 列表内 3 个 widget 的 owner 只批量解析一次
+	require.Equal(t, 2, fx.batchCalls)
+	require.Equal(t, []int{3, 1}, fx.batchSizes)
+}
+
+func TestExecute_VariablesFragmentsDirectives(t *testing.T) {
+	fx := newTestFixture()
+	res := fx.schema.Execute(context.Background(), Request{
+		Query: `
+			query ($limit: Int, $withOwner: Boolean!, $since: Time) {
+				widgets(limit: $limit, since: $since) {
+					...basic
+					owner @include(if: $withOwner) { name }
+					... on Widget { tags @skip(if: true) }
+				}
+			}
+			fragment basic on Widget { id name }`,
+		Variables: map[string]any{"limit": float64(2), "withOwner": false, "since": "2026-01-01"},
+	})
+
+	require.Empty(t, res.Errors)
+	require.JSONEq(t, `{"data":{"widgets":[{"id":1,"name":"alpha"},{"id":2,"name":"beta"}]}}`, marshal(t, res))
+	require.Equal(t, int64(2), fx.lastArgs.Int("limit"))
+	require.Equal(t, time.Date(2026, 1, 1, 0, 0, 0, 0, time.UTC), fx.lastArgs.Time("since"))
+	require.Zero(t, fx.batchCalls)
+}
+
+func TestExecute_ResolverErrorsArePartial(t *testing.T) {
+	fx := newTestFixture()
+	fx.resolverErr = errors.New("db down: secret dsn")
+	fx.schema.ErrorPresenter = func(err error) string { return "internal error" }
+
+	res := fx.schema.Execute(context.Background(), Request{Query: `{ widget(id: 1) { name broken } }`})
+	require.JSONEq(t, `{"data":{"widget":{"name":"alpha","broken":null}},"errors":[{"message":"internal error","path":["widget","broken"]}]}`, marshal(t, res))
+
+	res = fx.schema.Execute(context.Background(), Request{Query: `{ widget { name } }`})
+	require.Len(t, res.Errors, 1)
+	require.Contains(t, res.Errors[0].Message, `Argument "id" of required type "Int!" was not provided.`)
+}
+
+func TestExecute_RejectsInvalidDocuments(t *testing.T) {
+	fx := newTestFixture()
+	fx.schema.MaxDepth = 2
+
+	cases := []struct {
+		query string
+		want  string
+	}{
+		{query: `{ widgets { nope } }`, want: `Cannot query field "nope" on type "Widget".`},
+		{query: `{ widgets }`, want: `must have a selection of subfields`},
+		{query: `{ widgets { name { x } } }`, want: `must not have a selection`},
+		{query: `{ widget(id: 1, bogus: 2) { name } }`, want: `Unknown argument "bogus"`},
+		{query: `{ widgets { owner { name } } }`, want: `maximum depth of 2`},
+		{query: `{ widgets { ...f } } fragment f on Widget { ...f }`, want: `within itself`},
+		{query: `mutation { widgets { name } }`, want: `mutation operations are not supported`},
+		{query: `query A { widgets { id } } query B { widgets { id } }`, want: `Must provide operation name`},
+		{query: `query ($id: Int!) { widget(id: $id) { id } }`, want: `Variable "$id" of required type "Int!" was not provided.`},
+	}
+	for _, tc := range cases {
+		res := fx.schema.Execute(context.Background(), Request{Query: tc.query})
+		require.Nil(t, res.Data, tc.query)
+		require.Len(t, res.Errors, 1, tc.query)
+		require.Contains(t, res.Errors[0].Message, tc.want, tc.query)
+	}
+
+	res := fx.schema.Execute(context.Background(), Request{Query: "{\n  widgets { name \n"})
+	require.Len(t, res.Errors, 1)
+	require.Contains(t, res.Errors[0].Message, "Syntax Error")
+	require.Equal(t, []Location{{Line: 3, Column: 1}}, res.Errors[0].Locations)
+}
+
+func TestExecute_ComplexityLimit(t *testing.T) {
+	fx := newTestFixture()
+	fx.schema.MaxComplexity = 2*ResolverCost + 3
+
+	cases := []string{
+		// 别名重复选择带解析器的字段
+		`{ a: widgets { id } b: widgets { id } c: widgets { id } }`,
+		// 片段每次展开都计入
+		`{ widgets { ...f ...f ...f } } fragment f on Widget { owner { id } }`,
+	}
+	for _, query := range cases {
+		res := fx.schema.Execute(context.Background(), Request{Query: query})
+		require.Nil(t, res.Data, query)
+		require.Len(t, res.Errors, 1, query)
+		require.Contains(t, res.Errors[0].Message, "maximum complexity of 23", query)
+	}
+	require.Zero(t, fx.batchCalls)
+
+	// widgets(10) + id + owner(10) + name = 22
+	res := fx.schema.Execute(context.Background(), Request{Query: `{ widgets { id owner { name } } }`})
+	require.Empty(t, res.Errors)
+
+	fx.schema.MaxComplexity = 0
+	res = fx.schema.Execute(context.Background(), Request{Query: cases[0]})
+	require.Empty(t, res.Errors)
+}
+
+func TestSchema_SDL(t *testing.T) {
+	fx := newTestFixture()
+	sdl := fx.schema.SDL()
+
+	require.Contains(t, sdl, "scalar Time\n")
+	require.Contains(t, sdl, "type Query {\n  widgets(limit: Int = 10, since: Time): [Widget!]!\n  widget(id: Int!): Widget\n}\n")
+	require.Contains(t, sdl, "\"A widget\"\ntype Widget {\n  id: Int!\n  name: String!\n  price: Float\n  tags: [String!]\n  owner_id: Int!\n  created_at: Time!\n  owner: Owner\n  broken: String\n}\n")
+	require.NotContains(t, sdl, "Secret")
+	require.NotContains(t, sdl, "Extra")
+}
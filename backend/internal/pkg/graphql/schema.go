@@ -0,0 +1,520 @@
+package graphql
+
+import (
+	"context"
+	"encoding/json"
+	"fmt"
+	"math"
+	"reflect"
+	"sort"
+	"strconv"
+	"strings"
+	"sync"
+	"time"
+)
+
+// Type GraphQL 类型：*Scalar、*Object、*List 或 *NonNull
+type Type interface {
+	String() string
+}
+
+// Scalar 标量类型
+type Scalar struct {
+	Name        string
+	Description string
+	// Serialize 将解析结果（已解引用）转换为 JSON 值，ok=false 表示类型不匹配
+	Serialize func(v any) (any, bool)
+	// Parse 将参数/变量值转换为 Go 值
+	Parse func(v any) (any, bool)
+}
+
+func (s *Scalar) String() string { return s.Name }
+
+// List 列表类型
+type List struct {
+	Of Type
+}
+
+// ListOf 创建列表类型
+func ListOf(t Type) *List { return &List{Of: t} }
+
+func (l *List) String() string { return "[" + l.Of.String() + "]" }
+
+// NonNull 非空类型
+type NonNull struct {
+	Of Type
+}
+
+// NonNullOf 创建非空类型
+func NonNullOf(t Type) *NonNull { return &NonNull{Of: t} }
+
+func (n *NonNull) String() string { return n.Of.String() + "!" }
+
+// ResolveFunc 逐个解析字段值
+type ResolveFunc func(ctx context.Context, source any, args Args) (any, error)
+
+// BatchResolveFunc 一次解析同一层级所有 source 的字段值（dataloader 语义），
+// 返回值须与 sources 一一对应
+type BatchResolveFunc func(ctx context.Context, sources []any, args Args) ([]any, error)
+
+// Field 对象字段。Resolve 与 Batch 均为空时按 json tag（或 map 键）读取 source 的同名字段
+type Field struct {
+	Name        string
+	Description string
+	Type        Type
+	Args        []*Argument
+	Resolve     ResolveFunc
+	Batch       BatchResolveFunc
+}
+
+func (f *Field) arg(name string) *Argument {
+	for _, a := range f.Args {
+		if a.Name == name {
+			return a
+		}
+	}
+	return nil
+}
+
+// Argument 字段参数；Default 为空且类型为 NonNull 时必填
+type Argument struct {
+	Name        string
+	Description string
+	Type        Type
+	Default     any
+}
+
+// Object 对象类型
+type Object struct {
+	Name        string
+	Description string
+	fields      []*Field
+	index       map[string]*Field
+}
+
+// NewObject 创建对象类型
+func NewObject(name, description string, fields ...*Field) *Object {
+	o := &Object{Name: name, Description: description, index: map[string]*Field{}}
+	return o.AddFields(fields...)
+}
+
+// ObjectOf 按 sample 结构体的 json tag 生成对象类型的标量字段（含嵌入结构体字段）。
+// 非指针字段为 NonNull；map、结构体（time.Time 除外）及其切片被跳过，需要时通过 AddFields 补充
+func ObjectOf(name, description string, sample any) *Object {
+	o := NewObject(name, description)
+	t := reflect.TypeOf(sample)
+	for t.Kind() == reflect.Pointer {
+		t = t.Elem()
+	}
+	for _, sf := range reflect.VisibleFields(t) {
+		if !sf.IsExported() || sf.Anonymous {
+			continue
+		}
+		fieldName, ok := jsonName(sf)
+		if !ok {
+			continue
+		}
+		if typ := typeOfGo(sf.Type); typ != nil {
+			o.AddFields(&Field{Name: fieldName, Type: typ})
+		}
+	}
+	return o
+}
+
+// AddFields 追加字段，同名字段被替换
+func (o *Object) AddFields(fields ...*Field) *Object {
+	for _, f := range fields {
+		if _, exists := o.index[f.Name]; exists {
+			for i, old := range o.fields {
+				if old.Name == f.Name {
+					o.fields[i] = f
+				}
+			}
+		} else {
+			o.fields = append(o.fields, f)
+		}
+		o.index[f.Name] = f
+	}
+	return o
+}
+
+// Field 按名称查找字段
+func (o *Object) Field(name string) *Field {
+	return o.index[name]
+}
+
+func (o *Object) String() string { return o.Name }
+
+// Args 已按声明类型转换的参数值
+type Args map[string]any
+
+// Has 参数是否提供（或有默认值）
+func (a Args) Has(name string) bool {
+	v, ok := a[name]
+	return ok && v != nil
+}
+
+// Int 整数参数，未提供时为 0
+func (a Args) Int(name string) int64 {
+	v, _ := a[name].(int64)
+	return v
+}
+
+// String 字符串参数，未提供时为空
+func (a Args) String(name string) string {
+	v, _ := a[name].(string)
+	return v
+}
+
+// Bool 布尔参数，未提供时为 nil
+func (a Args) Bool(name string) *bool {
+	if v, ok := a[name].(bool); ok {
+		return &v
+	}
+	return nil
+}
+
+// Time 时间参数，未提供时为零值
+func (a Args) Time(name string) time.Time {
+	v, _ := a[name].(time.Time)
+	return v
+}
+
+// 内置标量
+var (
+	Int = &Scalar{
+		Name:        "Int",
+		Description: "64 位有符号整数",
+		Serialize:   serializeInt,
+		Parse: func(v any) (any, bool) {
+			switch n := v.(type) {
+			case int64:
+				return n, true
+			case float64:
+				if n == math.Trunc(n) && math.Abs(n) < 1<<63 {
+					return int64(n), true
+				}
+			case json.Number:
+				i, err := n.Int64()
+				return i, err == nil
+			}
+			return nil, false
+		},
+	}
+	Float = &Scalar{
+		Name:        "Float",
+		Description: "双精度浮点数",
+		Serialize: func(v any) (any, bool) {
+			rv := reflect.ValueOf(v)
+			switch rv.Kind() {
+			case reflect.Float32, reflect.Float64:
+				return rv.Float(), true
+			}
+			return serializeInt(v)
+		},
+		Parse: func(v any) (any, bool) {
+			switch n := v.(type) {
+			case int64:
+				return float64(n), true
+			case float64:
+				return n, true
+			case json.Number:
+				f, err := n.Float64()
+				return f, err == nil
+			}
+			return nil, false
+		},
+	}
+	String = &Scalar{
+		Name:        "String",
+		Description: "UTF-8 字符串",
+		Serialize: func(v any) (any, bool) {
+			rv := reflect.ValueOf(v)
+			if rv.Kind() == reflect.String {
+				return rv.String(), true
+			}
+			return nil, false
+		},
+		Parse: func(v any) (any, bool) {
+			s, ok := v.(string)
+			return s, ok
+		},
+	}
+	Boolean = &Scalar{
+		Name:        "Boolean",
+		Description: "布尔值",
+		Serialize: func(v any) (any, bool) {
+			rv := reflect.ValueOf(v)
+			if rv.Kind() == reflect.Bool {
+				return rv.Bool(), true
+			}
+			return nil, false
+		},
+		Parse: func(v any) (any, bool) {
+			b, ok := v.(bool)
+			return b, ok
+		},
+	}
+	Time = &Scalar{
+		Name:        "Time",
+		Description: "RFC 3339 时间；作为参数时也接受 YYYY-MM-DD",
+		Serialize: func(v any) (any, bool) {
+			t, ok := v.(time.Time)
+			if !ok {
+				return nil, false
+			}
+			return t.Format(time.RFC3339Nano), true
+		},
+		Parse: func(v any) (any, bool) {
+			s, ok := v.(string)
+			if !ok {
+				return nil, false
+			}
+			if t, err := time.Parse(time.RFC3339Nano, s); err == nil {
+				return t, true
+			}
+			if t, err := time.Parse(time.DateOnly, s); err == nil {
+				return t, true
+			}
+			return nil, false
+		},
+	}
+)
+
+var builtinScalars = map[string]bool{"Int": true, "Float": true, "String": true, "Boolean": true}
+
+func serializeInt(v any) (any, bool) {
+	rv := reflect.ValueOf(v)
+	switch rv.Kind() {
+	case reflect.Int, reflect.Int8, reflect.Int16, reflect.Int32, reflect.Int64:
+		return rv.Int(), true
+	case reflect.Uint, reflect.Uint8, reflect.Uint16, reflect.Uint32, reflect.Uint64:
+		if u := rv.Uint(); u <= math.MaxInt64 {
+			return int64(u), true
+		}
+	}
+	return nil, false
+}
+
+var timeType = reflect.TypeOf(time.Time{})
+
+// typeOfGo 将 Go 字段类型映射为 GraphQL 类型；不支持的类型返回 nil
+func typeOfGo(t reflect.Type) Type {
+	nullable := false
+	for t.Kind() == reflect.Pointer {
+		nullable = true
+		t = t.Elem()
+	}
+	var typ Type
+	switch {
+	case t == timeType:
+		typ = Time
+	case t.Kind() == reflect.Bool:
+		typ = Boolean
+	case t.Kind() == reflect.String:
+		typ = String
+	case t.Kind() == reflect.Float32 || t.Kind() == reflect.Float64:
+		typ = Float
+	case t.Kind() >= reflect.Int && t.Kind() <= reflect.Uint64:
+		typ = Int
+	case t.Kind() == reflect.Slice && t.Elem().Kind() != reflect.Uint8:
+		elem := typeOfGo(t.Elem())
+		if elem == nil {
+			return nil
+		}
+		// 切片可能为 nil，按可空处理
+		return ListOf(elem)
+	default:
+		return nil
+	}
+	if nullable {
+		return typ
+	}
+	return NonNullOf(typ)
+}
+
+func jsonName(sf reflect.StructField) (string, bool) {
+	tag := sf.Tag.Get("json")
+	if tag == "-" {
+		return "", false
+	}
+	name, _, _ := strings.Cut(tag, ",")
+	if name == "" {
+		name = sf.Name
+	}
+	return name, true
+}
+
+// structFieldIndex 缓存结构体类型的 json 名称到字段索引
+var structFieldIndex sync.Map // reflect.Type -> map[string][]int
+
+func fieldIndex(t reflect.Type) map[string][]int {
+	if cached, ok := structFieldIndex.Load(t); ok {
+		return cached.(map[string][]int)
+	}
+	index := map[string][]int{}
+	for _, sf := range reflect.VisibleFields(t) {
+		if !sf.IsExported() || sf.Anonymous {
+			continue
+		}
+		if name, ok := jsonName(sf); ok {
+			if _, dup := index[name]; !dup {
+				index[name] = sf.Index
+			}
+		}
+	}
+	structFieldIndex.Store(t, index)
+	return index
+}
+
+// defaultResolve 读取 source 中 json 名称为 name 的字段
+func defaultResolve(source any, name string) any {
+	if m, ok := source.(map[string]any); ok {
+		return m[name]
+	}
+	rv := reflect.ValueOf(source)
+	for rv.Kind() == reflect.Pointer || rv.Kind() == reflect.Interface {
+		if rv.IsNil() {
+			return nil
+		}
+		rv = rv.Elem()
+	}
+	if rv.Kind() != reflect.Struct {
+		return nil
+	}
+	idx, ok := fieldIndex(rv.Type())[name]
+	if !ok {
+		return nil
+	}
+	fv, err := rv.FieldByIndexErr(idx)
+	if err != nil {
+		return nil
+	}
+	return fv.Interface()
+}
+
+// Schema 只读（query）GraphQL schema
+type Schema struct {
+	Query *Object
+	// MaxDepth 选择集最大嵌套深度
+	MaxDepth int
+	// MaxComplexity 查询复杂度上限（展开片段后逐个字段累加，带解析器的字段计 ResolverCost，其余计 1）；<= 0 表示不限制
+	MaxComplexity int
+	// ErrorPresenter 将解析器返回的错误转换为对外的错误信息；为空时使用 err.Error()
+	ErrorPresenter func(err error) string
+}
+
+const (
+	// DefaultMaxDepth 默认最大查询深度
+	DefaultMaxDepth = 10
+	// DefaultMaxComplexity 默认查询复杂度上限，约为 30 个带解析器的字段（每层一次后端查询）
+	DefaultMaxComplexity = 300
+	// ResolverCost 带 Resolve / Batch 解析器的字段的复杂度（会触发后端查询，别名重复选择同样计入）
+	ResolverCost = 10
+)
+
+// NewSchema 创建 schema
+func NewSchema(query *Object) *Schema {
+	return &Schema{Query: query, MaxDepth: DefaultMaxDepth, MaxComplexity: DefaultMaxComplexity}
+}
+
+func namedType(t Type) Type {
+	for {
+		switch tt := t.(type) {
+		case *NonNull:
+			t = tt.Of
+		case *List:
+			t = tt.Of
+		default:
+			return t
+		}
+	}
+}
+
+// types 按从 Query 出发的广度优先顺序返回所有具名类型
+func (s *Schema) types() (scalars []*Scalar, objects []*Object) {
+	seen := map[string]bool{}
+	queue := []*Object{s.Query}
+	seen[s.Query.Name] = true
+	visit := func(t Type) {
+		switch nt := namedType(t).(type) {
+		case *Object:
+			if !seen[nt.Name] {
+				seen[nt.Name] = true
+				queue = append(queue, nt)
+			}
+		case *Scalar:
+			if !seen[nt.Name] && !builtinScalars[nt.Name] {
+				seen[nt.Name] = true
+				scalars = append(scalars, nt)
+			}
+		}
+	}
+	for len(queue) > 0 {
+		obj := queue[0]
+		queue = queue[1:]
+		objects = append(objects, obj)
+		for _, f := range obj.fields {
+			visit(f.Type)
+			for _, a := range f.Args {
+				visit(a.Type)
+			}
+		}
+	}
+	sort.Slice(scalars, func(i, j int) bool { return scalars[i].Name < scalars[j].Name })
+	return scalars, objects
+}
+
+// SDL 输出 schema 的 SDL 描述
+func (s *Schema) SDL() string {
+	var b strings.Builder
+	scalars, objects := s.types()
+	if s.Query.Name != "Query" {
+		_, _ = fmt.Fprintf(&b, "schema {\n  query: %s\n}\n\n", s.Query.Name)
+	}
+	for _, sc := range scalars {
+		writeDescription(&b, "", sc.Description)
+		_, _ = fmt.Fprintf(&b, "scalar %s\n\n", sc.Name)
+	}
+	for i, obj := range objects {
+		writeDescription(&b, "", obj.Description)
+		_, _ = fmt.Fprintf(&b, "type %s {\n", obj.Name)
+		for _, f := range obj.fields {
+			writeDescription(&b, "  ", f.Description)
+			_, _ = b.WriteString("  " + f.Name)
+			if len(f.Args) > 0 {
+				parts := make([]string, 0, len(f.Args))
+				for _, a := range f.Args {
+					part := a.Name + ": " + a.Type.String()
+					if a.Default != nil {
+						part += " = " + formatValue(a.Default)
+					}
+					parts = append(parts, part)
+				}
+				_, _ = b.WriteString("(" + strings.Join(parts, ", ") + ")")
+			}
+			_, _ = b.WriteString(": " + f.Type.String() + "\n")
+		}
+		_, _ = b.WriteString("}\n")
+		if i < len(objects)-1 {
+			_, _ = b.WriteString("\n")
+		}
+	}
+	return b.String()
+}
+
+func writeDescription(b *strings.Builder, indent, desc string) {
+	if desc != "" {
+		_, _ = b.WriteString(indent + strconv.Quote(desc) + "\n")
+	}
+}
+
+func formatValue(v any) string {
+	switch x := v.(type) {
+	case string:
+		return strconv.Quote(x)
+	case time.Time:
+		return strconv.Quote(x.Format(time.RFC3339))
+	}
+	return fmt.Sprint(v)
+}
@@ -31,6 +31,17 @@ type Sink interface {
 	WriteLogEvent(event *LogEvent)
 }
 
+// MultiSink 将日志事件依次写入多个 sink（sink 不得修改事件）
+type MultiSink []Sink
+
+func (m MultiSink) WriteLogEvent(event *LogEvent) {
+	for _, sink := range m {
+		if sink != nil {
+			sink.WriteLogEvent(event)
+		}
+	}
+}
+
 type LogEvent struct {
 	Time       time.Time
 	Level      string
@@ -0,0 +1,425 @@
+// Package openapi 根据 gin 路由表与路由注解生成 OpenAPI 3.0 文档。
+//
+// 路径、方法与路径参数直接取自运行时注册的路由，新增或删除路由后文档自动同步；
+// 摘要、请求体、查询参数与响应数据类型通过 Describe 注解补充，类型结构由反射推导。
+package openapi
+
+import (
+	"fmt"
+	"reflect"
+	"regexp"
+	"runtime"
+	"sort"
+	"strings"
+
+	"github.com/gin-gonic/gin"
+)
+
+// Version 生成文档使用的 OpenAPI 版本
+const Version = "3.0.3"
+
+// Document OpenAPI 文档
+type Document struct {
+	OpenAPI    string                           `json:"openapi"`
+	Info       Info                             `json:"info"`
+	Tags       []Tag                            `json:"tags,omitempty"`
+	Paths      map[string]map[string]*Operation `json:"paths"`
+	Components Components                       `json:"components"`
+}
+
+// Info 文档基本信息
+type Info struct {
+	Title       string `json:"title"`
+	Version     string `json:"version"`
+	Description string `json:"description,omitempty"`
+}
+
+// Tag 接口分组
+type Tag struct {
+	Name string `json:"name"`
+}
+
+// Components 可复用的 schema 与认证方式
+type Components struct {
+	Schemas         map[string]*Schema         `json:"schemas,omitempty"`
+	SecuritySchemes map[string]*SecurityScheme `json:"securitySchemes,omitempty"`
+}
+
+// SecurityScheme 认证方式
+type SecurityScheme struct {
+	Type         string `json:"type"`
+	Scheme       string `json:"scheme,omitempty"`
+	BearerFormat string `json:"bearerFormat,omitempty"`
+	In           string `json:"in,omitempty"`
+	Name         string `json:"name,omitempty"`
+	Description  string `json:"description,omitempty"`
+}
+
+// Operation 单个接口
+type Operation struct {
+	OperationID string                `json:"operationId"`
+	Summary     string                `json:"summary,omitempty"`
+	Description string                `json:"description,omitempty"`
+	Tags        []string              `json:"tags,omitempty"`
+	Parameters  []*Parameter          `json:"parameters,omitempty"`
+	RequestBody *RequestBody          `json:"requestBody,omitempty"`
+	Responses   map[string]*Response  `json:"responses"`
+	Security    []map[string][]string `json:"security,omitempty"`
+}
+
+// Parameter 路径 / 查询参数
+type Parameter struct {
+	Name        string  `json:"name"`
+	In          string  `json:"in"`
+	Required    bool    `json:"required,omitempty"`
+	Description string  `json:"description,omitempty"`
+	Schema      *Schema `json:"schema"`
+}
+
+// RequestBody 请求体
+type RequestBody struct {
+	Required bool                 `json:"required,omitempty"`
+	Content  map[string]MediaType `json:"content"`
+}
+
+// Response 响应
+type Response struct {
+	Description string               `json:"description"`
+	Content     map[string]MediaType `json:"content,omitempty"`
+}
+
+// MediaType 某种内容类型的 schema
+type MediaType struct {
+	Schema *Schema `json:"schema"`
+}
+
+// Schema JSON Schema（OpenAPI 3.0 子集）
+type Schema struct {
+	Ref                  string             `json:"$ref,omitempty"`
+	Type                 string             `json:"type,omitempty"`
+	Format               string             `json:"format,omitempty"`
+	Description          string             `json:"description,omitempty"`
+	Nullable             bool               `json:"nullable,omitempty"`
+	Enum                 []any              `json:"enum,omitempty"`
+	Properties           map[string]*Schema `json:"properties,omitempty"`
+	Required             []string           `json:"required,omitempty"`
+	Items                *Schema            `json:"items,omitempty"`
+	AdditionalProperties *Schema            `json:"additionalProperties,omitempty"`
+}
+
+// Route 路由注解
+type Route struct {
+	Summary     string
+	Description string
+	// Tags 为空时按路径推导
+	Tags []string
+	// Request 请求体：Go 值（反射推导 schema）或 *Schema；nil 表示无请求体
+	Request any
+	// Query 查询参数结构体（按 form 标签推导）
+	Query any
+	// Response 成功响应的数据：Go 值或 *Schema；nil 表示未声明
+	Response any
+	// Stream 响应可能为 SSE 流
+	Stream bool
+	// Raw 响应不使用 {code, message, data} 包装（即使路径匹配 EnvelopeFor）
+	Raw bool
+}
+
+// SecurityRule 按路径前缀指定认证方式（最长前缀优先），Schemes 为空表示无需认证
+type SecurityRule struct {
+	Prefix  string
+	Schemes []string
+}
+
+// Generator 文档生成器
+type Generator struct {
+	info            Info
+	routes          map[string]Route
+	handlers        map[string]Route
+	security        []SecurityRule
+	securitySchemes map[string]*SecurityScheme
+	// envelope 为 true 的路径前缀使用 {code, message, data} 统一响应包装
+	envelopePrefixes []string
+	skip             map[string]struct{}
+}
+
+// NewGenerator 创建文档生成器
+func NewGenerator(info Info) *Generator {
+	return &Generator{
+		info:            info,
+		routes:          make(map[string]Route),
+		handlers:        make(map[string]Route),
+		securitySchemes: make(map[string]*SecurityScheme),
+		skip:            make(map[string]struct{}),
+	}
+}
+
+// AddSecurityScheme 注册认证方式
+func (g *Generator) AddSecurityScheme(name string, scheme *SecurityScheme) {
+	g.securitySchemes[name] = scheme
+}
+
+// SecurityFor 指定路径前缀下接口的认证方式
+func (g *Generator) SecurityFor(prefix string, schemes ...string) {
+	g.security = append(g.security, SecurityRule{Prefix: prefix, Schemes: schemes})
+}
+
+// EnvelopeFor 指定路径前缀下接口的成功响应使用 {code, message, data} 包装
+func (g *Generator) EnvelopeFor(prefix string) {
+	g.envelopePrefixes = append(g.envelopePrefixes, prefix)
+}
+
+// Skip 不在文档中列出的路径（gin 路径格式）
+func (g *Generator) Skip(paths ...string) {
+	for _, p := range paths {
+		g.skip[p] = struct{}{}
+	}
+}
+
+// Describe 为路由补充注解；path 使用 gin 格式（如 /api/v1/admin/accounts/:id）
+func (g *Generator) Describe(method, path string, route Route) {
+	g.routes[strings.ToUpper(method)+" "+path] = route
+}
+
+// DescribeHandler 为处理函数补充注解，所有挂载该处理函数的路由共用；
+// handler 传方法表达式（如 (*admin.AccountHandler).Create），重命名或删除方法时编译即失败
+func (g *Generator) DescribeHandler(handler any, route Route) {
+	g.handlers[handlerName(handler)] = route
+}
+
+// Build 根据路由表生成文档
+func (g *Generator) Build(routes gin.RoutesInfo) *Document {
+	doc := &Document{
+		OpenAPI: Version,
+		Info:    g.info,
+		Paths:   make(map[string]map[string]*Operation),
+		Components: Components{
+			Schemas:         make(map[string]*Schema),
+			SecuritySchemes: g.securitySchemes,
+		},
+	}
+	reflector := newSchemaReflector(doc.Components.Schemas)
+	usedIDs := make(map[string]int)
+	tags := make(map[string]struct{})
+
+	sorted := make(gin.RoutesInfo, len(routes))
+	copy(sorted, routes)
+	sort.SliceStable(sorted, func(i, j int) bool {
+		if sorted[i].Path != sorted[j].Path {
+			return sorted[i].Path < sorted[j].Path
+		}
+		return sorted[i].Method < sorted[j].Method
+	})
+
+	for _, rt := range sorted {
+		if _, ok := g.skip[rt.Path]; ok {
+			continue
+		}
+		method := strings.ToLower(rt.Method)
+		path, params := convertPath(rt.Path)
+		route := g.routes[rt.Method+" "+rt.Path]
+
+		op := &Operation{
+			OperationID: uniqueOperationID(usedIDs, operationIDFor(rt)),
+			Summary:     route.Summary,
+			Description: route.Description,
+			Tags:        route.Tags,
+			Parameters:  params,
+			Responses:   make(map[string]*Response),
+		}
+		if op.Summary == "" {
+			op.Summary = summaryFor(rt.Handler)
+		}
+		if len(op.Tags) == 0 {
+			op.Tags = []string{tagFor(rt.Path)}
+		}
+		for _, tag := range op.Tags {
+			tags[tag] = struct{}{}
+		}
+		if route.Query != nil {
+			op.Parameters = append(op.Parameters, reflector.queryParameters(route.Query)...)
+		}
+		if route.Request != nil {
+			op.RequestBody = &RequestBody{
+				Required: true,
+				Content:  map[string]MediaType{"application/json": {Schema: reflector.schemaOf(route.Request)}},
+			}
+		}
+		if schemes, ok := g.securityFor(rt.Path); ok && len(schemes) > 0 {
+			for _, name := range schemes {
+				op.Security = append(op.Security, map[string][]string{name: {}})
+			}
+		}
+
+		var data *Schema
+		if route.Response != nil {
+			data = reflector.schemaOf(route.Response)
+		}
+		if g.usesEnvelope(rt.Path) && !route.Raw {
+			op.Responses["200"] = &Response{
+				Description: "OK",
+				Content:     map[string]MediaType{"application/json": {Schema: envelopeSchema(data)}},
+			}
+			op.Responses["default"] = &Response{
+				Description: "Error",
+				Content:     map[string]MediaType{"application/json": {Schema: envelopeSchema(nil)}},
+			}
+		} else {
+			resp := &Response{Description: "OK"}
+			if data != nil {
+				resp.Content = map[string]MediaType{"application/json": {Schema: data}}
+			}
+			if route.Stream {
+				if resp.Content == nil {
+					resp.Content = make(map[string]MediaType)
+				}
+				resp.Content["text/event-stream"] = MediaType{Schema: &Schema{Type: "string", Description: "Server-Sent Events (stream=true)"}}
+			}
+			op.Responses["200"] = resp
+		}
+
+		if doc.Paths[path] == nil {
+			doc.Paths[path] = make(map[string]*Operation)
+		}
+		doc.Paths[path][method] = op
+	}
+
+	for tag := range tags {
+		doc.Tags = append(doc.Tags, Tag{Name: tag})
+	}
+	sort.Slice(doc.Tags, func(i, j int) bool { return doc.Tags[i].Name < doc.Tags[j].Name })
+	return doc
+}
+
+func (g *Generator) securityFor(path string) ([]string, bool) {
+	best := -1
+	var schemes []string
+	for _, rule := range g.security {
+		if hasPathPrefix(path, rule.Prefix) && len(rule.Prefix) > best {
+			best = len(rule.Prefix)
+			schemes = rule.Schemes
+		}
+	}
+	return schemes, best >= 0
+}
+
+func (g *Generator) usesEnvelope(path string) bool {
+	for _, prefix := range g.envelopePrefixes {
+		if hasPathPrefix(path, prefix) {
+			return true
+		}
+	}
+	return false
+}
+
+func hasPathPrefix(path, prefix string) bool {
+	return path == prefix || strings.HasPrefix(path, strings.TrimSuffix(prefix, "/")+"/")
+}
+
+func envelopeSchema(data *Schema) *Schema {
+	s := &Schema{
+		Type: "object",
+		Properties: map[string]*Schema{
+			"code":    {Type: "integer", Description: "0 表示成功"},
+			"message": {Type: "string"},
+			"reason":  {Type: "string"},
+		},
+		Required: []string{"code", "message"},
+	}
+	if data != nil {
+		s.Properties["data"] = data
+	}
+	return s
+}
+
+// convertPath 将 gin 路径（:id、*path）转换为 OpenAPI 路径模板并生成路径参数
+func convertPath(ginPath string) (string, []*Parameter) {
+	segments := strings.Split(ginPath, "/")
+	var params []*Parameter
+	for i, seg := range segments {
+		if len(seg) < 2 || (seg[0] != ':' && seg[0] != '*') {
+			continue
+		}
+		name := seg[1:]
+		segments[i] = "{" + name + "}"
+		params = append(params, &Parameter{Name: name, In: "path", Required: true, Schema: &Schema{Type: "string"}})
+	}
+	return strings.Join(segments, "/"), params
+}
+
+// tagFor 按路径推导分组：/api/v1/admin/accounts/... -> admin/accounts，/api/v1/user/... -> user，/v1/... -> v1
+func tagFor(path string) string {
+	segments := strings.Split(strings.Trim(path, "/"), "/")
+	if len(segments) >= 2 && segments[0] == "api" && segments[1] == "v1" {
+		segments = segments[2:]
+		if len(segments) >= 2 && segments[0] == "admin" {
+			return "admin/" + segments[1]
+		}
+	}
+	if len(segments) == 0 || segments[0] == "" {
+		return "default"
+	}
+	return segments[0]
+}
+
+// handlerName 与 gin 记录的处理函数名一致（方法值带 -fm 后缀，此处统一去除）
+func handlerName(handler any) string {
+	v := reflect.ValueOf(handler)
+	if v.Kind() != reflect.Func {
+		return ""
+	}
+	fn := runtime.FuncForPC(v.Pointer())
+	if fn == nil {
+		return ""
+	}
+	return strings.TrimSuffix(fn.Name(), "-fm")
+}
+
+var handlerNamePattern = regexp.MustCompile(`\.\(\*([A-Za-z0-9_]+)\)\.([A-Za-z0-9_]+)(-fm)?$`)
+
+// operationIDFor 由处理函数名推导 operationId（如 AccountShardHandler.List -> AccountShard_List），
+// 匿名函数则使用方法与路径
+func operationIDFor(rt gin.RouteInfo) string {
+	if m := handlerNamePattern.FindStringSubmatch(rt.Handler); m != nil {
+		return strings.TrimSuffix(m[1], "Handler") + "_" + m[2]
+	}
+	id := strings.ToLower(rt.Method) + "_" + strings.Trim(rt.Path, "/")
+	return strings.NewReplacer("/", "_", ":", "", "*", "", "-", "_", ".", "_").Replace(id)
+}
+
+func uniqueOperationID(used map[string]int, id string) string {
+	used[id]++
+	if n := used[id]; n > 1 {
+		return fmt.Sprintf("%s_%d", id, n)
+	}
+	return id
+}
+
+// summaryFor 由处理函数名推导摘要（如 AccountShardHandler.GetByID -> Get by id）
+func summaryFor(handler string) string {
+	m := handlerNamePattern.FindStringSubmatch(handler)
+	if m == nil {
+		return ""
+	}
+	return humanize(m[2])
+}
+
+func humanize(name string) string {
+	var b strings.Builder
+	runes := []rune(name)
+	for i, r := range runes {
+		upper := r >= 'A' && r <= 'Z'
+		if i > 0 && upper {
+			prevLower := runes[i-1] >= 'a' && runes[i-1] <= 'z'
+			nextLower := i+1 < len(runes) && runes[i+1] >= 'a' && runes[i+1] <= 'z'
+			if prevLower || nextLower {
+				_, _ = b.WriteRune(' ')
+			}
+		}
+		if i > 0 && upper {
+			r += 'a' - 'A'
+		}
+		_, _ = b.WriteRune(r)
+	}
+	return b.String()
+}
@@ -0,0 +1,166 @@
+//go:build unit
+
+package openapi
+
+import (
+	"encoding/json"
+	"net/http"
+	"testing"
+	"time"
+
+	"github.com/gin-gonic/gin"
+	"github.com/stretchr/testify/require"
+)
+
+type widgetHandler struct{}
+
+func (h *widgetHandler) Create(c *gin.Context) {}
+func (h *widgetHandler) GetByID(c *gin.Context) {}
+func (h *widgetHandler) Stream(c *gin.Context) {}
+
+type createWidgetRequest struct {
+	Name     string            `json:"name" binding:"required"`
+	Size     *int              `json:"size"`
+	Labels   map[string]string `json:"labels,omitempty"`
+	Internal string            `json:"-"`
+}
+
+type widget struct {
+	ID        int64     `json:"id"`
+	Parent    *widget   `json:"parent,omitempty"`
+	CreatedAt time.Time `json:"created_at"`
+}
+
+type listWidgetsQuery struct {
+	Page   int    `form:"page"`
+	Status string `form:"status" binding:"required"`
+}
+
+func newTestEngine() *gin.Engine {
+	gin.SetMode(gin.TestMode)
+	r := gin.New()
+	h := &widgetHandler{}
+	r.GET("/health", func(c *gin.Context) {})
+	r.GET("/openapi.json", func(c *gin.Context) {})
+	r.POST("/api/v1/admin/widgets", h.Create)
+	r.GET("/api/v1/admin/widgets", func(c *gin.Context) {})
+	r.GET("/api/v1/admin/widgets/:id", h.GetByID)
+	r.GET("/api/v1/auth/public", func(c *gin.Context) {})
+	r.POST("/v1/widgets/*action", h.Stream)
+	r.POST("/v2/widgets/*action", h.Stream)
+	return r
+}
+
+func newTestGenerator() *Generator {
+	g := NewGenerator(Info{Title: "test", Version: "1"})
+	g.AddSecurityScheme("bearerAuth", &SecurityScheme{Type: "http", Scheme: "bearer"})
+	g.AddSecurityScheme("apiKey", &SecurityScheme{Type: "apiKey", In: "header", Name: "x-api-key"})
+	g.SecurityFor("/api/v1", "bearerAuth")
+	g.SecurityFor("/api/v1/auth")
+	g.SecurityFor("/v1", "apiKey")
+	g.EnvelopeFor("/api/v1")
+	g.Skip("/openapi.json")
+	g.DescribeHandler((*widgetHandler).Create, Route{Request: createWidgetRequest{}, Response: widget{}})
+	g.DescribeHandler((*widgetHandler).Stream, Route{Summary: "Run action", Stream: true, Response: &Schema{Type: "object"}})
+	g.Describe(http.MethodGet, "/api/v1/admin/widgets", Route{Summary: "List widgets", Query: listWidgetsQuery{}, Response: []widget{}})
+	g.Describe(http.MethodGet, "/api/v1/auth/public", Route{Response: widget{}, Raw: true})
+	return g
+}
+
+func TestBuild_PathsParametersAndSkip(t *testing.T) {
+	doc := newTestGenerator().Build(newTestEngine().Routes())
+
+	require.Equal(t, Version, doc.OpenAPI)
+	require.NotContains(t, doc.Paths, "/openapi.json")
+	require.Contains(t, doc.Paths, "/health")
+
+	get := doc.Paths["/api/v1/admin/widgets/{id}"]["get"]
+	require.NotNil(t, get)
+	require.Equal(t, "widget_GetByID", get.OperationID)
+	require.Equal(t, "Get by id", get.Summary)
+	require.Equal(t, []string{"admin/widgets"}, get.Tags)
+	require.Len(t, get.Parameters, 1)
+	require.Equal(t, "id", get.Parameters[0].Name)
+	require.Equal(t, "path", get.Parameters[0].In)
+	require.True(t, get.Parameters[0].Required)
+
+	action := doc.Paths["/v1/widgets/{action}"]["post"]
+	require.NotNil(t, action)
+	require.Equal(t, "action", action.Parameters[0].Name)
+
+	list := doc.Paths["/api/v1/admin/widgets"]["get"]
+	require.Equal(t, "List widgets", list.Summary)
+	require.Len(t, list.Parameters, 2)
+	require.Equal(t, "page", list.Parameters[0].Name)
+	require.False(t, list.Parameters[0].Required)
+	require.Equal(t, "status", list.Parameters[1].Name)
+	require.True(t, list.Parameters[1].Required)
+}
+
+func TestBuild_SecurityLongestPrefixWins(t *testing.T) {
+	doc := newTestGenerator().Build(newTestEngine().Routes())
+
+	require.Equal(t, []map[string][]string{{"bearerAuth": {}}}, doc.Paths["/api/v1/admin/widgets"]["post"].Security)
+	require.Empty(t, doc.Paths["/api/v1/auth/public"]["get"].Security)
+	require.Equal(t, []map[string][]string{{"apiKey": {}}}, doc.Paths["/v1/widgets/{action}"]["post"].Security)
+	// /v2 不匹配 /v1 前缀
+	require.Empty(t, doc.Paths["/v2/widgets/{action}"]["post"].Security)
+	require.Empty(t, doc.Paths["/health"]["get"].Security)
+}
+
+func TestBuild_RequestAndEnvelopeSchemas(t *testing.T) {
+	doc := newTestGenerator().Build(newTestEngine().Routes())
+
+	create := doc.Paths["/api/v1/admin/widgets"]["post"]
+	require.NotNil(t, create.RequestBody)
+	reqRef := create.RequestBody.Content["application/json"].Schema.Ref
+	require.Equal(t, "#/components/schemas/openapi.createWidgetRequest", reqRef)
+
+	req := doc.Components.Schemas["openapi.createWidgetRequest"]
+	require.NotNil(t, req)
+	require.Equal(t, []string{"name"}, req.Required)
+	require.NotContains(t, req.Properties, "-")
+	require.NotContains(t, req.Properties, "Internal")
+	require.True(t, req.Properties["size"].Nullable)
+	require.Equal(t, "string", req.Properties["labels"].AdditionalProperties.Type)
+
+	ok := create.Responses["200"].Content["application/json"].Schema
+	require.Contains(t, ok.Properties, "code")
+	require.Equal(t, "#/components/schemas/openapi.widget", ok.Properties["data"].Ref)
+	require.Contains(t, create.Responses, "default")
+
+	// 自引用类型只注册一次
+	w := doc.Components.Schemas["openapi.widget"]
+	require.Equal(t, "#/components/schemas/openapi.widget", w.Properties["parent"].Ref)
+	require.Equal(t, "date-time", w.Properties["created_at"].Format)
+
+	action := doc.Paths["/v1/widgets/{action}"]["post"]
+	require.Equal(t, "Run action", action.Summary)
+	require.Contains(t, action.Responses["200"].Content, "application/json")
+	require.Contains(t, action.Responses["200"].Content, "text/event-stream")
+	require.NotContains(t, action.Responses["200"].Content["application/json"].Schema.Properties, "code")
+
+	// Raw 路由即使位于 /api/v1 下也不包装
+	public := doc.Paths["/api/v1/auth/public"]["get"]
+	require.Equal(t, "#/components/schemas/openapi.widget", public.Responses["200"].Content["application/json"].Schema.Ref)
+	require.NotContains(t, public.Responses, "default")
+}
+
+func TestBuild_OperationIDsUniqueAndSerializable(t *testing.T) {
+	doc := newTestGenerator().Build(newTestEngine().Routes())
+
+	seen := make(map[string]struct{})
+	for _, ops := range doc.Paths {
+		for _, op := range ops {
+			_, dup := seen[op.OperationID]
+			require.False(t, dup, "duplicate operationId %s", op.OperationID)
+			seen[op.OperationID] = struct{}{}
+		}
+	}
+	require.Contains(t, seen, "widget_Stream")
+	require.Contains(t, seen, "widget_Stream_2")
+	require.Contains(t, seen, "get_health")
+
+	_, err := json.Marshal(doc)
+	require.NoError(t, err)
+}
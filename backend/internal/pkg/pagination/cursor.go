@@ -0,0 +1,81 @@
+package pagination
+
+import (
+	"encoding/base64"
+	"encoding/json"
+	"errors"
+	"strings"
+)
+
+// ErrInvalidCursor 游标格式错误或与当前排序参数不匹配
+var ErrInvalidCursor = errors.New("invalid cursor")
+
+// Cursor 游标内容：上一页最后一条记录的排序字段值与 ID。
+// ID 作为第二排序键，保证排序字段存在重复值时翻页结果依然稳定。
+type Cursor struct {
+	SortBy    string `json:"s"`
+	SortOrder string `json:"o"`
+	Value     string `json:"v"`
+	ID        int64  `json:"id"`
+}
+
+// CursorParams 游标分页参数
+type CursorParams struct {
+	Cursor    string
+	Limit     int
+	SortBy    string
+	SortOrder string
+}
+
+// CursorResult 游标分页结果
+type CursorResult struct {
+	NextCursor string
+	HasMore    bool
+}
+
+// PageLimit 获取每页数量（默认 20，最大 100）
+func (p CursorParams) PageLimit() int {
+	if p.Limit < 1 {
+		return 20
+	}
+	if p.Limit > 100 {
+		return 100
+	}
+	return p.Limit
+}
+
+// Descending 是否倒序（默认倒序）
+func (p CursorParams) Descending() bool {
+	return !strings.EqualFold(p.SortOrder, "asc")
+}
+
+// NormalizedOrder 返回规范化后的排序方向（asc / desc）
+func (p CursorParams) NormalizedOrder() string {
+	if p.Descending() {
+		return "desc"
+	}
+	return "asc"
+}
+
+// EncodeCursor 将游标编码为不透明字符串
+func EncodeCursor(c Cursor) string {
+	raw, _ := json.Marshal(c)
+	return base64.RawURLEncoding.EncodeToString(raw)
+}
+
+// DecodeCursor 解码游标；空字符串表示第一页，返回 (nil, nil)
+func DecodeCursor(s string) (*Cursor, error) {
+	s = strings.TrimSpace(s)
+	if s == "" {
+		return nil, nil
+	}
+	raw, err := base64.RawURLEncoding.DecodeString(s)
+	if err != nil {
+		return nil, ErrInvalidCursor
+	}
+	var c Cursor
+	if err := json.Unmarshal(raw, &c); err != nil || c.SortBy == "" || c.ID <= 0 {
+		return nil, ErrInvalidCursor
+	}
+	return &c, nil
+}
@@ -0,0 +1,394 @@
+// Package parquet 提供一个最小化的 Parquet 文件写入器：扁平 schema、PLAIN 编码、不压缩。
+//
+// 仅用于导出报表等一次性写入场景，按行组（row group）分块写出，内存占用与行组大小成正比。
+package parquet
+
+import (
+	"bytes"
+	"encoding/binary"
+	"errors"
+	"fmt"
+	"io"
+	"math"
+	"time"
+)
+
+var magic = []byte("PAR1")
+
+// Type 列的逻辑类型
+type Type int
+
+const (
+	Int64 Type = iota
+	Double
+	String
+	Bool
+	// TimestampMillis 以 UTC 毫秒时间戳存储的 time.Time
+	TimestampMillis
+)
+
+// Parquet 物理类型 / 枚举值
+const (
+	physicalBoolean   int32 = 0
+	physicalInt64     int32 = 2
+	physicalDouble    int32 = 5
+	physicalByteArray int32 = 6
+
+	convertedUTF8            int32 = 0
+	convertedTimestampMillis int32 = 9
+
+	repetitionRequired int32 = 0
+	repetitionOptional int32 = 1
+
+	encodingPlain int32 = 0
+	encodingRLE   int32 = 3
+
+	codecUncompressed int32 = 0
+	pageTypeData      int32 = 0
+)
+
+// Column 列定义
+type Column struct {
+	Name string
+	Type Type
+	// Optional 允许写入 nil
+	Optional bool
+}
+
+type columnBuffer struct {
+	values  bytes.Buffer
+	bools   []bool
+	defined []bool
+	count   int
+}
+
+type columnChunkMeta struct {
+	offset    int64
+	size      int64
+	numValues int64
+}
+
+type rowGroupMeta struct {
+	columns []columnChunkMeta
+	size    int64
+	numRows int64
+}
+
+// Writer 按行写入 Parquet 文件，Close 时写出文件尾元数据
+type Writer struct {
+	w            io.Writer
+	offset       int64
+	columns      []Column
+	rowGroupSize int
+
+	buffers   []columnBuffer
+	rows      int
+	rowGroups []rowGroupMeta
+	totalRows int64
+	closed    bool
+}
+
+// NewWriter 创建写入器并写出文件头；rowGroupSize 为每个行组的行数
+func NewWriter(w io.Writer, columns []Column, rowGroupSize int) (*Writer, error) {
+	if len(columns) == 0 {
+		return nil, errors.New("parquet: no columns")
+	}
+	if rowGroupSize <= 0 {
+		rowGroupSize = 10000
+	}
+	pw := &Writer{
+		w:            w,
+		columns:      columns,
+		rowGroupSize: rowGroupSize,
+		buffers:      make([]columnBuffer, len(columns)),
+	}
+	if err := pw.write(magic); err != nil {
+		return nil, err
+	}
+	return pw, nil
+}
+
+func (w *Writer) write(p []byte) error {
+	n, err := w.w.Write(p)
+	w.offset += int64(n)
+	return err
+}
+
+// Write 写入一行；值类型需与列类型一致（int64/int/int32、float64、string、bool、time.Time），可选列允许 nil
+func (w *Writer) Write(row []any) error {
+	if w.closed {
+		return errors.New("parquet: writer closed")
+	}
+	if len(row) != len(w.columns) {
+		return fmt.Errorf("parquet: row has %d values, want %d", len(row), len(w.columns))
+	}
+	// 先校验整行，避免部分列写入后出错导致列长度不一致
+	for i, v := range row {
+		if err := checkValue(w.columns[i], v); err != nil {
+			return err
+		}
+	}
+	for i, v := range row {
+		w.appendValue(i, v)
+	}
+	w.rows++
+	if w.rows >= w.rowGroupSize {
+		return w.flushRowGroup()
+	}
+	return nil
+}
+
+func checkValue(col Column, v any) error {
+	if v == nil {
+		if !col.Optional {
+			return fmt.Errorf("parquet: column %s is required", col.Name)
+		}
+		return nil
+	}
+	ok := false
+	switch col.Type {
+	case Int64:
+		switch v.(type) {
+		case int64, int, int32:
+			ok = true
+		}
+	case Double:
+		_, ok = v.(float64)
+	case String:
+		_, ok = v.(string)
+	case Bool:
+		_, ok = v.(bool)
+	case TimestampMillis:
+		_, ok = v.(time.Time)
+	}
+	if !ok {
+		return fmt.Errorf("parquet: column %s: unexpected value type %T", col.Name, v)
+	}
+	return nil
+}
+
+func (w *Writer) appendValue(i int, v any) {
+	col := w.columns[i]
+	buf := &w.buffers[i]
+	buf.count++
+	if col.Optional {
+		buf.defined = append(buf.defined, v != nil)
+	}
+	if v == nil {
+		return
+	}
+
+	var tmp [8]byte
+	switch col.Type {
+	case Int64:
+		var n int64
+		switch x := v.(type) {
+		case int64:
+			n = x
+		case int:
+			n = int64(x)
+		case int32:
+			n = int64(x)
+		}
+		binary.LittleEndian.PutUint64(tmp[:], uint64(n))
+		buf.values.Write(tmp[:])
+	case Double:
+		binary.LittleEndian.PutUint64(tmp[:], math.Float64bits(v.(float64)))
+		buf.values.Write(tmp[:])
+	case String:
+		s := v.(string)
+		binary.LittleEndian.PutUint32(tmp[:4], uint32(len(s)))
+		buf.values.Write(tmp[:4])
+		buf.values.WriteString(s)
+	case Bool:
+		buf.bools = append(buf.bools, v.(bool))
+	case TimestampMillis:
+		binary.LittleEndian.PutUint64(tmp[:], uint64(v.(time.Time).UnixMilli()))
+		buf.values.Write(tmp[:])
+	}
+}
+
+// flushRowGroup 将缓冲的行写成一个行组（每列一个数据页）
+func (w *Writer) flushRowGroup() error {
+	if w.rows == 0 {
+		return nil
+	}
+	group := rowGroupMeta{numRows: int64(w.rows), columns: make([]columnChunkMeta, len(w.columns))}
+	for i, col := range w.columns {
+		buf := &w.buffers[i]
+
+		var page bytes.Buffer
+		if col.Optional {
+			levels := encodeDefinitionLevels(buf.defined)
+			var lenBuf [4]byte
+			binary.LittleEndian.PutUint32(lenBuf[:], uint32(len(levels)))
+			page.Write(lenBuf[:])
+			page.Write(levels)
+		}
+		if col.Type == Bool {
+			page.Write(packBools(buf.bools))
+		} else {
+			page.Write(buf.values.Bytes())
+		}
+
+		header := encodePageHeader(page.Len(), buf.count)
+		offset := w.offset
+		if err := w.write(header); err != nil {
+			return err
+		}
+		if err := w.write(page.Bytes()); err != nil {
+			return err
+		}
+		size := int64(len(header) + page.Len())
+		group.columns[i] = columnChunkMeta{offset: offset, size: size, numValues: int64(buf.count)}
+		group.size += size
+
+		*buf = columnBuffer{}
+	}
+	w.rowGroups = append(w.rowGroups, group)
+	w.totalRows += int64(w.rows)
+	w.rows = 0
+	return nil
+}
+
+// Close 写出剩余行与文件尾；不会关闭底层 io.Writer
+func (w *Writer) Close() error {
+	if w.closed {
+		return nil
+	}
+	if err := w.flushRowGroup(); err != nil {
+		return err
+	}
+	w.closed = true
+
+	footer := w.encodeFileMetaData()
+	if err := w.write(footer); err != nil {
+		return err
+	}
+	var lenBuf [4]byte
+	binary.LittleEndian.PutUint32(lenBuf[:], uint32(len(footer)))
+	if err := w.write(lenBuf[:]); err != nil {
+		return err
+	}
+	return w.write(magic)
+}
+
+// encodeDefinitionLevels 以 RLE/bit-packing 混合编码（位宽 1，只使用 RLE 段）写出定义级别
+func encodeDefinitionLevels(defined []bool) []byte {
+	var out bytes.Buffer
+	var tmp [binary.MaxVarintLen64]byte
+	for i := 0; i < len(defined); {
+		j := i
+		for j < len(defined) && defined[j] == defined[i] {
+			j++
+		}
+		n := binary.PutUvarint(tmp[:], uint64(j-i)<<1)
+		out.Write(tmp[:n])
+		if defined[i] {
+			out.WriteByte(1)
+		} else {
+			out.WriteByte(0)
+		}
+		i = j
+	}
+	return out.Bytes()
+}
+
+// packBools 布尔值 PLAIN 编码：按位打包，低位在前
+func packBools(values []bool) []byte {
+	out := make([]byte, (len(values)+7)/8)
+	for i, v := range values {
+		if v {
+			out[i/8] |= 1 << (uint(i) % 8)
+		}
+	}
+	return out
+}
+
+func encodePageHeader(pageSize, numValues int) []byte {
+	t := &thriftWriter{}
+	t.i32(1, pageTypeData)
+	t.i32(2, int32(pageSize))
+	t.i32(3, int32(pageSize))
+	t.beginStruct(5)
+	t.i32(1, int32(numValues))
+	t.i32(2, encodingPlain)
+	t.i32(3, encodingRLE)
+	t.i32(4, encodingRLE)
+	t.endStruct()
+	return t.bytes()
+}
+
+func (c Column) physicalType() int32 {
+	switch c.Type {
+	case Double:
+		return physicalDouble
+	case String:
+		return physicalByteArray
+	case Bool:
+		return physicalBoolean
+	default:
+		return physicalInt64
+	}
+}
+
+func (w *Writer) encodeFileMetaData() []byte {
+	t := &thriftWriter{}
+	t.i32(1, 1)
+
+	t.listHeader(2, thriftStruct, len(w.columns)+1)
+	t.beginStruct(0)
+	t.binary(4, "schema")
+	t.i32(5, int32(len(w.columns)))
+	t.endStruct()
+	for _, col := range w.columns {
+		t.beginStruct(0)
+		t.i32(1, col.physicalType())
+		repetition := repetitionRequired
+		if col.Optional {
+			repetition = repetitionOptional
+		}
+		t.i32(3, repetition)
+		t.binary(4, col.Name)
+		switch col.Type {
+		case String:
+			t.i32(6, convertedUTF8)
+		case TimestampMillis:
+			t.i32(6, convertedTimestampMillis)
+		}
+		t.endStruct()
+	}
+
+	t.i64(3, w.totalRows)
+
+	t.listHeader(4, thriftStruct, len(w.rowGroups))
+	for _, group := range w.rowGroups {
+		t.beginStruct(0)
+		t.listHeader(1, thriftStruct, len(group.columns))
+		for i, chunk := range group.columns {
+			col := w.columns[i]
+			t.beginStruct(0)
+			t.i64(2, chunk.offset)
+			t.beginStruct(3)
+			t.i32(1, col.physicalType())
+			t.listHeader(2, thriftI32, 2)
+			t.zigzag32(encodingPlain)
+			t.zigzag32(encodingRLE)
+			t.listHeader(3, thriftBinary, 1)
+			t.rawString(col.Name)
+			t.i32(4, codecUncompressed)
+			t.i64(5, chunk.numValues)
+			t.i64(6, chunk.size)
+			t.i64(7, chunk.size)
+			t.i64(9, chunk.offset)
+			t.endStruct()
+			t.endStruct()
+		}
+		t.i64(2, group.size)
+		t.i64(3, group.numRows)
+		t.endStruct()
+	}
+
+	t.binary(6, "sub2api")
+	return t.bytes()
+}
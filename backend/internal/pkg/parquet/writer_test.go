@@ -0,0 +1,67 @@
+//go:build unit
+
+package parquet
+
+import (
+	"bytes"
+	"encoding/binary"
+	"testing"
+	"time"
+
+	"github.com/stretchr/testify/require"
+)
+
+func TestWriter_FileLayout(t *testing.T) {
+	var buf bytes.Buffer
+	w, err := NewWriter(&buf, []Column{
+		{Name: "id", Type: Int64},
+		{Name: "name", Type: String, Optional: true},
+		{Name: "cost", Type: Double},
+		{Name: "stream", Type: Bool},
+		{Name: "created_at", Type: TimestampMillis},
+	}, 2)
+	require.NoError(t, err)
+
+	now := time.Date(2026, 1, 2, 3, 4, 5, 0, time.UTC)
+	for i := 0; i < 5; i++ {
+		var name any
+		if i%2 == 0 {
+			name = "key"
+		}
+		require.NoError(t, w.Write([]any{int64(i), name, 1.5, i%2 == 0, now}))
+	}
+	require.NoError(t, w.Close())
+	require.Len(t, w.rowGroups, 3)
+	require.Equal(t, int64(5), w.totalRows)
+
+	data := buf.Bytes()
+	require.Equal(t, "PAR1", string(data[:4]))
+	require.Equal(t, "PAR1", string(data[len(data)-4:]))
+	footerLen := int(binary.LittleEndian.Uint32(data[len(data)-8 : len(data)-4]))
+	require.Greater(t, footerLen, 0)
+	require.Less(t, footerLen, len(data)-12)
+	// 第一个列块紧跟在文件头之后
+	require.Equal(t, int64(4), w.rowGroups[0].columns[0].offset)
+}
+
+func TestWriter_RejectsInvalidRows(t *testing.T) {
+	w, err := NewWriter(&bytes.Buffer{}, []Column{{Name: "id", Type: Int64}, {Name: "name", Type: String}}, 10)
+	require.NoError(t, err)
+
+	require.Error(t, w.Write([]any{int64(1)}))
+	require.Error(t, w.Write([]any{int64(1), nil}))
+	require.Error(t, w.Write([]any{"1", "a"}))
+	require.NoError(t, w.Write([]any{1, "a"}))
+	require.Equal(t, 1, w.rows)
+	require.Equal(t, 1, w.buffers[0].count)
+}
+
+func TestEncodeDefinitionLevels(t *testing.T) {
+	// 3 个已定义 + 1 个空值 + 2 个已定义 => 三段 RLE
+	levels := encodeDefinitionLevels([]bool{true, true, true, false, true, true})
+	require.Equal(t, []byte{3 << 1, 1, 1 << 1, 0, 2 << 1, 1}, levels)
+}
+
+func TestPackBools(t *testing.T) {
+	require.Equal(t, []byte{0b00000101, 0b00000001}, packBools([]bool{true, false, true, false, false, false, false, false, true}))
+}
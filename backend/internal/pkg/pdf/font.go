@@ -0,0 +1,241 @@
+package pdf
+
+import (
+	"strconv"
+	"strings"
+	"unicode/utf16"
+	"unicode/utf8"
+)
+
+// font 把内容流中的字符串编码转换为 Unicode
+type font struct {
+	// codeLen 固定码长（字节），由 CMap 的 codespacerange 或字体类型决定
+	codeLen int
+	// toUnicode ToUnicode CMap 映射；为空时按 encoding 解码单字节编码
+	toUnicode map[uint32]string
+	encoding  *[256]rune
+	// composite Type0 字体没有 ToUnicode 时无法可靠还原文本
+	composite bool
+}
+
+var defaultFont = &font{codeLen: 1, encoding: &winAnsiEncoding}
+
+func newFont(doc *document, fd dict) *font {
+	f := &font{codeLen: 1}
+	if fd["Subtype"] == name("Type0") {
+		f.codeLen = 2
+		f.composite = true
+	}
+	if s, ok := doc.resolve(fd["ToUnicode"]).(*stream); ok {
+		if data, err := s.decode(); err == nil {
+			f.parseCMap(data)
+		}
+	}
+	if f.composite {
+		return f
+	}
+
+	enc := winAnsiEncoding
+	switch e := doc.resolve(fd["Encoding"]).(type) {
+	case name:
+		if e == "MacRomanEncoding" {
+			enc = macRomanEncoding()
+		}
+	case dict:
+		if doc.resolve(e["BaseEncoding"]) == name("MacRomanEncoding") {
+			enc = macRomanEncoding()
+		}
+		if diffs, ok := doc.resolve(e["Differences"]).(array); ok {
+			code := 0
+			for _, item := range diffs {
+				switch v := item.(type) {
+				case float64:
+					code = int(v)
+				case name:
+					if code >= 0 && code < 256 {
+						if r, ok := glyphRune(string(v)); ok {
+							enc[code] = r
+						}
+					}
+					code++
+				}
+			}
+		}
+	}
+	f.encoding = &enc
+	return f
+}
+
+// parseCMap 解析 ToUnicode CMap 中的 codespacerange、bfchar 与 bfrange
+func (f *font) parseCMap(data []byte) {
+	l := &lexer{data: data}
+	f.toUnicode = map[uint32]string{}
+	var operands []any
+	section := ""
+	for {
+		v, err := l.object()
+		if err != nil {
+			if l.eof() {
+				return
+			}
+			continue
+		}
+		kw, ok := v.(keyword)
+		if !ok {
+			if section != "" {
+				operands = append(operands, v)
+			}
+			continue
+		}
+		switch kw {
+		case "begincodespacerange", "beginbfchar", "beginbfrange":
+			section = string(kw)
+			operands = operands[:0]
+		case "endcodespacerange":
+			if len(operands) > 0 {
+				if lo, ok := operands[0].([]byte); ok && len(lo) > 0 && len(lo) <= 4 {
+					f.codeLen = len(lo)
+				}
+			}
+			section = ""
+		case "endbfchar":
+			for i := 0; i+1 < len(operands); i += 2 {
+				src, ok1 := operands[i].([]byte)
+				dst, ok2 := operands[i+1].([]byte)
+				if ok1 && ok2 {
+					f.toUnicode[codeOf(src)] = utf16BE(dst)
+				}
+			}
+			section = ""
+		case "endbfrange":
+			for i := 0; i+2 < len(operands); i += 3 {
+				lo, ok1 := operands[i].([]byte)
+				hi, ok2 := operands[i+1].([]byte)
+				if !ok1 || !ok2 {
+					continue
+				}
+				start, end := codeOf(lo), codeOf(hi)
+				if end < start || end-start > 0xffff {
+					continue
+				}
+				switch dst := operands[i+2].(type) {
+				case []byte:
+					base := []rune(utf16BE(dst))
+					if len(base) == 0 {
+						continue
+					}
+					for c := start; c <= end; c++ {
+						r := append([]rune(nil), base...)
+						r[len(r)-1] += rune(c - start)
+						f.toUnicode[c] = string(r)
+					}
+				case array:
+					for j, item := range dst {
+						if b, ok := item.([]byte); ok && start+uint32(j) <= end {
+							f.toUnicode[start+uint32(j)] = utf16BE(b)
+						}
+					}
+				}
+			}
+			section = ""
+		}
+	}
+}
+
+func (f *font) decode(s []byte) string {
+	var b strings.Builder
+	if len(f.toUnicode) > 0 {
+		n := max(f.codeLen, 1)
+		for i := 0; i+n <= len(s); i += n {
+			if u, ok := f.toUnicode[codeOf(s[i:i+n])]; ok {
+				b.WriteString(u)
+			}
+		}
+		return b.String()
+	}
+	if f.composite || f.encoding == nil {
+		return ""
+	}
+	for _, c := range s {
+		if r := f.encoding[c]; r != 0 {
+			b.WriteRune(r)
+		}
+	}
+	return b.String()
+}
+
+func codeOf(b []byte) uint32 {
+	var c uint32
+	for _, x := range b {
+		c = c<<8 | uint32(x)
+	}
+	return c
+}
+
+func utf16BE(b []byte) string {
+	if len(b)%2 == 1 {
+		b = append(b, 0)
+	}
+	units := make([]uint16, len(b)/2)
+	for i := range units {
+		units[i] = uint16(b[2*i])<<8 | uint16(b[2*i+1])
+	}
+	return string(utf16.Decode(units))
+}
+
+// glyphRune 解析 Differences 中的字形名：uniXXXX、单字符名与常见标点
+func glyphRune(g string) (rune, bool) {
+	if strings.HasPrefix(g, "uni") && len(g) == 7 {
+		if v, err := strconv.ParseUint(g[3:], 16, 32); err == nil {
+			return rune(v), true
+		}
+	}
+	if utf8.RuneCountInString(g) == 1 {
+		r, _ := utf8.DecodeRuneInString(g)
+		return r, true
+	}
+	r, ok := glyphNames[g]
+	return r, ok
+}
+
+var glyphNames = map[string]rune{
+	"space": ' ', "exclam": '!', "quotedbl": '"', "numbersign": '#', "dollar": '$', "percent": '%',
+	"ampersand": '&', "quotesingle": '\'', "parenleft": '(', "parenright": ')', "asterisk": '*',
+	"plus": '+', "comma": ',', "hyphen": '-', "period": '.', "slash": '/', "colon": ':',
+	"semicolon": ';', "less": '<', "equal": '=', "greater": '>', "question": '?', "at": '@',
+	"bracketleft": '[', "backslash": '\\', "bracketright": ']', "underscore": '_', "braceleft": '{',
+	"bar": '|', "braceright": '}', "asciitilde": '~', "zero": '0', "one": '1', "two": '2',
+	"three": '3', "four": '4', "five": '5', "six": '6', "seven": '7', "eight": '8', "nine": '9',
+	"quoteleft": '‘', "quoteright": '’', "quotedblleft": '“', "quotedblright": '”', "bullet": '•',
+	"endash": '–', "emdash": '—', "ellipsis": '…', "fi": 'ﬁ', "fl": 'ﬂ', "degree": '°',
+	"copyright": '©', "registered": '®', "trademark": '™', "Euro": '€', "section": '§',
+}
+
+// winAnsiEncoding Latin-1 加上 0x80-0x9F 区间的 Windows-1252 字符
+var winAnsiEncoding = func() [256]rune {
+	var t [256]rune
+	for i := 0x20; i < 256; i++ {
+		t[i] = rune(i)
+	}
+	t['\t'], t['\n'], t['\r'] = ' ', '\n', '\n'
+	t[0x7f] = 0
+	high := []rune("€\x00‚ƒ„…†‡ˆ‰Š‹Œ\x00Ž\x00\x00‘’“”•–—˜™š›œ\x00žŸ")
+	for i, r := range high {
+		t[0x80+i] = r
+	}
+	t[0xa0] = ' '
+	return t
+}()
+
+// macRomanEncoding Mac OS Roman 编码表（控制字符除外）
+func macRomanEncoding() [256]rune {
+	var t [256]rune
+	for i := 0x20; i < 0x7f; i++ {
+		t[i] = rune(i)
+	}
+	high := []rune("ÄÅÇÉÑÖÜáàâäãåçéèêëíìîïñóòôöõúùûü†°¢£§•¶ß®©™´¨≠ÆØ∞±≤≥¥µ∂∑∏π∫ªºΩæø¿¡¬√ƒ≈∆«»… ÀÃÕŒœ–—“”‘’÷◊ÿŸ⁄€‹›ﬁﬂ‡·‚„‰ÂÊÁËÈÍÎÏÌÓÔÒÚÛÙıˆ˜¯˘˙˚¸˝˛ˇ")
+	for i, r := range high {
+		t[0x80+i] = r
+	}
+	return t
+}
@@ -0,0 +1,429 @@
+package pdf
+
+import (
+	"bytes"
+	"compress/flate"
+	"compress/zlib"
+	"encoding/ascii85"
+	"encoding/hex"
+	"errors"
+	"fmt"
+	"io"
+	"regexp"
+	"strconv"
+)
+
+// maxStreamBytes 单个流解压后的上限，防止压缩炸弹
+const maxStreamBytes = 32 << 20
+
+var errStreamTooLarge = errors.New("pdf: stream exceeds size limit")
+
+// PDF 对象在内存中的表示：
+//
+//	null → nil，布尔 → bool，数字 → float64，字符串 → []byte，
+//	名称 → name，数组 → array，字典 → dict，流 → *stream，间接引用 → objRef，
+//	内容流中的操作符 → keyword
+type (
+	name    string
+	keyword string
+	array   []any
+	dict    map[name]any
+	objRef  struct{ num, gen int }
+)
+
+type stream struct {
+	dict dict
+	data []byte
+}
+
+type lexer struct {
+	data []byte
+	pos  int
+}
+
+func isSpace(c byte) bool {
+	switch c {
+	case 0, '\t', '\n', '\f', '\r', ' ':
+		return true
+	}
+	return false
+}
+
+func isDelim(c byte) bool {
+	switch c {
+	case '(', ')', '<', '>', '[', ']', '{', '}', '/', '%':
+		return true
+	}
+	return false
+}
+
+func (l *lexer) eof() bool { return l.pos >= len(l.data) }
+
+func (l *lexer) skipSpace() {
+	for l.pos < len(l.data) {
+		c := l.data[l.pos]
+		if c == '%' {
+			for l.pos < len(l.data) && l.data[l.pos] != '\n' && l.data[l.pos] != '\r' {
+				l.pos++
+			}
+			continue
+		}
+		if !isSpace(c) {
+			return
+		}
+		l.pos++
+	}
+}
+
+// regular 读取一段非空白、非分隔符字节
+func (l *lexer) regular() []byte {
+	start := l.pos
+	for l.pos < len(l.data) && !isSpace(l.data[l.pos]) && !isDelim(l.data[l.pos]) {
+		l.pos++
+	}
+	return l.data[start:l.pos]
+}
+
+// object 读取下一个对象；遇到数组、字典结束符时返回对应 keyword
+func (l *lexer) object() (any, error) {
+	l.skipSpace()
+	if l.eof() {
+		return nil, io.EOF
+	}
+	switch c := l.data[l.pos]; c {
+	case '/':
+		l.pos++
+		return l.name(), nil
+	case '(':
+		l.pos++
+		return l.literalString(), nil
+	case '<':
+		if l.pos+1 < len(l.data) && l.data[l.pos+1] == '<' {
+			l.pos += 2
+			return l.dict()
+		}
+		l.pos++
+		return l.hexString(), nil
+	case '>':
+		if l.pos+1 < len(l.data) && l.data[l.pos+1] == '>' {
+			l.pos += 2
+			return keyword(">>"), nil
+		}
+		l.pos++
+		return nil, fmt.Errorf("pdf: unexpected '>' at %d", l.pos)
+	case '[':
+		l.pos++
+		return l.array()
+	case ']':
+		l.pos++
+		return keyword("]"), nil
+	case '{', '}', ')':
+		l.pos++
+		return keyword(string(c)), nil
+	default:
+		tok := l.regular()
+		if len(tok) == 0 {
+			l.pos++
+			return nil, fmt.Errorf("pdf: unexpected byte %q at %d", c, l.pos-1)
+		}
+		if n, ok := parseNumber(tok); ok {
+			return l.maybeRef(n), nil
+		}
+		switch string(tok) {
+		case "true":
+			return true, nil
+		case "false":
+			return false, nil
+		case "null":
+			return nil, nil
+		}
+		return keyword(tok), nil
+	}
+}
+
+func parseNumber(tok []byte) (float64, bool) {
+	c := tok[0]
+	if !(c >= '0' && c <= '9') && c != '+' && c != '-' && c != '.' {
+		return 0, false
+	}
+	n, err := strconv.ParseFloat(string(tok), 64)
+	return n, err == nil
+}
+
+// maybeRef 尝试把 "n g R" 识别为间接引用
+func (l *lexer) maybeRef(n float64) any {
+	if n < 0 || n != float64(int(n)) {
+		return n
+	}
+	save := l.pos
+	l.skipSpace()
+	gen := l.regular()
+	if len(gen) > 0 {
+		if g, err := strconv.Atoi(string(gen)); err == nil && g >= 0 {
+			l.skipSpace()
+			if r := l.regular(); string(r) == "R" {
+				return objRef{num: int(n), gen: g}
+			}
+		}
+	}
+	l.pos = save
+	return n
+}
+
+func (l *lexer) name() name {
+	raw := l.regular()
+	if bytes.IndexByte(raw, '#') < 0 {
+		return name(raw)
+	}
+	out := make([]byte, 0, len(raw))
+	for i := 0; i < len(raw); i++ {
+		if raw[i] == '#' && i+2 < len(raw) {
+			if b, err := hex.DecodeString(string(raw[i+1 : i+3])); err == nil {
+				out = append(out, b[0])
+				i += 2
+				continue
+			}
+		}
+		out = append(out, raw[i])
+	}
+	return name(out)
+}
+
+func (l *lexer) literalString() []byte {
+	var out []byte
+	depth := 1
+	for l.pos < len(l.data) {
+		c := l.data[l.pos]
+		l.pos++
+		switch c {
+		case '(':
+			depth++
+		case ')':
+			depth--
+			if depth == 0 {
+				return out
+			}
+		case '\\':
+			if l.pos >= len(l.data) {
+				return out
+			}
+			e := l.data[l.pos]
+			l.pos++
+			switch e {
+			case 'n':
+				c = '\n'
+			case 'r':
+				c = '\r'
+			case 't':
+				c = '\t'
+			case 'b':
+				c = '\b'
+			case 'f':
+				c = '\f'
+			case '\r':
+				if l.pos < len(l.data) && l.data[l.pos] == '\n' {
+					l.pos++
+				}
+				continue
+			case '\n':
+				continue
+			default:
+				if e >= '0' && e <= '7' {
+					v := int(e - '0')
+					for i := 0; i < 2 && l.pos < len(l.data) && l.data[l.pos] >= '0' && l.data[l.pos] <= '7'; i++ {
+						v = v*8 + int(l.data[l.pos]-'0')
+						l.pos++
+					}
+					c = byte(v)
+				} else {
+					c = e
+				}
+			}
+		}
+		out = append(out, c)
+	}
+	return out
+}
+
+func (l *lexer) hexString() []byte {
+	digits := make([]byte, 0, 32)
+	for l.pos < len(l.data) {
+		c := l.data[l.pos]
+		l.pos++
+		if c == '>' {
+			break
+		}
+		if (c >= '0' && c <= '9') || (c >= 'a' && c <= 'f') || (c >= 'A' && c <= 'F') {
+			digits = append(digits, c)
+		}
+	}
+	if len(digits)%2 == 1 {
+		digits = append(digits, '0')
+	}
+	out := make([]byte, len(digits)/2)
+	_, _ = hex.Decode(out, digits)
+	return out
+}
+
+func (l *lexer) array() (array, error) {
+	var arr array
+	for {
+		v, err := l.object()
+		if err != nil {
+			return arr, err
+		}
+		if k, ok := v.(keyword); ok && k == "]" {
+			return arr, nil
+		}
+		arr = append(arr, v)
+	}
+}
+
+func (l *lexer) dict() (dict, error) {
+	d := dict{}
+	for {
+		k, err := l.object()
+		if err != nil {
+			return d, err
+		}
+		if kw, ok := k.(keyword); ok && kw == ">>" {
+			return d, nil
+		}
+		key, ok := k.(name)
+		if !ok {
+			return d, fmt.Errorf("pdf: dictionary key is %T at %d", k, l.pos)
+		}
+		v, err := l.object()
+		if err != nil {
+			return d, err
+		}
+		if kw, ok := v.(keyword); ok && kw == ">>" {
+			return d, nil
+		}
+		d[key] = v
+	}
+}
+
+var (
+	objHeader    = regexp.MustCompile(`(\d+)[ \t\r\n\f\x00]+(\d+)[ \t\r\n\f\x00]+obj\b`)
+	endstreamTag = []byte("endstream")
+)
+
+// indirectObject 解析 "obj" 关键字之后的对象体，流对象读取到 endstream 为止
+func (l *lexer) indirectObject() (any, error) {
+	v, err := l.object()
+	if err != nil {
+		return nil, err
+	}
+	d, ok := v.(dict)
+	if !ok {
+		return v, nil
+	}
+	save := l.pos
+	l.skipSpace()
+	if !bytes.HasPrefix(l.data[l.pos:], []byte("stream")) {
+		l.pos = save
+		return d, nil
+	}
+	l.pos += len("stream")
+	if l.pos < len(l.data) && l.data[l.pos] == '\r' {
+		l.pos++
+	}
+	if l.pos < len(l.data) && l.data[l.pos] == '\n' {
+		l.pos++
+	}
+	start := l.pos
+
+	// 直接给出的 /Length 可信时使用，否则（间接引用或长度错误）向后搜索 endstream
+	if n, ok := d["Length"].(float64); ok && n >= 0 && start+int(n) <= len(l.data) {
+		end := start + int(n)
+		rest := bytes.TrimLeft(l.data[end:min(end+16, len(l.data))], " \t\r\n\f\x00")
+		if bytes.HasPrefix(rest, endstreamTag) {
+			l.pos = end
+			return &stream{dict: d, data: l.data[start:end]}, nil
+		}
+	}
+	idx := bytes.Index(l.data[start:], endstreamTag)
+	if idx < 0 {
+		return nil, fmt.Errorf("pdf: unterminated stream at %d", start)
+	}
+	end := start + idx
+	l.pos = end + len(endstreamTag)
+	if end > start && l.data[end-1] == '\n' {
+		end--
+	}
+	if end > start && l.data[end-1] == '\r' {
+		end--
+	}
+	return &stream{dict: d, data: l.data[start:end]}, nil
+}
+
+// decode 依次应用流的过滤器；DCTDecode 等图片编码保持原样
+func (s *stream) decode() ([]byte, error) {
+	data := s.data
+	for _, f := range filters(s.dict["Filter"]) {
+		var err error
+		switch f {
+		case "FlateDecode", "Fl":
+			data, err = inflate(data)
+		case "ASCIIHexDecode", "AHx":
+			l := &lexer{data: data}
+			data = l.hexString()
+		case "ASCII85Decode", "A85":
+			data, err = decodeASCII85(data)
+		default:
+			return data, nil
+		}
+		if err != nil {
+			return nil, err
+		}
+	}
+	return data, nil
+}
+
+func filters(v any) []name {
+	switch f := v.(type) {
+	case name:
+		return []name{f}
+	case array:
+		out := make([]name, 0, len(f))
+		for _, item := range f {
+			if n, ok := item.(name); ok {
+				out = append(out, n)
+			}
+		}
+		return out
+	}
+	return nil
+}
+
+func inflate(data []byte) ([]byte, error) {
+	var r io.Reader
+	if zr, err := zlib.NewReader(bytes.NewReader(data)); err == nil {
+		r = zr
+	} else {
+		r = flate.NewReader(bytes.NewReader(data))
+	}
+	out, err := io.ReadAll(io.LimitReader(r, maxStreamBytes+1))
+	if len(out) > maxStreamBytes {
+		return nil, errStreamTooLarge
+	}
+	// 不少文件的压缩流末尾缺少校验和，已读出的内容仍然可用
+	if err != nil && len(out) == 0 {
+		return nil, fmt.Errorf("pdf: inflate: %w", err)
+	}
+	return out, nil
+}
+
+func decodeASCII85(data []byte) ([]byte, error) {
+	data = bytes.TrimPrefix(bytes.TrimSpace(data), []byte("<~"))
+	if i := bytes.Index(data, []byte("~>")); i >= 0 {
+		data = data[:i]
+	}
+	out := make([]byte, len(data))
+	n, _, err := ascii85.Decode(out, data, true)
+	if err != nil {
+		return nil, fmt.Errorf("pdf: ascii85: %w", err)
+	}
+	return out[:n], nil
+}
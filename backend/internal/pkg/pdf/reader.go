@@ -0,0 +1,479 @@
+package pdf
+
+import (
+	"bytes"
+	"errors"
+	"regexp"
+	"sort"
+	"strconv"
+	"strings"
+)
+
+var (
+	// ErrEncrypted 加密文档（包括仅有所有者密码的文档）不支持提取
+	ErrEncrypted = errors.New("pdf: encrypted documents are not supported")
+	// ErrNoPages 未找到任何页面
+	ErrNoPages = errors.New("pdf: no pages found")
+)
+
+// 页面树、表单嵌套的深度上限，防止恶意文件构造的循环引用
+const (
+	maxTreeDepth = 64
+	maxFormDepth = 8
+	// minImageSide 宽或高小于该值的图片视为图标、装饰线条，不提取
+	minImageSide = 64
+)
+
+var encryptPattern = regexp.MustCompile(`/Encrypt[ \t\r\n\f\x00]*(<<|\d+[ \t\r\n\f\x00]+\d+[ \t\r\n\f\x00]+R)`)
+
+// Image 页面引用的 JPEG 图片（DCTDecode），数据可直接作为 image/jpeg 使用
+type Image struct {
+	Page   int
+	Width  int
+	Height int
+	Data   []byte
+}
+
+// PageContent 单页提取结果
+type PageContent struct {
+	Number int
+	Text   string
+	Images []Image
+}
+
+// ExtractOptions 提取选项
+type ExtractOptions struct {
+	// MaxPages 最多处理的页数，<=0 表示不限制
+	MaxPages int
+	// Images 是否提取页面引用的 JPEG 图片
+	Images bool
+}
+
+// Extract 提取每页文本（以及可选的 JPEG 图片）。
+//
+// 只实现文本提取所需的子集：经典对象与对象流、FlateDecode / ASCIIHex / ASCII85 过滤器、
+// ToUnicode CMap 与 WinAnsi 编码；不依赖 xref 表，损坏或增量更新的文件按出现顺序以最后一次定义为准。
+// 扫描件等没有文本层的页面返回空文本。
+func Extract(data []byte, opts ExtractOptions) ([]PageContent, int, error) {
+	doc, err := load(data)
+	if err != nil {
+		return nil, 0, err
+	}
+	pages := doc.pages()
+	if len(pages) == 0 {
+		return nil, 0, ErrNoPages
+	}
+	total := len(pages)
+	if opts.MaxPages > 0 && len(pages) > opts.MaxPages {
+		pages = pages[:opts.MaxPages]
+	}
+
+	out := make([]PageContent, 0, len(pages))
+	seenImages := map[int]bool{}
+	for i, page := range pages {
+		ex := &extractor{doc: doc, fonts: map[objRef]*font{}, seenImages: seenImages, images: opts.Images, page: i + 1}
+		resources, _ := doc.resolve(page["Resources"]).(dict)
+		for _, content := range doc.contents(page["Contents"]) {
+			ex.run(content, resources, 0)
+		}
+		out = append(out, PageContent{Number: i + 1, Text: normalizeText(ex.text.String()), Images: ex.found})
+	}
+	return out, total, nil
+}
+
+type document struct {
+	objects map[int]any
+}
+
+func load(data []byte) (*document, error) {
+	if !bytes.HasPrefix(bytes.TrimLeft(data[:min(len(data), 1024)], " \t\r\n\f\x00"), []byte("%PDF-")) {
+		return nil, errors.New("pdf: missing header")
+	}
+	if encryptPattern.Match(data) {
+		return nil, ErrEncrypted
+	}
+
+	doc := &document{objects: map[int]any{}}
+	var objStreams []*stream
+	for pos := 0; pos < len(data); {
+		loc := objHeader.FindSubmatchIndex(data[pos:])
+		if loc == nil {
+			break
+		}
+		num, _ := strconv.Atoi(string(data[pos+loc[2] : pos+loc[3]]))
+		l := &lexer{data: data, pos: pos + loc[1]}
+		v, err := l.indirectObject()
+		if err != nil {
+			pos += loc[1]
+			continue
+		}
+		doc.objects[num] = v
+		if s, ok := v.(*stream); ok && s.dict["Type"] == name("ObjStm") {
+			objStreams = append(objStreams, s)
+		}
+		pos = max(l.pos, pos+loc[1])
+	}
+
+	// 对象流中的对象不覆盖文件中直接定义的同号对象
+	for _, s := range objStreams {
+		doc.loadObjectStream(s)
+	}
+	return doc, nil
+}
+
+func (d *document) loadObjectStream(s *stream) {
+	data, err := s.decode()
+	if err != nil {
+		return
+	}
+	n, _ := s.dict["N"].(float64)
+	first, _ := s.dict["First"].(float64)
+	if n <= 0 || first <= 0 || int(first) > len(data) {
+		return
+	}
+	header := &lexer{data: data[:int(first)]}
+	for i := 0; i < int(n); i++ {
+		numV, err1 := header.object()
+		offV, err2 := header.object()
+		num, ok1 := numV.(float64)
+		off, ok2 := offV.(float64)
+		if err1 != nil || err2 != nil || !ok1 || !ok2 {
+			return
+		}
+		if _, exists := d.objects[int(num)]; exists {
+			continue
+		}
+		start := int(first) + int(off)
+		if start >= len(data) {
+			continue
+		}
+		l := &lexer{data: data, pos: start}
+		if v, err := l.object(); err == nil {
+			d.objects[int(num)] = v
+		}
+	}
+}
+
+// resolve 解引用间接对象
+func (d *document) resolve(v any) any {
+	for i := 0; i < 32; i++ {
+		ref, ok := v.(objRef)
+		if !ok {
+			return v
+		}
+		v = d.objects[ref.num]
+	}
+	return nil
+}
+
+func (d *document) dictOf(v any) dict {
+	switch o := d.resolve(v).(type) {
+	case dict:
+		return o
+	case *stream:
+		return o.dict
+	}
+	return nil
+}
+
+// pages 按页面树顺序返回页面字典，Resources 已按继承规则填充。
+// 找不到可用的页面树时退化为按对象编号收集 /Type /Page。
+func (d *document) pages() []dict {
+	var catalog dict
+	nums := make([]int, 0, len(d.objects))
+	for num := range d.objects {
+		nums = append(nums, num)
+	}
+	sort.Ints(nums)
+	for _, num := range nums {
+		if obj, ok := d.objects[num].(dict); ok && obj["Type"] == name("Catalog") {
+			catalog = obj
+		}
+	}
+
+	var out []dict
+	if catalog != nil {
+		visited := map[int]bool{}
+		d.walkPages(catalog["Pages"], nil, visited, 0, &out)
+	}
+	if len(out) > 0 {
+		return out
+	}
+	for _, num := range nums {
+		if obj, ok := d.objects[num].(dict); ok && obj["Type"] == name("Page") {
+			out = append(out, obj)
+		}
+	}
+	return out
+}
+
+func (d *document) walkPages(v any, inherited any, visited map[int]bool, depth int, out *[]dict) {
+	if depth > maxTreeDepth {
+		return
+	}
+	if ref, ok := v.(objRef); ok {
+		if visited[ref.num] {
+			return
+		}
+		visited[ref.num] = true
+	}
+	node := d.dictOf(v)
+	if node == nil {
+		return
+	}
+	if res, ok := node["Resources"]; ok {
+		inherited = res
+	}
+	kids, isTree := d.resolve(node["Kids"]).(array)
+	if !isTree || node["Type"] == name("Page") {
+		page := make(dict, len(node)+1)
+		for k, val := range node {
+			page[k] = val
+		}
+		if inherited != nil {
+			page["Resources"] = inherited
+		}
+		*out = append(*out, page)
+		return
+	}
+	for _, kid := range kids {
+		d.walkPages(kid, inherited, visited, depth+1, out)
+	}
+}
+
+// contents 返回页面内容流（数组形式的多个流按顺序拼接执行）
+func (d *document) contents(v any) [][]byte {
+	var out [][]byte
+	switch c := d.resolve(v).(type) {
+	case *stream:
+		if data, err := c.decode(); err == nil {
+			out = append(out, data)
+		}
+	case array:
+		for _, item := range c {
+			if s, ok := d.resolve(item).(*stream); ok {
+				if data, err := s.decode(); err == nil {
+					out = append(out, data)
+				}
+			}
+		}
+	}
+	return out
+}
+
+type extractor struct {
+	doc        *document
+	fonts      map[objRef]*font
+	seenImages map[int]bool
+	images     bool
+	page       int
+	text       strings.Builder
+	found      []Image
+}
+
+// run 执行内容流中的文本与 XObject 操作符，其余图形操作忽略
+func (e *extractor) run(content []byte, resources dict, depth int) {
+	l := &lexer{data: content}
+	var operands []any
+	var current *font
+	fontsDict := e.doc.dictOf(resources["Font"])
+	xobjects := e.doc.dictOf(resources["XObject"])
+	lastTmY, haveTm := 0.0, false
+
+	for {
+		v, err := l.object()
+		if err != nil {
+			if l.eof() {
+				return
+			}
+			operands = operands[:0]
+			continue
+		}
+		op, ok := v.(keyword)
+		if !ok {
+			operands = append(operands, v)
+			continue
+		}
+		switch op {
+		case "Tf":
+			if len(operands) >= 2 {
+				if n, ok := operands[len(operands)-2].(name); ok {
+					current = e.font(fontsDict, n)
+				}
+			}
+		case "Tj":
+			if len(operands) >= 1 {
+				e.show(current, operands[len(operands)-1])
+			}
+		case "'", "\"":
+			e.newline()
+			if len(operands) >= 1 {
+				e.show(current, operands[len(operands)-1])
+			}
+		case "TJ":
+			if len(operands) >= 1 {
+				arr, _ := operands[len(operands)-1].(array)
+				for _, item := range arr {
+					if n, ok := item.(float64); ok {
+						// 较大的负字距通常是词间距
+						if n < -180 {
+							e.space()
+						}
+						continue
+					}
+					e.show(current, item)
+				}
+			}
+		case "Td", "TD":
+			if len(operands) >= 2 {
+				tx, _ := operands[len(operands)-2].(float64)
+				ty, _ := operands[len(operands)-1].(float64)
+				if ty != 0 {
+					e.newline()
+				} else if tx != 0 {
+					e.space()
+				}
+			}
+		case "T*":
+			e.newline()
+		case "Tm":
+			if len(operands) >= 6 {
+				y, _ := operands[len(operands)-1].(float64)
+				if haveTm && y != lastTmY {
+					e.newline()
+				} else if haveTm {
+					e.space()
+				}
+				lastTmY, haveTm = y, true
+			}
+		case "Do":
+			if len(operands) >= 1 {
+				if n, ok := operands[len(operands)-1].(name); ok && xobjects != nil {
+					e.xobject(xobjects[n], resources, depth)
+				}
+			}
+		case "BI":
+			// 内联图片：跳过 ID 与 EI 之间的二进制数据
+			idx := bytes.Index(content[l.pos:], []byte("ID"))
+			if idx < 0 {
+				return
+			}
+			l.pos += idx + 2
+			end := findInlineImageEnd(content, l.pos)
+			if end < 0 {
+				return
+			}
+			l.pos = end
+		}
+		operands = operands[:0]
+	}
+}
+
+func findInlineImageEnd(content []byte, from int) int {
+	for i := max(from, 1); i+1 < len(content); i++ {
+		if content[i] == 'E' && content[i+1] == 'I' && isSpace(content[i-1]) &&
+			(i+2 == len(content) || isSpace(content[i+2]) || isDelim(content[i+2])) {
+			return i + 2
+		}
+	}
+	return -1
+}
+
+func (e *extractor) xobject(v any, parentResources dict, depth int) {
+	ref, isRef := v.(objRef)
+	s, ok := e.doc.resolve(v).(*stream)
+	if !ok {
+		return
+	}
+	switch s.dict["Subtype"] {
+	case name("Form"):
+		if depth >= maxFormDepth {
+			return
+		}
+		data, err := s.decode()
+		if err != nil {
+			return
+		}
+		resources := e.doc.dictOf(s.dict["Resources"])
+		if resources == nil {
+			resources = parentResources
+		}
+		e.newline()
+		e.run(data, resources, depth+1)
+		e.newline()
+	case name("Image"):
+		if !e.images || !isRef || e.seenImages[ref.num] {
+			return
+		}
+		fs := filters(s.dict["Filter"])
+		if len(fs) != 1 || (fs[0] != "DCTDecode" && fs[0] != "DCT") {
+			return
+		}
+		w, _ := e.doc.resolve(s.dict["Width"]).(float64)
+		h, _ := e.doc.resolve(s.dict["Height"]).(float64)
+		if w < minImageSide || h < minImageSide {
+			return
+		}
+		e.seenImages[ref.num] = true
+		e.found = append(e.found, Image{Page: e.page, Width: int(w), Height: int(h), Data: s.data})
+	}
+}
+
+func (e *extractor) font(fonts dict, n name) *font {
+	if fonts == nil {
+		return nil
+	}
+	fd := e.doc.dictOf(fonts[n])
+	if fd == nil {
+		return nil
+	}
+	ref, isRef := fonts[n].(objRef)
+	if f, ok := e.fonts[ref]; isRef && ok {
+		return f
+	}
+	f := newFont(e.doc, fd)
+	if isRef {
+		e.fonts[ref] = f
+	}
+	return f
+}
+
+func (e *extractor) show(f *font, v any) {
+	s, ok := v.([]byte)
+	if !ok || len(s) == 0 {
+		return
+	}
+	if f == nil {
+		f = defaultFont
+	}
+	e.text.WriteString(f.decode(s))
+}
+
+func (e *extractor) space() {
+	str := e.text.String()
+	if len(str) == 0 || str[len(str)-1] == ' ' || str[len(str)-1] == '\n' {
+		return
+	}
+	e.text.WriteByte(' ')
+}
+
+func (e *extractor) newline() {
+	str := e.text.String()
+	if len(str) == 0 || str[len(str)-1] == '\n' {
+		return
+	}
+	e.text.WriteByte('\n')
+}
+
+var (
+	trailingSpace = regexp.MustCompile(`[ \t]+\n`)
+	blankLines    = regexp.MustCompile(`\n{3,}`)
+)
+
+func normalizeText(s string) string {
+	s = strings.ReplaceAll(s, "\r\n", "\n")
+	s = strings.ReplaceAll(s, "\r", "\n")
+	s = trailingSpace.ReplaceAllString(s, "\n")
+	s = blankLines.ReplaceAllString(s, "\n\n")
+	return strings.TrimSpace(s)
+}
@@ -0,0 +1,96 @@
+//go:build unit
+
+package pdf
+
+import (
+	"bytes"
+	"compress/zlib"
+	"fmt"
+	"testing"
+
+	"github.com/stretchr/testify/require"
+)
+
+func TestExtract_RoundTripWriter(t *testing.T) {
+	doc := New()
+	p := doc.AddPage()
+	p.Text(40, 60, 18, HelveticaBold, "Statement (2026-09)")
+	p.Text(40, 90, 10, Helvetica, "Café total: 12.50")
+	doc.AddPage().Text(40, 60, 10, Courier, "page two")
+
+	var buf bytes.Buffer
+	_, err := doc.WriteTo(&buf)
+	require.NoError(t, err)
+
+	pages, total, err := Extract(buf.Bytes(), ExtractOptions{})
+	require.NoError(t, err)
+	require.Equal(t, 2, total)
+	require.Len(t, pages, 2)
+	require.Equal(t, "Statement (2026-09)\nCafé total: 12.50", pages[0].Text)
+	require.Equal(t, "page two", pages[1].Text)
+
+	pages, total, err = Extract(buf.Bytes(), ExtractOptions{MaxPages: 1})
+	require.NoError(t, err)
+	require.Equal(t, 2, total)
+	require.Len(t, pages, 1)
+}
+
+// 压缩内容流 + Type0 字体 + ToUnicode CMap + JPEG 图片
+func TestExtract_CompressedToUnicodeAndImages(t *testing.T) {
+	deflate := func(s string) []byte {
+		var b bytes.Buffer
+		w := zlib.NewWriter(&b)
+		_, _ = w.Write([]byte(s))
+		_ = w.Close()
+		return b.Bytes()
+	}
+	cmap := "/CIDInit /ProcSet findresource begin\n1 begincodespacerange <0000> <FFFF> endcodespacerange\n" +
+		"2 beginbfchar <0001> <4F60> <0002> <597D> endbfchar\n" +
+		"1 beginbfrange <0010> <0012> <0041> endbfrange\nend\n"
+	content := deflate("BT /F1 12 Tf 1 0 0 1 50 700 Tm <00010002> Tj 1 0 0 1 50 680 Tm [<0010> -300 <00110012>] TJ ET\n" +
+		"q 200 0 0 100 50 500 cm /Im1 Do Q\n" +
+		"BI /W 1 /H 1 /BPC 8 /CS /G ID \xff EI\n")
+	jpeg := []byte("\xff\xd8\xff\xe0fake-jpeg\xff\xd9")
+
+	var buf bytes.Buffer
+	buf.WriteString("%PDF-1.7\n")
+	obj := func(num int, body string, data []byte) {
+		fmt.Fprintf(&buf, "%d 0 obj\n%s", num, body)
+		if data != nil {
+			fmt.Fprintf(&buf, "\nstream\n%s\nendstream", data)
+		}
+		buf.WriteString("\nendobj\n")
+	}
+	obj(1, "<< /Type /Catalog /Pages 2 0 R >>", nil)
+	obj(2, "<< /Type /Pages /Kids [3 0 R] /Count 1 /Resources << /Font << /F1 5 0 R >> /XObject << /Im1 7 0 R >> >> >>", nil)
+	obj(3, "<< /Type /Page /Parent 2 0 R /Contents 4 0 R >>", nil)
+	obj(4, "<< /Length 8 0 R /Filter /FlateDecode >>", content)
+	obj(5, "<< /Type /Font /Subtype /Type0 /BaseFont /Demo /ToUnicode 6 0 R >>", nil)
+	obj(6, fmt.Sprintf("<< /Length %d /Filter /FlateDecode >>", len(deflate(cmap))), deflate(cmap))
+	obj(7, fmt.Sprintf("<< /Type /XObject /Subtype /Image /Width 200 /Height 100 /Filter /DCTDecode /Length %d >>", len(jpeg)), jpeg)
+	obj(8, fmt.Sprintf("%d", len(content)), nil)
+	buf.WriteString("trailer\n<< /Root 1 0 R >>\n%%EOF\n")
+
+	pages, _, err := Extract(buf.Bytes(), ExtractOptions{Images: true})
+	require.NoError(t, err)
+	require.Len(t, pages, 1)
+	require.Equal(t, "你好\nA BC", pages[0].Text)
+	require.Len(t, pages[0].Images, 1)
+	require.Equal(t, jpeg, pages[0].Images[0].Data)
+	require.Equal(t, 200, pages[0].Images[0].Width)
+
+	pages, _, err = Extract(buf.Bytes(), ExtractOptions{})
+	require.NoError(t, err)
+	require.Empty(t, pages[0].Images)
+}
+
+func TestExtract_Rejects(t *testing.T) {
+	_, _, err := Extract([]byte("not a pdf"), ExtractOptions{})
+	require.Error(t, err)
+
+	_, _, err = Extract([]byte("%PDF-1.4\n1 0 obj\n<< /Type /Catalog >>\nendobj\ntrailer\n<< /Root 1 0 R /Encrypt 9 0 R >>\n"), ExtractOptions{})
+	require.ErrorIs(t, err, ErrEncrypted)
+
+	_, _, err = Extract([]byte("%PDF-1.4\n1 0 obj\n<< /Type /Catalog >>\nendobj\n"), ExtractOptions{})
+	require.ErrorIs(t, err, ErrNoPages)
+}
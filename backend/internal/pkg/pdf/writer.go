@@ -0,0 +1,174 @@
+// Package pdf 提供一个最小化的 PDF 写入器：A4 页面、标准 14 字体（无需嵌入）、文本与直线；
+// 以及用于附件预处理的文本提取（见 Extract）。
+//
+// 写入器仅用于账单等简单报表。文本按 WinAnsi 编码输出，编码外的字符替换为 '?'。
+// 坐标以页面左上角为原点、单位为 pt（1/72 英寸），写出时转换为 PDF 的左下角坐标系。
+package pdf
+
+import (
+	"bytes"
+	"fmt"
+	"io"
+	"strconv"
+	"strings"
+)
+
+// A4 页面尺寸（pt）
+const (
+	PageWidth  = 595.28
+	PageHeight = 841.89
+)
+
+// Font 标准 14 字体中的一种
+type Font int
+
+const (
+	Helvetica Font = iota
+	HelveticaBold
+	// Courier 等宽字体，适合表格中需要对齐的数字
+	Courier
+)
+
+var fontNames = []string{"Helvetica", "Helvetica-Bold", "Courier"}
+
+// courierAdvance Courier 每个字符宽度（相对字号）
+const courierAdvance = 0.6
+
+// Document PDF 文档
+type Document struct {
+	pages []*Page
+}
+
+// Page 单个页面的内容流
+type Page struct {
+	content bytes.Buffer
+}
+
+// New 创建空文档
+func New() *Document {
+	return &Document{}
+}
+
+// AddPage 追加一页并返回
+func (d *Document) AddPage() *Page {
+	p := &Page{}
+	d.pages = append(d.pages, p)
+	return p
+}
+
+// PageCount 页数
+func (d *Document) PageCount() int {
+	return len(d.pages)
+}
+
+// Text 在 (x, y) 处绘制单行文本，y 为基线位置
+func (p *Page) Text(x, y, size float64, font Font, s string) {
+	fmt.Fprintf(&p.content, "BT /F%d %s Tf %s %s Td (%s) Tj ET\n",
+		int(font)+1, num(size), num(x), num(PageHeight-y), escape(s))
+}
+
+// TextRight 以 x 为右边界绘制 Courier 文本（仅等宽字体可精确计算宽度）
+func (p *Page) TextRight(x, y, size float64, s string) {
+	p.Text(x-MonoWidth(s, size), y, size, Courier, s)
+}
+
+// Line 绘制直线
+func (p *Page) Line(x1, y1, x2, y2, width float64) {
+	fmt.Fprintf(&p.content, "%s w %s %s m %s %s l S\n",
+		num(width), num(x1), num(PageHeight-y1), num(x2), num(PageHeight-y2))
+}
+
+// MonoWidth 返回 Courier 文本宽度
+func MonoWidth(s string, size float64) float64 {
+	return float64(len([]rune(s))) * courierAdvance * size
+}
+
+// WriteTo 写出完整 PDF 文件；没有页面时写出一个空白页
+func (d *Document) WriteTo(w io.Writer) (int64, error) {
+	pages := d.pages
+	if len(pages) == 0 {
+		pages = []*Page{{}}
+	}
+
+	var buf bytes.Buffer
+	offsets := []int{0}
+	beginObj := func() int {
+		offsets = append(offsets, buf.Len())
+		id := len(offsets) - 1
+		fmt.Fprintf(&buf, "%d 0 obj\n", id)
+		return id
+	}
+	endObj := func() { buf.WriteString("endobj\n") }
+
+	buf.WriteString("%PDF-1.4\n%\xe2\xe3\xcf\xd3\n")
+
+	// 对象编号：1 Catalog，2 Pages，3.. 字体，其后每页 Page + Contents 两个对象
+	fontBase := 3
+	pageBase := fontBase + len(fontNames)
+
+	beginObj()
+	buf.WriteString("<< /Type /Catalog /Pages 2 0 R >>\n")
+	endObj()
+
+	beginObj()
+	kids := make([]string, len(pages))
+	for i := range pages {
+		kids[i] = strconv.Itoa(pageBase+2*i) + " 0 R"
+	}
+	fmt.Fprintf(&buf, "<< /Type /Pages /Kids [%s] /Count %d >>\n", strings.Join(kids, " "), len(pages))
+	endObj()
+
+	fonts := make([]string, len(fontNames))
+	for i, name := range fontNames {
+		beginObj()
+		fmt.Fprintf(&buf, "<< /Type /Font /Subtype /Type1 /BaseFont /%s /Encoding /WinAnsiEncoding >>\n", name)
+		endObj()
+		fonts[i] = fmt.Sprintf("/F%d %d 0 R", i+1, fontBase+i)
+	}
+
+	for i, p := range pages {
+		beginObj()
+		fmt.Fprintf(&buf, "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 %s %s] /Resources << /Font << %s >> >> /Contents %d 0 R >>\n",
+			num(PageWidth), num(PageHeight), strings.Join(fonts, " "), pageBase+2*i+1)
+		endObj()
+
+		beginObj()
+		fmt.Fprintf(&buf, "<< /Length %d >>\nstream\n", p.content.Len())
+		buf.Write(p.content.Bytes())
+		buf.WriteString("endstream\n")
+		endObj()
+	}
+
+	xref := buf.Len()
+	fmt.Fprintf(&buf, "xref\n0 %d\n0000000000 65535 f \n", len(offsets))
+	for _, off := range offsets[1:] {
+		fmt.Fprintf(&buf, "%010d 00000 n \n", off)
+	}
+	fmt.Fprintf(&buf, "trailer\n<< /Size %d /Root 1 0 R >>\nstartxref\n%d\n%%%%EOF\n", len(offsets), xref)
+
+	n, err := w.Write(buf.Bytes())
+	return int64(n), err
+}
+
+// escape 转换为 WinAnsi 字节并转义字符串字面量中的特殊字符
+func escape(s string) string {
+	var b strings.Builder
+	for _, r := range s {
+		switch {
+		case r == '\\' || r == '(' || r == ')':
+			b.WriteByte('\\')
+			b.WriteRune(r)
+		case r >= 0x20 && r < 0x7f:
+			b.WriteRune(r)
+		case r >= 0xa0 && r <= 0xff:
+			fmt.Fprintf(&b, "\\%03o", r)
+		default:
+			b.WriteByte('?')
+		}
+	}
+	return b.String()
+}
+
+func num(v float64) string {
+	return strconv.FormatFloat(v, 'f', -1, 64)
+}
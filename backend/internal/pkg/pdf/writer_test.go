@@ -0,0 +1,58 @@
+//go:build unit
+
+package pdf
+
+import (
+	"bytes"
+	"regexp"
+	"strconv"
+	"strings"
+	"testing"
+
+	"github.com/stretchr/testify/require"
+)
+
+func TestDocument_WriteTo_Layout(t *testing.T) {
+	doc := New()
+	p := doc.AddPage()
+	p.Text(40, 60, 18, HelveticaBold, "Statement (2026-09)")
+	p.Line(40, 70, 555, 70, 0.5)
+	p.TextRight(555, 90, 9, "12.50")
+	doc.AddPage().Text(40, 60, 10, Helvetica, "page 2")
+
+	var buf bytes.Buffer
+	n, err := doc.WriteTo(&buf)
+	require.NoError(t, err)
+	require.Equal(t, int64(buf.Len()), n)
+
+	out := buf.String()
+	require.True(t, strings.HasPrefix(out, "%PDF-1.4\n"))
+	require.True(t, strings.HasSuffix(out, "%%EOF\n"))
+	require.Contains(t, out, "/Count 2")
+	require.Contains(t, out, `(Statement \(2026-09\)) Tj`)
+	require.Contains(t, out, "/BaseFont /Courier")
+
+	// xref 中的偏移量必须指向对应对象的起始位置
+	m := regexp.MustCompile(`startxref\n(\d+)\n`).FindStringSubmatch(out)
+	require.Len(t, m, 2)
+	xref, err := strconv.Atoi(m[1])
+	require.NoError(t, err)
+	require.True(t, strings.HasPrefix(out[xref:], "xref\n"))
+	entries := regexp.MustCompile(`(\d{10}) 00000 n `).FindAllStringSubmatch(out[xref:], -1)
+	require.NotEmpty(t, entries)
+	for i, e := range entries {
+		off, err := strconv.Atoi(e[1])
+		require.NoError(t, err)
+		require.True(t, strings.HasPrefix(out[off:], strconv.Itoa(i+1)+" 0 obj\n"), "object %d offset mismatch", i+1)
+	}
+}
+
+func TestEscape_WinAnsi(t *testing.T) {
+	require.Equal(t, `a\\b\(c\)`, escape(`a\b(c)`))
+	require.Equal(t, `caf\351`, escape("café"))
+	require.Equal(t, "??", escape("账单"))
+}
+
+func TestMonoWidth(t *testing.T) {
+	require.InDelta(t, 30.0, MonoWidth("12345", 10), 1e-9)
+}
@@ -5,8 +5,10 @@ import (
 	"log"
 	"math"
 	"net/http"
+	"strings"
 
 	infraerrors "github.com/Wei-Shaw/sub2api/internal/pkg/errors"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/pagination"
 	"github.com/Wei-Shaw/sub2api/internal/util/logredact"
 	"github.com/gin-gonic/gin"
 )
@@ -157,6 +159,33 @@ func PaginatedWithResult(c *gin.Context, items any, pagination *PaginationResult
 	})
 }
 
+// CursorPaginatedData 游标分页数据格式
+type CursorPaginatedData struct {
+	Items      any    `json:"items"`
+	NextCursor string `json:"next_cursor"`
+	HasMore    bool   `json:"has_more"`
+}
+
+// CursorPaginated 返回游标分页数据
+func CursorPaginated(c *gin.Context, items any, nextCursor string, hasMore bool) {
+	Success(c, CursorPaginatedData{
+		Items:      items,
+		NextCursor: nextCursor,
+		HasMore:    hasMore,
+	})
+}
+
+// ParseCursorPagination 解析游标分页参数（cursor、limit/page_size、sort_by、sort_order）
+func ParseCursorPagination(c *gin.Context) pagination.CursorParams {
+	_, limit := ParsePagination(c)
+	return pagination.CursorParams{
+		Cursor:    c.Query("cursor"),
+		Limit:     limit,
+		SortBy:    strings.TrimSpace(c.Query("sort_by")),
+		SortOrder: strings.TrimSpace(c.Query("sort_order")),
+	}
+}
+
 // ParsePagination 解析分页参数
 func ParsePagination(c *gin.Context) (page, pageSize int) {
 	page = 1
@@ -0,0 +1,127 @@
+//go:build unit
+
+package s3
+
+import (
+	"bytes"
+	"context"
+	"io"
+	"net/http"
+	"net/http/httptest"
+	"strings"
+	"sync"
+	"testing"
+
+	"github.com/stretchr/testify/require"
+)
+
+func TestWriter_SmallObjectUsesPutObject(t *testing.T) {
+	var (
+		gotMethod, gotPath, gotAuth string
+		gotBody                     []byte
+	)
+	srv := httptest.NewServer(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		gotMethod, gotPath, gotAuth = r.Method, r.URL.EscapedPath(), r.Header.Get("Authorization")
+		gotBody, _ = io.ReadAll(r.Body)
+	}))
+	defer srv.Close()
+
+	c, err := New(Config{Endpoint: srv.URL, Bucket: "exports", AccessKeyID: "AK", SecretAccessKey: "SK", UsePathStyle: true})
+	require.NoError(t, err)
+	w := c.NewWriter(context.Background(), "usage/2026 01.csv", "text/csv", 0)
+	_, err = w.Write([]byte("id\n1\n"))
+	require.NoError(t, err)
+	require.NoError(t, w.Close())
+
+	require.Equal(t, http.MethodPut, gotMethod)
+	require.Equal(t, "/exports/usage/2026%2001.csv", gotPath)
+	require.Equal(t, "id\n1\n", string(gotBody))
+	require.True(t, strings.HasPrefix(gotAuth, "AWS4-HMAC-SHA256 Credential=AK/"))
+	require.Contains(t, gotAuth, "/us-east-1/s3/aws4_request")
+}
+
+func TestWriter_LargeObjectUsesMultipartUpload(t *testing.T) {
+	var (
+		mu       sync.Mutex
+		parts    = map[string]int{}
+		complete []byte
+	)
+	srv := httptest.NewServer(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		mu.Lock()
+		defer mu.Unlock()
+		q := r.URL.Query()
+		body, _ := io.ReadAll(r.Body)
+		switch {
+		case r.Method == http.MethodPost && q.Has("uploads"):
+			_, _ = w.Write([]byte(`<InitiateMultipartUploadResult><UploadId>up-1</UploadId></InitiateMultipartUploadResult>`))
+		case r.Method == http.MethodPut && q.Get("uploadId") == "up-1":
+			parts[q.Get("partNumber")] = len(body)
+			w.Header().Set("ETag", `"etag-`+q.Get("partNumber")+`"`)
+		case r.Method == http.MethodPost && q.Get("uploadId") == "up-1":
+			complete = body
+			_, _ = w.Write([]byte(`<CompleteMultipartUploadResult></CompleteMultipartUploadResult>`))
+		default:
+			w.WriteHeader(http.StatusBadRequest)
+		}
+	}))
+	defer srv.Close()
+
+	c, err := New(Config{Endpoint: srv.URL, Bucket: "exports", UsePathStyle: true})
+	require.NoError(t, err)
+	w := c.NewWriter(context.Background(), "big.parquet", "", MinPartSize)
+	_, err = w.Write(bytes.Repeat([]byte("x"), MinPartSize+10))
+	require.NoError(t, err)
+	require.NoError(t, w.Close())
+
+	require.Equal(t, map[string]int{"1": MinPartSize, "2": 10}, parts)
+	require.Contains(t, string(complete), `<Part><PartNumber>2</PartNumber><ETag>&#34;etag-2&#34;</ETag></Part>`)
+}
+
+func TestClient_GetListDelete(t *testing.T) {
+	srv := httptest.NewServer(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		switch {
+		case r.Method == http.MethodGet && r.URL.Path == "/exports" && r.URL.Query().Get("list-type") == "2":
+			if r.URL.Query().Get("continuation-token") == "" {
+				_, _ = w.Write([]byte(`<ListBucketResult><Contents><Key>p/a</Key><Size>1</Size><LastModified>2026-01-02T03:04:05.000Z</LastModified></Contents><IsTruncated>true</IsTruncated><NextContinuationToken>t1</NextContinuationToken></ListBucketResult>`))
+				return
+			}
+			_, _ = w.Write([]byte(`<ListBucketResult><Contents><Key>p/b</Key><Size>2</Size></Contents><IsTruncated>false</IsTruncated></ListBucketResult>`))
+		case r.Method == http.MethodGet && r.URL.Path == "/exports/p/a":
+			w.Header().Set("Content-Type", "text/plain")
+			_, _ = w.Write([]byte("hello"))
+		case r.Method == http.MethodDelete:
+			w.WriteHeader(http.StatusNotFound)
+		default:
+			w.WriteHeader(http.StatusNotFound)
+		}
+	}))
+	defer srv.Close()
+
+	c, err := New(Config{Endpoint: srv.URL, Bucket: "exports", UsePathStyle: true})
+	require.NoError(t, err)
+	ctx := context.Background()
+
+	var keys []string
+	require.NoError(t, c.ListObjects(ctx, "p/", func(obj ObjectInfo) error {
+		keys = append(keys, obj.Key)
+		return nil
+	}))
+	require.Equal(t, []string{"p/a", "p/b"}, keys)
+
+	obj, err := c.GetObject(ctx, "p/a")
+	require.NoError(t, err)
+	data, err := io.ReadAll(obj.Body)
+	require.NoError(t, obj.Body.Close())
+	require.NoError(t, err)
+	require.Equal(t, "hello", string(data))
+	require.Equal(t, "text/plain", obj.ContentType)
+
+	_, err = c.GetObject(ctx, "p/missing")
+	require.ErrorIs(t, err, ErrNotFound)
+	require.NoError(t, c.DeleteObject(ctx, "p/missing"))
+}
+
+func TestURIEncode(t *testing.T) {
+	require.Equal(t, "a/b%20c~d", uriEncode("a/b c~d", false))
+	require.Equal(t, "a%2Fb", uriEncode("a/b", true))
+}
@@ -0,0 +1,115 @@
+package s3
+
+import (
+	"context"
+	"errors"
+	"time"
+)
+
+// Writer 以流式方式上传对象：数据按分片缓冲，超过一个分片时自动切换为分片上传。
+// 写入完成后必须调用 Close；出错时调用 Abort 清理未完成的分片上传。
+type Writer struct {
+	ctx         context.Context
+	client      *Client
+	key         string
+	contentType string
+	partSize    int
+
+	buf      []byte
+	uploadID string
+	parts    []completedPart
+	done     bool
+}
+
+// NewWriter 创建流式上传写入器；partSize 小于 MinPartSize 时使用 DefaultPartSize
+func (c *Client) NewWriter(ctx context.Context, key, contentType string, partSize int) *Writer {
+	if partSize < MinPartSize {
+		partSize = DefaultPartSize
+	}
+	return &Writer{
+		ctx:         ctx,
+		client:      c,
+		key:         key,
+		contentType: contentType,
+		partSize:    partSize,
+		buf:         make([]byte, 0, partSize),
+	}
+}
+
+func (w *Writer) Write(p []byte) (int, error) {
+	if w.done {
+		return 0, errors.New("s3: writer closed")
+	}
+	written := 0
+	for len(p) > 0 {
+		n := copy(w.buf[len(w.buf):cap(w.buf)], p)
+		w.buf = w.buf[:len(w.buf)+n]
+		p = p[n:]
+		written += n
+		if len(w.buf) == cap(w.buf) {
+			if err := w.flushPart(); err != nil {
+				return written, err
+			}
+		}
+	}
+	return written, nil
+}
+
+func (w *Writer) flushPart() error {
+	if w.uploadID == "" {
+		id, err := w.client.createMultipartUpload(w.ctx, w.key, w.contentType)
+		if err != nil {
+			return err
+		}
+		w.uploadID = id
+	}
+	partNumber := len(w.parts) + 1
+	etag, err := w.client.uploadPart(w.ctx, w.key, w.uploadID, partNumber, w.buf)
+	if err != nil {
+		return err
+	}
+	w.parts = append(w.parts, completedPart{PartNumber: partNumber, ETag: etag})
+	w.buf = w.buf[:0]
+	return nil
+}
+
+// Close 上传剩余数据并完成上传；数据不足一个分片时直接 PutObject
+func (w *Writer) Close() error {
+	if w.done {
+		return nil
+	}
+	w.done = true
+	if w.uploadID == "" {
+		return w.client.PutObject(w.ctx, w.key, w.buf, w.contentType)
+	}
+	if len(w.buf) > 0 {
+		if err := w.flushPart(); err != nil {
+			w.abort()
+			return err
+		}
+	}
+	if err := w.client.completeMultipartUpload(w.ctx, w.key, w.uploadID, w.parts); err != nil {
+		w.abort()
+		return err
+	}
+	return nil
+}
+
+// Abort 放弃上传（已上传的分片会被清理）
+func (w *Writer) Abort() {
+	if w.done {
+		return
+	}
+	w.done = true
+	w.abort()
+}
+
+func (w *Writer) abort() {
+	if w.uploadID == "" {
+		return
+	}
+	// 原 ctx 可能已取消，使用独立超时清理
+	ctx, cancel := context.WithTimeout(context.Background(), 30*time.Second)
+	defer cancel()
+	_ = w.client.abortMultipartUpload(ctx, w.key, w.uploadID)
+}
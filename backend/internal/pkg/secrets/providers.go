@@ -0,0 +1,203 @@
+package secrets
+
+import (
+	"bytes"
+	"context"
+	"encoding/json"
+	"errors"
+	"fmt"
+	"io"
+	"net/http"
+	"net/url"
+	"os"
+	"os/exec"
+	"path/filepath"
+	"strings"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/pkg/httpclient"
+)
+
+// VaultConfig HashiCorp Vault KV v2 连接参数
+type VaultConfig struct {
+	// Address 例如 https://vault.internal:8200
+	Address string
+	// Token 直接提供的 token；为空时读取 TokenFile
+	Token string
+	// TokenFile token 文件（例如 Vault Agent sink），每次请求重新读取以支持 token 续期
+	TokenFile string
+	// Mount KV v2 挂载路径，默认 secret
+	Mount     string
+	Namespace string
+	Timeout   time.Duration
+}
+
+type vaultProvider struct {
+	cfg    VaultConfig
+	base   *url.URL
+	client *http.Client
+}
+
+// NewVaultProvider 创建 Vault KV v2 后端
+func NewVaultProvider(cfg VaultConfig) (Provider, error) {
+	base, err := url.Parse(strings.TrimRight(strings.TrimSpace(cfg.Address), "/"))
+	if err != nil || base.Host == "" {
+		return nil, fmt.Errorf("secrets: invalid vault address %q", cfg.Address)
+	}
+	if cfg.Token == "" && cfg.TokenFile == "" {
+		return nil, errors.New("secrets: vault token or token_file is required")
+	}
+	if cfg.Mount == "" {
+		cfg.Mount = "secret"
+	}
+	if cfg.Timeout <= 0 {
+		cfg.Timeout = 10 * time.Second
+	}
+	client, err := httpclient.GetClient(httpclient.Options{Timeout: cfg.Timeout})
+	if err != nil {
+		return nil, err
+	}
+	return &vaultProvider{cfg: cfg, base: base, client: client}, nil
+}
+
+func (p *vaultProvider) token() (string, error) {
+	if p.cfg.Token != "" {
+		return p.cfg.Token, nil
+	}
+	b, err := os.ReadFile(p.cfg.TokenFile)
+	if err != nil {
+		return "", fmt.Errorf("secrets: read vault token file: %w", err)
+	}
+	return strings.TrimSpace(string(b)), nil
+}
+
+func (p *vaultProvider) Fetch(ctx context.Context, path string) (map[string]string, error) {
+	token, err := p.token()
+	if err != nil {
+		return nil, err
+	}
+	u := p.base.JoinPath("v1", strings.Trim(p.cfg.Mount, "/"), "data", path)
+	req, err := http.NewRequestWithContext(ctx, http.MethodGet, u.String(), nil)
+	if err != nil {
+		return nil, err
+	}
+	req.Header.Set("X-Vault-Token", token)
+	if p.cfg.Namespace != "" {
+		req.Header.Set("X-Vault-Namespace", p.cfg.Namespace)
+	}
+	resp, err := p.client.Do(req)
+	if err != nil {
+		return nil, fmt.Errorf("secrets: vault request: %w", err)
+	}
+	defer func() { _ = resp.Body.Close() }()
+	body, err := io.ReadAll(io.LimitReader(resp.Body, 1<<20))
+	if err != nil {
+		return nil, fmt.Errorf("secrets: read vault response: %w", err)
+	}
+	if resp.StatusCode == http.StatusNotFound {
+		return nil, fmt.Errorf("%w: %s", ErrNotFound, path)
+	}
+	if resp.StatusCode != http.StatusOK {
+		return nil, fmt.Errorf("secrets: vault returned status %d for %s", resp.StatusCode, path)
+	}
+	var parsed struct {
+		Data struct {
+			Data map[string]any `json:"data"`
+		} `json:"data"`
+	}
+	if err := json.Unmarshal(body, &parsed); err != nil {
+		return nil, fmt.Errorf("secrets: decode vault response: %w", err)
+	}
+	if parsed.Data.Data == nil {
+		return nil, fmt.Errorf("%w: %s", ErrNotFound, path)
+	}
+	return stringFields(parsed.Data.Data), nil
+}
+
+type fileProvider struct {
+	dir string
+}
+
+// NewFileProvider 创建挂载目录后端：path 对应 dir 下的文件（JSON 对象或纯文本），
+// 若 path 为目录则目录下每个文件作为一个字段。
+func NewFileProvider(dir string) (Provider, error) {
+	if strings.TrimSpace(dir) == "" {
+		return nil, errors.New("secrets: file provider dir is required")
+	}
+	return &fileProvider{dir: filepath.Clean(dir)}, nil
+}
+
+func (p *fileProvider) Fetch(_ context.Context, path string) (map[string]string, error) {
+	full := filepath.Join(p.dir, filepath.FromSlash(path))
+	if full != p.dir && !strings.HasPrefix(full, p.dir+string(filepath.Separator)) {
+		return nil, fmt.Errorf("secrets: path %q escapes secrets dir", path)
+	}
+	info, err := os.Stat(full)
+	if errors.Is(err, os.ErrNotExist) {
+		return nil, fmt.Errorf("%w: %s", ErrNotFound, path)
+	}
+	if err != nil {
+		return nil, err
+	}
+	if !info.IsDir() {
+		b, err := os.ReadFile(full)
+		if err != nil {
+			return nil, err
+		}
+		return parseFields(b), nil
+	}
+	entries, err := os.ReadDir(full)
+	if err != nil {
+		return nil, err
+	}
+	out := make(map[string]string, len(entries))
+	for _, e := range entries {
+		// Kubernetes 挂载目录中的 ..data 等为内部链接
+		if e.IsDir() || strings.HasPrefix(e.Name(), ".") {
+			continue
+		}
+		b, err := os.ReadFile(filepath.Join(full, e.Name()))
+		if err != nil {
+			return nil, err
+		}
+		out[e.Name()] = strings.TrimSpace(string(b))
+	}
+	return out, nil
+}
+
+type execProvider struct {
+	command []string
+	timeout time.Duration
+}
+
+// NewExecProvider 创建外部命令后端：执行 command + path，stdout 为 JSON 对象或纯文本。例如
+//
+//	["aws", "secretsmanager", "get-secret-value", "--query", "SecretString", "--output", "text", "--secret-id"]
+//	["gcloud", "secrets", "versions", "access", "latest", "--secret"]
+func NewExecProvider(command []string, timeout time.Duration) (Provider, error) {
+	if len(command) == 0 || strings.TrimSpace(command[0]) == "" {
+		return nil, errors.New("secrets: exec provider command is required")
+	}
+	if timeout <= 0 {
+		timeout = 15 * time.Second
+	}
+	return &execProvider{command: command, timeout: timeout}, nil
+}
+
+func (p *execProvider) Fetch(ctx context.Context, path string) (map[string]string, error) {
+	ctx, cancel := context.WithTimeout(ctx, p.timeout)
+	defer cancel()
+	args := append(append([]string{}, p.command[1:]...), path)
+	cmd := exec.CommandContext(ctx, p.command[0], args...)
+	var stdout, stderr bytes.Buffer
+	cmd.Stdout = &stdout
+	cmd.Stderr = &stderr
+	if err := cmd.Run(); err != nil {
+		msg := strings.TrimSpace(stderr.String())
+		if len(msg) > 200 {
+			msg = msg[:200]
+		}
+		return nil, fmt.Errorf("secrets: exec %s for %s: %w: %s", p.command[0], path, err, msg)
+	}
+	return parseFields(stdout.Bytes()), nil
+}
@@ -0,0 +1,158 @@
+// Package secrets 从外部密钥后端读取敏感配置，避免将密码与密钥直接写入环境变量或配置文件。
+//
+// 配置值或账号凭证字段写成引用形式即可：
+//
+//	secret://<path>#<field>
+//
+// 支持的后端：
+//   - vault: HashiCorp Vault KV v2（token 认证）
+//   - file:  挂载目录（Kubernetes Secret、各云 Secret Manager 的 CSI 驱动等）
+//   - exec:  外部命令（例如 aws secretsmanager / gcloud secrets 命令行），stdout 为密钥内容
+//
+// Resolver 带 TTL 缓存：过期后重新拉取以感知后端轮换，拉取失败时继续使用旧值。
+package secrets
+
+import (
+	"context"
+	"encoding/json"
+	"errors"
+	"fmt"
+	"log/slog"
+	"strings"
+	"sync"
+	"time"
+)
+
+// RefPrefix 密钥引用前缀
+const RefPrefix = "secret://"
+
+// ErrNotFound 密钥或字段不存在
+var ErrNotFound = errors.New("secrets: not found")
+
+// Provider 读取一个密钥路径下的所有字段
+type Provider interface {
+	Fetch(ctx context.Context, path string) (map[string]string, error)
+}
+
+// IsRef 判断字符串是否为密钥引用
+func IsRef(s string) bool {
+	return strings.HasPrefix(strings.TrimSpace(s), RefPrefix)
+}
+
+// ParseRef 解析 secret://path#field；省略 field 时返回空字符串
+func ParseRef(ref string) (path, field string, err error) {
+	ref = strings.TrimSpace(ref)
+	if !strings.HasPrefix(ref, RefPrefix) {
+		return "", "", fmt.Errorf("secrets: %q is not a secret reference", ref)
+	}
+	path, field, _ = strings.Cut(ref[len(RefPrefix):], "#")
+	path = strings.Trim(path, "/")
+	if path == "" {
+		return "", "", fmt.Errorf("secrets: empty path in %q", ref)
+	}
+	return path, field, nil
+}
+
+type cacheEntry struct {
+	fields    map[string]string
+	fetchedAt time.Time
+}
+
+// Resolver 解析密钥引用，按路径缓存
+type Resolver struct {
+	provider Provider
+	ttl      time.Duration
+	now      func() time.Time
+
+	mu    sync.Mutex
+	cache map[string]cacheEntry
+}
+
+// NewResolver 创建解析器；ttl <= 0 表示不过期（只在首次使用时拉取）
+func NewResolver(provider Provider, ttl time.Duration) *Resolver {
+	return &Resolver{provider: provider, ttl: ttl, now: time.Now, cache: make(map[string]cacheEntry)}
+}
+
+// Resolve 解析单个引用。field 为空时：密钥只有一个字段则返回该字段，否则返回 "value" 字段。
+func (r *Resolver) Resolve(ctx context.Context, ref string) (string, error) {
+	path, field, err := ParseRef(ref)
+	if err != nil {
+		return "", err
+	}
+	fields, err := r.fetch(ctx, path)
+	if err != nil {
+		return "", err
+	}
+	if field == "" {
+		if len(fields) == 1 {
+			for _, v := range fields {
+				return v, nil
+			}
+		}
+		field = "value"
+	}
+	v, ok := fields[field]
+	if !ok {
+		return "", fmt.Errorf("%w: %s#%s", ErrNotFound, path, field)
+	}
+	return v, nil
+}
+
+// ResolveString 非引用原样返回，引用则解析
+func (r *Resolver) ResolveString(ctx context.Context, s string) (string, error) {
+	if r == nil || !IsRef(s) {
+		return s, nil
+	}
+	return r.Resolve(ctx, s)
+}
+
+func (r *Resolver) fetch(ctx context.Context, path string) (map[string]string, error) {
+	r.mu.Lock()
+	entry, ok := r.cache[path]
+	r.mu.Unlock()
+	if ok && (r.ttl <= 0 || r.now().Sub(entry.fetchedAt) < r.ttl) {
+		return entry.fields, nil
+	}
+
+	fields, err := r.provider.Fetch(ctx, path)
+	if err != nil {
+		if ok {
+			// 后端暂时不可用时继续使用缓存值，避免轮换窗口内请求失败
+			slog.Warn("secrets: refresh failed, using cached value", "path", path, "error", err)
+			return entry.fields, nil
+		}
+		return nil, err
+	}
+	r.mu.Lock()
+	r.cache[path] = cacheEntry{fields: fields, fetchedAt: r.now()}
+	r.mu.Unlock()
+	return fields, nil
+}
+
+// parseFields 解析后端返回的原始内容：JSON 对象按字段展开，其他内容作为 "value" 字段
+func parseFields(raw []byte) map[string]string {
+	trimmed := strings.TrimSpace(string(raw))
+	if strings.HasPrefix(trimmed, "{") {
+		var obj map[string]any
+		if err := json.Unmarshal([]byte(trimmed), &obj); err == nil {
+			return stringFields(obj)
+		}
+	}
+	return map[string]string{"value": trimmed}
+}
+
+func stringFields(obj map[string]any) map[string]string {
+	out := make(map[string]string, len(obj))
+	for k, v := range obj {
+		switch val := v.(type) {
+		case string:
+			out[k] = val
+		case nil:
+			out[k] = ""
+		default:
+			b, _ := json.Marshal(val)
+			out[k] = string(b)
+		}
+	}
+	return out
+}
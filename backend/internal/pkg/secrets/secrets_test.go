@@ -0,0 +1,123 @@
+package secrets
+
+import (
+	"context"
+	"errors"
+	"net/http"
+	"net/http/httptest"
+	"os"
+	"path/filepath"
+	"testing"
+	"time"
+
+	"github.com/stretchr/testify/require"
+)
+
+type countingProvider struct {
+	calls  int
+	fields map[string]string
+	err    error
+}
+
+func (p *countingProvider) Fetch(context.Context, string) (map[string]string, error) {
+	p.calls++
+	if p.err != nil {
+		return nil, p.err
+	}
+	return p.fields, nil
+}
+
+func TestParseRef(t *testing.T) {
+	path, field, err := ParseRef("secret://sub2api/db#password")
+	require.NoError(t, err)
+	require.Equal(t, "sub2api/db", path)
+	require.Equal(t, "password", field)
+
+	path, field, err = ParseRef("secret:///redis/")
+	require.NoError(t, err)
+	require.Equal(t, "redis", path)
+	require.Empty(t, field)
+
+	_, _, err = ParseRef("secret://#field")
+	require.Error(t, err)
+	require.False(t, IsRef("plain"))
+}
+
+func TestResolverCachesAndRefreshes(t *testing.T) {
+	provider := &countingProvider{fields: map[string]string{"password": "v1", "user": "app"}}
+	r := NewResolver(provider, time.Minute)
+	now := time.Unix(1_700_000_000, 0)
+	r.now = func() time.Time { return now }
+
+	v, err := r.Resolve(context.Background(), "secret://db#password")
+	require.NoError(t, err)
+	require.Equal(t, "v1", v)
+	_, err = r.Resolve(context.Background(), "secret://db#user")
+	require.NoError(t, err)
+	require.Equal(t, 1, provider.calls)
+
+	// TTL 过期后重新拉取，感知轮换
+	provider.fields = map[string]string{"password": "v2"}
+	now = now.Add(2 * time.Minute)
+	v, err = r.Resolve(context.Background(), "secret://db#password")
+	require.NoError(t, err)
+	require.Equal(t, "v2", v)
+
+	// 后端失败时继续使用缓存值
+	provider.err = errors.New("vault sealed")
+	now = now.Add(2 * time.Minute)
+	v, err = r.Resolve(context.Background(), "secret://db#password")
+	require.NoError(t, err)
+	require.Equal(t, "v2", v)
+
+	_, err = r.Resolve(context.Background(), "secret://db#missing")
+	require.ErrorIs(t, err, ErrNotFound)
+}
+
+func TestVaultProviderReadsKVv2(t *testing.T) {
+	srv := httptest.NewServer(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		if r.Header.Get("X-Vault-Token") != "tok" {
+			w.WriteHeader(http.StatusForbidden)
+			return
+		}
+		if r.URL.Path != "/v1/kv/data/sub2api/db" {
+			w.WriteHeader(http.StatusNotFound)
+			return
+		}
+		_, _ = w.Write([]byte(`{"data":{"data":{"password":"s3cret","port":5432}}}`))
+	}))
+	defer srv.Close()
+
+	provider, err := NewVaultProvider(VaultConfig{Address: srv.URL, Token: "tok", Mount: "kv"})
+	require.NoError(t, err)
+	fields, err := provider.Fetch(context.Background(), "sub2api/db")
+	require.NoError(t, err)
+	require.Equal(t, "s3cret", fields["password"])
+	require.Equal(t, "5432", fields["port"])
+
+	_, err = provider.Fetch(context.Background(), "sub2api/other")
+	require.ErrorIs(t, err, ErrNotFound)
+}
+
+func TestFileProvider(t *testing.T) {
+	dir := t.TempDir()
+	require.NoError(t, os.MkdirAll(filepath.Join(dir, "redis"), 0o755))
+	require.NoError(t, os.WriteFile(filepath.Join(dir, "redis", "password"), []byte("pw\n"), 0o600))
+	require.NoError(t, os.WriteFile(filepath.Join(dir, "master"), []byte("abc\n"), 0o600))
+
+	provider, err := NewFileProvider(dir)
+	require.NoError(t, err)
+	r := NewResolver(provider, 0)
+
+	v, err := r.Resolve(context.Background(), "secret://redis#password")
+	require.NoError(t, err)
+	require.Equal(t, "pw", v)
+	v, err = r.Resolve(context.Background(), "secret://master")
+	require.NoError(t, err)
+	require.Equal(t, "abc", v)
+
+	_, err = provider.Fetch(context.Background(), "../etc/passwd")
+	require.Error(t, err)
+	_, err = provider.Fetch(context.Background(), "nope")
+	require.ErrorIs(t, err, ErrNotFound)
+}
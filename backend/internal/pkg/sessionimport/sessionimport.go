@@ -0,0 +1,249 @@
+// Package sessionimport 解析浏览器导出的 Cookie / HAR，用于导入网页会话类上游账号。
+//
+// 支持的格式：
+//   - HAR（浏览器开发者工具 "Save all as HAR"）：读取请求 / 响应 Cookie、Authorization 头与 User-Agent
+//   - JSON Cookie 数组（Cookie-Editor、EditThisCookie 等扩展的导出格式）
+//   - Netscape cookies.txt（curl / yt-dlp 等工具使用的格式）
+//   - 原始 Cookie 请求头（"a=1; b=2"），此时无法得知域名
+package sessionimport
+
+import (
+	"bufio"
+	"bytes"
+	"encoding/json"
+	"errors"
+	"net/url"
+	"strings"
+)
+
+// Format 导出内容的格式
+type Format string
+
+const (
+	FormatHAR         Format = "har"
+	FormatJSONCookies Format = "json"
+	FormatNetscape    Format = "netscape"
+	FormatCookieLine  Format = "cookie"
+)
+
+// ErrNoCookies 内容可以解析，但不包含任何 Cookie 或 Bearer token
+var ErrNoCookies = errors.New("no cookies or tokens found in the export")
+
+// Cookie 单个 Cookie；Domain 为空表示来源中没有域名信息（原始 Cookie 头）
+type Cookie struct {
+	Domain string
+	Name   string
+	Value  string
+}
+
+// Capture 从导出内容中提取的会话材料
+type Capture struct {
+	Format  Format
+	Cookies []Cookie
+	// BearerTokens 按主机名记录最后一次出现的 Authorization: Bearer 值（仅 HAR）
+	BearerTokens map[string]string
+	// UserAgent 最后一次出现的 User-Agent（仅 HAR），用于保持与浏览器一致的指纹
+	UserAgent string
+}
+
+// Parse 自动识别格式并解析
+func Parse(data []byte) (*Capture, error) {
+	data = bytes.TrimSpace(bytes.TrimPrefix(data, []byte("\xef\xbb\xbf")))
+	if len(data) == 0 {
+		return nil, errors.New("export is empty")
+	}
+
+	var capture *Capture
+	var err error
+	switch data[0] {
+	case '{':
+		capture, err = parseHAR(data)
+	case '[':
+		capture, err = parseJSONCookies(data)
+	default:
+		if looksLikeNetscape(data) {
+			capture = parseNetscape(data)
+		} else {
+			capture = parseCookieLine(string(data))
+		}
+	}
+	if err != nil {
+		return nil, err
+	}
+	if len(capture.Cookies) == 0 && len(capture.BearerTokens) == 0 {
+		return nil, ErrNoCookies
+	}
+	return capture, nil
+}
+
+// Cookie 返回匹配域名的 Cookie 值；domain 为空或 Cookie 无域名信息时只按名称匹配。
+// 同名 Cookie 出现多次时取最后一次（HAR 中越靠后越新）。
+func (c *Capture) Cookie(domain, name string) string {
+	value := ""
+	for _, ck := range c.Cookies {
+		if ck.Name == name && domainMatches(ck.Domain, domain) {
+			value = ck.Value
+		}
+	}
+	return value
+}
+
+// HasDomain 返回是否存在属于该域名的 Cookie 或 Bearer token
+func (c *Capture) HasDomain(domain string) bool {
+	for _, ck := range c.Cookies {
+		if ck.Domain != "" && domainMatches(ck.Domain, domain) {
+			return true
+		}
+	}
+	return c.BearerToken(domain) != ""
+}
+
+// BearerToken 返回属于该域名（含子域名）的 Bearer token
+func (c *Capture) BearerToken(domain string) string {
+	for host, token := range c.BearerTokens {
+		if domainMatches(host, domain) {
+			return token
+		}
+	}
+	return ""
+}
+
+// domainMatches cookieDomain 为 ".google.com" / "google.com" / "gemini.google.com" 时均匹配 domain "google.com"；
+// 反之 cookieDomain ".google.com" 也匹配 domain "gemini.google.com"（父域 Cookie 会发送给子域）。
+func domainMatches(cookieDomain, domain string) bool {
+	if cookieDomain == "" || domain == "" {
+		return true
+	}
+	cd := strings.ToLower(strings.TrimPrefix(cookieDomain, "."))
+	d := strings.ToLower(strings.TrimPrefix(domain, "."))
+	return cd == d || strings.HasSuffix(cd, "."+d) || strings.HasSuffix(d, "."+cd)
+}
+
+type harNameValue struct {
+	Name   string `json:"name"`
+	Value  string `json:"value"`
+	Domain string `json:"domain"`
+}
+
+type harMessage struct {
+	URL     string         `json:"url"`
+	Headers []harNameValue `json:"headers"`
+	Cookies []harNameValue `json:"cookies"`
+}
+
+type harFile struct {
+	Log *struct {
+		Entries []struct {
+			Request  harMessage `json:"request"`
+			Response harMessage `json:"response"`
+		} `json:"entries"`
+	} `json:"log"`
+}
+
+func parseHAR(data []byte) (*Capture, error) {
+	var har harFile
+	if err := json.Unmarshal(data, &har); err != nil {
+		return nil, errors.New("invalid HAR: " + err.Error())
+	}
+	if har.Log == nil {
+		return nil, errors.New("invalid HAR: missing log")
+	}
+
+	capture := &Capture{Format: FormatHAR, BearerTokens: map[string]string{}}
+	for _, entry := range har.Log.Entries {
+		host := ""
+		if u, err := url.Parse(entry.Request.URL); err == nil {
+			host = u.Hostname()
+		}
+		for _, ck := range entry.Request.Cookies {
+			capture.Cookies = append(capture.Cookies, Cookie{Domain: host, Name: ck.Name, Value: ck.Value})
+		}
+		for _, h := range entry.Request.Headers {
+			switch strings.ToLower(h.Name) {
+			case "authorization":
+				if token, ok := strings.CutPrefix(strings.TrimSpace(h.Value), "Bearer "); ok && host != "" && token != "" {
+					capture.BearerTokens[host] = token
+				}
+			case "user-agent":
+				capture.UserAgent = h.Value
+			case "cookie":
+				// 部分浏览器导出时 cookies 数组为空，只保留原始请求头
+				if len(entry.Request.Cookies) == 0 {
+					for _, ck := range parseCookieLine(h.Value).Cookies {
+						ck.Domain = host
+						capture.Cookies = append(capture.Cookies, ck)
+					}
+				}
+			}
+		}
+		// 响应中的 Set-Cookie 代表轮换后的新值，排在请求 Cookie 之后以覆盖旧值
+		for _, ck := range entry.Response.Cookies {
+			domain := ck.Domain
+			if domain == "" {
+				domain = host
+			}
+			capture.Cookies = append(capture.Cookies, Cookie{Domain: domain, Name: ck.Name, Value: ck.Value})
+		}
+	}
+	return capture, nil
+}
+
+func parseJSONCookies(data []byte) (*Capture, error) {
+	var items []harNameValue
+	if err := json.Unmarshal(data, &items); err != nil {
+		return nil, errors.New("invalid cookie JSON: " + err.Error())
+	}
+	capture := &Capture{Format: FormatJSONCookies}
+	for _, item := range items {
+		if item.Name == "" {
+			continue
+		}
+		capture.Cookies = append(capture.Cookies, Cookie{Domain: item.Domain, Name: item.Name, Value: item.Value})
+	}
+	return capture, nil
+}
+
+func looksLikeNetscape(data []byte) bool {
+	if bytes.HasPrefix(data, []byte("# Netscape HTTP Cookie File")) || bytes.HasPrefix(data, []byte("# HTTP Cookie File")) {
+		return true
+	}
+	firstLine, _, _ := bytes.Cut(data, []byte("\n"))
+	return bytes.Count(firstLine, []byte("\t")) >= 6
+}
+
+// parseNetscape 每行 7 列：domain, include_subdomains, path, secure, expiry, name, value；
+// "#HttpOnly_" 前缀表示 HttpOnly Cookie，不是注释。
+func parseNetscape(data []byte) *Capture {
+	capture := &Capture{Format: FormatNetscape}
+	scanner := bufio.NewScanner(bytes.NewReader(data))
+	scanner.Buffer(make([]byte, 0, 64*1024), 1<<20)
+	for scanner.Scan() {
+		line := strings.TrimRight(scanner.Text(), "\r")
+		line = strings.TrimPrefix(line, "#HttpOnly_")
+		if line == "" || strings.HasPrefix(line, "#") {
+			continue
+		}
+		fields := strings.Split(line, "\t")
+		if len(fields) < 7 {
+			continue
+		}
+		capture.Cookies = append(capture.Cookies, Cookie{Domain: fields[0], Name: fields[5], Value: fields[6]})
+	}
+	return capture
+}
+
+func parseCookieLine(line string) *Capture {
+	capture := &Capture{Format: FormatCookieLine}
+	line = strings.TrimSpace(line)
+	if name, rest, ok := strings.Cut(line, ":"); ok && strings.EqualFold(strings.TrimSpace(name), "cookie") {
+		line = rest
+	}
+	for _, part := range strings.Split(line, ";") {
+		name, value, ok := strings.Cut(strings.TrimSpace(part), "=")
+		if !ok || name == "" {
+			continue
+		}
+		capture.Cookies = append(capture.Cookies, Cookie{Name: strings.TrimSpace(name), Value: strings.TrimSpace(value)})
+	}
+	return capture
+}
@@ -0,0 +1,54 @@
+//go:build unit
+
+package sessionimport
+
+import (
+	"testing"
+
+	"github.com/stretchr/testify/require"
+)
+
+func TestParse_HAR(t *testing.T) {
+	har := `{"log":{"entries":[
+		{"request":{"url":"https://chatgpt.com/backend-api/me","headers":[
+			{"name":"Authorization","value":"Bearer eyJ.old"},
+			{"name":"User-Agent","value":"Mozilla/5.0 Test"}],
+			"cookies":[{"name":"oai-did","value":"dev-1"}]},
+		 "response":{"cookies":[]}},
+		{"request":{"url":"https://chatgpt.com/backend-api/conversations","headers":[
+			{"name":"authorization","value":"Bearer eyJ.new"},
+			{"name":"Cookie","value":"__Secure-next-auth.session-token=abc; oai-did=dev-1"}],
+			"cookies":[]},
+		 "response":{"cookies":[{"name":"__Secure-next-auth.session-token","value":"rotated","domain":".chatgpt.com"}]}}
+	]}}`
+	capture, err := Parse([]byte(har))
+	require.NoError(t, err)
+	require.Equal(t, FormatHAR, capture.Format)
+	require.Equal(t, "eyJ.new", capture.BearerToken("chatgpt.com"))
+	require.Equal(t, "Mozilla/5.0 Test", capture.UserAgent)
+	require.Equal(t, "rotated", capture.Cookie("chatgpt.com", "__Secure-next-auth.session-token"), "Set-Cookie overrides request cookie")
+	require.True(t, capture.HasDomain("chatgpt.com"))
+	require.False(t, capture.HasDomain("claude.ai"))
+}
+
+func TestParse_CookieFormats(t *testing.T) {
+	capture, err := Parse([]byte(`[{"domain":".google.com","name":"__Secure-1PSID","value":"g.a000"},{"domain":"claude.ai","name":"sessionKey","value":"sk-ant-sid01"}]`))
+	require.NoError(t, err)
+	require.Equal(t, FormatJSONCookies, capture.Format)
+	require.Equal(t, "g.a000", capture.Cookie("gemini.google.com", "__Secure-1PSID"))
+	require.Empty(t, capture.Cookie("google.com", "sessionKey"))
+
+	capture, err = Parse([]byte("# Netscape HTTP Cookie File\n#HttpOnly_.claude.ai\tTRUE\t/\tTRUE\t0\tsessionKey\tsk-ant-sid01\n"))
+	require.NoError(t, err)
+	require.Equal(t, FormatNetscape, capture.Format)
+	require.Equal(t, "sk-ant-sid01", capture.Cookie("claude.ai", "sessionKey"))
+
+	capture, err = Parse([]byte("Cookie: sessionKey=sk-ant-sid01; lastActiveOrg=org-1"))
+	require.NoError(t, err)
+	require.Equal(t, FormatCookieLine, capture.Format)
+	require.Equal(t, "org-1", capture.Cookie("claude.ai", "lastActiveOrg"))
+	require.False(t, capture.HasDomain("claude.ai"), "raw cookie headers carry no domain")
+
+	_, err = Parse([]byte(`{"log":{"entries":[]}}`))
+	require.ErrorIs(t, err, ErrNoCookies)
+}
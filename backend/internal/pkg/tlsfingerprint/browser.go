@@ -0,0 +1,146 @@
+package tlsfingerprint
+
+import (
+	"context"
+	"log/slog"
+	"net"
+	"sort"
+	"strings"
+
+	utls "github.com/refraction-networking/utls"
+)
+
+// HTTP2Settings describes the HTTP/2 connection preface sent after the TLS handshake.
+// Browsers differ in SETTINGS values and the initial WINDOW_UPDATE, which are part of
+// the HTTP/2 (Akamai) fingerprint. Zero values keep the Go defaults.
+type HTTP2Settings struct {
+	HeaderTableSize      uint32 // SETTINGS_HEADER_TABLE_SIZE
+	InitialWindowSize    uint32 // SETTINGS_INITIAL_WINDOW_SIZE (per stream)
+	ConnectionWindowSize uint32 // connection-level window (65535 + initial WINDOW_UPDATE)
+	MaxFrameSize         uint32 // SETTINGS_MAX_FRAME_SIZE
+	MaxHeaderListSize    uint32 // SETTINGS_MAX_HEADER_LIST_SIZE
+}
+
+// clientHelloIDs maps ClientHello preset names to the browser parrots shipped with utls.
+var clientHelloIDs = map[string]utls.ClientHelloID{
+	"chrome":  utls.HelloChrome_Auto,
+	"firefox": utls.HelloFirefox_Auto,
+	"safari":  utls.HelloSafari_Auto,
+	"edge":    utls.HelloEdge_Auto,
+	"ios":     utls.HelloIOS_Auto,
+}
+
+// builtinBrowserProfiles are selectable by name (per account) but never take part in
+// the accountID-based rotation, so existing Claude CLI accounts keep their fingerprint.
+var builtinBrowserProfiles = map[string]*Profile{
+	"chrome": {
+		Name:        "Chrome (latest)",
+		ClientHello: "chrome",
+		HTTP2: &HTTP2Settings{
+			HeaderTableSize:      65536,
+			InitialWindowSize:    6291456,
+			ConnectionWindowSize: 15728640,
+			MaxHeaderListSize:    262144,
+		},
+	},
+	"edge": {
+		Name:        "Edge (latest)",
+		ClientHello: "edge",
+		HTTP2: &HTTP2Settings{
+			HeaderTableSize:      65536,
+			InitialWindowSize:    6291456,
+			ConnectionWindowSize: 15728640,
+			MaxHeaderListSize:    262144,
+		},
+	},
+	"firefox": {
+		Name:        "Firefox (latest)",
+		ClientHello: "firefox",
+		HTTP2: &HTTP2Settings{
+			HeaderTableSize:      65536,
+			InitialWindowSize:    131072,
+			ConnectionWindowSize: 12582912,
+			MaxFrameSize:         16384,
+		},
+	},
+	"safari": {
+		Name:        "Safari (latest)",
+		ClientHello: "safari",
+		HTTP2: &HTTP2Settings{
+			InitialWindowSize:    2097152,
+			ConnectionWindowSize: 10485760,
+		},
+	},
+}
+
+// ClientHelloPresets returns the supported ClientHello preset names.
+func ClientHelloPresets() []string {
+	names := make([]string, 0, len(clientHelloIDs))
+	for name := range clientHelloIDs {
+		names = append(names, name)
+	}
+	sort.Strings(names)
+	return names
+}
+
+// IsClientHelloPreset reports whether name is a supported ClientHello preset.
+func IsClientHelloPreset(name string) bool {
+	_, ok := clientHelloIDs[strings.ToLower(strings.TrimSpace(name))]
+	return ok
+}
+
+// buildBrowserClientHelloSpec builds the ClientHello of a browser preset.
+// ALPN is rewritten to match the transport: h2 is only offered when the profile
+// carries HTTP/2 settings, otherwise the connection would negotiate a protocol
+// the HTTP/1.1 transport cannot speak.
+func buildBrowserClientHelloSpec(profile *Profile) (*utls.ClientHelloSpec, bool) {
+	id, ok := clientHelloIDs[strings.ToLower(strings.TrimSpace(profile.ClientHello))]
+	if !ok {
+		slog.Debug("tls_fingerprint_unknown_client_hello", "client_hello", profile.ClientHello)
+		return nil, false
+	}
+	spec, err := utls.UTLSIdToSpec(id)
+	if err != nil {
+		slog.Debug("tls_fingerprint_client_hello_spec_failed", "client_hello", profile.ClientHello, "error", err)
+		return nil, false
+	}
+
+	alpn := []string{"http/1.1"}
+	if profile.HTTP2 != nil {
+		alpn = []string{"h2", "http/1.1"}
+	}
+	for _, ext := range spec.Extensions {
+		if alpnExt, ok := ext.(*utls.ALPNExtension); ok {
+			alpnExt.AlpnProtocols = alpn
+		}
+	}
+	return &spec, true
+}
+
+type profileNameKey struct{}
+
+// WithProfileName selects a registry profile by name for requests made with ctx.
+// An empty name keeps the accountID-based selection.
+func WithProfileName(ctx context.Context, name string) context.Context {
+	if name == "" {
+		return ctx
+	}
+	return context.WithValue(ctx, profileNameKey{}, name)
+}
+
+// ProfileNameFromContext returns the profile name set by WithProfileName.
+func ProfileNameFromContext(ctx context.Context) string {
+	if ctx == nil {
+		return ""
+	}
+	name, _ := ctx.Value(profileNameKey{}).(string)
+	return name
+}
+
+// NegotiatedProtocol returns the ALPN protocol negotiated on a connection created by the dialers.
+func NegotiatedProtocol(conn net.Conn) string {
+	if uconn, ok := conn.(*utls.UConn); ok {
+		return uconn.ConnectionState().NegotiatedProtocol
+	}
+	return ""
+}
@@ -0,0 +1,69 @@
+package tlsfingerprint
+
+import (
+	"context"
+	"testing"
+
+	utls "github.com/refraction-networking/utls"
+)
+
+func alpnProtocols(spec *utls.ClientHelloSpec) []string {
+	for _, ext := range spec.Extensions {
+		if alpn, ok := ext.(*utls.ALPNExtension); ok {
+			return alpn.AlpnProtocols
+		}
+	}
+	return nil
+}
+
+func TestResolveProfile(t *testing.T) {
+	r := NewRegistry()
+	r.RegisterProfile("custom", &Profile{Name: "Custom"})
+
+	key, profile := r.ResolveProfile("chrome", 7)
+	if key != "chrome" || profile == nil || profile.ClientHello != "chrome" {
+		t.Fatalf("expected built-in chrome profile, got %q %+v", key, profile)
+	}
+
+	// Browser profiles do not take part in the accountID rotation
+	if r.ProfileCount() != 2 {
+		t.Errorf("expected 2 registered profiles, got %d", r.ProfileCount())
+	}
+	key, profile = r.ResolveProfile("", 1)
+	if profile == nil || key != r.ProfileNames()[1] {
+		t.Errorf("expected accountID-based selection, got %q", key)
+	}
+
+	key, _ = r.ResolveProfile("missing", 0)
+	if key != r.ProfileNames()[0] {
+		t.Errorf("unknown profile should fall back to rotation, got %q", key)
+	}
+}
+
+func TestBuildClientHelloSpecFromBrowserProfile(t *testing.T) {
+	spec := buildClientHelloSpecFromProfile(&Profile{ClientHello: "chrome"})
+	if got := alpnProtocols(spec); len(got) != 1 || got[0] != "http/1.1" {
+		t.Errorf("HTTP/1.1 profile should only offer http/1.1, got %v", got)
+	}
+
+	spec = buildClientHelloSpecFromProfile(builtinBrowserProfiles["firefox"])
+	if got := alpnProtocols(spec); len(got) != 2 || got[0] != "h2" {
+		t.Errorf("HTTP/2 profile should offer h2 first, got %v", got)
+	}
+
+	// Unknown presets fall back to the Claude CLI spec
+	spec = buildClientHelloSpecFromProfile(&Profile{ClientHello: "netscape"})
+	if len(spec.CipherSuites) != len(defaultCipherSuites) {
+		t.Errorf("expected default cipher suites, got %d", len(spec.CipherSuites))
+	}
+}
+
+func TestProfileNameContext(t *testing.T) {
+	ctx := WithProfileName(context.Background(), "safari")
+	if got := ProfileNameFromContext(ctx); got != "safari" {
+		t.Errorf("expected safari, got %q", got)
+	}
+	if got := ProfileNameFromContext(WithProfileName(context.Background(), "")); got != "" {
+		t.Errorf("expected empty profile name, got %q", got)
+	}
+}
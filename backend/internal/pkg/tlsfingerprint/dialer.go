@@ -23,6 +23,11 @@ type Profile struct {
 	Curves       []uint16
 	PointFormats []uint8
 	EnableGREASE bool
+	// ClientHello selects a browser preset (chrome/firefox/safari/edge/ios).
+	// When set, the preset replaces CipherSuites/Curves/PointFormats/EnableGREASE.
+	ClientHello string
+	// HTTP2 enables HTTP/2 with browser-like settings; nil keeps HTTP/1.1.
+	HTTP2 *HTTP2Settings
 }
 
 // Dialer creates TLS connections with custom fingerprints.
@@ -494,6 +499,12 @@ func toUTLSCurves(curves []uint16) []utls.CurveID {
 // buildClientHelloSpecFromProfile constructs ClientHelloSpec from a Profile.
 // This is a standalone function that can be used by both Dialer and HTTPProxyDialer.
 func buildClientHelloSpecFromProfile(profile *Profile) *utls.ClientHelloSpec {
+	if profile != nil && profile.ClientHello != "" {
+		if spec, ok := buildBrowserClientHelloSpec(profile); ok {
+			return spec
+		}
+	}
+
 	// Get cipher suites
 	var cipherSuites []uint16
 	if profile != nil && len(profile.CipherSuites) > 0 {
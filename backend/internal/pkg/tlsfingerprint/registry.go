@@ -53,6 +53,16 @@ func NewRegistryFromConfig(cfg *config.TLSFingerprintConfig) *Registry {
 			CipherSuites: profileCfg.CipherSuites,
 			Curves:       profileCfg.Curves,
 			PointFormats: profileCfg.PointFormats,
+			ClientHello:  profileCfg.ClientHello,
+		}
+		if h2 := profileCfg.HTTP2; h2.Enabled {
+			profile.HTTP2 = &HTTP2Settings{
+				HeaderTableSize:      h2.HeaderTableSize,
+				InitialWindowSize:    h2.InitialWindowSize,
+				ConnectionWindowSize: h2.ConnectionWindowSize,
+				MaxFrameSize:         h2.MaxFrameSize,
+				MaxHeaderListSize:    h2.MaxHeaderListSize,
+			}
 		}
 
 		// If the profile has empty values, they will use defaults in dialer
@@ -94,11 +104,39 @@ func (r *Registry) RegisterProfile(name string, profile *Profile) {
 }
 
 // GetProfile returns a profile by name.
-// Returns nil if the profile does not exist.
+// Registered profiles take precedence over the built-in browser profiles
+// (chrome, edge, firefox, safari). Returns nil if the profile does not exist.
 func (r *Registry) GetProfile(name string) *Profile {
 	r.mu.RLock()
 	defer r.mu.RUnlock()
-	return r.profiles[name]
+	if profile, ok := r.profiles[name]; ok {
+		return profile
+	}
+	return builtinBrowserProfiles[name]
+}
+
+// ResolveProfile returns the profile explicitly selected by name, falling back to
+// the accountID-based selection when name is empty or unknown.
+// The returned key identifies the profile for connection pool isolation.
+func (r *Registry) ResolveProfile(name string, accountID int64) (string, *Profile) {
+	if name != "" {
+		if profile := r.GetProfile(name); profile != nil {
+			return name, profile
+		}
+		slog.Warn("tls_fingerprint_profile_not_found", "profile", name, "account_id", accountID)
+	}
+
+	r.mu.RLock()
+	defer r.mu.RUnlock()
+	if len(r.profileNames) == 0 {
+		return "", nil
+	}
+	idx := accountID
+	if idx < 0 {
+		idx = -idx
+	}
+	selectedName := r.profileNames[int(idx%int64(len(r.profileNames)))]
+	return selectedName, r.profiles[selectedName]
 }
 
 // GetDefaultProfile returns the built-in default profile.
@@ -0,0 +1,108 @@
+package repository
+
+import (
+	"context"
+	"database/sql"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/service"
+)
+
+type accountHealthRepository struct {
+	db *sql.DB
+}
+
+// NewAccountHealthRepository 创建账号健康度汇总表查询仓储
+func NewAccountHealthRepository(sqlDB *sql.DB) service.AccountHealthRepository {
+	return &accountHealthRepository{db: sqlDB}
+}
+
+func (r *accountHealthRepository) ListHourly(ctx context.Context, accountID int64, start, end time.Time) ([]service.AccountHealthBucket, error) {
+	rows, err := r.db.QueryContext(ctx, `
+SELECT bucket_start, account_id, success_count, error_count, output_tokens,
+       duration_p50_ms, duration_p95_ms, duration_p99_ms,
+       first_token_p50_ms, first_token_p95_ms, first_token_p99_ms
+FROM account_health_rollup_hourly
+WHERE account_id = $1 AND bucket_start >= $2 AND bucket_start < $3
+ORDER BY bucket_start`, accountID, start.UTC(), end.UTC())
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	out := make([]service.AccountHealthBucket, 0)
+	for rows.Next() {
+		var (
+			b                               service.AccountHealthBucket
+			d50, d95, d99, ft50, ft95, ft99 sql.NullInt64
+		)
+		if err := rows.Scan(&b.BucketStart, &b.AccountID, &b.SuccessCount, &b.ErrorCount, &b.OutputTokens,
+			&d50, &d95, &d99, &ft50, &ft95, &ft99); err != nil {
+			return nil, err
+		}
+		b.DurationP50Ms = nullInt64ToIntPtr(d50)
+		b.DurationP95Ms = nullInt64ToIntPtr(d95)
+		b.DurationP99Ms = nullInt64ToIntPtr(d99)
+		b.FirstTokenP50Ms = nullInt64ToIntPtr(ft50)
+		b.FirstTokenP95Ms = nullInt64ToIntPtr(ft95)
+		b.FirstTokenP99Ms = nullInt64ToIntPtr(ft99)
+		out = append(out, b)
+	}
+	if err := rows.Err(); err != nil {
+		return nil, err
+	}
+	return out, nil
+}
+
+func (r *accountHealthRepository) ListSummaries(ctx context.Context, start, end time.Time) ([]service.AccountHealthSummary, error) {
+	rows, err := r.db.QueryContext(ctx, `
+SELECT h.account_id,
+       COALESCE(a.name, ''),
+       COALESCE(a.platform, ''),
+       SUM(h.success_count),
+       SUM(h.error_count),
+       SUM(h.output_tokens),
+       SUM(h.duration_p95_ms::float8 * h.success_count) FILTER (WHERE h.duration_p95_ms IS NOT NULL)
+         / NULLIF(SUM(h.success_count) FILTER (WHERE h.duration_p95_ms IS NOT NULL), 0),
+       SUM(h.first_token_p95_ms::float8 * h.success_count) FILTER (WHERE h.first_token_p95_ms IS NOT NULL)
+         / NULLIF(SUM(h.success_count) FILTER (WHERE h.first_token_p95_ms IS NOT NULL), 0)
+FROM account_health_rollup_hourly h
+LEFT JOIN accounts a ON a.id = h.account_id
+WHERE h.bucket_start >= $1 AND h.bucket_start < $2
+GROUP BY h.account_id, a.name, a.platform
+ORDER BY h.account_id`, start.UTC(), end.UTC())
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	out := make([]service.AccountHealthSummary, 0)
+	for rows.Next() {
+		var (
+			s               service.AccountHealthSummary
+			duration, first sql.NullFloat64
+		)
+		if err := rows.Scan(&s.AccountID, &s.AccountName, &s.Platform, &s.SuccessCount, &s.ErrorCount, &s.OutputTokens, &duration, &first); err != nil {
+			return nil, err
+		}
+		if duration.Valid {
+			s.DurationP95Ms = &duration.Float64
+		}
+		if first.Valid {
+			s.FirstTokenP95Ms = &first.Float64
+		}
+		out = append(out, s)
+	}
+	if err := rows.Err(); err != nil {
+		return nil, err
+	}
+	return out, nil
+}
+
+func nullInt64ToIntPtr(v sql.NullInt64) *int {
+	if !v.Valid {
+		return nil
+	}
+	n := int(v.Int64)
+	return &n
+}
@@ -0,0 +1,233 @@
+package repository
+
+import (
+	"context"
+	"database/sql"
+	"errors"
+
+	"github.com/Wei-Shaw/sub2api/internal/service"
+	"github.com/lib/pq"
+)
+
+const (
+	accountShardColumns     = `s.id, s.name, s.description, s.isolated, s.isolated_reason, s.isolated_at, s.created_at, s.updated_at, COALESCE(m.account_ids, '{}')`
+	accountShardFrom        = ` FROM account_shards s LEFT JOIN (SELECT shard_id, array_agg(account_id ORDER BY account_id) AS account_ids FROM account_shard_members GROUP BY shard_id) m ON m.shard_id = s.id`
+	accountShardRuleColumns = `id, shard_id, priority, api_key_id, group_id, model_pattern, enabled, created_at, updated_at`
+)
+
+type accountShardRepository struct {
+	db *sql.DB
+}
+
+// NewAccountShardRepository 创建账号分片仓储
+func NewAccountShardRepository(sqlDB *sql.DB) service.AccountShardRepository {
+	return &accountShardRepository{db: sqlDB}
+}
+
+func (r *accountShardRepository) ListShards(ctx context.Context) ([]*service.AccountShard, error) {
+	rows, err := r.db.QueryContext(ctx, `SELECT `+accountShardColumns+accountShardFrom+` ORDER BY s.id`)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	out := make([]*service.AccountShard, 0)
+	for rows.Next() {
+		shard, err := scanAccountShard(rows)
+		if err != nil {
+			return nil, err
+		}
+		out = append(out, shard)
+	}
+	if err := rows.Err(); err != nil {
+		return nil, err
+	}
+	return out, nil
+}
+
+func (r *accountShardRepository) GetShard(ctx context.Context, id int64) (*service.AccountShard, error) {
+	shard, err := scanAccountShard(r.db.QueryRowContext(ctx, `SELECT `+accountShardColumns+accountShardFrom+` WHERE s.id = $1`, id))
+	if errors.Is(err, sql.ErrNoRows) {
+		return nil, service.ErrAccountShardNotFound
+	}
+	return shard, err
+}
+
+func (r *accountShardRepository) CreateShard(ctx context.Context, shard *service.AccountShard) error {
+	err := r.db.QueryRowContext(ctx, `
+INSERT INTO account_shards (name, description, isolated, isolated_reason, isolated_at)
+VALUES ($1, $2, $3, $4, $5)
+RETURNING id, created_at, updated_at
+`, shard.Name, shard.Description, shard.Isolated, shard.IsolatedReason, shard.IsolatedAt).Scan(&shard.ID, &shard.CreatedAt, &shard.UpdatedAt)
+	if isUniqueConstraintViolation(err) {
+		return service.ErrAccountShardExists
+	}
+	if err == nil {
+		shard.AccountIDs = []int64{}
+	}
+	return err
+}
+
+func (r *accountShardRepository) UpdateShard(ctx context.Context, shard *service.AccountShard) error {
+	err := r.db.QueryRowContext(ctx, `
+UPDATE account_shards SET
+	name = $2,
+	description = $3,
+	isolated = $4,
+	isolated_reason = $5,
+	isolated_at = $6,
+	updated_at = NOW()
+WHERE id = $1
+RETURNING created_at, updated_at
+`, shard.ID, shard.Name, shard.Description, shard.Isolated, shard.IsolatedReason, shard.IsolatedAt).Scan(&shard.CreatedAt, &shard.UpdatedAt)
+	switch {
+	case errors.Is(err, sql.ErrNoRows):
+		return service.ErrAccountShardNotFound
+	case isUniqueConstraintViolation(err):
+		return service.ErrAccountShardExists
+	}
+	return err
+}
+
+func (r *accountShardRepository) DeleteShard(ctx context.Context, id int64) error {
+	res, err := r.db.ExecContext(ctx, `DELETE FROM account_shards WHERE id = $1`, id)
+	if err != nil {
+		return err
+	}
+	if n, err := res.RowsAffected(); err == nil && n == 0 {
+		return service.ErrAccountShardNotFound
+	}
+	return nil
+}
+
+func (r *accountShardRepository) AddMembers(ctx context.Context, shardID int64, accountIDs []int64) (int64, error) {
+	if len(accountIDs) == 0 {
+		return 0, nil
+	}
+	// 仅写入存在的账号；已属于其他分片的账号直接改挂到目标分片
+	res, err := r.db.ExecContext(ctx, `
+INSERT INTO account_shard_members (account_id, shard_id)
+SELECT a.id, $1 FROM accounts a WHERE a.id = ANY($2) AND a.deleted_at IS NULL
+ON CONFLICT (account_id) DO UPDATE SET shard_id = EXCLUDED.shard_id, created_at = NOW()
+WHERE account_shard_members.shard_id <> EXCLUDED.shard_id
+`, shardID, pq.Array(accountIDs))
+	if err != nil {
+		return 0, err
+	}
+	return res.RowsAffected()
+}
+
+func (r *accountShardRepository) RemoveMembers(ctx context.Context, shardID int64, accountIDs []int64) (int64, error) {
+	if len(accountIDs) == 0 {
+		return 0, nil
+	}
+	res, err := r.db.ExecContext(ctx, `DELETE FROM account_shard_members WHERE shard_id = $1 AND account_id = ANY($2)`, shardID, pq.Array(accountIDs))
+	if err != nil {
+		return 0, err
+	}
+	return res.RowsAffected()
+}
+
+func (r *accountShardRepository) ListRules(ctx context.Context) ([]*service.AccountShardRule, error) {
+	rows, err := r.db.QueryContext(ctx, `SELECT `+accountShardRuleColumns+` FROM account_shard_rules ORDER BY priority, id`)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	out := make([]*service.AccountShardRule, 0)
+	for rows.Next() {
+		rule, err := scanAccountShardRule(rows)
+		if err != nil {
+			return nil, err
+		}
+		out = append(out, rule)
+	}
+	if err := rows.Err(); err != nil {
+		return nil, err
+	}
+	return out, nil
+}
+
+func (r *accountShardRepository) GetRule(ctx context.Context, id int64) (*service.AccountShardRule, error) {
+	rule, err := scanAccountShardRule(r.db.QueryRowContext(ctx, `SELECT `+accountShardRuleColumns+` FROM account_shard_rules WHERE id = $1`, id))
+	if errors.Is(err, sql.ErrNoRows) {
+		return nil, service.ErrAccountShardRuleNotFound
+	}
+	return rule, err
+}
+
+func (r *accountShardRepository) CreateRule(ctx context.Context, rule *service.AccountShardRule) error {
+	return r.db.QueryRowContext(ctx, `
+INSERT INTO account_shard_rules (shard_id, priority, api_key_id, group_id, model_pattern, enabled)
+VALUES ($1, $2, $3, $4, $5, $6)
+RETURNING id, created_at, updated_at
+`, rule.ShardID, rule.Priority, rule.APIKeyID, rule.GroupID, rule.ModelPattern, rule.Enabled).Scan(&rule.ID, &rule.CreatedAt, &rule.UpdatedAt)
+}
+
+func (r *accountShardRepository) UpdateRule(ctx context.Context, rule *service.AccountShardRule) error {
+	err := r.db.QueryRowContext(ctx, `
+UPDATE account_shard_rules SET
+	shard_id = $2,
+	priority = $3,
+	api_key_id = $4,
+	group_id = $5,
+	model_pattern = $6,
+	enabled = $7,
+	updated_at = NOW()
+WHERE id = $1
+RETURNING created_at, updated_at
+`, rule.ID, rule.ShardID, rule.Priority, rule.APIKeyID, rule.GroupID, rule.ModelPattern, rule.Enabled).Scan(&rule.CreatedAt, &rule.UpdatedAt)
+	if errors.Is(err, sql.ErrNoRows) {
+		return service.ErrAccountShardRuleNotFound
+	}
+	return err
+}
+
+func (r *accountShardRepository) DeleteRule(ctx context.Context, id int64) error {
+	res, err := r.db.ExecContext(ctx, `DELETE FROM account_shard_rules WHERE id = $1`, id)
+	if err != nil {
+		return err
+	}
+	if n, err := res.RowsAffected(); err == nil && n == 0 {
+		return service.ErrAccountShardRuleNotFound
+	}
+	return nil
+}
+
+func scanAccountShard(row interface{ Scan(...any) error }) (*service.AccountShard, error) {
+	var (
+		shard      service.AccountShard
+		isolatedAt sql.NullTime
+		accountIDs pq.Int64Array
+	)
+	if err := row.Scan(&shard.ID, &shard.Name, &shard.Description, &shard.Isolated, &shard.IsolatedReason, &isolatedAt, &shard.CreatedAt, &shard.UpdatedAt, &accountIDs); err != nil {
+		return nil, err
+	}
+	if isolatedAt.Valid {
+		shard.IsolatedAt = &isolatedAt.Time
+	}
+	shard.AccountIDs = []int64(accountIDs)
+	if shard.AccountIDs == nil {
+		shard.AccountIDs = []int64{}
+	}
+	return &shard, nil
+}
+
+func scanAccountShardRule(row interface{ Scan(...any) error }) (*service.AccountShardRule, error) {
+	var (
+		rule     service.AccountShardRule
+		apiKeyID sql.NullInt64
+		groupID  sql.NullInt64
+	)
+	if err := row.Scan(&rule.ID, &rule.ShardID, &rule.Priority, &apiKeyID, &groupID, &rule.ModelPattern, &rule.Enabled, &rule.CreatedAt, &rule.UpdatedAt); err != nil {
+		return nil, err
+	}
+	if apiKeyID.Valid {
+		rule.APIKeyID = &apiKeyID.Int64
+	}
+	if groupID.Valid {
+		rule.GroupID = &groupID.Int64
+	}
+	return &rule, nil
+}
@@ -0,0 +1,41 @@
+package repository
+
+import (
+	"context"
+	"strconv"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/service"
+	"github.com/redis/go-redis/v9"
+)
+
+const accountStateLockKeyPrefix = "account:state_lock:"
+
+// accountStateUnlockScript 仅当锁仍由自己持有时才删除，避免误删过期后被其他副本取得的锁
+var accountStateUnlockScript = redis.NewScript(`
+if redis.call("GET", KEYS[1]) == ARGV[1] then
+  return redis.call("DEL", KEYS[1])
+end
+return 0
+`)
+
+type accountStateLocker struct {
+	rdb *redis.Client
+}
+
+// NewAccountStateLocker 创建基于 Redis 的账号状态锁（冷却迁移与槽位占用共用）
+func NewAccountStateLocker(rdb *redis.Client) service.AccountStateLocker {
+	return &accountStateLocker{rdb: rdb}
+}
+
+func accountStateLockKey(accountID int64) string {
+	return accountStateLockKeyPrefix + strconv.FormatInt(accountID, 10)
+}
+
+func (l *accountStateLocker) TryLock(ctx context.Context, accountID int64, owner string, ttl time.Duration) (bool, error) {
+	return l.rdb.SetNX(ctx, accountStateLockKey(accountID), owner, ttl).Result()
+}
+
+func (l *accountStateLocker) Unlock(ctx context.Context, accountID int64, owner string) error {
+	return accountStateUnlockScript.Run(ctx, l.rdb, []string{accountStateLockKey(accountID)}, owner).Err()
+}
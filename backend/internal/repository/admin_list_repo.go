@@ -0,0 +1,295 @@
+package repository
+
+import (
+	"context"
+	"database/sql"
+	"fmt"
+	"strings"
+
+	dbent "github.com/Wei-Shaw/sub2api/ent"
+	"github.com/Wei-Shaw/sub2api/ent/apikey"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/usagestats"
+	"github.com/Wei-Shaw/sub2api/internal/service"
+)
+
+// cursorColumn 可用于游标分页的排序列
+type cursorColumn struct {
+	expr string // SQL 列表达式
+	cast string // 游标值在 SQL 中的类型转换
+}
+
+var (
+	accountCursorColumns = map[string]cursorColumn{
+		"id":         {expr: "a.id", cast: "bigint"},
+		"name":       {expr: "a.name", cast: "text"},
+		"priority":   {expr: "a.priority", cast: "int"},
+		"created_at": {expr: "a.created_at", cast: "timestamptz"},
+	}
+	apiKeyCursorColumns = map[string]cursorColumn{
+		"id":         {expr: "k.id", cast: "bigint"},
+		"name":       {expr: "k.name", cast: "text"},
+		"created_at": {expr: "k.created_at", cast: "timestamptz"},
+	}
+	usageLogCursorColumns = map[string]cursorColumn{
+		"id":         {expr: "id", cast: "bigint"},
+		"created_at": {expr: "created_at", cast: "timestamptz"},
+	}
+)
+
+type adminListRepository struct {
+	client   *dbent.Client
+	sql      sqlExecutor
+	accounts *accountRepository
+	usage    *usageLogRepository
+}
+
+// NewAdminListRepository 创建管理后台游标分页列表仓储。
+// 列表查询均为只读，readDB 非空时原生 SQL 查询路由到只读副本。
+func NewAdminListRepository(client *dbent.Client, sqlDB *sql.DB, readDB *ReadDB, cipher *CredentialCipher) service.AdminListRepository {
+	var sqlq sqlExecutor = sqlDB
+	if readDB != nil {
+		sqlq = readDB
+	}
+	return &adminListRepository{
+		client:   client,
+		sql:      sqlq,
+		accounts: newAccountRepositoryWithSQL(client, sqlq, nil, cipher),
+		usage:    newUsageLogRepositoryWithSQL(client, sqlq),
+	}
+}
+
+// applyCursor 追加 keyset 条件并返回 ORDER BY / LIMIT 子句。
+// 排序列存在重复值时以 ID 作为第二排序键，使用行比较 (col, id) < (v, id) 保证翻页稳定。
+func applyCursor(columns map[string]cursorColumn, idExpr string, q service.CursorQuery, conditions []string, args []any) ([]string, []any, string, error) {
+	col, ok := columns[q.SortBy]
+	if !ok {
+		return nil, nil, "", service.ErrListInvalidSort
+	}
+	op, dir := ">", "ASC"
+	if q.Desc {
+		op, dir = "<", "DESC"
+	}
+
+	if q.After != nil {
+		if col.expr == idExpr {
+			args = append(args, q.After.ID)
+			conditions = append(conditions, fmt.Sprintf("%s %s $%d", idExpr, op, len(args)))
+		} else {
+			args = append(args, q.After.Value, q.After.ID)
+			conditions = append(conditions, fmt.Sprintf("(%s, %s) %s ($%d::%s, $%d)", col.expr, idExpr, op, len(args)-1, col.cast, len(args)))
+		}
+	}
+
+	order := fmt.Sprintf("ORDER BY %s %s", col.expr, dir)
+	if col.expr != idExpr {
+		order += fmt.Sprintf(", %s %s", idExpr, dir)
+	}
+	args = append(args, q.Limit)
+	order += fmt.Sprintf(" LIMIT $%d", len(args))
+	return conditions, args, order, nil
+}
+
+func (r *adminListRepository) queryIDs(ctx context.Context, query string, args []any) ([]int64, error) {
+	rows, err := r.sql.QueryContext(ctx, query, args...)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	ids := make([]int64, 0)
+	for rows.Next() {
+		var id int64
+		if err := rows.Scan(&id); err != nil {
+			return nil, err
+		}
+		ids = append(ids, id)
+	}
+	if err := rows.Err(); err != nil {
+		return nil, err
+	}
+	return ids, nil
+}
+
+// ListAccountsByCursor 先按过滤/排序条件取出 ID，再复用账号仓储批量加载完整数据（保持顺序）
+func (r *adminListRepository) ListAccountsByCursor(ctx context.Context, filter service.AccountListFilter, q service.CursorQuery) ([]service.Account, error) {
+	conditions := []string{"a.deleted_at IS NULL"}
+	args := make([]any, 0, 8)
+
+	if filter.Platform != "" {
+		args = append(args, filter.Platform)
+		conditions = append(conditions, fmt.Sprintf("a.platform = $%d", len(args)))
+	}
+	if filter.Type != "" {
+		args = append(args, filter.Type)
+		conditions = append(conditions, fmt.Sprintf("a.type = $%d", len(args)))
+	}
+	switch filter.Status {
+	case "":
+	case "rate_limited":
+		conditions = append(conditions, "a.rate_limit_reset_at > NOW()")
+	default:
+		args = append(args, filter.Status)
+		conditions = append(conditions, fmt.Sprintf("a.status = $%d", len(args)))
+	}
+	if filter.Search != "" {
+		args = append(args, "%"+filter.Search+"%")
+		conditions = append(conditions, fmt.Sprintf("a.name ILIKE $%d", len(args)))
+	}
+	if filter.GroupID > 0 {
+		args = append(args, filter.GroupID)
+		conditions = append(conditions, fmt.Sprintf("EXISTS (SELECT 1 FROM account_groups ag WHERE ag.account_id = a.id AND ag.group_id = $%d)", len(args)))
+	}
+	if filter.CreatedFrom != nil {
+		args = append(args, *filter.CreatedFrom)
+		conditions = append(conditions, fmt.Sprintf("a.created_at >= $%d", len(args)))
+	}
+	if filter.CreatedTo != nil {
+		args = append(args, *filter.CreatedTo)
+		conditions = append(conditions, fmt.Sprintf("a.created_at <= $%d", len(args)))
+	}
+
+	conditions, args, order, err := applyCursor(accountCursorColumns, "a.id", q, conditions, args)
+	if err != nil {
+		return nil, err
+	}
+	ids, err := r.queryIDs(ctx, "SELECT a.id FROM accounts a "+buildWhere(conditions)+" "+order, args)
+	if err != nil {
+		return nil, err
+	}
+
+	accounts, err := r.accounts.GetByIDs(ctx, ids)
+	if err != nil {
+		return nil, err
+	}
+	out := make([]service.Account, 0, len(accounts))
+	for _, acc := range accounts {
+		out = append(out, *acc)
+	}
+	return out, nil
+}
+
+// ListAPIKeysByCursor 游标分页列出全部用户的 API Key
+func (r *adminListRepository) ListAPIKeysByCursor(ctx context.Context, filter service.APIKeyListFilter, q service.CursorQuery) ([]service.APIKey, error) {
+	conditions := []string{"k.deleted_at IS NULL"}
+	args := make([]any, 0, 8)
+
+	if filter.UserID > 0 {
+		args = append(args, filter.UserID)
+		conditions = append(conditions, fmt.Sprintf("k.user_id = $%d", len(args)))
+	}
+	if filter.GroupID > 0 {
+		args = append(args, filter.GroupID)
+		conditions = append(conditions, fmt.Sprintf("k.group_id = $%d", len(args)))
+	}
+	if filter.Status != "" {
+		args = append(args, filter.Status)
+		conditions = append(conditions, fmt.Sprintf("k.status = $%d", len(args)))
+	}
+	if filter.Search != "" {
+		// 名称模糊匹配，或按 Key 前缀匹配（前缀查询可利用 key 索引）
+		args = append(args, "%"+filter.Search+"%", escapeLikePattern(filter.Search)+"%")
+		conditions = append(conditions, fmt.Sprintf("(k.name ILIKE $%d OR k.key LIKE $%d)", len(args)-1, len(args)))
+	}
+	if filter.CreatedFrom != nil {
+		args = append(args, *filter.CreatedFrom)
+		conditions = append(conditions, fmt.Sprintf("k.created_at >= $%d", len(args)))
+	}
+	if filter.CreatedTo != nil {
+		args = append(args, *filter.CreatedTo)
+		conditions = append(conditions, fmt.Sprintf("k.created_at <= $%d", len(args)))
+	}
+
+	conditions, args, order, err := applyCursor(apiKeyCursorColumns, "k.id", q, conditions, args)
+	if err != nil {
+		return nil, err
+	}
+	ids, err := r.queryIDs(ctx, "SELECT k.id FROM api_keys k "+buildWhere(conditions)+" "+order, args)
+	if err != nil {
+		return nil, err
+	}
+	if len(ids) == 0 {
+		return []service.APIKey{}, nil
+	}
+
+	keys, err := r.client.APIKey.Query().
+		Where(apikey.IDIn(ids...)).
+		WithUser().
+		WithGroup().
+		All(ctx)
+	if err != nil {
+		return nil, err
+	}
+	byID := make(map[int64]*dbent.APIKey, len(keys))
+	for _, k := range keys {
+		byID[k.ID] = k
+	}
+	out := make([]service.APIKey, 0, len(ids))
+	for _, id := range ids {
+		if k, ok := byID[id]; ok {
+			out = append(out, *apiKeyEntityToService(k))
+		}
+	}
+	return out, nil
+}
+
+// ListUsageLogsByCursor 游标分页列出使用记录（过滤条件与 offset 分页列表一致）
+func (r *adminListRepository) ListUsageLogsByCursor(ctx context.Context, filter usagestats.UsageLogFilters, q service.CursorQuery) ([]service.UsageLog, error) {
+	conditions := make([]string, 0, 10)
+	args := make([]any, 0, 10)
+
+	if filter.UserID > 0 {
+		args = append(args, filter.UserID)
+		conditions = append(conditions, fmt.Sprintf("user_id = $%d", len(args)))
+	}
+	if filter.APIKeyID > 0 {
+		args = append(args, filter.APIKeyID)
+		conditions = append(conditions, fmt.Sprintf("api_key_id = $%d", len(args)))
+	}
+	if filter.AccountID > 0 {
+		args = append(args, filter.AccountID)
+		conditions = append(conditions, fmt.Sprintf("account_id = $%d", len(args)))
+	}
+	if filter.GroupID > 0 {
+		args = append(args, filter.GroupID)
+		conditions = append(conditions, fmt.Sprintf("group_id = $%d", len(args)))
+	}
+	if filter.Model != "" {
+		args = append(args, filter.Model)
+		conditions = append(conditions, fmt.Sprintf("model = $%d", len(args)))
+	}
+	if filter.Stream != nil {
+		args = append(args, *filter.Stream)
+		conditions = append(conditions, fmt.Sprintf("stream = $%d", len(args)))
+	}
+	if filter.BillingType != nil {
+		args = append(args, int16(*filter.BillingType))
+		conditions = append(conditions, fmt.Sprintf("billing_type = $%d", len(args)))
+	}
+	if filter.StartTime != nil {
+		args = append(args, *filter.StartTime)
+		conditions = append(conditions, fmt.Sprintf("created_at >= $%d", len(args)))
+	}
+	if filter.EndTime != nil {
+		args = append(args, *filter.EndTime)
+		conditions = append(conditions, fmt.Sprintf("created_at <= $%d", len(args)))
+	}
+
+	conditions, args, order, err := applyCursor(usageLogCursorColumns, "id", q, conditions, args)
+	if err != nil {
+		return nil, err
+	}
+	query := fmt.Sprintf("SELECT %s FROM usage_logs %s %s", usageLogSelectColumns, buildWhere(conditions), order)
+	logs, err := r.usage.queryUsageLogs(ctx, query, args...)
+	if err != nil {
+		return nil, err
+	}
+	if err := r.usage.hydrateUsageLogAssociations(ctx, logs); err != nil {
+		return nil, err
+	}
+	return logs, nil
+}
+
+// escapeLikePattern 转义 LIKE 通配符，避免用户输入的 % / _ 被当作通配符
+func escapeLikePattern(s string) string {
+	return strings.NewReplacer(`\`, `\\`, `%`, `\%`, `_`, `\_`).Replace(s)
+}
@@ -0,0 +1,86 @@
+package repository
+
+import (
+	"context"
+	"database/sql"
+	"errors"
+
+	"github.com/Wei-Shaw/sub2api/internal/service"
+	"github.com/lib/pq"
+)
+
+type adminUserRepository struct {
+	db *sql.DB
+}
+
+// NewAdminUserRepository 创建后台账号仓储
+func NewAdminUserRepository(sqlDB *sql.DB) service.AdminUserRepository {
+	return &adminUserRepository{db: sqlDB}
+}
+
+func (r *adminUserRepository) ListStaff(ctx context.Context, roles []string) ([]service.AdminUser, error) {
+	rows, err := r.db.QueryContext(ctx, `
+		SELECT u.id, u.email, u.username, u.role, u.status, u.totp_enabled,
+			COALESCE(c.must_change_password, FALSE), c.password_changed_at, u.created_at
+		FROM users u
+		LEFT JOIN admin_user_credentials c ON c.user_id = u.id
+		WHERE u.role = ANY($1) AND u.deleted_at IS NULL
+		ORDER BY u.id
+	`, pq.Array(roles))
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	items := make([]service.AdminUser, 0)
+	for rows.Next() {
+		var (
+			item      service.AdminUser
+			changedAt sql.NullTime
+		)
+		if err := rows.Scan(&item.ID, &item.Email, &item.Username, &item.Role, &item.Status, &item.TotpEnabled,
+			&item.MustChangePassword, &changedAt, &item.CreatedAt); err != nil {
+			return nil, err
+		}
+		item.PasswordChangedAt = nullTimeToPtr(changedAt)
+		items = append(items, item)
+	}
+	if err := rows.Err(); err != nil {
+		return nil, err
+	}
+	return items, nil
+}
+
+func (r *adminUserRepository) CountActiveByRole(ctx context.Context, role string) (int64, error) {
+	var count int64
+	err := scanSingleRow(ctx, r.db, `
+		SELECT COUNT(*) FROM users WHERE role = $1 AND status = $2 AND deleted_at IS NULL
+	`, []any{role, service.StatusActive}, &count)
+	return count, err
+}
+
+func (r *adminUserRepository) GetCredentialState(ctx context.Context, userID int64) (*service.AdminCredentialState, error) {
+	state := service.AdminCredentialState{UserID: userID}
+	err := scanSingleRow(ctx, r.db, `
+		SELECT must_change_password, password_changed_at FROM admin_user_credentials WHERE user_id = $1
+	`, []any{userID}, &state.MustChangePassword, &state.PasswordChangedAt)
+	if errors.Is(err, sql.ErrNoRows) {
+		return nil, nil
+	}
+	if err != nil {
+		return nil, err
+	}
+	return &state, nil
+}
+
+func (r *adminUserRepository) SaveCredentialState(ctx context.Context, state *service.AdminCredentialState) error {
+	_, err := r.db.ExecContext(ctx, `
+		INSERT INTO admin_user_credentials (user_id, must_change_password, password_changed_at)
+		VALUES ($1, $2, $3)
+		ON CONFLICT (user_id) DO UPDATE SET
+			must_change_password = EXCLUDED.must_change_password,
+			password_changed_at = EXCLUDED.password_changed_at,
+			updated_at = NOW()
+	`, state.UserID, state.MustChangePassword, state.PasswordChangedAt)
+	return err
+}
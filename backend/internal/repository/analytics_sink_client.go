@@ -0,0 +1,55 @@
+package repository
+
+import (
+	"bytes"
+	"context"
+	"errors"
+	"fmt"
+	"io"
+	"net/http"
+	"net/url"
+
+	"github.com/Wei-Shaw/sub2api/internal/pkg/httpclient"
+	"github.com/Wei-Shaw/sub2api/internal/service"
+)
+
+type analyticsSinkClient struct {
+	httpClient *http.Client
+}
+
+// NewAnalyticsSinkClient 创建请求摘要事件接收端客户端；超时由调用方 context 控制
+func NewAnalyticsSinkClient() service.AnalyticsSink {
+	sharedClient, err := httpclient.GetClient(httpclient.Options{})
+	if err != nil {
+		sharedClient = &http.Client{}
+	}
+	return &analyticsSinkClient{httpClient: sharedClient}
+}
+
+func (c *analyticsSinkClient) Send(ctx context.Context, endpoint, authToken, batchID string, payload []byte) error {
+	req, err := http.NewRequestWithContext(ctx, http.MethodPost, endpoint, bytes.NewReader(payload))
+	if err != nil {
+		return errors.New("create request: invalid analytics tee url")
+	}
+	req.Header.Set("Content-Type", "application/json")
+	req.Header.Set("Idempotency-Key", batchID)
+	if authToken != "" {
+		req.Header.Set("Authorization", "Bearer "+authToken)
+	}
+
+	resp, err := c.httpClient.Do(req)
+	if err != nil {
+		var urlErr *url.Error
+		if errors.As(err, &urlErr) {
+			err = urlErr.Err
+		}
+		return err
+	}
+	defer func() { _ = resp.Body.Close() }()
+	_, _ = io.Copy(io.Discard, io.LimitReader(resp.Body, 1<<16))
+
+	if resp.StatusCode < 200 || resp.StatusCode >= 300 {
+		return fmt.Errorf("analytics sink returned %d", resp.StatusCode)
+	}
+	return nil
+}
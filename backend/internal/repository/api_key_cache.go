@@ -99,8 +99,10 @@ func (c *apiKeyCache) PublishAuthCacheInvalidation(ctx context.Context, cacheKey
 	return c.rdb.Publish(ctx, authCacheInvalidateChannel, cacheKey).Err()
 }
 
-// SubscribeAuthCacheInvalidation subscribes to cache invalidation messages
-func (c *apiKeyCache) SubscribeAuthCacheInvalidation(ctx context.Context, handler func(cacheKey string)) error {
+// SubscribeAuthCacheInvalidation subscribes to cache invalidation messages.
+// go-redis re-subscribes transparently after a reconnect; the confirmation it receives
+// then is surfaced through onResubscribe so callers can drop state that may have missed messages.
+func (c *apiKeyCache) SubscribeAuthCacheInvalidation(ctx context.Context, handler func(cacheKey string), onResubscribe func()) error {
 	pubsub := c.rdb.Subscribe(ctx, authCacheInvalidateChannel)
 
 	// Verify subscription is working
@@ -117,7 +119,7 @@ func (c *apiKeyCache) SubscribeAuthCacheInvalidation(ctx context.Context, handle
 			}
 		}()
 
-		ch := pubsub.Channel()
+		ch := pubsub.ChannelWithSubscriptions()
 		for {
 			select {
 			case <-ctx.Done():
@@ -126,8 +128,13 @@ func (c *apiKeyCache) SubscribeAuthCacheInvalidation(ctx context.Context, handle
 				if !ok {
 					return
 				}
-				if msg != nil {
-					handler(msg.Payload)
+				switch m := msg.(type) {
+				case *redis.Message:
+					handler(m.Payload)
+				case *redis.Subscription:
+					if m.Kind == "subscribe" && onResubscribe != nil {
+						onResubscribe()
+					}
 				}
 			}
 		}
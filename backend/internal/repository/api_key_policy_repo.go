@@ -0,0 +1,70 @@
+package repository
+
+import (
+	"context"
+	"database/sql"
+	"encoding/json"
+	"errors"
+
+	"github.com/Wei-Shaw/sub2api/internal/service"
+)
+
+type apiKeyPolicyRepository struct {
+	db *sql.DB
+}
+
+// NewAPIKeyPolicyRepository 创建 API Key / 分组策略仓储
+func NewAPIKeyPolicyRepository(sqlDB *sql.DB) service.APIKeyPolicyRepository {
+	return &apiKeyPolicyRepository{db: sqlDB}
+}
+
+func (r *apiKeyPolicyRepository) GetByAPIKeyID(ctx context.Context, apiKeyID int64) (*service.APIKeyPolicy, error) {
+	return r.get(ctx, `SELECT policy FROM api_key_policies WHERE api_key_id = $1`, apiKeyID)
+}
+
+func (r *apiKeyPolicyRepository) Upsert(ctx context.Context, apiKeyID int64, policy *service.APIKeyPolicy) error {
+	return r.upsert(ctx, `
+INSERT INTO api_key_policies (api_key_id, policy, updated_at)
+VALUES ($1, $2, NOW())
+ON CONFLICT (api_key_id) DO UPDATE SET
+	policy = EXCLUDED.policy,
+	updated_at = NOW()`, apiKeyID, policy)
+}
+
+func (r *apiKeyPolicyRepository) GetByGroupID(ctx context.Context, groupID int64) (*service.APIKeyPolicy, error) {
+	return r.get(ctx, `SELECT policy FROM group_policies WHERE group_id = $1`, groupID)
+}
+
+func (r *apiKeyPolicyRepository) UpsertForGroup(ctx context.Context, groupID int64, policy *service.APIKeyPolicy) error {
+	return r.upsert(ctx, `
+INSERT INTO group_policies (group_id, policy, updated_at)
+VALUES ($1, $2, NOW())
+ON CONFLICT (group_id) DO UPDATE SET
+	policy = EXCLUDED.policy,
+	updated_at = NOW()`, groupID, policy)
+}
+
+func (r *apiKeyPolicyRepository) get(ctx context.Context, query string, id int64) (*service.APIKeyPolicy, error) {
+	var raw []byte
+	err := scanSingleRow(ctx, r.db, query, []any{id}, &raw)
+	if errors.Is(err, sql.ErrNoRows) {
+		return nil, nil
+	}
+	if err != nil {
+		return nil, err
+	}
+	policy := &service.APIKeyPolicy{}
+	if err := json.Unmarshal(raw, policy); err != nil {
+		return nil, err
+	}
+	return policy, nil
+}
+
+func (r *apiKeyPolicyRepository) upsert(ctx context.Context, query string, id int64, policy *service.APIKeyPolicy) error {
+	raw, err := json.Marshal(policy)
+	if err != nil {
+		return err
+	}
+	_, err = r.db.ExecContext(ctx, query, id, string(raw))
+	return err
+}
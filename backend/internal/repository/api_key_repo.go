@@ -396,6 +396,33 @@ func (r *apiKeyRepository) IncrementQuotaUsed(ctx context.Context, id int64, amo
 	return updated.QuotaUsed, nil
 }
 
+// AddQuota 在单条语句内完成充值且不写 quota_used，
+// 避免读-改-写期间并发请求累加的 quota_used 被覆盖。
+func (r *apiKeyRepository) AddQuota(ctx context.Context, id int64, amount float64) error {
+	const updateSQL = `
+		UPDATE api_keys
+		SET
+			quota = GREATEST(quota, quota_used) + $1,
+			status = CASE WHEN status = $2 THEN $3 ELSE status END,
+			updated_at = NOW()
+		WHERE id = $4 AND deleted_at IS NULL
+	`
+
+	client := clientFromContext(ctx, r.client)
+	result, err := client.ExecContext(ctx, updateSQL, amount, service.StatusAPIKeyQuotaExhausted, service.StatusActive, id)
+	if err != nil {
+		return err
+	}
+	affected, err := result.RowsAffected()
+	if err != nil {
+		return err
+	}
+	if affected == 0 {
+		return service.ErrAPIKeyNotFound
+	}
+	return nil
+}
+
 func (r *apiKeyRepository) UpdateLastUsed(ctx context.Context, id int64, usedAt time.Time) error {
 	affected, err := r.client.APIKey.Update().
 		Where(apikey.IDEQ(id), apikey.DeletedAtIsNil()).
@@ -0,0 +1,91 @@
+package repository
+
+import (
+	"context"
+	"database/sql"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/service"
+	"github.com/lib/pq"
+)
+
+type apiKeyRotationRepository struct {
+	sql sqlExecutor
+}
+
+// NewAPIKeyRotationRepository 创建 API Key 轮换记录仓储
+func NewAPIKeyRotationRepository(sqlDB *sql.DB) service.APIKeyRotationRepository {
+	return &apiKeyRotationRepository{sql: sqlDB}
+}
+
+// Create 写入轮换记录
+func (r *apiKeyRotationRepository) Create(ctx context.Context, rotation *service.APIKeyRotation) error {
+	query := `
+		INSERT INTO api_key_rotations (user_id, old_api_key_id, new_api_key_id, grace_until, created_at)
+		VALUES ($1, $2, $3, $4, $5)
+		RETURNING id
+	`
+	return scanSingleRow(ctx, r.sql, query,
+		[]any{rotation.UserID, rotation.OldAPIKeyID, rotation.NewAPIKeyID, rotation.GraceUntil, rotation.CreatedAt},
+		&rotation.ID)
+}
+
+// GetLatestByKeyID 获取与该 Key 相关的最近一次轮换
+func (r *apiKeyRotationRepository) GetLatestByKeyID(ctx context.Context, apiKeyID int64) (*service.APIKeyRotation, error) {
+	query := `
+		SELECT id, user_id, old_api_key_id, new_api_key_id, grace_until, created_at
+		FROM api_key_rotations
+		WHERE old_api_key_id = $1 OR new_api_key_id = $1
+		ORDER BY created_at DESC, id DESC
+		LIMIT 1
+	`
+	var rotation service.APIKeyRotation
+	err := scanSingleRow(ctx, r.sql, query, []any{apiKeyID},
+		&rotation.ID, &rotation.UserID, &rotation.OldAPIKeyID, &rotation.NewAPIKeyID, &rotation.GraceUntil, &rotation.CreatedAt)
+	if err == sql.ErrNoRows {
+		return nil, nil
+	}
+	if err != nil {
+		return nil, err
+	}
+	return &rotation, nil
+}
+
+// GetVariantUsage 统计指定 Key 自 since 起的请求量与最后使用时间
+func (r *apiKeyRotationRepository) GetVariantUsage(ctx context.Context, apiKeyIDs []int64, since time.Time) (map[int64]*service.APIKeyVariantUsage, error) {
+	result := make(map[int64]*service.APIKeyVariantUsage, len(apiKeyIDs))
+	if len(apiKeyIDs) == 0 {
+		return result, nil
+	}
+
+	query := `
+		SELECT api_key_id, COUNT(*), MAX(created_at)
+		FROM usage_logs
+		WHERE api_key_id = ANY($1) AND created_at >= $2
+		GROUP BY api_key_id
+	`
+	rows, err := r.sql.QueryContext(ctx, query, pq.Array(apiKeyIDs), since)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	for rows.Next() {
+		var (
+			usage    service.APIKeyVariantUsage
+			lastUsed sql.NullTime
+		)
+		if err := rows.Scan(&usage.APIKeyID, &usage.RequestCount, &lastUsed); err != nil {
+			return nil, err
+		}
+		if lastUsed.Valid {
+			t := lastUsed.Time
+			usage.LastUsedAt = &t
+		}
+		result[usage.APIKeyID] = &usage
+	}
+	if err := rows.Err(); err != nil {
+		return nil, err
+	}
+	return result, nil
+}
@@ -0,0 +1,67 @@
+package repository
+
+import (
+	"bytes"
+	"context"
+	"encoding/json"
+	"errors"
+	"fmt"
+	"io"
+	"net/http"
+	"net/url"
+
+	"github.com/Wei-Shaw/sub2api/internal/pkg/httpclient"
+	"github.com/Wei-Shaw/sub2api/internal/service"
+)
+
+type challengeHookClient struct {
+	httpClient *http.Client
+}
+
+// NewChallengeHookClient 创建人机验证处理服务客户端；超时由调用方 context 控制
+func NewChallengeHookClient() service.ChallengeHookClient {
+	sharedClient, err := httpclient.GetClient(httpclient.Options{})
+	if err != nil {
+		sharedClient = &http.Client{}
+	}
+	return &challengeHookClient{httpClient: sharedClient}
+}
+
+func (c *challengeHookClient) Notify(ctx context.Context, endpoint, authToken string, in *service.ChallengeHookRequest) (*service.ChallengeHookResponse, error) {
+	payload, err := json.Marshal(in)
+	if err != nil {
+		return nil, fmt.Errorf("encode request: %w", err)
+	}
+	req, err := http.NewRequestWithContext(ctx, http.MethodPost, endpoint, bytes.NewReader(payload))
+	if err != nil {
+		return nil, errors.New("create request: invalid challenge hook url")
+	}
+	req.Header.Set("Content-Type", "application/json")
+	if authToken != "" {
+		req.Header.Set("Authorization", "Bearer "+authToken)
+	}
+
+	resp, err := c.httpClient.Do(req)
+	if err != nil {
+		// 请求体包含账号凭证，错误信息中只保留底层原因
+		var urlErr *url.Error
+		if errors.As(err, &urlErr) {
+			err = urlErr.Err
+		}
+		return nil, err
+	}
+	defer func() { _ = resp.Body.Close() }()
+
+	body, err := io.ReadAll(io.LimitReader(resp.Body, 1<<20))
+	if err != nil {
+		return nil, fmt.Errorf("read response: %w", err)
+	}
+	if resp.StatusCode < 200 || resp.StatusCode >= 300 {
+		return nil, fmt.Errorf("challenge hook returned %d", resp.StatusCode)
+	}
+	var out service.ChallengeHookResponse
+	if err := json.Unmarshal(body, &out); err != nil {
+		return nil, fmt.Errorf("decode response: %w", err)
+	}
+	return &out, nil
+}
@@ -0,0 +1,199 @@
+package repository
+
+import (
+	"bytes"
+	"context"
+	"encoding/json"
+	"errors"
+	"fmt"
+	"io"
+	"io/fs"
+	"net/http"
+	"net/url"
+	"path"
+	"sort"
+	"strings"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/httpclient"
+	"github.com/Wei-Shaw/sub2api/internal/service"
+	"github.com/Wei-Shaw/sub2api/migrations"
+)
+
+// clickHouseUsageStore 通过 ClickHouse HTTP 接口写入用量明细
+type clickHouseUsageStore struct {
+	cfg        config.ClickHouseConfig
+	httpClient *http.Client
+}
+
+// NewClickHouseUsageStore 创建 ClickHouse 用量分析存储；超时由调用方 context 控制
+func NewClickHouseUsageStore(cfg *config.Config) service.UsageAnalyticsStore {
+	sharedClient, err := httpclient.GetClient(httpclient.Options{})
+	if err != nil {
+		sharedClient = &http.Client{}
+	}
+	store := &clickHouseUsageStore{httpClient: sharedClient}
+	if cfg != nil {
+		store.cfg = cfg.ClickHouse
+	}
+	return store
+}
+
+// Migrate 创建数据库，并按文件名顺序执行 migrations/clickhouse 下尚未应用的迁移
+func (s *clickHouseUsageStore) Migrate(ctx context.Context) error {
+	// 数据库名已在配置校验中限制为字母、数字与下划线
+	if _, err := s.exec(ctx, "", "CREATE DATABASE IF NOT EXISTS "+s.cfg.Database, nil); err != nil {
+		return fmt.Errorf("create database: %w", err)
+	}
+	if _, err := s.exec(ctx, s.cfg.Database, `CREATE TABLE IF NOT EXISTS schema_migrations
+(
+    version    String,
+    applied_at DateTime DEFAULT now()
+)
+ENGINE = ReplacingMergeTree
+ORDER BY version`, nil); err != nil {
+		return fmt.Errorf("create schema_migrations: %w", err)
+	}
+
+	out, err := s.exec(ctx, s.cfg.Database, "SELECT version FROM schema_migrations FORMAT TabSeparated", nil)
+	if err != nil {
+		return fmt.Errorf("list applied migrations: %w", err)
+	}
+	applied := make(map[string]struct{})
+	for _, line := range strings.Split(string(out), "\n") {
+		if line = strings.TrimSpace(line); line != "" {
+			applied[line] = struct{}{}
+		}
+	}
+
+	files, err := fs.Glob(migrations.ClickHouseFS, "clickhouse/*.sql")
+	if err != nil {
+		return err
+	}
+	sort.Strings(files)
+	for _, file := range files {
+		version := path.Base(file)
+		if _, ok := applied[version]; ok {
+			continue
+		}
+		content, err := fs.ReadFile(migrations.ClickHouseFS, file)
+		if err != nil {
+			return err
+		}
+		for _, stmt := range splitClickHouseStatements(string(content)) {
+			if _, err := s.exec(ctx, s.cfg.Database, stmt, nil); err != nil {
+				return fmt.Errorf("apply %s: %w", version, err)
+			}
+		}
+		if _, err := s.exec(ctx, s.cfg.Database, "INSERT INTO schema_migrations (version) VALUES ('"+version+"')", nil); err != nil {
+			return fmt.Errorf("record %s: %w", version, err)
+		}
+	}
+	return nil
+}
+
+func (s *clickHouseUsageStore) InsertUsage(ctx context.Context, dedupToken string, rows []*service.UsageAnalyticsRow) error {
+	if len(rows) == 0 {
+		return nil
+	}
+	var body bytes.Buffer
+	enc := json.NewEncoder(&body)
+	for _, row := range rows {
+		if err := enc.Encode(row); err != nil {
+			return err
+		}
+	}
+	params := url.Values{}
+	params.Set("insert_deduplication_token", dedupToken)
+	// created_at 以 RFC 3339 格式序列化
+	params.Set("date_time_input_format", "best_effort")
+	_, err := s.exec(ctx, s.cfg.Database, "INSERT INTO usage_events FORMAT JSONEachRow", &clickHouseRequest{params: params, body: &body})
+	return err
+}
+
+type clickHouseRequest struct {
+	params url.Values
+	body   io.Reader
+}
+
+// exec 执行一条语句。无数据体时语句作为请求体发送；有数据体（INSERT）时语句放在 query 参数中
+func (s *clickHouseUsageStore) exec(ctx context.Context, database, query string, data *clickHouseRequest) ([]byte, error) {
+	endpoint, err := url.Parse(s.cfg.URL)
+	if err != nil {
+		return nil, errors.New("invalid clickhouse url")
+	}
+	params := url.Values{}
+	body := io.Reader(strings.NewReader(query))
+	if data != nil {
+		params = data.params
+		params.Set("query", query)
+		body = data.body
+	}
+	if database != "" {
+		params.Set("database", database)
+	}
+	endpoint.RawQuery = params.Encode()
+
+	req, err := http.NewRequestWithContext(ctx, http.MethodPost, endpoint.String(), body)
+	if err != nil {
+		return nil, errors.New("create request: invalid clickhouse url")
+	}
+	if s.cfg.Username != "" {
+		req.Header.Set("X-ClickHouse-User", s.cfg.Username)
+	}
+	if s.cfg.Password != "" {
+		req.Header.Set("X-ClickHouse-Key", s.cfg.Password)
+	}
+
+	resp, err := s.httpClient.Do(req)
+	if err != nil {
+		var urlErr *url.Error
+		if errors.As(err, &urlErr) {
+			err = urlErr.Err
+		}
+		return nil, err
+	}
+	defer func() { _ = resp.Body.Close() }()
+	respBody, err := io.ReadAll(io.LimitReader(resp.Body, 4<<20))
+	if err != nil {
+		return nil, err
+	}
+	if resp.StatusCode < 200 || resp.StatusCode >= 300 {
+		return nil, fmt.Errorf("clickhouse returned %d: %s", resp.StatusCode, truncateClickHouseBody(respBody))
+	}
+	return respBody, nil
+}
+
+func truncateClickHouseBody(body []byte) string {
+	const limit = 512
+	if len(body) > limit {
+		body = body[:limit]
+	}
+	return strings.TrimSpace(string(body))
+}
+
+// splitClickHouseStatements 按行尾分号拆分多条语句，忽略空行与整行注释
+func splitClickHouseStatements(content string) []string {
+	var (
+		stmts   []string
+		current strings.Builder
+	)
+	for _, line := range strings.Split(content, "\n") {
+		trimmed := strings.TrimSpace(line)
+		if trimmed == "" || strings.HasPrefix(trimmed, "--") {
+			continue
+		}
+		_, _ = current.WriteString(line)
+		_, _ = current.WriteString("\n")
+		if strings.HasSuffix(trimmed, ";") {
+			if stmt := strings.TrimSuffix(strings.TrimSpace(current.String()), ";"); stmt != "" {
+				stmts = append(stmts, stmt)
+			}
+			current.Reset()
+		}
+	}
+	if stmt := strings.TrimSpace(current.String()); stmt != "" {
+		stmts = append(stmts, stmt)
+	}
+	return stmts
+}
@@ -0,0 +1,309 @@
+package repository
+
+import (
+	"context"
+	"database/sql"
+	"errors"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/service"
+)
+
+const conversationColumns = `id, user_id, api_key_id, model, system, message_count, parent_id, parent_seq, created_at, updated_at, expires_at`
+
+type conversationRepository struct {
+	db *sql.DB
+}
+
+// NewConversationRepository 创建服务端会话仓储
+func NewConversationRepository(sqlDB *sql.DB) service.ConversationRepository {
+	return &conversationRepository{db: sqlDB}
+}
+
+func (r *conversationRepository) Get(ctx context.Context, userID int64, id string) (*service.Conversation, error) {
+	conv, err := scanConversation(r.db.QueryRowContext(ctx, `
+SELECT `+conversationColumns+`
+FROM conversations
+WHERE user_id = $1 AND id = $2 AND expires_at > NOW()
+`, userID, id))
+	if errors.Is(err, sql.ErrNoRows) {
+		return nil, service.ErrConversationNotFound
+	}
+	return conv, err
+}
+
+func (r *conversationRepository) ListMessages(ctx context.Context, userID int64, id string, limit int) ([]service.ConversationMessage, error) {
+	// limit <= 0 时 LIMIT NULL 等价于不限制
+	var limitArg any
+	if limit > 0 {
+		limitArg = limit
+	}
+	// 沿 parent 链向上：每个会话贡献 (parent_seq, upto] 区间内的消息，upto 为子会话的分叉点
+	rows, err := r.db.QueryContext(ctx, `
+WITH RECURSIVE chain AS (
+	SELECT id, parent_id, parent_seq, 2147483647 AS upto
+	FROM conversations
+	WHERE user_id = $1 AND id = $2
+	UNION ALL
+	SELECT p.id, p.parent_id, p.parent_seq, c.parent_seq
+	FROM conversations p
+	JOIN chain c ON p.user_id = $1 AND p.id = c.parent_id
+)
+SELECT seq, role, content, created_at FROM (
+	SELECT m.seq, m.role, m.content, m.created_at
+	FROM conversation_messages m
+	JOIN chain ON m.user_id = $1 AND m.conversation_id = chain.id
+	WHERE m.seq > COALESCE(chain.parent_seq, 0) AND m.seq <= chain.upto
+	ORDER BY m.seq DESC
+	LIMIT $3
+) recent
+ORDER BY seq
+`, userID, id, limitArg)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	out := make([]service.ConversationMessage, 0)
+	for rows.Next() {
+		var (
+			msg     service.ConversationMessage
+			content []byte
+		)
+		if err := rows.Scan(&msg.Seq, &msg.Role, &content, &msg.CreatedAt); err != nil {
+			return nil, err
+		}
+		msg.Content = content
+		out = append(out, msg)
+	}
+	if err := rows.Err(); err != nil {
+		return nil, err
+	}
+	return out, nil
+}
+
+func (r *conversationRepository) AppendTurn(ctx context.Context, conv *service.Conversation, messages []service.ConversationMessage) error {
+	tx, err := r.db.BeginTx(ctx, nil)
+	if err != nil {
+		return err
+	}
+	defer func() { _ = tx.Rollback() }()
+
+	var system any
+	if len(conv.System) > 0 {
+		system = []byte(conv.System)
+	}
+	// 已过期但尚未清理的会话视为新的根会话，旧消息一并清除
+	var (
+		count     int
+		parentID  sql.NullString
+		parentSeq sql.NullInt64
+	)
+	if err := tx.QueryRowContext(ctx, `
+INSERT INTO conversations (user_id, id, api_key_id, model, system, message_count, expires_at)
+VALUES ($1, $2, $3, $4, $5, $6, $7)
+ON CONFLICT (user_id, id) DO UPDATE SET
+	api_key_id = EXCLUDED.api_key_id,
+	model = EXCLUDED.model,
+	system = EXCLUDED.system,
+	message_count = CASE WHEN conversations.expires_at > NOW() THEN conversations.message_count + EXCLUDED.message_count ELSE EXCLUDED.message_count END,
+	created_at = CASE WHEN conversations.expires_at > NOW() THEN conversations.created_at ELSE NOW() END,
+	parent_id = CASE WHEN conversations.expires_at > NOW() THEN conversations.parent_id END,
+	parent_seq = CASE WHEN conversations.expires_at > NOW() THEN conversations.parent_seq END,
+	updated_at = NOW(),
+	expires_at = EXCLUDED.expires_at
+RETURNING message_count, parent_id, parent_seq, created_at, updated_at
+`, conv.UserID, conv.ID, conv.APIKeyID, conv.Model, system, len(messages), conv.ExpiresAt,
+	).Scan(&count, &parentID, &parentSeq, &conv.CreatedAt, &conv.UpdatedAt); err != nil {
+		return err
+	}
+	first := count - len(messages) + 1
+	if parentID.Valid {
+		if err := extendConversationAncestors(ctx, tx, conv.UserID, conv.ID, conv.ExpiresAt); err != nil {
+			return err
+		}
+	} else if first == 1 {
+		if _, err := tx.ExecContext(ctx, `DELETE FROM conversation_messages WHERE user_id = $1 AND conversation_id = $2`, conv.UserID, conv.ID); err != nil {
+			return err
+		}
+	}
+	for i, msg := range messages {
+		if _, err := tx.ExecContext(ctx, `
+INSERT INTO conversation_messages (user_id, conversation_id, seq, role, content)
+VALUES ($1, $2, $3, $4, $5)
+`, conv.UserID, conv.ID, first+i, msg.Role, []byte(msg.Content)); err != nil {
+			return err
+		}
+	}
+	if err := tx.Commit(); err != nil {
+		return err
+	}
+	conv.MessageCount = count
+	conv.ParentID, conv.ParentSeq = nullStringPtr(parentID), nullIntPtr(parentSeq)
+	return nil
+}
+
+func (r *conversationRepository) Create(ctx context.Context, conv *service.Conversation) error {
+	tx, err := r.db.BeginTx(ctx, nil)
+	if err != nil {
+		return err
+	}
+	defer func() { _ = tx.Rollback() }()
+
+	var system any
+	if len(conv.System) > 0 {
+		system = []byte(conv.System)
+	}
+	err = tx.QueryRowContext(ctx, `
+INSERT INTO conversations (user_id, id, api_key_id, model, system, message_count, parent_id, parent_seq, expires_at)
+VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+ON CONFLICT (user_id, id) DO NOTHING
+RETURNING created_at, updated_at
+`, conv.UserID, conv.ID, conv.APIKeyID, conv.Model, system, conv.MessageCount, conv.ParentID, conv.ParentSeq, conv.ExpiresAt,
+	).Scan(&conv.CreatedAt, &conv.UpdatedAt)
+	if errors.Is(err, sql.ErrNoRows) {
+		return service.ErrConversationExists
+	}
+	if err != nil {
+		return err
+	}
+	if conv.ParentID != nil {
+		if err := extendConversationAncestors(ctx, tx, conv.UserID, conv.ID, conv.ExpiresAt); err != nil {
+			return err
+		}
+	}
+	return tx.Commit()
+}
+
+// extendConversationAncestors 分支依赖祖先会话的历史（删除祖先会级联删除分支），祖先的过期时间不早于分支
+func extendConversationAncestors(ctx context.Context, tx *sql.Tx, userID int64, id string, expiresAt time.Time) error {
+	_, err := tx.ExecContext(ctx, `
+WITH RECURSIVE chain AS (
+	SELECT parent_id FROM conversations WHERE user_id = $1 AND id = $2
+	UNION ALL
+	SELECT p.parent_id FROM conversations p JOIN chain c ON p.user_id = $1 AND p.id = c.parent_id
+)
+UPDATE conversations SET expires_at = GREATEST(expires_at, $3)
+WHERE user_id = $1 AND id IN (SELECT parent_id FROM chain WHERE parent_id IS NOT NULL)
+`, userID, id, expiresAt)
+	return err
+}
+
+func (r *conversationRepository) ListTree(ctx context.Context, userID int64, id string) ([]*service.Conversation, error) {
+	rows, err := r.db.QueryContext(ctx, `
+WITH RECURSIVE up AS (
+	SELECT id, parent_id FROM conversations WHERE user_id = $1 AND id = $2
+	UNION ALL
+	SELECT c.id, c.parent_id FROM conversations c JOIN up ON c.user_id = $1 AND c.id = up.parent_id
+), tree AS (
+	SELECT id FROM up WHERE parent_id IS NULL
+	UNION ALL
+	SELECT c.id FROM conversations c JOIN tree t ON c.user_id = $1 AND c.parent_id = t.id
+)
+SELECT `+conversationColumns+`
+FROM conversations
+WHERE user_id = $1 AND id IN (SELECT id FROM tree) AND expires_at > NOW()
+ORDER BY parent_id IS NOT NULL, created_at, id
+`, userID, id)
+	if err != nil {
+		return nil, err
+	}
+	return scanConversations(rows)
+}
+
+func (r *conversationRepository) ListRoots(ctx context.Context, userID int64, limit int) ([]*service.Conversation, error) {
+	rows, err := r.db.QueryContext(ctx, `
+SELECT `+conversationColumns+`
+FROM conversations
+WHERE user_id = $1 AND parent_id IS NULL AND expires_at > NOW()
+ORDER BY updated_at DESC, id
+LIMIT $2
+`, userID, limit)
+	if err != nil {
+		return nil, err
+	}
+	return scanConversations(rows)
+}
+
+func (r *conversationRepository) Delete(ctx context.Context, userID int64, id string) error {
+	res, err := r.db.ExecContext(ctx, `DELETE FROM conversations WHERE user_id = $1 AND id = $2`, userID, id)
+	if err != nil {
+		return err
+	}
+	if n, err := res.RowsAffected(); err == nil && n == 0 {
+		return service.ErrConversationNotFound
+	}
+	return nil
+}
+
+func (r *conversationRepository) DeleteExpired(ctx context.Context) (int64, error) {
+	res, err := r.db.ExecContext(ctx, `DELETE FROM conversations WHERE expires_at <= NOW()`)
+	if err != nil {
+		return 0, err
+	}
+	return res.RowsAffected()
+}
+
+func scanConversations(rows *sql.Rows) ([]*service.Conversation, error) {
+	defer func() { _ = rows.Close() }()
+	out := make([]*service.Conversation, 0)
+	for rows.Next() {
+		conv, err := scanConversation(rows)
+		if err != nil {
+			return nil, err
+		}
+		out = append(out, conv)
+	}
+	if err := rows.Err(); err != nil {
+		return nil, err
+	}
+	return out, nil
+}
+
+func scanConversation(row interface{ Scan(...any) error }) (*service.Conversation, error) {
+	var (
+		conv      service.Conversation
+		apiKeyID  sql.NullInt64
+		system    []byte
+		parentID  sql.NullString
+		parentSeq sql.NullInt64
+	)
+	if err := row.Scan(
+		&conv.ID,
+		&conv.UserID,
+		&apiKeyID,
+		&conv.Model,
+		&system,
+		&conv.MessageCount,
+		&parentID,
+		&parentSeq,
+		&conv.CreatedAt,
+		&conv.UpdatedAt,
+		&conv.ExpiresAt,
+	); err != nil {
+		return nil, err
+	}
+	if apiKeyID.Valid {
+		v := apiKeyID.Int64
+		conv.APIKeyID = &v
+	}
+	conv.System = system
+	conv.ParentID, conv.ParentSeq = nullStringPtr(parentID), nullIntPtr(parentSeq)
+	return &conv, nil
+}
+
+func nullStringPtr(v sql.NullString) *string {
+	if !v.Valid {
+		return nil
+	}
+	s := v.String
+	return &s
+}
+
+func nullIntPtr(v sql.NullInt64) *int {
+	if !v.Valid {
+		return nil
+	}
+	n := int(v.Int64)
+	return &n
+}
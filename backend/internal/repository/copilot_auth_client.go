@@ -0,0 +1,173 @@
+package repository
+
+import (
+	"context"
+	"encoding/json"
+	"fmt"
+	"io"
+	"net/http"
+	"net/url"
+	"strings"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/pkg/copilot"
+	infraerrors "github.com/Wei-Shaw/sub2api/internal/pkg/errors"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/httpclient"
+	"github.com/Wei-Shaw/sub2api/internal/service"
+)
+
+const copilotAuthTimeout = 30 * time.Second
+
+type copilotAuthClient struct {
+	deviceCodeURL    string
+	accessTokenURL   string
+	tokenExchangeURL string
+	// clientFor 按代理返回 HTTP 客户端，测试中可替换为进程内 Transport
+	clientFor func(proxyURL string) (*http.Client, error)
+}
+
+// NewCopilotAuthClient 创建 GitHub Copilot 认证客户端
+func NewCopilotAuthClient() service.CopilotAuthClient {
+	return &copilotAuthClient{
+		deviceCodeURL:    copilot.DeviceCodeURL,
+		accessTokenURL:   copilot.AccessTokenURL,
+		tokenExchangeURL: copilot.TokenExchangeURL,
+		clientFor: func(proxyURL string) (*http.Client, error) {
+			return httpclient.GetClient(httpclient.Options{ProxyURL: proxyURL, Timeout: copilotAuthTimeout})
+		},
+	}
+}
+
+func (c *copilotAuthClient) RequestDeviceCode(ctx context.Context, proxyURL string) (*copilot.DeviceCodeResponse, error) {
+	form := url.Values{}
+	form.Set("client_id", copilot.ClientID)
+	form.Set("scope", copilot.Scope)
+
+	var out copilot.DeviceCodeResponse
+	status, body, err := c.do(ctx, http.MethodPost, c.deviceCodeURL, proxyURL, form, nil, &out)
+	if err != nil {
+		return nil, err
+	}
+	if status != http.StatusOK || out.DeviceCode == "" {
+		return nil, infraerrors.Newf(http.StatusBadGateway, "COPILOT_DEVICE_CODE_FAILED", "device code request failed: status %d, body: %s", status, truncateCopilotBody(body))
+	}
+	return &out, nil
+}
+
+func (c *copilotAuthClient) PollAccessToken(ctx context.Context, deviceCode, proxyURL string) (*copilot.AccessTokenResponse, error) {
+	form := url.Values{}
+	form.Set("client_id", copilot.ClientID)
+	form.Set("device_code", deviceCode)
+	form.Set("grant_type", copilot.DeviceGrantType)
+	return c.accessToken(ctx, form, proxyURL)
+}
+
+func (c *copilotAuthClient) RefreshAccessToken(ctx context.Context, refreshToken, proxyURL string) (*copilot.AccessTokenResponse, error) {
+	form := url.Values{}
+	form.Set("client_id", copilot.ClientID)
+	form.Set("refresh_token", refreshToken)
+	form.Set("grant_type", copilot.RefreshGrantType)
+	return c.accessToken(ctx, form, proxyURL)
+}
+
+// accessToken GitHub 对 pending / slow_down 等状态同样返回 200，由调用方根据 Error 字段处理
+func (c *copilotAuthClient) accessToken(ctx context.Context, form url.Values, proxyURL string) (*copilot.AccessTokenResponse, error) {
+	var out copilot.AccessTokenResponse
+	status, body, err := c.do(ctx, http.MethodPost, c.accessTokenURL, proxyURL, form, nil, &out)
+	if err != nil {
+		return nil, err
+	}
+	if status != http.StatusOK {
+		return nil, infraerrors.Newf(http.StatusBadGateway, "COPILOT_ACCESS_TOKEN_FAILED", "access token request failed: status %d, body: %s", status, truncateCopilotBody(body))
+	}
+	return &out, nil
+}
+
+func (c *copilotAuthClient) ExchangeToken(ctx context.Context, githubToken, proxyURL string) (*copilot.Token, error) {
+	headers := http.Header{}
+	copilot.SetClientHeaders(headers)
+	headers.Set("Authorization", "token "+githubToken)
+
+	var out copilot.Token
+	status, body, err := c.do(ctx, http.MethodGet, c.tokenExchangeURL, proxyURL, nil, headers, &out)
+	if err != nil {
+		return nil, err
+	}
+	switch {
+	case status == http.StatusUnauthorized:
+		return nil, infraerrors.Unauthorized("COPILOT_GITHUB_TOKEN_INVALID", "github token rejected: "+truncateCopilotBody(body))
+	case status == http.StatusForbidden || status == http.StatusNotFound:
+		// 账号没有 Copilot 订阅时返回 403/404
+		return nil, infraerrors.Forbidden("COPILOT_NOT_ENTITLED", "copilot is not enabled for this github account: "+truncateCopilotBody(body))
+	case status != http.StatusOK || out.Token == "":
+		return nil, infraerrors.Newf(http.StatusBadGateway, "COPILOT_TOKEN_EXCHANGE_FAILED", "token exchange failed: status %d, body: %s", status, truncateCopilotBody(body))
+	}
+	return &out, nil
+}
+
+func (c *copilotAuthClient) ListModels(ctx context.Context, apiBaseURL, copilotToken, proxyURL string) ([]copilot.Model, error) {
+	headers := http.Header{}
+	copilot.SetClientHeaders(headers)
+	headers.Set("Authorization", "Bearer "+copilotToken)
+
+	var out struct {
+		Data []copilot.Model `json:"data"`
+	}
+	status, body, err := c.do(ctx, http.MethodGet, strings.TrimRight(apiBaseURL, "/")+"/models", proxyURL, nil, headers, &out)
+	if err != nil {
+		return nil, err
+	}
+	if status != http.StatusOK {
+		return nil, infraerrors.Newf(http.StatusBadGateway, "COPILOT_LIST_MODELS_FAILED", "list models failed: status %d, body: %s", status, truncateCopilotBody(body))
+	}
+	return out.Data, nil
+}
+
+func (c *copilotAuthClient) do(ctx context.Context, method, target, proxyURL string, form url.Values, headers http.Header, out any) (int, []byte, error) {
+	client, err := c.clientFor(proxyURL)
+	if err != nil {
+		return 0, nil, infraerrors.Newf(http.StatusBadGateway, "COPILOT_REQUEST_FAILED", "create http client: %v", err)
+	}
+
+	var reader io.Reader
+	if form != nil {
+		reader = strings.NewReader(form.Encode())
+	}
+	req, err := http.NewRequestWithContext(ctx, method, target, reader)
+	if err != nil {
+		return 0, nil, fmt.Errorf("create request: %w", err)
+	}
+	for key, values := range headers {
+		req.Header[key] = values
+	}
+	req.Header.Set("Accept", "application/json")
+	if form != nil {
+		req.Header.Set("Content-Type", "application/x-www-form-urlencoded")
+	}
+
+	resp, err := client.Do(req)
+	if err != nil {
+		return 0, nil, infraerrors.Newf(http.StatusBadGateway, "COPILOT_REQUEST_FAILED", "request failed: %v", err)
+	}
+	defer func() { _ = resp.Body.Close() }()
+
+	body, err := io.ReadAll(io.LimitReader(resp.Body, 4<<20))
+	if err != nil {
+		return resp.StatusCode, nil, infraerrors.Newf(http.StatusBadGateway, "COPILOT_REQUEST_FAILED", "read response: %v", err)
+	}
+	if resp.StatusCode == http.StatusOK && out != nil {
+		if err := json.Unmarshal(body, out); err != nil {
+			return resp.StatusCode, body, infraerrors.Newf(http.StatusBadGateway, "COPILOT_INVALID_RESPONSE", "decode response: %v", err)
+		}
+	}
+	return resp.StatusCode, body, nil
+}
+
+func truncateCopilotBody(body []byte) string {
+	const limit = 512
+	s := strings.TrimSpace(string(body))
+	if len(s) > limit {
+		return s[:limit] + "..."
+	}
+	return s
+}
@@ -0,0 +1,128 @@
+package repository
+
+import (
+	"context"
+	"net/http"
+	"net/url"
+	"testing"
+
+	"github.com/Wei-Shaw/sub2api/internal/pkg/copilot"
+	infraerrors "github.com/Wei-Shaw/sub2api/internal/pkg/errors"
+	"github.com/stretchr/testify/require"
+)
+
+func newCopilotAuthClientForTest(handler http.HandlerFunc, capture func(r *http.Request, body []byte)) *copilotAuthClient {
+	client := &http.Client{Transport: newInProcessTransport(handler, capture)}
+	return &copilotAuthClient{
+		deviceCodeURL:    copilot.DeviceCodeURL,
+		accessTokenURL:   copilot.AccessTokenURL,
+		tokenExchangeURL: copilot.TokenExchangeURL,
+		clientFor:        func(string) (*http.Client, error) { return client, nil },
+	}
+}
+
+func TestCopilotAuthClient_DeviceFlow(t *testing.T) {
+	var forms []url.Values
+	client := newCopilotAuthClientForTest(func(w http.ResponseWriter, r *http.Request) {
+		w.Header().Set("Content-Type", "application/json")
+		switch r.URL.Path {
+		case "/login/device/code":
+			_, _ = w.Write([]byte(`{"device_code":"dc-1","user_code":"ABCD-1234","verification_uri":"https://github.com/login/device","expires_in":900,"interval":5}`))
+		case "/login/oauth/access_token":
+			_, _ = w.Write([]byte(`{"error":"authorization_pending","error_description":"pending"}`))
+		default:
+			w.WriteHeader(http.StatusNotFound)
+		}
+	}, func(r *http.Request, body []byte) {
+		form, _ := url.ParseQuery(string(body))
+		forms = append(forms, form)
+	})
+
+	device, err := client.RequestDeviceCode(context.Background(), "")
+	require.NoError(t, err)
+	require.Equal(t, "dc-1", device.DeviceCode)
+	require.Equal(t, "ABCD-1234", device.UserCode)
+	require.Equal(t, int64(5), device.Interval)
+
+	token, err := client.PollAccessToken(context.Background(), "dc-1", "")
+	require.NoError(t, err)
+	require.Equal(t, copilot.ErrAuthorizationPending, token.Error)
+
+	require.Len(t, forms, 2)
+	require.Equal(t, copilot.ClientID, forms[0].Get("client_id"))
+	require.Equal(t, copilot.Scope, forms[0].Get("scope"))
+	require.Equal(t, "dc-1", forms[1].Get("device_code"))
+	require.Equal(t, copilot.DeviceGrantType, forms[1].Get("grant_type"))
+}
+
+func TestCopilotAuthClient_RefreshAccessToken(t *testing.T) {
+	var form url.Values
+	client := newCopilotAuthClientForTest(func(w http.ResponseWriter, r *http.Request) {
+		_, _ = w.Write([]byte(`{"access_token":"ghu_new","refresh_token":"ghr_new","expires_in":28800,"token_type":"bearer"}`))
+	}, func(r *http.Request, body []byte) {
+		form, _ = url.ParseQuery(string(body))
+	})
+
+	token, err := client.RefreshAccessToken(context.Background(), "ghr_old", "")
+	require.NoError(t, err)
+	require.Equal(t, "ghu_new", token.AccessToken)
+	require.Equal(t, "ghr_new", token.RefreshToken)
+	require.Equal(t, int64(28800), token.ExpiresIn)
+	require.Equal(t, copilot.RefreshGrantType, form.Get("grant_type"))
+	require.Equal(t, "ghr_old", form.Get("refresh_token"))
+}
+
+func TestCopilotAuthClient_ExchangeToken(t *testing.T) {
+	var header http.Header
+	client := newCopilotAuthClientForTest(func(w http.ResponseWriter, r *http.Request) {
+		_, _ = w.Write([]byte(`{"token":"tid=1;exp=2","expires_at":1700001800,"refresh_in":1500,"sku":"copilot_for_business_seat","endpoints":{"api":"https://api.business.githubcopilot.com"}}`))
+	}, func(r *http.Request, _ []byte) {
+		header = r.Header.Clone()
+	})
+
+	token, err := client.ExchangeToken(context.Background(), "ghu_abc", "")
+	require.NoError(t, err)
+	require.Equal(t, "tid=1;exp=2", token.Token)
+	require.Equal(t, int64(1700001800), token.ExpiresAt)
+	require.Equal(t, "https://api.business.githubcopilot.com", token.APIBaseURL())
+	require.Equal(t, "token ghu_abc", header.Get("Authorization"))
+	require.Equal(t, copilot.EditorVersion, header.Get("Editor-Version"))
+}
+
+func TestCopilotAuthClient_ExchangeTokenErrors(t *testing.T) {
+	status := http.StatusUnauthorized
+	client := newCopilotAuthClientForTest(func(w http.ResponseWriter, r *http.Request) {
+		w.WriteHeader(status)
+		_, _ = w.Write([]byte(`{"message":"Bad credentials"}`))
+	}, nil)
+
+	_, err := client.ExchangeToken(context.Background(), "ghu_bad", "")
+	require.True(t, infraerrors.IsUnauthorized(err))
+
+	status = http.StatusNotFound
+	_, err = client.ExchangeToken(context.Background(), "ghu_nosub", "")
+	require.True(t, infraerrors.IsForbidden(err))
+
+	status = http.StatusInternalServerError
+	_, err = client.ExchangeToken(context.Background(), "ghu_abc", "")
+	require.Equal(t, http.StatusBadGateway, infraerrors.Code(err))
+}
+
+func TestCopilotAuthClient_ListModels(t *testing.T) {
+	var gotURL, gotAuth string
+	client := newCopilotAuthClientForTest(func(w http.ResponseWriter, r *http.Request) {
+		_, _ = w.Write([]byte(`{"data":[{"id":"gpt-4.1","name":"GPT-4.1","vendor":"Azure OpenAI","capabilities":{"type":"chat","family":"gpt-4.1","supports":{"tool_calls":true,"streaming":true,"vision":true}}},{"id":"text-embedding-3-small","capabilities":{"type":"embeddings"}}]}`))
+	}, func(r *http.Request, _ []byte) {
+		gotURL = r.URL.String()
+		gotAuth = r.Header.Get("Authorization")
+	})
+
+	models, err := client.ListModels(context.Background(), "https://api.githubcopilot.com/", "tid=1", "")
+	require.NoError(t, err)
+	require.Len(t, models, 2)
+	require.Equal(t, "gpt-4.1", models[0].ID)
+	require.True(t, models[0].Capabilities.Supports.ToolCalls)
+	require.Equal(t, "embeddings", models[1].Capabilities.Type)
+	require.Equal(t, "https://api.githubcopilot.com/models", gotURL)
+	require.Equal(t, "Bearer tid=1", gotAuth)
+}
@@ -0,0 +1,235 @@
+package repository
+
+import (
+	"context"
+	"database/sql"
+	"encoding/json"
+	"errors"
+	"fmt"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/envelope"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/secrets"
+)
+
+// credentialSecretFields 账号 credentials 中需要加密的敏感字段。
+// 其余字段（tier_id、project_id、oauth_type 等）保持明文，供 SQL 查询与调度使用。
+var credentialSecretFields = []string{
+	"access_token",
+	"refresh_token",
+	"id_token",
+	"api_key",
+	"session_token",
+	"session_key",
+	"cookie",
+	"cookies",
+	"secure_1psid",
+	"secure_1psidts",
+	"github_token",
+	"github_refresh_token",
+	"client_secret",
+	"token",
+}
+
+// CredentialCipher 对账号凭证中的敏感字段做信封加密，并解析 secret:// 外部密钥引用。
+// nil 表示既未配置主密钥也未配置密钥后端，所有方法均原样透传。
+type CredentialCipher struct {
+	keys    envelope.KeyWrapper
+	encrypt bool
+	// resolver 解析外部密钥引用；引用本身原样落库，读取时按缓存 TTL 拉取最新值
+	resolver *secrets.Resolver
+}
+
+// NewCredentialCipher 根据 security.credential_encryption 与 secrets 创建凭证加密器；两者均未配置时返回 nil。
+func NewCredentialCipher(cfg *config.Config) (*CredentialCipher, error) {
+	encCfg := cfg.Security.CredentialEncryption
+	keys, err := encCfg.Keys()
+	if err != nil {
+		return nil, err
+	}
+	resolver, err := cfg.Secrets.NewResolver()
+	if err != nil {
+		return nil, err
+	}
+	if keys == nil && resolver == nil {
+		return nil, nil
+	}
+	c := &CredentialCipher{resolver: resolver}
+	if keys != nil {
+		keyring, err := envelope.NewKeyring(encCfg.MasterKeyID, keys)
+		if err != nil {
+			return nil, err
+		}
+		c.keys = keyring
+		c.encrypt = encCfg.Enabled
+	}
+	return c, nil
+}
+
+// EncryptCredentials 返回敏感字段已加密的副本；已是当前主密钥密文的值不重复加密。
+func (c *CredentialCipher) EncryptCredentials(credentials map[string]any) (map[string]any, error) {
+	if c == nil || !c.encrypt || len(credentials) == 0 {
+		return credentials, nil
+	}
+	out := make(map[string]any, len(credentials))
+	for k, v := range credentials {
+		out[k] = v
+	}
+	for _, field := range credentialSecretFields {
+		value, ok := out[field].(string)
+		if !ok || value == "" || secrets.IsRef(value) || !envelope.NeedsRewrap(c.keys, value) {
+			continue
+		}
+		if envelope.IsEncrypted(value) {
+			plain, err := envelope.Decrypt(c.keys, value)
+			if err != nil {
+				return nil, fmt.Errorf("decrypt credential %s: %w", field, err)
+			}
+			value = plain
+		}
+		enc, err := envelope.Encrypt(c.keys, value)
+		if err != nil {
+			return nil, fmt.Errorf("encrypt credential %s: %w", field, err)
+		}
+		out[field] = enc
+	}
+	return out, nil
+}
+
+// DecryptCredentials 原地解密敏感字段并解析外部密钥引用；明文值（迁移前的存量数据）原样保留。
+func (c *CredentialCipher) DecryptCredentials(ctx context.Context, credentials map[string]any) error {
+	if c == nil || len(credentials) == 0 {
+		return nil
+	}
+	for _, field := range credentialSecretFields {
+		value, ok := credentials[field].(string)
+		if !ok {
+			continue
+		}
+		if secrets.IsRef(value) {
+			if c.resolver == nil {
+				return fmt.Errorf("credential %s uses a secret reference but secrets.provider is not configured", field)
+			}
+			resolved, err := c.resolver.Resolve(ctx, value)
+			if err != nil {
+				return fmt.Errorf("resolve credential %s: %w", field, err)
+			}
+			credentials[field] = resolved
+			continue
+		}
+		if !envelope.IsEncrypted(value) {
+			continue
+		}
+		if c.keys == nil {
+			return fmt.Errorf("credential %s is encrypted but security.credential_encryption.master_key is not configured", field)
+		}
+		plain, err := envelope.Decrypt(c.keys, value)
+		if err != nil {
+			return fmt.Errorf("decrypt credential %s: %w", field, err)
+		}
+		credentials[field] = plain
+	}
+	return nil
+}
+
+// NeedsReencrypt 凭证中存在明文敏感字段或旧主密钥密文时返回 true。
+func (c *CredentialCipher) NeedsReencrypt(credentials map[string]any) bool {
+	if c == nil || !c.encrypt {
+		return false
+	}
+	for _, field := range credentialSecretFields {
+		if value, ok := credentials[field].(string); ok && value != "" && !secrets.IsRef(value) && envelope.NeedsRewrap(c.keys, value) {
+			return true
+		}
+	}
+	return false
+}
+
+// CredentialReencryptResult 存量凭证重加密结果
+type CredentialReencryptResult struct {
+	Scanned int
+	Updated int
+}
+
+// ReencryptAccounts 分批扫描 accounts（包括已软删除的行），
+// 将明文敏感字段加密、旧主密钥密文改用当前主密钥重新加密。
+// 用于启用加密后的存量迁移与主密钥轮换；dryRun 时只统计不写入。
+func (c *CredentialCipher) ReencryptAccounts(ctx context.Context, db *sql.DB, batchSize int, dryRun bool) (CredentialReencryptResult, error) {
+	var result CredentialReencryptResult
+	if c == nil || !c.encrypt {
+		return result, errors.New("credential encryption is not enabled")
+	}
+	if batchSize <= 0 {
+		batchSize = 200
+	}
+	var lastID int64
+	for {
+		rows, err := db.QueryContext(ctx, `
+			SELECT id, credentials FROM accounts
+			WHERE id > $1
+			ORDER BY id
+			LIMIT $2
+		`, lastID, batchSize)
+		if err != nil {
+			return result, err
+		}
+		type pending struct {
+			id  int64
+			raw []byte
+		}
+		batch := make([]pending, 0, batchSize)
+		for rows.Next() {
+			var p pending
+			if err := rows.Scan(&p.id, &p.raw); err != nil {
+				_ = rows.Close()
+				return result, err
+			}
+			batch = append(batch, p)
+		}
+		if err := rows.Err(); err != nil {
+			_ = rows.Close()
+			return result, err
+		}
+		_ = rows.Close()
+		if len(batch) == 0 {
+			return result, nil
+		}
+
+		for _, p := range batch {
+			lastID = p.id
+			result.Scanned++
+			var credentials map[string]any
+			if len(p.raw) > 0 {
+				if err := json.Unmarshal(p.raw, &credentials); err != nil {
+					return result, fmt.Errorf("account %d: decode credentials: %w", p.id, err)
+				}
+			}
+			if !c.NeedsReencrypt(credentials) {
+				continue
+			}
+			encrypted, err := c.EncryptCredentials(credentials)
+			if err != nil {
+				return result, fmt.Errorf("account %d: %w", p.id, err)
+			}
+			if dryRun {
+				result.Updated++
+				continue
+			}
+			payload, err := json.Marshal(encrypted)
+			if err != nil {
+				return result, err
+			}
+			// 以读取时的内容作为条件，避免覆盖期间被刷新的 token
+			res, err := db.ExecContext(ctx, `
+				UPDATE accounts SET credentials = $2::jsonb
+				WHERE id = $1 AND credentials = $3::jsonb
+			`, p.id, payload, p.raw)
+			if err != nil {
+				return result, fmt.Errorf("account %d: %w", p.id, err)
+			}
+			if n, _ := res.RowsAffected(); n > 0 {
+				result.Updated++
+			}
+		}
+	}
+}
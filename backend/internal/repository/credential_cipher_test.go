@@ -0,0 +1,81 @@
+package repository
+
+import (
+	"context"
+	"strings"
+	"testing"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/envelope"
+	"github.com/stretchr/testify/require"
+)
+
+func newTestCredentialCipher(t *testing.T, enabled bool, activeID, activeKey string, previous map[string]string) *CredentialCipher {
+	t.Helper()
+	cipher, err := NewCredentialCipher(&config.Config{Security: config.SecurityConfig{
+		CredentialEncryption: config.CredentialEncryptionConfig{
+			Enabled:      enabled,
+			MasterKey:    activeKey,
+			MasterKeyID:  activeID,
+			PreviousKeys: previous,
+		},
+	}})
+	require.NoError(t, err)
+	return cipher
+}
+
+func TestCredentialCipherDisabledWithoutKey(t *testing.T) {
+	cipher := newTestCredentialCipher(t, false, "k1", "", nil)
+	require.Nil(t, cipher)
+
+	creds := map[string]any{"access_token": "plain"}
+	out, err := cipher.EncryptCredentials(creds)
+	require.NoError(t, err)
+	require.Equal(t, "plain", out["access_token"])
+	require.NoError(t, cipher.DecryptCredentials(context.Background(), creds))
+}
+
+func TestCredentialCipherEncryptsOnlySecretFields(t *testing.T) {
+	cipher := newTestCredentialCipher(t, true, "k1", strings.Repeat("11", 32), nil)
+
+	creds := map[string]any{"refresh_token": "rt-secret", "project_id": "proj-1", "expires_at": float64(1)}
+	out, err := cipher.EncryptCredentials(creds)
+	require.NoError(t, err)
+	require.Equal(t, "rt-secret", creds["refresh_token"], "input map must not be mutated")
+	require.True(t, envelope.IsEncrypted(out["refresh_token"].(string)))
+	require.Equal(t, "proj-1", out["project_id"])
+	require.False(t, cipher.NeedsReencrypt(out))
+
+	// 已加密的值不会被重复加密
+	again, err := cipher.EncryptCredentials(out)
+	require.NoError(t, err)
+	require.Equal(t, out["refresh_token"], again["refresh_token"])
+
+	require.NoError(t, cipher.DecryptCredentials(context.Background(), out))
+	require.Equal(t, "rt-secret", out["refresh_token"])
+}
+
+func TestCredentialCipherRotationAndPlaintextMigration(t *testing.T) {
+	oldKey := strings.Repeat("11", 32)
+	old := newTestCredentialCipher(t, true, "k1", oldKey, nil)
+	encrypted, err := old.EncryptCredentials(map[string]any{"api_key": "sk-1"})
+	require.NoError(t, err)
+
+	rotated := newTestCredentialCipher(t, true, "k2", strings.Repeat("22", 32), map[string]string{"k1": oldKey})
+	require.True(t, rotated.NeedsReencrypt(encrypted))
+	require.True(t, rotated.NeedsReencrypt(map[string]any{"cookie": "plain-cookie"}))
+
+	reencrypted, err := rotated.EncryptCredentials(encrypted)
+	require.NoError(t, err)
+	require.Equal(t, "k2", envelope.KeyID(reencrypted["api_key"].(string)))
+	require.NoError(t, rotated.DecryptCredentials(context.Background(), reencrypted))
+	require.Equal(t, "sk-1", reencrypted["api_key"])
+
+	// 关闭加密但保留密钥时仍可读取已加密数据，新写入保持明文
+	readOnly := newTestCredentialCipher(t, false, "k1", oldKey, nil)
+	plain, err := readOnly.EncryptCredentials(map[string]any{"api_key": "sk-2"})
+	require.NoError(t, err)
+	require.Equal(t, "sk-2", plain["api_key"])
+	require.NoError(t, readOnly.DecryptCredentials(context.Background(), encrypted))
+	require.Equal(t, "sk-1", encrypted["api_key"])
+}
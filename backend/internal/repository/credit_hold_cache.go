@@ -0,0 +1,139 @@
+package repository
+
+import (
+	"context"
+	"fmt"
+	"strconv"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/service"
+	"github.com/redis/go-redis/v9"
+)
+
+// 每个对象两个键：ZSET 记录预占 ID 及过期时间（毫秒），HASH 记录预占金额
+const (
+	creditHoldZSetPrefix   = "credit_hold:"
+	creditHoldAmountPrefix = "credit_hold_amount:"
+)
+
+// reserveCreditScript 清理过期预占后检查所有对象的可用额度，全部满足时写入预占
+// KEYS: 成对的 zset/hash；ARGV: now_ms, expires_at_ms, hold_id, amount, key_ttl_ms, available...
+var reserveCreditScript = redis.NewScript(`
+	local now = tonumber(ARGV[1])
+	local amount = tonumber(ARGV[4])
+	local n = #KEYS / 2
+	for i = 1, n do
+		local z, h = KEYS[2 * i - 1], KEYS[2 * i]
+		local expired = redis.call('ZRANGEBYSCORE', z, '-inf', now)
+		if #expired > 0 then
+			redis.call('ZREMRANGEBYSCORE', z, '-inf', now)
+			redis.call('HDEL', h, unpack(expired))
+		end
+		local held = 0
+		for _, v in ipairs(redis.call('HVALS', h)) do
+			held = held + tonumber(v)
+		end
+		if tonumber(ARGV[5 + i]) - held < amount then
+			return {i - 1, tostring(held)}
+		end
+	end
+	for i = 1, n do
+		local z, h = KEYS[2 * i - 1], KEYS[2 * i]
+		redis.call('ZADD', z, ARGV[2], ARGV[3])
+		redis.call('HSET', h, ARGV[3], ARGV[4])
+		redis.call('PEXPIRE', z, ARGV[5])
+		redis.call('PEXPIRE', h, ARGV[5])
+	end
+	return {-1, '0'}
+`)
+
+// releaseCreditScript expires_at_ms <= 0 时删除预占，否则仅缩短其过期时间
+// KEYS: 成对的 zset/hash；ARGV: hold_id, expires_at_ms
+var releaseCreditScript = redis.NewScript(`
+	for i = 1, #KEYS / 2 do
+		local z, h = KEYS[2 * i - 1], KEYS[2 * i]
+		if tonumber(ARGV[2]) <= 0 then
+			redis.call('ZREM', z, ARGV[1])
+			redis.call('HDEL', h, ARGV[1])
+		else
+			redis.call('ZADD', z, 'XX', ARGV[2], ARGV[1])
+		end
+	end
+	return 1
+`)
+
+type creditHoldCache struct {
+	rdb *redis.Client
+}
+
+// NewCreditHoldCache 创建预付费额度预占缓存
+func NewCreditHoldCache(rdb *redis.Client) service.CreditHoldCache {
+	return &creditHoldCache{rdb: rdb}
+}
+
+func creditHoldKeys(scopeType string, scopeID int64) (string, string) {
+	suffix := fmt.Sprintf("%s:%d", scopeType, scopeID)
+	return creditHoldZSetPrefix + suffix, creditHoldAmountPrefix + suffix
+}
+
+func creditHoldTargetKeys(targets []service.CreditHoldTarget) []string {
+	keys := make([]string, 0, len(targets)*2)
+	for _, t := range targets {
+		z, h := creditHoldKeys(t.ScopeType, t.ScopeID)
+		keys = append(keys, z, h)
+	}
+	return keys
+}
+
+func (c *creditHoldCache) Reserve(ctx context.Context, targets []service.CreditHoldTarget, holdID string, amount float64, expiresAt time.Time) (int, float64, error) {
+	now := time.Now()
+	// 键的存活时间覆盖最晚到期的预占
+	keyTTL := time.Until(expiresAt) + time.Minute
+	args := []any{now.UnixMilli(), expiresAt.UnixMilli(), holdID, strconv.FormatFloat(amount, 'f', -1, 64), keyTTL.Milliseconds()}
+	for _, t := range targets {
+		args = append(args, strconv.FormatFloat(t.Available, 'f', -1, 64))
+	}
+	res, err := reserveCreditScript.Run(ctx, c.rdb, creditHoldTargetKeys(targets), args...).Slice()
+	if err != nil {
+		return 0, 0, err
+	}
+	if len(res) != 2 {
+		return 0, 0, fmt.Errorf("credit hold: unexpected script result %v", res)
+	}
+	failed, ok := res[0].(int64)
+	if !ok {
+		return 0, 0, fmt.Errorf("credit hold: unexpected script result %T", res[0])
+	}
+	heldStr, _ := res[1].(string)
+	held, _ := strconv.ParseFloat(heldStr, 64)
+	return int(failed), held, nil
+}
+
+func (c *creditHoldCache) Release(ctx context.Context, targets []service.CreditHoldTarget, holdID string, expiresAt time.Time) error {
+	var expiresAtMs int64
+	if !expiresAt.IsZero() {
+		expiresAtMs = expiresAt.UnixMilli()
+	}
+	return releaseCreditScript.Run(ctx, c.rdb, creditHoldTargetKeys(targets), holdID, expiresAtMs).Err()
+}
+
+func (c *creditHoldCache) Held(ctx context.Context, scopeType string, scopeID int64) (float64, error) {
+	z, h := creditHoldKeys(scopeType, scopeID)
+	ids, err := c.rdb.ZRangeByScore(ctx, z, &redis.ZRangeBy{Min: "(" + strconv.FormatInt(time.Now().UnixMilli(), 10), Max: "+inf"}).Result()
+	if err != nil || len(ids) == 0 {
+		return 0, err
+	}
+	values, err := c.rdb.HMGet(ctx, h, ids...).Result()
+	if err != nil {
+		return 0, err
+	}
+	var total float64
+	for _, v := range values {
+		if s, ok := v.(string); ok {
+			if f, err := strconv.ParseFloat(s, 64); err == nil {
+				total += f
+			}
+		}
+	}
+	return total, nil
+}
@@ -0,0 +1,36 @@
+package repository
+
+import (
+	"context"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/service"
+	"github.com/redis/go-redis/v9"
+)
+
+const cronJobLockKeyPrefix = "cron:lock:"
+
+// cronJobUnlockScript 仅当锁仍由自己持有时才删除，避免误删其他实例在过期后获得的锁
+var cronJobUnlockScript = redis.NewScript(`
+if redis.call("GET", KEYS[1]) == ARGV[1] then
+  return redis.call("DEL", KEYS[1])
+end
+return 0
+`)
+
+type cronJobLocker struct {
+	rdb *redis.Client
+}
+
+// NewCronJobLocker 创建基于 Redis 的定时任务执行锁
+func NewCronJobLocker(rdb *redis.Client) service.CronJobLocker {
+	return &cronJobLocker{rdb: rdb}
+}
+
+func (l *cronJobLocker) TryLock(ctx context.Context, name, owner string, ttl time.Duration) (bool, error) {
+	return l.rdb.SetNX(ctx, cronJobLockKeyPrefix+name, owner, ttl).Result()
+}
+
+func (l *cronJobLocker) Unlock(ctx context.Context, name, owner string) error {
+	return cronJobUnlockScript.Run(ctx, l.rdb, []string{cronJobLockKeyPrefix + name}, owner).Err()
+}
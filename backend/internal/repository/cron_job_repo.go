@@ -0,0 +1,129 @@
+package repository
+
+import (
+	"context"
+	"database/sql"
+	"strings"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/service"
+)
+
+// cronJobErrorMaxLen 持久化的错误信息最大长度
+const cronJobErrorMaxLen = 2048
+
+const cronJobStateColumns = `name, last_scheduled_at, last_started_at, last_finished_at, last_status, last_error,
+	last_duration_ms, last_instance, run_count, failure_count`
+
+type cronJobStateRepository struct {
+	db *sql.DB
+}
+
+// NewCronJobStateRepository 创建定时任务执行状态仓储
+func NewCronJobStateRepository(sqlDB *sql.DB) service.CronJobStateRepository {
+	return &cronJobStateRepository{db: sqlDB}
+}
+
+func (r *cronJobStateRepository) ListStates(ctx context.Context) ([]service.CronJobState, error) {
+	rows, err := r.db.QueryContext(ctx, `SELECT `+cronJobStateColumns+` FROM cron_job_runs ORDER BY name`)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	out := make([]service.CronJobState, 0)
+	for rows.Next() {
+		st, err := scanCronJobState(rows)
+		if err != nil {
+			return nil, err
+		}
+		out = append(out, *st)
+	}
+	if err := rows.Err(); err != nil {
+		return nil, err
+	}
+	return out, nil
+}
+
+func (r *cronJobStateRepository) GetState(ctx context.Context, name string) (*service.CronJobState, error) {
+	rows, err := r.db.QueryContext(ctx, `SELECT `+cronJobStateColumns+` FROM cron_job_runs WHERE name = $1`, name)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	if !rows.Next() {
+		return nil, rows.Err()
+	}
+	return scanCronJobState(rows)
+}
+
+func (r *cronJobStateRepository) MarkStarted(ctx context.Context, name string, scheduledAt, startedAt time.Time, instance string) error {
+	_, err := r.db.ExecContext(ctx, `
+INSERT INTO cron_job_runs (name, last_scheduled_at, last_started_at, last_status, last_instance, run_count, updated_at)
+VALUES ($1, $2, $3, $4, $5, 1, NOW())
+ON CONFLICT (name) DO UPDATE SET
+	last_scheduled_at = EXCLUDED.last_scheduled_at,
+	last_started_at = EXCLUDED.last_started_at,
+	last_status = EXCLUDED.last_status,
+	last_instance = EXCLUDED.last_instance,
+	run_count = cron_job_runs.run_count + 1,
+	updated_at = NOW()
+`, name, scheduledAt, startedAt, service.CronJobStatusRunning, instance)
+	return err
+}
+
+func (r *cronJobStateRepository) MarkFinished(ctx context.Context, name string, finishedAt time.Time, duration time.Duration, runErr error) error {
+	status, msg, failed := service.CronJobStatusSucceeded, "", 0
+	if runErr != nil {
+		status, msg, failed = service.CronJobStatusFailed, runErr.Error(), 1
+		if len(msg) > cronJobErrorMaxLen {
+			msg = strings.ToValidUTF8(msg[:cronJobErrorMaxLen], "")
+		}
+	}
+	_, err := r.db.ExecContext(ctx, `
+UPDATE cron_job_runs SET
+	last_finished_at = $2,
+	last_status = $3,
+	last_error = $4,
+	last_duration_ms = $5,
+	failure_count = failure_count + $6,
+	updated_at = NOW()
+WHERE name = $1
+`, name, finishedAt, status, msg, duration.Milliseconds(), failed)
+	return err
+}
+
+func scanCronJobState(rows *sql.Rows) (*service.CronJobState, error) {
+	var (
+		st                                 service.CronJobState
+		scheduledAt, startedAt, finishedAt sql.NullTime
+	)
+	if err := rows.Scan(
+		&st.Name,
+		&scheduledAt,
+		&startedAt,
+		&finishedAt,
+		&st.LastStatus,
+		&st.LastError,
+		&st.LastDurationMs,
+		&st.LastInstance,
+		&st.RunCount,
+		&st.FailureCount,
+	); err != nil {
+		return nil, err
+	}
+	if scheduledAt.Valid {
+		t := scheduledAt.Time
+		st.LastScheduledAt = &t
+	}
+	if startedAt.Valid {
+		t := startedAt.Time
+		st.LastStartedAt = &t
+	}
+	if finishedAt.Valid {
+		t := finishedAt.Time
+		st.LastFinishedAt = &t
+	}
+	return &st, nil
+}
@@ -74,6 +74,15 @@ func (r *dashboardAggregationRepository) AggregateRange(ctx context.Context, sta
 	if err := r.upsertDailyAggregates(ctx, dayStart, dayEnd); err != nil {
 		return err
 	}
+	if err := r.upsertHourlyRollups(ctx, hourStart, hourEnd); err != nil {
+		return err
+	}
+	if err := r.upsertDailyRollups(ctx, dayStart, dayEnd); err != nil {
+		return err
+	}
+	if err := r.upsertHourlyAccountHealth(ctx, hourStart, hourEnd); err != nil {
+		return err
+	}
 	return nil
 }
 
@@ -130,6 +139,15 @@ func (r *dashboardAggregationRepository) recomputeRangeInTx(ctx context.Context,
 	if _, err := r.sql.ExecContext(ctx, "DELETE FROM usage_dashboard_daily_users WHERE bucket_date >= $1::date AND bucket_date < $2::date", dayStart, dayEnd); err != nil {
 		return err
 	}
+	if _, err := r.sql.ExecContext(ctx, "DELETE FROM usage_rollup_hourly WHERE bucket_start >= $1 AND bucket_start < $2", hourStart, hourEnd); err != nil {
+		return err
+	}
+	if _, err := r.sql.ExecContext(ctx, "DELETE FROM usage_rollup_daily WHERE bucket_date >= $1::date AND bucket_date < $2::date", dayStart, dayEnd); err != nil {
+		return err
+	}
+	if _, err := r.sql.ExecContext(ctx, "DELETE FROM account_health_rollup_hourly WHERE bucket_start >= $1 AND bucket_start < $2", hourStart, hourEnd); err != nil {
+		return err
+	}
 
 	if err := r.insertHourlyActiveUsers(ctx, hourStart, hourEnd); err != nil {
 		return err
@@ -143,6 +161,15 @@ func (r *dashboardAggregationRepository) recomputeRangeInTx(ctx context.Context,
 	if err := r.upsertDailyAggregates(ctx, dayStart, dayEnd); err != nil {
 		return err
 	}
+	if err := r.upsertHourlyRollups(ctx, hourStart, hourEnd); err != nil {
+		return err
+	}
+	if err := r.upsertDailyRollups(ctx, dayStart, dayEnd); err != nil {
+		return err
+	}
+	if err := r.upsertHourlyAccountHealth(ctx, hourStart, hourEnd); err != nil {
+		return err
+	}
 	return nil
 }
 
@@ -184,6 +211,15 @@ func (r *dashboardAggregationRepository) CleanupAggregates(ctx context.Context,
 	if _, err := r.sql.ExecContext(ctx, "DELETE FROM usage_dashboard_daily_users WHERE bucket_date < $1::date", dailyCutoffUTC); err != nil {
 		return err
 	}
+	if _, err := r.sql.ExecContext(ctx, "DELETE FROM usage_rollup_hourly WHERE bucket_start < $1", hourlyCutoffUTC); err != nil {
+		return err
+	}
+	if _, err := r.sql.ExecContext(ctx, "DELETE FROM usage_rollup_daily WHERE bucket_date < $1::date", dailyCutoffUTC); err != nil {
+		return err
+	}
+	if _, err := r.sql.ExecContext(ctx, "DELETE FROM account_health_rollup_hourly WHERE bucket_start < $1", hourlyCutoffUTC); err != nil {
+		return err
+	}
 	return nil
 }
 
@@ -382,6 +418,198 @@ func (r *dashboardAggregationRepository) upsertDailyAggregates(ctx context.Conte
 	return err
 }
 
+// upsertHourlyRollups 按 (小时, api_key_id, account_id, model) 维度聚合原始使用记录。
+// 以整桶为单位重算并覆盖，重复执行（含回填）结果一致。
+func (r *dashboardAggregationRepository) upsertHourlyRollups(ctx context.Context, start, end time.Time) error {
+	tzName := timezone.Name()
+	query := `
+		INSERT INTO usage_rollup_hourly (
+			bucket_start,
+			api_key_id,
+			account_id,
+			model,
+			total_requests,
+			input_tokens,
+			output_tokens,
+			cache_creation_tokens,
+			cache_read_tokens,
+			total_cost,
+			actual_cost,
+			account_cost,
+			total_duration_ms,
+			computed_at
+		)
+		SELECT
+			date_trunc('hour', created_at AT TIME ZONE $3) AT TIME ZONE $3 AS bucket_start,
+			api_key_id,
+			account_id,
+			model,
+			COUNT(*) AS total_requests,
+			COALESCE(SUM(input_tokens), 0) AS input_tokens,
+			COALESCE(SUM(output_tokens), 0) AS output_tokens,
+			COALESCE(SUM(cache_creation_tokens), 0) AS cache_creation_tokens,
+			COALESCE(SUM(cache_read_tokens), 0) AS cache_read_tokens,
+			COALESCE(SUM(total_cost), 0) AS total_cost,
+			COALESCE(SUM(actual_cost), 0) AS actual_cost,
+			COALESCE(SUM(total_cost * COALESCE(account_rate_multiplier, 1)), 0) AS account_cost,
+			COALESCE(SUM(COALESCE(duration_ms, 0)), 0) AS total_duration_ms,
+			NOW()
+		FROM usage_logs
+		WHERE created_at >= $1 AND created_at < $2
+		GROUP BY 1, api_key_id, account_id, model
+		ON CONFLICT (bucket_start, api_key_id, account_id, model)
+		DO UPDATE SET
+			total_requests = EXCLUDED.total_requests,
+			input_tokens = EXCLUDED.input_tokens,
+			output_tokens = EXCLUDED.output_tokens,
+			cache_creation_tokens = EXCLUDED.cache_creation_tokens,
+			cache_read_tokens = EXCLUDED.cache_read_tokens,
+			total_cost = EXCLUDED.total_cost,
+			actual_cost = EXCLUDED.actual_cost,
+			account_cost = EXCLUDED.account_cost,
+			total_duration_ms = EXCLUDED.total_duration_ms,
+			computed_at = EXCLUDED.computed_at
+	`
+	_, err := r.sql.ExecContext(ctx, query, start, end, tzName)
+	return err
+}
+
+// upsertDailyRollups 由小时维度汇总表上卷为日维度（不再扫描原始记录）。
+func (r *dashboardAggregationRepository) upsertDailyRollups(ctx context.Context, start, end time.Time) error {
+	tzName := timezone.Name()
+	query := `
+		INSERT INTO usage_rollup_daily (
+			bucket_date,
+			api_key_id,
+			account_id,
+			model,
+			total_requests,
+			input_tokens,
+			output_tokens,
+			cache_creation_tokens,
+			cache_read_tokens,
+			total_cost,
+			actual_cost,
+			account_cost,
+			total_duration_ms,
+			computed_at
+		)
+		SELECT
+			(bucket_start AT TIME ZONE $3)::date AS bucket_date,
+			api_key_id,
+			account_id,
+			model,
+			COALESCE(SUM(total_requests), 0),
+			COALESCE(SUM(input_tokens), 0),
+			COALESCE(SUM(output_tokens), 0),
+			COALESCE(SUM(cache_creation_tokens), 0),
+			COALESCE(SUM(cache_read_tokens), 0),
+			COALESCE(SUM(total_cost), 0),
+			COALESCE(SUM(actual_cost), 0),
+			COALESCE(SUM(account_cost), 0),
+			COALESCE(SUM(total_duration_ms), 0),
+			NOW()
+		FROM usage_rollup_hourly
+		WHERE bucket_start >= $1 AND bucket_start < $2
+		GROUP BY 1, api_key_id, account_id, model
+		ON CONFLICT (bucket_date, api_key_id, account_id, model)
+		DO UPDATE SET
+			total_requests = EXCLUDED.total_requests,
+			input_tokens = EXCLUDED.input_tokens,
+			output_tokens = EXCLUDED.output_tokens,
+			cache_creation_tokens = EXCLUDED.cache_creation_tokens,
+			cache_read_tokens = EXCLUDED.cache_read_tokens,
+			total_cost = EXCLUDED.total_cost,
+			actual_cost = EXCLUDED.actual_cost,
+			account_cost = EXCLUDED.account_cost,
+			total_duration_ms = EXCLUDED.total_duration_ms,
+			computed_at = EXCLUDED.computed_at
+	`
+	_, err := r.sql.ExecContext(ctx, query, start, end, tzName)
+	return err
+}
+
+// upsertHourlyAccountHealth 按 (小时, account_id) 聚合账号健康度：
+// 成功请求的耗时/首 token 分位数来自 usage_logs，上游错误数来自 ops_error_logs（仅统计上游责任、非业务限流的错误）。
+// 以整桶为单位重算并覆盖，重复执行（含回填）结果一致。
+func (r *dashboardAggregationRepository) upsertHourlyAccountHealth(ctx context.Context, start, end time.Time) error {
+	tzName := timezone.Name()
+	query := `
+		INSERT INTO account_health_rollup_hourly (
+			bucket_start,
+			account_id,
+			success_count,
+			error_count,
+			output_tokens,
+			duration_p50_ms,
+			duration_p95_ms,
+			duration_p99_ms,
+			first_token_p50_ms,
+			first_token_p95_ms,
+			first_token_p99_ms,
+			computed_at
+		)
+		WITH succeeded AS (
+			SELECT
+				date_trunc('hour', created_at AT TIME ZONE $3) AT TIME ZONE $3 AS bucket_start,
+				account_id,
+				COUNT(*) AS success_count,
+				COALESCE(SUM(output_tokens), 0) AS output_tokens,
+				percentile_cont(0.50) WITHIN GROUP (ORDER BY duration_ms) AS duration_p50,
+				percentile_cont(0.95) WITHIN GROUP (ORDER BY duration_ms) AS duration_p95,
+				percentile_cont(0.99) WITHIN GROUP (ORDER BY duration_ms) AS duration_p99,
+				percentile_cont(0.50) WITHIN GROUP (ORDER BY first_token_ms) AS first_token_p50,
+				percentile_cont(0.95) WITHIN GROUP (ORDER BY first_token_ms) AS first_token_p95,
+				percentile_cont(0.99) WITHIN GROUP (ORDER BY first_token_ms) AS first_token_p99
+			FROM usage_logs
+			WHERE created_at >= $1 AND created_at < $2 AND account_id > 0
+			GROUP BY 1, account_id
+		),
+		failed AS (
+			SELECT
+				date_trunc('hour', created_at AT TIME ZONE $3) AT TIME ZONE $3 AS bucket_start,
+				account_id,
+				COUNT(*) AS error_count
+			FROM ops_error_logs
+			WHERE created_at >= $1 AND created_at < $2
+				AND account_id > 0
+				AND error_owner = 'provider'
+				AND NOT is_business_limited
+				AND is_count_tokens = FALSE
+			GROUP BY 1, account_id
+		)
+		SELECT
+			COALESCE(s.bucket_start, f.bucket_start),
+			COALESCE(s.account_id, f.account_id),
+			COALESCE(s.success_count, 0),
+			COALESCE(f.error_count, 0),
+			COALESCE(s.output_tokens, 0),
+			ROUND(s.duration_p50)::int,
+			ROUND(s.duration_p95)::int,
+			ROUND(s.duration_p99)::int,
+			ROUND(s.first_token_p50)::int,
+			ROUND(s.first_token_p95)::int,
+			ROUND(s.first_token_p99)::int,
+			NOW()
+		FROM succeeded s
+		FULL OUTER JOIN failed f ON f.bucket_start = s.bucket_start AND f.account_id = s.account_id
+		ON CONFLICT (bucket_start, account_id)
+		DO UPDATE SET
+			success_count = EXCLUDED.success_count,
+			error_count = EXCLUDED.error_count,
+			output_tokens = EXCLUDED.output_tokens,
+			duration_p50_ms = EXCLUDED.duration_p50_ms,
+			duration_p95_ms = EXCLUDED.duration_p95_ms,
+			duration_p99_ms = EXCLUDED.duration_p99_ms,
+			first_token_p50_ms = EXCLUDED.first_token_p50_ms,
+			first_token_p95_ms = EXCLUDED.first_token_p95_ms,
+			first_token_p99_ms = EXCLUDED.first_token_p99_ms,
+			computed_at = EXCLUDED.computed_at
+	`
+	_, err := r.sql.ExecContext(ctx, query, start, end, tzName)
+	return err
+}
+
 func (r *dashboardAggregationRepository) isUsageLogsPartitioned(ctx context.Context) (bool, error) {
 	query := `
 		SELECT EXISTS(
@@ -0,0 +1,239 @@
+package repository
+
+import (
+	"context"
+	"database/sql"
+	"encoding/json"
+	"errors"
+	"fmt"
+
+	"github.com/Wei-Shaw/sub2api/internal/pkg/pagination"
+	"github.com/Wei-Shaw/sub2api/internal/service"
+)
+
+// dataSubjectScope 每张表按主体筛选的条件（$1 为主体 ID），空字符串表示该主体类型在此表无数据
+type dataSubjectScope struct {
+	user   string
+	apiKey string
+	// projection 导出时的列投影，默认整行
+	projection string
+}
+
+const dataSubjectUserKeys = "api_key_id IN (SELECT id FROM api_keys WHERE user_id = $1)"
+
+var dataSubjectScopes = map[string]dataSubjectScope{
+	"users": {
+		user:       "id = $1",
+		projection: "to_jsonb(t) - 'password_hash' - 'totp_secret_encrypted'",
+	},
+	"api_keys": {
+		user:       "user_id = $1",
+		apiKey:     "id = $1",
+		projection: "to_jsonb(t) - 'key'",
+	},
+	"usage_logs":             {user: "user_id = $1", apiKey: "api_key_id = $1"},
+	"usage_rollup_hourly":    {user: dataSubjectUserKeys, apiKey: "api_key_id = $1"},
+	"usage_rollup_daily":     {user: dataSubjectUserKeys, apiKey: "api_key_id = $1"},
+	"request_logs":           {user: "user_id = $1", apiKey: "api_key_id = $1"},
+	"ops_error_logs":         {user: "user_id = $1", apiKey: "api_key_id = $1"},
+	"ops_system_logs":        {user: "user_id = $1"},
+	"traffic_mirror_samples": {user: dataSubjectUserKeys, apiKey: "api_key_id = $1"},
+	"api_key_rotations":      {user: "user_id = $1", apiKey: "(old_api_key_id = $1 OR new_api_key_id = $1)"},
+}
+
+func dataSubjectCondition(table, subjectType string) (string, error) {
+	scope, ok := dataSubjectScopes[table]
+	if !ok {
+		return "", fmt.Errorf("unsupported data subject table: %s", table)
+	}
+	if subjectType == service.DataSubjectAPIKey {
+		return scope.apiKey, nil
+	}
+	return scope.user, nil
+}
+
+type dataSubjectRepository struct {
+	db *sql.DB
+}
+
+// NewDataSubjectRepository 创建数据主体请求仓储
+func NewDataSubjectRepository(sqlDB *sql.DB) service.DataSubjectRepository {
+	return &dataSubjectRepository{db: sqlDB}
+}
+
+const dataSubjectRequestColumns = `id, kind, subject_type, subject_id, status, reason, requested_by, report,
+	COALESCE(export_key, ''), COALESCE(error_message, ''), created_at, started_at, finished_at`
+
+func scanDataSubjectRequest(row interface{ Scan(...any) error }) (*service.DataSubjectRequest, error) {
+	var (
+		req         service.DataSubjectRequest
+		requestedBy sql.NullInt64
+		report      []byte
+		startedAt   sql.NullTime
+		finishedAt  sql.NullTime
+	)
+	if err := row.Scan(&req.ID, &req.Kind, &req.SubjectType, &req.SubjectID, &req.Status, &req.Reason, &requestedBy, &report,
+		&req.ExportKey, &req.ErrorMessage, &req.CreatedAt, &startedAt, &finishedAt); err != nil {
+		return nil, err
+	}
+	if requestedBy.Valid {
+		v := requestedBy.Int64
+		req.RequestedBy = &v
+	}
+	if len(report) > 0 {
+		if err := json.Unmarshal(report, &req.Report); err != nil {
+			return nil, fmt.Errorf("decode data subject report: %w", err)
+		}
+	}
+	if startedAt.Valid {
+		t := startedAt.Time
+		req.StartedAt = &t
+	}
+	if finishedAt.Valid {
+		t := finishedAt.Time
+		req.FinishedAt = &t
+	}
+	return &req, nil
+}
+
+func (r *dataSubjectRepository) Create(ctx context.Context, req *service.DataSubjectRequest) error {
+	return r.db.QueryRowContext(ctx, `
+		INSERT INTO data_subject_requests (kind, subject_type, subject_id, status, reason, requested_by)
+		VALUES ($1, $2, $3, $4, $5, $6)
+		RETURNING id, created_at
+	`, req.Kind, req.SubjectType, req.SubjectID, req.Status, req.Reason, nullInt64(req.RequestedBy)).Scan(&req.ID, &req.CreatedAt)
+}
+
+func (r *dataSubjectRepository) GetByID(ctx context.Context, id int64) (*service.DataSubjectRequest, error) {
+	req, err := scanDataSubjectRequest(r.db.QueryRowContext(ctx,
+		`SELECT `+dataSubjectRequestColumns+` FROM data_subject_requests WHERE id = $1`, id))
+	if errors.Is(err, sql.ErrNoRows) {
+		return nil, service.ErrDataSubjectRequestNotFound
+	}
+	return req, err
+}
+
+func (r *dataSubjectRepository) List(ctx context.Context, params pagination.PaginationParams) ([]service.DataSubjectRequest, *pagination.PaginationResult, error) {
+	var total int64
+	if err := scanSingleRow(ctx, r.db, `SELECT COUNT(*) FROM data_subject_requests`, nil, &total); err != nil {
+		return nil, nil, err
+	}
+	rows, err := r.db.QueryContext(ctx, `
+		SELECT `+dataSubjectRequestColumns+`
+		FROM data_subject_requests
+		ORDER BY created_at DESC, id DESC
+		LIMIT $1 OFFSET $2
+	`, params.Limit(), params.Offset())
+	if err != nil {
+		return nil, nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	items := make([]service.DataSubjectRequest, 0)
+	for rows.Next() {
+		req, err := scanDataSubjectRequest(rows)
+		if err != nil {
+			return nil, nil, err
+		}
+		items = append(items, *req)
+	}
+	if err := rows.Err(); err != nil {
+		return nil, nil, err
+	}
+	return items, paginationResultFromTotal(total, params), nil
+}
+
+func (r *dataSubjectRepository) HasActive(ctx context.Context, subjectType string, subjectID int64) (bool, error) {
+	var exists bool
+	err := scanSingleRow(ctx, r.db, `
+		SELECT EXISTS (
+			SELECT 1 FROM data_subject_requests
+			WHERE subject_type = $1 AND subject_id = $2 AND status IN ('pending', 'running')
+		)
+	`, []any{subjectType, subjectID}, &exists)
+	return exists, err
+}
+
+func (r *dataSubjectRepository) MarkRunning(ctx context.Context, id int64) (bool, error) {
+	res, err := r.db.ExecContext(ctx, `
+		UPDATE data_subject_requests
+		SET status = $2, started_at = NOW()
+		WHERE id = $1 AND status = $3
+	`, id, service.DataSubjectStatusRunning, service.DataSubjectStatusPending)
+	if err != nil {
+		return false, err
+	}
+	n, err := res.RowsAffected()
+	return n > 0, err
+}
+
+func (r *dataSubjectRepository) Finish(ctx context.Context, req *service.DataSubjectRequest) error {
+	report, err := json.Marshal(req.Report)
+	if err != nil {
+		return err
+	}
+	_, err = r.db.ExecContext(ctx, `
+		UPDATE data_subject_requests
+		SET status = $2, report = $3, export_key = $4, error_message = $5, finished_at = $6
+		WHERE id = $1
+	`, req.ID, req.Status, report, opsNullString(req.ExportKey), opsNullString(req.ErrorMessage), req.FinishedAt)
+	return err
+}
+
+func (r *dataSubjectRepository) SubjectExists(ctx context.Context, subjectType string, subjectID int64) (bool, error) {
+	table := "users"
+	if subjectType == service.DataSubjectAPIKey {
+		table = "api_keys"
+	}
+	var exists bool
+	err := scanSingleRow(ctx, r.db, `SELECT EXISTS (SELECT 1 FROM `+table+` WHERE id = $1)`, []any{subjectID}, &exists)
+	return exists, err
+}
+
+func (r *dataSubjectRepository) ExportRows(ctx context.Context, table, subjectType string, subjectID int64, fn func(row json.RawMessage) error) (int64, error) {
+	cond, err := dataSubjectCondition(table, subjectType)
+	if err != nil || cond == "" {
+		return 0, err
+	}
+	projection := dataSubjectScopes[table].projection
+	if projection == "" {
+		projection = "to_jsonb(t)"
+	}
+	rows, err := r.db.QueryContext(ctx, `SELECT (`+projection+`)::text FROM `+table+` t WHERE `+cond, subjectID)
+	if err != nil {
+		return 0, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	var n int64
+	for rows.Next() {
+		var row string
+		if err := rows.Scan(&row); err != nil {
+			return n, err
+		}
+		if err := fn(json.RawMessage(row)); err != nil {
+			return n, err
+		}
+		n++
+	}
+	return n, rows.Err()
+}
+
+func (r *dataSubjectRepository) DeleteRows(ctx context.Context, table, subjectType string, subjectID int64, limit int) (int64, error) {
+	if table == "users" || table == "api_keys" {
+		return 0, fmt.Errorf("refusing to delete identity table: %s", table)
+	}
+	cond, err := dataSubjectCondition(table, subjectType)
+	if err != nil || cond == "" {
+		return 0, err
+	}
+	// 部分表（如 usage_rollup_*）没有 id 列，按 ctid 分批删除
+	res, err := r.db.ExecContext(ctx, `
+		DELETE FROM `+table+`
+		WHERE ctid = ANY(ARRAY(SELECT ctid FROM `+table+` t WHERE `+cond+` LIMIT $2))
+	`, subjectID, limit)
+	if err != nil {
+		return 0, err
+	}
+	return res.RowsAffected()
+}
@@ -0,0 +1,178 @@
+package repository
+
+import (
+	"context"
+	"database/sql"
+	"errors"
+	"fmt"
+	"sync"
+	"sync/atomic"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/logger"
+)
+
+const (
+	readReplicaProbeInterval = 5 * time.Second
+	readReplicaProbeTimeout  = 3 * time.Second
+)
+
+// readReplicaLagQuery 副本复制延迟（秒）：已回放到最新 WAL 时视为无延迟，
+// 避免主库空闲期间 pg_last_xact_replay_timestamp 停滞被误判为延迟
+const readReplicaLagQuery = `
+	SELECT CASE
+		WHEN NOT pg_is_in_recovery() THEN 0
+		WHEN pg_last_wal_receive_lsn() = pg_last_wal_replay_lsn() THEN 0
+		ELSE COALESCE(EXTRACT(EPOCH FROM now() - pg_last_xact_replay_timestamp()), 0)
+	END
+`
+
+// sqlQueryer 只读查询所需的最小接口，*sql.DB 与 *ReadDB 均实现
+type sqlQueryer interface {
+	QueryContext(ctx context.Context, query string, args ...any) (*sql.Rows, error)
+	QueryRowContext(ctx context.Context, query string, args ...any) *sql.Row
+}
+
+// ReadDB 读写分离的只读连接：用量查询、仪表盘聚合与列表接口通过它查询，
+// 在健康且复制延迟不超过 database.replica_max_lag_seconds 的副本间轮询；
+// 未配置副本或副本均不可用时回落到主库。ExecContext 始终走主库。
+type ReadDB struct {
+	primary  *sql.DB
+	replicas []*readReplica
+	maxLag   time.Duration
+	next     atomic.Uint64
+
+	stopOnce sync.Once
+	stop     chan struct{}
+	wg       sync.WaitGroup
+}
+
+type readReplica struct {
+	addr    string
+	db      *sql.DB
+	healthy atomic.Bool
+	lag     atomic.Int64 // 最近一次探测的复制延迟（纳秒）
+}
+
+// ProvideReadDB 按配置连接只读副本并启动延迟探测。
+// 副本连接参数有误时启动失败；副本暂时不可达时仅标记为不可用，不阻塞启动。
+func ProvideReadDB(cfg *config.Config, primary *sql.DB) (*ReadDB, error) {
+	replicas := make([]*readReplica, 0, len(cfg.Database.Replicas))
+	for _, rc := range cfg.Database.Replicas {
+		dbCfg := cfg.Database.ReplicaConfig(rc)
+		db, err := sql.Open("postgres", dbCfg.DSNWithTimezone(cfg.Timezone))
+		if err != nil {
+			for _, r := range replicas {
+				_ = r.db.Close()
+			}
+			return nil, fmt.Errorf("open read replica %s:%d: %w", dbCfg.Host, dbCfg.Port, err)
+		}
+		applyDBPoolSettings(db, cfg)
+		replicas = append(replicas, &readReplica{addr: fmt.Sprintf("%s:%d", dbCfg.Host, dbCfg.Port), db: db})
+	}
+	r := newReadDB(primary, replicas, time.Duration(cfg.Database.ReplicaMaxLagSeconds)*time.Second)
+	r.start()
+	return r, nil
+}
+
+func newReadDB(primary *sql.DB, replicas []*readReplica, maxLag time.Duration) *ReadDB {
+	return &ReadDB{
+		primary:  primary,
+		replicas: replicas,
+		maxLag:   maxLag,
+		stop:     make(chan struct{}),
+	}
+}
+
+func (r *ReadDB) start() {
+	if len(r.replicas) == 0 {
+		return
+	}
+	r.probeAll()
+	r.wg.Add(1)
+	go func() {
+		defer r.wg.Done()
+		ticker := time.NewTicker(readReplicaProbeInterval)
+		defer ticker.Stop()
+		for {
+			select {
+			case <-r.stop:
+				return
+			case <-ticker.C:
+				r.probeAll()
+			}
+		}
+	}()
+}
+
+func (r *ReadDB) probeAll() {
+	for _, replica := range r.replicas {
+		r.probe(replica)
+	}
+}
+
+func (r *ReadDB) probe(replica *readReplica) {
+	ctx, cancel := context.WithTimeout(context.Background(), readReplicaProbeTimeout)
+	defer cancel()
+
+	var lagSeconds float64
+	err := replica.db.QueryRowContext(ctx, readReplicaLagQuery).Scan(&lagSeconds)
+	lag := time.Duration(lagSeconds * float64(time.Second))
+	healthy := err == nil && lag <= r.maxLag
+	replica.lag.Store(int64(lag))
+
+	if replica.healthy.Swap(healthy) == healthy {
+		return
+	}
+	switch {
+	case healthy:
+		logger.LegacyPrintf("repository.read_db", "[ReadDB] replica %s available (lag=%s)", replica.addr, lag)
+	case err != nil:
+		logger.LegacyPrintf("repository.read_db", "[ReadDB] replica %s unavailable, falling back: %v", replica.addr, err)
+	default:
+		logger.LegacyPrintf("repository.read_db", "[ReadDB] replica %s lag %s exceeds %s, falling back", replica.addr, lag, r.maxLag)
+	}
+}
+
+// pick 轮询选择可用副本，无可用副本时返回主库
+func (r *ReadDB) pick() *sql.DB {
+	n := len(r.replicas)
+	if n == 0 {
+		return r.primary
+	}
+	start := r.next.Add(1)
+	for i := range n {
+		replica := r.replicas[(start+uint64(i))%uint64(n)]
+		if replica.healthy.Load() {
+			return replica.db
+		}
+	}
+	return r.primary
+}
+
+func (r *ReadDB) QueryContext(ctx context.Context, query string, args ...any) (*sql.Rows, error) {
+	return r.pick().QueryContext(ctx, query, args...)
+}
+
+func (r *ReadDB) QueryRowContext(ctx context.Context, query string, args ...any) *sql.Row {
+	return r.pick().QueryRowContext(ctx, query, args...)
+}
+
+// ExecContext 始终在主库执行，使 ReadDB 可作为 sqlExecutor 传给只读仓储
+func (r *ReadDB) ExecContext(ctx context.Context, query string, args ...any) (sql.Result, error) {
+	return r.primary.ExecContext(ctx, query, args...)
+}
+
+// Close 停止延迟探测并关闭副本连接；主库连接由 Ent 客户端负责关闭
+func (r *ReadDB) Close() error {
+	r.stopOnce.Do(func() { close(r.stop) })
+	r.wg.Wait()
+	var errs []error
+	for _, replica := range r.replicas {
+		if err := replica.db.Close(); err != nil {
+			errs = append(errs, fmt.Errorf("close read replica %s: %w", replica.addr, err))
+		}
+	}
+	return errors.Join(errs...)
+}
@@ -0,0 +1,69 @@
+//go:build unit
+
+package repository
+
+import (
+	"database/sql"
+	"errors"
+	"testing"
+	"time"
+
+	"github.com/DATA-DOG/go-sqlmock"
+	"github.com/stretchr/testify/require"
+)
+
+func newMockReplica(t *testing.T, addr string) (*readReplica, sqlmock.Sqlmock) {
+	t.Helper()
+	db, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = db.Close() })
+	return &readReplica{addr: addr, db: db}, mock
+}
+
+func TestReadDB_PickSkipsLaggingReplicas(t *testing.T) {
+	primary, _, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = primary.Close() }()
+
+	fresh, freshMock := newMockReplica(t, "fresh:5432")
+	lagging, laggingMock := newMockReplica(t, "lagging:5432")
+	down, downMock := newMockReplica(t, "down:5432")
+	r := newReadDB(primary, []*readReplica{fresh, lagging, down}, 10*time.Second)
+
+	freshMock.ExpectQuery("pg_is_in_recovery").WillReturnRows(sqlmock.NewRows([]string{"lag"}).AddRow(1.5))
+	laggingMock.ExpectQuery("pg_is_in_recovery").WillReturnRows(sqlmock.NewRows([]string{"lag"}).AddRow(30.0))
+	downMock.ExpectQuery("pg_is_in_recovery").WillReturnError(errors.New("connection refused"))
+	r.probeAll()
+
+	require.True(t, fresh.healthy.Load())
+	require.False(t, lagging.healthy.Load())
+	require.False(t, down.healthy.Load())
+	for range 5 {
+		require.Same(t, fresh.db, r.pick())
+	}
+
+	// 副本全部不可用时回落到主库
+	freshMock.ExpectQuery("pg_is_in_recovery").WillReturnRows(sqlmock.NewRows([]string{"lag"}).AddRow(45.0))
+	laggingMock.ExpectQuery("pg_is_in_recovery").WillReturnRows(sqlmock.NewRows([]string{"lag"}).AddRow(30.0))
+	downMock.ExpectQuery("pg_is_in_recovery").WillReturnError(errors.New("connection refused"))
+	r.probeAll()
+	require.Same(t, primary, r.pick())
+}
+
+func TestReadDB_NoReplicasUsesPrimary(t *testing.T) {
+	primary, mock, err := sqlmock.New()
+	require.NoError(t, err)
+	defer func() { _ = primary.Close() }()
+
+	r := newReadDB(primary, nil, 10*time.Second)
+	require.Same(t, primary, r.pick())
+
+	mock.ExpectExec("UPDATE").WillReturnResult(sqlmock.NewResult(0, 1))
+	_, err = r.ExecContext(t.Context(), "UPDATE t SET x = 1")
+	require.NoError(t, err)
+	require.NoError(t, mock.ExpectationsWereMet())
+	require.NoError(t, r.Close())
+}
+
+var _ sqlExecutor = (*ReadDB)(nil)
+var _ sqlQueryer = (*sql.DB)(nil)
@@ -0,0 +1,73 @@
+package repository
+
+import (
+	"bytes"
+	"context"
+	"encoding/json"
+	"errors"
+	"fmt"
+	"io"
+	"net/http"
+	"net/url"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/pkg/httpclient"
+	"github.com/Wei-Shaw/sub2api/internal/service"
+)
+
+const discordWebhookTimeout = 15 * time.Second
+
+type discordWebhookClient struct {
+	httpClient *http.Client
+}
+
+// NewDiscordWebhookClient 创建 Discord Webhook 客户端
+func NewDiscordWebhookClient() service.DiscordWebhookClient {
+	sharedClient, err := httpclient.GetClient(httpclient.Options{Timeout: discordWebhookTimeout})
+	if err != nil {
+		sharedClient = &http.Client{Timeout: discordWebhookTimeout}
+	}
+	return &discordWebhookClient{httpClient: sharedClient}
+}
+
+func (c *discordWebhookClient) Execute(ctx context.Context, webhookURL string, msg *service.DiscordWebhookMessage) error {
+	payload, err := json.Marshal(msg)
+	if err != nil {
+		return fmt.Errorf("encode request: %w", err)
+	}
+	req, err := http.NewRequestWithContext(ctx, http.MethodPost, webhookURL, bytes.NewReader(payload))
+	if err != nil {
+		return errors.New("create request: invalid webhook url")
+	}
+	req.Header.Set("Content-Type", "application/json")
+
+	resp, err := c.httpClient.Do(req)
+	if err != nil {
+		// Webhook URL 中的 token 等同于发送凭证，不能写入日志
+		var urlErr *url.Error
+		if errors.As(err, &urlErr) {
+			err = urlErr.Err
+		}
+		return fmt.Errorf("discord webhook: %w", err)
+	}
+	defer func() { _ = resp.Body.Close() }()
+
+	if resp.StatusCode >= 200 && resp.StatusCode < 300 {
+		_, _ = io.Copy(io.Discard, io.LimitReader(resp.Body, 64<<10))
+		return nil
+	}
+
+	body, _ := io.ReadAll(io.LimitReader(resp.Body, 64<<10))
+	var parsed struct {
+		Message    string  `json:"message"`
+		RetryAfter float64 `json:"retry_after"`
+	}
+	_ = json.Unmarshal(body, &parsed)
+	if resp.StatusCode == http.StatusTooManyRequests {
+		return fmt.Errorf("discord webhook rate limited, retry after %.1fs", parsed.RetryAfter)
+	}
+	if parsed.Message != "" {
+		return fmt.Errorf("discord webhook returned %d: %s", resp.StatusCode, parsed.Message)
+	}
+	return fmt.Errorf("discord webhook returned %d", resp.StatusCode)
+}
@@ -0,0 +1,67 @@
+package repository
+
+import (
+	"context"
+	"encoding/json"
+	"net/http"
+	"testing"
+
+	"github.com/Wei-Shaw/sub2api/internal/service"
+	"github.com/stretchr/testify/require"
+)
+
+const testDiscordWebhookURL = "https://discord.com/api/webhooks/123/secret-token"
+
+func newDiscordWebhookClientForTest(handler http.HandlerFunc, capture func(r *http.Request, body []byte)) *discordWebhookClient {
+	return &discordWebhookClient{
+		httpClient: &http.Client{Transport: newInProcessTransport(handler, capture)},
+	}
+}
+
+func TestDiscordWebhookClient_Execute(t *testing.T) {
+	var (
+		gotPath string
+		payload map[string]any
+	)
+	client := newDiscordWebhookClientForTest(func(w http.ResponseWriter, r *http.Request) {
+		w.WriteHeader(http.StatusNoContent)
+	}, func(r *http.Request, body []byte) {
+		gotPath = r.URL.Path
+		_ = json.Unmarshal(body, &payload)
+	})
+
+	err := client.Execute(context.Background(), testDiscordWebhookURL, &service.DiscordWebhookMessage{
+		Username:        "sub2api",
+		Embeds:          []service.DiscordEmbed{{Title: "hello", Color: 0x3498DB}},
+		AllowedMentions: &service.DiscordAllowedMentions{Parse: []string{}},
+	})
+	require.NoError(t, err)
+	require.Equal(t, "/api/webhooks/123/secret-token", gotPath)
+	require.Equal(t, "sub2api", payload["username"])
+	require.Len(t, payload["embeds"], 1)
+	require.Equal(t, []any{}, payload["allowed_mentions"].(map[string]any)["parse"])
+}
+
+func TestDiscordWebhookClient_RateLimited(t *testing.T) {
+	client := newDiscordWebhookClientForTest(func(w http.ResponseWriter, r *http.Request) {
+		w.WriteHeader(http.StatusTooManyRequests)
+		_, _ = w.Write([]byte(`{"message":"You are being rate limited.","retry_after":1.5,"global":false}`))
+	}, nil)
+
+	err := client.Execute(context.Background(), testDiscordWebhookURL, &service.DiscordWebhookMessage{})
+	require.Error(t, err)
+	require.Contains(t, err.Error(), "retry after 1.5s")
+	require.NotContains(t, err.Error(), "secret-token")
+}
+
+func TestDiscordWebhookClient_ErrorMessage(t *testing.T) {
+	client := newDiscordWebhookClientForTest(func(w http.ResponseWriter, r *http.Request) {
+		w.WriteHeader(http.StatusNotFound)
+		_, _ = w.Write([]byte(`{"message":"Unknown Webhook","code":10015}`))
+	}, nil)
+
+	err := client.Execute(context.Background(), testDiscordWebhookURL, &service.DiscordWebhookMessage{})
+	require.Error(t, err)
+	require.Contains(t, err.Error(), "404: Unknown Webhook")
+	require.NotContains(t, err.Error(), "secret-token")
+}
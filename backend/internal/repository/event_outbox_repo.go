@@ -0,0 +1,95 @@
+package repository
+
+import (
+	"context"
+	"database/sql"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/service"
+	"github.com/lib/pq"
+)
+
+type eventOutboxRepository struct {
+	db *sql.DB
+}
+
+// NewEventOutboxRepository 创建事件导出 outbox 仓储
+func NewEventOutboxRepository(sqlDB *sql.DB) service.EventOutboxRepository {
+	return &eventOutboxRepository{db: sqlDB}
+}
+
+func (r *eventOutboxRepository) Insert(ctx context.Context, eventType, eventKey string, payload []byte) error {
+	_, err := r.db.ExecContext(ctx, `INSERT INTO event_outbox (event_type, event_key, payload) VALUES ($1, $2, $3)`, eventType, eventKey, payload)
+	return err
+}
+
+func (r *eventOutboxRepository) Claim(ctx context.Context, limit int, lease time.Duration) ([]*service.EventOutboxRecord, error) {
+	rows, err := r.db.QueryContext(ctx, `
+UPDATE event_outbox o
+SET attempts = o.attempts + 1,
+    next_attempt_at = NOW() + make_interval(secs => $2)
+FROM (
+    SELECT id FROM event_outbox
+    WHERE published_at IS NULL AND next_attempt_at <= NOW()
+    ORDER BY next_attempt_at, id
+    LIMIT $1
+    FOR UPDATE SKIP LOCKED
+) claimed
+WHERE o.id = claimed.id
+RETURNING o.id, o.event_type, o.event_key, o.payload, o.attempts, o.created_at
+`, limit, lease.Seconds())
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	out := make([]*service.EventOutboxRecord, 0, limit)
+	for rows.Next() {
+		record := &service.EventOutboxRecord{}
+		if err := rows.Scan(&record.ID, &record.EventType, &record.EventKey, &record.Payload, &record.Attempts, &record.CreatedAt); err != nil {
+			return nil, err
+		}
+		out = append(out, record)
+	}
+	if err := rows.Err(); err != nil {
+		return nil, err
+	}
+	return out, nil
+}
+
+func (r *eventOutboxRepository) MarkPublished(ctx context.Context, ids []int64) error {
+	if len(ids) == 0 {
+		return nil
+	}
+	_, err := r.db.ExecContext(ctx, `UPDATE event_outbox SET published_at = NOW(), last_error = NULL WHERE id = ANY($1)`, pq.Array(ids))
+	return err
+}
+
+func (r *eventOutboxRepository) MarkFailed(ctx context.Context, ids []int64, lastError string, maxBackoff time.Duration) error {
+	if len(ids) == 0 {
+		return nil
+	}
+	// attempts 在认领时已递增：第 1 次失败后等待 2 秒，之后逐次翻倍
+	_, err := r.db.ExecContext(ctx, `
+UPDATE event_outbox
+SET last_error = $2,
+    next_attempt_at = NOW() + make_interval(secs => LEAST(POWER(2, LEAST(attempts, 16)), $3))
+WHERE id = ANY($1)
+`, pq.Array(ids), lastError, maxBackoff.Seconds())
+	return err
+}
+
+func (r *eventOutboxRepository) DeletePublishedBefore(ctx context.Context, before time.Time, limit int) (int64, error) {
+	res, err := r.db.ExecContext(ctx, `
+DELETE FROM event_outbox
+WHERE id IN (
+    SELECT id FROM event_outbox
+    WHERE published_at IS NOT NULL AND published_at < $1
+    LIMIT $2
+)
+`, before, limit)
+	if err != nil {
+		return 0, err
+	}
+	return res.RowsAffected()
+}
@@ -0,0 +1,123 @@
+package repository
+
+import (
+	"bytes"
+	"context"
+	"encoding/json"
+	"errors"
+	"fmt"
+	"io"
+	"net/http"
+	"net/url"
+	"strings"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/httpclient"
+	"github.com/Wei-Shaw/sub2api/internal/service"
+)
+
+// NewEventPublisher 按 event_export.driver 创建事件发布器
+func NewEventPublisher(cfg *config.Config) service.EventPublisher {
+	if cfg == nil {
+		return newKafkaRESTEventPublisher(config.EventExportKafkaConfig{})
+	}
+	if cfg.EventExport.Driver == "nats" {
+		return newNATSEventPublisher(cfg.EventExport.NATS)
+	}
+	return newKafkaRESTEventPublisher(cfg.EventExport.Kafka)
+}
+
+// kafkaRESTEventPublisher 通过 Kafka REST Proxy v2 API 发布（POST /topics/{topic}）
+type kafkaRESTEventPublisher struct {
+	cfg        config.EventExportKafkaConfig
+	httpClient *http.Client
+}
+
+func newKafkaRESTEventPublisher(cfg config.EventExportKafkaConfig) *kafkaRESTEventPublisher {
+	sharedClient, err := httpclient.GetClient(httpclient.Options{})
+	if err != nil {
+		sharedClient = &http.Client{}
+	}
+	return &kafkaRESTEventPublisher{cfg: cfg, httpClient: sharedClient}
+}
+
+type kafkaRESTRecord struct {
+	Key   *string         `json:"key,omitempty"`
+	Value json.RawMessage `json:"value"`
+}
+
+type kafkaRESTProduceResponse struct {
+	Offsets []struct {
+		Partition *int    `json:"partition"`
+		Offset    *int64  `json:"offset"`
+		ErrorCode *int    `json:"error_code"`
+		Error     *string `json:"error"`
+	} `json:"offsets"`
+}
+
+func (p *kafkaRESTEventPublisher) Publish(ctx context.Context, topic string, messages []service.EventExportMessage) error {
+	if len(messages) == 0 {
+		return nil
+	}
+	records := make([]kafkaRESTRecord, 0, len(messages))
+	for i := range messages {
+		record := kafkaRESTRecord{Value: messages[i].Value}
+		if messages[i].Key != "" {
+			record.Key = &messages[i].Key
+		}
+		records = append(records, record)
+	}
+	body, err := json.Marshal(map[string]any{"records": records})
+	if err != nil {
+		return err
+	}
+
+	endpoint := strings.TrimRight(p.cfg.RESTURL, "/") + "/topics/" + url.PathEscape(topic)
+	req, err := http.NewRequestWithContext(ctx, http.MethodPost, endpoint, bytes.NewReader(body))
+	if err != nil {
+		return errors.New("create request: invalid kafka rest url")
+	}
+	req.Header.Set("Content-Type", "application/vnd.kafka.json.v2+json")
+	req.Header.Set("Accept", "application/vnd.kafka.v2+json")
+	if p.cfg.AuthToken != "" {
+		req.Header.Set("Authorization", "Bearer "+p.cfg.AuthToken)
+	}
+
+	resp, err := p.httpClient.Do(req)
+	if err != nil {
+		var urlErr *url.Error
+		if errors.As(err, &urlErr) {
+			err = urlErr.Err
+		}
+		return err
+	}
+	defer func() { _ = resp.Body.Close() }()
+	respBody, _ := io.ReadAll(io.LimitReader(resp.Body, 1<<20))
+
+	if resp.StatusCode < 200 || resp.StatusCode >= 300 {
+		return fmt.Errorf("kafka rest proxy returned %d: %s", resp.StatusCode, truncateEventPublisherBody(respBody))
+	}
+	// 单条记录写入失败时整体返回 200，需逐条检查
+	var parsed kafkaRESTProduceResponse
+	if err := json.Unmarshal(respBody, &parsed); err != nil {
+		return fmt.Errorf("parse kafka rest proxy response: %w", err)
+	}
+	for _, offset := range parsed.Offsets {
+		if offset.ErrorCode != nil || offset.Error != nil {
+			msg := ""
+			if offset.Error != nil {
+				msg = *offset.Error
+			}
+			return fmt.Errorf("kafka rest proxy rejected record: %s", msg)
+		}
+	}
+	return nil
+}
+
+func truncateEventPublisherBody(body []byte) string {
+	const limit = 256
+	if len(body) > limit {
+		body = body[:limit]
+	}
+	return strings.TrimSpace(string(body))
+}
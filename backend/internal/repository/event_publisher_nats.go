@@ -0,0 +1,241 @@
+package repository
+
+import (
+	"bufio"
+	"bytes"
+	"context"
+	"encoding/json"
+	"errors"
+	"fmt"
+	"io"
+	"net"
+	"net/url"
+	"strconv"
+	"strings"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/Wei-Shaw/sub2api/internal/service"
+	"github.com/google/uuid"
+)
+
+// natsMaxControlLine 服务端协议控制行的最大长度
+const natsMaxControlLine = 4096
+
+// natsEventPublisher 通过 NATS 核心协议发布事件。
+//
+// 每批事件使用一条短连接：核心模式下以 PING/PONG 确认服务端已处理全部 PUB；
+// JetStream 模式下以 HPUB 携带 Nats-Msg-Id 发布并逐条等待 stream 的持久化确认。
+type natsEventPublisher struct {
+	cfg config.EventExportNATSConfig
+}
+
+func newNATSEventPublisher(cfg config.EventExportNATSConfig) *natsEventPublisher {
+	return &natsEventPublisher{cfg: cfg}
+}
+
+type natsConnectOptions struct {
+	Verbose      bool   `json:"verbose"`
+	Pedantic     bool   `json:"pedantic"`
+	Name         string `json:"name"`
+	Lang         string `json:"lang"`
+	Version      string `json:"version"`
+	Protocol     int    `json:"protocol"`
+	Headers      bool   `json:"headers"`
+	NoResponders bool   `json:"no_responders"`
+	AuthToken    string `json:"auth_token,omitempty"`
+	User         string `json:"user,omitempty"`
+	Pass         string `json:"pass,omitempty"`
+}
+
+type natsPubAck struct {
+	Stream string `json:"stream"`
+	Seq    uint64 `json:"seq"`
+	Error  *struct {
+		Code        int    `json:"code"`
+		Description string `json:"description"`
+	} `json:"error"`
+}
+
+func (p *natsEventPublisher) Publish(ctx context.Context, subject string, messages []service.EventExportMessage) error {
+	if len(messages) == 0 {
+		return nil
+	}
+	u, err := url.Parse(p.cfg.URL)
+	if err != nil || u.Host == "" {
+		return errors.New("invalid nats url")
+	}
+
+	var dialer net.Dialer
+	conn, err := dialer.DialContext(ctx, "tcp", u.Host)
+	if err != nil {
+		return err
+	}
+	defer func() { _ = conn.Close() }()
+	if deadline, ok := ctx.Deadline(); ok {
+		if err := conn.SetDeadline(deadline); err != nil {
+			return err
+		}
+	}
+	reader := bufio.NewReaderSize(conn, natsMaxControlLine)
+
+	// 连接建立后服务端先发送 INFO
+	line, err := readNATSLine(reader)
+	if err != nil {
+		return fmt.Errorf("read nats info: %w", err)
+	}
+	if !strings.HasPrefix(line, "INFO ") {
+		return fmt.Errorf("unexpected nats greeting: %s", truncateNATSLine(line))
+	}
+
+	connect := natsConnectOptions{
+		Name:         "sub2api-event-export",
+		Lang:         "go",
+		Version:      "1.0.0",
+		Protocol:     1,
+		Headers:      true,
+		NoResponders: true,
+		AuthToken:    p.cfg.Token,
+		User:         p.cfg.User,
+		Pass:         p.cfg.Password,
+	}
+	if u.User != nil && connect.User == "" {
+		connect.User = u.User.Username()
+		connect.Pass, _ = u.User.Password()
+	}
+	connectJSON, err := json.Marshal(&connect)
+	if err != nil {
+		return err
+	}
+
+	var buf bytes.Buffer
+	fmt.Fprintf(&buf, "CONNECT %s\r\n", connectJSON)
+	if !p.cfg.JetStream {
+		for i := range messages {
+			fmt.Fprintf(&buf, "PUB %s %d\r\n%s\r\n", subject, len(messages[i].Value), messages[i].Value)
+		}
+		// 服务端按顺序处理命令，收到 PONG 说明此前的 CONNECT 与 PUB 均已被接受
+		fmt.Fprintf(&buf, "PING\r\n")
+		if _, err := conn.Write(buf.Bytes()); err != nil {
+			return err
+		}
+		return waitNATSPong(conn, reader)
+	}
+
+	inbox := "_INBOX." + strings.ReplaceAll(uuid.NewString(), "-", "")
+	fmt.Fprintf(&buf, "SUB %s.* 1\r\n", inbox)
+	for i := range messages {
+		header := "NATS/1.0\r\nNats-Msg-Id: " + messages[i].ID + "\r\n\r\n"
+		fmt.Fprintf(&buf, "HPUB %s %s.%d %d %d\r\n%s%s\r\n", subject, inbox, i, len(header), len(header)+len(messages[i].Value), header, messages[i].Value)
+	}
+	if _, err := conn.Write(buf.Bytes()); err != nil {
+		return err
+	}
+	return waitNATSAcks(conn, reader, len(messages))
+}
+
+func waitNATSPong(conn net.Conn, reader *bufio.Reader) error {
+	for {
+		line, err := readNATSLine(reader)
+		if err != nil {
+			return err
+		}
+		switch {
+		case line == "PONG":
+			return nil
+		case line == "PING":
+			if _, err := conn.Write([]byte("PONG\r\n")); err != nil {
+				return err
+			}
+		case strings.HasPrefix(line, "-ERR"):
+			return fmt.Errorf("nats error: %s", truncateNATSLine(line))
+		}
+	}
+}
+
+// waitNATSAcks 等待每条 HPUB 的 JetStream 确认（确认消息投递到 SUB 的收件箱）
+func waitNATSAcks(conn net.Conn, reader *bufio.Reader, expected int) error {
+	for acked := 0; acked < expected; {
+		line, err := readNATSLine(reader)
+		if err != nil {
+			return err
+		}
+		switch {
+		case line == "PING":
+			if _, err := conn.Write([]byte("PONG\r\n")); err != nil {
+				return err
+			}
+		case strings.HasPrefix(line, "-ERR"):
+			return fmt.Errorf("nats error: %s", truncateNATSLine(line))
+		case strings.HasPrefix(line, "MSG "), strings.HasPrefix(line, "HMSG "):
+			// MSG <subject> <sid> [reply-to] <#bytes>
+			// HMSG <subject> <sid> [reply-to] <#header bytes> <#total bytes>
+			fields := strings.Fields(line)
+			size, err := strconv.Atoi(fields[len(fields)-1])
+			if err != nil || len(fields) < 4 {
+				return fmt.Errorf("malformed nats message: %s", truncateNATSLine(line))
+			}
+			payload, err := readNATSPayload(reader, size)
+			if err != nil {
+				return err
+			}
+			if fields[0] == "HMSG" {
+				headerSize, err := strconv.Atoi(fields[len(fields)-2])
+				if err != nil || headerSize > size {
+					return fmt.Errorf("malformed nats message: %s", truncateNATSLine(line))
+				}
+				// 没有 stream 绑定该 subject 时服务端回复 "NATS/1.0 503" 状态头
+				status, _, _ := strings.Cut(string(payload[:headerSize]), "\r\n")
+				if code := strings.TrimSpace(strings.TrimPrefix(status, "NATS/1.0")); code != "" {
+					return fmt.Errorf("jetstream publish failed: %s", code)
+				}
+				payload = payload[headerSize:]
+			}
+			if err := checkNATSPubAck(payload); err != nil {
+				return err
+			}
+			acked++
+		}
+	}
+	return nil
+}
+
+func checkNATSPubAck(payload []byte) error {
+	var ack natsPubAck
+	if err := json.Unmarshal(payload, &ack); err != nil {
+		return fmt.Errorf("parse jetstream ack: %w", err)
+	}
+	if ack.Error != nil {
+		return fmt.Errorf("jetstream rejected message: %d %s", ack.Error.Code, ack.Error.Description)
+	}
+	return nil
+}
+
+func readNATSLine(reader *bufio.Reader) (string, error) {
+	line, err := reader.ReadSlice('\n')
+	if err != nil {
+		if errors.Is(err, bufio.ErrBufferFull) {
+			return "", errors.New("nats control line too long")
+		}
+		return "", err
+	}
+	return strings.TrimRight(string(line), "\r\n"), nil
+}
+
+func readNATSPayload(reader *bufio.Reader, size int) ([]byte, error) {
+	if size < 0 || size > 1<<20 {
+		return nil, fmt.Errorf("unexpected nats payload size %d", size)
+	}
+	// 负载之后紧跟 \r\n
+	payload := make([]byte, size+2)
+	if _, err := io.ReadFull(reader, payload); err != nil {
+		return nil, err
+	}
+	return payload[:size], nil
+}
+
+func truncateNATSLine(line string) string {
+	if len(line) > 256 {
+		return line[:256]
+	}
+	return line
+}
@@ -0,0 +1,127 @@
+package repository
+
+import (
+	"context"
+	"encoding/json"
+	"log"
+	"sync"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/service"
+	"github.com/redis/go-redis/v9"
+)
+
+const (
+	featureFlagCacheKey  = "feature_flags"
+	featureFlagPubSubKey = "feature_flags_updated"
+	featureFlagCacheTTL  = 24 * time.Hour
+)
+
+type featureFlagCache struct {
+	rdb        *redis.Client
+	localCache []*service.FeatureFlag
+	localMu    sync.RWMutex
+}
+
+// NewFeatureFlagCache 创建功能开关缓存
+func NewFeatureFlagCache(rdb *redis.Client) service.FeatureFlagCache {
+	return &featureFlagCache{
+		rdb: rdb,
+	}
+}
+
+// Get 从缓存获取功能开关列表
+func (c *featureFlagCache) Get(ctx context.Context) ([]*service.FeatureFlag, bool) {
+	// 先检查本地缓存
+	c.localMu.RLock()
+	if c.localCache != nil {
+		flags := c.localCache
+		c.localMu.RUnlock()
+		return flags, true
+	}
+	c.localMu.RUnlock()
+
+	// 从 Redis 获取
+	data, err := c.rdb.Get(ctx, featureFlagCacheKey).Bytes()
+	if err != nil {
+		if err != redis.Nil {
+			log.Printf("[FeatureFlagCache] Failed to get from Redis: %v", err)
+		}
+		return nil, false
+	}
+
+	var flags []*service.FeatureFlag
+	if err := json.Unmarshal(data, &flags); err != nil {
+		log.Printf("[FeatureFlagCache] Failed to unmarshal flags: %v", err)
+		return nil, false
+	}
+
+	// 更新本地缓存
+	c.localMu.Lock()
+	c.localCache = flags
+	c.localMu.Unlock()
+
+	return flags, true
+}
+
+// Set 设置缓存
+func (c *featureFlagCache) Set(ctx context.Context, flags []*service.FeatureFlag) error {
+	data, err := json.Marshal(flags)
+	if err != nil {
+		return err
+	}
+
+	if err := c.rdb.Set(ctx, featureFlagCacheKey, data, featureFlagCacheTTL).Err(); err != nil {
+		return err
+	}
+
+	// 更新本地缓存
+	c.localMu.Lock()
+	c.localCache = flags
+	c.localMu.Unlock()
+
+	return nil
+}
+
+// Invalidate 使缓存失效
+func (c *featureFlagCache) Invalidate(ctx context.Context) error {
+	// 清除本地缓存
+	c.localMu.Lock()
+	c.localCache = nil
+	c.localMu.Unlock()
+
+	// 清除 Redis 缓存
+	return c.rdb.Del(ctx, featureFlagCacheKey).Err()
+}
+
+// NotifyUpdate 通知其他实例刷新缓存
+func (c *featureFlagCache) NotifyUpdate(ctx context.Context) error {
+	return c.rdb.Publish(ctx, featureFlagPubSubKey, "refresh").Err()
+}
+
+// SubscribeUpdates 订阅缓存更新通知
+func (c *featureFlagCache) SubscribeUpdates(ctx context.Context, handler func()) {
+	go func() {
+		sub := c.rdb.Subscribe(ctx, featureFlagPubSubKey)
+		defer func() { _ = sub.Close() }()
+
+		ch := sub.Channel()
+		for {
+			select {
+			case <-ctx.Done():
+				return
+			case msg := <-ch:
+				if msg == nil {
+					return
+				}
+				// 清除本地缓存，下次访问时会从 Redis 或数据库重新加载
+				c.localMu.Lock()
+				c.localCache = nil
+				c.localMu.Unlock()
+
+				// 调用处理函数
+				handler()
+			}
+		}
+	}()
+}
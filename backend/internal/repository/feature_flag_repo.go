@@ -0,0 +1,81 @@
+package repository
+
+import (
+	"context"
+	"database/sql"
+
+	"github.com/Wei-Shaw/sub2api/internal/service"
+	"github.com/lib/pq"
+)
+
+type featureFlagRepository struct {
+	db *sql.DB
+}
+
+// NewFeatureFlagRepository 创建功能开关仓储
+func NewFeatureFlagRepository(sqlDB *sql.DB) service.FeatureFlagRepository {
+	return &featureFlagRepository{db: sqlDB}
+}
+
+func (r *featureFlagRepository) List(ctx context.Context) ([]*service.FeatureFlag, error) {
+	rows, err := r.db.QueryContext(ctx, `
+		SELECT key, description, enabled, rollout_percent, environments, user_ids, group_ids, api_key_ids,
+			updated_by, created_at, updated_at
+		FROM feature_flags
+		ORDER BY key
+	`)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	flags := make([]*service.FeatureFlag, 0)
+	for rows.Next() {
+		var (
+			f         service.FeatureFlag
+			updatedBy sql.NullInt64
+		)
+		if err := rows.Scan(&f.Key, &f.Description, &f.Enabled, &f.RolloutPercent,
+			pq.Array(&f.Environments), pq.Array(&f.UserIDs), pq.Array(&f.GroupIDs), pq.Array(&f.APIKeyIDs),
+			&updatedBy, &f.CreatedAt, &f.UpdatedAt); err != nil {
+			return nil, err
+		}
+		if updatedBy.Valid {
+			v := updatedBy.Int64
+			f.UpdatedBy = &v
+		}
+		flags = append(flags, &f)
+	}
+	return flags, rows.Err()
+}
+
+func (r *featureFlagRepository) Upsert(ctx context.Context, f *service.FeatureFlag) error {
+	return r.db.QueryRowContext(ctx, `
+		INSERT INTO feature_flags (key, description, enabled, rollout_percent, environments, user_ids, group_ids, api_key_ids, updated_by)
+		VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+		ON CONFLICT (key) DO UPDATE SET
+			description = EXCLUDED.description,
+			enabled = EXCLUDED.enabled,
+			rollout_percent = EXCLUDED.rollout_percent,
+			environments = EXCLUDED.environments,
+			user_ids = EXCLUDED.user_ids,
+			group_ids = EXCLUDED.group_ids,
+			api_key_ids = EXCLUDED.api_key_ids,
+			updated_by = EXCLUDED.updated_by,
+			updated_at = NOW()
+		RETURNING created_at, updated_at
+	`, f.Key, f.Description, f.Enabled, f.RolloutPercent,
+		pq.Array(f.Environments), pq.Array(f.UserIDs), pq.Array(f.GroupIDs), pq.Array(f.APIKeyIDs),
+		nullInt64(f.UpdatedBy)).Scan(&f.CreatedAt, &f.UpdatedAt)
+}
+
+func (r *featureFlagRepository) Delete(ctx context.Context, key string) error {
+	res, err := r.db.ExecContext(ctx, `DELETE FROM feature_flags WHERE key = $1`, key)
+	if err != nil {
+		return err
+	}
+	if n, _ := res.RowsAffected(); n == 0 {
+		return service.ErrFeatureFlagNotFound
+	}
+	return nil
+}
@@ -0,0 +1,130 @@
+package repository
+
+import (
+	"context"
+	"database/sql"
+	"errors"
+
+	"github.com/Wei-Shaw/sub2api/internal/service"
+)
+
+const gatewayFileColumns = `id, user_id, api_key_id, filename, media_type, size_bytes, sha256, object_key, created_at, expires_at`
+
+type gatewayFileRepository struct {
+	db *sql.DB
+}
+
+// NewGatewayFileRepository 创建网关文件元数据仓储
+func NewGatewayFileRepository(sqlDB *sql.DB) service.GatewayFileRepository {
+	return &gatewayFileRepository{db: sqlDB}
+}
+
+func (r *gatewayFileRepository) Create(ctx context.Context, file *service.GatewayFile) error {
+	_, err := r.db.ExecContext(ctx, `
+INSERT INTO gateway_files (id, user_id, api_key_id, filename, media_type, size_bytes, sha256, object_key, created_at, expires_at)
+VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+`, file.ID, file.UserID, file.APIKeyID, file.Filename, file.MediaType, file.SizeBytes, file.SHA256, file.ObjectKey, file.CreatedAt, file.ExpiresAt)
+	return err
+}
+
+func (r *gatewayFileRepository) Get(ctx context.Context, userID int64, id string) (*service.GatewayFile, error) {
+	file, err := scanGatewayFile(r.db.QueryRowContext(ctx, `
+SELECT `+gatewayFileColumns+`
+FROM gateway_files
+WHERE user_id = $1 AND id = $2 AND expires_at > NOW()
+`, userID, id))
+	if errors.Is(err, sql.ErrNoRows) {
+		return nil, service.ErrAttachmentNotFound
+	}
+	return file, err
+}
+
+func (r *gatewayFileRepository) List(ctx context.Context, userID int64, limit int) ([]*service.GatewayFile, error) {
+	rows, err := r.db.QueryContext(ctx, `
+SELECT `+gatewayFileColumns+`
+FROM gateway_files
+WHERE user_id = $1 AND expires_at > NOW()
+ORDER BY created_at DESC, id
+LIMIT $2
+`, userID, limit)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	out := make([]*service.GatewayFile, 0)
+	for rows.Next() {
+		file, err := scanGatewayFile(rows)
+		if err != nil {
+			return nil, err
+		}
+		out = append(out, file)
+	}
+	if err := rows.Err(); err != nil {
+		return nil, err
+	}
+	return out, nil
+}
+
+func (r *gatewayFileRepository) Delete(ctx context.Context, userID int64, id string) (string, error) {
+	var key string
+	err := r.db.QueryRowContext(ctx, `
+DELETE FROM gateway_files WHERE user_id = $1 AND id = $2
+RETURNING object_key
+`, userID, id).Scan(&key)
+	if errors.Is(err, sql.ErrNoRows) {
+		return "", service.ErrAttachmentNotFound
+	}
+	return key, err
+}
+
+func (r *gatewayFileRepository) DeleteExpired(ctx context.Context, limit int) ([]string, error) {
+	rows, err := r.db.QueryContext(ctx, `
+DELETE FROM gateway_files
+WHERE id IN (SELECT id FROM gateway_files WHERE expires_at <= NOW() ORDER BY expires_at LIMIT $1)
+RETURNING object_key
+`, limit)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	keys := make([]string, 0)
+	for rows.Next() {
+		var key string
+		if err := rows.Scan(&key); err != nil {
+			return nil, err
+		}
+		keys = append(keys, key)
+	}
+	if err := rows.Err(); err != nil {
+		return nil, err
+	}
+	return keys, nil
+}
+
+func scanGatewayFile(row interface{ Scan(...any) error }) (*service.GatewayFile, error) {
+	var (
+		file     service.GatewayFile
+		apiKeyID sql.NullInt64
+	)
+	if err := row.Scan(
+		&file.ID,
+		&file.UserID,
+		&apiKeyID,
+		&file.Filename,
+		&file.MediaType,
+		&file.SizeBytes,
+		&file.SHA256,
+		&file.ObjectKey,
+		&file.CreatedAt,
+		&file.ExpiresAt,
+	); err != nil {
+		return nil, err
+	}
+	if apiKeyID.Valid {
+		v := apiKeyID.Int64
+		file.APIKeyID = &v
+	}
+	return &file, nil
+}
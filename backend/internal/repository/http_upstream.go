@@ -1,12 +1,15 @@
 package repository
 
 import (
+	"context"
+	"crypto/tls"
 	"errors"
 	"fmt"
 	"io"
 	"log/slog"
 	"net"
 	"net/http"
+	"net/http/httptrace"
 	"net/url"
 	"strings"
 	"sync"
@@ -18,6 +21,7 @@ import (
 	"github.com/Wei-Shaw/sub2api/internal/pkg/tlsfingerprint"
 	"github.com/Wei-Shaw/sub2api/internal/service"
 	"github.com/Wei-Shaw/sub2api/internal/util/urlvalidator"
+	"golang.org/x/net/http2"
 )
 
 // 默认配置常量
@@ -56,6 +60,10 @@ type poolSettings struct {
 	maxConnsPerHost       int           // 每主机最大连接数（含活跃）
 	idleConnTimeout       time.Duration // 空闲连接超时时间
 	responseHeaderTimeout time.Duration // 等待响应头超时时间
+	forceHTTP2            bool          // 是否尝试 HTTP/2（自定义拨号/代理时 Go 默认不尝试）
+	disableKeepAlives     bool          // 是否关闭连接复用
+	// dialContext 直连与连接 HTTP 代理时使用的拨号函数（DNS 缓存/固定解析），nil 使用 Go 默认
+	dialContext func(ctx context.Context, network, addr string) (net.Conn, error)
 }
 
 // upstreamClientEntry 上游客户端缓存条目
@@ -64,6 +72,7 @@ type upstreamClientEntry struct {
 	client   *http.Client // HTTP 客户端实例
 	proxyKey string       // 代理标识（用于检测代理变更）
 	poolKey  string       // 连接池配置标识（用于检测配置变更）
+	adapter  string       // 创建该客户端的上游平台（未配置平台覆盖参数时各平台共享客户端），用于统计
 	lastUsed int64        // 最后使用时间戳（纳秒），用于 LRU 淘汰
 	inFlight int64        // 当前进行中的请求数，>0 时不可淘汰
 }
@@ -86,9 +95,14 @@ type upstreamClientEntry struct {
 // 7. 代理变更时清空旧连接池，避免复用错误代理
 // 8. 账号并发数与连接池上限对应（账号隔离策略下）
 type httpUpstreamService struct {
-	cfg     *config.Config                  // 全局配置
-	mu      sync.RWMutex                    // 保护 clients map 的读写锁
-	clients map[string]*upstreamClientEntry // 客户端缓存池，key 由隔离策略决定
+	cfg             *config.Config                   // 全局配置
+	mu              sync.RWMutex                     // 保护 clients map 的读写锁
+	clients         map[string]*upstreamClientEntry  // 客户端缓存池，key 由隔离策略决定
+	dialer          *upstreamDialer                  // 共享拨号器（DNS 缓存/固定解析/happy eyeballs）
+	headerAllowlist *service.UpstreamHeaderAllowlist // 策略可附加的自定义请求头白名单
+
+	connsNew    atomic.Int64 // 新建连接的请求数
+	connsReused atomic.Int64 // 复用空闲连接的请求数
 }
 
 // NewHTTPUpstream 创建通用 HTTP 上游服务
@@ -96,14 +110,25 @@ type httpUpstreamService struct {
 //
 // 参数:
 //   - cfg: 全局配置，包含连接池参数和隔离策略
+//   - headerAllowlist: API Key / 分组策略可附加的自定义请求头白名单，nil 表示不附加
 //
 // 返回:
 //   - service.HTTPUpstream 接口实现
-func NewHTTPUpstream(cfg *config.Config) service.HTTPUpstream {
-	return &httpUpstreamService{
-		cfg:     cfg,
-		clients: make(map[string]*upstreamClientEntry),
+func NewHTTPUpstream(cfg *config.Config, headerAllowlist *service.UpstreamHeaderAllowlist) service.HTTPUpstream {
+	if cfg != nil {
+		// 加载配置中的自定义 TLS 指纹模板
+		tlsfingerprint.InitGlobalRegistry(&cfg.Gateway.TLSFingerprint)
+	}
+	upstream := &httpUpstreamService{
+		cfg:             cfg,
+		clients:         make(map[string]*upstreamClientEntry),
+		dialer:          newUpstreamDialer(cfg),
+		headerAllowlist: headerAllowlist,
+	}
+	if cfg != nil && cfg.FaultInjection.Enabled {
+		return newFaultInjectingUpstream(upstream, cfg.FaultInjection)
 	}
+	return upstream
 }
 
 // Do 执行 HTTP 请求
@@ -126,15 +151,18 @@ func (s *httpUpstreamService) Do(req *http.Request, proxyURL string, accountID i
 	if err := s.validateRequestHost(req); err != nil {
 		return nil, err
 	}
+	// 按 API Key / 分组策略附加自定义请求头（白名单内）
+	service.ApplyUpstreamHeaderPolicy(req, s.headerAllowlist)
 
 	// 获取或创建对应的客户端，并标记请求占用
-	entry, err := s.acquireClient(proxyURL, accountID, accountConcurrency)
+	adapter := service.UpstreamAdapterFromContext(req.Context())
+	entry, err := s.acquireClient(proxyURL, accountID, accountConcurrency, adapter)
 	if err != nil {
 		return nil, err
 	}
 
-	// 执行请求
-	resp, err := entry.client.Do(req)
+	// 执行请求（按 context 中的分阶段上游超时）
+	resp, err := service.DoWithUpstreamTimeouts(s.traceConnReuse(req), entry.client.Do)
 	if err != nil {
 		// 请求失败，立即减少计数
 		atomic.AddInt64(&entry.inFlight, -1)
@@ -164,7 +192,9 @@ func (s *httpUpstreamService) Do(req *http.Request, proxyURL string, accountID i
 //
 // TLS 指纹说明:
 //   - 当 enableTLSFingerprint=true 时，使用 utls 库模拟 Claude CLI 的 TLS 指纹
-//   - 指纹模板根据 accountID % len(profiles) 自动选择
+//   - 请求 context 通过 tlsfingerprint.WithProfileName 指定模板时使用该模板（如浏览器模板），
+//     否则根据 accountID % len(profiles) 自动选择
+//   - 模板带 HTTP/2 参数时使用 HTTP/2 传输，并按模板发送 SETTINGS
 //   - 支持直连、HTTP/HTTPS 代理、SOCKS5 代理三种场景
 func (s *httpUpstreamService) DoWithTLS(req *http.Request, proxyURL string, accountID int64, accountConcurrency int, enableTLSFingerprint bool) (*http.Response, error) {
 	// 如果未启用 TLS 指纹，直接使用标准请求路径
@@ -186,10 +216,12 @@ func (s *httpUpstreamService) DoWithTLS(req *http.Request, proxyURL string, acco
 	if err := s.validateRequestHost(req); err != nil {
 		return nil, err
 	}
+	// 按 API Key / 分组策略附加自定义请求头（白名单内）
+	service.ApplyUpstreamHeaderPolicy(req, s.headerAllowlist)
 
-	// 获取 TLS 指纹 Profile
+	// 获取 TLS 指纹 Profile（context 指定的模板优先）
 	registry := tlsfingerprint.GlobalRegistry()
-	profile := registry.GetProfileByAccountID(accountID)
+	profileKey, profile := registry.ResolveProfile(tlsfingerprint.ProfileNameFromContext(req.Context()), accountID)
 	if profile == nil {
 		// 如果获取不到 profile，回退到普通请求
 		slog.Debug("tls_fingerprint_no_profile", "account_id", accountID, "fallback", "standard_request")
@@ -199,14 +231,15 @@ func (s *httpUpstreamService) DoWithTLS(req *http.Request, proxyURL string, acco
 	slog.Debug("tls_fingerprint_using_profile", "account_id", accountID, "profile", profile.Name, "grease", profile.EnableGREASE)
 
 	// 获取或创建带 TLS 指纹的客户端
-	entry, err := s.acquireClientWithTLS(proxyURL, accountID, accountConcurrency, profile)
+	adapter := service.UpstreamAdapterFromContext(req.Context())
+	entry, err := s.acquireClientWithTLS(proxyURL, accountID, accountConcurrency, adapter, profileKey, profile)
 	if err != nil {
 		slog.Debug("tls_fingerprint_acquire_client_failed", "account_id", accountID, "error", err)
 		return nil, err
 	}
 
-	// 执行请求
-	resp, err := entry.client.Do(req)
+	// 执行请求（按 context 中的分阶段上游超时）
+	resp, err := service.DoWithUpstreamTimeouts(s.traceConnReuse(req), entry.client.Do)
 	if err != nil {
 		// 请求失败，立即减少计数
 		atomic.AddInt64(&entry.inFlight, -1)
@@ -227,18 +260,18 @@ func (s *httpUpstreamService) DoWithTLS(req *http.Request, proxyURL string, acco
 }
 
 // acquireClientWithTLS 获取或创建带 TLS 指纹的客户端
-func (s *httpUpstreamService) acquireClientWithTLS(proxyURL string, accountID int64, accountConcurrency int, profile *tlsfingerprint.Profile) (*upstreamClientEntry, error) {
-	return s.getClientEntryWithTLS(proxyURL, accountID, accountConcurrency, profile, true, true)
+func (s *httpUpstreamService) acquireClientWithTLS(proxyURL string, accountID int64, accountConcurrency int, adapter, profileKey string, profile *tlsfingerprint.Profile) (*upstreamClientEntry, error) {
+	return s.getClientEntryWithTLS(proxyURL, accountID, accountConcurrency, adapter, profileKey, profile, true, true)
 }
 
 // getClientEntryWithTLS 获取或创建带 TLS 指纹的客户端条目
-// TLS 指纹客户端使用独立的缓存键，与普通客户端隔离
-func (s *httpUpstreamService) getClientEntryWithTLS(proxyURL string, accountID int64, accountConcurrency int, profile *tlsfingerprint.Profile, markInFlight bool, enforceLimit bool) (*upstreamClientEntry, error) {
+// TLS 指纹客户端使用独立的缓存键，与普通客户端隔离；不同模板的连接互不复用
+func (s *httpUpstreamService) getClientEntryWithTLS(proxyURL string, accountID int64, accountConcurrency int, adapter, profileKey string, profile *tlsfingerprint.Profile, markInFlight bool, enforceLimit bool) (*upstreamClientEntry, error) {
 	isolation := s.getIsolationMode()
 	proxyKey, parsedProxy := normalizeProxyURL(proxyURL)
-	// TLS 指纹客户端使用独立的缓存键，加 "tls:" 前缀
-	cacheKey := "tls:" + buildCacheKey(isolation, proxyKey, accountID)
-	poolKey := s.buildPoolKey(isolation, accountConcurrency) + ":tls"
+	// TLS 指纹客户端使用独立的缓存键，加 "tls:<模板>:" 前缀
+	cacheKey := "tls:" + profileKey + ":" + s.adapterCacheKeyPrefix(adapter) + buildCacheKey(isolation, proxyKey, accountID)
+	poolKey := s.buildPoolKey(isolation, accountConcurrency, adapter) + ":tls"
 
 	now := time.Now()
 	nowUnix := now.UnixNano()
@@ -289,7 +322,7 @@ func (s *httpUpstreamService) getClientEntryWithTLS(proxyURL string, accountID i
 
 	// 创建带 TLS 指纹的 Transport
 	slog.Debug("tls_fingerprint_creating_new_client", "account_id", accountID, "cache_key", cacheKey, "proxy", proxyKey)
-	settings := s.resolvePoolSettings(isolation, accountConcurrency)
+	settings := s.resolvePoolSettings(isolation, accountConcurrency, adapter)
 	transport, err := buildUpstreamTransportWithTLSFingerprint(settings, parsedProxy, profile)
 	if err != nil {
 		s.mu.Unlock()
@@ -305,6 +338,7 @@ func (s *httpUpstreamService) getClientEntryWithTLS(proxyURL string, accountID i
 		client:   client,
 		proxyKey: proxyKey,
 		poolKey:  poolKey,
+		adapter:  adapter,
 	}
 	atomic.StoreInt64(&entry.lastUsed, nowUnix)
 	if markInFlight {
@@ -318,6 +352,52 @@ func (s *httpUpstreamService) getClientEntryWithTLS(proxyURL string, accountID i
 	return entry, nil
 }
 
+// traceConnReuse 统计请求复用空闲连接与新建连接的次数
+func (s *httpUpstreamService) traceConnReuse(req *http.Request) *http.Request {
+	trace := &httptrace.ClientTrace{
+		GotConn: func(info httptrace.GotConnInfo) {
+			if info.Reused {
+				s.connsReused.Add(1)
+			} else {
+				s.connsNew.Add(1)
+			}
+		},
+	}
+	return req.WithContext(httptrace.WithClientTrace(req.Context(), trace))
+}
+
+// PoolStats 返回上游客户端缓存与连接统计（实现 service.UpstreamPoolStatsProvider）
+func (s *httpUpstreamService) PoolStats() service.UpstreamPoolStats {
+	stats := service.UpstreamPoolStats{
+		ConnsNew:       s.connsNew.Load(),
+		ConnsReused:    s.connsReused.Load(),
+		Dials:          s.dialer.dials.Load(),
+		DialErrors:     s.dialer.dialErrors.Load(),
+		DNSCacheHits:   s.dialer.cacheHits.Load(),
+		DNSCacheMisses: s.dialer.cacheMisses.Load(),
+		Adapters:       make(map[string]service.UpstreamAdapterPoolStats),
+	}
+	s.mu.RLock()
+	for key, entry := range s.clients {
+		inFlight := atomic.LoadInt64(&entry.inFlight)
+		stats.Clients++
+		stats.InFlight += inFlight
+		if strings.HasPrefix(key, "tls:") {
+			stats.TLSFingerprintClients++
+		}
+		adapter := entry.adapter
+		if adapter == "" {
+			adapter = "default"
+		}
+		adapterStats := stats.Adapters[adapter]
+		adapterStats.Clients++
+		adapterStats.InFlight += inFlight
+		stats.Adapters[adapter] = adapterStats
+	}
+	s.mu.RUnlock()
+	return stats
+}
+
 func (s *httpUpstreamService) shouldValidateResolvedIP() bool {
 	if s.cfg == nil {
 		return false
@@ -354,8 +434,8 @@ func (s *httpUpstreamService) redirectChecker(req *http.Request, via []*http.Req
 
 // acquireClient 获取或创建客户端，并标记为进行中请求
 // 用于请求路径，避免在获取后被淘汰
-func (s *httpUpstreamService) acquireClient(proxyURL string, accountID int64, accountConcurrency int) (*upstreamClientEntry, error) {
-	return s.getClientEntry(proxyURL, accountID, accountConcurrency, true, true)
+func (s *httpUpstreamService) acquireClient(proxyURL string, accountID int64, accountConcurrency int, adapter string) (*upstreamClientEntry, error) {
+	return s.getClientEntry(proxyURL, accountID, accountConcurrency, adapter, true, true)
 }
 
 // getOrCreateClient 获取或创建客户端
@@ -374,22 +454,22 @@ func (s *httpUpstreamService) acquireClient(proxyURL string, accountID int64, ac
 //   - account: 按账户隔离，同一账户共享客户端（代理变更时重建）
 //   - account_proxy: 按账户+代理组合隔离，最细粒度
 func (s *httpUpstreamService) getOrCreateClient(proxyURL string, accountID int64, accountConcurrency int) *upstreamClientEntry {
-	entry, _ := s.getClientEntry(proxyURL, accountID, accountConcurrency, false, false)
+	entry, _ := s.getClientEntry(proxyURL, accountID, accountConcurrency, "", false, false)
 	return entry
 }
 
 // getClientEntry 获取或创建客户端条目
 // markInFlight=true 时会标记进行中请求，用于请求路径防止被淘汰
 // enforceLimit=true 时会限制客户端数量，超限且无法淘汰时返回错误
-func (s *httpUpstreamService) getClientEntry(proxyURL string, accountID int64, accountConcurrency int, markInFlight bool, enforceLimit bool) (*upstreamClientEntry, error) {
+func (s *httpUpstreamService) getClientEntry(proxyURL string, accountID int64, accountConcurrency int, adapter string, markInFlight bool, enforceLimit bool) (*upstreamClientEntry, error) {
 	// 获取隔离模式
 	isolation := s.getIsolationMode()
 	// 标准化代理 URL 并解析
 	proxyKey, parsedProxy := normalizeProxyURL(proxyURL)
-	// 构建缓存键（根据隔离策略不同）
-	cacheKey := buildCacheKey(isolation, proxyKey, accountID)
+	// 构建缓存键（根据隔离策略不同；配置了平台覆盖参数时按平台区分）
+	cacheKey := s.adapterCacheKeyPrefix(adapter) + buildCacheKey(isolation, proxyKey, accountID)
 	// 构建连接池配置键（用于检测配置变更）
-	poolKey := s.buildPoolKey(isolation, accountConcurrency)
+	poolKey := s.buildPoolKey(isolation, accountConcurrency, adapter)
 
 	now := time.Now()
 	nowUnix := now.UnixNano()
@@ -432,7 +512,7 @@ func (s *httpUpstreamService) getClientEntry(proxyURL string, accountID int64, a
 	}
 
 	// 缓存未命中或需要重建，创建新客户端
-	settings := s.resolvePoolSettings(isolation, accountConcurrency)
+	settings := s.resolvePoolSettings(isolation, accountConcurrency, adapter)
 	transport, err := buildUpstreamTransport(settings, parsedProxy)
 	if err != nil {
 		s.mu.Unlock()
@@ -446,6 +526,7 @@ func (s *httpUpstreamService) getClientEntry(proxyURL string, accountID int64, a
 		client:   client,
 		proxyKey: proxyKey,
 		poolKey:  poolKey,
+		adapter:  adapter,
 	}
 	atomic.StoreInt64(&entry.lastUsed, nowUnix)
 	if markInFlight {
@@ -611,15 +692,34 @@ func (s *httpUpstreamService) clientIdleTTL() time.Duration {
 // 参数:
 //   - isolation: 隔离模式
 //   - accountConcurrency: 账户并发限制
+//   - adapter: 上游平台，用于应用 gateway.http_client.adapters 覆盖参数
 //
 // 返回:
 //   - poolSettings: 连接池配置
 //
 // 说明:
+//   - 平台覆盖参数优先于全局参数
 //   - 账户隔离模式下，连接池大小与账户并发数对应
 //   - 这确保了单账户不会占用过多连接资源
-func (s *httpUpstreamService) resolvePoolSettings(isolation string, accountConcurrency int) poolSettings {
+func (s *httpUpstreamService) resolvePoolSettings(isolation string, accountConcurrency int, adapter string) poolSettings {
 	settings := defaultPoolSettings(s.cfg)
+	if s.dialer != nil {
+		settings.dialContext = s.dialer.DialContext
+	}
+	if override, ok := s.adapterConfig(adapter); ok {
+		if override.HTTP2 != nil {
+			settings.forceHTTP2 = *override.HTTP2
+		}
+		if override.MaxIdleConnsPerHost > 0 {
+			settings.maxIdleConnsPerHost = override.MaxIdleConnsPerHost
+		}
+		if override.MaxConnsPerHost > 0 {
+			settings.maxConnsPerHost = override.MaxConnsPerHost
+		}
+		if override.IdleConnTimeoutSeconds > 0 {
+			settings.idleConnTimeout = time.Duration(override.IdleConnTimeoutSeconds) * time.Second
+		}
+	}
 	// 账户隔离模式下，根据账户并发数调整连接池大小
 	if (isolation == config.ConnectionPoolIsolationAccount || isolation == config.ConnectionPoolIsolationAccountProxy) && accountConcurrency > 0 {
 		settings.maxIdleConns = accountConcurrency
@@ -635,16 +735,39 @@ func (s *httpUpstreamService) resolvePoolSettings(isolation string, accountConcu
 // 参数:
 //   - isolation: 隔离模式
 //   - accountConcurrency: 账户并发限制
+//   - adapter: 上游平台（仅配置了覆盖参数时计入）
 //
 // 返回:
 //   - string: 配置键
-func (s *httpUpstreamService) buildPoolKey(isolation string, accountConcurrency int) string {
+func (s *httpUpstreamService) buildPoolKey(isolation string, accountConcurrency int, adapter string) string {
+	key := "default"
 	if isolation == config.ConnectionPoolIsolationAccount || isolation == config.ConnectionPoolIsolationAccountProxy {
 		if accountConcurrency > 0 {
-			return fmt.Sprintf("account:%d", accountConcurrency)
+			key = fmt.Sprintf("account:%d", accountConcurrency)
 		}
 	}
-	return "default"
+	if _, ok := s.adapterConfig(adapter); ok {
+		key += "|adapter:" + adapter
+	}
+	return key
+}
+
+// adapterConfig 返回平台的客户端覆盖参数，未配置时 ok=false
+func (s *httpUpstreamService) adapterConfig(adapter string) (config.GatewayHTTPClientAdapterConfig, bool) {
+	if s.cfg == nil || adapter == "" {
+		return config.GatewayHTTPClientAdapterConfig{}, false
+	}
+	override, ok := s.cfg.Gateway.HTTPClient.Adapters[adapter]
+	return override, ok
+}
+
+// adapterCacheKeyPrefix 配置了平台覆盖参数时，按平台拆分客户端缓存；
+// 未配置的平台共享同一客户端，避免无谓地放大连接池数量
+func (s *httpUpstreamService) adapterCacheKeyPrefix(adapter string) string {
+	if _, ok := s.adapterConfig(adapter); ok {
+		return "adapter:" + adapter + "|"
+	}
+	return ""
 }
 
 // buildCacheKey 构建客户端缓存键
@@ -746,13 +869,18 @@ func defaultPoolSettings(cfg *config.Config) poolSettings {
 		}
 	}
 
-	return poolSettings{
+	settings := poolSettings{
 		maxIdleConns:          maxIdleConns,
 		maxIdleConnsPerHost:   maxIdleConnsPerHost,
 		maxConnsPerHost:       maxConnsPerHost,
 		idleConnTimeout:       idleConnTimeout,
 		responseHeaderTimeout: responseHeaderTimeout,
 	}
+	if cfg != nil {
+		settings.forceHTTP2 = cfg.Gateway.HTTPClient.HTTP2
+		settings.disableKeepAlives = cfg.Gateway.HTTPClient.DisableKeepAlives
+	}
+	return settings
 }
 
 // buildUpstreamTransport 构建上游请求的 Transport
@@ -772,6 +900,8 @@ func defaultPoolSettings(cfg *config.Config) poolSettings {
 //   - MaxConnsPerHost: 每主机最大连接数（达到后新请求等待）
 //   - IdleConnTimeout: 空闲连接超时（超时后关闭）
 //   - ResponseHeaderTimeout: 等待响应头超时（不影响流式传输）
+//   - ForceAttemptHTTP2: 设置了 DialContext/代理后仍尝试 h2
+//   - DialContext: 共享拨号器；SOCKS5 代理会覆盖为代理拨号
 func buildUpstreamTransport(settings poolSettings, proxyURL *url.URL) (*http.Transport, error) {
 	transport := &http.Transport{
 		MaxIdleConns:          settings.maxIdleConns,
@@ -779,6 +909,9 @@ func buildUpstreamTransport(settings poolSettings, proxyURL *url.URL) (*http.Tra
 		MaxConnsPerHost:       settings.maxConnsPerHost,
 		IdleConnTimeout:       settings.idleConnTimeout,
 		ResponseHeaderTimeout: settings.responseHeaderTimeout,
+		ForceAttemptHTTP2:     settings.forceHTTP2,
+		DisableKeepAlives:     settings.disableKeepAlives,
+		DialContext:           settings.dialContext,
 	}
 	if err := proxyutil.ConfigureTransportProxy(transport, proxyURL); err != nil {
 		return nil, err
@@ -795,20 +928,21 @@ func buildUpstreamTransport(settings poolSettings, proxyURL *url.URL) (*http.Tra
 //   - profile: TLS 指纹配置
 //
 // 返回:
-//   - *http.Transport: 配置好的 Transport 实例
+//   - http.RoundTripper: 配置好的 Transport 实例（模板带 HTTP/2 参数时为 HTTP/2 Transport）
 //   - error: 配置错误
 //
 // 代理类型处理:
 //   - nil/空: 直连，使用 TLSFingerprintDialer
 //   - http/https: HTTP 代理，使用 HTTPProxyDialer（CONNECT 隧道 + utls 握手）
 //   - socks5: SOCKS5 代理，使用 SOCKS5ProxyDialer（SOCKS5 隧道 + utls 握手）
-func buildUpstreamTransportWithTLSFingerprint(settings poolSettings, proxyURL *url.URL, profile *tlsfingerprint.Profile) (*http.Transport, error) {
+func buildUpstreamTransportWithTLSFingerprint(settings poolSettings, proxyURL *url.URL, profile *tlsfingerprint.Profile) (http.RoundTripper, error) {
 	transport := &http.Transport{
 		MaxIdleConns:          settings.maxIdleConns,
 		MaxIdleConnsPerHost:   settings.maxIdleConnsPerHost,
 		MaxConnsPerHost:       settings.maxConnsPerHost,
 		IdleConnTimeout:       settings.idleConnTimeout,
 		ResponseHeaderTimeout: settings.responseHeaderTimeout,
+		DisableKeepAlives:     settings.disableKeepAlives,
 		// 禁用默认的 TLS，我们使用自定义的 DialTLSContext
 		ForceAttemptHTTP2: false,
 	}
@@ -841,9 +975,44 @@ func buildUpstreamTransportWithTLSFingerprint(settings poolSettings, proxyURL *u
 		}
 	}
 
+	if profile != nil && profile.HTTP2 != nil && transport.DialTLSContext != nil {
+		return buildFingerprintHTTP2Transport(transport, profile.HTTP2)
+	}
 	return transport, nil
 }
 
+// buildFingerprintHTTP2Transport 在 TLS 指纹 Dialer 之上构建 HTTP/2 Transport。
+// utls 连接不是 *tls.Conn，net/http 无法据此升级到 h2，因此直接使用 x/net/http2 的 Transport，
+// 并通过 HTTP2Config 设置 SETTINGS 与连接级窗口，使 HTTP/2 指纹与模板一致。
+// 上游必须支持 h2：握手未协商出 h2 时返回错误。
+func buildFingerprintHTTP2Transport(t1 *http.Transport, settings *tlsfingerprint.HTTP2Settings) (http.RoundTripper, error) {
+	t1.HTTP2 = &http.HTTP2Config{
+		MaxDecoderHeaderTableSize:     int(settings.HeaderTableSize),
+		MaxReceiveBufferPerStream:     int(settings.InitialWindowSize),
+		MaxReceiveBufferPerConnection: int(settings.ConnectionWindowSize),
+		MaxReadFrameSize:              int(settings.MaxFrameSize),
+	}
+	t2, err := http2.ConfigureTransports(t1)
+	if err != nil {
+		return nil, fmt.Errorf("configure http2 transport: %w", err)
+	}
+	t2.MaxHeaderListSize = settings.MaxHeaderListSize
+
+	dialTLS := t1.DialTLSContext
+	t2.DialTLSContext = func(ctx context.Context, network, addr string, _ *tls.Config) (net.Conn, error) {
+		conn, err := dialTLS(ctx, network, addr)
+		if err != nil {
+			return nil, err
+		}
+		if proto := tlsfingerprint.NegotiatedProtocol(conn); proto != http2.NextProtoTLS {
+			_ = conn.Close()
+			return nil, fmt.Errorf("upstream %s did not negotiate h2 (alpn=%q)", addr, proto)
+		}
+		return conn, nil
+	}
+	return t2, nil
+}
+
 // trackedBody 带跟踪功能的响应体包装器
 // 在 Close 时执行回调，用于更新请求计数
 type trackedBody struct {
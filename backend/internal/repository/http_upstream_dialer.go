@@ -0,0 +1,240 @@
+package repository
+
+import (
+	"context"
+	"errors"
+	"net"
+	"strings"
+	"sync"
+	"sync/atomic"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+)
+
+// upstreamDialer 上游 TCP 拨号器
+// 在 net.Dialer 之上增加固定解析（dns_overrides）与 DNS 缓存，所有普通上游客户端共享同一实例，
+// 缓存因此跨客户端生效；未启用缓存且无固定解析时直接交给 net.Dialer（保留其 happy eyeballs 实现）
+//
+// 注意:
+//   - 经 HTTP/SOCKS 代理访问时，这里只负责连接代理服务器，目标主机由代理解析
+type upstreamDialer struct {
+	dialer    *net.Dialer
+	resolver  *net.Resolver
+	overrides map[string]string // 小写主机名 -> IP
+	cacheTTL  time.Duration
+
+	mu    sync.Mutex
+	cache map[string]dnsCacheEntry
+
+	dials       atomic.Int64
+	dialErrors  atomic.Int64
+	cacheHits   atomic.Int64
+	cacheMisses atomic.Int64
+}
+
+type dnsCacheEntry struct {
+	addrs     []net.IP
+	expiresAt time.Time
+}
+
+// newUpstreamDialer 根据 gateway.http_client 构建拨号器
+func newUpstreamDialer(cfg *config.Config) *upstreamDialer {
+	var hc config.GatewayHTTPClientConfig
+	if cfg != nil {
+		hc = cfg.Gateway.HTTPClient
+	}
+	dialer := &net.Dialer{Timeout: 30 * time.Second}
+	switch {
+	case hc.KeepAliveSeconds < 0:
+		dialer.KeepAlive = -1
+	case hc.KeepAliveSeconds > 0:
+		dialer.KeepAlive = time.Duration(hc.KeepAliveSeconds) * time.Second
+	}
+	if !hc.HappyEyeballs {
+		// 负值关闭 RFC 6555 并行拨号
+		dialer.FallbackDelay = -1
+	} else if hc.FallbackDelayMs > 0 {
+		dialer.FallbackDelay = time.Duration(hc.FallbackDelayMs) * time.Millisecond
+	}
+
+	d := &upstreamDialer{
+		dialer:   dialer,
+		resolver: net.DefaultResolver,
+		cacheTTL: time.Duration(hc.DNSCacheTTLSeconds) * time.Second,
+		cache:    make(map[string]dnsCacheEntry),
+	}
+	if len(hc.DNSOverrides) > 0 {
+		d.overrides = make(map[string]string, len(hc.DNSOverrides))
+		for _, override := range hc.DNSOverrides {
+			d.overrides[strings.ToLower(strings.TrimSpace(override.Host))] = strings.TrimSpace(override.Address)
+		}
+	}
+	return d
+}
+
+// DialContext 实现 http.Transport.DialContext
+func (d *upstreamDialer) DialContext(ctx context.Context, network, addr string) (net.Conn, error) {
+	d.dials.Add(1)
+	conn, err := d.dial(ctx, network, addr)
+	if err != nil {
+		d.dialErrors.Add(1)
+	}
+	return conn, err
+}
+
+func (d *upstreamDialer) dial(ctx context.Context, network, addr string) (net.Conn, error) {
+	host, port, err := net.SplitHostPort(addr)
+	if err != nil {
+		return d.dialer.DialContext(ctx, network, addr)
+	}
+	if ip, ok := d.overrides[strings.ToLower(host)]; ok {
+		return d.dialer.DialContext(ctx, network, net.JoinHostPort(ip, port))
+	}
+	if d.cacheTTL <= 0 || net.ParseIP(host) != nil {
+		return d.dialer.DialContext(ctx, network, addr)
+	}
+
+	addrs, err := d.lookup(ctx, host)
+	if err != nil {
+		return nil, err
+	}
+	return d.dialAddrs(ctx, network, port, addrs)
+}
+
+// lookup 解析主机名，命中未过期缓存时直接返回
+func (d *upstreamDialer) lookup(ctx context.Context, host string) ([]net.IP, error) {
+	key := strings.ToLower(host)
+	now := time.Now()
+	d.mu.Lock()
+	if entry, ok := d.cache[key]; ok && now.Before(entry.expiresAt) {
+		d.mu.Unlock()
+		d.cacheHits.Add(1)
+		return entry.addrs, nil
+	}
+	d.mu.Unlock()
+	d.cacheMisses.Add(1)
+
+	ipAddrs, err := d.resolver.LookupIPAddr(ctx, host)
+	if err != nil {
+		return nil, err
+	}
+	addrs := make([]net.IP, 0, len(ipAddrs))
+	for _, ipAddr := range ipAddrs {
+		addrs = append(addrs, ipAddr.IP)
+	}
+	if len(addrs) == 0 {
+		return nil, &net.DNSError{Err: "no such host", Name: host, IsNotFound: true}
+	}
+
+	d.mu.Lock()
+	d.cache[key] = dnsCacheEntry{addrs: addrs, expiresAt: now.Add(d.cacheTTL)}
+	d.mu.Unlock()
+	return addrs, nil
+}
+
+// dialAddrs 按解析结果拨号：首个地址所属地址族为主，另一地址族在 FallbackDelay 后并行尝试（happy eyeballs），
+// 关闭 happy eyeballs 时按顺序逐个尝试
+func (d *upstreamDialer) dialAddrs(ctx context.Context, network, port string, addrs []net.IP) (net.Conn, error) {
+	primaries, fallbacks := splitAddrFamilies(addrs)
+	if d.dialer.FallbackDelay < 0 || len(fallbacks) == 0 {
+		return d.dialSerial(ctx, network, port, append(primaries, fallbacks...))
+	}
+
+	fallbackDelay := d.dialer.FallbackDelay
+	if fallbackDelay == 0 {
+		fallbackDelay = 300 * time.Millisecond
+	}
+
+	type dialResult struct {
+		conn    net.Conn
+		err     error
+		primary bool
+	}
+	ctx, cancel := context.WithCancel(ctx)
+	defer cancel()
+	results := make(chan dialResult, 2)
+	start := func(primary bool, list []net.IP) {
+		conn, err := d.dialSerial(ctx, network, port, list)
+		results <- dialResult{conn: conn, err: err, primary: primary}
+	}
+	go start(true, primaries)
+	pending := 1
+
+	timer := time.NewTimer(fallbackDelay)
+	defer timer.Stop()
+	var primaryErr, fallbackErr error
+	fallbackStarted := false
+	startFallback := func() {
+		if !fallbackStarted {
+			fallbackStarted = true
+			pending++
+			go start(false, fallbacks)
+		}
+	}
+	for {
+		select {
+		case <-timer.C:
+			startFallback()
+		case res := <-results:
+			pending--
+			if res.err == nil {
+				if pending > 0 {
+					// 另一路可能稍后成功，关闭多余连接
+					go func() {
+						if other := <-results; other.conn != nil {
+							_ = other.conn.Close()
+						}
+					}()
+				}
+				return res.conn, nil
+			}
+			if res.primary {
+				primaryErr = res.err
+			} else {
+				fallbackErr = res.err
+			}
+			if primaryErr != nil && fallbackErr != nil {
+				return nil, primaryErr
+			}
+			timer.Stop()
+			startFallback()
+		}
+	}
+}
+
+func (d *upstreamDialer) dialSerial(ctx context.Context, network, port string, addrs []net.IP) (net.Conn, error) {
+	var firstErr error
+	for _, ip := range addrs {
+		conn, err := d.dialer.DialContext(ctx, network, net.JoinHostPort(ip.String(), port))
+		if err == nil {
+			return conn, nil
+		}
+		if firstErr == nil {
+			firstErr = err
+		}
+		if ctx.Err() != nil {
+			break
+		}
+	}
+	if firstErr == nil {
+		firstErr = errors.New("no addresses to dial")
+	}
+	return nil, firstErr
+}
+
+// splitAddrFamilies 按首个地址的地址族拆分为主/备两组
+func splitAddrFamilies(addrs []net.IP) (primaries, fallbacks []net.IP) {
+	if len(addrs) == 0 {
+		return nil, nil
+	}
+	primaryIsV4 := addrs[0].To4() != nil
+	for _, ip := range addrs {
+		if (ip.To4() != nil) == primaryIsV4 {
+			primaries = append(primaries, ip)
+		} else {
+			fallbacks = append(fallbacks, ip)
+		}
+	}
+	return primaries, fallbacks
+}
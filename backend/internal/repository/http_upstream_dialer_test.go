@@ -0,0 +1,22 @@
+package repository
+
+import (
+	"net"
+	"testing"
+
+	"github.com/stretchr/testify/require"
+)
+
+func TestSplitAddrFamilies(t *testing.T) {
+	v6 := net.ParseIP("2001:db8::1")
+	v4a := net.ParseIP("192.0.2.1")
+	v4b := net.ParseIP("192.0.2.2")
+
+	primaries, fallbacks := splitAddrFamilies([]net.IP{v6, v4a, v4b})
+	require.Equal(t, []net.IP{v6}, primaries)
+	require.Equal(t, []net.IP{v4a, v4b}, fallbacks)
+
+	primaries, fallbacks = splitAddrFamilies([]net.IP{v4a, v4b})
+	require.Equal(t, []net.IP{v4a, v4b}, primaries)
+	require.Empty(t, fallbacks)
+}
@@ -0,0 +1,220 @@
+package repository
+
+import (
+	"bytes"
+	"errors"
+	"fmt"
+	"io"
+	"log/slog"
+	"math/rand/v2"
+	"net/http"
+	"slices"
+	"strconv"
+	"strings"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/Wei-Shaw/sub2api/internal/service"
+)
+
+// faultMalformedChunk 注入到 SSE 流中的畸形事件（data 不是合法 JSON）
+const faultMalformedChunk = "event: content_block_delta\ndata: {\"type\":\"content_block_delta\",\"delta\":{\"text\":\n\n"
+
+var (
+	errFaultConnectionReset = errors.New("fault injection: connection reset by peer")
+	errFaultStreamDropped   = fmt.Errorf("fault injection: upstream stream dropped: %w", io.ErrUnexpectedEOF)
+)
+
+// faultInjectingUpstream 按 fault_injection.rules 向上游请求注入故障的 HTTPUpstream 装饰器，仅用于混沌测试。
+//
+// latency 在发出请求前等待；error / connection_error 不访问上游直接返回；
+// drop_stream / malformed_chunk 正常请求上游，在转发 after_bytes 字节后截断响应体或插入畸形数据块。
+type faultInjectingUpstream struct {
+	next  service.HTTPUpstream
+	rules []config.FaultInjectionRule
+	rand  func() float64
+}
+
+func newFaultInjectingUpstream(next service.HTTPUpstream, cfg config.FaultInjectionConfig) *faultInjectingUpstream {
+	slog.Warn("fault_injection_enabled", "rules", len(cfg.Rules))
+	return &faultInjectingUpstream{next: next, rules: cfg.Rules, rand: rand.Float64}
+}
+
+func (u *faultInjectingUpstream) Do(req *http.Request, proxyURL string, accountID int64, accountConcurrency int) (*http.Response, error) {
+	return u.inject(req, accountID, func() (*http.Response, error) {
+		return u.next.Do(req, proxyURL, accountID, accountConcurrency)
+	})
+}
+
+func (u *faultInjectingUpstream) DoWithTLS(req *http.Request, proxyURL string, accountID int64, accountConcurrency int, enableTLSFingerprint bool) (*http.Response, error) {
+	return u.inject(req, accountID, func() (*http.Response, error) {
+		return u.next.DoWithTLS(req, proxyURL, accountID, accountConcurrency, enableTLSFingerprint)
+	})
+}
+
+func (u *faultInjectingUpstream) inject(req *http.Request, accountID int64, call func() (*http.Response, error)) (*http.Response, error) {
+	// 只记录 host + path，避免 query 中的 API Key 进入日志
+	target := req.URL.Host + req.URL.Path
+	var fault *config.FaultInjectionRule
+	for i := range u.rules {
+		rule := &u.rules[i]
+		if !faultRuleMatches(rule, target, accountID) || u.rand() >= rule.Rate {
+			continue
+		}
+		slog.Warn("fault_injection_injected", "fault", rule.Fault, "account_id", accountID, "target", target)
+		if rule.Fault != config.FaultLatency {
+			fault = rule
+			break
+		}
+		delay := time.Duration(rule.LatencyMS) * time.Millisecond
+		if rule.LatencyJitterMS > 0 {
+			delay += time.Duration(u.rand() * float64(time.Duration(rule.LatencyJitterMS)*time.Millisecond))
+		}
+		timer := time.NewTimer(delay)
+		select {
+		case <-req.Context().Done():
+			timer.Stop()
+			return nil, req.Context().Err()
+		case <-timer.C:
+		}
+	}
+	if fault == nil {
+		return call()
+	}
+
+	switch fault.Fault {
+	case config.FaultError:
+		if req.Body != nil {
+			_ = req.Body.Close()
+		}
+		return faultErrorResponse(req, fault), nil
+	case config.FaultConnectionError:
+		if req.Body != nil {
+			_ = req.Body.Close()
+		}
+		return nil, errFaultConnectionReset
+	}
+
+	resp, err := call()
+	if err != nil {
+		return resp, err
+	}
+	resp.Body = &faultBody{
+		ReadCloser: resp.Body,
+		fault:      fault.Fault,
+		after:      fault.AfterBytes,
+		sse:        strings.Contains(resp.Header.Get("Content-Type"), "text/event-stream"),
+	}
+	resp.ContentLength = -1
+	resp.Header.Del("Content-Length")
+	return resp, nil
+}
+
+func faultRuleMatches(rule *config.FaultInjectionRule, target string, accountID int64) bool {
+	if len(rule.AccountIDs) > 0 && !slices.Contains(rule.AccountIDs, accountID) {
+		return false
+	}
+	return rule.URLContains == "" || strings.Contains(target, rule.URLContains)
+}
+
+// faultErrorResponse 构造注入的错误响应；未配置 body 时按状态码生成 Anthropic 风格的错误 JSON
+func faultErrorResponse(req *http.Request, rule *config.FaultInjectionRule) *http.Response {
+	body := rule.Body
+	if body == "" {
+		errType := "api_error"
+		switch rule.StatusCode {
+		case http.StatusBadRequest:
+			errType = "invalid_request_error"
+		case http.StatusUnauthorized:
+			errType = "authentication_error"
+		case http.StatusForbidden:
+			errType = "permission_error"
+		case http.StatusNotFound:
+			errType = "not_found_error"
+		case http.StatusRequestEntityTooLarge:
+			errType = "request_too_large"
+		case http.StatusTooManyRequests:
+			errType = "rate_limit_error"
+		case 529:
+			errType = "overloaded_error"
+		}
+		body = fmt.Sprintf(`{"type":"error","error":{"type":%q,"message":"fault injection: HTTP %d"}}`, errType, rule.StatusCode)
+	}
+	header := http.Header{}
+	header.Set("Content-Type", "application/json")
+	if rule.RetryAfterSeconds > 0 {
+		header.Set("Retry-After", strconv.Itoa(rule.RetryAfterSeconds))
+	}
+	return &http.Response{
+		Status:        fmt.Sprintf("%d %s", rule.StatusCode, http.StatusText(rule.StatusCode)),
+		StatusCode:    rule.StatusCode,
+		Proto:         "HTTP/1.1",
+		ProtoMajor:    1,
+		ProtoMinor:    1,
+		Header:        header,
+		Body:          io.NopCloser(strings.NewReader(body)),
+		ContentLength: int64(len(body)),
+		Request:       req,
+	}
+}
+
+// faultBody 在转发 after 字节后截断响应体（drop_stream）或插入畸形数据块（malformed_chunk）。
+// SSE 响应的畸形数据块插入在下一个事件边界处，其余响应直接插入在 after 字节处。
+type faultBody struct {
+	io.ReadCloser
+	fault    string
+	after    int
+	sse      bool
+	read     int
+	injected bool
+	pending  []byte
+}
+
+func (b *faultBody) Read(p []byte) (int, error) {
+	if len(b.pending) > 0 {
+		n := copy(p, b.pending)
+		b.pending = b.pending[n:]
+		return n, nil
+	}
+	if b.fault == config.FaultDropStream {
+		remaining := b.after - b.read
+		if b.injected || remaining <= 0 {
+			b.injected = true
+			return 0, errFaultStreamDropped
+		}
+		if len(p) > remaining {
+			p = p[:remaining]
+		}
+		n, err := b.ReadCloser.Read(p)
+		b.read += n
+		return n, err
+	}
+	if b.injected {
+		return b.ReadCloser.Read(p)
+	}
+
+	n, err := b.ReadCloser.Read(p)
+	start := max(b.after-b.read, 0)
+	b.read += n
+	if (err != nil && err != io.EOF) || start > n {
+		return n, err
+	}
+	at := start
+	if b.sse {
+		if i := bytes.Index(p[start:n], []byte("\n\n")); i >= 0 {
+			at = start + i + 2
+		} else if err == nil {
+			return n, nil
+		} else {
+			at = n
+		}
+	}
+	b.injected = true
+	b.pending = append([]byte(faultMalformedChunk), p[at:n]...)
+	if at == 0 {
+		m := copy(p, b.pending)
+		b.pending = b.pending[m:]
+		return m, nil
+	}
+	return at, nil
+}
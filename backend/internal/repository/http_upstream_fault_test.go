@@ -0,0 +1,114 @@
+//go:build unit
+
+package repository
+
+import (
+	"context"
+	"errors"
+	"io"
+	"net/http"
+	"strings"
+	"testing"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/stretchr/testify/require"
+)
+
+type stubHTTPUpstream struct {
+	calls       int
+	contentType string
+	body        string
+}
+
+func (s *stubHTTPUpstream) Do(req *http.Request, _ string, _ int64, _ int) (*http.Response, error) {
+	s.calls++
+	header := http.Header{}
+	header.Set("Content-Type", s.contentType)
+	return &http.Response{StatusCode: http.StatusOK, Header: header, Body: io.NopCloser(strings.NewReader(s.body)), Request: req}, nil
+}
+
+func (s *stubHTTPUpstream) DoWithTLS(req *http.Request, proxyURL string, accountID int64, concurrency int, _ bool) (*http.Response, error) {
+	return s.Do(req, proxyURL, accountID, concurrency)
+}
+
+func newFaultTestUpstream(next *stubHTTPUpstream, rules ...config.FaultInjectionRule) *faultInjectingUpstream {
+	u := newFaultInjectingUpstream(next, config.FaultInjectionConfig{Enabled: true, Rules: rules})
+	u.rand = func() float64 { return 0 }
+	return u
+}
+
+func newFaultTestRequest(t *testing.T) *http.Request {
+	req, err := http.NewRequestWithContext(context.Background(), http.MethodPost, "https://api.anthropic.com/v1/messages?beta=true", strings.NewReader("{}"))
+	require.NoError(t, err)
+	return req
+}
+
+func TestFaultInjectingUpstream_ErrorAndConnectionError(t *testing.T) {
+	next := &stubHTTPUpstream{contentType: "application/json", body: `{"ok":true}`}
+	u := newFaultTestUpstream(next, config.FaultInjectionRule{Fault: config.FaultError, Rate: 1, StatusCode: 529, RetryAfterSeconds: 3, URLContains: "/v1/messages"})
+
+	resp, err := u.Do(newFaultTestRequest(t), "", 1, 1)
+	require.NoError(t, err)
+	require.Equal(t, 529, resp.StatusCode)
+	require.Equal(t, "3", resp.Header.Get("Retry-After"))
+	body, _ := io.ReadAll(resp.Body)
+	require.Contains(t, string(body), "overloaded_error")
+	require.Zero(t, next.calls)
+
+	u = newFaultTestUpstream(next, config.FaultInjectionRule{Fault: config.FaultConnectionError, Rate: 1, AccountIDs: []int64{2}})
+	resp, err = u.Do(newFaultTestRequest(t), "", 1, 1)
+	require.NoError(t, err, "rule limited to account 2")
+	require.Equal(t, http.StatusOK, resp.StatusCode)
+	_, err = u.DoWithTLS(newFaultTestRequest(t), "", 2, 1, true)
+	require.ErrorIs(t, err, errFaultConnectionReset)
+}
+
+func TestFaultInjectingUpstream_RateAndLatency(t *testing.T) {
+	next := &stubHTTPUpstream{contentType: "application/json", body: `{}`}
+	u := newFaultTestUpstream(next,
+		config.FaultInjectionRule{Fault: config.FaultLatency, Rate: 1, LatencyMS: 30},
+		config.FaultInjectionRule{Fault: config.FaultError, Rate: 0.5, StatusCode: 500},
+	)
+	u.rand = func() float64 { return 0.6 }
+
+	start := time.Now()
+	resp, err := u.Do(newFaultTestRequest(t), "", 1, 1)
+	require.NoError(t, err)
+	require.Equal(t, http.StatusOK, resp.StatusCode, "0.6 >= rate 0.5, error not injected")
+	require.GreaterOrEqual(t, time.Since(start), 30*time.Millisecond)
+
+	ctx, cancel := context.WithCancel(context.Background())
+	cancel()
+	_, err = u.Do(newFaultTestRequest(t).WithContext(ctx), "", 1, 1)
+	require.ErrorIs(t, err, context.Canceled)
+}
+
+func TestFaultInjectingUpstream_DropStream(t *testing.T) {
+	next := &stubHTTPUpstream{contentType: "text/event-stream", body: "data: {\"a\":1}\n\ndata: {\"b\":2}\n\n"}
+	u := newFaultTestUpstream(next, config.FaultInjectionRule{Fault: config.FaultDropStream, Rate: 1, AfterBytes: 16})
+
+	resp, err := u.Do(newFaultTestRequest(t), "", 1, 1)
+	require.NoError(t, err)
+	data, err := io.ReadAll(resp.Body)
+	require.True(t, errors.Is(err, io.ErrUnexpectedEOF))
+	require.Equal(t, "data: {\"a\":1}\n\nd", string(data))
+}
+
+func TestFaultInjectingUpstream_MalformedChunk(t *testing.T) {
+	next := &stubHTTPUpstream{contentType: "text/event-stream", body: "data: {\"a\":1}\n\ndata: {\"b\":2}\n\n"}
+	u := newFaultTestUpstream(next, config.FaultInjectionRule{Fault: config.FaultMalformedChunk, Rate: 1, AfterBytes: 1})
+
+	resp, err := u.Do(newFaultTestRequest(t), "", 1, 1)
+	require.NoError(t, err)
+	data, err := io.ReadAll(resp.Body)
+	require.NoError(t, err)
+	require.Equal(t, "data: {\"a\":1}\n\n"+faultMalformedChunk+"data: {\"b\":2}\n\n", string(data))
+
+	next.contentType, next.body = "application/json", `{"content":"hello"}`
+	resp, err = u.Do(newFaultTestRequest(t), "", 1, 1)
+	require.NoError(t, err)
+	data, err = io.ReadAll(resp.Body)
+	require.NoError(t, err)
+	require.Equal(t, `{`+faultMalformedChunk+`"content":"hello"}`, string(data))
+}
@@ -2,6 +2,7 @@ package repository
 
 import (
 	"io"
+	"net"
 	"net/http"
 	"sync/atomic"
 	"testing"
@@ -34,7 +35,7 @@ func (s *HTTPUpstreamSuite) SetupTest() {
 // newService 创建测试用的 httpUpstreamService 实例
 // 返回具体类型以便访问内部状态进行断言
 func (s *HTTPUpstreamSuite) newService() *httpUpstreamService {
-	up := NewHTTPUpstream(s.cfg)
+	up := NewHTTPUpstream(s.cfg, nil)
 	svc, ok := up.(*httpUpstreamService)
 	require.True(s.T(), ok, "expected *httpUpstreamService")
 	return svc
@@ -85,11 +86,11 @@ func (s *HTTPUpstreamSuite) TestAcquireClient_OverLimitReturnsError() {
 		MaxUpstreamClients:      1,
 	}
 	svc := s.newService()
-	entry1, err := svc.acquireClient("http://proxy-a:8080", 1, 1)
+	entry1, err := svc.acquireClient("http://proxy-a:8080", 1, 1, "")
 	require.NoError(s.T(), err, "expected first acquire to succeed")
 	require.NotNil(s.T(), entry1, "expected entry")
 
-	entry2, err := svc.acquireClient("http://proxy-b:8080", 2, 1)
+	entry2, err := svc.acquireClient("http://proxy-b:8080", 2, 1, "")
 	require.Error(s.T(), err, "expected error when cache limit reached")
 	require.Nil(s.T(), entry2, "expected nil entry when cache limit reached")
 }
@@ -103,7 +104,7 @@ func (s *HTTPUpstreamSuite) TestDo_WithoutProxy_GoesDirect() {
 	}))
 	s.T().Cleanup(upstream.Close)
 
-	up := NewHTTPUpstream(s.cfg)
+	up := NewHTTPUpstream(s.cfg, nil)
 
 	req, err := http.NewRequest(http.MethodGet, upstream.URL+"/x", nil)
 	require.NoError(s.T(), err, "NewRequest")
@@ -127,7 +128,7 @@ func (s *HTTPUpstreamSuite) TestDo_WithHTTPProxy_UsesProxy() {
 	s.T().Cleanup(proxySrv.Close)
 
 	s.cfg.Gateway = config.GatewayConfig{ResponseHeaderTimeout: 1}
-	up := NewHTTPUpstream(s.cfg)
+	up := NewHTTPUpstream(s.cfg, nil)
 
 	// 发送请求到外部地址，应通过代理
 	req, err := http.NewRequest(http.MethodGet, "http://example.com/test", nil)
@@ -155,7 +156,7 @@ func (s *HTTPUpstreamSuite) TestDo_EmptyProxy_UsesDirect() {
 	}))
 	s.T().Cleanup(upstream.Close)
 
-	up := NewHTTPUpstream(s.cfg)
+	up := NewHTTPUpstream(s.cfg, nil)
 	req, err := http.NewRequest(http.MethodGet, upstream.URL+"/y", nil)
 	require.NoError(s.T(), err, "NewRequest")
 	resp, err := up.Do(req, "", 1, 1)
@@ -236,6 +237,71 @@ func (s *HTTPUpstreamSuite) TestAccountConcurrencyFallbackToDefault() {
 	require.Equal(s.T(), 55, transport.MaxIdleConnsPerHost, "MaxIdleConnsPerHost fallback mismatch")
 }
 
+// TestAdapterOverridesPoolSettings 测试按平台覆盖连接池参数
+// 验证配置了覆盖参数的平台使用独立客户端，未配置的平台沿用全局值
+func (s *HTTPUpstreamSuite) TestAdapterOverridesPoolSettings() {
+	http2Off := false
+	s.cfg.Gateway = config.GatewayConfig{
+		MaxConnsPerHost: 66,
+		HTTPClient: config.GatewayHTTPClientConfig{
+			HTTP2: true,
+			Adapters: map[string]config.GatewayHTTPClientAdapterConfig{
+				"openai": {HTTP2: &http2Off, MaxConnsPerHost: 7},
+			},
+		},
+	}
+	svc := s.newService()
+	openaiEntry, err := svc.getClientEntry("", 1, 0, "openai", false, false)
+	require.NoError(s.T(), err)
+	geminiEntry, err := svc.getClientEntry("", 1, 0, "gemini", false, false)
+	require.NoError(s.T(), err)
+	require.NotSame(s.T(), openaiEntry, geminiEntry, "平台覆盖参数应使用独立客户端")
+
+	transport, ok := openaiEntry.client.Transport.(*http.Transport)
+	require.True(s.T(), ok, "expected *http.Transport")
+	require.Equal(s.T(), 7, transport.MaxConnsPerHost)
+	require.False(s.T(), transport.ForceAttemptHTTP2)
+
+	transport, ok = geminiEntry.client.Transport.(*http.Transport)
+	require.True(s.T(), ok, "expected *http.Transport")
+	require.Equal(s.T(), 66, transport.MaxConnsPerHost)
+	require.True(s.T(), transport.ForceAttemptHTTP2)
+}
+
+// TestDo_DNSOverrideAndPoolStats 测试固定解析与连接池统计
+// 验证固定解析的主机名可直连，第二次请求复用连接
+func (s *HTTPUpstreamSuite) TestDo_DNSOverrideAndPoolStats() {
+	upstream := newLocalTestServer(s.T(), http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		_, _ = io.WriteString(w, "pinned")
+	}))
+	s.T().Cleanup(upstream.Close)
+	_, port, err := net.SplitHostPort(upstream.Listener.Addr().String())
+	require.NoError(s.T(), err)
+
+	s.cfg.Gateway.HTTPClient = config.GatewayHTTPClientConfig{
+		DNSOverrides: []config.GatewayDNSOverride{{Host: "upstream.invalid", Address: "127.0.0.1"}},
+	}
+	svc := s.newService()
+
+	for i := 0; i < 2; i++ {
+		req, err := http.NewRequest(http.MethodGet, "http://upstream.invalid:"+port+"/x", nil)
+		require.NoError(s.T(), err)
+		resp, err := svc.Do(req, "", 1, 1)
+		require.NoError(s.T(), err, "Do")
+		b, _ := io.ReadAll(resp.Body)
+		_ = resp.Body.Close()
+		require.Equal(s.T(), "pinned", string(b))
+	}
+
+	stats := svc.PoolStats()
+	require.Equal(s.T(), 1, stats.Clients)
+	require.Equal(s.T(), int64(0), stats.InFlight)
+	require.Equal(s.T(), int64(1), stats.Dials)
+	require.Equal(s.T(), int64(1), stats.ConnsNew)
+	require.Equal(s.T(), int64(1), stats.ConnsReused)
+	require.Equal(s.T(), 1, stats.Adapters["default"].Clients)
+}
+
 // TestEvictOverLimitRemovesOldestIdle 测试超出数量限制时的 LRU 淘汰
 // 验证优先淘汰最久未使用的空闲客户端
 func (s *HTTPUpstreamSuite) TestEvictOverLimitRemovesOldestIdle() {
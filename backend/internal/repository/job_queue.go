@@ -0,0 +1,257 @@
+package repository
+
+import (
+	"context"
+	"errors"
+	"strconv"
+	"strings"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/Wei-Shaw/sub2api/internal/service"
+	"github.com/redis/go-redis/v9"
+)
+
+const (
+	jobQueueGroup            = "sub2api"
+	jobQueueDeadLetterSuffix = ":dead"
+
+	jobFieldPayload    = "payload"
+	jobFieldEnqueuedAt = "enqueued_at"
+	jobFieldReason     = "reason"
+	jobFieldSourceID   = "source_id"
+	jobFieldDeliveries = "deliveries"
+)
+
+type redisJobQueue struct {
+	rdb    *redis.Client
+	prefix string
+	maxLen int64
+}
+
+// NewJobQueue 创建基于 Redis Streams 的任务队列
+func NewJobQueue(rdb *redis.Client, cfg *config.Config) service.JobQueue {
+	q := &redisJobQueue{rdb: rdb, prefix: "jobs:"}
+	if cfg != nil {
+		if cfg.JobQueue.KeyPrefix != "" {
+			q.prefix = cfg.JobQueue.KeyPrefix
+		}
+		q.maxLen = cfg.JobQueue.MaxLen
+	}
+	return q
+}
+
+func (q *redisJobQueue) streamKey(topic string) string {
+	return q.prefix + topic
+}
+
+func (q *redisJobQueue) deadLetterKey(topic string) string {
+	return q.prefix + topic + jobQueueDeadLetterSuffix
+}
+
+func (q *redisJobQueue) Enqueue(ctx context.Context, topic string, payload []byte) (string, error) {
+	return q.rdb.XAdd(ctx, &redis.XAddArgs{
+		Stream: q.streamKey(topic),
+		MaxLen: q.maxLen,
+		Approx: q.maxLen > 0,
+		Values: map[string]any{
+			jobFieldPayload:    payload,
+			jobFieldEnqueuedAt: time.Now().UnixMilli(),
+		},
+	}).Result()
+}
+
+func (q *redisJobQueue) EnsureGroup(ctx context.Context, topic string) error {
+	err := q.rdb.XGroupCreateMkStream(ctx, q.streamKey(topic), jobQueueGroup, "0").Err()
+	if err != nil && strings.Contains(err.Error(), "BUSYGROUP") {
+		return nil
+	}
+	return err
+}
+
+func (q *redisJobQueue) Read(ctx context.Context, topic, consumer string, count int, block time.Duration) ([]service.QueuedJob, error) {
+	streams, err := q.rdb.XReadGroup(ctx, &redis.XReadGroupArgs{
+		Group:    jobQueueGroup,
+		Consumer: consumer,
+		Streams:  []string{q.streamKey(topic), ">"},
+		Count:    int64(count),
+		Block:    block,
+	}).Result()
+	if errors.Is(err, redis.Nil) {
+		return nil, nil
+	}
+	if err != nil {
+		return nil, err
+	}
+
+	jobs := make([]service.QueuedJob, 0, count)
+	for _, stream := range streams {
+		for _, msg := range stream.Messages {
+			jobs = append(jobs, jobFromMessage(topic, msg, 1))
+		}
+	}
+	return jobs, nil
+}
+
+func (q *redisJobQueue) ListStalled(ctx context.Context, topic string, minIdle time.Duration, count int) ([]service.StalledJob, error) {
+	pending, err := q.rdb.XPendingExt(ctx, &redis.XPendingExtArgs{
+		Stream: q.streamKey(topic),
+		Group:  jobQueueGroup,
+		Idle:   minIdle,
+		Start:  "-",
+		End:    "+",
+		Count:  int64(count),
+	}).Result()
+	if err != nil {
+		return nil, err
+	}
+	out := make([]service.StalledJob, 0, len(pending))
+	for _, p := range pending {
+		out = append(out, service.StalledJob{ID: p.ID, Deliveries: p.RetryCount})
+	}
+	return out, nil
+}
+
+func (q *redisJobQueue) Claim(ctx context.Context, topic, consumer string, minIdle time.Duration, stalled []service.StalledJob) ([]service.QueuedJob, error) {
+	if len(stalled) == 0 {
+		return nil, nil
+	}
+	ids := make([]string, 0, len(stalled))
+	deliveries := make(map[string]int64, len(stalled))
+	for _, st := range stalled {
+		ids = append(ids, st.ID)
+		deliveries[st.ID] = st.Deliveries
+	}
+
+	// XCLAIM 会再次校验空闲时间，避免与其他实例重复认领；认领成功后投递次数 +1
+	msgs, err := q.rdb.XClaim(ctx, &redis.XClaimArgs{
+		Stream:   q.streamKey(topic),
+		Group:    jobQueueGroup,
+		Consumer: consumer,
+		MinIdle:  minIdle,
+		Messages: ids,
+	}).Result()
+	if err != nil {
+		return nil, err
+	}
+
+	jobs := make([]service.QueuedJob, 0, len(msgs))
+	for _, msg := range msgs {
+		if msg.Values == nil {
+			// 消息已被裁剪，仅剩 PEL 记录，直接确认丢弃
+			_ = q.Ack(ctx, topic, msg.ID)
+			continue
+		}
+		jobs = append(jobs, jobFromMessage(topic, msg, deliveries[msg.ID]+1))
+	}
+	return jobs, nil
+}
+
+func (q *redisJobQueue) Ack(ctx context.Context, topic string, ids ...string) error {
+	if len(ids) == 0 {
+		return nil
+	}
+	pipe := q.rdb.TxPipeline()
+	pipe.XAck(ctx, q.streamKey(topic), jobQueueGroup, ids...)
+	pipe.XDel(ctx, q.streamKey(topic), ids...)
+	_, err := pipe.Exec(ctx)
+	return err
+}
+
+func (q *redisJobQueue) DeadLetter(ctx context.Context, job service.QueuedJob, reason string) error {
+	pipe := q.rdb.TxPipeline()
+	pipe.XAdd(ctx, &redis.XAddArgs{
+		Stream: q.deadLetterKey(job.Topic),
+		MaxLen: q.maxLen,
+		Approx: q.maxLen > 0,
+		Values: map[string]any{
+			jobFieldPayload:    job.Payload,
+			jobFieldEnqueuedAt: job.EnqueuedAt.UnixMilli(),
+			jobFieldSourceID:   job.ID,
+			jobFieldDeliveries: job.Deliveries - 1,
+			jobFieldReason:     reason,
+		},
+	})
+	pipe.XAck(ctx, q.streamKey(job.Topic), jobQueueGroup, job.ID)
+	pipe.XDel(ctx, q.streamKey(job.Topic), job.ID)
+	_, err := pipe.Exec(ctx)
+	return err
+}
+
+func (q *redisJobQueue) Stats(ctx context.Context, topic string) (*service.JobQueueStats, error) {
+	stats := &service.JobQueueStats{Topic: topic}
+	key := q.streamKey(topic)
+
+	length, err := q.rdb.XLen(ctx, key).Result()
+	if err != nil {
+		return nil, err
+	}
+	stats.Length = length
+	dead, err := q.rdb.XLen(ctx, q.deadLetterKey(topic)).Result()
+	if err != nil {
+		return nil, err
+	}
+	stats.DeadLetters = dead
+	if length == 0 {
+		return stats, nil
+	}
+
+	groups, err := q.rdb.XInfoGroups(ctx, key).Result()
+	if err != nil {
+		return nil, err
+	}
+	for _, g := range groups {
+		if g.Name != jobQueueGroup {
+			continue
+		}
+		stats.Pending = g.Pending
+		stats.Lag = g.Lag
+		stats.Consumers = g.Consumers
+	}
+
+	if stats.Pending > 0 {
+		summary, err := q.rdb.XPending(ctx, key, jobQueueGroup).Result()
+		if err != nil {
+			return nil, err
+		}
+		if ms, ok := streamIDMillis(summary.Lower); ok {
+			stats.OldestPendingSeconds = time.Since(time.UnixMilli(ms)).Seconds()
+		}
+	}
+	return stats, nil
+}
+
+func jobFromMessage(topic string, msg redis.XMessage, deliveries int64) service.QueuedJob {
+	job := service.QueuedJob{
+		ID:         msg.ID,
+		Topic:      topic,
+		Deliveries: deliveries,
+	}
+	if v, ok := msg.Values[jobFieldPayload].(string); ok {
+		job.Payload = []byte(v)
+	}
+	if v, ok := msg.Values[jobFieldEnqueuedAt].(string); ok {
+		if ms, err := strconv.ParseInt(v, 10, 64); err == nil {
+			job.EnqueuedAt = time.UnixMilli(ms)
+		}
+	}
+	if job.EnqueuedAt.IsZero() {
+		if ms, ok := streamIDMillis(msg.ID); ok {
+			job.EnqueuedAt = time.UnixMilli(ms)
+		}
+	}
+	return job
+}
+
+// streamIDMillis 从 Stream 消息 ID（<ms>-<seq>）中解析毫秒时间戳
+func streamIDMillis(id string) (int64, bool) {
+	msPart, _, ok := strings.Cut(id, "-")
+	if !ok {
+		return 0, false
+	}
+	ms, err := strconv.ParseInt(msPart, 10, 64)
+	if err != nil {
+		return 0, false
+	}
+	return ms, true
+}
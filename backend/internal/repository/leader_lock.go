@@ -0,0 +1,64 @@
+package repository
+
+import (
+	"context"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/service"
+	"github.com/redis/go-redis/v9"
+)
+
+const leaderLockKeyPrefix = "leader:"
+
+// leaderAcquireScript 租约空闲时获取；已由自己持有时（如续期失败后让出）直接延长
+var leaderAcquireScript = redis.NewScript(`
+local current = redis.call("GET", KEYS[1])
+if current == ARGV[1] then
+  redis.call("PEXPIRE", KEYS[1], ARGV[2])
+  return 1
+end
+if current then
+  return 0
+end
+redis.call("SET", KEYS[1], ARGV[1], "PX", ARGV[2])
+return 1
+`)
+
+// leaderRenewScript 仅当租约仍由自己持有时延长
+var leaderRenewScript = redis.NewScript(`
+if redis.call("GET", KEYS[1]) == ARGV[1] then
+  return redis.call("PEXPIRE", KEYS[1], ARGV[2])
+end
+return 0
+`)
+
+// leaderReleaseScript 仅当租约仍由自己持有时删除
+var leaderReleaseScript = redis.NewScript(`
+if redis.call("GET", KEYS[1]) == ARGV[1] then
+  return redis.call("DEL", KEYS[1])
+end
+return 0
+`)
+
+type leaderLock struct {
+	rdb *redis.Client
+}
+
+// NewLeaderLock 创建基于 Redis 的选主租约存储
+func NewLeaderLock(rdb *redis.Client) service.LeaderLock {
+	return &leaderLock{rdb: rdb}
+}
+
+func (l *leaderLock) TryAcquire(ctx context.Context, name, owner string, ttl time.Duration) (bool, error) {
+	n, err := leaderAcquireScript.Run(ctx, l.rdb, []string{leaderLockKeyPrefix + name}, owner, ttl.Milliseconds()).Int()
+	return n == 1, err
+}
+
+func (l *leaderLock) Renew(ctx context.Context, name, owner string, ttl time.Duration) (bool, error) {
+	n, err := leaderRenewScript.Run(ctx, l.rdb, []string{leaderLockKeyPrefix + name}, owner, ttl.Milliseconds()).Int()
+	return n == 1, err
+}
+
+func (l *leaderLock) Release(ctx context.Context, name, owner string) error {
+	return leaderReleaseScript.Run(ctx, l.rdb, []string{leaderLockKeyPrefix + name}, owner).Err()
+}
@@ -0,0 +1,117 @@
+package repository
+
+import (
+	"context"
+	"database/sql"
+	"encoding/json"
+	"errors"
+
+	"github.com/Wei-Shaw/sub2api/internal/service"
+)
+
+const modelCanaryRouteColumns = `id, alias, enabled, targets, created_at, updated_at`
+
+type modelCanaryRouteRepository struct {
+	db *sql.DB
+}
+
+// NewModelCanaryRouteRepository 创建金丝雀路由仓储
+func NewModelCanaryRouteRepository(sqlDB *sql.DB) service.ModelCanaryRouteRepository {
+	return &modelCanaryRouteRepository{db: sqlDB}
+}
+
+func (r *modelCanaryRouteRepository) List(ctx context.Context) ([]*service.ModelCanaryRoute, error) {
+	rows, err := r.db.QueryContext(ctx, `SELECT `+modelCanaryRouteColumns+` FROM model_canary_routes ORDER BY id`)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	out := make([]*service.ModelCanaryRoute, 0)
+	for rows.Next() {
+		route, err := scanModelCanaryRoute(rows)
+		if err != nil {
+			return nil, err
+		}
+		out = append(out, route)
+	}
+	if err := rows.Err(); err != nil {
+		return nil, err
+	}
+	return out, nil
+}
+
+func (r *modelCanaryRouteRepository) GetByID(ctx context.Context, id int64) (*service.ModelCanaryRoute, error) {
+	route, err := scanModelCanaryRoute(r.db.QueryRowContext(ctx, `SELECT `+modelCanaryRouteColumns+` FROM model_canary_routes WHERE id = $1`, id))
+	if errors.Is(err, sql.ErrNoRows) {
+		return nil, service.ErrModelCanaryRouteNotFound
+	}
+	return route, err
+}
+
+func (r *modelCanaryRouteRepository) Create(ctx context.Context, route *service.ModelCanaryRoute) error {
+	targets, err := json.Marshal(route.Targets)
+	if err != nil {
+		return err
+	}
+	err = r.db.QueryRowContext(ctx, `
+INSERT INTO model_canary_routes (alias, enabled, targets)
+VALUES ($1, $2, $3)
+RETURNING id, created_at, updated_at
+`, route.Alias, route.Enabled, string(targets)).Scan(&route.ID, &route.CreatedAt, &route.UpdatedAt)
+	if isUniqueConstraintViolation(err) {
+		return service.ErrModelCanaryRouteExists
+	}
+	return err
+}
+
+func (r *modelCanaryRouteRepository) Update(ctx context.Context, route *service.ModelCanaryRoute) error {
+	targets, err := json.Marshal(route.Targets)
+	if err != nil {
+		return err
+	}
+	err = r.db.QueryRowContext(ctx, `
+UPDATE model_canary_routes SET
+	alias = $2,
+	enabled = $3,
+	targets = $4,
+	updated_at = NOW()
+WHERE id = $1
+RETURNING created_at, updated_at
+`, route.ID, route.Alias, route.Enabled, string(targets)).Scan(&route.CreatedAt, &route.UpdatedAt)
+	switch {
+	case errors.Is(err, sql.ErrNoRows):
+		return service.ErrModelCanaryRouteNotFound
+	case isUniqueConstraintViolation(err):
+		return service.ErrModelCanaryRouteExists
+	}
+	return err
+}
+
+func (r *modelCanaryRouteRepository) Delete(ctx context.Context, id int64) error {
+	res, err := r.db.ExecContext(ctx, `DELETE FROM model_canary_routes WHERE id = $1`, id)
+	if err != nil {
+		return err
+	}
+	if n, err := res.RowsAffected(); err == nil && n == 0 {
+		return service.ErrModelCanaryRouteNotFound
+	}
+	return nil
+}
+
+func scanModelCanaryRoute(row interface{ Scan(...any) error }) (*service.ModelCanaryRoute, error) {
+	var (
+		route   service.ModelCanaryRoute
+		targets []byte
+	)
+	if err := row.Scan(&route.ID, &route.Alias, &route.Enabled, &targets, &route.CreatedAt, &route.UpdatedAt); err != nil {
+		return nil, err
+	}
+	route.Targets = []service.ModelCanaryTarget{}
+	if len(targets) > 0 {
+		if err := json.Unmarshal(targets, &route.Targets); err != nil {
+			return nil, err
+		}
+	}
+	return &route, nil
+}
@@ -0,0 +1,87 @@
+package repository
+
+import (
+	"context"
+	"fmt"
+	"strconv"
+	"strings"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/service"
+	"github.com/redis/go-redis/v9"
+)
+
+const (
+	modelCanaryStatsPrefix = "canary_stats:route:"
+	// modelCanaryStatsTTL 统计在最后一次写入后保留的时间
+	modelCanaryStatsTTL = 30 * 24 * time.Hour
+)
+
+// 哈希字段：<target>|requests、<target>|successes、<target>|latency_ms
+const (
+	modelCanaryFieldRequests  = "requests"
+	modelCanaryFieldSuccesses = "successes"
+	modelCanaryFieldLatency   = "latency_ms"
+)
+
+type modelCanaryStatsCache struct {
+	rdb *redis.Client
+}
+
+// NewModelCanaryStatsCache 创建金丝雀目标统计缓存
+func NewModelCanaryStatsCache(rdb *redis.Client) service.ModelCanaryStatsCache {
+	return &modelCanaryStatsCache{rdb: rdb}
+}
+
+func modelCanaryStatsKey(routeID int64) string {
+	return fmt.Sprintf("%s%d", modelCanaryStatsPrefix, routeID)
+}
+
+func (c *modelCanaryStatsCache) Record(ctx context.Context, routeID int64, target string, success bool, latencyMs int64) error {
+	key := modelCanaryStatsKey(routeID)
+	pipe := c.rdb.TxPipeline()
+	pipe.HIncrBy(ctx, key, target+"|"+modelCanaryFieldRequests, 1)
+	if success {
+		pipe.HIncrBy(ctx, key, target+"|"+modelCanaryFieldSuccesses, 1)
+	}
+	pipe.HIncrBy(ctx, key, target+"|"+modelCanaryFieldLatency, latencyMs)
+	pipe.Expire(ctx, key, modelCanaryStatsTTL)
+	if _, err := pipe.Exec(ctx); err != nil {
+		return fmt.Errorf("record canary stats: %w", err)
+	}
+	return nil
+}
+
+func (c *modelCanaryStatsCache) Get(ctx context.Context, routeID int64) (map[string]service.ModelCanaryCounters, error) {
+	fields, err := c.rdb.HGetAll(ctx, modelCanaryStatsKey(routeID)).Result()
+	if err != nil {
+		return nil, fmt.Errorf("get canary stats: %w", err)
+	}
+	out := make(map[string]service.ModelCanaryCounters)
+	for field, raw := range fields {
+		idx := strings.LastIndex(field, "|")
+		if idx <= 0 {
+			continue
+		}
+		value, err := strconv.ParseInt(raw, 10, 64)
+		if err != nil {
+			continue
+		}
+		target := field[:idx]
+		counters := out[target]
+		switch field[idx+1:] {
+		case modelCanaryFieldRequests:
+			counters.Requests = value
+		case modelCanaryFieldSuccesses:
+			counters.Successes = value
+		case modelCanaryFieldLatency:
+			counters.TotalLatencyMs = value
+		}
+		out[target] = counters
+	}
+	return out, nil
+}
+
+func (c *modelCanaryStatsCache) Reset(ctx context.Context, routeID int64) error {
+	return c.rdb.Del(ctx, modelCanaryStatsKey(routeID)).Err()
+}
@@ -0,0 +1,58 @@
+package repository
+
+import (
+	"bytes"
+	"context"
+	"errors"
+	"fmt"
+	"io"
+	"net/http"
+	"net/url"
+
+	"github.com/Wei-Shaw/sub2api/internal/pkg/httpclient"
+	"github.com/Wei-Shaw/sub2api/internal/service"
+)
+
+type moderationClient struct {
+	httpClient *http.Client
+}
+
+// NewModerationClient 创建内容审核服务商客户端；延迟预算由调用方 context 控制
+func NewModerationClient() service.ModerationClient {
+	sharedClient, err := httpclient.GetClient(httpclient.Options{})
+	if err != nil {
+		sharedClient = &http.Client{}
+	}
+	return &moderationClient{httpClient: sharedClient}
+}
+
+func (c *moderationClient) Post(ctx context.Context, endpoint, apiKey string, payload []byte) ([]byte, error) {
+	req, err := http.NewRequestWithContext(ctx, http.MethodPost, endpoint, bytes.NewReader(payload))
+	if err != nil {
+		return nil, errors.New("create request: invalid moderation url")
+	}
+	req.Header.Set("Content-Type", "application/json")
+	if apiKey != "" {
+		req.Header.Set("Authorization", "Bearer "+apiKey)
+	}
+
+	resp, err := c.httpClient.Do(req)
+	if err != nil {
+		// 请求体包含用户提示词，错误信息中只保留底层原因
+		var urlErr *url.Error
+		if errors.As(err, &urlErr) {
+			err = urlErr.Err
+		}
+		return nil, err
+	}
+	defer func() { _ = resp.Body.Close() }()
+
+	body, err := io.ReadAll(io.LimitReader(resp.Body, 1<<20))
+	if err != nil {
+		return nil, fmt.Errorf("read response: %w", err)
+	}
+	if resp.StatusCode < 200 || resp.StatusCode >= 300 {
+		return nil, fmt.Errorf("moderation endpoint returned %d", resp.StatusCode)
+	}
+	return body, nil
+}
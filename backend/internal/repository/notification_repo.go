@@ -0,0 +1,162 @@
+package repository
+
+import (
+	"context"
+	"database/sql"
+	"errors"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/pkg/pagination"
+	"github.com/Wei-Shaw/sub2api/internal/service"
+)
+
+type notificationRepository struct {
+	db *sql.DB
+}
+
+// NewNotificationRepository 创建通知偏好与发送记录仓储
+func NewNotificationRepository(sqlDB *sql.DB) service.NotificationRepository {
+	return &notificationRepository{db: sqlDB}
+}
+
+func (r *notificationRepository) DisabledKinds(ctx context.Context, userID int64) ([]string, error) {
+	rows, err := r.db.QueryContext(ctx, `
+		SELECT kind FROM notification_preferences WHERE user_id = $1 AND NOT email_enabled
+	`, userID)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	kinds := make([]string, 0)
+	for rows.Next() {
+		var kind string
+		if err := rows.Scan(&kind); err != nil {
+			return nil, err
+		}
+		kinds = append(kinds, kind)
+	}
+	if err := rows.Err(); err != nil {
+		return nil, err
+	}
+	return kinds, nil
+}
+
+func (r *notificationRepository) SetPreference(ctx context.Context, userID int64, kind string, enabled bool) error {
+	_, err := r.db.ExecContext(ctx, `
+		INSERT INTO notification_preferences (user_id, kind, email_enabled)
+		VALUES ($1, $2, $3)
+		ON CONFLICT (user_id, kind) DO UPDATE SET
+			email_enabled = EXCLUDED.email_enabled,
+			updated_at = NOW()
+	`, userID, kind, enabled)
+	return err
+}
+
+func (r *notificationRepository) CreateLog(ctx context.Context, log *service.NotificationLog) (bool, error) {
+	var dedupeKey sql.NullString
+	if log.DedupeKey != "" {
+		dedupeKey = sql.NullString{String: log.DedupeKey, Valid: true}
+	}
+	err := r.db.QueryRowContext(ctx, `
+		INSERT INTO notification_send_logs (user_id, recipient, kind, subject, status, error, dedupe_key)
+		VALUES ($1, $2, $3, $4, $5, $6, $7)
+		ON CONFLICT (dedupe_key) WHERE dedupe_key IS NOT NULL DO NOTHING
+		RETURNING id, created_at, updated_at
+	`, nullInt64(log.UserID), log.Recipient, log.Kind, log.Subject, log.Status, log.Error, dedupeKey).
+		Scan(&log.ID, &log.CreatedAt, &log.UpdatedAt)
+	if errors.Is(err, sql.ErrNoRows) {
+		return false, nil
+	}
+	if err != nil {
+		return false, err
+	}
+	return true, nil
+}
+
+func (r *notificationRepository) UpdateLogStatus(ctx context.Context, id int64, status, errMsg string) error {
+	_, err := r.db.ExecContext(ctx, `
+		UPDATE notification_send_logs
+		SET status = $2, error = $3, attempts = attempts + 1, updated_at = NOW(),
+			sent_at = CASE WHEN $2 = 'sent' THEN NOW() ELSE sent_at END
+		WHERE id = $1
+	`, id, status, errMsg)
+	return err
+}
+
+func (r *notificationRepository) ListLogs(ctx context.Context, filter service.NotificationLogFilter, params pagination.PaginationParams) ([]service.NotificationLog, *pagination.PaginationResult, error) {
+	where := `WHERE ($1 = 0 OR user_id = $1) AND ($2 = '' OR recipient = $2) AND ($3 = '' OR kind = $3) AND ($4 = '' OR status = $4)`
+	args := []any{filter.UserID, filter.Recipient, filter.Kind, filter.Status}
+	var total int64
+	if err := r.db.QueryRowContext(ctx, `SELECT COUNT(*) FROM notification_send_logs `+where, args...).Scan(&total); err != nil {
+		return nil, nil, err
+	}
+	rows, err := r.db.QueryContext(ctx, `
+		SELECT id, user_id, recipient, kind, subject, status, error, attempts, COALESCE(dedupe_key, ''),
+			created_at, updated_at, sent_at
+		FROM notification_send_logs `+where+`
+		ORDER BY id DESC
+		LIMIT $5 OFFSET $6`, append(args, params.Limit(), params.Offset())...)
+	if err != nil {
+		return nil, nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	logs := make([]service.NotificationLog, 0)
+	for rows.Next() {
+		var (
+			item   service.NotificationLog
+			userID sql.NullInt64
+			sentAt sql.NullTime
+		)
+		if err := rows.Scan(&item.ID, &userID, &item.Recipient, &item.Kind, &item.Subject, &item.Status, &item.Error,
+			&item.Attempts, &item.DedupeKey, &item.CreatedAt, &item.UpdatedAt, &sentAt); err != nil {
+			return nil, nil, err
+		}
+		if userID.Valid {
+			item.UserID = &userID.Int64
+		}
+		item.SentAt = nullTimeToPtr(sentAt)
+		logs = append(logs, item)
+	}
+	if err := rows.Err(); err != nil {
+		return nil, nil, err
+	}
+	return logs, paginationResultFromTotal(total, params), nil
+}
+
+func (r *notificationRepository) PurgeLogs(ctx context.Context, before time.Time) (int64, error) {
+	res, err := r.db.ExecContext(ctx, `DELETE FROM notification_send_logs WHERE created_at < $1`, before)
+	if err != nil {
+		return 0, err
+	}
+	return res.RowsAffected()
+}
+
+func (r *notificationRepository) ListExpiringAPIKeys(ctx context.Context, from, to time.Time) ([]service.ExpiringAPIKey, error) {
+	rows, err := r.db.QueryContext(ctx, `
+		SELECT k.id, k.user_id, k.name, u.email, k.expires_at
+		FROM api_keys k
+		JOIN users u ON u.id = k.user_id
+		WHERE k.expires_at >= $1 AND k.expires_at < $2
+			AND k.status = $3 AND k.deleted_at IS NULL AND u.deleted_at IS NULL
+		ORDER BY k.expires_at
+	`, from, to, service.StatusActive)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	keys := make([]service.ExpiringAPIKey, 0)
+	for rows.Next() {
+		var k service.ExpiringAPIKey
+		if err := rows.Scan(&k.ID, &k.UserID, &k.Name, &k.Email, &k.ExpiresAt); err != nil {
+			return nil, err
+		}
+		keys = append(keys, k)
+	}
+	if err := rows.Err(); err != nil {
+		return nil, err
+	}
+	return keys, nil
+}
@@ -0,0 +1,87 @@
+package repository
+
+import (
+	"context"
+	"errors"
+	"fmt"
+	"strings"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/s3"
+	"github.com/Wei-Shaw/sub2api/internal/service"
+)
+
+type s3ObjectStorage struct {
+	client *s3.Client
+	prefix string
+}
+
+// NewObjectStorage 根据 storage 配置创建对象存储；未启用时返回 nil
+func NewObjectStorage(cfg *config.Config) (service.ObjectStorage, error) {
+	if cfg == nil || !cfg.Storage.Enabled {
+		return nil, nil
+	}
+	client, err := s3.New(s3.Config{
+		Endpoint:        cfg.Storage.Endpoint,
+		Region:          cfg.Storage.Region,
+		Bucket:          cfg.Storage.Bucket,
+		AccessKeyID:     cfg.Storage.AccessKeyID,
+		SecretAccessKey: cfg.Storage.SecretAccessKey,
+		SessionToken:    cfg.Storage.SessionToken,
+		UsePathStyle:    cfg.Storage.UsePathStyle,
+	})
+	if err != nil {
+		return nil, fmt.Errorf("init object storage: %w", err)
+	}
+	return newS3ObjectStorage(client, cfg.Storage.Prefix), nil
+}
+
+func newS3ObjectStorage(client *s3.Client, prefix string) *s3ObjectStorage {
+	prefix = strings.Trim(strings.TrimSpace(prefix), "/")
+	if prefix != "" {
+		prefix += "/"
+	}
+	return &s3ObjectStorage{client: client, prefix: prefix}
+}
+
+func (s *s3ObjectStorage) Bucket() string {
+	return s.client.Bucket()
+}
+
+func (s *s3ObjectStorage) FullKey(key string) string {
+	return s.prefix + strings.TrimLeft(key, "/")
+}
+
+func (s *s3ObjectStorage) NewWriter(ctx context.Context, key, contentType string) service.ObjectWriter {
+	return s.client.NewWriter(ctx, s.FullKey(key), contentType, 0)
+}
+
+func (s *s3ObjectStorage) Get(ctx context.Context, key string) (*service.StoredObject, error) {
+	obj, err := s.client.GetObject(ctx, s.FullKey(key))
+	if err != nil {
+		if errors.Is(err, s3.ErrNotFound) {
+			return nil, service.ErrObjectNotFound
+		}
+		return nil, err
+	}
+	return &service.StoredObject{
+		Body:         obj.Body,
+		ContentType:  obj.ContentType,
+		Size:         obj.Size,
+		LastModified: obj.LastModified,
+	}, nil
+}
+
+func (s *s3ObjectStorage) Delete(ctx context.Context, key string) error {
+	return s.client.DeleteObject(ctx, s.FullKey(key))
+}
+
+func (s *s3ObjectStorage) List(ctx context.Context, prefix string, fn func(service.StoredObjectInfo) error) error {
+	return s.client.ListObjects(ctx, s.FullKey(prefix), func(obj s3.ObjectInfo) error {
+		return fn(service.StoredObjectInfo{
+			Key:          strings.TrimPrefix(obj.Key, s.prefix),
+			Size:         obj.Size,
+			LastModified: obj.LastModified,
+		})
+	})
+}
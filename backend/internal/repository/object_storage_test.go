@@ -0,0 +1,56 @@
+//go:build unit
+
+package repository
+
+import (
+	"context"
+	"net/http"
+	"net/http/httptest"
+	"testing"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/Wei-Shaw/sub2api/internal/service"
+	"github.com/stretchr/testify/require"
+)
+
+func TestNewObjectStorage_DisabledReturnsNil(t *testing.T) {
+	storage, err := NewObjectStorage(&config.Config{})
+	require.NoError(t, err)
+	require.Nil(t, storage)
+}
+
+func TestS3ObjectStorage_PrefixAndNotFound(t *testing.T) {
+	var paths []string
+	srv := httptest.NewServer(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		paths = append(paths, r.URL.Path)
+		if r.Method == http.MethodGet && r.URL.Query().Get("list-type") == "2" {
+			require.Equal(t, "prod/sora/", r.URL.Query().Get("prefix"))
+			_, _ = w.Write([]byte(`<ListBucketResult><Contents><Key>prod/sora/a.png</Key><Size>3</Size></Contents></ListBucketResult>`))
+			return
+		}
+		w.WriteHeader(http.StatusNotFound)
+	}))
+	defer srv.Close()
+
+	cfg := &config.Config{Storage: config.StorageConfig{
+		Enabled:      true,
+		Endpoint:     srv.URL,
+		Bucket:       "b",
+		Prefix:       "/prod/",
+		UsePathStyle: true,
+	}}
+	storage, err := NewObjectStorage(cfg)
+	require.NoError(t, err)
+	require.Equal(t, "prod/usage-exports/x.csv", storage.FullKey("usage-exports/x.csv"))
+
+	_, err = storage.Get(context.Background(), "missing.csv")
+	require.ErrorIs(t, err, service.ErrObjectNotFound)
+	require.Equal(t, "/b/prod/missing.csv", paths[0])
+
+	var keys []string
+	require.NoError(t, storage.List(context.Background(), "sora/", func(obj service.StoredObjectInfo) error {
+		keys = append(keys, obj.Key)
+		return nil
+	}))
+	require.Equal(t, []string{"sora/a.png"}, keys)
+}
@@ -14,10 +14,20 @@ import (
 
 type opsRepository struct {
 	db *sql.DB
+	// read 仪表盘聚合、趋势与请求明细等只读查询使用，可路由到只读副本
+	read *ReadDB
 }
 
-func NewOpsRepository(db *sql.DB) service.OpsRepository {
-	return &opsRepository{db: db}
+func NewOpsRepository(db *sql.DB, readDB *ReadDB) service.OpsRepository {
+	return &opsRepository{db: db, read: readDB}
+}
+
+// reader 返回只读查询连接，未配置 ReadDB 时使用主库
+func (r *opsRepository) reader() sqlQueryer {
+	if r.read != nil {
+		return r.read
+	}
+	return r.db
 }
 
 func (r *opsRepository) InsertErrorLog(ctx context.Context, input *service.OpsInsertErrorLogInput) (int64, error) {
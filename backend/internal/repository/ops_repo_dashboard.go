@@ -381,7 +381,7 @@ FROM ops_metrics_hourly
 WHERE ` + where + `
 ORDER BY bucket_start ASC`
 
-	rows, err := r.db.QueryContext(ctx, q, args...)
+	rows, err := r.reader().QueryContext(ctx, q, args...)
 	if err != nil {
 		return nil, err
 	}
@@ -606,7 +606,7 @@ func (r *opsRepository) rawOpsDataExists(ctx context.Context, filter *service.Op
 		join, where, args, _ := buildUsageWhere(filter, start, end, 1)
 		q := `SELECT EXISTS(SELECT 1 FROM usage_logs ul ` + join + ` ` + where + ` LIMIT 1)`
 		var exists bool
-		if err := r.db.QueryRowContext(ctx, q, args...).Scan(&exists); err != nil {
+		if err := r.reader().QueryRowContext(ctx, q, args...).Scan(&exists); err != nil {
 			return false, err
 		}
 		if exists {
@@ -618,7 +618,7 @@ func (r *opsRepository) rawOpsDataExists(ctx context.Context, filter *service.Op
 		where, args, _ := buildErrorWhere(filter, start, end, 1)
 		q := `SELECT EXISTS(SELECT 1 FROM ops_error_logs ` + where + ` LIMIT 1)`
 		var exists bool
-		if err := r.db.QueryRowContext(ctx, q, args...).Scan(&exists); err != nil {
+		if err := r.reader().QueryRowContext(ctx, q, args...).Scan(&exists); err != nil {
 			return false, err
 		}
 		return exists, nil
@@ -725,7 +725,7 @@ FROM usage_logs ul
 ` + where
 
 	var tokens sql.NullInt64
-	if err := r.db.QueryRowContext(ctx, q, args...).Scan(&successCount, &tokens); err != nil {
+	if err := r.reader().QueryRowContext(ctx, q, args...).Scan(&successCount, &tokens); err != nil {
 		return 0, 0, err
 	}
 	if tokens.Valid {
@@ -753,7 +753,7 @@ AND duration_ms IS NOT NULL`
 		var p50, p90, p95, p99 sql.NullFloat64
 		var avg sql.NullFloat64
 		var max sql.NullInt64
-		if err := r.db.QueryRowContext(ctx, q, args...).Scan(&p50, &p90, &p95, &p99, &avg, &max); err != nil {
+		if err := r.reader().QueryRowContext(ctx, q, args...).Scan(&p50, &p90, &p95, &p99, &avg, &max); err != nil {
 			return service.OpsPercentiles{}, service.OpsPercentiles{}, err
 		}
 		duration.P50 = floatToIntPtr(p50)
@@ -785,7 +785,7 @@ AND first_token_ms IS NOT NULL`
 		var p50, p90, p95, p99 sql.NullFloat64
 		var avg sql.NullFloat64
 		var max sql.NullInt64
-		if err := r.db.QueryRowContext(ctx, q, args...).Scan(&p50, &p90, &p95, &p99, &avg, &max); err != nil {
+		if err := r.reader().QueryRowContext(ctx, q, args...).Scan(&p50, &p90, &p95, &p99, &avg, &max); err != nil {
 			return service.OpsPercentiles{}, service.OpsPercentiles{}, err
 		}
 		ttft.P50 = floatToIntPtr(p50)
@@ -824,7 +824,7 @@ SELECT
 FROM ops_error_logs
 ` + where
 
-	if err := r.db.QueryRowContext(ctx, q, args...).Scan(
+	if err := r.reader().QueryRowContext(ctx, q, args...).Scan(
 		&errorTotal,
 		&businessLimited,
 		&errorCountSLA,
@@ -884,7 +884,7 @@ SELECT COALESCE(MAX(total), 0) FROM combined`
 	args := append(usageArgs, errorArgs...)
 
 	var maxPerMinute sql.NullInt64
-	if err := r.db.QueryRowContext(ctx, q, args...).Scan(&maxPerMinute); err != nil {
+	if err := r.reader().QueryRowContext(ctx, q, args...).Scan(&maxPerMinute); err != nil {
 		return 0, err
 	}
 	if !maxPerMinute.Valid || maxPerMinute.Int64 <= 0 {
@@ -909,7 +909,7 @@ FROM (
 ) t`
 
 	var maxPerMinute sql.NullInt64
-	if err := r.db.QueryRowContext(ctx, q, args...).Scan(&maxPerMinute); err != nil {
+	if err := r.reader().QueryRowContext(ctx, q, args...).Scan(&maxPerMinute); err != nil {
 		return 0, err
 	}
 	if !maxPerMinute.Valid || maxPerMinute.Int64 <= 0 {
@@ -0,0 +1,206 @@
+package repository
+
+import (
+	"context"
+	"database/sql"
+	"errors"
+	"fmt"
+	"strings"
+
+	"github.com/Wei-Shaw/sub2api/internal/pkg/pagination"
+	"github.com/Wei-Shaw/sub2api/internal/service"
+)
+
+type paymentRepository struct {
+	db *sql.DB
+}
+
+// NewPaymentRepository 创建支付订单仓储
+func NewPaymentRepository(sqlDB *sql.DB) service.PaymentRepository {
+	return &paymentRepository{db: sqlDB}
+}
+
+const paymentOrderColumns = `id, user_id, provider, session_id, COALESCE(payment_intent_id, ''), currency, amount_minor,
+	credit_amount, refunded_minor, refunded_credit, status, checkout_url, created_at, updated_at, paid_at`
+
+func scanPaymentOrder(row interface{ Scan(...any) error }) (*service.PaymentOrder, error) {
+	var (
+		o      service.PaymentOrder
+		paidAt sql.NullTime
+	)
+	if err := row.Scan(&o.ID, &o.UserID, &o.Provider, &o.SessionID, &o.PaymentIntentID, &o.Currency, &o.AmountMinor,
+		&o.CreditAmount, &o.RefundedMinor, &o.RefundedCredit, &o.Status, &o.CheckoutURL, &o.CreatedAt, &o.UpdatedAt, &paidAt); err != nil {
+		return nil, err
+	}
+	o.PaidAt = nullTimeToPtr(paidAt)
+	return &o, nil
+}
+
+func (r *paymentRepository) Create(ctx context.Context, order *service.PaymentOrder) error {
+	return r.db.QueryRowContext(ctx, `
+		INSERT INTO payment_orders (user_id, provider, session_id, currency, amount_minor, credit_amount, status, checkout_url)
+		VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+		RETURNING id, created_at, updated_at
+	`, order.UserID, order.Provider, order.SessionID, order.Currency, order.AmountMinor, order.CreditAmount, order.Status, order.CheckoutURL,
+	).Scan(&order.ID, &order.CreatedAt, &order.UpdatedAt)
+}
+
+func (r *paymentRepository) GetBySessionID(ctx context.Context, provider, sessionID string) (*service.PaymentOrder, error) {
+	order, err := scanPaymentOrder(r.db.QueryRowContext(ctx,
+		`SELECT `+paymentOrderColumns+` FROM payment_orders WHERE provider = $1 AND session_id = $2`, provider, sessionID))
+	if errors.Is(err, sql.ErrNoRows) {
+		return nil, service.ErrPaymentOrderNotFound
+	}
+	return order, err
+}
+
+func (r *paymentRepository) List(ctx context.Context, userID int64, status string, params pagination.PaginationParams) ([]service.PaymentOrder, *pagination.PaginationResult, error) {
+	conds := make([]string, 0, 2)
+	args := make([]any, 0, 4)
+	if userID > 0 {
+		args = append(args, userID)
+		conds = append(conds, fmt.Sprintf("user_id = $%d", len(args)))
+	}
+	if status != "" {
+		args = append(args, status)
+		conds = append(conds, fmt.Sprintf("status = $%d", len(args)))
+	}
+	where := ""
+	if len(conds) > 0 {
+		where = " WHERE " + strings.Join(conds, " AND ")
+	}
+
+	var total int64
+	if err := scanSingleRow(ctx, r.db, `SELECT COUNT(*) FROM payment_orders`+where, args, &total); err != nil {
+		return nil, nil, err
+	}
+	args = append(args, params.Limit(), params.Offset())
+	rows, err := r.db.QueryContext(ctx, `SELECT `+paymentOrderColumns+` FROM payment_orders`+where+
+		fmt.Sprintf(" ORDER BY created_at DESC, id DESC LIMIT $%d OFFSET $%d", len(args)-1, len(args)), args...)
+	if err != nil {
+		return nil, nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	items := make([]service.PaymentOrder, 0)
+	for rows.Next() {
+		order, err := scanPaymentOrder(rows)
+		if err != nil {
+			return nil, nil, err
+		}
+		items = append(items, *order)
+	}
+	if err := rows.Err(); err != nil {
+		return nil, nil, err
+	}
+	return items, paginationResultFromTotal(total, params), nil
+}
+
+func (r *paymentRepository) MarkPaid(ctx context.Context, provider, sessionID, paymentIntentID, ledgerCode string) (*service.PaymentOrder, bool, error) {
+	tx, err := r.db.BeginTx(ctx, nil)
+	if err != nil {
+		return nil, false, err
+	}
+	defer func() { _ = tx.Rollback() }()
+
+	// 条件更新保证同一订单只入账一次；过期后才完成的延迟支付同样入账
+	order, err := scanPaymentOrder(tx.QueryRowContext(ctx, `
+		UPDATE payment_orders
+		SET status = $3, payment_intent_id = NULLIF($4, ''), paid_at = NOW(), updated_at = NOW()
+		WHERE provider = $1 AND session_id = $2 AND status IN ($5, $6)
+		RETURNING `+paymentOrderColumns,
+		provider, sessionID, service.PaymentStatusPaid, paymentIntentID, service.PaymentStatusPending, service.PaymentStatusExpired))
+	if errors.Is(err, sql.ErrNoRows) {
+		existing, getErr := r.GetBySessionID(ctx, provider, sessionID)
+		return existing, false, getErr
+	}
+	if err != nil {
+		return nil, false, err
+	}
+
+	if err := creditUserBalance(ctx, tx, order.UserID, order.CreditAmount, ledgerCode,
+		fmt.Sprintf("%s payment %s", provider, sessionID)); err != nil {
+		return nil, false, err
+	}
+	if err := tx.Commit(); err != nil {
+		return nil, false, err
+	}
+	return order, true, nil
+}
+
+func (r *paymentRepository) ApplyRefund(ctx context.Context, provider, paymentIntentID string, refundedMinor int64, ledgerCode string) (*service.PaymentOrder, float64, error) {
+	tx, err := r.db.BeginTx(ctx, nil)
+	if err != nil {
+		return nil, 0, err
+	}
+	defer func() { _ = tx.Rollback() }()
+
+	order, err := scanPaymentOrder(tx.QueryRowContext(ctx, `
+		SELECT `+paymentOrderColumns+` FROM payment_orders
+		WHERE provider = $1 AND payment_intent_id = $2 AND status IN ($3, $4, $5)
+		FOR UPDATE
+	`, provider, paymentIntentID, service.PaymentStatusPaid, service.PaymentStatusPartiallyRefunded, service.PaymentStatusRefunded))
+	if errors.Is(err, sql.ErrNoRows) {
+		return nil, 0, service.ErrPaymentOrderNotFound
+	}
+	if err != nil {
+		return nil, 0, err
+	}
+	// 事件按累计退款金额处理，乱序或重复投递时不会重复扣回
+	if refundedMinor <= order.RefundedMinor {
+		return order, 0, nil
+	}
+
+	refundedCredit := service.PaymentRefundCredit(order, refundedMinor)
+	deducted := refundedCredit - order.RefundedCredit
+	status := service.PaymentStatusPartiallyRefunded
+	if refundedMinor >= order.AmountMinor {
+		status = service.PaymentStatusRefunded
+	}
+	if _, err := tx.ExecContext(ctx, `
+		UPDATE payment_orders
+		SET refunded_minor = $2, refunded_credit = $3, status = $4, updated_at = NOW()
+		WHERE id = $1
+	`, order.ID, refundedMinor, refundedCredit, status); err != nil {
+		return nil, 0, err
+	}
+	// 余额可能已被消费，扣回后允许为负，由余额检查阻止后续请求
+	if err := creditUserBalance(ctx, tx, order.UserID, -deducted, ledgerCode,
+		fmt.Sprintf("%s refund %s", provider, paymentIntentID)); err != nil {
+		return nil, 0, err
+	}
+	if err := tx.Commit(); err != nil {
+		return nil, 0, err
+	}
+	order.RefundedMinor, order.RefundedCredit, order.Status = refundedMinor, refundedCredit, status
+	return order, deducted, nil
+}
+
+func (r *paymentRepository) MarkExpired(ctx context.Context, provider, sessionID string) error {
+	_, err := r.db.ExecContext(ctx, `
+		UPDATE payment_orders SET status = $3, updated_at = NOW()
+		WHERE provider = $1 AND session_id = $2 AND status = $4
+	`, provider, sessionID, service.PaymentStatusExpired, service.PaymentStatusPending)
+	return err
+}
+
+// creditUserBalance 调整用户余额并写入一条已使用的余额记录（与卡密兑换、管理员调整共用余额历史）
+func creditUserBalance(ctx context.Context, tx *sql.Tx, userID int64, amount float64, code, notes string) error {
+	res, err := tx.ExecContext(ctx, `
+		UPDATE users SET balance = balance + $2, updated_at = NOW()
+		WHERE id = $1 AND deleted_at IS NULL
+	`, userID, amount)
+	if err != nil {
+		return err
+	}
+	if n, err := res.RowsAffected(); err != nil {
+		return err
+	} else if n == 0 {
+		return service.ErrUserNotFound
+	}
+	_, err = tx.ExecContext(ctx, `
+		INSERT INTO redeem_codes (code, type, value, status, used_by, used_at, notes, created_at)
+		VALUES ($1, $2, $3, $4, $5, NOW(), $6, NOW())
+	`, code, service.RedeemTypeBalance, amount, service.StatusUsed, userID, notes)
+	return err
+}
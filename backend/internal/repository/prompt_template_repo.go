@@ -0,0 +1,244 @@
+package repository
+
+import (
+	"context"
+	"database/sql"
+	"encoding/json"
+	"errors"
+
+	"github.com/Wei-Shaw/sub2api/internal/service"
+)
+
+const promptTemplateColumns = `id, user_id, name, description, latest_version, created_at, updated_at`
+
+const promptTemplateVersionColumns = `v.version, v.system, v.messages, v.variables, v.created_at`
+
+type promptTemplateRepository struct {
+	db *sql.DB
+}
+
+// NewPromptTemplateRepository 创建提示词模板仓储
+func NewPromptTemplateRepository(sqlDB *sql.DB) service.PromptTemplateRepository {
+	return &promptTemplateRepository{db: sqlDB}
+}
+
+func (r *promptTemplateRepository) Create(ctx context.Context, tmpl *service.PromptTemplate, version *service.PromptTemplateVersion) error {
+	messages, variables, err := marshalPromptTemplateVersion(version)
+	if err != nil {
+		return err
+	}
+	tx, err := r.db.BeginTx(ctx, nil)
+	if err != nil {
+		return err
+	}
+	defer func() { _ = tx.Rollback() }()
+
+	var id string
+	err = tx.QueryRowContext(ctx, `
+INSERT INTO prompt_templates (id, user_id, name, description, latest_version, created_at, updated_at)
+VALUES ($1, $2, $3, $4, $5, $6, $7)
+ON CONFLICT (user_id, name) DO NOTHING
+RETURNING id
+`, tmpl.ID, tmpl.UserID, tmpl.Name, tmpl.Description, version.Version, tmpl.CreatedAt, tmpl.UpdatedAt).Scan(&id)
+	if errors.Is(err, sql.ErrNoRows) {
+		return service.ErrPromptTemplateNameExists
+	}
+	if err != nil {
+		return err
+	}
+	if _, err := tx.ExecContext(ctx, `
+INSERT INTO prompt_template_versions (template_id, version, system, messages, variables, created_at)
+VALUES ($1, $2, $3, $4, $5, $6)
+`, tmpl.ID, version.Version, version.System, messages, variables, version.CreatedAt); err != nil {
+		return err
+	}
+	return tx.Commit()
+}
+
+func (r *promptTemplateRepository) AddVersion(ctx context.Context, userID int64, ref string, description *string, version *service.PromptTemplateVersion) (*service.PromptTemplate, error) {
+	messages, variables, err := marshalPromptTemplateVersion(version)
+	if err != nil {
+		return nil, err
+	}
+	tx, err := r.db.BeginTx(ctx, nil)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = tx.Rollback() }()
+
+	// 行锁保证并发更新时版本号连续
+	tmpl, err := scanPromptTemplate(tx.QueryRowContext(ctx, `
+UPDATE prompt_templates
+SET latest_version = latest_version + 1,
+    description = COALESCE($3, description),
+    updated_at = $4
+WHERE user_id = $1 AND (id = $2 OR name = $2)
+RETURNING `+promptTemplateColumns+`
+`, userID, ref, description, version.CreatedAt))
+	if errors.Is(err, sql.ErrNoRows) {
+		return nil, service.ErrPromptTemplateNotFound
+	}
+	if err != nil {
+		return nil, err
+	}
+	if _, err := tx.ExecContext(ctx, `
+INSERT INTO prompt_template_versions (template_id, version, system, messages, variables, created_at)
+VALUES ($1, $2, $3, $4, $5, $6)
+`, tmpl.ID, tmpl.LatestVersion, version.System, messages, variables, version.CreatedAt); err != nil {
+		return nil, err
+	}
+	if err := tx.Commit(); err != nil {
+		return nil, err
+	}
+	version.Version = tmpl.LatestVersion
+	return tmpl, nil
+}
+
+func (r *promptTemplateRepository) Get(ctx context.Context, userID int64, ref string) (*service.PromptTemplate, error) {
+	tmpl, err := scanPromptTemplate(r.db.QueryRowContext(ctx, `
+SELECT `+promptTemplateColumns+`
+FROM prompt_templates
+WHERE user_id = $1 AND (id = $2 OR name = $2)
+`, userID, ref))
+	if errors.Is(err, sql.ErrNoRows) {
+		return nil, service.ErrPromptTemplateNotFound
+	}
+	return tmpl, err
+}
+
+func (r *promptTemplateRepository) GetVersion(ctx context.Context, userID int64, ref string, version int) (*service.PromptTemplateVersion, error) {
+	v, err := scanPromptTemplateVersion(r.db.QueryRowContext(ctx, `
+SELECT `+promptTemplateVersionColumns+`
+FROM prompt_templates t
+JOIN prompt_template_versions v ON v.template_id = t.id
+WHERE t.user_id = $1 AND (t.id = $2 OR t.name = $2)
+  AND v.version = CASE WHEN $3 > 0 THEN $3 ELSE t.latest_version END
+`, userID, ref, version))
+	if errors.Is(err, sql.ErrNoRows) {
+		return nil, service.ErrPromptTemplateNotFound
+	}
+	return v, err
+}
+
+func (r *promptTemplateRepository) List(ctx context.Context, userID int64) ([]*service.PromptTemplate, error) {
+	rows, err := r.db.QueryContext(ctx, `
+SELECT `+promptTemplateColumns+`
+FROM prompt_templates
+WHERE user_id = $1
+ORDER BY updated_at DESC, id
+`, userID)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	out := make([]*service.PromptTemplate, 0)
+	for rows.Next() {
+		tmpl, err := scanPromptTemplate(rows)
+		if err != nil {
+			return nil, err
+		}
+		out = append(out, tmpl)
+	}
+	if err := rows.Err(); err != nil {
+		return nil, err
+	}
+	return out, nil
+}
+
+func (r *promptTemplateRepository) ListVersions(ctx context.Context, userID int64, ref string) ([]*service.PromptTemplateVersion, error) {
+	rows, err := r.db.QueryContext(ctx, `
+SELECT `+promptTemplateVersionColumns+`
+FROM prompt_templates t
+JOIN prompt_template_versions v ON v.template_id = t.id
+WHERE t.user_id = $1 AND (t.id = $2 OR t.name = $2)
+ORDER BY v.version DESC
+`, userID, ref)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	out := make([]*service.PromptTemplateVersion, 0)
+	for rows.Next() {
+		v, err := scanPromptTemplateVersion(rows)
+		if err != nil {
+			return nil, err
+		}
+		out = append(out, v)
+	}
+	if err := rows.Err(); err != nil {
+		return nil, err
+	}
+	// 每个模板至少有一个版本，结果为空说明模板不存在
+	if len(out) == 0 {
+		return nil, service.ErrPromptTemplateNotFound
+	}
+	return out, nil
+}
+
+func (r *promptTemplateRepository) Count(ctx context.Context, userID int64) (int, error) {
+	var count int
+	err := r.db.QueryRowContext(ctx, `SELECT COUNT(*) FROM prompt_templates WHERE user_id = $1`, userID).Scan(&count)
+	return count, err
+}
+
+func (r *promptTemplateRepository) Delete(ctx context.Context, userID int64, ref string) error {
+	res, err := r.db.ExecContext(ctx, `
+DELETE FROM prompt_templates WHERE user_id = $1 AND (id = $2 OR name = $2)
+`, userID, ref)
+	if err != nil {
+		return err
+	}
+	affected, err := res.RowsAffected()
+	if err != nil {
+		return err
+	}
+	if affected == 0 {
+		return service.ErrPromptTemplateNotFound
+	}
+	return nil
+}
+
+func marshalPromptTemplateVersion(version *service.PromptTemplateVersion) ([]byte, []byte, error) {
+	messages := version.Messages
+	if messages == nil {
+		messages = []service.PromptTemplateMessage{}
+	}
+	variables := version.Variables
+	if variables == nil {
+		variables = []service.PromptTemplateVariable{}
+	}
+	rawMessages, err := json.Marshal(messages)
+	if err != nil {
+		return nil, nil, err
+	}
+	rawVariables, err := json.Marshal(variables)
+	if err != nil {
+		return nil, nil, err
+	}
+	return rawMessages, rawVariables, nil
+}
+
+func scanPromptTemplate(row interface{ Scan(...any) error }) (*service.PromptTemplate, error) {
+	tmpl := &service.PromptTemplate{}
+	if err := row.Scan(&tmpl.ID, &tmpl.UserID, &tmpl.Name, &tmpl.Description, &tmpl.LatestVersion, &tmpl.CreatedAt, &tmpl.UpdatedAt); err != nil {
+		return nil, err
+	}
+	return tmpl, nil
+}
+
+func scanPromptTemplateVersion(row interface{ Scan(...any) error }) (*service.PromptTemplateVersion, error) {
+	v := &service.PromptTemplateVersion{}
+	var messages, variables []byte
+	if err := row.Scan(&v.Version, &v.System, &messages, &variables, &v.CreatedAt); err != nil {
+		return nil, err
+	}
+	if err := json.Unmarshal(messages, &v.Messages); err != nil {
+		return nil, err
+	}
+	if err := json.Unmarshal(variables, &v.Variables); err != nil {
+		return nil, err
+	}
+	return v, nil
+}
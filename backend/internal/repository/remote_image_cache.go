@@ -0,0 +1,58 @@
+package repository
+
+import (
+	"context"
+	"crypto/sha256"
+	"encoding/hex"
+	"encoding/json"
+	"errors"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/service"
+	"github.com/redis/go-redis/v9"
+)
+
+const remoteImageCacheKeyPrefix = "attachment:remote_image:"
+
+type remoteImageCache struct {
+	rdb *redis.Client
+}
+
+type remoteImageCacheEntry struct {
+	Filename  string `json:"filename"`
+	MediaType string `json:"media_type"`
+	Data      []byte `json:"data"`
+}
+
+// NewRemoteImageCache 创建远程图片缓存（按 URL 的 SHA-256 存储）
+func NewRemoteImageCache(rdb *redis.Client) service.RemoteImageCache {
+	return &remoteImageCache{rdb: rdb}
+}
+
+func remoteImageCacheKey(rawURL string) string {
+	sum := sha256.Sum256([]byte(rawURL))
+	return remoteImageCacheKeyPrefix + hex.EncodeToString(sum[:])
+}
+
+func (c *remoteImageCache) Get(ctx context.Context, rawURL string) (*service.Attachment, error) {
+	val, err := c.rdb.Get(ctx, remoteImageCacheKey(rawURL)).Bytes()
+	if errors.Is(err, redis.Nil) {
+		return nil, nil
+	}
+	if err != nil {
+		return nil, err
+	}
+	var entry remoteImageCacheEntry
+	if err := json.Unmarshal(val, &entry); err != nil {
+		return nil, err
+	}
+	return &service.Attachment{Filename: entry.Filename, MediaType: entry.MediaType, Data: entry.Data}, nil
+}
+
+func (c *remoteImageCache) Set(ctx context.Context, rawURL string, att *service.Attachment, ttl time.Duration) error {
+	val, err := json.Marshal(remoteImageCacheEntry{Filename: att.Filename, MediaType: att.MediaType, Data: att.Data})
+	if err != nil {
+		return err
+	}
+	return c.rdb.Set(ctx, remoteImageCacheKey(rawURL), val, ttl).Err()
+}
@@ -0,0 +1,146 @@
+package repository
+
+import (
+	"context"
+	"errors"
+	"fmt"
+	"io"
+	"net"
+	"net/http"
+	"net/url"
+	"strings"
+	"syscall"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/Wei-Shaw/sub2api/internal/service"
+	"github.com/Wei-Shaw/sub2api/internal/util/urlvalidator"
+)
+
+const remoteImageMaxRedirects = 3
+
+var errRemoteImageBlockedAddress = errors.New("destination address is not allowed")
+
+// remoteImageBlockedNets 除 net.IP 分类方法外需要拒绝的保留网段
+var remoteImageBlockedNets = mustParseCIDRs(
+	"0.0.0.0/8",       // 本网络
+	"100.64.0.0/10",   // 运营商级 NAT
+	"192.0.0.0/24",    // IETF 协议分配
+	"192.0.2.0/24",    // 文档示例
+	"198.18.0.0/15",   // 基准测试
+	"198.51.100.0/24", // 文档示例
+	"203.0.113.0/24",  // 文档示例
+	"240.0.0.0/4",     // 保留
+	"64:ff9b::/96",    // NAT64（可映射到内网 IPv4）
+	"64:ff9b:1::/48",  // 本地 NAT64
+	"100::/64",        // 丢弃前缀
+	"2001:db8::/32",   // 文档示例
+	"2002::/16",       // 6to4（可映射到内网 IPv4）
+)
+
+type remoteImageFetcher struct {
+	httpClient *http.Client
+}
+
+// NewRemoteImageFetcher 创建远程图片下载器。
+//
+// 在拨号时校验实际连接的 IP（而非预先解析），避免 DNS Rebinding；不使用环境代理，
+// 重定向同样经过 scheme / 白名单校验。
+func NewRemoteImageFetcher(cfg *config.Config) service.RemoteImageFetcher {
+	opts := cfg.Attachments.RemoteImages
+	dialer := &net.Dialer{
+		Timeout: 5 * time.Second,
+		Control: func(_, address string, _ syscall.RawConn) error {
+			host, _, err := net.SplitHostPort(address)
+			if err != nil {
+				return err
+			}
+			if ip := net.ParseIP(host); ip == nil || !isPublicRemoteImageIP(ip) {
+				return errRemoteImageBlockedAddress
+			}
+			return nil
+		},
+	}
+	transport := &http.Transport{
+		Proxy:                 nil,
+		DialContext:           dialer.DialContext,
+		ForceAttemptHTTP2:     true,
+		MaxIdleConns:          20,
+		MaxIdleConnsPerHost:   2,
+		IdleConnTimeout:       30 * time.Second,
+		TLSHandshakeTimeout:   5 * time.Second,
+		ResponseHeaderTimeout: 10 * time.Second,
+	}
+	return &remoteImageFetcher{httpClient: &http.Client{
+		Transport: transport,
+		CheckRedirect: func(req *http.Request, via []*http.Request) error {
+			if len(via) >= remoteImageMaxRedirects {
+				return errors.New("too many redirects")
+			}
+			_, err := urlvalidator.ValidateHTTPURL(req.URL.String(), opts.AllowInsecureHTTP, urlvalidator.ValidationOptions{AllowedHosts: opts.AllowedHosts})
+			return err
+		},
+	}}
+}
+
+func (f *remoteImageFetcher) Fetch(ctx context.Context, rawURL string, maxBytes int64) (*service.Attachment, error) {
+	req, err := http.NewRequestWithContext(ctx, http.MethodGet, rawURL, nil)
+	if err != nil {
+		return nil, errors.New("invalid url")
+	}
+	req.Header.Set("Accept", "image/*")
+
+	resp, err := f.httpClient.Do(req)
+	if err != nil {
+		var urlErr *url.Error
+		if errors.As(err, &urlErr) {
+			err = urlErr.Err
+		}
+		return nil, err
+	}
+	defer func() { _ = resp.Body.Close() }()
+
+	if resp.StatusCode != http.StatusOK {
+		return nil, fmt.Errorf("remote server returned %d", resp.StatusCode)
+	}
+	contentType := resp.Header.Get("Content-Type")
+	if !strings.HasPrefix(strings.ToLower(strings.TrimSpace(contentType)), "image/") {
+		return nil, service.ErrAttachmentUnsupportedType
+	}
+	if resp.ContentLength > maxBytes {
+		return nil, service.ErrAttachmentTooLarge
+	}
+	data, err := io.ReadAll(io.LimitReader(resp.Body, maxBytes+1))
+	if err != nil {
+		return nil, fmt.Errorf("read response: %w", err)
+	}
+	if int64(len(data)) > maxBytes {
+		return nil, service.ErrAttachmentTooLarge
+	}
+	return &service.Attachment{MediaType: contentType, Data: data}, nil
+}
+
+func isPublicRemoteImageIP(ip net.IP) bool {
+	if ip.IsLoopback() || ip.IsPrivate() || ip.IsUnspecified() || ip.IsMulticast() ||
+		ip.IsLinkLocalUnicast() || ip.IsLinkLocalMulticast() || ip.IsInterfaceLocalMulticast() {
+		return false
+	}
+	for _, blocked := range remoteImageBlockedNets {
+		if blocked.Contains(ip) {
+			return false
+		}
+	}
+	return true
+}
+
+func mustParseCIDRs(cidrs ...string) []*net.IPNet {
+	nets := make([]*net.IPNet, 0, len(cidrs))
+	for _, cidr := range cidrs {
+		_, n, err := net.ParseCIDR(cidr)
+		if err != nil {
+			panic(err)
+		}
+		nets = append(nets, n)
+	}
+	return nets
+}
@@ -0,0 +1,53 @@
+//go:build unit
+
+package repository
+
+import (
+	"context"
+	"net"
+	"net/http"
+	"net/http/httptest"
+	"testing"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/stretchr/testify/require"
+)
+
+func TestIsPublicRemoteImageIP(t *testing.T) {
+	cases := map[string]bool{
+		"8.8.8.8":                true,
+		"2606:4700::1111":        true,
+		"127.0.0.1":              false,
+		"10.1.2.3":               false,
+		"172.16.0.1":             false,
+		"192.168.1.1":            false,
+		"169.254.169.254":        false,
+		"100.64.0.1":             false,
+		"0.0.0.0":                false,
+		"::1":                    false,
+		"fd00::1":                false,
+		"fe80::1":                false,
+		"::ffff:127.0.0.1":       false,
+		"64:ff9b::a00:1":         false,
+		"2002:c0a8:101::1":       false,
+		"255.255.255.255":        false,
+		"ff02::1":                false,
+		"2001:db8::1":            false,
+		"::ffff:169.254.169.254": false,
+	}
+	for raw, want := range cases {
+		require.Equal(t, want, isPublicRemoteImageIP(net.ParseIP(raw)), raw)
+	}
+}
+
+func TestRemoteImageFetcher_RefusesLoopback(t *testing.T) {
+	server := httptest.NewServer(http.HandlerFunc(func(w http.ResponseWriter, _ *http.Request) {
+		w.Header().Set("Content-Type", "image/png")
+		_, _ = w.Write([]byte("\x89PNG\r\n\x1a\n"))
+	}))
+	defer server.Close()
+
+	fetcher := NewRemoteImageFetcher(&config.Config{})
+	_, err := fetcher.Fetch(context.Background(), server.URL+"/a.png", 1<<20)
+	require.ErrorIs(t, err, errRemoteImageBlockedAddress)
+}
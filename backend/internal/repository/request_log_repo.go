@@ -0,0 +1,230 @@
+package repository
+
+import (
+	"context"
+	"database/sql"
+	"strings"
+	"time"
+	"unicode/utf8"
+
+	"github.com/Wei-Shaw/sub2api/internal/service"
+	"github.com/lib/pq"
+)
+
+type requestLogRepository struct {
+	db *sql.DB
+}
+
+// NewRequestLogRepository 创建请求日志仓储
+func NewRequestLogRepository(sqlDB *sql.DB) service.RequestLogRepository {
+	return &requestLogRepository{db: sqlDB}
+}
+
+// BatchInsert 使用 COPY 批量写入请求日志
+func (r *requestLogRepository) BatchInsert(ctx context.Context, entries []*service.RequestLogEntry) error {
+	if len(entries) == 0 {
+		return nil
+	}
+	tx, err := r.db.BeginTx(ctx, nil)
+	if err != nil {
+		return err
+	}
+	stmt, err := tx.PrepareContext(ctx, pq.CopyIn(
+		"request_logs",
+		"created_at",
+		"trace_id",
+		"client_request_id",
+		"user_id",
+		"api_key_id",
+		"account_id",
+		"group_id",
+		"platform",
+		"model",
+		"method",
+		"path",
+		"stream",
+		"status_code",
+		"error_class",
+		"duration_ms",
+		"client_ip",
+	))
+	if err != nil {
+		_ = tx.Rollback()
+		return err
+	}
+
+	for _, e := range entries {
+		if e == nil {
+			continue
+		}
+		if _, err := stmt.ExecContext(
+			ctx,
+			e.CreatedAt.UTC(),
+			opsNullString(truncateRequestLogField(e.TraceID, 64)),
+			opsNullString(truncateRequestLogField(e.ClientRequestID, 64)),
+			opsNullInt64(e.UserID),
+			opsNullInt64(e.APIKeyID),
+			opsNullInt64(e.AccountID),
+			opsNullInt64(e.GroupID),
+			opsNullString(truncateRequestLogField(e.Platform, 32)),
+			opsNullString(truncateRequestLogField(e.Model, 128)),
+			truncateRequestLogField(e.Method, 16),
+			truncateRequestLogField(e.Path, 256),
+			e.Stream,
+			e.StatusCode,
+			opsNullString(truncateRequestLogField(e.ErrorClass, 64)),
+			e.DurationMs,
+			opsNullString(truncateRequestLogField(e.ClientIP, 64)),
+		); err != nil {
+			_ = stmt.Close()
+			_ = tx.Rollback()
+			return err
+		}
+	}
+
+	if _, err := stmt.ExecContext(ctx); err != nil {
+		_ = stmt.Close()
+		_ = tx.Rollback()
+		return err
+	}
+	if err := stmt.Close(); err != nil {
+		_ = tx.Rollback()
+		return err
+	}
+	return tx.Commit()
+}
+
+// Search 按条件分页检索请求日志（created_at 倒序）
+func (r *requestLogRepository) Search(ctx context.Context, filter service.RequestLogFilter) ([]*service.RequestLogEntry, int64, error) {
+	clauses := make([]string, 0, 10)
+	args := make([]any, 0, 12)
+
+	args = append(args, filter.StartTime.UTC())
+	clauses = append(clauses, "created_at >= $"+itoa(len(args)))
+	args = append(args, filter.EndTime.UTC())
+	clauses = append(clauses, "created_at < $"+itoa(len(args)))
+
+	if filter.APIKeyID != nil {
+		args = append(args, *filter.APIKeyID)
+		clauses = append(clauses, "api_key_id = $"+itoa(len(args)))
+	}
+	if filter.UserID != nil {
+		args = append(args, *filter.UserID)
+		clauses = append(clauses, "user_id = $"+itoa(len(args)))
+	}
+	if filter.AccountID != nil {
+		args = append(args, *filter.AccountID)
+		clauses = append(clauses, "account_id = $"+itoa(len(args)))
+	}
+	if filter.Model != "" {
+		args = append(args, filter.Model)
+		clauses = append(clauses, "model = $"+itoa(len(args)))
+	}
+	if len(filter.StatusCodes) > 0 {
+		args = append(args, pq.Array(filter.StatusCodes))
+		clauses = append(clauses, "status_code = ANY($"+itoa(len(args))+")")
+	}
+	if filter.ErrorsOnly {
+		clauses = append(clauses, "status_code >= 400")
+	}
+	if filter.ErrorClass != "" {
+		args = append(args, filter.ErrorClass)
+		clauses = append(clauses, "error_class = $"+itoa(len(args)))
+	}
+	if filter.TraceID != "" {
+		args = append(args, filter.TraceID)
+		n := itoa(len(args))
+		clauses = append(clauses, "(trace_id = $"+n+" OR client_request_id = $"+n+")")
+	}
+	where := "WHERE " + strings.Join(clauses, " AND ")
+
+	var total int64
+	if err := r.db.QueryRowContext(ctx, "SELECT COUNT(*) FROM request_logs "+where, args...).Scan(&total); err != nil {
+		return nil, 0, err
+	}
+	if total == 0 {
+		return []*service.RequestLogEntry{}, 0, nil
+	}
+
+	args = append(args, filter.PageSize)
+	limitArg := itoa(len(args))
+	args = append(args, (filter.Page-1)*filter.PageSize)
+	offsetArg := itoa(len(args))
+	q := `
+SELECT id, created_at, trace_id, client_request_id, user_id, api_key_id, account_id, group_id,
+       platform, model, method, path, stream, status_code, error_class, duration_ms, client_ip
+FROM request_logs
+` + where + `
+ORDER BY created_at DESC, id DESC
+LIMIT $` + limitArg + ` OFFSET $` + offsetArg
+
+	rows, err := r.db.QueryContext(ctx, q, args...)
+	if err != nil {
+		return nil, 0, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	items := make([]*service.RequestLogEntry, 0, filter.PageSize)
+	for rows.Next() {
+		var (
+			e                                         service.RequestLogEntry
+			traceID, clientRequestID, platform, model sql.NullString
+			errorClass, clientIP                      sql.NullString
+			userID, apiKeyID, accountID, groupID      sql.NullInt64
+		)
+		if err := rows.Scan(
+			&e.ID, &e.CreatedAt, &traceID, &clientRequestID, &userID, &apiKeyID, &accountID, &groupID,
+			&platform, &model, &e.Method, &e.Path, &e.Stream, &e.StatusCode, &errorClass, &e.DurationMs, &clientIP,
+		); err != nil {
+			return nil, 0, err
+		}
+		e.TraceID = traceID.String
+		e.ClientRequestID = clientRequestID.String
+		e.Platform = platform.String
+		e.Model = model.String
+		e.ErrorClass = errorClass.String
+		e.ClientIP = clientIP.String
+		e.UserID = nullInt64Ptr(userID)
+		e.APIKeyID = nullInt64Ptr(apiKeyID)
+		e.AccountID = nullInt64Ptr(accountID)
+		e.GroupID = nullInt64Ptr(groupID)
+		items = append(items, &e)
+	}
+	if err := rows.Err(); err != nil {
+		return nil, 0, err
+	}
+	return items, total, nil
+}
+
+// DeleteBefore 删除 cutoff 之前的请求日志，单次最多 limit 条
+func (r *requestLogRepository) DeleteBefore(ctx context.Context, cutoff time.Time, limit int) (int64, error) {
+	res, err := r.db.ExecContext(ctx, `
+DELETE FROM request_logs
+WHERE id IN (
+	SELECT id FROM request_logs WHERE created_at < $1 ORDER BY id LIMIT $2
+)`, cutoff.UTC(), limit)
+	if err != nil {
+		return 0, err
+	}
+	return res.RowsAffected()
+}
+
+func nullInt64Ptr(v sql.NullInt64) *int64 {
+	if !v.Valid {
+		return nil
+	}
+	n := v.Int64
+	return &n
+}
+
+// truncateRequestLogField 按列宽截断（保持 UTF-8 完整）
+func truncateRequestLogField(s string, maxLen int) string {
+	if len(s) <= maxLen {
+		return s
+	}
+	cut := maxLen
+	for cut > 0 && !utf8.RuneStart(s[cut]) {
+		cut--
+	}
+	return s[:cut]
+}
@@ -0,0 +1,223 @@
+package repository
+
+import (
+	"context"
+	"database/sql"
+	"encoding/json"
+	"errors"
+
+	"github.com/Wei-Shaw/sub2api/internal/service"
+	"github.com/lib/pq"
+)
+
+const routingRuleColumns = `id, name, description, priority, enabled, conditions, target_group_id, fallback_group_ids, param_overrides, created_at, updated_at`
+
+type routingRuleRepository struct {
+	db *sql.DB
+}
+
+// NewRoutingRuleRepository 创建路由规则仓储
+func NewRoutingRuleRepository(sqlDB *sql.DB) service.RoutingRuleRepository {
+	return &routingRuleRepository{db: sqlDB}
+}
+
+func (r *routingRuleRepository) List(ctx context.Context) ([]*service.RoutingRule, error) {
+	rows, err := r.db.QueryContext(ctx, `SELECT `+routingRuleColumns+` FROM routing_rules ORDER BY priority, id`)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	out := make([]*service.RoutingRule, 0)
+	for rows.Next() {
+		rule, err := scanRoutingRule(rows)
+		if err != nil {
+			return nil, err
+		}
+		out = append(out, rule)
+	}
+	if err := rows.Err(); err != nil {
+		return nil, err
+	}
+	return out, nil
+}
+
+func (r *routingRuleRepository) GetByID(ctx context.Context, id int64) (*service.RoutingRule, error) {
+	rule, err := scanRoutingRule(r.db.QueryRowContext(ctx, `SELECT `+routingRuleColumns+` FROM routing_rules WHERE id = $1`, id))
+	if errors.Is(err, sql.ErrNoRows) {
+		return nil, service.ErrRoutingRuleNotFound
+	}
+	return rule, err
+}
+
+func (r *routingRuleRepository) Create(ctx context.Context, rule *service.RoutingRule) error {
+	conditions, overrides, err := marshalRoutingRuleJSON(rule)
+	if err != nil {
+		return err
+	}
+	return r.db.QueryRowContext(ctx, `
+INSERT INTO routing_rules (name, description, priority, enabled, conditions, target_group_id, fallback_group_ids, param_overrides)
+VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+RETURNING id, created_at, updated_at
+`, rule.Name, rule.Description, rule.Priority, rule.Enabled, conditions, rule.TargetGroupID, pq.Array(rule.FallbackGroupIDs), overrides,
+	).Scan(&rule.ID, &rule.CreatedAt, &rule.UpdatedAt)
+}
+
+func (r *routingRuleRepository) Update(ctx context.Context, rule *service.RoutingRule) error {
+	conditions, overrides, err := marshalRoutingRuleJSON(rule)
+	if err != nil {
+		return err
+	}
+	err = r.db.QueryRowContext(ctx, `
+UPDATE routing_rules SET
+	name = $2,
+	description = $3,
+	priority = $4,
+	enabled = $5,
+	conditions = $6,
+	target_group_id = $7,
+	fallback_group_ids = $8,
+	param_overrides = $9,
+	updated_at = NOW()
+WHERE id = $1
+RETURNING created_at, updated_at
+`, rule.ID, rule.Name, rule.Description, rule.Priority, rule.Enabled, conditions, rule.TargetGroupID, pq.Array(rule.FallbackGroupIDs), overrides,
+	).Scan(&rule.CreatedAt, &rule.UpdatedAt)
+	if errors.Is(err, sql.ErrNoRows) {
+		return service.ErrRoutingRuleNotFound
+	}
+	return err
+}
+
+func (r *routingRuleRepository) Delete(ctx context.Context, id int64) error {
+	res, err := r.db.ExecContext(ctx, `DELETE FROM routing_rules WHERE id = $1`, id)
+	if err != nil {
+		return err
+	}
+	if n, err := res.RowsAffected(); err == nil && n == 0 {
+		return service.ErrRoutingRuleNotFound
+	}
+	return nil
+}
+
+func (r *routingRuleRepository) GetKeyTags(ctx context.Context, apiKeyID int64) ([]string, error) {
+	var tags pq.StringArray
+	err := r.db.QueryRowContext(ctx, `
+SELECT COALESCE((SELECT array_agg(tag ORDER BY tag) FROM api_key_tags WHERE api_key_id = k.id), '{}')
+FROM api_keys k WHERE k.id = $1 AND k.deleted_at IS NULL
+`, apiKeyID).Scan(&tags)
+	if errors.Is(err, sql.ErrNoRows) {
+		return nil, service.ErrAPIKeyNotFound
+	}
+	if err != nil {
+		return nil, err
+	}
+	if tags == nil {
+		return []string{}, nil
+	}
+	return []string(tags), nil
+}
+
+func (r *routingRuleRepository) SetKeyTags(ctx context.Context, apiKeyID int64, tags []string) error {
+	tx, err := r.db.BeginTx(ctx, nil)
+	if err != nil {
+		return err
+	}
+	defer func() { _ = tx.Rollback() }()
+
+	var exists bool
+	if err := tx.QueryRowContext(ctx, `SELECT EXISTS(SELECT 1 FROM api_keys WHERE id = $1 AND deleted_at IS NULL)`, apiKeyID).Scan(&exists); err != nil {
+		return err
+	}
+	if !exists {
+		return service.ErrAPIKeyNotFound
+	}
+	if _, err := tx.ExecContext(ctx, `DELETE FROM api_key_tags WHERE api_key_id = $1`, apiKeyID); err != nil {
+		return err
+	}
+	if len(tags) > 0 {
+		if _, err := tx.ExecContext(ctx, `
+INSERT INTO api_key_tags (api_key_id, tag)
+SELECT $1, t FROM unnest($2::text[]) AS t
+ON CONFLICT DO NOTHING
+`, apiKeyID, pq.Array(tags)); err != nil {
+			return err
+		}
+	}
+	return tx.Commit()
+}
+
+func (r *routingRuleRepository) ListKeyTagsByTags(ctx context.Context, tags []string) (map[int64][]string, error) {
+	out := make(map[int64][]string)
+	if len(tags) == 0 {
+		return out, nil
+	}
+	rows, err := r.db.QueryContext(ctx, `SELECT api_key_id, tag FROM api_key_tags WHERE tag = ANY($1) ORDER BY api_key_id, tag`, pq.Array(tags))
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	for rows.Next() {
+		var (
+			apiKeyID int64
+			tag      string
+		)
+		if err := rows.Scan(&apiKeyID, &tag); err != nil {
+			return nil, err
+		}
+		out[apiKeyID] = append(out[apiKeyID], tag)
+	}
+	if err := rows.Err(); err != nil {
+		return nil, err
+	}
+	return out, nil
+}
+
+func marshalRoutingRuleJSON(rule *service.RoutingRule) (string, string, error) {
+	conditions, err := json.Marshal(rule.Conditions)
+	if err != nil {
+		return "", "", err
+	}
+	overrides := rule.ParamOverrides
+	if overrides == nil {
+		overrides = map[string]any{}
+	}
+	overridesJSON, err := json.Marshal(overrides)
+	if err != nil {
+		return "", "", err
+	}
+	return string(conditions), string(overridesJSON), nil
+}
+
+func scanRoutingRule(row interface{ Scan(...any) error }) (*service.RoutingRule, error) {
+	var (
+		rule          service.RoutingRule
+		conditions    []byte
+		targetGroupID sql.NullInt64
+		fallbacks     pq.Int64Array
+		overrides     []byte
+	)
+	if err := row.Scan(&rule.ID, &rule.Name, &rule.Description, &rule.Priority, &rule.Enabled, &conditions, &targetGroupID, &fallbacks, &overrides, &rule.CreatedAt, &rule.UpdatedAt); err != nil {
+		return nil, err
+	}
+	if len(conditions) > 0 {
+		if err := json.Unmarshal(conditions, &rule.Conditions); err != nil {
+			return nil, err
+		}
+	}
+	if targetGroupID.Valid {
+		rule.TargetGroupID = &targetGroupID.Int64
+	}
+	rule.FallbackGroupIDs = []int64(fallbacks)
+	if rule.FallbackGroupIDs == nil {
+		rule.FallbackGroupIDs = []int64{}
+	}
+	rule.ParamOverrides = map[string]any{}
+	if len(overrides) > 0 {
+		if err := json.Unmarshal(overrides, &rule.ParamOverrides); err != nil {
+			return nil, err
+		}
+	}
+	return &rule, nil
+}
@@ -0,0 +1,123 @@
+package repository
+
+import (
+	"context"
+	"database/sql"
+	"errors"
+	"strconv"
+	"strings"
+
+	"github.com/Wei-Shaw/sub2api/internal/service"
+)
+
+const semanticCacheEntryColumns = `id, api_key_id, request_type, model, context_hash, prompt_hash, prompt, response, hit_count, created_at, expires_at`
+
+type semanticCacheRepository struct {
+	db *sql.DB
+}
+
+// NewSemanticCacheRepository 创建语义缓存仓储（依赖 pgvector，表在扩展可用时由迁移创建）
+func NewSemanticCacheRepository(sqlDB *sql.DB) service.SemanticCacheRepository {
+	return &semanticCacheRepository{db: sqlDB}
+}
+
+func (r *semanticCacheRepository) FindExact(ctx context.Context, scope service.SemanticCacheScope, promptHash string) (*service.SemanticCacheEntry, error) {
+	entry, err := scanSemanticCacheEntry(r.db.QueryRowContext(ctx, `
+SELECT `+semanticCacheEntryColumns+`
+FROM semantic_cache_entries
+WHERE api_key_id = $1 AND request_type = $2 AND model = $3 AND context_hash = $4 AND prompt_hash = $5 AND expires_at > NOW()
+ORDER BY id DESC
+LIMIT 1
+`, scope.APIKeyID, scope.RequestType, scope.Model, scope.ContextHash, promptHash))
+	if errors.Is(err, sql.ErrNoRows) {
+		return nil, nil
+	}
+	return entry, err
+}
+
+func (r *semanticCacheRepository) FindNearest(ctx context.Context, scope service.SemanticCacheScope, embedding []float32) (*service.SemanticCacheEntry, float64, error) {
+	if len(embedding) == 0 {
+		return nil, 0, nil
+	}
+	var similarity float64
+	entry, err := scanSemanticCacheEntry(r.db.QueryRowContext(ctx, `
+SELECT `+semanticCacheEntryColumns+`, 1 - (embedding <=> $5::vector)
+FROM semantic_cache_entries
+WHERE api_key_id = $1 AND request_type = $2 AND model = $3 AND context_hash = $4 AND dimensions = $6 AND expires_at > NOW()
+ORDER BY embedding <=> $5::vector
+LIMIT 1
+`, scope.APIKeyID, scope.RequestType, scope.Model, scope.ContextHash, formatVector(embedding), len(embedding)), &similarity)
+	if errors.Is(err, sql.ErrNoRows) {
+		return nil, 0, nil
+	}
+	if err != nil {
+		return nil, 0, err
+	}
+	return entry, similarity, nil
+}
+
+func (r *semanticCacheRepository) Create(ctx context.Context, entry *service.SemanticCacheEntry) error {
+	return r.db.QueryRowContext(ctx, `
+INSERT INTO semantic_cache_entries (
+	api_key_id, request_type, model, context_hash, prompt_hash, prompt, embedding, dimensions, response, expires_at
+) VALUES ($1, $2, $3, $4, $5, $6, $7::vector, $8, $9, $10)
+RETURNING id, created_at
+`,
+		entry.APIKeyID, entry.RequestType, entry.Model, entry.ContextHash, entry.PromptHash, entry.Prompt,
+		formatVector(entry.Embedding), len(entry.Embedding), string(entry.Response), entry.ExpiresAt,
+	).Scan(&entry.ID, &entry.CreatedAt)
+}
+
+func (r *semanticCacheRepository) IncrementHit(ctx context.Context, id int64) error {
+	_, err := r.db.ExecContext(ctx, `UPDATE semantic_cache_entries SET hit_count = hit_count + 1 WHERE id = $1`, id)
+	return err
+}
+
+func (r *semanticCacheRepository) DeleteExpired(ctx context.Context, apiKeyID int64) (int64, error) {
+	res, err := r.db.ExecContext(ctx, `DELETE FROM semantic_cache_entries WHERE api_key_id = $1 AND expires_at <= NOW()`, apiKeyID)
+	if err != nil {
+		return 0, err
+	}
+	return res.RowsAffected()
+}
+
+// scanSemanticCacheEntry 扫描条目列；extra 为查询中追加的列（如相似度）
+func scanSemanticCacheEntry(row interface{ Scan(...any) error }, extra ...any) (*service.SemanticCacheEntry, error) {
+	var (
+		entry    service.SemanticCacheEntry
+		response string
+	)
+	dest := []any{
+		&entry.ID,
+		&entry.APIKeyID,
+		&entry.RequestType,
+		&entry.Model,
+		&entry.ContextHash,
+		&entry.PromptHash,
+		&entry.Prompt,
+		&response,
+		&entry.HitCount,
+		&entry.CreatedAt,
+		&entry.ExpiresAt,
+	}
+	if err := row.Scan(append(dest, extra...)...); err != nil {
+		return nil, err
+	}
+	entry.Response = []byte(response)
+	return &entry, nil
+}
+
+// formatVector 转为 pgvector 文本格式，如 [0.1,0.2]
+func formatVector(v []float32) string {
+	var sb strings.Builder
+	sb.Grow(len(v)*10 + 2)
+	sb.WriteByte('[')
+	for i, f := range v {
+		if i > 0 {
+			sb.WriteByte(',')
+		}
+		sb.WriteString(strconv.FormatFloat(float64(f), 'g', -1, 32))
+	}
+	sb.WriteByte(']')
+	return sb.String()
+}
@@ -0,0 +1,129 @@
+package repository
+
+import (
+	"context"
+	"errors"
+	"fmt"
+	"strconv"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/service"
+	"github.com/redis/go-redis/v9"
+)
+
+const (
+	spendingCapKeyPrefix       = "spending_cap:"
+	spendingCapDryRunKeyPrefix = "spending_cap_dry_run:"
+)
+
+// addSpendScript 计数存在时累加并续期，不存在时返回 false（由调用方以账本初始化）
+var addSpendScript = redis.NewScript(`
+	if redis.call('EXISTS', KEYS[1]) == 0 then
+		return false
+	end
+	local v = redis.call('INCRBYFLOAT', KEYS[1], ARGV[1])
+	redis.call('EXPIRE', KEYS[1], ARGV[2])
+	return v
+`)
+
+type spendingCapCache struct {
+	rdb *redis.Client
+}
+
+// NewSpendingCapCache 创建消费上限周期计数缓存
+func NewSpendingCapCache(rdb *redis.Client) service.SpendingCapCache {
+	return &spendingCapCache{rdb: rdb}
+}
+
+// spendingCapKey spending_cap:{scope}:{id}:{period_start_unix}
+func spendingCapKey(scopeType string, scopeID int64, periodStart time.Time) string {
+	return fmt.Sprintf("%s%s:%d:%d", spendingCapKeyPrefix, scopeType, scopeID, periodStart.Unix())
+}
+
+func (c *spendingCapCache) GetSpend(ctx context.Context, scopeType string, scopeID int64, periodStart time.Time) (float64, bool, error) {
+	v, err := c.rdb.Get(ctx, spendingCapKey(scopeType, scopeID, periodStart)).Float64()
+	if errors.Is(err, redis.Nil) {
+		return 0, false, nil
+	}
+	if err != nil {
+		return 0, false, err
+	}
+	return v, true, nil
+}
+
+func (c *spendingCapCache) InitSpend(ctx context.Context, scopeType string, scopeID int64, periodStart time.Time, value float64, ttl time.Duration) (bool, error) {
+	return c.rdb.SetNX(ctx, spendingCapKey(scopeType, scopeID, periodStart), strconv.FormatFloat(value, 'f', -1, 64), ttl).Result()
+}
+
+func (c *spendingCapCache) AddSpend(ctx context.Context, scopeType string, scopeID int64, periodStart time.Time, amount float64, ttl time.Duration) (float64, bool, error) {
+	res, err := addSpendScript.Run(ctx, c.rdb, []string{spendingCapKey(scopeType, scopeID, periodStart)},
+		strconv.FormatFloat(amount, 'f', -1, 64), int64(ttl.Seconds())).Result()
+	if errors.Is(err, redis.Nil) {
+		return 0, false, nil
+	}
+	if err != nil {
+		return 0, false, err
+	}
+	s, ok := res.(string)
+	if !ok {
+		return 0, false, fmt.Errorf("spending cap: unexpected script result %T", res)
+	}
+	v, err := strconv.ParseFloat(s, 64)
+	if err != nil {
+		return 0, false, err
+	}
+	return v, true, nil
+}
+
+// spendingCapDryRunKey spending_cap_dry_run:{scope}:{id}:{period_start_unix}，Hash 字段 count / last（毫秒时间戳）
+func spendingCapDryRunKey(scopeType string, scopeID int64, periodStart time.Time) string {
+	return fmt.Sprintf("%s%s:%d:%d", spendingCapDryRunKeyPrefix, scopeType, scopeID, periodStart.Unix())
+}
+
+func (c *spendingCapCache) IncrDryRun(ctx context.Context, scopeType string, scopeID int64, periodStart, at time.Time, ttl time.Duration) (int64, error) {
+	key := spendingCapDryRunKey(scopeType, scopeID, periodStart)
+	var count *redis.IntCmd
+	_, err := c.rdb.TxPipelined(ctx, func(pipe redis.Pipeliner) error {
+		count = pipe.HIncrBy(ctx, key, "count", 1)
+		pipe.HSet(ctx, key, "last", at.UnixMilli())
+		pipe.Expire(ctx, key, ttl)
+		return nil
+	})
+	if err != nil {
+		return 0, err
+	}
+	return count.Val(), nil
+}
+
+func (c *spendingCapCache) GetDryRun(ctx context.Context, scopeType string, scopeID int64, periodStart time.Time) (int64, time.Time, error) {
+	vals, err := c.rdb.HMGet(ctx, spendingCapDryRunKey(scopeType, scopeID, periodStart), "count", "last").Result()
+	if err != nil {
+		return 0, time.Time{}, err
+	}
+	count, _ := parseDryRunField(vals[0])
+	last, ok := parseDryRunField(vals[1])
+	if count == 0 || !ok {
+		return count, time.Time{}, nil
+	}
+	return count, time.UnixMilli(last), nil
+}
+
+func (c *spendingCapCache) ClearDryRun(ctx context.Context, scopeType string, scopeID int64, periodStarts ...time.Time) error {
+	if len(periodStarts) == 0 {
+		return nil
+	}
+	keys := make([]string, 0, len(periodStarts))
+	for _, start := range periodStarts {
+		keys = append(keys, spendingCapDryRunKey(scopeType, scopeID, start))
+	}
+	return c.rdb.Del(ctx, keys...).Err()
+}
+
+func parseDryRunField(v any) (int64, bool) {
+	s, ok := v.(string)
+	if !ok {
+		return 0, false
+	}
+	n, err := strconv.ParseInt(s, 10, 64)
+	return n, err == nil
+}
@@ -0,0 +1,180 @@
+package repository
+
+import (
+	"context"
+	"database/sql"
+	"errors"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/pkg/pagination"
+	"github.com/Wei-Shaw/sub2api/internal/service"
+)
+
+type spendingCapRepository struct {
+	db *sql.DB
+}
+
+// NewSpendingCapRepository 创建消费上限仓储
+func NewSpendingCapRepository(sqlDB *sql.DB) service.SpendingCapRepository {
+	return &spendingCapRepository{db: sqlDB}
+}
+
+const spendingCapSelectColumns = `scope_type, scope_id, period, limit_usd, dry_run, suspended_at, suspended_period_start,
+	exempt_period_start, updated_by, created_at, updated_at`
+
+func (r *spendingCapRepository) Get(ctx context.Context, scopeType string, scopeID int64) (*service.SpendingCap, error) {
+	row := r.db.QueryRowContext(ctx, `SELECT `+spendingCapSelectColumns+` FROM spending_caps WHERE scope_type = $1 AND scope_id = $2`,
+		scopeType, scopeID)
+	c, err := scanSpendingCap(row)
+	if errors.Is(err, sql.ErrNoRows) {
+		return nil, nil
+	}
+	return c, err
+}
+
+func (r *spendingCapRepository) List(ctx context.Context, scopeType string, suspendedOnly bool, params pagination.PaginationParams) ([]service.SpendingCap, *pagination.PaginationResult, error) {
+	where := `WHERE ($1 = '' OR scope_type = $1) AND (NOT $2 OR suspended_at IS NOT NULL)`
+	var total int64
+	if err := r.db.QueryRowContext(ctx, `SELECT COUNT(*) FROM spending_caps `+where, scopeType, suspendedOnly).Scan(&total); err != nil {
+		return nil, nil, err
+	}
+	rows, err := r.db.QueryContext(ctx, `SELECT `+spendingCapSelectColumns+` FROM spending_caps `+where+`
+		ORDER BY suspended_at DESC NULLS LAST, updated_at DESC
+		LIMIT $3 OFFSET $4`, scopeType, suspendedOnly, params.Limit(), params.Offset())
+	if err != nil {
+		return nil, nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	caps := make([]service.SpendingCap, 0)
+	for rows.Next() {
+		c, err := scanSpendingCap(rows)
+		if err != nil {
+			return nil, nil, err
+		}
+		caps = append(caps, *c)
+	}
+	if err := rows.Err(); err != nil {
+		return nil, nil, err
+	}
+	return caps, paginationResultFromTotal(total, params), nil
+}
+
+func (r *spendingCapRepository) Upsert(ctx context.Context, c *service.SpendingCap) error {
+	c.SuspendedAt, c.SuspendedPeriodStart, c.ExemptPeriodStart = nil, nil, nil
+	return r.db.QueryRowContext(ctx, `
+		INSERT INTO spending_caps (scope_type, scope_id, period, limit_usd, dry_run, updated_by)
+		VALUES ($1, $2, $3, $4, $5, $6)
+		ON CONFLICT (scope_type, scope_id) DO UPDATE SET
+			period = EXCLUDED.period,
+			limit_usd = EXCLUDED.limit_usd,
+			dry_run = EXCLUDED.dry_run,
+			suspended_at = NULL,
+			suspended_period_start = NULL,
+			exempt_period_start = NULL,
+			updated_by = EXCLUDED.updated_by,
+			updated_at = NOW()
+		RETURNING created_at, updated_at
+	`, c.ScopeType, c.ScopeID, c.Period, c.LimitUSD, c.DryRun, nullInt64(c.UpdatedBy)).Scan(&c.CreatedAt, &c.UpdatedAt)
+}
+
+func (r *spendingCapRepository) Delete(ctx context.Context, scopeType string, scopeID int64) error {
+	res, err := r.db.ExecContext(ctx, `DELETE FROM spending_caps WHERE scope_type = $1 AND scope_id = $2`, scopeType, scopeID)
+	if err != nil {
+		return err
+	}
+	if n, _ := res.RowsAffected(); n == 0 {
+		return service.ErrSpendingCapNotFound
+	}
+	return nil
+}
+
+func (r *spendingCapRepository) MarkSuspended(ctx context.Context, scopeType string, scopeID int64, periodStart, at time.Time) (bool, error) {
+	res, err := r.db.ExecContext(ctx, `
+		UPDATE spending_caps
+		SET suspended_at = $4, suspended_period_start = $3, updated_at = NOW()
+		WHERE scope_type = $1 AND scope_id = $2
+			AND suspended_period_start IS DISTINCT FROM $3
+			AND exempt_period_start IS DISTINCT FROM $3
+	`, scopeType, scopeID, periodStart, at)
+	if err != nil {
+		return false, err
+	}
+	n, err := res.RowsAffected()
+	return n > 0, err
+}
+
+func (r *spendingCapRepository) Lift(ctx context.Context, scopeType string, scopeID int64, periodStart time.Time) error {
+	res, err := r.db.ExecContext(ctx, `
+		UPDATE spending_caps
+		SET suspended_at = NULL, suspended_period_start = NULL, exempt_period_start = $3, updated_at = NOW()
+		WHERE scope_type = $1 AND scope_id = $2
+	`, scopeType, scopeID, periodStart)
+	if err != nil {
+		return err
+	}
+	if n, _ := res.RowsAffected(); n == 0 {
+		return service.ErrSpendingCapNotFound
+	}
+	return nil
+}
+
+func (r *spendingCapRepository) SumSpend(ctx context.Context, scopeType string, scopeID int64, since time.Time) (float64, error) {
+	var total float64
+	if scopeType == service.SpendingCapScopeTeam {
+		// 按当前成员汇总；移出团队的 Key 不再计入团队预算
+		err := r.db.QueryRowContext(ctx, `
+			SELECT COALESCE(SUM(ul.actual_cost), 0)
+			FROM usage_logs ul
+			JOIN api_key_team_members m ON m.api_key_id = ul.api_key_id
+			WHERE m.team_id = $1 AND ul.created_at >= $2
+		`, scopeID, since).Scan(&total)
+		return total, err
+	}
+	column := "api_key_id"
+	if scopeType == service.SpendingCapScopeUser {
+		column = "user_id"
+	}
+	err := r.db.QueryRowContext(ctx,
+		`SELECT COALESCE(SUM(actual_cost), 0) FROM usage_logs WHERE `+column+` = $1 AND created_at >= $2`,
+		scopeID, since).Scan(&total)
+	return total, err
+}
+
+func (r *spendingCapRepository) GetTeamID(ctx context.Context, apiKeyID int64) (int64, error) {
+	var teamID int64
+	err := r.db.QueryRowContext(ctx, `SELECT team_id FROM api_key_team_members WHERE api_key_id = $1`, apiKeyID).Scan(&teamID)
+	if errors.Is(err, sql.ErrNoRows) {
+		return 0, nil
+	}
+	return teamID, err
+}
+
+type spendingCapScanner interface {
+	Scan(dest ...any) error
+}
+
+func scanSpendingCap(s spendingCapScanner) (*service.SpendingCap, error) {
+	var (
+		c                                               service.SpendingCap
+		suspendedAt, suspendedPeriodStart, exemptPeriod sql.NullTime
+		updatedBy                                       sql.NullInt64
+	)
+	if err := s.Scan(&c.ScopeType, &c.ScopeID, &c.Period, &c.LimitUSD, &c.DryRun, &suspendedAt, &suspendedPeriodStart,
+		&exemptPeriod, &updatedBy, &c.CreatedAt, &c.UpdatedAt); err != nil {
+		return nil, err
+	}
+	c.SuspendedAt = nullTimeToPtr(suspendedAt)
+	c.SuspendedPeriodStart = nullTimeToPtr(suspendedPeriodStart)
+	c.ExemptPeriodStart = nullTimeToPtr(exemptPeriod)
+	c.UpdatedBy = nullInt64Ptr(updatedBy)
+	return &c, nil
+}
+
+func nullTimeToPtr(v sql.NullTime) *time.Time {
+	if !v.Valid {
+		return nil
+	}
+	t := v.Time
+	return &t
+}
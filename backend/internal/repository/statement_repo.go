@@ -0,0 +1,197 @@
+package repository
+
+import (
+	"context"
+	"database/sql"
+	"encoding/json"
+	"errors"
+	"fmt"
+	"strings"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/pkg/pagination"
+	"github.com/Wei-Shaw/sub2api/internal/service"
+)
+
+// statementCreditTypes 计入账单充值的兑换记录类型（余额卡密与管理员正向调整）
+var statementCreditTypes = []any{service.RedeemTypeBalance, service.AdjustmentTypeAdminBalance}
+
+type statementRepository struct {
+	db *sql.DB
+}
+
+// NewStatementRepository 创建月度账单仓储
+func NewStatementRepository(sqlDB *sql.DB) service.StatementRepository {
+	return &statementRepository{db: sqlDB}
+}
+
+const statementColumns = `id, user_id, period_start, period_end, request_count, total_cost, actual_cost,
+	credits_applied, object_keys, generated_at`
+
+func scanStatement(row interface{ Scan(...any) error }) (*service.Statement, error) {
+	var (
+		st   service.Statement
+		keys []byte
+	)
+	if err := row.Scan(&st.ID, &st.UserID, &st.PeriodStart, &st.PeriodEnd, &st.RequestCount, &st.TotalCost, &st.ActualCost,
+		&st.CreditsApplied, &keys, &st.GeneratedAt); err != nil {
+		return nil, err
+	}
+	if len(keys) > 0 {
+		if err := json.Unmarshal(keys, &st.ObjectKeys); err != nil {
+			return nil, fmt.Errorf("decode statement object keys: %w", err)
+		}
+	}
+	return &st, nil
+}
+
+func (r *statementRepository) ListActiveUserIDs(ctx context.Context, start, end time.Time) ([]int64, error) {
+	rows, err := r.db.QueryContext(ctx, `
+		SELECT user_id FROM usage_logs
+		WHERE created_at >= $1 AND created_at < $2
+		UNION
+		SELECT used_by FROM redeem_codes
+		WHERE used_by IS NOT NULL AND status = $3 AND type IN ($4, $5) AND value > 0
+			AND used_at >= $1 AND used_at < $2
+		ORDER BY 1
+	`, start, end, service.StatusUsed, statementCreditTypes[0], statementCreditTypes[1])
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	ids := make([]int64, 0)
+	for rows.Next() {
+		var id int64
+		if err := rows.Scan(&id); err != nil {
+			return nil, err
+		}
+		ids = append(ids, id)
+	}
+	return ids, rows.Err()
+}
+
+func (r *statementRepository) UsageByModel(ctx context.Context, userID int64, start, end time.Time) ([]service.StatementModelLine, error) {
+	rows, err := r.db.QueryContext(ctx, `
+		SELECT model, COUNT(*),
+			COALESCE(SUM(input_tokens), 0), COALESCE(SUM(output_tokens), 0),
+			COALESCE(SUM(cache_creation_tokens), 0), COALESCE(SUM(cache_read_tokens), 0),
+			COALESCE(SUM(total_cost), 0), COALESCE(SUM(actual_cost), 0)
+		FROM usage_logs
+		WHERE user_id = $1 AND created_at >= $2 AND created_at < $3
+		GROUP BY model
+		ORDER BY SUM(actual_cost) DESC, model
+	`, userID, start, end)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	lines := make([]service.StatementModelLine, 0)
+	for rows.Next() {
+		var l service.StatementModelLine
+		if err := rows.Scan(&l.Model, &l.Requests, &l.InputTokens, &l.OutputTokens,
+			&l.CacheCreationTokens, &l.CacheReadTokens, &l.TotalCost, &l.ActualCost); err != nil {
+			return nil, err
+		}
+		lines = append(lines, l)
+	}
+	return lines, rows.Err()
+}
+
+func (r *statementRepository) Credits(ctx context.Context, userID int64, start, end time.Time) ([]service.StatementCredit, error) {
+	rows, err := r.db.QueryContext(ctx, `
+		SELECT type, value, used_at
+		FROM redeem_codes
+		WHERE used_by = $1 AND status = $2 AND type IN ($3, $4) AND value > 0
+			AND used_at >= $5 AND used_at < $6
+		ORDER BY used_at, id
+	`, userID, service.StatusUsed, statementCreditTypes[0], statementCreditTypes[1], start, end)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	credits := make([]service.StatementCredit, 0)
+	for rows.Next() {
+		var c service.StatementCredit
+		if err := rows.Scan(&c.Type, &c.Amount, &c.AppliedAt); err != nil {
+			return nil, err
+		}
+		credits = append(credits, c)
+	}
+	return credits, rows.Err()
+}
+
+func (r *statementRepository) Upsert(ctx context.Context, st *service.Statement) error {
+	keys, err := json.Marshal(st.ObjectKeys)
+	if err != nil {
+		return err
+	}
+	return r.db.QueryRowContext(ctx, `
+		INSERT INTO billing_statements (user_id, period_start, period_end, request_count, total_cost, actual_cost,
+			credits_applied, object_keys, generated_at)
+		VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+		ON CONFLICT (user_id, period_start) DO UPDATE SET
+			period_end = EXCLUDED.period_end,
+			request_count = EXCLUDED.request_count,
+			total_cost = EXCLUDED.total_cost,
+			actual_cost = EXCLUDED.actual_cost,
+			credits_applied = EXCLUDED.credits_applied,
+			object_keys = EXCLUDED.object_keys,
+			generated_at = EXCLUDED.generated_at
+		RETURNING id
+	`, st.UserID, st.PeriodStart, st.PeriodEnd, st.RequestCount, st.TotalCost, st.ActualCost,
+		st.CreditsApplied, keys, st.GeneratedAt).Scan(&st.ID)
+}
+
+func (r *statementRepository) GetByID(ctx context.Context, id int64) (*service.Statement, error) {
+	st, err := scanStatement(r.db.QueryRowContext(ctx,
+		`SELECT `+statementColumns+` FROM billing_statements WHERE id = $1`, id))
+	if errors.Is(err, sql.ErrNoRows) {
+		return nil, service.ErrStatementNotFound
+	}
+	return st, err
+}
+
+func (r *statementRepository) List(ctx context.Context, userID int64, periodStart *time.Time, params pagination.PaginationParams) ([]service.Statement, *pagination.PaginationResult, error) {
+	conds := make([]string, 0, 2)
+	args := make([]any, 0, 4)
+	if userID > 0 {
+		args = append(args, userID)
+		conds = append(conds, fmt.Sprintf("user_id = $%d", len(args)))
+	}
+	if periodStart != nil {
+		args = append(args, *periodStart)
+		conds = append(conds, fmt.Sprintf("period_start = $%d", len(args)))
+	}
+	where := ""
+	if len(conds) > 0 {
+		where = " WHERE " + strings.Join(conds, " AND ")
+	}
+
+	var total int64
+	if err := scanSingleRow(ctx, r.db, `SELECT COUNT(*) FROM billing_statements`+where, args, &total); err != nil {
+		return nil, nil, err
+	}
+	args = append(args, params.Limit(), params.Offset())
+	rows, err := r.db.QueryContext(ctx, `SELECT `+statementColumns+` FROM billing_statements`+where+
+		fmt.Sprintf(" ORDER BY period_start DESC, user_id LIMIT $%d OFFSET $%d", len(args)-1, len(args)), args...)
+	if err != nil {
+		return nil, nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	items := make([]service.Statement, 0)
+	for rows.Next() {
+		st, err := scanStatement(rows)
+		if err != nil {
+			return nil, nil, err
+		}
+		items = append(items, *st)
+	}
+	if err := rows.Err(); err != nil {
+		return nil, nil, err
+	}
+	return items, paginationResultFromTotal(total, params), nil
+}
@@ -0,0 +1,94 @@
+package repository
+
+import (
+	"context"
+	"encoding/json"
+	"fmt"
+	"io"
+	"net/http"
+	"net/url"
+	"strconv"
+	"strings"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/httpclient"
+	"github.com/Wei-Shaw/sub2api/internal/service"
+)
+
+type stripeClient struct {
+	httpClient *http.Client
+	apiBase    string
+	secretKey  string
+}
+
+// NewStripeClient 创建 Stripe API 客户端（仅使用 Checkout Session 接口）
+func NewStripeClient(cfg *config.Config) service.StripeClient {
+	sharedClient, err := httpclient.GetClient(httpclient.Options{
+		Timeout:            15 * time.Second,
+		ValidateResolvedIP: true,
+	})
+	if err != nil {
+		sharedClient = &http.Client{Timeout: 15 * time.Second}
+	}
+	return &stripeClient{
+		httpClient: sharedClient,
+		apiBase:    strings.TrimRight(cfg.Payments.Stripe.APIBase, "/"),
+		secretKey:  cfg.Payments.Stripe.SecretKey,
+	}
+}
+
+func (c *stripeClient) CreateCheckoutSession(ctx context.Context, params service.StripeCheckoutParams) (*service.StripeCheckoutSession, error) {
+	userID := strconv.FormatInt(params.UserID, 10)
+	form := url.Values{}
+	form.Set("mode", "payment")
+	form.Set("success_url", params.SuccessURL)
+	form.Set("cancel_url", params.CancelURL)
+	form.Set("client_reference_id", userID)
+	form.Set("metadata[user_id]", userID)
+	form.Set("payment_intent_data[metadata][user_id]", userID)
+	if params.Email != "" {
+		form.Set("customer_email", params.Email)
+	}
+	form.Set("line_items[0][quantity]", "1")
+	form.Set("line_items[0][price_data][currency]", params.Currency)
+	form.Set("line_items[0][price_data][unit_amount]", strconv.FormatInt(params.AmountMinor, 10))
+	form.Set("line_items[0][price_data][product_data][name]", params.ProductName)
+
+	req, err := http.NewRequestWithContext(ctx, http.MethodPost, c.apiBase+"/v1/checkout/sessions", strings.NewReader(form.Encode()))
+	if err != nil {
+		return nil, fmt.Errorf("create request: %w", err)
+	}
+	req.SetBasicAuth(c.secretKey, "")
+	req.Header.Set("Content-Type", "application/x-www-form-urlencoded")
+
+	resp, err := c.httpClient.Do(req)
+	if err != nil {
+		return nil, fmt.Errorf("send request: %w", err)
+	}
+	defer func() { _ = resp.Body.Close() }()
+
+	body, err := io.ReadAll(io.LimitReader(resp.Body, 1<<20))
+	if err != nil {
+		return nil, fmt.Errorf("read response: %w", err)
+	}
+	if resp.StatusCode != http.StatusOK {
+		var stripeErr struct {
+			Error struct {
+				Type    string `json:"type"`
+				Message string `json:"message"`
+			} `json:"error"`
+		}
+		_ = json.Unmarshal(body, &stripeErr)
+		return nil, fmt.Errorf("stripe returned %d: %s %s", resp.StatusCode, stripeErr.Error.Type, stripeErr.Error.Message)
+	}
+
+	var session service.StripeCheckoutSession
+	if err := json.Unmarshal(body, &session); err != nil {
+		return nil, fmt.Errorf("decode response: %w", err)
+	}
+	if session.ID == "" || session.URL == "" {
+		return nil, fmt.Errorf("stripe returned an incomplete checkout session")
+	}
+	return &session, nil
+}
@@ -0,0 +1,65 @@
+package repository
+
+import (
+	"context"
+	"net/http"
+	"net/url"
+	"testing"
+
+	"github.com/Wei-Shaw/sub2api/internal/service"
+	"github.com/stretchr/testify/require"
+)
+
+func newStripeClientForTest(handler http.HandlerFunc, capture func(r *http.Request, body []byte)) *stripeClient {
+	return &stripeClient{
+		httpClient: &http.Client{Transport: newInProcessTransport(handler, capture)},
+		apiBase:    "http://in-process/stripe",
+		secretKey:  "sk_test_123",
+	}
+}
+
+func TestStripeClient_CreateCheckoutSession(t *testing.T) {
+	var (
+		gotPath string
+		gotUser string
+		form    url.Values
+	)
+	client := newStripeClientForTest(func(w http.ResponseWriter, r *http.Request) {
+		w.Header().Set("Content-Type", "application/json")
+		_, _ = w.Write([]byte(`{"id":"cs_test_1","url":"https://checkout.stripe.com/c/pay/cs_test_1","object":"checkout.session"}`))
+	}, func(r *http.Request, body []byte) {
+		gotPath = r.URL.Path
+		gotUser, _, _ = r.BasicAuth()
+		form, _ = url.ParseQuery(string(body))
+	})
+
+	session, err := client.CreateCheckoutSession(context.Background(), service.StripeCheckoutParams{
+		UserID:      42,
+		Email:       "user@example.com",
+		Currency:    "usd",
+		AmountMinor: 2500,
+		ProductName: "Account credit (25.00 USD)",
+		SuccessURL:  "https://example.com/ok",
+		CancelURL:   "https://example.com/cancel",
+	})
+	require.NoError(t, err)
+	require.Equal(t, "cs_test_1", session.ID)
+	require.Equal(t, "/stripe/v1/checkout/sessions", gotPath)
+	require.Equal(t, "sk_test_123", gotUser)
+	require.Equal(t, "payment", form.Get("mode"))
+	require.Equal(t, "42", form.Get("client_reference_id"))
+	require.Equal(t, "42", form.Get("metadata[user_id]"))
+	require.Equal(t, "2500", form.Get("line_items[0][price_data][unit_amount]"))
+	require.Equal(t, "usd", form.Get("line_items[0][price_data][currency]"))
+}
+
+func TestStripeClient_CreateCheckoutSessionError(t *testing.T) {
+	client := newStripeClientForTest(func(w http.ResponseWriter, r *http.Request) {
+		w.WriteHeader(http.StatusBadRequest)
+		_, _ = w.Write([]byte(`{"error":{"type":"invalid_request_error","message":"Invalid currency"}}`))
+	}, nil)
+
+	_, err := client.CreateCheckoutSession(context.Background(), service.StripeCheckoutParams{UserID: 1, Currency: "xxx", AmountMinor: 100})
+	require.Error(t, err)
+	require.Contains(t, err.Error(), "Invalid currency")
+}
@@ -0,0 +1,180 @@
+package repository
+
+import (
+	"context"
+	"database/sql"
+	"errors"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/pkg/pagination"
+	"github.com/Wei-Shaw/sub2api/internal/service"
+)
+
+type teamRepository struct {
+	db *sql.DB
+}
+
+// NewTeamRepository 创建团队仓储
+func NewTeamRepository(sqlDB *sql.DB) service.TeamRepository {
+	return &teamRepository{db: sqlDB}
+}
+
+func (r *teamRepository) Create(ctx context.Context, team *service.Team) error {
+	return r.db.QueryRowContext(ctx, `
+		INSERT INTO api_key_teams (user_id, name, period)
+		VALUES ($1, $2, $3)
+		RETURNING id, created_at, updated_at
+	`, team.UserID, team.Name, team.Period).Scan(&team.ID, &team.CreatedAt, &team.UpdatedAt)
+}
+
+func (r *teamRepository) GetByID(ctx context.Context, id int64) (*service.Team, error) {
+	var team service.Team
+	err := scanSingleRow(ctx, r.db, `
+		SELECT id, user_id, name, period, created_at, updated_at FROM api_key_teams WHERE id = $1
+	`, []any{id}, &team.ID, &team.UserID, &team.Name, &team.Period, &team.CreatedAt, &team.UpdatedAt)
+	if errors.Is(err, sql.ErrNoRows) {
+		return nil, service.ErrTeamNotFound
+	}
+	if err != nil {
+		return nil, err
+	}
+	return &team, nil
+}
+
+func (r *teamRepository) List(ctx context.Context, userID int64, params pagination.PaginationParams) ([]service.Team, *pagination.PaginationResult, error) {
+	where := `WHERE ($1 = 0 OR user_id = $1)`
+	var total int64
+	if err := r.db.QueryRowContext(ctx, `SELECT COUNT(*) FROM api_key_teams `+where, userID).Scan(&total); err != nil {
+		return nil, nil, err
+	}
+	rows, err := r.db.QueryContext(ctx, `
+		SELECT id, user_id, name, period, created_at, updated_at FROM api_key_teams `+where+`
+		ORDER BY id DESC
+		LIMIT $2 OFFSET $3`, userID, params.Limit(), params.Offset())
+	if err != nil {
+		return nil, nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	teams := make([]service.Team, 0)
+	for rows.Next() {
+		var team service.Team
+		if err := rows.Scan(&team.ID, &team.UserID, &team.Name, &team.Period, &team.CreatedAt, &team.UpdatedAt); err != nil {
+			return nil, nil, err
+		}
+		teams = append(teams, team)
+	}
+	if err := rows.Err(); err != nil {
+		return nil, nil, err
+	}
+	return teams, paginationResultFromTotal(total, params), nil
+}
+
+func (r *teamRepository) Update(ctx context.Context, team *service.Team) error {
+	err := r.db.QueryRowContext(ctx, `
+		UPDATE api_key_teams SET name = $2, period = $3, updated_at = NOW()
+		WHERE id = $1
+		RETURNING updated_at
+	`, team.ID, team.Name, team.Period).Scan(&team.UpdatedAt)
+	if errors.Is(err, sql.ErrNoRows) {
+		return service.ErrTeamNotFound
+	}
+	return err
+}
+
+func (r *teamRepository) Delete(ctx context.Context, id int64) error {
+	res, err := r.db.ExecContext(ctx, `DELETE FROM api_key_teams WHERE id = $1`, id)
+	if err != nil {
+		return err
+	}
+	if n, _ := res.RowsAffected(); n == 0 {
+		return service.ErrTeamNotFound
+	}
+	return nil
+}
+
+func (r *teamRepository) ListMembers(ctx context.Context, teamID int64) ([]service.TeamMember, error) {
+	rows, err := r.db.QueryContext(ctx, `
+		SELECT m.api_key_id, k.name, m.member_name, m.created_at
+		FROM api_key_team_members m
+		JOIN api_keys k ON k.id = m.api_key_id
+		WHERE m.team_id = $1 AND k.deleted_at IS NULL
+		ORDER BY m.created_at, m.api_key_id
+	`, teamID)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	members := make([]service.TeamMember, 0)
+	for rows.Next() {
+		var m service.TeamMember
+		if err := rows.Scan(&m.APIKeyID, &m.APIKeyName, &m.MemberName, &m.JoinedAt); err != nil {
+			return nil, err
+		}
+		members = append(members, m)
+	}
+	if err := rows.Err(); err != nil {
+		return nil, err
+	}
+	return members, nil
+}
+
+func (r *teamRepository) UpsertMember(ctx context.Context, teamID, apiKeyID int64, memberName string) error {
+	// 仅当 Key 未加入团队或已在本团队时写入；属于其他团队时不更新任何行
+	res, err := r.db.ExecContext(ctx, `
+		INSERT INTO api_key_team_members (api_key_id, team_id, member_name)
+		VALUES ($1, $2, $3)
+		ON CONFLICT (api_key_id) DO UPDATE SET member_name = EXCLUDED.member_name
+		WHERE api_key_team_members.team_id = EXCLUDED.team_id
+	`, apiKeyID, teamID, memberName)
+	if err != nil {
+		return err
+	}
+	if n, _ := res.RowsAffected(); n == 0 {
+		return service.ErrTeamKeyInOtherTeam
+	}
+	return nil
+}
+
+func (r *teamRepository) RemoveMember(ctx context.Context, teamID, apiKeyID int64) error {
+	res, err := r.db.ExecContext(ctx, `DELETE FROM api_key_team_members WHERE team_id = $1 AND api_key_id = $2`, teamID, apiKeyID)
+	if err != nil {
+		return err
+	}
+	if n, _ := res.RowsAffected(); n == 0 {
+		return service.ErrTeamMemberNotFound
+	}
+	return nil
+}
+
+func (r *teamRepository) UsageByMember(ctx context.Context, teamID int64, start, end time.Time) ([]service.TeamUsageLine, error) {
+	rows, err := r.db.QueryContext(ctx, `
+		SELECT m.api_key_id, k.name, m.member_name,
+			COUNT(ul.id), COALESCE(SUM(ul.input_tokens), 0), COALESCE(SUM(ul.output_tokens), 0),
+			COALESCE(SUM(ul.actual_cost), 0)
+		FROM api_key_team_members m
+		JOIN api_keys k ON k.id = m.api_key_id
+		LEFT JOIN usage_logs ul ON ul.api_key_id = m.api_key_id AND ul.created_at >= $2 AND ul.created_at < $3
+		WHERE m.team_id = $1
+		GROUP BY m.api_key_id, k.name, m.member_name
+		ORDER BY COALESCE(SUM(ul.actual_cost), 0) DESC, m.api_key_id
+	`, teamID, start, end)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	lines := make([]service.TeamUsageLine, 0)
+	for rows.Next() {
+		var l service.TeamUsageLine
+		if err := rows.Scan(&l.APIKeyID, &l.APIKeyName, &l.MemberName, &l.Requests, &l.InputTokens, &l.OutputTokens, &l.ActualCost); err != nil {
+			return nil, err
+		}
+		lines = append(lines, l)
+	}
+	if err := rows.Err(); err != nil {
+		return nil, err
+	}
+	return lines, nil
+}
@@ -0,0 +1,45 @@
+package repository
+
+import (
+	"context"
+	"errors"
+	"strconv"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/service"
+	"github.com/redis/go-redis/v9"
+)
+
+const (
+	telegramAlertKeyPrefix = "telegram:alert:"
+	telegramOffsetKey      = "telegram:update_offset"
+)
+
+type telegramStateCache struct {
+	rdb *redis.Client
+}
+
+// NewTelegramStateCache 创建 Telegram 机器人状态缓存（告警冷却与消息 offset）
+func NewTelegramStateCache(rdb *redis.Client) service.TelegramStateCache {
+	return &telegramStateCache{rdb: rdb}
+}
+
+func (c *telegramStateCache) MarkAlerted(ctx context.Context, key string, cooldown time.Duration) (bool, error) {
+	return c.rdb.SetNX(ctx, telegramAlertKeyPrefix+key, 1, cooldown).Result()
+}
+
+func (c *telegramStateCache) GetUpdateOffset(ctx context.Context) (int64, error) {
+	val, err := c.rdb.Get(ctx, telegramOffsetKey).Result()
+	if errors.Is(err, redis.Nil) {
+		return 0, nil
+	}
+	if err != nil {
+		return 0, err
+	}
+	return strconv.ParseInt(val, 10, 64)
+}
+
+func (c *telegramStateCache) SetUpdateOffset(ctx context.Context, offset int64) error {
+	// Telegram 只保留 24 小时内未确认的消息，offset 无需长期保存
+	return c.rdb.Set(ctx, telegramOffsetKey, offset, 7*24*time.Hour).Err()
+}
@@ -0,0 +1,135 @@
+package repository
+
+import (
+	"bytes"
+	"context"
+	"encoding/json"
+	"errors"
+	"fmt"
+	"io"
+	"net/http"
+	"net/url"
+	"strings"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/httpclient"
+	"github.com/Wei-Shaw/sub2api/internal/service"
+)
+
+// telegramHTTPTimeout 需大于 getUpdates 长轮询上限（50 秒）
+const telegramHTTPTimeout = 75 * time.Second
+
+type telegramClient struct {
+	httpClient *http.Client
+	apiBase    string
+	botToken   string
+}
+
+// NewTelegramClient 创建 Telegram Bot API 客户端
+func NewTelegramClient(cfg *config.Config) service.TelegramClient {
+	sharedClient, err := httpclient.GetClient(httpclient.Options{Timeout: telegramHTTPTimeout})
+	if err != nil {
+		sharedClient = &http.Client{Timeout: telegramHTTPTimeout}
+	}
+	return &telegramClient{
+		httpClient: sharedClient,
+		apiBase:    strings.TrimRight(cfg.Notifications.Telegram.APIBase, "/"),
+		botToken:   cfg.Notifications.Telegram.BotToken,
+	}
+}
+
+type telegramResponse struct {
+	OK          bool            `json:"ok"`
+	Result      json.RawMessage `json:"result"`
+	ErrorCode   int             `json:"error_code"`
+	Description string          `json:"description"`
+}
+
+func (c *telegramClient) GetUpdates(ctx context.Context, offset int64, timeout time.Duration) ([]service.TelegramUpdate, error) {
+	var raw []struct {
+		UpdateID int64 `json:"update_id"`
+		Message  *struct {
+			Text string `json:"text"`
+			Chat struct {
+				ID int64 `json:"id"`
+			} `json:"chat"`
+			From *struct {
+				Username string `json:"username"`
+			} `json:"from"`
+		} `json:"message"`
+	}
+	err := c.call(ctx, "getUpdates", map[string]any{
+		"offset":          offset,
+		"timeout":         int(timeout / time.Second),
+		"allowed_updates": []string{"message"},
+	}, &raw)
+	if err != nil {
+		return nil, err
+	}
+
+	updates := make([]service.TelegramUpdate, 0, len(raw))
+	for _, u := range raw {
+		update := service.TelegramUpdate{UpdateID: u.UpdateID}
+		if u.Message != nil {
+			update.ChatID = u.Message.Chat.ID
+			update.Text = u.Message.Text
+			if u.Message.From != nil {
+				update.From = u.Message.From.Username
+			}
+		}
+		updates = append(updates, update)
+	}
+	return updates, nil
+}
+
+func (c *telegramClient) SendMessage(ctx context.Context, chatID int64, text string) error {
+	return c.call(ctx, "sendMessage", map[string]any{
+		"chat_id":                  chatID,
+		"text":                     text,
+		"parse_mode":               "HTML",
+		"disable_web_page_preview": true,
+	}, nil)
+}
+
+func (c *telegramClient) call(ctx context.Context, method string, params map[string]any, result any) error {
+	payload, err := json.Marshal(params)
+	if err != nil {
+		return fmt.Errorf("encode request: %w", err)
+	}
+	req, err := http.NewRequestWithContext(ctx, http.MethodPost, c.apiBase+"/bot"+c.botToken+"/"+method, bytes.NewReader(payload))
+	if err != nil {
+		return fmt.Errorf("create request: %w", err)
+	}
+	req.Header.Set("Content-Type", "application/json")
+
+	resp, err := c.httpClient.Do(req)
+	if err != nil {
+		// url.Error 会带上含 Bot Token 的完整 URL，不能写入日志
+		var urlErr *url.Error
+		if errors.As(err, &urlErr) {
+			err = urlErr.Err
+		}
+		return fmt.Errorf("telegram %s: %w", method, err)
+	}
+	defer func() { _ = resp.Body.Close() }()
+
+	body, err := io.ReadAll(io.LimitReader(resp.Body, 4<<20))
+	if err != nil {
+		return fmt.Errorf("read response: %w", err)
+	}
+	var parsed telegramResponse
+	if err := json.Unmarshal(body, &parsed); err != nil {
+		return fmt.Errorf("telegram %s returned %d: invalid response", method, resp.StatusCode)
+	}
+	if !parsed.OK {
+		return fmt.Errorf("telegram %s returned %d: %s", method, parsed.ErrorCode, parsed.Description)
+	}
+	if result == nil {
+		return nil
+	}
+	if err := json.Unmarshal(parsed.Result, result); err != nil {
+		return fmt.Errorf("decode %s result: %w", method, err)
+	}
+	return nil
+}
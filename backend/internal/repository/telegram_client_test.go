@@ -0,0 +1,59 @@
+package repository
+
+import (
+	"context"
+	"encoding/json"
+	"net/http"
+	"testing"
+	"time"
+
+	"github.com/stretchr/testify/require"
+)
+
+func newTelegramClientForTest(handler http.HandlerFunc, capture func(r *http.Request, body []byte)) *telegramClient {
+	return &telegramClient{
+		httpClient: &http.Client{Transport: newInProcessTransport(handler, capture)},
+		apiBase:    "http://in-process",
+		botToken:   "123:abc",
+	}
+}
+
+func TestTelegramClient_GetUpdates(t *testing.T) {
+	var (
+		gotPath string
+		params  map[string]any
+	)
+	client := newTelegramClientForTest(func(w http.ResponseWriter, r *http.Request) {
+		_, _ = w.Write([]byte(`{"ok":true,"result":[
+			{"update_id":7,"message":{"text":"/status","chat":{"id":-100},"from":{"username":"ops"}}},
+			{"update_id":8,"edited_message":{"text":"ignored"}}
+		]}`))
+	}, func(r *http.Request, body []byte) {
+		gotPath = r.URL.Path
+		_ = json.Unmarshal(body, &params)
+	})
+
+	updates, err := client.GetUpdates(context.Background(), 7, 25*time.Second)
+	require.NoError(t, err)
+	require.Equal(t, "/bot123:abc/getUpdates", gotPath)
+	require.EqualValues(t, 7, params["offset"])
+	require.EqualValues(t, 25, params["timeout"])
+	require.Len(t, updates, 2)
+	require.Equal(t, int64(-100), updates[0].ChatID)
+	require.Equal(t, "ops", updates[0].From)
+	require.Equal(t, "/status", updates[0].Text)
+	require.Equal(t, int64(8), updates[1].UpdateID)
+	require.Empty(t, updates[1].Text)
+}
+
+func TestTelegramClient_SendMessageError(t *testing.T) {
+	client := newTelegramClientForTest(func(w http.ResponseWriter, r *http.Request) {
+		w.WriteHeader(http.StatusForbidden)
+		_, _ = w.Write([]byte(`{"ok":false,"error_code":403,"description":"Forbidden: bot was kicked from the group chat"}`))
+	}, nil)
+
+	err := client.SendMessage(context.Background(), -100, "<b>hi</b>")
+	require.Error(t, err)
+	require.Contains(t, err.Error(), "bot was kicked")
+	require.NotContains(t, err.Error(), "123:abc")
+}
@@ -0,0 +1,77 @@
+package repository
+
+import (
+	"context"
+	"database/sql"
+	"fmt"
+
+	"github.com/Wei-Shaw/sub2api/internal/service"
+)
+
+type totpBackupCodeRepository struct {
+	db *sql.DB
+}
+
+// NewTotpBackupCodeRepository 创建 TOTP 备用码仓储
+func NewTotpBackupCodeRepository(sqlDB *sql.DB) service.TotpBackupCodeRepository {
+	return &totpBackupCodeRepository{db: sqlDB}
+}
+
+// ReplaceCodes 删除用户旧的备用码并写入新的备用码（事务内完成）
+func (r *totpBackupCodeRepository) ReplaceCodes(ctx context.Context, userID int64, codeHashes []string) (err error) {
+	tx, err := r.db.BeginTx(ctx, nil)
+	if err != nil {
+		return fmt.Errorf("begin tx: %w", err)
+	}
+	defer func() {
+		if err != nil {
+			_ = tx.Rollback()
+		}
+	}()
+
+	if _, err = tx.ExecContext(ctx, `DELETE FROM user_totp_backup_codes WHERE user_id = $1`, userID); err != nil {
+		return err
+	}
+	for _, hash := range codeHashes {
+		if _, err = tx.ExecContext(ctx,
+			`INSERT INTO user_totp_backup_codes (user_id, code_hash, created_at) VALUES ($1, $2, NOW())`,
+			userID, hash); err != nil {
+			return err
+		}
+	}
+	return tx.Commit()
+}
+
+// ConsumeCode 标记备用码为已使用，返回是否成功消费（未使用且存在）
+func (r *totpBackupCodeRepository) ConsumeCode(ctx context.Context, userID int64, codeHash string) (bool, error) {
+	res, err := r.db.ExecContext(ctx, `
+		UPDATE user_totp_backup_codes SET used_at = NOW()
+		WHERE user_id = $1 AND code_hash = $2 AND used_at IS NULL
+	`, userID, codeHash)
+	if err != nil {
+		return false, err
+	}
+	affected, err := res.RowsAffected()
+	if err != nil {
+		return false, err
+	}
+	return affected > 0, nil
+}
+
+// CountRemaining 统计用户剩余可用的备用码数量
+func (r *totpBackupCodeRepository) CountRemaining(ctx context.Context, userID int64) (int, error) {
+	var count int
+	err := scanSingleRow(ctx, r.db,
+		`SELECT COUNT(*) FROM user_totp_backup_codes WHERE user_id = $1 AND used_at IS NULL`,
+		[]any{userID}, &count)
+	if err != nil {
+		return 0, err
+	}
+	return count, nil
+}
+
+// DeleteByUserID 删除用户的全部备用码（关闭 2FA 时调用）
+func (r *totpBackupCodeRepository) DeleteByUserID(ctx context.Context, userID int64) error {
+	_, err := r.db.ExecContext(ctx, `DELETE FROM user_totp_backup_codes WHERE user_id = $1`, userID)
+	return err
+}
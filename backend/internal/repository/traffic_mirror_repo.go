@@ -0,0 +1,351 @@
+package repository
+
+import (
+	"context"
+	"database/sql"
+	"errors"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/service"
+)
+
+const trafficMirrorRuleColumns = `id, name, enabled, group_id, model_pattern, sample_rate, target_account_id, target_model, created_at, updated_at`
+
+const trafficMirrorSampleSummaryColumns = `id, rule_id, request_type, api_key_id, group_id, model, primary_account_id, primary_latency_ms,
+	primary_truncated, primary_stop_reason, primary_output_tokens, mirror_account_id, mirror_model, mirror_status_code, mirror_latency_ms,
+	mirror_truncated, mirror_error, mirror_stop_reason, mirror_output_tokens, output_match, created_at`
+
+type trafficMirrorRepository struct {
+	db *sql.DB
+}
+
+// NewTrafficMirrorRepository 创建流量镜像规则与样本仓储
+func NewTrafficMirrorRepository(sqlDB *sql.DB) service.TrafficMirrorRepository {
+	return &trafficMirrorRepository{db: sqlDB}
+}
+
+func (r *trafficMirrorRepository) ListRules(ctx context.Context) ([]*service.TrafficMirrorRule, error) {
+	rows, err := r.db.QueryContext(ctx, `SELECT `+trafficMirrorRuleColumns+` FROM traffic_mirror_rules ORDER BY id`)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	out := make([]*service.TrafficMirrorRule, 0)
+	for rows.Next() {
+		rule, err := scanTrafficMirrorRule(rows)
+		if err != nil {
+			return nil, err
+		}
+		out = append(out, rule)
+	}
+	if err := rows.Err(); err != nil {
+		return nil, err
+	}
+	return out, nil
+}
+
+func (r *trafficMirrorRepository) GetRule(ctx context.Context, id int64) (*service.TrafficMirrorRule, error) {
+	rule, err := scanTrafficMirrorRule(r.db.QueryRowContext(ctx, `SELECT `+trafficMirrorRuleColumns+` FROM traffic_mirror_rules WHERE id = $1`, id))
+	if errors.Is(err, sql.ErrNoRows) {
+		return nil, service.ErrTrafficMirrorRuleNotFound
+	}
+	return rule, err
+}
+
+func (r *trafficMirrorRepository) CreateRule(ctx context.Context, rule *service.TrafficMirrorRule) error {
+	return r.db.QueryRowContext(ctx, `
+INSERT INTO traffic_mirror_rules (name, enabled, group_id, model_pattern, sample_rate, target_account_id, target_model)
+VALUES ($1, $2, $3, $4, $5, $6, $7)
+RETURNING id, created_at, updated_at
+`, rule.Name, rule.Enabled, rule.GroupID, rule.ModelPattern, rule.SampleRate, rule.TargetAccountID, rule.TargetModel,
+	).Scan(&rule.ID, &rule.CreatedAt, &rule.UpdatedAt)
+}
+
+func (r *trafficMirrorRepository) UpdateRule(ctx context.Context, rule *service.TrafficMirrorRule) error {
+	err := r.db.QueryRowContext(ctx, `
+UPDATE traffic_mirror_rules SET
+	name = $2,
+	enabled = $3,
+	group_id = $4,
+	model_pattern = $5,
+	sample_rate = $6,
+	target_account_id = $7,
+	target_model = $8,
+	updated_at = NOW()
+WHERE id = $1
+RETURNING created_at, updated_at
+`, rule.ID, rule.Name, rule.Enabled, rule.GroupID, rule.ModelPattern, rule.SampleRate, rule.TargetAccountID, rule.TargetModel,
+	).Scan(&rule.CreatedAt, &rule.UpdatedAt)
+	if errors.Is(err, sql.ErrNoRows) {
+		return service.ErrTrafficMirrorRuleNotFound
+	}
+	return err
+}
+
+func (r *trafficMirrorRepository) DeleteRule(ctx context.Context, id int64) error {
+	res, err := r.db.ExecContext(ctx, `DELETE FROM traffic_mirror_rules WHERE id = $1`, id)
+	if err != nil {
+		return err
+	}
+	if n, err := res.RowsAffected(); err == nil && n == 0 {
+		return service.ErrTrafficMirrorRuleNotFound
+	}
+	return nil
+}
+
+func (r *trafficMirrorRepository) CreateSample(ctx context.Context, sample *service.TrafficMirrorSample) error {
+	return r.db.QueryRowContext(ctx, `
+INSERT INTO traffic_mirror_samples (
+	rule_id, request_type, api_key_id, group_id, model,
+	primary_account_id, primary_latency_ms, primary_response, primary_truncated, primary_stop_reason, primary_output_tokens,
+	mirror_account_id, mirror_model, mirror_status_code, mirror_latency_ms, mirror_response, mirror_truncated, mirror_error,
+	mirror_stop_reason, mirror_output_tokens, output_match
+) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20, $21)
+RETURNING id, created_at
+`,
+		sample.RuleID, sample.RequestType, sample.APIKeyID, sample.GroupID, sample.Model,
+		sample.PrimaryAccountID, sample.PrimaryLatencyMs, sample.PrimaryResponse, sample.PrimaryTruncated, sample.PrimaryStopReason, sample.PrimaryOutputTokens,
+		sample.MirrorAccountID, sample.MirrorModel, sample.MirrorStatusCode, sample.MirrorLatencyMs, sample.MirrorResponse, sample.MirrorTruncated, sample.MirrorError,
+		sample.MirrorStopReason, sample.MirrorOutputTokens, sample.OutputMatch,
+	).Scan(&sample.ID, &sample.CreatedAt)
+}
+
+func (r *trafficMirrorRepository) ListSamples(ctx context.Context, ruleID int64, page, pageSize int) ([]*service.TrafficMirrorSample, int64, error) {
+	if page < 1 {
+		page = 1
+	}
+	if pageSize < 1 {
+		pageSize = 20
+	}
+
+	var total int64
+	if err := r.db.QueryRowContext(ctx, `SELECT COUNT(*) FROM traffic_mirror_samples WHERE ($1 = 0 OR rule_id = $1)`, ruleID).Scan(&total); err != nil {
+		return nil, 0, err
+	}
+
+	rows, err := r.db.QueryContext(ctx, `
+SELECT `+trafficMirrorSampleSummaryColumns+`
+FROM traffic_mirror_samples
+WHERE ($1 = 0 OR rule_id = $1)
+ORDER BY created_at DESC, id DESC
+LIMIT $2 OFFSET $3
+`, ruleID, pageSize, (page-1)*pageSize)
+	if err != nil {
+		return nil, 0, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	out := make([]*service.TrafficMirrorSample, 0, pageSize)
+	for rows.Next() {
+		sample, err := scanTrafficMirrorSample(rows, false)
+		if err != nil {
+			return nil, 0, err
+		}
+		out = append(out, sample)
+	}
+	if err := rows.Err(); err != nil {
+		return nil, 0, err
+	}
+	return out, total, nil
+}
+
+func (r *trafficMirrorRepository) GetSample(ctx context.Context, id int64) (*service.TrafficMirrorSample, error) {
+	row := r.db.QueryRowContext(ctx, `
+SELECT `+trafficMirrorSampleSummaryColumns+`, primary_response, mirror_response
+FROM traffic_mirror_samples
+WHERE id = $1
+`, id)
+	sample, err := scanTrafficMirrorSample(row, true)
+	if errors.Is(err, sql.ErrNoRows) {
+		return nil, service.ErrTrafficMirrorSampleNotFound
+	}
+	return sample, err
+}
+
+func (r *trafficMirrorRepository) Report(ctx context.Context, ruleID int64, start, end time.Time) ([]*service.TrafficMirrorRuleReport, error) {
+	rows, err := r.db.QueryContext(ctx, `
+SELECT
+	s.rule_id, r.name, r.target_account_id, r.target_model,
+	COUNT(*),
+	COUNT(*) FILTER (WHERE s.mirror_error IS NOT NULL),
+	COALESCE(percentile_cont(0.50) WITHIN GROUP (ORDER BY s.primary_latency_ms), 0),
+	COALESCE(percentile_cont(0.95) WITHIN GROUP (ORDER BY s.primary_latency_ms), 0),
+	COALESCE(percentile_cont(0.50) WITHIN GROUP (ORDER BY s.mirror_latency_ms) FILTER (WHERE s.mirror_error IS NULL), 0),
+	COALESCE(percentile_cont(0.95) WITHIN GROUP (ORDER BY s.mirror_latency_ms) FILTER (WHERE s.mirror_error IS NULL), 0),
+	COUNT(s.output_match),
+	COUNT(*) FILTER (WHERE s.output_match),
+	COUNT(*) FILTER (WHERE s.output_match IS NOT NULL AND s.primary_stop_reason = s.mirror_stop_reason),
+	COALESCE(AVG(s.primary_output_tokens) FILTER (WHERE s.output_match IS NOT NULL), 0),
+	COALESCE(AVG(s.mirror_output_tokens) FILTER (WHERE s.output_match IS NOT NULL), 0),
+	MIN(s.created_at),
+	MAX(s.created_at)
+FROM traffic_mirror_samples s
+JOIN traffic_mirror_rules r ON r.id = s.rule_id
+WHERE s.created_at >= $1 AND s.created_at < $2 AND ($3 = 0 OR s.rule_id = $3)
+GROUP BY s.rule_id, r.name, r.target_account_id, r.target_model
+ORDER BY s.rule_id
+`, start, end, ruleID)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	out := make([]*service.TrafficMirrorRuleReport, 0)
+	byRule := make(map[int64]*service.TrafficMirrorRuleReport)
+	for rows.Next() {
+		var (
+			report      service.TrafficMirrorRuleReport
+			first, last time.Time
+		)
+		if err := rows.Scan(
+			&report.RuleID,
+			&report.RuleName,
+			&report.TargetAccountID,
+			&report.TargetModel,
+			&report.Samples,
+			&report.MirrorErrors,
+			&report.PrimaryLatencyP50Ms,
+			&report.PrimaryLatencyP95Ms,
+			&report.MirrorLatencyP50Ms,
+			&report.MirrorLatencyP95Ms,
+			&report.Compared,
+			&report.OutputMatches,
+			&report.StopReasonMatches,
+			&report.AvgPrimaryOutputTokens,
+			&report.AvgMirrorOutputTokens,
+			&first,
+			&last,
+		); err != nil {
+			return nil, err
+		}
+		report.FirstSampleAt = &first
+		report.LastSampleAt = &last
+		report.MirrorStatusCodes = make(map[int]int64)
+		out = append(out, &report)
+		byRule[report.RuleID] = &report
+	}
+	if err := rows.Err(); err != nil {
+		return nil, err
+	}
+	if len(out) == 0 {
+		return out, nil
+	}
+
+	statusRows, err := r.db.QueryContext(ctx, `
+SELECT rule_id, COALESCE(mirror_status_code, 0), COUNT(*)
+FROM traffic_mirror_samples
+WHERE created_at >= $1 AND created_at < $2 AND ($3 = 0 OR rule_id = $3)
+GROUP BY rule_id, COALESCE(mirror_status_code, 0)
+`, start, end, ruleID)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = statusRows.Close() }()
+	for statusRows.Next() {
+		var (
+			id     int64
+			status int
+			count  int64
+		)
+		if err := statusRows.Scan(&id, &status, &count); err != nil {
+			return nil, err
+		}
+		if report := byRule[id]; report != nil {
+			report.MirrorStatusCodes[status] = count
+		}
+	}
+	if err := statusRows.Err(); err != nil {
+		return nil, err
+	}
+	return out, nil
+}
+
+func scanTrafficMirrorRule(row interface{ Scan(...any) error }) (*service.TrafficMirrorRule, error) {
+	var (
+		rule    service.TrafficMirrorRule
+		groupID sql.NullInt64
+	)
+	if err := row.Scan(
+		&rule.ID,
+		&rule.Name,
+		&rule.Enabled,
+		&groupID,
+		&rule.ModelPattern,
+		&rule.SampleRate,
+		&rule.TargetAccountID,
+		&rule.TargetModel,
+		&rule.CreatedAt,
+		&rule.UpdatedAt,
+	); err != nil {
+		return nil, err
+	}
+	if groupID.Valid {
+		v := groupID.Int64
+		rule.GroupID = &v
+	}
+	return &rule, nil
+}
+
+// scanTrafficMirrorSample 扫描样本摘要列；withResponses 为 true 时额外扫描主请求与镜像请求输出
+func scanTrafficMirrorSample(row interface{ Scan(...any) error }, withResponses bool) (*service.TrafficMirrorSample, error) {
+	var (
+		sample                                           service.TrafficMirrorSample
+		apiKeyID, groupID, primaryAccountID              sql.NullInt64
+		primaryLatency, mirrorAccountID, mirrorLatencyMs sql.NullInt64
+		mirrorStatus                                     sql.NullInt64
+		mirrorError                                      sql.NullString
+		outputMatch                                      sql.NullBool
+	)
+	dest := []any{
+		&sample.ID,
+		&sample.RuleID,
+		&sample.RequestType,
+		&apiKeyID,
+		&groupID,
+		&sample.Model,
+		&primaryAccountID,
+		&primaryLatency,
+		&sample.PrimaryTruncated,
+		&sample.PrimaryStopReason,
+		&sample.PrimaryOutputTokens,
+		&mirrorAccountID,
+		&sample.MirrorModel,
+		&mirrorStatus,
+		&mirrorLatencyMs,
+		&sample.MirrorTruncated,
+		&mirrorError,
+		&sample.MirrorStopReason,
+		&sample.MirrorOutputTokens,
+		&outputMatch,
+		&sample.CreatedAt,
+	}
+	if withResponses {
+		dest = append(dest, &sample.PrimaryResponse, &sample.MirrorResponse)
+	}
+	if err := row.Scan(dest...); err != nil {
+		return nil, err
+	}
+	if apiKeyID.Valid {
+		v := apiKeyID.Int64
+		sample.APIKeyID = &v
+	}
+	if groupID.Valid {
+		v := groupID.Int64
+		sample.GroupID = &v
+	}
+	sample.PrimaryAccountID = primaryAccountID.Int64
+	sample.PrimaryLatencyMs = primaryLatency.Int64
+	sample.MirrorAccountID = mirrorAccountID.Int64
+	sample.MirrorStatusCode = int(mirrorStatus.Int64)
+	sample.MirrorLatencyMs = mirrorLatencyMs.Int64
+	if mirrorError.Valid {
+		v := mirrorError.String
+		sample.MirrorError = &v
+	}
+	if outputMatch.Valid {
+		v := outputMatch.Bool
+		sample.OutputMatch = &v
+	}
+	return &sample, nil
+}
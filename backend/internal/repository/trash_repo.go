@@ -0,0 +1,274 @@
+package repository
+
+import (
+	"context"
+	"database/sql"
+	"errors"
+	"fmt"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/pkg/logger"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/pagination"
+	"github.com/Wei-Shaw/sub2api/internal/service"
+	"github.com/lib/pq"
+)
+
+type trashRepository struct {
+	db *sql.DB
+}
+
+// NewTrashRepository 创建回收站仓储
+func NewTrashRepository(sqlDB *sql.DB) service.TrashRepository {
+	return &trashRepository{db: sqlDB}
+}
+
+// ListAPIKeys 列出已删除、尚未清除的 API Key（按删除时间倒序）
+func (r *trashRepository) ListAPIKeys(ctx context.Context, params pagination.PaginationParams, search string) ([]service.TrashedAPIKey, *pagination.PaginationResult, error) {
+	where := `k.deleted_at IS NOT NULL AND k.purged_at IS NULL`
+	args := []any{}
+	if search != "" {
+		args = append(args, "%"+search+"%")
+		where += fmt.Sprintf(` AND (k.name ILIKE $%d OR u.email ILIKE $%d)`, len(args), len(args))
+	}
+
+	var total int64
+	countQuery := `SELECT COUNT(*) FROM api_keys k LEFT JOIN users u ON u.id = k.user_id WHERE ` + where
+	if err := scanSingleRow(ctx, r.db, countQuery, args, &total); err != nil {
+		return nil, nil, err
+	}
+
+	query := fmt.Sprintf(`
+		SELECT k.id, k.user_id, COALESCE(u.email, ''), k.name, k.group_id, k.deleted_at
+		FROM api_keys k
+		LEFT JOIN users u ON u.id = k.user_id
+		WHERE %s
+		ORDER BY k.deleted_at DESC, k.id DESC
+		LIMIT $%d OFFSET $%d
+	`, where, len(args)+1, len(args)+2)
+	rows, err := r.db.QueryContext(ctx, query, append(args, params.Limit(), params.Offset())...)
+	if err != nil {
+		return nil, nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	items := make([]service.TrashedAPIKey, 0)
+	for rows.Next() {
+		var (
+			item    service.TrashedAPIKey
+			groupID sql.NullInt64
+		)
+		if err := rows.Scan(&item.ID, &item.UserID, &item.UserEmail, &item.Name, &groupID, &item.DeletedAt); err != nil {
+			return nil, nil, err
+		}
+		if groupID.Valid {
+			v := groupID.Int64
+			item.GroupID = &v
+		}
+		items = append(items, item)
+	}
+	if err := rows.Err(); err != nil {
+		return nil, nil, err
+	}
+	return items, paginationResultFromTotal(total, params), nil
+}
+
+// ListAccounts 列出已删除、尚未清除的账号（按删除时间倒序），附带删除时的分组绑定
+func (r *trashRepository) ListAccounts(ctx context.Context, params pagination.PaginationParams, search string) ([]service.TrashedAccount, *pagination.PaginationResult, error) {
+	where := `a.deleted_at IS NOT NULL AND a.purged_at IS NULL`
+	args := []any{}
+	if search != "" {
+		args = append(args, "%"+search+"%")
+		where += fmt.Sprintf(` AND a.name ILIKE $%d`, len(args))
+	}
+
+	var total int64
+	if err := scanSingleRow(ctx, r.db, `SELECT COUNT(*) FROM accounts a WHERE `+where, args, &total); err != nil {
+		return nil, nil, err
+	}
+
+	query := fmt.Sprintf(`
+		SELECT a.id, a.name, a.platform, a.type, a.deleted_at,
+			COALESCE((SELECT array_agg(t.group_id ORDER BY t.group_id) FROM trashed_account_groups t WHERE t.account_id = a.id), '{}')
+		FROM accounts a
+		WHERE %s
+		ORDER BY a.deleted_at DESC, a.id DESC
+		LIMIT $%d OFFSET $%d
+	`, where, len(args)+1, len(args)+2)
+	rows, err := r.db.QueryContext(ctx, query, append(args, params.Limit(), params.Offset())...)
+	if err != nil {
+		return nil, nil, err
+	}
+	defer func() { _ = rows.Close() }()
+
+	items := make([]service.TrashedAccount, 0)
+	for rows.Next() {
+		var item service.TrashedAccount
+		if err := rows.Scan(&item.ID, &item.Name, &item.Platform, &item.Type, &item.DeletedAt, pq.Array(&item.GroupIDs)); err != nil {
+			return nil, nil, err
+		}
+		items = append(items, item)
+	}
+	if err := rows.Err(); err != nil {
+		return nil, nil, err
+	}
+	return items, paginationResultFromTotal(total, params), nil
+}
+
+// RestoreAPIKey 恢复 API Key（所属用户已删除时不可恢复）
+func (r *trashRepository) RestoreAPIKey(ctx context.Context, id int64) (string, error) {
+	var key string
+	err := scanSingleRow(ctx, r.db, `
+		UPDATE api_keys k SET deleted_at = NULL, updated_at = NOW()
+		FROM users u
+		WHERE k.id = $1 AND k.deleted_at IS NOT NULL AND k.purged_at IS NULL
+			AND u.id = k.user_id AND u.deleted_at IS NULL
+		RETURNING k.key
+	`, []any{id}, &key)
+	if errors.Is(err, sql.ErrNoRows) {
+		return "", service.ErrTrashItemNotFound
+	}
+	if err != nil {
+		return "", err
+	}
+	return key, nil
+}
+
+// RestoreAccount 恢复账号，并重建删除时的分组绑定（跳过已删除的分组）
+func (r *trashRepository) RestoreAccount(ctx context.Context, id int64) (err error) {
+	tx, err := r.db.BeginTx(ctx, nil)
+	if err != nil {
+		return fmt.Errorf("begin tx: %w", err)
+	}
+	defer func() {
+		if err != nil {
+			_ = tx.Rollback()
+		}
+	}()
+
+	res, err := tx.ExecContext(ctx, `
+		UPDATE accounts SET deleted_at = NULL, updated_at = NOW()
+		WHERE id = $1 AND deleted_at IS NOT NULL AND purged_at IS NULL
+	`, id)
+	if err != nil {
+		return err
+	}
+	affected, err := res.RowsAffected()
+	if err != nil {
+		return err
+	}
+	if affected == 0 {
+		err = service.ErrTrashItemNotFound
+		return err
+	}
+
+	rows, err := tx.QueryContext(ctx, `
+		INSERT INTO account_groups (account_id, group_id, priority, created_at)
+		SELECT t.account_id, t.group_id, t.priority, NOW()
+		FROM trashed_account_groups t
+		JOIN groups g ON g.id = t.group_id AND g.deleted_at IS NULL
+		WHERE t.account_id = $1
+		ON CONFLICT (account_id, group_id) DO NOTHING
+		RETURNING group_id
+	`, id)
+	if err != nil {
+		return err
+	}
+	groupIDs := make([]int64, 0)
+	for rows.Next() {
+		var groupID int64
+		if err = rows.Scan(&groupID); err != nil {
+			_ = rows.Close()
+			return err
+		}
+		groupIDs = append(groupIDs, groupID)
+	}
+	if err = rows.Err(); err != nil {
+		_ = rows.Close()
+		return err
+	}
+	_ = rows.Close()
+
+	if _, err = tx.ExecContext(ctx, `DELETE FROM trashed_account_groups WHERE account_id = $1`, id); err != nil {
+		return err
+	}
+	if err = tx.Commit(); err != nil {
+		return err
+	}
+
+	if err := enqueueSchedulerOutbox(ctx, r.db, service.SchedulerOutboxEventAccountChanged, &id, nil, buildSchedulerGroupPayload(groupIDs)); err != nil {
+		logger.LegacyPrintf("repository.trash", "[SchedulerOutbox] enqueue account restore failed: account=%d err=%v", id, err)
+	}
+	return nil
+}
+
+// PurgeAPIKeys 清除超过保留期的 API Key
+// 没有用量记录的直接物理删除；仍被 usage_logs 引用的只抹掉 Key 值并标记 purged_at，
+// 避免外键级联删除历史用量与账单数据。
+func (r *trashRepository) PurgeAPIKeys(ctx context.Context, before time.Time) (int64, error) {
+	deleted, err := r.db.ExecContext(ctx, `
+		DELETE FROM api_keys k
+		WHERE k.deleted_at IS NOT NULL AND k.deleted_at < $1 AND k.purged_at IS NULL
+			AND NOT EXISTS (SELECT 1 FROM usage_logs ul WHERE ul.api_key_id = k.id)
+	`, before)
+	if err != nil {
+		return 0, err
+	}
+	deletedCount, err := deleted.RowsAffected()
+	if err != nil {
+		return 0, err
+	}
+
+	scrubbed, err := r.db.ExecContext(ctx, `
+		UPDATE api_keys
+		SET key = 'purged-' || id || '-' || md5(random()::text), purged_at = NOW()
+		WHERE deleted_at IS NOT NULL AND deleted_at < $1 AND purged_at IS NULL
+	`, before)
+	if err != nil {
+		return deletedCount, err
+	}
+	scrubbedCount, err := scrubbed.RowsAffected()
+	if err != nil {
+		return deletedCount, err
+	}
+	return deletedCount + scrubbedCount, nil
+}
+
+// PurgeAccounts 清除超过保留期的账号
+// 没有用量记录的直接物理删除；仍被 usage_logs 引用的清空凭证并标记 purged_at。
+func (r *trashRepository) PurgeAccounts(ctx context.Context, before time.Time) (int64, error) {
+	deleted, err := r.db.ExecContext(ctx, `
+		DELETE FROM accounts a
+		WHERE a.deleted_at IS NOT NULL AND a.deleted_at < $1 AND a.purged_at IS NULL
+			AND NOT EXISTS (SELECT 1 FROM usage_logs ul WHERE ul.account_id = a.id)
+	`, before)
+	if err != nil {
+		return 0, err
+	}
+	deletedCount, err := deleted.RowsAffected()
+	if err != nil {
+		return 0, err
+	}
+
+	scrubbed, err := r.db.ExecContext(ctx, `
+		UPDATE accounts
+		SET credentials = '{}'::jsonb, purged_at = NOW()
+		WHERE deleted_at IS NOT NULL AND deleted_at < $1 AND purged_at IS NULL
+	`, before)
+	if err != nil {
+		return deletedCount, err
+	}
+	scrubbedCount, err := scrubbed.RowsAffected()
+	if err != nil {
+		return deletedCount, err
+	}
+
+	// 已清除的账号不可再恢复，分组快照一并删除（物理删除的账号由外键级联清理）
+	if _, err := r.db.ExecContext(ctx, `
+		DELETE FROM trashed_account_groups t
+		USING accounts a
+		WHERE a.id = t.account_id AND a.purged_at IS NOT NULL
+	`); err != nil {
+		return deletedCount + scrubbedCount, err
+	}
+	return deletedCount + scrubbedCount, nil
+}
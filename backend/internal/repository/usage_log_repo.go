@@ -22,7 +22,7 @@ import (
 	"github.com/lib/pq"
 )
 
-const usageLogSelectColumns = "id, user_id, api_key_id, account_id, request_id, model, group_id, subscription_id, input_tokens, output_tokens, cache_creation_tokens, cache_read_tokens, cache_creation_5m_tokens, cache_creation_1h_tokens, input_cost, output_cost, cache_creation_cost, cache_read_cost, total_cost, actual_cost, rate_multiplier, account_rate_multiplier, billing_type, stream, duration_ms, first_token_ms, user_agent, ip_address, image_count, image_size, media_type, reasoning_effort, cache_ttl_overridden, created_at"
+const usageLogSelectColumns = "id, user_id, api_key_id, account_id, request_id, model, group_id, subscription_id, input_tokens, output_tokens, cache_creation_tokens, cache_read_tokens, cache_creation_5m_tokens, cache_creation_1h_tokens, input_cost, output_cost, cache_creation_cost, cache_read_cost, total_cost, actual_cost, rate_multiplier, account_rate_multiplier, billing_type, stream, duration_ms, first_token_ms, user_agent, ip_address, image_count, image_size, media_type, reasoning_effort, reasoning_tokens, prompt_firewall, system_fingerprint, usage_estimated, cache_ttl_overridden, created_at"
 
 // dateFormatWhitelist 将 granularity 参数映射为 PostgreSQL TO_CHAR 格式字符串，防止外部输入直接拼入 SQL
 var dateFormatWhitelist = map[string]string{
@@ -43,15 +43,21 @@ func safeDateFormat(granularity string) string {
 type usageLogRepository struct {
 	client *dbent.Client
 	sql    sqlExecutor
+	// read 统计聚合与列表查询使用，可路由到只读副本；写入及调度热路径的账号窗口统计仍走 sql
+	read sqlExecutor
 }
 
-func NewUsageLogRepository(client *dbent.Client, sqlDB *sql.DB) service.UsageLogRepository {
-	return newUsageLogRepositoryWithSQL(client, sqlDB)
+func NewUsageLogRepository(client *dbent.Client, sqlDB *sql.DB, readDB *ReadDB) service.UsageLogRepository {
+	repo := newUsageLogRepositoryWithSQL(client, sqlDB)
+	if readDB != nil {
+		repo.read = readDB
+	}
+	return repo
 }
 
 func newUsageLogRepositoryWithSQL(client *dbent.Client, sqlq sqlExecutor) *usageLogRepository {
 	// 使用 scanSingleRow 替代 QueryRowContext，保证 ent.Tx 作为 sqlExecutor 可用。
-	return &usageLogRepository{client: client, sql: sqlq}
+	return &usageLogRepository{client: client, sql: sqlq, read: sqlq}
 }
 
 // getPerformanceStats 获取 RPM 和 TPM（近5分钟平均值，可选按用户过滤）
@@ -71,7 +77,7 @@ func (r *usageLogRepository) getPerformanceStats(ctx context.Context, userID int
 
 	var requestCount int64
 	var tokenCount int64
-	if err := scanSingleRow(ctx, r.sql, query, args, &requestCount, &tokenCount); err != nil {
+	if err := scanSingleRow(ctx, r.read, query, args, &requestCount, &tokenCount); err != nil {
 		return 0, 0, err
 	}
 	return requestCount / 5, tokenCount / 5, nil
@@ -132,6 +138,10 @@ func (r *usageLogRepository) Create(ctx context.Context, log *service.UsageLog)
 			image_size,
 			media_type,
 			reasoning_effort,
+			reasoning_tokens,
+			prompt_firewall,
+			system_fingerprint,
+			usage_estimated,
 			cache_ttl_overridden,
 			created_at
 		) VALUES (
@@ -140,7 +150,7 @@ func (r *usageLogRepository) Create(ctx context.Context, log *service.UsageLog)
 			$8, $9, $10, $11,
 			$12, $13,
 			$14, $15, $16, $17, $18, $19,
-			$20, $21, $22, $23, $24, $25, $26, $27, $28, $29, $30, $31, $32, $33
+			$20, $21, $22, $23, $24, $25, $26, $27, $28, $29, $30, $31, $32, $33, $34, $35, $36, $37
 		)
 		ON CONFLICT (request_id, api_key_id) DO NOTHING
 		RETURNING id, created_at
@@ -155,6 +165,8 @@ func (r *usageLogRepository) Create(ctx context.Context, log *service.UsageLog)
 	imageSize := nullString(log.ImageSize)
 	mediaType := nullString(log.MediaType)
 	reasoningEffort := nullString(log.ReasoningEffort)
+	promptFirewall := nullString(log.PromptFirewall)
+	systemFingerprint := nullString(log.SystemFingerprint)
 
 	var requestIDArg any
 	if requestID != "" {
@@ -193,6 +205,10 @@ func (r *usageLogRepository) Create(ctx context.Context, log *service.UsageLog)
 		imageSize,
 		mediaType,
 		reasoningEffort,
+		log.ReasoningTokens,
+		promptFirewall,
+		systemFingerprint,
+		log.UsageEstimated,
 		log.CacheTTLOverridden,
 		createdAt,
 	}
@@ -276,7 +292,7 @@ func (r *usageLogRepository) GetUserStats(ctx context.Context, userID int64, sta
 	stats := &UserStats{}
 	if err := scanSingleRow(
 		ctx,
-		r.sql,
+		r.read,
 		query,
 		[]any{userID, startTime, endTime},
 		&stats.TotalRequests,
@@ -354,7 +370,7 @@ func (r *usageLogRepository) fillDashboardEntityStats(ctx context.Context, stats
 	`
 	if err := scanSingleRow(
 		ctx,
-		r.sql,
+		r.read,
 		userStatsQuery,
 		[]any{todayUTC},
 		&stats.TotalUsers,
@@ -372,7 +388,7 @@ func (r *usageLogRepository) fillDashboardEntityStats(ctx context.Context, stats
 	`
 	if err := scanSingleRow(
 		ctx,
-		r.sql,
+		r.read,
 		apiKeyStatsQuery,
 		[]any{service.StatusActive},
 		&stats.TotalAPIKeys,
@@ -393,7 +409,7 @@ func (r *usageLogRepository) fillDashboardEntityStats(ctx context.Context, stats
 	`
 	if err := scanSingleRow(
 		ctx,
-		r.sql,
+		r.read,
 		accountStatsQuery,
 		[]any{service.StatusActive, service.StatusError, now, now},
 		&stats.TotalAccounts,
@@ -424,7 +440,7 @@ func (r *usageLogRepository) fillDashboardUsageStatsAggregated(ctx context.Conte
 	var totalDurationMs int64
 	if err := scanSingleRow(
 		ctx,
-		r.sql,
+		r.read,
 		totalStatsQuery,
 		nil,
 		&stats.TotalRequests,
@@ -458,7 +474,7 @@ func (r *usageLogRepository) fillDashboardUsageStatsAggregated(ctx context.Conte
 	`
 	if err := scanSingleRow(
 		ctx,
-		r.sql,
+		r.read,
 		todayStatsQuery,
 		[]any{todayUTC},
 		&stats.TodayRequests,
@@ -482,7 +498,7 @@ func (r *usageLogRepository) fillDashboardUsageStatsAggregated(ctx context.Conte
 		WHERE bucket_start = $1
 	`
 	hourStart := now.In(timezone.Location()).Truncate(time.Hour)
-	if err := scanSingleRow(ctx, r.sql, hourlyActiveQuery, []any{hourStart}, &stats.HourlyActiveUsers); err != nil {
+	if err := scanSingleRow(ctx, r.read, hourlyActiveQuery, []any{hourStart}, &stats.HourlyActiveUsers); err != nil {
 		if err != sql.ErrNoRows {
 			return err
 		}
@@ -508,7 +524,7 @@ func (r *usageLogRepository) fillDashboardUsageStatsFromUsageLogs(ctx context.Co
 	var totalDurationMs int64
 	if err := scanSingleRow(
 		ctx,
-		r.sql,
+		r.read,
 		totalStatsQuery,
 		[]any{startUTC, endUTC},
 		&stats.TotalRequests,
@@ -542,7 +558,7 @@ func (r *usageLogRepository) fillDashboardUsageStatsFromUsageLogs(ctx context.Co
 	`
 	if err := scanSingleRow(
 		ctx,
-		r.sql,
+		r.read,
 		todayStatsQuery,
 		[]any{todayUTC, todayEnd},
 		&stats.TodayRequests,
@@ -562,7 +578,7 @@ func (r *usageLogRepository) fillDashboardUsageStatsFromUsageLogs(ctx context.Co
 		FROM usage_logs
 		WHERE created_at >= $1 AND created_at < $2
 	`
-	if err := scanSingleRow(ctx, r.sql, activeUsersQuery, []any{todayUTC, todayEnd}, &stats.ActiveUsers); err != nil {
+	if err := scanSingleRow(ctx, r.read, activeUsersQuery, []any{todayUTC, todayEnd}, &stats.ActiveUsers); err != nil {
 		return err
 	}
 
@@ -573,7 +589,7 @@ func (r *usageLogRepository) fillDashboardUsageStatsFromUsageLogs(ctx context.Co
 		FROM usage_logs
 		WHERE created_at >= $1 AND created_at < $2
 	`
-	if err := scanSingleRow(ctx, r.sql, hourlyActiveQuery, []any{hourStart, hourEnd}, &stats.HourlyActiveUsers); err != nil {
+	if err := scanSingleRow(ctx, r.read, hourlyActiveQuery, []any{hourStart, hourEnd}, &stats.HourlyActiveUsers); err != nil {
 		return err
 	}
 
@@ -608,7 +624,7 @@ func (r *usageLogRepository) GetUserStatsAggregated(ctx context.Context, userID
 	var stats usagestats.UsageStats
 	if err := scanSingleRow(
 		ctx,
-		r.sql,
+		r.read,
 		query,
 		[]any{userID, startTime, endTime},
 		&stats.TotalRequests,
@@ -643,7 +659,7 @@ func (r *usageLogRepository) GetAPIKeyStatsAggregated(ctx context.Context, apiKe
 	var stats usagestats.UsageStats
 	if err := scanSingleRow(
 		ctx,
-		r.sql,
+		r.read,
 		query,
 		[]any{apiKeyID, startTime, endTime},
 		&stats.TotalRequests,
@@ -688,7 +704,7 @@ func (r *usageLogRepository) GetAccountStatsAggregated(ctx context.Context, acco
 	var stats usagestats.UsageStats
 	if err := scanSingleRow(
 		ctx,
-		r.sql,
+		r.read,
 		query,
 		[]any{accountID, startTime, endTime},
 		&stats.TotalRequests,
@@ -724,7 +740,7 @@ func (r *usageLogRepository) GetModelStatsAggregated(ctx context.Context, modelN
 	var stats usagestats.UsageStats
 	if err := scanSingleRow(
 		ctx,
-		r.sql,
+		r.read,
 		query,
 		[]any{modelName, startTime, endTime},
 		&stats.TotalRequests,
@@ -762,7 +778,7 @@ func (r *usageLogRepository) GetDailyStatsAggregated(ctx context.Context, userID
 		ORDER BY 1
 	`
 
-	rows, err := r.sql.QueryContext(ctx, query, userID, startTime, endTime, tzName)
+	rows, err := r.read.QueryContext(ctx, query, userID, startTime, endTime, tzName)
 	if err != nil {
 		return nil, err
 	}
@@ -984,6 +1000,35 @@ type APIKeyUsageTrendPoint = usagestats.APIKeyUsageTrendPoint
 func (r *usageLogRepository) GetAPIKeyUsageTrend(ctx context.Context, startTime, endTime time.Time, granularity string, limit int) (results []APIKeyUsageTrendPoint, err error) {
 	dateFormat := safeDateFormat(granularity)
 
+	source, sourceArgs, err := r.usageRollupSource(ctx, startTime, endTime, granularity != "hour")
+	if err != nil {
+		return nil, err
+	}
+	if source != "" {
+		query := fmt.Sprintf(`
+			WITH src AS (%s),
+			top_keys AS (
+				SELECT api_key_id
+				FROM src
+				GROUP BY api_key_id
+				ORDER BY SUM(input_tokens + output_tokens + cache_creation_tokens + cache_read_tokens) DESC
+				LIMIT $%d
+			)
+			SELECT
+				TO_CHAR(s.bucket, '%s') as date,
+				s.api_key_id,
+				COALESCE(k.name, '') as key_name,
+				COALESCE(SUM(s.requests), 0)::bigint as requests,
+				COALESCE(SUM(s.input_tokens + s.output_tokens + s.cache_creation_tokens + s.cache_read_tokens), 0)::bigint as tokens
+			FROM src s
+			LEFT JOIN api_keys k ON s.api_key_id = k.id
+			WHERE s.api_key_id IN (SELECT api_key_id FROM top_keys)
+			GROUP BY date, s.api_key_id, k.name
+			ORDER BY date ASC, tokens DESC
+		`, source, len(sourceArgs)+1, dateFormat)
+		return r.queryAPIKeyUsageTrend(ctx, query, append(sourceArgs, limit)...)
+	}
+
 	query := fmt.Sprintf(`
 		WITH top_keys AS (
 			SELECT api_key_id
@@ -1007,7 +1052,11 @@ func (r *usageLogRepository) GetAPIKeyUsageTrend(ctx context.Context, startTime,
 		ORDER BY date ASC, tokens DESC
 	`, dateFormat)
 
-	rows, err := r.sql.QueryContext(ctx, query, startTime, endTime, limit, startTime, endTime)
+	return r.queryAPIKeyUsageTrend(ctx, query, startTime, endTime, limit, startTime, endTime)
+}
+
+func (r *usageLogRepository) queryAPIKeyUsageTrend(ctx context.Context, query string, args ...any) (results []APIKeyUsageTrendPoint, err error) {
+	rows, err := r.read.QueryContext(ctx, query, args...)
 	if err != nil {
 		return nil, err
 	}
@@ -1064,7 +1113,7 @@ func (r *usageLogRepository) GetUserUsageTrend(ctx context.Context, startTime, e
 		ORDER BY date ASC, tokens DESC
 	`, dateFormat)
 
-	rows, err := r.sql.QueryContext(ctx, query, startTime, endTime, limit, startTime, endTime)
+	rows, err := r.read.QueryContext(ctx, query, startTime, endTime, limit, startTime, endTime)
 	if err != nil {
 		return nil, err
 	}
@@ -1103,7 +1152,7 @@ func (r *usageLogRepository) GetUserDashboardStats(ctx context.Context, userID i
 	// API Key 统计
 	if err := scanSingleRow(
 		ctx,
-		r.sql,
+		r.read,
 		"SELECT COUNT(*) FROM api_keys WHERE user_id = $1 AND deleted_at IS NULL",
 		[]any{userID},
 		&stats.TotalAPIKeys,
@@ -1112,7 +1161,7 @@ func (r *usageLogRepository) GetUserDashboardStats(ctx context.Context, userID i
 	}
 	if err := scanSingleRow(
 		ctx,
-		r.sql,
+		r.read,
 		"SELECT COUNT(*) FROM api_keys WHERE user_id = $1 AND status = $2 AND deleted_at IS NULL",
 		[]any{userID, service.StatusActive},
 		&stats.ActiveAPIKeys,
@@ -1136,7 +1185,7 @@ func (r *usageLogRepository) GetUserDashboardStats(ctx context.Context, userID i
 	`
 	if err := scanSingleRow(
 		ctx,
-		r.sql,
+		r.read,
 		totalStatsQuery,
 		[]any{userID},
 		&stats.TotalRequests,
@@ -1167,7 +1216,7 @@ func (r *usageLogRepository) GetUserDashboardStats(ctx context.Context, userID i
 	`
 	if err := scanSingleRow(
 		ctx,
-		r.sql,
+		r.read,
 		todayStatsQuery,
 		[]any{userID, today},
 		&stats.TodayRequests,
@@ -1206,7 +1255,7 @@ func (r *usageLogRepository) getPerformanceStatsByAPIKey(ctx context.Context, ap
 
 	var requestCount int64
 	var tokenCount int64
-	if err := scanSingleRow(ctx, r.sql, query, args, &requestCount, &tokenCount); err != nil {
+	if err := scanSingleRow(ctx, r.read, query, args, &requestCount, &tokenCount); err != nil {
 		return 0, 0, err
 	}
 	return requestCount / 5, tokenCount / 5, nil
@@ -1237,7 +1286,7 @@ func (r *usageLogRepository) GetAPIKeyDashboardStats(ctx context.Context, apiKey
 	`
 	if err := scanSingleRow(
 		ctx,
-		r.sql,
+		r.read,
 		totalStatsQuery,
 		[]any{apiKeyID},
 		&stats.TotalRequests,
@@ -1268,7 +1317,7 @@ func (r *usageLogRepository) GetAPIKeyDashboardStats(ctx context.Context, apiKey
 	`
 	if err := scanSingleRow(
 		ctx,
-		r.sql,
+		r.read,
 		todayStatsQuery,
 		[]any{apiKeyID, today},
 		&stats.TodayRequests,
@@ -1314,7 +1363,7 @@ func (r *usageLogRepository) GetUserUsageTrendByUserID(ctx context.Context, user
 		ORDER BY date ASC
 	`, dateFormat)
 
-	rows, err := r.sql.QueryContext(ctx, query, userID, startTime, endTime)
+	rows, err := r.read.QueryContext(ctx, query, userID, startTime, endTime)
 	if err != nil {
 		return nil, err
 	}
@@ -1351,7 +1400,7 @@ func (r *usageLogRepository) GetUserModelStats(ctx context.Context, userID int64
 		ORDER BY total_tokens DESC
 	`
 
-	rows, err := r.sql.QueryContext(ctx, query, userID, startTime, endTime)
+	rows, err := r.read.QueryContext(ctx, query, userID, startTime, endTime)
 	if err != nil {
 		return nil, err
 	}
@@ -1460,7 +1509,7 @@ func (r *usageLogRepository) GetBatchUserUsageStats(ctx context.Context, userIDs
 		WHERE user_id = ANY($1) AND created_at >= $2 AND created_at < $3
 		GROUP BY user_id
 	`
-	rows, err := r.sql.QueryContext(ctx, query, pq.Array(userIDs), startTime, endTime)
+	rows, err := r.read.QueryContext(ctx, query, pq.Array(userIDs), startTime, endTime)
 	if err != nil {
 		return nil, err
 	}
@@ -1489,7 +1538,7 @@ func (r *usageLogRepository) GetBatchUserUsageStats(ctx context.Context, userIDs
 		WHERE user_id = ANY($1) AND created_at >= $2
 		GROUP BY user_id
 	`
-	rows, err = r.sql.QueryContext(ctx, todayQuery, pq.Array(userIDs), today)
+	rows, err = r.read.QueryContext(ctx, todayQuery, pq.Array(userIDs), today)
 	if err != nil {
 		return nil, err
 	}
@@ -1543,7 +1592,7 @@ func (r *usageLogRepository) GetBatchAPIKeyUsageStats(ctx context.Context, apiKe
 		WHERE api_key_id = ANY($1) AND created_at >= $2 AND created_at < $3
 		GROUP BY api_key_id
 	`
-	rows, err := r.sql.QueryContext(ctx, query, pq.Array(apiKeyIDs), startTime, endTime)
+	rows, err := r.read.QueryContext(ctx, query, pq.Array(apiKeyIDs), startTime, endTime)
 	if err != nil {
 		return nil, err
 	}
@@ -1572,7 +1621,7 @@ func (r *usageLogRepository) GetBatchAPIKeyUsageStats(ctx context.Context, apiKe
 		WHERE api_key_id = ANY($1) AND created_at >= $2
 		GROUP BY api_key_id
 	`
-	rows, err = r.sql.QueryContext(ctx, todayQuery, pq.Array(apiKeyIDs), today)
+	rows, err = r.read.QueryContext(ctx, todayQuery, pq.Array(apiKeyIDs), today)
 	if err != nil {
 		return nil, err
 	}
@@ -1646,7 +1695,7 @@ func (r *usageLogRepository) GetUsageTrendWithFilters(ctx context.Context, start
 	}
 	query += " GROUP BY date ORDER BY date ASC"
 
-	rows, err := r.sql.QueryContext(ctx, query, args...)
+	rows, err := r.read.QueryContext(ctx, query, args...)
 	if err != nil {
 		return nil, err
 	}
@@ -1674,6 +1723,17 @@ func (r *usageLogRepository) GetModelStatsWithFilters(ctx context.Context, start
 		actualCostExpr = "COALESCE(SUM(total_cost * COALESCE(account_rate_multiplier, 1)), 0) as actual_cost"
 	}
 
+	// 仅按 API Key / 账号过滤时可走维度汇总表，避免扫描原始记录
+	if userID == 0 && groupID == 0 && stream == nil && billingType == nil {
+		source, args, err := r.usageRollupSource(ctx, startTime, endTime, true)
+		if err != nil {
+			return nil, err
+		}
+		if source != "" {
+			return r.getModelStatsFromRollups(ctx, source, args, apiKeyID, accountID)
+		}
+	}
+
 	query := fmt.Sprintf(`
 		SELECT
 			model,
@@ -1714,7 +1774,7 @@ func (r *usageLogRepository) GetModelStatsWithFilters(ctx context.Context, start
 	}
 	query += " GROUP BY model ORDER BY total_tokens DESC"
 
-	rows, err := r.sql.QueryContext(ctx, query, args...)
+	rows, err := r.read.QueryContext(ctx, query, args...)
 	if err != nil {
 		return nil, err
 	}
@@ -1734,6 +1794,118 @@ func (r *usageLogRepository) GetModelStatsWithFilters(ctx context.Context, start
 	return results, nil
 }
 
+func (r *usageLogRepository) getModelStatsFromRollups(ctx context.Context, source string, args []any, apiKeyID, accountID int64) (results []ModelStat, err error) {
+	actualCostExpr := "COALESCE(SUM(actual_cost), 0) as actual_cost"
+	if accountID > 0 && apiKeyID == 0 {
+		actualCostExpr = "COALESCE(SUM(account_cost), 0) as actual_cost"
+	}
+
+	query := fmt.Sprintf(`
+		SELECT
+			model,
+			COALESCE(SUM(requests), 0)::bigint as requests,
+			COALESCE(SUM(input_tokens), 0)::bigint as input_tokens,
+			COALESCE(SUM(output_tokens), 0)::bigint as output_tokens,
+			COALESCE(SUM(input_tokens + output_tokens + cache_creation_tokens + cache_read_tokens), 0)::bigint as total_tokens,
+			COALESCE(SUM(total_cost), 0) as cost,
+			%s
+		FROM (%s) src
+		WHERE 1 = 1
+	`, actualCostExpr, source)
+	if apiKeyID > 0 {
+		query += fmt.Sprintf(" AND api_key_id = $%d", len(args)+1)
+		args = append(args, apiKeyID)
+	}
+	if accountID > 0 {
+		query += fmt.Sprintf(" AND account_id = $%d", len(args)+1)
+		args = append(args, accountID)
+	}
+	query += " GROUP BY model ORDER BY total_tokens DESC"
+
+	rows, err := r.read.QueryContext(ctx, query, args...)
+	if err != nil {
+		return nil, err
+	}
+	defer func() {
+		if closeErr := rows.Close(); closeErr != nil && err == nil {
+			err = closeErr
+			results = nil
+		}
+	}()
+
+	return scanModelStatsRows(rows)
+}
+
+// usageRollupSource 构造 [start, end) 区间的统一明细子查询，列为
+// (bucket, api_key_id, account_id, model, requests, input_tokens, output_tokens,
+// cache_creation_tokens, cache_read_tokens, total_cost, actual_cost, account_cost)。
+//
+// 已聚合的区间（watermark 之前的整小时）读取 usage_rollup_daily / usage_rollup_hourly，
+// watermark 之后尚未聚合的尾部仍读取 usage_logs。
+// 起点未按小时对齐、或汇总表未覆盖起点（未回填/已过保留期）时返回空字符串，调用方应回退为扫描原始记录。
+// allowDaily=false 时不使用日表（例如按小时粒度分组）。
+func (r *usageLogRepository) usageRollupSource(ctx context.Context, start, end time.Time, allowDaily bool) (string, []any, error) {
+	loc := timezone.Location()
+	startLocal := start.In(loc)
+	endLocal := end.In(loc)
+	if !endLocal.After(startLocal) || !startLocal.Truncate(time.Hour).Equal(startLocal) {
+		return "", nil, nil
+	}
+
+	var watermark, minHourly, minDaily sql.NullTime
+	coverageQuery := `
+		SELECT
+			(SELECT last_aggregated_at FROM usage_dashboard_aggregation_watermark WHERE id = 1),
+			(SELECT MIN(bucket_start) FROM usage_rollup_hourly),
+			(SELECT MIN(bucket_date)::timestamptz FROM usage_rollup_daily)
+	`
+	if err := scanSingleRow(ctx, r.read, coverageQuery, nil, &watermark, &minHourly, &minDaily); err != nil {
+		return "", nil, err
+	}
+	if !watermark.Valid {
+		return "", nil, nil
+	}
+
+	// split 之前的整小时桶已由聚合任务写入
+	split := watermark.Time.In(loc).Truncate(time.Hour)
+	if endTrunc := endLocal.Truncate(time.Hour); endTrunc.Before(split) {
+		split = endTrunc
+	}
+	if !split.After(startLocal) {
+		return "", nil, nil
+	}
+
+	hourlyFrom := startLocal
+	useDaily := false
+	if allowDaily && truncateToDay(startLocal).Equal(startLocal) {
+		splitDay := truncateToDay(split)
+		if splitDay.After(startLocal) && minDaily.Valid && !minDaily.Time.After(startLocal) {
+			useDaily = true
+			hourlyFrom = splitDay
+		}
+	}
+	if hourlyFrom.Before(split) && (!minHourly.Valid || minHourly.Time.After(hourlyFrom)) {
+		return "", nil, nil
+	}
+
+	const rollupColumns = "api_key_id, account_id, model, total_requests AS requests, input_tokens, output_tokens, cache_creation_tokens, cache_read_tokens, total_cost, actual_cost, account_cost"
+	parts := make([]string, 0, 3)
+	args := make([]any, 0, 6)
+	if useDaily {
+		args = append(args, startLocal, hourlyFrom)
+		parts = append(parts, fmt.Sprintf("SELECT bucket_date::timestamptz AS bucket, %s FROM usage_rollup_daily WHERE bucket_date >= $%d::date AND bucket_date < $%d::date", rollupColumns, len(args)-1, len(args)))
+	}
+	if hourlyFrom.Before(split) {
+		args = append(args, hourlyFrom, split)
+		parts = append(parts, fmt.Sprintf("SELECT bucket_start AS bucket, %s FROM usage_rollup_hourly WHERE bucket_start >= $%d AND bucket_start < $%d", rollupColumns, len(args)-1, len(args)))
+	}
+	if endLocal.After(split) {
+		args = append(args, split, endLocal)
+		parts = append(parts, fmt.Sprintf(`SELECT created_at AS bucket, api_key_id, account_id, model, 1 AS requests, input_tokens, output_tokens, cache_creation_tokens, cache_read_tokens, total_cost, actual_cost, total_cost * COALESCE(account_rate_multiplier, 1) AS account_cost FROM usage_logs WHERE created_at >= $%d AND created_at < $%d`, len(args)-1, len(args)))
+	}
+	return strings.Join(parts, " UNION ALL "), args, nil
+}
+
 // GetGlobalStats gets usage statistics for all users within a time range
 func (r *usageLogRepository) GetGlobalStats(ctx context.Context, startTime, endTime time.Time) (*UsageStats, error) {
 	query := `
@@ -1752,7 +1924,7 @@ func (r *usageLogRepository) GetGlobalStats(ctx context.Context, startTime, endT
 	stats := &UsageStats{}
 	if err := scanSingleRow(
 		ctx,
-		r.sql,
+		r.read,
 		query,
 		[]any{startTime, endTime},
 		&stats.TotalRequests,
@@ -1829,7 +2001,7 @@ func (r *usageLogRepository) GetStatsWithFilters(ctx context.Context, filters Us
 	var totalAccountCost float64
 	if err := scanSingleRow(
 		ctx,
-		r.sql,
+		r.read,
 		query,
 		args,
 		&stats.TotalRequests,
@@ -1880,7 +2052,7 @@ func (r *usageLogRepository) GetAccountUsageStats(ctx context.Context, accountID
 		ORDER BY date ASC
 	`
 
-	rows, err := r.sql.QueryContext(ctx, query, accountID, startTime, endTime)
+	rows, err := r.read.QueryContext(ctx, query, accountID, startTime, endTime)
 	if err != nil {
 		return nil, err
 	}
@@ -1946,7 +2118,7 @@ func (r *usageLogRepository) GetAccountUsageStats(ctx context.Context, accountID
 
 	avgQuery := "SELECT COALESCE(AVG(duration_ms), 0) as avg_duration_ms FROM usage_logs WHERE account_id = $1 AND created_at >= $2 AND created_at < $3"
 	var avgDuration float64
-	if err := scanSingleRow(ctx, r.sql, avgQuery, []any{accountID, startTime, endTime}, &avgDuration); err != nil {
+	if err := scanSingleRow(ctx, r.read, avgQuery, []any{accountID, startTime, endTime}, &avgDuration); err != nil {
 		return nil, err
 	}
 
@@ -2033,7 +2205,7 @@ func (r *usageLogRepository) GetAccountUsageStats(ctx context.Context, accountID
 func (r *usageLogRepository) listUsageLogsWithPagination(ctx context.Context, whereClause string, args []any, params pagination.PaginationParams) ([]service.UsageLog, *pagination.PaginationResult, error) {
 	countQuery := "SELECT COUNT(*) FROM usage_logs " + whereClause
 	var total int64
-	if err := scanSingleRow(ctx, r.sql, countQuery, args, &total); err != nil {
+	if err := scanSingleRow(ctx, r.read, countQuery, args, &total); err != nil {
 		return nil, nil, err
 	}
 
@@ -2049,7 +2221,7 @@ func (r *usageLogRepository) listUsageLogsWithPagination(ctx context.Context, wh
 }
 
 func (r *usageLogRepository) queryUsageLogs(ctx context.Context, query string, args ...any) (logs []service.UsageLog, err error) {
-	rows, err := r.sql.QueryContext(ctx, query, args...)
+	rows, err := r.read.QueryContext(ctx, query, args...)
 	if err != nil {
 		return nil, err
 	}
@@ -2276,6 +2448,10 @@ func scanUsageLog(scanner interface{ Scan(...any) error }) (*service.UsageLog, e
 		imageSize             sql.NullString
 		mediaType             sql.NullString
 		reasoningEffort       sql.NullString
+		reasoningTokens       int
+		promptFirewall        sql.NullString
+		systemFingerprint     sql.NullString
+		usageEstimated        bool
 		cacheTTLOverridden    bool
 		createdAt             time.Time
 	)
@@ -2313,6 +2489,10 @@ func scanUsageLog(scanner interface{ Scan(...any) error }) (*service.UsageLog, e
 		&imageSize,
 		&mediaType,
 		&reasoningEffort,
+		&reasoningTokens,
+		&promptFirewall,
+		&systemFingerprint,
+		&usageEstimated,
 		&cacheTTLOverridden,
 		&createdAt,
 	); err != nil {
@@ -2329,6 +2509,7 @@ func scanUsageLog(scanner interface{ Scan(...any) error }) (*service.UsageLog, e
 		OutputTokens:          outputTokens,
 		CacheCreationTokens:   cacheCreationTokens,
 		CacheReadTokens:       cacheReadTokens,
+		ReasoningTokens:       reasoningTokens,
 		CacheCreation5mTokens: cacheCreation5m,
 		CacheCreation1hTokens: cacheCreation1h,
 		InputCost:             inputCost,
@@ -2342,6 +2523,7 @@ func scanUsageLog(scanner interface{ Scan(...any) error }) (*service.UsageLog, e
 		BillingType:           int8(billingType),
 		Stream:                stream,
 		ImageCount:            imageCount,
+		UsageEstimated:        usageEstimated,
 		CacheTTLOverridden:    cacheTTLOverridden,
 		CreatedAt:             createdAt,
 	}
@@ -2380,6 +2562,12 @@ func scanUsageLog(scanner interface{ Scan(...any) error }) (*service.UsageLog, e
 	if reasoningEffort.Valid {
 		log.ReasoningEffort = &reasoningEffort.String
 	}
+	if promptFirewall.Valid {
+		log.PromptFirewall = &promptFirewall.String
+	}
+	if systemFingerprint.Valid {
+		log.SystemFingerprint = &systemFingerprint.String
+	}
 
 	return log, nil
 }
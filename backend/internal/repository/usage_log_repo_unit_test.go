@@ -3,8 +3,12 @@
 package repository
 
 import (
+	"context"
 	"testing"
+	"time"
 
+	"github.com/DATA-DOG/go-sqlmock"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/timezone"
 	"github.com/stretchr/testify/require"
 )
 
@@ -39,3 +43,69 @@ func TestSafeDateFormat(t *testing.T) {
 		})
 	}
 }
+
+func TestUsageRollupSource(t *testing.T) {
+	loc := timezone.Location()
+	start := time.Date(2026, 3, 1, 0, 0, 0, 0, loc)
+	end := time.Date(2026, 3, 8, 0, 0, 0, 0, loc)
+	watermark := time.Date(2026, 3, 7, 15, 20, 0, 0, loc)
+	coverageColumns := []string{"watermark", "min_hourly", "min_daily"}
+
+	t.Run("daily + hourly + raw tail", func(t *testing.T) {
+		db, mock := newSQLMock(t)
+		repo := newUsageLogRepositoryWithSQL(nil, db)
+		mock.ExpectQuery(`usage_dashboard_aggregation_watermark`).
+			WillReturnRows(sqlmock.NewRows(coverageColumns).AddRow(watermark, start.AddDate(0, 0, -3), start.AddDate(0, 0, -30)))
+
+		source, args, err := repo.usageRollupSource(context.Background(), start, end, true)
+		require.NoError(t, err)
+		require.Contains(t, source, "FROM usage_rollup_daily")
+		require.Contains(t, source, "FROM usage_rollup_hourly")
+		require.Contains(t, source, "FROM usage_logs")
+
+		splitDay := time.Date(2026, 3, 7, 0, 0, 0, 0, loc)
+		split := time.Date(2026, 3, 7, 15, 0, 0, 0, loc)
+		require.Len(t, args, 6)
+		require.True(t, start.Equal(args[0].(time.Time)))
+		require.True(t, splitDay.Equal(args[1].(time.Time)))
+		require.True(t, splitDay.Equal(args[2].(time.Time)))
+		require.True(t, split.Equal(args[3].(time.Time)))
+		require.True(t, split.Equal(args[4].(time.Time)))
+		require.True(t, end.Equal(args[5].(time.Time)))
+		require.NoError(t, mock.ExpectationsWereMet())
+	})
+
+	t.Run("hour granularity skips daily table", func(t *testing.T) {
+		db, mock := newSQLMock(t)
+		repo := newUsageLogRepositoryWithSQL(nil, db)
+		mock.ExpectQuery(`usage_dashboard_aggregation_watermark`).
+			WillReturnRows(sqlmock.NewRows(coverageColumns).AddRow(watermark, start, start))
+
+		source, args, err := repo.usageRollupSource(context.Background(), start, end, false)
+		require.NoError(t, err)
+		require.NotContains(t, source, "usage_rollup_daily")
+		require.Contains(t, source, "FROM usage_rollup_hourly")
+		require.Len(t, args, 4)
+	})
+
+	t.Run("rollups not covering start fall back", func(t *testing.T) {
+		db, mock := newSQLMock(t)
+		repo := newUsageLogRepositoryWithSQL(nil, db)
+		mock.ExpectQuery(`usage_dashboard_aggregation_watermark`).
+			WillReturnRows(sqlmock.NewRows(coverageColumns).AddRow(watermark, start.AddDate(0, 0, 2), nil))
+
+		source, _, err := repo.usageRollupSource(context.Background(), start, end, true)
+		require.NoError(t, err)
+		require.Empty(t, source)
+	})
+
+	t.Run("unaligned start skips coverage query", func(t *testing.T) {
+		db, mock := newSQLMock(t)
+		repo := newUsageLogRepositoryWithSQL(nil, db)
+
+		source, _, err := repo.usageRollupSource(context.Background(), start.Add(17*time.Minute), end, true)
+		require.NoError(t, err)
+		require.Empty(t, source)
+		require.NoError(t, mock.ExpectationsWereMet())
+	})
+}
@@ -0,0 +1,136 @@
+package repository
+
+import (
+	"context"
+	"encoding/json"
+	"fmt"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/service"
+	"github.com/redis/go-redis/v9"
+)
+
+const (
+	webSessionKeyPrefix     = "web_session:"
+	userWebSessionsPrefix   = "user_web_sessions:"
+	userWebSessionsSetSlack = time.Hour
+)
+
+// webSessionKey generates the Redis key for a web session.
+func webSessionKey(sessionID string) string {
+	return webSessionKeyPrefix + sessionID
+}
+
+// userWebSessionsKey generates the Redis key for user's session set.
+func userWebSessionsKey(userID int64) string {
+	return fmt.Sprintf("%s%d", userWebSessionsPrefix, userID)
+}
+
+type webSessionStore struct {
+	rdb *redis.Client
+}
+
+// NewWebSessionStore creates a new WebSessionStore implementation.
+func NewWebSessionStore(rdb *redis.Client) service.WebSessionStore {
+	return &webSessionStore{rdb: rdb}
+}
+
+func (s *webSessionStore) SaveSession(ctx context.Context, session *service.WebSession, ttl time.Duration) error {
+	if ttl <= 0 {
+		return nil
+	}
+	val, err := json.Marshal(session)
+	if err != nil {
+		return fmt.Errorf("marshal web session: %w", err)
+	}
+
+	setKey := userWebSessionsKey(session.UserID)
+	setTTL := time.Until(session.ExpiresAt) + userWebSessionsSetSlack
+	pipe := s.rdb.TxPipeline()
+	pipe.Set(ctx, webSessionKey(session.ID), val, ttl)
+	pipe.SAdd(ctx, setKey, session.ID)
+	// 集合的 TTL 只延长不缩短（按会话绝对过期时间），保证覆盖该用户所有会话（需要 Redis 7+）
+	pipe.ExpireNX(ctx, setKey, setTTL)
+	pipe.ExpireGT(ctx, setKey, setTTL)
+	_, err = pipe.Exec(ctx)
+	return err
+}
+
+func (s *webSessionStore) GetSession(ctx context.Context, sessionID string) (*service.WebSession, error) {
+	val, err := s.rdb.Get(ctx, webSessionKey(sessionID)).Result()
+	if err != nil {
+		if err == redis.Nil {
+			return nil, service.ErrWebSessionNotFound
+		}
+		return nil, err
+	}
+	var session service.WebSession
+	if err := json.Unmarshal([]byte(val), &session); err != nil {
+		return nil, fmt.Errorf("unmarshal web session: %w", err)
+	}
+	return &session, nil
+}
+
+func (s *webSessionStore) DeleteSession(ctx context.Context, userID int64, sessionID string) error {
+	pipe := s.rdb.Pipeline()
+	pipe.Del(ctx, webSessionKey(sessionID))
+	pipe.SRem(ctx, userWebSessionsKey(userID), sessionID)
+	_, err := pipe.Exec(ctx)
+	return err
+}
+
+func (s *webSessionStore) ListUserSessions(ctx context.Context, userID int64) ([]*service.WebSession, error) {
+	setKey := userWebSessionsKey(userID)
+	ids, err := s.rdb.SMembers(ctx, setKey).Result()
+	if err != nil && err != redis.Nil {
+		return nil, fmt.Errorf("get user web sessions: %w", err)
+	}
+	sessions := make([]*service.WebSession, 0, len(ids))
+	if len(ids) == 0 {
+		return sessions, nil
+	}
+
+	keys := make([]string, 0, len(ids))
+	for _, id := range ids {
+		keys = append(keys, webSessionKey(id))
+	}
+	vals, err := s.rdb.MGet(ctx, keys...).Result()
+	if err != nil {
+		return nil, fmt.Errorf("mget web sessions: %w", err)
+	}
+
+	stale := make([]any, 0)
+	for i, v := range vals {
+		raw, ok := v.(string)
+		if !ok {
+			// 会话已过期，顺便清理集合中的残留 ID
+			stale = append(stale, ids[i])
+			continue
+		}
+		var session service.WebSession
+		if err := json.Unmarshal([]byte(raw), &session); err != nil {
+			continue
+		}
+		sessions = append(sessions, &session)
+	}
+	if len(stale) > 0 {
+		_ = s.rdb.SRem(ctx, setKey, stale...).Err()
+	}
+	return sessions, nil
+}
+
+func (s *webSessionStore) DeleteUserSessions(ctx context.Context, userID int64) error {
+	setKey := userWebSessionsKey(userID)
+	ids, err := s.rdb.SMembers(ctx, setKey).Result()
+	if err != nil && err != redis.Nil {
+		return fmt.Errorf("get user web sessions: %w", err)
+	}
+
+	pipe := s.rdb.Pipeline()
+	for _, id := range ids {
+		pipe.Del(ctx, webSessionKey(id))
+	}
+	pipe.Del(ctx, setKey)
+	_, err = pipe.Exec(ctx)
+	return err
+}
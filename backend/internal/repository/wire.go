@@ -69,10 +69,36 @@ var ProviderSet = wire.NewSet(
 	NewUserAttributeDefinitionRepository,
 	NewUserAttributeValueRepository,
 	NewUserGroupRateRepository,
+	NewTotpBackupCodeRepository,
+	NewAPIKeyRotationRepository,
+	NewTrashRepository,
+	NewRequestLogRepository,
+	NewDataSubjectRepository,
+	NewCronJobStateRepository,
+	NewAPIKeyPolicyRepository,
+	NewAdminListRepository,
 	NewErrorPassthroughRepository,
+	NewFeatureFlagRepository,
+	NewSpendingCapRepository,
+	NewStatementRepository,
+	NewPaymentRepository,
+	NewAdminUserRepository,
+	NewTeamRepository,
+	NewNotificationRepository,
+	NewTrafficMirrorRepository,
+	NewModelCanaryRouteRepository,
+	NewAccountHealthRepository,
+	NewSemanticCacheRepository,
+	NewConversationRepository,
+	NewGatewayFileRepository,
+	NewPromptTemplateRepository,
+	NewAccountShardRepository,
+	NewRoutingRuleRepository,
+	NewEventOutboxRepository,
 
 	// Cache implementations
 	NewGatewayCache,
+	NewRemoteImageCache,
 	NewBillingCache,
 	NewAPIKeyCache,
 	NewTempUnschedCache,
@@ -90,13 +116,35 @@ var ProviderSet = wire.NewSet(
 	NewProxyLatencyCache,
 	NewTotpCache,
 	NewRefreshTokenCache,
+	NewWebSessionStore,
+	NewJobQueue,
+	NewCronJobLocker,
+	NewLeaderLock,
+	NewAccountStateLocker,
 	NewErrorPassthroughCache,
+	NewFeatureFlagCache,
+	NewSpendingCapCache,
+	NewCreditHoldCache,
+	NewModelCanaryStatsCache,
+	NewTelegramStateCache,
 
 	// Encryptors
 	NewAESEncryptor,
+	NewCredentialCipher,
 
 	// HTTP service ports (DI Strategy A: return interface directly)
 	NewTurnstileVerifier,
+	NewStripeClient,
+	NewTelegramClient,
+	NewDiscordWebhookClient,
+	NewSessionRefreshHookClient,
+	NewChallengeHookClient,
+	NewAnalyticsSinkClient,
+	NewEventPublisher,
+	NewClickHouseUsageStore,
+	NewSemanticCacheEmbedder,
+	NewModerationClient,
+	NewRemoteImageFetcher,
 	ProvidePricingRemoteClient,
 	ProvideGitHubReleaseClient,
 	NewProxyExitInfoProber,
@@ -104,12 +152,15 @@ var ProviderSet = wire.NewSet(
 	NewClaudeOAuthClient,
 	NewHTTPUpstream,
 	NewOpenAIOAuthClient,
+	NewCopilotAuthClient,
 	NewGeminiOAuthClient,
 	NewGeminiCliCodeAssistClient,
 	NewGeminiDriveClient,
+	NewObjectStorage,
 
 	ProvideEnt,
 	ProvideSQLDB,
+	ProvideReadDB,
 	ProvideRedis,
 )
 
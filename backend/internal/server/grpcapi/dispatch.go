@@ -0,0 +1,370 @@
+package grpcapi
+
+import (
+	"bytes"
+	"context"
+	"encoding/json"
+	"net/http"
+	"strings"
+
+	gatewayv1 "github.com/Wei-Shaw/sub2api/proto/gateway/v1"
+
+	"google.golang.org/grpc/codes"
+	"google.golang.org/grpc/metadata"
+	"google.golang.org/grpc/peer"
+	"google.golang.org/grpc/status"
+)
+
+const (
+	messagesPath = "/v1/messages"
+	// defaultMaxTokens 请求未指定 max_tokens 时使用（Messages API 要求必填）
+	defaultMaxTokens = 4096
+	userAgent        = "sub2api-grpc/1.0"
+)
+
+// forwardedMetadata 转为 HTTP 请求头的 metadata（认证与请求追踪）
+var forwardedMetadata = []string{"authorization", "x-api-key", "x-request-id", "anthropic-beta"}
+
+type anthropicMessage struct {
+	Role    string `json:"role"`
+	Content string `json:"content"`
+}
+
+type anthropicRequest struct {
+	Model         string             `json:"model"`
+	MaxTokens     int32              `json:"max_tokens"`
+	System        string             `json:"system,omitempty"`
+	Messages      []anthropicMessage `json:"messages"`
+	Temperature   *float64           `json:"temperature,omitempty"`
+	TopP          *float64           `json:"top_p,omitempty"`
+	StopSequences []string           `json:"stop_sequences,omitempty"`
+	Stream        bool               `json:"stream,omitempty"`
+}
+
+type anthropicUsage struct {
+	InputTokens              int32 `json:"input_tokens"`
+	OutputTokens             int32 `json:"output_tokens"`
+	CacheCreationInputTokens int32 `json:"cache_creation_input_tokens"`
+	CacheReadInputTokens     int32 `json:"cache_read_input_tokens"`
+}
+
+type anthropicResponse struct {
+	ID      string `json:"id"`
+	Model   string `json:"model"`
+	Content []struct {
+		Type string `json:"type"`
+		Text string `json:"text"`
+	} `json:"content"`
+	StopReason string          `json:"stop_reason"`
+	Usage      *anthropicUsage `json:"usage"`
+}
+
+type anthropicStreamEvent struct {
+	Type    string             `json:"type"`
+	Message *anthropicResponse `json:"message"`
+	Delta   struct {
+		Type       string `json:"type"`
+		Text       string `json:"text"`
+		StopReason string `json:"stop_reason"`
+	} `json:"delta"`
+	Usage *anthropicUsage `json:"usage"`
+	Error *struct {
+		Type    string `json:"type"`
+		Message string `json:"message"`
+	} `json:"error"`
+}
+
+// newMessagesRequest 将 gRPC 请求转换为 /v1/messages HTTP 请求
+func newMessagesRequest(ctx context.Context, req *gatewayv1.ChatCompletionRequest, stream bool) (*http.Request, error) {
+	if strings.TrimSpace(req.Model) == "" {
+		return nil, status.Error(codes.InvalidArgument, "model is required")
+	}
+	body := anthropicRequest{
+		Model:         req.Model,
+		MaxTokens:     req.MaxTokens,
+		Temperature:   req.Temperature,
+		TopP:          req.TopP,
+		StopSequences: req.Stop,
+		Stream:        stream,
+	}
+	if body.MaxTokens <= 0 {
+		body.MaxTokens = defaultMaxTokens
+	}
+	var system []string
+	for _, m := range req.Messages {
+		switch m.Role {
+		case "system":
+			system = append(system, m.Content)
+		case "user", "assistant":
+			body.Messages = append(body.Messages, anthropicMessage{Role: m.Role, Content: m.Content})
+		default:
+			return nil, status.Errorf(codes.InvalidArgument, "unsupported message role %q", m.Role)
+		}
+	}
+	if len(body.Messages) == 0 {
+		return nil, status.Error(codes.InvalidArgument, "at least one user or assistant message is required")
+	}
+	body.System = strings.Join(system, "\n\n")
+
+	payload, err := json.Marshal(body)
+	if err != nil {
+		return nil, status.Error(codes.Internal, "encode request")
+	}
+	httpReq, err := http.NewRequestWithContext(ctx, http.MethodPost, messagesPath, bytes.NewReader(payload))
+	if err != nil {
+		return nil, status.Error(codes.Internal, "create request")
+	}
+	httpReq.Header.Set("Content-Type", "application/json")
+	httpReq.Header.Set("User-Agent", userAgent)
+	httpReq.Header.Set("anthropic-version", "2023-06-01")
+	if md, ok := metadata.FromIncomingContext(ctx); ok {
+		for _, key := range forwardedMetadata {
+			if values := md.Get(key); len(values) > 0 {
+				httpReq.Header.Set(key, values[0])
+			}
+		}
+	}
+	if p, ok := peer.FromContext(ctx); ok && p.Addr != nil {
+		httpReq.RemoteAddr = p.Addr.String()
+	}
+	return httpReq, nil
+}
+
+func parseMessagesResponse(body []byte) (*gatewayv1.ChatCompletionResponse, error) {
+	var resp anthropicResponse
+	if err := json.Unmarshal(body, &resp); err != nil {
+		return nil, status.Error(codes.Internal, "invalid upstream response")
+	}
+	out := &gatewayv1.ChatCompletionResponse{Id: resp.ID, Model: resp.Model, FinishReason: resp.StopReason, Usage: toUsage(resp.Usage)}
+	var text strings.Builder
+	for _, block := range resp.Content {
+		if block.Type == "text" {
+			_, _ = text.WriteString(block.Text)
+		}
+	}
+	out.Content = text.String()
+	return out, nil
+}
+
+func toUsage(u *anthropicUsage) *gatewayv1.Usage {
+	if u == nil {
+		return nil
+	}
+	return &gatewayv1.Usage{
+		InputTokens:              u.InputTokens,
+		OutputTokens:             u.OutputTokens,
+		CacheCreationInputTokens: u.CacheCreationInputTokens,
+		CacheReadInputTokens:     u.CacheReadInputTokens,
+	}
+}
+
+// streamRelay 把网关写出的 SSE 事件转换为 ChatCompletionChunk
+type streamRelay struct {
+	send func(*gatewayv1.ChatCompletionChunk) error
+
+	buf       []byte
+	sawEvents bool
+	done      bool
+	id        string
+	model     string
+	finish    string
+	usage     anthropicUsage
+	err       error
+}
+
+// write 接收一段响应体，按空行切分出完整事件
+func (r *streamRelay) write(p []byte) error {
+	if r.err != nil {
+		return r.err
+	}
+	r.buf = append(r.buf, p...)
+	for {
+		i, sep := bytes.Index(r.buf, []byte("\n\n")), 2
+		if j := bytes.Index(r.buf, []byte("\r\n\r\n")); j >= 0 && (i < 0 || j < i) {
+			i, sep = j, 4
+		}
+		if i < 0 {
+			return nil
+		}
+		event := r.buf[:i]
+		r.buf = r.buf[i+sep:]
+		if err := r.handleEvent(event); err != nil {
+			r.err = err
+			return err
+		}
+	}
+}
+
+func (r *streamRelay) handleEvent(raw []byte) error {
+	var data []byte
+	for _, line := range bytes.Split(raw, []byte("\n")) {
+		line = bytes.TrimRight(line, "\r")
+		if rest, ok := bytes.CutPrefix(line, []byte("data:")); ok {
+			data = append(data, bytes.TrimSpace(rest)...)
+		}
+	}
+	if len(data) == 0 {
+		return nil
+	}
+	var ev anthropicStreamEvent
+	if err := json.Unmarshal(data, &ev); err != nil {
+		return nil
+	}
+	r.sawEvents = true
+
+	switch ev.Type {
+	case "message_start":
+		if ev.Message != nil {
+			r.id, r.model = ev.Message.ID, ev.Message.Model
+			r.mergeUsage(ev.Message.Usage)
+		}
+	case "content_block_delta":
+		if ev.Delta.Type == "text_delta" && ev.Delta.Text != "" {
+			return r.send(&gatewayv1.ChatCompletionChunk{Id: r.id, Model: r.model, Delta: ev.Delta.Text})
+		}
+	case "message_delta":
+		if ev.Delta.StopReason != "" {
+			r.finish = ev.Delta.StopReason
+		}
+		r.mergeUsage(ev.Usage)
+	case "message_stop":
+		r.done = true
+		return r.send(&gatewayv1.ChatCompletionChunk{Id: r.id, Model: r.model, FinishReason: r.finish, Usage: toUsage(&r.usage)})
+	case "error":
+		msg := "upstream stream error"
+		if ev.Error != nil && ev.Error.Message != "" {
+			msg = ev.Error.Message
+		}
+		return status.Error(codes.Unavailable, msg)
+	}
+	return nil
+}
+
+// mergeUsage message_delta 只携带增量字段，非零值覆盖
+func (r *streamRelay) mergeUsage(u *anthropicUsage) {
+	if u == nil {
+		return
+	}
+	if u.InputTokens > 0 {
+		r.usage.InputTokens = u.InputTokens
+	}
+	if u.OutputTokens > 0 {
+		r.usage.OutputTokens = u.OutputTokens
+	}
+	if u.CacheCreationInputTokens > 0 {
+		r.usage.CacheCreationInputTokens = u.CacheCreationInputTokens
+	}
+	if u.CacheReadInputTokens > 0 {
+		r.usage.CacheReadInputTokens = u.CacheReadInputTokens
+	}
+}
+
+// finish 在网关处理结束后调用；body 为未按 SSE 解析的响应体（网关返回了非流式 JSON 时）
+func (r *streamRelay) finish(body []byte) error {
+	if r.err != nil {
+		return r.err
+	}
+	if r.done {
+		return nil
+	}
+	if !r.sawEvents && len(body) > 0 {
+		resp, err := parseMessagesResponse(body)
+		if err != nil {
+			return err
+		}
+		return r.send(&gatewayv1.ChatCompletionChunk{Id: resp.Id, Model: resp.Model, Delta: resp.Content, FinishReason: resp.FinishReason, Usage: resp.Usage})
+	}
+	return status.Error(codes.Unavailable, "stream ended before completion")
+}
+
+// responseWriter 接收网关的 HTTP 响应。流式模式下 2xx SSE 响应体逐段交给 relay，
+// 其余情况（错误响应、非流式响应）缓存在 body 中
+type responseWriter struct {
+	header http.Header
+	status int
+	relay  *streamRelay
+	body   bytes.Buffer
+}
+
+func newResponseWriter(relay *streamRelay) *responseWriter {
+	return &responseWriter{header: make(http.Header), relay: relay}
+}
+
+func (w *responseWriter) Header() http.Header {
+	return w.header
+}
+
+func (w *responseWriter) WriteHeader(code int) {
+	if w.status == 0 {
+		w.status = code
+	}
+}
+
+func (w *responseWriter) Write(p []byte) (int, error) {
+	if w.status == 0 {
+		w.status = http.StatusOK
+	}
+	if w.relay != nil && w.status < 300 && strings.HasPrefix(w.header.Get("Content-Type"), "text/event-stream") {
+		if err := w.relay.write(p); err != nil {
+			return 0, err
+		}
+		return len(p), nil
+	}
+	return w.body.Write(p)
+}
+
+// Flush 实现 http.Flusher；流式事件在 Write 时已转发
+func (w *responseWriter) Flush() {}
+
+// statusError 将 HTTP 错误响应转换为 gRPC 状态
+func (w *responseWriter) statusError() error {
+	if w.status == 0 || w.status < 300 {
+		return nil
+	}
+	return status.Error(codeForHTTPStatus(w.status), errorMessage(w.body.Bytes(), w.status))
+}
+
+func codeForHTTPStatus(code int) codes.Code {
+	switch code {
+	case http.StatusBadRequest, http.StatusRequestEntityTooLarge:
+		return codes.InvalidArgument
+	case http.StatusUnauthorized:
+		return codes.Unauthenticated
+	case http.StatusPaymentRequired, http.StatusForbidden:
+		return codes.PermissionDenied
+	case http.StatusNotFound:
+		return codes.NotFound
+	case http.StatusRequestTimeout, http.StatusGatewayTimeout:
+		return codes.DeadlineExceeded
+	case http.StatusTooManyRequests:
+		return codes.ResourceExhausted
+	case 499:
+		return codes.Canceled
+	case http.StatusNotImplemented:
+		return codes.Unimplemented
+	case http.StatusBadGateway, http.StatusServiceUnavailable, 529:
+		return codes.Unavailable
+	case http.StatusInternalServerError:
+		return codes.Internal
+	}
+	return codes.Unknown
+}
+
+// errorMessage 提取 {"error": {"message"}} 或 {"message"} 格式的错误信息
+func errorMessage(body []byte, code int) string {
+	var parsed struct {
+		Message string `json:"message"`
+		Error   *struct {
+			Message string `json:"message"`
+		} `json:"error"`
+	}
+	if json.Unmarshal(body, &parsed) == nil {
+		if parsed.Error != nil && parsed.Error.Message != "" {
+			return parsed.Error.Message
+		}
+		if parsed.Message != "" {
+			return parsed.Message
+		}
+	}
+	return http.StatusText(code)
+}
@@ -0,0 +1,185 @@
+//go:build unit
+
+package grpcapi
+
+import (
+	"context"
+	"encoding/json"
+	"io"
+	"net"
+	"net/http"
+	"testing"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	gatewayv1 "github.com/Wei-Shaw/sub2api/proto/gateway/v1"
+	"github.com/gin-gonic/gin"
+	"github.com/stretchr/testify/require"
+	"google.golang.org/grpc"
+	"google.golang.org/grpc/codes"
+	"google.golang.org/grpc/credentials/insecure"
+	"google.golang.org/grpc/metadata"
+	"google.golang.org/grpc/status"
+	"google.golang.org/grpc/test/bufconn"
+	"google.golang.org/protobuf/proto"
+)
+
+// fakeChunkSender 记录发送的 chunk；未覆盖的 grpc.ServerStream 方法不会被调用
+type fakeChunkSender struct {
+	grpc.ServerStream
+
+	ctx    context.Context
+	chunks []*gatewayv1.ChatCompletionChunk
+}
+
+func (s *fakeChunkSender) Context() context.Context { return s.ctx }
+
+func (s *fakeChunkSender) Send(chunk *gatewayv1.ChatCompletionChunk) error {
+	s.chunks = append(s.chunks, chunk)
+	return nil
+}
+
+func newTestEngine(t *testing.T, handle func(c *gin.Context, body anthropicRequest)) *gin.Engine {
+	gin.SetMode(gin.TestMode)
+	r := gin.New()
+	r.POST(messagesPath, func(c *gin.Context) {
+		raw, err := io.ReadAll(c.Request.Body)
+		require.NoError(t, err)
+		var body anthropicRequest
+		require.NoError(t, json.Unmarshal(raw, &body))
+		handle(c, body)
+	})
+	return r
+}
+
+func TestServer_ServesGeneratedGatewayService(t *testing.T) {
+	engine := newTestEngine(t, func(c *gin.Context, body anthropicRequest) {
+		// optional 字段保留是否设置：temperature 透传，top_p 未设置
+		require.NotNil(t, body.Temperature)
+		require.Equal(t, 0.5, *body.Temperature)
+		require.Nil(t, body.TopP)
+		require.Equal(t, []string{"END"}, body.StopSequences)
+		c.JSON(http.StatusOK, gin.H{
+			"id":          "msg_1",
+			"model":       body.Model,
+			"content":     []gin.H{{"type": "text", "text": "pong"}},
+			"stop_reason": "end_turn",
+		})
+	})
+	srv := NewServer(&config.Config{}, engine)
+	lis := bufconn.Listen(1 << 20)
+	go func() { _ = srv.server.Serve(lis) }()
+	t.Cleanup(srv.server.Stop)
+
+	conn, err := grpc.NewClient("passthrough:///bufnet",
+		grpc.WithContextDialer(func(ctx context.Context, _ string) (net.Conn, error) { return lis.DialContext(ctx) }),
+		grpc.WithTransportCredentials(insecure.NewCredentials()),
+	)
+	require.NoError(t, err)
+	t.Cleanup(func() { _ = conn.Close() })
+
+	resp, err := gatewayv1.NewGatewayClient(conn).ChatCompletion(context.Background(), &gatewayv1.ChatCompletionRequest{
+		Model:       "claude-sonnet-4",
+		Messages:    []*gatewayv1.ChatMessage{{Role: "user", Content: "ping"}},
+		Temperature: proto.Float64(0.5),
+		Stop:        []string{"END"},
+	})
+	require.NoError(t, err)
+	require.Equal(t, "msg_1", resp.GetId())
+	require.Equal(t, "claude-sonnet-4", resp.GetModel())
+	require.Equal(t, "pong", resp.GetContent())
+	require.Equal(t, "end_turn", resp.GetFinishReason())
+}
+
+func TestChatCompletion_ForwardsToMessagesRoute(t *testing.T) {
+	var gotHeader http.Header
+	engine := newTestEngine(t, func(c *gin.Context, body anthropicRequest) {
+		gotHeader = c.Request.Header.Clone()
+		require.Equal(t, "claude-sonnet-4", body.Model)
+		require.Equal(t, int32(defaultMaxTokens), body.MaxTokens)
+		require.Equal(t, "a\n\nb", body.System)
+		require.Equal(t, []anthropicMessage{{Role: "user", Content: "hi"}}, body.Messages)
+		require.False(t, body.Stream)
+		c.JSON(http.StatusOK, gin.H{
+			"id":          "msg_1",
+			"model":       "claude-sonnet-4",
+			"content":     []gin.H{{"type": "text", "text": "Hello"}, {"type": "text", "text": " world"}},
+			"stop_reason": "end_turn",
+			"usage":       gin.H{"input_tokens": 5, "output_tokens": 2},
+		})
+	})
+	svc := &gatewayService{handler: engine}
+	ctx := metadata.NewIncomingContext(context.Background(), metadata.Pairs("authorization", "Bearer sk-test", "cookie", "ignored"))
+
+	resp, err := svc.ChatCompletion(ctx, &gatewayv1.ChatCompletionRequest{
+		Model:    "claude-sonnet-4",
+		Messages: []*gatewayv1.ChatMessage{{Role: "system", Content: "a"}, {Role: "system", Content: "b"}, {Role: "user", Content: "hi"}},
+	})
+	require.NoError(t, err)
+	require.Equal(t, "Bearer sk-test", gotHeader.Get("Authorization"))
+	require.Empty(t, gotHeader.Get("Cookie"))
+	require.True(t, proto.Equal(&gatewayv1.ChatCompletionResponse{
+		Id:           "msg_1",
+		Model:        "claude-sonnet-4",
+		Content:      "Hello world",
+		FinishReason: "end_turn",
+		Usage:        &gatewayv1.Usage{InputTokens: 5, OutputTokens: 2},
+	}, resp), "unexpected response: %v", resp)
+}
+
+func TestChatCompletion_MapsErrors(t *testing.T) {
+	engine := newTestEngine(t, func(c *gin.Context, body anthropicRequest) {
+		c.JSON(http.StatusUnauthorized, gin.H{"code": "INVALID_API_KEY", "message": "Invalid API key"})
+	})
+	svc := &gatewayService{handler: engine}
+
+	_, err := svc.ChatCompletion(context.Background(), &gatewayv1.ChatCompletionRequest{Model: "m", Messages: []*gatewayv1.ChatMessage{{Role: "user", Content: "hi"}}})
+	require.Equal(t, codes.Unauthenticated, status.Code(err))
+	require.Equal(t, "Invalid API key", status.Convert(err).Message())
+
+	_, err = svc.ChatCompletion(context.Background(), &gatewayv1.ChatCompletionRequest{Model: "m", Messages: []*gatewayv1.ChatMessage{{Role: "tool", Content: "x"}}})
+	require.Equal(t, codes.InvalidArgument, status.Code(err))
+}
+
+func TestStreamChatCompletion_RelaysSSE(t *testing.T) {
+	engine := newTestEngine(t, func(c *gin.Context, body anthropicRequest) {
+		require.True(t, body.Stream)
+		c.Header("Content-Type", "text/event-stream")
+		c.Status(http.StatusOK)
+		events := []string{
+			"event: message_start\ndata: {\"type\":\"message_start\",\"message\":{\"id\":\"msg_2\",\"model\":\"claude-sonnet-4\",\"usage\":{\"input_tokens\":9,\"cache_read_input_tokens\":4}}}\n\n",
+			"event: ping\ndata: {\"type\":\"ping\"}\n\n",
+			"event: content_block_delta\ndata: {\"type\":\"content_block_delta\",\"delta\":{\"type\":\"text_delta\",\"text\":\"Hel\"}}\n\nevent: content_block_delta\ndata: {\"type\":\"content_block_",
+			"delta\",\"delta\":{\"type\":\"text_delta\",\"text\":\"lo\"}}\n\n",
+			"event: message_delta\ndata: {\"type\":\"message_delta\",\"delta\":{\"stop_reason\":\"end_turn\"},\"usage\":{\"output_tokens\":2}}\n\n",
+			"event: message_stop\ndata: {\"type\":\"message_stop\"}\n\n",
+		}
+		for _, ev := range events {
+			_, _ = c.Writer.WriteString(ev)
+			c.Writer.Flush()
+		}
+	})
+	svc := &gatewayService{handler: engine}
+	sender := &fakeChunkSender{ctx: context.Background()}
+
+	err := svc.StreamChatCompletion(&gatewayv1.ChatCompletionRequest{Model: "claude-sonnet-4", Messages: []*gatewayv1.ChatMessage{{Role: "user", Content: "hi"}}}, sender)
+	require.NoError(t, err)
+	require.Len(t, sender.chunks, 3)
+	require.Equal(t, "Hel", sender.chunks[0].Delta)
+	require.Equal(t, "msg_2", sender.chunks[0].Id)
+	require.Equal(t, "lo", sender.chunks[1].Delta)
+	require.Equal(t, "end_turn", sender.chunks[2].FinishReason)
+	require.True(t, proto.Equal(&gatewayv1.Usage{InputTokens: 9, OutputTokens: 2, CacheReadInputTokens: 4}, sender.chunks[2].Usage))
+}
+
+func TestStreamChatCompletion_StreamErrorEvent(t *testing.T) {
+	engine := newTestEngine(t, func(c *gin.Context, body anthropicRequest) {
+		c.Header("Content-Type", "text/event-stream")
+		c.Status(http.StatusOK)
+		_, _ = c.Writer.WriteString("event: error\ndata: {\"type\":\"error\",\"error\":{\"type\":\"overloaded_error\",\"message\":\"Overloaded\"}}\n\n")
+	})
+	svc := &gatewayService{handler: engine}
+
+	err := svc.StreamChatCompletion(&gatewayv1.ChatCompletionRequest{Model: "m", Messages: []*gatewayv1.ChatMessage{{Role: "user", Content: "hi"}}}, &fakeChunkSender{ctx: context.Background()})
+	require.Equal(t, codes.Unavailable, status.Code(err))
+	require.Equal(t, "Overloaded", status.Convert(err).Message())
+}
@@ -0,0 +1,96 @@
+// Package grpcapi 提供网关的 gRPC 接口（契约见 proto/gateway/v1/gateway.proto）。
+//
+// gRPC 请求被转换为 HTTP 请求，在进程内交给网关路由（/v1/messages）处理，
+// 因此 API Key 认证、分组调度、限流、计费与请求日志与 HTTP 接口完全一致；
+// 流式响应由 SSE 事件逐个转换为 ChatCompletionChunk。
+package grpcapi
+
+import (
+	"context"
+	"net"
+	"net/http"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	gatewayv1 "github.com/Wei-Shaw/sub2api/proto/gateway/v1"
+
+	"google.golang.org/grpc"
+)
+
+// Server 网关 gRPC 服务器
+type Server struct {
+	addr   string
+	server *grpc.Server
+}
+
+// NewServer 创建 gRPC 服务器；handler 为 HTTP 网关路由（gin.Engine）
+func NewServer(cfg *config.Config, handler http.Handler) *Server {
+	var opts []grpc.ServerOption
+	if cfg.Gateway.MaxBodySize > 0 {
+		opts = append(opts, grpc.MaxRecvMsgSize(int(cfg.Gateway.MaxBodySize)))
+	}
+	s := &Server{addr: cfg.GRPC.Address(), server: grpc.NewServer(opts...)}
+	gatewayv1.RegisterGatewayServer(s.server, &gatewayService{handler: handler})
+	return s
+}
+
+// Addr 监听地址
+func (s *Server) Addr() string {
+	return s.addr
+}
+
+// ListenAndServe 监听并处理请求，直到 Shutdown 被调用
+func (s *Server) ListenAndServe() error {
+	lis, err := net.Listen("tcp", s.addr)
+	if err != nil {
+		return err
+	}
+	return s.server.Serve(lis)
+}
+
+// Shutdown 停止接收新请求并等待进行中的请求结束；ctx 到期后强制关闭
+func (s *Server) Shutdown(ctx context.Context) {
+	done := make(chan struct{})
+	go func() {
+		s.server.GracefulStop()
+		close(done)
+	}()
+	select {
+	case <-done:
+	case <-ctx.Done():
+		s.server.Stop()
+	}
+}
+
+// gatewayService 将 gRPC 调用转交 HTTP 网关
+type gatewayService struct {
+	gatewayv1.UnimplementedGatewayServer
+
+	handler http.Handler
+}
+
+func (s *gatewayService) ChatCompletion(ctx context.Context, req *gatewayv1.ChatCompletionRequest) (*gatewayv1.ChatCompletionResponse, error) {
+	httpReq, err := newMessagesRequest(ctx, req, false)
+	if err != nil {
+		return nil, err
+	}
+	w := newResponseWriter(nil)
+	s.handler.ServeHTTP(w, httpReq)
+	if err := w.statusError(); err != nil {
+		return nil, err
+	}
+	return parseMessagesResponse(w.body.Bytes())
+}
+
+func (s *gatewayService) StreamChatCompletion(req *gatewayv1.ChatCompletionRequest, stream gatewayv1.Gateway_StreamChatCompletionServer) error {
+	httpReq, err := newMessagesRequest(stream.Context(), req, true)
+	if err != nil {
+		return err
+	}
+	relay := &streamRelay{send: stream.Send}
+	w := newResponseWriter(relay)
+	s.handler.ServeHTTP(w, httpReq)
+	if err := w.statusError(); err != nil {
+		return err
+	}
+	return relay.finish(w.body.Bytes())
+}
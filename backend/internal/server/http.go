@@ -8,6 +8,7 @@ import (
 
 	"github.com/Wei-Shaw/sub2api/internal/config"
 	"github.com/Wei-Shaw/sub2api/internal/handler"
+	"github.com/Wei-Shaw/sub2api/internal/server/grpcapi"
 	middleware2 "github.com/Wei-Shaw/sub2api/internal/server/middleware"
 	"github.com/Wei-Shaw/sub2api/internal/service"
 
@@ -22,6 +23,7 @@ import (
 var ProviderSet = wire.NewSet(
 	ProvideRouter,
 	ProvideHTTPServer,
+	ProvideGRPCServer,
 )
 
 // ProvideRouter 提供路由器
@@ -33,8 +35,13 @@ func ProvideRouter(
 	apiKeyAuth middleware2.APIKeyAuthMiddleware,
 	apiKeyService *service.APIKeyService,
 	subscriptionService *service.SubscriptionService,
+	policyService *service.APIKeyPolicyService,
 	opsService *service.OpsService,
+	requestLogService *service.RequestLogService,
 	settingService *service.SettingService,
+	maintenanceService *service.MaintenanceService,
+	prepaidCreditService *service.PrepaidCreditService,
+	routingRuleService *service.RoutingRuleService,
 	redisClient *redis.Client,
 ) *gin.Engine {
 	if cfg.Server.Mode == "release" {
@@ -56,7 +63,7 @@ func ProvideRouter(
 		}
 	}
 
-	return SetupRouter(r, handlers, jwtAuth, adminAuth, apiKeyAuth, apiKeyService, subscriptionService, opsService, settingService, cfg, redisClient)
+	return SetupRouter(r, handlers, jwtAuth, adminAuth, apiKeyAuth, apiKeyService, subscriptionService, policyService, opsService, requestLogService, settingService, maintenanceService, prepaidCreditService, routingRuleService, cfg, redisClient)
 }
 
 // ProvideHTTPServer 提供 HTTP 服务器
@@ -102,3 +109,11 @@ func ProvideHTTPServer(cfg *config.Config, router *gin.Engine) *http.Server {
 		// 不设置 ReadTimeout，因为大请求体可能需要较长时间读取
 	}
 }
+
+// ProvideGRPCServer 提供网关 gRPC 服务器；未启用时返回 nil
+func ProvideGRPCServer(cfg *config.Config, router *gin.Engine) *grpcapi.Server {
+	if !cfg.GRPC.Enabled {
+		return nil
+	}
+	return grpcapi.NewServer(cfg, router)
+}
@@ -6,6 +6,7 @@ import (
 	"errors"
 	"strings"
 
+	infraerrors "github.com/Wei-Shaw/sub2api/internal/pkg/errors"
 	"github.com/Wei-Shaw/sub2api/internal/service"
 
 	"github.com/gin-gonic/gin"
@@ -16,18 +17,24 @@ func NewAdminAuthMiddleware(
 	authService *service.AuthService,
 	userService *service.UserService,
 	settingService *service.SettingService,
+	webSessionService *service.WebSessionService,
+	adminUserService *service.AdminUserService,
 ) AdminAuthMiddleware {
-	return AdminAuthMiddleware(adminAuth(authService, userService, settingService))
+	return AdminAuthMiddleware(adminAuth(authService, userService, settingService, webSessionService, adminUserService))
 }
 
 // adminAuth 管理员认证中间件实现
 // 支持两种认证方式（通过不同的 header 区分）：
 // 1. Admin API Key: x-api-key: <admin-api-key>
-// 2. JWT Token: Authorization: Bearer <jwt-token> (需要管理员角色)
+// 2. JWT Token: Authorization: Bearer <jwt-token> (需要后台角色)
+// 3. Cookie 会话（web_session.enabled=true 时，需要后台角色）
+// 具体接口的角色权限由 AdminRolePermission 校验
 func adminAuth(
 	authService *service.AuthService,
 	userService *service.UserService,
 	settingService *service.SettingService,
+	webSessionService *service.WebSessionService,
+	adminUserService *service.AdminUserService,
 ) gin.HandlerFunc {
 	return func(c *gin.Context) {
 		// WebSocket upgrade requests cannot set Authorization headers in browsers.
@@ -36,7 +43,7 @@ func adminAuth(
 		//   Sec-WebSocket-Protocol: sub2api-admin, jwt.<token>
 		if isWebSocketUpgradeRequest(c) {
 			if token := extractJWTFromWebSocketSubprotocol(c); token != "" {
-				if !validateJWTForAdmin(c, token, authService, userService) {
+				if !validateJWTForAdmin(c, token, authService, userService, settingService, adminUserService) {
 					return
 				}
 				c.Next()
@@ -64,7 +71,7 @@ func adminAuth(
 					AbortWithError(c, 401, "UNAUTHORIZED", "Authorization required")
 					return
 				}
-				if !validateJWTForAdmin(c, token, authService, userService) {
+				if !validateJWTForAdmin(c, token, authService, userService, settingService, adminUserService) {
 					return
 				}
 				c.Next()
@@ -72,6 +79,32 @@ func adminAuth(
 			}
 		}
 
+		// 检查 Cookie 会话
+		if sessionToken := WebSessionTokenFromCookie(c, webSessionService); sessionToken != "" {
+			user, ok := authenticateWebSession(c, sessionToken, webSessionService, userService)
+			if !ok {
+				return
+			}
+			if !user.IsStaff() {
+				AbortWithError(c, 403, "FORBIDDEN", "Admin access required")
+				return
+			}
+			if !checkAdminTotpEnrollment(c, user, settingService) {
+				return
+			}
+			if !checkAdminPasswordRotation(c, user, adminUserService) {
+				return
+			}
+			c.Set(string(ContextKeyUser), AuthSubject{
+				UserID:      user.ID,
+				Concurrency: user.Concurrency,
+			})
+			c.Set(string(ContextKeyUserRole), user.Role)
+			c.Set("auth_method", "web_session")
+			c.Next()
+			return
+		}
+
 		// 无有效认证信息
 		AbortWithError(c, 401, "UNAUTHORIZED", "Authorization required")
 	}
@@ -156,6 +189,8 @@ func validateJWTForAdmin(
 	token string,
 	authService *service.AuthService,
 	userService *service.UserService,
+	settingService *service.SettingService,
+	adminUserService *service.AdminUserService,
 ) bool {
 	// 验证 JWT token
 	claims, err := authService.ValidateToken(token)
@@ -187,12 +222,19 @@ func validateJWTForAdmin(
 		return false
 	}
 
-	// 检查管理员权限
-	if !user.IsAdmin() {
+	// 检查后台角色
+	if !user.IsStaff() {
 		AbortWithError(c, 403, "FORBIDDEN", "Admin access required")
 		return false
 	}
 
+	if !checkAdminTotpEnrollment(c, user, settingService) {
+		return false
+	}
+	if !checkAdminPasswordRotation(c, user, adminUserService) {
+		return false
+	}
+
 	c.Set(string(ContextKeyUser), AuthSubject{
 		UserID:      user.ID,
 		Concurrency: user.Concurrency,
@@ -202,3 +244,27 @@ func validateJWTForAdmin(
 
 	return true
 }
+
+// checkAdminTotpEnrollment 强制 2FA 时，未绑定 TOTP 的管理员无法访问管理接口
+// 绑定入口位于 /api/v1/user/totp/*（仅需 JWT 认证），不受此限制
+func checkAdminTotpEnrollment(c *gin.Context, user *service.User, settingService *service.SettingService) bool {
+	if !settingService.IsAdminTotpRequired() || user.TotpEnabled {
+		return true
+	}
+	AbortWithError(c, 403, "TOTP_ENROLLMENT_REQUIRED", "Two-factor authentication must be enabled before accessing the admin console")
+	return false
+}
+
+// checkAdminPasswordRotation 临时密码或已过期的密码需先通过 /api/v1/user/password 修改后才能进入管理后台
+func checkAdminPasswordRotation(c *gin.Context, user *service.User, adminUserService *service.AdminUserService) bool {
+	err := adminUserService.CheckPasswordRotation(c.Request.Context(), user)
+	if err == nil {
+		return true
+	}
+	if infraerrors.IsForbidden(err) {
+		AbortWithError(c, 403, infraerrors.Reason(err), infraerrors.Message(err))
+		return false
+	}
+	AbortWithError(c, 500, "INTERNAL_ERROR", "Internal server error")
+	return false
+}
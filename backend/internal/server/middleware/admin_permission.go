@@ -0,0 +1,119 @@
+package middleware
+
+import (
+	"net/http"
+	"strings"
+
+	"github.com/Wei-Shaw/sub2api/internal/service"
+
+	"github.com/gin-gonic/gin"
+)
+
+// adminReadOnlyPostRoutes 以 POST 实现的只读查询，按完整路由模板精确匹配（不含 /api/v1/admin），
+// 同前缀下新增的写接口不会被误判为只读
+var adminReadOnlyPostRoutes = map[string]struct{}{
+	"/dashboard/users-usage":    {},
+	"/dashboard/api-keys-usage": {},
+	"/graphql":                  {},
+	"/routing-rules/evaluate":   {},
+}
+
+// adminWritePermissionRules 写操作所需的权限，按路由模板前缀匹配（不含 /api/v1/admin）。
+// 未匹配的写操作仅超级管理员可执行。
+var adminWritePermissionRules = []struct {
+	prefix string
+	perm   service.AdminPermission
+}{
+	// 财务
+	{"/users/:id/balance", service.AdminPermBilling},
+	{"/redeem-codes", service.AdminPermBilling},
+	{"/promo-codes", service.AdminPermBilling},
+	{"/subscriptions", service.AdminPermBilling},
+	{"/payments", service.AdminPermBilling},
+	{"/statements", service.AdminPermBilling},
+	{"/spending-caps", service.AdminPermBilling},
+	{"/api-keys/:id/credits", service.AdminPermBilling},
+
+	// 账号运维
+	{"/accounts", service.AdminPermAccounts},
+	{"/groups", service.AdminPermAccounts},
+	{"/proxies", service.AdminPermAccounts},
+	{"/openai", service.AdminPermAccounts},
+	{"/sora", service.AdminPermAccounts},
+	{"/gemini", service.AdminPermAccounts},
+	{"/antigravity", service.AdminPermAccounts},
+	{"/canary-routes", service.AdminPermAccounts},
+	{"/account-shards", service.AdminPermAccounts},
+	{"/account-shard-rules", service.AdminPermAccounts},
+	{"/account-health", service.AdminPermAccounts},
+	{"/routing-rules", service.AdminPermAccounts},
+	{"/api-keys/:id/tags", service.AdminPermAccounts},
+}
+
+// adminSuperuserOnlyPrefixes 读操作也仅超级管理员可访问的敏感接口：按返回的数据分类而非 HTTP 方法，
+// 包括明文凭证、可直接使用的兑换码、用户个人数据与批量导出
+var adminSuperuserOnlyPrefixes = []string{
+	"/settings/admin-api-key",
+	"/staff",
+	// 账号凭证（上游 OAuth Token / API Key）与代理密码的导入导出
+	"/accounts/data",
+	"/proxies/data",
+	// 未使用的兑换码
+	"/redeem-codes/export",
+	// 数据主体（GDPR）导出包
+	"/data-subject-requests/:id/download",
+	// 全量用量导出与实时日志（含请求内容）
+	"/usage/export",
+	"/ws/logs",
+}
+
+// AdminRolePermission 后台角色权限中间件：只读审计仅可查看，财务与账号运维只能修改各自负责的资源。
+// 必须在 AdminAuth 中间件之后使用。
+func AdminRolePermission() gin.HandlerFunc {
+	return func(c *gin.Context) {
+		role, ok := GetUserRoleFromContext(c)
+		if !ok {
+			AbortWithError(c, 401, "UNAUTHORIZED", "User not found in context")
+			return
+		}
+		if !service.AdminRoleAllows(role, requiredAdminPermission(c.Request.Method, c.FullPath())) {
+			AbortWithError(c, 403, "ADMIN_PERMISSION_DENIED", "Your admin role does not permit this operation")
+			return
+		}
+		c.Next()
+	}
+}
+
+func requiredAdminPermission(method, fullPath string) service.AdminPermission {
+	path := fullPath
+	if i := strings.Index(path, "/admin/"); i >= 0 {
+		path = path[i+len("/admin"):]
+	}
+	for _, prefix := range adminSuperuserOnlyPrefixes {
+		if hasRoutePrefix(path, prefix) {
+			return service.AdminPermSuperuser
+		}
+	}
+	switch method {
+	case http.MethodGet, http.MethodHead, http.MethodOptions:
+		return service.AdminPermRead
+	case http.MethodPost:
+		if _, ok := adminReadOnlyPostRoutes[path]; ok {
+			return service.AdminPermRead
+		}
+	}
+	for _, rule := range adminWritePermissionRules {
+		if hasRoutePrefix(path, rule.prefix) {
+			return rule.perm
+		}
+	}
+	return service.AdminPermSuperuser
+}
+
+// hasRoutePrefix 按路径段匹配前缀：/users/:id/balance 不匹配 /users/:id/balance-history
+func hasRoutePrefix(path, prefix string) bool {
+	if !strings.HasPrefix(path, prefix) {
+		return false
+	}
+	return len(path) == len(prefix) || path[len(prefix)] == '/'
+}
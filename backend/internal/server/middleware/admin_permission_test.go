@@ -0,0 +1,136 @@
+//go:build unit
+
+package middleware
+
+import (
+	"net/http"
+	"net/http/httptest"
+	"strings"
+	"testing"
+
+	"github.com/Wei-Shaw/sub2api/internal/service"
+	"github.com/gin-gonic/gin"
+	"github.com/stretchr/testify/require"
+)
+
+func TestRequiredAdminPermission(t *testing.T) {
+	cases := []struct {
+		method string
+		path   string
+		want   service.AdminPermission
+	}{
+		{http.MethodGet, "/api/v1/admin/users", service.AdminPermRead},
+		{http.MethodGet, "/api/v1/admin/users/:id/balance-history", service.AdminPermRead},
+		{http.MethodPost, "/api/v1/admin/users/:id/balance", service.AdminPermBilling},
+		{http.MethodPut, "/api/v1/admin/users/:id", service.AdminPermSuperuser},
+		{http.MethodPost, "/api/v1/admin/redeem-codes/generate", service.AdminPermBilling},
+		{http.MethodPost, "/api/v1/admin/api-keys/:id/credits", service.AdminPermBilling},
+		{http.MethodPut, "/api/v1/admin/api-keys/:id/policy", service.AdminPermSuperuser},
+		{http.MethodPost, "/api/v1/admin/accounts/:id/refresh", service.AdminPermAccounts},
+		{http.MethodDelete, "/api/v1/admin/proxies/:id", service.AdminPermAccounts},
+		{http.MethodPost, "/api/v1/admin/dashboard/users-usage", service.AdminPermRead},
+		{http.MethodPost, "/api/v1/admin/graphql", service.AdminPermRead},
+		{http.MethodPost, "/api/v1/admin/graphql/persisted", service.AdminPermSuperuser},
+		{http.MethodPut, "/api/v1/admin/graphql", service.AdminPermSuperuser},
+		{http.MethodPost, "/api/v1/admin/dashboard/users-usage/export", service.AdminPermSuperuser},
+		{http.MethodPost, "/api/v1/admin/routing-rules/evaluate", service.AdminPermRead},
+		{http.MethodPut, "/api/v1/admin/routing-rules/:id", service.AdminPermAccounts},
+		{http.MethodPut, "/api/v1/admin/api-keys/:id/tags", service.AdminPermAccounts},
+		{http.MethodPut, "/api/v1/admin/settings", service.AdminPermSuperuser},
+		{http.MethodGet, "/api/v1/admin/settings/admin-api-key", service.AdminPermSuperuser},
+		{http.MethodGet, "/api/v1/admin/staff", service.AdminPermSuperuser},
+		{http.MethodPost, "/api/v1/admin/unknown", service.AdminPermSuperuser},
+		{http.MethodGet, "/api/v1/admin/accounts/data", service.AdminPermSuperuser},
+		{http.MethodPost, "/api/v1/admin/accounts/data", service.AdminPermSuperuser},
+		{http.MethodGet, "/api/v1/admin/accounts/:id", service.AdminPermRead},
+		{http.MethodGet, "/api/v1/admin/redeem-codes", service.AdminPermRead},
+		{http.MethodGet, "/api/v1/admin/redeem-codes/export", service.AdminPermSuperuser},
+		{http.MethodGet, "/api/v1/admin/data-subject-requests/:id", service.AdminPermRead},
+		{http.MethodGet, "/api/v1/admin/data-subject-requests/:id/download", service.AdminPermSuperuser},
+		{http.MethodGet, "/api/v1/admin/ws/live", service.AdminPermRead},
+		{http.MethodGet, "/api/v1/admin/ws/logs", service.AdminPermSuperuser},
+	}
+	for _, tc := range cases {
+		require.Equal(t, tc.want, requiredAdminPermission(tc.method, tc.path), "%s %s", tc.method, tc.path)
+	}
+}
+
+func TestAdminRolePermission(t *testing.T) {
+	gin.SetMode(gin.TestMode)
+
+	newRouter := func(role string) *gin.Engine {
+		router := gin.New()
+		admin := router.Group("/api/v1/admin")
+		admin.Use(func(c *gin.Context) {
+			c.Set(string(ContextKeyUserRole), role)
+			c.Next()
+		}, AdminRolePermission())
+		ok := func(c *gin.Context) { c.Status(http.StatusNoContent) }
+		admin.GET("/users", ok)
+		admin.POST("/users/:id/balance", ok)
+		admin.POST("/accounts", ok)
+		admin.PUT("/settings", ok)
+		return router
+	}
+	do := func(router *gin.Engine, method, path string) int {
+		w := httptest.NewRecorder()
+		router.ServeHTTP(w, httptest.NewRequest(method, path, nil))
+		return w.Code
+	}
+
+	expect := map[string][4]int{
+		service.RoleAdmin:    {204, 204, 204, 204},
+		service.RoleBilling:  {204, 204, 403, 403},
+		service.RoleAccounts: {204, 403, 204, 403},
+		service.RoleAuditor:  {204, 403, 403, 403},
+		service.RoleUser:     {403, 403, 403, 403},
+	}
+	for role, want := range expect {
+		router := newRouter(role)
+		got := [4]int{
+			do(router, http.MethodGet, "/api/v1/admin/users"),
+			do(router, http.MethodPost, "/api/v1/admin/users/1/balance"),
+			do(router, http.MethodPost, "/api/v1/admin/accounts"),
+			do(router, http.MethodPut, "/api/v1/admin/settings"),
+		}
+		require.Equal(t, want, got, role)
+	}
+}
+
+func TestAdminRolePermission_SensitiveReads(t *testing.T) {
+	gin.SetMode(gin.TestMode)
+
+	sensitive := []string{
+		"/accounts/data",
+		"/proxies/data",
+		"/redeem-codes/export",
+		"/data-subject-requests/:id/download",
+		"/usage/export",
+		"/ws/logs",
+	}
+	newRouter := func(role string) *gin.Engine {
+		router := gin.New()
+		admin := router.Group("/api/v1/admin")
+		admin.Use(func(c *gin.Context) {
+			c.Set(string(ContextKeyUserRole), role)
+			c.Next()
+		}, AdminRolePermission())
+		for _, path := range sensitive {
+			admin.GET(path, func(c *gin.Context) { c.Status(http.StatusNoContent) })
+		}
+		return router
+	}
+
+	for _, role := range []string{service.RoleAdmin, service.RoleBilling, service.RoleAccounts, service.RoleAuditor} {
+		router := newRouter(role)
+		want := http.StatusForbidden
+		if role == service.RoleAdmin {
+			want = http.StatusNoContent
+		}
+		for _, path := range sensitive {
+			w := httptest.NewRecorder()
+			router.ServeHTTP(w, httptest.NewRequest(http.MethodGet, "/api/v1/admin"+strings.Replace(path, ":id", "7", 1), nil))
+			require.Equal(t, want, w.Code, "%s GET %s", role, path)
+		}
+	}
+}
@@ -3,10 +3,12 @@ package middleware
 import (
 	"context"
 	"errors"
+	"net/http"
 	"strings"
 
 	"github.com/Wei-Shaw/sub2api/internal/config"
 	"github.com/Wei-Shaw/sub2api/internal/pkg/ctxkey"
+	infraerrors "github.com/Wei-Shaw/sub2api/internal/pkg/errors"
 	"github.com/Wei-Shaw/sub2api/internal/pkg/ip"
 	"github.com/Wei-Shaw/sub2api/internal/service"
 
@@ -14,12 +16,12 @@ import (
 )
 
 // NewAPIKeyAuthMiddleware 创建 API Key 认证中间件
-func NewAPIKeyAuthMiddleware(apiKeyService *service.APIKeyService, subscriptionService *service.SubscriptionService, cfg *config.Config) APIKeyAuthMiddleware {
-	return APIKeyAuthMiddleware(apiKeyAuthWithSubscription(apiKeyService, subscriptionService, cfg))
+func NewAPIKeyAuthMiddleware(apiKeyService *service.APIKeyService, subscriptionService *service.SubscriptionService, policyService *service.APIKeyPolicyService, billingCacheService *service.BillingCacheService, cfg *config.Config) APIKeyAuthMiddleware {
+	return APIKeyAuthMiddleware(apiKeyAuthWithSubscription(apiKeyService, subscriptionService, policyService, billingCacheService, cfg))
 }
 
 // apiKeyAuthWithSubscription API Key认证中间件（支持订阅验证）
-func apiKeyAuthWithSubscription(apiKeyService *service.APIKeyService, subscriptionService *service.SubscriptionService, cfg *config.Config) gin.HandlerFunc {
+func apiKeyAuthWithSubscription(apiKeyService *service.APIKeyService, subscriptionService *service.SubscriptionService, policyService *service.APIKeyPolicyService, billingCacheService *service.BillingCacheService, cfg *config.Config) gin.HandlerFunc {
 	return func(c *gin.Context) {
 		queryKey := strings.TrimSpace(c.Query("key"))
 		queryApiKey := strings.TrimSpace(c.Query("api_key"))
@@ -67,8 +69,12 @@ func apiKeyAuthWithSubscription(apiKeyService *service.APIKeyService, subscripti
 			return
 		}
 
+		// API Key 总额度演练模式：额度用尽时只记录本应拒绝的请求，照常放行
+		quotaDryRun := (apiKey.Status == service.StatusAPIKeyQuotaExhausted || apiKey.IsQuotaExhausted()) &&
+			billingCacheService.LimitDryRun(c.Request.Context(), service.LimitDryRunScopeAPIKeyQuota, apiKey.ID, "", apiKey.QuotaUsed, apiKey.Quota)
+
 		// 检查API key是否激活
-		if !apiKey.IsActive() {
+		if !apiKey.IsActive() && !(quotaDryRun && apiKey.Status == service.StatusAPIKeyQuotaExhausted) {
 			// Provide more specific error message based on status
 			switch apiKey.Status {
 			case service.StatusAPIKeyQuotaExhausted:
@@ -88,7 +94,7 @@ func apiKeyAuthWithSubscription(apiKeyService *service.APIKeyService, subscripti
 		}
 
 		// 检查API Key配额是否耗尽
-		if apiKey.IsQuotaExhausted() {
+		if apiKey.IsQuotaExhausted() && !quotaDryRun {
 			AbortWithError(c, 429, "API_KEY_QUOTA_EXHAUSTED", "API key 额度已用完")
 			return
 		}
@@ -125,6 +131,12 @@ func apiKeyAuthWithSubscription(apiKeyService *service.APIKeyService, subscripti
 			})
 			c.Set(string(ContextKeyUserRole), apiKey.User.Role)
 			setGroupContext(c, apiKey.Group)
+			c.Request = c.Request.WithContext(service.WithAPIKeyID(c.Request.Context(), apiKey.ID))
+			policy := setAPIKeyPolicyContext(c, policyService, apiKey)
+			if err := checkAPIKeyRestrictions(c, policy); err != nil {
+				AbortWithError(c, infraerrors.Code(err), infraerrors.Reason(err), infraerrors.Message(err))
+				return
+			}
 			_ = apiKeyService.TouchLastUsed(c.Request.Context(), apiKey.ID)
 			c.Next()
 			return
@@ -146,7 +158,7 @@ func apiKeyAuthWithSubscription(apiKeyService *service.APIKeyService, subscripti
 			}
 
 			// 合并验证 + 限额检查（纯内存操作）
-			needsMaintenance, err := subscriptionService.ValidateAndCheckLimits(subscription, apiKey.Group)
+			needsMaintenance, err := subscriptionService.ValidateAndCheckLimits(c.Request.Context(), subscription, apiKey.Group)
 			if err != nil {
 				code := "SUBSCRIPTION_INVALID"
 				status := 403
@@ -185,6 +197,12 @@ func apiKeyAuthWithSubscription(apiKeyService *service.APIKeyService, subscripti
 		})
 		c.Set(string(ContextKeyUserRole), apiKey.User.Role)
 		setGroupContext(c, apiKey.Group)
+		c.Request = c.Request.WithContext(service.WithAPIKeyID(c.Request.Context(), apiKey.ID))
+		policy := setAPIKeyPolicyContext(c, policyService, apiKey)
+		if err := checkAPIKeyRestrictions(c, policy); err != nil {
+			AbortWithError(c, infraerrors.Code(err), infraerrors.Reason(err), infraerrors.Message(err))
+			return
+		}
 		_ = apiKeyService.TouchLastUsed(c.Request.Context(), apiKey.ID)
 
 		c.Next()
@@ -221,3 +239,34 @@ func setGroupContext(c *gin.Context, group *service.Group) {
 	ctx := context.WithValue(c.Request.Context(), ctxkey.Group, group)
 	c.Request = c.Request.WithContext(ctx)
 }
+
+// setAPIKeyPolicyContext 将 API Key 生效策略（含分组默认值）写入请求上下文并返回；读取失败时按默认策略处理，不阻断请求
+func setAPIKeyPolicyContext(c *gin.Context, policyService *service.APIKeyPolicyService, apiKey *service.APIKey) *service.APIKeyPolicy {
+	if policyService == nil {
+		return nil
+	}
+	policy, err := policyService.ResolveRequestPolicy(c.Request.Context(), apiKey)
+	if err != nil {
+		return nil
+	}
+	c.Request = c.Request.WithContext(service.WithAPIKeyPolicy(c.Request.Context(), policy))
+	return policy
+}
+
+// checkAPIKeyRestrictions 按策略中的模型 / 能力白名单检查请求。
+// 需要时读取请求体（缓冲后可重复读取），读取失败的错误原样交还给 handler，由其按协议格式返回。
+func checkAPIKeyRestrictions(c *gin.Context, policy *service.APIKeyPolicy) error {
+	if policy == nil || policy.Restrictions == nil {
+		return nil
+	}
+	restrictions := policy.Restrictions
+	var body []byte
+	if c.Request.Method != http.MethodGet && c.Request.Body != nil && restrictions.NeedsBody() {
+		data, err := ReadRequestBody(c.Request)
+		if err != nil {
+			return nil
+		}
+		body = data
+	}
+	return restrictions.CheckRequest(c.Request.Method, c.Request.URL.Path, body)
+}
@@ -10,16 +10,30 @@ import (
 )
 
 // NewJWTAuthMiddleware 创建 JWT 认证中间件
-func NewJWTAuthMiddleware(authService *service.AuthService, userService *service.UserService) JWTAuthMiddleware {
-	return JWTAuthMiddleware(jwtAuth(authService, userService))
+func NewJWTAuthMiddleware(authService *service.AuthService, userService *service.UserService, webSessionService *service.WebSessionService) JWTAuthMiddleware {
+	return JWTAuthMiddleware(jwtAuth(authService, userService, webSessionService))
 }
 
 // jwtAuth JWT认证中间件实现
-func jwtAuth(authService *service.AuthService, userService *service.UserService) gin.HandlerFunc {
+// 未携带 Authorization header 时，回退到 Cookie 会话认证（如已启用）
+func jwtAuth(authService *service.AuthService, userService *service.UserService, webSessionService *service.WebSessionService) gin.HandlerFunc {
 	return func(c *gin.Context) {
 		// 从Authorization header中提取token
 		authHeader := c.GetHeader("Authorization")
 		if authHeader == "" {
+			if sessionToken := WebSessionTokenFromCookie(c, webSessionService); sessionToken != "" {
+				user, ok := authenticateWebSession(c, sessionToken, webSessionService, userService)
+				if !ok {
+					return
+				}
+				c.Set(string(ContextKeyUser), AuthSubject{
+					UserID:      user.ID,
+					Concurrency: user.Concurrency,
+				})
+				c.Set(string(ContextKeyUserRole), user.Role)
+				c.Next()
+				return
+			}
 			AbortWithError(c, 401, "UNAUTHORIZED", "Authorization header is required")
 			return
 		}
@@ -0,0 +1,55 @@
+package middleware
+
+import (
+	"math"
+	"net/http"
+	"strconv"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/service"
+	"github.com/gin-gonic/gin"
+)
+
+// MaintenanceGuard 维护模式中间件：维护窗口内对网关请求返回结构化 503，放行列表中的路径除外。
+// 仅挂载在网关路由上，健康检查与管理后台不受影响。
+func MaintenanceGuard(maintenanceService *service.MaintenanceService) gin.HandlerFunc {
+	return func(c *gin.Context) {
+		settings, blocked := maintenanceService.Check(c.Request.Context(), c.Request.URL.Path)
+		if !blocked {
+			c.Next()
+			return
+		}
+
+		if settings.EndsAt != nil {
+			retryAfter := int(math.Ceil(time.Until(*settings.EndsAt).Seconds()))
+			if retryAfter > 0 {
+				c.Header("Retry-After", strconv.Itoa(retryAfter))
+			}
+		}
+
+		message := settings.EffectiveMessage()
+		if allowGoogleQueryKey(c.Request.URL.Path) {
+			abortWithGoogleError(c, http.StatusServiceUnavailable, message)
+			return
+		}
+
+		maintenance := gin.H{}
+		if settings.StartsAt != nil {
+			maintenance["starts_at"] = settings.StartsAt.UTC().Format(time.RFC3339)
+		}
+		if settings.EndsAt != nil {
+			maintenance["ends_at"] = settings.EndsAt.UTC().Format(time.RFC3339)
+		}
+		// 同时兼容 Anthropic（type=error）与 OpenAI（error.code）客户端的错误解析
+		c.JSON(http.StatusServiceUnavailable, gin.H{
+			"type": "error",
+			"error": gin.H{
+				"type":    "service_unavailable",
+				"code":    "maintenance",
+				"message": message,
+			},
+			"maintenance": maintenance,
+		})
+		c.Abort()
+	}
+}
@@ -0,0 +1,68 @@
+package middleware
+
+import (
+	"context"
+	"net/http"
+	"strings"
+
+	infraerrors "github.com/Wei-Shaw/sub2api/internal/pkg/errors"
+	"github.com/Wei-Shaw/sub2api/internal/service"
+	"github.com/gin-gonic/gin"
+	"github.com/tidwall/gjson"
+)
+
+// PrepaidCreditHold 预付费额度预占中间件（挂载在 API Key 认证之后）：
+// 按请求体预估费用并原子预占用户余额 / API Key 额度，额度不足时返回 402；预占随实际扣费释放。
+// count_tokens 不计费，不预占；会话、文件、提示词模板等本地资源路由不挂载该中间件（见 routes.RegisterGatewayRoutes）。
+func PrepaidCreditHold(prepaidCreditService *service.PrepaidCreditService) gin.HandlerFunc {
+	return func(c *gin.Context) {
+		if !prepaidCreditService.Enabled() || c.Request.Method != http.MethodPost || c.Request.Body == nil || isCountTokensPath(c.Request.URL.Path) {
+			c.Next()
+			return
+		}
+		apiKey, ok := GetAPIKeyFromContext(c)
+		if !ok {
+			c.Next()
+			return
+		}
+		subscription, _ := GetSubscriptionFromContext(c)
+
+		body, err := ReadRequestBody(c.Request)
+		if err != nil {
+			c.Next()
+			return
+		}
+
+		model := service.RequestModelFromPath(c.Request.URL.Path)
+		if model == "" {
+			model = gjson.GetBytes(body, "model").String()
+		}
+		holds, err := prepaidCreditService.Reserve(c.Request.Context(), apiKey, subscription, model, body)
+		if err != nil {
+			if allowGoogleQueryKey(c.Request.URL.Path) {
+				abortWithGoogleError(c, infraerrors.Code(err), infraerrors.Message(err))
+				return
+			}
+			AbortWithError(c, infraerrors.Code(err), infraerrors.Reason(err), infraerrors.Message(err))
+			return
+		}
+		if len(holds) == 0 {
+			c.Next()
+			return
+		}
+		// 处理器记录用量时认领预占，由 RecordUsage 在扣费后释放；未被认领（失败、无用量）的预占请求结束即释放
+		c.Request = c.Request.WithContext(service.WithCreditHolds(c.Request.Context(), holds))
+		defer func() {
+			for _, hold := range holds {
+				if !hold.Claimed() {
+					prepaidCreditService.Release(context.WithoutCancel(c.Request.Context()), hold)
+				}
+			}
+		}()
+		c.Next()
+	}
+}
+
+func isCountTokensPath(path string) bool {
+	return strings.HasSuffix(path, "/count_tokens") || strings.HasSuffix(path, ":countTokens")
+}
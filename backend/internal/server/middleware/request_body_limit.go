@@ -1,11 +1,19 @@
 package middleware
 
 import (
+	"bytes"
+	"fmt"
+	"io"
 	"net/http"
+	"strings"
 
+	"github.com/Wei-Shaw/sub2api/internal/config"
 	"github.com/gin-gonic/gin"
 )
 
+// maxBodyPrealloc 按 Content-Length 预分配读取缓冲的上限，超过部分按需增长
+const maxBodyPrealloc = 64 << 20
+
 // RequestBodyLimit 使用 MaxBytesReader 限制请求体大小。
 func RequestBodyLimit(maxBytes int64) gin.HandlerFunc {
 	return func(c *gin.Context) {
@@ -13,3 +21,125 @@ func RequestBodyLimit(maxBytes int64) gin.HandlerFunc {
 		c.Next()
 	}
 }
+
+// GatewayBodyLimit 网关请求体限制：按路径选择上限（gateway.body_limits 中前缀最长的规则，未命中时使用 defaultMax）。
+// Content-Length 已超限的请求在读取请求体之前直接返回 413；未声明长度的请求读取到上限即停止，由 handler 返回 413。
+func GatewayBodyLimit(defaultMax int64, rules []config.GatewayBodyLimitRule) gin.HandlerFunc {
+	return func(c *gin.Context) {
+		maxBytes := resolveBodyLimit(c.Request.URL.Path, defaultMax, rules)
+		if maxBytes > 0 && c.Request.ContentLength > maxBytes {
+			abortBodyTooLarge(c, maxBytes)
+			return
+		}
+		if maxBytes > 0 && c.Request.Body != nil && c.Request.Body != http.NoBody {
+			c.Request.Body = http.MaxBytesReader(c.Writer, c.Request.Body, maxBytes)
+		}
+		c.Next()
+	}
+}
+
+func resolveBodyLimit(path string, defaultMax int64, rules []config.GatewayBodyLimitRule) int64 {
+	limit, matched := defaultMax, 0
+	for _, rule := range rules {
+		if len(rule.Path) > matched && strings.HasPrefix(path, rule.Path) {
+			limit, matched = rule.MaxBytes, len(rule.Path)
+		}
+	}
+	return limit
+}
+
+// abortBodyTooLarge 按所访问端点的协议格式返回 413，与 handler 读取超限时的响应一致：
+// Gemini 路径使用 Google 错误格式，OpenAI 协议端点使用 OpenAI 格式，其余使用 Anthropic 格式
+func abortBodyTooLarge(c *gin.Context, limit int64) {
+	message := BodyTooLargeMessage(limit)
+	path := c.Request.URL.Path
+	switch {
+	case allowGoogleQueryKey(path):
+		abortWithGoogleError(c, http.StatusRequestEntityTooLarge, message)
+		return
+	case isOpenAIProtocolPath(path):
+		c.JSON(http.StatusRequestEntityTooLarge, gin.H{
+			"error": gin.H{
+				"type":    "invalid_request_error",
+				"message": message,
+			},
+		})
+	default:
+		c.JSON(http.StatusRequestEntityTooLarge, gin.H{
+			"type": "error",
+			"error": gin.H{
+				"type":    "invalid_request_error",
+				"message": message,
+			},
+		})
+	}
+	c.Abort()
+}
+
+// isOpenAIProtocolPath OpenAI 协议端点（Responses、Completions、Sora Chat Completions）
+func isOpenAIProtocolPath(path string) bool {
+	return strings.HasSuffix(path, "/responses") || strings.HasSuffix(path, "/completions")
+}
+
+// BodyTooLargeMessage 请求体超限的错误信息
+func BodyTooLargeMessage(limit int64) string {
+	const mb = 1024 * 1024
+	if limit >= mb {
+		return fmt.Sprintf("Request body too large, limit is %dMB", limit/mb)
+	}
+	return fmt.Sprintf("Request body too large, limit is %dB", limit)
+}
+
+// bufferedBody 已完整读取的请求体；读取错误一并保存，重复读取时原样返回
+type bufferedBody struct {
+	data []byte
+	err  error
+	off  int
+}
+
+func (b *bufferedBody) Read(p []byte) (int, error) {
+	if b.off >= len(b.data) {
+		if b.err != nil {
+			return 0, b.err
+		}
+		return 0, io.EOF
+	}
+	n := copy(p, b.data[b.off:])
+	b.off += n
+	return n, nil
+}
+
+func (b *bufferedBody) Close() error {
+	return nil
+}
+
+// ReadRequestBody 读取完整请求体（按 Content-Length 预分配一次缓冲）并替换为可重复读取的缓冲。
+// 网关需将请求体转发给上游，无法只解析部分字段；这里保证整条链路只持有一份副本。
+// 之后的中间件与 handler 再次调用时直接返回同一份数据而不再复制；读取错误（如超限）同样被保留，
+// 由最终的 handler 按协议格式响应。调用方不得原地修改返回的切片，改写请求体须使用 SetRequestBody。
+func ReadRequestBody(r *http.Request) ([]byte, error) {
+	if b, ok := r.Body.(*bufferedBody); ok {
+		return b.data, b.err
+	}
+	if r.Body == nil || r.Body == http.NoBody {
+		return nil, nil
+	}
+	data, err := readBody(r.Body, r.ContentLength)
+	r.Body = &bufferedBody{data: data, err: err}
+	return data, err
+}
+
+// SetRequestBody 替换请求体（如中间件改写参数后），后续 ReadRequestBody 返回新内容
+func SetRequestBody(r *http.Request, data []byte) {
+	r.Body = &bufferedBody{data: data}
+	r.ContentLength = int64(len(data))
+}
+
+func readBody(body io.Reader, contentLength int64) ([]byte, error) {
+	if contentLength <= 0 {
+		return io.ReadAll(body)
+	}
+	buf := bytes.NewBuffer(make([]byte, 0, min(contentLength, maxBodyPrealloc)+bytes.MinRead))
+	_, err := buf.ReadFrom(body)
+	return buf.Bytes(), err
+}
@@ -0,0 +1,118 @@
+package middleware
+
+import (
+	"bytes"
+	"encoding/json"
+	"errors"
+	"io"
+	"net/http"
+	"net/http/httptest"
+	"strings"
+	"testing"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/gin-gonic/gin"
+	"github.com/stretchr/testify/require"
+)
+
+func newBodyLimitRouter(t *testing.T, handle func(c *gin.Context)) *gin.Engine {
+	t.Helper()
+	gin.SetMode(gin.TestMode)
+	router := gin.New()
+	router.Use(GatewayBodyLimit(64, []config.GatewayBodyLimitRule{
+		{Path: "/v1/messages", MaxBytes: 32},
+		{Path: "/v1/messages/count_tokens", MaxBytes: 16},
+	}))
+	router.POST("/*path", handle)
+	return router
+}
+
+func TestGatewayBodyLimit_RejectsByContentLength(t *testing.T) {
+	called := false
+	router := newBodyLimitRouter(t, func(c *gin.Context) { called = true })
+
+	// 最长前缀规则生效
+	req := httptest.NewRequest(http.MethodPost, "/v1/messages/count_tokens", bytes.NewReader(bytes.Repeat([]byte(" "), 17)))
+	w := httptest.NewRecorder()
+	router.ServeHTTP(w, req)
+	require.Equal(t, http.StatusRequestEntityTooLarge, w.Code)
+	require.False(t, called)
+	var resp struct {
+		Type  *string `json:"type"`
+		Error struct {
+			Type    string `json:"type"`
+			Message string `json:"message"`
+		} `json:"error"`
+	}
+	require.NoError(t, json.Unmarshal(w.Body.Bytes(), &resp))
+	require.NotNil(t, resp.Type)
+	require.Equal(t, "error", *resp.Type)
+	require.Equal(t, "invalid_request_error", resp.Error.Type)
+	require.Equal(t, BodyTooLargeMessage(16), resp.Error.Message)
+
+	// OpenAI 路径使用 OpenAI 错误格式（无顶层 type）
+	req = httptest.NewRequest(http.MethodPost, "/v1/responses", bytes.NewReader(bytes.Repeat([]byte(" "), 65)))
+	w = httptest.NewRecorder()
+	router.ServeHTTP(w, req)
+	require.Equal(t, http.StatusRequestEntityTooLarge, w.Code)
+	resp.Type = nil
+	require.NoError(t, json.Unmarshal(w.Body.Bytes(), &resp))
+	require.Nil(t, resp.Type)
+	require.Equal(t, "invalid_request_error", resp.Error.Type)
+	require.Equal(t, BodyTooLargeMessage(64), resp.Error.Message)
+
+	req = httptest.NewRequest(http.MethodPost, "/v1/messages", bytes.NewReader(bytes.Repeat([]byte(" "), 17)))
+	w = httptest.NewRecorder()
+	router.ServeHTTP(w, req)
+	require.True(t, called)
+
+	// Gemini 路径使用 Google 错误格式
+	req = httptest.NewRequest(http.MethodPost, "/v1beta/models/gemini:generateContent", bytes.NewReader(bytes.Repeat([]byte(" "), 65)))
+	w = httptest.NewRecorder()
+	router.ServeHTTP(w, req)
+	require.Equal(t, http.StatusRequestEntityTooLarge, w.Code)
+	require.Contains(t, w.Body.String(), `"code":413`)
+}
+
+func TestGatewayBodyLimit_StreamingErrors(t *testing.T) {
+	var gotErr error
+	router := newBodyLimitRouter(t, func(c *gin.Context) {
+		_, gotErr = ReadRequestBody(c.Request)
+	})
+
+	// 未声明长度时读取超限返回 MaxBytesError
+	req := httptest.NewRequest(http.MethodPost, "/v1/messages", io.MultiReader(strings.NewReader(`{"a":"`+strings.Repeat("x", 40)+`"}`)))
+	req.ContentLength = -1
+	req.Header.Set("Content-Type", "application/json")
+	router.ServeHTTP(httptest.NewRecorder(), req)
+	var maxErr *http.MaxBytesError
+	require.True(t, errors.As(gotErr, &maxErr))
+
+	// 请求体不做 JSON 语法校验，交由 handler 解析
+	req = httptest.NewRequest(http.MethodPost, "/v1/messages", strings.NewReader(`not json`))
+	req.Header.Set("Content-Type", "application/json")
+	router.ServeHTTP(httptest.NewRecorder(), req)
+	require.NoError(t, gotErr)
+}
+
+func TestReadRequestBody_SharesBuffer(t *testing.T) {
+	req := httptest.NewRequest(http.MethodPost, "/v1/messages", strings.NewReader(`{"model":"claude"}`))
+
+	first, err := ReadRequestBody(req)
+	require.NoError(t, err)
+	second, err := ReadRequestBody(req)
+	require.NoError(t, err)
+	require.Equal(t, `{"model":"claude"}`, string(second))
+	require.Same(t, &first[0], &second[0])
+
+	// 仍可按普通 Reader 读取
+	data, err := io.ReadAll(req.Body)
+	require.NoError(t, err)
+	require.Equal(t, first, data)
+
+	SetRequestBody(req, []byte(`{}`))
+	replaced, err := ReadRequestBody(req)
+	require.NoError(t, err)
+	require.Equal(t, `{}`, string(replaced))
+	require.Equal(t, int64(2), req.ContentLength)
+}
@@ -0,0 +1,281 @@
+package middleware
+
+import (
+	"io"
+	"net/http"
+	"strconv"
+	"strings"
+	"sync"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/andybalholm/brotli"
+	"github.com/gin-gonic/gin"
+	"github.com/klauspost/compress/gzip"
+	"github.com/klauspost/compress/zstd"
+)
+
+// 支持的压缩算法（Content-Encoding 取值）
+const (
+	encodingGzip   = "gzip"
+	encodingBrotli = "br"
+	encodingZstd   = "zstd"
+)
+
+// defaultCompressionAlgorithms 未配置算法时的服务端偏好顺序
+var defaultCompressionAlgorithms = []string{encodingZstd, encodingBrotli, encodingGzip}
+
+// compressionEncoder 压缩器：Flush 将已写入的数据立即输出（SSE 逐事件下发依赖它）
+type compressionEncoder interface {
+	io.WriteCloser
+	Flush() error
+	Reset(w io.Writer)
+}
+
+var compressionEncoderPools = map[string]*sync.Pool{
+	encodingGzip: {New: func() any {
+		w, _ := gzip.NewWriterLevel(io.Discard, gzip.DefaultCompression)
+		return w
+	}},
+	encodingBrotli: {New: func() any {
+		// 级别 4 在压缩率与 CPU 之间折中，默认级别 6 对在线响应偏慢
+		return brotli.NewWriterLevel(io.Discard, 4)
+	}},
+	encodingZstd: {New: func() any {
+		w, _ := zstd.NewWriter(io.Discard, zstd.WithEncoderLevel(zstd.SpeedDefault), zstd.WithEncoderConcurrency(1))
+		return w
+	}},
+}
+
+// ResponseCompression 按 Accept-Encoding 压缩网关响应。
+//
+// 说明：
+//   - 只压缩 JSON/文本响应；已带 Content-Encoding 的响应（如上游透传的压缩体）不处理
+//   - 非流式响应先缓冲到 min_size_bytes，不足该大小时原样输出
+//   - SSE 仅在开启 sse 时压缩，每次 Flush 同时 flush 压缩器，事件不会滞留在压缩缓冲区
+//   - 需注册在捕获响应体的中间件之前，使其看到的是未压缩内容
+func ResponseCompression(cfg config.GatewayResponseCompressionConfig) gin.HandlerFunc {
+	if !cfg.Enabled {
+		return func(c *gin.Context) { c.Next() }
+	}
+	algorithms := make([]string, 0, len(cfg.Algorithms))
+	for _, algorithm := range cfg.Algorithms {
+		algorithm = strings.ToLower(strings.TrimSpace(algorithm))
+		if _, ok := compressionEncoderPools[algorithm]; ok {
+			algorithms = append(algorithms, algorithm)
+		}
+	}
+	if len(algorithms) == 0 {
+		algorithms = defaultCompressionAlgorithms
+	}
+
+	return func(c *gin.Context) {
+		if c.Request.Method == http.MethodHead || c.GetHeader("Upgrade") != "" {
+			c.Next()
+			return
+		}
+		encoding := negotiateEncoding(c.GetHeader("Accept-Encoding"), algorithms)
+		if encoding == "" {
+			c.Next()
+			return
+		}
+
+		w := &compressResponseWriter{
+			ResponseWriter: c.Writer,
+			encoding:       encoding,
+			minSize:        cfg.MinSizeBytes,
+			sse:            cfg.SSE,
+		}
+		w.Header().Add("Vary", "Accept-Encoding")
+		c.Writer = w
+		defer w.finish()
+		c.Next()
+	}
+}
+
+// negotiateEncoding 从 Accept-Encoding 中选出 q 值最高的可用算法，q 值相同时按服务端偏好顺序
+func negotiateEncoding(acceptEncoding string, algorithms []string) string {
+	if strings.TrimSpace(acceptEncoding) == "" {
+		return ""
+	}
+	explicit := make(map[string]float64)
+	wildcard := -1.0
+	for _, part := range strings.Split(acceptEncoding, ",") {
+		name, params, _ := strings.Cut(strings.TrimSpace(part), ";")
+		name = strings.ToLower(strings.TrimSpace(name))
+		if name == "" {
+			continue
+		}
+		q := 1.0
+		for _, param := range strings.Split(params, ";") {
+			key, value, ok := strings.Cut(strings.TrimSpace(param), "=")
+			if ok && strings.EqualFold(strings.TrimSpace(key), "q") {
+				if parsed, err := strconv.ParseFloat(strings.TrimSpace(value), 64); err == nil {
+					q = parsed
+				}
+			}
+		}
+		if name == "*" {
+			wildcard = q
+		} else {
+			explicit[name] = q
+		}
+	}
+
+	best, bestQ := "", 0.0
+	for _, algorithm := range algorithms {
+		q, ok := explicit[algorithm]
+		if !ok {
+			q = wildcard
+		}
+		if q > bestQ {
+			best, bestQ = algorithm, q
+		}
+	}
+	return best
+}
+
+// isCompressibleContentType 是否为值得压缩的文本类响应
+func isCompressibleContentType(contentType string) bool {
+	mediaType, _, _ := strings.Cut(contentType, ";")
+	mediaType = strings.ToLower(strings.TrimSpace(mediaType))
+	switch {
+	case strings.HasPrefix(mediaType, "text/"):
+		return true
+	case mediaType == "application/json", strings.HasSuffix(mediaType, "+json"):
+		return true
+	case mediaType == "application/x-ndjson", mediaType == "application/javascript", mediaType == "application/xml":
+		return true
+	}
+	return false
+}
+
+const (
+	compressUndecided = iota
+	compressPassthrough
+	compressActive
+)
+
+// compressResponseWriter 延迟决定是否压缩：首批数据达到最小大小、遇到 Flush 或请求结束时决定
+type compressResponseWriter struct {
+	gin.ResponseWriter
+	encoding string
+	minSize  int
+	sse      bool
+
+	state   int
+	buf     []byte
+	encoder compressionEncoder
+}
+
+func (w *compressResponseWriter) Write(data []byte) (int, error) {
+	switch w.state {
+	case compressPassthrough:
+		return w.ResponseWriter.Write(data)
+	case compressActive:
+		return w.encoder.Write(data)
+	}
+
+	if !w.eligible() {
+		if err := w.decide(false); err != nil {
+			return 0, err
+		}
+		return w.ResponseWriter.Write(data)
+	}
+	w.buf = append(w.buf, data...)
+	if len(w.buf) >= w.minSize {
+		if err := w.decide(true); err != nil {
+			return 0, err
+		}
+	}
+	return len(data), nil
+}
+
+func (w *compressResponseWriter) WriteString(s string) (int, error) {
+	return w.Write([]byte(s))
+}
+
+// WriteHeaderNow 提前提交响应头：SSE 流按 sse 配置决定，其余响应（如 AbortWithStatus）不再压缩
+func (w *compressResponseWriter) WriteHeaderNow() {
+	if w.state == compressUndecided && len(w.buf) == 0 {
+		_ = w.decide(w.isEventStream() && w.eligible())
+	}
+	w.ResponseWriter.WriteHeaderNow()
+}
+
+// Written 缓冲中的数据视为已写出，避免错误兜底逻辑重复写响应
+func (w *compressResponseWriter) Written() bool {
+	return w.state != compressUndecided || len(w.buf) > 0 || w.ResponseWriter.Written()
+}
+
+func (w *compressResponseWriter) Flush() {
+	if w.state == compressUndecided {
+		// 流式输出：开启 sse 压缩时立即开始压缩，否则不再压缩后续内容
+		_ = w.decide(w.sse && w.eligible())
+	}
+	if w.state == compressActive {
+		_ = w.encoder.Flush()
+	}
+	w.ResponseWriter.Flush()
+}
+
+// eligible 根据已设置的响应头判断是否可以压缩
+func (w *compressResponseWriter) eligible() bool {
+	status := w.Status()
+	if status < http.StatusOK || status == http.StatusNoContent || status == http.StatusNotModified || status == http.StatusPartialContent {
+		return false
+	}
+	header := w.Header()
+	if header.Get("Content-Encoding") != "" {
+		return false
+	}
+	if w.isEventStream() {
+		return w.sse
+	}
+	return isCompressibleContentType(header.Get("Content-Type"))
+}
+
+func (w *compressResponseWriter) isEventStream() bool {
+	return strings.HasPrefix(strings.ToLower(w.Header().Get("Content-Type")), "text/event-stream")
+}
+
+// decide 确定输出方式并写出缓冲数据
+func (w *compressResponseWriter) decide(compress bool) error {
+	if w.state != compressUndecided {
+		return nil
+	}
+	buffered := w.buf
+	w.buf = nil
+	if !compress {
+		w.state = compressPassthrough
+		if len(buffered) > 0 {
+			_, err := w.ResponseWriter.Write(buffered)
+			return err
+		}
+		return nil
+	}
+
+	w.state = compressActive
+	header := w.Header()
+	header.Set("Content-Encoding", w.encoding)
+	header.Del("Content-Length")
+	w.encoder = compressionEncoderPools[w.encoding].Get().(compressionEncoder)
+	w.encoder.Reset(w.ResponseWriter)
+	if len(buffered) > 0 {
+		_, err := w.encoder.Write(buffered)
+		return err
+	}
+	return nil
+}
+
+// finish 请求结束：未达到最小大小的缓冲原样输出，压缩器关闭并归还
+func (w *compressResponseWriter) finish() {
+	if w.state == compressUndecided {
+		_ = w.decide(len(w.buf) >= w.minSize && len(w.buf) > 0 && w.eligible())
+	}
+	if w.state == compressActive && w.encoder != nil {
+		_ = w.encoder.Close()
+		w.encoder.Reset(io.Discard)
+		compressionEncoderPools[w.encoding].Put(w.encoder)
+		w.encoder = nil
+	}
+}
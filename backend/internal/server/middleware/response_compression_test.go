@@ -0,0 +1,121 @@
+package middleware
+
+import (
+	"bytes"
+	"io"
+	"net/http"
+	"net/http/httptest"
+	"strings"
+	"testing"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/andybalholm/brotli"
+	"github.com/gin-gonic/gin"
+	"github.com/klauspost/compress/gzip"
+	"github.com/klauspost/compress/zstd"
+	"github.com/stretchr/testify/require"
+)
+
+func newCompressionRouter(cfg config.GatewayResponseCompressionConfig) *gin.Engine {
+	r := gin.New()
+	r.Use(ResponseCompression(cfg))
+	r.GET("/large", func(c *gin.Context) {
+		c.JSON(http.StatusOK, gin.H{"text": strings.Repeat("hello world ", 200)})
+	})
+	r.GET("/small", func(c *gin.Context) {
+		c.JSON(http.StatusOK, gin.H{"ok": true})
+	})
+	r.GET("/stream", func(c *gin.Context) {
+		c.Header("Content-Type", "text/event-stream")
+		c.Status(http.StatusOK)
+		for i := 0; i < 3; i++ {
+			_, _ = c.Writer.WriteString("data: {\"n\":1}\n\n")
+			c.Writer.Flush()
+		}
+	})
+	r.GET("/precompressed", func(c *gin.Context) {
+		c.Header("Content-Encoding", "gzip")
+		c.Data(http.StatusOK, "application/json", bytes.Repeat([]byte("x"), 4096))
+	})
+	return r
+}
+
+func doCompressionRequest(r *gin.Engine, path, acceptEncoding string) *httptest.ResponseRecorder {
+	req := httptest.NewRequest(http.MethodGet, path, nil)
+	if acceptEncoding != "" {
+		req.Header.Set("Accept-Encoding", acceptEncoding)
+	}
+	w := httptest.NewRecorder()
+	r.ServeHTTP(w, req)
+	return w
+}
+
+func decodeCompressed(t *testing.T, encoding string, body []byte) string {
+	t.Helper()
+	var reader io.Reader
+	switch encoding {
+	case encodingGzip:
+		gz, err := gzip.NewReader(bytes.NewReader(body))
+		require.NoError(t, err)
+		reader = gz
+	case encodingBrotli:
+		reader = brotli.NewReader(bytes.NewReader(body))
+	case encodingZstd:
+		zr, err := zstd.NewReader(bytes.NewReader(body))
+		require.NoError(t, err)
+		defer zr.Close()
+		reader = zr
+	default:
+		return string(body)
+	}
+	out, err := io.ReadAll(reader)
+	require.NoError(t, err)
+	return string(out)
+}
+
+func TestNegotiateEncoding(t *testing.T) {
+	algorithms := []string{encodingZstd, encodingBrotli, encodingGzip}
+	require.Equal(t, encodingZstd, negotiateEncoding("gzip, br, zstd", algorithms))
+	require.Equal(t, encodingGzip, negotiateEncoding("gzip;q=1.0, br;q=0.5", algorithms))
+	require.Equal(t, encodingBrotli, negotiateEncoding("*, zstd;q=0", algorithms))
+	require.Equal(t, "", negotiateEncoding("identity", algorithms))
+	require.Equal(t, "", negotiateEncoding("gzip;q=0", algorithms))
+	require.Equal(t, "", negotiateEncoding("", algorithms))
+}
+
+func TestResponseCompression_CompressesLargeJSON(t *testing.T) {
+	r := newCompressionRouter(config.GatewayResponseCompressionConfig{Enabled: true, MinSizeBytes: 1024})
+
+	for _, encoding := range []string{encodingGzip, encodingBrotli, encodingZstd} {
+		w := doCompressionRequest(r, "/large", encoding)
+		require.Equal(t, http.StatusOK, w.Code)
+		require.Equal(t, encoding, w.Header().Get("Content-Encoding"))
+		require.Contains(t, w.Header().Values("Vary"), "Accept-Encoding")
+		require.Contains(t, decodeCompressed(t, encoding, w.Body.Bytes()), "hello world hello world")
+	}
+}
+
+func TestResponseCompression_SkipsSmallAndUnsupported(t *testing.T) {
+	r := newCompressionRouter(config.GatewayResponseCompressionConfig{Enabled: true, MinSizeBytes: 1024})
+
+	w := doCompressionRequest(r, "/small", "gzip")
+	require.Empty(t, w.Header().Get("Content-Encoding"))
+	require.JSONEq(t, `{"ok":true}`, w.Body.String())
+
+	w = doCompressionRequest(r, "/large", "")
+	require.Empty(t, w.Header().Get("Content-Encoding"))
+
+	w = doCompressionRequest(r, "/precompressed", "zstd")
+	require.Equal(t, "gzip", w.Header().Get("Content-Encoding"))
+	require.Equal(t, 4096, w.Body.Len())
+}
+
+func TestResponseCompression_SSE(t *testing.T) {
+	w := doCompressionRequest(newCompressionRouter(config.GatewayResponseCompressionConfig{Enabled: true, MinSizeBytes: 1024}), "/stream", "gzip")
+	require.Empty(t, w.Header().Get("Content-Encoding"), "SSE 默认不压缩")
+	require.Equal(t, 3, strings.Count(w.Body.String(), "data: "))
+
+	w = doCompressionRequest(newCompressionRouter(config.GatewayResponseCompressionConfig{Enabled: true, MinSizeBytes: 1024, SSE: true}), "/stream", "gzip")
+	require.Equal(t, encodingGzip, w.Header().Get("Content-Encoding"))
+	require.Equal(t, 3, strings.Count(decodeCompressed(t, encodingGzip, w.Body.Bytes()), "data: "))
+}
@@ -0,0 +1,71 @@
+package middleware
+
+import (
+	"net/http"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/pkg/logger"
+	"github.com/Wei-Shaw/sub2api/internal/service"
+	"github.com/gin-gonic/gin"
+	"github.com/tidwall/gjson"
+	"go.uber.org/zap"
+)
+
+// RoutingRules 声明式路由中间件（挂载在 API Key 认证之后、额度预占之前）：
+// 命中规则时切换请求使用的分组并覆盖请求体参数，后续的调度、额度预占与计费均使用切换后的分组。
+// 没有启用的规则时不读取请求体。
+func RoutingRules(routingService *service.RoutingRuleService) gin.HandlerFunc {
+	return func(c *gin.Context) {
+		if c.Request.Method != http.MethodPost || c.Request.Body == nil || isCountTokensPath(c.Request.URL.Path) || !routingService.Active(c.Request.Context()) {
+			c.Next()
+			return
+		}
+		apiKey, ok := GetAPIKeyFromContext(c)
+		if !ok {
+			c.Next()
+			return
+		}
+
+		body, err := ReadRequestBody(c.Request)
+		if err != nil {
+			c.Next()
+			return
+		}
+
+		model := service.RequestModelFromPath(c.Request.URL.Path)
+		if model == "" {
+			model = gjson.GetBytes(body, "model").String()
+		}
+		decision := routingService.Route(c.Request.Context(), &service.RoutingRequest{
+			APIKeyID:    apiKey.ID,
+			Group:       apiKey.Group,
+			Model:       model,
+			PromptChars: service.RoutingPromptChars(body),
+			Header:      c.Request.Header,
+			Now:         time.Now(),
+		})
+		if decision != nil {
+			reqLog := logger.FromContext(c.Request.Context())
+			if routed, err := service.ApplyRoutingParamOverrides(body, decision.ParamOverrides); err != nil {
+				reqLog.Warn("gateway.routing_rule_override_failed", zap.Int64("routing_rule_id", decision.Rule.ID), zap.Error(err))
+			} else {
+				body = routed
+			}
+			SetRequestBody(c.Request, body)
+			if decision.Group != nil {
+				routedKey := *apiKey
+				routedKey.GroupID = decision.GroupID
+				routedKey.Group = decision.Group
+				c.Set(string(ContextKeyAPIKey), &routedKey)
+				setGroupContext(c, decision.Group)
+			}
+			reqLog.Debug("gateway.routing_rule_matched",
+				zap.Int64("routing_rule_id", decision.Rule.ID),
+				zap.String("model", model),
+				zap.Any("group_id", decision.GroupID),
+				zap.Int("skipped_groups", len(decision.Skipped)),
+			)
+		}
+		c.Next()
+	}
+}
@@ -0,0 +1,96 @@
+package middleware
+
+import (
+	"net/http"
+
+	"github.com/Wei-Shaw/sub2api/internal/service"
+
+	"github.com/gin-gonic/gin"
+)
+
+// WebSessionTokenFromCookie 从 Cookie 中读取会话 Token（未启用或不存在时返回空字符串）
+func WebSessionTokenFromCookie(c *gin.Context, webSessionService *service.WebSessionService) string {
+	if !webSessionService.Enabled() {
+		return ""
+	}
+	token, err := c.Cookie(webSessionService.CookieOptions().Name)
+	if err != nil {
+		return ""
+	}
+	return token
+}
+
+// authenticateWebSession 通过 Cookie 会话认证用户
+// 浏览器会在跨站请求中自动携带 Cookie，非 GET/HEAD/OPTIONS 请求要求 Origin（缺失时 Referer）来自可信来源。
+// 校验失败时会中止请求并返回 false。
+func authenticateWebSession(
+	c *gin.Context,
+	token string,
+	webSessionService *service.WebSessionService,
+	userService *service.UserService,
+) (*service.User, bool) {
+	if !isSafeMethod(c.Request.Method) && !webSessionService.TrustedOrigin(webSessionRequestOrigin(c), c.Request.Host) {
+		AbortWithError(c, 403, "CSRF_ORIGIN_MISMATCH", "Cross-site request rejected: Origin or Referer must match this site")
+		return nil, false
+	}
+
+	session, err := webSessionService.Authenticate(c.Request.Context(), token)
+	if err != nil {
+		ClearWebSessionCookie(c, webSessionService)
+		AbortWithError(c, 401, "SESSION_INVALID", "Session is invalid or has expired")
+		return nil, false
+	}
+
+	user, err := userService.GetByID(c.Request.Context(), session.UserID)
+	if err != nil {
+		AbortWithError(c, 401, "USER_NOT_FOUND", "User not found")
+		return nil, false
+	}
+
+	if !user.IsActive() {
+		AbortWithError(c, 401, "USER_INACTIVE", "User account is not active")
+		return nil, false
+	}
+
+	// 改密后 TokenVersion 变化，旧会话立即失效
+	if session.TokenVersion != user.TokenVersion {
+		_ = webSessionService.Revoke(c.Request.Context(), token)
+		ClearWebSessionCookie(c, webSessionService)
+		AbortWithError(c, 401, "SESSION_REVOKED", "Session has been revoked (password changed)")
+		return nil, false
+	}
+
+	return user, true
+}
+
+func isSafeMethod(method string) bool {
+	return method == http.MethodGet || method == http.MethodHead || method == http.MethodOptions
+}
+
+// webSessionRequestOrigin 返回请求来源：优先 Origin；Origin 缺失时取 Referer（不透明来源 "null" 不回退）
+func webSessionRequestOrigin(c *gin.Context) string {
+	if origin := c.GetHeader("Origin"); origin != "" {
+		return origin
+	}
+	return c.GetHeader("Referer")
+}
+
+// SetWebSessionCookie 写入会话 Cookie（HttpOnly）
+func SetWebSessionCookie(c *gin.Context, webSessionService *service.WebSessionService, token string) {
+	if !webSessionService.Enabled() {
+		return
+	}
+	opts := webSessionService.CookieOptions()
+	c.SetSameSite(opts.SameSite)
+	c.SetCookie(opts.Name, token, opts.MaxAge, "/", opts.Domain, opts.Secure, true)
+}
+
+// ClearWebSessionCookie 清除会话 Cookie
+func ClearWebSessionCookie(c *gin.Context, webSessionService *service.WebSessionService) {
+	if !webSessionService.Enabled() {
+		return
+	}
+	opts := webSessionService.CookieOptions()
+	c.SetSameSite(opts.SameSite)
+	c.SetCookie(opts.Name, "", -1, "/", opts.Domain, opts.Secure, true)
+}
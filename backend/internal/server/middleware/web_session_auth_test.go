@@ -0,0 +1,108 @@
+//go:build unit
+
+package middleware
+
+import (
+	"context"
+	"net/http"
+	"net/http/httptest"
+	"testing"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/Wei-Shaw/sub2api/internal/service"
+	"github.com/gin-gonic/gin"
+	"github.com/stretchr/testify/require"
+)
+
+// memoryWebSessionStore 内存会话存储，仅用于中间件测试
+type memoryWebSessionStore struct {
+	sessions map[string]*service.WebSession
+}
+
+func (s *memoryWebSessionStore) SaveSession(_ context.Context, session *service.WebSession, _ time.Duration) error {
+	cp := *session
+	s.sessions[session.ID] = &cp
+	return nil
+}
+
+func (s *memoryWebSessionStore) GetSession(_ context.Context, sessionID string) (*service.WebSession, error) {
+	session, ok := s.sessions[sessionID]
+	if !ok {
+		return nil, service.ErrWebSessionNotFound
+	}
+	cp := *session
+	return &cp, nil
+}
+
+func (s *memoryWebSessionStore) DeleteSession(_ context.Context, _ int64, sessionID string) error {
+	delete(s.sessions, sessionID)
+	return nil
+}
+
+func (s *memoryWebSessionStore) ListUserSessions(context.Context, int64) ([]*service.WebSession, error) {
+	return nil, nil
+}
+
+func (s *memoryWebSessionStore) DeleteUserSessions(context.Context, int64) error {
+	return nil
+}
+
+func TestJWTAuth_WebSessionRequiresTrustedOriginForWrites(t *testing.T) {
+	gin.SetMode(gin.TestMode)
+
+	cfg := &config.Config{}
+	cfg.JWT.Secret = "test-jwt-secret-32bytes-long!!!"
+	cfg.Server.FrontendURL = "https://console.example.com"
+	cfg.WebSession = config.WebSessionConfig{
+		Enabled:              true,
+		CookieName:           "sub2api_session",
+		Secure:               true,
+		SameSite:             "lax",
+		IdleTimeoutMinutes:   30,
+		AbsoluteTimeoutHours: 24,
+	}
+	user := &service.User{ID: 1, Role: "user", Status: service.StatusActive, Concurrency: 1}
+	userRepo := &stubJWTUserRepo{users: map[int64]*service.User{1: user}}
+	webSessionSvc := service.NewWebSessionService(&memoryWebSessionStore{sessions: map[string]*service.WebSession{}}, cfg)
+	token, _, err := webSessionSvc.Create(context.Background(), user, "", "")
+	require.NoError(t, err)
+
+	authSvc := service.NewAuthService(userRepo, nil, nil, cfg, nil, nil, nil, nil, nil)
+	r := gin.New()
+	r.Use(gin.HandlerFunc(NewJWTAuthMiddleware(authSvc, service.NewUserService(userRepo, nil, nil), webSessionSvc)))
+	handler := func(c *gin.Context) { c.Status(http.StatusNoContent) }
+	r.GET("/protected", handler)
+	r.POST("/protected", handler)
+
+	cases := []struct {
+		name    string
+		method  string
+		origin  string
+		referer string
+		want    int
+	}{
+		{name: "get without origin", method: http.MethodGet, want: http.StatusNoContent},
+		{name: "post without origin", method: http.MethodPost, want: http.StatusForbidden},
+		{name: "post cross-site origin", method: http.MethodPost, origin: "https://evil.example.com", want: http.StatusForbidden},
+		{name: "post opaque origin", method: http.MethodPost, origin: "null", referer: "https://api.example.com/", want: http.StatusForbidden},
+		{name: "post same-origin", method: http.MethodPost, origin: "https://api.example.com", want: http.StatusNoContent},
+		{name: "post same-origin referer", method: http.MethodPost, referer: "https://api.example.com/keys", want: http.StatusNoContent},
+		{name: "post frontend origin", method: http.MethodPost, origin: "https://console.example.com", want: http.StatusNoContent},
+	}
+	for _, tc := range cases {
+		t.Run(tc.name, func(t *testing.T) {
+			req := httptest.NewRequest(tc.method, "https://api.example.com/protected", nil)
+			req.AddCookie(&http.Cookie{Name: "sub2api_session", Value: token})
+			if tc.origin != "" {
+				req.Header.Set("Origin", tc.origin)
+			}
+			if tc.referer != "" {
+				req.Header.Set("Referer", tc.referer)
+			}
+			w := httptest.NewRecorder()
+			r.ServeHTTP(w, req)
+			require.Equal(t, tc.want, w.Code)
+		})
+	}
+}
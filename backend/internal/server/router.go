@@ -23,8 +23,13 @@ func SetupRouter(
 	apiKeyAuth middleware2.APIKeyAuthMiddleware,
 	apiKeyService *service.APIKeyService,
 	subscriptionService *service.SubscriptionService,
+	policyService *service.APIKeyPolicyService,
 	opsService *service.OpsService,
+	requestLogService *service.RequestLogService,
 	settingService *service.SettingService,
+	maintenanceService *service.MaintenanceService,
+	prepaidCreditService *service.PrepaidCreditService,
+	routingRuleService *service.RoutingRuleService,
 	cfg *config.Config,
 	redisClient *redis.Client,
 ) *gin.Engine {
@@ -48,7 +53,7 @@ func SetupRouter(
 	}
 
 	// 注册路由
-	registerRoutes(r, handlers, jwtAuth, adminAuth, apiKeyAuth, apiKeyService, subscriptionService, opsService, cfg, redisClient)
+	registerRoutes(r, handlers, jwtAuth, adminAuth, apiKeyAuth, apiKeyService, subscriptionService, policyService, opsService, requestLogService, maintenanceService, prepaidCreditService, routingRuleService, cfg, redisClient)
 
 	return r
 }
@@ -62,7 +67,12 @@ func registerRoutes(
 	apiKeyAuth middleware2.APIKeyAuthMiddleware,
 	apiKeyService *service.APIKeyService,
 	subscriptionService *service.SubscriptionService,
+	policyService *service.APIKeyPolicyService,
 	opsService *service.OpsService,
+	requestLogService *service.RequestLogService,
+	maintenanceService *service.MaintenanceService,
+	prepaidCreditService *service.PrepaidCreditService,
+	routingRuleService *service.RoutingRuleService,
 	cfg *config.Config,
 	redisClient *redis.Client,
 ) {
@@ -76,5 +86,8 @@ func registerRoutes(
 	routes.RegisterAuthRoutes(v1, h, jwtAuth, redisClient)
 	routes.RegisterUserRoutes(v1, h, jwtAuth)
 	routes.RegisterAdminRoutes(v1, h, adminAuth)
-	routes.RegisterGatewayRoutes(r, h, apiKeyAuth, apiKeyService, subscriptionService, opsService, cfg)
+	routes.RegisterGatewayRoutes(r, h, apiKeyAuth, apiKeyService, subscriptionService, policyService, opsService, requestLogService, maintenanceService, prepaidCreditService, routingRuleService, cfg)
+
+	// OpenAPI 文档（需在其它路由之后注册，以便包含全部路由）
+	routes.RegisterAPIDocsRoutes(r, cfg)
 }
@@ -15,7 +15,7 @@ func RegisterAdminRoutes(
 	adminAuth middleware.AdminAuthMiddleware,
 ) {
 	admin := v1.Group("/admin")
-	admin.Use(gin.HandlerFunc(adminAuth))
+	admin.Use(gin.HandlerFunc(adminAuth), middleware.AdminRolePermission())
 	{
 		// 仪表盘
 		registerDashboardRoutes(admin, h)
@@ -43,6 +43,9 @@ func RegisterAdminRoutes(
 		// Antigravity OAuth
 		registerAntigravityOAuthRoutes(admin, h)
 
+		// GitHub Copilot 设备码登录
+		registerCopilotRoutes(admin, h)
+
 		// 代理管理
 		registerProxyRoutes(admin, h)
 
@@ -72,6 +75,84 @@ func RegisterAdminRoutes(
 
 		// 错误透传规则管理
 		registerErrorPassthroughRoutes(admin, h)
+
+		// 流量镜像规则与样本
+		registerTrafficMirrorRoutes(admin, h)
+
+		// 模型金丝雀路由
+		registerModelCanaryRoutes(admin, h)
+
+		// 账号分片与分片路由规则
+		registerAccountShardRoutes(admin, h)
+
+		// 声明式路由规则
+		registerRoutingRuleRoutes(admin, h)
+
+		// GraphQL 只读查询
+		registerGraphQLRoutes(admin, h)
+
+		// 账号延迟与成功率分析
+		registerAccountHealthRoutes(admin, h)
+
+		// 实时请求流与实时日志（WebSocket）
+		admin.GET("/ws/live", h.Admin.LiveUsage.LiveWSHandler)
+		admin.GET("/ws/logs", h.Admin.LiveUsage.LogsWSHandler)
+
+		// 请求日志检索
+		admin.GET("/request-logs", h.Admin.RequestLog.Search)
+
+		// 数据主体导出 / 删除（GDPR）
+		registerDataSubjectRoutes(admin, h)
+
+		// 运行时功能开关
+		registerFeatureFlagRoutes(admin, h)
+
+		// 网关维护模式
+		registerMaintenanceRoutes(admin, h)
+
+		// 消费硬上限
+		registerSpendingCapRoutes(admin, h)
+
+		// 月度账单
+		registerStatementRoutes(admin, h)
+
+		// 在线支付订单
+		admin.GET("/payments", h.Admin.Payment.List)
+
+		// 后台账号与角色
+		registerStaffRoutes(admin, h)
+
+		// 团队 API Key（共享预算）
+		registerTeamRoutes(admin, h)
+
+		// 通知邮件发送记录
+		admin.GET("/notifications/logs", h.Admin.Notification.ListLogs)
+
+		// 回收站
+		registerTrashRoutes(admin, h)
+
+		// 后台任务队列
+		registerJobRoutes(admin, h)
+
+		// 全局 API Key 列表（游标分页）
+		admin.GET("/api-keys", h.Admin.List.APIKeys)
+		admin.GET("/api-keys/:id/policy", h.Admin.APIKeyPolicy.Get)
+		admin.PUT("/api-keys/:id/policy", h.Admin.APIKeyPolicy.Update)
+		admin.GET("/api-keys/:id/credits", h.Admin.PrepaidCredit.Get)
+		admin.POST("/api-keys/:id/credits", h.Admin.PrepaidCredit.TopUp)
+		admin.GET("/api-keys/:id/tags", h.Admin.RoutingRule.GetKeyTags)
+		admin.PUT("/api-keys/:id/tags", h.Admin.RoutingRule.UpdateKeyTags)
+	}
+}
+
+// withCursor 请求携带 cursor 参数时走游标分页，否则走原有的 offset 分页，保持旧客户端兼容
+func withCursor(cursorHandler, offsetHandler gin.HandlerFunc) gin.HandlerFunc {
+	return func(c *gin.Context) {
+		if _, ok := c.GetQuery("cursor"); ok {
+			cursorHandler(c)
+			return
+		}
+		offsetHandler(c)
 	}
 }
 
@@ -81,6 +162,8 @@ func registerOpsRoutes(admin *gin.RouterGroup, h *handler.Handlers) {
 		// Realtime ops signals
 		ops.GET("/concurrency", h.Admin.Ops.GetConcurrencyStats)
 		ops.GET("/user-concurrency", h.Admin.Ops.GetUserConcurrencyStats)
+		ops.GET("/fair-share", h.Admin.Ops.GetFairShareStats)
+		ops.GET("/upstream-pool", h.Admin.Ops.GetUpstreamPoolStats)
 		ops.GET("/account-availability", h.Admin.Ops.GetAccountAvailability)
 		ops.GET("/realtime-traffic", h.Admin.Ops.GetRealtimeTrafficSummary)
 
@@ -97,6 +180,8 @@ func registerOpsRoutes(admin *gin.RouterGroup, h *handler.Handlers) {
 		// Email notification config (DB-backed)
 		ops.GET("/email-notification/config", h.Admin.Ops.GetEmailNotificationConfig)
 		ops.PUT("/email-notification/config", h.Admin.Ops.UpdateEmailNotificationConfig)
+		ops.GET("/discord-notification/config", h.Admin.Ops.GetDiscordNotificationConfig)
+		ops.PUT("/discord-notification/config", h.Admin.Ops.UpdateDiscordNotificationConfig)
 
 		// Runtime settings (DB-backed)
 		runtime := ops.Group("/runtime")
@@ -154,6 +239,10 @@ func registerOpsRoutes(admin *gin.RouterGroup, h *handler.Handlers) {
 		ops.POST("/system-logs/cleanup", h.Admin.Ops.CleanupSystemLogs)
 		ops.GET("/system-logs/health", h.Admin.Ops.GetSystemLogIngestionHealth)
 
+		// Log masking profiles (config-backed)
+		ops.GET("/log-masking/profiles", h.Admin.Ops.ListLogMaskingProfiles)
+		ops.POST("/log-masking/preview", h.Admin.Ops.PreviewLogMasking)
+
 		// Dashboard (vNext - raw path for MVP)
 		ops.GET("/dashboard/overview", h.Admin.Ops.GetDashboardOverview)
 		ops.GET("/dashboard/throughput-trend", h.Admin.Ops.GetDashboardThroughputTrend)
@@ -195,6 +284,11 @@ func registerUserManagementRoutes(admin *gin.RouterGroup, h *handler.Handlers) {
 		// User attribute values
 		users.GET("/:id/attributes", h.Admin.UserAttribute.GetUserAttributes)
 		users.PUT("/:id/attributes", h.Admin.UserAttribute.UpdateUserAttributes)
+
+		// Web sessions
+		users.GET("/:id/sessions", h.Admin.WebSession.ListUserSessions)
+		users.DELETE("/:id/sessions", h.Admin.WebSession.RevokeUserSessions)
+		users.DELETE("/:id/sessions/:session_id", h.Admin.WebSession.RevokeUserSession)
 	}
 }
 
@@ -210,23 +304,27 @@ func registerGroupRoutes(admin *gin.RouterGroup, h *handler.Handlers) {
 		groups.DELETE("/:id", h.Admin.Group.Delete)
 		groups.GET("/:id/stats", h.Admin.Group.GetStats)
 		groups.GET("/:id/api-keys", h.Admin.Group.GetGroupAPIKeys)
+		groups.GET("/:id/policy", h.Admin.APIKeyPolicy.GetGroup)
+		groups.PUT("/:id/policy", h.Admin.APIKeyPolicy.UpdateGroup)
 	}
 }
 
 func registerAccountRoutes(admin *gin.RouterGroup, h *handler.Handlers) {
 	accounts := admin.Group("/accounts")
 	{
-		accounts.GET("", h.Admin.Account.List)
+		accounts.GET("", withCursor(h.Admin.List.Accounts, h.Admin.Account.List))
 		accounts.GET("/:id", h.Admin.Account.GetByID)
 		accounts.POST("", h.Admin.Account.Create)
 		accounts.POST("/check-mixed-channel", h.Admin.Account.CheckMixedChannel)
 		accounts.POST("/sync/crs", h.Admin.Account.SyncFromCRS)
 		accounts.POST("/sync/crs/preview", h.Admin.Account.PreviewFromCRS)
+		accounts.POST("/import-session", h.Admin.SessionImport.Import)
 		accounts.PUT("/:id", h.Admin.Account.Update)
 		accounts.DELETE("/:id", h.Admin.Account.Delete)
 		accounts.POST("/:id/test", h.Admin.Account.Test)
 		accounts.POST("/:id/refresh", h.Admin.Account.Refresh)
 		accounts.POST("/:id/refresh-tier", h.Admin.Account.RefreshTier)
+		accounts.POST("/:id/challenge/resolve", h.Admin.Challenge.Resolve)
 		accounts.GET("/:id/stats", h.Admin.Account.GetStats)
 		accounts.POST("/:id/clear-error", h.Admin.Account.ClearError)
 		accounts.GET("/:id/usage", h.Admin.Account.GetUsage)
@@ -279,6 +377,15 @@ func registerOpenAIOAuthRoutes(admin *gin.RouterGroup, h *handler.Handlers) {
 	}
 }
 
+func registerCopilotRoutes(admin *gin.RouterGroup, h *handler.Handlers) {
+	copilot := admin.Group("/copilot")
+	{
+		copilot.POST("/device-code", h.Admin.Copilot.StartDeviceAuth)
+		copilot.POST("/poll", h.Admin.Copilot.PollDeviceAuth)
+		copilot.GET("/accounts/:id/models", h.Admin.Copilot.ListModels)
+	}
+}
+
 func registerSoraOAuthRoutes(admin *gin.RouterGroup, h *handler.Handlers) {
 	sora := admin.Group("/sora")
 	{
@@ -406,8 +513,9 @@ func registerSubscriptionRoutes(admin *gin.RouterGroup, h *handler.Handlers) {
 func registerUsageRoutes(admin *gin.RouterGroup, h *handler.Handlers) {
 	usage := admin.Group("/usage")
 	{
-		usage.GET("", h.Admin.Usage.List)
+		usage.GET("", withCursor(h.Admin.List.UsageLogs, h.Admin.Usage.List))
 		usage.GET("/stats", h.Admin.Usage.Stats)
+		usage.GET("/export", h.Admin.UsageExport.Export)
 		usage.GET("/search-users", h.Admin.Usage.SearchUsers)
 		usage.GET("/search-api-keys", h.Admin.Usage.SearchAPIKeys)
 		usage.GET("/cleanup-tasks", h.Admin.Usage.ListCleanupTasks)
@@ -438,3 +546,169 @@ func registerErrorPassthroughRoutes(admin *gin.RouterGroup, h *handler.Handlers)
 		rules.DELETE("/:id", h.Admin.ErrorPassthrough.Delete)
 	}
 }
+
+func registerTrafficMirrorRoutes(admin *gin.RouterGroup, h *handler.Handlers) {
+	mirror := admin.Group("/traffic-mirror")
+	{
+		mirror.GET("/rules", h.Admin.TrafficMirror.ListRules)
+		mirror.GET("/rules/:id", h.Admin.TrafficMirror.GetRule)
+		mirror.POST("/rules", h.Admin.TrafficMirror.CreateRule)
+		mirror.PUT("/rules/:id", h.Admin.TrafficMirror.UpdateRule)
+		mirror.DELETE("/rules/:id", h.Admin.TrafficMirror.DeleteRule)
+		mirror.GET("/samples", h.Admin.TrafficMirror.ListSamples)
+		mirror.GET("/samples/:id", h.Admin.TrafficMirror.GetSample)
+		mirror.GET("/report", h.Admin.TrafficMirror.Report)
+	}
+}
+
+func registerModelCanaryRoutes(admin *gin.RouterGroup, h *handler.Handlers) {
+	routes := admin.Group("/canary-routes")
+	{
+		routes.GET("", h.Admin.ModelCanary.List)
+		routes.GET("/:id", h.Admin.ModelCanary.GetByID)
+		routes.POST("", h.Admin.ModelCanary.Create)
+		routes.PUT("/:id", h.Admin.ModelCanary.Update)
+		routes.DELETE("/:id", h.Admin.ModelCanary.Delete)
+		routes.GET("/:id/stats", h.Admin.ModelCanary.GetStats)
+		routes.DELETE("/:id/stats", h.Admin.ModelCanary.ResetStats)
+	}
+}
+
+func registerAccountHealthRoutes(admin *gin.RouterGroup, h *handler.Handlers) {
+	health := admin.Group("/account-health")
+	{
+		health.GET("", h.Admin.AccountHealth.List)
+		health.GET("/:id", h.Admin.AccountHealth.GetByAccount)
+		health.GET("/:id/quota", h.Admin.AccountHealth.GetQuota)
+	}
+}
+
+func registerDataSubjectRoutes(admin *gin.RouterGroup, h *handler.Handlers) {
+	requests := admin.Group("/data-subject-requests")
+	{
+		requests.GET("", h.Admin.DataSubject.List)
+		requests.POST("", h.Admin.DataSubject.Create)
+		requests.GET("/:id", h.Admin.DataSubject.Get)
+		requests.GET("/:id/download", h.Admin.DataSubject.Download)
+	}
+}
+
+func registerFeatureFlagRoutes(admin *gin.RouterGroup, h *handler.Handlers) {
+	flags := admin.Group("/feature-flags")
+	{
+		flags.GET("", h.Admin.FeatureFlag.List)
+		flags.PUT("/:key", h.Admin.FeatureFlag.Upsert)
+		flags.DELETE("/:key", h.Admin.FeatureFlag.Delete)
+		flags.GET("/:key/evaluate", h.Admin.FeatureFlag.Evaluate)
+	}
+}
+
+func registerMaintenanceRoutes(admin *gin.RouterGroup, h *handler.Handlers) {
+	maintenance := admin.Group("/maintenance")
+	{
+		maintenance.GET("", h.Admin.Maintenance.Get)
+		maintenance.PUT("", h.Admin.Maintenance.Update)
+	}
+}
+
+func registerSpendingCapRoutes(admin *gin.RouterGroup, h *handler.Handlers) {
+	caps := admin.Group("/spending-caps")
+	{
+		caps.GET("", h.Admin.SpendingCap.List)
+		caps.GET("/:scope/:id", h.Admin.SpendingCap.Get)
+		caps.PUT("/:scope/:id", h.Admin.SpendingCap.Upsert)
+		caps.DELETE("/:scope/:id", h.Admin.SpendingCap.Delete)
+		caps.POST("/:scope/:id/lift", h.Admin.SpendingCap.Lift)
+	}
+}
+
+func registerStatementRoutes(admin *gin.RouterGroup, h *handler.Handlers) {
+	statements := admin.Group("/statements")
+	{
+		statements.GET("", h.Admin.Statement.List)
+		statements.POST("/generate", h.Admin.Statement.Generate)
+		statements.GET("/:id", h.Admin.Statement.Get)
+		statements.GET("/:id/download", h.Admin.Statement.Download)
+	}
+}
+
+func registerStaffRoutes(admin *gin.RouterGroup, h *handler.Handlers) {
+	staff := admin.Group("/staff")
+	{
+		staff.GET("", h.Admin.Staff.List)
+		staff.POST("", h.Admin.Staff.Create)
+		staff.PUT("/:id", h.Admin.Staff.Update)
+		staff.DELETE("/:id", h.Admin.Staff.Revoke)
+		staff.POST("/:id/reset-password", h.Admin.Staff.ResetPassword)
+	}
+}
+
+func registerAccountShardRoutes(admin *gin.RouterGroup, h *handler.Handlers) {
+	shards := admin.Group("/account-shards")
+	{
+		shards.GET("", h.Admin.AccountShard.List)
+		shards.GET("/:id", h.Admin.AccountShard.GetByID)
+		shards.POST("", h.Admin.AccountShard.Create)
+		shards.PUT("/:id", h.Admin.AccountShard.Update)
+		shards.DELETE("/:id", h.Admin.AccountShard.Delete)
+		shards.POST("/:id/isolate", h.Admin.AccountShard.Isolate)
+		shards.POST("/:id/restore", h.Admin.AccountShard.Restore)
+		shards.POST("/:id/accounts", h.Admin.AccountShard.AddMembers)
+		shards.DELETE("/:id/accounts", h.Admin.AccountShard.RemoveMembers)
+	}
+	rules := admin.Group("/account-shard-rules")
+	{
+		rules.GET("", h.Admin.AccountShard.ListRules)
+		rules.POST("", h.Admin.AccountShard.CreateRule)
+		rules.PUT("/:id", h.Admin.AccountShard.UpdateRule)
+		rules.DELETE("/:id", h.Admin.AccountShard.DeleteRule)
+	}
+}
+
+func registerRoutingRuleRoutes(admin *gin.RouterGroup, h *handler.Handlers) {
+	rules := admin.Group("/routing-rules")
+	{
+		rules.GET("", h.Admin.RoutingRule.List)
+		rules.POST("/evaluate", h.Admin.RoutingRule.Evaluate)
+		rules.GET("/:id", h.Admin.RoutingRule.GetByID)
+		rules.POST("", h.Admin.RoutingRule.Create)
+		rules.PUT("/:id", h.Admin.RoutingRule.Update)
+		rules.DELETE("/:id", h.Admin.RoutingRule.Delete)
+	}
+}
+
+func registerGraphQLRoutes(admin *gin.RouterGroup, h *handler.Handlers) {
+	graphql := admin.Group("/graphql")
+	{
+		graphql.POST("", h.Admin.GraphQL.Query)
+		graphql.GET("/schema", h.Admin.GraphQL.Schema)
+	}
+}
+
+func registerTeamRoutes(admin *gin.RouterGroup, h *handler.Handlers) {
+	teams := admin.Group("/teams")
+	{
+		teams.GET("", h.Admin.Team.List)
+		teams.GET("/:id", h.Admin.Team.Get)
+		teams.GET("/:id/usage", h.Admin.Team.Usage)
+	}
+}
+
+func registerTrashRoutes(admin *gin.RouterGroup, h *handler.Handlers) {
+	trash := admin.Group("/trash")
+	{
+		trash.GET("/api-keys", h.Admin.Trash.ListAPIKeys)
+		trash.POST("/api-keys/:id/restore", h.Admin.Trash.RestoreAPIKey)
+		trash.GET("/accounts", h.Admin.Trash.ListAccounts)
+		trash.POST("/accounts/:id/restore", h.Admin.Trash.RestoreAccount)
+	}
+}
+
+func registerJobRoutes(admin *gin.RouterGroup, h *handler.Handlers) {
+	jobs := admin.Group("/jobs")
+	{
+		jobs.GET("/queues", h.Admin.JobQueue.Queues)
+		jobs.GET("/schedules", h.Admin.CronJob.List)
+		jobs.POST("/schedules/:name/run", h.Admin.CronJob.Run)
+	}
+}
@@ -0,0 +1,352 @@
+package routes
+
+import (
+	"encoding/json"
+	"fmt"
+	"html/template"
+	"net/http"
+	"net/url"
+	"strings"
+	"sync"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/Wei-Shaw/sub2api/internal/handler"
+	"github.com/Wei-Shaw/sub2api/internal/handler/admin"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/graphql"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/openapi"
+	"github.com/Wei-Shaw/sub2api/internal/server/middleware"
+	"github.com/Wei-Shaw/sub2api/internal/service"
+
+	"github.com/gin-gonic/gin"
+)
+
+// RegisterAPIDocsRoutes 注册 /openapi.json 与 /docs（Swagger UI）。
+// 需在其它路由注册完成后调用；文档在首次请求时根据 r.Routes() 生成并缓存。
+func RegisterAPIDocsRoutes(r *gin.Engine, cfg *config.Config) {
+	if cfg == nil || !cfg.APIDocs.Enabled {
+		return
+	}
+	g := newAPIDocsGenerator(cfg)
+
+	var (
+		once    sync.Once
+		spec    []byte
+		specErr error
+	)
+	r.GET("/openapi.json", func(c *gin.Context) {
+		once.Do(func() { spec, specErr = json.Marshal(g.Build(r.Routes())) })
+		if specErr != nil {
+			c.JSON(http.StatusInternalServerError, gin.H{"error": "failed to build openapi spec"})
+			return
+		}
+		c.Data(http.StatusOK, "application/json; charset=utf-8", spec)
+	})
+
+	assets := strings.TrimSuffix(strings.TrimSpace(cfg.APIDocs.SwaggerUIURL), "/")
+	assetOrigin := assets
+	if u, err := url.Parse(assets); err == nil {
+		assetOrigin = u.Scheme + "://" + u.Host
+	}
+	r.GET("/docs", func(c *gin.Context) {
+		// Swagger UI 从 CDN 加载，需放宽默认 CSP；初始化脚本沿用安全头中间件生成的 nonce
+		scriptSrc := "'unsafe-inline'"
+		nonce := middleware.GetNonceFromContext(c)
+		if nonce != "" {
+			scriptSrc = "'nonce-" + nonce + "'"
+		}
+		c.Header("Content-Security-Policy", fmt.Sprintf(
+			"default-src 'self'; script-src 'self' %s %s; style-src 'self' 'unsafe-inline' %s; img-src 'self' data: %s; connect-src 'self'; frame-ancestors 'none'",
+			scriptSrc, assetOrigin, assetOrigin, assetOrigin,
+		))
+		c.Header("Content-Type", "text/html; charset=utf-8")
+		c.Status(http.StatusOK)
+		_ = swaggerUITemplate.Execute(c.Writer, struct {
+			Assets string
+			Nonce  string
+		}{Assets: assets, Nonce: nonce})
+	})
+}
+
+var swaggerUITemplate = template.Must(template.New("swagger-ui").Parse(`<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>Sub2API - API Docs</title>
+<link rel="stylesheet" href="{{.Assets}}/swagger-ui.css">
+</head>
+<body>
+<div id="swagger-ui"></div>
+<script src="{{.Assets}}/swagger-ui-bundle.js"></script>
+<script{{if .Nonce}} nonce="{{.Nonce}}"{{end}}>
+window.ui = SwaggerUIBundle({url: "/openapi.json", dom_id: "#swagger-ui", deepLinking: true});
+</script>
+</body>
+</html>
+`))
+
+// newAPIDocsGenerator 配置认证方式与接口注解。
+// 未注解的路由同样会出现在文档中（路径、方法、路径参数与认证方式来自路由表）。
+func newAPIDocsGenerator(cfg *config.Config) *openapi.Generator {
+	g := openapi.NewGenerator(openapi.Info{
+		Title:       "Sub2API",
+		Version:     "v1",
+		Description: "管理后台 / 用户接口（/api/v1，统一 {code, message, data} 响应）与 API 网关（Claude / OpenAI / Gemini 兼容协议）。",
+	})
+
+	g.AddSecurityScheme("bearerAuth", &openapi.SecurityScheme{Type: "http", Scheme: "bearer", BearerFormat: "JWT", Description: "登录后获取的 access token"})
+	g.AddSecurityScheme("adminApiKey", &openapi.SecurityScheme{Type: "apiKey", In: "header", Name: "x-api-key", Description: "管理员 API Key（仅限 /api/v1/admin）"})
+	g.AddSecurityScheme("apiKeyBearer", &openapi.SecurityScheme{Type: "http", Scheme: "bearer", Description: "网关 API Key（Authorization: Bearer sk-...）"})
+	g.AddSecurityScheme("apiKeyHeader", &openapi.SecurityScheme{Type: "apiKey", In: "header", Name: "x-api-key", Description: "网关 API Key"})
+	g.AddSecurityScheme("googApiKey", &openapi.SecurityScheme{Type: "apiKey", In: "header", Name: "x-goog-api-key", Description: "网关 API Key（Gemini SDK 兼容）"})
+
+	g.SecurityFor("/api/v1", "bearerAuth")
+	g.SecurityFor("/api/v1/admin", "bearerAuth", "adminApiKey")
+	g.SecurityFor("/api/v1/auth")
+	g.SecurityFor("/api/v1/auth/me", "bearerAuth")
+	g.SecurityFor("/api/v1/auth/revoke-all-sessions", "bearerAuth")
+	g.SecurityFor("/api/v1/settings/public")
+	for _, prefix := range []string{"/v1", "/responses", "/antigravity", "/sora"} {
+		g.SecurityFor(prefix, "apiKeyBearer", "apiKeyHeader")
+	}
+	for _, prefix := range []string{"/v1beta", "/antigravity/v1beta"} {
+		g.SecurityFor(prefix, "apiKeyBearer", "apiKeyHeader", "googApiKey")
+	}
+	g.SecurityFor("/sora/media-signed")
+	if !cfg.Gateway.SoraMediaRequireAPIKey {
+		g.SecurityFor("/sora/media")
+	}
+	g.EnvelopeFor("/api/v1")
+	g.Skip("/openapi.json", "/docs")
+
+	describeGatewayRoutes(g)
+	describeUserRoutes(g)
+	describeAdminRoutes(g)
+	return g
+}
+
+var (
+	// 网关请求 / 响应体沿用上游协议，这里只列出主要字段
+	anthropicMessagesSchema = &openapi.Schema{
+		Type:        "object",
+		Description: "Anthropic Messages API 请求体",
+		Properties: map[string]*openapi.Schema{
+			"model":       {Type: "string"},
+			"max_tokens":  {Type: "integer"},
+			"messages":    {Type: "array", Items: &openapi.Schema{Type: "object"}},
+			"system":      {Description: "string 或 content block 数组"},
+			"tools":       {Type: "array", Items: &openapi.Schema{Type: "object"}},
+			"stream":      {Type: "boolean"},
+			"temperature": {Type: "number"},
+			"metadata":    {Type: "object"},
+		},
+		Required:             []string{"model", "messages"},
+		AdditionalProperties: &openapi.Schema{},
+	}
+	openAIResponsesSchema = &openapi.Schema{
+		Type:        "object",
+		Description: "OpenAI Responses API 请求体",
+		Properties: map[string]*openapi.Schema{
+			"model":        {Type: "string"},
+			"input":        {Description: "string 或 input item 数组"},
+			"instructions": {Type: "string"},
+			"tools":        {Type: "array", Items: &openapi.Schema{Type: "object"}},
+			"stream":       {Type: "boolean"},
+			"reasoning":    {Type: "object"},
+		},
+		Required:             []string{"model"},
+		AdditionalProperties: &openapi.Schema{},
+	}
+	chatCompletionsSchema = &openapi.Schema{
+		Type:        "object",
+		Description: "OpenAI Chat Completions 请求体",
+		Properties: map[string]*openapi.Schema{
+			"model":    {Type: "string"},
+			"messages": {Type: "array", Items: &openapi.Schema{Type: "object"}},
+			"stream":   {Type: "boolean"},
+		},
+		Required:             []string{"model", "messages"},
+		AdditionalProperties: &openapi.Schema{},
+	}
+	completionsSchema = &openapi.Schema{
+		Type:        "object",
+		Description: "OpenAI 旧版 Completions 请求体（n > 1 时并发请求上游后合并，best_of 须与 n 相同；logprobs 返回标记为 emulated 的空结构）",
+		Properties: map[string]*openapi.Schema{
+			"model":       {Type: "string"},
+			"prompt":      {Description: "string 或仅含一个 string 的数组"},
+			"suffix":      {Type: "string"},
+			"echo":        {Type: "boolean"},
+			"max_tokens":  {Type: "integer"},
+			"temperature": {Type: "number"},
+			"top_p":       {Type: "number"},
+			"stop":        {Description: "string 或 string 数组（最多 4 个）"},
+			"stream":      {Type: "boolean"},
+		},
+		Required:             []string{"model", "prompt"},
+		AdditionalProperties: &openapi.Schema{},
+	}
+	geminiGenerateSchema = &openapi.Schema{
+		Type:        "object",
+		Description: "Gemini generateContent / streamGenerateContent / countTokens 请求体",
+		Properties: map[string]*openapi.Schema{
+			"contents":          {Type: "array", Items: &openapi.Schema{Type: "object"}},
+			"systemInstruction": {Type: "object"},
+			"tools":             {Type: "array", Items: &openapi.Schema{Type: "object"}},
+			"generationConfig":  {Type: "object"},
+		},
+		AdditionalProperties: &openapi.Schema{},
+	}
+	upstreamObjectSchema = &openapi.Schema{Type: "object", AdditionalProperties: &openapi.Schema{}}
+)
+
+func describeGatewayRoutes(g *openapi.Generator) {
+	g.DescribeHandler((*handler.GatewayHandler).Messages, openapi.Route{Summary: "Create message (Anthropic Messages API)", Request: anthropicMessagesSchema, Response: upstreamObjectSchema, Stream: true})
+	g.DescribeHandler((*handler.GatewayHandler).CountTokens, openapi.Route{Summary: "Count tokens", Request: anthropicMessagesSchema, Response: upstreamObjectSchema})
+	g.DescribeHandler((*handler.GatewayHandler).Models, openapi.Route{Summary: "List models", Response: upstreamObjectSchema})
+	g.DescribeHandler((*handler.GatewayHandler).AntigravityModels, openapi.Route{Summary: "List Antigravity models", Response: upstreamObjectSchema})
+	g.DescribeHandler((*handler.GatewayHandler).Usage, openapi.Route{Summary: "Get API key usage and quota", Response: upstreamObjectSchema})
+	g.DescribeHandler((*handler.GatewayHandler).Completions, openapi.Route{Summary: "Create completion (legacy OpenAI Completions API)", Request: completionsSchema, Response: upstreamObjectSchema, Stream: true})
+	g.DescribeHandler((*handler.OpenAIGatewayHandler).Responses, openapi.Route{Summary: "Create response (OpenAI Responses API)", Request: openAIResponsesSchema, Response: upstreamObjectSchema, Stream: true})
+	g.DescribeHandler((*handler.SoraGatewayHandler).ChatCompletions, openapi.Route{Summary: "Generate image / video (Chat Completions format)", Request: chatCompletionsSchema, Response: upstreamObjectSchema, Stream: true})
+	g.DescribeHandler((*handler.SoraGatewayHandler).MediaProxy, openapi.Route{Summary: "Proxy generated media"})
+	g.DescribeHandler((*handler.SoraGatewayHandler).MediaProxySigned, openapi.Route{Summary: "Proxy generated media (signed URL)"})
+	g.DescribeHandler((*handler.GatewayHandler).GeminiV1BetaListModels, openapi.Route{Summary: "List models (Gemini)", Response: upstreamObjectSchema})
+	g.DescribeHandler((*handler.GatewayHandler).GeminiV1BetaGetModel, openapi.Route{Summary: "Get model (Gemini)", Response: upstreamObjectSchema})
+	g.DescribeHandler((*handler.GatewayHandler).GeminiV1BetaModels, openapi.Route{
+		Summary:     "Call model action (Gemini)",
+		Description: "modelAction 形如 /{model}:generateContent、/{model}:streamGenerateContent?alt=sse 或 /{model}:countTokens",
+		Request:     geminiGenerateSchema,
+		Response:    upstreamObjectSchema,
+		Stream:      true,
+	})
+	g.Describe(http.MethodPost, "/v1/chat/completions", openapi.Route{
+		Summary:     "Chat Completions (not supported)",
+		Description: "固定返回 400，请改用 /v1/responses",
+		Tags:        []string{"v1"},
+	})
+}
+
+func describeUserRoutes(g *openapi.Generator) {
+	g.DescribeHandler((*handler.APIKeyHandler).Create, openapi.Route{Request: handler.CreateAPIKeyRequest{}})
+	g.DescribeHandler((*handler.APIKeyHandler).Update, openapi.Route{Request: handler.UpdateAPIKeyRequest{}})
+	g.DescribeHandler((*handler.APIKeyHandler).Rotate, openapi.Route{Request: handler.RotateAPIKeyRequest{}})
+	g.DescribeHandler((*handler.AuthHandler).Register, openapi.Route{Request: handler.RegisterRequest{}})
+	g.DescribeHandler((*handler.AuthHandler).SendVerifyCode, openapi.Route{Request: handler.SendVerifyCodeRequest{}})
+	g.DescribeHandler((*handler.AuthHandler).Login, openapi.Route{Request: handler.LoginRequest{}})
+	g.DescribeHandler((*handler.AuthHandler).Login2FA, openapi.Route{Request: handler.Login2FARequest{}})
+	g.DescribeHandler((*handler.AuthHandler).ValidatePromoCode, openapi.Route{Request: handler.ValidatePromoCodeRequest{}})
+	g.DescribeHandler((*handler.AuthHandler).ValidateInvitationCode, openapi.Route{Request: handler.ValidateInvitationCodeRequest{}})
+	g.DescribeHandler((*handler.AuthHandler).ForgotPassword, openapi.Route{Request: handler.ForgotPasswordRequest{}})
+	g.DescribeHandler((*handler.AuthHandler).ResetPassword, openapi.Route{Request: handler.ResetPasswordRequest{}})
+	g.DescribeHandler((*handler.AuthHandler).RefreshToken, openapi.Route{Request: handler.RefreshTokenRequest{}})
+	g.DescribeHandler((*handler.AuthHandler).Logout, openapi.Route{Request: handler.LogoutRequest{}})
+	g.DescribeHandler((*handler.NotificationHandler).UpdatePreferences, openapi.Route{Request: handler.UpdateNotificationPreferencesRequest{}})
+	g.DescribeHandler((*handler.PaymentHandler).CreateCheckout, openapi.Route{Request: handler.CreateCheckoutRequest{}})
+	g.DescribeHandler((*handler.RedeemHandler).Redeem, openapi.Route{Request: handler.RedeemRequest{}})
+	g.DescribeHandler((*handler.TeamHandler).Create, openapi.Route{Request: handler.CreateTeamRequest{}})
+	g.DescribeHandler((*handler.TeamHandler).Update, openapi.Route{Request: handler.UpdateTeamRequest{}})
+	g.DescribeHandler((*handler.TeamHandler).SetBudget, openapi.Route{Request: handler.SetTeamBudgetRequest{}})
+	g.DescribeHandler((*handler.TeamHandler).UpsertMember, openapi.Route{Request: handler.UpsertTeamMemberRequest{}})
+	g.DescribeHandler((*handler.TotpHandler).InitiateSetup, openapi.Route{Request: handler.TotpSetupRequest{}})
+	g.DescribeHandler((*handler.TotpHandler).Enable, openapi.Route{Request: handler.TotpEnableRequest{}})
+	g.DescribeHandler((*handler.TotpHandler).RegenerateBackupCodes, openapi.Route{Request: handler.TotpRegenerateBackupCodesRequest{}})
+	g.DescribeHandler((*handler.TotpHandler).Disable, openapi.Route{Request: handler.TotpDisableRequest{}})
+	g.DescribeHandler((*handler.UsageHandler).DashboardAPIKeysUsage, openapi.Route{Request: handler.BatchAPIKeysUsageRequest{}})
+	g.DescribeHandler((*handler.UserHandler).ChangePassword, openapi.Route{Request: handler.ChangePasswordRequest{}})
+	g.DescribeHandler((*handler.UserHandler).UpdateProfile, openapi.Route{Request: handler.UpdateProfileRequest{}})
+}
+
+func describeAdminRoutes(g *openapi.Generator) {
+	g.DescribeHandler((*admin.AccountHandler).ImportData, openapi.Route{Request: admin.DataImportRequest{}})
+	g.DescribeHandler((*admin.AccountHandler).CheckMixedChannel, openapi.Route{Request: admin.CheckMixedChannelRequest{}})
+	g.DescribeHandler((*admin.AccountHandler).Create, openapi.Route{Request: admin.CreateAccountRequest{}})
+	g.DescribeHandler((*admin.AccountHandler).Update, openapi.Route{Request: admin.UpdateAccountRequest{}})
+	g.DescribeHandler((*admin.AccountHandler).Test, openapi.Route{Request: admin.TestAccountRequest{}})
+	g.DescribeHandler((*admin.AccountHandler).SyncFromCRS, openapi.Route{Request: admin.SyncFromCRSRequest{}})
+	g.DescribeHandler((*admin.AccountHandler).PreviewFromCRS, openapi.Route{Request: admin.PreviewFromCRSRequest{}})
+	g.DescribeHandler((*admin.AccountHandler).BatchUpdateCredentials, openapi.Route{Request: admin.BatchUpdateCredentialsRequest{}})
+	g.DescribeHandler((*admin.AccountHandler).BulkUpdate, openapi.Route{Request: admin.BulkUpdateAccountsRequest{}})
+	g.DescribeHandler((*admin.OAuthHandler).GenerateAuthURL, openapi.Route{Request: admin.GenerateAuthURLRequest{}})
+	g.DescribeHandler((*admin.OAuthHandler).GenerateSetupTokenURL, openapi.Route{Request: admin.GenerateAuthURLRequest{}})
+	g.DescribeHandler((*admin.OAuthHandler).ExchangeCode, openapi.Route{Request: admin.ExchangeCodeRequest{}})
+	g.DescribeHandler((*admin.OAuthHandler).ExchangeSetupTokenCode, openapi.Route{Request: admin.ExchangeCodeRequest{}})
+	g.DescribeHandler((*admin.OAuthHandler).CookieAuth, openapi.Route{Request: admin.CookieAuthRequest{}})
+	g.DescribeHandler((*admin.OAuthHandler).SetupTokenCookieAuth, openapi.Route{Request: admin.CookieAuthRequest{}})
+	g.DescribeHandler((*admin.AccountHandler).SetSchedulable, openapi.Route{Request: admin.SetSchedulableRequest{}})
+	g.DescribeHandler((*admin.AccountHandler).BatchRefreshTier, openapi.Route{Request: admin.BatchRefreshTierRequest{}})
+	g.DescribeHandler((*admin.AccountShardHandler).Create, openapi.Route{Request: admin.CreateAccountShardRequest{}})
+	g.DescribeHandler((*admin.AccountShardHandler).Update, openapi.Route{Request: admin.UpdateAccountShardRequest{}})
+	g.DescribeHandler((*admin.AccountShardHandler).Isolate, openapi.Route{Request: admin.IsolateAccountShardRequest{}})
+	g.DescribeHandler((*admin.AccountShardHandler).CreateRule, openapi.Route{Request: admin.AccountShardRuleRequest{}})
+	g.DescribeHandler((*admin.AccountShardHandler).UpdateRule, openapi.Route{Request: admin.AccountShardRuleRequest{}})
+	g.DescribeHandler((*admin.AnnouncementHandler).Create, openapi.Route{Request: admin.CreateAnnouncementRequest{}})
+	g.DescribeHandler((*admin.AnnouncementHandler).Update, openapi.Route{Request: admin.UpdateAnnouncementRequest{}})
+	g.DescribeHandler((*admin.AntigravityOAuthHandler).GenerateAuthURL, openapi.Route{Request: admin.AntigravityGenerateAuthURLRequest{}})
+	g.DescribeHandler((*admin.AntigravityOAuthHandler).ExchangeCode, openapi.Route{Request: admin.AntigravityExchangeCodeRequest{}})
+	g.DescribeHandler((*admin.AntigravityOAuthHandler).RefreshToken, openapi.Route{Request: admin.AntigravityRefreshTokenRequest{}})
+	g.DescribeHandler((*admin.ChallengeHandler).Resolve, openapi.Route{Request: admin.ResolveChallengeRequest{}})
+	g.DescribeHandler((*admin.CopilotHandler).StartDeviceAuth, openapi.Route{Request: admin.CopilotDeviceCodeRequest{}})
+	g.DescribeHandler((*admin.CopilotHandler).PollDeviceAuth, openapi.Route{Request: admin.CopilotPollRequest{}})
+	g.DescribeHandler((*admin.DashboardHandler).BackfillAggregation, openapi.Route{Request: admin.DashboardAggregationBackfillRequest{}})
+	g.DescribeHandler((*admin.DashboardHandler).GetBatchUsersUsage, openapi.Route{Request: admin.BatchUsersUsageRequest{}})
+	g.DescribeHandler((*admin.DashboardHandler).GetBatchAPIKeysUsage, openapi.Route{Request: admin.BatchAPIKeysUsageRequest{}})
+	g.DescribeHandler((*admin.DataSubjectHandler).Create, openapi.Route{Request: admin.CreateDataSubjectRequest{}})
+	g.DescribeHandler((*admin.ErrorPassthroughHandler).Create, openapi.Route{Request: admin.CreateErrorPassthroughRuleRequest{}})
+	g.DescribeHandler((*admin.ErrorPassthroughHandler).Update, openapi.Route{Request: admin.UpdateErrorPassthroughRuleRequest{}})
+	g.DescribeHandler((*admin.FeatureFlagHandler).Upsert, openapi.Route{Request: admin.UpsertFeatureFlagRequest{}})
+	g.DescribeHandler((*admin.GeminiOAuthHandler).GenerateAuthURL, openapi.Route{Request: admin.GeminiGenerateAuthURLRequest{}})
+	g.DescribeHandler((*admin.GeminiOAuthHandler).ExchangeCode, openapi.Route{Request: admin.GeminiExchangeCodeRequest{}})
+	g.DescribeHandler((*admin.GroupHandler).Create, openapi.Route{Request: admin.CreateGroupRequest{}})
+	g.DescribeHandler((*admin.GroupHandler).Update, openapi.Route{Request: admin.UpdateGroupRequest{}})
+	g.DescribeHandler((*admin.GroupHandler).UpdateSortOrder, openapi.Route{Request: admin.UpdateSortOrderRequest{}})
+	g.DescribeHandler((*admin.MaintenanceHandler).Update, openapi.Route{Request: admin.UpdateMaintenanceRequest{}})
+	g.DescribeHandler((*admin.ModelCanaryHandler).Create, openapi.Route{Request: admin.CreateModelCanaryRouteRequest{}})
+	g.DescribeHandler((*admin.ModelCanaryHandler).Update, openapi.Route{Request: admin.UpdateModelCanaryRouteRequest{}})
+	g.DescribeHandler((*admin.OpenAIOAuthHandler).GenerateAuthURL, openapi.Route{Request: admin.OpenAIGenerateAuthURLRequest{}})
+	g.DescribeHandler((*admin.OpenAIOAuthHandler).ExchangeCode, openapi.Route{Request: admin.OpenAIExchangeCodeRequest{}})
+	g.DescribeHandler((*admin.OpenAIOAuthHandler).RefreshToken, openapi.Route{Request: admin.OpenAIRefreshTokenRequest{}})
+	g.DescribeHandler((*admin.PrepaidCreditHandler).TopUp, openapi.Route{Request: admin.TopUpCreditRequest{}})
+	g.DescribeHandler((*admin.PromoHandler).Create, openapi.Route{Request: admin.CreatePromoCodeRequest{}})
+	g.DescribeHandler((*admin.PromoHandler).Update, openapi.Route{Request: admin.UpdatePromoCodeRequest{}})
+	g.DescribeHandler((*admin.ProxyHandler).Create, openapi.Route{Request: admin.CreateProxyRequest{}})
+	g.DescribeHandler((*admin.ProxyHandler).Update, openapi.Route{Request: admin.UpdateProxyRequest{}})
+	g.DescribeHandler((*admin.ProxyHandler).BatchCreate, openapi.Route{Request: admin.BatchCreateRequest{}})
+	g.DescribeHandler((*admin.RedeemHandler).Generate, openapi.Route{Request: admin.GenerateRedeemCodesRequest{}})
+	g.DescribeHandler((*admin.SessionImportHandler).Import, openapi.Route{Request: admin.SessionImportRequest{}})
+	g.DescribeHandler((*admin.SettingHandler).UpdateSettings, openapi.Route{Request: admin.UpdateSettingsRequest{}})
+	g.DescribeHandler((*admin.SettingHandler).TestSMTPConnection, openapi.Route{Request: admin.TestSMTPRequest{}})
+	g.DescribeHandler((*admin.SettingHandler).SendTestEmail, openapi.Route{Request: admin.SendTestEmailRequest{}})
+	g.DescribeHandler((*admin.SettingHandler).UpdateStreamTimeoutSettings, openapi.Route{Request: admin.UpdateStreamTimeoutSettingsRequest{}})
+	g.DescribeHandler((*admin.SpendingCapHandler).Upsert, openapi.Route{Request: admin.UpsertSpendingCapRequest{}})
+	g.DescribeHandler((*admin.StaffHandler).Create, openapi.Route{Request: admin.CreateStaffRequest{}})
+	g.DescribeHandler((*admin.StaffHandler).Update, openapi.Route{Request: admin.UpdateStaffRequest{}})
+	g.DescribeHandler((*admin.StaffHandler).ResetPassword, openapi.Route{Request: admin.ResetStaffPasswordRequest{}})
+	g.DescribeHandler((*admin.StatementHandler).Generate, openapi.Route{Request: admin.GenerateStatementRequest{}})
+	g.DescribeHandler((*admin.SubscriptionHandler).Assign, openapi.Route{Request: admin.AssignSubscriptionRequest{}})
+	g.DescribeHandler((*admin.SubscriptionHandler).BulkAssign, openapi.Route{Request: admin.BulkAssignSubscriptionRequest{}})
+	g.DescribeHandler((*admin.SubscriptionHandler).Extend, openapi.Route{Request: admin.AdjustSubscriptionRequest{}})
+	g.DescribeHandler((*admin.TrafficMirrorHandler).CreateRule, openapi.Route{Request: admin.CreateTrafficMirrorRuleRequest{}})
+	g.DescribeHandler((*admin.TrafficMirrorHandler).UpdateRule, openapi.Route{Request: admin.UpdateTrafficMirrorRuleRequest{}})
+	g.DescribeHandler((*admin.UsageHandler).CreateCleanupTask, openapi.Route{Request: admin.CreateUsageCleanupTaskRequest{}})
+	g.DescribeHandler((*admin.UserAttributeHandler).CreateDefinition, openapi.Route{Request: admin.CreateAttributeDefinitionRequest{}})
+	g.DescribeHandler((*admin.UserAttributeHandler).UpdateDefinition, openapi.Route{Request: admin.UpdateAttributeDefinitionRequest{}})
+	g.DescribeHandler((*admin.UserAttributeHandler).ReorderDefinitions, openapi.Route{Request: admin.ReorderRequest{}})
+	g.DescribeHandler((*admin.UserAttributeHandler).UpdateUserAttributes, openapi.Route{Request: admin.UpdateUserAttributesRequest{}})
+	g.DescribeHandler((*admin.UserAttributeHandler).GetBatchUserAttributes, openapi.Route{Request: admin.BatchGetUserAttributesRequest{}})
+	g.DescribeHandler((*admin.UserHandler).Create, openapi.Route{Request: admin.CreateUserRequest{}})
+	g.DescribeHandler((*admin.UserHandler).Update, openapi.Route{Request: admin.UpdateUserRequest{}})
+	g.DescribeHandler((*admin.UserHandler).UpdateBalance, openapi.Route{Request: admin.UpdateBalanceRequest{}})
+	g.DescribeHandler((*admin.AccountShardHandler).AddMembers, openapi.Route{Request: admin.AccountShardMembersRequest{}})
+	g.DescribeHandler((*admin.AccountShardHandler).RemoveMembers, openapi.Route{Request: admin.AccountShardMembersRequest{}})
+	g.DescribeHandler((*admin.RoutingRuleHandler).Create, openapi.Route{Request: admin.RoutingRuleRequest{}})
+	g.DescribeHandler((*admin.RoutingRuleHandler).Update, openapi.Route{Request: admin.RoutingRuleRequest{}})
+	g.DescribeHandler((*admin.RoutingRuleHandler).Evaluate, openapi.Route{Request: service.RoutingEvaluateInput{}, Response: admin.RoutingEvaluateResponse{}})
+	g.DescribeHandler((*admin.RoutingRuleHandler).UpdateKeyTags, openapi.Route{Request: admin.APIKeyTagsRequest{}})
+	g.DescribeHandler((*admin.GraphQLHandler).Query, openapi.Route{
+		Summary:     "Execute GraphQL query",
+		Description: "Read-only queries over accounts, users, API keys, usage and alerts. Limited to depth 6 and complexity 300 (10 per resolver-backed field, 1 per plain field). Schema (SDL): GET /api/v1/admin/graphql/schema",
+		Request:     graphql.Request{},
+		Response:    graphql.Result{},
+		Raw:         true,
+	})
+	g.DescribeHandler((*admin.GraphQLHandler).Schema, openapi.Route{Summary: "Get GraphQL schema (SDL)", Raw: true})
+}
@@ -18,29 +18,49 @@ func RegisterGatewayRoutes(
 	apiKeyAuth middleware.APIKeyAuthMiddleware,
 	apiKeyService *service.APIKeyService,
 	subscriptionService *service.SubscriptionService,
+	policyService *service.APIKeyPolicyService,
 	opsService *service.OpsService,
+	requestLogService *service.RequestLogService,
+	maintenanceService *service.MaintenanceService,
+	prepaidCreditService *service.PrepaidCreditService,
+	routingRuleService *service.RoutingRuleService,
 	cfg *config.Config,
 ) {
-	bodyLimit := middleware.RequestBodyLimit(cfg.Gateway.MaxBodySize)
+	bodyLimit := middleware.GatewayBodyLimit(cfg.Gateway.MaxBodySize, cfg.Gateway.BodyLimits)
 	soraMaxBodySize := cfg.Gateway.SoraMaxBodySize
 	if soraMaxBodySize <= 0 {
 		soraMaxBodySize = cfg.Gateway.MaxBodySize
 	}
-	soraBodyLimit := middleware.RequestBodyLimit(soraMaxBodySize)
+	soraBodyLimit := middleware.GatewayBodyLimit(soraMaxBodySize, cfg.Gateway.BodyLimits)
 	clientRequestID := middleware.ClientRequestID()
+	// 响应压缩需在 opsErrorLogger/requestLogger 之前注册，使其捕获未压缩的响应体
+	compression := middleware.ResponseCompression(cfg.Gateway.ResponseCompression)
 	opsErrorLogger := handler.OpsErrorLoggerMiddleware(opsService)
+	requestLogger := handler.RequestLogMiddleware(requestLogService)
+	maintenance := middleware.MaintenanceGuard(maintenanceService)
+	// 预付费额度预占（Sora 按图片 / 视频计费，无法按 token 预估，不挂载）
+	prepaidHold := middleware.PrepaidCreditHold(prepaidCreditService)
+	// 声明式路由规则需在额度预占之前执行，使预占与计费使用切换后的分组
+	routingRules := middleware.RoutingRules(routingRuleService)
 
 	// API网关（Claude API兼容）
 	gateway := r.Group("/v1")
 	gateway.Use(bodyLimit)
+	gateway.Use(compression)
 	gateway.Use(clientRequestID)
 	gateway.Use(opsErrorLogger)
+	gateway.Use(requestLogger)
+	gateway.Use(maintenance)
 	gateway.Use(gin.HandlerFunc(apiKeyAuth))
+	gateway.Use(routingRules)
+	gateway.Use(prepaidHold)
 	{
 		gateway.POST("/messages", h.Gateway.Messages)
 		gateway.POST("/messages/count_tokens", h.Gateway.CountTokens)
 		gateway.GET("/models", h.Gateway.Models)
 		gateway.GET("/usage", h.Gateway.Usage)
+		// OpenAI 旧版 Completions API：提示词包装为单轮对话，经 /v1/messages 链路处理
+		gateway.POST("/completions", h.Gateway.Completions)
 		// OpenAI Responses API
 		gateway.POST("/responses", h.OpenAIGateway.Responses)
 		// 明确阻止旧协议入口：OpenAI 仅支持 Responses API，避免客户端误解为会自动路由到其它平台。
@@ -54,12 +74,47 @@ func RegisterGatewayRoutes(
 		})
 	}
 
+	// 网关本地资源（不产生上游调用）：不挂载声明式路由规则与额度预占
+	gatewayLocal := r.Group("/v1")
+	gatewayLocal.Use(bodyLimit)
+	gatewayLocal.Use(compression)
+	gatewayLocal.Use(clientRequestID)
+	gatewayLocal.Use(opsErrorLogger)
+	gatewayLocal.Use(requestLogger)
+	gatewayLocal.Use(maintenance)
+	gatewayLocal.Use(gin.HandlerFunc(apiKeyAuth))
+	{
+		// 服务端会话持久化（conversation.enabled）
+		gatewayLocal.GET("/conversations", h.Gateway.ListConversations)
+		gatewayLocal.GET("/conversations/:id", h.Gateway.GetConversation)
+		gatewayLocal.DELETE("/conversations/:id", h.Gateway.DeleteConversation)
+		gatewayLocal.POST("/conversations/:id/fork", h.Gateway.ForkConversation)
+		gatewayLocal.GET("/conversations/:id/tree", h.Gateway.ConversationTree)
+		// 多模态附件文件（attachments.enabled，需启用对象存储）
+		gatewayLocal.POST("/files", h.Gateway.UploadFile)
+		gatewayLocal.GET("/files", h.Gateway.ListFiles)
+		gatewayLocal.GET("/files/:id", h.Gateway.GetFile)
+		gatewayLocal.DELETE("/files/:id", h.Gateway.DeleteFile)
+		// 提示词模板（prompt_templates.enabled）
+		gatewayLocal.POST("/prompt-templates", h.Gateway.CreatePromptTemplate)
+		gatewayLocal.GET("/prompt-templates", h.Gateway.ListPromptTemplates)
+		gatewayLocal.GET("/prompt-templates/:id", h.Gateway.GetPromptTemplate)
+		gatewayLocal.PUT("/prompt-templates/:id", h.Gateway.UpdatePromptTemplate)
+		gatewayLocal.DELETE("/prompt-templates/:id", h.Gateway.DeletePromptTemplate)
+		gatewayLocal.GET("/prompt-templates/:id/versions", h.Gateway.ListPromptTemplateVersions)
+	}
+
 	// Gemini 原生 API 兼容层（Gemini SDK/CLI 直连）
 	gemini := r.Group("/v1beta")
 	gemini.Use(bodyLimit)
+	gemini.Use(compression)
 	gemini.Use(clientRequestID)
 	gemini.Use(opsErrorLogger)
-	gemini.Use(middleware.APIKeyAuthWithSubscriptionGoogle(apiKeyService, subscriptionService, cfg))
+	gemini.Use(requestLogger)
+	gemini.Use(maintenance)
+	gemini.Use(middleware.APIKeyAuthWithSubscriptionGoogle(apiKeyService, subscriptionService, policyService, cfg))
+	gemini.Use(routingRules)
+	gemini.Use(prepaidHold)
 	{
 		gemini.GET("/models", h.Gateway.GeminiV1BetaListModels)
 		gemini.GET("/models/:model", h.Gateway.GeminiV1BetaGetModel)
@@ -68,18 +123,22 @@ func RegisterGatewayRoutes(
 	}
 
 	// OpenAI Responses API（不带v1前缀的别名）
-	r.POST("/responses", bodyLimit, clientRequestID, opsErrorLogger, gin.HandlerFunc(apiKeyAuth), h.OpenAIGateway.Responses)
+	r.POST("/responses", bodyLimit, clientRequestID, opsErrorLogger, requestLogger, maintenance, gin.HandlerFunc(apiKeyAuth), routingRules, prepaidHold, h.OpenAIGateway.Responses)
 
 	// Antigravity 模型列表
-	r.GET("/antigravity/models", gin.HandlerFunc(apiKeyAuth), h.Gateway.AntigravityModels)
+	r.GET("/antigravity/models", maintenance, gin.HandlerFunc(apiKeyAuth), h.Gateway.AntigravityModels)
 
 	// Antigravity 专用路由（仅使用 antigravity 账户，不混合调度）
 	antigravityV1 := r.Group("/antigravity/v1")
 	antigravityV1.Use(bodyLimit)
+	antigravityV1.Use(compression)
 	antigravityV1.Use(clientRequestID)
 	antigravityV1.Use(opsErrorLogger)
+	antigravityV1.Use(requestLogger)
+	antigravityV1.Use(maintenance)
 	antigravityV1.Use(middleware.ForcePlatform(service.PlatformAntigravity))
 	antigravityV1.Use(gin.HandlerFunc(apiKeyAuth))
+	antigravityV1.Use(prepaidHold)
 	{
 		antigravityV1.POST("/messages", h.Gateway.Messages)
 		antigravityV1.POST("/messages/count_tokens", h.Gateway.CountTokens)
@@ -89,10 +148,14 @@ func RegisterGatewayRoutes(
 
 	antigravityV1Beta := r.Group("/antigravity/v1beta")
 	antigravityV1Beta.Use(bodyLimit)
+	antigravityV1Beta.Use(compression)
 	antigravityV1Beta.Use(clientRequestID)
 	antigravityV1Beta.Use(opsErrorLogger)
+	antigravityV1Beta.Use(requestLogger)
+	antigravityV1Beta.Use(maintenance)
 	antigravityV1Beta.Use(middleware.ForcePlatform(service.PlatformAntigravity))
-	antigravityV1Beta.Use(middleware.APIKeyAuthWithSubscriptionGoogle(apiKeyService, subscriptionService, cfg))
+	antigravityV1Beta.Use(middleware.APIKeyAuthWithSubscriptionGoogle(apiKeyService, subscriptionService, policyService, cfg))
+	antigravityV1Beta.Use(prepaidHold)
 	{
 		antigravityV1Beta.GET("/models", h.Gateway.GeminiV1BetaListModels)
 		antigravityV1Beta.GET("/models/:model", h.Gateway.GeminiV1BetaGetModel)
@@ -102,8 +165,11 @@ func RegisterGatewayRoutes(
 	// Sora 专用路由（强制使用 sora 平台）
 	soraV1 := r.Group("/sora/v1")
 	soraV1.Use(soraBodyLimit)
+	soraV1.Use(compression)
 	soraV1.Use(clientRequestID)
 	soraV1.Use(opsErrorLogger)
+	soraV1.Use(requestLogger)
+	soraV1.Use(maintenance)
 	soraV1.Use(middleware.ForcePlatform(service.PlatformSora))
 	soraV1.Use(gin.HandlerFunc(apiKeyAuth))
 	{
@@ -113,10 +179,10 @@ func RegisterGatewayRoutes(
 
 	// Sora 媒体代理（可选 API Key 验证）
 	if cfg.Gateway.SoraMediaRequireAPIKey {
-		r.GET("/sora/media/*filepath", gin.HandlerFunc(apiKeyAuth), h.SoraGateway.MediaProxy)
+		r.GET("/sora/media/*filepath", maintenance, gin.HandlerFunc(apiKeyAuth), h.SoraGateway.MediaProxy)
 	} else {
-		r.GET("/sora/media/*filepath", h.SoraGateway.MediaProxy)
+		r.GET("/sora/media/*filepath", maintenance, h.SoraGateway.MediaProxy)
 	}
 	// Sora 媒体代理（签名 URL，无需 API Key）
-	r.GET("/sora/media-signed/*filepath", h.SoraGateway.MediaProxySigned)
+	r.GET("/sora/media-signed/*filepath", maintenance, h.SoraGateway.MediaProxySigned)
 }
@@ -13,6 +13,9 @@ func RegisterUserRoutes(
 	h *handler.Handlers,
 	jwtAuth middleware.JWTAuthMiddleware,
 ) {
+	// Stripe Webhook 不经过用户认证，来源由 Stripe-Signature 签名校验
+	v1.POST("/payments/stripe/webhook", h.Payment.StripeWebhook)
+
 	authenticated := v1.Group("")
 	authenticated.Use(gin.HandlerFunc(jwtAuth))
 	{
@@ -22,6 +25,8 @@ func RegisterUserRoutes(
 			user.GET("/profile", h.User.GetProfile)
 			user.PUT("/password", h.User.ChangePassword)
 			user.PUT("", h.User.UpdateProfile)
+			user.GET("/notification-preferences", h.Notification.GetPreferences)
+			user.PUT("/notification-preferences", h.Notification.UpdatePreferences)
 
 			// TOTP 双因素认证
 			totp := user.Group("/totp")
@@ -32,6 +37,7 @@ func RegisterUserRoutes(
 				totp.POST("/setup", h.Totp.InitiateSetup)
 				totp.POST("/enable", h.Totp.Enable)
 				totp.POST("/disable", h.Totp.Disable)
+				totp.POST("/backup-codes", h.Totp.RegenerateBackupCodes)
 			}
 		}
 
@@ -43,6 +49,8 @@ func RegisterUserRoutes(
 			keys.POST("", h.APIKey.Create)
 			keys.PUT("/:id", h.APIKey.Update)
 			keys.DELETE("/:id", h.APIKey.Delete)
+			keys.POST("/:id/rotate", h.APIKey.Rotate)
+			keys.GET("/:id/rotation", h.APIKey.GetRotationStatus)
 		}
 
 		// 用户可用分组（非管理员接口）
@@ -87,5 +95,26 @@ func RegisterUserRoutes(
 			subscriptions.GET("/progress", h.Subscription.GetProgress)
 			subscriptions.GET("/summary", h.Subscription.GetSummary)
 		}
+
+		// 在线充值
+		payments := authenticated.Group("/payments")
+		{
+			payments.POST("/checkout", h.Payment.CreateCheckout)
+			payments.GET("/orders", h.Payment.ListOrders)
+		}
+
+		// 团队 API Key（共享预算）
+		teams := authenticated.Group("/teams")
+		{
+			teams.GET("", h.Team.List)
+			teams.POST("", h.Team.Create)
+			teams.GET("/:id", h.Team.Get)
+			teams.PUT("/:id", h.Team.Update)
+			teams.DELETE("/:id", h.Team.Delete)
+			teams.PUT("/:id/budget", h.Team.SetBudget)
+			teams.GET("/:id/usage", h.Team.Usage)
+			teams.PUT("/:id/members/:key_id", h.Team.UpsertMember)
+			teams.DELETE("/:id/members/:key_id", h.Team.RemoveMember)
+		}
 	}
 }
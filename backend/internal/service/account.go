@@ -119,6 +119,11 @@ func (a *Account) IsGemini() bool {
 	return a.Platform == PlatformGemini
 }
 
+// IsGeminiWebSession 返回是否为 Gemini 网页会话账号（使用 __Secure-1PSID Cookie 驱动网页对话接口）。
+func (a *Account) IsGeminiWebSession() bool {
+	return a != nil && a.IsGemini() && a.Type == AccountTypeWebSession
+}
+
 func (a *Account) GeminiOAuthType() string {
 	if a.Platform != PlatformGemini || a.Type != AccountTypeOAuth {
 		return ""
@@ -623,6 +628,60 @@ func (a *Account) IsOpenAIApiKey() bool {
 	return a.IsOpenAI() && a.Type == AccountTypeAPIKey
 }
 
+// IsChatGPTWebSession 返回是否为 ChatGPT 网页会话账号（使用网页 access token 调用 backend-api）。
+func (a *Account) IsChatGPTWebSession() bool {
+	return a != nil && a.IsOpenAI() && a.Type == AccountTypeWebSession
+}
+
+// IsCopilot 返回是否为 GitHub Copilot 订阅账号（GitHub token 换取 Copilot token 后调用 chat/completions）。
+func (a *Account) IsCopilot() bool {
+	return a != nil && a.IsOpenAI() && a.Type == AccountTypeCopilot
+}
+
+// IsOpenAICompatible 返回是否为通用 OpenAI 兼容上游账号（OpenRouter、第三方网关、自建 vLLM 等，仅要求 /chat/completions）。
+func (a *Account) IsOpenAICompatible() bool {
+	return a != nil && a.IsOpenAI() && a.Type == AccountTypeUpstream
+}
+
+// GetOpenAICompatibleProvider 返回 OpenAI 兼容上游的实现（ollama / llamacpp），通用上游返回空。
+func (a *Account) GetOpenAICompatibleProvider() string {
+	if !a.IsOpenAICompatible() {
+		return ""
+	}
+	return strings.ToLower(strings.TrimSpace(a.GetCredential("provider")))
+}
+
+// IsLocalModelUpstream 返回是否为本地推理服务（Ollama、llama.cpp server）。
+func (a *Account) IsLocalModelUpstream() bool {
+	switch a.GetOpenAICompatibleProvider() {
+	case OpenAICompatibleProviderOllama, OpenAICompatibleProviderLlamaCpp:
+		return true
+	}
+	return false
+}
+
+// OpenAICompatibleSupportsTools 返回上游是否支持工具调用。
+// extra.supports_tools 显式配置优先；未配置时 llama.cpp 默认不支持，其余默认支持。
+func (a *Account) OpenAICompatibleSupportsTools() bool {
+	if a.Extra != nil {
+		if enabled, ok := a.Extra["supports_tools"].(bool); ok {
+			return enabled
+		}
+	}
+	return a.GetOpenAICompatibleProvider() != OpenAICompatibleProviderLlamaCpp
+}
+
+// OpenAICompatibleSupportsVision 返回上游是否支持图片输入。
+// extra.supports_vision 显式配置优先；未配置时本地推理服务默认不支持（取决于加载的模型），通用上游默认支持。
+func (a *Account) OpenAICompatibleSupportsVision() bool {
+	if a.Extra != nil {
+		if enabled, ok := a.Extra["supports_vision"].(bool); ok {
+			return enabled
+		}
+	}
+	return !a.IsLocalModelUpstream()
+}
+
 func (a *Account) GetOpenAIBaseURL() string {
 	if !a.IsOpenAI() {
 		return ""
@@ -758,6 +817,11 @@ func (a *Account) IsAnthropicAPIKeyPassthroughEnabled() bool {
 	return ok && enabled
 }
 
+// IsClaudeWebSession 返回是否为 claude.ai 网页会话账号（使用 sessionKey Cookie 驱动网页对话接口）。
+func (a *Account) IsClaudeWebSession() bool {
+	return a != nil && a.Platform == PlatformAnthropic && a.Type == AccountTypeWebSession
+}
+
 // IsCodexCLIOnlyEnabled 返回 OpenAI OAuth 账号是否启用“仅允许 Codex 官方客户端”。
 // 字段：accounts.extra.codex_cli_only。
 // 字段缺失或类型不正确时，按 false（关闭）处理。
@@ -788,22 +852,45 @@ func (a *Account) IsAnthropicOAuthOrSetupToken() bool {
 }
 
 // IsTLSFingerprintEnabled 检查是否启用 TLS 指纹伪装
-// 仅适用于 Anthropic OAuth/SetupToken 类型账号
-// 启用后将模拟 Claude Code (Node.js) 客户端的 TLS 握手特征
+// Anthropic OAuth/SetupToken 与网页会话账号通过 extra.enable_tls_fingerprint 开关；
+// 其他账号在 extra.tls_fingerprint_profile 中显式指定模板即视为启用
+// 启用后 Anthropic OAuth 账号默认模拟 Claude Code (Node.js) 的 TLS 握手特征，网页会话账号默认模拟浏览器
 func (a *Account) IsTLSFingerprintEnabled() bool {
-	// 仅支持 Anthropic OAuth/SetupToken 账号
-	if !a.IsAnthropicOAuthOrSetupToken() {
-		return false
-	}
 	if a.Extra == nil {
 		return false
 	}
-	if v, ok := a.Extra["enable_tls_fingerprint"]; ok {
-		if enabled, ok := v.(bool); ok {
-			return enabled
+	if a.IsAnthropicOAuthOrSetupToken() || a.Type == AccountTypeWebSession {
+		if v, ok := a.Extra["enable_tls_fingerprint"]; ok {
+			if enabled, ok := v.(bool); ok {
+				return enabled
+			}
 		}
 	}
-	return false
+	return a.GetTLSFingerprintProfileOverride() != ""
+}
+
+// GetTLSFingerprintProfileOverride 返回 extra.tls_fingerprint_profile 中显式指定的 TLS 指纹模板
+func (a *Account) GetTLSFingerprintProfileOverride() string {
+	if a.Extra == nil {
+		return ""
+	}
+	if v, ok := a.Extra["tls_fingerprint_profile"].(string); ok {
+		return strings.TrimSpace(v)
+	}
+	return ""
+}
+
+// GetTLSFingerprintProfile 返回请求上游时使用的 TLS 指纹模板名：
+// 显式指定的模板优先；网页会话账号默认模拟 Chrome 浏览器；
+// 其他账号返回空字符串，按账号 ID 在 Claude CLI 等模板中轮换
+func (a *Account) GetTLSFingerprintProfile() string {
+	if profile := a.GetTLSFingerprintProfileOverride(); profile != "" {
+		return profile
+	}
+	if a.Type == AccountTypeWebSession {
+		return defaultWebSessionTLSProfile
+	}
+	return ""
 }
 
 // IsSessionIDMaskingEnabled 检查是否启用会话ID伪装
@@ -11,6 +11,7 @@ import (
 type AccountExpiryService struct {
 	accountRepo AccountRepository
 	interval    time.Duration
+	leader      *LeaderElector
 	stopCh      chan struct{}
 	stopOnce    sync.Once
 	wg          sync.WaitGroup
@@ -24,6 +25,11 @@ func NewAccountExpiryService(accountRepo AccountRepository, interval time.Durati
 	}
 }
 
+// SetLeaderElector 多实例部署时仅由 leader 执行；需要在 Start() 之前调用
+func (s *AccountExpiryService) SetLeaderElector(leader *LeaderElector) {
+	s.leader = leader
+}
+
 func (s *AccountExpiryService) Start() {
 	if s == nil || s.accountRepo == nil || s.interval <= 0 {
 		return
@@ -57,6 +63,9 @@ func (s *AccountExpiryService) Stop() {
 }
 
 func (s *AccountExpiryService) runOnce() {
+	if !s.leader.IsLeader() {
+		return
+	}
 	ctx, cancel := context.WithTimeout(context.Background(), 5*time.Second)
 	defer cancel()
 
@@ -0,0 +1,153 @@
+package service
+
+import (
+	"context"
+	"sort"
+	"time"
+
+	infraerrors "github.com/Wei-Shaw/sub2api/internal/pkg/errors"
+)
+
+const (
+	defaultAccountHealthHours = 24
+	maxAccountHealthHours     = 30 * 24
+)
+
+var ErrAccountHealthRangeInvalid = infraerrors.BadRequest("ACCOUNT_HEALTH_RANGE_INVALID", "hours must be between 1 and 720")
+
+// AccountHealthBucket 账号单小时健康度（来自 account_health_rollup_hourly）
+type AccountHealthBucket struct {
+	BucketStart       time.Time `json:"bucket_start"`
+	AccountID         int64     `json:"account_id"`
+	SuccessCount      int64     `json:"success_count"`
+	ErrorCount        int64     `json:"error_count"`
+	SuccessRate       float64   `json:"success_rate"`
+	RequestsPerMinute float64   `json:"requests_per_minute"`
+	OutputTokens      int64     `json:"output_tokens"`
+	DurationP50Ms     *int      `json:"duration_p50_ms"`
+	DurationP95Ms     *int      `json:"duration_p95_ms"`
+	DurationP99Ms     *int      `json:"duration_p99_ms"`
+	FirstTokenP50Ms   *int      `json:"first_token_p50_ms"`
+	FirstTokenP95Ms   *int      `json:"first_token_p95_ms"`
+	FirstTokenP99Ms   *int      `json:"first_token_p99_ms"`
+}
+
+// AccountHealthSummary 账号在时间范围内的健康度汇总。
+// 分位数为各小时分位数按成功请求数加权的平均值（近似值）。
+type AccountHealthSummary struct {
+	AccountID         int64    `json:"account_id"`
+	AccountName       string   `json:"account_name"`
+	Platform          string   `json:"platform"`
+	SuccessCount      int64    `json:"success_count"`
+	ErrorCount        int64    `json:"error_count"`
+	SuccessRate       float64  `json:"success_rate"`
+	RequestsPerMinute float64  `json:"requests_per_minute"`
+	OutputTokens      int64    `json:"output_tokens"`
+	DurationP95Ms     *float64 `json:"duration_p95_ms"`
+	FirstTokenP95Ms   *float64 `json:"first_token_p95_ms"`
+}
+
+// AccountHealthRepository 账号健康度汇总表查询
+type AccountHealthRepository interface {
+	// ListHourly 返回账号在 [start, end) 内的小时桶（按时间升序）
+	ListHourly(ctx context.Context, accountID int64, start, end time.Time) ([]AccountHealthBucket, error)
+	// ListSummaries 返回所有账号在 [start, end) 内的汇总
+	ListSummaries(ctx context.Context, start, end time.Time) ([]AccountHealthSummary, error)
+}
+
+// AccountHealthService 账号延迟与成功率分析
+type AccountHealthService struct {
+	repo         AccountHealthRepository
+	accountRepo  AccountRepository
+	quotaTracker *AccountQuotaBudgetTracker
+	now          func() time.Time
+}
+
+// NewAccountHealthService 创建账号健康度分析服务
+func NewAccountHealthService(repo AccountHealthRepository, accountRepo AccountRepository, quotaTracker *AccountQuotaBudgetTracker) *AccountHealthService {
+	return &AccountHealthService{repo: repo, accountRepo: accountRepo, quotaTracker: quotaTracker, now: time.Now}
+}
+
+// GetAccountSeries 返回账号最近 hours 小时的健康度时间序列（hours 为 0 时取默认 24 小时）
+func (s *AccountHealthService) GetAccountSeries(ctx context.Context, accountID int64, hours int) ([]AccountHealthBucket, error) {
+	start, end, err := s.resolveRange(hours)
+	if err != nil {
+		return nil, err
+	}
+	buckets, err := s.repo.ListHourly(ctx, accountID, start, end)
+	if err != nil {
+		return nil, err
+	}
+	for i := range buckets {
+		b := &buckets[i]
+		b.SuccessRate = accountSuccessRate(b.SuccessCount, b.ErrorCount)
+		b.RequestsPerMinute = float64(b.SuccessCount+b.ErrorCount) / 60
+	}
+	return buckets, nil
+}
+
+// ListSummaries 返回所有账号最近 hours 小时的健康度汇总，成功率低的账号排在前面
+func (s *AccountHealthService) ListSummaries(ctx context.Context, hours int) ([]AccountHealthSummary, error) {
+	start, end, err := s.resolveRange(hours)
+	if err != nil {
+		return nil, err
+	}
+	summaries, err := s.repo.ListSummaries(ctx, start, end)
+	if err != nil {
+		return nil, err
+	}
+	minutes := end.Sub(start).Minutes()
+	for i := range summaries {
+		sum := &summaries[i]
+		sum.SuccessRate = accountSuccessRate(sum.SuccessCount, sum.ErrorCount)
+		sum.RequestsPerMinute = float64(sum.SuccessCount+sum.ErrorCount) / minutes
+	}
+	sortAccountHealthSummaries(summaries)
+	return summaries, nil
+}
+
+// GetQuotaStatus 返回账号当前的配额预算评估（各窗口用量、预测剩余额度与耗尽时间）
+func (s *AccountHealthService) GetQuotaStatus(ctx context.Context, accountID int64) (*AccountQuotaStatus, error) {
+	account, err := s.accountRepo.GetByID(ctx, accountID)
+	if err != nil {
+		return nil, err
+	}
+	if status := s.quotaTracker.Evaluate(ctx, account); status != nil {
+		return status, nil
+	}
+	return &AccountQuotaStatus{
+		AccountID:      accountID,
+		ReservePercent: defaultAccountQuotaReservePercent,
+		Windows:        []AccountQuotaWindowStatus{},
+	}, nil
+}
+
+func (s *AccountHealthService) resolveRange(hours int) (time.Time, time.Time, error) {
+	if hours == 0 {
+		hours = defaultAccountHealthHours
+	}
+	if hours < 0 || hours > maxAccountHealthHours {
+		return time.Time{}, time.Time{}, ErrAccountHealthRangeInvalid
+	}
+	end := s.now().Truncate(time.Hour).Add(time.Hour)
+	return end.Add(-time.Duration(hours) * time.Hour), end, nil
+}
+
+func accountSuccessRate(success, failed int64) float64 {
+	total := success + failed
+	if total == 0 {
+		return 1
+	}
+	return float64(success) / float64(total)
+}
+
+func sortAccountHealthSummaries(summaries []AccountHealthSummary) {
+	// 成功率升序，成功率相同时请求量大的优先
+	sort.SliceStable(summaries, func(i, j int) bool {
+		a, b := summaries[i], summaries[j]
+		if a.SuccessRate != b.SuccessRate {
+			return a.SuccessRate < b.SuccessRate
+		}
+		return a.SuccessCount+a.ErrorCount > b.SuccessCount+b.ErrorCount
+	})
+}
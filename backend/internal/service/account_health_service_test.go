@@ -0,0 +1,80 @@
+//go:build unit
+
+package service
+
+import (
+	"context"
+	"testing"
+	"time"
+
+	"github.com/stretchr/testify/require"
+)
+
+type accountHealthRepoStub struct {
+	buckets    []AccountHealthBucket
+	summaries  []AccountHealthSummary
+	start, end time.Time
+}
+
+func (s *accountHealthRepoStub) ListHourly(_ context.Context, _ int64, start, end time.Time) ([]AccountHealthBucket, error) {
+	s.start, s.end = start, end
+	return s.buckets, nil
+}
+
+func (s *accountHealthRepoStub) ListSummaries(_ context.Context, start, end time.Time) ([]AccountHealthSummary, error) {
+	s.start, s.end = start, end
+	return s.summaries, nil
+}
+
+func newAccountHealthServiceForTest(repo AccountHealthRepository) *AccountHealthService {
+	svc := NewAccountHealthService(repo, nil, nil)
+	svc.now = func() time.Time { return time.Date(2026, 3, 1, 10, 30, 0, 0, time.UTC) }
+	return svc
+}
+
+func TestAccountHealthService_GetAccountSeries(t *testing.T) {
+	repo := &accountHealthRepoStub{buckets: []AccountHealthBucket{
+		{AccountID: 1, SuccessCount: 90, ErrorCount: 30},
+		{AccountID: 1},
+	}}
+	svc := newAccountHealthServiceForTest(repo)
+
+	buckets, err := svc.GetAccountSeries(context.Background(), 1, 0)
+	require.NoError(t, err)
+	require.Equal(t, time.Date(2026, 3, 1, 11, 0, 0, 0, time.UTC), repo.end)
+	require.Equal(t, 24*time.Hour, repo.end.Sub(repo.start))
+	require.InDelta(t, 0.75, buckets[0].SuccessRate, 1e-9)
+	require.InDelta(t, 2, buckets[0].RequestsPerMinute, 1e-9)
+	// 无请求的小时视为健康
+	require.InDelta(t, 1, buckets[1].SuccessRate, 1e-9)
+}
+
+func TestAccountHealthService_ListSummariesSortsWorstFirst(t *testing.T) {
+	repo := &accountHealthRepoStub{summaries: []AccountHealthSummary{
+		{AccountID: 1, SuccessCount: 100},
+		{AccountID: 2, SuccessCount: 50, ErrorCount: 50},
+		{AccountID: 3, SuccessCount: 10},
+		{AccountID: 4, SuccessCount: 9, ErrorCount: 1},
+	}}
+	svc := newAccountHealthServiceForTest(repo)
+
+	summaries, err := svc.ListSummaries(context.Background(), 2)
+	require.NoError(t, err)
+	require.Equal(t, 2*time.Hour, repo.end.Sub(repo.start))
+
+	var ids []int64
+	for _, s := range summaries {
+		ids = append(ids, s.AccountID)
+	}
+	require.Equal(t, []int64{2, 4, 1, 3}, ids)
+	require.InDelta(t, 100.0/120, summaries[0].RequestsPerMinute, 1e-9)
+}
+
+func TestAccountHealthService_InvalidRange(t *testing.T) {
+	svc := newAccountHealthServiceForTest(&accountHealthRepoStub{})
+
+	_, err := svc.ListSummaries(context.Background(), -1)
+	require.ErrorIs(t, err, ErrAccountHealthRangeInvalid)
+	_, err = svc.GetAccountSeries(context.Background(), 1, maxAccountHealthHours+1)
+	require.ErrorIs(t, err, ErrAccountHealthRangeInvalid)
+}
@@ -0,0 +1,140 @@
+package service
+
+import (
+	"sync"
+	"time"
+)
+
+const (
+	// accountLatencyEWMAAlpha 新样本权重
+	accountLatencyEWMAAlpha = 0.2
+	// accountLatencyTolerance 延迟调度时视为“同样快”的相对容差（相对最快账号）
+	accountLatencyTolerance = 0.2
+)
+
+// AccountLatencyTracker 账号延迟统计（首 token 与总耗时的 EWMA），用于 latency 调度策略。
+//
+// 统计仅在本实例内维护：多区域部署时各区域的实例按本区域观测到的延迟调度，互不干扰。
+// 对 nil 安全。
+type AccountLatencyTracker struct {
+	mu       sync.RWMutex
+	accounts map[int64]*accountLatency
+}
+
+type accountLatency struct {
+	firstTokenMs      float64
+	firstTokenSamples int64
+	totalMs           float64
+	totalSamples      int64
+	updatedAt         time.Time
+}
+
+// NewAccountLatencyTracker 创建账号延迟统计
+func NewAccountLatencyTracker() *AccountLatencyTracker {
+	return &AccountLatencyTracker{accounts: make(map[int64]*accountLatency)}
+}
+
+// Record 记录一次成功请求的首 token 时间（非流式为 nil）与总耗时
+func (t *AccountLatencyTracker) Record(accountID int64, firstTokenMs *int, duration time.Duration) {
+	if t == nil || accountID <= 0 {
+		return
+	}
+	t.mu.Lock()
+	defer t.mu.Unlock()
+
+	stats := t.accounts[accountID]
+	if stats == nil {
+		stats = &accountLatency{}
+		t.accounts[accountID] = stats
+	}
+	if firstTokenMs != nil && *firstTokenMs >= 0 {
+		stats.firstTokenMs = ewma(stats.firstTokenMs, float64(*firstTokenMs), stats.firstTokenSamples)
+		stats.firstTokenSamples++
+	}
+	if duration > 0 {
+		stats.totalMs = ewma(stats.totalMs, float64(duration.Milliseconds()), stats.totalSamples)
+		stats.totalSamples++
+	}
+	stats.updatedAt = time.Now()
+}
+
+// Score 返回账号用于调度比较的延迟（毫秒）：优先使用首 token EWMA，没有流式样本时使用总耗时 EWMA。
+// 尚无样本时 ok=false。
+func (t *AccountLatencyTracker) Score(accountID int64) (ms float64, ok bool) {
+	if t == nil {
+		return 0, false
+	}
+	t.mu.RLock()
+	defer t.mu.RUnlock()
+
+	stats := t.accounts[accountID]
+	switch {
+	case stats == nil:
+		return 0, false
+	case stats.firstTokenSamples > 0:
+		return stats.firstTokenMs, true
+	case stats.totalSamples > 0:
+		return stats.totalMs, true
+	}
+	return 0, false
+}
+
+func ewma(current, sample float64, samples int64) float64 {
+	if samples == 0 {
+		return sample
+	}
+	return current + accountLatencyEWMAAlpha*(sample-current)
+}
+
+// filterByLatency 过滤出按负载修正后的延迟在最快账号容差范围内的账号集合。
+// 尚无延迟样本的账号始终保留，以便获得样本；所有账号都没有样本时原样返回。
+func filterByLatency(accounts []accountWithLoad, tracker *AccountLatencyTracker) []accountWithLoad {
+	if len(accounts) <= 1 || tracker == nil {
+		return accounts
+	}
+	scores := make([]float64, len(accounts))
+	known := make([]bool, len(accounts))
+	best := -1.0
+	for i, acc := range accounts {
+		ms, ok := tracker.Score(acc.account.ID)
+		if !ok {
+			continue
+		}
+		// 负载越高排队与上游争用越多，按负载率放大延迟
+		loadRate := 0
+		if acc.loadInfo != nil {
+			loadRate = acc.loadInfo.LoadRate
+		}
+		scores[i] = ms * (1 + float64(loadRate)/100)
+		known[i] = true
+		if best < 0 || scores[i] < best {
+			best = scores[i]
+		}
+	}
+	if best < 0 {
+		return accounts
+	}
+	limit := best * (1 + accountLatencyTolerance)
+	result := make([]accountWithLoad, 0, len(accounts))
+	for i, acc := range accounts {
+		if !known[i] || scores[i] <= limit {
+			result = append(result, acc)
+		}
+	}
+	return result
+}
+
+// latencyPreferredIDs 按优先级分组，返回每组内延迟在最快账号容差范围内（或尚无样本）的账号 ID
+func latencyPreferredIDs(accounts []accountWithLoad, tracker *AccountLatencyTracker) map[int64]bool {
+	groups := make(map[int][]accountWithLoad)
+	for _, acc := range accounts {
+		groups[acc.account.Priority] = append(groups[acc.account.Priority], acc)
+	}
+	preferred := make(map[int64]bool, len(accounts))
+	for _, group := range groups {
+		for _, acc := range filterByLatency(group, tracker) {
+			preferred[acc.account.ID] = true
+		}
+	}
+	return preferred
+}
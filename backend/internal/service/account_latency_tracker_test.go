@@ -0,0 +1,77 @@
+//go:build unit
+
+package service
+
+import (
+	"testing"
+	"time"
+
+	"github.com/stretchr/testify/require"
+)
+
+func TestAccountLatencyTracker_EWMA(t *testing.T) {
+	tracker := NewAccountLatencyTracker()
+
+	_, ok := tracker.Score(1)
+	require.False(t, ok)
+
+	tracker.Record(1, nil, 2*time.Second)
+	ms, ok := tracker.Score(1)
+	require.True(t, ok)
+	require.InDelta(t, 2000, ms, 0.001)
+
+	// 有流式样本后优先使用首 token 延迟
+	tracker.Record(1, intPtr(1000), 3*time.Second)
+	ms, _ = tracker.Score(1)
+	require.InDelta(t, 1000, ms, 0.001)
+
+	tracker.Record(1, intPtr(2000), 3*time.Second)
+	ms, _ = tracker.Score(1)
+	require.InDelta(t, 1200, ms, 0.001)
+
+	var nilTracker *AccountLatencyTracker
+	nilTracker.Record(1, intPtr(1), time.Second)
+	_, ok = nilTracker.Score(1)
+	require.False(t, ok)
+}
+
+func TestFilterByLatency(t *testing.T) {
+	tracker := NewAccountLatencyTracker()
+	tracker.Record(1, intPtr(1000), time.Second)
+	tracker.Record(2, intPtr(1100), time.Second)
+	tracker.Record(3, intPtr(3000), time.Second)
+	tracker.Record(4, intPtr(1000), time.Second)
+
+	accounts := []accountWithLoad{
+		{account: &Account{ID: 1}, loadInfo: &AccountLoadInfo{}},
+		{account: &Account{ID: 2}, loadInfo: &AccountLoadInfo{}},
+		{account: &Account{ID: 3}, loadInfo: &AccountLoadInfo{}},
+		// 延迟相同但负载 50%，修正后 1500ms 超出容差
+		{account: &Account{ID: 4}, loadInfo: &AccountLoadInfo{LoadRate: 50}},
+		// 无样本的账号保留以便探测
+		{account: &Account{ID: 5}, loadInfo: &AccountLoadInfo{}},
+	}
+
+	var ids []int64
+	for _, acc := range filterByLatency(accounts, tracker) {
+		ids = append(ids, acc.account.ID)
+	}
+	require.Equal(t, []int64{1, 2, 5}, ids)
+
+	require.Len(t, filterByLatency(accounts, NewAccountLatencyTracker()), len(accounts))
+	require.Len(t, filterByLatency(accounts, nil), len(accounts))
+}
+
+func TestLatencyPreferredIDs_PerPriority(t *testing.T) {
+	tracker := NewAccountLatencyTracker()
+	tracker.Record(1, intPtr(1000), time.Second)
+	tracker.Record(2, intPtr(5000), time.Second)
+	tracker.Record(3, intPtr(9000), time.Second)
+
+	preferred := latencyPreferredIDs([]accountWithLoad{
+		{account: &Account{ID: 1, Priority: 1}, loadInfo: &AccountLoadInfo{}},
+		{account: &Account{ID: 2, Priority: 1}, loadInfo: &AccountLoadInfo{}},
+		{account: &Account{ID: 3, Priority: 2}, loadInfo: &AccountLoadInfo{}},
+	}, tracker)
+	require.Equal(t, map[int64]bool{1: true, 3: true}, preferred)
+}
@@ -0,0 +1,318 @@
+package service
+
+import (
+	"context"
+	"strings"
+	"sync"
+	"time"
+)
+
+const (
+	// accountQuotaStatsTTL 配额窗口用量的本地缓存时间，避免每次调度都查询 usage_logs
+	accountQuotaStatsTTL = 30 * time.Second
+	// accountQuotaLookahead 预测剩余额度的前瞻时长，同时作为近期消耗速率的采样窗口
+	accountQuotaLookahead = 10 * time.Minute
+	// defaultAccountQuotaReservePercent 预测剩余额度低于该比例时视为接近耗尽
+	defaultAccountQuotaReservePercent = 10.0
+)
+
+// 配额窗口计量单位
+const (
+	AccountQuotaUnitRequests = "requests"
+	AccountQuotaUnitTokens   = "tokens"
+	AccountQuotaUnitCost     = "cost"
+	// AccountQuotaUnitPercent 上游上报的使用百分比（如 Codex 5h/7d 用量）
+	AccountQuotaUnitPercent = "percent"
+)
+
+// AccountQuotaLevel 账号配额预算状态
+type AccountQuotaLevel int
+
+const (
+	AccountQuotaOK AccountQuotaLevel = iota
+	// AccountQuotaLow 预测剩余额度低于预留比例：非粘性调度时降级为兜底候选
+	AccountQuotaLow
+	// AccountQuotaExhausted 窗口额度已用尽：不再调度
+	AccountQuotaExhausted
+)
+
+// AccountQuotaWindow 账号订阅的滚动用量窗口，配置于 account.Extra["quota_budget"]：
+//
+//	{"windows":[{"name":"5h","window_minutes":300,"unit":"requests","limit":200}],"reserve_percent":10}
+type AccountQuotaWindow struct {
+	Name          string  `json:"name"`
+	WindowMinutes int     `json:"window_minutes"`
+	Unit          string  `json:"unit"`
+	Limit         float64 `json:"limit"`
+}
+
+// AccountQuotaBudget 账号配额模型
+type AccountQuotaBudget struct {
+	Windows        []AccountQuotaWindow `json:"windows"`
+	ReservePercent float64              `json:"reserve_percent"`
+}
+
+// AccountQuotaWindowStatus 单个窗口的用量与预测
+type AccountQuotaWindowStatus struct {
+	Name   string  `json:"name"`
+	Unit   string  `json:"unit"`
+	Source string  `json:"source"` // usage_logs | upstream
+	Limit  float64 `json:"limit"`
+	Used   float64 `json:"used"`
+	// Remaining 当前剩余额度
+	Remaining float64 `json:"remaining"`
+	// PredictedRemaining 按近期消耗速率预测 accountQuotaLookahead 之后的剩余额度
+	PredictedRemaining float64 `json:"predicted_remaining"`
+	// ExhaustAt 按近期净消耗速率预测的耗尽时间，不会耗尽时为 nil
+	ExhaustAt *time.Time `json:"exhaust_at,omitempty"`
+	ResetAt   *time.Time `json:"reset_at,omitempty"`
+}
+
+// AccountQuotaStatus 账号配额预算评估结果
+type AccountQuotaStatus struct {
+	AccountID      int64                      `json:"account_id"`
+	Level          AccountQuotaLevel          `json:"level"`
+	ReservePercent float64                    `json:"reserve_percent"`
+	Windows        []AccountQuotaWindowStatus `json:"windows"`
+	// UpstreamWarning 上游已提示接近限额（Anthropic 5h 窗口 allowed_warning）
+	UpstreamWarning bool `json:"upstream_warning"`
+}
+
+// GetQuotaBudget 解析账号配置的配额窗口，未配置时返回 nil
+func (a *Account) GetQuotaBudget() *AccountQuotaBudget {
+	if a.Extra == nil {
+		return nil
+	}
+	raw, ok := a.Extra["quota_budget"].(map[string]any)
+	if !ok {
+		return nil
+	}
+	budget := &AccountQuotaBudget{ReservePercent: parseExtraFloat64(raw["reserve_percent"])}
+	items, _ := raw["windows"].([]any)
+	for _, item := range items {
+		entry, ok := item.(map[string]any)
+		if !ok {
+			continue
+		}
+		window := AccountQuotaWindow{
+			WindowMinutes: parseExtraInt(entry["window_minutes"]),
+			Limit:         parseExtraFloat64(entry["limit"]),
+		}
+		window.Name, _ = entry["name"].(string)
+		unit, _ := entry["unit"].(string)
+		window.Unit = strings.ToLower(strings.TrimSpace(unit))
+		switch window.Unit {
+		case AccountQuotaUnitRequests, AccountQuotaUnitTokens, AccountQuotaUnitCost:
+		default:
+			continue
+		}
+		if window.WindowMinutes <= 0 || window.Limit <= 0 {
+			continue
+		}
+		budget.Windows = append(budget.Windows, window)
+	}
+	if len(budget.Windows) == 0 {
+		return nil
+	}
+	return budget
+}
+
+// AccountQuotaBudgetTracker 账号配额预算跟踪：
+// 按账号配置的滚动窗口统计 usage_logs 中的用量，结合上游上报的使用率（Codex 5h/7d、Anthropic 5h 状态），
+// 预测剩余额度，使调度在账号接近耗尽前主动避开，而不是等到上游返回 429。
+// 对 nil 安全。
+type AccountQuotaBudgetTracker struct {
+	usageLogRepo UsageLogRepository
+	now          func() time.Time
+
+	mu    sync.Mutex
+	stats map[accountQuotaStatsKey]accountQuotaStatsEntry
+}
+
+type accountQuotaStatsKey struct {
+	accountID int64
+	minutes   int
+}
+
+type accountQuotaStatsEntry struct {
+	requests  float64
+	tokens    float64
+	cost      float64
+	fetchedAt time.Time
+}
+
+func (e accountQuotaStatsEntry) value(unit string) float64 {
+	switch unit {
+	case AccountQuotaUnitTokens:
+		return e.tokens
+	case AccountQuotaUnitCost:
+		return e.cost
+	}
+	return e.requests
+}
+
+// NewAccountQuotaBudgetTracker 创建账号配额预算跟踪器
+func NewAccountQuotaBudgetTracker(usageLogRepo UsageLogRepository) *AccountQuotaBudgetTracker {
+	return &AccountQuotaBudgetTracker{
+		usageLogRepo: usageLogRepo,
+		now:          time.Now,
+		stats:        make(map[accountQuotaStatsKey]accountQuotaStatsEntry),
+	}
+}
+
+// Level 返回账号当前的配额预算状态；未配置也无上游用量信息时为 AccountQuotaOK
+func (t *AccountQuotaBudgetTracker) Level(ctx context.Context, account *Account) AccountQuotaLevel {
+	if t == nil || account == nil {
+		return AccountQuotaOK
+	}
+	if status := t.Evaluate(ctx, account); status != nil {
+		return status.Level
+	}
+	return AccountQuotaOK
+}
+
+// Evaluate 评估账号各配额窗口的用量与预测剩余额度；未配置也无上游用量信息时返回 nil
+func (t *AccountQuotaBudgetTracker) Evaluate(ctx context.Context, account *Account) *AccountQuotaStatus {
+	if t == nil || account == nil {
+		return nil
+	}
+	now := t.now()
+	status := &AccountQuotaStatus{AccountID: account.ID, ReservePercent: defaultAccountQuotaReservePercent}
+
+	if budget := account.GetQuotaBudget(); budget != nil {
+		if budget.ReservePercent > 0 {
+			status.ReservePercent = budget.ReservePercent
+		}
+		for _, window := range budget.Windows {
+			if ws, ok := t.evaluateWindow(ctx, account.ID, window, now); ok {
+				status.Windows = append(status.Windows, ws)
+			}
+		}
+	}
+	status.Windows = append(status.Windows, upstreamQuotaWindows(account, now)...)
+	status.UpstreamWarning = account.SessionWindowStatus == "allowed_warning" &&
+		account.SessionWindowEnd != nil && now.Before(*account.SessionWindowEnd)
+
+	if len(status.Windows) == 0 && !status.UpstreamWarning {
+		return nil
+	}
+	if status.UpstreamWarning {
+		status.Level = AccountQuotaLow
+	}
+	for _, ws := range status.Windows {
+		if ws.Remaining <= 0 {
+			status.Level = AccountQuotaExhausted
+			break
+		}
+		if ws.PredictedRemaining/ws.Limit*100 < status.ReservePercent {
+			status.Level = AccountQuotaLow
+		}
+	}
+	return status
+}
+
+// evaluateWindow 统计滚动窗口用量并预测前瞻期后的剩余额度：
+// 前瞻期内新增用量按最近 accountQuotaLookahead 的速率估算，移出窗口的旧用量按窗口平均速率估算。
+func (t *AccountQuotaBudgetTracker) evaluateWindow(ctx context.Context, accountID int64, window AccountQuotaWindow, now time.Time) (AccountQuotaWindowStatus, bool) {
+	windowDur := time.Duration(window.WindowMinutes) * time.Minute
+	total, ok := t.windowStats(ctx, accountID, window.WindowMinutes, now)
+	if !ok {
+		return AccountQuotaWindowStatus{}, false
+	}
+	lookahead := min(accountQuotaLookahead, windowDur)
+	recent, ok := t.windowStats(ctx, accountID, int(lookahead/time.Minute), now)
+	if !ok {
+		return AccountQuotaWindowStatus{}, false
+	}
+
+	used := total.value(window.Unit)
+	added := recent.value(window.Unit)
+	expired := used * float64(lookahead) / float64(windowDur)
+	ws := AccountQuotaWindowStatus{
+		Name:               window.Name,
+		Unit:               window.Unit,
+		Source:             "usage_logs",
+		Limit:              window.Limit,
+		Used:               used,
+		Remaining:          window.Limit - used,
+		PredictedRemaining: window.Limit - used - added + expired,
+	}
+	if ws.Name == "" {
+		ws.Name = (time.Duration(window.WindowMinutes) * time.Minute).String()
+	}
+	if netPerSecond := (added - expired) / lookahead.Seconds(); netPerSecond > 0 && ws.Remaining > 0 {
+		exhaustAt := now.Add(time.Duration(ws.Remaining / netPerSecond * float64(time.Second)))
+		ws.ExhaustAt = &exhaustAt
+	}
+	return ws, true
+}
+
+// windowStats 读取账号最近 minutes 分钟的用量（带本地缓存）；查询失败时返回 false（失败开放）
+func (t *AccountQuotaBudgetTracker) windowStats(ctx context.Context, accountID int64, minutes int, now time.Time) (accountQuotaStatsEntry, bool) {
+	key := accountQuotaStatsKey{accountID: accountID, minutes: minutes}
+	t.mu.Lock()
+	entry, ok := t.stats[key]
+	t.mu.Unlock()
+	if ok && now.Sub(entry.fetchedAt) < accountQuotaStatsTTL {
+		return entry, true
+	}
+	if t.usageLogRepo == nil {
+		return accountQuotaStatsEntry{}, false
+	}
+
+	stats, err := t.usageLogRepo.GetAccountWindowStats(ctx, accountID, now.Add(-time.Duration(minutes)*time.Minute))
+	if err != nil || stats == nil {
+		return accountQuotaStatsEntry{}, false
+	}
+	entry = accountQuotaStatsEntry{
+		requests:  float64(stats.Requests),
+		tokens:    float64(stats.Tokens),
+		cost:      stats.StandardCost,
+		fetchedAt: now,
+	}
+	t.mu.Lock()
+	t.stats[key] = entry
+	t.mu.Unlock()
+	return entry, true
+}
+
+// upstreamQuotaWindows 将上游上报的使用率（Codex 5h/7d）转换为配额窗口，重置时间已过的快照忽略
+func upstreamQuotaWindows(account *Account, now time.Time) []AccountQuotaWindowStatus {
+	if account.Extra == nil {
+		return nil
+	}
+	var out []AccountQuotaWindowStatus
+	for _, name := range []string{"5h", "7d"} {
+		raw, ok := account.Extra["codex_"+name+"_used_percent"]
+		if !ok {
+			continue
+		}
+		resetStr, _ := account.Extra["codex_"+name+"_reset_at"].(string)
+		resetAt, err := time.Parse(time.RFC3339, resetStr)
+		if err != nil || !resetAt.After(now) {
+			continue
+		}
+		used := parseExtraFloat64(raw)
+		out = append(out, AccountQuotaWindowStatus{
+			Name:               name,
+			Unit:               AccountQuotaUnitPercent,
+			Source:             "upstream",
+			Limit:              100,
+			Used:               used,
+			Remaining:          100 - used,
+			PredictedRemaining: 100 - used,
+			ResetAt:            &resetAt,
+		})
+	}
+	return out
+}
+
+// isAccountSchedulableForQuota 检查账号配额预算是否允许调度：
+// 粘性会话仅在额度已耗尽时放弃，非粘性会话在接近耗尽时即避开。
+func (s *GatewayService) isAccountSchedulableForQuota(ctx context.Context, account *Account, isSticky bool) bool {
+	level := s.quotaTracker.Level(ctx, account)
+	if isSticky {
+		return level != AccountQuotaExhausted
+	}
+	return level == AccountQuotaOK
+}
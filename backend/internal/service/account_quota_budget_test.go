@@ -0,0 +1,152 @@
+//go:build unit
+
+package service
+
+import (
+	"context"
+	"errors"
+	"testing"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/pkg/usagestats"
+	"github.com/stretchr/testify/require"
+)
+
+var quotaBudgetTestNow = time.Date(2026, 3, 1, 10, 0, 0, 0, time.UTC)
+
+// usageLogQuotaRepoStub 按查询窗口长度返回用量
+type usageLogQuotaRepoStub struct {
+	UsageLogRepository
+
+	byWindow map[time.Duration]*usagestats.AccountStats
+	err      error
+	calls    int
+}
+
+func (s *usageLogQuotaRepoStub) GetAccountWindowStats(_ context.Context, _ int64, startTime time.Time) (*usagestats.AccountStats, error) {
+	s.calls++
+	if s.err != nil {
+		return nil, s.err
+	}
+	if stats, ok := s.byWindow[quotaBudgetTestNow.Sub(startTime)]; ok {
+		return stats, nil
+	}
+	return &usagestats.AccountStats{}, nil
+}
+
+func newQuotaBudgetTrackerForTest(repo UsageLogRepository) *AccountQuotaBudgetTracker {
+	tracker := NewAccountQuotaBudgetTracker(repo)
+	tracker.now = func() time.Time { return quotaBudgetTestNow }
+	return tracker
+}
+
+func quotaBudgetAccount(limit float64) *Account {
+	return &Account{
+		ID: 1,
+		Extra: map[string]any{
+			"quota_budget": map[string]any{
+				"windows": []any{
+					map[string]any{"name": "5h", "window_minutes": float64(300), "unit": "requests", "limit": limit},
+				},
+			},
+		},
+	}
+}
+
+func TestAccount_GetQuotaBudget(t *testing.T) {
+	account := &Account{Extra: map[string]any{
+		"quota_budget": map[string]any{
+			"reserve_percent": float64(20),
+			"windows": []any{
+				map[string]any{"name": "5h", "window_minutes": float64(300), "unit": "Tokens", "limit": float64(1000)},
+				map[string]any{"name": "bad-unit", "window_minutes": float64(60), "unit": "bytes", "limit": float64(10)},
+				map[string]any{"name": "no-limit", "window_minutes": float64(60), "unit": "requests"},
+			},
+		},
+	}}
+
+	budget := account.GetQuotaBudget()
+	require.NotNil(t, budget)
+	require.Equal(t, 20.0, budget.ReservePercent)
+	require.Equal(t, []AccountQuotaWindow{{Name: "5h", WindowMinutes: 300, Unit: AccountQuotaUnitTokens, Limit: 1000}}, budget.Windows)
+
+	require.Nil(t, (&Account{}).GetQuotaBudget())
+}
+
+func TestAccountQuotaBudgetTracker_PredictsRemaining(t *testing.T) {
+	repo := &usageLogQuotaRepoStub{byWindow: map[time.Duration]*usagestats.AccountStats{
+		300 * time.Minute: {Requests: 150},
+		10 * time.Minute:  {Requests: 40},
+	}}
+	tracker := newQuotaBudgetTrackerForTest(repo)
+
+	status := tracker.Evaluate(context.Background(), quotaBudgetAccount(200))
+	require.NotNil(t, status)
+	require.Len(t, status.Windows, 1)
+	ws := status.Windows[0]
+	require.Equal(t, 150.0, ws.Used)
+	require.Equal(t, 50.0, ws.Remaining)
+	// 200 - 150 - 40 + 150*10/300 = 15
+	require.InDelta(t, 15.0, ws.PredictedRemaining, 1e-9)
+	require.NotNil(t, ws.ExhaustAt)
+	// 净速率 (40-5)/600s，剩余 50 → 约 857s 后耗尽
+	require.InDelta(t, 857, ws.ExhaustAt.Sub(quotaBudgetTestNow).Seconds(), 1)
+	// 预测剩余 15/200 低于默认预留 10%
+	require.Equal(t, AccountQuotaLow, status.Level)
+
+	// 用量带缓存：再次评估不查询数据库
+	calls := repo.calls
+	tracker.Evaluate(context.Background(), quotaBudgetAccount(200))
+	require.Equal(t, calls, repo.calls)
+}
+
+func TestAccountQuotaBudgetTracker_Levels(t *testing.T) {
+	repo := &usageLogQuotaRepoStub{byWindow: map[time.Duration]*usagestats.AccountStats{
+		300 * time.Minute: {Requests: 150},
+		10 * time.Minute:  {Requests: 40},
+	}}
+	tracker := newQuotaBudgetTrackerForTest(repo)
+
+	require.Equal(t, AccountQuotaOK, tracker.Level(context.Background(), quotaBudgetAccount(400)))
+	require.Equal(t, AccountQuotaLow, tracker.Level(context.Background(), quotaBudgetAccount(160)))
+	require.Equal(t, AccountQuotaExhausted, tracker.Level(context.Background(), quotaBudgetAccount(150)))
+	require.Equal(t, AccountQuotaOK, tracker.Level(context.Background(), &Account{ID: 2}))
+
+	var nilTracker *AccountQuotaBudgetTracker
+	require.Equal(t, AccountQuotaOK, nilTracker.Level(context.Background(), quotaBudgetAccount(150)))
+}
+
+func TestAccountQuotaBudgetTracker_FailOpenOnQueryError(t *testing.T) {
+	tracker := newQuotaBudgetTrackerForTest(&usageLogQuotaRepoStub{err: errors.New("db down")})
+	require.Nil(t, tracker.Evaluate(context.Background(), quotaBudgetAccount(1)))
+	require.Equal(t, AccountQuotaOK, tracker.Level(context.Background(), quotaBudgetAccount(1)))
+}
+
+func TestAccountQuotaBudgetTracker_UpstreamSignals(t *testing.T) {
+	tracker := newQuotaBudgetTrackerForTest(nil)
+	future := quotaBudgetTestNow.Add(time.Hour).Format(time.RFC3339)
+	past := quotaBudgetTestNow.Add(-time.Hour).Format(time.RFC3339)
+
+	codex := &Account{ID: 3, Extra: map[string]any{
+		"codex_5h_used_percent": float64(95),
+		"codex_5h_reset_at":     future,
+		"codex_7d_used_percent": float64(100),
+		"codex_7d_reset_at":     past, // 已过重置时间，忽略
+	}}
+	status := tracker.Evaluate(context.Background(), codex)
+	require.NotNil(t, status)
+	require.Len(t, status.Windows, 1)
+	require.Equal(t, "upstream", status.Windows[0].Source)
+	require.Equal(t, AccountQuotaLow, status.Level)
+
+	codex.Extra["codex_5h_used_percent"] = float64(100)
+	require.Equal(t, AccountQuotaExhausted, tracker.Level(context.Background(), codex))
+
+	windowEnd := quotaBudgetTestNow.Add(30 * time.Minute)
+	warned := &Account{ID: 4, SessionWindowStatus: "allowed_warning", SessionWindowEnd: &windowEnd}
+	require.Equal(t, AccountQuotaLow, tracker.Level(context.Background(), warned))
+
+	expiredEnd := quotaBudgetTestNow.Add(-time.Minute)
+	warned.SessionWindowEnd = &expiredEnd
+	require.Equal(t, AccountQuotaOK, tracker.Level(context.Background(), warned))
+}
@@ -0,0 +1,452 @@
+package service
+
+import (
+	"context"
+	"errors"
+	"log"
+	"sort"
+	"strings"
+	"sync"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/pkg/ctxkey"
+	infraerrors "github.com/Wei-Shaw/sub2api/internal/pkg/errors"
+)
+
+const (
+	// accountShardCacheTTL 分片与规则的本地缓存时间；隔离操作在本实例立即生效，其他实例最迟在该时间后生效
+	accountShardCacheTTL    = 10 * time.Second
+	accountShardMaxNameLen  = 64
+	accountShardMaxModelLen = 200
+	accountShardMaxMembers  = 10000
+)
+
+var (
+	ErrAccountShardNotFound     = infraerrors.NotFound("ACCOUNT_SHARD_NOT_FOUND", "account shard not found")
+	ErrAccountShardExists       = infraerrors.Conflict("ACCOUNT_SHARD_EXISTS", "an account shard with this name already exists")
+	ErrInvalidAccountShard      = infraerrors.BadRequest("INVALID_ACCOUNT_SHARD", "shard name is required and may only contain letters, digits, '-', '_' and '.'")
+	ErrAccountShardRuleNotFound = infraerrors.NotFound("ACCOUNT_SHARD_RULE_NOT_FOUND", "account shard rule not found")
+	ErrInvalidAccountShardRule  = infraerrors.BadRequest("INVALID_ACCOUNT_SHARD_RULE", "shard_id is required and model_pattern must not exceed 200 characters")
+	ErrTooManyShardMembers      = infraerrors.BadRequest("TOO_MANY_SHARD_MEMBERS", "too many accounts in a single request")
+
+	// ErrAccountShardExcluded 账号所在分片已隔离或不在当前请求路由到的分片中（调度内部使用）
+	ErrAccountShardExcluded = errors.New("account excluded by shard routing")
+)
+
+// AccountShard 命名账号分片（如按出口地域或风险等级划分）
+type AccountShard struct {
+	ID             int64      `json:"id"`
+	Name           string     `json:"name"`
+	Description    string     `json:"description"`
+	Isolated       bool       `json:"isolated"`
+	IsolatedReason string     `json:"isolated_reason"`
+	IsolatedAt     *time.Time `json:"isolated_at"`
+	AccountIDs     []int64    `json:"account_ids"`
+	CreatedAt      time.Time  `json:"created_at"`
+	UpdatedAt      time.Time  `json:"updated_at"`
+}
+
+// AccountShardRule 分片路由规则：命中的请求只从指定分片选择账号。
+// APIKeyID / GroupID 为 nil、ModelPattern 为空表示该维度不限。
+type AccountShardRule struct {
+	ID           int64     `json:"id"`
+	ShardID      int64     `json:"shard_id"`
+	Priority     int       `json:"priority"`
+	APIKeyID     *int64    `json:"api_key_id"`
+	GroupID      *int64    `json:"group_id"`
+	ModelPattern string    `json:"model_pattern"`
+	Enabled      bool      `json:"enabled"`
+	CreatedAt    time.Time `json:"created_at"`
+	UpdatedAt    time.Time `json:"updated_at"`
+}
+
+// AccountShardRepository 账号分片与路由规则存储
+type AccountShardRepository interface {
+	ListShards(ctx context.Context) ([]*AccountShard, error)
+	GetShard(ctx context.Context, id int64) (*AccountShard, error)
+	CreateShard(ctx context.Context, shard *AccountShard) error
+	UpdateShard(ctx context.Context, shard *AccountShard) error
+	DeleteShard(ctx context.Context, id int64) error
+	// AddMembers 将账号移入分片（已属于其他分片的账号会被移出原分片），返回实际写入的数量
+	AddMembers(ctx context.Context, shardID int64, accountIDs []int64) (int64, error)
+	RemoveMembers(ctx context.Context, shardID int64, accountIDs []int64) (int64, error)
+
+	ListRules(ctx context.Context) ([]*AccountShardRule, error)
+	GetRule(ctx context.Context, id int64) (*AccountShardRule, error)
+	CreateRule(ctx context.Context, rule *AccountShardRule) error
+	UpdateRule(ctx context.Context, rule *AccountShardRule) error
+	DeleteRule(ctx context.Context, id int64) error
+}
+
+// accountShardState 调度使用的分片快照
+type accountShardState struct {
+	// accountShard 账号 ID -> 分片 ID
+	accountShard map[int64]int64
+	isolated     map[int64]bool
+	rules        []*AccountShardRule
+}
+
+// AccountShardService 账号分片：路由规则把 API Key / 分组 / 模型的请求限定到指定分片，
+// 被隔离分片中的账号不参与任何调度，便于在不影响其余账号池的情况下摘除出问题的分片。
+//
+// 未命中任何规则的请求可使用所有未隔离的账号（含未分配分片的账号），因此新增分片不会改变现有流量的调度范围。
+type AccountShardService struct {
+	repo AccountShardRepository
+
+	stateMu       sync.RWMutex
+	state         *accountShardState
+	stateLoadedAt time.Time
+}
+
+// NewAccountShardService 创建账号分片服务
+func NewAccountShardService(repo AccountShardRepository) *AccountShardService {
+	return &AccountShardService{repo: repo}
+}
+
+// ListShards 列出全部分片（含成员账号）
+func (s *AccountShardService) ListShards(ctx context.Context) ([]*AccountShard, error) {
+	return s.repo.ListShards(ctx)
+}
+
+// GetShard 获取分片
+func (s *AccountShardService) GetShard(ctx context.Context, id int64) (*AccountShard, error) {
+	return s.repo.GetShard(ctx, id)
+}
+
+// CreateShard 创建分片
+func (s *AccountShardService) CreateShard(ctx context.Context, shard *AccountShard) error {
+	if err := normalizeAccountShard(shard); err != nil {
+		return err
+	}
+	if err := s.repo.CreateShard(ctx, shard); err != nil {
+		return err
+	}
+	s.invalidate()
+	return nil
+}
+
+// UpdateShard 更新分片名称、描述与隔离状态
+func (s *AccountShardService) UpdateShard(ctx context.Context, shard *AccountShard) error {
+	if err := normalizeAccountShard(shard); err != nil {
+		return err
+	}
+	if err := s.repo.UpdateShard(ctx, shard); err != nil {
+		return err
+	}
+	s.invalidate()
+	return nil
+}
+
+// SetIsolated 隔离或恢复分片；隔离后分片内账号立即停止参与调度
+func (s *AccountShardService) SetIsolated(ctx context.Context, id int64, isolated bool, reason string) (*AccountShard, error) {
+	shard, err := s.repo.GetShard(ctx, id)
+	if err != nil {
+		return nil, err
+	}
+	if shard.Isolated != isolated {
+		shard.Isolated = isolated
+		if isolated {
+			now := time.Now()
+			shard.IsolatedAt = &now
+			shard.IsolatedReason = strings.TrimSpace(reason)
+		} else {
+			shard.IsolatedAt = nil
+			shard.IsolatedReason = ""
+		}
+	} else if isolated && strings.TrimSpace(reason) != "" {
+		shard.IsolatedReason = strings.TrimSpace(reason)
+	}
+	if err := s.UpdateShard(ctx, shard); err != nil {
+		return nil, err
+	}
+	log.Printf("[AccountShard] shard %s (id=%d) isolated=%v reason=%q", shard.Name, shard.ID, shard.Isolated, shard.IsolatedReason)
+	return shard, nil
+}
+
+// DeleteShard 删除分片；成员账号回到默认池，指向该分片的规则一并删除
+func (s *AccountShardService) DeleteShard(ctx context.Context, id int64) error {
+	if err := s.repo.DeleteShard(ctx, id); err != nil {
+		return err
+	}
+	s.invalidate()
+	return nil
+}
+
+// AddMembers 将账号移入分片
+func (s *AccountShardService) AddMembers(ctx context.Context, shardID int64, accountIDs []int64) (int64, error) {
+	ids, err := normalizeShardMemberIDs(accountIDs)
+	if err != nil {
+		return 0, err
+	}
+	if _, err := s.repo.GetShard(ctx, shardID); err != nil {
+		return 0, err
+	}
+	n, err := s.repo.AddMembers(ctx, shardID, ids)
+	if err != nil {
+		return 0, err
+	}
+	s.invalidate()
+	return n, nil
+}
+
+// RemoveMembers 将账号移出分片（回到默认池）
+func (s *AccountShardService) RemoveMembers(ctx context.Context, shardID int64, accountIDs []int64) (int64, error) {
+	ids, err := normalizeShardMemberIDs(accountIDs)
+	if err != nil {
+		return 0, err
+	}
+	n, err := s.repo.RemoveMembers(ctx, shardID, ids)
+	if err != nil {
+		return 0, err
+	}
+	s.invalidate()
+	return n, nil
+}
+
+// ListRules 列出全部路由规则（按匹配顺序）
+func (s *AccountShardService) ListRules(ctx context.Context) ([]*AccountShardRule, error) {
+	return s.repo.ListRules(ctx)
+}
+
+// GetRule 获取路由规则
+func (s *AccountShardService) GetRule(ctx context.Context, id int64) (*AccountShardRule, error) {
+	return s.repo.GetRule(ctx, id)
+}
+
+// CreateRule 创建路由规则
+func (s *AccountShardService) CreateRule(ctx context.Context, rule *AccountShardRule) error {
+	if err := s.validateRule(ctx, rule); err != nil {
+		return err
+	}
+	if err := s.repo.CreateRule(ctx, rule); err != nil {
+		return err
+	}
+	s.invalidate()
+	return nil
+}
+
+// UpdateRule 更新路由规则
+func (s *AccountShardService) UpdateRule(ctx context.Context, rule *AccountShardRule) error {
+	if err := s.validateRule(ctx, rule); err != nil {
+		return err
+	}
+	if err := s.repo.UpdateRule(ctx, rule); err != nil {
+		return err
+	}
+	s.invalidate()
+	return nil
+}
+
+// DeleteRule 删除路由规则
+func (s *AccountShardService) DeleteRule(ctx context.Context, id int64) error {
+	if err := s.repo.DeleteRule(ctx, id); err != nil {
+		return err
+	}
+	s.invalidate()
+	return nil
+}
+
+func (s *AccountShardService) validateRule(ctx context.Context, rule *AccountShardRule) error {
+	if rule == nil || rule.ShardID <= 0 {
+		return ErrInvalidAccountShardRule
+	}
+	rule.ModelPattern = strings.TrimSpace(rule.ModelPattern)
+	if len(rule.ModelPattern) > accountShardMaxModelLen {
+		return ErrInvalidAccountShardRule
+	}
+	if rule.APIKeyID != nil && *rule.APIKeyID <= 0 {
+		rule.APIKeyID = nil
+	}
+	if rule.GroupID != nil && *rule.GroupID <= 0 {
+		rule.GroupID = nil
+	}
+	_, err := s.repo.GetShard(ctx, rule.ShardID)
+	return err
+}
+
+// normalizeAccountShard 校验并规范化分片字段
+func normalizeAccountShard(shard *AccountShard) error {
+	if shard == nil {
+		return ErrInvalidAccountShard
+	}
+	shard.Name = strings.TrimSpace(shard.Name)
+	shard.Description = strings.TrimSpace(shard.Description)
+	if shard.Name == "" || len(shard.Name) > accountShardMaxNameLen {
+		return ErrInvalidAccountShard
+	}
+	if strings.IndexFunc(shard.Name, isInvalidShardNameRune) >= 0 {
+		return ErrInvalidAccountShard
+	}
+	return nil
+}
+
+func isInvalidShardNameRune(r rune) bool {
+	switch {
+	case r >= 'a' && r <= 'z', r >= 'A' && r <= 'Z', r >= '0' && r <= '9', r == '-', r == '_', r == '.':
+		return false
+	}
+	return true
+}
+
+// normalizeShardMemberIDs 去重并丢弃非法 ID
+func normalizeShardMemberIDs(accountIDs []int64) ([]int64, error) {
+	if len(accountIDs) > accountShardMaxMembers {
+		return nil, ErrTooManyShardMembers
+	}
+	seen := make(map[int64]struct{}, len(accountIDs))
+	out := make([]int64, 0, len(accountIDs))
+	for _, id := range accountIDs {
+		if id <= 0 {
+			continue
+		}
+		if _, dup := seen[id]; dup {
+			continue
+		}
+		seen[id] = struct{}{}
+		out = append(out, id)
+	}
+	return out, nil
+}
+
+func (s *AccountShardService) invalidate() {
+	s.stateMu.Lock()
+	s.stateLoadedAt = time.Time{}
+	s.stateMu.Unlock()
+}
+
+// loadState 返回分片快照（带短时缓存）；加载失败时沿用上一份快照，首次加载失败则不做分片过滤
+func (s *AccountShardService) loadState(ctx context.Context) *accountShardState {
+	s.stateMu.RLock()
+	if !s.stateLoadedAt.IsZero() && time.Since(s.stateLoadedAt) < accountShardCacheTTL {
+		state := s.state
+		s.stateMu.RUnlock()
+		return state
+	}
+	s.stateMu.RUnlock()
+
+	state, err := s.buildState(ctx)
+	s.stateMu.Lock()
+	defer s.stateMu.Unlock()
+	if err != nil {
+		log.Printf("[AccountShard] load shards failed: %v", err)
+		state = s.state
+	}
+	s.state = state
+	s.stateLoadedAt = time.Now()
+	return state
+}
+
+func (s *AccountShardService) buildState(ctx context.Context) (*accountShardState, error) {
+	shards, err := s.repo.ListShards(ctx)
+	if err != nil {
+		return nil, err
+	}
+	if len(shards) == 0 {
+		return nil, nil
+	}
+	rules, err := s.repo.ListRules(ctx)
+	if err != nil {
+		return nil, err
+	}
+	state := &accountShardState{
+		accountShard: make(map[int64]int64),
+		isolated:     make(map[int64]bool),
+	}
+	for _, shard := range shards {
+		if shard.Isolated {
+			state.isolated[shard.ID] = true
+		}
+		for _, accountID := range shard.AccountIDs {
+			state.accountShard[accountID] = shard.ID
+		}
+	}
+	for _, rule := range rules {
+		if rule != nil && rule.Enabled {
+			state.rules = append(state.rules, rule)
+		}
+	}
+	sort.SliceStable(state.rules, func(i, j int) bool {
+		if state.rules[i].Priority != state.rules[j].Priority {
+			return state.rules[i].Priority < state.rules[j].Priority
+		}
+		return state.rules[i].ID < state.rules[j].ID
+	})
+	return state, nil
+}
+
+// matchRule 返回第一条命中的规则；规则按 priority 升序、同优先级按 ID 升序排列
+func (st *accountShardState) matchRule(apiKeyID int64, groupID *int64, model string) *AccountShardRule {
+	for _, rule := range st.rules {
+		if rule.APIKeyID != nil && *rule.APIKeyID != apiKeyID {
+			continue
+		}
+		if rule.GroupID != nil && (groupID == nil || *rule.GroupID != *groupID) {
+			continue
+		}
+		if rule.ModelPattern != "" && !matchModelPattern(rule.ModelPattern, model) {
+			continue
+		}
+		return rule
+	}
+	return nil
+}
+
+// allows 账号是否可服务当前请求：隔离分片内的账号一律排除；命中规则时仅允许目标分片内的账号
+func (st *accountShardState) allows(accountID int64, rule *AccountShardRule) bool {
+	shardID, inShard := st.accountShard[accountID]
+	if inShard && st.isolated[shardID] {
+		return false
+	}
+	if rule != nil {
+		return inShard && shardID == rule.ShardID
+	}
+	return true
+}
+
+// WithAPIKeyID 将当前请求的 API Key ID 写入上下文，供分片路由匹配
+func WithAPIKeyID(ctx context.Context, apiKeyID int64) context.Context {
+	return context.WithValue(ctx, ctxkey.APIKeyID, apiKeyID)
+}
+
+// resolveRule 按上下文中的 API Key、请求模型与分组匹配路由规则；groupID 为 nil 时使用认证得到的分组
+func (st *accountShardState) resolveRule(ctx context.Context, groupID *int64) *AccountShardRule {
+	if groupID == nil {
+		if group, ok := ctx.Value(ctxkey.Group).(*Group); ok && group != nil {
+			groupID = &group.ID
+		}
+	}
+	apiKeyID, _ := ctx.Value(ctxkey.APIKeyID).(int64)
+	model, _ := ctx.Value(ctxkey.Model).(string)
+	return st.matchRule(apiKeyID, groupID, strings.TrimSpace(model))
+}
+
+// FilterAccounts 按分片路由与隔离状态过滤候选账号；未配置分片时原样返回
+func (s *AccountShardService) FilterAccounts(ctx context.Context, groupID *int64, accounts []Account) []Account {
+	if s == nil || len(accounts) == 0 {
+		return accounts
+	}
+	state := s.loadState(ctx)
+	if state == nil {
+		return accounts
+	}
+	rule := state.resolveRule(ctx, groupID)
+	filtered := make([]Account, 0, len(accounts))
+	for _, acc := range accounts {
+		if state.allows(acc.ID, rule) {
+			filtered = append(filtered, acc)
+		}
+	}
+	return filtered
+}
+
+// AllowsAccount 单个账号（如粘性会话绑定的账号）是否可服务当前请求
+func (s *AccountShardService) AllowsAccount(ctx context.Context, accountID int64) bool {
+	if s == nil {
+		return true
+	}
+	state := s.loadState(ctx)
+	if state == nil {
+		return true
+	}
+	return state.allows(accountID, state.resolveRule(ctx, nil))
+}
@@ -0,0 +1,156 @@
+//go:build unit
+
+package service
+
+import (
+	"context"
+	"testing"
+
+	"github.com/Wei-Shaw/sub2api/internal/pkg/ctxkey"
+	"github.com/stretchr/testify/require"
+)
+
+type accountShardRepoStub struct {
+	AccountShardRepository
+	shards    []*AccountShard
+	rules     []*AccountShardRule
+	listCalls int
+}
+
+func (r *accountShardRepoStub) ListShards(ctx context.Context) ([]*AccountShard, error) {
+	r.listCalls++
+	return r.shards, nil
+}
+
+func (r *accountShardRepoStub) GetShard(ctx context.Context, id int64) (*AccountShard, error) {
+	for _, shard := range r.shards {
+		if shard.ID == id {
+			cp := *shard
+			return &cp, nil
+		}
+	}
+	return nil, ErrAccountShardNotFound
+}
+
+func (r *accountShardRepoStub) UpdateShard(ctx context.Context, shard *AccountShard) error {
+	for i, existing := range r.shards {
+		if existing.ID == shard.ID {
+			r.shards[i] = shard
+			return nil
+		}
+	}
+	return ErrAccountShardNotFound
+}
+
+func (r *accountShardRepoStub) ListRules(ctx context.Context) ([]*AccountShardRule, error) {
+	return r.rules, nil
+}
+
+func accountShardIDs(accounts []Account) []int64 {
+	ids := make([]int64, 0, len(accounts))
+	for _, acc := range accounts {
+		ids = append(ids, acc.ID)
+	}
+	return ids
+}
+
+func newAccountShardTestService() (*AccountShardService, *accountShardRepoStub) {
+	keyID := int64(42)
+	repo := &accountShardRepoStub{
+		shards: []*AccountShard{
+			{ID: 1, Name: "us-east", AccountIDs: []int64{1, 2}},
+			{ID: 2, Name: "eu-west", AccountIDs: []int64{3}},
+		},
+		rules: []*AccountShardRule{
+			{ID: 1, ShardID: 1, Priority: 10, ModelPattern: "claude-opus-*", Enabled: true},
+			{ID: 2, ShardID: 2, Priority: 0, APIKeyID: &keyID, Enabled: true},
+			{ID: 3, ShardID: 2, Priority: -1, ModelPattern: "claude-haiku-*", Enabled: false},
+		},
+	}
+	return NewAccountShardService(repo), repo
+}
+
+func TestAccountShardService_FilterAccountsRouting(t *testing.T) {
+	svc, _ := newAccountShardTestService()
+	accounts := []Account{{ID: 1}, {ID: 2}, {ID: 3}, {ID: 4}}
+
+	// 未命中规则：所有未隔离账号（含未分片的 4）
+	require.Equal(t, []int64{1, 2, 3, 4}, accountShardIDs(svc.FilterAccounts(context.Background(), nil, accounts)))
+
+	// 命中模型规则：仅 us-east
+	ctx := context.WithValue(context.Background(), ctxkey.Model, "claude-opus-4-20250514")
+	require.Equal(t, []int64{1, 2}, accountShardIDs(svc.FilterAccounts(ctx, nil, accounts)))
+
+	// API Key 规则优先级更高
+	ctx = context.WithValue(ctx, ctxkey.APIKeyID, int64(42))
+	require.Equal(t, []int64{3}, accountShardIDs(svc.FilterAccounts(ctx, nil, accounts)))
+	require.False(t, svc.AllowsAccount(ctx, 1))
+	require.True(t, svc.AllowsAccount(ctx, 3))
+
+	// 已禁用规则不参与匹配
+	ctx = context.WithValue(context.Background(), ctxkey.Model, "claude-haiku-4-5")
+	require.Len(t, svc.FilterAccounts(ctx, nil, accounts), 4)
+}
+
+func TestAccountShardService_IsolationExcludesOnlyThatShard(t *testing.T) {
+	svc, _ := newAccountShardTestService()
+	accounts := []Account{{ID: 1}, {ID: 2}, {ID: 3}, {ID: 4}}
+
+	shard, err := svc.SetIsolated(context.Background(), 2, true, "flagged by upstream")
+	require.NoError(t, err)
+	require.True(t, shard.Isolated)
+	require.NotNil(t, shard.IsolatedAt)
+
+	// 隔离立即生效，其他分片与默认池不受影响
+	require.Equal(t, []int64{1, 2, 4}, accountShardIDs(svc.FilterAccounts(context.Background(), nil, accounts)))
+	require.False(t, svc.AllowsAccount(context.Background(), 3))
+
+	// 路由到隔离分片的请求没有可用账号，不回退到其他分片
+	ctx := context.WithValue(context.Background(), ctxkey.APIKeyID, int64(42))
+	require.Empty(t, svc.FilterAccounts(ctx, nil, accounts))
+
+	_, err = svc.SetIsolated(context.Background(), 2, false, "")
+	require.NoError(t, err)
+	require.Equal(t, []int64{3}, accountShardIDs(svc.FilterAccounts(ctx, nil, accounts)))
+}
+
+func TestAccountShardService_NoShardsAndNilService(t *testing.T) {
+	repo := &accountShardRepoStub{}
+	svc := NewAccountShardService(repo)
+	accounts := []Account{{ID: 1}, {ID: 2}}
+
+	require.Len(t, svc.FilterAccounts(context.Background(), nil, accounts), 2)
+	require.True(t, svc.AllowsAccount(context.Background(), 1))
+	// 快照在缓存期内复用
+	require.Equal(t, 1, repo.listCalls)
+
+	var nilSvc *AccountShardService
+	require.Len(t, nilSvc.FilterAccounts(context.Background(), nil, accounts), 2)
+	require.True(t, nilSvc.AllowsAccount(context.Background(), 1))
+}
+
+func TestAccountShardRule_GroupScope(t *testing.T) {
+	groupID := int64(7)
+	svc := NewAccountShardService(&accountShardRepoStub{
+		shards: []*AccountShard{{ID: 1, Name: "high-risk", AccountIDs: []int64{9}}},
+		rules:  []*AccountShardRule{{ID: 1, ShardID: 1, GroupID: &groupID, Enabled: true}},
+	})
+	accounts := []Account{{ID: 8}, {ID: 9}}
+
+	other := int64(8)
+	require.Len(t, svc.FilterAccounts(context.Background(), &other, accounts), 2)
+	require.Equal(t, []int64{9}, accountShardIDs(svc.FilterAccounts(context.Background(), &groupID, accounts)))
+
+	// 未显式传入分组时使用认证得到的分组
+	ctx := context.WithValue(context.Background(), ctxkey.Group, &Group{ID: groupID})
+	require.False(t, svc.AllowsAccount(ctx, 8))
+}
+
+func TestNormalizeAccountShard(t *testing.T) {
+	shard := &AccountShard{Name: "  us-east.tier_1 "}
+	require.NoError(t, normalizeAccountShard(shard))
+	require.Equal(t, "us-east.tier_1", shard.Name)
+
+	require.ErrorIs(t, normalizeAccountShard(&AccountShard{Name: "bad name"}), ErrInvalidAccountShard)
+	require.ErrorIs(t, normalizeAccountShard(&AccountShard{}), ErrInvalidAccountShard)
+}
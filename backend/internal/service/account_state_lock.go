@@ -0,0 +1,185 @@
+package service
+
+import (
+	"context"
+	"log/slog"
+	"math/rand/v2"
+	"time"
+
+	"github.com/google/uuid"
+)
+
+const (
+	// accountStateLockTTL 账号状态锁的过期时间，仅覆盖一次读取+写入或一次槽位占用，持有者崩溃时很快自动释放
+	accountStateLockTTL = 5 * time.Second
+	// accountStateLockBackoffMin/Max 锁被占用时重试的退避区间（指数增长，带随机抖动）
+	accountStateLockBackoffMin = 2 * time.Millisecond
+	accountStateLockBackoffMax = 50 * time.Millisecond
+)
+
+// AccountStateLocker 账号状态迁移的跨副本短锁（Redis），释放时校验持有者
+type AccountStateLocker interface {
+	TryLock(ctx context.Context, accountID int64, owner string, ttl time.Duration) (bool, error)
+	Unlock(ctx context.Context, accountID int64, owner string) error
+}
+
+// SetAccountStateLocker 设置账号状态迁移锁（可选依赖）
+//
+// 设置后，限流/过载/临时不可调度等冷却写入在锁内重新读取账号当前状态，只延长不缩短冷却，
+// 避免多个副本同时处理同一账号的上游错误时后写入者覆盖更长的冷却；401 强制刷新也改为在锁内
+// 基于最新账号数据写入，不再用请求开始时的快照覆盖其他副本刚刷新的凭证。
+func (s *RateLimitService) SetAccountStateLocker(locker AccountStateLocker) {
+	s.stateLocker = locker
+}
+
+// SetAccountStateLocker 设置账号状态锁（可选依赖）
+//
+// 设置后，账号槽位在锁内先读取当前并发数再占用，与冷却写入共用同一把锁，
+// 多个副本不会同时向刚达到并发上限的账号派发请求。
+func (s *ConcurrencyService) SetAccountStateLocker(locker AccountStateLocker) {
+	s.stateLocker = locker
+}
+
+// lockAccountState 阻塞获取账号状态锁：锁被占用时以短退避重试，直到取得锁、ctx 结束或等待超过锁的 TTL
+// （持有者崩溃时锁在 TTL 内自动过期，无需更久等待）。Redis 不可用或等待失败时 locked 为 false，
+// 调用方退化为不加锁执行，不因锁阻塞请求。
+func lockAccountState(ctx context.Context, locker AccountStateLocker, accountID int64) (unlock func(), locked bool) {
+	owner := uuid.NewString()
+	deadline := time.Now().Add(accountStateLockTTL)
+	backoff := accountStateLockBackoffMin
+	for {
+		ok, err := locker.TryLock(ctx, accountID, owner, accountStateLockTTL)
+		if err != nil {
+			slog.Warn("account_state_lock_failed", "account_id", accountID, "error", err)
+			return nil, false
+		}
+		if ok {
+			break
+		}
+		if time.Now().After(deadline) {
+			slog.Warn("account_state_lock_timeout", "account_id", accountID)
+			return nil, false
+		}
+		timer := time.NewTimer(backoff/2 + rand.N(backoff/2+1))
+		select {
+		case <-ctx.Done():
+			timer.Stop()
+			return nil, false
+		case <-timer.C:
+		}
+		backoff = min(backoff*2, accountStateLockBackoffMax)
+	}
+	return func() {
+		unlockCtx, cancel := context.WithTimeout(context.WithoutCancel(ctx), 3*time.Second)
+		defer cancel()
+		if err := locker.Unlock(unlockCtx, accountID, owner); err != nil {
+			slog.Warn("account_state_unlock_failed", "account_id", accountID, "error", err)
+		}
+	}, true
+}
+
+// withAccountStateLock 在账号状态锁内执行 fn，locked 表示是否实际持有锁。
+// 未配置锁或未能加锁时退化为不加锁执行，不因锁阻塞上游错误处理。
+func (s *RateLimitService) withAccountStateLock(ctx context.Context, accountID int64, fn func(locked bool) error) error {
+	if s.stateLocker == nil {
+		return fn(false)
+	}
+	unlock, locked := lockAccountState(ctx, s.stateLocker, accountID)
+	if !locked {
+		return fn(false)
+	}
+	defer unlock()
+	return fn(true)
+}
+
+// acquireAccountSlotLocked 在账号状态锁内占用账号槽位：并发数已达上限时直接返回未占用。
+// 未配置锁或未能加锁时只依赖缓存的原子占用脚本。
+func (s *ConcurrencyService) acquireAccountSlotLocked(ctx context.Context, accountID int64, maxConcurrency int, requestID string) (bool, error) {
+	if s.stateLocker == nil {
+		return s.cache.AcquireAccountSlot(ctx, accountID, maxConcurrency, requestID)
+	}
+	unlock, locked := lockAccountState(ctx, s.stateLocker, accountID)
+	if !locked {
+		return s.cache.AcquireAccountSlot(ctx, accountID, maxConcurrency, requestID)
+	}
+	defer unlock()
+	current, err := s.cache.GetAccountConcurrency(ctx, accountID)
+	if err != nil {
+		return false, err
+	}
+	if current >= maxConcurrency {
+		return false, nil
+	}
+	return s.cache.AcquireAccountSlot(ctx, accountID, maxConcurrency, requestID)
+}
+
+// currentAccountState 持锁时重新读取账号；未持锁或读取失败时返回 nil，调用方按原逻辑直接写入
+func (s *RateLimitService) currentAccountState(ctx context.Context, accountID int64, locked bool) *Account {
+	if !locked {
+		return nil
+	}
+	current, err := s.accountRepo.GetByID(ctx, accountID)
+	if err != nil {
+		slog.Warn("account_state_reload_failed", "account_id", accountID, "error", err)
+		return nil
+	}
+	return current
+}
+
+// coversUntil 已有冷却截止时间不早于新的截止时间时无需写入
+func coversUntil(existing *time.Time, until time.Time) bool {
+	return existing != nil && !existing.Before(until)
+}
+
+// setRateLimited 设置限流冷却；其他副本已写入更晚的重置时间时保留原值
+func (s *RateLimitService) setRateLimited(ctx context.Context, accountID int64, resetAt time.Time) error {
+	return s.withAccountStateLock(ctx, accountID, func(locked bool) error {
+		if current := s.currentAccountState(ctx, accountID, locked); current != nil && coversUntil(current.RateLimitResetAt, resetAt) {
+			return nil
+		}
+		return s.accountRepo.SetRateLimited(ctx, accountID, resetAt)
+	})
+}
+
+// setOverloaded 设置过载冷却；其他副本已写入更晚的截止时间时保留原值
+func (s *RateLimitService) setOverloaded(ctx context.Context, accountID int64, until time.Time) error {
+	return s.withAccountStateLock(ctx, accountID, func(locked bool) error {
+		if current := s.currentAccountState(ctx, accountID, locked); current != nil && coversUntil(current.OverloadUntil, until) {
+			return nil
+		}
+		return s.accountRepo.SetOverloaded(ctx, accountID, until)
+	})
+}
+
+// setTempUnschedulable 设置临时不可调度；applied 为 false 表示其他副本已设置了更晚的截止时间
+func (s *RateLimitService) setTempUnschedulable(ctx context.Context, accountID int64, until time.Time, reason string) (applied bool, err error) {
+	err = s.withAccountStateLock(ctx, accountID, func(locked bool) error {
+		if current := s.currentAccountState(ctx, accountID, locked); current != nil && coversUntil(current.TempUnschedulableUntil, until) {
+			return nil
+		}
+		applied = true
+		return s.accountRepo.SetTempUnschedulable(ctx, accountID, until, reason)
+	})
+	return applied, err
+}
+
+// forceOAuthRefresh 将 expires_at 置为当前时间，强制下次请求刷新 token。
+// 持锁时基于最新账号数据写入，避免覆盖其他副本刚刷新的凭证。
+func (s *RateLimitService) forceOAuthRefresh(ctx context.Context, account *Account) error {
+	return s.withAccountStateLock(ctx, account.ID, func(locked bool) error {
+		expiresAt := time.Now().Format(time.RFC3339)
+		if account.Credentials == nil {
+			account.Credentials = make(map[string]any)
+		}
+		account.Credentials["expires_at"] = expiresAt
+		target := account
+		if current := s.currentAccountState(ctx, account.ID, locked); current != nil {
+			if current.Credentials == nil {
+				current.Credentials = make(map[string]any)
+			}
+			current.Credentials["expires_at"] = expiresAt
+			target = current
+		}
+		return s.accountRepo.Update(ctx, target)
+	})
+}
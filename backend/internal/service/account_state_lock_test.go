@@ -0,0 +1,178 @@
+//go:build unit
+
+package service
+
+import (
+	"context"
+	"errors"
+	"sync"
+	"sync/atomic"
+	"testing"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/stretchr/testify/require"
+)
+
+type accountStateLockerStub struct {
+	mu       sync.Mutex
+	held     map[int64]string
+	tryErr   error
+	unlocked int
+}
+
+func (l *accountStateLockerStub) TryLock(ctx context.Context, accountID int64, owner string, ttl time.Duration) (bool, error) {
+	l.mu.Lock()
+	defer l.mu.Unlock()
+	if l.tryErr != nil {
+		return false, l.tryErr
+	}
+	if _, ok := l.held[accountID]; ok {
+		return false, nil
+	}
+	l.held[accountID] = owner
+	return true, nil
+}
+
+func (l *accountStateLockerStub) Unlock(ctx context.Context, accountID int64, owner string) error {
+	l.mu.Lock()
+	defer l.mu.Unlock()
+	if l.held[accountID] == owner {
+		delete(l.held, accountID)
+		l.unlocked++
+	}
+	return nil
+}
+
+type stateLockAccountRepoStub struct {
+	mockAccountRepoForGemini
+	rateLimitedCalls []time.Time
+	updated          *Account
+}
+
+func (r *stateLockAccountRepoStub) SetRateLimited(ctx context.Context, id int64, resetAt time.Time) error {
+	r.rateLimitedCalls = append(r.rateLimitedCalls, resetAt)
+	return nil
+}
+
+func (r *stateLockAccountRepoStub) Update(ctx context.Context, account *Account) error {
+	r.updated = account
+	return nil
+}
+
+func TestRateLimitService_SetRateLimitedKeepsLongerCooldown(t *testing.T) {
+	now := time.Now()
+	later := now.Add(time.Hour)
+	repo := &stateLockAccountRepoStub{}
+	repo.accountsByID = map[int64]*Account{7: {ID: 7, RateLimitResetAt: &later}}
+	locker := &accountStateLockerStub{held: map[int64]string{}}
+	svc := NewRateLimitService(repo, nil, &config.Config{}, nil, nil)
+	svc.SetAccountStateLocker(locker)
+
+	// 其他副本已写入更晚的重置时间，不缩短
+	require.NoError(t, svc.setRateLimited(context.Background(), 7, now.Add(time.Minute)))
+	require.Empty(t, repo.rateLimitedCalls)
+
+	require.NoError(t, svc.setRateLimited(context.Background(), 7, later.Add(time.Minute)))
+	require.Len(t, repo.rateLimitedCalls, 1)
+	require.Equal(t, 2, locker.unlocked)
+	require.Empty(t, locker.held)
+}
+
+func TestRateLimitService_StateLockFallsBackWhenUnavailable(t *testing.T) {
+	later := time.Now().Add(time.Hour)
+	repo := &stateLockAccountRepoStub{}
+	repo.accountsByID = map[int64]*Account{7: {ID: 7, RateLimitResetAt: &later}}
+	svc := NewRateLimitService(repo, nil, &config.Config{}, nil, nil)
+	svc.SetAccountStateLocker(&accountStateLockerStub{held: map[int64]string{}, tryErr: errors.New("redis down")})
+
+	// 无法加锁时按原逻辑直接写入
+	require.NoError(t, svc.setRateLimited(context.Background(), 7, time.Now().Add(time.Minute)))
+	require.Len(t, repo.rateLimitedCalls, 1)
+}
+
+func TestRateLimitService_ForceOAuthRefreshUsesLatestCredentials(t *testing.T) {
+	repo := &stateLockAccountRepoStub{}
+	repo.accountsByID = map[int64]*Account{
+		9: {ID: 9, Credentials: map[string]any{"access_token": "refreshed-by-other-replica"}},
+	}
+	svc := NewRateLimitService(repo, nil, &config.Config{}, nil, nil)
+	svc.SetAccountStateLocker(&accountStateLockerStub{held: map[int64]string{}})
+
+	stale := &Account{ID: 9, Credentials: map[string]any{"access_token": "stale"}}
+	require.NoError(t, svc.forceOAuthRefresh(context.Background(), stale))
+	require.NotNil(t, repo.updated)
+	require.Equal(t, "refreshed-by-other-replica", repo.updated.Credentials["access_token"])
+	require.NotEmpty(t, repo.updated.Credentials["expires_at"])
+	require.Equal(t, repo.updated.Credentials["expires_at"], stale.Credentials["expires_at"])
+}
+
+// racyAccountSlotCache 先读并发数再写入的非原子槽位缓存，读写之间留出间隙，
+// 模拟两个副本同时看到同一个空闲槽位
+type racyAccountSlotCache struct {
+	stubConcurrencyCacheForTest
+	mu    sync.Mutex
+	slots map[string]struct{}
+	peak  int
+}
+
+func (c *racyAccountSlotCache) GetAccountConcurrency(_ context.Context, _ int64) (int, error) {
+	c.mu.Lock()
+	defer c.mu.Unlock()
+	return len(c.slots), nil
+}
+
+func (c *racyAccountSlotCache) AcquireAccountSlot(ctx context.Context, accountID int64, maxConcurrency int, requestID string) (bool, error) {
+	current, _ := c.GetAccountConcurrency(ctx, accountID)
+	if current >= maxConcurrency {
+		return false, nil
+	}
+	time.Sleep(time.Millisecond)
+	c.mu.Lock()
+	defer c.mu.Unlock()
+	c.slots[requestID] = struct{}{}
+	c.peak = max(c.peak, len(c.slots))
+	return true, nil
+}
+
+func TestConcurrencyService_StateLockRespectsAccountCapAcrossReplicas(t *testing.T) {
+	const maxConcurrency = 2
+	cache := &racyAccountSlotCache{slots: map[string]struct{}{}}
+	locker := &accountStateLockerStub{held: map[int64]string{}}
+	// 两个副本共享同一个 Redis（槽位缓存与状态锁）
+	replicas := []*ConcurrencyService{NewConcurrencyService(cache), NewConcurrencyService(cache)}
+	for _, svc := range replicas {
+		svc.SetAccountStateLocker(locker)
+	}
+
+	const callers = 16
+	var acquired atomic.Int32
+	errs := make(chan error, callers)
+	start := make(chan struct{})
+	var wg sync.WaitGroup
+	for i := 0; i < callers; i++ {
+		wg.Add(1)
+		go func(svc *ConcurrencyService) {
+			defer wg.Done()
+			<-start
+			result, err := svc.AcquireAccountSlot(context.Background(), 7, maxConcurrency)
+			if err != nil {
+				errs <- err
+				return
+			}
+			if result.Acquired {
+				acquired.Add(1)
+			}
+		}(replicas[i%len(replicas)])
+	}
+	close(start)
+	wg.Wait()
+	close(errs)
+
+	for err := range errs {
+		require.NoError(t, err)
+	}
+	require.Equal(t, int32(maxConcurrency), acquired.Load())
+	require.Equal(t, maxConcurrency, cache.peak)
+	require.Empty(t, locker.held)
+}
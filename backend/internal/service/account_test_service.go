@@ -289,7 +289,7 @@ func (s *AccountTestService) testClaudeAccountConnection(c *gin.Context, account
 		proxyURL = account.Proxy.URL()
 	}
 
-	resp, err := s.httpUpstream.DoWithTLS(req, proxyURL, account.ID, account.Concurrency, account.IsTLSFingerprintEnabled())
+	resp, err := s.httpUpstream.DoWithTLS(withAccountTLSProfile(req, account), proxyURL, account.ID, account.Concurrency, account.IsTLSFingerprintEnabled())
 	if err != nil {
 		return s.sendErrorAndEnd(c, fmt.Sprintf("Request failed: %s", err.Error()))
 	}
@@ -399,7 +399,7 @@ func (s *AccountTestService) testOpenAIAccountConnection(c *gin.Context, account
 		proxyURL = account.Proxy.URL()
 	}
 
-	resp, err := s.httpUpstream.DoWithTLS(req, proxyURL, account.ID, account.Concurrency, account.IsTLSFingerprintEnabled())
+	resp, err := s.httpUpstream.DoWithTLS(withAccountTLSProfile(req, account), proxyURL, account.ID, account.Concurrency, account.IsTLSFingerprintEnabled())
 	if err != nil {
 		return s.sendErrorAndEnd(c, fmt.Sprintf("Request failed: %s", err.Error()))
 	}
@@ -453,6 +453,8 @@ func (s *AccountTestService) testGeminiAccountConnection(c *gin.Context, account
 		req, err = s.buildGeminiAPIKeyRequest(ctx, account, testModelID, payload)
 	case AccountTypeOAuth:
 		req, err = s.buildGeminiOAuthRequest(ctx, account, testModelID, payload)
+	case AccountTypeWebSession:
+		return s.testGeminiWebAccountConnection(c, account, testModelID)
 	default:
 		return s.sendErrorAndEnd(c, fmt.Sprintf("Unsupported account type: %s", account.Type))
 	}
@@ -470,7 +472,7 @@ func (s *AccountTestService) testGeminiAccountConnection(c *gin.Context, account
 		proxyURL = account.Proxy.URL()
 	}
 
-	resp, err := s.httpUpstream.DoWithTLS(req, proxyURL, account.ID, account.Concurrency, account.IsTLSFingerprintEnabled())
+	resp, err := s.httpUpstream.DoWithTLS(withAccountTLSProfile(req, account), proxyURL, account.ID, account.Concurrency, account.IsTLSFingerprintEnabled())
 	if err != nil {
 		return s.sendErrorAndEnd(c, fmt.Sprintf("Request failed: %s", err.Error()))
 	}
@@ -485,6 +487,71 @@ func (s *AccountTestService) testGeminiAccountConnection(c *gin.Context, account
 	return s.processGeminiStream(c, resp.Body)
 }
 
+// testGeminiWebAccountConnection 网页会话账号：拉取 /app 校验 Cookie，再发送一条测试消息
+func (s *AccountTestService) testGeminiWebAccountConnection(c *gin.Context, account *Account, testModelID string) error {
+	ctx := c.Request.Context()
+	s.sendEvent(c, TestEvent{Type: "test_start", Model: testModelID})
+
+	proxyURL := ""
+	if account.ProxyID != nil && account.Proxy != nil {
+		proxyURL = account.Proxy.URL()
+	}
+
+	req, err := newGeminiWebRequest(ctx, account, http.MethodGet, geminiWebBaseURL+"/app", nil, "")
+	if err != nil {
+		return s.sendErrorAndEnd(c, fmt.Sprintf("Failed to build request: %s", err.Error()))
+	}
+	resp, err := s.httpUpstream.Do(req, proxyURL, account.ID, account.Concurrency)
+	if err != nil {
+		return s.sendErrorAndEnd(c, fmt.Sprintf("Request failed: %s", sanitizeUpstreamErrorMessage(err.Error())))
+	}
+	page, _ := io.ReadAll(io.LimitReader(resp.Body, geminiWebMaxPageBytes))
+	_ = resp.Body.Close()
+	if resp.StatusCode != http.StatusOK {
+		return s.sendErrorAndEnd(c, fmt.Sprintf("Gemini web returned %d", resp.StatusCode))
+	}
+	sess, err := parseGeminiWebSession(page)
+	if err != nil {
+		return s.sendErrorAndEnd(c, err.Error())
+	}
+
+	webModel := ""
+	if mapped := account.GetMappedModel(testModelID); mapped != testModelID {
+		webModel = mapped
+	}
+	req, err = newGeminiWebGenerateRequest(ctx, account, sess, "hi", nil, webModel)
+	if err != nil {
+		return s.sendErrorAndEnd(c, fmt.Sprintf("Failed to build request: %s", err.Error()))
+	}
+	resp, err = s.httpUpstream.Do(req, proxyURL, account.ID, account.Concurrency)
+	if err != nil {
+		return s.sendErrorAndEnd(c, fmt.Sprintf("Request failed: %s", sanitizeUpstreamErrorMessage(err.Error())))
+	}
+	defer func() { _ = resp.Body.Close() }()
+	if resp.StatusCode != http.StatusOK {
+		body, _ := io.ReadAll(io.LimitReader(resp.Body, 2<<20))
+		return s.sendErrorAndEnd(c, fmt.Sprintf("API returned %d: %s", resp.StatusCode, truncateString(string(body), 500)))
+	}
+
+	st, err := readGeminiWebEvents(resp.Body, defaultMaxLineSize, func(delta string) bool {
+		s.sendEvent(c, TestEvent{Type: "content", Text: delta})
+		return true
+	})
+	if st.conversationID != "" {
+		// 测试对话同样不保留在网页端历史中
+		if delReq, delErr := newGeminiWebDeleteRequest(ctx, account, sess, st.conversationID); delErr == nil {
+			if delResp, delErr := s.httpUpstream.Do(delReq, proxyURL, account.ID, account.Concurrency); delErr == nil {
+				_ = delResp.Body.Close()
+			}
+		}
+	}
+	if err != nil {
+		return s.sendErrorAndEnd(c, err.Error())
+	}
+	s.sendEvent(c, TestEvent{Type: "test_complete", Success: true})
+	return nil
+}
+
 type soraProbeStep struct {
 	Name       string `json:"name"`
 	Status     string `json:"status"`
@@ -0,0 +1,65 @@
+//go:build unit
+
+package service
+
+import (
+	"net/http"
+	"testing"
+
+	"github.com/Wei-Shaw/sub2api/internal/pkg/tlsfingerprint"
+	"github.com/stretchr/testify/require"
+)
+
+func TestAccountTLSFingerprint(t *testing.T) {
+	tests := []struct {
+		name            string
+		account         Account
+		expectedEnabled bool
+		expectedProfile string
+	}{
+		{
+			name:            "anthropic oauth uses rotation",
+			account:         Account{Platform: PlatformAnthropic, Type: AccountTypeOAuth, Extra: map[string]any{"enable_tls_fingerprint": true}},
+			expectedEnabled: true,
+		},
+		{
+			name:            "web session defaults to browser profile",
+			account:         Account{Platform: PlatformOpenAI, Type: AccountTypeWebSession, Extra: map[string]any{"enable_tls_fingerprint": true}},
+			expectedEnabled: true,
+			expectedProfile: defaultWebSessionTLSProfile,
+		},
+		{
+			name:            "web session switch off",
+			account:         Account{Platform: PlatformOpenAI, Type: AccountTypeWebSession, Extra: map[string]any{"enable_tls_fingerprint": false, "tls_fingerprint_profile": "firefox"}},
+			expectedProfile: "firefox",
+		},
+		{
+			name:            "explicit profile enables other account types",
+			account:         Account{Platform: PlatformOpenAI, Type: AccountTypeAPIKey, Extra: map[string]any{"tls_fingerprint_profile": "safari"}},
+			expectedEnabled: true,
+			expectedProfile: "safari",
+		},
+		{
+			name:    "apikey without profile",
+			account: Account{Platform: PlatformAnthropic, Type: AccountTypeAPIKey, Extra: map[string]any{"enable_tls_fingerprint": true}},
+		},
+	}
+
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			require.Equal(t, tt.expectedEnabled, tt.account.IsTLSFingerprintEnabled())
+			require.Equal(t, tt.expectedProfile, tt.account.GetTLSFingerprintProfile())
+		})
+	}
+}
+
+func TestWithAccountTLSProfile(t *testing.T) {
+	req, err := http.NewRequest(http.MethodGet, "https://chatgpt.com/backend-api/me", nil)
+	require.NoError(t, err)
+
+	web := &Account{Type: AccountTypeWebSession}
+	require.Equal(t, defaultWebSessionTLSProfile, tlsfingerprint.ProfileNameFromContext(withAccountTLSProfile(req, web).Context()))
+
+	oauth := &Account{Platform: PlatformAnthropic, Type: AccountTypeOAuth}
+	require.Same(t, req, withAccountTLSProfile(req, oauth))
+}
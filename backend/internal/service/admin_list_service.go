@@ -0,0 +1,200 @@
+package service
+
+import (
+	"context"
+	"errors"
+	"fmt"
+	"strconv"
+	"time"
+
+	infraerrors "github.com/Wei-Shaw/sub2api/internal/pkg/errors"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/pagination"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/usagestats"
+)
+
+var (
+	ErrListInvalidCursor = infraerrors.BadRequest("INVALID_CURSOR", "cursor is invalid or does not match the sort options")
+	ErrListInvalidSort   = infraerrors.BadRequest("INVALID_SORT", "unsupported sort field")
+)
+
+// 各列表支持的排序字段（ID 始终作为第二排序键）
+var (
+	accountListSortFields  = []string{"id", "name", "priority", "created_at"}
+	apiKeyListSortFields   = []string{"id", "name", "created_at"}
+	usageLogListSortFields = []string{"id", "created_at"}
+)
+
+// AccountListFilter 管理后台账号列表过滤条件
+type AccountListFilter struct {
+	Platform    string
+	Type        string
+	Status      string
+	Search      string // 按名称模糊匹配
+	GroupID     int64
+	CreatedFrom *time.Time
+	CreatedTo   *time.Time
+}
+
+// APIKeyListFilter 管理后台 API Key 列表过滤条件
+type APIKeyListFilter struct {
+	UserID      int64
+	GroupID     int64
+	Status      string
+	Search      string // 按名称模糊匹配或按 Key 前缀匹配
+	CreatedFrom *time.Time
+	CreatedTo   *time.Time
+}
+
+// CursorQuery 传给仓储层的游标查询（排序字段已校验）
+type CursorQuery struct {
+	SortBy string
+	Desc   bool
+	// After 上一页最后一条记录；nil 表示第一页
+	After *pagination.Cursor
+	// Limit 仓储层需返回的最大条数（比页大小多 1，用于判断是否还有下一页）
+	Limit int
+}
+
+// AdminListRepository 管理后台列表的游标分页查询
+type AdminListRepository interface {
+	ListAccountsByCursor(ctx context.Context, filter AccountListFilter, q CursorQuery) ([]Account, error)
+	ListAPIKeysByCursor(ctx context.Context, filter APIKeyListFilter, q CursorQuery) ([]APIKey, error)
+	ListUsageLogsByCursor(ctx context.Context, filter usagestats.UsageLogFilters, q CursorQuery) ([]UsageLog, error)
+}
+
+// AdminListService 管理后台列表（游标分页、过滤、排序）
+// 与 offset 分页不同，游标分页不计算总数，翻页时结果不会因新增/删除记录而错位。
+type AdminListService struct {
+	repo AdminListRepository
+}
+
+// NewAdminListService 创建管理后台列表服务
+func NewAdminListService(repo AdminListRepository) *AdminListService {
+	return &AdminListService{repo: repo}
+}
+
+// ListAccounts 游标分页列出账号
+func (s *AdminListService) ListAccounts(ctx context.Context, filter AccountListFilter, params pagination.CursorParams) ([]Account, *pagination.CursorResult, error) {
+	q, err := buildCursorQuery(params, accountListSortFields)
+	if err != nil {
+		return nil, nil, err
+	}
+	items, err := s.repo.ListAccountsByCursor(ctx, filter, q)
+	if err != nil {
+		return nil, nil, fmt.Errorf("list accounts: %w", err)
+	}
+	items, result := finishCursorPage(items, q, params, func(a *Account) (string, int64) {
+		switch q.SortBy {
+		case "name":
+			return a.Name, a.ID
+		case "priority":
+			return strconv.Itoa(a.Priority), a.ID
+		case "created_at":
+			return formatCursorTime(a.CreatedAt), a.ID
+		}
+		return strconv.FormatInt(a.ID, 10), a.ID
+	})
+	return items, result, nil
+}
+
+// ListAPIKeys 游标分页列出 API Key
+func (s *AdminListService) ListAPIKeys(ctx context.Context, filter APIKeyListFilter, params pagination.CursorParams) ([]APIKey, *pagination.CursorResult, error) {
+	q, err := buildCursorQuery(params, apiKeyListSortFields)
+	if err != nil {
+		return nil, nil, err
+	}
+	items, err := s.repo.ListAPIKeysByCursor(ctx, filter, q)
+	if err != nil {
+		return nil, nil, fmt.Errorf("list api keys: %w", err)
+	}
+	items, result := finishCursorPage(items, q, params, func(k *APIKey) (string, int64) {
+		switch q.SortBy {
+		case "name":
+			return k.Name, k.ID
+		case "created_at":
+			return formatCursorTime(k.CreatedAt), k.ID
+		}
+		return strconv.FormatInt(k.ID, 10), k.ID
+	})
+	return items, result, nil
+}
+
+// ListUsageLogs 游标分页列出使用记录
+func (s *AdminListService) ListUsageLogs(ctx context.Context, filter usagestats.UsageLogFilters, params pagination.CursorParams) ([]UsageLog, *pagination.CursorResult, error) {
+	q, err := buildCursorQuery(params, usageLogListSortFields)
+	if err != nil {
+		return nil, nil, err
+	}
+	items, err := s.repo.ListUsageLogsByCursor(ctx, filter, q)
+	if err != nil {
+		return nil, nil, fmt.Errorf("list usage logs: %w", err)
+	}
+	items, result := finishCursorPage(items, q, params, func(l *UsageLog) (string, int64) {
+		if q.SortBy == "created_at" {
+			return formatCursorTime(l.CreatedAt), l.ID
+		}
+		return strconv.FormatInt(l.ID, 10), l.ID
+	})
+	return items, result, nil
+}
+
+// buildCursorQuery 校验排序字段并解码游标；未指定排序字段时按 ID 倒序
+func buildCursorQuery(params pagination.CursorParams, sortFields []string) (CursorQuery, error) {
+	sortBy := params.SortBy
+	if sortBy == "" {
+		sortBy = "id"
+	}
+	supported := false
+	for _, f := range sortFields {
+		if f == sortBy {
+			supported = true
+			break
+		}
+	}
+	if !supported {
+		return CursorQuery{}, ErrListInvalidSort
+	}
+
+	after, err := pagination.DecodeCursor(params.Cursor)
+	if err != nil {
+		if errors.Is(err, pagination.ErrInvalidCursor) {
+			return CursorQuery{}, ErrListInvalidCursor
+		}
+		return CursorQuery{}, err
+	}
+	// 游标必须与当前排序参数一致，否则翻页结果无意义
+	if after != nil && (after.SortBy != sortBy || after.SortOrder != params.NormalizedOrder()) {
+		return CursorQuery{}, ErrListInvalidCursor
+	}
+
+	return CursorQuery{
+		SortBy: sortBy,
+		Desc:   params.Descending(),
+		After:  after,
+		Limit:  params.PageLimit() + 1,
+	}, nil
+}
+
+// finishCursorPage 截断多取的一条记录，并基于本页最后一条记录生成下一页游标
+func finishCursorPage[T any](items []T, q CursorQuery, params pagination.CursorParams, key func(*T) (string, int64)) ([]T, *pagination.CursorResult) {
+	result := &pagination.CursorResult{}
+	pageSize := q.Limit - 1
+	if len(items) <= pageSize {
+		return items, result
+	}
+
+	items = items[:pageSize]
+	value, id := key(&items[len(items)-1])
+	result.HasMore = true
+	result.NextCursor = pagination.EncodeCursor(pagination.Cursor{
+		SortBy:    q.SortBy,
+		SortOrder: params.NormalizedOrder(),
+		Value:     value,
+		ID:        id,
+	})
+	return items, result
+}
+
+func formatCursorTime(t time.Time) string {
+	return t.UTC().Format(time.RFC3339Nano)
+}
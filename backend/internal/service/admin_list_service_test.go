@@ -0,0 +1,113 @@
+//go:build unit
+
+package service
+
+import (
+	"context"
+	"testing"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/pkg/pagination"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/usagestats"
+	"github.com/stretchr/testify/require"
+)
+
+type adminListRepoStub struct {
+	accounts []Account
+	lastQ    CursorQuery
+}
+
+func (r *adminListRepoStub) ListAccountsByCursor(ctx context.Context, filter AccountListFilter, q CursorQuery) ([]Account, error) {
+	r.lastQ = q
+	out := make([]Account, 0, q.Limit)
+	for _, a := range r.accounts {
+		if q.After != nil && a.ID >= q.After.ID {
+			continue
+		}
+		out = append(out, a)
+		if len(out) == q.Limit {
+			break
+		}
+	}
+	return out, nil
+}
+
+func (r *adminListRepoStub) ListAPIKeysByCursor(ctx context.Context, filter APIKeyListFilter, q CursorQuery) ([]APIKey, error) {
+	r.lastQ = q
+	return nil, nil
+}
+
+func (r *adminListRepoStub) ListUsageLogsByCursor(ctx context.Context, filter usagestats.UsageLogFilters, q CursorQuery) ([]UsageLog, error) {
+	r.lastQ = q
+	return []UsageLog{{ID: 9, CreatedAt: time.Date(2026, 1, 2, 3, 4, 5, 600, time.UTC)}}, nil
+}
+
+func TestAdminListService_AccountsWalkAllPages(t *testing.T) {
+	repo := &adminListRepoStub{}
+	for id := int64(5); id >= 1; id-- {
+		repo.accounts = append(repo.accounts, Account{ID: id})
+	}
+	svc := NewAdminListService(repo)
+
+	params := pagination.CursorParams{Limit: 2}
+	var seen []int64
+	for pageNo := 0; pageNo < 10; pageNo++ {
+		items, result, err := svc.ListAccounts(context.Background(), AccountListFilter{}, params)
+		require.NoError(t, err)
+		for _, a := range items {
+			seen = append(seen, a.ID)
+		}
+		if !result.HasMore {
+			require.Empty(t, result.NextCursor)
+			break
+		}
+		params.Cursor = result.NextCursor
+	}
+	require.Equal(t, []int64{5, 4, 3, 2, 1}, seen)
+	require.Equal(t, "id", repo.lastQ.SortBy)
+	require.True(t, repo.lastQ.Desc)
+	require.Equal(t, 3, repo.lastQ.Limit)
+}
+
+func TestAdminListService_RejectsBadSortAndCursor(t *testing.T) {
+	svc := NewAdminListService(&adminListRepoStub{})
+
+	_, _, err := svc.ListAccounts(context.Background(), AccountListFilter{}, pagination.CursorParams{SortBy: "credentials"})
+	require.ErrorIs(t, err, ErrListInvalidSort)
+
+	_, _, err = svc.ListAccounts(context.Background(), AccountListFilter{}, pagination.CursorParams{Cursor: "not-a-cursor"})
+	require.ErrorIs(t, err, ErrListInvalidCursor)
+
+	// 游标与排序参数不一致
+	cursor := pagination.EncodeCursor(pagination.Cursor{SortBy: "name", SortOrder: "asc", Value: "a", ID: 1})
+	_, _, err = svc.ListAccounts(context.Background(), AccountListFilter{}, pagination.CursorParams{Cursor: cursor, SortBy: "name"})
+	require.ErrorIs(t, err, ErrListInvalidCursor)
+}
+
+func TestAdminListService_CursorCarriesSortValue(t *testing.T) {
+	repo := &adminListRepoStub{}
+	svc := NewAdminListService(repo)
+
+	params := pagination.CursorParams{Limit: 1, SortBy: "created_at", SortOrder: "asc"}
+	// 仓储返回 1 条（不多于页大小），没有下一页
+	_, result, err := svc.ListUsageLogs(context.Background(), usagestats.UsageLogFilters{}, params)
+	require.NoError(t, err)
+	require.False(t, result.HasMore)
+	require.False(t, repo.lastQ.Desc)
+
+	items := []UsageLog{{ID: 9, CreatedAt: time.Date(2026, 1, 2, 3, 4, 5, 600, time.UTC)}, {ID: 8}}
+	q, err := buildCursorQuery(params, usageLogListSortFields)
+	require.NoError(t, err)
+	page, result := finishCursorPage(items, q, params, func(l *UsageLog) (string, int64) {
+		return formatCursorTime(l.CreatedAt), l.ID
+	})
+	require.Len(t, page, 1)
+	require.True(t, result.HasMore)
+
+	decoded, err := pagination.DecodeCursor(result.NextCursor)
+	require.NoError(t, err)
+	require.Equal(t, "created_at", decoded.SortBy)
+	require.Equal(t, "asc", decoded.SortOrder)
+	require.Equal(t, int64(9), decoded.ID)
+	require.Equal(t, "2026-01-02T03:04:05.0000006Z", decoded.Value)
+}
@@ -497,9 +497,9 @@ func (s *adminServiceImpl) UpdateUser(ctx context.Context, id int64, input *Upda
 		return nil, err
 	}
 
-	// Protect admin users: cannot disable admin accounts
-	if user.Role == "admin" && input.Status == "disabled" {
-		return nil, errors.New("cannot disable admin user")
+	// Protect admin users: staff accounts are managed via AdminUserService
+	if user.IsStaff() && (input.Status == "disabled" || input.Password != "") {
+		return nil, errors.New("cannot disable or reset password of admin user, use admin user management instead")
 	}
 
 	oldConcurrency := user.Concurrency
@@ -581,7 +581,7 @@ func (s *adminServiceImpl) DeleteUser(ctx context.Context, id int64) error {
 	if err != nil {
 		return err
 	}
-	if user.Role == "admin" {
+	if user.IsStaff() {
 		return errors.New("cannot delete admin user")
 	}
 	if err := s.userRepo.Delete(ctx, id); err != nil {
@@ -0,0 +1,414 @@
+package service
+
+import (
+	"context"
+	"fmt"
+	"net/http"
+	"slices"
+	"strings"
+	"time"
+	"unicode"
+	"unicode/utf8"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	infraerrors "github.com/Wei-Shaw/sub2api/internal/pkg/errors"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/logger"
+)
+
+var (
+	ErrAdminRoleInvalid            = infraerrors.BadRequest("ADMIN_ROLE_INVALID", "role must be one of admin, billing, accounts, auditor")
+	ErrAdminUserNotFound           = infraerrors.NotFound("ADMIN_USER_NOT_FOUND", "admin user not found")
+	ErrAdminLastSuperuser          = infraerrors.Conflict("ADMIN_LAST_SUPERUSER", "at least one active superuser must remain")
+	ErrAdminSelfModify             = infraerrors.BadRequest("ADMIN_SELF_MODIFY", "cannot change your own role, status or password here")
+	ErrAdminPasswordPolicy         = infraerrors.BadRequest("PASSWORD_POLICY_VIOLATION", "password does not meet the password policy")
+	ErrAdminPasswordReused         = infraerrors.BadRequest("PASSWORD_REUSED", "new password must differ from the current password")
+	ErrAdminPasswordChangeRequired = infraerrors.Forbidden("PASSWORD_CHANGE_REQUIRED", "password must be changed before accessing the admin console")
+	ErrAdminPasswordExpired        = infraerrors.Forbidden("PASSWORD_EXPIRED", "password has expired and must be changed before accessing the admin console")
+)
+
+// bcrypt 只使用密码的前 72 字节
+const adminPasswordMaxBytes = 72
+
+// AdminPermission 后台接口所需的权限
+type AdminPermission string
+
+const (
+	AdminPermRead      AdminPermission = "read"      // 查看任意后台数据
+	AdminPermBilling   AdminPermission = "billing"   // 余额、卡密、优惠码、订阅、支付与账单的写操作
+	AdminPermAccounts  AdminPermission = "accounts"  // 上游账号、分组、代理与 OAuth 的写操作
+	AdminPermSuperuser AdminPermission = "superuser" // 系统设置、后台账号等其余所有写操作
+)
+
+var adminRolePermissions = map[string][]AdminPermission{
+	RoleAdmin:    {AdminPermRead, AdminPermBilling, AdminPermAccounts, AdminPermSuperuser},
+	RoleBilling:  {AdminPermRead, AdminPermBilling},
+	RoleAccounts: {AdminPermRead, AdminPermAccounts},
+	RoleAuditor:  {AdminPermRead},
+}
+
+// IsAdminRole 是否为可登录管理后台的角色
+func IsAdminRole(role string) bool {
+	_, ok := adminRolePermissions[role]
+	return ok
+}
+
+// AdminRoleAllows 判断后台角色是否拥有指定权限
+func AdminRoleAllows(role string, perm AdminPermission) bool {
+	return slices.Contains(adminRolePermissions[role], perm)
+}
+
+// AdminUser 后台账号列表项
+type AdminUser struct {
+	ID                 int64      `json:"id"`
+	Email              string     `json:"email"`
+	Username           string     `json:"username"`
+	Role               string     `json:"role"`
+	Status             string     `json:"status"`
+	TotpEnabled        bool       `json:"totp_enabled"`
+	MustChangePassword bool       `json:"must_change_password"`
+	PasswordChangedAt  *time.Time `json:"password_changed_at,omitempty"`
+	PasswordExpiresAt  *time.Time `json:"password_expires_at,omitempty"`
+	CreatedAt          time.Time  `json:"created_at"`
+}
+
+// AdminCredentialState 后台账号的密码状态
+type AdminCredentialState struct {
+	UserID             int64
+	MustChangePassword bool
+	PasswordChangedAt  time.Time
+}
+
+// AdminUserRepository 后台账号查询与密码状态存储；角色保存在 users.role
+type AdminUserRepository interface {
+	ListStaff(ctx context.Context, roles []string) ([]AdminUser, error)
+	CountActiveByRole(ctx context.Context, role string) (int64, error)
+	// GetCredentialState 不存在时返回 nil
+	GetCredentialState(ctx context.Context, userID int64) (*AdminCredentialState, error)
+	SaveCredentialState(ctx context.Context, state *AdminCredentialState) error
+}
+
+// CreateAdminUserInput 创建后台账号的参数；Password 为临时密码，首次使用前必须修改
+type CreateAdminUserInput struct {
+	Email    string
+	Username string
+	Password string
+	Role     string
+}
+
+// UpdateAdminUserInput 修改后台账号的参数，nil 字段保持不变
+type UpdateAdminUserInput struct {
+	Username *string
+	Role     *string
+	Status   *string
+}
+
+// AdminUserService 管理后台账号：细分角色、密码策略与强制改密。
+// 普通用户仍由 AdminService 管理；后台账号只能通过本服务创建和调整角色。
+type AdminUserService struct {
+	repo                 AdminUserRepository
+	userRepo             UserRepository
+	authCacheInvalidator APIKeyAuthCacheInvalidator
+	notifications        *NotificationService
+	cfg                  *config.Config
+	now                  func() time.Time
+}
+
+// NewAdminUserService 创建后台账号服务
+func NewAdminUserService(
+	repo AdminUserRepository,
+	userRepo UserRepository,
+	authCacheInvalidator APIKeyAuthCacheInvalidator,
+	notifications *NotificationService,
+	cfg *config.Config,
+) *AdminUserService {
+	return &AdminUserService{
+		repo:                 repo,
+		userRepo:             userRepo,
+		authCacheInvalidator: authCacheInvalidator,
+		notifications:        notifications,
+		cfg:                  cfg,
+		now:                  time.Now,
+	}
+}
+
+// List 列出所有后台账号
+func (s *AdminUserService) List(ctx context.Context) ([]AdminUser, error) {
+	items, err := s.repo.ListStaff(ctx, []string{RoleAdmin, RoleBilling, RoleAccounts, RoleAuditor})
+	if err != nil {
+		return nil, fmt.Errorf("list admin users: %w", err)
+	}
+	if maxAge := s.passwordMaxAge(); maxAge > 0 {
+		for i := range items {
+			if items[i].PasswordChangedAt != nil {
+				expiresAt := items[i].PasswordChangedAt.Add(maxAge)
+				items[i].PasswordExpiresAt = &expiresAt
+			}
+		}
+	}
+	return items, nil
+}
+
+// Create 创建后台账号，账号需在首次进入管理后台前修改临时密码
+func (s *AdminUserService) Create(ctx context.Context, input *CreateAdminUserInput) (*User, error) {
+	if !IsAdminRole(input.Role) {
+		return nil, ErrAdminRoleInvalid
+	}
+	if err := s.ValidatePassword(input.Password); err != nil {
+		return nil, err
+	}
+	exists, err := s.userRepo.ExistsByEmail(ctx, input.Email)
+	if err != nil {
+		return nil, fmt.Errorf("check email exists: %w", err)
+	}
+	if exists {
+		return nil, ErrEmailExists
+	}
+
+	user := &User{
+		Email:       input.Email,
+		Username:    input.Username,
+		Role:        input.Role,
+		Concurrency: s.cfg.Default.UserConcurrency,
+		Status:      StatusActive,
+	}
+	if err := user.SetPassword(input.Password); err != nil {
+		return nil, fmt.Errorf("set password: %w", err)
+	}
+	if err := s.userRepo.Create(ctx, user); err != nil {
+		return nil, fmt.Errorf("create admin user: %w", err)
+	}
+	if err := s.repo.SaveCredentialState(ctx, &AdminCredentialState{
+		UserID:             user.ID,
+		MustChangePassword: true,
+		PasswordChangedAt:  s.now(),
+	}); err != nil {
+		return nil, fmt.Errorf("save credential state: %w", err)
+	}
+	logger.LegacyPrintf("service.admin_user", "[AdminUser] AUDIT created admin user id=%d role=%s", user.ID, user.Role)
+	// 邀请邮件不包含临时密码，由创建者另行告知
+	if err := s.notifications.Notify(ctx, Notification{
+		Kind:   NotificationKindAdminInvite,
+		UserID: user.ID,
+		Email:  user.Email,
+		Data:   map[string]any{"Role": user.Role},
+	}); err != nil {
+		logger.LegacyPrintf("service.admin_user", "[AdminUser] send invitation to user %d failed: %v", user.ID, err)
+	}
+	return user, nil
+}
+
+// Update 修改后台账号的用户名、角色或状态；不能修改自己的角色和状态
+func (s *AdminUserService) Update(ctx context.Context, actorID, id int64, input *UpdateAdminUserInput) (*User, error) {
+	user, err := s.getStaff(ctx, id)
+	if err != nil {
+		return nil, err
+	}
+	role, status := user.Role, user.Status
+	if input.Role != nil {
+		role = *input.Role
+	}
+	if input.Status != nil {
+		status = *input.Status
+	}
+	if !IsAdminRole(role) {
+		return nil, ErrAdminRoleInvalid
+	}
+	if status != StatusActive && status != StatusDisabled {
+		return nil, infraerrors.BadRequest("INVALID_STATUS", "status must be active or disabled")
+	}
+	if (role != user.Role || status != user.Status) && actorID == user.ID {
+		return nil, ErrAdminSelfModify
+	}
+	if err := s.ensureSuperuserRemains(ctx, user, role, status); err != nil {
+		return nil, err
+	}
+
+	oldRole, oldStatus := user.Role, user.Status
+	user.Role, user.Status = role, status
+	if input.Username != nil {
+		user.Username = *input.Username
+	}
+	if err := s.userRepo.Update(ctx, user); err != nil {
+		return nil, fmt.Errorf("update admin user: %w", err)
+	}
+	if oldStatus != user.Status && s.authCacheInvalidator != nil {
+		s.authCacheInvalidator.InvalidateAuthCacheByUserID(ctx, user.ID)
+	}
+	if oldRole != user.Role || oldStatus != user.Status {
+		logger.LegacyPrintf("service.admin_user", "[AdminUser] AUDIT actor=%d updated admin user id=%d role=%s->%s status=%s->%s",
+			actorID, user.ID, oldRole, user.Role, oldStatus, user.Status)
+	}
+	return user, nil
+}
+
+// ResetPassword 为其他后台账号设置临时密码：旧登录态全部失效，下次进入后台前必须修改
+func (s *AdminUserService) ResetPassword(ctx context.Context, actorID, id int64, password string) error {
+	if actorID == id {
+		return ErrAdminSelfModify
+	}
+	user, err := s.getStaff(ctx, id)
+	if err != nil {
+		return err
+	}
+	if err := s.ValidatePassword(password); err != nil {
+		return err
+	}
+	if err := user.SetPassword(password); err != nil {
+		return fmt.Errorf("set password: %w", err)
+	}
+	user.TokenVersion++
+	if err := s.userRepo.Update(ctx, user); err != nil {
+		return fmt.Errorf("update admin user: %w", err)
+	}
+	if err := s.repo.SaveCredentialState(ctx, &AdminCredentialState{
+		UserID:             user.ID,
+		MustChangePassword: true,
+		PasswordChangedAt:  s.now(),
+	}); err != nil {
+		return fmt.Errorf("save credential state: %w", err)
+	}
+	logger.LegacyPrintf("service.admin_user", "[AdminUser] AUDIT actor=%d reset password of admin user id=%d", actorID, user.ID)
+	return nil
+}
+
+// Revoke 收回后台权限，账号降为普通用户（保留余额与 API Key）
+func (s *AdminUserService) Revoke(ctx context.Context, actorID, id int64) error {
+	if actorID == id {
+		return ErrAdminSelfModify
+	}
+	user, err := s.getStaff(ctx, id)
+	if err != nil {
+		return err
+	}
+	if err := s.ensureSuperuserRemains(ctx, user, RoleUser, user.Status); err != nil {
+		return err
+	}
+	oldRole := user.Role
+	user.Role = RoleUser
+	if err := s.userRepo.Update(ctx, user); err != nil {
+		return fmt.Errorf("update admin user: %w", err)
+	}
+	logger.LegacyPrintf("service.admin_user", "[AdminUser] AUDIT actor=%d revoked admin role %s from user id=%d", actorID, oldRole, user.ID)
+	return nil
+}
+
+// ValidatePassword 按 admin_users 配置校验后台账号密码强度
+func (s *AdminUserService) ValidatePassword(password string) error {
+	policy := s.cfg.AdminUsers
+	var hasUpper, hasLower, hasDigit, hasSymbol bool
+	for _, r := range password {
+		switch {
+		case unicode.IsUpper(r):
+			hasUpper = true
+		case unicode.IsLower(r):
+			hasLower = true
+		case unicode.IsDigit(r):
+			hasDigit = true
+		case unicode.IsPunct(r) || unicode.IsSymbol(r):
+			hasSymbol = true
+		}
+	}
+
+	var problems []string
+	if utf8.RuneCountInString(password) < policy.PasswordMinLength {
+		problems = append(problems, fmt.Sprintf("at least %d characters", policy.PasswordMinLength))
+	}
+	if len(password) > adminPasswordMaxBytes {
+		problems = append(problems, fmt.Sprintf("at most %d bytes", adminPasswordMaxBytes))
+	}
+	if policy.PasswordRequireUpper && !hasUpper {
+		problems = append(problems, "an uppercase letter")
+	}
+	if policy.PasswordRequireLower && !hasLower {
+		problems = append(problems, "a lowercase letter")
+	}
+	if policy.PasswordRequireDigit && !hasDigit {
+		problems = append(problems, "a digit")
+	}
+	if policy.PasswordRequireSymbol && !hasSymbol {
+		problems = append(problems, "a symbol")
+	}
+	if len(problems) > 0 {
+		return infraerrors.Newf(http.StatusBadRequest, ErrAdminPasswordPolicy.Reason,
+			"password must contain %s", strings.Join(problems, ", "))
+	}
+	return nil
+}
+
+// ValidateNewPassword 后台账号自助修改密码时的校验：满足密码策略且不能与当前密码相同
+func (s *AdminUserService) ValidateNewPassword(user *User, password string) error {
+	if err := s.ValidatePassword(password); err != nil {
+		return err
+	}
+	if user.CheckPassword(password) {
+		return ErrAdminPasswordReused
+	}
+	return nil
+}
+
+// RecordPasswordChange 记录后台账号已修改密码，清除强制改密标记并重新计算有效期
+func (s *AdminUserService) RecordPasswordChange(ctx context.Context, userID int64) error {
+	return s.repo.SaveCredentialState(ctx, &AdminCredentialState{
+		UserID:            userID,
+		PasswordChangedAt: s.now(),
+	})
+}
+
+// CheckPasswordRotation 进入管理后台前检查是否需要修改密码（临时密码或已过有效期）。
+// 修改密码的入口 /api/v1/user/password 仅需 JWT 认证，不受此限制。
+func (s *AdminUserService) CheckPasswordRotation(ctx context.Context, user *User) error {
+	if s == nil || user == nil {
+		return nil
+	}
+	state, err := s.repo.GetCredentialState(ctx, user.ID)
+	if err != nil {
+		return fmt.Errorf("get credential state: %w", err)
+	}
+	if state == nil {
+		// 启用密码轮换前已存在的后台账号：从现在开始计算有效期
+		return s.repo.SaveCredentialState(ctx, &AdminCredentialState{UserID: user.ID, PasswordChangedAt: s.now()})
+	}
+	if state.MustChangePassword {
+		return ErrAdminPasswordChangeRequired
+	}
+	if maxAge := s.passwordMaxAge(); maxAge > 0 && s.now().Sub(state.PasswordChangedAt) > maxAge {
+		return ErrAdminPasswordExpired
+	}
+	return nil
+}
+
+func (s *AdminUserService) passwordMaxAge() time.Duration {
+	return time.Duration(s.cfg.AdminUsers.PasswordMaxAgeDays) * 24 * time.Hour
+}
+
+func (s *AdminUserService) getStaff(ctx context.Context, id int64) (*User, error) {
+	user, err := s.userRepo.GetByID(ctx, id)
+	if err != nil {
+		if infraerrors.IsNotFound(err) {
+			return nil, ErrAdminUserNotFound
+		}
+		return nil, err
+	}
+	if !user.IsStaff() {
+		return nil, ErrAdminUserNotFound
+	}
+	return user, nil
+}
+
+// ensureSuperuserRemains 降级或禁用超级管理员时，确保至少还有一个启用的超级管理员
+func (s *AdminUserService) ensureSuperuserRemains(ctx context.Context, user *User, newRole, newStatus string) error {
+	if user.Role != RoleAdmin || user.Status != StatusActive {
+		return nil
+	}
+	if newRole == RoleAdmin && newStatus == StatusActive {
+		return nil
+	}
+	count, err := s.repo.CountActiveByRole(ctx, RoleAdmin)
+	if err != nil {
+		return fmt.Errorf("count superusers: %w", err)
+	}
+	if count <= 1 {
+		return ErrAdminLastSuperuser
+	}
+	return nil
+}
@@ -0,0 +1,203 @@
+//go:build unit
+
+package service
+
+import (
+	"context"
+	"testing"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	infraerrors "github.com/Wei-Shaw/sub2api/internal/pkg/errors"
+	"github.com/stretchr/testify/require"
+)
+
+type adminUserRepoStub struct {
+	states       map[int64]*AdminCredentialState
+	activeAdmins int64
+}
+
+func (r *adminUserRepoStub) ListStaff(context.Context, []string) ([]AdminUser, error) {
+	panic("unexpected ListStaff call")
+}
+
+func (r *adminUserRepoStub) CountActiveByRole(_ context.Context, role string) (int64, error) {
+	if role != RoleAdmin {
+		return 0, nil
+	}
+	return r.activeAdmins, nil
+}
+
+func (r *adminUserRepoStub) GetCredentialState(_ context.Context, userID int64) (*AdminCredentialState, error) {
+	return r.states[userID], nil
+}
+
+func (r *adminUserRepoStub) SaveCredentialState(_ context.Context, state *AdminCredentialState) error {
+	clone := *state
+	r.states[state.UserID] = &clone
+	return nil
+}
+
+// staffUserRepoStub 在 userRepoStub 基础上允许 Update
+type staffUserRepoStub struct {
+	userRepoStub
+	updated int
+}
+
+func (s *staffUserRepoStub) Update(context.Context, *User) error {
+	s.updated++
+	return nil
+}
+
+func newAdminUserServiceForTest(user *User) (*AdminUserService, *adminUserRepoStub, *staffUserRepoStub) {
+	cfg := &config.Config{}
+	cfg.Default.UserConcurrency = 3
+	cfg.AdminUsers = config.AdminUsersConfig{
+		PasswordMinLength:    12,
+		PasswordRequireUpper: true,
+		PasswordRequireLower: true,
+		PasswordRequireDigit: true,
+		PasswordMaxAgeDays:   90,
+	}
+	repo := &adminUserRepoStub{states: map[int64]*AdminCredentialState{}, activeAdmins: 1}
+	users := &staffUserRepoStub{userRepoStub: userRepoStub{user: user, nextID: 7}}
+	return NewAdminUserService(repo, users, &authCacheInvalidatorStub{}, nil, cfg), repo, users
+}
+
+func TestAdminRoleAllows(t *testing.T) {
+	require.True(t, AdminRoleAllows(RoleAdmin, AdminPermSuperuser))
+	require.True(t, AdminRoleAllows(RoleBilling, AdminPermBilling))
+	require.False(t, AdminRoleAllows(RoleBilling, AdminPermAccounts))
+	require.True(t, AdminRoleAllows(RoleAccounts, AdminPermAccounts))
+	require.True(t, AdminRoleAllows(RoleAuditor, AdminPermRead))
+	require.False(t, AdminRoleAllows(RoleAuditor, AdminPermBilling))
+	require.False(t, AdminRoleAllows(RoleUser, AdminPermRead))
+	require.False(t, IsAdminRole(RoleUser))
+}
+
+func TestAdminUserService_ValidatePassword(t *testing.T) {
+	svc, _, _ := newAdminUserServiceForTest(nil)
+
+	err := svc.ValidatePassword("Sh0rt")
+	require.ErrorIs(t, err, ErrAdminPasswordPolicy)
+	require.Contains(t, infraerrors.Message(err), "at least 12 characters")
+
+	err = svc.ValidatePassword("alllowercase123")
+	require.ErrorIs(t, err, ErrAdminPasswordPolicy)
+	require.Contains(t, infraerrors.Message(err), "an uppercase letter")
+
+	require.NoError(t, svc.ValidatePassword("Correct-Horse-9"))
+
+	svc.cfg.AdminUsers.PasswordRequireSymbol = true
+	require.ErrorIs(t, svc.ValidatePassword("CorrectHorse99"), ErrAdminPasswordPolicy)
+	require.NoError(t, svc.ValidatePassword("Correct-Horse-9"))
+}
+
+func TestAdminUserService_CreateRequiresPasswordChange(t *testing.T) {
+	svc, repo, users := newAdminUserServiceForTest(nil)
+	ctx := context.Background()
+
+	_, err := svc.Create(ctx, &CreateAdminUserInput{Email: "ops@example.com", Password: "Correct-Horse-9", Role: RoleUser})
+	require.ErrorIs(t, err, ErrAdminRoleInvalid)
+	_, err = svc.Create(ctx, &CreateAdminUserInput{Email: "ops@example.com", Password: "weak", Role: RoleBilling})
+	require.ErrorIs(t, err, ErrAdminPasswordPolicy)
+
+	user, err := svc.Create(ctx, &CreateAdminUserInput{Email: "ops@example.com", Password: "Correct-Horse-9", Role: RoleBilling})
+	require.NoError(t, err)
+	require.Equal(t, int64(7), user.ID)
+	require.Equal(t, RoleBilling, user.Role)
+	require.Equal(t, 3, user.Concurrency)
+	require.True(t, user.CheckPassword("Correct-Horse-9"))
+	require.Len(t, users.created, 1)
+	require.True(t, repo.states[7].MustChangePassword)
+
+	require.ErrorIs(t, svc.CheckPasswordRotation(ctx, user), ErrAdminPasswordChangeRequired)
+
+	users.exists = true
+	_, err = svc.Create(ctx, &CreateAdminUserInput{Email: "ops@example.com", Password: "Correct-Horse-9", Role: RoleBilling})
+	require.ErrorIs(t, err, ErrEmailExists)
+}
+
+func TestAdminUserService_CheckPasswordRotation(t *testing.T) {
+	user := &User{ID: 1, Role: RoleAdmin, Status: StatusActive}
+	svc, repo, _ := newAdminUserServiceForTest(user)
+	now := time.Date(2026, 3, 1, 0, 0, 0, 0, time.UTC)
+	svc.now = func() time.Time { return now }
+	ctx := context.Background()
+
+	// 旧账号首次检查时开始计算有效期
+	require.NoError(t, svc.CheckPasswordRotation(ctx, user))
+	require.Equal(t, now, repo.states[1].PasswordChangedAt)
+
+	now = now.Add(89 * 24 * time.Hour)
+	require.NoError(t, svc.CheckPasswordRotation(ctx, user))
+	now = now.Add(2 * 24 * time.Hour)
+	require.ErrorIs(t, svc.CheckPasswordRotation(ctx, user), ErrAdminPasswordExpired)
+
+	svc.cfg.AdminUsers.PasswordMaxAgeDays = 0
+	require.NoError(t, svc.CheckPasswordRotation(ctx, user))
+
+	var nilService *AdminUserService
+	require.NoError(t, nilService.CheckPasswordRotation(ctx, user))
+}
+
+func TestAdminUserService_UpdateProtectsSuperusers(t *testing.T) {
+	target := &User{ID: 2, Role: RoleAdmin, Status: StatusActive}
+	svc, repo, users := newAdminUserServiceForTest(target)
+	ctx := context.Background()
+	auditor := RoleAuditor
+
+	_, err := svc.Update(ctx, 2, 2, &UpdateAdminUserInput{Role: &auditor})
+	require.ErrorIs(t, err, ErrAdminSelfModify)
+
+	_, err = svc.Update(ctx, 1, 2, &UpdateAdminUserInput{Role: &auditor})
+	require.ErrorIs(t, err, ErrAdminLastSuperuser)
+	require.ErrorIs(t, svc.Revoke(ctx, 1, 2), ErrAdminLastSuperuser)
+
+	repo.activeAdmins = 2
+	updated, err := svc.Update(ctx, 1, 2, &UpdateAdminUserInput{Role: &auditor})
+	require.NoError(t, err)
+	require.Equal(t, RoleAuditor, updated.Role)
+	require.Equal(t, 1, users.updated)
+
+	require.NoError(t, svc.Revoke(ctx, 1, 2))
+	require.Equal(t, RoleUser, target.Role)
+
+	// 普通用户不属于后台账号
+	_, err = svc.Update(ctx, 1, 2, &UpdateAdminUserInput{Role: &auditor})
+	require.ErrorIs(t, err, ErrAdminUserNotFound)
+}
+
+func TestAdminUserService_ResetPassword(t *testing.T) {
+	target := &User{ID: 2, Role: RoleAccounts, Status: StatusActive, TokenVersion: 4}
+	svc, repo, _ := newAdminUserServiceForTest(target)
+	ctx := context.Background()
+
+	require.ErrorIs(t, svc.ResetPassword(ctx, 2, 2, "Correct-Horse-9"), ErrAdminSelfModify)
+	require.ErrorIs(t, svc.ResetPassword(ctx, 1, 2, "weak"), ErrAdminPasswordPolicy)
+
+	require.NoError(t, svc.ResetPassword(ctx, 1, 2, "Correct-Horse-9"))
+	require.Equal(t, int64(5), target.TokenVersion)
+	require.True(t, target.CheckPassword("Correct-Horse-9"))
+	require.True(t, repo.states[2].MustChangePassword)
+}
+
+func TestUserService_ChangePasswordEnforcesStaffPolicy(t *testing.T) {
+	staff := &User{ID: 3, Role: RoleBilling, Status: StatusActive}
+	require.NoError(t, staff.SetPassword("Temporary-Pass-1"))
+	adminUsers, repo, users := newAdminUserServiceForTest(staff)
+	repo.states[3] = &AdminCredentialState{UserID: 3, MustChangePassword: true}
+
+	svc := NewUserService(users, nil, nil)
+	svc.SetAdminUserService(adminUsers)
+	ctx := context.Background()
+
+	err := svc.ChangePassword(ctx, 3, ChangePasswordRequest{CurrentPassword: "Temporary-Pass-1", NewPassword: "Temporary-Pass-1"})
+	require.ErrorIs(t, err, ErrAdminPasswordReused)
+	err = svc.ChangePassword(ctx, 3, ChangePasswordRequest{CurrentPassword: "Temporary-Pass-1", NewPassword: "weak"})
+	require.ErrorIs(t, err, ErrAdminPasswordPolicy)
+
+	require.NoError(t, svc.ChangePassword(ctx, 3, ChangePasswordRequest{CurrentPassword: "Temporary-Pass-1", NewPassword: "Brand-New-Pass-2"}))
+	require.False(t, repo.states[3].MustChangePassword)
+	require.NoError(t, adminUsers.CheckPasswordRotation(ctx, staff))
+}
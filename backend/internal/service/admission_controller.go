@@ -0,0 +1,138 @@
+package service
+
+import (
+	"sync"
+	"time"
+)
+
+const (
+	// admissionThroughputWindow 吞吐统计窗口
+	admissionThroughputWindow = time.Minute
+	// admissionMaxSamples 单个账号保留的完成时间戳上限（超出时丢弃最旧的，吞吐按剩余样本跨度计算）
+	admissionMaxSamples = 4096
+)
+
+// AdmissionController 账号槽位等待队列的准入控制。
+//
+// 根据账号最近一分钟内释放槽位的速率（吞吐）估算新请求的排队时间：
+// 估算值 = (本实例排队数 + 1) / 吞吐。估算值超过上限时直接拒绝，由调用方返回 429 与 Retry-After，
+// 避免请求在队列中堆积直至超时。窗口内没有完成记录时无法估算，直接放行（由等待超时兜底）。
+// 统计仅在本实例内维护。
+type AdmissionController struct {
+	maxWait time.Duration
+	mu      sync.Mutex
+	pools   map[int64]*admissionPool
+	now     func() time.Time
+}
+
+type admissionPool struct {
+	waiting     int
+	completions []time.Time
+}
+
+// AdmissionTicket 已准入的排队请求；对 nil 安全
+type AdmissionTicket struct {
+	ctrl   *AdmissionController
+	poolID int64
+	once   sync.Once
+}
+
+// NewAdmissionController 创建准入控制器，maxWait 为允许的最大估算排队时间
+func NewAdmissionController(maxWait time.Duration) *AdmissionController {
+	return &AdmissionController{
+		maxWait: maxWait,
+		pools:   make(map[int64]*admissionPool),
+		now:     time.Now,
+	}
+}
+
+// Enter 判断请求能否进入账号 poolID 的等待队列。
+// timeout 为本次等待的超时时间（<= 0 表示不限制），与配置上限取较小值作为准入上限。
+// 拒绝时返回 ok=false 与按当前吞吐估算的重试等待时间。
+func (a *AdmissionController) Enter(poolID int64, timeout time.Duration) (ticket *AdmissionTicket, retryAfter time.Duration, ok bool) {
+	if a == nil {
+		return nil, 0, true
+	}
+	bound := a.maxWait
+	if timeout > 0 && timeout < bound {
+		bound = timeout
+	}
+
+	a.mu.Lock()
+	defer a.mu.Unlock()
+
+	pool := a.pools[poolID]
+	if pool == nil {
+		pool = &admissionPool{}
+		a.pools[poolID] = pool
+	}
+	if estimate, known := a.estimateLocked(pool, pool.waiting+1); known && estimate > bound {
+		if pool.waiting == 0 && len(pool.completions) == 0 {
+			delete(a.pools, poolID)
+		}
+		return nil, estimate, false
+	}
+	pool.waiting++
+	return &AdmissionTicket{ctrl: a, poolID: poolID}, 0, true
+}
+
+// Leave 请求离开等待队列（获得槽位、超时或断开），可重复调用
+func (t *AdmissionTicket) Leave() {
+	if t == nil {
+		return
+	}
+	t.once.Do(func() {
+		a := t.ctrl
+		a.mu.Lock()
+		defer a.mu.Unlock()
+		if pool := a.pools[t.poolID]; pool != nil && pool.waiting > 0 {
+			pool.waiting--
+		}
+	})
+}
+
+// RecordCompletion 记录账号释放了一个槽位，用于吞吐估算
+func (a *AdmissionController) RecordCompletion(poolID int64) {
+	if a == nil {
+		return
+	}
+	a.mu.Lock()
+	defer a.mu.Unlock()
+
+	pool := a.pools[poolID]
+	if pool == nil {
+		pool = &admissionPool{}
+		a.pools[poolID] = pool
+	}
+	pool.completions = append(pool.completions, a.now())
+	if len(pool.completions) > admissionMaxSamples {
+		pool.completions = pool.completions[len(pool.completions)-admissionMaxSamples:]
+	}
+	a.pruneLocked(pool)
+}
+
+// estimateLocked 估算排在 position 位的请求需要等待的时间；窗口内无完成记录时 known=false
+func (a *AdmissionController) estimateLocked(pool *admissionPool, position int) (estimate time.Duration, known bool) {
+	a.pruneLocked(pool)
+	n := len(pool.completions)
+	if n == 0 {
+		return 0, false
+	}
+	span := a.now().Sub(pool.completions[0])
+	if span < time.Second {
+		span = time.Second
+	}
+	// 吞吐 = n / span，估算等待 = position / 吞吐
+	return time.Duration(float64(span) * float64(position) / float64(n)), true
+}
+
+func (a *AdmissionController) pruneLocked(pool *admissionPool) {
+	cutoff := a.now().Add(-admissionThroughputWindow)
+	idx := 0
+	for idx < len(pool.completions) && pool.completions[idx].Before(cutoff) {
+		idx++
+	}
+	if idx > 0 {
+		pool.completions = append(pool.completions[:0], pool.completions[idx:]...)
+	}
+}
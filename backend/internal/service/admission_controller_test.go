@@ -0,0 +1,71 @@
+//go:build unit
+
+package service
+
+import (
+	"testing"
+	"time"
+
+	"github.com/stretchr/testify/require"
+)
+
+func TestAdmissionController_EstimateFromThroughput(t *testing.T) {
+	a := NewAdmissionController(3 * time.Second)
+	now := time.Date(2026, 1, 1, 10, 0, 0, 0, time.UTC)
+	a.now = func() time.Time { return now }
+
+	// 无完成记录时无法估算，直接放行
+	ticket, _, ok := a.Enter(1, 0)
+	require.True(t, ok)
+	ticket.Leave()
+	ticket.Leave()
+
+	// 10 秒内完成 10 个请求：吞吐 1 req/s
+	for i := 0; i < 10; i++ {
+		a.RecordCompletion(1)
+		now = now.Add(time.Second)
+	}
+
+	var tickets []*AdmissionTicket
+	for i := 0; i < 3; i++ {
+		ticket, _, ok := a.Enter(1, 0)
+		require.True(t, ok, "position %d", i+1)
+		tickets = append(tickets, ticket)
+	}
+	_, retryAfter, ok := a.Enter(1, 0)
+	require.False(t, ok)
+	require.Equal(t, 4*time.Second, retryAfter)
+
+	// 等待超时比配置上限更小时以超时为准
+	tickets[0].Leave()
+	tickets[1].Leave()
+	_, _, ok = a.Enter(1, 1500*time.Millisecond)
+	require.False(t, ok)
+	_, _, ok = a.Enter(1, 0)
+	require.True(t, ok)
+
+	// 其他账号互不影响
+	_, _, ok = a.Enter(2, 0)
+	require.True(t, ok)
+}
+
+func TestAdmissionController_WindowExpiry(t *testing.T) {
+	a := NewAdmissionController(time.Second)
+	now := time.Date(2026, 1, 1, 10, 0, 0, 0, time.UTC)
+	a.now = func() time.Time { return now }
+
+	a.RecordCompletion(1)
+	now = now.Add(30 * time.Second)
+	_, _, ok := a.Enter(1, 0)
+	require.False(t, ok)
+
+	now = now.Add(admissionThroughputWindow)
+	_, _, ok = a.Enter(1, 0)
+	require.True(t, ok)
+
+	var nilCtrl *AdmissionController
+	ticket, _, ok := nilCtrl.Enter(1, 0)
+	require.True(t, ok)
+	ticket.Leave()
+	nilCtrl.RecordCompletion(1)
+}
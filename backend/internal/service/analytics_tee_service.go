@@ -0,0 +1,141 @@
+package service
+
+import (
+	"context"
+	"encoding/json"
+	"fmt"
+	"sync"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/logger"
+)
+
+// JobTopicAnalyticsTee 请求摘要事件批次的投递主题
+const JobTopicAnalyticsTee = "analytics_tee"
+
+// analyticsTeeEnqueueTimeout 单批事件写入任务队列的超时
+const analyticsTeeEnqueueTimeout = 5 * time.Second
+
+// AnalyticsSink 外部分析系统接收端
+type AnalyticsSink interface {
+	// Send 投递一批事件；batchID 在重试间保持不变，供接收端去重
+	Send(ctx context.Context, endpoint, authToken, batchID string, payload []byte) error
+}
+
+// AnalyticsTeeBatch 一批请求摘要事件（任务载荷与投递请求体）
+type AnalyticsTeeBatch struct {
+	Events []*LiveUsageEvent `json:"events"`
+}
+
+// AnalyticsTeeService 将请求完成事件旁路转发到外部分析系统。
+//
+// 订阅 LiveUsageHub 的请求摘要（不含请求/响应内容），按条数或时间攒批后写入任务队列，
+// 由队列消费者投递到接收端；投递失败按任务队列的至少一次语义重试，超过次数进入死信。
+// 请求路径只做非阻塞的进程内分发，缓冲写满时丢弃事件并计数，不会因接收端变慢而增加延迟。
+type AnalyticsTeeService struct {
+	hub      *LiveUsageHub
+	jobQueue *JobQueueService
+	sink     AnalyticsSink
+	cfg      config.AnalyticsTeeConfig
+
+	sub      *LiveUsageSubscription
+	stopOnce sync.Once
+	wg       sync.WaitGroup
+}
+
+// NewAnalyticsTeeService 创建请求摘要旁路转发服务
+func NewAnalyticsTeeService(hub *LiveUsageHub, jobQueue *JobQueueService, sink AnalyticsSink, cfg *config.Config) *AnalyticsTeeService {
+	s := &AnalyticsTeeService{hub: hub, jobQueue: jobQueue, sink: sink}
+	if cfg != nil {
+		s.cfg = cfg.AnalyticsTee
+	}
+	return s
+}
+
+// Enabled 是否启用旁路转发
+func (s *AnalyticsTeeService) Enabled() bool {
+	return s != nil && s.cfg.Enabled && s.hub != nil && s.sink != nil && s.jobQueue.Enabled()
+}
+
+// Start 注册投递处理器并开始收集事件。
+// 事件收集在每个处理请求的实例上运行；投递由任务队列消费者执行（worker.mode=api 时在 worker 进程中）。
+func (s *AnalyticsTeeService) Start() {
+	if !s.Enabled() {
+		return
+	}
+	s.jobQueue.RegisterHandler(JobTopicAnalyticsTee, s.cfg.Workers, time.Duration(s.cfg.TimeoutSeconds)*time.Second, s.handleJob)
+
+	s.sub = s.hub.Subscribe(LiveUsageFilter{ErrorsOnly: s.cfg.ErrorsOnly, SampleRate: s.cfg.SampleRate}, s.cfg.BufferSize)
+	s.wg.Add(1)
+	go s.collect()
+	logger.LegacyPrintf("service.analytics_tee", "[AnalyticsTee] Started (batch_size=%d flush_interval=%dms)", s.cfg.BatchSize, s.cfg.FlushIntervalMs)
+}
+
+// Stop 停止收集并把缓冲中剩余的事件入队
+func (s *AnalyticsTeeService) Stop() {
+	if s == nil || s.sub == nil {
+		return
+	}
+	s.stopOnce.Do(s.sub.Close)
+	s.wg.Wait()
+}
+
+func (s *AnalyticsTeeService) collect() {
+	defer s.wg.Done()
+	ticker := time.NewTicker(time.Duration(s.cfg.FlushIntervalMs) * time.Millisecond)
+	defer ticker.Stop()
+
+	batch := make([]*LiveUsageEvent, 0, s.cfg.BatchSize)
+	flush := func() {
+		if dropped := s.sub.TakeDropped(); dropped > 0 {
+			logger.LegacyPrintf("service.analytics_tee", "[AnalyticsTee] Buffer full, dropped %d events", dropped)
+		}
+		if len(batch) == 0 {
+			return
+		}
+		s.enqueue(batch)
+		batch = make([]*LiveUsageEvent, 0, s.cfg.BatchSize)
+	}
+
+	for {
+		select {
+		case ev, ok := <-s.sub.Events():
+			if !ok {
+				flush()
+				return
+			}
+			batch = append(batch, ev)
+			if len(batch) >= s.cfg.BatchSize {
+				flush()
+			}
+		case <-ticker.C:
+			flush()
+		}
+	}
+}
+
+func (s *AnalyticsTeeService) enqueue(events []*LiveUsageEvent) {
+	ctx, cancel := context.WithTimeout(context.Background(), analyticsTeeEnqueueTimeout)
+	defer cancel()
+	if _, err := s.jobQueue.Enqueue(ctx, JobTopicAnalyticsTee, &AnalyticsTeeBatch{Events: events}); err != nil {
+		logger.LegacyPrintf("service.analytics_tee", "[AnalyticsTee] Enqueue %d events failed: %v", len(events), err)
+	}
+}
+
+// handleJob 投递一批事件；返回错误时任务由队列重新投递
+func (s *AnalyticsTeeService) handleJob(ctx context.Context, job *QueuedJob) error {
+	var batch AnalyticsTeeBatch
+	if err := json.Unmarshal(job.Payload, &batch); err != nil {
+		// 载荷损坏无法通过重试恢复，直接确认丢弃
+		logger.LegacyPrintf("service.analytics_tee", "[AnalyticsTee] Drop malformed batch %s: %v", job.ID, err)
+		return nil
+	}
+	if len(batch.Events) == 0 {
+		return nil
+	}
+	if err := s.sink.Send(ctx, s.cfg.URL, s.cfg.AuthToken, job.ID, job.Payload); err != nil {
+		return fmt.Errorf("deliver analytics batch (%d events): %w", len(batch.Events), err)
+	}
+	return nil
+}
@@ -0,0 +1,94 @@
+//go:build unit
+
+package service
+
+import (
+	"context"
+	"encoding/json"
+	"errors"
+	"testing"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/stretchr/testify/require"
+)
+
+type analyticsSinkStub struct {
+	batchIDs []string
+	payloads [][]byte
+	err      error
+}
+
+func (s *analyticsSinkStub) Send(ctx context.Context, endpoint, authToken, batchID string, payload []byte) error {
+	if s.err != nil {
+		return s.err
+	}
+	s.batchIDs = append(s.batchIDs, batchID)
+	s.payloads = append(s.payloads, payload)
+	return nil
+}
+
+func newAnalyticsTeeTestService(queue JobQueue, sink AnalyticsSink, hub *LiveUsageHub) *AnalyticsTeeService {
+	cfg := &config.Config{AnalyticsTee: config.AnalyticsTeeConfig{
+		Enabled:         true,
+		URL:             "https://analytics.example.com/ingest",
+		TimeoutSeconds:  5,
+		BatchSize:       2,
+		FlushIntervalMs: 60000,
+		BufferSize:      16,
+		Workers:         1,
+		ErrorsOnly:      true,
+	}}
+	return NewAnalyticsTeeService(hub, newTestJobQueueService(queue), sink, cfg)
+}
+
+func TestAnalyticsTeeService_BatchesEventsIntoJobQueue(t *testing.T) {
+	queue := newJobQueueStub()
+	hub := NewLiveUsageHub()
+	svc := newAnalyticsTeeTestService(queue, &analyticsSinkStub{}, hub)
+	svc.Start()
+
+	hub.Publish(&LiveUsageEvent{RequestID: "a", Model: "claude-sonnet-4", StatusCode: 500})
+	hub.Publish(&LiveUsageEvent{RequestID: "ok", Model: "claude-sonnet-4", StatusCode: 200})
+	hub.Publish(&LiveUsageEvent{RequestID: "b", Model: "claude-sonnet-4", StatusCode: 429})
+	hub.Publish(&LiveUsageEvent{RequestID: "c", Model: "claude-sonnet-4", StatusCode: 502})
+	// Stop 时剩余不足一批的事件也会入队
+	svc.Stop()
+
+	batches := queue.enqueued[JobTopicAnalyticsTee]
+	require.Len(t, batches, 2)
+	var first, second AnalyticsTeeBatch
+	require.NoError(t, json.Unmarshal(batches[0], &first))
+	require.NoError(t, json.Unmarshal(batches[1], &second))
+	require.Len(t, first.Events, 2)
+	require.Equal(t, "a", first.Events[0].RequestID)
+	require.Equal(t, "b", first.Events[1].RequestID)
+	require.Len(t, second.Events, 1)
+	require.Equal(t, "c", second.Events[0].RequestID)
+}
+
+func TestAnalyticsTeeService_HandleJobDeliversPayload(t *testing.T) {
+	sink := &analyticsSinkStub{}
+	svc := newAnalyticsTeeTestService(newJobQueueStub(), sink, NewLiveUsageHub())
+	payload, err := json.Marshal(&AnalyticsTeeBatch{Events: []*LiveUsageEvent{{RequestID: "a", StatusCode: 500}}})
+	require.NoError(t, err)
+
+	require.NoError(t, svc.handleJob(context.Background(), &QueuedJob{ID: "1-0", Payload: payload}))
+	require.Equal(t, []string{"1-0"}, sink.batchIDs)
+	require.Equal(t, payload, sink.payloads[0])
+
+	// 接收端失败时返回错误，由任务队列重试
+	sink.err = errors.New("503")
+	require.Error(t, svc.handleJob(context.Background(), &QueuedJob{ID: "2-0", Payload: payload}))
+
+	// 损坏的载荷直接确认，不反复重试
+	require.NoError(t, svc.handleJob(context.Background(), &QueuedJob{ID: "3-0", Payload: []byte("{")}))
+}
+
+func TestAnalyticsTeeService_DisabledIsNoop(t *testing.T) {
+	hub := NewLiveUsageHub()
+	svc := NewAnalyticsTeeService(hub, newTestJobQueueService(newJobQueueStub()), &analyticsSinkStub{}, &config.Config{})
+	require.False(t, svc.Enabled())
+	svc.Start()
+	require.Zero(t, hub.active.Load())
+	svc.Stop()
+}
@@ -3671,6 +3671,8 @@ func (s *AntigravityGatewayService) handleClaudeStreamingResponse(c *gin.Context
 	}
 
 	cw := newAntigravityClientWriter(c.Writer, flusher, "antigravity claude")
+	keepalive := newSSEKeepalive(s.settingService.cfg)
+	defer keepalive.Stop()
 
 	// 仅发送一次错误事件，避免多次写入导致协议混乱
 	errorEventSent := false
@@ -3720,7 +3722,9 @@ func (s *AntigravityGatewayService) handleClaudeStreamingResponse(c *gin.Context
 					ms := int(time.Since(startTime).Milliseconds())
 					firstTokenMs = &ms
 				}
-				cw.Write(claudeEvents)
+				if cw.Write(claudeEvents) {
+					keepalive.Touch()
+				}
 			}
 
 		case <-intervalCh:
@@ -3735,6 +3739,14 @@ func (s *AntigravityGatewayService) handleClaudeStreamingResponse(c *gin.Context
 			logger.LegacyPrintf("service.antigravity_gateway", "Stream data interval timeout (antigravity)")
 			sendErrorEvent("stream_timeout")
 			return &antigravityStreamResult{usage: convertUsage(nil), firstTokenMs: firstTokenMs}, fmt.Errorf("stream data interval timeout")
+
+		case <-keepalive.C():
+			if cw.Disconnected() || !keepalive.Due() {
+				continue
+			}
+			if cw.Write([]byte(sseKeepalivePing)) {
+				keepalive.Touch()
+			}
 		}
 	}
 }
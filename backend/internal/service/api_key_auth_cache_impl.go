@@ -10,6 +10,7 @@ import (
 	"time"
 
 	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/logger"
 	"github.com/dgraph-io/ristretto"
 )
 
@@ -87,18 +88,58 @@ func (s *APIKeyService) initAuthCache(cfg *config.Config) {
 	s.authCacheL1 = cache
 }
 
+const (
+	authCacheSubscribeRetryInitial = time.Second
+	authCacheSubscribeRetryMax     = 30 * time.Second
+)
+
 // StartAuthCacheInvalidationSubscriber starts the Pub/Sub subscriber for L1 cache invalidation.
 // This should be called after the service is fully initialized.
+//
+// 订阅失败（如启动时 Redis 暂不可用）时在后台退避重试。订阅中断期间其他实例发布的失效消息无法送达，
+// 因此重新订阅成功后清空 L1，避免已吊销/修改的 Key 在 L1 TTL 内仍按旧快照通过认证。
 func (s *APIKeyService) StartAuthCacheInvalidationSubscriber(ctx context.Context) {
 	if s.cache == nil || s.authCacheL1 == nil {
 		return
 	}
-	if err := s.cache.SubscribeAuthCacheInvalidation(ctx, func(cacheKey string) {
+	if err := s.subscribeAuthCacheInvalidation(ctx); err != nil {
+		logger.LegacyPrintf("service.api_key", "[APIKeyAuthCache] invalidation subscriber unavailable, retrying in background: %v", err)
+		go s.retryAuthCacheInvalidationSubscriber(ctx)
+	}
+}
+
+func (s *APIKeyService) subscribeAuthCacheInvalidation(ctx context.Context) error {
+	return s.cache.SubscribeAuthCacheInvalidation(ctx, func(cacheKey string) {
 		s.authCacheL1.Del(cacheKey)
-	}); err != nil {
-		// Log but don't fail - L1 cache will still work, just without cross-instance invalidation
-		println("[Service] Warning: failed to start auth cache invalidation subscriber:", err.Error())
+	}, func() {
+		s.clearAuthCacheL1("invalidation subscription re-established")
+	})
+}
+
+func (s *APIKeyService) retryAuthCacheInvalidationSubscriber(ctx context.Context) {
+	backoff := authCacheSubscribeRetryInitial
+	for {
+		select {
+		case <-ctx.Done():
+			return
+		case <-time.After(backoff):
+		}
+		if err := s.subscribeAuthCacheInvalidation(ctx); err != nil {
+			backoff = min(backoff*2, authCacheSubscribeRetryMax)
+			continue
+		}
+		s.clearAuthCacheL1("invalidation subscriber started")
+		return
+	}
+}
+
+// clearAuthCacheL1 清空本实例 L1；L2 由发布方直接删除，不受订阅中断影响
+func (s *APIKeyService) clearAuthCacheL1(reason string) {
+	if s.authCacheL1 == nil {
+		return
 	}
+	s.authCacheL1.Clear()
+	logger.LegacyPrintf("service.api_key", "[APIKeyAuthCache] L1 cleared: %s", reason)
 }
 
 func (s *APIKeyService) authCacheKey(key string) string {
@@ -0,0 +1,403 @@
+package service
+
+import (
+	"context"
+	"strconv"
+	"strings"
+	"sync"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/pkg/ctxkey"
+	infraerrors "github.com/Wei-Shaw/sub2api/internal/pkg/errors"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/timezone"
+)
+
+// API Key 策略中的推理内容处理方式
+const (
+	// ReasoningModePassthrough 默认：按上游返回原样透传
+	ReasoningModePassthrough = ""
+	// ReasoningModeStrip 从响应中移除 thinking / reasoning 内容（仍按实际 token 计费）
+	ReasoningModeStrip = "strip"
+	// ReasoningModeExpose 请求上游尽可能返回推理内容（Responses API 的 reasoning.summary、Gemini includeThoughts）
+	ReasoningModeExpose = "expose"
+)
+
+// 系统提示词模板的注入方式
+const (
+	SystemPromptModePrepend = "prepend"
+	SystemPromptModeAppend  = "append"
+	SystemPromptModeReplace = "replace"
+)
+
+// apiKeyPolicyCacheTTL 策略内存缓存时间；多实例部署下修改后最多延迟该时长生效
+const apiKeyPolicyCacheTTL = 30 * time.Second
+
+// systemPromptTemplateMaxLen 系统提示词模板最大长度（字节）
+const systemPromptTemplateMaxLen = 32 * 1024
+
+var (
+	ErrInvalidReasoningMode    = infraerrors.BadRequest("INVALID_REASONING_MODE", "reasoning_mode must be empty, 'strip' or 'expose'")
+	ErrInvalidSystemPromptMode = infraerrors.BadRequest("INVALID_SYSTEM_PROMPT_MODE", "system_prompt.mode must be 'prepend', 'append' or 'replace'")
+	ErrSystemPromptTooLong     = infraerrors.BadRequest("SYSTEM_PROMPT_TOO_LONG", "system_prompt.template is too long")
+)
+
+// SystemPromptTemplate 注入到请求系统提示词中的模板
+//
+// 支持的变量：{{date}}（YYYY-MM-DD）、{{datetime}}（RFC3339）、{{key_name}}、{{group_name}}，按服务端时区渲染。
+type SystemPromptTemplate struct {
+	// Mode 注入方式：prepend（默认）/ append / replace
+	Mode     string `json:"mode"`
+	Template string `json:"template"`
+}
+
+// APIKeyPolicy 按 API Key 或分组配置的请求/响应处理策略（api_key_policies / group_policies.policy，JSONB）
+//
+// 分组策略作为默认值，API Key 策略中已设置的字段覆盖分组策略。
+type APIKeyPolicy struct {
+	// ReasoningMode 推理内容处理方式，见 ReasoningMode* 常量
+	ReasoningMode string `json:"reasoning_mode,omitempty"`
+	// SystemPrompt 系统提示词模板，nil 表示不注入
+	SystemPrompt *SystemPromptTemplate `json:"system_prompt,omitempty"`
+	// Firewall 入站提示词检查，nil 表示不检查；API Key 配置时整体覆盖分组配置
+	Firewall *PromptFirewallPolicy `json:"firewall,omitempty"`
+	// Moderation 入站内容审核服务商链，nil 表示不审核；API Key 配置时整体覆盖分组配置
+	Moderation *ModerationPolicy `json:"moderation,omitempty"`
+	// MaxTokens max_tokens 默认值与上限，nil 表示不限制；API Key 配置时整体覆盖分组配置
+	MaxTokens *MaxTokensPolicy `json:"max_tokens,omitempty"`
+	// FairShareWeight 账号排队时的公平调度权重（租户等级），0 表示默认权重 1
+	FairShareWeight int `json:"fair_share_weight,omitempty"`
+	// MaskingProfile 运维日志脱敏配置档（log_masking.profiles），为空时使用 log_masking.default_profile
+	MaskingProfile string `json:"masking_profile,omitempty"`
+	// Restrictions 允许的模型与端点能力，nil 表示不限制；API Key 配置时整体覆盖分组配置
+	Restrictions *RestrictionPolicy `json:"restrictions,omitempty"`
+	// UpstreamTags 附加到上游请求的自定义请求头与 metadata，nil 表示不附加；API Key 配置时整体覆盖分组配置
+	UpstreamTags *UpstreamTagPolicy `json:"upstream_tags,omitempty"`
+}
+
+// Normalize 校验并规范化策略字段
+func (p *APIKeyPolicy) Normalize() error {
+	p.ReasoningMode = strings.ToLower(strings.TrimSpace(p.ReasoningMode))
+	switch p.ReasoningMode {
+	case ReasoningModePassthrough, ReasoningModeStrip, ReasoningModeExpose:
+	default:
+		return ErrInvalidReasoningMode
+	}
+
+	if sp := p.SystemPrompt; sp != nil {
+		if strings.TrimSpace(sp.Template) == "" {
+			p.SystemPrompt = nil
+		} else {
+			if len(sp.Template) > systemPromptTemplateMaxLen {
+				return ErrSystemPromptTooLong
+			}
+			sp.Mode = strings.ToLower(strings.TrimSpace(sp.Mode))
+			switch sp.Mode {
+			case "":
+				sp.Mode = SystemPromptModePrepend
+			case SystemPromptModePrepend, SystemPromptModeAppend, SystemPromptModeReplace:
+			default:
+				return ErrInvalidSystemPromptMode
+			}
+		}
+	}
+
+	if p.Firewall != nil {
+		enabled, err := p.Firewall.normalize()
+		if err != nil {
+			return err
+		}
+		if !enabled {
+			p.Firewall = nil
+		}
+	}
+
+	if p.Moderation != nil {
+		enabled, err := p.Moderation.normalize()
+		if err != nil {
+			return err
+		}
+		if !enabled {
+			p.Moderation = nil
+		}
+	}
+
+	if p.MaxTokens != nil {
+		enabled, err := p.MaxTokens.normalize()
+		if err != nil {
+			return err
+		}
+		if !enabled {
+			p.MaxTokens = nil
+		}
+	}
+
+	if p.FairShareWeight < 0 || p.FairShareWeight > fairShareMaxWeight {
+		return ErrInvalidFairShareWeight
+	}
+
+	p.MaskingProfile = strings.TrimSpace(p.MaskingProfile)
+
+	if p.Restrictions != nil {
+		enabled, err := p.Restrictions.normalize()
+		if err != nil {
+			return err
+		}
+		if !enabled {
+			p.Restrictions = nil
+		}
+	}
+
+	if p.UpstreamTags != nil {
+		enabled, err := p.UpstreamTags.normalize()
+		if err != nil {
+			return err
+		}
+		if !enabled {
+			p.UpstreamTags = nil
+		}
+	}
+	return nil
+}
+
+// ForRequest 返回渲染了模板变量的策略副本，供单个请求使用（不修改缓存中的策略）
+func (p *APIKeyPolicy) ForRequest(apiKey *APIKey, now time.Time) *APIKeyPolicy {
+	out := *p
+	if p.SystemPrompt == nil || !strings.Contains(p.SystemPrompt.Template, "{{") {
+		return &out
+	}
+	keyName, groupName := "", ""
+	if apiKey != nil {
+		keyName = apiKey.Name
+		if apiKey.Group != nil {
+			groupName = apiKey.Group.Name
+		}
+	}
+	replacer := strings.NewReplacer(
+		"{{date}}", now.Format("2006-01-02"),
+		"{{datetime}}", now.Format(time.RFC3339),
+		"{{key_name}}", keyName,
+		"{{group_name}}", groupName,
+	)
+	out.SystemPrompt = &SystemPromptTemplate{
+		Mode:     p.SystemPrompt.Mode,
+		Template: replacer.Replace(p.SystemPrompt.Template),
+	}
+	return &out
+}
+
+// mergeAPIKeyPolicy 以分组策略为默认值，叠加 API Key 策略中已设置的字段
+func mergeAPIKeyPolicy(group, key *APIKeyPolicy) *APIKeyPolicy {
+	out := &APIKeyPolicy{}
+	if group != nil {
+		*out = *group
+	}
+	if key == nil {
+		return out
+	}
+	if key.ReasoningMode != "" {
+		out.ReasoningMode = key.ReasoningMode
+	}
+	if key.SystemPrompt != nil {
+		out.SystemPrompt = key.SystemPrompt
+	}
+	if key.Firewall != nil {
+		out.Firewall = key.Firewall
+	}
+	if key.Moderation != nil {
+		out.Moderation = key.Moderation
+	}
+	if key.MaxTokens != nil {
+		out.MaxTokens = key.MaxTokens
+	}
+	if key.FairShareWeight > 0 {
+		out.FairShareWeight = key.FairShareWeight
+	}
+	if key.MaskingProfile != "" {
+		out.MaskingProfile = key.MaskingProfile
+	}
+	if key.Restrictions != nil {
+		out.Restrictions = key.Restrictions
+	}
+	if key.UpstreamTags != nil {
+		out.UpstreamTags = key.UpstreamTags
+	}
+	return out
+}
+
+// APIKeyPolicyRepository API Key / 分组策略持久化；未配置策略时 Get 返回 nil, nil
+type APIKeyPolicyRepository interface {
+	GetByAPIKeyID(ctx context.Context, apiKeyID int64) (*APIKeyPolicy, error)
+	Upsert(ctx context.Context, apiKeyID int64, policy *APIKeyPolicy) error
+	GetByGroupID(ctx context.Context, groupID int64) (*APIKeyPolicy, error)
+	UpsertForGroup(ctx context.Context, groupID int64, policy *APIKeyPolicy) error
+}
+
+type apiKeyPolicyCacheEntry struct {
+	policy    *APIKeyPolicy
+	expiresAt time.Time
+}
+
+// APIKeyPolicyService 读取与维护 API Key / 分组策略，网关请求路径上走内存缓存
+type APIKeyPolicyService struct {
+	repo            APIKeyPolicyRepository
+	apiKeyRepo      APIKeyRepository
+	groupRepo       GroupRepository
+	headerAllowlist *UpstreamHeaderAllowlist
+	logMasking      *LogMaskingProfiles
+
+	mu    sync.RWMutex
+	cache map[string]apiKeyPolicyCacheEntry
+}
+
+// NewAPIKeyPolicyService 创建 API Key 策略服务
+func NewAPIKeyPolicyService(repo APIKeyPolicyRepository, apiKeyRepo APIKeyRepository, groupRepo GroupRepository, headerAllowlist *UpstreamHeaderAllowlist, logMasking *LogMaskingProfiles) *APIKeyPolicyService {
+	return &APIKeyPolicyService{
+		repo:            repo,
+		apiKeyRepo:      apiKeyRepo,
+		groupRepo:       groupRepo,
+		headerAllowlist: headerAllowlist,
+		logMasking:      logMasking,
+		cache:           make(map[string]apiKeyPolicyCacheEntry),
+	}
+}
+
+// Get 返回 API Key 自身的策略（不含分组默认值）；未配置时返回零值策略（不会返回 nil）
+func (s *APIKeyPolicyService) Get(ctx context.Context, apiKeyID int64) (*APIKeyPolicy, error) {
+	return s.cached(apiKeyPolicyCacheKey(apiKeyID), func() (*APIKeyPolicy, error) {
+		return s.repo.GetByAPIKeyID(ctx, apiKeyID)
+	})
+}
+
+// GetGroup 返回分组策略；未配置时返回零值策略
+func (s *APIKeyPolicyService) GetGroup(ctx context.Context, groupID int64) (*APIKeyPolicy, error) {
+	return s.cached(groupPolicyCacheKey(groupID), func() (*APIKeyPolicy, error) {
+		return s.repo.GetByGroupID(ctx, groupID)
+	})
+}
+
+// Resolve 返回 API Key 的生效策略（分组策略 + API Key 策略）
+func (s *APIKeyPolicyService) Resolve(ctx context.Context, apiKey *APIKey) (*APIKeyPolicy, error) {
+	var groupPolicy *APIKeyPolicy
+	if apiKey.GroupID != nil {
+		p, err := s.GetGroup(ctx, *apiKey.GroupID)
+		if err != nil {
+			return nil, err
+		}
+		groupPolicy = p
+	}
+	keyPolicy, err := s.Get(ctx, apiKey.ID)
+	if err != nil {
+		return nil, err
+	}
+	return mergeAPIKeyPolicy(groupPolicy, keyPolicy), nil
+}
+
+// ResolveRequestPolicy 返回 API Key 当前请求的生效策略（已渲染模板变量）
+func (s *APIKeyPolicyService) ResolveRequestPolicy(ctx context.Context, apiKey *APIKey) (*APIKeyPolicy, error) {
+	policy, err := s.Resolve(ctx, apiKey)
+	if err != nil {
+		return nil, err
+	}
+	return policy.ForRequest(apiKey, timezone.Now()), nil
+}
+
+// Update 校验并保存 API Key 策略（整体覆盖）
+func (s *APIKeyPolicyService) Update(ctx context.Context, apiKeyID int64, policy *APIKeyPolicy) (*APIKeyPolicy, error) {
+	if policy == nil {
+		policy = &APIKeyPolicy{}
+	}
+	if err := s.normalize(policy); err != nil {
+		return nil, err
+	}
+	if _, err := s.apiKeyRepo.GetByID(ctx, apiKeyID); err != nil {
+		return nil, err
+	}
+	if err := s.repo.Upsert(ctx, apiKeyID, policy); err != nil {
+		return nil, err
+	}
+	s.invalidate(apiKeyPolicyCacheKey(apiKeyID))
+	return policy, nil
+}
+
+// UpdateGroup 校验并保存分组策略（整体覆盖）
+func (s *APIKeyPolicyService) UpdateGroup(ctx context.Context, groupID int64, policy *APIKeyPolicy) (*APIKeyPolicy, error) {
+	if policy == nil {
+		policy = &APIKeyPolicy{}
+	}
+	if err := s.normalize(policy); err != nil {
+		return nil, err
+	}
+	if _, err := s.groupRepo.GetByIDLite(ctx, groupID); err != nil {
+		return nil, err
+	}
+	if err := s.repo.UpsertForGroup(ctx, groupID, policy); err != nil {
+		return nil, err
+	}
+	s.invalidate(groupPolicyCacheKey(groupID))
+	return policy, nil
+}
+
+// normalize 规范化策略，校验引用的日志脱敏配置档，并按 gateway.upstream_header_allowlist 校验自定义上游请求头
+func (s *APIKeyPolicyService) normalize(policy *APIKeyPolicy) error {
+	if err := policy.Normalize(); err != nil {
+		return err
+	}
+	if err := s.logMasking.validate(policy.MaskingProfile); err != nil {
+		return err
+	}
+	return policy.UpstreamTags.checkAllowlist(s.headerAllowlist)
+}
+
+func (s *APIKeyPolicyService) cached(key string, load func() (*APIKeyPolicy, error)) (*APIKeyPolicy, error) {
+	now := time.Now()
+	s.mu.RLock()
+	entry, ok := s.cache[key]
+	s.mu.RUnlock()
+	if ok && now.Before(entry.expiresAt) {
+		return entry.policy, nil
+	}
+
+	policy, err := load()
+	if err != nil {
+		return nil, err
+	}
+	if policy == nil {
+		policy = &APIKeyPolicy{}
+	}
+	s.mu.Lock()
+	s.cache[key] = apiKeyPolicyCacheEntry{policy: policy, expiresAt: now.Add(apiKeyPolicyCacheTTL)}
+	s.mu.Unlock()
+	return policy, nil
+}
+
+func (s *APIKeyPolicyService) invalidate(key string) {
+	s.mu.Lock()
+	delete(s.cache, key)
+	s.mu.Unlock()
+}
+
+func apiKeyPolicyCacheKey(apiKeyID int64) string {
+	return "key:" + strconv.FormatInt(apiKeyID, 10)
+}
+
+func groupPolicyCacheKey(groupID int64) string {
+	return "group:" + strconv.FormatInt(groupID, 10)
+}
+
+// WithAPIKeyPolicy 将策略写入请求上下文，供网关服务读取
+func WithAPIKeyPolicy(ctx context.Context, policy *APIKeyPolicy) context.Context {
+	if policy == nil {
+		return ctx
+	}
+	return context.WithValue(ctx, ctxkey.APIKeyPolicy, policy)
+}
+
+// APIKeyPolicyFromContext 读取请求上下文中的策略；未设置时返回零值策略
+func APIKeyPolicyFromContext(ctx context.Context) *APIKeyPolicy {
+	if ctx != nil {
+		if policy, ok := ctx.Value(ctxkey.APIKeyPolicy).(*APIKeyPolicy); ok && policy != nil {
+			return policy
+		}
+	}
+	return &APIKeyPolicy{}
+}
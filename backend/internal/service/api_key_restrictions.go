@@ -0,0 +1,205 @@
+package service
+
+import (
+	"net/http"
+	"slices"
+	"strings"
+
+	infraerrors "github.com/Wei-Shaw/sub2api/internal/pkg/errors"
+	"github.com/tidwall/gjson"
+)
+
+// API Key 可用的端点能力
+const (
+	// CapabilityChat 文本对话 / 补全端点（messages、responses、generateContent、count_tokens）
+	CapabilityChat = "chat"
+	// CapabilityImages 图片输入，以及 Sora 图片 / 视频生成与媒体代理
+	CapabilityImages = "images"
+	// CapabilityFiles 文件 / 文档输入（PDF document、input_file、Gemini fileData 等）
+	CapabilityFiles = "files"
+)
+
+// knownCapabilities 可配置的能力列表
+var knownCapabilities = []string{CapabilityChat, CapabilityImages, CapabilityFiles}
+
+// restrictionPolicyMaxModels 单个策略允许的模型条目数上限
+const restrictionPolicyMaxModels = 200
+
+var (
+	ErrInvalidRestrictionPolicy = infraerrors.BadRequest("INVALID_RESTRICTION_POLICY", "restrictions.capabilities must be chosen from chat/images/files and models must be non-empty")
+	ErrModelNotAllowed          = infraerrors.Forbidden("MODEL_NOT_ALLOWED", "model is not allowed for this API key")
+	ErrCapabilityNotAllowed     = infraerrors.Forbidden("CAPABILITY_NOT_ALLOWED", "capability is not allowed for this API key")
+)
+
+// RestrictionPolicy API Key 可访问的模型与端点能力（APIKeyPolicy.Restrictions）
+type RestrictionPolicy struct {
+	// Models 允许的模型，支持以 * 结尾的前缀（如 "claude-sonnet-*"）；空表示不限制模型
+	Models []string `json:"models,omitempty"`
+	// Capabilities 允许的能力，见 Capability* 常量；空表示不限制能力
+	Capabilities []string `json:"capabilities,omitempty"`
+}
+
+// normalize 校验并规范化限制策略；返回 false 表示配置为空，调用方应置为 nil
+func (p *RestrictionPolicy) normalize() (bool, error) {
+	if len(p.Models) > restrictionPolicyMaxModels {
+		return false, ErrInvalidRestrictionPolicy
+	}
+	models := make([]string, 0, len(p.Models))
+	for _, m := range p.Models {
+		m = strings.ToLower(strings.TrimSpace(m))
+		if m == "" || m == "*" {
+			return false, ErrInvalidRestrictionPolicy.WithMetadata(map[string]string{"model": m})
+		}
+		if !slices.Contains(models, m) {
+			models = append(models, m)
+		}
+	}
+	capabilities := make([]string, 0, len(p.Capabilities))
+	for _, c := range p.Capabilities {
+		c = strings.ToLower(strings.TrimSpace(c))
+		if !slices.Contains(knownCapabilities, c) {
+			return false, ErrInvalidRestrictionPolicy.WithMetadata(map[string]string{"capability": c})
+		}
+		if !slices.Contains(capabilities, c) {
+			capabilities = append(capabilities, c)
+		}
+	}
+	p.Models, p.Capabilities = nil, nil
+	if len(models) > 0 {
+		p.Models = models
+	}
+	if len(capabilities) > 0 {
+		p.Capabilities = capabilities
+	}
+	return p.Models != nil || p.Capabilities != nil, nil
+}
+
+// NeedsBody 是否需要读取请求体（模型名与输入内容类型）才能完成检查
+func (p *RestrictionPolicy) NeedsBody() bool {
+	if p == nil {
+		return false
+	}
+	return len(p.Models) > 0 || !p.Allows(CapabilityImages) || !p.Allows(CapabilityFiles)
+}
+
+// Allows 判断是否允许指定能力
+func (p *RestrictionPolicy) Allows(capability string) bool {
+	return p == nil || len(p.Capabilities) == 0 || slices.Contains(p.Capabilities, capability)
+}
+
+// AllowsModel 判断是否允许指定模型；未知模型（空字符串）不拦截，由上游校验
+func (p *RestrictionPolicy) AllowsModel(model string) bool {
+	if p == nil || len(p.Models) == 0 {
+		return true
+	}
+	model = strings.ToLower(strings.TrimSpace(model))
+	if model == "" {
+		return true
+	}
+	for _, pattern := range p.Models {
+		if prefix, isPrefix := strings.CutSuffix(pattern, "*"); isPrefix {
+			if strings.HasPrefix(model, prefix) {
+				return true
+			}
+		} else if model == pattern {
+			return true
+		}
+	}
+	return false
+}
+
+// CheckRequest 检查网关请求是否在限制范围内
+//
+// method/path 决定端点能力，body 用于提取模型名与图片 / 文件输入；GET 请求（模型列表、用量查询）只检查 Sora 媒体代理。
+func (p *RestrictionPolicy) CheckRequest(method, path string, body []byte) error {
+	if p == nil {
+		return nil
+	}
+	isSora := strings.HasPrefix(path, "/sora/")
+	if method == http.MethodGet {
+		if isSora && !p.Allows(CapabilityImages) {
+			return capabilityNotAllowed(CapabilityImages)
+		}
+		return nil
+	}
+
+	required := CapabilityChat
+	if isSora {
+		required = CapabilityImages
+	}
+	if !p.Allows(required) {
+		return capabilityNotAllowed(required)
+	}
+
+	model := RequestModelFromPath(path)
+	if model == "" && len(body) > 0 {
+		model = gjson.GetBytes(body, "model").String()
+	}
+	if !p.AllowsModel(model) {
+		return infraerrors.Newf(http.StatusForbidden, ErrModelNotAllowed.Reason, "model %q is not allowed for this API key", model)
+	}
+
+	if len(body) > 0 && (!p.Allows(CapabilityImages) || !p.Allows(CapabilityFiles)) {
+		hasImages, hasFiles := detectMediaInputs(gjson.ParseBytes(body))
+		if hasImages && !p.Allows(CapabilityImages) {
+			return capabilityNotAllowed(CapabilityImages)
+		}
+		if hasFiles && !p.Allows(CapabilityFiles) {
+			return capabilityNotAllowed(CapabilityFiles)
+		}
+	}
+	return nil
+}
+
+func capabilityNotAllowed(capability string) error {
+	return infraerrors.Newf(http.StatusForbidden, ErrCapabilityNotAllowed.Reason, "capability %q is not allowed for this API key", capability)
+}
+
+// RequestModelFromPath 从 Gemini 原生路径（/v1beta/models/{model}:{action}）中提取模型名
+func RequestModelFromPath(path string) string {
+	_, rest, ok := strings.Cut(path, "/models/")
+	if !ok {
+		return ""
+	}
+	model, _, _ := strings.Cut(rest, ":")
+	return strings.Trim(model, "/")
+}
+
+// detectMediaInputs 遍历请求体，识别 Anthropic / OpenAI / Gemini 三种协议中的图片与文件输入
+func detectMediaInputs(node gjson.Result) (hasImages, hasFiles bool) {
+	var walk func(r gjson.Result)
+	walk = func(r gjson.Result) {
+		if hasImages && hasFiles {
+			return
+		}
+		if r.IsObject() {
+			switch r.Get("type").String() {
+			case "image", "input_image", "image_url":
+				hasImages = true
+			case "document", "input_file", "file":
+				hasFiles = true
+			}
+			for _, key := range []string{"inlineData", "inline_data", "fileData", "file_data"} {
+				if part := r.Get(key); part.Exists() {
+					mime := part.Get("mimeType").String()
+					if mime == "" {
+						mime = part.Get("mime_type").String()
+					}
+					if strings.HasPrefix(mime, "image/") {
+						hasImages = true
+					} else {
+						hasFiles = true
+					}
+				}
+			}
+		}
+		if r.IsObject() || r.IsArray() {
+			r.ForEach(func(_, value gjson.Result) bool {
+				walk(value)
+				return !(hasImages && hasFiles)
+			})
+		}
+	}
+	walk(node)
+	return hasImages, hasFiles
+}
@@ -0,0 +1,71 @@
+//go:build unit
+
+package service
+
+import (
+	"errors"
+	"net/http"
+	"testing"
+
+	"github.com/stretchr/testify/require"
+)
+
+func TestRestrictionPolicy_Normalize(t *testing.T) {
+	p := &APIKeyPolicy{Restrictions: &RestrictionPolicy{Models: []string{" Claude-Sonnet-* ", "claude-sonnet-*"}, Capabilities: []string{"CHAT"}}}
+	require.NoError(t, p.Normalize())
+	require.Equal(t, []string{"claude-sonnet-*"}, p.Restrictions.Models)
+	require.Equal(t, []string{CapabilityChat}, p.Restrictions.Capabilities)
+
+	p = &APIKeyPolicy{Restrictions: &RestrictionPolicy{}}
+	require.NoError(t, p.Normalize())
+	require.Nil(t, p.Restrictions)
+
+	require.ErrorIs(t, (&APIKeyPolicy{Restrictions: &RestrictionPolicy{Capabilities: []string{"audio"}}}).Normalize(), ErrInvalidRestrictionPolicy)
+	require.ErrorIs(t, (&APIKeyPolicy{Restrictions: &RestrictionPolicy{Models: []string{"*"}}}).Normalize(), ErrInvalidRestrictionPolicy)
+}
+
+func TestRestrictionPolicy_CheckRequest(t *testing.T) {
+	p := &RestrictionPolicy{Models: []string{"claude-sonnet-*", "gemini-2.5-flash"}, Capabilities: []string{CapabilityChat}}
+
+	require.NoError(t, p.CheckRequest(http.MethodPost, "/v1/messages", []byte(`{"model":"claude-sonnet-4-5","messages":[{"role":"user","content":"hi"}]}`)))
+	require.NoError(t, p.CheckRequest(http.MethodPost, "/v1beta/models/gemini-2.5-flash:streamGenerateContent", []byte(`{"contents":[]}`)))
+	require.NoError(t, p.CheckRequest(http.MethodGet, "/v1/models", nil))
+
+	err := p.CheckRequest(http.MethodPost, "/v1/messages", []byte(`{"model":"claude-opus-4-1"}`))
+	require.True(t, errors.Is(err, ErrModelNotAllowed))
+	err = p.CheckRequest(http.MethodPost, "/v1beta/models/gemini-2.5-pro:generateContent", nil)
+	require.True(t, errors.Is(err, ErrModelNotAllowed))
+
+	// 仅允许 chat：拒绝图片 / 文件输入与 Sora 生成
+	err = p.CheckRequest(http.MethodPost, "/v1/messages", []byte(`{"model":"claude-sonnet-4-5","messages":[{"role":"user","content":[{"type":"image","source":{}}]}]}`))
+	require.True(t, errors.Is(err, ErrCapabilityNotAllowed))
+	err = p.CheckRequest(http.MethodPost, "/v1/responses", []byte(`{"model":"claude-sonnet-4","input":[{"role":"user","content":[{"type":"input_file","file_data":"x"}]}]}`))
+	require.True(t, errors.Is(err, ErrCapabilityNotAllowed))
+	err = p.CheckRequest(http.MethodPost, "/v1beta/models/gemini-2.5-flash:generateContent", []byte(`{"contents":[{"parts":[{"inlineData":{"mimeType":"application/pdf","data":"x"}}]}]}`))
+	require.True(t, errors.Is(err, ErrCapabilityNotAllowed))
+	err = p.CheckRequest(http.MethodPost, "/sora/v1/chat/completions", []byte(`{"model":"sora2-landscape-10s"}`))
+	require.True(t, errors.Is(err, ErrCapabilityNotAllowed))
+	err = p.CheckRequest(http.MethodGet, "/sora/media/abc.mp4", nil)
+	require.True(t, errors.Is(err, ErrCapabilityNotAllowed))
+}
+
+func TestRestrictionPolicy_ImagesWithoutFiles(t *testing.T) {
+	p := &RestrictionPolicy{Capabilities: []string{CapabilityChat, CapabilityImages}}
+	require.True(t, p.NeedsBody())
+
+	require.NoError(t, p.CheckRequest(http.MethodPost, "/v1beta/models/gemini-2.5-flash:generateContent", []byte(`{"contents":[{"parts":[{"inlineData":{"mimeType":"image/png","data":"x"}}]}]}`)))
+	err := p.CheckRequest(http.MethodPost, "/v1/messages", []byte(`{"model":"claude-sonnet-4-5","messages":[{"role":"user","content":[{"type":"document","source":{}}]}]}`))
+	require.True(t, errors.Is(err, ErrCapabilityNotAllowed))
+
+	require.False(t, (&RestrictionPolicy{Models: nil, Capabilities: nil}).NeedsBody())
+}
+
+func TestMergeAPIKeyPolicy_Restrictions(t *testing.T) {
+	group := &APIKeyPolicy{Restrictions: &RestrictionPolicy{Capabilities: []string{CapabilityChat}}}
+	merged := mergeAPIKeyPolicy(group, &APIKeyPolicy{})
+	require.Equal(t, group.Restrictions, merged.Restrictions)
+
+	key := &APIKeyPolicy{Restrictions: &RestrictionPolicy{Models: []string{"gpt-5"}}}
+	merged = mergeAPIKeyPolicy(group, key)
+	require.Equal(t, key.Restrictions, merged.Restrictions)
+}
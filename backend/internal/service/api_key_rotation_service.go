@@ -0,0 +1,196 @@
+package service
+
+import (
+	"context"
+	"fmt"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	infraerrors "github.com/Wei-Shaw/sub2api/internal/pkg/errors"
+)
+
+var (
+	ErrAPIKeyRotationInvalidGrace = infraerrors.BadRequest("API_KEY_ROTATION_INVALID_GRACE", "grace period is out of allowed range")
+	ErrAPIKeyRotationNotActive    = infraerrors.BadRequest("API_KEY_ROTATION_NOT_ACTIVE", "only active, unexpired api keys can be rotated")
+	ErrAPIKeyRotationNotFound     = infraerrors.NotFound("API_KEY_ROTATION_NOT_FOUND", "api key has not been rotated")
+)
+
+// APIKeyRotation API Key 轮换记录
+type APIKeyRotation struct {
+	ID          int64
+	UserID      int64
+	OldAPIKeyID int64
+	NewAPIKeyID int64
+	GraceUntil  time.Time
+	CreatedAt   time.Time
+}
+
+// APIKeyVariantUsage 轮换后某个 Key 变体的请求统计
+type APIKeyVariantUsage struct {
+	APIKeyID     int64      `json:"api_key_id"`
+	RequestCount int64      `json:"request_count"`
+	LastUsedAt   *time.Time `json:"last_used_at,omitempty"`
+}
+
+// APIKeyRotationRepository 管理轮换记录
+type APIKeyRotationRepository interface {
+	Create(ctx context.Context, rotation *APIKeyRotation) error
+	// GetLatestByKeyID 返回与该 Key 相关（作为旧 Key 或新 Key）的最近一次轮换，不存在时返回 (nil, nil)
+	GetLatestByKeyID(ctx context.Context, apiKeyID int64) (*APIKeyRotation, error)
+	// GetVariantUsage 统计轮换之后新旧 Key 各自的请求量
+	GetVariantUsage(ctx context.Context, apiKeyIDs []int64, since time.Time) (map[int64]*APIKeyVariantUsage, error)
+}
+
+// APIKeyRotationResult 轮换结果
+type APIKeyRotationResult struct {
+	OldKey   *APIKey
+	NewKey   *APIKey
+	Rotation *APIKeyRotation
+}
+
+// APIKeyRotationStatus 轮换状态（用于观察客户端迁移进度）
+type APIKeyRotationStatus struct {
+	Rotation *APIKeyRotation
+	OldUsage *APIKeyVariantUsage
+	NewUsage *APIKeyVariantUsage
+	// InGracePeriod 旧 Key 是否仍在宽限期内
+	InGracePeriod bool
+}
+
+// APIKeyRotationService 负责 API Key 轮换：签发替换 Key，旧 Key 在宽限期后自动过期
+type APIKeyRotationService struct {
+	apiKeyRepo    APIKeyRepository
+	rotationRepo  APIKeyRotationRepository
+	apiKeyService *APIKeyService
+	cfg           *config.Config
+}
+
+// NewAPIKeyRotationService 创建 API Key 轮换服务
+func NewAPIKeyRotationService(
+	apiKeyRepo APIKeyRepository,
+	rotationRepo APIKeyRotationRepository,
+	apiKeyService *APIKeyService,
+	cfg *config.Config,
+) *APIKeyRotationService {
+	return &APIKeyRotationService{
+		apiKeyRepo:    apiKeyRepo,
+		rotationRepo:  rotationRepo,
+		apiKeyService: apiKeyService,
+		cfg:           cfg,
+	}
+}
+
+func (s *APIKeyRotationService) resolveGrace(graceHours *int) (time.Duration, error) {
+	hours := s.cfg.APIKeyRotation.DefaultGraceHours
+	if graceHours != nil {
+		hours = *graceHours
+	}
+	if hours < 0 || hours > s.cfg.APIKeyRotation.MaxGraceHours {
+		return 0, ErrAPIKeyRotationInvalidGrace
+	}
+	return time.Duration(hours) * time.Hour, nil
+}
+
+// Rotate 轮换 API Key
+// 新 Key 继承旧 Key 的名称、分组、IP 限制、额度与过期时间；
+// 旧 Key 的过期时间被设置为宽限期截止时间（若原本更早过期则保持不变）。
+func (s *APIKeyRotationService) Rotate(ctx context.Context, userID, keyID int64, graceHours *int) (*APIKeyRotationResult, error) {
+	grace, err := s.resolveGrace(graceHours)
+	if err != nil {
+		return nil, err
+	}
+
+	oldKey, err := s.apiKeyRepo.GetByID(ctx, keyID)
+	if err != nil {
+		return nil, fmt.Errorf("get api key: %w", err)
+	}
+	if oldKey.UserID != userID {
+		return nil, ErrInsufficientPerms
+	}
+	if !oldKey.IsActive() || oldKey.IsExpired() {
+		return nil, ErrAPIKeyRotationNotActive
+	}
+
+	newKeyValue, err := s.apiKeyService.GenerateKey()
+	if err != nil {
+		return nil, fmt.Errorf("generate key: %w", err)
+	}
+
+	newKey := &APIKey{
+		UserID:      oldKey.UserID,
+		Key:         newKeyValue,
+		Name:        oldKey.Name,
+		GroupID:     oldKey.GroupID,
+		Status:      StatusActive,
+		IPWhitelist: oldKey.IPWhitelist,
+		IPBlacklist: oldKey.IPBlacklist,
+		Quota:       oldKey.Quota,
+		QuotaUsed:   oldKey.QuotaUsed,
+		ExpiresAt:   oldKey.ExpiresAt,
+	}
+	if err := s.apiKeyRepo.Create(ctx, newKey); err != nil {
+		return nil, fmt.Errorf("create api key: %w", err)
+	}
+
+	now := time.Now()
+	graceUntil := now.Add(grace)
+	if oldKey.ExpiresAt == nil || graceUntil.Before(*oldKey.ExpiresAt) {
+		oldKey.ExpiresAt = &graceUntil
+	}
+	if err := s.apiKeyRepo.Update(ctx, oldKey); err != nil {
+		return nil, fmt.Errorf("update api key: %w", err)
+	}
+	s.apiKeyService.InvalidateAuthCacheByKey(ctx, oldKey.Key)
+	s.apiKeyService.InvalidateAuthCacheByKey(ctx, newKey.Key)
+
+	rotation := &APIKeyRotation{
+		UserID:      userID,
+		OldAPIKeyID: oldKey.ID,
+		NewAPIKeyID: newKey.ID,
+		GraceUntil:  *oldKey.ExpiresAt,
+		CreatedAt:   now,
+	}
+	if err := s.rotationRepo.Create(ctx, rotation); err != nil {
+		return nil, fmt.Errorf("create api key rotation: %w", err)
+	}
+
+	return &APIKeyRotationResult{OldKey: oldKey, NewKey: newKey, Rotation: rotation}, nil
+}
+
+// GetStatus 返回 Key 最近一次轮换的状态，以及新旧 Key 在轮换后的请求量
+func (s *APIKeyRotationService) GetStatus(ctx context.Context, userID, keyID int64) (*APIKeyRotationStatus, error) {
+	key, err := s.apiKeyRepo.GetByID(ctx, keyID)
+	if err != nil {
+		return nil, fmt.Errorf("get api key: %w", err)
+	}
+	if key.UserID != userID {
+		return nil, ErrInsufficientPerms
+	}
+
+	rotation, err := s.rotationRepo.GetLatestByKeyID(ctx, keyID)
+	if err != nil {
+		return nil, fmt.Errorf("get api key rotation: %w", err)
+	}
+	if rotation == nil {
+		return nil, ErrAPIKeyRotationNotFound
+	}
+
+	usage, err := s.rotationRepo.GetVariantUsage(ctx, []int64{rotation.OldAPIKeyID, rotation.NewAPIKeyID}, rotation.CreatedAt)
+	if err != nil {
+		return nil, fmt.Errorf("get api key variant usage: %w", err)
+	}
+
+	status := &APIKeyRotationStatus{
+		Rotation:      rotation,
+		OldUsage:      usage[rotation.OldAPIKeyID],
+		NewUsage:      usage[rotation.NewAPIKeyID],
+		InGracePeriod: time.Now().Before(rotation.GraceUntil),
+	}
+	if status.OldUsage == nil {
+		status.OldUsage = &APIKeyVariantUsage{APIKeyID: rotation.OldAPIKeyID}
+	}
+	if status.NewUsage == nil {
+		status.NewUsage = &APIKeyVariantUsage{APIKeyID: rotation.NewAPIKeyID}
+	}
+	return status, nil
+}
@@ -0,0 +1,138 @@
+//go:build unit
+
+package service
+
+import (
+	"context"
+	"testing"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/stretchr/testify/require"
+)
+
+type rotationAPIKeyRepoStub struct {
+	APIKeyRepository
+	keys   map[int64]*APIKey
+	nextID int64
+}
+
+func (r *rotationAPIKeyRepoStub) GetByID(ctx context.Context, id int64) (*APIKey, error) {
+	key, ok := r.keys[id]
+	if !ok {
+		return nil, ErrAPIKeyNotFound
+	}
+	cp := *key
+	return &cp, nil
+}
+
+func (r *rotationAPIKeyRepoStub) Create(ctx context.Context, key *APIKey) error {
+	r.nextID++
+	key.ID = r.nextID
+	cp := *key
+	r.keys[key.ID] = &cp
+	return nil
+}
+
+func (r *rotationAPIKeyRepoStub) Update(ctx context.Context, key *APIKey) error {
+	cp := *key
+	r.keys[key.ID] = &cp
+	return nil
+}
+
+type rotationRepoStub struct {
+	rotations []*APIKeyRotation
+}
+
+func (r *rotationRepoStub) Create(ctx context.Context, rotation *APIKeyRotation) error {
+	rotation.ID = int64(len(r.rotations) + 1)
+	r.rotations = append(r.rotations, rotation)
+	return nil
+}
+
+func (r *rotationRepoStub) GetLatestByKeyID(ctx context.Context, apiKeyID int64) (*APIKeyRotation, error) {
+	for i := len(r.rotations) - 1; i >= 0; i-- {
+		if r.rotations[i].OldAPIKeyID == apiKeyID || r.rotations[i].NewAPIKeyID == apiKeyID {
+			return r.rotations[i], nil
+		}
+	}
+	return nil, nil
+}
+
+func (r *rotationRepoStub) GetVariantUsage(ctx context.Context, apiKeyIDs []int64, since time.Time) (map[int64]*APIKeyVariantUsage, error) {
+	return map[int64]*APIKeyVariantUsage{
+		apiKeyIDs[0]: {APIKeyID: apiKeyIDs[0], RequestCount: 3},
+	}, nil
+}
+
+func newRotationTestService(keys map[int64]*APIKey) (*APIKeyRotationService, *rotationAPIKeyRepoStub, *rotationRepoStub) {
+	cfg := &config.Config{
+		APIKeyRotation: config.APIKeyRotationConfig{DefaultGraceHours: 24, MaxGraceHours: 72},
+	}
+	repo := &rotationAPIKeyRepoStub{keys: keys, nextID: 100}
+	rotations := &rotationRepoStub{}
+	apiKeySvc := NewAPIKeyService(repo, nil, nil, nil, nil, nil, cfg)
+	return NewAPIKeyRotationService(repo, rotations, apiKeySvc, cfg), repo, rotations
+}
+
+func TestAPIKeyRotationService_RotateKeepsOldKeyDuringGrace(t *testing.T) {
+	groupID := int64(5)
+	svc, repo, rotations := newRotationTestService(map[int64]*APIKey{
+		1: {ID: 1, UserID: 7, Key: "sk-old", Name: "prod", GroupID: &groupID, Status: StatusActive, Quota: 10, QuotaUsed: 2},
+	})
+
+	grace := 2
+	result, err := svc.Rotate(context.Background(), 7, 1, &grace)
+	require.NoError(t, err)
+	require.NotEqual(t, "sk-old", result.NewKey.Key)
+	require.Equal(t, "prod", result.NewKey.Name)
+	require.Equal(t, &groupID, result.NewKey.GroupID)
+	require.Equal(t, 10.0, result.NewKey.Quota)
+	require.Equal(t, 2.0, result.NewKey.QuotaUsed)
+	require.Nil(t, result.NewKey.ExpiresAt)
+
+	old := repo.keys[1]
+	require.NotNil(t, old.ExpiresAt)
+	require.WithinDuration(t, time.Now().Add(2*time.Hour), *old.ExpiresAt, time.Minute)
+	require.False(t, old.IsExpired())
+	require.Len(t, rotations.rotations, 1)
+	require.Equal(t, int64(1), rotations.rotations[0].OldAPIKeyID)
+	require.Equal(t, result.NewKey.ID, rotations.rotations[0].NewAPIKeyID)
+
+	status, err := svc.GetStatus(context.Background(), 7, result.NewKey.ID)
+	require.NoError(t, err)
+	require.True(t, status.InGracePeriod)
+	require.Equal(t, int64(3), status.OldUsage.RequestCount)
+	require.Equal(t, int64(0), status.NewUsage.RequestCount)
+}
+
+func TestAPIKeyRotationService_KeepsEarlierExpiry(t *testing.T) {
+	expiresAt := time.Now().Add(time.Hour)
+	svc, repo, _ := newRotationTestService(map[int64]*APIKey{
+		1: {ID: 1, UserID: 7, Key: "sk-old", Status: StatusActive, ExpiresAt: &expiresAt},
+	})
+
+	_, err := svc.Rotate(context.Background(), 7, 1, nil)
+	require.NoError(t, err)
+	require.WithinDuration(t, expiresAt, *repo.keys[1].ExpiresAt, time.Second)
+}
+
+func TestAPIKeyRotationService_Rejects(t *testing.T) {
+	svc, _, _ := newRotationTestService(map[int64]*APIKey{
+		1: {ID: 1, UserID: 7, Key: "sk-a", Status: StatusActive},
+		2: {ID: 2, UserID: 7, Key: "sk-b", Status: StatusAPIKeyDisabled},
+	})
+
+	tooLong := 100
+	_, err := svc.Rotate(context.Background(), 7, 1, &tooLong)
+	require.ErrorIs(t, err, ErrAPIKeyRotationInvalidGrace)
+
+	_, err = svc.Rotate(context.Background(), 8, 1, nil)
+	require.ErrorIs(t, err, ErrInsufficientPerms)
+
+	_, err = svc.Rotate(context.Background(), 7, 2, nil)
+	require.ErrorIs(t, err, ErrAPIKeyRotationNotActive)
+
+	_, err = svc.GetStatus(context.Background(), 7, 1)
+	require.ErrorIs(t, err, ErrAPIKeyRotationNotFound)
+}
@@ -63,6 +63,8 @@ type APIKeyRepository interface {
 
 	// Quota methods
 	IncrementQuotaUsed(ctx context.Context, id int64, amount float64) (float64, error)
+	// AddQuota 原子充值：quota = max(quota, quota_used) + amount，额度耗尽状态恢复为 active；不写 quota_used
+	AddQuota(ctx context.Context, id int64, amount float64) error
 	UpdateLastUsed(ctx context.Context, id int64, usedAt time.Time) error
 }
 
@@ -79,9 +81,11 @@ type APIKeyCache interface {
 	SetAuthCache(ctx context.Context, key string, entry *APIKeyAuthCacheEntry, ttl time.Duration) error
 	DeleteAuthCache(ctx context.Context, key string) error
 
-	// Pub/Sub for L1 cache invalidation across instances
+	// Pub/Sub for L1 cache invalidation across instances.
+	// onResubscribe is called after the subscription is re-established following a
+	// connection loss, since invalidations published in between were not delivered.
 	PublishAuthCacheInvalidation(ctx context.Context, cacheKey string) error
-	SubscribeAuthCacheInvalidation(ctx context.Context, handler func(cacheKey string)) error
+	SubscribeAuthCacheInvalidation(ctx context.Context, handler func(cacheKey string), onResubscribe func()) error
 }
 
 // APIKeyAuthCacheInvalidator 提供认证缓存失效能力
@@ -692,14 +696,19 @@ func (s *APIKeyService) CheckAPIKeyQuotaAndExpiry(apiKey *APIKey) error {
 		return ErrAPIKeyExpired
 	}
 
-	// Check quota
-	if apiKey.IsQuotaExhausted() {
+	// Check quota (dry-run mode only records, see billing.limit_dry_run.api_key_quota)
+	if apiKey.IsQuotaExhausted() && !s.quotaDryRun() {
 		return ErrAPIKeyQuotaExhausted
 	}
 
 	return nil
 }
 
+// quotaDryRun reports whether the API key quota runs in dry-run mode (exhausted keys are not rejected)
+func (s *APIKeyService) quotaDryRun() bool {
+	return s.cfg != nil && s.cfg.Billing.LimitDryRun.APIKeyQuota
+}
+
 // UpdateQuotaUsed updates the quota_used field after a request
 // Also checks if quota is exhausted and updates status accordingly
 func (s *APIKeyService) UpdateQuotaUsed(ctx context.Context, apiKeyID int64, cost float64) error {
@@ -719,8 +728,8 @@ func (s *APIKeyService) UpdateQuotaUsed(ctx context.Context, apiKeyID int64, cos
 		return nil // Don't fail the request, just log
 	}
 
-	// If quota is set and now exhausted, update status
-	if apiKey.Quota > 0 && newQuotaUsed >= apiKey.Quota {
+	// If quota is set and now exhausted, update status (not in quota dry-run mode)
+	if apiKey.Quota > 0 && newQuotaUsed >= apiKey.Quota && !s.quotaDryRun() {
 		apiKey.Status = StatusAPIKeyQuotaExhausted
 		if err := s.apiKeyRepo.Update(ctx, apiKey); err != nil {
 			return nil // Don't fail the request
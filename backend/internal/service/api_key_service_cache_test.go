@@ -103,6 +103,10 @@ func (s *authRepoStub) IncrementQuotaUsed(ctx context.Context, id int64, amount
 	panic("unexpected IncrementQuotaUsed call")
 }
 
+func (s *authRepoStub) AddQuota(ctx context.Context, id int64, amount float64) error {
+	panic("unexpected AddQuota call")
+}
+
 func (s *authRepoStub) UpdateLastUsed(ctx context.Context, id int64, usedAt time.Time) error {
 	panic("unexpected UpdateLastUsed call")
 }
@@ -111,6 +115,7 @@ type authCacheStub struct {
 	getAuthCache   func(ctx context.Context, key string) (*APIKeyAuthCacheEntry, error)
 	setAuthKeys    []string
 	deleteAuthKeys []string
+	subscribe      func(handler func(cacheKey string), onResubscribe func()) error
 }
 
 func (s *authCacheStub) GetCreateAttemptCount(ctx context.Context, userID int64) (int, error) {
@@ -154,8 +159,11 @@ func (s *authCacheStub) PublishAuthCacheInvalidation(ctx context.Context, cacheK
 	return nil
 }
 
-func (s *authCacheStub) SubscribeAuthCacheInvalidation(ctx context.Context, handler func(cacheKey string)) error {
-	return nil
+func (s *authCacheStub) SubscribeAuthCacheInvalidation(ctx context.Context, handler func(cacheKey string), onResubscribe func()) error {
+	if s.subscribe == nil {
+		return nil
+	}
+	return s.subscribe(handler, onResubscribe)
 }
 
 func TestAPIKeyService_GetByKey_UsesL2Cache(t *testing.T) {
@@ -311,6 +319,43 @@ func TestAPIKeyService_GetByKey_UsesL1Cache(t *testing.T) {
 	require.Equal(t, int32(1), atomic.LoadInt32(&calls))
 }
 
+func TestAPIKeyService_AuthCacheSubscriber_ClearsL1OnResubscribe(t *testing.T) {
+	var handler func(cacheKey string)
+	var onResubscribe func()
+	cache := &authCacheStub{
+		subscribe: func(h func(cacheKey string), r func()) error {
+			handler, onResubscribe = h, r
+			return nil
+		},
+	}
+	cfg := &config.Config{
+		APIKeyAuth: config.APIKeyAuthCacheConfig{
+			L1Size:       1000,
+			L1TTLSeconds: 60,
+		},
+	}
+	svc := NewAPIKeyService(&authRepoStub{}, nil, nil, nil, nil, cache, cfg)
+	svc.StartAuthCacheInvalidationSubscriber(context.Background())
+	require.NotNil(t, handler)
+	require.NotNil(t, onResubscribe)
+
+	entry := &APIKeyAuthCacheEntry{NotFound: true}
+	svc.setAuthCacheL1("a", entry)
+	svc.setAuthCacheL1("b", entry)
+	svc.authCacheL1.Wait()
+
+	handler("a")
+	_, ok := svc.authCacheL1.Get("a")
+	require.False(t, ok)
+	_, ok = svc.authCacheL1.Get("b")
+	require.True(t, ok)
+
+	// 断线重连期间可能漏收失效消息，重新订阅后整体清空 L1
+	onResubscribe()
+	_, ok = svc.authCacheL1.Get("b")
+	require.False(t, ok)
+}
+
 func TestAPIKeyService_InvalidateAuthCacheByUserID(t *testing.T) {
 	cache := &authCacheStub{}
 	repo := &authRepoStub{
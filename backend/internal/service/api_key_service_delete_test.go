@@ -125,6 +125,10 @@ func (s *apiKeyRepoStub) IncrementQuotaUsed(ctx context.Context, id int64, amoun
 	panic("unexpected IncrementQuotaUsed call")
 }
 
+func (s *apiKeyRepoStub) AddQuota(ctx context.Context, id int64, amount float64) error {
+	panic("unexpected AddQuota call")
+}
+
 func (s *apiKeyRepoStub) UpdateLastUsed(ctx context.Context, id int64, usedAt time.Time) error {
 	s.touchedIDs = append(s.touchedIDs, id)
 	s.touchedUsedAts = append(s.touchedUsedAts, usedAt)
@@ -188,7 +192,7 @@ func (s *apiKeyCacheStub) PublishAuthCacheInvalidation(ctx context.Context, cach
 	return nil
 }
 
-func (s *apiKeyCacheStub) SubscribeAuthCacheInvalidation(ctx context.Context, handler func(cacheKey string)) error {
+func (s *apiKeyCacheStub) SubscribeAuthCacheInvalidation(ctx context.Context, handler func(cacheKey string), onResubscribe func()) error {
 	return nil
 }
 
@@ -0,0 +1,363 @@
+package service
+
+import (
+	"bytes"
+	"encoding/json"
+	"fmt"
+	"net/http"
+	"path/filepath"
+	"strconv"
+	"strings"
+
+	"github.com/Wei-Shaw/sub2api/internal/pkg/docx"
+	infraerrors "github.com/Wei-Shaw/sub2api/internal/pkg/errors"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/pdf"
+	"github.com/tidwall/gjson"
+	"github.com/tidwall/sjson"
+)
+
+// attachmentDOCXMediaType Word 文档，仅在启用预处理时接受
+const attachmentDOCXMediaType = "application/vnd.openxmlformats-officedocument.wordprocessingml.document"
+
+var ErrAttachmentUnreadable = infraerrors.BadRequest("ATTACHMENT_UNREADABLE", "document could not be read")
+
+// documentSection 文档的一段连续文本：PDF 为一页（超过分块大小时拆分为多段），DOCX 为一个段落
+type documentSection struct {
+	page   int
+	text   string
+	tokens int
+	images []*Attachment
+}
+
+// attachmentPreprocessState 单个请求内所有文档共享的剩余额度
+type attachmentPreprocessState struct {
+	textTokens int
+	images     int
+}
+
+func (s *AttachmentService) preprocessEnabled() bool {
+	return s.Enabled() && s.cfg.Preprocess.Enabled
+}
+
+// shouldPreprocess DOCX 始终提取；PDF 在目标模型未配置为原生转发时提取
+func (s *AttachmentService) shouldPreprocess(model string, att *Attachment) bool {
+	if !s.preprocessEnabled() {
+		return false
+	}
+	switch att.MediaType {
+	case attachmentDOCXMediaType:
+		return true
+	case "application/pdf":
+		for _, pattern := range s.cfg.Preprocess.NativePDFModels {
+			if matchModelPattern(pattern, model) {
+				return false
+			}
+		}
+		return true
+	}
+	return false
+}
+
+// newPreprocessState 按目标模型上下文窗口计算提取文本的 token 额度
+func (s *AttachmentService) newPreprocessState(model string) *attachmentPreprocessState {
+	cfg := s.cfg.Preprocess
+	budget := cfg.MaxTextTokens
+	if limits := s.billing.GetModelTokenLimits(model); limits.MaxInput > 0 {
+		budget = int(float64(limits.MaxInput) * cfg.ContextRatio)
+	}
+	return &attachmentPreprocessState{textTokens: budget, images: cfg.MaxImages}
+}
+
+// preprocessDocument 把 PDF / DOCX 转换为若干文本块（附带页面内图片），超出额度的内容截断并追加说明。
+// 无法解析或没有文本层（扫描件）的 PDF 在模型支持时按原生 PDF 转发。
+func (s *AttachmentService) preprocessDocument(format AttachmentFormat, model string, block gjson.Result, att *Attachment, state *attachmentPreprocessState) ([][]byte, error) {
+	cfg := s.cfg.Preprocess
+	sections, totalPages, err := s.extractDocument(att)
+	sections = splitDocumentSections(sections, cfg.ChunkTokens)
+
+	hasText := false
+	for _, sec := range sections {
+		if sec.tokens > 0 {
+			hasText = true
+			break
+		}
+	}
+	if !hasText && att.MediaType == "application/pdf" && s.checkModel(model, att) == nil {
+		converted, convErr := convertAttachmentBlock(format, block, att)
+		if convErr != nil {
+			return nil, convErr
+		}
+		return [][]byte{converted}, nil
+	}
+	if err != nil {
+		return nil, infraerrors.Newf(http.StatusBadRequest, "ATTACHMENT_UNREADABLE", "failed to read %q: %v", att.Filename, err)
+	}
+
+	included := 0
+	var parts [][]documentSection
+	var part []documentSection
+	partTokens := 0
+	for _, sec := range sections {
+		if sec.tokens > state.textTokens {
+			break
+		}
+		state.textTokens -= sec.tokens
+		if len(part) > 0 && partTokens+sec.tokens > cfg.ChunkTokens {
+			parts = append(parts, part)
+			part, partTokens = nil, 0
+		}
+		part = append(part, sec)
+		partTokens += sec.tokens
+		included++
+	}
+	if len(part) > 0 {
+		parts = append(parts, part)
+	}
+
+	name := att.Filename
+	if name == "" {
+		name = "document"
+	}
+	var blocks [][]byte
+	for i, p := range parts {
+		if text := formatDocumentChunk(name, i+1, len(parts), p); text != "" {
+			blocks = append(blocks, attachmentTextBlock(format, text))
+		}
+		for _, sec := range p {
+			for _, img := range sec.images {
+				if state.images <= 0 || s.checkModel(model, img) != nil {
+					continue
+				}
+				converted, err := convertAttachmentBlock(format, gjson.Parse("{}"), img)
+				if err != nil {
+					return nil, err
+				}
+				blocks = append(blocks, converted)
+				state.images--
+			}
+		}
+	}
+	if note := documentTruncationNote(name, sections, included, totalPages); note != "" {
+		blocks = append(blocks, attachmentTextBlock(format, note))
+	}
+	if len(blocks) == 0 {
+		return nil, infraerrors.Newf(http.StatusBadRequest, "ATTACHMENT_UNREADABLE", "document %q contains no extractable text", name)
+	}
+
+	// 缓存断点保持在该文档内容的末尾
+	if cc := block.Get("cache_control"); format == AttachmentFormatAnthropic && cc.Exists() {
+		last := len(blocks) - 1
+		if blocks[last], err = sjson.SetRawBytes(blocks[last], "cache_control", []byte(cc.Raw)); err != nil {
+			return nil, err
+		}
+	}
+	return blocks, nil
+}
+
+// extractDocument 提取文档各段文本与图片；totalPages 为 PDF 总页数（DOCX 为 0）
+func (s *AttachmentService) extractDocument(att *Attachment) ([]documentSection, int, error) {
+	cfg := s.cfg.Preprocess
+	stem := strings.TrimSuffix(att.Filename, filepath.Ext(att.Filename))
+	if stem == "" {
+		stem = "document"
+	}
+	switch att.MediaType {
+	case "application/pdf":
+		pages, total, err := pdf.Extract(att.Data, pdf.ExtractOptions{MaxPages: cfg.MaxPages, Images: cfg.ExtractImages})
+		if err != nil {
+			return nil, 0, err
+		}
+		sections := make([]documentSection, 0, len(pages))
+		for _, page := range pages {
+			sec := documentSection{page: page.Number, text: page.Text}
+			for i, img := range page.Images {
+				sec.images = append(sec.images, &Attachment{
+					Filename:  fmt.Sprintf("%s-p%d-%d.jpg", stem, page.Number, i+1),
+					MediaType: "image/jpeg",
+					Data:      img.Data,
+				})
+			}
+			sections = append(sections, sec)
+		}
+		return sections, total, nil
+	case attachmentDOCXMediaType:
+		doc, err := docx.Extract(att.Data, cfg.ExtractImages)
+		if err != nil {
+			return nil, 0, err
+		}
+		sections := make([]documentSection, 0, len(doc.Paragraphs)+1)
+		for _, para := range doc.Paragraphs {
+			sections = append(sections, documentSection{text: para})
+		}
+		if len(doc.Images) > 0 {
+			if len(sections) == 0 {
+				sections = append(sections, documentSection{})
+			}
+			last := &sections[len(sections)-1]
+			for _, img := range doc.Images {
+				last.images = append(last.images, &Attachment{Filename: img.Name, MediaType: img.MediaType, Data: img.Data})
+			}
+		}
+		return sections, 0, nil
+	}
+	return nil, 0, fmt.Errorf("unsupported document type %s", att.MediaType)
+}
+
+// splitDocumentSections 估算每段 token 数，超过 limit 的段按行（单行过长时按字符）拆分
+func splitDocumentSections(sections []documentSection, limit int) []documentSection {
+	out := make([]documentSection, 0, len(sections))
+	for _, sec := range sections {
+		sec.tokens = estimateTokensForText(sec.text)
+		if sec.tokens <= limit {
+			out = append(out, sec)
+			continue
+		}
+		for i, piece := range splitTextByTokens(sec.text, limit) {
+			split := documentSection{page: sec.page, text: piece, tokens: estimateTokensForText(piece)}
+			if i == 0 {
+				split.images = sec.images
+			}
+			out = append(out, split)
+		}
+	}
+	return out
+}
+
+func splitTextByTokens(text string, limit int) []string {
+	var pieces []string
+	var cur strings.Builder
+	curTokens := 0
+	flush := func() {
+		if cur.Len() > 0 {
+			pieces = append(pieces, cur.String())
+			cur.Reset()
+			curTokens = 0
+		}
+	}
+	for _, line := range strings.Split(text, "\n") {
+		tokens := estimateTokensForText(line)
+		if tokens > limit {
+			flush()
+			runes := []rune(line)
+			step := max(len(runes)*limit/tokens, 1)
+			for start := 0; start < len(runes); start += step {
+				pieces = append(pieces, string(runes[start:min(start+step, len(runes))]))
+			}
+			continue
+		}
+		if curTokens+tokens > limit {
+			flush()
+		}
+		if cur.Len() > 0 {
+			cur.WriteByte('\n')
+		}
+		cur.WriteString(line)
+		curTokens += tokens
+	}
+	flush()
+	return pieces
+}
+
+// formatDocumentChunk 以 <document> 标签包裹分块文本，标注文件名、分块序号与页码范围
+func formatDocumentChunk(name string, index, count int, sections []documentSection) string {
+	var text strings.Builder
+	for _, sec := range sections {
+		if sec.text == "" {
+			continue
+		}
+		if text.Len() > 0 {
+			text.WriteString("\n\n")
+		}
+		text.WriteString(sec.text)
+	}
+	if text.Len() == 0 {
+		return ""
+	}
+
+	var b strings.Builder
+	fmt.Fprintf(&b, "<document name=%s part=\"%d/%d\"", strconv.Quote(name), index, count)
+	if first, last := sections[0].page, sections[len(sections)-1].page; first > 0 {
+		if first == last {
+			fmt.Fprintf(&b, " page=\"%d\"", first)
+		} else {
+			fmt.Fprintf(&b, " pages=\"%d-%d\"", first, last)
+		}
+	}
+	b.WriteString(">\n")
+	b.WriteString(text.String())
+	b.WriteString("\n</document>")
+	return b.String()
+}
+
+// documentTruncationNote 说明被截断或未处理的内容，便于模型在回答中提示用户
+func documentTruncationNote(name string, sections []documentSection, included, totalPages int) string {
+	var notes []string
+	if included < len(sections) {
+		omitted, all := 0, 0
+		for i, sec := range sections {
+			all += sec.tokens
+			if i >= included {
+				omitted += sec.tokens
+			}
+		}
+		if omitted > 0 {
+			where := ""
+			if page := sections[included].page; page > 0 {
+				where = fmt.Sprintf(" from page %d", page)
+			}
+			notes = append(notes, fmt.Sprintf("content%s (about %d%% of the extracted text) was omitted to fit the model context window", where, omitted*100/max(all, 1)))
+		}
+	}
+	if n := len(sections); n > 0 && totalPages > sections[n-1].page {
+		notes = append(notes, fmt.Sprintf("pages %d-%d were not processed", sections[n-1].page+1, totalPages))
+	}
+	if len(notes) == 0 {
+		return ""
+	}
+	return fmt.Sprintf("[%s truncated: %s]", name, strings.Join(notes, "; "))
+}
+
+func attachmentTextBlock(format AttachmentFormat, text string) []byte {
+	blockType := "text"
+	if format == AttachmentFormatOpenAIResponses {
+		blockType = "input_text"
+	}
+	out, _ := json.Marshal(map[string]string{"type": blockType, "text": text})
+	return out
+}
+
+// spliceAttachmentBlocks 用多个内容块替换数组中 path 指向的元素
+func spliceAttachmentBlocks(body []byte, path string, blocks [][]byte) ([]byte, error) {
+	dot := strings.LastIndexByte(path, '.')
+	if dot < 0 {
+		return body, fmt.Errorf("invalid content path %s", path)
+	}
+	parent := path[:dot]
+	index, err := strconv.Atoi(path[dot+1:])
+	if err != nil {
+		return body, fmt.Errorf("invalid content path %s", path)
+	}
+
+	var buf bytes.Buffer
+	buf.WriteByte('[')
+	write := func(raw []byte) {
+		if buf.Len() > 1 {
+			buf.WriteByte(',')
+		}
+		buf.Write(raw)
+	}
+	i := 0
+	gjson.GetBytes(body, parent).ForEach(func(_, item gjson.Result) bool {
+		if i == index {
+			for _, block := range blocks {
+				write(block)
+			}
+		} else {
+			write([]byte(item.Raw))
+		}
+		i++
+		return true
+	})
+	buf.WriteByte(']')
+	return sjson.SetRawBytes(body, parent, buf.Bytes())
+}
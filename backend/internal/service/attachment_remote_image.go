@@ -0,0 +1,102 @@
+package service
+
+import (
+	"context"
+	"errors"
+	"net/http"
+	"path"
+	"strings"
+	"time"
+
+	infraerrors "github.com/Wei-Shaw/sub2api/internal/pkg/errors"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/logger"
+	"github.com/Wei-Shaw/sub2api/internal/util/urlvalidator"
+)
+
+var (
+	ErrRemoteImageFetchFailed = infraerrors.BadRequest("REMOTE_IMAGE_FETCH_FAILED", "failed to fetch image url")
+	ErrRemoteImageTooMany     = infraerrors.BadRequest("REMOTE_IMAGE_TOO_MANY", "too many image urls in one request")
+)
+
+// RemoteImageFetcher 下载远程图片。
+//
+// 实现需在连接层拒绝解析到内网、回环、链路本地等保留地址的目标（包括重定向），
+// 响应不是图片时返回 ErrAttachmentUnsupportedType，超过 maxBytes 时返回 ErrAttachmentTooLarge。
+type RemoteImageFetcher interface {
+	Fetch(ctx context.Context, rawURL string, maxBytes int64) (*Attachment, error)
+}
+
+// RemoteImageCache 远程图片短期缓存（按 URL）
+type RemoteImageCache interface {
+	// Get 未命中时返回 nil, nil
+	Get(ctx context.Context, rawURL string) (*Attachment, error)
+	Set(ctx context.Context, rawURL string, att *Attachment, ttl time.Duration) error
+}
+
+func (s *AttachmentService) remoteImagesEnabled() bool {
+	return s.Enabled() && s.cfg.RemoteImages.Enabled && s.fetcher != nil
+}
+
+func isRemoteImageURL(raw string) bool {
+	return strings.HasPrefix(raw, "https://") || strings.HasPrefix(raw, "http://")
+}
+
+func (s *AttachmentService) checkRemoteImageCount(refs []attachmentRef) error {
+	count := 0
+	for _, ref := range refs {
+		if ref.imageURL != "" {
+			count++
+		}
+	}
+	if limit := s.cfg.RemoteImages.MaxImagesPerRequest; limit > 0 && count > limit {
+		return ErrRemoteImageTooMany
+	}
+	return nil
+}
+
+// fetchRemoteImage 校验 URL（scheme、白名单、字面量内网地址）后下载图片，结果短期缓存
+func (s *AttachmentService) fetchRemoteImage(ctx context.Context, rawURL string) (*Attachment, error) {
+	cfg := s.cfg.RemoteImages
+	if _, err := urlvalidator.ValidateHTTPURL(rawURL, cfg.AllowInsecureHTTP, urlvalidator.ValidationOptions{AllowedHosts: cfg.AllowedHosts}); err != nil {
+		return nil, infraerrors.Newf(http.StatusBadRequest, "REMOTE_IMAGE_FETCH_FAILED", "image url is not allowed: %v", err)
+	}
+	if s.imageCache != nil && cfg.CacheTTLSeconds > 0 {
+		cached, err := s.imageCache.Get(ctx, rawURL)
+		if err != nil {
+			logger.LegacyPrintf("service.attachment", "[Attachment] read image cache failed: %v", err)
+		}
+		if cached != nil {
+			return cached, nil
+		}
+	}
+
+	fetchCtx, cancel := context.WithTimeout(ctx, time.Duration(cfg.TimeoutSeconds)*time.Second)
+	defer cancel()
+	att, err := s.fetcher.Fetch(fetchCtx, rawURL, cfg.MaxBytes)
+	if err != nil {
+		switch {
+		case errors.Is(err, ErrAttachmentTooLarge):
+			return nil, infraerrors.Newf(http.StatusRequestEntityTooLarge, "ATTACHMENT_TOO_LARGE", "image %s exceeds the %d byte limit", rawURL, cfg.MaxBytes)
+		case errors.Is(err, ErrAttachmentUnsupportedType):
+			return nil, infraerrors.Newf(http.StatusBadRequest, "ATTACHMENT_UNSUPPORTED_TYPE", "url %s did not return a supported image", rawURL)
+		case ctx.Err() != nil:
+			return nil, ctx.Err()
+		default:
+			return nil, infraerrors.Newf(http.StatusBadRequest, "REMOTE_IMAGE_FETCH_FAILED", "failed to fetch image %s: %v", rawURL, err)
+		}
+	}
+	if att.Filename == "" {
+		att.Filename = path.Base(strings.SplitN(rawURL, "?", 2)[0])
+	}
+	att.MediaType = detectAttachmentMediaType(att.Filename, att.MediaType, att.Data)
+	if !strings.HasPrefix(att.MediaType, "image/") || !attachmentMediaTypes[att.MediaType] {
+		return nil, infraerrors.Newf(http.StatusBadRequest, "ATTACHMENT_UNSUPPORTED_TYPE", "url %s did not return a supported image", rawURL)
+	}
+
+	if s.imageCache != nil && cfg.CacheTTLSeconds > 0 {
+		if err := s.imageCache.Set(ctx, rawURL, att, time.Duration(cfg.CacheTTLSeconds)*time.Second); err != nil {
+			logger.LegacyPrintf("service.attachment", "[Attachment] write image cache failed: %v", err)
+		}
+	}
+	return att, nil
+}
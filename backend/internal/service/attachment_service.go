@@ -0,0 +1,671 @@
+package service
+
+import (
+	"bytes"
+	"context"
+	"crypto/sha256"
+	"encoding/base64"
+	"encoding/hex"
+	"errors"
+	"fmt"
+	"io"
+	"mime"
+	"mime/multipart"
+	"net/http"
+	"path/filepath"
+	"strconv"
+	"strings"
+	"time"
+	"unicode/utf8"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	infraerrors "github.com/Wei-Shaw/sub2api/internal/pkg/errors"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/logger"
+	"github.com/google/uuid"
+	"github.com/tidwall/gjson"
+	"github.com/tidwall/sjson"
+)
+
+const (
+	// AttachmentFileIDPrefix 网关文件 ID 前缀，与上游原生文件 ID（file_...）区分，后者原样透传
+	AttachmentFileIDPrefix = "gwfile_"
+	// AttachmentMultipartRefPrefix 引用同一 multipart 请求中上传的文件，如 multipart:report
+	AttachmentMultipartRefPrefix = "multipart:"
+	// AttachmentRequestField multipart 请求中承载 JSON 请求体的字段名
+	AttachmentRequestField = "request"
+
+	attachmentPurgeBatchSize = 500
+	attachmentFilenameMaxLen = 255
+)
+
+// AttachmentFormat 请求协议，决定文件转换后的内容块格式
+type AttachmentFormat string
+
+const (
+	AttachmentFormatAnthropic       AttachmentFormat = "anthropic"
+	AttachmentFormatOpenAIResponses AttachmentFormat = "openai_responses"
+)
+
+var (
+	ErrAttachmentDisabled         = infraerrors.BadRequest("ATTACHMENT_DISABLED", "file attachments are not enabled")
+	ErrAttachmentStorageDisabled  = infraerrors.ServiceUnavailable("ATTACHMENT_STORAGE_DISABLED", "file uploads require object storage to be configured")
+	ErrAttachmentNotFound         = infraerrors.NotFound("ATTACHMENT_NOT_FOUND", "file not found")
+	ErrAttachmentEmpty            = infraerrors.BadRequest("ATTACHMENT_EMPTY", "file is empty")
+	ErrAttachmentInvalidMultipart = infraerrors.BadRequest("ATTACHMENT_INVALID_MULTIPART", "multipart request must contain the JSON request body in the \"request\" field")
+	ErrAttachmentUnsupportedType  = infraerrors.BadRequest("ATTACHMENT_UNSUPPORTED_TYPE", "file type is not supported")
+	ErrAttachmentTooLarge         = infraerrors.New(http.StatusRequestEntityTooLarge, "ATTACHMENT_TOO_LARGE", "file is too large")
+	ErrAttachmentRequestTooLarge  = infraerrors.New(http.StatusRequestEntityTooLarge, "ATTACHMENT_REQUEST_TOO_LARGE", "total size of attached files exceeds the limit")
+)
+
+// attachmentMediaTypes 可转换为上游内容块的媒体类型
+var attachmentMediaTypes = map[string]bool{
+	"image/jpeg":      true,
+	"image/png":       true,
+	"image/gif":       true,
+	"image/webp":      true,
+	"application/pdf": true,
+	"text/plain":      true,
+}
+
+var attachmentExtensionTypes = map[string]string{
+	".jpg":  "image/jpeg",
+	".jpeg": "image/jpeg",
+	".png":  "image/png",
+	".gif":  "image/gif",
+	".webp": "image/webp",
+	".pdf":  "application/pdf",
+	".txt":  "text/plain",
+	".md":   "text/plain",
+	".csv":  "text/plain",
+	".docx": attachmentDOCXMediaType,
+}
+
+var attachmentOpenAIModels = []string{"gpt-*", "o1*", "o3*", "o4*", "codex-*"}
+
+// defaultAttachmentRules 未配置 attachments.rules 时使用的上游官方限制
+var defaultAttachmentRules = []config.AttachmentRule{
+	{Models: []string{"claude-*"}, MediaTypes: []string{"image/*"}, MaxFileBytes: 5 << 20},
+	{Models: []string{"claude-*"}, MediaTypes: []string{"application/pdf", "text/plain"}, MaxFileBytes: 32 << 20},
+	{Models: attachmentOpenAIModels, MediaTypes: []string{"image/*"}, MaxFileBytes: 20 << 20},
+	{Models: attachmentOpenAIModels, MediaTypes: []string{"application/pdf"}, MaxFileBytes: 32 << 20},
+}
+
+// GatewayFile 通过 /v1/files 上传的文件，内容存储在对象存储
+type GatewayFile struct {
+	ID        string    `json:"id"`
+	UserID    int64     `json:"-"`
+	APIKeyID  *int64    `json:"api_key_id,omitempty"`
+	Filename  string    `json:"filename"`
+	MediaType string    `json:"media_type"`
+	SizeBytes int64     `json:"bytes"`
+	SHA256    string    `json:"sha256"`
+	ObjectKey string    `json:"-"`
+	CreatedAt time.Time `json:"created_at"`
+	ExpiresAt time.Time `json:"expires_at"`
+}
+
+// Attachment 待内联的文件内容
+type Attachment struct {
+	Filename  string
+	MediaType string
+	Data      []byte
+}
+
+// GatewayFileRepository 网关文件元数据存储
+type GatewayFileRepository interface {
+	Create(ctx context.Context, file *GatewayFile) error
+	// Get 查询未过期的文件，不存在时返回 ErrAttachmentNotFound
+	Get(ctx context.Context, userID int64, id string) (*GatewayFile, error)
+	// List 按上传时间倒序返回用户未过期的文件
+	List(ctx context.Context, userID int64, limit int) ([]*GatewayFile, error)
+	// Delete 删除文件记录并返回对象 key，不存在时返回 ErrAttachmentNotFound
+	Delete(ctx context.Context, userID int64, id string) (string, error)
+	// DeleteExpired 删除最多 limit 条过期记录并返回其对象 key
+	DeleteExpired(ctx context.Context, limit int) ([]string, error)
+}
+
+// AttachmentService 多模态文件附件：管理 /v1/files 上传的文件，并在转发前把消息中的文件引用
+// （网关文件 ID、同请求 multipart 上传的文件或远程图片 URL）替换为上游要求的内联 base64 内容块，
+// 同时按模型校验文件类型与大小；启用预处理时 PDF / DOCX 转换为分块文本。
+type AttachmentService struct {
+	repo       GatewayFileRepository
+	storage    ObjectStorage
+	fetcher    RemoteImageFetcher
+	imageCache RemoteImageCache
+	billing    *BillingService
+	cfg        config.AttachmentsConfig
+	now        func() time.Time
+}
+
+// NewAttachmentService 创建附件服务；storage 为 nil 时仅支持 multipart 内联上传
+func NewAttachmentService(
+	repo GatewayFileRepository,
+	storage ObjectStorage,
+	fetcher RemoteImageFetcher,
+	imageCache RemoteImageCache,
+	billing *BillingService,
+	cfg *config.Config,
+) *AttachmentService {
+	return &AttachmentService{
+		repo:       repo,
+		storage:    storage,
+		fetcher:    fetcher,
+		imageCache: imageCache,
+		billing:    billing,
+		cfg:        cfg.Attachments,
+		now:        time.Now,
+	}
+}
+
+// Enabled 是否启用附件功能
+func (s *AttachmentService) Enabled() bool {
+	return s != nil && s.cfg.Enabled
+}
+
+// Upload 上传文件到对象存储并记录元数据；mediaType 为空或不可信时按扩展名与内容识别
+func (s *AttachmentService) Upload(ctx context.Context, userID int64, apiKeyID *int64, filename, mediaType string, r io.Reader) (*GatewayFile, error) {
+	if !s.Enabled() {
+		return nil, ErrAttachmentDisabled
+	}
+	if s.storage == nil || s.repo == nil {
+		return nil, ErrAttachmentStorageDisabled
+	}
+	data, err := io.ReadAll(io.LimitReader(r, s.cfg.MaxFileBytes+1))
+	if err != nil {
+		return nil, fmt.Errorf("read upload: %w", err)
+	}
+	att := newAttachment(filename, mediaType, data)
+	if err := s.checkFile(att, s.cfg.MaxFileBytes); err != nil {
+		return nil, err
+	}
+
+	sum := sha256.Sum256(data)
+	now := s.now()
+	file := &GatewayFile{
+		ID:        AttachmentFileIDPrefix + strings.ReplaceAll(uuid.NewString(), "-", ""),
+		UserID:    userID,
+		APIKeyID:  apiKeyID,
+		Filename:  att.Filename,
+		MediaType: att.MediaType,
+		SizeBytes: int64(len(data)),
+		SHA256:    hex.EncodeToString(sum[:]),
+		CreatedAt: now,
+		ExpiresAt: now.Add(time.Duration(s.cfg.TTLHours) * time.Hour),
+	}
+	file.ObjectKey = "attachments/" + strconv.FormatInt(userID, 10) + "/" + file.ID
+	w := s.storage.NewWriter(ctx, file.ObjectKey, file.MediaType)
+	if _, err := w.Write(data); err != nil {
+		w.Abort()
+		return nil, fmt.Errorf("upload attachment: %w", err)
+	}
+	if err := w.Close(); err != nil {
+		return nil, fmt.Errorf("upload attachment: %w", err)
+	}
+	if err := s.repo.Create(ctx, file); err != nil {
+		_ = s.storage.Delete(ctx, file.ObjectKey)
+		return nil, err
+	}
+	return file, nil
+}
+
+// Get 查询用户的文件
+func (s *AttachmentService) Get(ctx context.Context, userID int64, id string) (*GatewayFile, error) {
+	if !s.Enabled() {
+		return nil, ErrAttachmentDisabled
+	}
+	if s.storage == nil || s.repo == nil {
+		return nil, ErrAttachmentStorageDisabled
+	}
+	return s.repo.Get(ctx, userID, id)
+}
+
+// List 按上传时间倒序列出用户的文件（默认 20 条，最多 100 条）
+func (s *AttachmentService) List(ctx context.Context, userID int64, limit int) ([]*GatewayFile, error) {
+	if !s.Enabled() {
+		return nil, ErrAttachmentDisabled
+	}
+	if s.storage == nil || s.repo == nil {
+		return nil, ErrAttachmentStorageDisabled
+	}
+	if limit <= 0 {
+		limit = 20
+	}
+	if limit > 100 {
+		limit = 100
+	}
+	return s.repo.List(ctx, userID, limit)
+}
+
+// Delete 删除用户的文件及其对象
+func (s *AttachmentService) Delete(ctx context.Context, userID int64, id string) error {
+	if !s.Enabled() {
+		return ErrAttachmentDisabled
+	}
+	if s.storage == nil || s.repo == nil {
+		return ErrAttachmentStorageDisabled
+	}
+	key, err := s.repo.Delete(ctx, userID, id)
+	if err != nil {
+		return err
+	}
+	if err := s.storage.Delete(ctx, key); err != nil {
+		logger.LegacyPrintf("service.attachment", "[Attachment] delete object %s failed: %v", key, err)
+	}
+	return nil
+}
+
+// PurgeExpired 清除过期文件（定时任务）
+func (s *AttachmentService) PurgeExpired(ctx context.Context) error {
+	if !s.Enabled() || s.storage == nil || s.repo == nil {
+		return nil
+	}
+	purged := 0
+	for {
+		keys, err := s.repo.DeleteExpired(ctx, attachmentPurgeBatchSize)
+		if err != nil {
+			return err
+		}
+		for _, key := range keys {
+			if err := s.storage.Delete(ctx, key); err != nil {
+				logger.LegacyPrintf("service.attachment", "[Attachment] delete object %s failed: %v", key, err)
+			}
+		}
+		purged += len(keys)
+		if len(keys) < attachmentPurgeBatchSize {
+			break
+		}
+	}
+	if purged > 0 {
+		logger.LegacyPrintf("service.attachment", "[Attachment] purged %d expired files", purged)
+	}
+	return nil
+}
+
+// ParseMultipart 解析 multipart/form-data 请求：request 字段为 JSON 请求体，
+// 其余带文件名的字段为随请求上传的文件（按字段名引用）。
+func (s *AttachmentService) ParseMultipart(contentType string, body []byte) ([]byte, map[string]*Attachment, error) {
+	if !s.Enabled() {
+		return nil, nil, ErrAttachmentDisabled
+	}
+	_, params, err := mime.ParseMediaType(contentType)
+	if err != nil || params["boundary"] == "" {
+		return nil, nil, ErrAttachmentInvalidMultipart
+	}
+	reader := multipart.NewReader(bytes.NewReader(body), params["boundary"])
+	var payload []byte
+	uploads := make(map[string]*Attachment)
+	for {
+		part, err := reader.NextPart()
+		if errors.Is(err, io.EOF) {
+			break
+		}
+		if err != nil {
+			return nil, nil, ErrAttachmentInvalidMultipart.WithCause(err)
+		}
+		name := part.FormName()
+		data, err := io.ReadAll(part)
+		_ = part.Close()
+		if err != nil {
+			return nil, nil, ErrAttachmentInvalidMultipart.WithCause(err)
+		}
+		switch {
+		case name == AttachmentRequestField:
+			payload = data
+		case name != "" && part.FileName() != "":
+			uploads[name] = newAttachment(part.FileName(), part.Header.Get("Content-Type"), data)
+		}
+	}
+	if len(payload) == 0 || !gjson.ValidBytes(payload) {
+		return nil, nil, ErrAttachmentInvalidMultipart
+	}
+	return payload, uploads, nil
+}
+
+// Resolve 把请求体中引用的网关文件（gwfile_...）、multipart 上传文件（multipart:<字段名>）
+// 以及启用抓取时的远程图片 URL 替换为上游要求的内联内容块，并按模型校验类型与大小；
+// 需要预处理的文档替换为多个文本块。未引用附件时原样返回。
+func (s *AttachmentService) Resolve(ctx context.Context, format AttachmentFormat, userID int64, body []byte, uploads map[string]*Attachment) ([]byte, error) {
+	remote := s.remoteImagesEnabled() &&
+		(bytes.Contains(body, []byte("https://")) || bytes.Contains(body, []byte("http://")))
+	if len(uploads) == 0 && !remote &&
+		!bytes.Contains(body, []byte(AttachmentFileIDPrefix)) &&
+		!bytes.Contains(body, []byte(AttachmentMultipartRefPrefix)) {
+		return body, nil
+	}
+	refs := collectAttachmentRefs(format, body, remote)
+	if len(refs) == 0 {
+		return body, nil
+	}
+	if !s.Enabled() {
+		return body, ErrAttachmentDisabled
+	}
+	if err := s.checkRemoteImageCount(refs); err != nil {
+		return body, err
+	}
+
+	model := gjson.GetBytes(body, "model").String()
+	loaded := make(map[string]*Attachment, len(refs))
+	replacements := make([][][]byte, len(refs))
+	var preprocess *attachmentPreprocessState
+	var total int64
+	for i, ref := range refs {
+		att, err := s.load(ctx, userID, ref, uploads, loaded)
+		if err != nil {
+			return body, err
+		}
+		total += int64(len(att.Data))
+		if total > s.cfg.MaxRequestBytes {
+			return body, ErrAttachmentRequestTooLarge
+		}
+		if s.shouldPreprocess(model, att) {
+			if preprocess == nil {
+				preprocess = s.newPreprocessState(model)
+			}
+			if replacements[i], err = s.preprocessDocument(format, model, ref.block, att, preprocess); err != nil {
+				return body, err
+			}
+			continue
+		}
+		if err := s.checkModel(model, att); err != nil {
+			return body, err
+		}
+		block, err := convertAttachmentBlock(format, ref.block, att)
+		if err != nil {
+			return body, err
+		}
+		replacements[i] = [][]byte{block}
+	}
+
+	// 逆序替换：一个块展开为多个块时不影响前面引用的数组下标
+	var err error
+	for i := len(refs) - 1; i >= 0; i-- {
+		if len(replacements[i]) == 1 {
+			body, err = sjson.SetRawBytes(body, refs[i].path, replacements[i][0])
+		} else {
+			body, err = spliceAttachmentBlocks(body, refs[i].path, replacements[i])
+		}
+		if err != nil {
+			return body, err
+		}
+	}
+	return body, nil
+}
+
+func (s *AttachmentService) load(ctx context.Context, userID int64, ref attachmentRef, uploads, loaded map[string]*Attachment) (*Attachment, error) {
+	id := ref.fileID
+	if ref.imageURL != "" {
+		id = "url:" + ref.imageURL
+	}
+	if att, ok := loaded[id]; ok {
+		return att, nil
+	}
+	var att *Attachment
+	if ref.imageURL != "" {
+		fetched, err := s.fetchRemoteImage(ctx, ref.imageURL)
+		if err != nil {
+			return nil, err
+		}
+		att = fetched
+	} else if name, ok := strings.CutPrefix(id, AttachmentMultipartRefPrefix); ok {
+		if att = uploads[name]; att == nil {
+			return nil, infraerrors.Newf(http.StatusNotFound, "ATTACHMENT_NOT_FOUND", "multipart field %q not found", name)
+		}
+	} else {
+		file, err := s.Get(ctx, userID, id)
+		if errors.Is(err, ErrAttachmentNotFound) {
+			return nil, infraerrors.Newf(http.StatusNotFound, "ATTACHMENT_NOT_FOUND", "file %s not found", id)
+		}
+		if err != nil {
+			return nil, err
+		}
+		obj, err := s.storage.Get(ctx, file.ObjectKey)
+		if err != nil {
+			return nil, fmt.Errorf("load attachment %s: %w", id, err)
+		}
+		data, err := io.ReadAll(obj.Body)
+		_ = obj.Body.Close()
+		if err != nil {
+			return nil, fmt.Errorf("load attachment %s: %w", id, err)
+		}
+		att = &Attachment{Filename: file.Filename, MediaType: file.MediaType, Data: data}
+	}
+	if err := s.checkFile(att, s.cfg.MaxFileBytes); err != nil {
+		return nil, err
+	}
+	loaded[id] = att
+	return att, nil
+}
+
+// checkFile 校验与模型无关的限制：非空、可转换的类型、全局大小上限
+func (s *AttachmentService) checkFile(att *Attachment, maxBytes int64) error {
+	if len(att.Data) == 0 {
+		return ErrAttachmentEmpty
+	}
+	supported := attachmentMediaTypes[att.MediaType] || (att.MediaType == attachmentDOCXMediaType && s.preprocessEnabled())
+	if !supported || (att.MediaType == "text/plain" && !utf8.Valid(att.Data)) {
+		return infraerrors.Newf(http.StatusBadRequest, "ATTACHMENT_UNSUPPORTED_TYPE", "file %q has unsupported type %s", att.Filename, att.MediaType)
+	}
+	if int64(len(att.Data)) > maxBytes {
+		return infraerrors.Newf(http.StatusRequestEntityTooLarge, "ATTACHMENT_TOO_LARGE", "file %q exceeds the %d byte limit", att.Filename, maxBytes)
+	}
+	return nil
+}
+
+// checkModel 按第一条同时匹配模型与类型的规则校验；没有匹配规则时拒绝
+func (s *AttachmentService) checkModel(model string, att *Attachment) error {
+	rules := s.cfg.Rules
+	if len(rules) == 0 {
+		rules = defaultAttachmentRules
+	}
+	for _, rule := range rules {
+		if !attachmentRuleMatches(rule, model, att.MediaType) {
+			continue
+		}
+		maxBytes := rule.MaxFileBytes
+		if maxBytes <= 0 || maxBytes > s.cfg.MaxFileBytes {
+			maxBytes = s.cfg.MaxFileBytes
+		}
+		if int64(len(att.Data)) > maxBytes {
+			return infraerrors.Newf(http.StatusRequestEntityTooLarge, "ATTACHMENT_TOO_LARGE", "file %q exceeds the %d byte limit for model %s", att.Filename, maxBytes, model)
+		}
+		return nil
+	}
+	return infraerrors.Newf(http.StatusBadRequest, "ATTACHMENT_UNSUPPORTED_TYPE", "file type %s is not supported for model %s", att.MediaType, model)
+}
+
+func attachmentRuleMatches(rule config.AttachmentRule, model, mediaType string) bool {
+	typeMatched := false
+	for _, allowed := range rule.MediaTypes {
+		allowed = strings.ToLower(strings.TrimSpace(allowed))
+		if allowed == mediaType || (strings.HasSuffix(allowed, "/*") && strings.HasPrefix(mediaType, strings.TrimSuffix(allowed, "*"))) {
+			typeMatched = true
+			break
+		}
+	}
+	if !typeMatched {
+		return false
+	}
+	if len(rule.Models) == 0 {
+		return true
+	}
+	for _, pattern := range rule.Models {
+		if matchModelPattern(pattern, model) {
+			return true
+		}
+	}
+	return false
+}
+
+func newAttachment(filename, mediaType string, data []byte) *Attachment {
+	name := strings.TrimSpace(filepath.Base(strings.ReplaceAll(filename, "\\", "/")))
+	if name == "." || name == "/" {
+		name = ""
+	}
+	if len(name) > attachmentFilenameMaxLen {
+		name = strings.ToValidUTF8(name[:attachmentFilenameMaxLen], "")
+	}
+	return &Attachment{Filename: name, MediaType: detectAttachmentMediaType(name, mediaType, data), Data: data}
+}
+
+// detectAttachmentMediaType 以内容识别为准（上游会拒绝与实际内容不符的 media_type），
+// 识别不出时依次使用声明的类型与扩展名
+func detectAttachmentMediaType(filename, declared string, data []byte) string {
+	sniffed, _, _ := mime.ParseMediaType(http.DetectContentType(data))
+	if attachmentMediaTypes[sniffed] && sniffed != "text/plain" {
+		return sniffed
+	}
+	mediaType, _, _ := mime.ParseMediaType(declared)
+	mediaType = strings.ToLower(mediaType)
+	if mediaType == "image/jpg" {
+		mediaType = "image/jpeg"
+	}
+	if mediaType == "" || mediaType == "application/octet-stream" {
+		if byExt, ok := attachmentExtensionTypes[strings.ToLower(filepath.Ext(filename))]; ok {
+			mediaType = byExt
+		}
+	}
+	if mediaType == "" || mediaType == "application/octet-stream" {
+		mediaType = sniffed
+	}
+	// text/markdown、text/csv 等纯文本统一按 text/plain 转发
+	if strings.HasPrefix(mediaType, "text/") && mediaType != "text/html" {
+		mediaType = "text/plain"
+	}
+	return mediaType
+}
+
+type attachmentRef struct {
+	path     string
+	fileID   string
+	imageURL string
+	block    gjson.Result
+}
+
+// collectAttachmentRefs 查找引用附件的内容块：Anthropic 为 messages[].content[]（含 tool_result 内嵌内容），
+// OpenAI Responses 为 input[].content[]；remote 为 true 时同时收集远程图片 URL
+func collectAttachmentRefs(format AttachmentFormat, body []byte, remote bool) []attachmentRef {
+	var refs []attachmentRef
+	var visit func(path string, blocks gjson.Result)
+	visit = func(path string, blocks gjson.Result) {
+		if !blocks.IsArray() {
+			return
+		}
+		blocks.ForEach(func(key, block gjson.Result) bool {
+			blockPath := path + "." + key.String()
+			if id, imageURL := attachmentBlockRef(format, block, remote); id != "" || imageURL != "" {
+				refs = append(refs, attachmentRef{path: blockPath, fileID: id, imageURL: imageURL, block: block})
+			} else if format == AttachmentFormatAnthropic && block.Get("type").String() == "tool_result" {
+				visit(blockPath+".content", block.Get("content"))
+			}
+			return true
+		})
+	}
+	root := "messages"
+	if format == AttachmentFormatOpenAIResponses {
+		root = "input"
+	}
+	items := gjson.GetBytes(body, root)
+	if !items.IsArray() {
+		return nil
+	}
+	items.ForEach(func(key, item gjson.Result) bool {
+		visit(root+"."+key.String()+".content", item.Get("content"))
+		return true
+	})
+	return refs
+}
+
+// attachmentBlockRef 返回内容块引用的网关文件 ID 或远程图片 URL（remote 为 false 时不识别 URL）
+func attachmentBlockRef(format AttachmentFormat, block gjson.Result, remote bool) (id, imageURL string) {
+	blockType := block.Get("type").String()
+	switch format {
+	case AttachmentFormatAnthropic:
+		switch blockType {
+		case "image", "document":
+			switch block.Get("source.type").String() {
+			case "file":
+				id = block.Get("source.file_id").String()
+			case "url":
+				if remote && blockType == "image" {
+					imageURL = block.Get("source.url").String()
+				}
+			}
+		case "file":
+			id = block.Get("file_id").String()
+		}
+	case AttachmentFormatOpenAIResponses:
+		switch blockType {
+		case "input_image":
+			id = block.Get("file_id").String()
+			if remote {
+				imageURL = block.Get("image_url").String()
+			}
+		case "input_file", "file":
+			id = block.Get("file_id").String()
+		}
+	}
+	if !strings.HasPrefix(id, AttachmentFileIDPrefix) && !strings.HasPrefix(id, AttachmentMultipartRefPrefix) {
+		id = ""
+	}
+	if id != "" || !isRemoteImageURL(imageURL) {
+		imageURL = ""
+	}
+	return id, imageURL
+}
+
+// convertAttachmentBlock 按文件类型生成内联内容块，保留原块的其余字段（如 cache_control、detail）
+func convertAttachmentBlock(format AttachmentFormat, block gjson.Result, att *Attachment) ([]byte, error) {
+	out := []byte(block.Raw)
+	var err error
+	set := func(path string, value any) {
+		if err == nil {
+			out, err = sjson.SetBytes(out, path, value)
+		}
+	}
+	del := func(path string) {
+		if err == nil {
+			out, err = sjson.DeleteBytes(out, path)
+		}
+	}
+	encoded := base64.StdEncoding.EncodeToString(att.Data)
+	isImage := strings.HasPrefix(att.MediaType, "image/")
+
+	del("file_id")
+	switch format {
+	case AttachmentFormatAnthropic:
+		switch {
+		case isImage:
+			del("title")
+			del("context")
+			del("citations")
+			set("type", "image")
+			set("source", map[string]string{"type": "base64", "media_type": att.MediaType, "data": encoded})
+		case att.MediaType == "text/plain":
+			set("type", "document")
+			set("source", map[string]string{"type": "text", "media_type": att.MediaType, "data": string(att.Data)})
+		default:
+			set("type", "document")
+			set("source", map[string]string{"type": "base64", "media_type": att.MediaType, "data": encoded})
+		}
+		if !isImage && !block.Get("title").Exists() && att.Filename != "" {
+			set("title", att.Filename)
+		}
+	case AttachmentFormatOpenAIResponses:
+		dataURL := "data:" + att.MediaType + ";base64," + encoded
+		if isImage {
+			del("filename")
+			set("type", "input_image")
+			set("image_url", dataURL)
+		} else {
+			filename := att.Filename
+			if filename == "" {
+				filename = "file"
+			}
+			del("detail")
+			set("type", "input_file")
+			set("filename", filename)
+			set("file_data", dataURL)
+		}
+	}
+	return out, err
+}
@@ -0,0 +1,378 @@
+//go:build unit
+
+package service
+
+import (
+	"archive/zip"
+	"bytes"
+	"context"
+	"encoding/base64"
+	"errors"
+	"fmt"
+	"mime/multipart"
+	"net/textproto"
+	"strings"
+	"testing"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/pdf"
+	"github.com/stretchr/testify/require"
+	"github.com/tidwall/gjson"
+)
+
+type gatewayFileRepoStub struct {
+	files map[string]*GatewayFile
+}
+
+func (r *gatewayFileRepoStub) Create(_ context.Context, file *GatewayFile) error {
+	cp := *file
+	r.files[file.ID] = &cp
+	return nil
+}
+
+func (r *gatewayFileRepoStub) Get(_ context.Context, userID int64, id string) (*GatewayFile, error) {
+	file, ok := r.files[id]
+	if !ok || file.UserID != userID {
+		return nil, ErrAttachmentNotFound
+	}
+	cp := *file
+	return &cp, nil
+}
+
+func (r *gatewayFileRepoStub) List(context.Context, int64, int) ([]*GatewayFile, error) {
+	return nil, nil
+}
+
+func (r *gatewayFileRepoStub) Delete(_ context.Context, userID int64, id string) (string, error) {
+	file, ok := r.files[id]
+	if !ok || file.UserID != userID {
+		return "", ErrAttachmentNotFound
+	}
+	delete(r.files, id)
+	return file.ObjectKey, nil
+}
+
+func (r *gatewayFileRepoStub) DeleteExpired(context.Context, int) ([]string, error) {
+	return nil, nil
+}
+
+var (
+	testPNG = append([]byte("\x89PNG\r\n\x1a\n"), bytes.Repeat([]byte{0}, 24)...)
+	testPDF = []byte("%PDF-1.4\n1 0 obj\n<<>>\nendobj\n%%EOF")
+)
+
+func newAttachmentServiceForTest(rules ...config.AttachmentRule) (*AttachmentService, *memoryObjectStorage) {
+	storage := newMemoryObjectStorage()
+	cfg := &config.Config{Attachments: config.AttachmentsConfig{
+		Enabled:         true,
+		MaxFileBytes:    1 << 20,
+		MaxRequestBytes: 2 << 20,
+		TTLHours:        24,
+		Rules:           rules,
+	}}
+	return NewAttachmentService(&gatewayFileRepoStub{files: map[string]*GatewayFile{}}, storage, nil, nil, nil, cfg), storage
+}
+
+type remoteImageFetcherStub struct {
+	images map[string][]byte
+	calls  int
+}
+
+func (f *remoteImageFetcherStub) Fetch(_ context.Context, rawURL string, maxBytes int64) (*Attachment, error) {
+	f.calls++
+	data, ok := f.images[rawURL]
+	if !ok {
+		return nil, errors.New("remote server returned 404")
+	}
+	if int64(len(data)) > maxBytes {
+		return nil, ErrAttachmentTooLarge
+	}
+	return &Attachment{MediaType: "image/png", Data: data}, nil
+}
+
+type remoteImageCacheStub struct {
+	entries map[string]*Attachment
+}
+
+func (c *remoteImageCacheStub) Get(_ context.Context, rawURL string) (*Attachment, error) {
+	return c.entries[rawURL], nil
+}
+
+func (c *remoteImageCacheStub) Set(_ context.Context, rawURL string, att *Attachment, _ time.Duration) error {
+	c.entries[rawURL] = att
+	return nil
+}
+
+func TestAttachmentService_UploadAndResolveAnthropic(t *testing.T) {
+	svc, storage := newAttachmentServiceForTest()
+	ctx := context.Background()
+
+	// 声明类型与内容不符时以内容识别为准
+	image, err := svc.Upload(ctx, 9, nil, "dir/photo.jpg", "image/jpeg", bytes.NewReader(testPNG))
+	require.NoError(t, err)
+	require.True(t, strings.HasPrefix(image.ID, AttachmentFileIDPrefix))
+	require.Equal(t, "photo.jpg", image.Filename)
+	require.Equal(t, "image/png", image.MediaType)
+	require.Equal(t, testPNG, storage.objects[image.ObjectKey])
+
+	doc, err := svc.Upload(ctx, 9, nil, "report.pdf", "", bytes.NewReader(testPDF))
+	require.NoError(t, err)
+	require.Equal(t, "application/pdf", doc.MediaType)
+
+	_, err = svc.Upload(ctx, 9, nil, "page.html", "text/html", strings.NewReader("<html></html>"))
+	require.ErrorIs(t, err, ErrAttachmentUnsupportedType)
+
+	body := `{"model":"claude-sonnet-4-5","messages":[{"role":"user","content":[` +
+		`{"type":"image","source":{"type":"file","file_id":"` + image.ID + `"},"cache_control":{"type":"ephemeral"}},` +
+		`{"type":"file","file_id":"` + doc.ID + `"},` +
+		`{"type":"document","source":{"type":"file","file_id":"file_upstream_native"}},` +
+		`{"type":"text","text":"describe"}]}]}`
+	out, err := svc.Resolve(ctx, AttachmentFormatAnthropic, 9, []byte(body), nil)
+	require.NoError(t, err)
+	content := gjson.GetBytes(out, "messages.0.content").Array()
+	require.Equal(t, "image", content[0].Get("type").String())
+	require.Equal(t, "base64", content[0].Get("source.type").String())
+	require.Equal(t, "image/png", content[0].Get("source.media_type").String())
+	require.Equal(t, base64.StdEncoding.EncodeToString(testPNG), content[0].Get("source.data").String())
+	require.Equal(t, "ephemeral", content[0].Get("cache_control.type").String())
+	require.Equal(t, "document", content[1].Get("type").String())
+	require.Equal(t, "application/pdf", content[1].Get("source.media_type").String())
+	require.Equal(t, "report.pdf", content[1].Get("title").String())
+	require.False(t, content[1].Get("file_id").Exists())
+	// 上游原生文件 ID 原样透传
+	require.Equal(t, "file_upstream_native", content[2].Get("source.file_id").String())
+
+	// 其他用户无法引用
+	_, err = svc.Resolve(ctx, AttachmentFormatAnthropic, 10, []byte(body), nil)
+	require.ErrorIs(t, err, ErrAttachmentNotFound)
+
+	plain := []byte(`{"model":"claude-sonnet-4-5","messages":[{"role":"user","content":"hi"}]}`)
+	out, err = svc.Resolve(ctx, AttachmentFormatAnthropic, 9, plain, nil)
+	require.NoError(t, err)
+	require.Equal(t, plain, out)
+
+	require.NoError(t, svc.Delete(ctx, 9, image.ID))
+	require.NotContains(t, storage.objects, image.ObjectKey)
+}
+
+func TestAttachmentService_MultipartOpenAIResponses(t *testing.T) {
+	svc, _ := newAttachmentServiceForTest()
+
+	var buf bytes.Buffer
+	w := multipart.NewWriter(&buf)
+	require.NoError(t, w.WriteField(AttachmentRequestField, `{"model":"gpt-5","input":[{"role":"user","content":[`+
+		`{"type":"input_text","text":"summarize"},`+
+		`{"type":"input_file","file_id":"multipart:doc"},`+
+		`{"type":"input_image","file_id":"multipart:shot","detail":"high"}]}]}`))
+	header := textproto.MIMEHeader{}
+	header.Set("Content-Disposition", `form-data; name="doc"; filename="notes.pdf"`)
+	header.Set("Content-Type", "application/octet-stream")
+	part, err := w.CreatePart(header)
+	require.NoError(t, err)
+	_, _ = part.Write(testPDF)
+	part, err = w.CreateFormFile("shot", "screen.png")
+	require.NoError(t, err)
+	_, _ = part.Write(testPNG)
+	require.NoError(t, w.Close())
+
+	body, uploads, err := svc.ParseMultipart(w.FormDataContentType(), buf.Bytes())
+	require.NoError(t, err)
+	require.Len(t, uploads, 2)
+	out, err := svc.Resolve(context.Background(), AttachmentFormatOpenAIResponses, 9, body, uploads)
+	require.NoError(t, err)
+	content := gjson.GetBytes(out, "input.0.content").Array()
+	require.Equal(t, "input_file", content[1].Get("type").String())
+	require.Equal(t, "notes.pdf", content[1].Get("filename").String())
+	require.Equal(t, "data:application/pdf;base64,"+base64.StdEncoding.EncodeToString(testPDF), content[1].Get("file_data").String())
+	require.Equal(t, "input_image", content[2].Get("type").String())
+	require.Equal(t, "high", content[2].Get("detail").String())
+	require.True(t, strings.HasPrefix(content[2].Get("image_url").String(), "data:image/png;base64,"))
+
+	_, _, err = svc.ParseMultipart(w.FormDataContentType(), []byte("garbage"))
+	require.ErrorIs(t, err, ErrAttachmentInvalidMultipart)
+
+	missing := []byte(`{"model":"gpt-5","input":[{"role":"user","content":[{"type":"input_file","file_id":"multipart:other"}]}]}`)
+	_, err = svc.Resolve(context.Background(), AttachmentFormatOpenAIResponses, 9, missing, uploads)
+	require.ErrorIs(t, err, ErrAttachmentNotFound)
+}
+
+func TestAttachmentService_ModelRules(t *testing.T) {
+	svc, _ := newAttachmentServiceForTest(config.AttachmentRule{
+		Models:       []string{"claude-*"},
+		MediaTypes:   []string{"image/*"},
+		MaxFileBytes: 16,
+	})
+	uploads := map[string]*Attachment{
+		"img": newAttachment("a.png", "", testPNG),
+		"pdf": newAttachment("a.pdf", "", testPDF),
+	}
+	request := func(model, ref string) []byte {
+		return []byte(`{"model":"` + model + `","messages":[{"role":"user","content":[{"type":"file","file_id":"multipart:` + ref + `"}]}]}`)
+	}
+	ctx := context.Background()
+
+	_, err := svc.Resolve(ctx, AttachmentFormatAnthropic, 9, request("claude-opus-4", "img"), uploads)
+	require.ErrorIs(t, err, ErrAttachmentTooLarge)
+	_, err = svc.Resolve(ctx, AttachmentFormatAnthropic, 9, request("claude-opus-4", "pdf"), uploads)
+	require.ErrorIs(t, err, ErrAttachmentUnsupportedType)
+	_, err = svc.Resolve(ctx, AttachmentFormatAnthropic, 9, request("gpt-5", "img"), uploads)
+	require.ErrorIs(t, err, ErrAttachmentUnsupportedType)
+
+	// 内置规则：Claude 支持 PDF，总大小受 max_request_bytes 限制
+	svc, _ = newAttachmentServiceForTest()
+	_, err = svc.Resolve(ctx, AttachmentFormatAnthropic, 9, request("claude-opus-4", "pdf"), uploads)
+	require.NoError(t, err)
+	svc.cfg.MaxRequestBytes = int64(len(testPDF)) - 1
+	_, err = svc.Resolve(ctx, AttachmentFormatAnthropic, 9, request("claude-opus-4", "pdf"), uploads)
+	require.ErrorIs(t, err, ErrAttachmentRequestTooLarge)
+
+	svc.cfg.Enabled = false
+	_, err = svc.Resolve(ctx, AttachmentFormatAnthropic, 9, request("claude-opus-4", "pdf"), uploads)
+	require.ErrorIs(t, err, ErrAttachmentDisabled)
+}
+
+func TestAttachmentService_RemoteImages(t *testing.T) {
+	svc, _ := newAttachmentServiceForTest()
+	fetcher := &remoteImageFetcherStub{images: map[string][]byte{"https://cdn.example.com/cat.png": testPNG}}
+	svc.fetcher = fetcher
+	svc.imageCache = &remoteImageCacheStub{entries: map[string]*Attachment{}}
+	svc.cfg.RemoteImages = config.AttachmentRemoteImagesConfig{
+		Enabled:             true,
+		MaxBytes:            1 << 20,
+		MaxImagesPerRequest: 2,
+		TimeoutSeconds:      5,
+		CacheTTLSeconds:     60,
+	}
+	ctx := context.Background()
+	anthropic := func(urls ...string) []byte {
+		blocks := make([]string, 0, len(urls))
+		for _, u := range urls {
+			blocks = append(blocks, `{"type":"image","source":{"type":"url","url":"`+u+`"}}`)
+		}
+		return []byte(`{"model":"claude-sonnet-4-5","messages":[{"role":"user","content":[` + strings.Join(blocks, ",") + `]}]}`)
+	}
+
+	out, err := svc.Resolve(ctx, AttachmentFormatAnthropic, 9, anthropic("https://cdn.example.com/cat.png"), nil)
+	require.NoError(t, err)
+	block := gjson.GetBytes(out, "messages.0.content.0")
+	require.Equal(t, "base64", block.Get("source.type").String())
+	require.Equal(t, "image/png", block.Get("source.media_type").String())
+	require.False(t, block.Get("source.url").Exists())
+
+	// 短期缓存命中，不重复下载
+	responses := []byte(`{"model":"gpt-5","input":[{"role":"user","content":[{"type":"input_image","image_url":"https://cdn.example.com/cat.png"}]}]}`)
+	out, err = svc.Resolve(ctx, AttachmentFormatOpenAIResponses, 9, responses, nil)
+	require.NoError(t, err)
+	require.True(t, strings.HasPrefix(gjson.GetBytes(out, "input.0.content.0.image_url").String(), "data:image/png;base64,"))
+	require.Equal(t, 1, fetcher.calls)
+
+	// 字面量内网地址与非 https 地址在发起连接前拒绝
+	for _, u := range []string{"https://127.0.0.1/a.png", "https://localhost/a.png", "http://cdn.example.com/cat.png"} {
+		_, err = svc.Resolve(ctx, AttachmentFormatAnthropic, 9, anthropic(u), nil)
+		require.ErrorIs(t, err, ErrRemoteImageFetchFailed, u)
+	}
+	require.Equal(t, 1, fetcher.calls)
+
+	_, err = svc.Resolve(ctx, AttachmentFormatAnthropic, 9, anthropic("https://cdn.example.com/missing.png"), nil)
+	require.ErrorIs(t, err, ErrRemoteImageFetchFailed)
+	_, err = svc.Resolve(ctx, AttachmentFormatAnthropic, 9, anthropic("https://a.example.com/1.png", "https://a.example.com/2.png", "https://a.example.com/3.png"), nil)
+	require.ErrorIs(t, err, ErrRemoteImageTooMany)
+
+	// 未启用抓取时 URL 原样透传
+	svc.cfg.RemoteImages.Enabled = false
+	body := anthropic("https://cdn.example.com/cat.png")
+	out, err = svc.Resolve(ctx, AttachmentFormatAnthropic, 9, body, nil)
+	require.NoError(t, err)
+	require.Equal(t, body, out)
+}
+
+func TestAttachmentService_PreprocessDocuments(t *testing.T) {
+	svc, _ := newAttachmentServiceForTest()
+	svc.cfg.Preprocess = config.AttachmentPreprocessConfig{
+		Enabled:         true,
+		NativePDFModels: []string{"claude-opus-*"},
+		ExtractImages:   true,
+		MaxImages:       1,
+		MaxPages:        10,
+		ChunkTokens:     50,
+		ContextRatio:    0.5,
+		MaxTextTokens:   60,
+	}
+	ctx := context.Background()
+
+	// 每页约 23 个估算 token：额度 60 容纳前两页，第 3 页起截断
+	doc := pdf.New()
+	for i := 1; i <= 4; i++ {
+		page := doc.AddPage()
+		for line := 0; line < 3; line++ {
+			page.Text(40, 60+float64(line)*14, 10, pdf.Helvetica, fmt.Sprintf("page %d line %d alpha beta gamma", i, line))
+		}
+	}
+	var report bytes.Buffer
+	_, err := doc.WriteTo(&report)
+	require.NoError(t, err)
+	uploads := map[string]*Attachment{"report": newAttachment("report.pdf", "", report.Bytes())}
+	request := func(model string) []byte {
+		return []byte(`{"model":"` + model + `","messages":[{"role":"user","content":[` +
+			`{"type":"text","text":"question"},` +
+			`{"type":"document","source":{"type":"file","file_id":"multipart:report"},"cache_control":{"type":"ephemeral"}},` +
+			`{"type":"text","text":"after"}]}]}`)
+	}
+
+	out, err := svc.Resolve(ctx, AttachmentFormatAnthropic, 9, request("claude-sonnet-4-5"), uploads)
+	require.NoError(t, err)
+	content := gjson.GetBytes(out, "messages.0.content").Array()
+	require.Len(t, content, 4)
+	require.Equal(t, "question", content[0].Get("text").String())
+	chunk := content[1].Get("text").String()
+	require.True(t, strings.HasPrefix(chunk, `<document name="report.pdf" part="1/1" pages="1-2">`), chunk)
+	require.Contains(t, chunk, "page 2 line 2 alpha beta gamma")
+	require.NotContains(t, chunk, "page 3")
+	require.Equal(t, "[report.pdf truncated: content from page 3 (about 50% of the extracted text) was omitted to fit the model context window]", content[2].Get("text").String())
+	require.Equal(t, "ephemeral", content[2].Get("cache_control.type").String())
+	require.Equal(t, "after", content[3].Get("text").String())
+
+	// 原生转发的模型保持 PDF 文档块
+	out, err = svc.Resolve(ctx, AttachmentFormatAnthropic, 9, request("claude-opus-4"), uploads)
+	require.NoError(t, err)
+	require.Equal(t, "application/pdf", gjson.GetBytes(out, "messages.0.content.1.source.media_type").String())
+
+	// 无法提取文本的 PDF 在模型支持时原样转发
+	uploads["report"] = newAttachment("scan.pdf", "", testPDF)
+	out, err = svc.Resolve(ctx, AttachmentFormatAnthropic, 9, request("claude-sonnet-4-5"), uploads)
+	require.NoError(t, err)
+	require.Equal(t, "base64", gjson.GetBytes(out, "messages.0.content.1.source.type").String())
+
+	// DOCX：文本与内嵌图片
+	var archive bytes.Buffer
+	zw := zip.NewWriter(&archive)
+	for name, data := range map[string]string{
+		"word/document.xml": `<w:document xmlns:w="w" xmlns:a="a" xmlns:r="r"><w:body>` +
+			`<w:p><w:r><w:t>Hello</w:t></w:r></w:p><w:p><w:r><w:t>World</w:t></w:r></w:p>` +
+			`<w:p><w:r><a:blip r:embed="rId1"/><a:blip r:embed="rId2"/></w:r></w:p></w:body></w:document>`,
+		"word/_rels/document.xml.rels": `<Relationships><Relationship Id="rId1" Target="media/a.png"/><Relationship Id="rId2" Target="media/b.png"/></Relationships>`,
+		"word/media/a.png":             string(testPNG),
+		"word/media/b.png":             string(testPNG),
+	} {
+		w, err := zw.Create(name)
+		require.NoError(t, err)
+		_, _ = w.Write([]byte(data))
+	}
+	require.NoError(t, zw.Close())
+	uploads = map[string]*Attachment{"notes": newAttachment("notes.docx", "", archive.Bytes())}
+	require.Equal(t, attachmentDOCXMediaType, uploads["notes"].MediaType)
+	responses := []byte(`{"model":"gpt-5","input":[{"role":"user","content":[{"type":"input_file","file_id":"multipart:notes"}]}]}`)
+	out, err = svc.Resolve(ctx, AttachmentFormatOpenAIResponses, 9, responses, uploads)
+	require.NoError(t, err)
+	content = gjson.GetBytes(out, "input.0.content").Array()
+	require.Len(t, content, 2)
+	require.Equal(t, "input_text", content[0].Get("type").String())
+	require.Equal(t, "<document name=\"notes.docx\" part=\"1/1\">\nHello\n\nWorld\n</document>", content[0].Get("text").String())
+	require.Equal(t, "input_image", content[1].Get("type").String())
+
+	// 未启用预处理时不接受 DOCX
+	svc.cfg.Preprocess.Enabled = false
+	_, err = svc.Resolve(ctx, AttachmentFormatOpenAIResponses, 9, responses, uploads)
+	require.ErrorIs(t, err, ErrAttachmentUnsupportedType)
+}
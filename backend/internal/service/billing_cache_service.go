@@ -78,6 +78,7 @@ type BillingCacheService struct {
 	subRepo        UserSubscriptionRepository
 	cfg            *config.Config
 	circuitBreaker *billingCircuitBreaker
+	spendingCaps   *SpendingCapService
 
 	cacheWriteChan     chan cacheWriteTask
 	cacheWriteWg       sync.WaitGroup
@@ -90,12 +91,13 @@ type BillingCacheService struct {
 }
 
 // NewBillingCacheService 创建计费缓存服务
-func NewBillingCacheService(cache BillingCache, userRepo UserRepository, subRepo UserSubscriptionRepository, cfg *config.Config) *BillingCacheService {
+func NewBillingCacheService(cache BillingCache, userRepo UserRepository, subRepo UserSubscriptionRepository, cfg *config.Config, spendingCaps *SpendingCapService) *BillingCacheService {
 	svc := &BillingCacheService{
-		cache:    cache,
-		userRepo: userRepo,
-		subRepo:  subRepo,
-		cfg:      cfg,
+		cache:        cache,
+		userRepo:     userRepo,
+		subRepo:      subRepo,
+		cfg:          cfg,
+		spendingCaps: spendingCaps,
 	}
 	svc.circuitBreaker = newBillingCircuitBreaker(cfg.Billing.CircuitBreaker)
 	svc.startCacheWriteWorkers()
@@ -457,6 +459,11 @@ func (s *BillingCacheService) CheckBillingEligibility(ctx context.Context, user
 		return ErrBillingServiceUnavailable
 	}
 
+	// 消费硬上限（API Key / 用户级），与余额 / 订阅模式无关
+	if err := s.spendingCaps.Check(ctx, apiKey); err != nil {
+		return err
+	}
+
 	// 判断计费模式
 	isSubscriptionMode := group != nil && group.IsSubscriptionType() && subscription != nil
 
@@ -467,6 +474,14 @@ func (s *BillingCacheService) CheckBillingEligibility(ctx context.Context, user
 	return s.checkBalanceEligibility(ctx, user.ID)
 }
 
+// RecordSpend 请求计费后累加消费上限的周期计数（越过上限时暂停 API Key / 用户）
+func (s *BillingCacheService) RecordSpend(ctx context.Context, apiKey *APIKey, cost float64) {
+	if s == nil || cost <= 0 {
+		return
+	}
+	s.spendingCaps.RecordSpend(ctx, apiKey, cost)
+}
+
 // checkBalanceEligibility 检查余额模式资格
 func (s *BillingCacheService) checkBalanceEligibility(ctx context.Context, userID int64) error {
 	balance, err := s.GetUserBalance(ctx, userID)
@@ -513,22 +528,46 @@ func (s *BillingCacheService) checkSubscriptionEligibility(ctx context.Context,
 		return ErrSubscriptionInvalid
 	}
 
-	// 检查限额（使用传入的Group限额配置）
-	if group.HasDailyLimit() && subData.DailyUsage >= *group.DailyLimitUSD {
+	// 检查限额（使用传入的Group限额配置）；演练模式下超限只记录不拒绝
+	if group.HasDailyLimit() && subData.DailyUsage >= *group.DailyLimitUSD &&
+		!s.LimitDryRun(ctx, LimitDryRunScopeSubscription, subscription.ID, SpendingCapPeriodDaily, subData.DailyUsage, *group.DailyLimitUSD) {
 		return ErrDailyLimitExceeded
 	}
 
-	if group.HasWeeklyLimit() && subData.WeeklyUsage >= *group.WeeklyLimitUSD {
+	if group.HasWeeklyLimit() && subData.WeeklyUsage >= *group.WeeklyLimitUSD &&
+		!s.LimitDryRun(ctx, LimitDryRunScopeSubscription, subscription.ID, SpendingCapPeriodWeekly, subData.WeeklyUsage, *group.WeeklyLimitUSD) {
 		return ErrWeeklyLimitExceeded
 	}
 
-	if group.HasMonthlyLimit() && subData.MonthlyUsage >= *group.MonthlyLimitUSD {
+	if group.HasMonthlyLimit() && subData.MonthlyUsage >= *group.MonthlyLimitUSD &&
+		!s.LimitDryRun(ctx, LimitDryRunScopeSubscription, subscription.ID, SpendingCapPeriodMonthly, subData.MonthlyUsage, *group.MonthlyLimitUSD) {
 		return ErrMonthlyLimitExceeded
 	}
 
 	return nil
 }
 
+// LimitDryRun 对应限额处于演练模式（billing.limit_dry_run）时记录本应拒绝的请求并返回 true，调用方照常放行
+func (s *BillingCacheService) LimitDryRun(ctx context.Context, scopeType string, scopeID int64, period string, spent, limit float64) bool {
+	if s == nil || s.cfg == nil {
+		return false
+	}
+	switch scopeType {
+	case LimitDryRunScopeSubscription:
+		if !s.cfg.Billing.LimitDryRun.SubscriptionLimits {
+			return false
+		}
+	case LimitDryRunScopeAPIKeyQuota:
+		if !s.cfg.Billing.LimitDryRun.APIKeyQuota {
+			return false
+		}
+	default:
+		return false
+	}
+	s.spendingCaps.RecordLimitDryRun(ctx, scopeType, scopeID, period, spent, limit)
+	return true
+}
+
 type billingCircuitBreakerState int
 
 const (
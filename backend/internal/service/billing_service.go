@@ -3,6 +3,7 @@ package service
 import (
 	"context"
 	"fmt"
+	"math"
 
 	"log"
 	"strings"
@@ -218,6 +219,36 @@ func (s *BillingService) GetModelPricing(model string) (*ModelPricing, error) {
 	return nil, fmt.Errorf("pricing not found for model: %s", model)
 }
 
+// GetModelTokenLimits 返回模型的上下文窗口与输出 token 上限（来自动态价格数据），未知时对应字段为 0
+func (s *BillingService) GetModelTokenLimits(model string) ModelTokenLimits {
+	if s == nil || s.pricingService == nil {
+		return ModelTokenLimits{}
+	}
+	pricing := s.pricingService.GetModelPricing(strings.ToLower(model))
+	if pricing == nil {
+		return ModelTokenLimits{}
+	}
+	return ModelTokenLimits{MaxInput: pricing.MaxInputTokens, MaxOutput: pricing.MaxOutputTokens}
+}
+
+// ReconcileEstimatedUsage 对本地估算的用量应用 billing.estimated_usage_margin，返回用于计费的 token 数
+func (s *BillingService) ReconcileEstimatedUsage(tokens UsageTokens) UsageTokens {
+	if s == nil || s.cfg == nil || s.cfg.Billing.EstimatedUsageMargin == 0 {
+		return tokens
+	}
+	factor := 1 + s.cfg.Billing.EstimatedUsageMargin
+	scale := func(n int) int {
+		return int(math.Round(float64(n) * factor))
+	}
+	tokens.InputTokens = scale(tokens.InputTokens)
+	tokens.OutputTokens = scale(tokens.OutputTokens)
+	tokens.CacheCreationTokens = scale(tokens.CacheCreationTokens)
+	tokens.CacheReadTokens = scale(tokens.CacheReadTokens)
+	tokens.CacheCreation5mTokens = scale(tokens.CacheCreation5mTokens)
+	tokens.CacheCreation1hTokens = scale(tokens.CacheCreation1hTokens)
+	return tokens
+}
+
 // CalculateCost 计算使用费用
 func (s *BillingService) CalculateCost(model string, tokens UsageTokens, rateMultiplier float64) (*CostBreakdown, error) {
 	pricing, err := s.GetModelPricing(model)
@@ -435,3 +435,22 @@ func TestCalculateCost_LargeTokenCount(t *testing.T) {
 	require.False(t, math.IsNaN(cost.TotalCost))
 	require.False(t, math.IsInf(cost.TotalCost, 0))
 }
+
+func TestReconcileEstimatedUsage(t *testing.T) {
+	tokens := UsageTokens{InputTokens: 1000, OutputTokens: 333}
+
+	// 未配置 margin 时按估算值计费
+	require.Equal(t, tokens, newTestBillingService().ReconcileEstimatedUsage(tokens))
+
+	cfg := &config.Config{}
+	cfg.Billing.EstimatedUsageMargin = 0.1
+	got := NewBillingService(cfg, nil).ReconcileEstimatedUsage(tokens)
+	require.Equal(t, UsageTokens{InputTokens: 1100, OutputTokens: 366}, got)
+
+	cfg.Billing.EstimatedUsageMargin = -0.5
+	got = NewBillingService(cfg, nil).ReconcileEstimatedUsage(tokens)
+	require.Equal(t, UsageTokens{InputTokens: 500, OutputTokens: 167}, got)
+
+	var nilSvc *BillingService
+	require.Equal(t, tokens, nilSvc.ReconcileEstimatedUsage(tokens))
+}
@@ -0,0 +1,281 @@
+package service
+
+import (
+	"bytes"
+	"context"
+	"fmt"
+	"log/slog"
+	"net/http"
+	"strconv"
+	"strings"
+	"sync"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	infraerrors "github.com/Wei-Shaw/sub2api/internal/pkg/errors"
+	"github.com/Wei-Shaw/sub2api/internal/util/soraerror"
+	"github.com/google/uuid"
+)
+
+// 上游人机验证类型
+const (
+	ChallengeKindCloudflare      = "cloudflare"
+	ChallengeKindCaptcha         = "captcha"
+	ChallengeKindChatGPTSentinel = "chatgpt_sentinel"
+)
+
+// 外部服务返回的处理结果；pending 表示异步处理，稍后通过回调接口回报
+const (
+	ChallengeHookStatusSolved  = "solved"
+	ChallengeHookStatusFailed  = "failed"
+	ChallengeHookStatusPending = "pending"
+)
+
+// challengePendingKey 记录在 extra 中的待处理验证，供管理端展示与回调校验
+const challengePendingKey = "challenge_pending"
+
+var (
+	ErrChallengeNotPending = infraerrors.NotFound("CHALLENGE_NOT_PENDING", "account has no pending challenge")
+	ErrChallengeMismatch   = infraerrors.Conflict("CHALLENGE_MISMATCH", "challenge id does not match the pending challenge")
+)
+
+// captchaMarkers 出现在验证页面中的特征（Cloudflare 之外的验证码服务）
+var captchaMarkers = [][]byte{
+	[]byte("g-recaptcha"),
+	[]byte("hcaptcha.com"),
+	[]byte("arkoselabs.com"),
+	[]byte("funcaptcha"),
+	[]byte("cf-turnstile"),
+	[]byte("/sorry/index"), // Google "unusual traffic" 页面
+}
+
+// UpstreamChallenge 上游返回的人机验证信息
+type UpstreamChallenge struct {
+	Kind        string
+	StatusCode  int
+	RayID       string
+	BodyPreview string
+}
+
+// DetectUpstreamChallenge 判断上游错误响应是否为人机验证，不是则返回 nil
+func DetectUpstreamChallenge(statusCode int, headers http.Header, body []byte) *UpstreamChallenge {
+	if statusCode != http.StatusForbidden && statusCode != http.StatusTooManyRequests {
+		return nil
+	}
+	kind := ""
+	if soraerror.IsCloudflareChallengeResponse(statusCode, headers, body) {
+		kind = ChallengeKindCloudflare
+	} else {
+		lower := bytes.ToLower(body)
+		for _, marker := range captchaMarkers {
+			if bytes.Contains(lower, marker) {
+				kind = ChallengeKindCaptcha
+				break
+			}
+		}
+	}
+	if kind == "" {
+		return nil
+	}
+	return &UpstreamChallenge{
+		Kind:        kind,
+		StatusCode:  statusCode,
+		RayID:       soraerror.ExtractCloudflareRayID(headers, body),
+		BodyPreview: soraerror.TruncateBody(body, 2048),
+	}
+}
+
+// ChallengeHookRequest 发送给外部服务的验证上下文。
+// 包含账号凭证与代理地址，便于求解服务在同一会话、同一出口 IP 下完成验证。
+type ChallengeHookRequest struct {
+	ChallengeID string         `json:"challenge_id"`
+	AccountID   int64          `json:"account_id"`
+	Name        string         `json:"name"`
+	Platform    string         `json:"platform"`
+	Type        string         `json:"type"`
+	Kind        string         `json:"kind"`
+	StatusCode  int            `json:"status_code,omitempty"`
+	RayID       string         `json:"ray_id,omitempty"`
+	BodyPreview string         `json:"body_preview,omitempty"`
+	Credentials map[string]any `json:"credentials"`
+	ProxyURL    string         `json:"proxy_url,omitempty"`
+	CallbackURL string         `json:"callback_url,omitempty"`
+	HoldUntil   time.Time      `json:"hold_until"`
+}
+
+// ChallengeHookResponse 外部服务的响应；Credentials 只需包含有变化的字段（如 cf_clearance）
+type ChallengeHookResponse struct {
+	Status      string         `json:"status"`
+	Credentials map[string]any `json:"credentials,omitempty"`
+	Message     string         `json:"message,omitempty"`
+}
+
+// ChallengeHookClient 调用外部验证处理服务
+type ChallengeHookClient interface {
+	Notify(ctx context.Context, endpoint, authToken string, req *ChallengeHookRequest) (*ChallengeHookResponse, error)
+}
+
+// ChallengeHookService 处理上游人机验证：暂停账号调度、通知外部服务，并在其回报成功后恢复账号
+type ChallengeHookService struct {
+	accountRepo AccountRepository
+	client      ChallengeHookClient
+	cfg         config.ChallengeHookConfig
+
+	mu sync.Mutex
+	// inflight 进程内去重：账号 ID -> 暂停截止时间，同一账号在处理期间只通知一次
+	inflight map[int64]time.Time
+}
+
+func NewChallengeHookService(accountRepo AccountRepository, client ChallengeHookClient, cfg *config.Config) *ChallengeHookService {
+	return &ChallengeHookService{
+		accountRepo: accountRepo,
+		client:      client,
+		cfg:         cfg.ChallengeHook,
+		inflight:    make(map[int64]time.Time),
+	}
+}
+
+// Enabled 返回是否配置了验证处理钩子
+func (s *ChallengeHookService) Enabled() bool {
+	return s != nil && s.client != nil && s.cfg.Enabled && s.cfg.URL != ""
+}
+
+// Report 将账号标记为需要处理验证并异步通知外部服务。
+// 返回 true 表示已接管（调用方不应再按普通 403/429 处理账号）。
+func (s *ChallengeHookService) Report(ctx context.Context, account *Account, challenge *UpstreamChallenge) bool {
+	if !s.Enabled() || account == nil || challenge == nil {
+		return false
+	}
+
+	now := time.Now()
+	holdUntil := now.Add(time.Duration(s.cfg.HoldMinutes) * time.Minute)
+	s.mu.Lock()
+	if until, ok := s.inflight[account.ID]; ok && now.Before(until) {
+		s.mu.Unlock()
+		return true
+	}
+	s.inflight[account.ID] = holdUntil
+	s.mu.Unlock()
+
+	challengeID := uuid.NewString()
+	reason := "challenge_required: " + challenge.Kind
+	if err := s.accountRepo.SetTempUnschedulable(ctx, account.ID, holdUntil, reason); err != nil {
+		slog.Warn("challenge_hold_failed", "account_id", account.ID, "error", err)
+	}
+	if err := s.accountRepo.UpdateExtra(ctx, account.ID, map[string]any{
+		challengePendingKey: map[string]any{
+			"id":          challengeID,
+			"kind":        challenge.Kind,
+			"status_code": challenge.StatusCode,
+			"ray_id":      challenge.RayID,
+			"since":       now.Format(time.RFC3339),
+		},
+	}); err != nil {
+		slog.Warn("challenge_record_failed", "account_id", account.ID, "error", err)
+	}
+	slog.Info("challenge_reported", "account_id", account.ID, "platform", account.Platform, "kind", challenge.Kind, "challenge_id", challengeID)
+
+	req := &ChallengeHookRequest{
+		ChallengeID: challengeID,
+		AccountID:   account.ID,
+		Name:        account.Name,
+		Platform:    account.Platform,
+		Type:        account.Type,
+		Kind:        challenge.Kind,
+		StatusCode:  challenge.StatusCode,
+		RayID:       challenge.RayID,
+		BodyPreview: challenge.BodyPreview,
+		Credentials: account.Credentials,
+		HoldUntil:   holdUntil,
+	}
+	if account.ProxyID != nil && account.Proxy != nil {
+		req.ProxyURL = account.Proxy.URL()
+	}
+	if base := strings.TrimRight(s.cfg.CallbackBaseURL, "/"); base != "" {
+		req.CallbackURL = base + "/api/v1/admin/accounts/" + strconv.FormatInt(account.ID, 10) + "/challenge/resolve"
+	}
+	go s.notify(req)
+	return true
+}
+
+func (s *ChallengeHookService) notify(req *ChallengeHookRequest) {
+	ctx, cancel := context.WithTimeout(context.Background(), time.Duration(s.cfg.TimeoutSeconds)*time.Second)
+	defer cancel()
+
+	resp, err := s.client.Notify(ctx, s.cfg.URL, s.cfg.AuthToken, req)
+	if err != nil {
+		// 通知失败时保持暂停，到期后账号自动恢复调度
+		slog.Warn("challenge_hook_notify_failed", "account_id", req.AccountID, "challenge_id", req.ChallengeID, "error", err)
+		return
+	}
+	switch resp.Status {
+	case ChallengeHookStatusSolved, ChallengeHookStatusFailed:
+		if err := s.Resolve(ctx, req.AccountID, req.ChallengeID, resp.Status == ChallengeHookStatusSolved, resp.Credentials, resp.Message); err != nil {
+			slog.Warn("challenge_resolve_failed", "account_id", req.AccountID, "challenge_id", req.ChallengeID, "error", err)
+		}
+	case ChallengeHookStatusPending, "":
+	default:
+		slog.Warn("challenge_hook_unknown_status", "account_id", req.AccountID, "status", resp.Status)
+	}
+}
+
+// Resolve 处理外部服务回报的结果：成功时合并新凭证并恢复调度，失败时标记账号错误等待人工处理。
+// challengeID 为空时不校验（管理员手动处理）。
+func (s *ChallengeHookService) Resolve(ctx context.Context, accountID int64, challengeID string, success bool, credentials map[string]any, message string) error {
+	account, err := s.accountRepo.GetByID(ctx, accountID)
+	if err != nil {
+		return err
+	}
+	pendingID := pendingChallengeID(account)
+	if pendingID == "" {
+		return ErrChallengeNotPending
+	}
+	if challengeID != "" && challengeID != pendingID {
+		return ErrChallengeMismatch
+	}
+
+	if success {
+		if len(credentials) > 0 {
+			merged := make(map[string]any, len(account.Credentials)+len(credentials))
+			for k, v := range account.Credentials {
+				merged[k] = v
+			}
+			for k, v := range credentials {
+				merged[k] = v
+			}
+			account.Credentials = merged
+			if err := s.accountRepo.Update(ctx, account); err != nil {
+				return fmt.Errorf("update credentials: %w", err)
+			}
+		}
+		if err := s.accountRepo.ClearTempUnschedulable(ctx, accountID); err != nil {
+			return fmt.Errorf("clear hold: %w", err)
+		}
+	} else {
+		msg := "Challenge unresolved"
+		if message != "" {
+			msg += ": " + message
+		}
+		if err := s.accountRepo.SetError(ctx, accountID, msg); err != nil {
+			return fmt.Errorf("set error: %w", err)
+		}
+	}
+
+	if err := s.accountRepo.UpdateExtra(ctx, accountID, map[string]any{challengePendingKey: nil}); err != nil {
+		slog.Warn("challenge_clear_record_failed", "account_id", accountID, "error", err)
+	}
+	s.mu.Lock()
+	delete(s.inflight, accountID)
+	s.mu.Unlock()
+	slog.Info("challenge_resolved", "account_id", accountID, "challenge_id", pendingID, "success", success)
+	return nil
+}
+
+func pendingChallengeID(account *Account) string {
+	pending, ok := account.Extra[challengePendingKey].(map[string]any)
+	if !ok {
+		return ""
+	}
+	id, _ := pending["id"].(string)
+	return id
+}
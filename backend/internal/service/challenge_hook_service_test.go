@@ -0,0 +1,157 @@
+//go:build unit
+
+package service
+
+import (
+	"context"
+	"net/http"
+	"sync"
+	"testing"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/stretchr/testify/require"
+)
+
+type challengeHookAccountRepo struct {
+	mockAccountRepoForGemini
+	holdReason   string
+	holdCleared  bool
+	updatedCreds map[string]any
+
+	mu       sync.Mutex
+	errorMsg string
+}
+
+func (r *challengeHookAccountRepo) SetTempUnschedulable(_ context.Context, _ int64, _ time.Time, reason string) error {
+	r.holdReason = reason
+	return nil
+}
+
+func (r *challengeHookAccountRepo) ClearTempUnschedulable(_ context.Context, _ int64) error {
+	r.holdCleared = true
+	return nil
+}
+
+func (r *challengeHookAccountRepo) UpdateExtra(_ context.Context, id int64, updates map[string]any) error {
+	account := r.accountsByID[id]
+	if account.Extra == nil {
+		account.Extra = map[string]any{}
+	}
+	for k, v := range updates {
+		account.Extra[k] = v
+	}
+	return nil
+}
+
+func (r *challengeHookAccountRepo) Update(_ context.Context, account *Account) error {
+	r.updatedCreds = account.Credentials
+	return nil
+}
+
+func (r *challengeHookAccountRepo) SetError(_ context.Context, _ int64, errorMsg string) error {
+	r.mu.Lock()
+	defer r.mu.Unlock()
+	r.errorMsg = errorMsg
+	return nil
+}
+
+func (r *challengeHookAccountRepo) lastError() string {
+	r.mu.Lock()
+	defer r.mu.Unlock()
+	return r.errorMsg
+}
+
+type challengeHookClientStub struct {
+	resp  *ChallengeHookResponse
+	calls chan *ChallengeHookRequest
+}
+
+func (s *challengeHookClientStub) Notify(_ context.Context, _, _ string, req *ChallengeHookRequest) (*ChallengeHookResponse, error) {
+	s.calls <- req
+	return s.resp, nil
+}
+
+func newChallengeHookServiceForTest(resp *ChallengeHookResponse) (*ChallengeHookService, *challengeHookAccountRepo, *challengeHookClientStub, *Account) {
+	account := &Account{ID: 21, Name: "web", Platform: PlatformOpenAI, Type: AccountTypeWebSession, Credentials: map[string]any{"session_token": "st"}}
+	repo := &challengeHookAccountRepo{mockAccountRepoForGemini: mockAccountRepoForGemini{accountsByID: map[int64]*Account{account.ID: account}}}
+	client := &challengeHookClientStub{resp: resp, calls: make(chan *ChallengeHookRequest, 4)}
+	cfg := &config.Config{ChallengeHook: config.ChallengeHookConfig{
+		Enabled:         true,
+		URL:             "https://solver.internal/challenge",
+		TimeoutSeconds:  5,
+		HoldMinutes:     30,
+		CallbackBaseURL: "https://relay.example.com/",
+	}}
+	return NewChallengeHookService(repo, client, cfg), repo, client, account
+}
+
+func waitChallengeHookCall(t *testing.T, client *challengeHookClientStub) *ChallengeHookRequest {
+	t.Helper()
+	select {
+	case req := <-client.calls:
+		return req
+	case <-time.After(2 * time.Second):
+		t.Fatal("challenge hook was not called")
+		return nil
+	}
+}
+
+func TestDetectUpstreamChallenge(t *testing.T) {
+	headers := http.Header{}
+	headers.Set("cf-mitigated", "challenge")
+	headers.Set("cf-ray", "8abc-LAX")
+	challenge := DetectUpstreamChallenge(http.StatusForbidden, headers, []byte(`<html></html>`))
+	require.NotNil(t, challenge)
+	require.Equal(t, ChallengeKindCloudflare, challenge.Kind)
+	require.Equal(t, "8abc-LAX", challenge.RayID)
+
+	challenge = DetectUpstreamChallenge(http.StatusTooManyRequests, nil, []byte(`<div class="g-recaptcha" data-sitekey="x"></div>`))
+	require.NotNil(t, challenge)
+	require.Equal(t, ChallengeKindCaptcha, challenge.Kind)
+
+	require.Nil(t, DetectUpstreamChallenge(http.StatusForbidden, nil, []byte(`{"error":{"message":"forbidden"}}`)))
+	require.Nil(t, DetectUpstreamChallenge(http.StatusBadGateway, headers, []byte(`<html></html>`)))
+}
+
+func TestChallengeHookService_ReportAndCallback(t *testing.T) {
+	svc, repo, client, account := newChallengeHookServiceForTest(&ChallengeHookResponse{Status: ChallengeHookStatusPending})
+
+	require.True(t, svc.Report(context.Background(), account, &UpstreamChallenge{Kind: ChallengeKindCloudflare, StatusCode: http.StatusForbidden}))
+	req := waitChallengeHookCall(t, client)
+	require.Equal(t, "challenge_required: cloudflare", repo.holdReason)
+	require.Equal(t, int64(21), req.AccountID)
+	require.Equal(t, "st", req.Credentials["session_token"])
+	require.Equal(t, "https://relay.example.com/api/v1/admin/accounts/21/challenge/resolve", req.CallbackURL)
+	require.Equal(t, req.ChallengeID, pendingChallengeID(account))
+
+	// 处理期间重复上报不再通知
+	require.True(t, svc.Report(context.Background(), account, &UpstreamChallenge{Kind: ChallengeKindCloudflare}))
+	require.Empty(t, client.calls)
+
+	require.ErrorIs(t, svc.Resolve(context.Background(), account.ID, "other", true, nil, ""), ErrChallengeMismatch)
+	require.NoError(t, svc.Resolve(context.Background(), account.ID, req.ChallengeID, true, map[string]any{"cf_clearance": "cf"}, ""))
+	require.True(t, repo.holdCleared)
+	require.Equal(t, "cf", repo.updatedCreds["cf_clearance"])
+	require.Equal(t, "st", repo.updatedCreds["session_token"])
+	require.Empty(t, pendingChallengeID(account))
+	require.ErrorIs(t, svc.Resolve(context.Background(), account.ID, req.ChallengeID, true, nil, ""), ErrChallengeNotPending)
+}
+
+func TestChallengeHookService_SyncFailure(t *testing.T) {
+	svc, repo, client, account := newChallengeHookServiceForTest(&ChallengeHookResponse{Status: ChallengeHookStatusFailed, Message: "captcha unsolvable"})
+
+	require.True(t, svc.Report(context.Background(), account, &UpstreamChallenge{Kind: ChallengeKindCaptcha}))
+	waitChallengeHookCall(t, client)
+	require.Eventually(t, func() bool { return repo.lastError() != "" }, 2*time.Second, 10*time.Millisecond)
+	require.Equal(t, "Challenge unresolved: captcha unsolvable", repo.lastError())
+}
+
+func TestChallengeHookService_Disabled(t *testing.T) {
+	svc, _, _, account := newChallengeHookServiceForTest(nil)
+	svc.cfg.Enabled = false
+	require.False(t, svc.Report(context.Background(), account, &UpstreamChallenge{Kind: ChallengeKindCloudflare}))
+
+	var rateLimit *RateLimitService
+	require.False(t, rateLimit.ReportChallenge(context.Background(), account, &UpstreamChallenge{Kind: ChallengeKindCloudflare}))
+}
@@ -61,6 +61,12 @@ const (
 // ConcurrencyService manages concurrent request limiting for accounts and users
 type ConcurrencyService struct {
 	cache ConcurrencyCache
+	// fairShare 账号等待队列的租户公平调度，nil 表示未启用
+	fairShare *FairShareScheduler
+	// admission 账号等待队列的准入控制，nil 表示未启用
+	admission *AdmissionController
+	// stateLocker 账号状态锁，nil 表示槽位占用只依赖缓存的原子脚本
+	stateLocker AccountStateLocker
 }
 
 // NewConcurrencyService creates a new ConcurrencyService
@@ -68,6 +74,31 @@ func NewConcurrencyService(cache ConcurrencyCache) *ConcurrencyService {
 	return &ConcurrencyService{cache: cache}
 }
 
+// EnableFairShare 启用账号等待队列的租户公平调度
+func (s *ConcurrencyService) EnableFairShare() {
+	s.fairShare = NewFairShareScheduler()
+}
+
+// EnterFairShare 将等待账号槽位的请求加入公平调度队列；未启用时返回 nil（视为始终轮到）
+func (s *ConcurrencyService) EnterFairShare(accountID, userID int64, weight int) *FairShareTicket {
+	return s.fairShare.Enter(accountID, userID, weight)
+}
+
+// FairShareStats 返回各租户的公平调度统计；未启用时返回空列表
+func (s *ConcurrencyService) FairShareStats() []FairShareTenantStats {
+	return s.fairShare.Stats()
+}
+
+// EnableAdmissionControl 启用账号等待队列准入控制，估算排队时间超过 maxWait 的请求直接拒绝
+func (s *ConcurrencyService) EnableAdmissionControl(maxWait time.Duration) {
+	s.admission = NewAdmissionController(maxWait)
+}
+
+// EnterAdmission 判断请求能否进入账号等待队列；未启用时始终放行（ticket 为 nil）
+func (s *ConcurrencyService) EnterAdmission(accountID int64, timeout time.Duration) (*AdmissionTicket, time.Duration, bool) {
+	return s.admission.Enter(accountID, timeout)
+}
+
 // AcquireResult represents the result of acquiring a concurrency slot
 type AcquireResult struct {
 	Acquired    bool
@@ -113,7 +144,7 @@ func (s *ConcurrencyService) AcquireAccountSlot(ctx context.Context, accountID i
 	// Generate unique request ID for this slot
 	requestID := generateRequestID()
 
-	acquired, err := s.cache.AcquireAccountSlot(ctx, accountID, maxConcurrency, requestID)
+	acquired, err := s.acquireAccountSlotLocked(ctx, accountID, maxConcurrency, requestID)
 	if err != nil {
 		return nil, err
 	}
@@ -127,6 +158,7 @@ func (s *ConcurrencyService) AcquireAccountSlot(ctx context.Context, accountID i
 				if err := s.cache.ReleaseAccountSlot(bgCtx, accountID, requestID); err != nil {
 					logger.LegacyPrintf("service.concurrency", "Warning: failed to release account slot for %d (req=%s): %v", accountID, requestID, err)
 				}
+				s.admission.RecordCompletion(accountID)
 			},
 		}, nil
 	}
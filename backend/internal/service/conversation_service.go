@@ -0,0 +1,456 @@
+package service
+
+import (
+	"bytes"
+	"context"
+	"encoding/json"
+	"errors"
+	"net/http"
+	"regexp"
+	"strconv"
+	"strings"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	infraerrors "github.com/Wei-Shaw/sub2api/internal/pkg/errors"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/logger"
+	"github.com/gin-gonic/gin"
+	"github.com/google/uuid"
+	"github.com/tidwall/gjson"
+	"github.com/tidwall/sjson"
+)
+
+// ConversationIDHeader 请求头方式携带会话 ID（请求体 conversation_id 优先）
+const ConversationIDHeader = "X-Conversation-ID"
+
+var (
+	ErrConversationNotFound        = infraerrors.NotFound("CONVERSATION_NOT_FOUND", "conversation not found")
+	ErrConversationDisabled        = infraerrors.BadRequest("CONVERSATION_DISABLED", "conversation persistence is not enabled")
+	ErrConversationInvalidID       = infraerrors.BadRequest("CONVERSATION_INVALID_ID", "conversation_id must be 1-128 characters of letters, digits, '-', '_', '.' or ':'")
+	ErrConversationInvalidMessages = infraerrors.BadRequest("CONVERSATION_INVALID_MESSAGES", "messages must be a non-empty array starting with a user message when conversation_id is set")
+	ErrConversationExists          = infraerrors.Conflict("CONVERSATION_EXISTS", "conversation already exists")
+	ErrConversationInvalidForkSeq  = infraerrors.BadRequest("CONVERSATION_INVALID_FORK_SEQ", "seq must refer to an existing message of the conversation")
+)
+
+var conversationIDPattern = regexp.MustCompile(`^[A-Za-z0-9._:-]{1,128}$`)
+
+// Conversation 服务端持久化的会话（按用户隔离，ID 由客户端指定）
+//
+// 分支会话只保存分叉点之后的消息，seq <= ParentSeq 的历史从 ParentID 会话（及其祖先）读取；
+// MessageCount 包含继承的消息数，seq 在整条链上连续。
+type Conversation struct {
+	ID           string          `json:"id"`
+	UserID       int64           `json:"-"`
+	APIKeyID     *int64          `json:"api_key_id,omitempty"`
+	Model        string          `json:"model"`
+	System       json.RawMessage `json:"system,omitempty"`
+	MessageCount int             `json:"message_count"`
+	ParentID     *string         `json:"parent_id,omitempty"`
+	ParentSeq    *int            `json:"parent_seq,omitempty"`
+	CreatedAt    time.Time       `json:"created_at"`
+	UpdatedAt    time.Time       `json:"updated_at"`
+	ExpiresAt    time.Time       `json:"expires_at"`
+}
+
+// ConversationMessage 会话中的一条消息（Anthropic Messages 格式）
+type ConversationMessage struct {
+	Seq       int             `json:"seq"`
+	Role      string          `json:"role"`
+	Content   json.RawMessage `json:"content"`
+	CreatedAt time.Time       `json:"created_at"`
+}
+
+// ConversationRepository 会话存储
+type ConversationRepository interface {
+	// Get 查询未过期的会话，不存在时返回 ErrConversationNotFound
+	Get(ctx context.Context, userID int64, id string) (*Conversation, error)
+	// ListMessages 按 seq 升序返回最近 limit 条消息（含从祖先会话继承的消息，limit <= 0 表示全部）
+	ListMessages(ctx context.Context, userID int64, id string, limit int) ([]ConversationMessage, error)
+	// AppendTurn 创建或更新会话（model、system、过期时间）并在末尾追加消息，同时延长祖先会话的过期时间
+	AppendTurn(ctx context.Context, conv *Conversation, messages []ConversationMessage) error
+	// Create 创建不含消息的会话（分支），ID 已存在时返回 ErrConversationExists
+	Create(ctx context.Context, conv *Conversation) error
+	// ListTree 返回 id 所在会话树的全部未过期会话（根会话在前，按创建时间排序）
+	ListTree(ctx context.Context, userID int64, id string) ([]*Conversation, error)
+	// ListRoots 按最近更新时间返回用户的根会话
+	ListRoots(ctx context.Context, userID int64, limit int) ([]*Conversation, error)
+	// Delete 删除会话及其消息和全部分支，不存在时返回 ErrConversationNotFound
+	Delete(ctx context.Context, userID int64, id string) error
+	DeleteExpired(ctx context.Context) (int64, error)
+}
+
+// ConversationService 服务端会话持久化：客户端携带 conversation_id 时只需发送新消息，
+// 网关补全历史、按配置截断后转发，并在成功响应后追加本轮消息。
+type ConversationService struct {
+	repo ConversationRepository
+	cfg  config.ConversationConfig
+}
+
+// NewConversationService creates a new ConversationService
+func NewConversationService(repo ConversationRepository, cfg *config.Config) *ConversationService {
+	return &ConversationService{repo: repo, cfg: cfg.Conversation}
+}
+
+// ConversationTurn 一次携带 conversation_id 的请求；主请求成功后调用 Complete 写入本轮消息
+type ConversationTurn struct {
+	svc         *ConversationService
+	ctx         context.Context
+	conv        *Conversation
+	newMessages []ConversationMessage
+	stream      bool
+	capture     *trafficMirrorCaptureWriter
+}
+
+// ID 会话 ID
+func (t *ConversationTurn) ID() string {
+	if t == nil {
+		return ""
+	}
+	return t.conv.ID
+}
+
+// SessionKey 粘性会话依据：同一会话的所有请求调度到同一账号
+func (t *ConversationTurn) SessionKey() string {
+	if t == nil {
+		return ""
+	}
+	return "conversation:" + strconv.FormatInt(t.conv.UserID, 10) + ":" + t.conv.ID
+}
+
+// Prepare 处理 /v1/messages 请求中的 conversation_id：未携带时原样返回 body 与 nil；
+// 携带时返回补全历史后的请求体，并包装响应写入器以便记录本轮回复。
+func (s *ConversationService) Prepare(c *gin.Context, apiKey *APIKey, body []byte) ([]byte, *ConversationTurn, error) {
+	id := strings.TrimSpace(gjson.GetBytes(body, "conversation_id").String())
+	if id == "" {
+		id = strings.TrimSpace(c.GetHeader(ConversationIDHeader))
+	}
+	if id == "" {
+		return body, nil, nil
+	}
+	if s == nil || !s.cfg.Enabled || s.repo == nil || apiKey == nil {
+		return body, nil, ErrConversationDisabled
+	}
+	if !conversationIDPattern.MatchString(id) {
+		return body, nil, ErrConversationInvalidID
+	}
+	body, err := sjson.DeleteBytes(body, "conversation_id")
+	if err != nil {
+		return body, nil, ErrConversationInvalidMessages
+	}
+
+	rawMessages := gjson.GetBytes(body, "messages").Array()
+	if len(rawMessages) == 0 || rawMessages[0].Get("role").String() != "user" {
+		return body, nil, ErrConversationInvalidMessages
+	}
+	newMessages := make([]ConversationMessage, 0, len(rawMessages))
+	for _, msg := range rawMessages {
+		content := msg.Get("content")
+		if !content.Exists() {
+			return body, nil, ErrConversationInvalidMessages
+		}
+		newMessages = append(newMessages, ConversationMessage{Role: msg.Get("role").String(), Content: json.RawMessage(content.Raw)})
+	}
+
+	ctx := c.Request.Context()
+	conv, err := s.repo.Get(ctx, apiKey.UserID, id)
+	var history []ConversationMessage
+	switch {
+	case errors.Is(err, ErrConversationNotFound):
+		conv = &Conversation{ID: id, UserID: apiKey.UserID}
+	case err != nil:
+		return body, nil, err
+	default:
+		if history, err = s.repo.ListMessages(ctx, apiKey.UserID, id, s.cfg.MaxMessages); err != nil {
+			return body, nil, err
+		}
+	}
+	apiKeyID := apiKey.ID
+	conv.APIKeyID = &apiKeyID
+	conv.Model = gjson.GetBytes(body, "model").String()
+
+	// 未携带 system 时沿用会话中保存的 system
+	if system := gjson.GetBytes(body, "system"); system.Exists() {
+		conv.System = json.RawMessage(system.Raw)
+	} else if len(conv.System) > 0 {
+		if body, err = sjson.SetRawBytes(body, "system", conv.System); err != nil {
+			return body, nil, err
+		}
+	}
+
+	merged := append(history, newMessages...)
+	start := truncateConversation(merged, len(history), s.cfg.MaxMessages, s.cfg.MaxContextBytes)
+	if body, err = sjson.SetRawBytes(body, "messages", marshalConversationMessages(merged[start:])); err != nil {
+		return body, nil, err
+	}
+
+	capture := &trafficMirrorCaptureWriter{ResponseWriter: c.Writer, limit: s.cfg.MaxResponseBytes}
+	c.Writer = capture
+	return body, &ConversationTurn{
+		svc:         s,
+		ctx:         context.WithoutCancel(ctx),
+		conv:        conv,
+		newMessages: newMessages,
+		stream:      gjson.GetBytes(body, "stream").Bool(),
+		capture:     capture,
+	}, nil
+}
+
+// Complete 主请求成功后写入本轮消息（请求中的新消息 + 助手回复）；turn 为 nil 时为空操作。
+// 同步写入，保证客户端收到响应后立即发起的下一轮请求能读到本轮历史。
+func (t *ConversationTurn) Complete() {
+	if t == nil || t.capture.Status() != http.StatusOK {
+		return
+	}
+	if t.capture.truncated() {
+		logger.LegacyPrintf("service.conversation", "[Conversation] response of %s exceeds max_response_bytes, turn not stored", t.conv.ID)
+		return
+	}
+	var (
+		assistant json.RawMessage
+		ok        bool
+	)
+	if t.stream {
+		assistant, ok = assembleStreamedAssistantContent(t.capture.buf.Bytes())
+	} else {
+		content := gjson.GetBytes(t.capture.buf.Bytes(), "content")
+		assistant, ok = json.RawMessage(content.Raw), content.IsArray()
+	}
+	if !ok {
+		return
+	}
+
+	messages := append(append([]ConversationMessage(nil), t.newMessages...), ConversationMessage{Role: "assistant", Content: assistant})
+	t.conv.ExpiresAt = time.Now().Add(time.Duration(t.svc.cfg.TTLDays) * 24 * time.Hour)
+	ctx, cancel := context.WithTimeout(t.ctx, 10*time.Second)
+	defer cancel()
+	if err := t.svc.repo.AppendTurn(ctx, t.conv, messages); err != nil {
+		logger.LegacyPrintf("service.conversation", "[Conversation] append turn to %s failed: %v", t.conv.ID, err)
+	}
+}
+
+// Get 查询会话及全部消息
+func (s *ConversationService) Get(ctx context.Context, userID int64, id string) (*Conversation, []ConversationMessage, error) {
+	if s == nil || !s.cfg.Enabled {
+		return nil, nil, ErrConversationDisabled
+	}
+	conv, err := s.repo.Get(ctx, userID, id)
+	if err != nil {
+		return nil, nil, err
+	}
+	messages, err := s.repo.ListMessages(ctx, userID, id, 0)
+	if err != nil {
+		return nil, nil, err
+	}
+	return conv, messages, nil
+}
+
+// Delete 删除会话
+func (s *ConversationService) Delete(ctx context.Context, userID int64, id string) error {
+	if s == nil || !s.cfg.Enabled {
+		return ErrConversationDisabled
+	}
+	return s.repo.Delete(ctx, userID, id)
+}
+
+// Fork 从会话的第 seq 条消息处分叉出新会话（newID 为空时自动生成），新会话继承 seq 及之前的历史
+func (s *ConversationService) Fork(ctx context.Context, userID, apiKeyID int64, id string, seq int, newID string) (*Conversation, error) {
+	if s == nil || !s.cfg.Enabled {
+		return nil, ErrConversationDisabled
+	}
+	newID = strings.TrimSpace(newID)
+	if newID == "" {
+		newID = uuid.NewString()
+	}
+	if !conversationIDPattern.MatchString(newID) {
+		return nil, ErrConversationInvalidID
+	}
+	parent, err := s.repo.Get(ctx, userID, id)
+	if err != nil {
+		return nil, err
+	}
+	if seq < 1 || seq > parent.MessageCount {
+		return nil, ErrConversationInvalidForkSeq
+	}
+
+	fork := &Conversation{
+		ID:           newID,
+		UserID:       userID,
+		APIKeyID:     &apiKeyID,
+		Model:        parent.Model,
+		System:       parent.System,
+		MessageCount: seq,
+		ParentID:     &parent.ID,
+		ParentSeq:    &seq,
+		ExpiresAt:    time.Now().Add(time.Duration(s.cfg.TTLDays) * 24 * time.Hour),
+	}
+	if err := s.repo.Create(ctx, fork); err != nil {
+		return nil, err
+	}
+	return fork, nil
+}
+
+// Tree 返回会话所在的整棵分支树
+func (s *ConversationService) Tree(ctx context.Context, userID int64, id string) ([]*Conversation, error) {
+	if s == nil || !s.cfg.Enabled {
+		return nil, ErrConversationDisabled
+	}
+	tree, err := s.repo.ListTree(ctx, userID, id)
+	if err != nil {
+		return nil, err
+	}
+	if len(tree) == 0 {
+		return nil, ErrConversationNotFound
+	}
+	return tree, nil
+}
+
+// List 按最近更新时间列出用户的根会话（分支通过 Tree 查询）
+func (s *ConversationService) List(ctx context.Context, userID int64, limit int) ([]*Conversation, error) {
+	if s == nil || !s.cfg.Enabled {
+		return nil, ErrConversationDisabled
+	}
+	if limit <= 0 || limit > 100 {
+		limit = 20
+	}
+	return s.repo.ListRoots(ctx, userID, limit)
+}
+
+// PurgeExpired 删除已过期的会话（定时任务调用）
+func (s *ConversationService) PurgeExpired(ctx context.Context) error {
+	if s == nil || !s.cfg.Enabled {
+		return nil
+	}
+	deleted, err := s.repo.DeleteExpired(ctx)
+	if err != nil {
+		return err
+	}
+	if deleted > 0 {
+		logger.LegacyPrintf("service.conversation", "[Conversation] purged %d expired conversations", deleted)
+	}
+	return nil
+}
+
+// truncateConversation 返回需保留消息的起始下标：从最早的消息开始按整轮丢弃，直到条数与大小都在限制内。
+// 只在普通用户消息（非 tool_result）处截断，保证首条消息为 user 且不拆开工具调用（历史只加载了最近部分时同样适用）；
+// 本轮新消息（下标 >= newStart）始终保留。
+func truncateConversation(messages []ConversationMessage, newStart, maxMessages, maxBytes int) int {
+	size := 0
+	for _, msg := range messages {
+		size += len(msg.Content)
+	}
+	for start := 0; start < newStart; start++ {
+		if !isConversationTurnStart(messages[start]) {
+			size -= len(messages[start].Content)
+			continue
+		}
+		if len(messages)-start <= maxMessages && size <= maxBytes {
+			return start
+		}
+		size -= len(messages[start].Content)
+	}
+	return newStart
+}
+
+func isConversationTurnStart(msg ConversationMessage) bool {
+	if msg.Role != "user" {
+		return false
+	}
+	content := gjson.ParseBytes(msg.Content)
+	if !content.IsArray() {
+		return true
+	}
+	for _, block := range content.Array() {
+		if block.Get("type").String() == "tool_result" {
+			return false
+		}
+	}
+	return true
+}
+
+func marshalConversationMessages(messages []ConversationMessage) []byte {
+	var buf bytes.Buffer
+	buf.WriteByte('[')
+	for i, msg := range messages {
+		if i > 0 {
+			buf.WriteByte(',')
+		}
+		role, _ := json.Marshal(msg.Role)
+		buf.WriteString(`{"role":`)
+		buf.Write(role)
+		buf.WriteString(`,"content":`)
+		buf.Write(msg.Content)
+		buf.WriteByte('}')
+	}
+	buf.WriteByte(']')
+	return buf.Bytes()
+}
+
+// assembleStreamedAssistantContent 从 Anthropic SSE 响应还原助手消息的 content 数组
+func assembleStreamedAssistantContent(stream []byte) (json.RawMessage, bool) {
+	type streamBlock struct {
+		block       map[string]any
+		partialJSON strings.Builder
+	}
+	var (
+		blocks    []*streamBlock
+		completed bool
+	)
+	for _, line := range bytes.Split(stream, []byte("\n")) {
+		data, found := bytes.CutPrefix(bytes.TrimSpace(line), []byte("data:"))
+		if !found {
+			continue
+		}
+		event := gjson.ParseBytes(bytes.TrimSpace(data))
+		switch event.Get("type").String() {
+		case "content_block_start":
+			var block map[string]any
+			if err := json.Unmarshal([]byte(event.Get("content_block").Raw), &block); err != nil {
+				return nil, false
+			}
+			blocks = append(blocks, &streamBlock{block: block})
+		case "content_block_delta":
+			idx := int(event.Get("index").Int())
+			if idx < 0 || idx >= len(blocks) {
+				return nil, false
+			}
+			b, delta := blocks[idx], event.Get("delta")
+			switch delta.Get("type").String() {
+			case "text_delta":
+				b.block["text"] = conversationStringField(b.block, "text") + delta.Get("text").String()
+			case "thinking_delta":
+				b.block["thinking"] = conversationStringField(b.block, "thinking") + delta.Get("thinking").String()
+			case "signature_delta":
+				b.block["signature"] = conversationStringField(b.block, "signature") + delta.Get("signature").String()
+			case "input_json_delta":
+				b.partialJSON.WriteString(delta.Get("partial_json").String())
+			}
+		case "message_stop":
+			completed = true
+		}
+	}
+	if !completed || len(blocks) == 0 {
+		return nil, false
+	}
+
+	content := make([]map[string]any, 0, len(blocks))
+	for _, b := range blocks {
+		if b.partialJSON.Len() > 0 {
+			var input any
+			if err := json.Unmarshal([]byte(b.partialJSON.String()), &input); err != nil {
+				return nil, false
+			}
+			b.block["input"] = input
+		}
+		content = append(content, b.block)
+	}
+	out, err := json.Marshal(content)
+	if err != nil {
+		return nil, false
+	}
+	return out, true
+}
+
+func conversationStringField(m map[string]any, key string) string {
+	s, _ := m[key].(string)
+	return s
+}
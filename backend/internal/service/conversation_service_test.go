@@ -0,0 +1,273 @@
+//go:build unit
+
+package service
+
+import (
+	"context"
+	"encoding/json"
+	"net/http"
+	"net/http/httptest"
+	"strings"
+	"testing"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/gin-gonic/gin"
+	"github.com/stretchr/testify/require"
+	"github.com/tidwall/gjson"
+)
+
+type conversationRepoStub struct {
+	convs    map[string]*Conversation
+	messages map[string][]ConversationMessage
+}
+
+func newConversationRepoStub() *conversationRepoStub {
+	return &conversationRepoStub{convs: map[string]*Conversation{}, messages: map[string][]ConversationMessage{}}
+}
+
+func (r *conversationRepoStub) Get(_ context.Context, _ int64, id string) (*Conversation, error) {
+	conv, ok := r.convs[id]
+	if !ok {
+		return nil, ErrConversationNotFound
+	}
+	cp := *conv
+	return &cp, nil
+}
+
+func (r *conversationRepoStub) ListMessages(_ context.Context, _ int64, id string, limit int) ([]ConversationMessage, error) {
+	var msgs []ConversationMessage
+	upto := int(^uint(0) >> 1)
+	for conv := r.convs[id]; conv != nil; {
+		from := 0
+		if conv.ParentSeq != nil {
+			from = *conv.ParentSeq
+		}
+		own := make([]ConversationMessage, 0)
+		for _, msg := range r.messages[conv.ID] {
+			if msg.Seq > from && msg.Seq <= upto {
+				own = append(own, msg)
+			}
+		}
+		msgs = append(own, msgs...)
+		if conv.ParentID == nil {
+			break
+		}
+		upto = from
+		conv = r.convs[*conv.ParentID]
+	}
+	if limit > 0 && len(msgs) > limit {
+		msgs = msgs[len(msgs)-limit:]
+	}
+	return msgs, nil
+}
+
+func (r *conversationRepoStub) AppendTurn(_ context.Context, conv *Conversation, messages []ConversationMessage) error {
+	if existing, ok := r.convs[conv.ID]; ok {
+		conv.MessageCount, conv.ParentID, conv.ParentSeq = existing.MessageCount, existing.ParentID, existing.ParentSeq
+	}
+	for _, msg := range messages {
+		conv.MessageCount++
+		msg.Seq = conv.MessageCount
+		r.messages[conv.ID] = append(r.messages[conv.ID], msg)
+	}
+	cp := *conv
+	r.convs[conv.ID] = &cp
+	return nil
+}
+
+func (r *conversationRepoStub) Create(_ context.Context, conv *Conversation) error {
+	if _, ok := r.convs[conv.ID]; ok {
+		return ErrConversationExists
+	}
+	cp := *conv
+	r.convs[conv.ID] = &cp
+	return nil
+}
+
+func (r *conversationRepoStub) ListTree(context.Context, int64, string) ([]*Conversation, error) {
+	var out []*Conversation
+	for _, conv := range r.convs {
+		out = append(out, conv)
+	}
+	return out, nil
+}
+
+func (r *conversationRepoStub) ListRoots(context.Context, int64, int) ([]*Conversation, error) {
+	return nil, nil
+}
+
+func (r *conversationRepoStub) Delete(_ context.Context, _ int64, id string) error {
+	if _, ok := r.convs[id]; !ok {
+		return ErrConversationNotFound
+	}
+	delete(r.convs, id)
+	delete(r.messages, id)
+	return nil
+}
+
+func (r *conversationRepoStub) DeleteExpired(context.Context) (int64, error) {
+	return 0, nil
+}
+
+func newConversationServiceForTest() (*ConversationService, *conversationRepoStub) {
+	repo := newConversationRepoStub()
+	cfg := &config.Config{Conversation: config.ConversationConfig{
+		Enabled:          true,
+		MaxMessages:      200,
+		MaxContextBytes:  1 << 20,
+		MaxResponseBytes: 1 << 20,
+		TTLDays:          30,
+	}}
+	return NewConversationService(repo, cfg), repo
+}
+
+func conversationTestContext(body string) *gin.Context {
+	c, _ := gin.CreateTestContext(httptest.NewRecorder())
+	c.Request = httptest.NewRequest(http.MethodPost, "/v1/messages", strings.NewReader(body))
+	return c
+}
+
+func TestConversationService_PrepareAndComplete(t *testing.T) {
+	svc, repo := newConversationServiceForTest()
+	apiKey := &APIKey{ID: 3, UserID: 9}
+
+	first := `{"model":"claude","system":"be brief","conversation_id":"chat-1","messages":[{"role":"user","content":"hi"}]}`
+	c := conversationTestContext(first)
+	body, turn, err := svc.Prepare(c, apiKey, []byte(first))
+	require.NoError(t, err)
+	require.NotNil(t, turn)
+	require.False(t, gjson.GetBytes(body, "conversation_id").Exists())
+	require.Equal(t, "conversation:9:chat-1", turn.SessionKey())
+	c.JSON(http.StatusOK, gin.H{"role": "assistant", "content": []gin.H{{"type": "text", "text": "hello"}}})
+	turn.Complete()
+	require.Len(t, repo.messages["chat-1"], 2)
+	require.JSONEq(t, `"be brief"`, string(repo.convs["chat-1"].System))
+
+	// 第二轮只发送新消息，历史与 system 由服务端补全
+	second := `{"model":"claude","messages":[{"role":"user","content":"again"}],"stream":true}`
+	c = conversationTestContext(second)
+	c.Request.Header.Set(ConversationIDHeader, "chat-1")
+	body, turn, err = svc.Prepare(c, apiKey, []byte(second))
+	require.NoError(t, err)
+	require.Equal(t, "be brief", gjson.GetBytes(body, "system").String())
+	messages := gjson.GetBytes(body, "messages").Array()
+	require.Len(t, messages, 3)
+	require.Equal(t, "hi", messages[0].Get("content").String())
+	require.Equal(t, "hello", messages[1].Get("content.0.text").String())
+	require.Equal(t, "again", messages[2].Get("content").String())
+
+	c.Header("Content-Type", "text/event-stream")
+	c.Status(http.StatusOK)
+	_, _ = c.Writer.WriteString(strings.Join([]string{
+		`event: message_start`,
+		`data: {"type":"message_start","message":{"role":"assistant","content":[]}}`,
+		`data: {"type":"content_block_start","index":0,"content_block":{"type":"text","text":""}}`,
+		`data: {"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"sure"}}`,
+		`data: {"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":" thing"}}`,
+		`data: {"type":"content_block_start","index":1,"content_block":{"type":"tool_use","id":"tu_1","name":"search","input":{}}}`,
+		`data: {"type":"content_block_delta","index":1,"delta":{"type":"input_json_delta","partial_json":"{\"q\":"}}`,
+		`data: {"type":"content_block_delta","index":1,"delta":{"type":"input_json_delta","partial_json":"\"go\"}"}}`,
+		`data: {"type":"message_stop"}`,
+	}, "\n\n"))
+	turn.Complete()
+	stored := repo.messages["chat-1"]
+	require.Len(t, stored, 4)
+	require.JSONEq(t, `[{"type":"text","text":"sure thing"},{"type":"tool_use","id":"tu_1","name":"search","input":{"q":"go"}}]`, string(stored[3].Content))
+}
+
+func TestConversationService_Fork(t *testing.T) {
+	svc, repo := newConversationServiceForTest()
+	apiKey := &APIKey{ID: 3, UserID: 9}
+	ctx := context.Background()
+
+	conv := &Conversation{ID: "root", UserID: 9, System: json.RawMessage(`"sys"`)}
+	require.NoError(t, repo.AppendTurn(ctx, conv, []ConversationMessage{
+		{Role: "user", Content: json.RawMessage(`"q1"`)},
+		{Role: "assistant", Content: json.RawMessage(`"a1"`)},
+		{Role: "user", Content: json.RawMessage(`"q2"`)},
+		{Role: "assistant", Content: json.RawMessage(`"a2"`)},
+	}))
+
+	_, err := svc.Fork(ctx, 9, 3, "root", 5, "alt")
+	require.ErrorIs(t, err, ErrConversationInvalidForkSeq)
+	_, err = svc.Fork(ctx, 9, 3, "missing", 1, "alt")
+	require.ErrorIs(t, err, ErrConversationNotFound)
+
+	fork, err := svc.Fork(ctx, 9, 3, "root", 2, "alt")
+	require.NoError(t, err)
+	require.Equal(t, "root", *fork.ParentID)
+	require.Equal(t, 2, fork.MessageCount)
+	_, err = svc.Fork(ctx, 9, 3, "root", 2, "alt")
+	require.ErrorIs(t, err, ErrConversationExists)
+	generated, err := svc.Fork(ctx, 9, 3, "root", 2, "")
+	require.NoError(t, err)
+	require.NotEmpty(t, generated.ID)
+
+	// 分支只继承分叉点之前的历史，并沿用 system
+	req := `{"model":"claude","conversation_id":"alt","messages":[{"role":"user","content":"q2 alt"}]}`
+	c := conversationTestContext(req)
+	body, turn, err := svc.Prepare(c, apiKey, []byte(req))
+	require.NoError(t, err)
+	require.Equal(t, "sys", gjson.GetBytes(body, "system").String())
+	messages := gjson.GetBytes(body, "messages").Array()
+	require.Len(t, messages, 3)
+	require.Equal(t, "a1", messages[1].Get("content").String())
+	require.Equal(t, "q2 alt", messages[2].Get("content").String())
+
+	c.JSON(http.StatusOK, gin.H{"content": []gin.H{{"type": "text", "text": "a2 alt"}}})
+	turn.Complete()
+	require.Equal(t, 3, repo.messages["alt"][0].Seq)
+	history, err := repo.ListMessages(ctx, 9, "alt", 0)
+	require.NoError(t, err)
+	require.Len(t, history, 4)
+	// 原会话不受影响
+	history, err = repo.ListMessages(ctx, 9, "root", 0)
+	require.NoError(t, err)
+	require.Equal(t, `"a2"`, string(history[3].Content))
+}
+
+func TestConversationService_PrepareErrors(t *testing.T) {
+	svc, _ := newConversationServiceForTest()
+	apiKey := &APIKey{ID: 3, UserID: 9}
+
+	plain := `{"model":"claude","messages":[{"role":"user","content":"hi"}]}`
+	body, turn, err := svc.Prepare(conversationTestContext(plain), apiKey, []byte(plain))
+	require.NoError(t, err)
+	require.Nil(t, turn)
+	require.Equal(t, plain, string(body))
+
+	invalidID := `{"conversation_id":"bad id!","messages":[{"role":"user","content":"hi"}]}`
+	_, _, err = svc.Prepare(conversationTestContext(invalidID), apiKey, []byte(invalidID))
+	require.ErrorIs(t, err, ErrConversationInvalidID)
+
+	noUser := `{"conversation_id":"c","messages":[{"role":"assistant","content":"x"}]}`
+	_, _, err = svc.Prepare(conversationTestContext(noUser), apiKey, []byte(noUser))
+	require.ErrorIs(t, err, ErrConversationInvalidMessages)
+
+	svc.cfg.Enabled = false
+	withID := `{"conversation_id":"c","messages":[{"role":"user","content":"hi"}]}`
+	_, _, err = svc.Prepare(conversationTestContext(withID), apiKey, []byte(withID))
+	require.ErrorIs(t, err, ErrConversationDisabled)
+}
+
+func TestTruncateConversation(t *testing.T) {
+	msg := func(role, content string) ConversationMessage {
+		return ConversationMessage{Role: role, Content: json.RawMessage(content)}
+	}
+	messages := []ConversationMessage{
+		msg("user", `"q1"`),
+		msg("assistant", `[{"type":"tool_use","id":"t","name":"n","input":{}}]`),
+		msg("user", `[{"type":"tool_result","tool_use_id":"t","content":"r"}]`),
+		msg("assistant", `"a1"`),
+		msg("user", `"q2"`),
+		msg("assistant", `"a2"`),
+		msg("user", `"q3"`),
+	}
+
+	require.Equal(t, 0, truncateConversation(messages, 6, 10, 1<<20))
+	// 不在 tool_result 处截断，整轮丢弃到下一条普通用户消息
+	require.Equal(t, 4, truncateConversation(messages, 6, 5, 1<<20))
+	require.Equal(t, 4, truncateConversation(messages, 6, 10, 20))
+	// 新消息始终保留
+	require.Equal(t, 6, truncateConversation(messages, 6, 1, 1))
+}
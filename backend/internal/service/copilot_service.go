@@ -0,0 +1,329 @@
+package service
+
+import (
+	"context"
+	"log/slog"
+	"net/http"
+	"strconv"
+	"strings"
+	"sync"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/pkg/copilot"
+	infraerrors "github.com/Wei-Shaw/sub2api/internal/pkg/errors"
+)
+
+const (
+	// copilotTokenCacheSkew Copilot token 有效期约 30 分钟，提前失效以免请求途中过期
+	copilotTokenCacheSkew   = 2 * time.Minute
+	copilotTokenMinCacheTTL = 30 * time.Second
+)
+
+// CopilotAuthClient GitHub 设备码登录、Copilot token 交换与模型列表接口
+type CopilotAuthClient interface {
+	RequestDeviceCode(ctx context.Context, proxyURL string) (*copilot.DeviceCodeResponse, error)
+	PollAccessToken(ctx context.Context, deviceCode, proxyURL string) (*copilot.AccessTokenResponse, error)
+	RefreshAccessToken(ctx context.Context, refreshToken, proxyURL string) (*copilot.AccessTokenResponse, error)
+	ExchangeToken(ctx context.Context, githubToken, proxyURL string) (*copilot.Token, error)
+	ListModels(ctx context.Context, apiBaseURL, copilotToken, proxyURL string) ([]copilot.Model, error)
+}
+
+// CopilotTokenCacheKey 生成 Copilot 短期 token 的缓存键
+// 格式: "copilot:account:{account_id}"
+func CopilotTokenCacheKey(account *Account) string {
+	return "copilot:account:" + strconv.FormatInt(account.ID, 10)
+}
+
+type copilotDeviceSession struct {
+	deviceCode string
+	proxyURL   string
+	expiresAt  time.Time
+}
+
+// CopilotService 管理 GitHub Copilot 账号的设备码登录、token 交换与刷新
+type CopilotService struct {
+	authClient  CopilotAuthClient
+	proxyRepo   ProxyRepository
+	accountRepo AccountRepository
+	tokenCache  GeminiTokenCache
+
+	mu       sync.Mutex
+	sessions map[string]*copilotDeviceSession
+}
+
+// NewCopilotService 创建 Copilot 服务
+func NewCopilotService(authClient CopilotAuthClient, proxyRepo ProxyRepository, accountRepo AccountRepository, tokenCache GeminiTokenCache) *CopilotService {
+	return &CopilotService{
+		authClient:  authClient,
+		proxyRepo:   proxyRepo,
+		accountRepo: accountRepo,
+		tokenCache:  tokenCache,
+		sessions:    make(map[string]*copilotDeviceSession),
+	}
+}
+
+// CopilotDeviceAuthResult 设备码登录发起结果，管理员需在 VerificationURI 输入 UserCode
+type CopilotDeviceAuthResult struct {
+	SessionID       string `json:"session_id"`
+	UserCode        string `json:"user_code"`
+	VerificationURI string `json:"verification_uri"`
+	ExpiresIn       int64  `json:"expires_in"`
+	Interval        int64  `json:"interval"`
+}
+
+// 设备码轮询状态
+const (
+	CopilotDeviceStatusPending    = "pending"
+	CopilotDeviceStatusSlowDown   = "slow_down"
+	CopilotDeviceStatusAuthorized = "authorized"
+	CopilotDeviceStatusExpired    = "expired"
+	CopilotDeviceStatusDenied     = "denied"
+)
+
+// CopilotDevicePollResult 设备码轮询结果；authorized 时 Credentials 可直接用于创建账号
+type CopilotDevicePollResult struct {
+	Status      string         `json:"status"`
+	Interval    int64          `json:"interval,omitempty"`
+	SKU         string         `json:"sku,omitempty"`
+	Credentials map[string]any `json:"credentials,omitempty"`
+}
+
+// CopilotSessionToken 交换得到的短期 Copilot token 及其 API 地址
+type CopilotSessionToken struct {
+	Token      string
+	APIBaseURL string
+}
+
+func (s *CopilotService) resolveProxyURL(ctx context.Context, proxyID *int64) (string, error) {
+	if proxyID == nil {
+		return "", nil
+	}
+	proxy, err := s.proxyRepo.GetByID(ctx, *proxyID)
+	if err != nil {
+		return "", infraerrors.Newf(http.StatusBadRequest, "COPILOT_PROXY_NOT_FOUND", "proxy not found: %v", err)
+	}
+	if proxy == nil {
+		return "", nil
+	}
+	return proxy.URL(), nil
+}
+
+func copilotAccountProxyURL(account *Account) string {
+	if account.ProxyID != nil && account.Proxy != nil {
+		return account.Proxy.URL()
+	}
+	return ""
+}
+
+// StartDeviceAuth 发起 GitHub 设备码登录
+func (s *CopilotService) StartDeviceAuth(ctx context.Context, proxyID *int64) (*CopilotDeviceAuthResult, error) {
+	proxyURL, err := s.resolveProxyURL(ctx, proxyID)
+	if err != nil {
+		return nil, err
+	}
+	device, err := s.authClient.RequestDeviceCode(ctx, proxyURL)
+	if err != nil {
+		return nil, err
+	}
+
+	ttl := time.Duration(device.ExpiresIn) * time.Second
+	if ttl <= 0 || ttl > copilot.DeviceSessionTTL {
+		ttl = copilot.DeviceSessionTTL
+	}
+	sessionID := randomHex(16)
+
+	s.mu.Lock()
+	now := time.Now()
+	for id, sess := range s.sessions {
+		if now.After(sess.expiresAt) {
+			delete(s.sessions, id)
+		}
+	}
+	s.sessions[sessionID] = &copilotDeviceSession{
+		deviceCode: device.DeviceCode,
+		proxyURL:   proxyURL,
+		expiresAt:  now.Add(ttl),
+	}
+	s.mu.Unlock()
+
+	return &CopilotDeviceAuthResult{
+		SessionID:       sessionID,
+		UserCode:        device.UserCode,
+		VerificationURI: device.VerificationURI,
+		ExpiresIn:       int64(ttl / time.Second),
+		Interval:        device.Interval,
+	}, nil
+}
+
+// PollDeviceAuth 轮询设备码登录结果。授权完成后立即交换一次 Copilot token，确认该 GitHub 账号有 Copilot 订阅。
+func (s *CopilotService) PollDeviceAuth(ctx context.Context, sessionID string) (*CopilotDevicePollResult, error) {
+	s.mu.Lock()
+	sess, ok := s.sessions[sessionID]
+	if ok && time.Now().After(sess.expiresAt) {
+		delete(s.sessions, sessionID)
+		ok = false
+	}
+	s.mu.Unlock()
+	if !ok {
+		return nil, infraerrors.NotFound("COPILOT_DEVICE_SESSION_NOT_FOUND", "device session not found or expired")
+	}
+
+	resp, err := s.authClient.PollAccessToken(ctx, sess.deviceCode, sess.proxyURL)
+	if err != nil {
+		return nil, err
+	}
+	switch resp.Error {
+	case "":
+	case copilot.ErrAuthorizationPending:
+		return &CopilotDevicePollResult{Status: CopilotDeviceStatusPending}, nil
+	case copilot.ErrSlowDown:
+		return &CopilotDevicePollResult{Status: CopilotDeviceStatusSlowDown, Interval: resp.Interval}, nil
+	case copilot.ErrExpiredToken:
+		s.deleteSession(sessionID)
+		return &CopilotDevicePollResult{Status: CopilotDeviceStatusExpired}, nil
+	case copilot.ErrAccessDenied:
+		s.deleteSession(sessionID)
+		return &CopilotDevicePollResult{Status: CopilotDeviceStatusDenied}, nil
+	default:
+		s.deleteSession(sessionID)
+		return nil, infraerrors.Newf(http.StatusBadGateway, "COPILOT_DEVICE_AUTH_FAILED", "device authorization failed: %s %s", resp.Error, resp.ErrorDescription)
+	}
+	if resp.AccessToken == "" {
+		return nil, infraerrors.New(http.StatusBadGateway, "COPILOT_DEVICE_AUTH_FAILED", "device authorization returned no access token")
+	}
+	s.deleteSession(sessionID)
+
+	token, err := s.authClient.ExchangeToken(ctx, resp.AccessToken, sess.proxyURL)
+	if err != nil {
+		return nil, err
+	}
+	return &CopilotDevicePollResult{
+		Status:      CopilotDeviceStatusAuthorized,
+		SKU:         token.SKU,
+		Credentials: BuildCopilotCredentials(resp),
+	}, nil
+}
+
+func (s *CopilotService) deleteSession(sessionID string) {
+	s.mu.Lock()
+	delete(s.sessions, sessionID)
+	s.mu.Unlock()
+}
+
+// BuildCopilotCredentials 将 GitHub access token 响应转换为账号凭证。
+// 启用了过期 token 的 GitHub App 会返回 refresh_token，此时记录过期时间交由后台刷新。
+func BuildCopilotCredentials(resp *copilot.AccessTokenResponse) map[string]any {
+	creds := map[string]any{
+		"github_token": resp.AccessToken,
+	}
+	if resp.RefreshToken != "" {
+		creds["github_refresh_token"] = resp.RefreshToken
+	}
+	if resp.ExpiresIn > 0 {
+		creds["github_token_expires_at"] = strconv.FormatInt(time.Now().Unix()+resp.ExpiresIn, 10)
+	}
+	return creds
+}
+
+// RefreshGitHubToken 使用 refresh_token 刷新 GitHub token，返回合并后的凭证
+func (s *CopilotService) RefreshGitHubToken(ctx context.Context, account *Account) (map[string]any, error) {
+	refreshToken := account.GetCredential("github_refresh_token")
+	if refreshToken == "" {
+		return nil, infraerrors.New(http.StatusBadRequest, "COPILOT_NO_REFRESH_TOKEN", "account has no github_refresh_token")
+	}
+	resp, err := s.authClient.RefreshAccessToken(ctx, refreshToken, copilotAccountProxyURL(account))
+	if err != nil {
+		return nil, err
+	}
+	if resp.Error != "" || resp.AccessToken == "" {
+		return nil, infraerrors.Newf(http.StatusUnauthorized, "COPILOT_TOKEN_REFRESH_FAILED", "github token refresh failed: %s %s", resp.Error, resp.ErrorDescription)
+	}
+
+	newCredentials := BuildCopilotCredentials(resp)
+	for k, v := range account.Credentials {
+		if _, exists := newCredentials[k]; !exists {
+			newCredentials[k] = v
+		}
+	}
+	return newCredentials, nil
+}
+
+// GetSessionToken 返回可用的短期 Copilot token，缓存未命中时用 GitHub token 交换。
+// 交换接口幂等且开销很小，并发未命中时各自交换即可，不加刷新锁。
+func (s *CopilotService) GetSessionToken(ctx context.Context, account *Account) (*CopilotSessionToken, error) {
+	if !account.IsCopilot() {
+		return nil, infraerrors.BadRequest("COPILOT_ACCOUNT_REQUIRED", "not a copilot account")
+	}
+	apiBaseURL := account.GetExtraString("copilot_api_base")
+	if apiBaseURL == "" {
+		apiBaseURL = copilot.DefaultAPIBaseURL
+	}
+
+	cacheKey := CopilotTokenCacheKey(account)
+	if s.tokenCache != nil {
+		if token, err := s.tokenCache.GetAccessToken(ctx, cacheKey); err == nil && strings.TrimSpace(token) != "" {
+			return &CopilotSessionToken{Token: token, APIBaseURL: apiBaseURL}, nil
+		} else if err != nil {
+			slog.Warn("copilot_token_cache_get_failed", "account_id", account.ID, "error", err)
+		}
+	}
+
+	githubToken := account.GetCredential("github_token")
+	if githubToken == "" {
+		return nil, infraerrors.Unauthorized("COPILOT_NO_GITHUB_TOKEN", "account has no github_token")
+	}
+	token, err := s.authClient.ExchangeToken(ctx, githubToken, copilotAccountProxyURL(account))
+	if err != nil {
+		return nil, err
+	}
+
+	if s.tokenCache != nil {
+		ttl := time.Until(time.Unix(token.ExpiresAt, 0)) - copilotTokenCacheSkew
+		if ttl < copilotTokenMinCacheTTL {
+			ttl = copilotTokenMinCacheTTL
+		}
+		if err := s.tokenCache.SetAccessToken(ctx, cacheKey, token.Token, ttl); err != nil {
+			slog.Warn("copilot_token_cache_set_failed", "account_id", account.ID, "error", err)
+		}
+	}
+
+	// 个人 / 商业 / 企业版的 API 地址不同，变化时回写到 extra 供后续请求直接使用
+	if token.APIBaseURL() != apiBaseURL || (token.SKU != "" && token.SKU != account.GetExtraString("copilot_sku")) {
+		updates := map[string]any{"copilot_api_base": token.APIBaseURL()}
+		if token.SKU != "" {
+			updates["copilot_sku"] = token.SKU
+		}
+		if s.accountRepo != nil {
+			if err := s.accountRepo.UpdateExtra(ctx, account.ID, updates); err != nil {
+				slog.Warn("copilot_account_extra_update_failed", "account_id", account.ID, "error", err)
+			}
+		}
+	}
+	return &CopilotSessionToken{Token: token.Token, APIBaseURL: token.APIBaseURL()}, nil
+}
+
+// InvalidateSessionToken 上游拒绝缓存的 Copilot token 时清除缓存，下次请求重新交换
+func (s *CopilotService) InvalidateSessionToken(ctx context.Context, account *Account) {
+	if s.tokenCache == nil {
+		return
+	}
+	if err := s.tokenCache.DeleteAccessToken(ctx, CopilotTokenCacheKey(account)); err != nil {
+		slog.Warn("copilot_token_cache_delete_failed", "account_id", account.ID, "error", err)
+	}
+}
+
+// ListModels 返回 Copilot 账号可用的模型列表
+func (s *CopilotService) ListModels(ctx context.Context, accountID int64) ([]copilot.Model, error) {
+	account, err := s.accountRepo.GetByID(ctx, accountID)
+	if err != nil {
+		return nil, err
+	}
+	if !account.IsCopilot() {
+		return nil, infraerrors.BadRequest("COPILOT_ACCOUNT_REQUIRED", "not a copilot account")
+	}
+	token, err := s.GetSessionToken(ctx, account)
+	if err != nil {
+		return nil, err
+	}
+	return s.authClient.ListModels(ctx, token.APIBaseURL, token.Token, copilotAccountProxyURL(account))
+}
@@ -0,0 +1,32 @@
+package service
+
+import (
+	"context"
+	"time"
+)
+
+// CopilotTokenRefresher 刷新 Copilot 账号的 GitHub token。
+// 短期 Copilot token 在请求时按需交换，这里只处理带 refresh_token 的过期型 GitHub token。
+type CopilotTokenRefresher struct {
+	copilotService *CopilotService
+}
+
+func NewCopilotTokenRefresher(copilotService *CopilotService) *CopilotTokenRefresher {
+	return &CopilotTokenRefresher{copilotService: copilotService}
+}
+
+func (r *CopilotTokenRefresher) CanRefresh(account *Account) bool {
+	return r.copilotService != nil && account.IsCopilot() && account.GetCredential("github_refresh_token") != ""
+}
+
+func (r *CopilotTokenRefresher) NeedsRefresh(account *Account, refreshWindow time.Duration) bool {
+	expiresAt := account.GetCredentialAsTime("github_token_expires_at")
+	if expiresAt == nil {
+		return false
+	}
+	return time.Until(*expiresAt) < refreshWindow
+}
+
+func (r *CopilotTokenRefresher) Refresh(ctx context.Context, account *Account) (map[string]any, error) {
+	return r.copilotService.RefreshGitHubToken(ctx, account)
+}
@@ -0,0 +1,163 @@
+package service
+
+import (
+	"encoding/base64"
+	"image"
+	_ "image/gif"  // 注册 GIF 解码器，用于读取图片尺寸
+	_ "image/jpeg" // 注册 JPEG 解码器
+	_ "image/png"  // 注册 PNG 解码器
+	"strings"
+
+	"github.com/Wei-Shaw/sub2api/internal/pkg/claude"
+	"github.com/tidwall/gjson"
+)
+
+// count_tokens 计算模式（gateway.count_tokens_mode）
+const (
+	// CountTokensModeAuto 优先本地估算；包含无法本地估算的内容或客户端要求精确结果时转发上游
+	CountTokensModeAuto = "auto"
+	// CountTokensModeLocal 始终本地估算，不占用上游账号
+	CountTokensModeLocal = "local"
+	// CountTokensModeUpstream 始终转发上游（原行为）
+	CountTokensModeUpstream = "upstream"
+)
+
+// 本地估算的结构性开销（消息分隔、角色标记、工具调用协议等）
+const (
+	countTokensBaseOverhead       = 7
+	countTokensPerMessageOverhead = 3
+	countTokensPerToolOverhead    = 8
+	countTokensPerToolUseOverhead = 10
+	// countTokensToolSystemPrompt 声明工具时上游自动注入的工具使用说明
+	countTokensToolSystemPrompt = 346
+	// countTokensAttachmentEstimate 无法本地解析的附件（PDF、文件、音频等）按单张图片上限粗估，不按编码后的字节数计
+	countTokensAttachmentEstimate = 1600
+)
+
+// CountTokensMode 返回规范化后的 count_tokens 计算模式
+func (s *GatewayService) CountTokensMode() string {
+	if s == nil || s.cfg == nil {
+		return CountTokensModeAuto
+	}
+	switch mode := strings.ToLower(strings.TrimSpace(s.cfg.Gateway.CountTokensMode)); mode {
+	case CountTokensModeLocal, CountTokensModeUpstream:
+		return mode
+	default:
+		return CountTokensModeAuto
+	}
+}
+
+// EstimateClaudeInputTokens 本地估算 Anthropic Messages 请求的输入 token 数
+//
+// reliable 为 false 表示请求包含无法在本地准确估算的内容（PDF 文档、URL 图片、未知格式图片等），
+// 此时返回值仅为粗略下限，需要精确结果时应转发上游。
+func EstimateClaudeInputTokens(body []byte) (tokens int, reliable bool) {
+	reliable = true
+	tokens = countTokensBaseOverhead
+
+	system := gjson.GetBytes(body, "system")
+	if system.Type == gjson.String {
+		tokens += claude.EstimateTextTokens(system.String())
+	} else if system.IsArray() {
+		system.ForEach(func(_, block gjson.Result) bool {
+			tokens += claude.EstimateTextTokens(block.Get("text").String())
+			return true
+		})
+	}
+
+	gjson.GetBytes(body, "messages").ForEach(func(_, msg gjson.Result) bool {
+		tokens += countTokensPerMessageOverhead
+		n, ok := estimateClaudeContentTokens(msg.Get("content"))
+		tokens += n
+		reliable = reliable && ok
+		return true
+	})
+
+	tools := gjson.GetBytes(body, "tools")
+	if tools.IsArray() && len(tools.Array()) > 0 {
+		tokens += countTokensToolSystemPrompt
+		tools.ForEach(func(_, tool gjson.Result) bool {
+			tokens += countTokensPerToolOverhead
+			tokens += claude.EstimateTextTokens(tool.Get("name").String())
+			tokens += claude.EstimateTextTokens(tool.Get("description").String())
+			if schema := tool.Get("input_schema"); schema.Exists() {
+				tokens += claude.EstimateTextTokens(schema.Raw)
+			}
+			// 服务端工具（web_search 等）的注入内容无法本地确定
+			if t := tool.Get("type").String(); t != "" && t != "custom" {
+				reliable = false
+			}
+			return true
+		})
+	}
+	return tokens, reliable
+}
+
+func estimateClaudeContentTokens(content gjson.Result) (int, bool) {
+	if content.Type == gjson.String {
+		return claude.EstimateTextTokens(content.String()), true
+	}
+	if !content.IsArray() {
+		return 0, true
+	}
+	tokens, reliable := 0, true
+	content.ForEach(func(_, block gjson.Result) bool {
+		switch block.Get("type").String() {
+		case "text":
+			tokens += claude.EstimateTextTokens(block.Get("text").String())
+		case "thinking":
+			tokens += claude.EstimateTextTokens(block.Get("thinking").String())
+		case "redacted_thinking":
+			// 加密内容长度与 token 数无稳定对应关系
+			reliable = false
+		case "image":
+			n, ok := estimateClaudeImageTokens(block.Get("source"))
+			tokens += n
+			reliable = reliable && ok
+		case "tool_use", "server_tool_use":
+			tokens += countTokensPerToolUseOverhead
+			tokens += claude.EstimateTextTokens(block.Get("name").String())
+			tokens += claude.EstimateTextTokens(block.Get("input").Raw)
+		case "tool_result":
+			tokens += countTokensPerToolUseOverhead
+			n, ok := estimateClaudeContentTokens(block.Get("content"))
+			tokens += n
+			reliable = reliable && ok
+		case "document":
+			// 纯文本文档按文本计；base64 / URL 附件的编码内容不计入文本
+			if source := block.Get("source"); source.Get("type").String() == "text" {
+				tokens += claude.EstimateTextTokens(source.Get("data").String())
+			} else {
+				tokens += countTokensAttachmentEstimate
+			}
+			reliable = false
+		case "image_url", "input_image":
+			// OpenAI 格式图片（Chat Completions 请求复用本估算）
+			tokens += claude.EstimateImageTokens(0, 0)
+			reliable = false
+		case "file", "input_file", "input_audio":
+			tokens += countTokensAttachmentEstimate
+			reliable = false
+		default:
+			// search_result / web_search_tool_result 等
+			tokens += claude.EstimateTextTokens(block.Raw)
+			reliable = false
+		}
+		return true
+	})
+	return tokens, reliable
+}
+
+// estimateClaudeImageTokens 解析 base64 图片尺寸并按缩放规则估算；URL 图片无法获取尺寸
+func estimateClaudeImageTokens(source gjson.Result) (int, bool) {
+	if source.Get("type").String() != "base64" {
+		return claude.EstimateImageTokens(0, 0), false
+	}
+	// DecodeConfig 只读取文件头，不会解码整张图片
+	reader := base64.NewDecoder(base64.StdEncoding, strings.NewReader(source.Get("data").String()))
+	cfg, _, err := image.DecodeConfig(reader)
+	if err != nil {
+		return claude.EstimateImageTokens(0, 0), false
+	}
+	return claude.EstimateImageTokens(cfg.Width, cfg.Height), true
+}
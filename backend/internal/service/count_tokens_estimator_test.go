@@ -0,0 +1,92 @@
+//go:build unit
+
+package service
+
+import (
+	"bytes"
+	"encoding/base64"
+	"image"
+	"image/png"
+	"strings"
+	"testing"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/claude"
+	"github.com/stretchr/testify/require"
+)
+
+func TestEstimateTextTokens(t *testing.T) {
+	require.Equal(t, 0, claude.EstimateTextTokens(""))
+	// Hello(2) ,(1) world(2) !(1)
+	require.Equal(t, 6, claude.EstimateTextTokens("Hello, world!"))
+	require.Equal(t, 2, claude.EstimateTextTokens("123456"))
+	require.Equal(t, 4, claude.EstimateTextTokens("你好世界"))
+	require.Equal(t, 3, claude.EstimateTextTokens("a\nb"))
+}
+
+func TestEstimateImageTokens(t *testing.T) {
+	require.Equal(t, 1, claude.EstimateImageTokens(10, 10))
+	require.Equal(t, 1334, claude.EstimateImageTokens(1000, 1000))
+	require.Equal(t, 1600, claude.EstimateImageTokens(4000, 3000))
+	require.Equal(t, 1600, claude.EstimateImageTokens(0, 0))
+}
+
+func TestEstimateClaudeInputTokens_TextOnlyIsReliable(t *testing.T) {
+	body := []byte(`{"model":"claude-sonnet-4-5","system":"Be brief.","messages":[{"role":"user","content":"Hello, world!"},{"role":"assistant","content":[{"type":"text","text":"Hi"}]}]}`)
+	tokens, reliable := EstimateClaudeInputTokens(body)
+	require.True(t, reliable)
+	// base 7 + system(Be/brief/. = 4) + 2 messages * 3 + 6 + 1
+	require.Equal(t, 7+4+6+6+1, tokens)
+}
+
+func TestEstimateClaudeInputTokens_Tools(t *testing.T) {
+	body := []byte(`{"messages":[{"role":"user","content":"hi"}],"tools":[{"name":"get_weather","description":"Get weather","input_schema":{"type":"object"}}]}`)
+	tokens, reliable := EstimateClaudeInputTokens(body)
+	require.True(t, reliable)
+	require.Greater(t, tokens, countTokensToolSystemPrompt)
+
+	body = []byte(`{"messages":[{"role":"user","content":"hi"}],"tools":[{"type":"web_search_20250305","name":"web_search"}]}`)
+	_, reliable = EstimateClaudeInputTokens(body)
+	require.False(t, reliable)
+}
+
+func TestEstimateClaudeInputTokens_Images(t *testing.T) {
+	var buf bytes.Buffer
+	require.NoError(t, png.Encode(&buf, image.NewRGBA(image.Rect(0, 0, 750, 100))))
+	data := base64.StdEncoding.EncodeToString(buf.Bytes())
+
+	body := []byte(`{"messages":[{"role":"user","content":[{"type":"image","source":{"type":"base64","media_type":"image/png","data":"` + data + `"}}]}]}`)
+	tokens, reliable := EstimateClaudeInputTokens(body)
+	require.True(t, reliable)
+	require.Equal(t, countTokensBaseOverhead+countTokensPerMessageOverhead+100, tokens)
+
+	body = []byte(`{"messages":[{"role":"user","content":[{"type":"image","source":{"type":"url","url":"https://example.com/a.png"}}]}]}`)
+	_, reliable = EstimateClaudeInputTokens(body)
+	require.False(t, reliable)
+
+	body = []byte(`{"messages":[{"role":"user","content":[{"type":"document","source":{"type":"base64","media_type":"application/pdf","data":"JVBERi0="}}]}]}`)
+	_, reliable = EstimateClaudeInputTokens(body)
+	require.False(t, reliable)
+
+	// 附件的 base64 内容不按文本计
+	pdf := strings.Repeat("JVBERi0=", 64*1024)
+	body = []byte(`{"messages":[{"role":"user","content":[{"type":"document","source":{"type":"base64","media_type":"application/pdf","data":"` + pdf + `"}}]}]}`)
+	tokens, reliable = EstimateClaudeInputTokens(body)
+	require.False(t, reliable)
+	require.Equal(t, countTokensBaseOverhead+countTokensPerMessageOverhead+countTokensAttachmentEstimate, tokens)
+
+	body = []byte(`{"messages":[{"role":"user","content":[{"type":"image_url","image_url":{"url":"data:image/png;base64,` + data + `"}}]}]}`)
+	tokens, reliable = EstimateClaudeInputTokens(body)
+	require.False(t, reliable)
+	require.Equal(t, countTokensBaseOverhead+countTokensPerMessageOverhead+claude.EstimateImageTokens(0, 0), tokens)
+}
+
+func TestGatewayService_CountTokensMode(t *testing.T) {
+	require.Equal(t, CountTokensModeAuto, (&GatewayService{}).CountTokensMode())
+
+	svc := &GatewayService{cfg: &config.Config{Gateway: config.GatewayConfig{CountTokensMode: " Upstream "}}}
+	require.Equal(t, CountTokensModeUpstream, svc.CountTokensMode())
+
+	svc.cfg.Gateway.CountTokensMode = "bogus"
+	require.Equal(t, CountTokensModeAuto, svc.CountTokensMode())
+}
@@ -0,0 +1,423 @@
+package service
+
+import (
+	"context"
+	"fmt"
+	"math/rand/v2"
+	"strings"
+	"sync"
+	"sync/atomic"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	infraerrors "github.com/Wei-Shaw/sub2api/internal/pkg/errors"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/logger"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/timezone"
+	"github.com/google/uuid"
+	"github.com/robfig/cron/v3"
+)
+
+// 定时任务执行状态
+const (
+	CronJobStatusRunning   = "running"
+	CronJobStatusSucceeded = "succeeded"
+	CronJobStatusFailed    = "failed"
+)
+
+const (
+	defaultCronJobTimeout = 30 * time.Minute
+	// cronJobCatchUpMaxSteps 补跑时向后推算调度时间的最大步数（防止极短周期任务长时间停机后空转）
+	cronJobCatchUpMaxSteps = 10000
+)
+
+var (
+	ErrCronJobNotFound = infraerrors.NotFound("CRON_JOB_NOT_FOUND", "cron job not found")
+	ErrCronJobRunning  = infraerrors.Conflict("CRON_JOB_RUNNING", "cron job is already running")
+)
+
+// CronJob 定时任务定义
+type CronJob struct {
+	Name        string
+	Description string
+	// Schedule 默认 cron 表达式，可通过 cron_jobs.tasks.<name>.schedule 覆盖
+	Schedule string
+	// Disabled 默认关闭，可通过 cron_jobs.tasks.<name>.enabled 开启
+	Disabled bool
+	// Timeout 单次执行超时，同时决定跨副本执行锁的过期时间
+	Timeout time.Duration
+	Run     func(ctx context.Context) error
+}
+
+// CronJobState 定时任务最近一次执行状态（持久化在数据库中）
+type CronJobState struct {
+	Name            string     `json:"-"`
+	LastScheduledAt *time.Time `json:"last_scheduled_at,omitempty"`
+	LastStartedAt   *time.Time `json:"last_started_at,omitempty"`
+	LastFinishedAt  *time.Time `json:"last_finished_at,omitempty"`
+	LastStatus      string     `json:"last_status"`
+	LastError       string     `json:"last_error,omitempty"`
+	LastDurationMs  int64      `json:"last_duration_ms"`
+	LastInstance    string     `json:"last_instance,omitempty"`
+	RunCount        int64      `json:"run_count"`
+	FailureCount    int64      `json:"failure_count"`
+}
+
+// CronJobInfo 管理后台展示的定时任务信息
+type CronJobInfo struct {
+	Name          string     `json:"name"`
+	Description   string     `json:"description"`
+	Schedule      string     `json:"schedule"`
+	Enabled       bool       `json:"enabled"`
+	JitterSeconds int        `json:"jitter_seconds"`
+	NextRunAt     *time.Time `json:"next_run_at,omitempty"`
+	// Running 当前实例是否正在执行该任务
+	Running bool `json:"running"`
+	CronJobState
+}
+
+// CronJobStateRepository 定时任务执行状态持久化
+type CronJobStateRepository interface {
+	ListStates(ctx context.Context) ([]CronJobState, error)
+	// GetState 不存在时返回 nil, nil
+	GetState(ctx context.Context, name string) (*CronJobState, error)
+	MarkStarted(ctx context.Context, name string, scheduledAt, startedAt time.Time, instance string) error
+	MarkFinished(ctx context.Context, name string, finishedAt time.Time, duration time.Duration, runErr error) error
+}
+
+// CronJobLocker 跨副本执行锁，保证同一任务同一时间只在一个实例上运行
+type CronJobLocker interface {
+	TryLock(ctx context.Context, name, owner string, ttl time.Duration) (bool, error)
+	Unlock(ctx context.Context, name, owner string) error
+}
+
+type cronJobEntry struct {
+	job      CronJob
+	spec     string
+	schedule cron.Schedule
+	enabled  bool
+	jitter   time.Duration
+	running  atomic.Bool
+}
+
+// CronJobService 基于 cron 表达式的定时任务调度
+//
+// - 触发后按 jitter 随机延迟，避免多个任务/副本同时打到数据库
+// - 通过 CronJobLocker 防止多副本重叠执行，并以持久化的 last_scheduled_at 对同一调度时间去重
+// - 启动时若发现停机期间错过了调度，补跑最近一次
+type CronJobService struct {
+	repo       CronJobStateRepository
+	locker     CronJobLocker
+	cfg        config.CronJobsConfig
+	runMode    string
+	loc        *time.Location
+	instanceID string
+
+	ctx    context.Context
+	cancel context.CancelFunc
+	wg     sync.WaitGroup
+
+	mu      sync.Mutex
+	jobs    map[string]*cronJobEntry
+	order   []string
+	started bool
+}
+
+// NewCronJobService 创建定时任务调度服务
+func NewCronJobService(repo CronJobStateRepository, locker CronJobLocker, cfg *config.Config) *CronJobService {
+	ctx, cancel := context.WithCancel(context.Background())
+	s := &CronJobService{
+		repo:       repo,
+		locker:     locker,
+		loc:        timezone.Location(),
+		instanceID: uuid.NewString(),
+		ctx:        ctx,
+		cancel:     cancel,
+		jobs:       make(map[string]*cronJobEntry),
+	}
+	if cfg != nil {
+		s.cfg = cfg.CronJobs
+		s.runMode = cfg.RunMode
+	}
+	return s
+}
+
+// Register 注册定时任务；服务已启动时立即开始调度
+func (s *CronJobService) Register(job CronJob) error {
+	if job.Name == "" || job.Run == nil {
+		return fmt.Errorf("cron job requires name and run func")
+	}
+	if job.Timeout <= 0 {
+		job.Timeout = defaultCronJobTimeout
+	}
+
+	entry := &cronJobEntry{job: job, spec: job.Schedule, enabled: !job.Disabled}
+	jitterSeconds := s.cfg.JitterSeconds
+	if override, ok := s.cfg.Tasks[job.Name]; ok {
+		if spec := strings.TrimSpace(override.Schedule); spec != "" {
+			entry.spec = spec
+		}
+		if override.Enabled != nil {
+			entry.enabled = *override.Enabled
+		}
+		if override.JitterSeconds != nil {
+			jitterSeconds = *override.JitterSeconds
+		}
+	}
+	entry.jitter = time.Duration(jitterSeconds) * time.Second
+
+	schedule, err := config.CronJobScheduleParser.Parse(entry.spec)
+	if err != nil {
+		return fmt.Errorf("cron job %s: invalid schedule %q: %w", job.Name, entry.spec, err)
+	}
+	entry.schedule = schedule
+
+	s.mu.Lock()
+	defer s.mu.Unlock()
+	if _, exists := s.jobs[job.Name]; exists {
+		return fmt.Errorf("cron job %s already registered", job.Name)
+	}
+	s.jobs[job.Name] = entry
+	s.order = append(s.order, job.Name)
+	if s.started && entry.enabled {
+		s.startJobLocked(entry)
+	}
+	return nil
+}
+
+// Start 启动所有已启用任务的调度
+func (s *CronJobService) Start() {
+	if s == nil {
+		return
+	}
+	if !s.cfg.Enabled {
+		logger.LegacyPrintf("service.cron_job", "%s", "[CronJob] not started (disabled)")
+		return
+	}
+	s.mu.Lock()
+	defer s.mu.Unlock()
+	if s.started {
+		return
+	}
+	s.started = true
+	for _, name := range s.order {
+		if entry := s.jobs[name]; entry.enabled {
+			s.startJobLocked(entry)
+		}
+	}
+	logger.LegacyPrintf("service.cron_job", "[CronJob] started (jobs=%d tz=%s)", len(s.order), s.loc.String())
+}
+
+// Stop 停止调度并等待执行中的任务结束
+func (s *CronJobService) Stop() {
+	if s == nil {
+		return
+	}
+	s.cancel()
+	s.wg.Wait()
+}
+
+// List 返回所有已注册任务的调度配置与最近执行状态
+func (s *CronJobService) List(ctx context.Context) ([]CronJobInfo, error) {
+	states := map[string]CronJobState{}
+	if s.repo != nil {
+		list, err := s.repo.ListStates(ctx)
+		if err != nil {
+			return nil, err
+		}
+		for _, st := range list {
+			states[st.Name] = st
+		}
+	}
+
+	s.mu.Lock()
+	defer s.mu.Unlock()
+	now := time.Now().In(s.loc)
+	out := make([]CronJobInfo, 0, len(s.order))
+	for _, name := range s.order {
+		entry := s.jobs[name]
+		info := CronJobInfo{
+			Name:          name,
+			Description:   entry.job.Description,
+			Schedule:      entry.spec,
+			Enabled:       s.cfg.Enabled && entry.enabled,
+			JitterSeconds: int(entry.jitter / time.Second),
+			Running:       entry.running.Load(),
+			CronJobState:  states[name],
+		}
+		if info.Enabled {
+			next := entry.schedule.Next(now)
+			info.NextRunAt = &next
+		}
+		out = append(out, info)
+	}
+	return out, nil
+}
+
+// RunNow 立即异步执行一次任务（不等待 jitter，不受 enabled 限制）
+func (s *CronJobService) RunNow(name string) error {
+	s.mu.Lock()
+	entry, ok := s.jobs[name]
+	s.mu.Unlock()
+	if !ok {
+		return ErrCronJobNotFound
+	}
+	if entry.running.Load() {
+		return ErrCronJobRunning
+	}
+
+	s.wg.Add(1)
+	go func() {
+		defer s.wg.Done()
+		_ = s.execute(entry, time.Now(), false)
+	}()
+	return nil
+}
+
+func (s *CronJobService) startJobLocked(entry *cronJobEntry) {
+	s.wg.Add(1)
+	go s.scheduleLoop(entry)
+}
+
+func (s *CronJobService) scheduleLoop(entry *cronJobEntry) {
+	defer s.wg.Done()
+	s.catchUp(entry)
+
+	for {
+		next := entry.schedule.Next(time.Now().In(s.loc))
+		if next.IsZero() {
+			return
+		}
+		if sleepWithContext(s.ctx, time.Until(next)) != nil {
+			return
+		}
+		s.wg.Add(1)
+		go func(scheduledAt time.Time) {
+			defer s.wg.Done()
+			_ = s.execute(entry, scheduledAt, true)
+		}(next)
+	}
+}
+
+// catchUp 若停机期间错过了调度，补跑最近一次（从未执行过的任务不补跑）
+func (s *CronJobService) catchUp(entry *cronJobEntry) {
+	if s.repo == nil {
+		return
+	}
+	state, err := s.repo.GetState(s.ctx, entry.job.Name)
+	if err != nil || state == nil || state.LastScheduledAt == nil {
+		return
+	}
+	missed, ok := latestMissedSchedule(entry.schedule, state.LastScheduledAt.In(s.loc), time.Now().In(s.loc))
+	if !ok {
+		return
+	}
+	logger.LegacyPrintf("service.cron_job", "[CronJob] %s missed run at %s, catching up", entry.job.Name, missed.Format(time.RFC3339))
+	_ = s.execute(entry, missed, true)
+}
+
+// latestMissedSchedule 返回 (last, now] 区间内最近的一次调度时间
+func latestMissedSchedule(schedule cron.Schedule, last, now time.Time) (time.Time, bool) {
+	t := schedule.Next(last)
+	if t.IsZero() || t.After(now) {
+		return time.Time{}, false
+	}
+	for i := 0; i < cronJobCatchUpMaxSteps; i++ {
+		n := schedule.Next(t)
+		if n.IsZero() || n.After(now) {
+			break
+		}
+		t = n
+	}
+	return t, true
+}
+
+// execute 执行一次任务。scheduled=true 表示由调度触发：会先随机延迟，并跳过其他副本已执行的同一调度时间。
+func (s *CronJobService) execute(entry *cronJobEntry, scheduledAt time.Time, scheduled bool) error {
+	name := entry.job.Name
+	if scheduled && entry.jitter > 0 {
+		if sleepWithContext(s.ctx, rand.N(entry.jitter)) != nil {
+			return s.ctx.Err()
+		}
+	}
+	if !entry.running.CompareAndSwap(false, true) {
+		logger.LegacyPrintf("service.cron_job", "[CronJob] %s still running on this instance, skipped", name)
+		return ErrCronJobRunning
+	}
+	defer entry.running.Store(false)
+
+	release, ok := s.tryLock(entry)
+	if !ok {
+		return ErrCronJobRunning
+	}
+	defer release()
+
+	if scheduled && s.repo != nil {
+		state, err := s.repo.GetState(s.ctx, name)
+		if err != nil {
+			logger.LegacyPrintf("service.cron_job", "[CronJob] %s load state failed: %v", name, err)
+		} else if state != nil && state.LastScheduledAt != nil && !state.LastScheduledAt.Before(scheduledAt) {
+			// 其他副本已执行过这次调度
+			return nil
+		}
+	}
+
+	startedAt := time.Now()
+	if s.repo != nil {
+		if err := s.repo.MarkStarted(s.ctx, name, scheduledAt, startedAt, s.instanceID); err != nil {
+			logger.LegacyPrintf("service.cron_job", "[CronJob] %s record start failed: %v", name, err)
+		}
+	}
+
+	ctx, cancel := context.WithTimeout(s.ctx, entry.job.Timeout)
+	err := runCronJob(ctx, entry.job)
+	cancel()
+	duration := time.Since(startedAt)
+
+	if s.repo != nil {
+		recordCtx, recordCancel := context.WithTimeout(context.Background(), 5*time.Second)
+		if recErr := s.repo.MarkFinished(recordCtx, name, time.Now(), duration, err); recErr != nil {
+			logger.LegacyPrintf("service.cron_job", "[CronJob] %s record result failed: %v", name, recErr)
+		}
+		recordCancel()
+	}
+	if err != nil {
+		logger.LegacyPrintf("service.cron_job", "[CronJob] %s failed after %s: %v", name, duration.Round(time.Millisecond), err)
+		return err
+	}
+	logger.LegacyPrintf("service.cron_job", "[CronJob] %s succeeded in %s", name, duration.Round(time.Millisecond))
+	return nil
+}
+
+// tryLock 获取跨副本执行锁；simple 模式或未配置锁时视为单实例
+func (s *CronJobService) tryLock(entry *cronJobEntry) (func(), bool) {
+	if s.locker == nil || s.runMode == config.RunModeSimple {
+		return func() {}, true
+	}
+	name := entry.job.Name
+	// 锁过期时间略长于任务超时，避免任务仍在执行时锁被其他副本抢走
+	ttl := entry.job.Timeout + time.Minute
+	ok, err := s.locker.TryLock(s.ctx, name, s.instanceID, ttl)
+	if err != nil {
+		logger.LegacyPrintf("service.cron_job", "[CronJob] %s acquire lock failed; skipping this run: %v", name, err)
+		return nil, false
+	}
+	if !ok {
+		return nil, false
+	}
+	return func() {
+		ctx, cancel := context.WithTimeout(context.Background(), 3*time.Second)
+		defer cancel()
+		if err := s.locker.Unlock(ctx, name, s.instanceID); err != nil {
+			logger.LegacyPrintf("service.cron_job", "[CronJob] %s release lock failed: %v", name, err)
+		}
+	}, true
+}
+
+func runCronJob(ctx context.Context, job CronJob) (err error) {
+	defer func() {
+		if r := recover(); r != nil {
+			err = fmt.Errorf("cron job panic: %v", r)
+		}
+	}()
+	return job.Run(ctx)
+}
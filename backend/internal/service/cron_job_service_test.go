@@ -0,0 +1,177 @@
+//go:build unit
+
+package service
+
+import (
+	"context"
+	"errors"
+	"sync"
+	"testing"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/stretchr/testify/require"
+)
+
+type cronJobRepoStub struct {
+	mu     sync.Mutex
+	states map[string]*CronJobState
+}
+
+func newCronJobRepoStub() *cronJobRepoStub {
+	return &cronJobRepoStub{states: map[string]*CronJobState{}}
+}
+
+func (r *cronJobRepoStub) ListStates(ctx context.Context) ([]CronJobState, error) {
+	r.mu.Lock()
+	defer r.mu.Unlock()
+	out := make([]CronJobState, 0, len(r.states))
+	for _, st := range r.states {
+		out = append(out, *st)
+	}
+	return out, nil
+}
+
+func (r *cronJobRepoStub) GetState(ctx context.Context, name string) (*CronJobState, error) {
+	r.mu.Lock()
+	defer r.mu.Unlock()
+	st, ok := r.states[name]
+	if !ok {
+		return nil, nil
+	}
+	cp := *st
+	return &cp, nil
+}
+
+func (r *cronJobRepoStub) MarkStarted(ctx context.Context, name string, scheduledAt, startedAt time.Time, instance string) error {
+	r.mu.Lock()
+	defer r.mu.Unlock()
+	st, ok := r.states[name]
+	if !ok {
+		st = &CronJobState{Name: name}
+		r.states[name] = st
+	}
+	st.LastScheduledAt = &scheduledAt
+	st.LastStartedAt = &startedAt
+	st.LastStatus = CronJobStatusRunning
+	st.RunCount++
+	return nil
+}
+
+func (r *cronJobRepoStub) MarkFinished(ctx context.Context, name string, finishedAt time.Time, duration time.Duration, runErr error) error {
+	r.mu.Lock()
+	defer r.mu.Unlock()
+	st := r.states[name]
+	st.LastFinishedAt = &finishedAt
+	st.LastStatus = CronJobStatusSucceeded
+	if runErr != nil {
+		st.LastStatus = CronJobStatusFailed
+		st.LastError = runErr.Error()
+		st.FailureCount++
+	}
+	return nil
+}
+
+type cronJobLockerStub struct {
+	held map[string]string
+}
+
+func (l *cronJobLockerStub) TryLock(ctx context.Context, name, owner string, ttl time.Duration) (bool, error) {
+	if _, ok := l.held[name]; ok {
+		return false, nil
+	}
+	l.held[name] = owner
+	return true, nil
+}
+
+func (l *cronJobLockerStub) Unlock(ctx context.Context, name, owner string) error {
+	if l.held[name] == owner {
+		delete(l.held, name)
+	}
+	return nil
+}
+
+func TestCronJobService_RegisterAppliesOverrides(t *testing.T) {
+	enabled := true
+	jitter := 0
+	svc := NewCronJobService(nil, nil, &config.Config{CronJobs: config.CronJobsConfig{
+		Enabled:       true,
+		JitterSeconds: 30,
+		Tasks: map[string]config.CronTaskConfig{
+			"purge": {Enabled: &enabled, Schedule: "15 2 * * *", JitterSeconds: &jitter},
+		},
+	}})
+	noop := func(ctx context.Context) error { return nil }
+
+	require.NoError(t, svc.Register(CronJob{Name: "purge", Schedule: "0 4 * * *", Disabled: true, Run: noop}))
+	require.NoError(t, svc.Register(CronJob{Name: "rollup", Schedule: "30 3 * * *", Run: noop}))
+	require.Error(t, svc.Register(CronJob{Name: "rollup", Schedule: "30 3 * * *", Run: noop}))
+	require.Error(t, svc.Register(CronJob{Name: "bad", Schedule: "not a cron", Run: noop}))
+
+	jobs, err := svc.List(context.Background())
+	require.NoError(t, err)
+	require.Len(t, jobs, 2)
+	require.Equal(t, "purge", jobs[0].Name)
+	require.Equal(t, "15 2 * * *", jobs[0].Schedule)
+	require.True(t, jobs[0].Enabled)
+	require.Equal(t, 0, jobs[0].JitterSeconds)
+	require.NotNil(t, jobs[0].NextRunAt)
+	require.Equal(t, 30, jobs[1].JitterSeconds)
+}
+
+func TestCronJobService_ExecuteSkipsRunAlreadyDoneByOtherReplica(t *testing.T) {
+	repo := newCronJobRepoStub()
+	locker := &cronJobLockerStub{held: map[string]string{}}
+	svc := NewCronJobService(repo, locker, &config.Config{CronJobs: config.CronJobsConfig{Enabled: true}})
+	defer svc.Stop()
+
+	runs := 0
+	require.NoError(t, svc.Register(CronJob{Name: "rollup", Schedule: "0 * * * *", Run: func(ctx context.Context) error {
+		runs++
+		return nil
+	}}))
+	entry := svc.jobs["rollup"]
+	scheduledAt := time.Date(2026, 3, 1, 10, 0, 0, 0, time.UTC)
+
+	require.NoError(t, svc.execute(entry, scheduledAt, true))
+	require.NoError(t, svc.execute(entry, scheduledAt, true))
+	require.Equal(t, 1, runs)
+	require.Empty(t, locker.held)
+
+	// 锁被其他实例持有时跳过
+	locker.held["rollup"] = "other"
+	require.ErrorIs(t, svc.execute(entry, scheduledAt.Add(time.Hour), true), ErrCronJobRunning)
+	require.Equal(t, 1, runs)
+}
+
+func TestCronJobService_ExecuteRecordsFailureAndPanic(t *testing.T) {
+	repo := newCronJobRepoStub()
+	svc := NewCronJobService(repo, nil, &config.Config{CronJobs: config.CronJobsConfig{Enabled: true}})
+	defer svc.Stop()
+
+	require.NoError(t, svc.Register(CronJob{Name: "fail", Schedule: "@daily", Run: func(ctx context.Context) error {
+		return errors.New("boom")
+	}}))
+	require.NoError(t, svc.Register(CronJob{Name: "panic", Schedule: "@daily", Run: func(ctx context.Context) error {
+		panic("oops")
+	}}))
+
+	require.Error(t, svc.execute(svc.jobs["fail"], time.Now(), false))
+	require.Error(t, svc.execute(svc.jobs["panic"], time.Now(), false))
+	require.Equal(t, CronJobStatusFailed, repo.states["fail"].LastStatus)
+	require.Equal(t, "boom", repo.states["fail"].LastError)
+	require.Equal(t, int64(1), repo.states["panic"].FailureCount)
+}
+
+func TestLatestMissedSchedule(t *testing.T) {
+	schedule, err := config.CronJobScheduleParser.Parse("0 3 * * *")
+	require.NoError(t, err)
+	last := time.Date(2026, 3, 1, 3, 0, 0, 0, time.UTC)
+
+	_, ok := latestMissedSchedule(schedule, last, last.Add(23*time.Hour))
+	require.False(t, ok)
+
+	missed, ok := latestMissedSchedule(schedule, last, time.Date(2026, 3, 4, 12, 0, 0, 0, time.UTC))
+	require.True(t, ok)
+	require.Equal(t, time.Date(2026, 3, 4, 3, 0, 0, 0, time.UTC), missed)
+}
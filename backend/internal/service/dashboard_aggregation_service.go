@@ -2,13 +2,16 @@ package service
 
 import (
 	"context"
+	"encoding/json"
 	"errors"
+	"fmt"
 	"log/slog"
 	"sync/atomic"
 	"time"
 
 	"github.com/Wei-Shaw/sub2api/internal/config"
 	"github.com/Wei-Shaw/sub2api/internal/pkg/logger"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/timezone"
 )
 
 const (
@@ -42,9 +45,17 @@ type DashboardAggregationRepository interface {
 type DashboardAggregationService struct {
 	repo                 DashboardAggregationRepository
 	timingWheel          *TimingWheelService
+	jobQueue             *JobQueueService
 	cfg                  config.DashboardAggregationConfig
 	running              int32
 	lastRetentionCleanup atomic.Value // time.Time
+	leader               *LeaderElector
+}
+
+// dashboardBackfillJob 通过任务队列投递的回填任务
+type dashboardBackfillJob struct {
+	Start time.Time `json:"start"`
+	End   time.Time `json:"end"`
 }
 
 // NewDashboardAggregationService 创建聚合服务。
@@ -60,6 +71,11 @@ func NewDashboardAggregationService(repo DashboardAggregationRepository, timingW
 	}
 }
 
+// SetLeaderElector 多实例部署时仅由 leader 执行；需要在 Start() 之前调用
+func (s *DashboardAggregationService) SetLeaderElector(leader *LeaderElector) {
+	s.leader = leader
+}
+
 // Start 启动定时聚合作业（重启生效配置）。
 func (s *DashboardAggregationService) Start() {
 	if s == nil || s.repo == nil || s.timingWheel == nil {
@@ -88,6 +104,25 @@ func (s *DashboardAggregationService) Start() {
 	}
 }
 
+// SetJobQueue 注入任务队列：回填任务改为入队，由负责后台任务的进程（embedded/worker）执行。
+func (s *DashboardAggregationService) SetJobQueue(jobQueue *JobQueueService) {
+	if s == nil || !jobQueue.Enabled() {
+		return
+	}
+	s.jobQueue = jobQueue
+	jobQueue.RegisterHandler(JobTopicDashboardBackfill, 1, defaultDashboardAggregationBackfillTimeout, s.handleBackfillJob)
+}
+
+func (s *DashboardAggregationService) handleBackfillJob(ctx context.Context, job *QueuedJob) error {
+	var payload dashboardBackfillJob
+	if err := json.Unmarshal(job.Payload, &payload); err != nil {
+		logger.LegacyPrintf("service.dashboard_aggregation", "[DashboardAggregation] 丢弃无效回填任务 %s: %v", job.ID, err)
+		return nil
+	}
+	// 与定时聚合冲突时返回错误，由队列稍后重试
+	return s.backfillRange(ctx, payload.Start, payload.End)
+}
+
 // TriggerBackfill 触发回填（异步）。
 func (s *DashboardAggregationService) TriggerBackfill(start, end time.Time) error {
 	if s == nil || s.repo == nil {
@@ -107,6 +142,15 @@ func (s *DashboardAggregationService) TriggerBackfill(start, end time.Time) erro
 		}
 	}
 
+	if s.jobQueue != nil {
+		ctx, cancel := context.WithTimeout(context.Background(), 5*time.Second)
+		defer cancel()
+		if _, err := s.jobQueue.Enqueue(ctx, JobTopicDashboardBackfill, dashboardBackfillJob{Start: start.UTC(), End: end.UTC()}); err != nil {
+			return fmt.Errorf("enqueue backfill: %w", err)
+		}
+		return nil
+	}
+
 	go func() {
 		ctx, cancel := context.WithTimeout(context.Background(), defaultDashboardAggregationBackfillTimeout)
 		defer cancel()
@@ -152,6 +196,19 @@ func (s *DashboardAggregationService) TriggerRecomputeRange(start, end time.Time
 	return nil
 }
 
+// RecomputePreviousDay 同步重新计算前一自然日（按 timezone）的聚合数据，用于夜间定时任务修正迟到的使用记录。
+// 聚合作业正在运行时返回错误，由调度方记录失败。
+func (s *DashboardAggregationService) RecomputePreviousDay(ctx context.Context) error {
+	if s == nil || s.repo == nil {
+		return errors.New("聚合服务未初始化")
+	}
+	if !s.cfg.Enabled {
+		return nil
+	}
+	end := timezone.Today()
+	return s.recomputeRange(ctx, end.AddDate(0, 0, -1), end)
+}
+
 func (s *DashboardAggregationService) recomputeRecentDays() {
 	days := s.cfg.RecomputeDays
 	if days <= 0 {
@@ -187,6 +244,9 @@ func (s *DashboardAggregationService) recomputeRange(ctx context.Context, start,
 }
 
 func (s *DashboardAggregationService) runScheduledAggregation() {
+	if !s.leader.IsLeader() {
+		return
+	}
 	if !atomic.CompareAndSwapInt32(&s.running, 0, 1) {
 		return
 	}
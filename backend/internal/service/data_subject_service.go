@@ -0,0 +1,331 @@
+package service
+
+import (
+	"context"
+	"encoding/json"
+	"fmt"
+	"io"
+	"strconv"
+	"strings"
+	"time"
+
+	infraerrors "github.com/Wei-Shaw/sub2api/internal/pkg/errors"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/logger"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/pagination"
+)
+
+// 数据主体请求类型
+const (
+	DataSubjectKindExport = "export"
+	DataSubjectKindDelete = "delete"
+)
+
+// 数据主体类型
+const (
+	DataSubjectUser   = "user"
+	DataSubjectAPIKey = "api_key"
+)
+
+// 数据主体请求状态
+const (
+	DataSubjectStatusPending   = "pending"
+	DataSubjectStatusRunning   = "running"
+	DataSubjectStatusSucceeded = "succeeded"
+	DataSubjectStatusFailed    = "failed"
+)
+
+const (
+	dataSubjectJobTimeout      = time.Hour
+	dataSubjectDeleteBatchSize = 5000
+	dataSubjectExportPrefix    = "data-subject-exports/"
+)
+
+var (
+	ErrDataSubjectInvalidKind      = infraerrors.BadRequest("DATA_SUBJECT_INVALID_KIND", "kind must be export or delete")
+	ErrDataSubjectInvalidSubject   = infraerrors.BadRequest("DATA_SUBJECT_INVALID_SUBJECT", "subject_type must be user or api_key with a positive subject_id")
+	ErrDataSubjectNotFound         = infraerrors.NotFound("DATA_SUBJECT_NOT_FOUND", "data subject not found")
+	ErrDataSubjectRequestNotFound  = infraerrors.NotFound("DATA_SUBJECT_REQUEST_NOT_FOUND", "data subject request not found")
+	ErrDataSubjectExportNotReady   = infraerrors.Conflict("DATA_SUBJECT_EXPORT_NOT_READY", "export is not available for this request")
+	ErrDataSubjectStorageDisabled  = infraerrors.BadRequest("DATA_SUBJECT_STORAGE_DISABLED", "object storage is required for data exports")
+	ErrDataSubjectRequestDuplicate = infraerrors.Conflict("DATA_SUBJECT_REQUEST_DUPLICATE", "a request for this subject is already in progress")
+)
+
+// DataSubjectTables 与数据主体关联的数据表（导出与删除的范围）。
+// users / api_keys 本身只导出不删除：账号与 Key 通过常规删除 + 回收站清除移除。
+var DataSubjectTables = []string{
+	"usage_logs",
+	"usage_rollup_hourly",
+	"usage_rollup_daily",
+	"request_logs",
+	"ops_error_logs",
+	"ops_system_logs",
+	"traffic_mirror_samples",
+	"api_key_rotations",
+}
+
+// DataSubjectRequest 数据主体导出/删除请求；请求记录本身即为审计记录，不随删除一并清除
+type DataSubjectRequest struct {
+	ID           int64            `json:"id"`
+	Kind         string           `json:"kind"`
+	SubjectType  string           `json:"subject_type"`
+	SubjectID    int64            `json:"subject_id"`
+	Status       string           `json:"status"`
+	Reason       string           `json:"reason,omitempty"`
+	RequestedBy  *int64           `json:"requested_by,omitempty"`
+	Report       map[string]int64 `json:"report,omitempty"`
+	ExportKey    string           `json:"export_key,omitempty"`
+	ErrorMessage string           `json:"error_message,omitempty"`
+	CreatedAt    time.Time        `json:"created_at"`
+	StartedAt    *time.Time       `json:"started_at,omitempty"`
+	FinishedAt   *time.Time       `json:"finished_at,omitempty"`
+}
+
+// DataSubjectRepository 数据主体请求存储与按主体的数据导出/删除
+type DataSubjectRepository interface {
+	Create(ctx context.Context, req *DataSubjectRequest) error
+	GetByID(ctx context.Context, id int64) (*DataSubjectRequest, error)
+	List(ctx context.Context, params pagination.PaginationParams) ([]DataSubjectRequest, *pagination.PaginationResult, error)
+	// HasActive 主体是否存在 pending/running 的请求
+	HasActive(ctx context.Context, subjectType string, subjectID int64) (bool, error)
+	// MarkRunning 将 pending 请求置为 running，已被其他实例领取时返回 false
+	MarkRunning(ctx context.Context, id int64) (bool, error)
+	Finish(ctx context.Context, req *DataSubjectRequest) error
+
+	// SubjectExists 主体是否存在（包含已软删除的记录）
+	SubjectExists(ctx context.Context, subjectType string, subjectID int64) (bool, error)
+	// ExportRows 流式读取主体在 table 中的记录（JSON），table 为 users/api_keys 时返回去除凭据字段后的身份记录
+	ExportRows(ctx context.Context, table, subjectType string, subjectID int64, fn func(row json.RawMessage) error) (int64, error)
+	// DeleteRows 删除主体在 table 中的记录，单次最多 limit 条，返回删除数量
+	DeleteRows(ctx context.Context, table, subjectType string, subjectID int64, limit int) (int64, error)
+}
+
+type dataSubjectJob struct {
+	RequestID int64 `json:"request_id"`
+}
+
+// DataSubjectService 数据主体（用户 / API Key）的数据导出与永久删除。
+// 请求异步执行：任务队列可用时入队，否则在当前进程后台执行；完成后在请求记录中写入按表统计的报告。
+type DataSubjectService struct {
+	repo     DataSubjectRepository
+	storage  ObjectStorage
+	jobQueue *JobQueueService
+	now      func() time.Time
+}
+
+// NewDataSubjectService 创建数据主体服务；storage 为 nil 时不支持导出
+func NewDataSubjectService(repo DataSubjectRepository, storage ObjectStorage, jobQueue *JobQueueService) *DataSubjectService {
+	s := &DataSubjectService{repo: repo, storage: storage, now: time.Now}
+	if jobQueue.Enabled() {
+		s.jobQueue = jobQueue
+		jobQueue.RegisterHandler(JobTopicDataSubject, 1, dataSubjectJobTimeout, s.handleJob)
+	}
+	return s
+}
+
+// CreateRequest 校验并创建请求，随后异步执行
+func (s *DataSubjectService) CreateRequest(ctx context.Context, kind, subjectType string, subjectID int64, reason string, requestedBy int64) (*DataSubjectRequest, error) {
+	kind = strings.ToLower(strings.TrimSpace(kind))
+	if kind != DataSubjectKindExport && kind != DataSubjectKindDelete {
+		return nil, ErrDataSubjectInvalidKind
+	}
+	subjectType = strings.ToLower(strings.TrimSpace(subjectType))
+	if (subjectType != DataSubjectUser && subjectType != DataSubjectAPIKey) || subjectID <= 0 {
+		return nil, ErrDataSubjectInvalidSubject
+	}
+	if kind == DataSubjectKindExport && s.storage == nil {
+		return nil, ErrDataSubjectStorageDisabled
+	}
+	exists, err := s.repo.SubjectExists(ctx, subjectType, subjectID)
+	if err != nil {
+		return nil, fmt.Errorf("check data subject: %w", err)
+	}
+	if !exists {
+		return nil, ErrDataSubjectNotFound
+	}
+	active, err := s.repo.HasActive(ctx, subjectType, subjectID)
+	if err != nil {
+		return nil, fmt.Errorf("check active data subject requests: %w", err)
+	}
+	if active {
+		return nil, ErrDataSubjectRequestDuplicate
+	}
+
+	req := &DataSubjectRequest{
+		Kind:        kind,
+		SubjectType: subjectType,
+		SubjectID:   subjectID,
+		Status:      DataSubjectStatusPending,
+		Reason:      strings.TrimSpace(reason),
+	}
+	if requestedBy > 0 {
+		req.RequestedBy = &requestedBy
+	}
+	if err := s.repo.Create(ctx, req); err != nil {
+		return nil, fmt.Errorf("create data subject request: %w", err)
+	}
+	logger.LegacyPrintf("service.data_subject", "[DataSubject] AUDIT: request created id=%d kind=%s subject=%s:%d requested_by=%d",
+		req.ID, req.Kind, req.SubjectType, req.SubjectID, requestedBy)
+
+	s.dispatch(req.ID)
+	return req, nil
+}
+
+func (s *DataSubjectService) dispatch(id int64) {
+	if s.jobQueue != nil {
+		ctx, cancel := context.WithTimeout(context.Background(), 5*time.Second)
+		defer cancel()
+		_, err := s.jobQueue.Enqueue(ctx, JobTopicDataSubject, dataSubjectJob{RequestID: id})
+		if err == nil {
+			return
+		}
+		logger.LegacyPrintf("service.data_subject", "[DataSubject] enqueue request %d failed, running in-process: %v", id, err)
+	}
+	go func() {
+		ctx, cancel := context.WithTimeout(context.Background(), dataSubjectJobTimeout)
+		defer cancel()
+		if err := s.Process(ctx, id); err != nil {
+			logger.LegacyPrintf("service.data_subject", "[DataSubject] request %d failed: %v", id, err)
+		}
+	}()
+}
+
+func (s *DataSubjectService) handleJob(ctx context.Context, job *QueuedJob) error {
+	var payload dataSubjectJob
+	if err := json.Unmarshal(job.Payload, &payload); err != nil || payload.RequestID <= 0 {
+		logger.LegacyPrintf("service.data_subject", "[DataSubject] 丢弃无效任务 %s: %v", job.ID, err)
+		return nil
+	}
+	// 执行失败已记录在请求中，不交由队列重试（删除可能已部分完成，需人工确认后重新发起）
+	if err := s.Process(ctx, payload.RequestID); err != nil {
+		logger.LegacyPrintf("service.data_subject", "[DataSubject] request %d failed: %v", payload.RequestID, err)
+	}
+	return nil
+}
+
+// Process 执行请求；请求已被领取或不处于 pending 时直接返回
+func (s *DataSubjectService) Process(ctx context.Context, id int64) error {
+	claimed, err := s.repo.MarkRunning(ctx, id)
+	if err != nil {
+		return fmt.Errorf("claim data subject request: %w", err)
+	}
+	if !claimed {
+		return nil
+	}
+	req, err := s.repo.GetByID(ctx, id)
+	if err != nil {
+		return err
+	}
+
+	report := make(map[string]int64)
+	var runErr error
+	switch req.Kind {
+	case DataSubjectKindExport:
+		req.ExportKey, runErr = s.export(ctx, req, report)
+	case DataSubjectKindDelete:
+		runErr = s.delete(ctx, req, report)
+	default:
+		runErr = ErrDataSubjectInvalidKind
+	}
+
+	finishedAt := s.now()
+	req.FinishedAt = &finishedAt
+	req.Report = report
+	req.Status = DataSubjectStatusSucceeded
+	if runErr != nil {
+		req.Status = DataSubjectStatusFailed
+		req.ErrorMessage = runErr.Error()
+	}
+	// 即使任务上下文已取消也要写回结果
+	finishCtx, cancel := context.WithTimeout(context.WithoutCancel(ctx), 10*time.Second)
+	defer cancel()
+	if err := s.repo.Finish(finishCtx, req); err != nil {
+		return fmt.Errorf("finish data subject request: %w", err)
+	}
+	logger.LegacyPrintf("service.data_subject", "[DataSubject] AUDIT: request finished id=%d kind=%s subject=%s:%d status=%s report=%v",
+		req.ID, req.Kind, req.SubjectType, req.SubjectID, req.Status, report)
+	return runErr
+}
+
+func (s *DataSubjectService) export(ctx context.Context, req *DataSubjectRequest, report map[string]int64) (string, error) {
+	if s.storage == nil {
+		return "", ErrDataSubjectStorageDisabled
+	}
+	key := dataSubjectExportPrefix + strconv.FormatInt(req.ID, 10) + "_" + req.SubjectType + "_" + strconv.FormatInt(req.SubjectID, 10) + ".jsonl"
+	w := s.storage.NewWriter(ctx, key, "application/x-ndjson")
+
+	tables := append([]string{"users", "api_keys"}, DataSubjectTables...)
+	for _, table := range tables {
+		n, err := s.repo.ExportRows(ctx, table, req.SubjectType, req.SubjectID, func(row json.RawMessage) error {
+			return writeDataSubjectLine(w, table, row)
+		})
+		if err != nil {
+			w.Abort()
+			return "", fmt.Errorf("export %s: %w", table, err)
+		}
+		report[table] = n
+	}
+	if err := w.Close(); err != nil {
+		return "", fmt.Errorf("upload data subject export: %w", err)
+	}
+	return key, nil
+}
+
+func writeDataSubjectLine(w io.Writer, table string, row json.RawMessage) error {
+	line, err := json.Marshal(struct {
+		Table string          `json:"table"`
+		Row   json.RawMessage `json:"row"`
+	}{Table: table, Row: row})
+	if err != nil {
+		return err
+	}
+	_, err = w.Write(append(line, '\n'))
+	return err
+}
+
+func (s *DataSubjectService) delete(ctx context.Context, req *DataSubjectRequest, report map[string]int64) error {
+	for _, table := range DataSubjectTables {
+		var total int64
+		for {
+			n, err := s.repo.DeleteRows(ctx, table, req.SubjectType, req.SubjectID, dataSubjectDeleteBatchSize)
+			total += n
+			if err != nil {
+				report[table] = total
+				return fmt.Errorf("delete %s: %w", table, err)
+			}
+			if n < dataSubjectDeleteBatchSize {
+				break
+			}
+		}
+		report[table] = total
+	}
+	return nil
+}
+
+// Get 查询单个请求
+func (s *DataSubjectService) Get(ctx context.Context, id int64) (*DataSubjectRequest, error) {
+	return s.repo.GetByID(ctx, id)
+}
+
+// List 分页列出请求（按创建时间倒序）
+func (s *DataSubjectService) List(ctx context.Context, params pagination.PaginationParams) ([]DataSubjectRequest, *pagination.PaginationResult, error) {
+	return s.repo.List(ctx, params)
+}
+
+// OpenExport 打开已完成导出请求的结果文件，调用方负责关闭 Body
+func (s *DataSubjectService) OpenExport(ctx context.Context, id int64) (*DataSubjectRequest, *StoredObject, error) {
+	req, err := s.repo.GetByID(ctx, id)
+	if err != nil {
+		return nil, nil, err
+	}
+	if req.Kind != DataSubjectKindExport || req.Status != DataSubjectStatusSucceeded || req.ExportKey == "" {
+		return nil, nil, ErrDataSubjectExportNotReady
+	}
+	if s.storage == nil {
+		return nil, nil, ErrDataSubjectStorageDisabled
+	}
+	obj, err := s.storage.Get(ctx, req.ExportKey)
+	if err != nil {
+		return nil, nil, err
+	}
+	return req, obj, nil
+}
@@ -0,0 +1,181 @@
+//go:build unit
+
+package service
+
+import (
+	"context"
+	"encoding/json"
+	"errors"
+	"io"
+	"strconv"
+	"strings"
+	"sync"
+	"testing"
+
+	"github.com/Wei-Shaw/sub2api/internal/pkg/pagination"
+	"github.com/stretchr/testify/require"
+)
+
+type dataSubjectRepoStub struct {
+	mu       sync.Mutex
+	requests map[int64]*DataSubjectRequest
+	rows     map[string]int64 // table -> 剩余行数
+	active   bool
+	exists   bool
+	failOn   string
+}
+
+func newDataSubjectRepoStub() *dataSubjectRepoStub {
+	return &dataSubjectRepoStub{requests: map[int64]*DataSubjectRequest{}, rows: map[string]int64{}, exists: true}
+}
+
+func (s *dataSubjectRepoStub) Create(_ context.Context, req *DataSubjectRequest) error {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+	req.ID = int64(len(s.requests) + 1)
+	cp := *req
+	s.requests[req.ID] = &cp
+	return nil
+}
+
+func (s *dataSubjectRepoStub) GetByID(_ context.Context, id int64) (*DataSubjectRequest, error) {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+	req, ok := s.requests[id]
+	if !ok {
+		return nil, ErrDataSubjectRequestNotFound
+	}
+	cp := *req
+	return &cp, nil
+}
+
+func (s *dataSubjectRepoStub) List(context.Context, pagination.PaginationParams) ([]DataSubjectRequest, *pagination.PaginationResult, error) {
+	return nil, &pagination.PaginationResult{}, nil
+}
+
+func (s *dataSubjectRepoStub) HasActive(context.Context, string, int64) (bool, error) {
+	return s.active, nil
+}
+
+func (s *dataSubjectRepoStub) MarkRunning(_ context.Context, id int64) (bool, error) {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+	req, ok := s.requests[id]
+	if !ok || req.Status != DataSubjectStatusPending {
+		return false, nil
+	}
+	req.Status = DataSubjectStatusRunning
+	return true, nil
+}
+
+func (s *dataSubjectRepoStub) Finish(_ context.Context, req *DataSubjectRequest) error {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+	cp := *req
+	s.requests[req.ID] = &cp
+	return nil
+}
+
+func (s *dataSubjectRepoStub) SubjectExists(context.Context, string, int64) (bool, error) {
+	return s.exists, nil
+}
+
+func (s *dataSubjectRepoStub) ExportRows(_ context.Context, table, _ string, subjectID int64, fn func(json.RawMessage) error) (int64, error) {
+	n := s.rows[table]
+	for i := int64(0); i < n; i++ {
+		if err := fn(json.RawMessage(`{"subject":` + strconv.FormatInt(subjectID, 10) + `}`)); err != nil {
+			return i, err
+		}
+	}
+	return n, nil
+}
+
+func (s *dataSubjectRepoStub) DeleteRows(_ context.Context, table, _ string, _ int64, limit int) (int64, error) {
+	if table == s.failOn {
+		return 0, errors.New("db down")
+	}
+	n := min(s.rows[table], int64(limit))
+	s.rows[table] -= n
+	return n, nil
+}
+
+func TestDataSubjectService_CreateRequestValidation(t *testing.T) {
+	repo := newDataSubjectRepoStub()
+	svc := NewDataSubjectService(repo, nil, nil)
+	ctx := context.Background()
+
+	_, err := svc.CreateRequest(ctx, "purge", DataSubjectUser, 1, "", 9)
+	require.ErrorIs(t, err, ErrDataSubjectInvalidKind)
+	_, err = svc.CreateRequest(ctx, DataSubjectKindDelete, "org", 1, "", 9)
+	require.ErrorIs(t, err, ErrDataSubjectInvalidSubject)
+	_, err = svc.CreateRequest(ctx, DataSubjectKindExport, DataSubjectUser, 1, "", 9)
+	require.ErrorIs(t, err, ErrDataSubjectStorageDisabled)
+
+	repo.exists = false
+	_, err = svc.CreateRequest(ctx, DataSubjectKindDelete, DataSubjectUser, 1, "", 9)
+	require.ErrorIs(t, err, ErrDataSubjectNotFound)
+
+	repo.exists = true
+	repo.active = true
+	_, err = svc.CreateRequest(ctx, DataSubjectKindDelete, DataSubjectUser, 1, "", 9)
+	require.ErrorIs(t, err, ErrDataSubjectRequestDuplicate)
+}
+
+func TestDataSubjectService_ProcessDeleteReportsPerTable(t *testing.T) {
+	repo := newDataSubjectRepoStub()
+	repo.rows["usage_logs"] = dataSubjectDeleteBatchSize*2 + 3
+	repo.rows["ops_error_logs"] = 4
+	svc := NewDataSubjectService(repo, nil, nil)
+	require.NoError(t, repo.Create(context.Background(), &DataSubjectRequest{Kind: DataSubjectKindDelete, SubjectType: DataSubjectAPIKey, SubjectID: 7, Status: DataSubjectStatusPending}))
+
+	require.NoError(t, svc.Process(context.Background(), 1))
+	req, _ := repo.GetByID(context.Background(), 1)
+	require.Equal(t, DataSubjectStatusSucceeded, req.Status)
+	require.NotNil(t, req.FinishedAt)
+	require.Equal(t, int64(dataSubjectDeleteBatchSize*2+3), req.Report["usage_logs"])
+	require.Equal(t, int64(4), req.Report["ops_error_logs"])
+	require.Len(t, req.Report, len(DataSubjectTables))
+	require.Zero(t, repo.rows["usage_logs"])
+
+	// 已完成的请求不会重复执行
+	require.NoError(t, svc.Process(context.Background(), 1))
+}
+
+func TestDataSubjectService_ProcessDeleteFailureIsRecorded(t *testing.T) {
+	repo := newDataSubjectRepoStub()
+	repo.rows["usage_logs"] = 2
+	repo.failOn = "request_logs"
+	svc := NewDataSubjectService(repo, nil, nil)
+	require.NoError(t, repo.Create(context.Background(), &DataSubjectRequest{Kind: DataSubjectKindDelete, SubjectType: DataSubjectUser, SubjectID: 1, Status: DataSubjectStatusPending}))
+
+	require.Error(t, svc.Process(context.Background(), 1))
+	req, _ := repo.GetByID(context.Background(), 1)
+	require.Equal(t, DataSubjectStatusFailed, req.Status)
+	require.Contains(t, req.ErrorMessage, "request_logs")
+	require.Equal(t, int64(2), req.Report["usage_logs"])
+}
+
+func TestDataSubjectService_ExportWritesJSONLines(t *testing.T) {
+	repo := newDataSubjectRepoStub()
+	repo.rows["users"] = 1
+	repo.rows["usage_logs"] = 2
+	storage := newMemoryObjectStorage()
+	svc := NewDataSubjectService(repo, storage, nil)
+	require.NoError(t, repo.Create(context.Background(), &DataSubjectRequest{Kind: DataSubjectKindExport, SubjectType: DataSubjectUser, SubjectID: 1, Status: DataSubjectStatusPending}))
+
+	require.NoError(t, svc.Process(context.Background(), 1))
+	req, obj, err := svc.OpenExport(context.Background(), 1)
+	require.NoError(t, err)
+	require.Equal(t, "data-subject-exports/1_user_1.jsonl", req.ExportKey)
+	require.Equal(t, int64(2), req.Report["usage_logs"])
+
+	data, err := io.ReadAll(obj.Body)
+	require.NoError(t, err)
+	lines := strings.Split(strings.TrimSpace(string(data)), "\n")
+	require.Len(t, lines, 3)
+	require.JSONEq(t, `{"table":"users","row":{"subject":1}}`, lines[0])
+	require.JSONEq(t, `{"table":"usage_logs","row":{"subject":1}}`, lines[1])
+
+	_, _, err = svc.OpenExport(context.Background(), 2)
+	require.ErrorIs(t, err, ErrDataSubjectRequestNotFound)
+}
@@ -14,8 +14,11 @@ const (
 
 // Role constants
 const (
-	RoleAdmin = domain.RoleAdmin
-	RoleUser  = domain.RoleUser
+	RoleAdmin    = domain.RoleAdmin
+	RoleUser     = domain.RoleUser
+	RoleBilling  = domain.RoleBilling
+	RoleAccounts = domain.RoleAccounts
+	RoleAuditor  = domain.RoleAuditor
 )
 
 // Platform constants
@@ -33,6 +36,8 @@ const (
 	AccountTypeSetupToken = domain.AccountTypeSetupToken // Setup Token类型账号（inference only scope）
 	AccountTypeAPIKey     = domain.AccountTypeAPIKey     // API Key类型账号
 	AccountTypeUpstream   = domain.AccountTypeUpstream   // 上游透传类型账号（通过 Base URL + API Key 连接上游）
+	AccountTypeWebSession = domain.AccountTypeWebSession // 网页会话类型账号（通过网页登录 Cookie 驱动官方网页对话接口）
+	AccountTypeCopilot    = domain.AccountTypeCopilot    // GitHub Copilot 订阅账号（设备码登录，GitHub token 换取短期 Copilot token）
 )
 
 // Redeem type constants
@@ -152,6 +157,9 @@ const (
 	// SettingKeyOpsEmailNotificationConfig stores JSON config for ops email notifications.
 	SettingKeyOpsEmailNotificationConfig = "ops_email_notification_config"
 
+	// SettingKeyOpsDiscordNotificationConfig stores JSON config for ops Discord webhook notifications.
+	SettingKeyOpsDiscordNotificationConfig = "ops_discord_notification_config"
+
 	// SettingKeyOpsAlertRuntimeSettings stores JSON config for ops alert evaluator runtime settings.
 	SettingKeyOpsAlertRuntimeSettings = "ops_alert_runtime_settings"
 
@@ -171,6 +179,13 @@ const (
 	// SettingKeyStreamTimeoutSettings stores JSON config for stream timeout handling.
 	SettingKeyStreamTimeoutSettings = "stream_timeout_settings"
 
+	// =========================
+	// Maintenance Mode
+	// =========================
+
+	// SettingKeyMaintenanceSettings stores JSON config for gateway maintenance mode.
+	SettingKeyMaintenanceSettings = "maintenance_settings"
+
 	// Onboarding tour
 	SettingKeyOnboardingEnabled = "onboarding_enabled" // 是否在登录后自动显示引导教程
 )
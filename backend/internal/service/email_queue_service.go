@@ -2,6 +2,7 @@ package service
 
 import (
 	"context"
+	"encoding/json"
 	"fmt"
 	"sync"
 	"time"
@@ -13,27 +14,45 @@ import (
 const (
 	TaskTypeVerifyCode    = "verify_code"
 	TaskTypePasswordReset = "password_reset"
+	TaskTypeNotice        = "notice"
 )
 
 // EmailTask 邮件发送任务
 type EmailTask struct {
-	Email    string
-	SiteName string
-	TaskType string // "verify_code" or "password_reset"
-	ResetURL string // Only used for password_reset task type
+	Email    string `json:"email"`
+	SiteName string `json:"site_name"`
+	TaskType string `json:"task_type"`           // "verify_code", "password_reset" or "notice"
+	ResetURL string `json:"reset_url,omitempty"` // Only used for password_reset task type
+	Subject  string `json:"subject,omitempty"`   // Only used for notice task type
+	Body     string `json:"body,omitempty"`      // Only used for notice task type (HTML)
+	LogID    int64  `json:"log_id,omitempty"`    // notification_send_logs.id，用于回写发送结果
+}
+
+// EmailSendRecorder 回写通知邮件的发送结果（每次尝试都会调用，持久化队列重试成功后覆盖失败状态）
+type EmailSendRecorder interface {
+	RecordEmailResult(ctx context.Context, logID int64, sendErr error)
 }
 
 // EmailQueueService 异步邮件队列服务
+// 启用持久化任务队列时，任务写入 Redis Streams 由任意实例消费（失败自动重试）；
+// 否则使用进程内通道。
 type EmailQueueService struct {
 	emailService *EmailService
+	jobQueue     *JobQueueService
 	taskChan     chan EmailTask
 	wg           sync.WaitGroup
 	stopChan     chan struct{}
 	workers      int
+	recorder     EmailSendRecorder
 }
 
 // NewEmailQueueService 创建邮件队列服务
 func NewEmailQueueService(emailService *EmailService, workers int) *EmailQueueService {
+	return NewEmailQueueServiceWithJobQueue(emailService, nil, workers)
+}
+
+// NewEmailQueueServiceWithJobQueue 创建邮件队列服务，jobQueue 可用时改为通过持久化队列投递
+func NewEmailQueueServiceWithJobQueue(emailService *EmailService, jobQueue *JobQueueService, workers int) *EmailQueueService {
 	if workers <= 0 {
 		workers = 3 // 默认3个工作协程
 	}
@@ -44,6 +63,10 @@ func NewEmailQueueService(emailService *EmailService, workers int) *EmailQueueSe
 		stopChan:     make(chan struct{}),
 		workers:      workers,
 	}
+	if jobQueue.Enabled() {
+		service.jobQueue = jobQueue
+		jobQueue.RegisterHandler(JobTopicEmail, workers, 30*time.Second, service.handleJob)
+	}
 
 	// 启动工作协程
 	service.start()
@@ -80,57 +103,116 @@ func (s *EmailQueueService) processTask(workerID int, task EmailTask) {
 	ctx, cancel := context.WithTimeout(context.Background(), 30*time.Second)
 	defer cancel()
 
+	err := s.sendTask(ctx, task)
+	s.record(ctx, task, err)
+	if err != nil {
+		logger.LegacyPrintf("service.email_queue", "[EmailQueue] Worker %d failed to send %s to %s: %v", workerID, task.TaskType, task.Email, err)
+	} else {
+		logger.LegacyPrintf("service.email_queue", "[EmailQueue] Worker %d sent %s to %s", workerID, task.TaskType, task.Email)
+	}
+}
+
+// handleJob 处理持久化队列中的邮件任务；返回错误时由队列重试
+func (s *EmailQueueService) handleJob(ctx context.Context, job *QueuedJob) error {
+	var task EmailTask
+	if err := json.Unmarshal(job.Payload, &task); err != nil {
+		// 无法解析的任务重试也无意义，直接丢弃
+		logger.LegacyPrintf("service.email_queue", "[EmailQueue] Drop malformed job %s: %v", job.ID, err)
+		return nil
+	}
+	err := s.sendTask(ctx, task)
+	s.record(ctx, task, err)
+	if err != nil {
+		return err
+	}
+	logger.LegacyPrintf("service.email_queue", "[EmailQueue] Sent %s to %s (job %s)", task.TaskType, task.Email, job.ID)
+	return nil
+}
+
+func (s *EmailQueueService) sendTask(ctx context.Context, task EmailTask) error {
 	switch task.TaskType {
 	case TaskTypeVerifyCode:
-		if err := s.emailService.SendVerifyCode(ctx, task.Email, task.SiteName); err != nil {
-			logger.LegacyPrintf("service.email_queue", "[EmailQueue] Worker %d failed to send verify code to %s: %v", workerID, task.Email, err)
-		} else {
-			logger.LegacyPrintf("service.email_queue", "[EmailQueue] Worker %d sent verify code to %s", workerID, task.Email)
-		}
+		return s.emailService.SendVerifyCode(ctx, task.Email, task.SiteName)
 	case TaskTypePasswordReset:
-		if err := s.emailService.SendPasswordResetEmailWithCooldown(ctx, task.Email, task.SiteName, task.ResetURL); err != nil {
-			logger.LegacyPrintf("service.email_queue", "[EmailQueue] Worker %d failed to send password reset to %s: %v", workerID, task.Email, err)
-		} else {
-			logger.LegacyPrintf("service.email_queue", "[EmailQueue] Worker %d sent password reset to %s", workerID, task.Email)
-		}
+		return s.emailService.SendPasswordResetEmailWithCooldown(ctx, task.Email, task.SiteName, task.ResetURL)
+	case TaskTypeNotice:
+		return s.emailService.SendEmail(ctx, task.Email, task.Subject, task.Body)
 	default:
-		logger.LegacyPrintf("service.email_queue", "[EmailQueue] Worker %d unknown task type: %s", workerID, task.TaskType)
+		return fmt.Errorf("unknown task type: %s", task.TaskType)
 	}
 }
 
-// EnqueueVerifyCode 将验证码发送任务加入队列
-func (s *EmailQueueService) EnqueueVerifyCode(email, siteName string) error {
-	task := EmailTask{
-		Email:    email,
-		SiteName: siteName,
-		TaskType: TaskTypeVerifyCode,
+// SetSendRecorder 设置通知邮件发送结果的回写；需在发送通知前调用
+func (s *EmailQueueService) SetSendRecorder(recorder EmailSendRecorder) {
+	s.recorder = recorder
+}
+
+func (s *EmailQueueService) record(ctx context.Context, task EmailTask, err error) {
+	if s.recorder != nil && task.LogID > 0 {
+		s.recorder.RecordEmailResult(ctx, task.LogID, err)
+	}
+}
+
+// enqueue 优先写入持久化队列，失败时退回进程内通道
+func (s *EmailQueueService) enqueue(task EmailTask) error {
+	if s.jobQueue != nil {
+		ctx, cancel := context.WithTimeout(context.Background(), 3*time.Second)
+		defer cancel()
+		_, err := s.jobQueue.Enqueue(ctx, JobTopicEmail, task)
+		if err == nil {
+			logger.LegacyPrintf("service.email_queue", "[EmailQueue] Enqueued %s task for %s", task.TaskType, task.Email)
+			return nil
+		}
+		logger.LegacyPrintf("service.email_queue", "[EmailQueue] Job queue unavailable, falling back to in-process queue: %v", err)
 	}
 
 	select {
 	case s.taskChan <- task:
-		logger.LegacyPrintf("service.email_queue", "[EmailQueue] Enqueued verify code task for %s", email)
+		logger.LegacyPrintf("service.email_queue", "[EmailQueue] Enqueued %s task for %s", task.TaskType, task.Email)
 		return nil
 	default:
 		return fmt.Errorf("email queue is full")
 	}
 }
 
+// EnqueueVerifyCode 将验证码发送任务加入队列
+func (s *EmailQueueService) EnqueueVerifyCode(email, siteName string) error {
+	return s.enqueue(EmailTask{
+		Email:    email,
+		SiteName: siteName,
+		TaskType: TaskTypeVerifyCode,
+	})
+}
+
 // EnqueuePasswordReset 将密码重置邮件任务加入队列
 func (s *EmailQueueService) EnqueuePasswordReset(email, siteName, resetURL string) error {
-	task := EmailTask{
+	return s.enqueue(EmailTask{
 		Email:    email,
 		SiteName: siteName,
 		TaskType: TaskTypePasswordReset,
 		ResetURL: resetURL,
-	}
+	})
+}
 
-	select {
-	case s.taskChan <- task:
-		logger.LegacyPrintf("service.email_queue", "[EmailQueue] Enqueued password reset task for %s", email)
-		return nil
-	default:
-		return fmt.Errorf("email queue is full")
-	}
+// EnqueueNotice 将系统通知邮件（已渲染的主题与 HTML 正文）加入队列
+func (s *EmailQueueService) EnqueueNotice(email, subject, body string) error {
+	return s.enqueue(EmailTask{
+		Email:    email,
+		TaskType: TaskTypeNotice,
+		Subject:  subject,
+		Body:     body,
+	})
+}
+
+// EnqueueLoggedNotice 与 EnqueueNotice 相同，发送结果回写到 logID 对应的发送记录
+func (s *EmailQueueService) EnqueueLoggedNotice(email, subject, body string, logID int64) error {
+	return s.enqueue(EmailTask{
+		Email:    email,
+		TaskType: TaskTypeNotice,
+		Subject:  subject,
+		Body:     body,
+		LogID:    logID,
+	})
 }
 
 // Stop 停止队列服务
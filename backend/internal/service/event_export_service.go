@@ -0,0 +1,367 @@
+package service
+
+import (
+	"context"
+	"encoding/json"
+	"sort"
+	"strconv"
+	"sync"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/logger"
+)
+
+// 导出事件类型
+const (
+	EventExportTypeUsage = "usage"
+	EventExportTypeAudit = "audit"
+	EventExportTypeAlert = "alert"
+)
+
+const (
+	// eventExportMaxBackoff 发布失败后的最长重试间隔
+	eventExportMaxBackoff = 5 * time.Minute
+	// eventExportCleanupInterval 已发布事件的清理间隔
+	eventExportCleanupInterval = time.Hour
+	// eventExportCleanupBatch 单次清理的最大行数
+	eventExportCleanupBatch = 5000
+	// eventExportMaxErrorLen last_error 最大长度
+	eventExportMaxErrorLen = 1024
+)
+
+// EventOutboxRecord outbox 中待发布的事件
+type EventOutboxRecord struct {
+	ID        int64
+	EventType string
+	EventKey  string
+	Payload   []byte
+	Attempts  int
+	CreatedAt time.Time
+}
+
+// EventOutboxRepository 事件 outbox 存储
+type EventOutboxRepository interface {
+	Insert(ctx context.Context, eventType, eventKey string, payload []byte) error
+	// Claim 认领到期的未发布事件并将其下次尝试时间推迟 lease，租约内其他实例不会重复认领
+	Claim(ctx context.Context, limit int, lease time.Duration) ([]*EventOutboxRecord, error)
+	MarkPublished(ctx context.Context, ids []int64) error
+	// MarkFailed 记录失败原因，并按已尝试次数指数退避（不超过 maxBackoff）
+	MarkFailed(ctx context.Context, ids []int64, lastError string, maxBackoff time.Duration) error
+	DeletePublishedBefore(ctx context.Context, before time.Time, limit int) (int64, error)
+}
+
+// EventExportMessage 一条待发布消息
+type EventExportMessage struct {
+	// ID 事件 id（NATS JetStream 用作 Nats-Msg-Id 去重）
+	ID string
+	// Key 分区键（Kafka），为空时由 broker 自行分配
+	Key string
+	// Value 完整事件 JSON（EventExportEnvelope）
+	Value []byte
+}
+
+// EventPublisher 事件发布端（Kafka / NATS）
+type EventPublisher interface {
+	// Publish 发布一批消息到同一 topic / subject；返回 nil 表示全部已被 broker 接受
+	Publish(ctx context.Context, topic string, messages []EventExportMessage) error
+}
+
+// EventExportEnvelope 发布到下游的事件格式
+type EventExportEnvelope struct {
+	// ID outbox 自增 id，重试时保持不变，下游据此去重
+	ID         int64           `json:"id"`
+	Type       string          `json:"type"`
+	OccurredAt time.Time       `json:"occurred_at"`
+	Data       json.RawMessage `json:"data"`
+}
+
+// EventExportUsage 用量事件（不含请求/响应内容）
+type EventExportUsage struct {
+	RequestID           string    `json:"request_id"`
+	UserID              int64     `json:"user_id"`
+	APIKeyID            int64     `json:"api_key_id"`
+	AccountID           int64     `json:"account_id"`
+	GroupID             *int64    `json:"group_id,omitempty"`
+	SubscriptionID      *int64    `json:"subscription_id,omitempty"`
+	Model               string    `json:"model"`
+	InputTokens         int       `json:"input_tokens"`
+	OutputTokens        int       `json:"output_tokens"`
+	CacheCreationTokens int       `json:"cache_creation_tokens"`
+	CacheReadTokens     int       `json:"cache_read_tokens"`
+	TotalCost           float64   `json:"total_cost"`
+	ActualCost          float64   `json:"actual_cost"`
+	RateMultiplier      float64   `json:"rate_multiplier"`
+	BillingType         int8      `json:"billing_type"`
+	Stream              bool      `json:"stream"`
+	DurationMs          *int      `json:"duration_ms,omitempty"`
+	FirstTokenMs        *int      `json:"first_token_ms,omitempty"`
+	ImageCount          int       `json:"image_count,omitempty"`
+	CreatedAt           time.Time `json:"created_at"`
+}
+
+// EventExportAudit 审计事件
+type EventExportAudit struct {
+	Action      string    `json:"action"`
+	ActorUserID int64     `json:"actor_user_id"`
+	ActorRole   string    `json:"actor_role"`
+	Changes     []string  `json:"changes,omitempty"`
+	At          time.Time `json:"at"`
+}
+
+// EventExportService 将用量、审计、告警事件导出到 Kafka / NATS。
+//
+// 产生事件的一方只把事件写入 event_outbox 表（写入失败仅记录日志，不影响业务流程）；
+// 后台转发任务认领到期事件、按 topic 批量发布，成功后标记已发布，失败按指数退避重试。
+// 认领带租约，多实例并发转发不会重复认领；进程在发布后、标记前退出时事件会被再次发布，
+// 因此语义为至少一次，下游按 EventExportEnvelope.ID 去重。
+type EventExportService struct {
+	repo      EventOutboxRepository
+	publisher EventPublisher
+	cfg       config.EventExportConfig
+
+	stopCh    chan struct{}
+	startOnce sync.Once
+	stopOnce  sync.Once
+	wg        sync.WaitGroup
+}
+
+// NewEventExportService 创建事件导出服务
+func NewEventExportService(repo EventOutboxRepository, publisher EventPublisher, cfg *config.Config) *EventExportService {
+	s := &EventExportService{repo: repo, publisher: publisher, stopCh: make(chan struct{})}
+	if cfg != nil {
+		s.cfg = cfg.EventExport
+	}
+	return s
+}
+
+// Enabled 是否启用事件导出
+func (s *EventExportService) Enabled() bool {
+	return s != nil && s.cfg.Enabled && s.repo != nil && s.publisher != nil
+}
+
+func (s *EventExportService) topicFor(eventType string) string {
+	switch eventType {
+	case EventExportTypeUsage:
+		return s.cfg.Topics.Usage
+	case EventExportTypeAudit:
+		return s.cfg.Topics.Audit
+	case EventExportTypeAlert:
+		return s.cfg.Topics.Alert
+	}
+	return ""
+}
+
+// Record 将事件写入 outbox；未启用或该类事件未配置 topic 时忽略
+func (s *EventExportService) Record(ctx context.Context, eventType, key string, data any) {
+	if !s.Enabled() || s.topicFor(eventType) == "" {
+		return
+	}
+	payload, err := json.Marshal(data)
+	if err != nil {
+		logger.LegacyPrintf("service.event_export", "[EventExport] marshal %s event failed: %v", eventType, err)
+		return
+	}
+	if err := s.repo.Insert(ctx, eventType, key, payload); err != nil {
+		logger.LegacyPrintf("service.event_export", "[EventExport] write %s event to outbox failed: %v", eventType, err)
+	}
+}
+
+// RecordUsage 记录一条用量事件（以用户 ID 作为分区键，同一用户的事件保持分区内有序）
+func (s *EventExportService) RecordUsage(ctx context.Context, log *UsageLog) {
+	if log == nil || !s.Enabled() {
+		return
+	}
+	s.Record(ctx, EventExportTypeUsage, strconv.FormatInt(log.UserID, 10), &EventExportUsage{
+		RequestID:           log.RequestID,
+		UserID:              log.UserID,
+		APIKeyID:            log.APIKeyID,
+		AccountID:           log.AccountID,
+		GroupID:             log.GroupID,
+		SubscriptionID:      log.SubscriptionID,
+		Model:               log.Model,
+		InputTokens:         log.InputTokens,
+		OutputTokens:        log.OutputTokens,
+		CacheCreationTokens: log.CacheCreationTokens,
+		CacheReadTokens:     log.CacheReadTokens,
+		TotalCost:           log.TotalCost,
+		ActualCost:          log.ActualCost,
+		RateMultiplier:      log.RateMultiplier,
+		BillingType:         log.BillingType,
+		Stream:              log.Stream,
+		DurationMs:          log.DurationMs,
+		FirstTokenMs:        log.FirstTokenMs,
+		ImageCount:          log.ImageCount,
+		CreatedAt:           log.CreatedAt,
+	})
+}
+
+// RecordAudit 记录一条审计事件
+func (s *EventExportService) RecordAudit(ctx context.Context, audit *EventExportAudit) {
+	if audit == nil {
+		return
+	}
+	s.Record(ctx, EventExportTypeAudit, audit.Action, audit)
+}
+
+// RecordAlert 记录一条告警触发 / 恢复事件
+func (s *EventExportService) RecordAlert(ctx context.Context, event *OpsAlertEvent) {
+	if event == nil {
+		return
+	}
+	s.Record(ctx, EventExportTypeAlert, strconv.FormatInt(event.RuleID, 10), event)
+}
+
+// Start 启动 outbox 转发任务
+func (s *EventExportService) Start() {
+	if !s.Enabled() {
+		return
+	}
+	s.startOnce.Do(func() {
+		s.wg.Add(1)
+		go s.run()
+		logger.LegacyPrintf("service.event_export", "[EventExport] Started (driver=%s batch_size=%d)", s.cfg.Driver, s.cfg.BatchSize)
+	})
+}
+
+// Stop 停止转发任务（未发布的事件保留在 outbox 中，由下次启动或其他实例继续发布）
+func (s *EventExportService) Stop() {
+	if s == nil {
+		return
+	}
+	s.stopOnce.Do(func() { close(s.stopCh) })
+	s.wg.Wait()
+}
+
+func (s *EventExportService) run() {
+	defer s.wg.Done()
+	poll := time.NewTicker(time.Duration(s.cfg.PollIntervalMs) * time.Millisecond)
+	defer poll.Stop()
+	cleanup := time.NewTicker(eventExportCleanupInterval)
+	defer cleanup.Stop()
+
+	for {
+		select {
+		case <-s.stopCh:
+			return
+		case <-poll.C:
+			s.relay()
+		case <-cleanup.C:
+			s.cleanup()
+		}
+	}
+}
+
+// relay 连续发布，直到没有到期事件、发布失败或服务停止
+func (s *EventExportService) relay() {
+	for {
+		claimed, err := s.relayOnce(context.Background())
+		if err != nil || claimed < s.cfg.BatchSize {
+			return
+		}
+		select {
+		case <-s.stopCh:
+			return
+		default:
+		}
+	}
+}
+
+// relayOnce 认领一批事件并按 topic 分组发布，返回认领数量
+func (s *EventExportService) relayOnce(ctx context.Context) (int, error) {
+	timeout := time.Duration(s.cfg.TimeoutSeconds) * time.Second
+	// 租约覆盖所有分组的发布时间，避免发布过程中被其他实例重复认领
+	records, err := s.repo.Claim(ctx, s.cfg.BatchSize, 2*timeout)
+	if err != nil {
+		logger.LegacyPrintf("service.event_export", "[EventExport] claim outbox events failed: %v", err)
+		return 0, err
+	}
+	if len(records) == 0 {
+		return 0, nil
+	}
+	sort.Slice(records, func(i, j int) bool { return records[i].ID < records[j].ID })
+
+	topics := make([]string, 0, 3)
+	messages := make(map[string][]EventExportMessage, 3)
+	ids := make(map[string][]int64, 3)
+	var discarded []int64
+	for _, record := range records {
+		topic := s.topicFor(record.EventType)
+		if topic == "" {
+			// 配置变更后不再导出的事件类型，直接标记完成
+			discarded = append(discarded, record.ID)
+			continue
+		}
+		value, err := json.Marshal(&EventExportEnvelope{
+			ID:         record.ID,
+			Type:       record.EventType,
+			OccurredAt: record.CreatedAt,
+			Data:       record.Payload,
+		})
+		if err != nil {
+			logger.LegacyPrintf("service.event_export", "[EventExport] drop malformed event %d: %v", record.ID, err)
+			discarded = append(discarded, record.ID)
+			continue
+		}
+		if _, ok := messages[topic]; !ok {
+			topics = append(topics, topic)
+		}
+		messages[topic] = append(messages[topic], EventExportMessage{
+			ID:    strconv.FormatInt(record.ID, 10),
+			Key:   record.EventKey,
+			Value: value,
+		})
+		ids[topic] = append(ids[topic], record.ID)
+	}
+	if len(discarded) > 0 {
+		if err := s.repo.MarkPublished(ctx, discarded); err != nil {
+			logger.LegacyPrintf("service.event_export", "[EventExport] mark %d discarded events failed: %v", len(discarded), err)
+		}
+	}
+
+	var firstErr error
+	for _, topic := range topics {
+		pubCtx, cancel := context.WithTimeout(ctx, timeout)
+		err := s.publisher.Publish(pubCtx, topic, messages[topic])
+		cancel()
+		if err != nil {
+			logger.LegacyPrintf("service.event_export", "[EventExport] publish %d events to %s failed: %v", len(ids[topic]), topic, err)
+			if markErr := s.repo.MarkFailed(ctx, ids[topic], truncateString(err.Error(), eventExportMaxErrorLen), eventExportMaxBackoff); markErr != nil {
+				logger.LegacyPrintf("service.event_export", "[EventExport] mark %d events failed: %v", len(ids[topic]), markErr)
+			}
+			if firstErr == nil {
+				firstErr = err
+			}
+			continue
+		}
+		if err := s.repo.MarkPublished(ctx, ids[topic]); err != nil {
+			// 租约到期后会被再次发布，由下游去重
+			logger.LegacyPrintf("service.event_export", "[EventExport] mark %d events published failed: %v", len(ids[topic]), err)
+		}
+	}
+	return len(records), firstErr
+}
+
+func (s *EventExportService) cleanup() {
+	before := time.Now().Add(-time.Duration(s.cfg.RetentionHours) * time.Hour)
+	var deleted int64
+	for {
+		n, err := s.repo.DeletePublishedBefore(context.Background(), before, eventExportCleanupBatch)
+		if err != nil {
+			logger.LegacyPrintf("service.event_export", "[EventExport] cleanup published events failed: %v", err)
+			break
+		}
+		deleted += n
+		if n < eventExportCleanupBatch {
+			break
+		}
+		select {
+		case <-s.stopCh:
+			return
+		default:
+		}
+	}
+	if deleted > 0 {
+		logger.LegacyPrintf("service.event_export", "[EventExport] cleaned up %d published events", deleted)
+	}
+}
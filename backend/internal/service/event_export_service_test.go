@@ -0,0 +1,150 @@
+//go:build unit
+
+package service
+
+import (
+	"context"
+	"encoding/json"
+	"errors"
+	"testing"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/stretchr/testify/require"
+)
+
+type eventOutboxRepoStub struct {
+	inserted  []*EventOutboxRecord
+	claimed   []*EventOutboxRecord
+	published []int64
+	failed    []int64
+	lastError string
+}
+
+func (r *eventOutboxRepoStub) Insert(ctx context.Context, eventType, eventKey string, payload []byte) error {
+	r.inserted = append(r.inserted, &EventOutboxRecord{ID: int64(len(r.inserted) + 1), EventType: eventType, EventKey: eventKey, Payload: payload})
+	return nil
+}
+
+func (r *eventOutboxRepoStub) Claim(ctx context.Context, limit int, lease time.Duration) ([]*EventOutboxRecord, error) {
+	out := r.claimed
+	r.claimed = nil
+	return out, nil
+}
+
+func (r *eventOutboxRepoStub) MarkPublished(ctx context.Context, ids []int64) error {
+	r.published = append(r.published, ids...)
+	return nil
+}
+
+func (r *eventOutboxRepoStub) MarkFailed(ctx context.Context, ids []int64, lastError string, maxBackoff time.Duration) error {
+	r.failed = append(r.failed, ids...)
+	r.lastError = lastError
+	return nil
+}
+
+func (r *eventOutboxRepoStub) DeletePublishedBefore(ctx context.Context, before time.Time, limit int) (int64, error) {
+	return 0, nil
+}
+
+type eventPublisherStub struct {
+	published map[string][]EventExportMessage
+	failTopic string
+}
+
+func (p *eventPublisherStub) Publish(ctx context.Context, topic string, messages []EventExportMessage) error {
+	if topic == p.failTopic {
+		return errors.New("broker unavailable")
+	}
+	if p.published == nil {
+		p.published = map[string][]EventExportMessage{}
+	}
+	p.published[topic] = append(p.published[topic], messages...)
+	return nil
+}
+
+func newEventExportTestService(repo EventOutboxRepository, publisher EventPublisher) *EventExportService {
+	return NewEventExportService(repo, publisher, &config.Config{EventExport: config.EventExportConfig{
+		Enabled:        true,
+		Driver:         "kafka",
+		Topics:         config.EventExportTopicsConfig{Usage: "sub2api.usage", Alert: "sub2api.alert"},
+		BatchSize:      100,
+		PollIntervalMs: 1000,
+		TimeoutSeconds: 5,
+		RetentionHours: 24,
+	}})
+}
+
+func TestEventExportService_RecordWritesOutbox(t *testing.T) {
+	repo := &eventOutboxRepoStub{}
+	svc := newEventExportTestService(repo, &eventPublisherStub{})
+
+	svc.RecordUsage(context.Background(), &UsageLog{UserID: 7, RequestID: "req-1", Model: "claude-sonnet-4", InputTokens: 10})
+	// 审计事件未配置 topic，不写入 outbox
+	svc.RecordAudit(context.Background(), &EventExportAudit{Action: "settings.update"})
+
+	require.Len(t, repo.inserted, 1)
+	require.Equal(t, EventExportTypeUsage, repo.inserted[0].EventType)
+	require.Equal(t, "7", repo.inserted[0].EventKey)
+	var usage EventExportUsage
+	require.NoError(t, json.Unmarshal(repo.inserted[0].Payload, &usage))
+	require.Equal(t, "req-1", usage.RequestID)
+	require.Equal(t, 10, usage.InputTokens)
+}
+
+func TestEventExportService_RelayGroupsByTopic(t *testing.T) {
+	createdAt := time.Date(2026, 1, 2, 3, 4, 5, 0, time.UTC)
+	repo := &eventOutboxRepoStub{claimed: []*EventOutboxRecord{
+		{ID: 3, EventType: EventExportTypeAlert, EventKey: "9", Payload: []byte(`{"rule_id":9}`), CreatedAt: createdAt},
+		{ID: 1, EventType: EventExportTypeUsage, EventKey: "7", Payload: []byte(`{"user_id":7}`), CreatedAt: createdAt},
+		{ID: 2, EventType: EventExportTypeAudit, Payload: []byte(`{}`), CreatedAt: createdAt},
+	}}
+	publisher := &eventPublisherStub{}
+	svc := newEventExportTestService(repo, publisher)
+
+	claimed, err := svc.relayOnce(context.Background())
+	require.NoError(t, err)
+	require.Equal(t, 3, claimed)
+	require.ElementsMatch(t, []int64{1, 2, 3}, repo.published)
+
+	require.Len(t, publisher.published["sub2api.usage"], 1)
+	msg := publisher.published["sub2api.usage"][0]
+	require.Equal(t, "1", msg.ID)
+	require.Equal(t, "7", msg.Key)
+	var envelope EventExportEnvelope
+	require.NoError(t, json.Unmarshal(msg.Value, &envelope))
+	require.Equal(t, int64(1), envelope.ID)
+	require.Equal(t, EventExportTypeUsage, envelope.Type)
+	require.True(t, createdAt.Equal(envelope.OccurredAt))
+	require.JSONEq(t, `{"user_id":7}`, string(envelope.Data))
+	require.Len(t, publisher.published["sub2api.alert"], 1)
+}
+
+func TestEventExportService_RelayFailureSchedulesRetry(t *testing.T) {
+	repo := &eventOutboxRepoStub{claimed: []*EventOutboxRecord{
+		{ID: 1, EventType: EventExportTypeUsage, Payload: []byte(`{}`)},
+		{ID: 2, EventType: EventExportTypeAlert, Payload: []byte(`{}`)},
+	}}
+	svc := newEventExportTestService(repo, &eventPublisherStub{failTopic: "sub2api.usage"})
+
+	_, err := svc.relayOnce(context.Background())
+	require.Error(t, err)
+	require.Equal(t, []int64{1}, repo.failed)
+	require.Contains(t, repo.lastError, "broker unavailable")
+	// 其他 topic 的发布不受影响
+	require.Equal(t, []int64{2}, repo.published)
+}
+
+func TestEventExportService_DisabledIsNoop(t *testing.T) {
+	repo := &eventOutboxRepoStub{}
+	svc := NewEventExportService(repo, &eventPublisherStub{}, &config.Config{})
+	require.False(t, svc.Enabled())
+	svc.RecordUsage(context.Background(), &UsageLog{UserID: 1})
+	require.Empty(t, repo.inserted)
+	svc.Start()
+	svc.Stop()
+
+	var nilSvc *EventExportService
+	nilSvc.RecordAlert(context.Background(), &OpsAlertEvent{RuleID: 1})
+	nilSvc.Stop()
+}
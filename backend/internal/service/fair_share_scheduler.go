@@ -0,0 +1,316 @@
+package service
+
+import (
+	"sort"
+	"sync"
+	"time"
+
+	infraerrors "github.com/Wei-Shaw/sub2api/internal/pkg/errors"
+)
+
+// fairShareMaxWeight 租户权重上限
+const fairShareMaxWeight = 100
+
+var ErrInvalidFairShareWeight = infraerrors.BadRequest("INVALID_FAIR_SHARE_WEIGHT", "fair_share_weight must be between 0 and 100")
+
+// FairShareScheduler 账号并发槽位等待队列的租户公平调度。
+//
+// 同一账号上排队的请求按租户（用户）分组，使用按权重的 Deficit Round Robin 决定下一个可以尝试获取槽位的请求：
+// 每轮每个租户获得与权重相等的配额，单个租户的大量排队请求无法长期占满账号。
+// 调度状态仅在本实例内维护，多实例部署时各实例独立调度。
+type FairShareScheduler struct {
+	mu      sync.Mutex
+	pools   map[int64]*fairSharePool
+	tenants map[int64]*fairShareTenantStats
+	now     func() time.Time
+}
+
+// fairSharePool 单个账号的等待队列
+type fairSharePool struct {
+	queues map[int64]*fairShareQueue
+	// ring 有排队请求的租户，按加入顺序轮转；cursor 指向当前服务的租户
+	ring   []*fairShareQueue
+	cursor int
+}
+
+type fairShareQueue struct {
+	tenantID int64
+	weight   int
+	deficit  int
+	waiters  []*FairShareTicket
+}
+
+type fairShareTenantStats struct {
+	weight      int
+	waiting     int
+	dispatched  int64
+	abandoned   int64
+	totalWaitMs int64
+	// 按分钟统计的吞吐：minuteStart 所在分钟的计数与上一分钟的计数
+	minuteStart    time.Time
+	minuteCount    int64
+	lastMinuteRate int64
+}
+
+// FairShareTenantStats 单个租户的公平调度统计（本实例，自进程启动起累计）
+type FairShareTenantStats struct {
+	UserID     int64 `json:"user_id"`
+	Weight     int   `json:"weight"`
+	Waiting    int   `json:"waiting"`
+	Dispatched int64 `json:"dispatched"`
+	// Abandoned 排队超时或客户端断开而未获得槽位的请求数
+	Abandoned int64 `json:"abandoned"`
+	AvgWaitMs int64 `json:"avg_wait_ms"`
+	// DispatchedLastMinute 上一完整分钟内获得槽位的请求数
+	DispatchedLastMinute int64 `json:"dispatched_last_minute"`
+}
+
+// FairShareTicket 单个排队请求；所有方法对 nil 安全（未启用公平调度时视为始终轮到）
+type FairShareTicket struct {
+	sched      *FairShareScheduler
+	poolID     int64
+	tenantID   int64
+	enqueuedAt time.Time
+	turn       chan struct{}
+	done       bool
+}
+
+// NewFairShareScheduler 创建公平调度器
+func NewFairShareScheduler() *FairShareScheduler {
+	return &FairShareScheduler{
+		pools:   make(map[int64]*fairSharePool),
+		tenants: make(map[int64]*fairShareTenantStats),
+		now:     time.Now,
+	}
+}
+
+// Enter 将请求加入账号 poolID 的等待队列；weight <= 0 按 1 处理
+func (s *FairShareScheduler) Enter(poolID, tenantID int64, weight int) *FairShareTicket {
+	if s == nil {
+		return nil
+	}
+	weight = min(max(weight, 1), fairShareMaxWeight)
+	ticket := &FairShareTicket{
+		sched:      s,
+		poolID:     poolID,
+		tenantID:   tenantID,
+		enqueuedAt: s.now(),
+		turn:       make(chan struct{}, 1),
+	}
+
+	s.mu.Lock()
+	defer s.mu.Unlock()
+
+	pool := s.pools[poolID]
+	if pool == nil {
+		pool = &fairSharePool{queues: make(map[int64]*fairShareQueue)}
+		s.pools[poolID] = pool
+	}
+	q := pool.queues[tenantID]
+	if q == nil {
+		q = &fairShareQueue{tenantID: tenantID}
+		pool.queues[tenantID] = q
+		pool.ring = append(pool.ring, q)
+		if len(pool.ring) == 1 {
+			pool.cursor = 0
+			q.deficit = weight
+		}
+	}
+	q.weight = weight
+	q.waiters = append(q.waiters, ticket)
+
+	stats := s.tenantStats(tenantID)
+	stats.weight = weight
+	stats.waiting++
+
+	s.signalHead(pool)
+	return ticket
+}
+
+// Turn 轮到该请求时收到信号，用于在退避等待期间被提前唤醒
+func (t *FairShareTicket) Turn() <-chan struct{} {
+	if t == nil {
+		return nil
+	}
+	return t.turn
+}
+
+// IsTurn 该请求是否为队列中下一个可以尝试获取槽位的请求
+func (t *FairShareTicket) IsTurn() bool {
+	if t == nil {
+		return true
+	}
+	t.sched.mu.Lock()
+	defer t.sched.mu.Unlock()
+	if t.done {
+		return false
+	}
+	pool := t.sched.pools[t.poolID]
+	return pool != nil && pool.head() == t
+}
+
+// Acquired 请求已获得槽位：扣减租户配额并出队，唤醒下一个请求
+func (t *FairShareTicket) Acquired() {
+	t.finish(true)
+}
+
+// Leave 请求放弃排队（超时、断开或出错）；已出队时为空操作
+func (t *FairShareTicket) Leave() {
+	t.finish(false)
+}
+
+func (t *FairShareTicket) finish(acquired bool) {
+	if t == nil {
+		return
+	}
+	s := t.sched
+	s.mu.Lock()
+	defer s.mu.Unlock()
+	if t.done {
+		return
+	}
+	t.done = true
+
+	pool := s.pools[t.poolID]
+	if pool != nil {
+		if q := pool.queues[t.tenantID]; q != nil {
+			for i, w := range q.waiters {
+				if w == t {
+					q.waiters = append(q.waiters[:i], q.waiters[i+1:]...)
+					break
+				}
+			}
+			if acquired {
+				q.deficit--
+			}
+			if len(q.waiters) == 0 {
+				pool.remove(q)
+			}
+		}
+		if len(pool.ring) == 0 {
+			delete(s.pools, t.poolID)
+		} else {
+			s.signalHead(pool)
+		}
+	}
+
+	now := s.now()
+	stats := s.tenantStats(t.tenantID)
+	stats.waiting--
+	if !acquired {
+		stats.abandoned++
+		return
+	}
+	stats.dispatched++
+	stats.totalWaitMs += now.Sub(t.enqueuedAt).Milliseconds()
+	minute := now.Truncate(time.Minute)
+	switch {
+	case minute.Equal(stats.minuteStart):
+	case minute.Sub(stats.minuteStart) == time.Minute:
+		stats.lastMinuteRate = stats.minuteCount
+		stats.minuteStart, stats.minuteCount = minute, 0
+	default:
+		stats.lastMinuteRate = 0
+		stats.minuteStart, stats.minuteCount = minute, 0
+	}
+	stats.minuteCount++
+}
+
+// Stats 返回各租户的调度统计，按已调度请求数降序
+func (s *FairShareScheduler) Stats() []FairShareTenantStats {
+	if s == nil {
+		return []FairShareTenantStats{}
+	}
+	s.mu.Lock()
+	defer s.mu.Unlock()
+
+	minute := s.now().Truncate(time.Minute)
+	out := make([]FairShareTenantStats, 0, len(s.tenants))
+	for tenantID, st := range s.tenants {
+		item := FairShareTenantStats{
+			UserID:     tenantID,
+			Weight:     st.weight,
+			Waiting:    st.waiting,
+			Dispatched: st.dispatched,
+			Abandoned:  st.abandoned,
+		}
+		if st.dispatched > 0 {
+			item.AvgWaitMs = st.totalWaitMs / st.dispatched
+		}
+		switch minute.Sub(st.minuteStart) {
+		case 0:
+			item.DispatchedLastMinute = st.lastMinuteRate
+		case time.Minute:
+			item.DispatchedLastMinute = st.minuteCount
+		}
+		out = append(out, item)
+	}
+	sort.Slice(out, func(i, j int) bool {
+		if out[i].Dispatched != out[j].Dispatched {
+			return out[i].Dispatched > out[j].Dispatched
+		}
+		return out[i].UserID < out[j].UserID
+	})
+	return out
+}
+
+func (s *FairShareScheduler) tenantStats(tenantID int64) *fairShareTenantStats {
+	st := s.tenants[tenantID]
+	if st == nil {
+		st = &fairShareTenantStats{}
+		s.tenants[tenantID] = st
+	}
+	return st
+}
+
+// signalHead 唤醒当前队首请求（非阻塞，信号可合并）
+func (s *FairShareScheduler) signalHead(pool *fairSharePool) {
+	if head := pool.head(); head != nil {
+		select {
+		case head.turn <- struct{}{}:
+		default:
+		}
+	}
+}
+
+// head 返回按 DRR 下一个应服务的请求：当前租户配额用尽时轮转到下一个租户并为其补充权重等额的配额
+func (p *fairSharePool) head() *FairShareTicket {
+	for len(p.ring) > 0 {
+		q := p.ring[p.cursor]
+		if q.deficit > 0 {
+			return q.waiters[0]
+		}
+		p.cursor = (p.cursor + 1) % len(p.ring)
+		next := p.ring[p.cursor]
+		next.deficit += next.weight
+	}
+	return nil
+}
+
+// remove 移除已无排队请求的租户；移除当前租户时轮转到下一个租户
+func (p *fairSharePool) remove(q *fairShareQueue) {
+	delete(p.queues, q.tenantID)
+	idx := -1
+	for i, candidate := range p.ring {
+		if candidate == q {
+			idx = i
+			break
+		}
+	}
+	if idx < 0 {
+		return
+	}
+	p.ring = append(p.ring[:idx], p.ring[idx+1:]...)
+	if len(p.ring) == 0 {
+		p.cursor = 0
+		return
+	}
+	switch {
+	case idx < p.cursor:
+		p.cursor--
+	case idx == p.cursor:
+		p.cursor %= len(p.ring)
+		next := p.ring[p.cursor]
+		next.deficit += next.weight
+	}
+}
@@ -0,0 +1,95 @@
+//go:build unit
+
+package service
+
+import (
+	"testing"
+	"time"
+
+	"github.com/stretchr/testify/require"
+)
+
+// drainFairShare 依次让队首请求获得槽位，返回获得槽位的租户顺序
+func drainFairShare(t *testing.T, tickets []*FairShareTicket) []int64 {
+	t.Helper()
+	var order []int64
+	for range tickets {
+		var head *FairShareTicket
+		for _, ticket := range tickets {
+			if ticket.IsTurn() {
+				require.Nil(t, head, "only one ticket may hold the turn")
+				head = ticket
+			}
+		}
+		require.NotNil(t, head)
+		head.Acquired()
+		order = append(order, head.tenantID)
+	}
+	return order
+}
+
+func TestFairShareScheduler_WeightedRoundRobin(t *testing.T) {
+	s := NewFairShareScheduler()
+	var tickets []*FairShareTicket
+	for i := 0; i < 6; i++ {
+		tickets = append(tickets, s.Enter(1, 100, 2))
+	}
+	tickets = append(tickets, s.Enter(1, 200, 1), s.Enter(1, 200, 1))
+
+	require.Equal(t, []int64{100, 100, 200, 100, 100, 200, 100, 100}, drainFairShare(t, tickets))
+	require.Empty(t, s.pools)
+}
+
+func TestFairShareScheduler_HeavyTenantDoesNotStarveOthers(t *testing.T) {
+	s := NewFairShareScheduler()
+	var tickets []*FairShareTicket
+	for i := 0; i < 20; i++ {
+		tickets = append(tickets, s.Enter(1, 100, 0))
+	}
+	late := s.Enter(1, 200, 0)
+
+	tickets[0].Acquired()
+	require.True(t, late.IsTurn())
+	select {
+	case <-late.Turn():
+	default:
+		t.Fatal("late tenant should be signalled when it becomes head")
+	}
+}
+
+func TestFairShareScheduler_LeaveAndStats(t *testing.T) {
+	s := NewFairShareScheduler()
+	now := time.Date(2026, 1, 1, 10, 0, 30, 0, time.UTC)
+	s.now = func() time.Time { return now }
+
+	a := s.Enter(1, 100, 1)
+	b := s.Enter(1, 200, 1)
+	other := s.Enter(2, 200, 1)
+	require.True(t, a.IsTurn())
+	require.True(t, other.IsTurn())
+
+	a.Leave()
+	a.Leave()
+	require.True(t, b.IsTurn())
+
+	now = now.Add(500 * time.Millisecond)
+	b.Acquired()
+	require.False(t, b.IsTurn())
+
+	now = now.Add(time.Minute)
+	stats := s.Stats()
+	require.Len(t, stats, 2)
+	require.Equal(t, FairShareTenantStats{UserID: 200, Weight: 1, Waiting: 1, Dispatched: 1, AvgWaitMs: 500, DispatchedLastMinute: 1}, stats[0])
+	require.Equal(t, FairShareTenantStats{UserID: 100, Weight: 1, Abandoned: 1}, stats[1])
+
+	var nilTicket *FairShareTicket
+	require.True(t, nilTicket.IsTurn())
+	nilTicket.Acquired()
+	require.Nil(t, (*FairShareScheduler)(nil).Enter(1, 1, 1))
+}
+
+func TestAPIKeyPolicy_FairShareWeight(t *testing.T) {
+	require.ErrorIs(t, (&APIKeyPolicy{FairShareWeight: 101}).Normalize(), ErrInvalidFairShareWeight)
+	require.Equal(t, 5, mergeAPIKeyPolicy(&APIKeyPolicy{FairShareWeight: 5}, &APIKeyPolicy{}).FairShareWeight)
+	require.Equal(t, 8, mergeAPIKeyPolicy(&APIKeyPolicy{FairShareWeight: 5}, &APIKeyPolicy{FairShareWeight: 8}).FairShareWeight)
+}
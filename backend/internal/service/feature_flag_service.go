@@ -0,0 +1,343 @@
+package service
+
+import (
+	"context"
+	"hash/fnv"
+	"regexp"
+	"slices"
+	"sort"
+	"strconv"
+	"strings"
+	"sync"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	infraerrors "github.com/Wei-Shaw/sub2api/internal/pkg/errors"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/logger"
+)
+
+// 内置功能开关。未在数据库中配置时使用 Default。
+const (
+	// FeatureFlagTrafficMirror 流量镜像（traffic mirror 规则命中后复制请求到目标账号）
+	FeatureFlagTrafficMirror = "traffic_mirror"
+	// FeatureFlagSemanticCache 语义响应缓存（需同时开启 semantic_cache.enabled），按 API Key 定向开启
+	FeatureFlagSemanticCache = "semantic_cache"
+)
+
+// FeatureFlagDefinition 代码内置的功能开关定义
+type FeatureFlagDefinition struct {
+	Key         string `json:"key"`
+	Description string `json:"description"`
+	Default     bool   `json:"default"`
+}
+
+// KnownFeatureFlags 内置功能开关列表，新增高风险功能时在此登记
+var KnownFeatureFlags = []FeatureFlagDefinition{
+	{Key: FeatureFlagTrafficMirror, Description: "Mirror sampled requests to shadow accounts per traffic mirror rules", Default: true},
+	{Key: FeatureFlagSemanticCache, Description: "Serve non-streaming requests from the embedding-based response cache", Default: false},
+}
+
+var (
+	ErrFeatureFlagNotFound   = infraerrors.NotFound("FEATURE_FLAG_NOT_FOUND", "feature flag not found")
+	ErrFeatureFlagInvalidKey = infraerrors.BadRequest("FEATURE_FLAG_INVALID_KEY", "feature flag key must match [a-z0-9_.-]{1,100}")
+	ErrFeatureFlagInvalid    = infraerrors.BadRequest("FEATURE_FLAG_INVALID", "rollout_percent must be within [0, 100]")
+)
+
+var featureFlagKeyPattern = regexp.MustCompile(`^[a-z0-9_.-]{1,100}$`)
+
+// FeatureFlag 数据库中的功能开关配置
+type FeatureFlag struct {
+	Key         string `json:"key"`
+	Description string `json:"description"`
+	// Enabled 总开关，关闭时对所有主体关闭
+	Enabled bool `json:"enabled"`
+	// RolloutPercent 未命中定向列表时，按主体哈希放量的百分比
+	RolloutPercent int `json:"rollout_percent"`
+	// Environments 生效的部署环境（log.env），空表示不限
+	Environments []string  `json:"environments"`
+	UserIDs      []int64   `json:"user_ids"`
+	GroupIDs     []int64   `json:"group_ids"`
+	APIKeyIDs    []int64   `json:"api_key_ids"`
+	UpdatedBy    *int64    `json:"updated_by,omitempty"`
+	CreatedAt    time.Time `json:"created_at"`
+	UpdatedAt    time.Time `json:"updated_at"`
+}
+
+// FeatureFlagSubject 开关判定的主体，零值表示未知
+type FeatureFlagSubject struct {
+	UserID   int64
+	GroupID  int64
+	APIKeyID int64
+}
+
+// FeatureFlagSubjectFromAPIKey 从 API Key 构造判定主体
+func FeatureFlagSubjectFromAPIKey(apiKey *APIKey) FeatureFlagSubject {
+	if apiKey == nil {
+		return FeatureFlagSubject{}
+	}
+	subject := FeatureFlagSubject{UserID: apiKey.UserID, APIKeyID: apiKey.ID}
+	if apiKey.GroupID != nil {
+		subject.GroupID = *apiKey.GroupID
+	}
+	return subject
+}
+
+// FeatureFlagView 管理端展示：内置定义与数据库配置合并
+type FeatureFlagView struct {
+	Key         string       `json:"key"`
+	Description string       `json:"description"`
+	Builtin     bool         `json:"builtin"`
+	Default     bool         `json:"default"`
+	Flag        *FeatureFlag `json:"flag,omitempty"`
+}
+
+// FeatureFlagRepository 功能开关存储
+type FeatureFlagRepository interface {
+	List(ctx context.Context) ([]*FeatureFlag, error)
+	Upsert(ctx context.Context, flag *FeatureFlag) error
+	Delete(ctx context.Context, key string) error
+}
+
+// FeatureFlagCache 功能开关的 Redis 缓存与跨实例刷新通知
+type FeatureFlagCache interface {
+	Get(ctx context.Context) ([]*FeatureFlag, bool)
+	Set(ctx context.Context, flags []*FeatureFlag) error
+	Invalidate(ctx context.Context) error
+	NotifyUpdate(ctx context.Context) error
+	SubscribeUpdates(ctx context.Context, handler func())
+}
+
+// FeatureFlagService 运行时功能开关
+type FeatureFlagService struct {
+	repo        FeatureFlagRepository
+	cache       FeatureFlagCache
+	environment string
+
+	mu    sync.RWMutex
+	flags map[string]*FeatureFlag
+}
+
+// NewFeatureFlagService 创建功能开关服务，启动时加载配置并订阅其他实例的变更通知
+func NewFeatureFlagService(repo FeatureFlagRepository, cache FeatureFlagCache, cfg *config.Config) *FeatureFlagService {
+	s := &FeatureFlagService{repo: repo, cache: cache}
+	if cfg != nil {
+		s.environment = strings.ToLower(strings.TrimSpace(cfg.Log.Environment))
+	}
+
+	ctx := context.Background()
+	if err := s.reloadFromDB(ctx); err != nil {
+		logger.LegacyPrintf("service.feature_flag", "[FeatureFlag] load from DB failed: %v", err)
+		if err := s.refreshLocal(ctx); err != nil {
+			logger.LegacyPrintf("service.feature_flag", "[FeatureFlag] load from cache failed: %v", err)
+		}
+	}
+	if cache != nil {
+		cache.SubscribeUpdates(ctx, func() {
+			if err := s.refreshLocal(context.Background()); err != nil {
+				logger.LegacyPrintf("service.feature_flag", "[FeatureFlag] refresh on notification failed: %v", err)
+			}
+		})
+	}
+	return s
+}
+
+// IsEnabled 判定功能对主体是否开启；服务为 nil 或开关未配置时返回内置默认值
+func (s *FeatureFlagService) IsEnabled(key string, subject FeatureFlagSubject) bool {
+	if s == nil {
+		return featureFlagDefault(key)
+	}
+	s.mu.RLock()
+	flag, ok := s.flags[key]
+	s.mu.RUnlock()
+	if !ok {
+		return featureFlagDefault(key)
+	}
+	return evaluateFeatureFlag(flag, s.environment, subject)
+}
+
+// List 列出内置与已配置的功能开关
+func (s *FeatureFlagService) List(ctx context.Context) ([]FeatureFlagView, error) {
+	flags, err := s.repo.List(ctx)
+	if err != nil {
+		return nil, err
+	}
+	byKey := make(map[string]*FeatureFlag, len(flags))
+	for _, f := range flags {
+		byKey[f.Key] = f
+	}
+	views := make([]FeatureFlagView, 0, len(KnownFeatureFlags)+len(flags))
+	for _, def := range KnownFeatureFlags {
+		views = append(views, FeatureFlagView{Key: def.Key, Description: def.Description, Builtin: true, Default: def.Default, Flag: byKey[def.Key]})
+		delete(byKey, def.Key)
+	}
+	extra := make([]string, 0, len(byKey))
+	for key := range byKey {
+		extra = append(extra, key)
+	}
+	sort.Strings(extra)
+	for _, key := range extra {
+		f := byKey[key]
+		views = append(views, FeatureFlagView{Key: key, Description: f.Description, Flag: f})
+	}
+	return views, nil
+}
+
+// Upsert 创建或更新功能开关，立即生效并通知其他实例
+func (s *FeatureFlagService) Upsert(ctx context.Context, flag *FeatureFlag) error {
+	if err := normalizeFeatureFlag(flag); err != nil {
+		return err
+	}
+	if err := s.repo.Upsert(ctx, flag); err != nil {
+		return err
+	}
+	var updatedBy int64
+	if flag.UpdatedBy != nil {
+		updatedBy = *flag.UpdatedBy
+	}
+	logger.LegacyPrintf("service.feature_flag", "AUDIT feature flag updated: key=%s enabled=%v rollout=%d by=%d",
+		flag.Key, flag.Enabled, flag.RolloutPercent, updatedBy)
+	s.invalidateAndNotify()
+	return nil
+}
+
+// Delete 删除功能开关配置，恢复为内置默认值
+func (s *FeatureFlagService) Delete(ctx context.Context, key string) error {
+	if err := s.repo.Delete(ctx, key); err != nil {
+		return err
+	}
+	logger.LegacyPrintf("service.feature_flag", "AUDIT feature flag deleted: key=%s", key)
+	s.invalidateAndNotify()
+	return nil
+}
+
+func featureFlagDefault(key string) bool {
+	for _, def := range KnownFeatureFlags {
+		if def.Key == key {
+			return def.Default
+		}
+	}
+	return false
+}
+
+// evaluateFeatureFlag 判定顺序：总开关 → 部署环境 → 定向列表 → 按主体哈希放量
+func evaluateFeatureFlag(flag *FeatureFlag, environment string, subject FeatureFlagSubject) bool {
+	if flag == nil || !flag.Enabled {
+		return false
+	}
+	if len(flag.Environments) > 0 && !slices.Contains(flag.Environments, environment) {
+		return false
+	}
+	if (subject.APIKeyID > 0 && slices.Contains(flag.APIKeyIDs, subject.APIKeyID)) ||
+		(subject.UserID > 0 && slices.Contains(flag.UserIDs, subject.UserID)) ||
+		(subject.GroupID > 0 && slices.Contains(flag.GroupIDs, subject.GroupID)) {
+		return true
+	}
+	if flag.RolloutPercent >= 100 {
+		return true
+	}
+	if flag.RolloutPercent <= 0 {
+		return false
+	}
+	// 以用户为放量单位，同一用户的所有 Key 结果一致；无用户时退化为 API Key
+	unit := subject.UserID
+	if unit <= 0 {
+		unit = subject.APIKeyID
+	}
+	if unit <= 0 {
+		return false
+	}
+	h := fnv.New32a()
+	_, _ = h.Write([]byte(flag.Key + ":" + strconv.FormatInt(unit, 10)))
+	return int(h.Sum32()%100) < flag.RolloutPercent
+}
+
+func normalizeFeatureFlag(flag *FeatureFlag) error {
+	if flag == nil {
+		return ErrFeatureFlagInvalid
+	}
+	flag.Key = strings.ToLower(strings.TrimSpace(flag.Key))
+	if !featureFlagKeyPattern.MatchString(flag.Key) {
+		return ErrFeatureFlagInvalidKey
+	}
+	if flag.RolloutPercent < 0 || flag.RolloutPercent > 100 {
+		return ErrFeatureFlagInvalid
+	}
+	flag.Description = strings.TrimSpace(flag.Description)
+	envs := make([]string, 0, len(flag.Environments))
+	for _, env := range flag.Environments {
+		if env = strings.ToLower(strings.TrimSpace(env)); env != "" && !slices.Contains(envs, env) {
+			envs = append(envs, env)
+		}
+	}
+	flag.Environments = envs
+	flag.UserIDs = normalizeFeatureFlagIDs(flag.UserIDs)
+	flag.GroupIDs = normalizeFeatureFlagIDs(flag.GroupIDs)
+	flag.APIKeyIDs = normalizeFeatureFlagIDs(flag.APIKeyIDs)
+	return nil
+}
+
+func normalizeFeatureFlagIDs(ids []int64) []int64 {
+	out := make([]int64, 0, len(ids))
+	for _, id := range ids {
+		if id > 0 {
+			out = append(out, id)
+		}
+	}
+	slices.Sort(out)
+	return slices.Compact(out)
+}
+
+func (s *FeatureFlagService) setLocal(flags []*FeatureFlag) {
+	m := make(map[string]*FeatureFlag, len(flags))
+	for _, f := range flags {
+		if f != nil {
+			m[f.Key] = f
+		}
+	}
+	s.mu.Lock()
+	s.flags = m
+	s.mu.Unlock()
+}
+
+func (s *FeatureFlagService) refreshLocal(ctx context.Context) error {
+	if s.cache != nil {
+		if flags, ok := s.cache.Get(ctx); ok {
+			s.setLocal(flags)
+			return nil
+		}
+	}
+	return s.reloadFromDB(ctx)
+}
+
+func (s *FeatureFlagService) reloadFromDB(ctx context.Context) error {
+	flags, err := s.repo.List(ctx)
+	if err != nil {
+		return err
+	}
+	if s.cache != nil {
+		if err := s.cache.Set(ctx, flags); err != nil {
+			logger.LegacyPrintf("service.feature_flag", "[FeatureFlag] set cache failed: %v", err)
+		}
+	}
+	s.setLocal(flags)
+	return nil
+}
+
+// invalidateAndNotify 写路径使用独立上下文同步缓存，避免受请求取消影响
+func (s *FeatureFlagService) invalidateAndNotify() {
+	ctx, cancel := context.WithTimeout(context.Background(), 3*time.Second)
+	defer cancel()
+	if s.cache != nil {
+		if err := s.cache.Invalidate(ctx); err != nil {
+			logger.LegacyPrintf("service.feature_flag", "[FeatureFlag] invalidate cache failed: %v", err)
+		}
+	}
+	if err := s.reloadFromDB(ctx); err != nil {
+		logger.LegacyPrintf("service.feature_flag", "[FeatureFlag] reload failed: %v", err)
+	}
+	if s.cache != nil {
+		if err := s.cache.NotifyUpdate(ctx); err != nil {
+			logger.LegacyPrintf("service.feature_flag", "[FeatureFlag] notify update failed: %v", err)
+		}
+	}
+}
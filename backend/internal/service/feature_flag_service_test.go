@@ -0,0 +1,112 @@
+//go:build unit
+
+package service
+
+import (
+	"context"
+	"testing"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/stretchr/testify/require"
+)
+
+type featureFlagRepoStub struct {
+	flags map[string]*FeatureFlag
+}
+
+func (r *featureFlagRepoStub) List(context.Context) ([]*FeatureFlag, error) {
+	out := make([]*FeatureFlag, 0, len(r.flags))
+	for _, f := range r.flags {
+		out = append(out, f)
+	}
+	return out, nil
+}
+
+func (r *featureFlagRepoStub) Upsert(_ context.Context, flag *FeatureFlag) error {
+	r.flags[flag.Key] = flag
+	return nil
+}
+
+func (r *featureFlagRepoStub) Delete(_ context.Context, key string) error {
+	if _, ok := r.flags[key]; !ok {
+		return ErrFeatureFlagNotFound
+	}
+	delete(r.flags, key)
+	return nil
+}
+
+func TestEvaluateFeatureFlag(t *testing.T) {
+	flag := &FeatureFlag{Key: "new_router", Enabled: true, RolloutPercent: 0, UserIDs: []int64{7}, GroupIDs: []int64{3}, APIKeyIDs: []int64{42}}
+
+	require.True(t, evaluateFeatureFlag(flag, "", FeatureFlagSubject{UserID: 7}))
+	require.True(t, evaluateFeatureFlag(flag, "", FeatureFlagSubject{UserID: 1, GroupID: 3}))
+	require.True(t, evaluateFeatureFlag(flag, "", FeatureFlagSubject{APIKeyID: 42}))
+	require.False(t, evaluateFeatureFlag(flag, "", FeatureFlagSubject{UserID: 1, GroupID: 1, APIKeyID: 1}))
+
+	// 总开关优先于定向列表
+	flag.Enabled = false
+	require.False(t, evaluateFeatureFlag(flag, "", FeatureFlagSubject{UserID: 7}))
+
+	flag.Enabled = true
+	flag.Environments = []string{"staging"}
+	require.False(t, evaluateFeatureFlag(flag, "production", FeatureFlagSubject{UserID: 7}))
+	require.True(t, evaluateFeatureFlag(flag, "staging", FeatureFlagSubject{UserID: 7}))
+
+	flag.Environments = nil
+	flag.RolloutPercent = 100
+	require.True(t, evaluateFeatureFlag(flag, "", FeatureFlagSubject{}))
+}
+
+func TestEvaluateFeatureFlag_RolloutIsDeterministicPerUser(t *testing.T) {
+	flag := &FeatureFlag{Key: "new_router", Enabled: true, RolloutPercent: 30}
+
+	enabled := 0
+	for userID := int64(1); userID <= 1000; userID++ {
+		got := evaluateFeatureFlag(flag, "", FeatureFlagSubject{UserID: userID, APIKeyID: 1})
+		// 同一用户换 Key 结果不变
+		require.Equal(t, got, evaluateFeatureFlag(flag, "", FeatureFlagSubject{UserID: userID, APIKeyID: 2}))
+		if got {
+			enabled++
+		}
+	}
+	require.InDelta(t, 300, enabled, 60)
+
+	require.False(t, evaluateFeatureFlag(flag, "", FeatureFlagSubject{}))
+}
+
+func TestFeatureFlagService_DefaultsAndUpsert(t *testing.T) {
+	repo := &featureFlagRepoStub{flags: map[string]*FeatureFlag{}}
+	svc := NewFeatureFlagService(repo, nil, &config.Config{Log: config.LogConfig{Environment: "Production"}})
+	subject := FeatureFlagSubject{UserID: 1}
+
+	require.True(t, svc.IsEnabled(FeatureFlagTrafficMirror, subject))
+	require.False(t, svc.IsEnabled("unknown", subject))
+	var nilSvc *FeatureFlagService
+	require.True(t, nilSvc.IsEnabled(FeatureFlagTrafficMirror, subject))
+
+	require.NoError(t, svc.Upsert(context.Background(), &FeatureFlag{Key: " Traffic_Mirror ", Enabled: false}))
+	require.False(t, svc.IsEnabled(FeatureFlagTrafficMirror, subject))
+
+	require.NoError(t, svc.Upsert(context.Background(), &FeatureFlag{Key: "beta", Enabled: true, RolloutPercent: 100, Environments: []string{" PRODUCTION "}}))
+	require.True(t, svc.IsEnabled("beta", subject))
+
+	views, err := svc.List(context.Background())
+	require.NoError(t, err)
+	require.Len(t, views, len(KnownFeatureFlags)+1)
+	require.True(t, views[0].Builtin)
+	require.NotNil(t, views[0].Flag)
+	require.Equal(t, "beta", views[len(views)-1].Key)
+
+	require.NoError(t, svc.Delete(context.Background(), FeatureFlagTrafficMirror))
+	require.True(t, svc.IsEnabled(FeatureFlagTrafficMirror, subject))
+}
+
+func TestNormalizeFeatureFlag(t *testing.T) {
+	require.ErrorIs(t, normalizeFeatureFlag(&FeatureFlag{Key: "bad key"}), ErrFeatureFlagInvalidKey)
+	require.ErrorIs(t, normalizeFeatureFlag(&FeatureFlag{Key: "ok", RolloutPercent: 101}), ErrFeatureFlagInvalid)
+
+	flag := &FeatureFlag{Key: "ok", UserIDs: []int64{3, 0, 1, 3}, Environments: []string{"Prod", "prod", ""}}
+	require.NoError(t, normalizeFeatureFlag(flag))
+	require.Equal(t, []int64{1, 3}, flag.UserIDs)
+	require.Equal(t, []string{"prod"}, flag.Environments)
+}
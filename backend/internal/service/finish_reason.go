@@ -0,0 +1,103 @@
+package service
+
+import (
+	"strings"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+)
+
+// OpenAI finish_reason 取值
+const (
+	FinishReasonStop          = "stop"
+	FinishReasonLength        = "length"
+	FinishReasonToolCalls     = "tool_calls"
+	FinishReasonContentFilter = "content_filter"
+)
+
+// 停止原因的来源协议（gateway.finish_reason_overrides 的一级键）
+const (
+	// FinishReasonSourceAnthropic Messages API 的 stop_reason（含 Claude 网页版）
+	FinishReasonSourceAnthropic = "anthropic"
+	// FinishReasonSourceGemini Gemini 的 candidates[].finishReason
+	FinishReasonSourceGemini = "gemini"
+	// FinishReasonSourceOpenAI Chat Completions 的 finish_reason（含 OpenAI 兼容上游的非标准取值）
+	FinishReasonSourceOpenAI = "openai"
+)
+
+// finishReasonTables 各来源停止原因到 OpenAI finish_reason 的映射，键为小写；未收录的取值按 stop 处理
+var finishReasonTables = map[string]map[string]string{
+	FinishReasonSourceAnthropic: {
+		"end_turn":                      FinishReasonStop,
+		"stop_sequence":                 FinishReasonStop,
+		"pause_turn":                    FinishReasonStop,
+		"max_tokens":                    FinishReasonLength,
+		"model_context_window_exceeded": FinishReasonLength,
+		"tool_use":                      FinishReasonToolCalls,
+		"refusal":                       FinishReasonContentFilter,
+	},
+	FinishReasonSourceGemini: {
+		"stop":                      FinishReasonStop,
+		"finish_reason_unspecified": FinishReasonStop,
+		"other":                     FinishReasonStop,
+		"malformed_function_call":   FinishReasonStop,
+		"max_tokens":                FinishReasonLength,
+		"safety":                    FinishReasonContentFilter,
+		"recitation":                FinishReasonContentFilter,
+		"language":                  FinishReasonContentFilter,
+		"blocklist":                 FinishReasonContentFilter,
+		"prohibited_content":        FinishReasonContentFilter,
+		"spii":                      FinishReasonContentFilter,
+		"image_safety":              FinishReasonContentFilter,
+	},
+	FinishReasonSourceOpenAI: {
+		"stop":           FinishReasonStop,
+		"eos":            FinishReasonStop,
+		"end_turn":       FinishReasonStop,
+		"stop_sequence":  FinishReasonStop,
+		"length":         FinishReasonLength,
+		"max_tokens":     FinishReasonLength,
+		"model_length":   FinishReasonLength,
+		"tool_calls":     FinishReasonToolCalls,
+		"function_call":  FinishReasonToolCalls,
+		"tool_use":       FinishReasonToolCalls,
+		"content_filter": FinishReasonContentFilter,
+		"safety":         FinishReasonContentFilter,
+		"refusal":        FinishReasonContentFilter,
+	},
+}
+
+// NormalizeFinishReason 将上游停止原因映射为 OpenAI finish_reason（stop / length / tool_calls / content_filter）。
+// overrides 为 gateway.finish_reason_overrides（来源 -> 上游停止原因 -> finish_reason，加载配置时已转为小写），
+// 优先于内置表；空值与未收录的取值按 stop 处理，保证客户端重试逻辑只会看到这四种取值。
+func NormalizeFinishReason(overrides map[string]map[string]string, source, reason string) string {
+	key := strings.ToLower(strings.TrimSpace(reason))
+	if finish, ok := overrides[source][key]; ok {
+		return finish
+	}
+	if finish, ok := finishReasonTables[source][key]; ok {
+		return finish
+	}
+	return FinishReasonStop
+}
+
+// finishReasonOverrides 返回 gateway.finish_reason_overrides；cfg 为 nil 时只使用内置表
+func finishReasonOverrides(cfg *config.Config) map[string]map[string]string {
+	if cfg == nil {
+		return nil
+	}
+	return cfg.Gateway.FinishReasonOverrides
+}
+
+// claudeStopReasonForFinish 将 finish_reason 映射回 Messages API 的 stop_reason（供转换为 Claude 格式的上游使用）
+func claudeStopReasonForFinish(finishReason string) string {
+	switch finishReason {
+	case FinishReasonLength:
+		return "max_tokens"
+	case FinishReasonToolCalls:
+		return "tool_use"
+	case FinishReasonContentFilter:
+		return "refusal"
+	default:
+		return "end_turn"
+	}
+}
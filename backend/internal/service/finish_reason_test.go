@@ -0,0 +1,115 @@
+//go:build unit
+
+package service
+
+import (
+	"testing"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/stretchr/testify/require"
+)
+
+func TestNormalizeFinishReason(t *testing.T) {
+	for _, tc := range []struct {
+		source string
+		reason string
+		want   string
+	}{
+		{FinishReasonSourceAnthropic, "end_turn", FinishReasonStop},
+		{FinishReasonSourceAnthropic, "stop_sequence", FinishReasonStop},
+		{FinishReasonSourceAnthropic, "pause_turn", FinishReasonStop},
+		{FinishReasonSourceAnthropic, "max_tokens", FinishReasonLength},
+		{FinishReasonSourceAnthropic, "model_context_window_exceeded", FinishReasonLength},
+		{FinishReasonSourceAnthropic, "tool_use", FinishReasonToolCalls},
+		{FinishReasonSourceAnthropic, "refusal", FinishReasonContentFilter},
+		{FinishReasonSourceGemini, "STOP", FinishReasonStop},
+		{FinishReasonSourceGemini, "FINISH_REASON_UNSPECIFIED", FinishReasonStop},
+		{FinishReasonSourceGemini, "OTHER", FinishReasonStop},
+		{FinishReasonSourceGemini, "MALFORMED_FUNCTION_CALL", FinishReasonStop},
+		{FinishReasonSourceGemini, "MAX_TOKENS", FinishReasonLength},
+		{FinishReasonSourceGemini, "SAFETY", FinishReasonContentFilter},
+		{FinishReasonSourceGemini, "RECITATION", FinishReasonContentFilter},
+		{FinishReasonSourceGemini, "LANGUAGE", FinishReasonContentFilter},
+		{FinishReasonSourceGemini, "BLOCKLIST", FinishReasonContentFilter},
+		{FinishReasonSourceGemini, "PROHIBITED_CONTENT", FinishReasonContentFilter},
+		{FinishReasonSourceGemini, "SPII", FinishReasonContentFilter},
+		{FinishReasonSourceGemini, "IMAGE_SAFETY", FinishReasonContentFilter},
+		{FinishReasonSourceOpenAI, "stop", FinishReasonStop},
+		{FinishReasonSourceOpenAI, "eos", FinishReasonStop},
+		{FinishReasonSourceOpenAI, "end_turn", FinishReasonStop},
+		{FinishReasonSourceOpenAI, "stop_sequence", FinishReasonStop},
+		{FinishReasonSourceOpenAI, "length", FinishReasonLength},
+		{FinishReasonSourceOpenAI, "max_tokens", FinishReasonLength},
+		{FinishReasonSourceOpenAI, "model_length", FinishReasonLength},
+		{FinishReasonSourceOpenAI, "tool_calls", FinishReasonToolCalls},
+		{FinishReasonSourceOpenAI, "function_call", FinishReasonToolCalls},
+		{FinishReasonSourceOpenAI, "tool_use", FinishReasonToolCalls},
+		{FinishReasonSourceOpenAI, "content_filter", FinishReasonContentFilter},
+		{FinishReasonSourceOpenAI, "safety", FinishReasonContentFilter},
+		{FinishReasonSourceOpenAI, "refusal", FinishReasonContentFilter},
+		// 大小写与空白不敏感
+		{FinishReasonSourceGemini, " max_tokens ", FinishReasonLength},
+		{FinishReasonSourceOpenAI, "LENGTH", FinishReasonLength},
+		// 空值与未收录的取值按 stop 处理
+		{FinishReasonSourceAnthropic, "", FinishReasonStop},
+		{FinishReasonSourceOpenAI, "something_new", FinishReasonStop},
+		{"unknown", "max_tokens", FinishReasonStop},
+	} {
+		require.Equal(t, tc.want, NormalizeFinishReason(nil, tc.source, tc.reason), "%s:%s", tc.source, tc.reason)
+	}
+
+	// 内置表只产出四种 OpenAI 取值
+	for source, table := range finishReasonTables {
+		for reason, finish := range table {
+			require.Contains(t, []string{FinishReasonStop, FinishReasonLength, FinishReasonToolCalls, FinishReasonContentFilter}, finish, "%s:%s", source, reason)
+		}
+	}
+}
+
+func TestNormalizeFinishReason_Overrides(t *testing.T) {
+	overrides := map[string]map[string]string{
+		"gemini": {"recitation": "stop"},
+		"openai": {"error": "length"},
+	}
+	require.Equal(t, FinishReasonStop, NormalizeFinishReason(overrides, FinishReasonSourceGemini, "RECITATION"))
+	require.Equal(t, FinishReasonLength, NormalizeFinishReason(overrides, FinishReasonSourceOpenAI, " Error "))
+	// 未覆盖的取值仍使用内置表
+	require.Equal(t, FinishReasonContentFilter, NormalizeFinishReason(overrides, FinishReasonSourceGemini, "SAFETY"))
+
+	require.Nil(t, finishReasonOverrides(nil))
+	cfg := &config.Config{}
+	cfg.Gateway.FinishReasonOverrides = overrides
+	require.Equal(t, overrides, finishReasonOverrides(cfg))
+}
+
+func TestFinishReasonConsumers(t *testing.T) {
+	req := &CompletionRequest{}
+	require.Equal(t, "length", req.finishReason("max_tokens"))
+	require.Equal(t, "content_filter", req.finishReason("refusal"))
+	require.Equal(t, "stop", req.finishReason("end_turn"))
+	req.finishReasonOverrides = map[string]map[string]string{"anthropic": {"refusal": "stop"}}
+	require.Equal(t, "stop", req.finishReason("refusal"))
+
+	require.Equal(t, "max_tokens", mapGeminiFinishReasonToClaudeStopReason(nil, "MAX_TOKENS"))
+	require.Equal(t, "refusal", mapGeminiFinishReasonToClaudeStopReason(nil, "SAFETY"))
+	require.Equal(t, "end_turn", mapGeminiFinishReasonToClaudeStopReason(nil, "STOP"))
+	require.Equal(t, "end_turn", mapGeminiFinishReasonToClaudeStopReason(nil, ""))
+	require.Equal(t, "end_turn", mapGeminiFinishReasonToClaudeStopReason(map[string]map[string]string{"gemini": {"safety": "stop"}}, "SAFETY"))
+
+	for _, tc := range []struct {
+		finishReason string
+		status       string
+		incomplete   string
+	}{
+		{"stop", "completed", ""},
+		{"tool_calls", "completed", ""},
+		{"length", "incomplete", "max_output_tokens"},
+		{"max_tokens", "incomplete", "max_output_tokens"},
+		{"content_filter", "incomplete", "content_filter"},
+		{"", "completed", ""},
+	} {
+		status, incomplete := chatFinishStatus(nil, tc.finishReason)
+		require.Equal(t, tc.status, status, tc.finishReason)
+		require.Equal(t, tc.incomplete, incomplete, tc.finishReason)
+	}
+}
@@ -0,0 +1,524 @@
+package service
+
+import (
+	"bufio"
+	"bytes"
+	"context"
+	"encoding/json"
+	"errors"
+	"fmt"
+	"io"
+	"net/http"
+	"strings"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/pkg/claude"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/logger"
+	"github.com/gin-gonic/gin"
+	"github.com/google/uuid"
+	"github.com/tidwall/gjson"
+)
+
+const (
+	claudeWebDefaultBaseURL   = "https://claude.ai"
+	claudeWebDefaultUserAgent = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/131.0.0.0 Safari/537.36"
+
+	// claudeWebOrgUUIDExtraKey 自动发现的组织 UUID 缓存在 extra 中，避免每次请求都查询 /api/organizations
+	claudeWebOrgUUIDExtraKey = "claude_web_org_uuid"
+
+	claudeWebCleanupTimeout = 10 * time.Second
+)
+
+// buildClaudeWebPrompt 将 Messages 请求压平为网页对话的单轮输入。
+// 网页接口每轮只接受一段文本，多轮历史以 Human/Assistant 转录的形式写入同一条 prompt。
+func buildClaudeWebPrompt(body []byte) (string, error) {
+	if tools := gjson.GetBytes(body, "tools"); tools.IsArray() && len(tools.Array()) > 0 {
+		return "", errors.New("tools are not supported by claude.ai web session accounts")
+	}
+
+	system, err := claudeWebContentText(gjson.GetBytes(body, "system"))
+	if err != nil {
+		return "", err
+	}
+
+	messages := gjson.GetBytes(body, "messages").Array()
+	if len(messages) == 0 {
+		return "", errors.New("messages: at least one message is required")
+	}
+
+	type turn struct {
+		role string
+		text string
+	}
+	turns := make([]turn, 0, len(messages))
+	for _, msg := range messages {
+		text, err := claudeWebContentText(msg.Get("content"))
+		if err != nil {
+			return "", err
+		}
+		turns = append(turns, turn{role: msg.Get("role").String(), text: text})
+	}
+
+	if system == "" && len(turns) == 1 && turns[0].role == "user" {
+		return turns[0].text, nil
+	}
+
+	var b strings.Builder
+	if system != "" {
+		b.WriteString(system)
+		b.WriteString("\n\n")
+	}
+	for _, t := range turns {
+		if t.role == "assistant" {
+			b.WriteString("Assistant: ")
+		} else {
+			b.WriteString("Human: ")
+		}
+		b.WriteString(t.text)
+		b.WriteString("\n\n")
+	}
+	return strings.TrimSpace(b.String()), nil
+}
+
+func claudeWebContentText(content gjson.Result) (string, error) {
+	if !content.Exists() || content.Type == gjson.Null {
+		return "", nil
+	}
+	if content.Type == gjson.String {
+		return content.String(), nil
+	}
+
+	var (
+		parts []string
+		err   error
+	)
+	content.ForEach(func(_, block gjson.Result) bool {
+		switch blockType := block.Get("type").String(); blockType {
+		case "text":
+			parts = append(parts, block.Get("text").String())
+		case "thinking", "redacted_thinking":
+			// 历史中的思考块对网页对话没有意义，直接丢弃
+		default:
+			err = fmt.Errorf("content block type %q is not supported by claude.ai web session accounts", blockType)
+			return false
+		}
+		return true
+	})
+	return strings.Join(parts, "\n"), err
+}
+
+// claudeWebStopReason 网页接口的停止原因映射为 Messages API 语义
+func claudeWebStopReason(raw string) string {
+	if raw == "max_tokens" {
+		return "max_tokens"
+	}
+	return "end_turn"
+}
+
+// readClaudeWebEvents 解析网页对话的 SSE 输出，兼容旧版 completion 事件与新版 messages 事件。
+// onText 返回 false 时停止读取。
+func readClaudeWebEvents(r io.Reader, maxLineSize int, onText func(text string) bool) (string, error) {
+	scanner := bufio.NewScanner(r)
+	scanner.Buffer(make([]byte, 0, 64*1024), maxLineSize)
+
+	stopReason := ""
+	for scanner.Scan() {
+		line := strings.TrimSpace(scanner.Text())
+		if !strings.HasPrefix(line, "data:") {
+			continue
+		}
+		data := strings.TrimSpace(strings.TrimPrefix(line, "data:"))
+		if data == "" || !gjson.Valid(data) {
+			continue
+		}
+
+		event := gjson.Parse(data)
+		text := ""
+		switch event.Get("type").String() {
+		case "completion":
+			text = event.Get("completion").String()
+			if reason := event.Get("stop_reason").String(); reason != "" {
+				stopReason = reason
+			}
+		case "content_block_delta":
+			if event.Get("delta.type").String() == "text_delta" {
+				text = event.Get("delta.text").String()
+			}
+		case "message_delta":
+			if reason := event.Get("delta.stop_reason").String(); reason != "" {
+				stopReason = reason
+			}
+		case "error":
+			msg := event.Get("error.message").String()
+			if msg == "" {
+				msg = "unknown error"
+			}
+			return stopReason, fmt.Errorf("claude.ai stream error: %s", msg)
+		}
+		if text != "" && !onText(text) {
+			return stopReason, nil
+		}
+	}
+	if err := scanner.Err(); err != nil {
+		return stopReason, fmt.Errorf("stream read error: %w", err)
+	}
+	return stopReason, nil
+}
+
+func writeClaudeWebError(c *gin.Context, status int, errType, message string) {
+	c.JSON(status, gin.H{
+		"type": "error",
+		"error": gin.H{
+			"type":    errType,
+			"message": message,
+		},
+	})
+}
+
+func (s *GatewayService) claudeWebBaseURL(account *Account) (string, error) {
+	raw := strings.TrimSpace(account.GetCredential("base_url"))
+	if raw == "" {
+		return claudeWebDefaultBaseURL, nil
+	}
+	validated, err := s.validateUpstreamBaseURL(raw)
+	if err != nil {
+		return "", err
+	}
+	return strings.TrimRight(validated, "/"), nil
+}
+
+func (s *GatewayService) newClaudeWebRequest(ctx context.Context, account *Account, method, baseURL, path string, body any) (*http.Request, error) {
+	var reader io.Reader
+	if body != nil {
+		payload, err := json.Marshal(body)
+		if err != nil {
+			return nil, err
+		}
+		reader = bytes.NewReader(payload)
+	}
+	req, err := http.NewRequestWithContext(ctx, method, baseURL+path, reader)
+	if err != nil {
+		return nil, err
+	}
+
+	userAgent := strings.TrimSpace(account.GetCredential("user_agent"))
+	if userAgent == "" {
+		userAgent = claudeWebDefaultUserAgent
+	}
+	req.Header.Set("User-Agent", userAgent)
+	req.Header.Set("Cookie", "sessionKey="+account.GetCredential("session_key"))
+	req.Header.Set("Origin", baseURL)
+	req.Header.Set("Referer", baseURL+"/new")
+	req.Header.Set("anthropic-client-platform", "web_claude_ai")
+	if body != nil {
+		req.Header.Set("Content-Type", "application/json")
+	}
+	return req, nil
+}
+
+// doClaudeWebRequest 发送网页接口请求；上游错误与 API 账号走同一套故障转移 / 限流处理
+func (s *GatewayService) doClaudeWebRequest(ctx context.Context, c *gin.Context, account *Account, req *http.Request, proxyURL string) (*http.Response, error) {
+	resp, err := s.httpUpstream.DoWithTLS(withAccountTLSProfile(req, account), proxyURL, account.ID, account.Concurrency, account.IsTLSFingerprintEnabled())
+	if err != nil {
+		if resp != nil && resp.Body != nil {
+			_ = resp.Body.Close()
+		}
+		safeErr := sanitizeUpstreamErrorMessage(err.Error())
+		setOpsUpstreamError(c, 0, safeErr, "")
+		appendOpsUpstreamError(c, OpsUpstreamErrorEvent{
+			Platform:    account.Platform,
+			AccountID:   account.ID,
+			AccountName: account.Name,
+			Kind:        "request_error",
+			Message:     safeErr,
+		})
+		writeUpstreamRequestError(c, UpstreamErrorFormatAnthropic, err)
+		return nil, fmt.Errorf("upstream request failed: %s", safeErr)
+	}
+	if resp.StatusCode < 400 {
+		return resp, nil
+	}
+
+	respBody, _ := io.ReadAll(io.LimitReader(resp.Body, 2<<20))
+	_ = resp.Body.Close()
+	resp.Body = io.NopCloser(bytes.NewReader(respBody))
+
+	if s.shouldFailoverUpstreamError(resp.StatusCode) {
+		logger.LegacyPrintf("service.gateway", "[ClaudeWeb] Upstream error (failover): Account=%d(%s) Status=%d Body=%s",
+			account.ID, account.Name, resp.StatusCode, truncateString(string(respBody), 1000))
+
+		s.handleFailoverSideEffects(ctx, resp, account)
+		appendOpsUpstreamError(c, OpsUpstreamErrorEvent{
+			Platform:           account.Platform,
+			AccountID:          account.ID,
+			AccountName:        account.Name,
+			UpstreamStatusCode: resp.StatusCode,
+			Kind:               "failover",
+			Message:            extractUpstreamErrorMessage(respBody),
+		})
+		return nil, &UpstreamFailoverError{StatusCode: resp.StatusCode, ResponseBody: respBody}
+	}
+
+	if _, err := s.handleErrorResponse(ctx, resp, c, account); err != nil {
+		return nil, err
+	}
+	return nil, fmt.Errorf("upstream error: %d", resp.StatusCode)
+}
+
+// resolveClaudeWebOrgUUID 优先使用凭证中的 org_uuid，其次是已缓存的自动发现结果
+func (s *GatewayService) resolveClaudeWebOrgUUID(ctx context.Context, c *gin.Context, account *Account, baseURL, proxyURL string) (string, error) {
+	if orgUUID := strings.TrimSpace(account.GetCredential("org_uuid")); orgUUID != "" {
+		return orgUUID, nil
+	}
+	if cached, ok := account.Extra[claudeWebOrgUUIDExtraKey].(string); ok && cached != "" {
+		return cached, nil
+	}
+
+	req, err := s.newClaudeWebRequest(ctx, account, http.MethodGet, baseURL, "/api/organizations", nil)
+	if err != nil {
+		return "", err
+	}
+	resp, err := s.doClaudeWebRequest(ctx, c, account, req, proxyURL)
+	if err != nil {
+		return "", err
+	}
+	defer func() { _ = resp.Body.Close() }()
+
+	var orgs []struct {
+		UUID      string  `json:"uuid"`
+		RavenType *string `json:"raven_type"`
+	}
+	if err := json.NewDecoder(io.LimitReader(resp.Body, 1<<20)).Decode(&orgs); err != nil {
+		return "", fmt.Errorf("decode organizations: %w", err)
+	}
+	if len(orgs) == 0 {
+		return "", errors.New("claude.ai session has no organizations")
+	}
+	// 与 OAuth 授权流程一致：多个组织时优先 team 组织
+	orgUUID := orgs[0].UUID
+	for _, org := range orgs {
+		if org.RavenType != nil && *org.RavenType == "team" {
+			orgUUID = org.UUID
+			break
+		}
+	}
+
+	if s.accountRepo != nil {
+		if err := s.accountRepo.UpdateExtra(ctx, account.ID, map[string]any{claudeWebOrgUUIDExtraKey: orgUUID}); err != nil {
+			logger.LegacyPrintf("service.gateway", "[ClaudeWeb] cache org uuid failed: account=%d err=%v", account.ID, err)
+		}
+	}
+	return orgUUID, nil
+}
+
+// forwardClaudeWeb 通过 claude.ai 网页会话转发请求：每个请求创建一个临时对话，结束后删除
+func (s *GatewayService) forwardClaudeWeb(ctx context.Context, c *gin.Context, account *Account, parsed *ParsedRequest, startTime time.Time) (*ForwardResult, error) {
+	prompt, err := buildClaudeWebPrompt(parsed.Body)
+	if err != nil {
+		writeClaudeWebError(c, http.StatusBadRequest, "invalid_request_error", err.Error())
+		return nil, err
+	}
+	if strings.TrimSpace(account.GetCredential("session_key")) == "" {
+		return nil, fmt.Errorf("claude web session account %d has no session_key", account.ID)
+	}
+
+	baseURL, err := s.claudeWebBaseURL(account)
+	if err != nil {
+		return nil, err
+	}
+	proxyURL := ""
+	if account.ProxyID != nil && account.Proxy != nil {
+		proxyURL = account.Proxy.URL()
+	}
+
+	orgUUID, err := s.resolveClaudeWebOrgUUID(ctx, c, account, baseURL, proxyURL)
+	if err != nil {
+		return nil, err
+	}
+
+	conversationUUID := uuid.NewString()
+	convPath := "/api/organizations/" + orgUUID + "/chat_conversations"
+	createReq, err := s.newClaudeWebRequest(ctx, account, http.MethodPost, baseURL, convPath, map[string]any{
+		"uuid": conversationUUID,
+		"name": "",
+	})
+	if err != nil {
+		return nil, err
+	}
+	createResp, err := s.doClaudeWebRequest(ctx, c, account, createReq, proxyURL)
+	if err != nil {
+		return nil, err
+	}
+	_, _ = io.Copy(io.Discard, io.LimitReader(createResp.Body, 1<<20))
+	_ = createResp.Body.Close()
+	defer s.deleteClaudeWebConversation(account, baseURL, convPath+"/"+conversationUUID, proxyURL)
+
+	originalModel := parsed.Model
+	completion := map[string]any{
+		"prompt":         prompt,
+		"timezone":       "UTC",
+		"attachments":    []any{},
+		"files":          []any{},
+		"rendering_mode": "messages",
+	}
+	if mapped := account.GetMappedModel(originalModel); mapped != "" {
+		completion["model"] = mapped
+	}
+	completionReq, err := s.newClaudeWebRequest(ctx, account, http.MethodPost, baseURL, convPath+"/"+conversationUUID+"/completion", completion)
+	if err != nil {
+		return nil, err
+	}
+	completionReq.Header.Set("Accept", "text/event-stream")
+	setOpsUpstreamRequestBody(c, parsed.Body)
+
+	resp, err := s.doClaudeWebRequest(ctx, c, account, completionReq, proxyURL)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = resp.Body.Close() }()
+
+	// 网页接口不返回 token 用量，按本地估算计费
+	inputTokens, _ := EstimateClaudeInputTokens(parsed.Body)
+	maxLineSize := defaultMaxLineSize
+	if s.cfg != nil && s.cfg.Gateway.MaxLineSize > 0 {
+		maxLineSize = s.cfg.Gateway.MaxLineSize
+	}
+
+	// 网页接口不返回用量，按本地分词器估算
+	result := &ForwardResult{
+		RequestID:      conversationUUID,
+		Model:          originalModel,
+		Stream:         parsed.Stream,
+		UsageEstimated: true,
+	}
+	var output strings.Builder
+	if parsed.Stream {
+		err = s.streamClaudeWebResponse(c, resp.Body, maxLineSize, originalModel, inputTokens, startTime, &output, result)
+	} else {
+		var stopReason string
+		stopReason, err = readClaudeWebEvents(resp.Body, maxLineSize, func(text string) bool {
+			output.WriteString(text)
+			return true
+		})
+		if err == nil {
+			c.JSON(http.StatusOK, map[string]any{
+				"id":            "msg_" + randomHex(12),
+				"type":          "message",
+				"role":          "assistant",
+				"model":         originalModel,
+				"content":       []any{map[string]any{"type": "text", "text": output.String()}},
+				"stop_reason":   claudeWebStopReason(stopReason),
+				"stop_sequence": nil,
+				"usage": map[string]any{
+					"input_tokens":  inputTokens,
+					"output_tokens": claude.EstimateTextTokens(output.String()),
+				},
+			})
+		}
+	}
+	if err != nil && !parsed.Stream {
+		writeClaudeWebError(c, http.StatusBadGateway, "api_error", "Upstream stream ended with an error")
+		return nil, err
+	}
+
+	result.Usage = ClaudeUsage{InputTokens: inputTokens, OutputTokens: claude.EstimateTextTokens(output.String())}
+	result.Duration = time.Since(startTime)
+	return result, err
+}
+
+func (s *GatewayService) streamClaudeWebResponse(c *gin.Context, body io.Reader, maxLineSize int, model string, inputTokens int, startTime time.Time, output *strings.Builder, result *ForwardResult) error {
+	c.Header("Content-Type", "text/event-stream")
+	c.Header("Cache-Control", "no-cache")
+	c.Header("Connection", "keep-alive")
+	c.Header("X-Accel-Buffering", "no")
+	c.Status(http.StatusOK)
+
+	flusher, ok := c.Writer.(http.Flusher)
+	if !ok {
+		return errors.New("streaming not supported")
+	}
+	cancelOnDisconnect := s.cfg != nil && s.cfg.Gateway.CancelUpstreamOnClientDisconnect
+
+	writeSSE(c.Writer, "message_start", map[string]any{
+		"type": "message_start",
+		"message": map[string]any{
+			"id":            "msg_" + randomHex(12),
+			"type":          "message",
+			"role":          "assistant",
+			"model":         model,
+			"content":       []any{},
+			"stop_reason":   nil,
+			"stop_sequence": nil,
+			"usage":         map[string]any{"input_tokens": inputTokens, "output_tokens": 0},
+		},
+	})
+	writeSSE(c.Writer, "content_block_start", map[string]any{
+		"type":          "content_block_start",
+		"index":         0,
+		"content_block": map[string]any{"type": "text", "text": ""},
+	})
+	flusher.Flush()
+
+	stopReason, err := readClaudeWebEvents(body, maxLineSize, func(text string) bool {
+		output.WriteString(text)
+		if result.FirstTokenMs == nil {
+			ms := int(time.Since(startTime).Milliseconds())
+			result.FirstTokenMs = &ms
+		}
+		if result.ClientDisconnect {
+			// 继续读取上游以完成计费，除非配置了断开即取消
+			return !cancelOnDisconnect
+		}
+		writeSSE(c.Writer, "content_block_delta", map[string]any{
+			"type":  "content_block_delta",
+			"index": 0,
+			"delta": map[string]any{"type": "text_delta", "text": text},
+		})
+		flusher.Flush()
+		if c.Request != nil && c.Request.Context().Err() != nil {
+			result.ClientDisconnect = true
+		}
+		return true
+	})
+	if result.ClientDisconnect {
+		return nil
+	}
+	if err != nil {
+		writeSSE(c.Writer, "error", map[string]any{
+			"type":  "error",
+			"error": map[string]any{"type": "api_error", "message": "Upstream stream ended with an error"},
+		})
+		flusher.Flush()
+		return err
+	}
+
+	writeSSE(c.Writer, "content_block_stop", map[string]any{"type": "content_block_stop", "index": 0})
+	writeSSE(c.Writer, "message_delta", map[string]any{
+		"type":  "message_delta",
+		"delta": map[string]any{"stop_reason": claudeWebStopReason(stopReason), "stop_sequence": nil},
+		"usage": map[string]any{"output_tokens": claude.EstimateTextTokens(output.String())},
+	})
+	writeSSE(c.Writer, "message_stop", map[string]any{"type": "message_stop"})
+	flusher.Flush()
+	return nil
+}
+
+// deleteClaudeWebConversation 清理临时对话，避免在用户的网页端历史中堆积；失败只记录日志
+func (s *GatewayService) deleteClaudeWebConversation(account *Account, baseURL, path, proxyURL string) {
+	ctx, cancel := context.WithTimeout(context.Background(), claudeWebCleanupTimeout)
+	defer cancel()
+
+	req, err := s.newClaudeWebRequest(ctx, account, http.MethodDelete, baseURL, path, nil)
+	if err != nil {
+		return
+	}
+	resp, err := s.httpUpstream.DoWithTLS(withAccountTLSProfile(req, account), proxyURL, account.ID, account.Concurrency, account.IsTLSFingerprintEnabled())
+	if err != nil {
+		logger.LegacyPrintf("service.gateway", "[ClaudeWeb] delete conversation failed: account=%d err=%s", account.ID, sanitizeUpstreamErrorMessage(err.Error()))
+		return
+	}
+	_ = resp.Body.Close()
+	if resp.StatusCode >= 400 {
+		logger.LegacyPrintf("service.gateway", "[ClaudeWeb] delete conversation failed: account=%d status=%d", account.ID, resp.StatusCode)
+	}
+}
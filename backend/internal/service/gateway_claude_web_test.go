@@ -0,0 +1,232 @@
+package service
+
+import (
+	"bytes"
+	"context"
+	"encoding/json"
+	"io"
+	"net/http"
+	"net/http/httptest"
+	"strings"
+	"sync"
+	"testing"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/gin-gonic/gin"
+	"github.com/stretchr/testify/require"
+	"github.com/tidwall/gjson"
+)
+
+type claudeWebUpstreamStub struct {
+	mu       sync.Mutex
+	requests []string
+	bodies   map[string][]byte
+	headers  map[string]http.Header
+	cookies  []string
+	respond  func(req *http.Request) *http.Response
+}
+
+func (u *claudeWebUpstreamStub) Do(req *http.Request, proxyURL string, accountID int64, accountConcurrency int) (*http.Response, error) {
+	return u.DoWithTLS(req, proxyURL, accountID, accountConcurrency, false)
+}
+
+func (u *claudeWebUpstreamStub) DoWithTLS(req *http.Request, _ string, _ int64, _ int, _ bool) (*http.Response, error) {
+	u.mu.Lock()
+	defer u.mu.Unlock()
+	key := req.Method + " " + req.URL.Path
+	u.requests = append(u.requests, key)
+	u.cookies = append(u.cookies, req.Header.Get("Cookie"))
+	if u.headers == nil {
+		u.headers = map[string]http.Header{}
+	}
+	u.headers[key] = req.Header.Clone()
+	if req.Body != nil {
+		body, _ := io.ReadAll(req.Body)
+		if u.bodies == nil {
+			u.bodies = map[string][]byte{}
+		}
+		u.bodies[key] = body
+	}
+	return u.respond(req), nil
+}
+
+func claudeWebTestResponse(status int, body string) *http.Response {
+	return &http.Response{
+		StatusCode: status,
+		Header:     http.Header{},
+		Body:       io.NopCloser(strings.NewReader(body)),
+	}
+}
+
+type claudeWebAccountRepoStub struct {
+	AccountRepository
+	extra    map[string]any
+	errorMsg string
+}
+
+func (r *claudeWebAccountRepoStub) SetError(_ context.Context, _ int64, errorMsg string) error {
+	r.errorMsg = errorMsg
+	return nil
+}
+
+func (r *claudeWebAccountRepoStub) UpdateExtra(_ context.Context, _ int64, updates map[string]any) error {
+	r.extra = updates
+	return nil
+}
+
+func newClaudeWebAccountForTest(credentials map[string]any) *Account {
+	return &Account{
+		ID:          301,
+		Name:        "claude-web",
+		Platform:    PlatformAnthropic,
+		Type:        AccountTypeWebSession,
+		Concurrency: 1,
+		Credentials: credentials,
+		Status:      StatusActive,
+		Schedulable: true,
+	}
+}
+
+const claudeWebTestStream = "event: message_start\n" +
+	"data: {\"type\":\"message_start\",\"message\":{\"id\":\"x\"}}\n\n" +
+	"data: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"text_delta\",\"text\":\"Hello\"}}\n\n" +
+	"data: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"text_delta\",\"text\":\" world\"}}\n\n" +
+	"data: {\"type\":\"message_delta\",\"delta\":{\"stop_reason\":\"end_turn\"}}\n\n" +
+	"data: {\"type\":\"message_stop\"}\n\n"
+
+func TestBuildClaudeWebPrompt(t *testing.T) {
+	prompt, err := buildClaudeWebPrompt([]byte(`{"messages":[{"role":"user","content":"hi"}]}`))
+	require.NoError(t, err)
+	require.Equal(t, "hi", prompt)
+
+	prompt, err = buildClaudeWebPrompt([]byte(`{
+		"system":[{"type":"text","text":"Be brief."}],
+		"messages":[
+			{"role":"user","content":[{"type":"text","text":"2+2?"}]},
+			{"role":"assistant","content":[{"type":"thinking","thinking":"..."},{"type":"text","text":"4"}]},
+			{"role":"user","content":"and 3+3?"}
+		]}`))
+	require.NoError(t, err)
+	require.Equal(t, "Be brief.\n\nHuman: 2+2?\n\nAssistant: 4\n\nHuman: and 3+3?", prompt)
+
+	_, err = buildClaudeWebPrompt([]byte(`{"tools":[{"name":"x"}],"messages":[{"role":"user","content":"hi"}]}`))
+	require.ErrorContains(t, err, "tools are not supported")
+
+	_, err = buildClaudeWebPrompt([]byte(`{"messages":[{"role":"user","content":[{"type":"image","source":{}}]}]}`))
+	require.ErrorContains(t, err, `"image"`)
+}
+
+func TestReadClaudeWebEvents_LegacyCompletion(t *testing.T) {
+	stream := "data: {\"type\":\"completion\",\"completion\":\"Hi\",\"stop_reason\":null}\n\n" +
+		"data: {\"type\":\"completion\",\"completion\":\" there\",\"stop_reason\":\"stop_sequence\"}\n\n"
+	var out strings.Builder
+	stopReason, err := readClaudeWebEvents(strings.NewReader(stream), 1<<20, func(text string) bool {
+		out.WriteString(text)
+		return true
+	})
+	require.NoError(t, err)
+	require.Equal(t, "Hi there", out.String())
+	require.Equal(t, "end_turn", claudeWebStopReason(stopReason))
+
+	_, err = readClaudeWebEvents(strings.NewReader("data: {\"type\":\"error\",\"error\":{\"message\":\"overloaded\"}}\n"), 1<<20, func(string) bool { return true })
+	require.ErrorContains(t, err, "overloaded")
+}
+
+func TestGatewayService_ForwardClaudeWeb_NonStream(t *testing.T) {
+	gin.SetMode(gin.TestMode)
+	upstream := &claudeWebUpstreamStub{respond: func(req *http.Request) *http.Response {
+		switch {
+		case req.Method == http.MethodGet && req.URL.Path == "/api/organizations":
+			return claudeWebTestResponse(http.StatusOK, `[{"uuid":"org-personal","raven_type":null},{"uuid":"org-team","raven_type":"team"}]`)
+		case strings.HasSuffix(req.URL.Path, "/completion"):
+			return claudeWebTestResponse(http.StatusOK, claudeWebTestStream)
+		case req.Method == http.MethodDelete:
+			return claudeWebTestResponse(http.StatusNoContent, "")
+		default:
+			return claudeWebTestResponse(http.StatusCreated, `{}`)
+		}
+	}}
+	repo := &claudeWebAccountRepoStub{}
+	svc := &GatewayService{httpUpstream: upstream, accountRepo: repo, cfg: &config.Config{}}
+	account := newClaudeWebAccountForTest(map[string]any{"session_key": "sk-ant-sid01-test"})
+
+	rec := httptest.NewRecorder()
+	c, _ := gin.CreateTestContext(rec)
+	c.Request = httptest.NewRequest(http.MethodPost, "/v1/messages", nil)
+	body := []byte(`{"model":"claude-sonnet-4-5","messages":[{"role":"user","content":"hi"}]}`)
+
+	result, err := svc.Forward(context.Background(), c, account, &ParsedRequest{Body: body, Model: "claude-sonnet-4-5"})
+	require.NoError(t, err)
+	require.Equal(t, "claude-sonnet-4-5", result.Model)
+	require.Positive(t, result.Usage.InputTokens)
+	require.Positive(t, result.Usage.OutputTokens)
+
+	require.Equal(t, http.StatusOK, rec.Code)
+	require.Equal(t, "Hello world", gjson.Get(rec.Body.String(), "content.0.text").String())
+	require.Equal(t, "end_turn", gjson.Get(rec.Body.String(), "stop_reason").String())
+
+	require.Equal(t, map[string]any{claudeWebOrgUUIDExtraKey: "org-team"}, repo.extra)
+	require.Len(t, upstream.requests, 4)
+	require.Equal(t, "GET /api/organizations", upstream.requests[0])
+	require.True(t, strings.HasPrefix(upstream.requests[1], "POST /api/organizations/org-team/chat_conversations"))
+	require.True(t, strings.HasPrefix(upstream.requests[3], "DELETE /api/organizations/org-team/chat_conversations/"), "temporary conversation must be cleaned up")
+	for _, cookie := range upstream.cookies {
+		require.Equal(t, "sessionKey=sk-ant-sid01-test", cookie)
+	}
+
+	var completion map[string]any
+	require.NoError(t, json.Unmarshal(upstream.bodies[upstream.requests[2]], &completion))
+	require.Equal(t, "hi", completion["prompt"])
+	require.Equal(t, "claude-sonnet-4-5", completion["model"])
+}
+
+func TestGatewayService_ForwardClaudeWeb_StreamTranslatesEvents(t *testing.T) {
+	gin.SetMode(gin.TestMode)
+	upstream := &claudeWebUpstreamStub{respond: func(req *http.Request) *http.Response {
+		if strings.HasSuffix(req.URL.Path, "/completion") {
+			return claudeWebTestResponse(http.StatusOK, claudeWebTestStream)
+		}
+		return claudeWebTestResponse(http.StatusOK, `{}`)
+	}}
+	svc := &GatewayService{httpUpstream: upstream, cfg: &config.Config{}}
+	account := newClaudeWebAccountForTest(map[string]any{"session_key": "sk", "org_uuid": "org-1"})
+
+	rec := httptest.NewRecorder()
+	c, _ := gin.CreateTestContext(rec)
+	c.Request = httptest.NewRequest(http.MethodPost, "/v1/messages", nil)
+	body := []byte(`{"model":"claude-opus-4","stream":true,"messages":[{"role":"user","content":"hi"}]}`)
+
+	result, err := svc.Forward(context.Background(), c, account, &ParsedRequest{Body: body, Model: "claude-opus-4", Stream: true})
+	require.NoError(t, err)
+	require.True(t, result.Stream)
+	require.NotNil(t, result.FirstTokenMs)
+
+	out := rec.Body.String()
+	require.Equal(t, "text/event-stream", rec.Header().Get("Content-Type"))
+	for _, event := range []string{"message_start", "content_block_start", "content_block_delta", "content_block_stop", "message_delta", "message_stop"} {
+		require.Contains(t, out, "event: "+event+"\n")
+	}
+	require.Contains(t, out, `"text":" world"`)
+	require.NotEqual(t, "GET /api/organizations", upstream.requests[0], "org_uuid credential skips discovery")
+}
+
+func TestGatewayService_ForwardClaudeWeb_ExpiredSessionFailsOver(t *testing.T) {
+	gin.SetMode(gin.TestMode)
+	upstream := &claudeWebUpstreamStub{respond: func(req *http.Request) *http.Response {
+		return claudeWebTestResponse(http.StatusForbidden, `{"error":{"type":"permission_error","message":"Invalid authorization"}}`)
+	}}
+	repo := &claudeWebAccountRepoStub{}
+	svc := &GatewayService{httpUpstream: upstream, cfg: &config.Config{}, rateLimitService: &RateLimitService{accountRepo: repo}}
+	account := newClaudeWebAccountForTest(map[string]any{"session_key": "sk", "org_uuid": "org-1"})
+
+	rec := httptest.NewRecorder()
+	c, _ := gin.CreateTestContext(rec)
+	c.Request = httptest.NewRequest(http.MethodPost, "/v1/messages", bytes.NewReader(nil))
+
+	_, err := svc.Forward(context.Background(), c, account, &ParsedRequest{Body: []byte(`{"messages":[{"role":"user","content":"hi"}]}`)})
+	var failover *UpstreamFailoverError
+	require.ErrorAs(t, err, &failover)
+	require.Equal(t, http.StatusForbidden, failover.StatusCode)
+	require.Len(t, upstream.requests, 1, "no conversation is created when the session is rejected")
+	require.Contains(t, repo.errorMsg, "Invalid authorization")
+}
@@ -61,6 +61,7 @@ type ParsedRequest struct {
 	ThinkingEnabled bool            // 是否开启 thinking（部分平台会影响最终模型名）
 	MaxTokens       int             // max_tokens 值（用于探测请求拦截）
 	SessionContext  *SessionContext // 可选：请求上下文区分因子（nil 时行为不变）
+	ConversationKey string          // 可选：服务端会话标识（携带 conversation_id 时），作为粘性会话依据
 }
 
 // ParseGatewayRequest 解析网关请求体并返回结构化结果。
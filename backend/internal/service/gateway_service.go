@@ -458,6 +458,7 @@ type ClaudeUsage struct {
 	CacheReadInputTokens     int `json:"cache_read_input_tokens"`
 	CacheCreation5mTokens    int // 5分钟缓存创建token（来自嵌套 cache_creation 对象）
 	CacheCreation1hTokens    int // 1小时缓存创建token（来自嵌套 cache_creation 对象）
+	ReasoningTokens          int // 推理 token（上游提供时直接使用，否则按 thinking 内容估算），已包含在 OutputTokens 内
 }
 
 // ForwardResult 转发结果
@@ -469,6 +470,7 @@ type ForwardResult struct {
 	Duration         time.Duration
 	FirstTokenMs     *int // 首字时间（流式请求）
 	ClientDisconnect bool // 客户端是否在流式传输过程中断开
+	UsageEstimated   bool // 上游未返回用量（网页会话），Usage 为本地分词器估算值
 
 	// 图片生成计费字段（图片生成模型使用）
 	ImageCount int    // 生成的图片数量
@@ -527,6 +529,12 @@ type GatewayService struct {
 	deferredService     *DeferredService
 	concurrencyService  *ConcurrencyService
 	claudeTokenProvider *ClaudeTokenProvider
+	latencyTracker      *AccountLatencyTracker
+	quotaTracker        *AccountQuotaBudgetTracker
+	liveUsage           *LiveUsageHub
+	accountShards       *AccountShardService
+	eventExport         *EventExportService
+	usageAnalytics      *UsageAnalyticsService
 	sessionLimitCache   SessionLimitCache // 会话数量限制缓存（仅 Anthropic OAuth/SetupToken）
 	userGroupRateCache  *gocache.Cache
 	userGroupRateSF     singleflight.Group
@@ -555,6 +563,12 @@ func NewGatewayService(
 	claudeTokenProvider *ClaudeTokenProvider,
 	sessionLimitCache SessionLimitCache,
 	digestStore *DigestSessionStore,
+	latencyTracker *AccountLatencyTracker,
+	quotaTracker *AccountQuotaBudgetTracker,
+	liveUsage *LiveUsageHub,
+	accountShards *AccountShardService,
+	eventExport *EventExportService,
+	usageAnalytics *UsageAnalyticsService,
 ) *GatewayService {
 	userGroupRateTTL := resolveUserGroupRateCacheTTL(cfg)
 	modelsListTTL := resolveModelsListCacheTTL(cfg)
@@ -578,6 +592,12 @@ func NewGatewayService(
 		httpUpstream:        httpUpstream,
 		deferredService:     deferredService,
 		claudeTokenProvider: claudeTokenProvider,
+		latencyTracker:      latencyTracker,
+		quotaTracker:        quotaTracker,
+		liveUsage:           liveUsage,
+		accountShards:       accountShards,
+		eventExport:         eventExport,
+		usageAnalytics:      usageAnalytics,
 		sessionLimitCache:   sessionLimitCache,
 		userGroupRateCache:  gocache.New(userGroupRateTTL, time.Minute),
 		modelsListCache:     gocache.New(modelsListTTL, time.Minute),
@@ -598,6 +618,11 @@ func (s *GatewayService) GenerateSessionHash(parsed *ParsedRequest) string {
 		}
 	}
 
+	// 服务端会话：同一 conversation_id 的请求历史每轮都在增长，按会话标识而非内容摘要绑定账号
+	if parsed.ConversationKey != "" {
+		return s.hashContent(parsed.ConversationKey)
+	}
+
 	// 2. 提取带 cache_control: {type: "ephemeral"} 的内容
 	cacheableContent := s.extractCacheableContent(parsed)
 	if cacheableContent != "" {
@@ -1196,7 +1221,7 @@ func (s *GatewayService) SelectAccountWithLoadAwareness(ctx context.Context, gro
 	if len(routingAccountIDs) > 0 && s.concurrencyService != nil {
 		// 1. 过滤出路由列表中可调度的账号
 		var routingCandidates []*Account
-		var filteredExcluded, filteredMissing, filteredUnsched, filteredPlatform, filteredModelScope, filteredModelMapping, filteredWindowCost int
+		var filteredExcluded, filteredMissing, filteredUnsched, filteredPlatform, filteredModelScope, filteredModelMapping, filteredWindowCost, filteredQuota int
 		var modelScopeSkippedIDs []int64 // 记录因模型限流被跳过的账号 ID
 		for _, routingAccountID := range routingAccountIDs {
 			if isExcluded(routingAccountID) {
@@ -1230,13 +1255,18 @@ func (s *GatewayService) SelectAccountWithLoadAwareness(ctx context.Context, gro
 				filteredWindowCost++
 				continue
 			}
+			// 配额预算检查（非粘性会话路径：接近耗尽的账号不参与路由）
+			if !s.isAccountSchedulableForQuota(ctx, account, false) {
+				filteredQuota++
+				continue
+			}
 			routingCandidates = append(routingCandidates, account)
 		}
 
 		if s.debugModelRoutingEnabled() {
-			logger.LegacyPrintf("service.gateway", "[ModelRoutingDebug] routed candidates: group_id=%v model=%s routed=%d candidates=%d filtered(excluded=%d missing=%d unsched=%d platform=%d model_scope=%d model_mapping=%d window_cost=%d)",
+			logger.LegacyPrintf("service.gateway", "[ModelRoutingDebug] routed candidates: group_id=%v model=%s routed=%d candidates=%d filtered(excluded=%d missing=%d unsched=%d platform=%d model_scope=%d model_mapping=%d window_cost=%d quota=%d)",
 				derefGroupID(groupID), requestedModel, len(routingAccountIDs), len(routingCandidates),
-				filteredExcluded, filteredMissing, filteredUnsched, filteredPlatform, filteredModelScope, filteredModelMapping, filteredWindowCost)
+				filteredExcluded, filteredMissing, filteredUnsched, filteredPlatform, filteredModelScope, filteredModelMapping, filteredWindowCost, filteredQuota)
 			if len(modelScopeSkippedIDs) > 0 {
 				logger.LegacyPrintf("service.gateway", "[ModelRoutingDebug] model_rate_limited accounts skipped: group_id=%v model=%s account_ids=%v",
 					derefGroupID(groupID), requestedModel, modelScopeSkippedIDs)
@@ -1253,7 +1283,8 @@ func (s *GatewayService) SelectAccountWithLoadAwareness(ctx context.Context, gro
 							s.isAccountAllowedForPlatform(stickyAccount, platform, useMixed) &&
 							(requestedModel == "" || s.isModelSupportedByAccountWithContext(ctx, stickyAccount, requestedModel)) &&
 							stickyAccount.IsSchedulableForModelWithContext(ctx, requestedModel) &&
-							s.isAccountSchedulableForWindowCost(ctx, stickyAccount, true) { // 粘性会话窗口费用检查
+							s.isAccountSchedulableForWindowCost(ctx, stickyAccount, true) && // 粘性会话窗口费用检查
+							s.isAccountSchedulableForQuota(ctx, stickyAccount, true) { // 粘性会话配额预算检查
 							result, err := s.tryAcquireAccountSlot(ctx, stickyAccountID, stickyAccount.Concurrency)
 							if err == nil && result.Acquired {
 								// 会话数量限制检查
@@ -1407,7 +1438,8 @@ func (s *GatewayService) SelectAccountWithLoadAwareness(ctx context.Context, gro
 					s.isAccountAllowedForPlatform(account, platform, useMixed) &&
 					(requestedModel == "" || s.isModelSupportedByAccountWithContext(ctx, account, requestedModel)) &&
 					account.IsSchedulableForModelWithContext(ctx, requestedModel) &&
-					s.isAccountSchedulableForWindowCost(ctx, account, true) { // 粘性会话窗口费用检查
+					s.isAccountSchedulableForWindowCost(ctx, account, true) && // 粘性会话窗口费用检查
+					s.isAccountSchedulableForQuota(ctx, account, true) { // 粘性会话配额预算检查
 					result, err := s.tryAcquireAccountSlot(ctx, accountID, account.Concurrency)
 					if err == nil && result.Acquired {
 						// 会话数量限制检查
@@ -1449,6 +1481,7 @@ func (s *GatewayService) SelectAccountWithLoadAwareness(ctx context.Context, gro
 
 	// ============ Layer 2: 负载感知选择 ============
 	candidates := make([]*Account, 0, len(accounts))
+	var quotaLowCandidates []*Account
 	for i := range accounts {
 		acc := &accounts[i]
 		if isExcluded(acc.ID) {
@@ -1473,8 +1506,19 @@ func (s *GatewayService) SelectAccountWithLoadAwareness(ctx context.Context, gro
 		if !s.isAccountSchedulableForWindowCost(ctx, acc, false) {
 			continue
 		}
+		// 配额预算检查：已耗尽的跳过，接近耗尽的仅在没有其他候选时兜底
+		switch s.quotaTracker.Level(ctx, acc) {
+		case AccountQuotaExhausted:
+			continue
+		case AccountQuotaLow:
+			quotaLowCandidates = append(quotaLowCandidates, acc)
+			continue
+		}
 		candidates = append(candidates, acc)
 	}
+	if len(candidates) == 0 {
+		candidates = quotaLowCandidates
+	}
 
 	if len(candidates) == 0 {
 		return nil, errors.New("no available accounts")
@@ -1508,13 +1552,17 @@ func (s *GatewayService) SelectAccountWithLoadAwareness(ctx context.Context, gro
 			}
 		}
 
-		// 分层过滤选择：优先级 → 负载率 → LRU
+		// 分层过滤选择：优先级 → (延迟) → 负载率 → LRU
 		for len(available) > 0 {
 			// 1. 取优先级最小的集合
 			candidates := filterByMinPriority(available)
-			// 2. 取负载率最低的集合
+			// 2. latency 策略：取延迟在最快账号容差范围内的集合
+			if cfg.SelectionStrategy == "latency" {
+				candidates = filterByLatency(candidates, s.latencyTracker)
+			}
+			// 3. 取负载率最低的集合
 			candidates = filterByMinLoadRate(candidates)
-			// 3. LRU 选择最久未用的账号
+			// 4. LRU 选择最久未用的账号
 			selected := selectByLRU(candidates, preferOAuth)
 			if selected == nil {
 				break
@@ -1736,7 +1784,16 @@ func (s *GatewayService) resolvePlatform(ctx context.Context, groupID *int64, gr
 	return PlatformAnthropic, false, nil
 }
 
+// listSchedulableAccounts 列出候选账号，并按账号分片路由与隔离状态过滤
 func (s *GatewayService) listSchedulableAccounts(ctx context.Context, groupID *int64, platform string, hasForcePlatform bool) ([]Account, bool, error) {
+	accounts, useMixed, err := s.listAllSchedulableAccounts(ctx, groupID, platform, hasForcePlatform)
+	if err != nil {
+		return accounts, useMixed, err
+	}
+	return s.accountShards.FilterAccounts(ctx, groupID, accounts), useMixed, nil
+}
+
+func (s *GatewayService) listAllSchedulableAccounts(ctx context.Context, groupID *int64, platform string, hasForcePlatform bool) ([]Account, bool, error) {
 	if s.schedulerSnapshot != nil {
 		accounts, useMixed, err := s.schedulerSnapshot.ListSchedulableAccounts(ctx, groupID, platform, hasForcePlatform)
 		if err == nil {
@@ -2093,6 +2150,9 @@ func (s *GatewayService) checkAndRegisterSession(ctx context.Context, account *A
 }
 
 func (s *GatewayService) getSchedulableAccount(ctx context.Context, accountID int64) (*Account, error) {
+	if !s.accountShards.AllowsAccount(ctx, accountID) {
+		return nil, ErrAccountShardExcluded
+	}
 	if s.schedulerSnapshot != nil {
 		return s.schedulerSnapshot.GetAccount(ctx, accountID)
 	}
@@ -3286,12 +3346,19 @@ func (s *GatewayService) Forward(ctx context.Context, c *gin.Context, account *A
 	if account != nil && account.IsAnthropicAPIKeyPassthroughEnabled() {
 		return s.forwardAnthropicAPIKeyPassthrough(ctx, c, account, parsed.Body, parsed.Model, parsed.Stream, startTime)
 	}
+	if account.IsClaudeWebSession() {
+		return s.forwardClaudeWeb(ctx, c, account, parsed, startTime)
+	}
 
 	body := parsed.Body
 	reqModel := parsed.Model
 	reqStream := parsed.Stream
 	originalModel := reqModel
 
+	// 按上游能力规范化采样参数（temperature / top_p / top_k / stop），调整结果通过响应头告知客户端
+	body, samplingAdjusted := normalizeClaudeSamplingParams(body, samplingCapsAnthropic)
+	setSamplingAdjustedHeader(c, samplingAdjusted)
+
 	isClaudeCode := isClaudeCodeRequest(ctx, c, parsed)
 	shouldMimicClaudeCode := account.IsOAuth() && !isClaudeCode
 
@@ -3380,7 +3447,7 @@ func (s *GatewayService) Forward(ctx context.Context, c *gin.Context, account *A
 		}
 
 		// 发送请求
-		resp, err = s.httpUpstream.DoWithTLS(upstreamReq, proxyURL, account.ID, account.Concurrency, account.IsTLSFingerprintEnabled())
+		resp, err = s.httpUpstream.DoWithTLS(withAccountTLSProfile(upstreamReq, account), proxyURL, account.ID, account.Concurrency, account.IsTLSFingerprintEnabled())
 		if err != nil {
 			if resp != nil && resp.Body != nil {
 				_ = resp.Body.Close()
@@ -3396,13 +3463,7 @@ func (s *GatewayService) Forward(ctx context.Context, c *gin.Context, account *A
 				Kind:               "request_error",
 				Message:            safeErr,
 			})
-			c.JSON(http.StatusBadGateway, gin.H{
-				"type": "error",
-				"error": gin.H{
-					"type":    "upstream_error",
-					"message": "Upstream request failed",
-				},
-			})
+			writeUpstreamRequestError(c, UpstreamErrorFormatAnthropic, err)
 			return nil, fmt.Errorf("upstream request failed: %s", safeErr)
 		}
 
@@ -3454,7 +3515,7 @@ func (s *GatewayService) Forward(ctx context.Context, c *gin.Context, account *A
 					filteredBody := FilterThinkingBlocksForRetry(body)
 					retryReq, buildErr := s.buildUpstreamRequest(ctx, c, account, filteredBody, token, tokenType, reqModel, reqStream, shouldMimicClaudeCode)
 					if buildErr == nil {
-						retryResp, retryErr := s.httpUpstream.DoWithTLS(retryReq, proxyURL, account.ID, account.Concurrency, account.IsTLSFingerprintEnabled())
+						retryResp, retryErr := s.httpUpstream.DoWithTLS(withAccountTLSProfile(retryReq, account), proxyURL, account.ID, account.Concurrency, account.IsTLSFingerprintEnabled())
 						if retryErr == nil {
 							if retryResp.StatusCode < 400 {
 								logger.LegacyPrintf("service.gateway", "Account %d: signature error retry succeeded (thinking downgraded)", account.ID)
@@ -3486,7 +3547,7 @@ func (s *GatewayService) Forward(ctx context.Context, c *gin.Context, account *A
 									filteredBody2 := FilterSignatureSensitiveBlocksForRetry(body)
 									retryReq2, buildErr2 := s.buildUpstreamRequest(ctx, c, account, filteredBody2, token, tokenType, reqModel, reqStream, shouldMimicClaudeCode)
 									if buildErr2 == nil {
-										retryResp2, retryErr2 := s.httpUpstream.DoWithTLS(retryReq2, proxyURL, account.ID, account.Concurrency, account.IsTLSFingerprintEnabled())
+										retryResp2, retryErr2 := s.httpUpstream.DoWithTLS(withAccountTLSProfile(retryReq2, account), proxyURL, account.ID, account.Concurrency, account.IsTLSFingerprintEnabled())
 										if retryErr2 == nil {
 											resp = retryResp2
 											break
@@ -3725,6 +3786,7 @@ func (s *GatewayService) Forward(ctx context.Context, c *gin.Context, account *A
 			return nil, err
 		}
 	}
+	clampReasoningTokens(usage)
 
 	return &ForwardResult{
 		RequestID:        resp.Header.Get("x-request-id"),
@@ -3776,7 +3838,7 @@ func (s *GatewayService) forwardAnthropicAPIKeyPassthrough(
 			return nil, err
 		}
 
-		resp, err = s.httpUpstream.DoWithTLS(upstreamReq, proxyURL, account.ID, account.Concurrency, account.IsTLSFingerprintEnabled())
+		resp, err = s.httpUpstream.DoWithTLS(withAccountTLSProfile(upstreamReq, account), proxyURL, account.ID, account.Concurrency, account.IsTLSFingerprintEnabled())
 		if err != nil {
 			if resp != nil && resp.Body != nil {
 				_ = resp.Body.Close()
@@ -3792,13 +3854,7 @@ func (s *GatewayService) forwardAnthropicAPIKeyPassthrough(
 				Kind:               "request_error",
 				Message:            safeErr,
 			})
-			c.JSON(http.StatusBadGateway, gin.H{
-				"type": "error",
-				"error": gin.H{
-					"type":    "upstream_error",
-					"message": "Upstream request failed",
-				},
-			})
+			writeUpstreamRequestError(c, UpstreamErrorFormatAnthropic, err)
 			return nil, fmt.Errorf("upstream request failed: %s", safeErr)
 		}
 
@@ -4039,6 +4095,12 @@ func (s *GatewayService) handleStreamingResponseAnthropicAPIKeyPassthrough(
 	usage := &ClaudeUsage{}
 	var firstTokenMs *int
 	clientDisconnected := false
+	cancelOnDisconnect := s.cfg != nil && s.cfg.Gateway.CancelUpstreamOnClientDisconnect
+	streamedOutputTokens := 0
+	disconnectResult := func() *streamingResult {
+		applyStreamedOutputEstimate(usage, streamedOutputTokens)
+		return &streamingResult{usage: usage, firstTokenMs: firstTokenMs, clientDisconnect: true}
+	}
 
 	scanner := bufio.NewScanner(resp.Body)
 	maxLineSize := defaultMaxLineSize
@@ -4092,6 +4154,8 @@ func (s *GatewayService) handleStreamingResponseAnthropicAPIKeyPassthrough(
 	if intervalTicker != nil {
 		intervalCh = intervalTicker.C
 	}
+	keepalive := newSSEKeepalive(s.cfg)
+	defer keepalive.Stop()
 
 	for {
 		select {
@@ -4106,12 +4170,12 @@ func (s *GatewayService) handleStreamingResponseAnthropicAPIKeyPassthrough(
 			if ev.err != nil {
 				if clientDisconnected {
 					logger.LegacyPrintf("service.gateway", "[Anthropic passthrough] Upstream read error after client disconnect: account=%d err=%v", account.ID, ev.err)
-					return &streamingResult{usage: usage, firstTokenMs: firstTokenMs, clientDisconnect: true}, nil
+					return disconnectResult(), nil
 				}
 				if errors.Is(ev.err, context.Canceled) || errors.Is(ev.err, context.DeadlineExceeded) {
 					logger.LegacyPrintf("service.gateway", "[Anthropic passthrough] 流读取被取消: account=%d request_id=%s err=%v ctx_err=%v",
 						account.ID, resp.Header.Get("x-request-id"), ev.err, ctx.Err())
-					return &streamingResult{usage: usage, firstTokenMs: firstTokenMs, clientDisconnect: true}, nil
+					return disconnectResult(), nil
 				}
 				if errors.Is(ev.err, bufio.ErrTooLong) {
 					logger.LegacyPrintf("service.gateway", "[Anthropic passthrough] SSE line too long: account=%d max_size=%d error=%v", account.ID, maxLineSize, ev.err)
@@ -4128,18 +4192,27 @@ func (s *GatewayService) handleStreamingResponseAnthropicAPIKeyPassthrough(
 					firstTokenMs = &ms
 				}
 				s.parseSSEUsagePassthrough(data, usage)
+				streamedOutputTokens += estimateClaudeStreamDeltaTokens(data)
 			}
 
 			if !clientDisconnected {
-				if _, err := io.WriteString(w, line); err != nil {
-					clientDisconnected = true
-					logger.LegacyPrintf("service.gateway", "[Anthropic passthrough] Client disconnected during streaming, continue draining upstream for usage: account=%d", account.ID)
-				} else if _, err := io.WriteString(w, "\n"); err != nil {
+				_, err := io.WriteString(w, line)
+				if err == nil {
+					_, err = io.WriteString(w, "\n")
+				}
+				if err != nil {
 					clientDisconnected = true
+					if cancelOnDisconnect {
+						logger.LegacyPrintf("service.gateway", "[Anthropic passthrough] Client disconnected during streaming, canceling upstream: account=%d", account.ID)
+						return disconnectResult(), nil
+					}
 					logger.LegacyPrintf("service.gateway", "[Anthropic passthrough] Client disconnected during streaming, continue draining upstream for usage: account=%d", account.ID)
-				} else if line == "" {
-					// 按 SSE 事件边界刷出，减少每行 flush 带来的 syscall 开销。
-					flusher.Flush()
+				} else {
+					keepalive.TouchLine(line)
+					if line == "" {
+						// 按 SSE 事件边界刷出，减少每行 flush 带来的 syscall 开销。
+						flusher.Flush()
+					}
 				}
 			}
 
@@ -4150,17 +4223,54 @@ func (s *GatewayService) handleStreamingResponseAnthropicAPIKeyPassthrough(
 			}
 			if clientDisconnected {
 				logger.LegacyPrintf("service.gateway", "[Anthropic passthrough] Upstream timeout after client disconnect: account=%d model=%s", account.ID, model)
-				return &streamingResult{usage: usage, firstTokenMs: firstTokenMs, clientDisconnect: true}, nil
+				return disconnectResult(), nil
 			}
 			logger.LegacyPrintf("service.gateway", "[Anthropic passthrough] Stream data interval timeout: account=%d model=%s interval=%s", account.ID, model, streamInterval)
 			if s.rateLimitService != nil {
 				s.rateLimitService.HandleStreamTimeout(ctx, account, model)
 			}
 			return &streamingResult{usage: usage, firstTokenMs: firstTokenMs}, fmt.Errorf("stream data interval timeout")
+
+		case <-keepalive.C():
+			if clientDisconnected || !keepalive.Due() {
+				continue
+			}
+			if _, err := io.WriteString(w, sseKeepalivePing); err != nil {
+				clientDisconnected = true
+				if cancelOnDisconnect {
+					logger.LegacyPrintf("service.gateway", "[Anthropic passthrough] Client disconnected during streaming, canceling upstream: account=%d", account.ID)
+					return disconnectResult(), nil
+				}
+				logger.LegacyPrintf("service.gateway", "[Anthropic passthrough] Client disconnected during streaming, continue draining upstream for usage: account=%d", account.ID)
+				continue
+			}
+			flusher.Flush()
+			keepalive.Touch()
 		}
 	}
 }
 
+// estimateClaudeStreamDeltaTokens 估算 content_block_delta 事件输出内容的 token 数
+func estimateClaudeStreamDeltaTokens(data string) int {
+	if !strings.Contains(data, `"content_block_delta"`) {
+		return 0
+	}
+	delta := gjson.Get(data, "delta")
+	for _, key := range []string{"text", "thinking", "partial_json"} {
+		if v := delta.Get(key); v.Exists() {
+			return claude.EstimateTextTokens(v.String())
+		}
+	}
+	return 0
+}
+
+// applyStreamedOutputEstimate 客户端断开导致未收到最终 usage 时，以已输出内容的估算值作为输出 token 数
+func applyStreamedOutputEstimate(usage *ClaudeUsage, estimated int) {
+	if usage != nil && usage.OutputTokens == 0 {
+		usage.OutputTokens = estimated
+	}
+}
+
 func extractAnthropicSSEDataLine(line string) (string, bool) {
 	if !strings.HasPrefix(line, "data:") {
 		return "", false
@@ -4845,12 +4955,8 @@ func (s *GatewayService) handleErrorResponse(ctx context.Context, resp *http.Res
 		return nil, fmt.Errorf("upstream error: %d (passthrough rule matched) message=%s", resp.StatusCode, summary)
 	}
 
-	// 根据状态码返回适当的自定义错误响应（不透传上游详细信息）
-	var errType, errMsg string
-	var statusCode int
-
-	switch resp.StatusCode {
-	case 400:
+	// 400 多为请求本身的问题，原样透传 Anthropic 错误体便于客户端修正请求
+	if resp.StatusCode == http.StatusBadRequest {
 		c.Data(http.StatusBadRequest, "application/json", body)
 		summary := upstreamMsg
 		if summary == "" {
@@ -4860,40 +4966,14 @@ func (s *GatewayService) handleErrorResponse(ctx context.Context, resp *http.Res
 			return nil, fmt.Errorf("upstream error: %d", resp.StatusCode)
 		}
 		return nil, fmt.Errorf("upstream error: %d message=%s", resp.StatusCode, summary)
-	case 401:
-		statusCode = http.StatusBadGateway
-		errType = "upstream_error"
-		errMsg = "Upstream authentication failed, please contact administrator"
-	case 403:
-		statusCode = http.StatusBadGateway
-		errType = "upstream_error"
-		errMsg = "Upstream access forbidden, please contact administrator"
-	case 429:
-		statusCode = http.StatusTooManyRequests
-		errType = "rate_limit_error"
-		errMsg = "Upstream rate limit exceeded, please retry later"
-	case 529:
-		statusCode = http.StatusServiceUnavailable
-		errType = "overloaded_error"
-		errMsg = "Upstream service overloaded, please retry later"
-	case 500, 502, 503, 504:
-		statusCode = http.StatusBadGateway
-		errType = "upstream_error"
-		errMsg = "Upstream service temporarily unavailable"
-	default:
-		statusCode = http.StatusBadGateway
-		errType = "upstream_error"
-		errMsg = "Upstream request failed"
 	}
 
-	// 返回自定义错误响应
-	c.JSON(statusCode, gin.H{
-		"type": "error",
-		"error": gin.H{
-			"type":    errType,
-			"message": errMsg,
-		},
-	})
+	// 其余错误按统一分类返回自定义错误响应（不透传上游详细信息）
+	class := ClassifyUpstreamError(resp.StatusCode, body)
+	mapped := MapUpstreamError(class, UpstreamErrorFormatAnthropic)
+	setAccountRetryAfterHeaders(ctx, c, s.accountRepo, mapped.Status, account.ID)
+	writeUpstreamError(c, UpstreamErrorFormatAnthropic, mapped)
+	s.liveUsage.publishUpstreamError(ctx, c, account, mapped.Status, class)
 
 	if upstreamMsg == "" {
 		return nil, fmt.Errorf("upstream error: %d", resp.StatusCode)
@@ -5119,11 +5199,27 @@ func (s *GatewayService) handleStreamingResponse(ctx context.Context, resp *http
 		flusher.Flush()
 	}
 
+	keepalive := newSSEKeepalive(s.cfg)
+	defer keepalive.Stop()
+
 	needModelReplace := originalModel != mappedModel
-	clientDisconnected := false // 客户端断开标志，断开后继续读取上游以获取完整usage
+	clientDisconnected := false // 客户端断开标志，未启用断开即取消上游时继续读取上游以获取完整usage
+	cancelOnDisconnect := s.cfg != nil && s.cfg.Gateway.CancelUpstreamOnClientDisconnect
+	// streamedOutputTokens 已输出内容的估算 token 数，上游未返回最终 usage 时用于计费
+	streamedOutputTokens := 0
+	disconnectResult := func() *streamingResult {
+		applyStreamedOutputEstimate(usage, streamedOutputTokens)
+		return &streamingResult{usage: usage, firstTokenMs: firstTokenMs, clientDisconnect: true}
+	}
 
 	pendingEventLines := make([]string, 0, 4)
 
+	// API Key 策略要求隐藏推理内容时，丢弃 thinking 块事件并重排后续块的 index
+	var thinkingStripper *claudeThinkingStreamStripper
+	if APIKeyPolicyFromContext(ctx).ReasoningMode == ReasoningModeStrip {
+		thinkingStripper = newClaudeThinkingStreamStripper()
+	}
+
 	processSSEEvent := func(lines []string) ([]string, string, error) {
 		if len(lines) == 0 {
 			return nil, "", nil
@@ -5175,6 +5271,18 @@ func (s *GatewayService) handleStreamingResponse(ctx context.Context, resp *http
 			eventName = eventType
 		}
 
+		// 上游 usage 不区分推理 token，按 thinking_delta 文本估算
+		if eventType == "content_block_delta" {
+			if delta, ok := event["delta"].(map[string]any); ok && delta["type"] == "thinking_delta" {
+				if text, ok := delta["thinking"].(string); ok {
+					usage.ReasoningTokens += claude.EstimateTextTokens(text)
+				}
+			}
+		}
+		if thinkingStripper != nil && !thinkingStripper.filter(eventType, event) {
+			return nil, "", nil
+		}
+
 		// 兼容 Kimi cached_tokens → cache_read_input_tokens
 		if eventType == "message_start" {
 			if msg, ok := event["message"].(map[string]any); ok {
@@ -5244,12 +5352,12 @@ func (s *GatewayService) handleStreamingResponse(ctx context.Context, resp *http
 				// 检测 context 取消（客户端断开会导致 context 取消，进而影响上游读取）
 				if errors.Is(ev.err, context.Canceled) || errors.Is(ev.err, context.DeadlineExceeded) {
 					logger.LegacyPrintf("service.gateway", "Context canceled during streaming, returning collected usage")
-					return &streamingResult{usage: usage, firstTokenMs: firstTokenMs, clientDisconnect: true}, nil
+					return disconnectResult(), nil
 				}
 				// 客户端已通过写入失败检测到断开，上游也出错了，返回已收集的 usage
 				if clientDisconnected {
 					logger.LegacyPrintf("service.gateway", "Upstream read error after client disconnect: %v, returning collected usage", ev.err)
-					return &streamingResult{usage: usage, firstTokenMs: firstTokenMs, clientDisconnect: true}, nil
+					return disconnectResult(), nil
 				}
 				// 客户端未断开，正常的错误处理
 				if errors.Is(ev.err, bufio.ErrTooLong) {
@@ -5257,6 +5365,10 @@ func (s *GatewayService) handleStreamingResponse(ctx context.Context, resp *http
 					sendErrorEvent("response_too_large")
 					return &streamingResult{usage: usage, firstTokenMs: firstTokenMs}, ev.err
 				}
+				if timeoutErr, ok := AsUpstreamTimeoutError(ev.err); ok {
+					sendErrorEvent(timeoutErr.Code())
+					return &streamingResult{usage: usage, firstTokenMs: firstTokenMs}, timeoutErr
+				}
 				sendErrorEvent("stream_read_error")
 				return &streamingResult{usage: usage, firstTokenMs: firstTokenMs}, fmt.Errorf("stream read error: %w", ev.err)
 			}
@@ -5272,7 +5384,7 @@ func (s *GatewayService) handleStreamingResponse(ctx context.Context, resp *http
 				pendingEventLines = pendingEventLines[:0]
 				if err != nil {
 					if clientDisconnected {
-						return &streamingResult{usage: usage, firstTokenMs: firstTokenMs, clientDisconnect: true}, nil
+						return disconnectResult(), nil
 					}
 					return nil, err
 				}
@@ -5281,10 +5393,16 @@ func (s *GatewayService) handleStreamingResponse(ctx context.Context, resp *http
 					if !clientDisconnected {
 						if _, werr := fmt.Fprint(w, block); werr != nil {
 							clientDisconnected = true
+							if cancelOnDisconnect {
+								// 返回后关闭上游响应体，断开上游连接停止生成
+								logger.LegacyPrintf("service.gateway", "Client disconnected during streaming, canceling upstream: account=%d", account.ID)
+								return disconnectResult(), nil
+							}
 							logger.LegacyPrintf("service.gateway", "Client disconnected during streaming, continuing to drain upstream for billing")
 							break
 						}
 						flusher.Flush()
+						keepalive.Touch()
 					}
 					if data != "" {
 						if firstTokenMs == nil && data != "[DONE]" {
@@ -5292,6 +5410,7 @@ func (s *GatewayService) handleStreamingResponse(ctx context.Context, resp *http
 							firstTokenMs = &ms
 						}
 						s.parseSSEUsage(data, usage)
+						streamedOutputTokens += estimateClaudeStreamDeltaTokens(data)
 					}
 				}
 				continue
@@ -5307,7 +5426,7 @@ func (s *GatewayService) handleStreamingResponse(ctx context.Context, resp *http
 			if clientDisconnected {
 				// 客户端已断开，上游也超时了，返回已收集的 usage
 				logger.LegacyPrintf("service.gateway", "Upstream timeout after client disconnect, returning collected usage")
-				return &streamingResult{usage: usage, firstTokenMs: firstTokenMs, clientDisconnect: true}, nil
+				return disconnectResult(), nil
 			}
 			logger.LegacyPrintf("service.gateway", "Stream data interval timeout: account=%d model=%s interval=%s", account.ID, originalModel, streamInterval)
 			// 处理流超时，可能标记账户为临时不可调度或错误状态
@@ -5316,6 +5435,22 @@ func (s *GatewayService) handleStreamingResponse(ctx context.Context, resp *http
 			}
 			sendErrorEvent("stream_timeout")
 			return &streamingResult{usage: usage, firstTokenMs: firstTokenMs}, fmt.Errorf("stream data interval timeout")
+
+		case <-keepalive.C():
+			if clientDisconnected || !keepalive.Due() {
+				continue
+			}
+			if _, err := fmt.Fprint(w, sseKeepalivePing); err != nil {
+				clientDisconnected = true
+				if cancelOnDisconnect {
+					logger.LegacyPrintf("service.gateway", "Client disconnected during streaming, canceling upstream: account=%d", account.ID)
+					return disconnectResult(), nil
+				}
+				logger.LegacyPrintf("service.gateway", "Client disconnected during streaming, continuing to drain upstream for billing")
+				continue
+			}
+			flusher.Flush()
+			keepalive.Touch()
 		}
 	}
 
@@ -5495,6 +5630,12 @@ func (s *GatewayService) handleNonStreamingResponse(ctx context.Context, resp *h
 		}
 	}
 
+	// 上游 usage 不区分推理 token，按 thinking 块文本估算
+	response.Usage.ReasoningTokens = estimateClaudeThinkingTokens(body)
+	if APIKeyPolicyFromContext(ctx).ReasoningMode == ReasoningModeStrip {
+		body = stripClaudeThinkingBlocks(body)
+	}
+
 	// 如果有模型映射，替换响应中的model字段
 	if originalModel != mappedModel {
 		body = s.replaceModelInResponseBody(body, mappedModel, originalModel)
@@ -5598,7 +5739,9 @@ type RecordUsageInput struct {
 	UserAgent         string             // 请求的 User-Agent
 	IPAddress         string             // 请求的客户端 IP 地址
 	ForceCacheBilling bool               // 强制缓存计费：将 input_tokens 转为 cache_read 计费（用于粘性会话切换）
+	PromptFirewall    string             // 提示词防火墙处理结果摘要（未命中为空）
 	APIKeyService     APIKeyQuotaUpdater // 可选：用于更新API Key配额
+	CreditHold        *CreditHold        // 可选：处理器认领的额度预占，扣费后释放
 }
 
 // APIKeyQuotaUpdater defines the interface for updating API Key quota
@@ -5608,12 +5751,20 @@ type APIKeyQuotaUpdater interface {
 
 // RecordUsage 记录使用量并扣费（或更新订阅用量）
 func (s *GatewayService) RecordUsage(ctx context.Context, input *RecordUsageInput) error {
+	// 余额与配额扣减写入后释放预占（提前返回时同样释放）
+	defer input.CreditHold.Settle(ctx)
+
 	result := input.Result
 	apiKey := input.APIKey
 	user := input.User
 	account := input.Account
 	subscription := input.Subscription
 
+	// 延迟统计（客户端中途断开的请求耗时不代表上游速度）
+	if !result.ClientDisconnect {
+		s.latencyTracker.Record(account.ID, result.FirstTokenMs, result.Duration)
+	}
+
 	// 强制缓存计费：将 input_tokens 转为 cache_read_input_tokens
 	// 用于粘性会话切换时的特殊计费处理
 	if input.ForceCacheBilling && result.Usage.InputTokens > 0 {
@@ -5681,6 +5832,9 @@ func (s *GatewayService) RecordUsage(ctx context.Context, input *RecordUsageInpu
 			CacheCreation5mTokens: result.Usage.CacheCreation5mTokens,
 			CacheCreation1hTokens: result.Usage.CacheCreation1hTokens,
 		}
+		if result.UsageEstimated {
+			tokens = s.billingService.ReconcileEstimatedUsage(tokens)
+		}
 		var err error
 		cost, err = s.billingService.CalculateCost(result.Model, tokens, multiplier)
 		if err != nil {
@@ -5707,6 +5861,7 @@ func (s *GatewayService) RecordUsage(ctx context.Context, input *RecordUsageInpu
 		mediaType = &result.MediaType
 	}
 	accountRateMultiplier := account.BillingRateMultiplier()
+	systemFingerprint := SystemFingerprint(account, result.Model)
 	usageLog := &UsageLog{
 		UserID:                user.ID,
 		APIKeyID:              apiKey.ID,
@@ -5717,6 +5872,7 @@ func (s *GatewayService) RecordUsage(ctx context.Context, input *RecordUsageInpu
 		OutputTokens:          result.Usage.OutputTokens,
 		CacheCreationTokens:   result.Usage.CacheCreationInputTokens,
 		CacheReadTokens:       result.Usage.CacheReadInputTokens,
+		ReasoningTokens:       result.Usage.ReasoningTokens,
 		CacheCreation5mTokens: result.Usage.CacheCreation5mTokens,
 		CacheCreation1hTokens: result.Usage.CacheCreation1hTokens,
 		InputCost:             cost.InputCost,
@@ -5735,6 +5891,8 @@ func (s *GatewayService) RecordUsage(ctx context.Context, input *RecordUsageInpu
 		ImageSize:             imageSize,
 		MediaType:             mediaType,
 		CacheTTLOverridden:    cacheTTLOverridden,
+		SystemFingerprint:     &systemFingerprint,
+		UsageEstimated:        result.UsageEstimated,
 		CreatedAt:             time.Now(),
 	}
 
@@ -5742,6 +5900,9 @@ func (s *GatewayService) RecordUsage(ctx context.Context, input *RecordUsageInpu
 	if input.UserAgent != "" {
 		usageLog.UserAgent = &input.UserAgent
 	}
+	if input.PromptFirewall != "" {
+		usageLog.PromptFirewall = &input.PromptFirewall
+	}
 
 	// 添加 IPAddress
 	if input.IPAddress != "" {
@@ -5760,6 +5921,11 @@ func (s *GatewayService) RecordUsage(ctx context.Context, input *RecordUsageInpu
 	if err != nil {
 		logger.LegacyPrintf("service.gateway", "Create usage log failed: %v", err)
 	}
+	s.liveUsage.publishUsageLog(usageLog, account, apiKey)
+	if inserted {
+		s.eventExport.RecordUsage(ctx, usageLog)
+		s.usageAnalytics.Record(usageLog)
+	}
 
 	if s.cfg != nil && s.cfg.RunMode == config.RunModeSimple {
 		logger.LegacyPrintf("service.gateway", "[SIMPLE MODE] Usage recorded (not billed): user=%d, tokens=%d", usageLog.UserID, usageLog.TotalTokens())
@@ -5785,8 +5951,8 @@ func (s *GatewayService) RecordUsage(ctx context.Context, input *RecordUsageInpu
 			if err := s.userRepo.DeductBalance(ctx, user.ID, cost.ActualCost); err != nil {
 				logger.LegacyPrintf("service.gateway", "Deduct balance failed: %v", err)
 			}
-			// 异步更新余额缓存
-			s.billingCacheService.QueueDeductBalance(user.ID, cost.ActualCost)
+			// 更新余额缓存（持有预占时同步更新）
+			deductBalanceCache(ctx, s.billingCacheService, input.CreditHold, user.ID, cost.ActualCost)
 		}
 	}
 
@@ -5797,6 +5963,11 @@ func (s *GatewayService) RecordUsage(ctx context.Context, input *RecordUsageInpu
 		}
 	}
 
+	// 消费硬上限计数（余额 / 订阅模式均按实际费用累计）
+	if shouldBill {
+		s.billingCacheService.RecordSpend(ctx, apiKey, cost.ActualCost)
+	}
+
 	// Schedule batch update for account last_used_at
 	s.deferredService.ScheduleLastUsedUpdate(account.ID)
 
@@ -5816,10 +5987,14 @@ type RecordUsageLongContextInput struct {
 	LongContextMultiplier float64           // 超出阈值部分的倍率（如 2.0）
 	ForceCacheBilling     bool              // 强制缓存计费：将 input_tokens 转为 cache_read 计费（用于粘性会话切换）
 	APIKeyService         *APIKeyService    // API Key 配额服务（可选）
+	CreditHold            *CreditHold       // 可选：处理器认领的额度预占，扣费后释放
 }
 
 // RecordUsageWithLongContext 记录使用量并扣费，支持长上下文双倍计费（用于 Gemini）
 func (s *GatewayService) RecordUsageWithLongContext(ctx context.Context, input *RecordUsageLongContextInput) error {
+	// 余额与配额扣减写入后释放预占（提前返回时同样释放）
+	defer input.CreditHold.Settle(ctx)
+
 	result := input.Result
 	apiKey := input.APIKey
 	user := input.User
@@ -5876,6 +6051,9 @@ func (s *GatewayService) RecordUsageWithLongContext(ctx context.Context, input *
 			CacheCreation5mTokens: result.Usage.CacheCreation5mTokens,
 			CacheCreation1hTokens: result.Usage.CacheCreation1hTokens,
 		}
+		if result.UsageEstimated {
+			tokens = s.billingService.ReconcileEstimatedUsage(tokens)
+		}
 		var err error
 		cost, err = s.billingService.CalculateCostWithLongContext(result.Model, tokens, multiplier, input.LongContextThreshold, input.LongContextMultiplier)
 		if err != nil {
@@ -5898,6 +6076,7 @@ func (s *GatewayService) RecordUsageWithLongContext(ctx context.Context, input *
 		imageSize = &result.ImageSize
 	}
 	accountRateMultiplier := account.BillingRateMultiplier()
+	systemFingerprint := SystemFingerprint(account, result.Model)
 	usageLog := &UsageLog{
 		UserID:                user.ID,
 		APIKeyID:              apiKey.ID,
@@ -5908,6 +6087,7 @@ func (s *GatewayService) RecordUsageWithLongContext(ctx context.Context, input *
 		OutputTokens:          result.Usage.OutputTokens,
 		CacheCreationTokens:   result.Usage.CacheCreationInputTokens,
 		CacheReadTokens:       result.Usage.CacheReadInputTokens,
+		ReasoningTokens:       result.Usage.ReasoningTokens,
 		CacheCreation5mTokens: result.Usage.CacheCreation5mTokens,
 		CacheCreation1hTokens: result.Usage.CacheCreation1hTokens,
 		InputCost:             cost.InputCost,
@@ -5925,6 +6105,8 @@ func (s *GatewayService) RecordUsageWithLongContext(ctx context.Context, input *
 		ImageCount:            result.ImageCount,
 		ImageSize:             imageSize,
 		CacheTTLOverridden:    cacheTTLOverridden,
+		SystemFingerprint:     &systemFingerprint,
+		UsageEstimated:        result.UsageEstimated,
 		CreatedAt:             time.Now(),
 	}
 
@@ -5950,6 +6132,11 @@ func (s *GatewayService) RecordUsageWithLongContext(ctx context.Context, input *
 	if err != nil {
 		logger.LegacyPrintf("service.gateway", "Create usage log failed: %v", err)
 	}
+	s.liveUsage.publishUsageLog(usageLog, account, apiKey)
+	if inserted {
+		s.eventExport.RecordUsage(ctx, usageLog)
+		s.usageAnalytics.Record(usageLog)
+	}
 
 	if s.cfg != nil && s.cfg.RunMode == config.RunModeSimple {
 		logger.LegacyPrintf("service.gateway", "[SIMPLE MODE] Usage recorded (not billed): user=%d, tokens=%d", usageLog.UserID, usageLog.TotalTokens())
@@ -5975,8 +6162,8 @@ func (s *GatewayService) RecordUsageWithLongContext(ctx context.Context, input *
 			if err := s.userRepo.DeductBalance(ctx, user.ID, cost.ActualCost); err != nil {
 				logger.LegacyPrintf("service.gateway", "Deduct balance failed: %v", err)
 			}
-			// 异步更新余额缓存
-			s.billingCacheService.QueueDeductBalance(user.ID, cost.ActualCost)
+			// 更新余额缓存（持有预占时同步更新）
+			deductBalanceCache(ctx, s.billingCacheService, input.CreditHold, user.ID, cost.ActualCost)
 			// API Key 独立配额扣费
 			if input.APIKeyService != nil && apiKey.Quota > 0 {
 				if err := input.APIKeyService.UpdateQuotaUsed(ctx, apiKey.ID, cost.ActualCost); err != nil {
@@ -5986,6 +6173,11 @@ func (s *GatewayService) RecordUsageWithLongContext(ctx context.Context, input *
 		}
 	}
 
+	// 消费硬上限计数（余额 / 订阅模式均按实际费用累计）
+	if shouldBill {
+		s.billingCacheService.RecordSpend(ctx, apiKey, cost.ActualCost)
+	}
+
 	// Schedule batch update for account last_used_at
 	s.deferredService.ScheduleLastUsedUpdate(account.ID)
 
@@ -6003,6 +6195,12 @@ func (s *GatewayService) ForwardCountTokens(ctx context.Context, c *gin.Context,
 	if account != nil && account.IsAnthropicAPIKeyPassthroughEnabled() {
 		return s.forwardCountTokensAnthropicAPIKeyPassthrough(ctx, c, account, parsed.Body)
 	}
+	// 网页会话没有 count_tokens 接口，直接返回本地估算
+	if account.IsClaudeWebSession() {
+		estimated, _ := EstimateClaudeInputTokens(parsed.Body)
+		c.JSON(http.StatusOK, gin.H{"input_tokens": estimated})
+		return nil
+	}
 
 	body := parsed.Body
 	reqModel := parsed.Model
@@ -6069,7 +6267,7 @@ func (s *GatewayService) ForwardCountTokens(ctx context.Context, c *gin.Context,
 	}
 
 	// 发送请求
-	resp, err := s.httpUpstream.DoWithTLS(upstreamReq, proxyURL, account.ID, account.Concurrency, account.IsTLSFingerprintEnabled())
+	resp, err := s.httpUpstream.DoWithTLS(withAccountTLSProfile(upstreamReq, account), proxyURL, account.ID, account.Concurrency, account.IsTLSFingerprintEnabled())
 	if err != nil {
 		setOpsUpstreamError(c, 0, sanitizeUpstreamErrorMessage(err.Error()), "")
 		s.countTokensError(c, http.StatusBadGateway, "upstream_error", "Request failed")
@@ -6097,7 +6295,7 @@ func (s *GatewayService) ForwardCountTokens(ctx context.Context, c *gin.Context,
 		filteredBody := FilterThinkingBlocksForRetry(body)
 		retryReq, buildErr := s.buildCountTokensRequest(ctx, c, account, filteredBody, token, tokenType, reqModel, shouldMimicClaudeCode)
 		if buildErr == nil {
-			retryResp, retryErr := s.httpUpstream.DoWithTLS(retryReq, proxyURL, account.ID, account.Concurrency, account.IsTLSFingerprintEnabled())
+			retryResp, retryErr := s.httpUpstream.DoWithTLS(withAccountTLSProfile(retryReq, account), proxyURL, account.ID, account.Concurrency, account.IsTLSFingerprintEnabled())
 			if retryErr == nil {
 				resp = retryResp
 				respBody, err = readUpstreamResponseBodyLimited(resp.Body, maxReadBytes)
@@ -6186,7 +6384,7 @@ func (s *GatewayService) forwardCountTokensAnthropicAPIKeyPassthrough(ctx contex
 		proxyURL = account.Proxy.URL()
 	}
 
-	resp, err := s.httpUpstream.DoWithTLS(upstreamReq, proxyURL, account.ID, account.Concurrency, account.IsTLSFingerprintEnabled())
+	resp, err := s.httpUpstream.DoWithTLS(withAccountTLSProfile(upstreamReq, account), proxyURL, account.ID, account.Concurrency, account.IsTLSFingerprintEnabled())
 	if err != nil {
 		setOpsUpstreamError(c, 0, sanitizeUpstreamErrorMessage(err.Error()), "")
 		appendOpsUpstreamError(c, OpsUpstreamErrorEvent{
@@ -50,3 +50,113 @@ func TestGatewayService_StreamingReusesScannerBufferAndStillParsesUsage(t *testi
 	require.Equal(t, 3, result.usage.InputTokens)
 	require.Equal(t, 7, result.usage.OutputTokens)
 }
+
+func TestGatewayService_StreamingSendsKeepaliveDuringUpstreamSilence(t *testing.T) {
+	gin.SetMode(gin.TestMode)
+	cfg := &config.Config{
+		Gateway: config.GatewayConfig{
+			StreamKeepaliveInterval: 1,
+			MaxLineSize:             defaultMaxLineSize,
+		},
+	}
+
+	svc := &GatewayService{
+		cfg:              cfg,
+		rateLimitService: &RateLimitService{},
+	}
+
+	rec := httptest.NewRecorder()
+	c, _ := gin.CreateTestContext(rec)
+	c.Request = httptest.NewRequest(http.MethodPost, "/v1/messages", nil)
+
+	pr, pw := io.Pipe()
+	resp := &http.Response{StatusCode: http.StatusOK, Header: http.Header{}, Body: pr}
+
+	go func() {
+		defer func() { _ = pw.Close() }()
+		_, _ = pw.Write([]byte("event: message_start\n"))
+		_, _ = pw.Write([]byte("data: {\"type\":\"message_start\",\"message\":{\"usage\":{\"input_tokens\":3}}}\n\n"))
+		// 模拟思考阶段上游长时间无输出
+		time.Sleep(1500 * time.Millisecond)
+		_, _ = pw.Write([]byte("data: {\"type\":\"message_delta\",\"usage\":{\"output_tokens\":7}}\n\n"))
+	}()
+
+	result, err := svc.handleStreamingResponse(context.Background(), resp, c, &Account{ID: 1}, time.Now(), "model", "model", false)
+	_ = pr.Close()
+	require.NoError(t, err)
+	require.Equal(t, 7, result.usage.OutputTokens)
+	require.Equal(t, "no", rec.Header().Get("X-Accel-Buffering"))
+	require.Equal(t, "no-cache", rec.Header().Get("Cache-Control"))
+
+	body := rec.Body.String()
+	require.Contains(t, body, "}\n\n"+sseKeepalivePing+"event: message_delta\n")
+}
+
+func TestSSEKeepalive_OnlyAtEventBoundary(t *testing.T) {
+	k := &sseKeepalive{interval: 10 * time.Millisecond, ticker: time.NewTicker(time.Hour)}
+	defer k.Stop()
+	require.True(t, k.Due())
+
+	k.TouchLine("event: response.output_text.delta")
+	k.lastWrite = time.Now().Add(-time.Second)
+	require.False(t, k.Due(), "must not split an event")
+
+	k.TouchLine("")
+	require.False(t, k.Due())
+	k.lastWrite = time.Now().Add(-time.Second)
+	require.True(t, k.Due())
+
+	disabled := newSSEKeepalive(&config.Config{})
+	require.Nil(t, disabled.C())
+	require.False(t, disabled.Due())
+	disabled.Stop()
+}
+
+func TestGatewayService_StreamingClientDisconnectCancelsUpstream(t *testing.T) {
+	gin.SetMode(gin.TestMode)
+	cfg := &config.Config{
+		Gateway: config.GatewayConfig{
+			CancelUpstreamOnClientDisconnect: true,
+			MaxLineSize:                      defaultMaxLineSize,
+		},
+	}
+
+	svc := &GatewayService{
+		cfg:              cfg,
+		rateLimitService: &RateLimitService{},
+	}
+
+	rec := httptest.NewRecorder()
+	c, _ := gin.CreateTestContext(rec)
+	c.Request = httptest.NewRequest(http.MethodPost, "/v1/messages", nil)
+	c.Writer = &failingGinWriter{ResponseWriter: c.Writer, failAfter: 2}
+
+	pr, pw := io.Pipe()
+	resp := &http.Response{StatusCode: http.StatusOK, Header: http.Header{}, Body: pr}
+
+	upstreamDone := make(chan struct{})
+	go func() {
+		defer close(upstreamDone)
+		defer func() { _ = pw.Close() }()
+		_, _ = pw.Write([]byte("data: {\"type\":\"message_start\",\"message\":{\"usage\":{\"input_tokens\":3}}}\n\n"))
+		_, _ = pw.Write([]byte("data: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"text_delta\",\"text\":\"hello world\"}}\n\n"))
+		_, _ = pw.Write([]byte("data: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"text_delta\",\"text\":\"never delivered\"}}\n\n"))
+		// 上游仍在生成：断开即取消时不应等待这里
+		_, _ = pw.Write([]byte("data: {\"type\":\"message_delta\",\"usage\":{\"output_tokens\":100}}\n\n"))
+	}()
+
+	result, err := svc.handleStreamingResponse(context.Background(), resp, c, &Account{ID: 1}, time.Now(), "model", "model", false)
+	require.NoError(t, err)
+	require.True(t, result.clientDisconnect)
+	require.Equal(t, 3, result.usage.InputTokens)
+	require.Positive(t, result.usage.OutputTokens)
+	require.Less(t, result.usage.OutputTokens, 100)
+
+	// 关闭上游响应体（Forward 中由 defer 完成）后上游写入方立即结束
+	_ = pr.Close()
+	select {
+	case <-upstreamDone:
+	case <-time.After(time.Second):
+		t.Fatal("upstream writer should be released after body close")
+	}
+}
@@ -52,6 +52,7 @@ type GeminiMessagesCompatService struct {
 	rateLimitService          *RateLimitService
 	httpUpstream              HTTPUpstream
 	antigravityGatewayService *AntigravityGatewayService
+	accountShards             *AccountShardService
 	cfg                       *config.Config
 }
 
@@ -65,6 +66,7 @@ func NewGeminiMessagesCompatService(
 	httpUpstream HTTPUpstream,
 	antigravityGatewayService *AntigravityGatewayService,
 	cfg *config.Config,
+	accountShards *AccountShardService,
 ) *GeminiMessagesCompatService {
 	return &GeminiMessagesCompatService{
 		accountRepo:               accountRepo,
@@ -75,6 +77,7 @@ func NewGeminiMessagesCompatService(
 		rateLimitService:          rateLimitService,
 		httpUpstream:              httpUpstream,
 		antigravityGatewayService: antigravityGatewayService,
+		accountShards:             accountShards,
 		cfg:                       cfg,
 	}
 }
@@ -378,13 +381,25 @@ func (s *GeminiMessagesCompatService) GetAntigravityGatewayService() *Antigravit
 }
 
 func (s *GeminiMessagesCompatService) getSchedulableAccount(ctx context.Context, accountID int64) (*Account, error) {
+	if !s.accountShards.AllowsAccount(ctx, accountID) {
+		return nil, ErrAccountShardExcluded
+	}
 	if s.schedulerSnapshot != nil {
 		return s.schedulerSnapshot.GetAccount(ctx, accountID)
 	}
 	return s.accountRepo.GetByID(ctx, accountID)
 }
 
+// listSchedulableAccountsOnce 列出候选账号，并按账号分片路由与隔离状态过滤
 func (s *GeminiMessagesCompatService) listSchedulableAccountsOnce(ctx context.Context, groupID *int64, platform string, hasForcePlatform bool) ([]Account, error) {
+	accounts, err := s.listAllSchedulableAccounts(ctx, groupID, platform, hasForcePlatform)
+	if err != nil {
+		return nil, err
+	}
+	return s.accountShards.FilterAccounts(ctx, groupID, accounts), nil
+}
+
+func (s *GeminiMessagesCompatService) listAllSchedulableAccounts(ctx context.Context, groupID *int64, platform string, hasForcePlatform bool) ([]Account, error) {
 	if s.schedulerSnapshot != nil {
 		accounts, _, err := s.schedulerSnapshot.ListSchedulableAccounts(ctx, groupID, platform, hasForcePlatform)
 		return accounts, err
@@ -534,10 +549,16 @@ func (s *GeminiMessagesCompatService) Forward(ctx context.Context, c *gin.Contex
 		mappedModel = account.GetMappedModel(req.Model)
 	}
 
+	body, samplingAdjusted := normalizeClaudeSamplingParams(body, samplingCapsGemini)
+	setSamplingAdjustedHeader(c, samplingAdjusted)
+
 	geminiReq, err := convertClaudeMessagesToGeminiGenerateContent(body)
 	if err != nil {
 		return nil, s.writeClaudeError(c, http.StatusBadRequest, "invalid_request_error", err.Error())
 	}
+	if account.IsGeminiWebSession() {
+		return s.forwardGeminiWeb(ctx, c, account, originalModel, geminiReq, req.Stream, false, startTime)
+	}
 	geminiReq = ensureGeminiFunctionCallThoughtSignatures(geminiReq)
 	originalClaudeBody := body
 
@@ -971,7 +992,7 @@ func (s *GeminiMessagesCompatService) Forward(ctx context.Context, c *gin.Contex
 				return nil, s.writeClaudeError(c, http.StatusBadGateway, "upstream_error", "Failed to read upstream stream")
 			}
 			collectedBytes, _ := json.Marshal(collected)
-			claudeResp, usageObj2 := convertGeminiToClaudeMessage(collected, originalModel, collectedBytes)
+			claudeResp, usageObj2 := convertGeminiToClaudeMessage(collected, originalModel, collectedBytes, finishReasonOverrides(s.cfg))
 			c.JSON(http.StatusOK, claudeResp)
 			usage = usageObj2
 			if usageObj != nil && (usageObj.InputTokens > 0 || usageObj.OutputTokens > 0) {
@@ -1037,6 +1058,15 @@ func (s *GeminiMessagesCompatService) ForwardNative(ctx context.Context, c *gin.
 		return nil, s.writeGoogleError(c, http.StatusNotFound, "Unsupported action: "+action)
 	}
 
+	if account.IsGeminiWebSession() {
+		if action == "countTokens" {
+			// 网页端没有计数接口，直接返回本地估算
+			c.JSON(http.StatusOK, map[string]any{"totalTokens": estimateGeminiCountTokens(body)})
+			return &ForwardResult{Model: originalModel, Duration: time.Since(startTime)}, nil
+		}
+		return s.forwardGeminiWeb(ctx, c, account, originalModel, body, stream, true, startTime)
+	}
+
 	// Some Gemini upstreams validate tool call parts strictly; ensure any `functionCall` part includes a
 	// `thoughtSignature` to avoid frequent INVALID_ARGUMENT 400s.
 	body = ensureGeminiFunctionCallThoughtSignatures(body)
@@ -1834,7 +1864,7 @@ func (s *GeminiMessagesCompatService) handleNonStreamingResponse(c *gin.Context,
 		return nil, s.writeClaudeError(c, http.StatusBadGateway, "upstream_error", "Failed to parse upstream response")
 	}
 
-	claudeResp, usage := convertGeminiToClaudeMessage(geminiResp, originalModel, unwrappedBody)
+	claudeResp, usage := convertGeminiToClaudeMessage(geminiResp, originalModel, unwrappedBody, finishReasonOverrides(s.cfg))
 	c.JSON(http.StatusOK, claudeResp)
 
 	return usage, nil
@@ -2066,7 +2096,7 @@ func (s *GeminiMessagesCompatService) handleStreamingResponse(c *gin.Context, re
 		})
 	}
 
-	stopReason := mapGeminiFinishReasonToClaudeStopReason(finishReason)
+	stopReason := mapGeminiFinishReasonToClaudeStopReason(finishReasonOverrides(s.cfg), finishReason)
 	if sawToolUse {
 		stopReason = "tool_use"
 	}
@@ -2578,7 +2608,7 @@ func unwrapGeminiResponse(raw []byte) ([]byte, error) {
 	return raw, nil
 }
 
-func convertGeminiToClaudeMessage(geminiResp map[string]any, originalModel string, rawData []byte) (map[string]any, *ClaudeUsage) {
+func convertGeminiToClaudeMessage(geminiResp map[string]any, originalModel string, rawData []byte, overrides map[string]map[string]string) (map[string]any, *ClaudeUsage) {
 	usage := extractGeminiUsage(rawData)
 	if usage == nil {
 		usage = &ClaudeUsage{}
@@ -2621,7 +2651,7 @@ func convertGeminiToClaudeMessage(geminiResp map[string]any, originalModel strin
 		}
 	}
 
-	stopReason := mapGeminiFinishReasonToClaudeStopReason(extractGeminiFinishReason(geminiResp))
+	stopReason := mapGeminiFinishReasonToClaudeStopReason(overrides, extractGeminiFinishReason(geminiResp))
 	if sawToolUse {
 		stopReason = "tool_use"
 	}
@@ -2658,6 +2688,7 @@ func extractGeminiUsage(data []byte) *ClaudeUsage {
 		InputTokens:          prompt - cached,
 		OutputTokens:         cand + thoughts,
 		CacheReadInputTokens: cached,
+		ReasoningTokens:      thoughts,
 	}
 }
 
@@ -2890,15 +2921,8 @@ func computeGeminiTextDelta(seen, incoming string) (delta, newSeen string) {
 	return incoming, seen + incoming
 }
 
-func mapGeminiFinishReasonToClaudeStopReason(finishReason string) string {
-	switch strings.ToUpper(strings.TrimSpace(finishReason)) {
-	case "MAX_TOKENS":
-		return "max_tokens"
-	case "STOP":
-		return "end_turn"
-	default:
-		return "end_turn"
-	}
+func mapGeminiFinishReasonToClaudeStopReason(overrides map[string]map[string]string, finishReason string) string {
+	return claudeStopReasonForFinish(NormalizeFinishReason(overrides, FinishReasonSourceGemini, finishReason))
 }
 
 func convertClaudeMessagesToGeminiGenerateContent(body []byte) ([]byte, error) {
@@ -3234,9 +3258,18 @@ func convertClaudeGenerationConfig(req map[string]any) map[string]any {
 	if topP, ok := req["top_p"].(float64); ok {
 		out["topP"] = topP
 	}
+	if topK, ok := asInt(req["top_k"]); ok && topK > 0 {
+		out["topK"] = topK
+	}
+	if seed, ok := asInt(req["seed"]); ok {
+		out["seed"] = seed
+	}
 	if stopSeq, ok := req["stop_sequences"].([]any); ok && len(stopSeq) > 0 {
 		out["stopSequences"] = stopSeq
 	}
+	if thinkingConfig := convertClaudeThinkingToGeminiConfig(req["thinking"]); thinkingConfig != nil {
+		out["thinkingConfig"] = thinkingConfig
+	}
 	if len(out) == 0 {
 		return nil
 	}
@@ -0,0 +1,815 @@
+package service
+
+import (
+	"bufio"
+	"bytes"
+	"context"
+	"encoding/base64"
+	"encoding/json"
+	"errors"
+	"fmt"
+	"io"
+	mathrand "math/rand"
+	"mime/multipart"
+	"net/http"
+	"net/url"
+	"regexp"
+	"strconv"
+	"strings"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/pkg/claude"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/googleapi"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/logger"
+	"github.com/gin-gonic/gin"
+	"github.com/tidwall/gjson"
+)
+
+const (
+	geminiWebBaseURL          = "https://gemini.google.com"
+	geminiWebGeneratePath     = "/_/BardChatUi/data/assistant.lamda.BardFrontendService/StreamGenerate"
+	geminiWebBatchExecutePath = "/_/BardChatUi/data/batchexecute"
+	geminiWebUploadURL        = "https://content-push.googleapis.com/upload"
+	geminiWebUploadPushID     = "feeds/mcudyrk2a4khkz"
+	geminiWebDefaultUserAgent = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/131.0.0.0 Safari/537.36"
+
+	// geminiWebModelHeader 网页端通过该扩展头选择模型；值来自 model_mapping 映射后的网页模型 ID，未映射时使用网页默认模型
+	geminiWebModelHeader = "x-goog-ext-525001261-jspb"
+
+	// geminiWebDeleteChatRPC batchexecute 中删除对话的 RPC ID
+	geminiWebDeleteChatRPC = "GzXR5e"
+
+	geminiWebMaxImages      = 10
+	geminiWebMaxImageBytes  = 20 << 20
+	geminiWebMaxPageBytes   = 8 << 20
+	geminiWebCleanupTimeout = 10 * time.Second
+)
+
+var (
+	geminiWebAccessTokenRe = regexp.MustCompile(`"SNlM0e":"([^"]*)"`)
+	geminiWebBuildLabelRe  = regexp.MustCompile(`"cfb2h":"([^"]*)"`)
+	geminiWebSessionIDRe   = regexp.MustCompile(`"FdrFJe":"([^"]*)"`)
+
+	// errGeminiWebSessionExpired Cookie 失效时 /app 会跳转到登录页，页面中不再包含 SNlM0e
+	errGeminiWebSessionExpired = errors.New("gemini web session expired: SNlM0e token not found")
+)
+
+// geminiWebSession /app 页面中提取的会话参数
+type geminiWebSession struct {
+	AccessToken string // SNlM0e，作为表单字段 at 提交
+	BuildLabel  string // cfb2h，对应查询参数 bl
+	SessionID   string // FdrFJe，对应查询参数 f.sid
+}
+
+func parseGeminiWebSession(page []byte) (*geminiWebSession, error) {
+	m := geminiWebAccessTokenRe.FindSubmatch(page)
+	if m == nil || len(m[1]) == 0 {
+		return nil, errGeminiWebSessionExpired
+	}
+	sess := &geminiWebSession{AccessToken: string(m[1])}
+	if m := geminiWebBuildLabelRe.FindSubmatch(page); m != nil {
+		sess.BuildLabel = string(m[1])
+	}
+	if m := geminiWebSessionIDRe.FindSubmatch(page); m != nil {
+		sess.SessionID = string(m[1])
+	}
+	return sess, nil
+}
+
+// geminiWebCookie 优先使用完整的 cookie 凭证，否则由 __Secure-1PSID / __Secure-1PSIDTS 拼装
+func geminiWebCookie(account *Account) (string, error) {
+	if cookie := strings.TrimSpace(account.GetCredential("cookie")); cookie != "" {
+		return cookie, nil
+	}
+	psid := strings.TrimSpace(account.GetCredential("secure_1psid"))
+	if psid == "" {
+		return "", fmt.Errorf("gemini web session account %d has no secure_1psid or cookie", account.ID)
+	}
+	cookie := "__Secure-1PSID=" + psid
+	if psidts := strings.TrimSpace(account.GetCredential("secure_1psidts")); psidts != "" {
+		cookie += "; __Secure-1PSIDTS=" + psidts
+	}
+	return cookie, nil
+}
+
+func newGeminiWebRequest(ctx context.Context, account *Account, method, rawURL string, body io.Reader, contentType string) (*http.Request, error) {
+	cookie, err := geminiWebCookie(account)
+	if err != nil {
+		return nil, err
+	}
+	req, err := http.NewRequestWithContext(ctx, method, rawURL, body)
+	if err != nil {
+		return nil, err
+	}
+
+	userAgent := strings.TrimSpace(account.GetCredential("user_agent"))
+	if userAgent == "" {
+		userAgent = geminiWebDefaultUserAgent
+	}
+	req.Header.Set("User-Agent", userAgent)
+	req.Header.Set("Cookie", cookie)
+	req.Header.Set("Origin", geminiWebBaseURL)
+	req.Header.Set("Referer", geminiWebBaseURL+"/")
+	req.Header.Set("X-Same-Domain", "1")
+	if contentType != "" {
+		req.Header.Set("Content-Type", contentType)
+	}
+	return req, nil
+}
+
+func geminiWebQuery(sess *geminiWebSession) url.Values {
+	q := url.Values{}
+	if sess.BuildLabel != "" {
+		q.Set("bl", sess.BuildLabel)
+	}
+	if sess.SessionID != "" {
+		q.Set("f.sid", sess.SessionID)
+	}
+	q.Set("hl", "en")
+	q.Set("_reqid", strconv.Itoa(10000+mathrand.Intn(90000)))
+	q.Set("rt", "c")
+	return q
+}
+
+// geminiWebFile 已上传到 content-push 的图片
+type geminiWebFile struct {
+	ID   string
+	Name string
+}
+
+// newGeminiWebGenerateRequest 构造 StreamGenerate 请求。
+// f.req 为两层 JSON：外层 [null, inner]，inner 为 [[prompt, 0, null, files], null, null]（末位为空即新建对话）。
+func newGeminiWebGenerateRequest(ctx context.Context, account *Account, sess *geminiWebSession, prompt string, files []geminiWebFile, webModel string) (*http.Request, error) {
+	message := []any{prompt}
+	if len(files) > 0 {
+		refs := make([]any, 0, len(files))
+		for _, f := range files {
+			refs = append(refs, []any{[]any{f.ID}, f.Name})
+		}
+		message = []any{prompt, 0, nil, refs}
+	}
+	inner, err := json.Marshal([]any{message, nil, nil})
+	if err != nil {
+		return nil, err
+	}
+	outer, err := json.Marshal([]any{nil, string(inner)})
+	if err != nil {
+		return nil, err
+	}
+
+	form := url.Values{}
+	form.Set("f.req", string(outer))
+	form.Set("at", sess.AccessToken)
+	req, err := newGeminiWebRequest(ctx, account, http.MethodPost,
+		geminiWebBaseURL+geminiWebGeneratePath+"?"+geminiWebQuery(sess).Encode(),
+		strings.NewReader(form.Encode()), "application/x-www-form-urlencoded;charset=utf-8")
+	if err != nil {
+		return nil, err
+	}
+	if webModel != "" {
+		req.Header.Set(geminiWebModelHeader, fmt.Sprintf(`[1,null,null,null,%q]`, webModel))
+	}
+	return req, nil
+}
+
+func newGeminiWebDeleteRequest(ctx context.Context, account *Account, sess *geminiWebSession, conversationID string) (*http.Request, error) {
+	args, err := json.Marshal([]string{conversationID})
+	if err != nil {
+		return nil, err
+	}
+	freq, err := json.Marshal([]any{[]any{[]any{geminiWebDeleteChatRPC, string(args), nil, "generic"}}})
+	if err != nil {
+		return nil, err
+	}
+
+	q := geminiWebQuery(sess)
+	q.Set("rpcids", geminiWebDeleteChatRPC)
+	form := url.Values{}
+	form.Set("f.req", string(freq))
+	form.Set("at", sess.AccessToken)
+	return newGeminiWebRequest(ctx, account, http.MethodPost,
+		geminiWebBaseURL+geminiWebBatchExecutePath+"?"+q.Encode(),
+		strings.NewReader(form.Encode()), "application/x-www-form-urlencoded;charset=utf-8")
+}
+
+// geminiWebImage 请求中以 inlineData 携带的图片
+type geminiWebImage struct {
+	MimeType string
+	Data     []byte
+}
+
+// geminiWebPrompt 压平后的网页对话输入
+type geminiWebPrompt struct {
+	Text   string
+	Images []geminiWebImage
+}
+
+// buildGeminiWebPrompt 将 generateContent 请求压平为网页对话的单轮输入；
+// Claude Messages 请求先经 convertClaudeMessagesToGeminiGenerateContent 转换再进入这里。
+// 多轮历史以 User/Model 转录写入同一条 prompt，各轮中的图片统一作为附件上传。
+func buildGeminiWebPrompt(body []byte) (*geminiWebPrompt, error) {
+	if tools := gjson.GetBytes(body, "tools"); tools.IsArray() && len(tools.Array()) > 0 {
+		return nil, errors.New("tools are not supported by Gemini web session accounts")
+	}
+
+	prompt := &geminiWebPrompt{}
+	systemInstruction := gjson.GetBytes(body, "systemInstruction")
+	if !systemInstruction.Exists() {
+		systemInstruction = gjson.GetBytes(body, "system_instruction")
+	}
+	system, err := prompt.partsText(systemInstruction.Get("parts"))
+	if err != nil {
+		return nil, err
+	}
+
+	contents := gjson.GetBytes(body, "contents").Array()
+	if len(contents) == 0 {
+		return nil, errors.New("contents: at least one message is required")
+	}
+
+	type turn struct {
+		role string
+		text string
+	}
+	turns := make([]turn, 0, len(contents))
+	for _, content := range contents {
+		text, err := prompt.partsText(content.Get("parts"))
+		if err != nil {
+			return nil, err
+		}
+		turns = append(turns, turn{role: content.Get("role").String(), text: text})
+	}
+
+	if system == "" && len(turns) == 1 && turns[0].role != "model" {
+		prompt.Text = turns[0].text
+		return prompt, nil
+	}
+
+	var b strings.Builder
+	if system != "" {
+		b.WriteString(system)
+		b.WriteString("\n\n")
+	}
+	for _, t := range turns {
+		if t.role == "model" {
+			b.WriteString("Model: ")
+		} else {
+			b.WriteString("User: ")
+		}
+		b.WriteString(t.text)
+		b.WriteString("\n\n")
+	}
+	prompt.Text = strings.TrimSpace(b.String())
+	return prompt, nil
+}
+
+func (p *geminiWebPrompt) partsText(parts gjson.Result) (string, error) {
+	var (
+		texts []string
+		err   error
+	)
+	parts.ForEach(func(_, part gjson.Result) bool {
+		if part.Get("thought").Bool() {
+			// 历史中的思考内容对网页对话没有意义，直接丢弃
+			return true
+		}
+		if text := part.Get("text"); text.Exists() {
+			texts = append(texts, text.String())
+			return true
+		}
+		inline := part.Get("inlineData")
+		if !inline.Exists() {
+			inline = part.Get("inline_data")
+		}
+		if inline.Exists() {
+			err = p.addImage(inline)
+			return err == nil
+		}
+		err = errors.New("only text and inline image parts are supported by Gemini web session accounts")
+		return false
+	})
+	return strings.Join(texts, "\n"), err
+}
+
+func (p *geminiWebPrompt) addImage(inline gjson.Result) error {
+	mimeType := inline.Get("mimeType").String()
+	if mimeType == "" {
+		mimeType = inline.Get("mime_type").String()
+	}
+	if !strings.HasPrefix(mimeType, "image/") {
+		return fmt.Errorf("inline data type %q is not supported by Gemini web session accounts", mimeType)
+	}
+	if len(p.Images) >= geminiWebMaxImages {
+		return fmt.Errorf("at most %d images are supported by Gemini web session accounts", geminiWebMaxImages)
+	}
+	data, err := base64.StdEncoding.DecodeString(inline.Get("data").String())
+	if err != nil {
+		return fmt.Errorf("invalid base64 image data: %w", err)
+	}
+	if len(data) == 0 || len(data) > geminiWebMaxImageBytes {
+		return fmt.Errorf("image size must be between 1 byte and %d MB", geminiWebMaxImageBytes>>20)
+	}
+	p.Images = append(p.Images, geminiWebImage{MimeType: mimeType, Data: data})
+	return nil
+}
+
+// geminiWebStreamError 网页端在 wrb.fr 块中以 BardErrorInfo 返回的错误码
+type geminiWebStreamError struct {
+	Code int64
+}
+
+func (e *geminiWebStreamError) Error() string {
+	if e.Code == 0 {
+		return "gemini web returned an empty response"
+	}
+	return fmt.Sprintf("gemini web error code %d", e.Code)
+}
+
+// status 将网页端错误码映射为 HTTP 状态码，以复用 API 账号的限流 / 故障转移处理
+func (e *geminiWebStreamError) status() int {
+	switch e.Code {
+	case 1037, 1060: // 用量超限 / IP 被临时封禁
+		return http.StatusTooManyRequests
+	case 1050, 1052: // 模型头与对话不一致 / 模型头无效
+		return http.StatusBadRequest
+	default:
+		return http.StatusBadGateway
+	}
+}
+
+// geminiWebStreamState StreamGenerate 以 )]}' 前缀加长度分隔的 JSON 块返回，候选文本为累计快照
+type geminiWebStreamState struct {
+	conversationID string
+	text           string
+}
+
+func (st *geminiWebStreamState) apply(chunk gjson.Result) (string, error) {
+	var (
+		delta strings.Builder
+		err   error
+	)
+	chunk.ForEach(func(_, item gjson.Result) bool {
+		if item.Get("0").String() != "wrb.fr" {
+			return true
+		}
+		payload := item.Get("2")
+		if payload.Type != gjson.String {
+			if code := item.Get("5.2.0.1.0"); code.Exists() {
+				err = &geminiWebStreamError{Code: code.Int()}
+				return false
+			}
+			return true
+		}
+		if !gjson.Valid(payload.String()) {
+			return true
+		}
+		inner := gjson.Parse(payload.String())
+		if id := inner.Get("1.0").String(); id != "" {
+			st.conversationID = id
+		}
+		full := inner.Get("4.0.1.0").String()
+		if full == "" {
+			return true
+		}
+		if strings.HasPrefix(full, st.text) {
+			delta.WriteString(full[len(st.text):])
+		}
+		// 快照被改写时无法撤回已输出的内容，只更新基准
+		st.text = full
+		return true
+	})
+	return delta.String(), err
+}
+
+// readGeminiWebEvents 解析 StreamGenerate 输出，onText 收到增量文本；onText 返回 false 时停止读取
+func readGeminiWebEvents(r io.Reader, maxLineSize int, onText func(delta string) bool) (*geminiWebStreamState, error) {
+	scanner := bufio.NewScanner(r)
+	scanner.Buffer(make([]byte, 0, 64*1024), maxLineSize)
+
+	st := &geminiWebStreamState{}
+	for scanner.Scan() {
+		line := strings.TrimSpace(scanner.Text())
+		if !strings.HasPrefix(line, "[") || !gjson.Valid(line) {
+			continue
+		}
+		delta, err := st.apply(gjson.Parse(line))
+		if err != nil {
+			return st, err
+		}
+		if delta != "" && !onText(delta) {
+			return st, nil
+		}
+	}
+	if err := scanner.Err(); err != nil {
+		return st, fmt.Errorf("stream read error: %w", err)
+	}
+	if st.text == "" {
+		return st, &geminiWebStreamError{}
+	}
+	return st, nil
+}
+
+// geminiWebOutput 按入口协议（Claude Messages 或 Gemini 原生）输出网页对话结果
+type geminiWebOutput struct {
+	c       *gin.Context
+	native  bool
+	model   string
+	started bool
+}
+
+func (o *geminiWebOutput) writeError(status int, errType, message string) {
+	if o.native {
+		o.c.JSON(status, gin.H{
+			"error": gin.H{
+				"code":    status,
+				"message": message,
+				"status":  googleapi.HTTPStatusToGoogleStatus(status),
+			},
+		})
+		return
+	}
+	o.c.JSON(status, gin.H{
+		"type":  "error",
+		"error": gin.H{"type": errType, "message": message},
+	})
+}
+
+func (o *geminiWebOutput) writeJSON(text string, inputTokens, outputTokens int) {
+	if o.native {
+		o.c.JSON(http.StatusOK, geminiWebNativeChunk(o.model, text, "STOP", inputTokens, outputTokens))
+		return
+	}
+	o.c.JSON(http.StatusOK, map[string]any{
+		"id":            "msg_" + randomHex(12),
+		"type":          "message",
+		"role":          "assistant",
+		"model":         o.model,
+		"content":       []any{map[string]any{"type": "text", "text": text}},
+		"stop_reason":   "end_turn",
+		"stop_sequence": nil,
+		"usage":         map[string]any{"input_tokens": inputTokens, "output_tokens": outputTokens},
+	})
+}
+
+// startStream 延迟到收到首段文本才写出响应头，此前的错误仍可故障转移到其他账号
+func (o *geminiWebOutput) startStream(inputTokens int) {
+	o.started = true
+	o.c.Header("Content-Type", "text/event-stream")
+	o.c.Header("Cache-Control", "no-cache")
+	o.c.Header("Connection", "keep-alive")
+	o.c.Header("X-Accel-Buffering", "no")
+	o.c.Status(http.StatusOK)
+	if o.native {
+		return
+	}
+	writeSSE(o.c.Writer, "message_start", map[string]any{
+		"type": "message_start",
+		"message": map[string]any{
+			"id":            "msg_" + randomHex(12),
+			"type":          "message",
+			"role":          "assistant",
+			"model":         o.model,
+			"content":       []any{},
+			"stop_reason":   nil,
+			"stop_sequence": nil,
+			"usage":         map[string]any{"input_tokens": inputTokens, "output_tokens": 0},
+		},
+	})
+	writeSSE(o.c.Writer, "content_block_start", map[string]any{
+		"type":          "content_block_start",
+		"index":         0,
+		"content_block": map[string]any{"type": "text", "text": ""},
+	})
+}
+
+func (o *geminiWebOutput) writeDelta(text string) {
+	if o.native {
+		writeGeminiWebNativeSSE(o.c.Writer, geminiWebNativeChunk(o.model, text, "", 0, 0))
+		return
+	}
+	writeSSE(o.c.Writer, "content_block_delta", map[string]any{
+		"type":  "content_block_delta",
+		"index": 0,
+		"delta": map[string]any{"type": "text_delta", "text": text},
+	})
+}
+
+func (o *geminiWebOutput) finishStream(inputTokens, outputTokens int) {
+	if o.native {
+		writeGeminiWebNativeSSE(o.c.Writer, geminiWebNativeChunk(o.model, "", "STOP", inputTokens, outputTokens))
+		return
+	}
+	writeSSE(o.c.Writer, "content_block_stop", map[string]any{"type": "content_block_stop", "index": 0})
+	writeSSE(o.c.Writer, "message_delta", map[string]any{
+		"type":  "message_delta",
+		"delta": map[string]any{"stop_reason": "end_turn", "stop_sequence": nil},
+		"usage": map[string]any{"output_tokens": outputTokens},
+	})
+	writeSSE(o.c.Writer, "message_stop", map[string]any{"type": "message_stop"})
+}
+
+func (o *geminiWebOutput) streamError() {
+	if o.native {
+		writeGeminiWebNativeSSE(o.c.Writer, map[string]any{
+			"error": map[string]any{"code": http.StatusBadGateway, "message": "Upstream stream ended with an error", "status": googleapi.HTTPStatusToGoogleStatus(http.StatusBadGateway)},
+		})
+		return
+	}
+	writeSSE(o.c.Writer, "error", map[string]any{
+		"type":  "error",
+		"error": map[string]any{"type": "api_error", "message": "Upstream stream ended with an error"},
+	})
+}
+
+func geminiWebNativeChunk(model, text, finishReason string, inputTokens, outputTokens int) map[string]any {
+	candidate := map[string]any{
+		"content": map[string]any{
+			"role":  "model",
+			"parts": []any{map[string]any{"text": text}},
+		},
+		"index": 0,
+	}
+	chunk := map[string]any{
+		"candidates":   []any{candidate},
+		"modelVersion": model,
+	}
+	if finishReason != "" {
+		candidate["finishReason"] = finishReason
+		chunk["usageMetadata"] = map[string]any{
+			"promptTokenCount":     inputTokens,
+			"candidatesTokenCount": outputTokens,
+			"totalTokenCount":      inputTokens + outputTokens,
+		}
+	}
+	return chunk
+}
+
+func writeGeminiWebNativeSSE(w io.Writer, payload any) {
+	data, _ := json.Marshal(payload)
+	_, _ = fmt.Fprintf(w, "data: %s\n\n", data)
+}
+
+// doGeminiWebRequest 发送网页接口请求；上游错误与 API 账号走同一套限流 / 故障转移处理
+func (s *GeminiMessagesCompatService) doGeminiWebRequest(ctx context.Context, c *gin.Context, out *geminiWebOutput, account *Account, req *http.Request, proxyURL string) (*http.Response, error) {
+	resp, err := s.httpUpstream.DoWithTLS(withAccountTLSProfile(req, account), proxyURL, account.ID, account.Concurrency, account.IsTLSFingerprintEnabled())
+	if err != nil {
+		if resp != nil && resp.Body != nil {
+			_ = resp.Body.Close()
+		}
+		safeErr := sanitizeUpstreamErrorMessage(err.Error())
+		setOpsUpstreamError(c, 0, safeErr, "")
+		appendOpsUpstreamError(c, OpsUpstreamErrorEvent{
+			Platform:    account.Platform,
+			AccountID:   account.ID,
+			AccountName: account.Name,
+			Kind:        "request_error",
+			Message:     safeErr,
+		})
+		out.writeError(http.StatusBadGateway, "upstream_error", "Upstream request failed")
+		return nil, fmt.Errorf("upstream request failed: %s", safeErr)
+	}
+	if resp.StatusCode < 400 {
+		return resp, nil
+	}
+
+	respBody, _ := io.ReadAll(io.LimitReader(resp.Body, 2<<20))
+	_ = resp.Body.Close()
+	return nil, s.handleGeminiWebError(ctx, c, out, account, resp.StatusCode, resp.Header, respBody)
+}
+
+func (s *GeminiMessagesCompatService) handleGeminiWebError(ctx context.Context, c *gin.Context, out *geminiWebOutput, account *Account, statusCode int, headers http.Header, respBody []byte) error {
+	s.handleGeminiUpstreamError(ctx, account, statusCode, headers, respBody)
+	upstreamMsg := sanitizeUpstreamErrorMessage(strings.TrimSpace(extractUpstreamErrorMessage(respBody)))
+	if s.shouldFailoverGeminiUpstreamError(statusCode) {
+		logger.LegacyPrintf("service.gemini_messages_compat", "[GeminiWeb] Upstream error (failover): Account=%d(%s) Status=%d Body=%s",
+			account.ID, account.Name, statusCode, truncateString(string(respBody), 1000))
+		appendOpsUpstreamError(c, OpsUpstreamErrorEvent{
+			Platform:           account.Platform,
+			AccountID:          account.ID,
+			AccountName:        account.Name,
+			UpstreamStatusCode: statusCode,
+			Kind:               "failover",
+			Message:            upstreamMsg,
+		})
+		return &UpstreamFailoverError{StatusCode: statusCode, ResponseBody: respBody}
+	}
+
+	setOpsUpstreamError(c, statusCode, upstreamMsg, "")
+	appendOpsUpstreamError(c, OpsUpstreamErrorEvent{
+		Platform:           account.Platform,
+		AccountID:          account.ID,
+		AccountName:        account.Name,
+		UpstreamStatusCode: statusCode,
+		Kind:               "http_error",
+		Message:            upstreamMsg,
+	})
+	if statusCode == http.StatusBadRequest {
+		out.writeError(http.StatusBadRequest, "invalid_request_error", "Upstream rejected the request")
+	} else {
+		out.writeError(http.StatusBadGateway, "upstream_error", "Upstream request failed")
+	}
+	return fmt.Errorf("upstream error: %d", statusCode)
+}
+
+// fetchGeminiWebSession 拉取 /app 页面获取 SNlM0e；Cookie 失效视为 401，使账号进入错误状态并切换账号
+func (s *GeminiMessagesCompatService) fetchGeminiWebSession(ctx context.Context, c *gin.Context, out *geminiWebOutput, account *Account, proxyURL string) (*geminiWebSession, error) {
+	req, err := newGeminiWebRequest(ctx, account, http.MethodGet, geminiWebBaseURL+"/app", nil, "")
+	if err != nil {
+		return nil, err
+	}
+	resp, err := s.doGeminiWebRequest(ctx, c, out, account, req, proxyURL)
+	if err != nil {
+		return nil, err
+	}
+	page, err := io.ReadAll(io.LimitReader(resp.Body, geminiWebMaxPageBytes))
+	_ = resp.Body.Close()
+	if err != nil {
+		return nil, fmt.Errorf("read gemini web page: %w", err)
+	}
+
+	sess, err := parseGeminiWebSession(page)
+	if err != nil {
+		body, _ := json.Marshal(map[string]any{"error": map[string]any{"code": http.StatusUnauthorized, "message": err.Error()}})
+		return nil, s.handleGeminiWebError(ctx, c, out, account, http.StatusUnauthorized, http.Header{}, body)
+	}
+	return sess, nil
+}
+
+// uploadGeminiWebImage 图片先上传到 content-push，网页对话请求中只引用返回的文件 ID
+func (s *GeminiMessagesCompatService) uploadGeminiWebImage(ctx context.Context, c *gin.Context, out *geminiWebOutput, account *Account, proxyURL string, index int, img geminiWebImage) (geminiWebFile, error) {
+	name := fmt.Sprintf("image_%d.%s", index+1, strings.TrimPrefix(img.MimeType, "image/"))
+
+	var buf bytes.Buffer
+	mw := multipart.NewWriter(&buf)
+	fw, err := mw.CreateFormFile("file", name)
+	if err != nil {
+		return geminiWebFile{}, err
+	}
+	if _, err := fw.Write(img.Data); err != nil {
+		return geminiWebFile{}, err
+	}
+	if err := mw.Close(); err != nil {
+		return geminiWebFile{}, err
+	}
+
+	req, err := newGeminiWebRequest(ctx, account, http.MethodPost, geminiWebUploadURL, &buf, mw.FormDataContentType())
+	if err != nil {
+		return geminiWebFile{}, err
+	}
+	req.Header.Set("Push-ID", geminiWebUploadPushID)
+	resp, err := s.doGeminiWebRequest(ctx, c, out, account, req, proxyURL)
+	if err != nil {
+		return geminiWebFile{}, err
+	}
+	defer func() { _ = resp.Body.Close() }()
+	id, err := io.ReadAll(io.LimitReader(resp.Body, 4096))
+	if err != nil {
+		return geminiWebFile{}, fmt.Errorf("read upload response: %w", err)
+	}
+	if strings.TrimSpace(string(id)) == "" {
+		return geminiWebFile{}, errors.New("gemini web upload returned an empty file id")
+	}
+	return geminiWebFile{ID: strings.TrimSpace(string(id)), Name: name}, nil
+}
+
+// forwardGeminiWeb 通过 Gemini 网页会话转发请求：geminiReq 为 generateContent 格式，native 决定响应协议。
+// 每个请求新建一个对话，结束后删除，避免在网页端历史中堆积。
+func (s *GeminiMessagesCompatService) forwardGeminiWeb(ctx context.Context, c *gin.Context, account *Account, originalModel string, geminiReq []byte, stream, native bool, startTime time.Time) (*ForwardResult, error) {
+	out := &geminiWebOutput{c: c, native: native, model: originalModel}
+	prompt, err := buildGeminiWebPrompt(geminiReq)
+	if err != nil {
+		out.writeError(http.StatusBadRequest, "invalid_request_error", err.Error())
+		return nil, err
+	}
+
+	proxyURL := ""
+	if account.ProxyID != nil && account.Proxy != nil {
+		proxyURL = account.Proxy.URL()
+	}
+
+	sess, err := s.fetchGeminiWebSession(ctx, c, out, account, proxyURL)
+	if err != nil {
+		return nil, err
+	}
+
+	files := make([]geminiWebFile, 0, len(prompt.Images))
+	for i, img := range prompt.Images {
+		file, err := s.uploadGeminiWebImage(ctx, c, out, account, proxyURL, i, img)
+		if err != nil {
+			return nil, err
+		}
+		files = append(files, file)
+	}
+
+	webModel := ""
+	if mapped := account.GetMappedModel(originalModel); mapped != originalModel {
+		webModel = mapped
+	}
+	req, err := newGeminiWebGenerateRequest(ctx, account, sess, prompt.Text, files, webModel)
+	if err != nil {
+		return nil, err
+	}
+	setOpsUpstreamRequestBody(c, geminiReq)
+
+	resp, err := s.doGeminiWebRequest(ctx, c, out, account, req, proxyURL)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = resp.Body.Close() }()
+
+	// 网页接口不返回 token 用量，按本地估算计费
+	inputTokens := estimateGeminiCountTokens(geminiReq)
+	maxLineSize := defaultMaxLineSize
+	if s.cfg != nil && s.cfg.Gateway.MaxLineSize > 0 {
+		maxLineSize = s.cfg.Gateway.MaxLineSize
+	}
+	cancelOnDisconnect := s.cfg != nil && s.cfg.Gateway.CancelUpstreamOnClientDisconnect
+
+	// 网页接口不返回用量，按本地分词器估算
+	result := &ForwardResult{
+		Model:          originalModel,
+		Stream:         stream,
+		UsageEstimated: true,
+	}
+	var output strings.Builder
+	st, readErr := readGeminiWebEvents(resp.Body, maxLineSize, func(delta string) bool {
+		output.WriteString(delta)
+		if result.FirstTokenMs == nil {
+			ms := int(time.Since(startTime).Milliseconds())
+			result.FirstTokenMs = &ms
+		}
+		if !stream {
+			return true
+		}
+		if result.ClientDisconnect {
+			// 继续读取上游以完成计费，除非配置了断开即取消
+			return !cancelOnDisconnect
+		}
+		if !out.started {
+			out.startStream(inputTokens)
+		}
+		out.writeDelta(delta)
+		c.Writer.Flush()
+		if c.Request != nil && c.Request.Context().Err() != nil {
+			result.ClientDisconnect = true
+		}
+		return true
+	})
+	if st.conversationID != "" {
+		defer s.deleteGeminiWebConversation(account, sess, st.conversationID, proxyURL)
+	}
+	result.RequestID = st.conversationID
+
+	var webErr *geminiWebStreamError
+	if readErr != nil && output.Len() == 0 {
+		if errors.As(readErr, &webErr) {
+			body, _ := json.Marshal(map[string]any{"error": map[string]any{"code": webErr.status(), "message": webErr.Error()}})
+			return nil, s.handleGeminiWebError(ctx, c, out, account, webErr.status(), http.Header{}, body)
+		}
+		out.writeError(http.StatusBadGateway, "upstream_error", "Upstream stream ended with an error")
+		return nil, readErr
+	}
+
+	outputTokens := claude.EstimateTextTokens(output.String())
+	switch {
+	case stream && readErr != nil:
+		if !result.ClientDisconnect {
+			out.streamError()
+			c.Writer.Flush()
+		}
+	case stream:
+		if !result.ClientDisconnect {
+			out.finishStream(inputTokens, outputTokens)
+			c.Writer.Flush()
+		}
+	case readErr != nil:
+		out.writeError(http.StatusBadGateway, "upstream_error", "Upstream stream ended with an error")
+		return nil, readErr
+	default:
+		out.writeJSON(output.String(), inputTokens, outputTokens)
+	}
+
+	result.Usage = ClaudeUsage{InputTokens: inputTokens, OutputTokens: outputTokens}
+	result.Duration = time.Since(startTime)
+	return result, readErr
+}
+
+// deleteGeminiWebConversation 清理临时对话；失败只记录日志
+func (s *GeminiMessagesCompatService) deleteGeminiWebConversation(account *Account, sess *geminiWebSession, conversationID, proxyURL string) {
+	ctx, cancel := context.WithTimeout(context.Background(), geminiWebCleanupTimeout)
+	defer cancel()
+
+	req, err := newGeminiWebDeleteRequest(ctx, account, sess, conversationID)
+	if err != nil {
+		return
+	}
+	resp, err := s.httpUpstream.DoWithTLS(withAccountTLSProfile(req, account), proxyURL, account.ID, account.Concurrency, account.IsTLSFingerprintEnabled())
+	if err != nil {
+		logger.LegacyPrintf("service.gemini_messages_compat", "[GeminiWeb] delete conversation failed: account=%d err=%s", account.ID, sanitizeUpstreamErrorMessage(err.Error()))
+		return
+	}
+	_ = resp.Body.Close()
+	if resp.StatusCode >= 400 {
+		logger.LegacyPrintf("service.gemini_messages_compat", "[GeminiWeb] delete conversation failed: account=%d status=%d", account.ID, resp.StatusCode)
+	}
+}
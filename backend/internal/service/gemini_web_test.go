@@ -0,0 +1,224 @@
+package service
+
+import (
+	"context"
+	"encoding/json"
+	"fmt"
+	"net/http"
+	"net/http/httptest"
+	"net/url"
+	"strings"
+	"testing"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/gin-gonic/gin"
+	"github.com/stretchr/testify/require"
+	"github.com/tidwall/gjson"
+)
+
+const geminiWebTestPage = `<script>WIZ_global_data = {"cfb2h":"boq_assistant-bard-web-server_20250101.00_p0","FdrFJe":"-4242","SNlM0e":"AT-token:1700000000000"};</script>`
+
+const geminiWebTestErrorStream = ")]}'\n\n" +
+	`[["wrb.fr",null,null,null,null,[3,null,[["type.googleapis.com/assistant.boq.bard.application.BardErrorInfo",[1037]]]]]]` + "\n"
+
+func geminiWebTestChunk(conversationID, text string) string {
+	inner, _ := json.Marshal([]any{nil, []any{conversationID, "r_1"}, nil, nil, []any{[]any{"rc_1", []any{text}}}})
+	outer, _ := json.Marshal([]any{[]any{"wrb.fr", nil, string(inner)}})
+	return fmt.Sprintf("%d\n%s\n", len(outer), outer)
+}
+
+func geminiWebTestStream() string {
+	return ")]}'\n\n" +
+		geminiWebTestChunk("c_abc", "Hello") +
+		"25\n[[\"di\",120],[\"af.httprm\",119,\"1\",4]]\n" +
+		geminiWebTestChunk("c_abc", "Hello world")
+}
+
+type geminiWebAccountRepoStub struct {
+	claudeWebAccountRepoStub
+	rateLimitedUntil time.Time
+}
+
+func (r *geminiWebAccountRepoStub) SetRateLimited(_ context.Context, _ int64, resetAt time.Time) error {
+	r.rateLimitedUntil = resetAt
+	return nil
+}
+
+func newGeminiWebAccountForTest() *Account {
+	return &Account{
+		ID:          401,
+		Name:        "gemini-web",
+		Platform:    PlatformGemini,
+		Type:        AccountTypeWebSession,
+		Concurrency: 1,
+		Credentials: map[string]any{"secure_1psid": "psid-value", "secure_1psidts": "psidts-value"},
+		Status:      StatusActive,
+		Schedulable: true,
+	}
+}
+
+func newGeminiWebUpstreamForTest(generate string) *claudeWebUpstreamStub {
+	return &claudeWebUpstreamStub{respond: func(req *http.Request) *http.Response {
+		switch {
+		case req.URL.Path == "/app":
+			return claudeWebTestResponse(http.StatusOK, geminiWebTestPage)
+		case req.URL.Host == "content-push.googleapis.com":
+			return claudeWebTestResponse(http.StatusOK, "/contrib_service/ttl_1d/file-1")
+		case req.URL.Path == geminiWebGeneratePath:
+			return claudeWebTestResponse(http.StatusOK, generate)
+		default:
+			return claudeWebTestResponse(http.StatusOK, ")]}'\n")
+		}
+	}}
+}
+
+func TestBuildGeminiWebPrompt(t *testing.T) {
+	prompt, err := buildGeminiWebPrompt([]byte(`{"contents":[{"role":"user","parts":[{"text":"hi"}]}]}`))
+	require.NoError(t, err)
+	require.Equal(t, "hi", prompt.Text)
+	require.Empty(t, prompt.Images)
+
+	prompt, err = buildGeminiWebPrompt([]byte(`{
+		"system_instruction":{"parts":[{"text":"Be brief."}]},
+		"contents":[
+			{"role":"user","parts":[{"text":"What is this?"},{"inline_data":{"mime_type":"image/png","data":"iVBORw0KGgo="}}]},
+			{"role":"model","parts":[{"text":"...","thought":true},{"text":"A PNG header."}]},
+			{"role":"user","parts":[{"text":"Sure?"}]}
+		]}`))
+	require.NoError(t, err)
+	require.Equal(t, "Be brief.\n\nUser: What is this?\n\nModel: A PNG header.\n\nUser: Sure?", prompt.Text)
+	require.Len(t, prompt.Images, 1)
+	require.Equal(t, "image/png", prompt.Images[0].MimeType)
+
+	_, err = buildGeminiWebPrompt([]byte(`{"tools":[{"functionDeclarations":[]}],"contents":[{"role":"user","parts":[{"text":"hi"}]}]}`))
+	require.ErrorContains(t, err, "tools are not supported")
+
+	_, err = buildGeminiWebPrompt([]byte(`{"contents":[{"role":"user","parts":[{"inlineData":{"mimeType":"application/pdf","data":"JVBERg=="}}]}]}`))
+	require.ErrorContains(t, err, "application/pdf")
+}
+
+func TestReadGeminiWebEvents(t *testing.T) {
+	var deltas []string
+	st, err := readGeminiWebEvents(strings.NewReader(geminiWebTestStream()), 1<<20, func(delta string) bool {
+		deltas = append(deltas, delta)
+		return true
+	})
+	require.NoError(t, err)
+	require.Equal(t, []string{"Hello", " world"}, deltas)
+	require.Equal(t, "c_abc", st.conversationID)
+
+	_, err = readGeminiWebEvents(strings.NewReader(geminiWebTestErrorStream), 1<<20, func(string) bool { return true })
+	var webErr *geminiWebStreamError
+	require.ErrorAs(t, err, &webErr)
+	require.Equal(t, http.StatusTooManyRequests, webErr.status())
+
+	_, err = readGeminiWebEvents(strings.NewReader(")]}'\n\n"), 1<<20, func(string) bool { return true })
+	require.ErrorAs(t, err, &webErr)
+	require.Equal(t, http.StatusBadGateway, webErr.status())
+}
+
+func TestGeminiMessagesCompatService_ForwardGeminiWeb_StreamWithImage(t *testing.T) {
+	gin.SetMode(gin.TestMode)
+	upstream := newGeminiWebUpstreamForTest(geminiWebTestStream())
+	svc := &GeminiMessagesCompatService{httpUpstream: upstream, cfg: &config.Config{}}
+	account := newGeminiWebAccountForTest()
+
+	rec := httptest.NewRecorder()
+	c, _ := gin.CreateTestContext(rec)
+	c.Request = httptest.NewRequest(http.MethodPost, "/v1/messages", nil)
+	body := []byte(`{"model":"gemini-2.5-pro","stream":true,"messages":[{"role":"user","content":[
+		{"type":"image","source":{"type":"base64","media_type":"image/png","data":"iVBORw0KGgo="}},
+		{"type":"text","text":"Describe it"}]}]}`)
+
+	result, err := svc.Forward(context.Background(), c, account, body)
+	require.NoError(t, err)
+	require.True(t, result.Stream)
+	require.Equal(t, "c_abc", result.RequestID)
+	require.NotNil(t, result.FirstTokenMs)
+	require.Positive(t, result.Usage.OutputTokens)
+
+	out := rec.Body.String()
+	require.Equal(t, "text/event-stream", rec.Header().Get("Content-Type"))
+	for _, event := range []string{"message_start", "content_block_start", "content_block_delta", "content_block_stop", "message_delta", "message_stop"} {
+		require.Contains(t, out, "event: "+event+"\n")
+	}
+	require.Contains(t, out, `"text":" world"`)
+
+	require.Equal(t, []string{
+		"GET /app",
+		"POST /upload",
+		"POST " + geminiWebGeneratePath,
+		"POST " + geminiWebBatchExecutePath,
+	}, upstream.requests, "temporary conversation must be cleaned up")
+	require.Equal(t, geminiWebUploadPushID, upstream.headers["POST /upload"].Get("Push-ID"))
+	require.Equal(t, "__Secure-1PSID=psid-value; __Secure-1PSIDTS=psidts-value", upstream.cookies[0])
+
+	form, err := url.ParseQuery(string(upstream.bodies["POST "+geminiWebGeneratePath]))
+	require.NoError(t, err)
+	require.Equal(t, "AT-token:1700000000000", form.Get("at"))
+	inner := gjson.Get(form.Get("f.req"), "1").String()
+	require.Equal(t, "Describe it", gjson.Get(inner, "0.0").String())
+	require.Equal(t, "/contrib_service/ttl_1d/file-1", gjson.Get(inner, "0.3.0.0.0").String())
+	require.Empty(t, upstream.headers["POST "+geminiWebGeneratePath].Get(geminiWebModelHeader), "unmapped models use the web default")
+}
+
+func TestGeminiMessagesCompatService_ForwardGeminiWebNative_NonStream(t *testing.T) {
+	gin.SetMode(gin.TestMode)
+	upstream := newGeminiWebUpstreamForTest(geminiWebTestStream())
+	svc := &GeminiMessagesCompatService{httpUpstream: upstream, cfg: &config.Config{}}
+	account := newGeminiWebAccountForTest()
+	account.Credentials["model_mapping"] = map[string]any{"gemini-2.5-pro": "71c2d248d3b102ff"}
+
+	rec := httptest.NewRecorder()
+	c, _ := gin.CreateTestContext(rec)
+	c.Request = httptest.NewRequest(http.MethodPost, "/v1beta/models/gemini-2.5-pro:generateContent", nil)
+	body := []byte(`{"contents":[{"role":"user","parts":[{"text":"hi"}]}]}`)
+
+	result, err := svc.ForwardNative(context.Background(), c, account, "gemini-2.5-pro", "generateContent", false, body)
+	require.NoError(t, err)
+	require.False(t, result.Stream)
+	require.Equal(t, http.StatusOK, rec.Code)
+	require.Equal(t, "Hello world", gjson.Get(rec.Body.String(), "candidates.0.content.parts.0.text").String())
+	require.Equal(t, "STOP", gjson.Get(rec.Body.String(), "candidates.0.finishReason").String())
+	require.Equal(t, `[1,null,null,null,"71c2d248d3b102ff"]`, upstream.headers["POST "+geminiWebGeneratePath].Get(geminiWebModelHeader))
+}
+
+func TestGeminiMessagesCompatService_ForwardGeminiWeb_ExpiredSessionFailsOver(t *testing.T) {
+	gin.SetMode(gin.TestMode)
+	upstream := &claudeWebUpstreamStub{respond: func(req *http.Request) *http.Response {
+		// Cookie 失效时 /app 返回登录页
+		return claudeWebTestResponse(http.StatusOK, `<html>Sign in - Google Accounts</html>`)
+	}}
+	repo := &geminiWebAccountRepoStub{}
+	svc := &GeminiMessagesCompatService{httpUpstream: upstream, accountRepo: repo, rateLimitService: &RateLimitService{accountRepo: repo}, cfg: &config.Config{}}
+
+	rec := httptest.NewRecorder()
+	c, _ := gin.CreateTestContext(rec)
+	c.Request = httptest.NewRequest(http.MethodPost, "/v1/messages", nil)
+
+	_, err := svc.Forward(context.Background(), c, newGeminiWebAccountForTest(), []byte(`{"model":"gemini-2.5-pro","messages":[{"role":"user","content":"hi"}]}`))
+	var failover *UpstreamFailoverError
+	require.ErrorAs(t, err, &failover)
+	require.Equal(t, http.StatusUnauthorized, failover.StatusCode)
+	require.Len(t, upstream.requests, 1)
+	require.Contains(t, repo.errorMsg, "SNlM0e")
+}
+
+func TestGeminiMessagesCompatService_ForwardGeminiWeb_UsageLimitCoolsDown(t *testing.T) {
+	gin.SetMode(gin.TestMode)
+	upstream := newGeminiWebUpstreamForTest(geminiWebTestErrorStream)
+	repo := &geminiWebAccountRepoStub{}
+	svc := &GeminiMessagesCompatService{httpUpstream: upstream, accountRepo: repo, rateLimitService: &RateLimitService{accountRepo: repo}, cfg: &config.Config{}}
+
+	rec := httptest.NewRecorder()
+	c, _ := gin.CreateTestContext(rec)
+	c.Request = httptest.NewRequest(http.MethodPost, "/v1/messages", nil)
+
+	_, err := svc.Forward(context.Background(), c, newGeminiWebAccountForTest(), []byte(`{"model":"gemini-2.5-pro","stream":true,"messages":[{"role":"user","content":"hi"}]}`))
+	var failover *UpstreamFailoverError
+	require.ErrorAs(t, err, &failover)
+	require.Equal(t, http.StatusTooManyRequests, failover.StatusCode)
+	require.True(t, repo.rateLimitedUntil.After(time.Now()))
+	require.Empty(t, rec.Body.String(), "nothing is written before the first token so the request can fail over")
+}
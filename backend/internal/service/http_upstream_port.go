@@ -1,6 +1,14 @@
 package service
 
-import "net/http"
+import (
+	"context"
+	"net/http"
+
+	"github.com/Wei-Shaw/sub2api/internal/pkg/tlsfingerprint"
+)
+
+// defaultWebSessionTLSProfile 网页会话账号默认使用的浏览器指纹模板（TLS + HTTP/2）
+const defaultWebSessionTLSProfile = "chrome"
 
 // HTTPUpstream 上游 HTTP 请求接口
 // 用于向上游 API（Claude、OpenAI、Gemini 等）发送请求
@@ -43,8 +51,9 @@ type HTTPUpstream interface {
 	//   - error: 请求错误（网络错误、超时等）
 	//
 	// TLS 指纹说明:
-	//   - 当 enableTLSFingerprint=true 时，使用 utls 库模拟 Claude CLI 的 TLS 指纹
-	//   - TLS 指纹模板根据 accountID % len(profiles) 自动选择
+	//   - 当 enableTLSFingerprint=true 时，使用 utls 库模拟 Claude CLI 或浏览器的 TLS 指纹
+	//   - 请求 context 中指定了模板（withAccountTLSProfile）时使用该模板，
+	//     否则根据 accountID % len(profiles) 自动选择
 	//   - 支持直连、HTTP/HTTPS 代理、SOCKS5 代理三种场景
 	//   - 如果 enableTLSFingerprint=false，行为与 Do 方法相同
 	//
@@ -53,3 +62,57 @@ type HTTPUpstream interface {
 	//   - TLS 指纹客户端与普通客户端使用不同的缓存键，互不影响
 	DoWithTLS(req *http.Request, proxyURL string, accountID int64, accountConcurrency int, enableTLSFingerprint bool) (*http.Response, error)
 }
+
+// withAccountTLSProfile 将账号的 TLS 指纹模板写入请求 context，供 DoWithTLS 选择模板
+func withAccountTLSProfile(req *http.Request, account *Account) *http.Request {
+	profile := account.GetTLSFingerprintProfile()
+	if profile == "" {
+		return req
+	}
+	return req.WithContext(tlsfingerprint.WithProfileName(req.Context(), profile))
+}
+
+type upstreamAdapterKey struct{}
+
+// WithUpstreamAdapter 将本次请求的上游平台写入 context，
+// HTTPUpstream 实现据此应用 gateway.http_client.adapters 中的连接池覆盖参数
+func WithUpstreamAdapter(ctx context.Context, platform string) context.Context {
+	if platform == "" {
+		return ctx
+	}
+	return context.WithValue(ctx, upstreamAdapterKey{}, platform)
+}
+
+// UpstreamAdapterFromContext 读取 context 中的上游平台，未设置时返回空字符串
+func UpstreamAdapterFromContext(ctx context.Context) string {
+	if ctx == nil {
+		return ""
+	}
+	platform, _ := ctx.Value(upstreamAdapterKey{}).(string)
+	return platform
+}
+
+// UpstreamPoolStats 上游客户端缓存与连接统计（计数器自进程启动累计）
+type UpstreamPoolStats struct {
+	Clients               int                                 `json:"clients"`
+	TLSFingerprintClients int                                 `json:"tls_fingerprint_clients"`
+	InFlight              int64                               `json:"in_flight"`
+	ConnsNew              int64                               `json:"conns_new"`
+	ConnsReused           int64                               `json:"conns_reused"`
+	Dials                 int64                               `json:"dials"`
+	DialErrors            int64                               `json:"dial_errors"`
+	DNSCacheHits          int64                               `json:"dns_cache_hits"`
+	DNSCacheMisses        int64                               `json:"dns_cache_misses"`
+	Adapters              map[string]UpstreamAdapterPoolStats `json:"adapters"`
+}
+
+// UpstreamAdapterPoolStats 按平台汇总的客户端统计
+type UpstreamAdapterPoolStats struct {
+	Clients  int   `json:"clients"`
+	InFlight int64 `json:"in_flight"`
+}
+
+// UpstreamPoolStatsProvider 可选接口：HTTPUpstream 实现提供连接池统计
+type UpstreamPoolStatsProvider interface {
+	PoolStats() UpstreamPoolStats
+}
@@ -14,6 +14,7 @@ type IdempotencyCleanupService struct {
 	repo     IdempotencyRepository
 	interval time.Duration
 	batch    int
+	leader   *LeaderElector
 
 	startOnce sync.Once
 	stopOnce  sync.Once
@@ -39,6 +40,11 @@ func NewIdempotencyCleanupService(repo IdempotencyRepository, cfg *config.Config
 	}
 }
 
+// SetLeaderElector 多实例部署时仅由 leader 执行；需要在 Start() 之前调用
+func (s *IdempotencyCleanupService) SetLeaderElector(leader *LeaderElector) {
+	s.leader = leader
+}
+
 func (s *IdempotencyCleanupService) Start() {
 	if s == nil || s.repo == nil {
 		return
@@ -77,6 +83,9 @@ func (s *IdempotencyCleanupService) runLoop() {
 }
 
 func (s *IdempotencyCleanupService) cleanupOnce() {
+	if !s.leader.IsLeader() {
+		return
+	}
 	ctx, cancel := context.WithTimeout(context.Background(), 10*time.Second)
 	defer cancel()
 
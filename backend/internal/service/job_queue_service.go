@@ -0,0 +1,331 @@
+package service
+
+import (
+	"context"
+	"encoding/json"
+	"errors"
+	"fmt"
+	"os"
+	"sort"
+	"sync"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/logger"
+)
+
+// 任务队列主题
+const (
+	JobTopicEmail             = "email"
+	JobTopicDashboardBackfill = "dashboard_backfill"
+	JobTopicDataSubject       = "data_subject"
+)
+
+// ErrJobQueueDisabled 任务队列未启用
+var ErrJobQueueDisabled = errors.New("job queue is disabled")
+
+// QueuedJob 队列中的一条任务
+type QueuedJob struct {
+	ID         string
+	Topic      string
+	Payload    []byte
+	EnqueuedAt time.Time
+	// Deliveries 已投递次数（首次投递为 1）
+	Deliveries int64
+}
+
+// StalledJob 已被取走但长时间未确认的任务
+type StalledJob struct {
+	ID         string
+	Deliveries int64
+}
+
+// JobQueueStats 单个主题的队列指标
+type JobQueueStats struct {
+	Topic string `json:"topic"`
+	// Length 尚未确认的任务总数（已确认的任务会从 Stream 中删除）
+	Length int64 `json:"length"`
+	// Pending 已投递但未确认的任务数
+	Pending int64 `json:"pending"`
+	// Lag 尚未投递给任何消费者的任务数
+	Lag int64 `json:"lag"`
+	// OldestPendingSeconds 最早一条待处理任务已等待的时间（秒），用于观察处理延迟
+	OldestPendingSeconds float64 `json:"oldest_pending_seconds"`
+	// DeadLetters 死信队列中的任务数
+	DeadLetters int64 `json:"dead_letters"`
+	Consumers   int64 `json:"consumers"`
+}
+
+// JobQueue 持久化任务队列（Redis Streams + 消费者组）
+type JobQueue interface {
+	Enqueue(ctx context.Context, topic string, payload []byte) (string, error)
+	EnsureGroup(ctx context.Context, topic string) error
+	// Read 阻塞读取尚未投递的新任务
+	Read(ctx context.Context, topic, consumer string, count int, block time.Duration) ([]QueuedJob, error)
+	// ListStalled 列出空闲时间超过 minIdle 的待确认任务
+	ListStalled(ctx context.Context, topic string, minIdle time.Duration, count int) ([]StalledJob, error)
+	// Claim 将卡住的任务转移给当前消费者（投递次数 +1）
+	Claim(ctx context.Context, topic, consumer string, minIdle time.Duration, stalled []StalledJob) ([]QueuedJob, error)
+	Ack(ctx context.Context, topic string, ids ...string) error
+	// DeadLetter 将任务写入死信 Stream 并确认原任务
+	DeadLetter(ctx context.Context, job QueuedJob, reason string) error
+	Stats(ctx context.Context, topic string) (*JobQueueStats, error)
+}
+
+// JobHandler 任务处理函数；返回错误时任务保持待确认状态，空闲超时后重新投递
+type JobHandler func(ctx context.Context, job *QueuedJob) error
+
+type jobTopic struct {
+	name        string
+	concurrency int
+	handler     JobHandler
+	timeout     time.Duration
+}
+
+// JobQueueService 任务队列的生产/消费调度
+//
+// 投递语义为至少一次：处理成功后才确认；处理失败或消费者崩溃的任务在
+// claim_idle_seconds 后被重新认领，投递次数达到 max_attempts 后转入死信 Stream。
+type JobQueueService struct {
+	queue    JobQueue
+	cfg      config.JobQueueConfig
+	consumer string
+
+	mu      sync.Mutex
+	topics  map[string]*jobTopic
+	running bool
+	ctx     context.Context
+	cancel  context.CancelFunc
+	wg      sync.WaitGroup
+}
+
+// NewJobQueueService 创建任务队列服务
+func NewJobQueueService(queue JobQueue, cfg *config.Config) *JobQueueService {
+	s := &JobQueueService{
+		queue:  queue,
+		topics: make(map[string]*jobTopic),
+	}
+	if cfg != nil {
+		s.cfg = cfg.JobQueue
+	}
+	s.consumer = s.cfg.ConsumerName
+	if s.consumer == "" {
+		host, _ := os.Hostname()
+		if host == "" {
+			host = "sub2api"
+		}
+		s.consumer = fmt.Sprintf("%s-%d", host, os.Getpid())
+	}
+	return s
+}
+
+// Enabled 队列是否可用
+func (s *JobQueueService) Enabled() bool {
+	return s != nil && s.queue != nil && s.cfg.Enabled
+}
+
+// Enqueue 将任务序列化为 JSON 后入队
+func (s *JobQueueService) Enqueue(ctx context.Context, topic string, payload any) (string, error) {
+	if !s.Enabled() {
+		return "", ErrJobQueueDisabled
+	}
+	data, err := json.Marshal(payload)
+	if err != nil {
+		return "", fmt.Errorf("marshal job payload: %w", err)
+	}
+	return s.queue.Enqueue(ctx, topic, data)
+}
+
+// RegisterHandler 注册主题处理器；服务已启动时立即开始消费
+func (s *JobQueueService) RegisterHandler(topic string, concurrency int, timeout time.Duration, handler JobHandler) {
+	if !s.Enabled() || handler == nil {
+		return
+	}
+	if concurrency <= 0 {
+		concurrency = 1
+	}
+	if timeout <= 0 {
+		timeout = time.Minute
+	}
+	t := &jobTopic{name: topic, concurrency: concurrency, handler: handler, timeout: timeout}
+
+	s.mu.Lock()
+	defer s.mu.Unlock()
+	if _, exists := s.topics[topic]; exists {
+		logger.LegacyPrintf("service.job_queue", "[JobQueue] Handler for topic %s already registered, ignored", topic)
+		return
+	}
+	s.topics[topic] = t
+	if s.running {
+		s.startTopicLocked(t)
+	}
+}
+
+// Start 启动已注册主题的消费者
+func (s *JobQueueService) Start() {
+	if !s.Enabled() {
+		return
+	}
+	s.mu.Lock()
+	defer s.mu.Unlock()
+	if s.running {
+		return
+	}
+	s.ctx, s.cancel = context.WithCancel(context.Background())
+	s.running = true
+	for _, t := range s.topics {
+		s.startTopicLocked(t)
+	}
+	logger.LegacyPrintf("service.job_queue", "[JobQueue] Started (consumer=%s)", s.consumer)
+}
+
+// Stop 停止消费并等待进行中的任务结束
+func (s *JobQueueService) Stop() {
+	if s == nil {
+		return
+	}
+	s.mu.Lock()
+	if !s.running {
+		s.mu.Unlock()
+		return
+	}
+	s.running = false
+	s.cancel()
+	s.mu.Unlock()
+	s.wg.Wait()
+	logger.LegacyPrintf("service.job_queue", "%s", "[JobQueue] Stopped")
+}
+
+// Stats 返回所有已注册主题的队列指标
+func (s *JobQueueService) Stats(ctx context.Context) ([]JobQueueStats, error) {
+	if !s.Enabled() {
+		return []JobQueueStats{}, nil
+	}
+	s.mu.Lock()
+	names := make([]string, 0, len(s.topics))
+	for name := range s.topics {
+		names = append(names, name)
+	}
+	s.mu.Unlock()
+	sort.Strings(names)
+
+	out := make([]JobQueueStats, 0, len(names))
+	for _, name := range names {
+		st, err := s.queue.Stats(ctx, name)
+		if err != nil {
+			return nil, fmt.Errorf("job queue stats %s: %w", name, err)
+		}
+		out = append(out, *st)
+	}
+	return out, nil
+}
+
+func (s *JobQueueService) startTopicLocked(t *jobTopic) {
+	if err := s.queue.EnsureGroup(s.ctx, t.name); err != nil {
+		logger.LegacyPrintf("service.job_queue", "[JobQueue] Ensure consumer group for %s failed: %v", t.name, err)
+	}
+	for i := 0; i < t.concurrency; i++ {
+		s.wg.Add(1)
+		go s.consumeLoop(t)
+	}
+	s.wg.Add(1)
+	go s.reclaimLoop(t)
+}
+
+func (s *JobQueueService) consumeLoop(t *jobTopic) {
+	defer s.wg.Done()
+	block := time.Duration(s.cfg.BlockSeconds) * time.Second
+	for {
+		if s.ctx.Err() != nil {
+			return
+		}
+		jobs, err := s.queue.Read(s.ctx, t.name, s.consumer, 1, block)
+		if err != nil {
+			if s.ctx.Err() != nil {
+				return
+			}
+			logger.LegacyPrintf("service.job_queue", "[JobQueue] Read %s failed: %v", t.name, err)
+			// 组被删除（如 Redis 被清空）时重建后继续
+			_ = s.queue.EnsureGroup(s.ctx, t.name)
+			if sleepWithContext(s.ctx, time.Second) != nil {
+				return
+			}
+			continue
+		}
+		for i := range jobs {
+			s.process(t, &jobs[i])
+		}
+	}
+}
+
+func (s *JobQueueService) reclaimLoop(t *jobTopic) {
+	defer s.wg.Done()
+	minIdle := time.Duration(s.cfg.ClaimIdleSeconds) * time.Second
+	ticker := time.NewTicker(minIdle / 2)
+	defer ticker.Stop()
+	for {
+		select {
+		case <-s.ctx.Done():
+			return
+		case <-ticker.C:
+			s.reclaimOnce(t, minIdle)
+		}
+	}
+}
+
+// reclaimOnce 认领卡住的任务：超过最大投递次数的转入死信，其余重新处理
+func (s *JobQueueService) reclaimOnce(t *jobTopic, minIdle time.Duration) {
+	stalled, err := s.queue.ListStalled(s.ctx, t.name, minIdle, 50)
+	if err != nil {
+		if s.ctx.Err() == nil {
+			logger.LegacyPrintf("service.job_queue", "[JobQueue] List stalled %s failed: %v", t.name, err)
+		}
+		return
+	}
+	if len(stalled) == 0 {
+		return
+	}
+
+	jobs, err := s.queue.Claim(s.ctx, t.name, s.consumer, minIdle, stalled)
+	if err != nil {
+		if s.ctx.Err() == nil {
+			logger.LegacyPrintf("service.job_queue", "[JobQueue] Claim %s failed: %v", t.name, err)
+		}
+		return
+	}
+	for i := range jobs {
+		job := &jobs[i]
+		if job.Deliveries > int64(s.cfg.MaxAttempts) {
+			if err := s.queue.DeadLetter(s.ctx, *job, "max attempts exceeded"); err != nil {
+				logger.LegacyPrintf("service.job_queue", "[JobQueue] Dead-letter %s/%s failed: %v", t.name, job.ID, err)
+			} else {
+				logger.LegacyPrintf("service.job_queue", "[JobQueue] Job %s/%s moved to dead-letter after %d deliveries", t.name, job.ID, job.Deliveries-1)
+			}
+			continue
+		}
+		s.process(t, job)
+	}
+}
+
+func (s *JobQueueService) process(t *jobTopic, job *QueuedJob) {
+	ctx, cancel := context.WithTimeout(s.ctx, t.timeout)
+	err := runJobHandler(ctx, t.handler, job)
+	cancel()
+	if err != nil {
+		// 不确认，等待空闲超时后重新投递
+		logger.LegacyPrintf("service.job_queue", "[JobQueue] Job %s/%s failed (delivery %d): %v", t.name, job.ID, job.Deliveries, err)
+		return
+	}
+	if err := s.queue.Ack(s.ctx, t.name, job.ID); err != nil && s.ctx.Err() == nil {
+		logger.LegacyPrintf("service.job_queue", "[JobQueue] Ack %s/%s failed: %v", t.name, job.ID, err)
+	}
+}
+
+func runJobHandler(ctx context.Context, handler JobHandler, job *QueuedJob) (err error) {
+	defer func() {
+		if r := recover(); r != nil {
+			err = fmt.Errorf("job handler panic: %v", r)
+		}
+	}()
+	return handler(ctx, job)
+}
@@ -0,0 +1,155 @@
+//go:build unit
+
+package service
+
+import (
+	"context"
+	"errors"
+	"sync"
+	"testing"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/stretchr/testify/require"
+)
+
+type jobQueueStub struct {
+	mu       sync.Mutex
+	enqueued map[string][][]byte
+	stalled  []StalledJob
+	claimed  map[string][]byte
+	acked    []string
+	dead     []QueuedJob
+}
+
+func newJobQueueStub() *jobQueueStub {
+	return &jobQueueStub{enqueued: map[string][][]byte{}, claimed: map[string][]byte{}}
+}
+
+func (q *jobQueueStub) Enqueue(ctx context.Context, topic string, payload []byte) (string, error) {
+	q.mu.Lock()
+	defer q.mu.Unlock()
+	q.enqueued[topic] = append(q.enqueued[topic], payload)
+	return "1-0", nil
+}
+
+func (q *jobQueueStub) EnsureGroup(ctx context.Context, topic string) error { return nil }
+
+func (q *jobQueueStub) Read(ctx context.Context, topic, consumer string, count int, block time.Duration) ([]QueuedJob, error) {
+	<-ctx.Done()
+	return nil, ctx.Err()
+}
+
+func (q *jobQueueStub) ListStalled(ctx context.Context, topic string, minIdle time.Duration, count int) ([]StalledJob, error) {
+	return q.stalled, nil
+}
+
+func (q *jobQueueStub) Claim(ctx context.Context, topic, consumer string, minIdle time.Duration, stalled []StalledJob) ([]QueuedJob, error) {
+	out := make([]QueuedJob, 0, len(stalled))
+	for _, st := range stalled {
+		out = append(out, QueuedJob{ID: st.ID, Topic: topic, Payload: q.claimed[st.ID], Deliveries: st.Deliveries + 1})
+	}
+	return out, nil
+}
+
+func (q *jobQueueStub) Ack(ctx context.Context, topic string, ids ...string) error {
+	q.mu.Lock()
+	defer q.mu.Unlock()
+	q.acked = append(q.acked, ids...)
+	return nil
+}
+
+func (q *jobQueueStub) DeadLetter(ctx context.Context, job QueuedJob, reason string) error {
+	q.dead = append(q.dead, job)
+	return nil
+}
+
+func (q *jobQueueStub) Stats(ctx context.Context, topic string) (*JobQueueStats, error) {
+	return &JobQueueStats{Topic: topic, Pending: int64(len(q.stalled))}, nil
+}
+
+func newTestJobQueueService(queue JobQueue) *JobQueueService {
+	return NewJobQueueService(queue, &config.Config{JobQueue: config.JobQueueConfig{
+		Enabled:          true,
+		BlockSeconds:     1,
+		ClaimIdleSeconds: 60,
+		MaxAttempts:      3,
+	}})
+}
+
+func TestJobQueueService_EnqueueDisabled(t *testing.T) {
+	svc := NewJobQueueService(newJobQueueStub(), &config.Config{})
+	require.False(t, svc.Enabled())
+	_, err := svc.Enqueue(context.Background(), JobTopicEmail, EmailTask{Email: "a@example.com"})
+	require.ErrorIs(t, err, ErrJobQueueDisabled)
+
+	var nilSvc *JobQueueService
+	require.False(t, nilSvc.Enabled())
+}
+
+func TestJobQueueService_EnqueueMarshalsPayload(t *testing.T) {
+	queue := newJobQueueStub()
+	svc := newTestJobQueueService(queue)
+
+	_, err := svc.Enqueue(context.Background(), JobTopicEmail, EmailTask{Email: "a@example.com", TaskType: TaskTypeVerifyCode})
+	require.NoError(t, err)
+	require.Len(t, queue.enqueued[JobTopicEmail], 1)
+	require.JSONEq(t, `{"email":"a@example.com","site_name":"","task_type":"verify_code"}`, string(queue.enqueued[JobTopicEmail][0]))
+}
+
+func TestJobQueueService_ReclaimRetriesAndDeadLetters(t *testing.T) {
+	queue := newJobQueueStub()
+	queue.stalled = []StalledJob{
+		{ID: "1-0", Deliveries: 1},
+		{ID: "2-0", Deliveries: 3}, // 已达到最大投递次数
+		{ID: "3-0", Deliveries: 2},
+	}
+	queue.claimed = map[string][]byte{"1-0": []byte("ok"), "2-0": []byte("ok"), "3-0": []byte("fail")}
+	svc := newTestJobQueueService(queue)
+	svc.ctx, svc.cancel = context.WithCancel(context.Background())
+	defer svc.cancel()
+
+	var handled []string
+	topic := &jobTopic{name: "test", concurrency: 1, timeout: time.Second, handler: func(ctx context.Context, job *QueuedJob) error {
+		handled = append(handled, job.ID)
+		if string(job.Payload) == "fail" {
+			return errors.New("boom")
+		}
+		return nil
+	}}
+
+	svc.reclaimOnce(topic, time.Minute)
+
+	require.Equal(t, []string{"1-0", "3-0"}, handled)
+	require.Equal(t, []string{"1-0"}, queue.acked, "失败的任务不应被确认")
+	require.Len(t, queue.dead, 1)
+	require.Equal(t, "2-0", queue.dead[0].ID)
+}
+
+func TestJobQueueService_HandlerPanicIsRecovered(t *testing.T) {
+	queue := newJobQueueStub()
+	svc := newTestJobQueueService(queue)
+	svc.ctx, svc.cancel = context.WithCancel(context.Background())
+	defer svc.cancel()
+
+	topic := &jobTopic{name: "test", timeout: time.Second, handler: func(ctx context.Context, job *QueuedJob) error {
+		panic("unexpected")
+	}}
+	svc.process(topic, &QueuedJob{ID: "1-0", Deliveries: 1})
+	require.Empty(t, queue.acked)
+}
+
+func TestJobQueueService_StartStop(t *testing.T) {
+	queue := newJobQueueStub()
+	svc := newTestJobQueueService(queue)
+	svc.RegisterHandler("test", 2, time.Second, func(ctx context.Context, job *QueuedJob) error { return nil })
+	svc.Start()
+
+	stats, err := svc.Stats(context.Background())
+	require.NoError(t, err)
+	require.Len(t, stats, 1)
+	require.Equal(t, "test", stats[0].Topic)
+
+	svc.Stop()
+	svc.Stop() // 重复调用安全
+}
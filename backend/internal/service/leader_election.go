@@ -0,0 +1,136 @@
+package service
+
+import (
+	"context"
+	"sync"
+	"sync/atomic"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/logger"
+	"github.com/google/uuid"
+)
+
+// leaderElectionKey 后台单例任务共用的选主租约名
+const leaderElectionKey = "background_jobs"
+
+// LeaderLock 选主租约存储，续期与释放均只对当前持有者生效
+type LeaderLock interface {
+	// TryAcquire 租约空闲或已由 owner 持有时获取/延长租约
+	TryAcquire(ctx context.Context, name, owner string, ttl time.Duration) (bool, error)
+	// Renew 仅当租约仍由 owner 持有时延长，返回 false 表示已被其他实例取得
+	Renew(ctx context.Context, name, owner string, ttl time.Duration) (bool, error)
+	Release(ctx context.Context, name, owner string) error
+}
+
+// LeaderElector 多副本运行后台任务时的 Redis 选主
+//
+// Token 刷新、仪表盘预聚合、过期检查、回收站/用量/幂等记录清理等周期任务在每次执行前检查 IsLeader，
+// 只有持有租约的实例真正执行，其余实例跳过；leader 退出或失联后租约过期，由其他实例接管。
+// 按 cron 调度的任务与运维聚合/告警任务已有各自的执行锁，不依赖此选主。
+//
+// nil *LeaderElector 表示未启用选主（单实例部署），IsLeader 恒为 true。
+type LeaderElector struct {
+	lock     LeaderLock
+	owner    string
+	lease    time.Duration
+	isLeader atomic.Bool
+
+	stopCh   chan struct{}
+	stopOnce sync.Once
+	wg       sync.WaitGroup
+}
+
+// NewLeaderElector 创建选主器；未启用选主或 simple 模式下返回 nil
+func NewLeaderElector(lock LeaderLock, cfg *config.Config) *LeaderElector {
+	if lock == nil || cfg == nil || !cfg.Worker.LeaderElection.Enabled || cfg.RunMode == config.RunModeSimple {
+		return nil
+	}
+	return &LeaderElector{
+		lock:   lock,
+		owner:  uuid.NewString(),
+		lease:  time.Duration(cfg.Worker.LeaderElection.LeaseSeconds) * time.Second,
+		stopCh: make(chan struct{}),
+	}
+}
+
+// IsLeader 当前实例是否应执行单例后台任务
+func (e *LeaderElector) IsLeader() bool {
+	if e == nil {
+		return true
+	}
+	return e.isLeader.Load()
+}
+
+// Start 同步参与一次选举后按租约的 1/3 周期续期或重新竞选，
+// 保证随后启动的后台任务在首次执行时已知晓自身角色
+func (e *LeaderElector) Start() {
+	if e == nil {
+		return
+	}
+	e.campaign()
+	e.wg.Add(1)
+	go func() {
+		defer e.wg.Done()
+		ticker := time.NewTicker(e.lease / 3)
+		defer ticker.Stop()
+		for {
+			select {
+			case <-ticker.C:
+				e.campaign()
+			case <-e.stopCh:
+				return
+			}
+		}
+	}()
+}
+
+// Stop 停止续期并主动释放租约，让其他实例无需等待过期即可接管
+func (e *LeaderElector) Stop() {
+	if e == nil {
+		return
+	}
+	e.stopOnce.Do(func() {
+		close(e.stopCh)
+	})
+	e.wg.Wait()
+	if !e.isLeader.Swap(false) {
+		return
+	}
+	ctx, cancel := context.WithTimeout(context.Background(), 3*time.Second)
+	defer cancel()
+	if err := e.lock.Release(ctx, leaderElectionKey, e.owner); err != nil {
+		logger.LegacyPrintf("service.leader_election", "[LeaderElection] release lease failed: %v", err)
+	}
+}
+
+func (e *LeaderElector) campaign() {
+	ctx, cancel := context.WithTimeout(context.Background(), e.lease/3)
+	defer cancel()
+
+	if e.isLeader.Load() {
+		ok, err := e.lock.Renew(ctx, leaderElectionKey, e.owner, e.lease)
+		if err == nil && ok {
+			return
+		}
+		// 无法确认租约仍归自己时立即让出，避免与新 leader 重复执行；
+		// 租约实际未丢失时下一轮 TryAcquire 会重新取得
+		e.isLeader.Store(false)
+		if err != nil {
+			logger.LegacyPrintf("service.leader_election", "[LeaderElection] renew lease failed, stepping down: %v", err)
+		} else {
+			logger.LegacyPrintf("service.leader_election", "[LeaderElection] lease taken over by another instance, stepping down")
+		}
+		return
+	}
+
+	ok, err := e.lock.TryAcquire(ctx, leaderElectionKey, e.owner, e.lease)
+	if err != nil {
+		logger.LegacyPrintf("service.leader_election", "[LeaderElection] acquire lease failed: %v", err)
+		return
+	}
+	if ok {
+		e.isLeader.Store(true)
+		logger.LegacyPrintf("service.leader_election", "[LeaderElection] instance %s elected leader for background jobs (lease=%s)", e.owner, e.lease)
+	}
+}
@@ -0,0 +1,105 @@
+//go:build unit
+
+package service
+
+import (
+	"context"
+	"errors"
+	"sync"
+	"testing"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/stretchr/testify/require"
+)
+
+type leaderLockStub struct {
+	mu       sync.Mutex
+	holder   string
+	renewErr error
+}
+
+func (l *leaderLockStub) TryAcquire(ctx context.Context, name, owner string, ttl time.Duration) (bool, error) {
+	l.mu.Lock()
+	defer l.mu.Unlock()
+	if l.holder != "" && l.holder != owner {
+		return false, nil
+	}
+	l.holder = owner
+	return true, nil
+}
+
+func (l *leaderLockStub) Renew(ctx context.Context, name, owner string, ttl time.Duration) (bool, error) {
+	l.mu.Lock()
+	defer l.mu.Unlock()
+	if l.renewErr != nil {
+		return false, l.renewErr
+	}
+	return l.holder == owner, nil
+}
+
+func (l *leaderLockStub) Release(ctx context.Context, name, owner string) error {
+	l.mu.Lock()
+	defer l.mu.Unlock()
+	if l.holder == owner {
+		l.holder = ""
+	}
+	return nil
+}
+
+func newLeaderElectionTestConfig() *config.Config {
+	return &config.Config{
+		Worker: config.WorkerConfig{
+			LeaderElection: config.WorkerLeaderElectionConfig{Enabled: true, LeaseSeconds: 30},
+		},
+	}
+}
+
+func TestLeaderElector_SingleLeaderAndFailover(t *testing.T) {
+	lock := &leaderLockStub{}
+	cfg := newLeaderElectionTestConfig()
+	a := NewLeaderElector(lock, cfg)
+	b := NewLeaderElector(lock, cfg)
+
+	a.campaign()
+	b.campaign()
+	require.True(t, a.IsLeader())
+	require.False(t, b.IsLeader())
+
+	// leader 退出时释放租约，其他实例下一轮即可接管
+	a.Stop()
+	require.False(t, a.IsLeader())
+	b.campaign()
+	require.True(t, b.IsLeader())
+}
+
+func TestLeaderElector_StepsDownWhenRenewFails(t *testing.T) {
+	lock := &leaderLockStub{}
+	e := NewLeaderElector(lock, newLeaderElectionTestConfig())
+	e.campaign()
+	require.True(t, e.IsLeader())
+
+	lock.renewErr = errors.New("redis unavailable")
+	e.campaign()
+	require.False(t, e.IsLeader())
+
+	// 租约仍归自己时，恢复后重新取得
+	lock.renewErr = nil
+	e.campaign()
+	require.True(t, e.IsLeader())
+
+	// 租约被其他实例取得后让出
+	lock.holder = "other"
+	e.campaign()
+	require.False(t, e.IsLeader())
+}
+
+func TestLeaderElector_DisabledActsAsSingleInstance(t *testing.T) {
+	cfg := newLeaderElectionTestConfig()
+	cfg.Worker.LeaderElection.Enabled = false
+	e := NewLeaderElector(&leaderLockStub{}, cfg)
+	require.Nil(t, e)
+	require.True(t, e.IsLeader())
+	e.Start()
+	e.Stop()
+}
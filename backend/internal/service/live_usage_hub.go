@@ -0,0 +1,240 @@
+package service
+
+import (
+	"context"
+	"strings"
+	"sync"
+	"sync/atomic"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/pkg/ctxkey"
+	"github.com/gin-gonic/gin"
+)
+
+// defaultLiveUsageBuffer 每个订阅的事件缓冲，订阅端消费不及时时丢弃新事件而不是阻塞网关
+const defaultLiveUsageBuffer = 256
+
+// LiveUsageEvent 实时请求事件（管理后台实时流量视图）
+type LiveUsageEvent struct {
+	Timestamp    time.Time `json:"timestamp"`
+	RequestID    string    `json:"request_id,omitempty"`
+	UserID       int64     `json:"user_id,omitempty"`
+	APIKeyID     int64     `json:"api_key_id,omitempty"`
+	APIKeyName   string    `json:"api_key_name,omitempty"`
+	AccountID    int64     `json:"account_id,omitempty"`
+	Platform     string    `json:"platform,omitempty"`
+	Model        string    `json:"model"`
+	Stream       bool      `json:"stream"`
+	StatusCode   int       `json:"status_code"`
+	ErrorClass   string    `json:"error_class,omitempty"`
+	InputTokens  int       `json:"input_tokens"`
+	OutputTokens int       `json:"output_tokens"`
+	CacheTokens  int       `json:"cache_tokens"`
+	ActualCost   float64   `json:"actual_cost"`
+	DurationMs   *int      `json:"duration_ms,omitempty"`
+	FirstTokenMs *int      `json:"first_token_ms,omitempty"`
+}
+
+// LiveUsageFilter 订阅端的服务端过滤与采样条件，空列表表示不限制
+type LiveUsageFilter struct {
+	Models     []string
+	Platforms  []string
+	UserIDs    []int64
+	APIKeyIDs  []int64
+	AccountIDs []int64
+	ErrorsOnly bool
+	// SampleRate 采样比例 (0, 1]，0 或 1 表示全量；按匹配事件均匀采样
+	SampleRate float64
+}
+
+// Match 判断事件是否满足过滤条件（不含采样）
+func (f LiveUsageFilter) Match(ev *LiveUsageEvent) bool {
+	if ev == nil {
+		return false
+	}
+	if f.ErrorsOnly && ev.StatusCode < 400 {
+		return false
+	}
+	if len(f.Models) > 0 && !containsFoldString(f.Models, ev.Model) {
+		return false
+	}
+	if len(f.Platforms) > 0 && !containsFoldString(f.Platforms, ev.Platform) {
+		return false
+	}
+	if len(f.UserIDs) > 0 && !containsInt64(f.UserIDs, ev.UserID) {
+		return false
+	}
+	if len(f.APIKeyIDs) > 0 && !containsInt64(f.APIKeyIDs, ev.APIKeyID) {
+		return false
+	}
+	if len(f.AccountIDs) > 0 && !containsInt64(f.AccountIDs, ev.AccountID) {
+		return false
+	}
+	return true
+}
+
+// LiveUsageHub 进程内实时请求事件分发。
+// 发布端不阻塞：没有订阅时直接返回，订阅缓冲已满时丢弃并计数。
+type LiveUsageHub struct {
+	mu   sync.RWMutex
+	subs map[*LiveUsageSubscription]struct{}
+	// active 订阅数快照，发布热路径无订阅时免锁返回
+	active atomic.Int32
+}
+
+// NewLiveUsageHub 创建实时请求事件分发器
+func NewLiveUsageHub() *LiveUsageHub {
+	return &LiveUsageHub{subs: make(map[*LiveUsageSubscription]struct{})}
+}
+
+// LiveUsageSubscription 单个实时事件订阅
+type LiveUsageSubscription struct {
+	hub     *LiveUsageHub
+	filter  LiveUsageFilter
+	ch      chan *LiveUsageEvent
+	dropped atomic.Int64
+
+	sampleMu  sync.Mutex
+	sampleAcc float64
+	closeOnce sync.Once
+}
+
+// Subscribe 注册订阅；buffer <= 0 时使用默认缓冲。调用方结束时必须 Close。
+func (h *LiveUsageHub) Subscribe(filter LiveUsageFilter, buffer int) *LiveUsageSubscription {
+	if buffer <= 0 {
+		buffer = defaultLiveUsageBuffer
+	}
+	if filter.SampleRate < 0 || filter.SampleRate > 1 {
+		filter.SampleRate = 0
+	}
+	sub := &LiveUsageSubscription{hub: h, filter: filter, ch: make(chan *LiveUsageEvent, buffer)}
+	h.mu.Lock()
+	h.subs[sub] = struct{}{}
+	h.active.Store(int32(len(h.subs)))
+	h.mu.Unlock()
+	return sub
+}
+
+// Events 返回事件通道；订阅关闭后通道关闭
+func (s *LiveUsageSubscription) Events() <-chan *LiveUsageEvent {
+	return s.ch
+}
+
+// TakeDropped 返回并清零自上次调用以来因缓冲已满丢弃的事件数
+func (s *LiveUsageSubscription) TakeDropped() int64 {
+	return s.dropped.Swap(0)
+}
+
+// Close 取消订阅，可重复调用
+func (s *LiveUsageSubscription) Close() {
+	s.closeOnce.Do(func() {
+		h := s.hub
+		h.mu.Lock()
+		delete(h.subs, s)
+		h.active.Store(int32(len(h.subs)))
+		close(s.ch)
+		h.mu.Unlock()
+	})
+}
+
+func (s *LiveUsageSubscription) sample() bool {
+	rate := s.filter.SampleRate
+	if rate <= 0 || rate >= 1 {
+		return true
+	}
+	s.sampleMu.Lock()
+	defer s.sampleMu.Unlock()
+	s.sampleAcc += rate
+	if s.sampleAcc >= 1 {
+		s.sampleAcc--
+		return true
+	}
+	return false
+}
+
+// Publish 将事件分发给匹配的订阅，对 nil 安全
+func (h *LiveUsageHub) Publish(ev *LiveUsageEvent) {
+	if h == nil || ev == nil || h.active.Load() == 0 {
+		return
+	}
+	if ev.Timestamp.IsZero() {
+		ev.Timestamp = time.Now()
+	}
+	h.mu.RLock()
+	defer h.mu.RUnlock()
+	for sub := range h.subs {
+		if !sub.filter.Match(ev) || !sub.sample() {
+			continue
+		}
+		select {
+		case sub.ch <- ev:
+		default:
+			sub.dropped.Add(1)
+		}
+	}
+}
+
+// publishUsageLog 发布一条成功请求事件（来自已记录的使用日志）
+func (h *LiveUsageHub) publishUsageLog(usageLog *UsageLog, account *Account, apiKey *APIKey) {
+	if h == nil || usageLog == nil || h.active.Load() == 0 {
+		return
+	}
+	ev := &LiveUsageEvent{
+		Timestamp:    usageLog.CreatedAt,
+		RequestID:    usageLog.RequestID,
+		UserID:       usageLog.UserID,
+		APIKeyID:     usageLog.APIKeyID,
+		AccountID:    usageLog.AccountID,
+		Model:        usageLog.Model,
+		Stream:       usageLog.Stream,
+		StatusCode:   200,
+		InputTokens:  usageLog.InputTokens,
+		OutputTokens: usageLog.OutputTokens,
+		CacheTokens:  usageLog.CacheCreationTokens + usageLog.CacheReadTokens,
+		ActualCost:   usageLog.ActualCost,
+		DurationMs:   usageLog.DurationMs,
+		FirstTokenMs: usageLog.FirstTokenMs,
+	}
+	if account != nil {
+		ev.Platform = account.Platform
+	}
+	if apiKey != nil {
+		ev.APIKeyName = apiKey.Name
+	}
+	h.Publish(ev)
+}
+
+// publishUpstreamError 发布一条直接返回给客户端的上游错误事件
+func (h *LiveUsageHub) publishUpstreamError(ctx context.Context, c *gin.Context, account *Account, status int, class UpstreamErrorClass) {
+	if h == nil || h.active.Load() == 0 {
+		return
+	}
+	ev := &LiveUsageEvent{StatusCode: status, ErrorClass: string(class)}
+	if account != nil {
+		ev.AccountID = account.ID
+		ev.Platform = account.Platform
+	}
+	if ctx != nil {
+		ev.Model, _ = ctx.Value(ctxkey.Model).(string)
+		ev.RequestID, _ = ctx.Value(ctxkey.ClientRequestID).(string)
+	}
+	if c != nil {
+		if v, ok := c.Get("api_key"); ok {
+			if apiKey, ok := v.(*APIKey); ok && apiKey != nil {
+				ev.APIKeyID = apiKey.ID
+				ev.APIKeyName = apiKey.Name
+				ev.UserID = apiKey.UserID
+			}
+		}
+	}
+	h.Publish(ev)
+}
+
+func containsFoldString(list []string, v string) bool {
+	for _, item := range list {
+		if strings.EqualFold(item, v) {
+			return true
+		}
+	}
+	return false
+}
@@ -0,0 +1,105 @@
+//go:build unit
+
+package service
+
+import (
+	"testing"
+
+	"github.com/stretchr/testify/require"
+)
+
+func drainLiveUsage(sub *LiveUsageSubscription) []*LiveUsageEvent {
+	var out []*LiveUsageEvent
+	for {
+		select {
+		case ev := <-sub.Events():
+			out = append(out, ev)
+		default:
+			return out
+		}
+	}
+}
+
+func TestLiveUsageFilter_Match(t *testing.T) {
+	ev := &LiveUsageEvent{Model: "claude-sonnet-4-5", Platform: PlatformAnthropic, APIKeyID: 7, AccountID: 3, StatusCode: 200}
+
+	require.True(t, LiveUsageFilter{}.Match(ev))
+	require.True(t, LiveUsageFilter{Models: []string{"Claude-Sonnet-4-5"}, APIKeyIDs: []int64{7}}.Match(ev))
+	require.False(t, LiveUsageFilter{Models: []string{"gpt-5"}}.Match(ev))
+	require.False(t, LiveUsageFilter{AccountIDs: []int64{4}}.Match(ev))
+	require.False(t, LiveUsageFilter{ErrorsOnly: true}.Match(ev))
+
+	ev.StatusCode = 429
+	require.True(t, LiveUsageFilter{ErrorsOnly: true, Platforms: []string{PlatformAnthropic}}.Match(ev))
+}
+
+func TestLiveUsageHub_PublishFiltersAndSamples(t *testing.T) {
+	hub := NewLiveUsageHub()
+	all := hub.Subscribe(LiveUsageFilter{}, 0)
+	sampled := hub.Subscribe(LiveUsageFilter{SampleRate: 0.25}, 0)
+	gptOnly := hub.Subscribe(LiveUsageFilter{Models: []string{"gpt-5"}}, 0)
+	defer all.Close()
+	defer sampled.Close()
+	defer gptOnly.Close()
+
+	for i := 0; i < 8; i++ {
+		hub.Publish(&LiveUsageEvent{Model: "claude-sonnet-4-5", StatusCode: 200})
+	}
+	hub.Publish(&LiveUsageEvent{Model: "gpt-5", StatusCode: 200})
+
+	require.Len(t, drainLiveUsage(all), 9)
+	require.Len(t, drainLiveUsage(sampled), 2)
+	got := drainLiveUsage(gptOnly)
+	require.Len(t, got, 1)
+	require.False(t, got[0].Timestamp.IsZero())
+}
+
+func TestLiveUsageHub_DropsWhenBufferFull(t *testing.T) {
+	hub := NewLiveUsageHub()
+	sub := hub.Subscribe(LiveUsageFilter{}, 2)
+
+	for i := 0; i < 5; i++ {
+		hub.Publish(&LiveUsageEvent{Model: "m"})
+	}
+	require.Len(t, drainLiveUsage(sub), 2)
+	require.Equal(t, int64(3), sub.TakeDropped())
+	require.Equal(t, int64(0), sub.TakeDropped())
+
+	sub.Close()
+	sub.Close()
+	_, open := <-sub.Events()
+	require.False(t, open)
+	// 无订阅时发布直接返回
+	hub.Publish(&LiveUsageEvent{Model: "m"})
+
+	var nilHub *LiveUsageHub
+	nilHub.Publish(&LiveUsageEvent{Model: "m"})
+	nilHub.publishUsageLog(&UsageLog{}, nil, nil)
+}
+
+func TestLiveUsageHub_PublishUsageLog(t *testing.T) {
+	hub := NewLiveUsageHub()
+	sub := hub.Subscribe(LiveUsageFilter{}, 0)
+	defer sub.Close()
+
+	duration := 1200
+	hub.publishUsageLog(&UsageLog{
+		RequestID:       "req-1",
+		UserID:          1,
+		APIKeyID:        2,
+		AccountID:       3,
+		Model:           "gpt-5",
+		InputTokens:     10,
+		OutputTokens:    20,
+		CacheReadTokens: 5,
+		DurationMs:      &duration,
+	}, &Account{Platform: PlatformOpenAI}, &APIKey{Name: "team-a"})
+
+	got := drainLiveUsage(sub)
+	require.Len(t, got, 1)
+	require.Equal(t, PlatformOpenAI, got[0].Platform)
+	require.Equal(t, "team-a", got[0].APIKeyName)
+	require.Equal(t, 200, got[0].StatusCode)
+	require.Equal(t, 5, got[0].CacheTokens)
+	require.Equal(t, &duration, got[0].DurationMs)
+}
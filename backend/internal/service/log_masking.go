@@ -0,0 +1,284 @@
+package service
+
+import (
+	"bytes"
+	"context"
+	"encoding/json"
+	"fmt"
+	"regexp"
+	"sort"
+	"strings"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	infraerrors "github.com/Wei-Shaw/sub2api/internal/pkg/errors"
+)
+
+// logMaskMaxDepth 脱敏 JSON 时的最大递归深度
+const logMaskMaxDepth = 32
+
+var (
+	ErrLogMaskingProfileNotFound = infraerrors.NotFound("LOG_MASKING_PROFILE_NOT_FOUND", "log masking profile not found")
+	ErrUnknownLogMaskingProfile  = infraerrors.BadRequest("UNKNOWN_LOG_MASKING_PROFILE", "masking_profile is not defined in log_masking.profiles")
+)
+
+var (
+	logMaskCreditCardPattern = regexp.MustCompile(`\b(?:\d[ -]?){12,18}\d\b`)
+	logMaskCNIDPattern       = regexp.MustCompile(`\b\d{17}[\dXx]\b`)
+	logMaskUSSSNPattern      = regexp.MustCompile(`\b\d{3}-\d{2}-\d{4}\b`)
+	logMaskUKNINOPattern     = regexp.MustCompile(`(?i)\b[A-CEGHJ-PR-TW-Z][A-CEGHJ-NPR-TW-Z] ?\d{2} ?\d{2} ?\d{2} ?[A-D]\b`)
+)
+
+// logMaskRule 一条脱敏规则；validate 非空时仅替换通过校验的匹配（如 Luhn、身份证校验码）
+type logMaskRule struct {
+	name        string
+	re          *regexp.Regexp
+	validate    func(string) bool
+	replacement string
+}
+
+// LogMasker 编译后的脱敏配置档，nil 表示不脱敏
+type LogMasker struct {
+	rules []logMaskRule
+}
+
+func newLogMasker(pc config.LogMaskingProfileConfig) (*LogMasker, error) {
+	m := &LogMasker{}
+	add := func(name string, re *regexp.Regexp, validate func(string) bool) {
+		m.rules = append(m.rules, logMaskRule{name: name, re: re, validate: validate, replacement: "[" + strings.ToUpper(name) + "]"})
+	}
+	// 证件号规则更具体（带校验码），先于银行卡号执行，避免 18 位身份证号恰好通过 Luhn 校验时被标记为银行卡号
+	for _, idType := range pc.NationalIDs {
+		switch idType {
+		case config.LogMaskCNID:
+			add(idType, logMaskCNIDPattern, isValidCNID)
+		case config.LogMaskUSSSN:
+			add(idType, logMaskUSSSNPattern, isValidUSSSN)
+		case config.LogMaskUKNINO:
+			add(idType, logMaskUKNINOPattern, nil)
+		}
+	}
+	if pc.CreditCards {
+		add("credit_card", logMaskCreditCardPattern, isLuhnCardNumber)
+	}
+	names := make([]string, 0, len(pc.Patterns))
+	for name := range pc.Patterns {
+		names = append(names, name)
+	}
+	sort.Strings(names)
+	for _, name := range names {
+		re, err := regexp.Compile(pc.Patterns[name])
+		if err != nil {
+			return nil, fmt.Errorf("patterns.%s is not a valid regex: %w", name, err)
+		}
+		add(name, re, nil)
+	}
+	return m, nil
+}
+
+// Mask 返回脱敏后的文本
+func (m *LogMasker) Mask(text string) string {
+	masked, _ := m.mask(text)
+	return masked
+}
+
+// mask 依次应用各规则，返回脱敏后的文本及各规则的替换次数
+func (m *LogMasker) mask(text string) (string, map[string]int) {
+	if m == nil || text == "" {
+		return text, nil
+	}
+	var counts map[string]int
+	for _, rule := range m.rules {
+		text = rule.re.ReplaceAllStringFunc(text, func(match string) string {
+			if rule.validate != nil && !rule.validate(match) {
+				return match
+			}
+			if counts == nil {
+				counts = make(map[string]int)
+			}
+			counts[rule.name]++
+			return rule.replacement
+		})
+	}
+	return text, counts
+}
+
+// MaskJSON 对 JSON 中的字符串值（不含键名）脱敏；非 JSON 内容按纯文本处理
+func (m *LogMasker) MaskJSON(raw []byte) []byte {
+	if m == nil || len(raw) == 0 {
+		return raw
+	}
+	if !json.Valid(raw) {
+		return []byte(m.Mask(string(raw)))
+	}
+	dec := json.NewDecoder(bytes.NewReader(raw))
+	dec.UseNumber()
+	var value any
+	if err := dec.Decode(&value); err != nil {
+		return []byte(m.Mask(string(raw)))
+	}
+	encoded, err := json.Marshal(m.MaskValue(value))
+	if err != nil {
+		return []byte(m.Mask(string(raw)))
+	}
+	return encoded
+}
+
+// MaskValue 递归脱敏 map / slice 中的字符串值
+func (m *LogMasker) MaskValue(value any) any {
+	if m == nil {
+		return value
+	}
+	return m.maskValueWithDepth(value, 0)
+}
+
+func (m *LogMasker) maskValueWithDepth(value any, depth int) any {
+	if depth > logMaskMaxDepth {
+		return value
+	}
+	switch v := value.(type) {
+	case string:
+		return m.Mask(v)
+	case map[string]any:
+		out := make(map[string]any, len(v))
+		for k, item := range v {
+			out[k] = m.maskValueWithDepth(item, depth+1)
+		}
+		return out
+	case []any:
+		out := make([]any, len(v))
+		for i, item := range v {
+			out[i] = m.maskValueWithDepth(item, depth+1)
+		}
+		return out
+	default:
+		return value
+	}
+}
+
+func isLuhnCardNumber(match string) bool {
+	sum, digits := 0, 0
+	for i := len(match) - 1; i >= 0; i-- {
+		ch := match[i]
+		if ch < '0' || ch > '9' {
+			continue
+		}
+		d := int(ch - '0')
+		if digits%2 == 1 {
+			d *= 2
+			if d > 9 {
+				d -= 9
+			}
+		}
+		sum += d
+		digits++
+	}
+	return digits >= 13 && digits <= 19 && sum%10 == 0
+}
+
+func isValidCNID(match string) bool {
+	weights := [17]int{7, 9, 10, 5, 8, 4, 2, 1, 6, 3, 7, 9, 10, 5, 8, 4, 2}
+	sum := 0
+	for i, w := range weights {
+		sum += int(match[i]-'0') * w
+	}
+	return strings.ToUpper(match[17:]) == string("10X98765432"[sum%11])
+}
+
+func isValidUSSSN(match string) bool {
+	area, group, serial := match[0:3], match[4:6], match[7:11]
+	return area != "000" && area != "666" && area[0] != '9' && group != "00" && serial != "0000"
+}
+
+// LogMaskingProfiles 编译后的 log_masking 配置档，启动时创建并注入各使用方；nil 表示未配置（不脱敏）
+type LogMaskingProfiles struct {
+	profiles       map[string]*LogMasker
+	defaultProfile string
+}
+
+// NewLogMaskingProfiles 编译 log_masking 配置档，任一自定义正则无法编译时返回错误
+func NewLogMaskingProfiles(cfg config.LogMaskingConfig) (*LogMaskingProfiles, error) {
+	p := &LogMaskingProfiles{profiles: make(map[string]*LogMasker, len(cfg.Profiles)), defaultProfile: cfg.DefaultProfile}
+	for name, pc := range cfg.Profiles {
+		masker, err := newLogMasker(pc)
+		if err != nil {
+			return nil, fmt.Errorf("log_masking.profiles.%s.%w", name, err)
+		}
+		p.profiles[name] = masker
+	}
+	return p, nil
+}
+
+func (p *LogMaskingProfiles) lookup(name string) (*LogMasker, bool) {
+	if p == nil {
+		return nil, false
+	}
+	if name == "" {
+		name = p.defaultProfile
+	}
+	masker, ok := p.profiles[name]
+	return masker, ok
+}
+
+// Default 返回默认配置档；未配置时返回 nil（不脱敏）
+func (p *LogMaskingProfiles) Default() *LogMasker {
+	masker, _ := p.lookup("")
+	return masker
+}
+
+// For 返回请求使用的配置档：API Key / 分组策略的 masking_profile 优先，
+// 该配置档已从配置文件中移除时回退到默认配置档
+func (p *LogMaskingProfiles) For(ctx context.Context) *LogMasker {
+	if masker, ok := p.lookup(APIKeyPolicyFromContext(ctx).MaskingProfile); ok {
+		return masker
+	}
+	return p.Default()
+}
+
+// Names 返回已配置的配置档名称（已排序）及默认配置档
+func (p *LogMaskingProfiles) Names() (names []string, defaultProfile string) {
+	if p == nil {
+		return []string{}, ""
+	}
+	names = make([]string, 0, len(p.profiles))
+	for name := range p.profiles {
+		names = append(names, name)
+	}
+	sort.Strings(names)
+	return names, p.defaultProfile
+}
+
+// LogMaskingPreview 脱敏预览结果
+type LogMaskingPreview struct {
+	Profile string `json:"profile"`
+	Masked  string `json:"masked"`
+	// Matches 各规则的替换次数
+	Matches map[string]int `json:"matches"`
+}
+
+// Preview 返回文本按指定配置档（为空时为默认配置档）脱敏后的结果，供管理员验证规则
+func (p *LogMaskingProfiles) Preview(profile, text string) (*LogMaskingPreview, error) {
+	profile = strings.TrimSpace(profile)
+	if profile == "" {
+		_, profile = p.Names()
+	}
+	masker, ok := p.lookup(profile)
+	if !ok {
+		return nil, ErrLogMaskingProfileNotFound
+	}
+	masked, counts := masker.mask(text)
+	if counts == nil {
+		counts = map[string]int{}
+	}
+	return &LogMaskingPreview{Profile: profile, Masked: masked, Matches: counts}, nil
+}
+
+// validate 校验策略引用的配置档是否存在
+func (p *LogMaskingProfiles) validate(name string) error {
+	if name == "" {
+		return nil
+	}
+	if _, ok := p.lookup(name); !ok {
+		return ErrUnknownLogMaskingProfile
+	}
+	return nil
+}
@@ -0,0 +1,124 @@
+//go:build unit
+
+package service
+
+import (
+	"context"
+	"testing"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/stretchr/testify/require"
+)
+
+func newLogMaskingProfilesForTest(t *testing.T, cfg config.LogMaskingConfig) *LogMaskingProfiles {
+	t.Helper()
+	profiles, err := NewLogMaskingProfiles(cfg)
+	require.NoError(t, err)
+	return profiles
+}
+
+func TestLogMasker_BuiltinRules(t *testing.T) {
+	masker, err := newLogMasker(config.LogMaskingProfileConfig{
+		CreditCards: true,
+		NationalIDs: []string{config.LogMaskCNID, config.LogMaskUSSSN, config.LogMaskUKNINO},
+	})
+	require.NoError(t, err)
+
+	tests := []struct {
+		name string
+		in   string
+		want string
+	}{
+		{"card with spaces", "card 4111 1111 1111 1111 exp 12/30", "card [CREDIT_CARD] exp 12/30"},
+		{"card failing luhn is kept", "order 4111111111111112", "order 4111111111111112"},
+		{"cn id", "身份证11010519491231002X，请核对", "身份证[CN_ID]，请核对"},
+		{"cn id with bad checksum is kept", "id 110105194912310021", "id 110105194912310021"},
+		{"us ssn", "ssn 123-45-6789", "ssn [US_SSN]"},
+		{"invalid us ssn is kept", "ssn 000-45-6789", "ssn 000-45-6789"},
+		{"uk nino", "NI number AB 12 34 56 C", "NI number [UK_NINO]"},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			require.Equal(t, tt.want, masker.Mask(tt.in))
+		})
+	}
+}
+
+func TestLogMasker_MaskJSON(t *testing.T) {
+	masker, err := newLogMasker(config.LogMaskingProfileConfig{
+		CreditCards: true,
+		Patterns:    map[string]string{"email": `[\w.+-]+@[\w-]+\.[\w.]+`},
+	})
+	require.NoError(t, err)
+	raw := []byte(`{"model":"claude","max_tokens":1234567890123456,"messages":[{"role":"user","content":"mail a@b.com, card 4111111111111111"}]}`)
+	require.JSONEq(t,
+		`{"model":"claude","max_tokens":1234567890123456,"messages":[{"role":"user","content":"mail [EMAIL], card [CREDIT_CARD]"}]}`,
+		string(masker.MaskJSON(raw)))
+
+	// 非 JSON 按纯文本处理
+	require.Equal(t, "data: [EMAIL]", string(masker.MaskJSON([]byte("data: x@y.io"))))
+
+	var nilMasker *LogMasker
+	require.Equal(t, raw, nilMasker.MaskJSON(raw))
+}
+
+func TestNewLogMaskingProfiles_RejectsInvalidPattern(t *testing.T) {
+	_, err := NewLogMaskingProfiles(config.LogMaskingConfig{
+		Profiles: map[string]config.LogMaskingProfileConfig{
+			"pii": {CreditCards: true, Patterns: map[string]string{"ok": `TICKET-\d+`, "bad": "(unclosed"}},
+		},
+	})
+	require.Error(t, err)
+	require.Contains(t, err.Error(), "log_masking.profiles.pii.patterns.bad")
+}
+
+func TestLogMaskingProfiles_For(t *testing.T) {
+	profiles := newLogMaskingProfilesForTest(t, config.LogMaskingConfig{
+		DefaultProfile: "basic",
+		Profiles: map[string]config.LogMaskingProfileConfig{
+			"basic":  {CreditCards: true},
+			"strict": {CreditCards: true, NationalIDs: []string{config.LogMaskUSSSN}},
+		},
+	})
+	text := "4111111111111111 / 123-45-6789"
+
+	require.Equal(t, "[CREDIT_CARD] / 123-45-6789", profiles.For(context.Background()).Mask(text))
+
+	ctx := WithAPIKeyPolicy(context.Background(), &APIKeyPolicy{MaskingProfile: "strict"})
+	require.Equal(t, "[CREDIT_CARD] / [US_SSN]", profiles.For(ctx).Mask(text))
+
+	// 配置档已移除时回退到默认配置档
+	ctx = WithAPIKeyPolicy(context.Background(), &APIKeyPolicy{MaskingProfile: "removed"})
+	require.Equal(t, "[CREDIT_CARD] / 123-45-6789", profiles.For(ctx).Mask(text))
+
+	var unconfigured *LogMaskingProfiles
+	require.Nil(t, unconfigured.For(ctx))
+
+	svc := &APIKeyPolicyService{logMasking: profiles}
+	policy := &APIKeyPolicy{MaskingProfile: " strict "}
+	require.NoError(t, svc.normalize(policy))
+	require.Equal(t, "strict", policy.MaskingProfile)
+	require.ErrorIs(t, svc.normalize(&APIKeyPolicy{MaskingProfile: "missing"}), ErrUnknownLogMaskingProfile)
+}
+
+func TestLogMaskingProfiles_Preview(t *testing.T) {
+	var unconfigured *LogMaskingProfiles
+	_, err := unconfigured.Preview("", "anything")
+	require.ErrorIs(t, err, ErrLogMaskingProfileNotFound)
+
+	profiles := newLogMaskingProfilesForTest(t, config.LogMaskingConfig{
+		DefaultProfile: "pii",
+		Profiles: map[string]config.LogMaskingProfileConfig{
+			"pii": {CreditCards: true, Patterns: map[string]string{"ticket": `TICKET-\d+`}},
+		},
+	})
+
+	preview, err := profiles.Preview("", "TICKET-42 paid with 4111-1111-1111-1111, see TICKET-43")
+	require.NoError(t, err)
+	require.Equal(t, "pii", preview.Profile)
+	require.Equal(t, "[TICKET] paid with [CREDIT_CARD], see [TICKET]", preview.Masked)
+	require.Equal(t, map[string]int{"credit_card": 1, "ticket": 2}, preview.Matches)
+
+	_, err = profiles.Preview("other", "x")
+	require.ErrorIs(t, err, ErrLogMaskingProfileNotFound)
+}
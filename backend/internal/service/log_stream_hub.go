@@ -0,0 +1,244 @@
+package service
+
+import (
+	"strings"
+	"sync"
+	"sync/atomic"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/pkg/logger"
+	"github.com/Wei-Shaw/sub2api/internal/util/logredact"
+)
+
+const (
+	// defaultLogStreamBuffer 每个订阅的日志缓冲，订阅端消费不及时时丢弃新日志而不是阻塞日志写入
+	defaultLogStreamBuffer = 512
+	// LogStreamDefaultRate 订阅未指定速率时每秒最多推送的日志条数
+	LogStreamDefaultRate = 50
+	// LogStreamMaxRate 单个订阅每秒推送条数上限
+	LogStreamMaxRate = 500
+)
+
+// logStreamLevelRank 日志级别排序，未知级别按 info 处理
+var logStreamLevelRank = map[string]int{
+	"debug":   0,
+	"info":    1,
+	"warn":    2,
+	"warning": 2,
+	"error":   3,
+	"dpanic":  4,
+	"panic":   4,
+	"fatal":   4,
+}
+
+// ValidLogStreamLevel 判断是否为可用于过滤的日志级别
+func ValidLogStreamLevel(level string) bool {
+	_, ok := logStreamLevelRank[strings.ToLower(strings.TrimSpace(level))]
+	return ok
+}
+
+func logStreamLevel(level string) int {
+	if rank, ok := logStreamLevelRank[strings.ToLower(strings.TrimSpace(level))]; ok {
+		return rank
+	}
+	return logStreamLevelRank["info"]
+}
+
+// logEventComponent 业务组件名优先取字段 component（zap 的 LoggerName 往往为空），与系统日志索引一致
+func logEventComponent(event *logger.LogEvent) string {
+	if event.Fields != nil {
+		if fc := asString(event.Fields["component"]); fc != "" {
+			return fc
+		}
+	}
+	if component := strings.TrimSpace(event.Component); component != "" {
+		return component
+	}
+	return "app"
+}
+
+// LogStreamFilter 订阅端的服务端过滤条件
+type LogStreamFilter struct {
+	// MinLevel 最低日志级别（debug/info/warn/error），空表示 info；低于全局日志级别的日志不会产生
+	MinLevel string
+	// Components 组件名子串（不区分大小写），空表示不限制
+	Components []string
+	// Query 消息子串（不区分大小写），空表示不限制
+	Query string
+	// RatePerSecond 每秒最多推送的日志条数，超出部分丢弃并计数；<= 0 时使用默认值
+	RatePerSecond int
+}
+
+// Match 判断日志是否满足过滤条件（不含限速）
+func (f LogStreamFilter) Match(event *logger.LogEvent) bool {
+	if event == nil {
+		return false
+	}
+	minLevel := f.MinLevel
+	if minLevel == "" {
+		minLevel = "info"
+	}
+	if logStreamLevel(event.Level) < logStreamLevel(minLevel) {
+		return false
+	}
+	if len(f.Components) > 0 {
+		component := strings.ToLower(logEventComponent(event))
+		matched := false
+		for _, item := range f.Components {
+			if strings.Contains(component, strings.ToLower(item)) {
+				matched = true
+				break
+			}
+		}
+		if !matched {
+			return false
+		}
+	}
+	if f.Query != "" && !strings.Contains(strings.ToLower(event.Message), strings.ToLower(f.Query)) {
+		return false
+	}
+	return true
+}
+
+// LogStreamEntry 推送给订阅端的日志条目（已脱敏）
+type LogStreamEntry struct {
+	Time      time.Time      `json:"time"`
+	Level     string         `json:"level"`
+	Component string         `json:"component"`
+	Message   string         `json:"message"`
+	Fields    map[string]any `json:"fields,omitempty"`
+}
+
+// NewEntry 按系统日志索引相同的规则（敏感字段 + 默认脱敏配置档）脱敏日志
+func (h *LogStreamHub) NewEntry(event *logger.LogEvent) *LogStreamEntry {
+	masker := h.logMasking.Default()
+	entry := &LogStreamEntry{
+		Time:      event.Time.UTC(),
+		Level:     strings.ToLower(strings.TrimSpace(event.Level)),
+		Component: logEventComponent(event),
+		Message:   masker.Mask(logredact.RedactText(strings.TrimSpace(event.Message))),
+	}
+	if len(event.Fields) > 0 {
+		fields := copyMap(event.Fields)
+		delete(fields, "component")
+		if len(fields) > 0 {
+			entry.Fields, _ = masker.MaskValue(logredact.RedactMap(fields)).(map[string]any)
+		}
+	}
+	return entry
+}
+
+// LogStreamHub 进程内日志实时分发（管理后台实时日志），作为 logger.Sink 接收结构化日志。
+// 写入端不阻塞：没有订阅时直接返回，订阅缓冲已满或超出速率时丢弃并计数。
+type LogStreamHub struct {
+	mu   sync.RWMutex
+	subs map[*LogStreamSubscription]struct{}
+	// active 订阅数快照，日志热路径无订阅时免锁返回
+	active atomic.Int32
+
+	logMasking *LogMaskingProfiles
+}
+
+// NewLogStreamHub 创建日志实时分发器
+func NewLogStreamHub(logMasking *LogMaskingProfiles) *LogStreamHub {
+	return &LogStreamHub{subs: make(map[*LogStreamSubscription]struct{}), logMasking: logMasking}
+}
+
+// LogStreamSubscription 单个日志订阅
+type LogStreamSubscription struct {
+	hub         *LogStreamHub
+	filter      LogStreamFilter
+	ch          chan *logger.LogEvent
+	dropped     atomic.Int64
+	rateLimited atomic.Int64
+
+	limiterMu  sync.Mutex
+	tokens     float64
+	lastRefill time.Time
+	closeOnce  sync.Once
+}
+
+// Subscribe 注册订阅；buffer <= 0 时使用默认缓冲。调用方结束时必须 Close。
+func (h *LogStreamHub) Subscribe(filter LogStreamFilter, buffer int) *LogStreamSubscription {
+	if buffer <= 0 {
+		buffer = defaultLogStreamBuffer
+	}
+	if filter.RatePerSecond <= 0 {
+		filter.RatePerSecond = LogStreamDefaultRate
+	}
+	filter.RatePerSecond = min(filter.RatePerSecond, LogStreamMaxRate)
+	sub := &LogStreamSubscription{
+		hub:        h,
+		filter:     filter,
+		ch:         make(chan *logger.LogEvent, buffer),
+		tokens:     float64(filter.RatePerSecond),
+		lastRefill: time.Now(),
+	}
+	h.mu.Lock()
+	h.subs[sub] = struct{}{}
+	h.active.Store(int32(len(h.subs)))
+	h.mu.Unlock()
+	return sub
+}
+
+// Events 返回日志通道；订阅关闭后通道关闭
+func (s *LogStreamSubscription) Events() <-chan *logger.LogEvent {
+	return s.ch
+}
+
+// TakeDropped 返回并清零自上次调用以来因缓冲已满、超出速率丢弃的日志数
+func (s *LogStreamSubscription) TakeDropped() (dropped, rateLimited int64) {
+	return s.dropped.Swap(0), s.rateLimited.Swap(0)
+}
+
+// Close 取消订阅，可重复调用
+func (s *LogStreamSubscription) Close() {
+	s.closeOnce.Do(func() {
+		h := s.hub
+		h.mu.Lock()
+		delete(h.subs, s)
+		h.active.Store(int32(len(h.subs)))
+		close(s.ch)
+		h.mu.Unlock()
+	})
+}
+
+// allow 令牌桶限速，桶容量为每秒速率（允许 1 秒的突发）
+func (s *LogStreamSubscription) allow(now time.Time) bool {
+	rate := float64(s.filter.RatePerSecond)
+	s.limiterMu.Lock()
+	defer s.limiterMu.Unlock()
+	if elapsed := now.Sub(s.lastRefill).Seconds(); elapsed > 0 {
+		s.tokens = min(rate, s.tokens+elapsed*rate)
+		s.lastRefill = now
+	}
+	if s.tokens < 1 {
+		return false
+	}
+	s.tokens--
+	return true
+}
+
+// WriteLogEvent 实现 logger.Sink，对 nil 安全
+func (h *LogStreamHub) WriteLogEvent(event *logger.LogEvent) {
+	if h == nil || event == nil || h.active.Load() == 0 {
+		return
+	}
+	now := time.Now()
+	h.mu.RLock()
+	defer h.mu.RUnlock()
+	for sub := range h.subs {
+		if !sub.filter.Match(event) {
+			continue
+		}
+		if !sub.allow(now) {
+			sub.rateLimited.Add(1)
+			continue
+		}
+		select {
+		case sub.ch <- event:
+		default:
+			sub.dropped.Add(1)
+		}
+	}
+}
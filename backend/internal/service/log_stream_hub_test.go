@@ -0,0 +1,106 @@
+//go:build unit
+
+package service
+
+import (
+	"testing"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/logger"
+	"github.com/stretchr/testify/require"
+)
+
+func drainLogStream(sub *LogStreamSubscription) []*logger.LogEvent {
+	var out []*logger.LogEvent
+	for {
+		select {
+		case ev := <-sub.Events():
+			out = append(out, ev)
+		default:
+			return out
+		}
+	}
+}
+
+func TestLogStreamFilter_Match(t *testing.T) {
+	ev := &logger.LogEvent{Level: "warn", Component: "", Message: "Account 42 rate limited", Fields: map[string]any{"component": "service.gateway"}}
+
+	require.True(t, LogStreamFilter{}.Match(ev))
+	require.True(t, LogStreamFilter{MinLevel: "warn", Components: []string{"Gateway"}, Query: "RATE LIMITED"}.Match(ev))
+	require.False(t, LogStreamFilter{MinLevel: "error"}.Match(ev))
+	require.False(t, LogStreamFilter{Components: []string{"ops"}}.Match(ev))
+	require.False(t, LogStreamFilter{Query: "timeout"}.Match(ev))
+
+	debug := &logger.LogEvent{Level: "debug", Message: "tls_fingerprint_enabled"}
+	require.False(t, LogStreamFilter{}.Match(debug))
+	require.True(t, LogStreamFilter{MinLevel: "debug", Components: []string{"app"}}.Match(debug))
+}
+
+func TestLogStreamHub_RateLimitAndBuffer(t *testing.T) {
+	hub := NewLogStreamHub(nil)
+	limited := hub.Subscribe(LogStreamFilter{RatePerSecond: 3}, 0)
+	small := hub.Subscribe(LogStreamFilter{RatePerSecond: LogStreamMaxRate * 2}, 2)
+	errorsOnly := hub.Subscribe(LogStreamFilter{MinLevel: "error"}, 0)
+	defer limited.Close()
+	defer small.Close()
+	defer errorsOnly.Close()
+
+	for range 5 {
+		hub.WriteLogEvent(&logger.LogEvent{Time: time.Now(), Level: "info", Message: "m"})
+	}
+
+	require.Len(t, drainLogStream(limited), 3)
+	dropped, rateLimited := limited.TakeDropped()
+	require.Equal(t, int64(0), dropped)
+	require.Equal(t, int64(2), rateLimited)
+
+	require.Len(t, drainLogStream(small), 2)
+	dropped, rateLimited = small.TakeDropped()
+	require.Equal(t, int64(3), dropped)
+	require.Equal(t, int64(0), rateLimited)
+
+	require.Empty(t, drainLogStream(errorsOnly))
+
+	// 令牌按速率恢复
+	limited.limiterMu.Lock()
+	limited.lastRefill = limited.lastRefill.Add(-time.Second)
+	limited.limiterMu.Unlock()
+	hub.WriteLogEvent(&logger.LogEvent{Level: "info", Message: "m"})
+	require.Len(t, drainLogStream(limited), 1)
+
+	limited.Close()
+	limited.Close()
+	_, open := <-limited.Events()
+	require.False(t, open)
+
+	var nilHub *LogStreamHub
+	nilHub.WriteLogEvent(&logger.LogEvent{Message: "m"})
+	logger.MultiSink{hub, nilHub}.WriteLogEvent(&logger.LogEvent{Level: "error", Message: "m"})
+	require.Len(t, drainLogStream(errorsOnly), 1)
+}
+
+func TestLogStreamHub_NewEntryRedacts(t *testing.T) {
+	hub := NewLogStreamHub(newLogMaskingProfilesForTest(t, config.LogMaskingConfig{
+		DefaultProfile: "basic",
+		Profiles:       map[string]config.LogMaskingProfileConfig{"basic": {CreditCards: true}},
+	}))
+
+	entry := hub.NewEntry(&logger.LogEvent{
+		Time:    time.Date(2026, 1, 2, 3, 4, 5, 0, time.FixedZone("CST", 8*3600)),
+		Level:   "WARN",
+		Message: "card 4111111111111111 rejected",
+		Fields: map[string]any{
+			"component":    "handler.payment",
+			"access_token": "secret-token",
+			"account_id":   int64(7),
+		},
+	})
+	require.Equal(t, "warn", entry.Level)
+	require.Equal(t, "handler.payment", entry.Component)
+	require.Equal(t, "card [CREDIT_CARD] rejected", entry.Message)
+	require.Equal(t, time.UTC, entry.Time.Location())
+	require.NotContains(t, entry.Fields, "component")
+	require.Equal(t, "***", entry.Fields["access_token"])
+	require.Equal(t, int64(7), entry.Fields["account_id"])
+}
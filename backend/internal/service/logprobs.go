@@ -0,0 +1,49 @@
+package service
+
+import (
+	"slices"
+
+	"github.com/tidwall/gjson"
+)
+
+// LogprobsHeader 响应头：请求了 logprobs 但上游未提供时为 emulated，此时响应中的 logprobs 为网关生成的空结构
+const LogprobsHeader = "X-Sub2API-Logprobs"
+
+// logprobs 能力（/v1/models 的 capabilities.logprobs）
+const (
+	// LogprobsPassthrough 透传上游返回的 logprobs
+	LogprobsPassthrough = "passthrough"
+	// LogprobsEmulated 上游不提供 logprobs，返回标记为 emulated 的空结构，避免客户端因字段缺失报错
+	LogprobsEmulated = "emulated"
+)
+
+// completionMaxLogprobs Completions API 的 logprobs 取值上限
+const completionMaxLogprobs = 5
+
+// LogprobsCapability 分组平台的 logprobs 能力：仅 OpenAI 上游可能返回真实 logprobs
+func LogprobsCapability(platform string) string {
+	if platform == PlatformOpenAI {
+		return LogprobsPassthrough
+	}
+	return LogprobsEmulated
+}
+
+// responsesLogprobsRequested Responses 请求是否要求 logprobs（top_logprobs 或 include message.output_text.logprobs）
+func responsesLogprobsRequested(req gjson.Result) (bool, int64) {
+	top := req.Get("top_logprobs").Int()
+	included := slices.ContainsFunc(req.Get("include").Array(), func(v gjson.Result) bool {
+		return v.String() == "message.output_text.logprobs"
+	})
+	return top > 0 || included, top
+}
+
+// emulatedCompletionLogprobs Completions 响应中代替上游 logprobs 的空结构
+func emulatedCompletionLogprobs() map[string]any {
+	return map[string]any{
+		"tokens":         []string{},
+		"token_logprobs": []float64{},
+		"top_logprobs":   []any{},
+		"text_offset":    []int{},
+		"emulated":       true,
+	}
+}
@@ -0,0 +1,182 @@
+package service
+
+import (
+	"context"
+	"encoding/json"
+	"errors"
+	"fmt"
+	"strings"
+	"sync"
+	"time"
+
+	infraerrors "github.com/Wei-Shaw/sub2api/internal/pkg/errors"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/logger"
+)
+
+// maintenanceCacheTTL 维护模式配置的内存缓存时间；多实例部署下修改后最多延迟该时长生效
+const maintenanceCacheTTL = 5 * time.Second
+
+// DefaultMaintenanceMessage 未配置自定义提示时返回给客户端的消息
+const DefaultMaintenanceMessage = "Service is under scheduled maintenance, please retry later."
+
+var ErrMaintenanceInvalid = infraerrors.BadRequest("MAINTENANCE_INVALID", "ends_at must be after starts_at")
+
+// MaintenanceSettings 维护模式配置。仅作用于网关路由，健康检查与管理后台不受影响。
+type MaintenanceSettings struct {
+	Enabled bool   `json:"enabled"`
+	Message string `json:"message"`
+	// StartsAt / EndsAt 计划维护窗口，为空表示立即开始 / 不自动结束
+	StartsAt *time.Time `json:"starts_at,omitempty"`
+	EndsAt   *time.Time `json:"ends_at,omitempty"`
+	// AllowedPaths 维护期间仍放行的网关路径前缀，例如 /v1/models、/v1/usage
+	AllowedPaths []string `json:"allowed_paths"`
+	UpdatedBy    *int64   `json:"updated_by,omitempty"`
+}
+
+// ActiveAt 判断维护窗口在指定时间是否生效
+func (m *MaintenanceSettings) ActiveAt(now time.Time) bool {
+	if m == nil || !m.Enabled {
+		return false
+	}
+	if m.StartsAt != nil && now.Before(*m.StartsAt) {
+		return false
+	}
+	if m.EndsAt != nil && !now.Before(*m.EndsAt) {
+		return false
+	}
+	return true
+}
+
+// Allows 判断路径是否在维护放行列表中（按路径段前缀匹配）
+func (m *MaintenanceSettings) Allows(path string) bool {
+	for _, prefix := range m.AllowedPaths {
+		if path == prefix || strings.HasPrefix(path, strings.TrimSuffix(prefix, "/")+"/") {
+			return true
+		}
+	}
+	return false
+}
+
+// EffectiveMessage 返回给客户端的提示消息
+func (m *MaintenanceSettings) EffectiveMessage() string {
+	if m == nil || strings.TrimSpace(m.Message) == "" {
+		return DefaultMaintenanceMessage
+	}
+	return m.Message
+}
+
+// MaintenanceService 维护模式开关，配置存储在 settings 表
+type MaintenanceService struct {
+	settingRepo SettingRepository
+	now         func() time.Time
+
+	mu        sync.Mutex
+	cached    *MaintenanceSettings
+	expiresAt time.Time
+}
+
+// NewMaintenanceService 创建维护模式服务
+func NewMaintenanceService(settingRepo SettingRepository) *MaintenanceService {
+	return &MaintenanceService{settingRepo: settingRepo, now: time.Now}
+}
+
+// GetSettings 读取维护模式配置，未配置时返回关闭状态
+func (s *MaintenanceService) GetSettings(ctx context.Context) (*MaintenanceSettings, error) {
+	value, err := s.settingRepo.GetValue(ctx, SettingKeyMaintenanceSettings)
+	if err != nil {
+		if errors.Is(err, ErrSettingNotFound) {
+			return &MaintenanceSettings{AllowedPaths: []string{}}, nil
+		}
+		return nil, fmt.Errorf("get maintenance settings: %w", err)
+	}
+	settings := &MaintenanceSettings{}
+	if value != "" {
+		if err := json.Unmarshal([]byte(value), settings); err != nil {
+			return nil, fmt.Errorf("parse maintenance settings: %w", err)
+		}
+	}
+	if settings.AllowedPaths == nil {
+		settings.AllowedPaths = []string{}
+	}
+	return settings, nil
+}
+
+// UpdateSettings 保存维护模式配置，本实例立即生效
+func (s *MaintenanceService) UpdateSettings(ctx context.Context, settings *MaintenanceSettings) error {
+	if settings == nil {
+		return ErrMaintenanceInvalid
+	}
+	if settings.StartsAt != nil && settings.EndsAt != nil && !settings.EndsAt.After(*settings.StartsAt) {
+		return ErrMaintenanceInvalid
+	}
+	settings.Message = strings.TrimSpace(settings.Message)
+	paths := make([]string, 0, len(settings.AllowedPaths))
+	for _, p := range settings.AllowedPaths {
+		if p = strings.TrimSpace(p); p == "" {
+			continue
+		}
+		if !strings.HasPrefix(p, "/") {
+			p = "/" + p
+		}
+		paths = append(paths, p)
+	}
+	settings.AllowedPaths = paths
+
+	data, err := json.Marshal(settings)
+	if err != nil {
+		return fmt.Errorf("marshal maintenance settings: %w", err)
+	}
+	if err := s.settingRepo.Set(ctx, SettingKeyMaintenanceSettings, string(data)); err != nil {
+		return err
+	}
+
+	var updatedBy int64
+	if settings.UpdatedBy != nil {
+		updatedBy = *settings.UpdatedBy
+	}
+	logger.LegacyPrintf("service.maintenance", "AUDIT maintenance settings updated: enabled=%v starts_at=%v ends_at=%v by=%d",
+		settings.Enabled, settings.StartsAt, settings.EndsAt, updatedBy)
+
+	s.mu.Lock()
+	s.cached = settings
+	s.expiresAt = s.now().Add(maintenanceCacheTTL)
+	s.mu.Unlock()
+	return nil
+}
+
+// Check 判断网关请求是否因维护被拒绝，命中时返回当前配置。
+// 读取配置失败时放行，避免存储故障放大为全站不可用。
+func (s *MaintenanceService) Check(ctx context.Context, path string) (*MaintenanceSettings, bool) {
+	if s == nil {
+		return nil, false
+	}
+	settings := s.current(ctx)
+	if settings == nil || !settings.ActiveAt(s.now()) || settings.Allows(path) {
+		return nil, false
+	}
+	return settings, true
+}
+
+func (s *MaintenanceService) current(ctx context.Context) *MaintenanceSettings {
+	now := s.now()
+	s.mu.Lock()
+	if now.Before(s.expiresAt) {
+		cached := s.cached
+		s.mu.Unlock()
+		return cached
+	}
+	s.mu.Unlock()
+
+	settings, err := s.GetSettings(ctx)
+	if err != nil {
+		logger.LegacyPrintf("service.maintenance", "[Maintenance] load settings failed: %v", err)
+	}
+
+	s.mu.Lock()
+	defer s.mu.Unlock()
+	if err == nil {
+		s.cached = settings
+	}
+	s.expiresAt = now.Add(maintenanceCacheTTL)
+	return s.cached
+}
@@ -0,0 +1,66 @@
+//go:build unit
+
+package service
+
+import (
+	"context"
+	"errors"
+	"testing"
+	"time"
+
+	"github.com/stretchr/testify/require"
+)
+
+func TestMaintenanceSettings_ActiveAtAndAllows(t *testing.T) {
+	now := time.Date(2026, 1, 1, 12, 0, 0, 0, time.UTC)
+	start, end := now.Add(-time.Hour), now.Add(time.Hour)
+	m := &MaintenanceSettings{Enabled: true, StartsAt: &start, EndsAt: &end, AllowedPaths: []string{"/v1/models", "/v1beta/"}}
+
+	require.True(t, m.ActiveAt(now))
+	require.False(t, m.ActiveAt(start.Add(-time.Second)))
+	require.False(t, m.ActiveAt(end))
+	m.Enabled = false
+	require.False(t, m.ActiveAt(now))
+
+	require.True(t, m.Allows("/v1/models"))
+	require.True(t, m.Allows("/v1beta/models/gemini-pro"))
+	require.False(t, m.Allows("/v1/models_extra"))
+	require.False(t, m.Allows("/v1/messages"))
+	require.Equal(t, DefaultMaintenanceMessage, m.EffectiveMessage())
+}
+
+func TestMaintenanceService_CheckUsesScheduleAndCache(t *testing.T) {
+	repo := newRuntimeSettingRepoStub()
+	svc := NewMaintenanceService(repo)
+	now := time.Date(2026, 1, 1, 12, 0, 0, 0, time.UTC)
+	svc.now = func() time.Time { return now }
+	ctx := context.Background()
+
+	_, blocked := svc.Check(ctx, "/v1/messages")
+	require.False(t, blocked)
+
+	start := now.Add(10 * time.Second)
+	require.NoError(t, svc.UpdateSettings(ctx, &MaintenanceSettings{Enabled: true, Message: " migrating ", StartsAt: &start, AllowedPaths: []string{"v1/usage"}}))
+	_, blocked = svc.Check(ctx, "/v1/messages")
+	require.False(t, blocked)
+
+	now = now.Add(11 * time.Second)
+	settings, blocked := svc.Check(ctx, "/v1/messages")
+	require.True(t, blocked)
+	require.Equal(t, "migrating", settings.EffectiveMessage())
+	_, blocked = svc.Check(ctx, "/v1/usage")
+	require.False(t, blocked)
+
+	// 存储故障时沿用上次配置
+	repo.getValueFn = func(string) (string, error) { return "", errors.New("db down") }
+	now = now.Add(time.Minute)
+	_, blocked = svc.Check(ctx, "/v1/messages")
+	require.True(t, blocked)
+}
+
+func TestMaintenanceService_UpdateRejectsInvalidWindow(t *testing.T) {
+	svc := NewMaintenanceService(newRuntimeSettingRepoStub())
+	start := time.Now()
+	end := start.Add(-time.Minute)
+	require.ErrorIs(t, svc.UpdateSettings(context.Background(), &MaintenanceSettings{StartsAt: &start, EndsAt: &end}), ErrMaintenanceInvalid)
+}
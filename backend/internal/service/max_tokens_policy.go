@@ -0,0 +1,262 @@
+package service
+
+import (
+	"context"
+	"net/http"
+	"strings"
+
+	"github.com/Wei-Shaw/sub2api/internal/pkg/claude"
+	infraerrors "github.com/Wei-Shaw/sub2api/internal/pkg/errors"
+	"github.com/tidwall/gjson"
+	"github.com/tidwall/sjson"
+)
+
+// maxTokensPolicyMaxModels 单个策略允许的按模型覆盖条目数上限
+const maxTokensPolicyMaxModels = 100
+
+var (
+	ErrInvalidMaxTokensPolicy = infraerrors.BadRequest("INVALID_MAX_TOKENS_POLICY", "max_tokens default/ceiling must be non-negative and default must not exceed ceiling")
+	ErrContextWindowExceeded  = infraerrors.BadRequest("CONTEXT_WINDOW_EXCEEDED", "prompt plus requested completion exceeds the model context window")
+)
+
+// ModelTokenLimits 模型的 token 限制（来自动态价格数据），0 表示未知
+type ModelTokenLimits struct {
+	// MaxInput 上下文窗口；Anthropic 模型的输入与输出共享该窗口，其他模型仅限制输入
+	MaxInput  int
+	MaxOutput int
+}
+
+// MaxTokensLimit max_tokens 默认值与上限，0 表示不设置
+type MaxTokensLimit struct {
+	// Default 客户端未指定 max_tokens 时填充的值
+	Default int `json:"default,omitempty"`
+	// Ceiling 客户端请求值的上限，超出时下调
+	Ceiling int `json:"ceiling,omitempty"`
+}
+
+// MaxTokensPolicy max_tokens 策略（APIKeyPolicy.MaxTokens）
+type MaxTokensPolicy struct {
+	Default int `json:"default,omitempty"`
+	Ceiling int `json:"ceiling,omitempty"`
+	// Models 按模型覆盖，键为模型名或以 * 结尾的前缀（如 "claude-opus-*"）；精确匹配优先，其次最长前缀，
+	// 条目中为 0 的字段沿用外层配置
+	Models map[string]MaxTokensLimit `json:"models,omitempty"`
+}
+
+func (l MaxTokensLimit) valid() bool {
+	return l.Default >= 0 && l.Ceiling >= 0 && (l.Ceiling == 0 || l.Default <= l.Ceiling)
+}
+
+// normalize 校验并规范化 max_tokens 策略；返回 false 表示配置为空，调用方应置为 nil
+func (p *MaxTokensPolicy) normalize() (bool, error) {
+	if !(MaxTokensLimit{Default: p.Default, Ceiling: p.Ceiling}).valid() || len(p.Models) > maxTokensPolicyMaxModels {
+		return false, ErrInvalidMaxTokensPolicy
+	}
+	models := make(map[string]MaxTokensLimit, len(p.Models))
+	for pattern, limit := range p.Models {
+		pattern = strings.ToLower(strings.TrimSpace(pattern))
+		if pattern == "" || pattern == "*" || !limit.valid() {
+			return false, ErrInvalidMaxTokensPolicy.WithMetadata(map[string]string{"model": pattern})
+		}
+		if limit.Default == 0 && limit.Ceiling == 0 {
+			continue
+		}
+		models[pattern] = limit
+	}
+	p.Models = nil
+	if len(models) > 0 {
+		p.Models = models
+	}
+	return p.Default > 0 || p.Ceiling > 0 || p.Models != nil, nil
+}
+
+// limitFor 返回指定模型的生效限制
+func (p *MaxTokensPolicy) limitFor(model string) MaxTokensLimit {
+	if p == nil {
+		return MaxTokensLimit{}
+	}
+	limit := MaxTokensLimit{Default: p.Default, Ceiling: p.Ceiling}
+	model = strings.ToLower(strings.TrimSpace(model))
+	override, ok := p.Models[model]
+	if !ok {
+		bestLen := -1
+		for pattern, candidate := range p.Models {
+			prefix, isPrefix := strings.CutSuffix(pattern, "*")
+			if isPrefix && strings.HasPrefix(model, prefix) && len(prefix) > bestLen {
+				override, bestLen, ok = candidate, len(prefix), true
+			}
+		}
+	}
+	if ok {
+		if override.Default > 0 {
+			limit.Default = override.Default
+		}
+		if override.Ceiling > 0 {
+			limit.Ceiling = override.Ceiling
+		}
+	}
+	return limit
+}
+
+// resolveMaxTokens 计算最终的输出 token 上限，返回 0 表示不设置
+//
+// 客户端请求值只受策略上限约束（不按模型上限下调，避免误伤通过 beta 头扩展输出的请求）；
+// 填充的默认值额外受模型输出上限与剩余上下文窗口约束。
+func resolveMaxTokens(requested int, limit MaxTokensLimit, fallbackDefault int, model ModelTokenLimits, inputTokens int, sharedWindow bool) int {
+	if requested > 0 {
+		if limit.Ceiling > 0 && requested > limit.Ceiling {
+			return limit.Ceiling
+		}
+		return requested
+	}
+
+	value := limit.Default
+	if value <= 0 {
+		value = fallbackDefault
+	}
+	if value <= 0 {
+		return 0
+	}
+	if limit.Ceiling > 0 && value > limit.Ceiling {
+		value = limit.Ceiling
+	}
+	if model.MaxOutput > 0 && value > model.MaxOutput {
+		value = model.MaxOutput
+	}
+	if sharedWindow && model.MaxInput > 0 && inputTokens+value > model.MaxInput && model.MaxInput > inputTokens {
+		value = model.MaxInput - inputTokens
+	}
+	return value
+}
+
+// checkContextWindow 校验（估算的）输入 token 与输出上限是否超出模型上下文窗口
+func checkContextWindow(model string, limits ModelTokenLimits, inputTokens, maxTokens int, sharedWindow bool) error {
+	if limits.MaxInput <= 0 {
+		return nil
+	}
+	required := inputTokens
+	if sharedWindow {
+		required += maxTokens
+	}
+	if required <= limits.MaxInput {
+		return nil
+	}
+	if sharedWindow {
+		return infraerrors.Newf(http.StatusBadRequest, "CONTEXT_WINDOW_EXCEEDED",
+			"prompt (~%d tokens) plus max_tokens (%d) exceeds the context window of %s (%d tokens); shorten the prompt or lower max_tokens",
+			inputTokens, maxTokens, model, limits.MaxInput)
+	}
+	return infraerrors.Newf(http.StatusBadRequest, "CONTEXT_WINDOW_EXCEEDED",
+		"prompt (~%d tokens) exceeds the input limit of %s (%d tokens)", inputTokens, model, limits.MaxInput)
+}
+
+// ApplyMaxTokensPolicy 按 API Key / 分组策略调整 Anthropic Messages 请求的 max_tokens，并校验上下文窗口。
+//
+// 返回调整后的请求体及是否修改；输入 token 为本地估算值。anthropicBeta 含 context-1m 时跳过窗口校验。
+func (s *GatewayService) ApplyMaxTokensPolicy(ctx context.Context, body []byte, model, anthropicBeta string) ([]byte, bool, error) {
+	limit := APIKeyPolicyFromContext(ctx).MaxTokens.limitFor(model)
+	fallbackDefault := 0
+	if s.cfg != nil {
+		fallbackDefault = s.cfg.Gateway.DefaultMaxTokens
+	}
+	limits := s.billingService.GetModelTokenLimits(model)
+	if strings.Contains(anthropicBeta, "context-1m") {
+		limits.MaxInput = 0
+	}
+
+	requested := int(gjson.GetBytes(body, "max_tokens").Int())
+	if requested > 0 && (limit.Ceiling == 0 || requested <= limit.Ceiling) && limits.MaxInput == 0 {
+		// 无需调整也无需校验，跳过本地估算
+		return body, false, nil
+	}
+	inputTokens, _ := EstimateClaudeInputTokens(body)
+
+	value := resolveMaxTokens(requested, limit, fallbackDefault, limits, inputTokens, true)
+	if err := checkContextWindow(model, limits, inputTokens, value, true); err != nil {
+		return body, false, err
+	}
+	if value <= 0 || value == requested {
+		return body, false, nil
+	}
+	out, err := sjson.SetBytes(body, "max_tokens", value)
+	if err != nil {
+		return body, false, nil
+	}
+	return fitClaudeThinkingBudget(out, value), true, nil
+}
+
+// fitClaudeThinkingBudget 下调 max_tokens 后保证 thinking.budget_tokens 小于 max_tokens；预算不足最小值时关闭 thinking
+func fitClaudeThinkingBudget(body []byte, maxTokens int) []byte {
+	if gjson.GetBytes(body, "thinking.type").String() != "enabled" {
+		return body
+	}
+	budget := int(gjson.GetBytes(body, "thinking.budget_tokens").Int())
+	if budget < maxTokens {
+		return body
+	}
+	if maxTokens-1 < reasoningBudgetMinimal {
+		if next, err := sjson.DeleteBytes(body, "thinking"); err == nil {
+			return next
+		}
+		return body
+	}
+	if next, err := sjson.SetBytes(body, "thinking.budget_tokens", maxTokens-1); err == nil {
+		return next
+	}
+	return body
+}
+
+// ApplyMaxTokensPolicy 按 API Key / 分组策略调整 Responses API 请求的 max_output_tokens，并校验输入 token 上限。
+//
+// Responses API 不要求 max_output_tokens，仅在策略配置了默认值时填充。
+func (s *OpenAIGatewayService) ApplyMaxTokensPolicy(ctx context.Context, body []byte, model string) ([]byte, bool, error) {
+	limit := APIKeyPolicyFromContext(ctx).MaxTokens.limitFor(model)
+	limits := s.billingService.GetModelTokenLimits(model)
+
+	requested := int(gjson.GetBytes(body, "max_output_tokens").Int())
+	if limits.MaxInput > 0 {
+		inputTokens := estimateOpenAIResponsesInputTokens(body)
+		if err := checkContextWindow(model, limits, inputTokens, requested, false); err != nil {
+			return body, false, err
+		}
+	}
+
+	value := resolveMaxTokens(requested, limit, 0, limits, 0, false)
+	if value <= 0 || value == requested {
+		return body, false, nil
+	}
+	out, err := sjson.SetBytes(body, "max_output_tokens", value)
+	if err != nil {
+		return body, false, nil
+	}
+	return out, true, nil
+}
+
+// estimateOpenAIResponsesInputTokens 粗略估算 Responses API 请求的输入 token（不含 previous_response_id 引用的历史）
+func estimateOpenAIResponsesInputTokens(body []byte) int {
+	tokens := claude.EstimateTextTokens(gjson.GetBytes(body, "instructions").String())
+	input := gjson.GetBytes(body, "input")
+	if input.Type == gjson.String {
+		return tokens + claude.EstimateTextTokens(input.String())
+	}
+	input.ForEach(func(_, item gjson.Result) bool {
+		tokens += countTokensPerMessageOverhead
+		content := item.Get("content")
+		if content.Type == gjson.String {
+			tokens += claude.EstimateTextTokens(content.String())
+		} else {
+			content.ForEach(func(_, part gjson.Result) bool {
+				tokens += claude.EstimateTextTokens(part.Get("text").String())
+				return true
+			})
+		}
+		tokens += claude.EstimateTextTokens(item.Get("arguments").String())
+		tokens += claude.EstimateTextTokens(item.Get("output").String())
+		return true
+	})
+	gjson.GetBytes(body, "tools").ForEach(func(_, tool gjson.Result) bool {
+		tokens += countTokensPerToolOverhead + claude.EstimateTextTokens(tool.Raw)
+		return true
+	})
+	return tokens
+}
@@ -0,0 +1,119 @@
+//go:build unit
+
+package service
+
+import (
+	"context"
+	"strings"
+	"testing"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/stretchr/testify/require"
+	"github.com/tidwall/gjson"
+)
+
+func TestMaxTokensPolicy_NormalizeAndLimitFor(t *testing.T) {
+	policy := &APIKeyPolicy{MaxTokens: &MaxTokensPolicy{
+		Default: 2048,
+		Ceiling: 8192,
+		Models: map[string]MaxTokensLimit{
+			" Claude-Opus-* ":     {Ceiling: 4096},
+			"claude-opus-4-1":     {Default: 1024},
+			"claude-haiku-4-5":    {},
+			"claude-opus-4-1-foo": {Default: 512},
+		},
+	}}
+	require.NoError(t, policy.Normalize())
+	require.NotContains(t, policy.MaxTokens.Models, "claude-haiku-4-5")
+
+	mt := policy.MaxTokens
+	require.Equal(t, MaxTokensLimit{Default: 2048, Ceiling: 8192}, mt.limitFor("claude-sonnet-4-5"))
+	require.Equal(t, MaxTokensLimit{Default: 2048, Ceiling: 4096}, mt.limitFor("claude-opus-4-5"))
+	// 精确匹配优先于前缀
+	require.Equal(t, MaxTokensLimit{Default: 1024, Ceiling: 8192}, mt.limitFor("claude-opus-4-1"))
+
+	require.Equal(t, MaxTokensLimit{}, (*MaxTokensPolicy)(nil).limitFor("x"))
+
+	empty := &APIKeyPolicy{MaxTokens: &MaxTokensPolicy{}}
+	require.NoError(t, empty.Normalize())
+	require.Nil(t, empty.MaxTokens)
+
+	bad := &APIKeyPolicy{MaxTokens: &MaxTokensPolicy{Default: 9000, Ceiling: 4096}}
+	require.ErrorIs(t, bad.Normalize(), ErrInvalidMaxTokensPolicy)
+	bad = &APIKeyPolicy{MaxTokens: &MaxTokensPolicy{Models: map[string]MaxTokensLimit{"m": {Ceiling: -1}}}}
+	require.ErrorIs(t, bad.Normalize(), ErrInvalidMaxTokensPolicy)
+}
+
+func TestResolveMaxTokens(t *testing.T) {
+	limit := MaxTokensLimit{Default: 4096, Ceiling: 8192}
+	model := ModelTokenLimits{MaxInput: 200000, MaxOutput: 64000}
+
+	require.Equal(t, 1000, resolveMaxTokens(1000, limit, 0, model, 0, true))
+	require.Equal(t, 8192, resolveMaxTokens(32000, limit, 0, model, 0, true))
+	require.Equal(t, 4096, resolveMaxTokens(0, limit, 1024, model, 0, true))
+	require.Equal(t, 1024, resolveMaxTokens(0, MaxTokensLimit{}, 1024, model, 0, true))
+	require.Equal(t, 0, resolveMaxTokens(0, MaxTokensLimit{}, 0, model, 0, true))
+	// 客户端请求值不按模型输出上限下调
+	require.Equal(t, 100000, resolveMaxTokens(100000, MaxTokensLimit{}, 0, model, 0, true))
+	// 默认值受模型输出上限与剩余窗口约束
+	require.Equal(t, 64000, resolveMaxTokens(0, MaxTokensLimit{Default: 100000}, 0, model, 0, true))
+	require.Equal(t, 1000, resolveMaxTokens(0, limit, 0, model, 199000, true))
+}
+
+func TestCheckContextWindow(t *testing.T) {
+	limits := ModelTokenLimits{MaxInput: 200000}
+	require.NoError(t, checkContextWindow("m", limits, 100000, 64000, true))
+	require.NoError(t, checkContextWindow("m", ModelTokenLimits{}, 1000000000, 1, true))
+
+	err := checkContextWindow("claude-x", limits, 190000, 20000, true)
+	require.ErrorIs(t, err, ErrContextWindowExceeded)
+	require.Contains(t, err.Error(), "claude-x")
+
+	// 非共享窗口只校验输入
+	require.NoError(t, checkContextWindow("gpt-x", limits, 190000, 20000, false))
+	require.ErrorIs(t, checkContextWindow("gpt-x", limits, 210000, 0, false), ErrContextWindowExceeded)
+}
+
+func TestGatewayService_ApplyMaxTokensPolicy(t *testing.T) {
+	svc := &GatewayService{cfg: &config.Config{Gateway: config.GatewayConfig{DefaultMaxTokens: 4096}}}
+	ctx := WithAPIKeyPolicy(context.Background(), &APIKeyPolicy{MaxTokens: &MaxTokensPolicy{Ceiling: 2048}})
+
+	t.Run("fills default", func(t *testing.T) {
+		out, changed, err := svc.ApplyMaxTokensPolicy(context.Background(), []byte(`{"model":"m","messages":[]}`), "m", "")
+		require.NoError(t, err)
+		require.True(t, changed)
+		require.Equal(t, int64(4096), gjson.GetBytes(out, "max_tokens").Int())
+	})
+
+	t.Run("clamps and fits thinking budget", func(t *testing.T) {
+		body := []byte(`{"model":"m","max_tokens":16000,"thinking":{"type":"enabled","budget_tokens":8000}}`)
+		out, changed, err := svc.ApplyMaxTokensPolicy(ctx, body, "m", "")
+		require.NoError(t, err)
+		require.True(t, changed)
+		require.Equal(t, int64(2048), gjson.GetBytes(out, "max_tokens").Int())
+		require.Equal(t, int64(2047), gjson.GetBytes(out, "thinking.budget_tokens").Int())
+	})
+
+	t.Run("within ceiling untouched", func(t *testing.T) {
+		body := []byte(`{"model":"m","max_tokens":1000}`)
+		out, changed, err := svc.ApplyMaxTokensPolicy(ctx, body, "m", "")
+		require.NoError(t, err)
+		require.False(t, changed)
+		require.Equal(t, body, out)
+	})
+}
+
+func TestFitClaudeThinkingBudget(t *testing.T) {
+	out := fitClaudeThinkingBudget([]byte(`{"max_tokens":1000,"thinking":{"type":"enabled","budget_tokens":2048}}`), 1000)
+	require.False(t, gjson.GetBytes(out, "thinking").Exists())
+
+	body := []byte(`{"thinking":{"type":"adaptive"}}`)
+	require.Equal(t, body, fitClaudeThinkingBudget(body, 10))
+}
+
+func TestEstimateOpenAIResponsesInputTokens(t *testing.T) {
+	short := estimateOpenAIResponsesInputTokens([]byte(`{"input":"hi"}`))
+	long := estimateOpenAIResponsesInputTokens([]byte(`{"instructions":"be brief","input":[{"role":"user","content":[{"type":"input_text","text":"` + strings.Repeat("hello world ", 200) + `"}]}]}`))
+	require.Positive(t, short)
+	require.Greater(t, long, 200)
+}
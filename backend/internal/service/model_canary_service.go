@@ -0,0 +1,336 @@
+package service
+
+import (
+	"context"
+	"log"
+	"math/rand/v2"
+	"strings"
+	"sync"
+	"time"
+
+	infraerrors "github.com/Wei-Shaw/sub2api/internal/pkg/errors"
+	"github.com/tidwall/gjson"
+	"github.com/tidwall/sjson"
+)
+
+const (
+	modelCanaryRoutesCacheTTL = 30 * time.Second
+	modelCanaryMaxTargets     = 10
+	modelCanaryMaxNameLen     = 64
+	modelCanaryMaxModelLen    = 200
+	modelCanaryRecordTimeout  = 3 * time.Second
+)
+
+var (
+	ErrModelCanaryRouteNotFound = infraerrors.NotFound("MODEL_CANARY_ROUTE_NOT_FOUND", "canary route not found")
+	ErrModelCanaryRouteExists   = infraerrors.Conflict("MODEL_CANARY_ROUTE_EXISTS", "a canary route for this alias already exists")
+	ErrInvalidModelCanaryRoute  = infraerrors.BadRequest("INVALID_MODEL_CANARY_ROUTE", "alias is required and targets need a model, a unique name and non-negative weights with a positive total")
+)
+
+// ModelCanaryTarget 金丝雀路由的一个目标
+type ModelCanaryTarget struct {
+	// Name 目标标识，用于统计；为空时使用 Model
+	Name string `json:"name"`
+	// Model 实际转发的模型名
+	Model string `json:"model"`
+	// Weight 相对权重，0 表示暂停分流
+	Weight int `json:"weight"`
+}
+
+// ModelCanaryRoute 模型别名到多个目标的加权路由
+type ModelCanaryRoute struct {
+	ID        int64               `json:"id"`
+	Alias     string              `json:"alias"`
+	Enabled   bool                `json:"enabled"`
+	Targets   []ModelCanaryTarget `json:"targets"`
+	CreatedAt time.Time           `json:"created_at"`
+	UpdatedAt time.Time           `json:"updated_at"`
+}
+
+// ModelCanaryCounters 单个目标的累计计数
+type ModelCanaryCounters struct {
+	Requests       int64
+	Successes      int64
+	TotalLatencyMs int64
+}
+
+// ModelCanaryTargetStats 单个目标的成功率与延迟统计
+type ModelCanaryTargetStats struct {
+	Name         string  `json:"name"`
+	Model        string  `json:"model"`
+	Weight       int     `json:"weight"`
+	Requests     int64   `json:"requests"`
+	Successes    int64   `json:"successes"`
+	Errors       int64   `json:"errors"`
+	SuccessRate  float64 `json:"success_rate"`
+	AvgLatencyMs float64 `json:"avg_latency_ms"`
+}
+
+// ModelCanaryRouteRepository 金丝雀路由存储
+type ModelCanaryRouteRepository interface {
+	List(ctx context.Context) ([]*ModelCanaryRoute, error)
+	GetByID(ctx context.Context, id int64) (*ModelCanaryRoute, error)
+	Create(ctx context.Context, route *ModelCanaryRoute) error
+	Update(ctx context.Context, route *ModelCanaryRoute) error
+	Delete(ctx context.Context, id int64) error
+}
+
+// ModelCanaryStatsCache 金丝雀目标统计（Redis，多实例共享）
+type ModelCanaryStatsCache interface {
+	Record(ctx context.Context, routeID int64, target string, success bool, latencyMs int64) error
+	Get(ctx context.Context, routeID int64) (map[string]ModelCanaryCounters, error)
+	Reset(ctx context.Context, routeID int64) error
+}
+
+// ModelCanaryService 金丝雀路由：按权重将模型别名改写为目标模型，并统计各目标的成功率与延迟
+type ModelCanaryService struct {
+	repo       ModelCanaryRouteRepository
+	statsCache ModelCanaryStatsCache
+
+	routesMu       sync.RWMutex
+	routes         map[string]*ModelCanaryRoute
+	routesLoadedAt time.Time
+}
+
+// NewModelCanaryService creates a new ModelCanaryService
+func NewModelCanaryService(repo ModelCanaryRouteRepository, statsCache ModelCanaryStatsCache) *ModelCanaryService {
+	return &ModelCanaryService{repo: repo, statsCache: statsCache}
+}
+
+// List 列出全部路由
+func (s *ModelCanaryService) List(ctx context.Context) ([]*ModelCanaryRoute, error) {
+	return s.repo.List(ctx)
+}
+
+// GetByID 获取路由
+func (s *ModelCanaryService) GetByID(ctx context.Context, id int64) (*ModelCanaryRoute, error) {
+	return s.repo.GetByID(ctx, id)
+}
+
+// Create 创建路由
+func (s *ModelCanaryService) Create(ctx context.Context, route *ModelCanaryRoute) error {
+	if err := normalizeModelCanaryRoute(route); err != nil {
+		return err
+	}
+	if err := s.repo.Create(ctx, route); err != nil {
+		return err
+	}
+	s.invalidateRoutes()
+	return nil
+}
+
+// Update 更新路由（含运行时调整权重）
+func (s *ModelCanaryService) Update(ctx context.Context, route *ModelCanaryRoute) error {
+	if err := normalizeModelCanaryRoute(route); err != nil {
+		return err
+	}
+	if err := s.repo.Update(ctx, route); err != nil {
+		return err
+	}
+	s.invalidateRoutes()
+	return nil
+}
+
+// Delete 删除路由及其统计
+func (s *ModelCanaryService) Delete(ctx context.Context, id int64) error {
+	if err := s.repo.Delete(ctx, id); err != nil {
+		return err
+	}
+	s.invalidateRoutes()
+	if err := s.statsCache.Reset(ctx, id); err != nil {
+		log.Printf("[ModelCanary] reset stats for route %d failed: %v", id, err)
+	}
+	return nil
+}
+
+// GetStats 返回路由各目标的统计；已从路由中移除的目标不再返回
+func (s *ModelCanaryService) GetStats(ctx context.Context, id int64) ([]ModelCanaryTargetStats, error) {
+	route, err := s.repo.GetByID(ctx, id)
+	if err != nil {
+		return nil, err
+	}
+	counters, err := s.statsCache.Get(ctx, id)
+	if err != nil {
+		return nil, err
+	}
+	return buildModelCanaryStats(route.Targets, counters), nil
+}
+
+// ResetStats 清空路由统计，通常在调整权重开始新一轮观察前调用
+func (s *ModelCanaryService) ResetStats(ctx context.Context, id int64) error {
+	if _, err := s.repo.GetByID(ctx, id); err != nil {
+		return err
+	}
+	return s.statsCache.Reset(ctx, id)
+}
+
+func buildModelCanaryStats(targets []ModelCanaryTarget, counters map[string]ModelCanaryCounters) []ModelCanaryTargetStats {
+	out := make([]ModelCanaryTargetStats, 0, len(targets))
+	for _, target := range targets {
+		c := counters[target.Name]
+		stats := ModelCanaryTargetStats{
+			Name:      target.Name,
+			Model:     target.Model,
+			Weight:    target.Weight,
+			Requests:  c.Requests,
+			Successes: c.Successes,
+			Errors:    c.Requests - c.Successes,
+		}
+		if c.Requests > 0 {
+			stats.SuccessRate = float64(c.Successes) / float64(c.Requests)
+			stats.AvgLatencyMs = float64(c.TotalLatencyMs) / float64(c.Requests)
+		}
+		out = append(out, stats)
+	}
+	return out
+}
+
+// normalizeModelCanaryRoute 校验并规范化路由字段
+func normalizeModelCanaryRoute(route *ModelCanaryRoute) error {
+	if route == nil {
+		return ErrInvalidModelCanaryRoute
+	}
+	route.Alias = strings.ToLower(strings.TrimSpace(route.Alias))
+	if route.Alias == "" || len(route.Alias) > modelCanaryMaxModelLen ||
+		len(route.Targets) == 0 || len(route.Targets) > modelCanaryMaxTargets {
+		return ErrInvalidModelCanaryRoute
+	}
+	totalWeight := 0
+	seen := make(map[string]struct{}, len(route.Targets))
+	for i := range route.Targets {
+		target := &route.Targets[i]
+		target.Model = strings.TrimSpace(target.Model)
+		target.Name = strings.TrimSpace(target.Name)
+		if target.Name == "" {
+			target.Name = target.Model
+		}
+		if target.Model == "" || len(target.Model) > modelCanaryMaxModelLen || len(target.Name) > modelCanaryMaxNameLen || target.Weight < 0 {
+			return ErrInvalidModelCanaryRoute
+		}
+		if _, dup := seen[target.Name]; dup {
+			return ErrInvalidModelCanaryRoute.WithMetadata(map[string]string{"target": target.Name})
+		}
+		seen[target.Name] = struct{}{}
+		totalWeight += target.Weight
+	}
+	if totalWeight <= 0 {
+		return ErrInvalidModelCanaryRoute
+	}
+	return nil
+}
+
+// pickModelCanaryTarget 按权重选择目标；roll 返回 [0, n) 的随机数
+func pickModelCanaryTarget(targets []ModelCanaryTarget, roll func(n int) int) *ModelCanaryTarget {
+	total := 0
+	for _, target := range targets {
+		total += max(target.Weight, 0)
+	}
+	if total <= 0 {
+		return nil
+	}
+	n := roll(total)
+	for i := range targets {
+		if targets[i].Weight <= 0 {
+			continue
+		}
+		if n < targets[i].Weight {
+			return &targets[i]
+		}
+		n -= targets[i].Weight
+	}
+	return nil
+}
+
+func (s *ModelCanaryService) invalidateRoutes() {
+	s.routesMu.Lock()
+	s.routesLoadedAt = time.Time{}
+	s.routesMu.Unlock()
+}
+
+// routeFor 返回别名对应的已启用路由（带短时缓存）
+func (s *ModelCanaryService) routeFor(ctx context.Context, alias string) *ModelCanaryRoute {
+	s.routesMu.RLock()
+	if !s.routesLoadedAt.IsZero() && time.Since(s.routesLoadedAt) < modelCanaryRoutesCacheTTL {
+		route := s.routes[alias]
+		s.routesMu.RUnlock()
+		return route
+	}
+	s.routesMu.RUnlock()
+
+	all, err := s.repo.List(ctx)
+	if err != nil {
+		log.Printf("[ModelCanary] load routes failed: %v", err)
+	}
+	routes := make(map[string]*ModelCanaryRoute, len(all))
+	for _, route := range all {
+		if route != nil && route.Enabled {
+			routes[route.Alias] = route
+		}
+	}
+
+	s.routesMu.Lock()
+	s.routes = routes
+	s.routesLoadedAt = time.Now()
+	s.routesMu.Unlock()
+	return routes[alias]
+}
+
+// ModelCanaryDecision 单个请求的金丝雀路由结果
+type ModelCanaryDecision struct {
+	svc     *ModelCanaryService
+	RouteID int64
+	Alias   string
+	Target  string
+	Model   string
+	start   time.Time
+}
+
+// Route 请求模型命中金丝雀路由时，按权重选择目标并改写请求体中的 model；未命中返回原请求体与 nil
+func (s *ModelCanaryService) Route(ctx context.Context, body []byte) ([]byte, *ModelCanaryDecision) {
+	if s == nil {
+		return body, nil
+	}
+	model := gjson.GetBytes(body, "model")
+	if model.Type != gjson.String || model.String() == "" {
+		return body, nil
+	}
+	route := s.routeFor(ctx, strings.ToLower(strings.TrimSpace(model.String())))
+	if route == nil {
+		return body, nil
+	}
+	target := pickModelCanaryTarget(route.Targets, rand.IntN)
+	if target == nil {
+		return body, nil
+	}
+	decision := &ModelCanaryDecision{
+		svc:     s,
+		RouteID: route.ID,
+		Alias:   route.Alias,
+		Target:  target.Name,
+		Model:   target.Model,
+		start:   time.Now(),
+	}
+	if target.Model == model.String() {
+		return body, decision
+	}
+	next, err := sjson.SetBytes(body, "model", target.Model)
+	if err != nil {
+		return body, nil
+	}
+	return next, decision
+}
+
+// Finish 记录请求结果与耗时；decision 为 nil 时为空操作。统计写入异步执行，不阻塞请求。
+func (d *ModelCanaryDecision) Finish(success bool) {
+	if d == nil {
+		return
+	}
+	latencyMs := time.Since(d.start).Milliseconds()
+	go func() {
+		ctx, cancel := context.WithTimeout(context.Background(), modelCanaryRecordTimeout)
+		defer cancel()
+		if err := d.svc.statsCache.Record(ctx, d.RouteID, d.Target, success, latencyMs); err != nil {
+			log.Printf("[ModelCanary] record stats for route %d target %s failed: %v", d.RouteID, d.Target, err)
+		}
+	}()
+}
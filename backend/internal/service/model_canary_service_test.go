@@ -0,0 +1,96 @@
+//go:build unit
+
+package service
+
+import (
+	"context"
+	"testing"
+
+	"github.com/stretchr/testify/require"
+	"github.com/tidwall/gjson"
+)
+
+type modelCanaryRepoStub struct {
+	ModelCanaryRouteRepository
+	routes []*ModelCanaryRoute
+}
+
+func (s *modelCanaryRepoStub) List(context.Context) ([]*ModelCanaryRoute, error) {
+	return s.routes, nil
+}
+
+func TestNormalizeModelCanaryRoute(t *testing.T) {
+	route := &ModelCanaryRoute{Alias: " Claude-Sonnet ", Targets: []ModelCanaryTarget{
+		{Model: " claude-sonnet-4-5 ", Weight: 95},
+		{Name: "next", Model: "claude-sonnet-5", Weight: 5},
+	}}
+	require.NoError(t, normalizeModelCanaryRoute(route))
+	require.Equal(t, "claude-sonnet", route.Alias)
+	require.Equal(t, "claude-sonnet-4-5", route.Targets[0].Name)
+
+	for _, bad := range []*ModelCanaryRoute{
+		nil,
+		{Alias: "a"},
+		{Alias: "", Targets: []ModelCanaryTarget{{Model: "m", Weight: 1}}},
+		{Alias: "a", Targets: []ModelCanaryTarget{{Model: "m", Weight: 0}}},
+		{Alias: "a", Targets: []ModelCanaryTarget{{Model: "m", Weight: -1}, {Model: "n", Weight: 5}}},
+		{Alias: "a", Targets: []ModelCanaryTarget{{Model: "m", Weight: 1}, {Name: "m", Model: "n", Weight: 1}}},
+	} {
+		require.ErrorIs(t, normalizeModelCanaryRoute(bad), ErrInvalidModelCanaryRoute)
+	}
+}
+
+func TestPickModelCanaryTarget(t *testing.T) {
+	targets := []ModelCanaryTarget{{Name: "a", Weight: 95}, {Name: "paused", Weight: 0}, {Name: "b", Weight: 5}}
+	pick := func(n int) string {
+		return pickModelCanaryTarget(targets, func(int) int { return n }).Name
+	}
+	require.Equal(t, "a", pick(0))
+	require.Equal(t, "a", pick(94))
+	require.Equal(t, "b", pick(95))
+	require.Equal(t, "b", pick(99))
+
+	counts := map[string]int{}
+	for i := 0; i < 100; i++ {
+		counts[pickModelCanaryTarget(targets, func(int) int { return i }).Name]++
+	}
+	require.Equal(t, map[string]int{"a": 95, "b": 5}, counts)
+
+	require.Nil(t, pickModelCanaryTarget([]ModelCanaryTarget{{Weight: 0}}, func(int) int { return 0 }))
+}
+
+func TestModelCanaryService_Route(t *testing.T) {
+	svc := NewModelCanaryService(&modelCanaryRepoStub{routes: []*ModelCanaryRoute{
+		{ID: 1, Alias: "claude-sonnet", Enabled: true, Targets: []ModelCanaryTarget{{Name: "next", Model: "claude-sonnet-5", Weight: 1}}},
+		{ID: 2, Alias: "gpt-5", Enabled: false, Targets: []ModelCanaryTarget{{Name: "x", Model: "gpt-6", Weight: 1}}},
+	}}, nil)
+
+	body := []byte(`{"model":"Claude-Sonnet","messages":[]}`)
+	out, decision := svc.Route(context.Background(), body)
+	require.NotNil(t, decision)
+	require.Equal(t, int64(1), decision.RouteID)
+	require.Equal(t, "next", decision.Target)
+	require.Equal(t, "claude-sonnet-5", gjson.GetBytes(out, "model").String())
+
+	body = []byte(`{"model":"gpt-5"}`)
+	out, decision = svc.Route(context.Background(), body)
+	require.Nil(t, decision)
+	require.Equal(t, body, out)
+
+	var nilSvc *ModelCanaryService
+	_, decision = nilSvc.Route(context.Background(), body)
+	require.Nil(t, decision)
+	decision.Finish(true)
+}
+
+func TestBuildModelCanaryStats(t *testing.T) {
+	stats := buildModelCanaryStats(
+		[]ModelCanaryTarget{{Name: "a", Model: "m1", Weight: 9}, {Name: "b", Model: "m2", Weight: 1}},
+		map[string]ModelCanaryCounters{"a": {Requests: 4, Successes: 3, TotalLatencyMs: 400}, "removed": {Requests: 1}},
+	)
+	require.Len(t, stats, 2)
+	require.Equal(t, int64(1), stats[0].Errors)
+	require.InDelta(t, 0.75, stats[0].SuccessRate, 1e-9)
+	require.InDelta(t, 100, stats[0].AvgLatencyMs, 1e-9)
+	require.Zero(t, stats[1].Requests)
+}
@@ -0,0 +1,398 @@
+package service
+
+import (
+	"context"
+	"encoding/json"
+	"fmt"
+	"regexp"
+	"sort"
+	"strings"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	infraerrors "github.com/Wei-Shaw/sub2api/internal/pkg/errors"
+)
+
+// 审核服务商全部失败时的处理方式
+const (
+	// ModerationOnErrorAllow 放行请求，在使用记录中标记 moderation_unavailable
+	ModerationOnErrorAllow = "allow"
+	// ModerationOnErrorBlock 拒绝请求（503）
+	ModerationOnErrorBlock = "block"
+)
+
+const (
+	moderationMaxProviders        = 5
+	moderationMaxBudgetMS         = 30000
+	moderationDefaultTimeout      = 2 * time.Second
+	moderationUnavailableRuleName = "moderation_unavailable"
+)
+
+var (
+	ErrInvalidModerationProviders = infraerrors.BadRequest("INVALID_MODERATION_PROVIDERS", "moderation.providers must list 1-5 distinct provider names")
+	ErrInvalidModerationAction    = infraerrors.BadRequest("INVALID_MODERATION_ACTION", "moderation.action must be 'block' or 'flag'")
+	ErrInvalidModerationOnError   = infraerrors.BadRequest("INVALID_MODERATION_ON_ERROR", "moderation.on_error must be 'allow' or 'block'")
+	ErrInvalidModerationBudget    = infraerrors.BadRequest("INVALID_MODERATION_BUDGET", "moderation.budget_ms must be between 0 and 30000")
+)
+
+// ModerationPolicy 入站内容审核配置（APIKeyPolicy.Moderation）
+//
+// 检查的文本范围与提示词防火墙一致；防火墙 sanitize 改写后的文本才会发送给审核服务商。
+type ModerationPolicy struct {
+	// Providers 审核服务商名称（配置文件 moderation.providers），按顺序调用，前一个失败或超时时回退到下一个
+	Providers []string `json:"providers"`
+	// Action 判定违规时的动作：block（默认）/ flag
+	Action string `json:"action,omitempty"`
+	// BudgetMS 整个审核链的延迟预算（毫秒），0 表示仅受各服务商自身超时限制
+	BudgetMS int `json:"budget_ms,omitempty"`
+	// OnError 全部服务商失败或超出预算时的处理：allow（默认）/ block
+	OnError string `json:"on_error,omitempty"`
+}
+
+// normalize 校验并规范化审核配置；返回 false 表示配置为空，调用方应置为 nil
+func (m *ModerationPolicy) normalize() (bool, error) {
+	providers := make([]string, 0, len(m.Providers))
+	for _, name := range m.Providers {
+		name = strings.ToLower(strings.TrimSpace(name))
+		if name == "" {
+			continue
+		}
+		for _, existing := range providers {
+			if existing == name {
+				return false, ErrInvalidModerationProviders
+			}
+		}
+		providers = append(providers, name)
+	}
+	if len(providers) > moderationMaxProviders {
+		return false, ErrInvalidModerationProviders
+	}
+	m.Providers = providers
+
+	m.Action = strings.ToLower(strings.TrimSpace(m.Action))
+	switch m.Action {
+	case "":
+		m.Action = PromptFirewallActionBlock
+	case PromptFirewallActionBlock, PromptFirewallActionFlag:
+	default:
+		return false, ErrInvalidModerationAction
+	}
+
+	m.OnError = strings.ToLower(strings.TrimSpace(m.OnError))
+	switch m.OnError {
+	case "":
+		m.OnError = ModerationOnErrorAllow
+	case ModerationOnErrorAllow, ModerationOnErrorBlock:
+	default:
+		return false, ErrInvalidModerationOnError
+	}
+
+	if m.BudgetMS < 0 || m.BudgetMS > moderationMaxBudgetMS {
+		return false, ErrInvalidModerationBudget
+	}
+	return len(m.Providers) > 0, nil
+}
+
+// ModerationVerdict 单个审核服务商的判定结果
+type ModerationVerdict struct {
+	Flagged bool
+	// Categories 命中的违规类别（已排序）
+	Categories []string
+}
+
+// ModerationProvider 内容审核服务商。Moderate 返回 error 表示本次调用失败，由审核链回退到下一个服务商
+type ModerationProvider interface {
+	Moderate(ctx context.Context, texts []string) (*ModerationVerdict, error)
+}
+
+// ModerationClient 调用外部审核接口：POST JSON，2xx 时返回响应体
+type ModerationClient interface {
+	Post(ctx context.Context, endpoint, apiKey string, payload []byte) ([]byte, error)
+}
+
+// ModerationDecision 一次审核链调用的结果
+type ModerationDecision struct {
+	// Action block / flag；回退后判定未违规时为空，此时仅 Failures 有意义
+	Action string
+	// Provider 给出违规判定的服务商；Unavailable 时为空
+	Provider   string
+	Categories []string
+	// Unavailable 所有服务商均失败或超出延迟预算
+	Unavailable bool
+	// Failures 失败的服务商及原因（name: error），供调用方记录日志
+	Failures []string
+}
+
+// Summary 返回写入使用记录的摘要，如 "block:moderation.openai(hate,violence)"、"flag:moderation_unavailable"；
+// 回退后判定未违规（Action 为空）时返回空串
+func (d *ModerationDecision) Summary() string {
+	if d == nil || d.Action == "" {
+		return ""
+	}
+	if d.Unavailable {
+		return d.Action + ":" + moderationUnavailableRuleName
+	}
+	return d.Action + ":moderation." + d.Provider + "(" + strings.Join(d.Categories, ",") + ")"
+}
+
+// MergePromptCheckSummaries 合并提示词防火墙与内容审核的摘要（以 ; 分隔），长度与 usage_logs.prompt_firewall 列宽一致
+func MergePromptCheckSummaries(summaries ...string) string {
+	parts := make([]string, 0, len(summaries))
+	for _, summary := range summaries {
+		if summary != "" {
+			parts = append(parts, summary)
+		}
+	}
+	merged := strings.Join(parts, ";")
+	if len(merged) > promptFirewallSummaryMaxLen {
+		merged = merged[:promptFirewallSummaryMaxLen]
+	}
+	return merged
+}
+
+type moderationProviderEntry struct {
+	provider ModerationProvider
+	timeout  time.Duration
+}
+
+// ModerationService 按 API Key / 分组策略选择的服务商顺序执行入站内容审核
+type ModerationService struct {
+	providers map[string]moderationProviderEntry
+}
+
+// NewModerationService creates a new ModerationService
+func NewModerationService(client ModerationClient, cfg *config.Config) *ModerationService {
+	s := &ModerationService{providers: make(map[string]moderationProviderEntry)}
+	var configured map[string]config.ModerationProviderConfig
+	if cfg != nil {
+		configured = cfg.Moderation.Providers
+	}
+	for name, pc := range configured {
+		var provider ModerationProvider
+		switch pc.Type {
+		case config.ModerationProviderLocal:
+			provider = newLocalModerationProvider(pc.Patterns, pc.Jailbreak)
+		case config.ModerationProviderOpenAI:
+			provider = &openAIModerationProvider{client: client, url: pc.URL, apiKey: pc.APIKey, model: pc.Model}
+		case config.ModerationProviderHTTP:
+			provider = &httpModerationProvider{client: client, url: pc.URL, apiKey: pc.APIKey}
+		default:
+			continue
+		}
+		s.Register(name, provider, time.Duration(pc.TimeoutMS)*time.Millisecond)
+	}
+	if _, ok := s.providers[config.ModerationProviderLocal]; !ok {
+		s.Register(config.ModerationProviderLocal, newLocalModerationProvider(nil, true), 0)
+	}
+	return s
+}
+
+// Register 注册（或替换）审核服务商；timeout 为单次调用的延迟预算，0 表示默认 2 秒
+func (s *ModerationService) Register(name string, provider ModerationProvider, timeout time.Duration) {
+	if timeout <= 0 {
+		timeout = moderationDefaultTimeout
+	}
+	s.providers[strings.ToLower(strings.TrimSpace(name))] = moderationProviderEntry{provider: provider, timeout: timeout}
+}
+
+// Check 按策略中的服务商顺序审核请求体中的提示词文本。
+// 第一个成功返回的服务商给出最终判定；未启用、无可检查文本，或判定未违规且没有服务商失败时返回 nil。
+func (s *ModerationService) Check(ctx context.Context, policy *ModerationPolicy, body []byte, format string) *ModerationDecision {
+	if s == nil || policy == nil || len(policy.Providers) == 0 {
+		return nil
+	}
+	segments := collectPromptSegments(body, format)
+	if len(segments) == 0 {
+		return nil
+	}
+	texts := make([]string, len(segments))
+	for i, seg := range segments {
+		texts[i] = seg.text
+	}
+
+	if policy.BudgetMS > 0 {
+		var cancel context.CancelFunc
+		ctx, cancel = context.WithTimeout(ctx, time.Duration(policy.BudgetMS)*time.Millisecond)
+		defer cancel()
+	}
+
+	var failures []string
+	for _, name := range policy.Providers {
+		if ctx.Err() != nil {
+			failures = append(failures, name+": latency budget exhausted")
+			continue
+		}
+		entry, ok := s.providers[name]
+		if !ok {
+			failures = append(failures, name+": provider not configured")
+			continue
+		}
+		verdict, err := s.moderate(ctx, entry, texts)
+		if err != nil {
+			failures = append(failures, name+": "+err.Error())
+			continue
+		}
+		if !verdict.Flagged {
+			if len(failures) > 0 {
+				return &ModerationDecision{Provider: name, Failures: failures}
+			}
+			return nil
+		}
+		return &ModerationDecision{Action: policy.Action, Provider: name, Categories: verdict.Categories, Failures: failures}
+	}
+
+	action := PromptFirewallActionFlag
+	if policy.OnError == ModerationOnErrorBlock {
+		action = PromptFirewallActionBlock
+	}
+	return &ModerationDecision{Action: action, Unavailable: true, Failures: failures}
+}
+
+func (s *ModerationService) moderate(ctx context.Context, entry moderationProviderEntry, texts []string) (*ModerationVerdict, error) {
+	callCtx, cancel := context.WithTimeout(ctx, entry.timeout)
+	defer cancel()
+	verdict, err := entry.provider.Moderate(callCtx, texts)
+	if err == nil && callCtx.Err() != nil {
+		// 忽略超出预算后才返回的结果，保证延迟预算对所有服务商一致生效
+		err = callCtx.Err()
+	}
+	if err != nil {
+		return nil, err
+	}
+	if verdict == nil {
+		return nil, fmt.Errorf("empty verdict")
+	}
+	return verdict, nil
+}
+
+// localModerationProvider 本地规则引擎：按类别配置的正则与内置越狱话术检测
+type localModerationProvider struct {
+	categories []string
+	patterns   []*regexp.Regexp
+	jailbreak  bool
+}
+
+func newLocalModerationProvider(patterns map[string]string, jailbreak bool) *localModerationProvider {
+	p := &localModerationProvider{jailbreak: jailbreak}
+	for category := range patterns {
+		p.categories = append(p.categories, category)
+	}
+	sort.Strings(p.categories)
+	compiled := p.categories[:0]
+	for _, category := range p.categories {
+		re, err := regexp.Compile(patterns[category])
+		if err != nil {
+			continue
+		}
+		compiled = append(compiled, category)
+		p.patterns = append(p.patterns, re)
+	}
+	p.categories = compiled
+	return p
+}
+
+func (p *localModerationProvider) Moderate(_ context.Context, texts []string) (*ModerationVerdict, error) {
+	verdict := &ModerationVerdict{}
+	matches := func(re *regexp.Regexp) bool {
+		for _, text := range texts {
+			if re.MatchString(text) {
+				return true
+			}
+		}
+		return false
+	}
+	for i, re := range p.patterns {
+		if matches(re) {
+			verdict.Categories = append(verdict.Categories, p.categories[i])
+		}
+	}
+	if p.jailbreak {
+		for _, re := range promptJailbreakPatterns {
+			if matches(re) {
+				verdict.Categories = append(verdict.Categories, promptFirewallJailbreakRuleName)
+				break
+			}
+		}
+	}
+	sort.Strings(verdict.Categories)
+	verdict.Flagged = len(verdict.Categories) > 0
+	return verdict, nil
+}
+
+// openAIModerationProvider OpenAI 兼容的 moderations 接口
+type openAIModerationProvider struct {
+	client ModerationClient
+	url    string
+	apiKey string
+	model  string
+}
+
+func (p *openAIModerationProvider) Moderate(ctx context.Context, texts []string) (*ModerationVerdict, error) {
+	req := map[string]any{"input": texts}
+	if p.model != "" {
+		req["model"] = p.model
+	}
+	payload, err := json.Marshal(req)
+	if err != nil {
+		return nil, fmt.Errorf("encode request: %w", err)
+	}
+	body, err := p.client.Post(ctx, p.url, p.apiKey, payload)
+	if err != nil {
+		return nil, err
+	}
+	var out struct {
+		Results []struct {
+			Flagged    bool            `json:"flagged"`
+			Categories map[string]bool `json:"categories"`
+		} `json:"results"`
+	}
+	if err := json.Unmarshal(body, &out); err != nil {
+		return nil, fmt.Errorf("decode response: %w", err)
+	}
+	if len(out.Results) == 0 {
+		return nil, fmt.Errorf("moderation endpoint returned no results")
+	}
+	verdict := &ModerationVerdict{}
+	seen := make(map[string]struct{})
+	for _, result := range out.Results {
+		verdict.Flagged = verdict.Flagged || result.Flagged
+		for category, hit := range result.Categories {
+			if _, ok := seen[category]; hit && !ok {
+				seen[category] = struct{}{}
+				verdict.Categories = append(verdict.Categories, category)
+			}
+		}
+	}
+	sort.Strings(verdict.Categories)
+	return verdict, nil
+}
+
+// httpModerationProvider 外部审核服务：请求 {"input":[...]}，响应 {"flagged":bool,"categories":[...]}
+type httpModerationProvider struct {
+	client ModerationClient
+	url    string
+	apiKey string
+}
+
+func (p *httpModerationProvider) Moderate(ctx context.Context, texts []string) (*ModerationVerdict, error) {
+	payload, err := json.Marshal(map[string]any{"input": texts})
+	if err != nil {
+		return nil, fmt.Errorf("encode request: %w", err)
+	}
+	body, err := p.client.Post(ctx, p.url, p.apiKey, payload)
+	if err != nil {
+		return nil, err
+	}
+	var out struct {
+		Flagged    *bool    `json:"flagged"`
+		Categories []string `json:"categories"`
+	}
+	if err := json.Unmarshal(body, &out); err != nil {
+		return nil, fmt.Errorf("decode response: %w", err)
+	}
+	if out.Flagged == nil {
+		return nil, fmt.Errorf("moderation service response is missing flagged")
+	}
+	sort.Strings(out.Categories)
+	return &ModerationVerdict{Flagged: *out.Flagged, Categories: out.Categories}, nil
+}
@@ -0,0 +1,393 @@
+package service
+
+import (
+	"bytes"
+	"context"
+	"fmt"
+	"html/template"
+	"math"
+	"strings"
+	texttemplate "text/template"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	infraerrors "github.com/Wei-Shaw/sub2api/internal/pkg/errors"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/logger"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/pagination"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/timezone"
+)
+
+// 通知类型
+const (
+	NotificationKindQuotaAlert  = "quota_alert"
+	NotificationKindKeyExpiry   = "key_expiry"
+	NotificationKindAdminInvite = "admin_invite"
+	NotificationKindStatement   = "statement"
+)
+
+// NotificationKinds 全部通知类型（偏好设置的展示顺序）
+var NotificationKinds = []string{
+	NotificationKindQuotaAlert,
+	NotificationKindKeyExpiry,
+	NotificationKindStatement,
+	NotificationKindAdminInvite,
+}
+
+// notificationMandatoryKinds 不可退订的通知类型
+var notificationMandatoryKinds = map[string]bool{
+	NotificationKindAdminInvite: true,
+}
+
+// 发送记录状态
+const (
+	NotificationStatusQueued  = "queued"
+	NotificationStatusSent    = "sent"
+	NotificationStatusFailed  = "failed"
+	NotificationStatusSkipped = "skipped"
+)
+
+// notificationErrorMaxLen 发送记录中错误信息的最大长度
+const notificationErrorMaxLen = 1000
+
+var (
+	ErrNotificationKindInvalid = infraerrors.BadRequest("NOTIFICATION_KIND_INVALID", "unknown notification kind")
+	ErrNotificationMandatory   = infraerrors.BadRequest("NOTIFICATION_KIND_MANDATORY", "this notification cannot be disabled")
+)
+
+// Notification 待发送的通知
+type Notification struct {
+	Kind string
+	// UserID 收件人用户 ID，用于读取退订偏好；0 表示不检查偏好
+	UserID int64
+	Email  string
+	// DedupeKey 事件去重键，相同的键只发送一次（多实例下同样生效）
+	DedupeKey string
+	// Data 模板变量；SiteName 与 FrontendURL 会自动填充
+	Data map[string]any
+}
+
+// NotificationPreference 用户对某类通知的接收偏好
+type NotificationPreference struct {
+	Kind         string `json:"kind"`
+	EmailEnabled bool   `json:"email_enabled"`
+	// Mandatory 不可退订
+	Mandatory bool `json:"mandatory"`
+}
+
+// NotificationLog 通知邮件发送记录
+type NotificationLog struct {
+	ID        int64      `json:"id"`
+	UserID    *int64     `json:"user_id,omitempty"`
+	Recipient string     `json:"recipient"`
+	Kind      string     `json:"kind"`
+	Subject   string     `json:"subject"`
+	Status    string     `json:"status"`
+	Error     string     `json:"error,omitempty"`
+	Attempts  int        `json:"attempts"`
+	DedupeKey string     `json:"dedupe_key,omitempty"`
+	CreatedAt time.Time  `json:"created_at"`
+	UpdatedAt time.Time  `json:"updated_at"`
+	SentAt    *time.Time `json:"sent_at,omitempty"`
+}
+
+// NotificationLogFilter 发送记录筛选条件，空值表示不筛选
+type NotificationLogFilter struct {
+	UserID    int64
+	Recipient string
+	Kind      string
+	Status    string
+}
+
+// ExpiringAPIKey 即将到期的 API Key 及所属用户邮箱
+type ExpiringAPIKey struct {
+	ID        int64
+	UserID    int64
+	Name      string
+	Email     string
+	ExpiresAt time.Time
+}
+
+// NotificationRepository 通知偏好与发送记录存储
+type NotificationRepository interface {
+	// DisabledKinds 用户已退订的通知类型
+	DisabledKinds(ctx context.Context, userID int64) ([]string, error)
+	SetPreference(ctx context.Context, userID int64, kind string, enabled bool) error
+	// CreateLog 写入发送记录并回填 ID 与时间；DedupeKey 已存在时不写入并返回 false
+	CreateLog(ctx context.Context, log *NotificationLog) (bool, error)
+	// UpdateLogStatus 回写发送结果并累加尝试次数
+	UpdateLogStatus(ctx context.Context, id int64, status, errMsg string) error
+	ListLogs(ctx context.Context, filter NotificationLogFilter, params pagination.PaginationParams) ([]NotificationLog, *pagination.PaginationResult, error)
+	PurgeLogs(ctx context.Context, before time.Time) (int64, error)
+	// ListExpiringAPIKeys 在 [from, to) 内到期、仍为启用状态的 API Key
+	ListExpiringAPIKeys(ctx context.Context, from, to time.Time) ([]ExpiringAPIKey, error)
+}
+
+// NotificationService 模板化邮件通知：按用户偏好过滤、写入发送记录后交给邮件队列发送，
+// 队列回写每次尝试的结果，便于排查投递问题。
+type NotificationService struct {
+	repo        NotificationRepository
+	settingRepo SettingRepository
+	emailQueue  *EmailQueueService
+	cfg         *config.Config
+	now         func() time.Time
+}
+
+// NewNotificationService 创建通知服务
+func NewNotificationService(repo NotificationRepository, settingRepo SettingRepository, emailQueue *EmailQueueService, cfg *config.Config) *NotificationService {
+	return &NotificationService{repo: repo, settingRepo: settingRepo, emailQueue: emailQueue, cfg: cfg, now: time.Now}
+}
+
+// Notify 渲染并投递通知；收件人已退订或事件已通知过时不发送
+func (s *NotificationService) Notify(ctx context.Context, n Notification) error {
+	if s == nil || s.emailQueue == nil || strings.TrimSpace(n.Email) == "" {
+		return nil
+	}
+	tpl, ok := notificationTemplates[n.Kind]
+	if !ok {
+		return ErrNotificationKindInvalid
+	}
+	entry := &NotificationLog{Recipient: n.Email, Kind: n.Kind, DedupeKey: n.DedupeKey}
+	if n.UserID > 0 {
+		entry.UserID = &n.UserID
+	}
+
+	if n.UserID > 0 && !notificationMandatoryKinds[n.Kind] {
+		disabled, err := s.repo.DisabledKinds(ctx, n.UserID)
+		if err != nil {
+			return fmt.Errorf("load notification preferences: %w", err)
+		}
+		for _, kind := range disabled {
+			if kind == n.Kind {
+				entry.Status, entry.Error = NotificationStatusSkipped, "recipient unsubscribed"
+				_, err := s.repo.CreateLog(ctx, entry)
+				return err
+			}
+		}
+	}
+
+	subject, body, err := s.render(ctx, tpl, n.Data)
+	if err != nil {
+		return fmt.Errorf("render %s notification: %w", n.Kind, err)
+	}
+	entry.Subject, entry.Status = subject, NotificationStatusQueued
+	created, err := s.repo.CreateLog(ctx, entry)
+	if err != nil {
+		return fmt.Errorf("create notification log: %w", err)
+	}
+	if !created {
+		return nil
+	}
+	if err := s.emailQueue.EnqueueLoggedNotice(n.Email, subject, body, entry.ID); err != nil {
+		s.RecordEmailResult(ctx, entry.ID, err)
+		return err
+	}
+	return nil
+}
+
+// RecordEmailResult 实现 EmailSendRecorder，回写邮件队列的发送结果
+func (s *NotificationService) RecordEmailResult(ctx context.Context, logID int64, sendErr error) {
+	status, errMsg := NotificationStatusSent, ""
+	if sendErr != nil {
+		status, errMsg = NotificationStatusFailed, sendErr.Error()
+		if len(errMsg) > notificationErrorMaxLen {
+			errMsg = errMsg[:notificationErrorMaxLen]
+		}
+	}
+	if err := s.repo.UpdateLogStatus(ctx, logID, status, errMsg); err != nil {
+		logger.LegacyPrintf("service.notification", "[Notification] update send log %d failed: %v", logID, err)
+	}
+}
+
+// Preferences 返回用户对每类通知的接收偏好
+func (s *NotificationService) Preferences(ctx context.Context, userID int64) ([]NotificationPreference, error) {
+	disabled, err := s.repo.DisabledKinds(ctx, userID)
+	if err != nil {
+		return nil, err
+	}
+	off := make(map[string]bool, len(disabled))
+	for _, kind := range disabled {
+		off[kind] = true
+	}
+	prefs := make([]NotificationPreference, 0, len(NotificationKinds))
+	for _, kind := range NotificationKinds {
+		mandatory := notificationMandatoryKinds[kind]
+		prefs = append(prefs, NotificationPreference{Kind: kind, EmailEnabled: mandatory || !off[kind], Mandatory: mandatory})
+	}
+	return prefs, nil
+}
+
+// UpdatePreferences 修改接收偏好（kind -> 是否接收邮件），未出现的类型保持不变
+func (s *NotificationService) UpdatePreferences(ctx context.Context, userID int64, changes map[string]bool) ([]NotificationPreference, error) {
+	for kind, enabled := range changes {
+		if _, ok := notificationTemplates[kind]; !ok {
+			return nil, ErrNotificationKindInvalid
+		}
+		if notificationMandatoryKinds[kind] && !enabled {
+			return nil, ErrNotificationMandatory
+		}
+	}
+	for kind, enabled := range changes {
+		if err := s.repo.SetPreference(ctx, userID, kind, enabled); err != nil {
+			return nil, err
+		}
+	}
+	return s.Preferences(ctx, userID)
+}
+
+// ListLogs 分页查询发送记录
+func (s *NotificationService) ListLogs(ctx context.Context, filter NotificationLogFilter, params pagination.PaginationParams) ([]NotificationLog, *pagination.PaginationResult, error) {
+	return s.repo.ListLogs(ctx, filter, params)
+}
+
+// WarnExpiringAPIKeys 提醒即将到期的 API Key（定时任务入口），同一 Key 的同一到期时间只提醒一次
+func (s *NotificationService) WarnExpiringAPIKeys(ctx context.Context) error {
+	days := s.cfg.Notifications.KeyExpiryWarnDays
+	if days <= 0 {
+		return nil
+	}
+	now := s.now()
+	keys, err := s.repo.ListExpiringAPIKeys(ctx, now, now.Add(time.Duration(days)*24*time.Hour))
+	if err != nil {
+		return fmt.Errorf("list expiring api keys: %w", err)
+	}
+	failed := 0
+	for _, k := range keys {
+		err := s.Notify(ctx, Notification{
+			Kind:      NotificationKindKeyExpiry,
+			UserID:    k.UserID,
+			Email:     k.Email,
+			DedupeKey: fmt.Sprintf("%s:%d:%d", NotificationKindKeyExpiry, k.ID, k.ExpiresAt.Unix()),
+			Data: map[string]any{
+				"KeyName":   k.Name,
+				"ExpiresAt": k.ExpiresAt.In(timezone.Location()).Format("2006-01-02 15:04 MST"),
+				"DaysLeft":  int(math.Ceil(k.ExpiresAt.Sub(now).Hours() / 24)),
+			},
+		})
+		if err != nil {
+			failed++
+			logger.LegacyPrintf("service.notification", "[Notification] key expiry warning for api key %d failed: %v", k.ID, err)
+		}
+	}
+	if failed > 0 {
+		return fmt.Errorf("%d of %d key expiry warnings failed", failed, len(keys))
+	}
+	return nil
+}
+
+// PurgeExpiredLogs 清除超过保留天数的发送记录（定时任务入口）
+func (s *NotificationService) PurgeExpiredLogs(ctx context.Context) error {
+	before := s.now().AddDate(0, 0, -s.cfg.Notifications.LogRetentionDays)
+	n, err := s.repo.PurgeLogs(ctx, before)
+	if err != nil {
+		return err
+	}
+	if n > 0 {
+		logger.LegacyPrintf("service.notification", "[Notification] purged %d send logs before %s", n, before.Format(time.RFC3339))
+	}
+	return nil
+}
+
+func (s *NotificationService) render(ctx context.Context, tpl notificationTemplate, data map[string]any) (string, string, error) {
+	vars := make(map[string]any, len(data)+2)
+	for k, v := range data {
+		vars[k] = v
+	}
+	vars["SiteName"] = s.siteName(ctx)
+	vars["FrontendURL"] = ""
+	if s.cfg != nil {
+		vars["FrontendURL"] = strings.TrimSuffix(s.cfg.Server.FrontendURL, "/")
+	}
+
+	var subject, content, body bytes.Buffer
+	if err := tpl.subject.Execute(&subject, vars); err != nil {
+		return "", "", err
+	}
+	if err := tpl.body.Execute(&content, vars); err != nil {
+		return "", "", err
+	}
+	err := notificationLayout.Execute(&body, map[string]any{
+		"SiteName":    vars["SiteName"],
+		"FrontendURL": vars["FrontendURL"],
+		// 模板已由 html/template 转义
+		"Content": template.HTML(content.String()),
+	})
+	if err != nil {
+		return "", "", err
+	}
+	return strings.TrimSpace(subject.String()), body.String(), nil
+}
+
+func (s *NotificationService) siteName(ctx context.Context) string {
+	if s.settingRepo == nil {
+		return "Sub2API"
+	}
+	value, err := s.settingRepo.GetValue(ctx, SettingKeySiteName)
+	if err != nil || value == "" {
+		return "Sub2API"
+	}
+	return value
+}
+
+type notificationTemplate struct {
+	subject *texttemplate.Template
+	body    *template.Template
+}
+
+func mustNotificationTemplate(kind, subject, body string) notificationTemplate {
+	return notificationTemplate{
+		subject: texttemplate.Must(texttemplate.New(kind).Option("missingkey=error").Parse(subject)),
+		body:    template.Must(template.New(kind).Option("missingkey=error").Parse(body)),
+	}
+}
+
+var notificationTemplates = map[string]notificationTemplate{
+	NotificationKindQuotaAlert: mustNotificationTemplate(NotificationKindQuotaAlert,
+		`[{{.SiteName}}] Spending cap reached`,
+		`<p>{{.Target}} reached its {{.Period}} spending cap of ${{printf "%.2f" .LimitUSD}} (spent ${{printf "%.2f" .SpentUSD}} since {{.Since}}).</p>
+<p>Requests are suspended until the period resets or an administrator lifts the suspension.</p>`),
+	NotificationKindKeyExpiry: mustNotificationTemplate(NotificationKindKeyExpiry,
+		`[{{.SiteName}}] API key "{{.KeyName}}" expires in {{.DaysLeft}} day(s)`,
+		`<p>Your API key <strong>{{.KeyName}}</strong> expires at {{.ExpiresAt}}.</p>
+<p>Requests using this key will be rejected after it expires. Extend its expiry or create a replacement key before then.</p>
+{{if .FrontendURL}}<p><a href="{{.FrontendURL}}/keys">Manage API keys</a></p>{{end}}`),
+	NotificationKindAdminInvite: mustNotificationTemplate(NotificationKindAdminInvite,
+		`[{{.SiteName}}] You have been invited as {{.Role}}`,
+		`<p>An administrator created an admin console account for you with the <strong>{{.Role}}</strong> role.</p>
+<p>Sign in with this email address and the temporary password you received from the administrator. You will be asked to choose a new password on first sign-in.</p>
+{{if .FrontendURL}}<p><a href="{{.FrontendURL}}/login">Sign in</a></p>{{end}}`),
+	NotificationKindStatement: mustNotificationTemplate(NotificationKindStatement,
+		`[{{.SiteName}}] Your statement for {{.Month}}`,
+		`<p>Your statement for <strong>{{.Month}}</strong> is ready.</p>
+<p>Requests: {{.Requests}}<br>Charged: ${{printf "%.2f" .ActualCost}}<br>Credits added: ${{printf "%.2f" .Credits}}</p>
+<p>Contact the administrator if you need the PDF or CSV copy.</p>`),
+}
+
+var notificationLayout = template.Must(template.New("notification_layout").Parse(`<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="UTF-8">
+    <style>
+        body { font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, Oxygen, Ubuntu, sans-serif; background-color: #f5f5f5; margin: 0; padding: 20px; }
+        .container { max-width: 600px; margin: 0 auto; background-color: #ffffff; border-radius: 8px; overflow: hidden; box-shadow: 0 2px 8px rgba(0,0,0,0.1); }
+        .header { background: linear-gradient(135deg, #667eea 0%, #764ba2 100%); color: white; padding: 30px; text-align: center; }
+        .header h1 { margin: 0; font-size: 24px; }
+        .content { padding: 30px; color: #333; font-size: 15px; line-height: 1.6; }
+        .footer { background-color: #f8f9fa; padding: 20px; text-align: center; color: #999; font-size: 12px; }
+    </style>
+</head>
+<body>
+    <div class="container">
+        <div class="header">
+            <h1>{{.SiteName}}</h1>
+        </div>
+        <div class="content">
+            {{.Content}}
+        </div>
+        <div class="footer">
+            <p>This is an automated message, please do not reply.</p>
+            {{if .FrontendURL}}<p>Manage which emails you receive on your <a href="{{.FrontendURL}}/profile">profile page</a>.</p>{{end}}
+        </div>
+    </div>
+</body>
+</html>
+`))
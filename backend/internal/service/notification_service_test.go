@@ -0,0 +1,181 @@
+//go:build unit
+
+package service
+
+import (
+	"context"
+	"errors"
+	"testing"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/pagination"
+	"github.com/stretchr/testify/require"
+)
+
+type notificationRepoStub struct {
+	disabled map[int64]map[string]bool
+	logs     []*NotificationLog
+	expiring []ExpiringAPIKey
+}
+
+func newNotificationRepoStub() *notificationRepoStub {
+	return &notificationRepoStub{disabled: map[int64]map[string]bool{}}
+}
+
+func (r *notificationRepoStub) DisabledKinds(_ context.Context, userID int64) ([]string, error) {
+	out := make([]string, 0)
+	for kind, off := range r.disabled[userID] {
+		if off {
+			out = append(out, kind)
+		}
+	}
+	return out, nil
+}
+
+func (r *notificationRepoStub) SetPreference(_ context.Context, userID int64, kind string, enabled bool) error {
+	if r.disabled[userID] == nil {
+		r.disabled[userID] = map[string]bool{}
+	}
+	r.disabled[userID][kind] = !enabled
+	return nil
+}
+
+func (r *notificationRepoStub) CreateLog(_ context.Context, log *NotificationLog) (bool, error) {
+	if log.DedupeKey != "" {
+		for _, existing := range r.logs {
+			if existing.DedupeKey == log.DedupeKey {
+				return false, nil
+			}
+		}
+	}
+	log.ID = int64(len(r.logs) + 1)
+	cp := *log
+	r.logs = append(r.logs, &cp)
+	return true, nil
+}
+
+func (r *notificationRepoStub) UpdateLogStatus(_ context.Context, id int64, status, errMsg string) error {
+	log := r.logs[id-1]
+	log.Status, log.Error = status, errMsg
+	log.Attempts++
+	return nil
+}
+
+func (r *notificationRepoStub) ListLogs(context.Context, NotificationLogFilter, pagination.PaginationParams) ([]NotificationLog, *pagination.PaginationResult, error) {
+	panic("unexpected ListLogs call")
+}
+
+func (r *notificationRepoStub) PurgeLogs(context.Context, time.Time) (int64, error) {
+	return 0, nil
+}
+
+func (r *notificationRepoStub) ListExpiringAPIKeys(context.Context, time.Time, time.Time) ([]ExpiringAPIKey, error) {
+	return r.expiring, nil
+}
+
+// newNotificationServiceForTest 邮件队列不启动工作协程，投递的任务留在通道中供断言
+func newNotificationServiceForTest() (*NotificationService, *notificationRepoStub, *EmailQueueService) {
+	repo := newNotificationRepoStub()
+	queue := &EmailQueueService{taskChan: make(chan EmailTask, 10)}
+	cfg := &config.Config{}
+	cfg.Server.FrontendURL = "https://console.example.com/"
+	cfg.Notifications = config.NotificationsConfig{KeyExpiryWarnDays: 7, LogRetentionDays: 90}
+	svc := NewNotificationService(repo, nil, queue, cfg)
+	queue.SetSendRecorder(svc)
+	return svc, repo, queue
+}
+
+func TestNotificationService_NotifyRendersAndLogs(t *testing.T) {
+	svc, repo, queue := newNotificationServiceForTest()
+	ctx := context.Background()
+
+	err := svc.Notify(ctx, Notification{
+		Kind:      NotificationKindKeyExpiry,
+		UserID:    1,
+		Email:     "user@example.com",
+		DedupeKey: "key_expiry:9:100",
+		Data:      map[string]any{"KeyName": "<prod>", "ExpiresAt": "2026-06-01 00:00 UTC", "DaysLeft": 3},
+	})
+	require.NoError(t, err)
+	require.Len(t, queue.taskChan, 1)
+	task := <-queue.taskChan
+	require.Equal(t, `[Sub2API] API key "<prod>" expires in 3 day(s)`, task.Subject)
+	require.Contains(t, task.Body, "&lt;prod&gt;")
+	require.Contains(t, task.Body, `href="https://console.example.com/keys"`)
+	require.Equal(t, int64(1), task.LogID)
+	require.Equal(t, NotificationStatusQueued, repo.logs[0].Status)
+
+	// 同一事件只通知一次
+	require.NoError(t, svc.Notify(ctx, Notification{Kind: NotificationKindKeyExpiry, UserID: 1, Email: "user@example.com",
+		DedupeKey: "key_expiry:9:100", Data: map[string]any{"KeyName": "prod", "ExpiresAt": "x", "DaysLeft": 3}}))
+	require.Len(t, queue.taskChan, 0)
+	require.Len(t, repo.logs, 1)
+
+	// 队列回写发送结果
+	queue.record(ctx, task, errors.New("smtp auth: 535"))
+	require.Equal(t, NotificationStatusFailed, repo.logs[0].Status)
+	require.Equal(t, "smtp auth: 535", repo.logs[0].Error)
+	queue.record(ctx, task, nil)
+	require.Equal(t, NotificationStatusSent, repo.logs[0].Status)
+	require.Equal(t, 2, repo.logs[0].Attempts)
+
+	// 缺少模板变量时不发送
+	err = svc.Notify(ctx, Notification{Kind: NotificationKindKeyExpiry, Email: "user@example.com", Data: map[string]any{}})
+	require.Error(t, err)
+	require.ErrorIs(t, svc.Notify(ctx, Notification{Kind: "unknown", Email: "user@example.com"}), ErrNotificationKindInvalid)
+}
+
+func TestNotificationService_Preferences(t *testing.T) {
+	svc, repo, queue := newNotificationServiceForTest()
+	ctx := context.Background()
+
+	_, err := svc.UpdatePreferences(ctx, 1, map[string]bool{NotificationKindAdminInvite: false})
+	require.ErrorIs(t, err, ErrNotificationMandatory)
+	_, err = svc.UpdatePreferences(ctx, 1, map[string]bool{"newsletter": false})
+	require.ErrorIs(t, err, ErrNotificationKindInvalid)
+
+	prefs, err := svc.UpdatePreferences(ctx, 1, map[string]bool{NotificationKindQuotaAlert: false, NotificationKindAdminInvite: true})
+	require.NoError(t, err)
+	require.Len(t, prefs, len(NotificationKinds))
+	for _, p := range prefs {
+		require.Equal(t, p.Kind != NotificationKindQuotaAlert, p.EmailEnabled, p.Kind)
+		require.Equal(t, p.Kind == NotificationKindAdminInvite, p.Mandatory, p.Kind)
+	}
+
+	// 退订的类型只记录为 skipped
+	quota := Notification{Kind: NotificationKindQuotaAlert, UserID: 1, Email: "user@example.com", Data: map[string]any{
+		"Target": "Your account", "Period": "monthly", "LimitUSD": 10.0, "SpentUSD": 10.5, "Since": "2026-05-01",
+	}}
+	require.NoError(t, svc.Notify(ctx, quota))
+	require.Len(t, queue.taskChan, 0)
+	require.Equal(t, NotificationStatusSkipped, repo.logs[0].Status)
+
+	// 其他用户不受影响；不可退订的类型总是发送
+	quota.UserID = 2
+	require.NoError(t, svc.Notify(ctx, quota))
+	require.NoError(t, svc.Notify(ctx, Notification{Kind: NotificationKindAdminInvite, UserID: 1, Email: "user@example.com",
+		Data: map[string]any{"Role": RoleBilling}}))
+	require.Len(t, queue.taskChan, 2)
+}
+
+func TestNotificationService_WarnExpiringAPIKeys(t *testing.T) {
+	svc, repo, queue := newNotificationServiceForTest()
+	now := time.Date(2026, 5, 20, 12, 0, 0, 0, time.UTC)
+	svc.now = func() time.Time { return now }
+	repo.expiring = []ExpiringAPIKey{
+		{ID: 3, UserID: 1, Name: "ci", Email: "user@example.com", ExpiresAt: now.Add(30 * time.Hour)},
+	}
+	ctx := context.Background()
+
+	require.NoError(t, svc.WarnExpiringAPIKeys(ctx))
+	require.NoError(t, svc.WarnExpiringAPIKeys(ctx))
+	require.Len(t, queue.taskChan, 1)
+	task := <-queue.taskChan
+	require.Contains(t, task.Subject, "expires in 2 day(s)")
+
+	svc.cfg.Notifications.KeyExpiryWarnDays = 0
+	repo.expiring[0].ID = 4
+	require.NoError(t, svc.WarnExpiringAPIKeys(ctx))
+	require.Len(t, queue.taskChan, 0)
+}
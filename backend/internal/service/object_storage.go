@@ -0,0 +1,49 @@
+package service
+
+import (
+	"context"
+	"io"
+	"time"
+
+	infraerrors "github.com/Wei-Shaw/sub2api/internal/pkg/errors"
+)
+
+var ErrObjectNotFound = infraerrors.NotFound("OBJECT_NOT_FOUND", "object not found")
+
+// StoredObject 从对象存储读取的对象，调用方负责关闭 Body
+type StoredObject struct {
+	Body         io.ReadCloser
+	ContentType  string
+	Size         int64
+	LastModified time.Time
+}
+
+// StoredObjectInfo 列举对象时返回的摘要，Key 为相对 key（不含部署前缀）
+type StoredObjectInfo struct {
+	Key          string
+	Size         int64
+	LastModified time.Time
+}
+
+// ObjectWriter 流式写入对象；成功时调用 Close 提交，失败时调用 Abort 放弃
+type ObjectWriter interface {
+	io.WriteCloser
+	Abort()
+}
+
+// ObjectStorage 对象存储（S3 / MinIO 等），由 storage 配置启用；未启用时注入 nil。
+//
+// 所有 key 都是相对 key，实现负责拼接部署级前缀（storage.prefix）。
+type ObjectStorage interface {
+	// Bucket 返回存储桶名称
+	Bucket() string
+	// FullKey 返回带部署前缀的完整 key（用于展示与日志）
+	FullKey(key string) string
+	NewWriter(ctx context.Context, key, contentType string) ObjectWriter
+	// Get 读取对象，不存在时返回 ErrObjectNotFound
+	Get(ctx context.Context, key string) (*StoredObject, error)
+	// Delete 删除对象，不存在时不报错
+	Delete(ctx context.Context, key string) error
+	// List 按相对前缀列举对象，fn 返回错误时停止
+	List(ctx context.Context, prefix string, fn func(StoredObjectInfo) error) error
+}
@@ -0,0 +1,614 @@
+package service
+
+import (
+	"bufio"
+	"encoding/json"
+	"errors"
+	"fmt"
+	"io"
+	"net/http"
+	"strconv"
+	"strings"
+	"time"
+
+	"github.com/gin-gonic/gin"
+	"github.com/tidwall/gjson"
+)
+
+// 本文件负责 Responses API 与 Chat Completions API 之间的互转，
+// 供只提供 /chat/completions 的上游（Copilot 等）承接 /v1/responses 请求。
+
+// convertResponsesToChatCompletions 将 Responses 请求体转换为 Chat Completions 请求体
+func convertResponsesToChatCompletions(body []byte, model string) ([]byte, error) {
+	req := gjson.ParseBytes(body)
+	if req.Get("previous_response_id").Exists() {
+		return nil, errors.New("previous_response_id is not supported by this upstream")
+	}
+
+	var messages []map[string]any
+	if instructions := req.Get("instructions").String(); instructions != "" {
+		messages = append(messages, map[string]any{"role": "system", "content": instructions})
+	}
+
+	input := req.Get("input")
+	if input.Type == gjson.String {
+		messages = append(messages, map[string]any{"role": "user", "content": input.String()})
+	} else {
+		var err error
+		input.ForEach(func(_, item gjson.Result) bool {
+			messages, err = appendResponsesInputItem(messages, item)
+			return err == nil
+		})
+		if err != nil {
+			return nil, err
+		}
+	}
+	if len(messages) == 0 {
+		return nil, errors.New("input is required")
+	}
+
+	out := map[string]any{
+		"model":    model,
+		"messages": messages,
+	}
+	if req.Get("stream").Bool() {
+		out["stream"] = true
+		out["stream_options"] = map[string]any{"include_usage": true}
+	}
+	for _, key := range []string{"temperature", "top_p", "seed", "parallel_tool_calls", "user"} {
+		if v := req.Get(key); v.Exists() {
+			out[key] = v.Value()
+		}
+	}
+	if v := req.Get("max_output_tokens"); v.Exists() {
+		out["max_tokens"] = v.Int()
+	}
+	if effort := req.Get("reasoning.effort").String(); effort != "" {
+		out["reasoning_effort"] = effort
+	}
+	if want, top := responsesLogprobsRequested(req); want {
+		out["logprobs"] = true
+		if top > 0 {
+			out["top_logprobs"] = top
+		}
+	}
+
+	switch format := req.Get("text.format"); format.Get("type").String() {
+	case "json_schema":
+		schema := map[string]any{
+			"name":   format.Get("name").String(),
+			"schema": format.Get("schema").Value(),
+		}
+		if strict := format.Get("strict"); strict.Exists() {
+			schema["strict"] = strict.Bool()
+		}
+		out["response_format"] = map[string]any{"type": "json_schema", "json_schema": schema}
+	case "json_object":
+		out["response_format"] = map[string]any{"type": "json_object"}
+	}
+
+	if tools := req.Get("tools"); tools.IsArray() && len(tools.Array()) > 0 {
+		var chatTools []any
+		for _, tool := range tools.Array() {
+			if tool.Get("type").String() != "function" {
+				return nil, fmt.Errorf("tool type %q is not supported by this upstream", tool.Get("type").String())
+			}
+			fn := map[string]any{"name": tool.Get("name").String()}
+			if v := tool.Get("description"); v.Exists() {
+				fn["description"] = v.String()
+			}
+			if v := tool.Get("parameters"); v.Exists() {
+				fn["parameters"] = v.Value()
+			}
+			if v := tool.Get("strict"); v.Exists() {
+				fn["strict"] = v.Bool()
+			}
+			chatTools = append(chatTools, map[string]any{"type": "function", "function": fn})
+		}
+		out["tools"] = chatTools
+	}
+	if choice := req.Get("tool_choice"); choice.Exists() {
+		if choice.Type == gjson.String {
+			out["tool_choice"] = choice.String()
+		} else if choice.Get("type").String() == "function" {
+			out["tool_choice"] = map[string]any{"type": "function", "function": map[string]any{"name": choice.Get("name").String()}}
+		}
+	}
+
+	return json.Marshal(out)
+}
+
+func appendResponsesInputItem(messages []map[string]any, item gjson.Result) ([]map[string]any, error) {
+	itemType := item.Get("type").String()
+	if itemType == "" && item.Get("role").Exists() {
+		itemType = "message"
+	}
+
+	switch itemType {
+	case "message":
+		role := item.Get("role").String()
+		if role == "developer" {
+			role = "system"
+		}
+		content, err := responsesContentToChat(item.Get("content"), role == "user")
+		if err != nil {
+			return nil, err
+		}
+		return append(messages, map[string]any{"role": role, "content": content}), nil
+	case "function_call":
+		call := map[string]any{
+			"id":   item.Get("call_id").String(),
+			"type": "function",
+			"function": map[string]any{
+				"name":      item.Get("name").String(),
+				"arguments": item.Get("arguments").String(),
+			},
+		}
+		// 连续的 function_call 合并到同一条 assistant 消息
+		if n := len(messages); n > 0 && messages[n-1]["role"] == "assistant" {
+			last := messages[n-1]
+			calls, _ := last["tool_calls"].([]any)
+			last["tool_calls"] = append(calls, call)
+			return messages, nil
+		}
+		return append(messages, map[string]any{"role": "assistant", "content": nil, "tool_calls": []any{call}}), nil
+	case "function_call_output":
+		output := item.Get("output")
+		text := output.String()
+		if output.IsArray() {
+			parts, err := responsesContentToChat(output, false)
+			if err != nil {
+				return nil, err
+			}
+			text, _ = parts.(string)
+		}
+		return append(messages, map[string]any{
+			"role":         "tool",
+			"tool_call_id": item.Get("call_id").String(),
+			"content":      text,
+		}), nil
+	case "reasoning":
+		// 推理摘要只对原生 Responses 上游有意义
+		return messages, nil
+	default:
+		return nil, fmt.Errorf("input item type %q is not supported by this upstream", itemType)
+	}
+}
+
+// responsesContentToChat 转换消息内容；仅用户消息在包含图片时使用多段格式，其余拼接为纯文本
+func responsesContentToChat(content gjson.Result, allowImages bool) (any, error) {
+	if content.Type == gjson.String {
+		return content.String(), nil
+	}
+
+	var (
+		texts    []string
+		parts    []any
+		hasImage bool
+	)
+	for _, part := range content.Array() {
+		switch partType := part.Get("type").String(); partType {
+		case "input_text", "output_text", "text":
+			texts = append(texts, part.Get("text").String())
+			parts = append(parts, map[string]any{"type": "text", "text": part.Get("text").String()})
+		case "refusal":
+			texts = append(texts, part.Get("refusal").String())
+			parts = append(parts, map[string]any{"type": "text", "text": part.Get("refusal").String()})
+		case "input_image":
+			imageURL := part.Get("image_url").String()
+			if !allowImages || imageURL == "" {
+				return nil, errors.New("input_image is only supported in user messages with image_url")
+			}
+			image := map[string]any{"url": imageURL}
+			if detail := part.Get("detail").String(); detail != "" {
+				image["detail"] = detail
+			}
+			parts = append(parts, map[string]any{"type": "image_url", "image_url": image})
+			hasImage = true
+		default:
+			return nil, fmt.Errorf("content type %q is not supported by this upstream", partType)
+		}
+	}
+	if hasImage {
+		return parts, nil
+	}
+	return strings.Join(texts, "\n"), nil
+}
+
+// chatCompletionUsage 将 Chat Completions 的 usage 转为内部用量
+func chatCompletionUsage(usage gjson.Result) OpenAIUsage {
+	return OpenAIUsage{
+		InputTokens:          int(usage.Get("prompt_tokens").Int()),
+		OutputTokens:         int(usage.Get("completion_tokens").Int()),
+		CacheReadInputTokens: int(usage.Get("prompt_tokens_details.cached_tokens").Int()),
+		ReasoningTokens:      int(usage.Get("completion_tokens_details.reasoning_tokens").Int()),
+	}
+}
+
+// chatFinishStatus 将上游 finish_reason（先按 NormalizeFinishReason 规范化）映射为 Responses 的 status / incomplete_details.reason
+func chatFinishStatus(overrides map[string]map[string]string, finishReason string) (status string, incompleteReason string) {
+	switch NormalizeFinishReason(overrides, FinishReasonSourceOpenAI, finishReason) {
+	case FinishReasonLength:
+		return "incomplete", "max_output_tokens"
+	case FinishReasonContentFilter:
+		return "incomplete", "content_filter"
+	default:
+		return "completed", ""
+	}
+}
+
+func chatCompatResponseObject(id, model string, createdAt int64, status, incompleteReason string, output []any, usage *OpenAIUsage) map[string]any {
+	if output == nil {
+		output = []any{}
+	}
+	resp := map[string]any{
+		"id":         "resp_" + id,
+		"object":     "response",
+		"created_at": createdAt,
+		"status":     status,
+		"model":      model,
+		"output":     output,
+	}
+	if incompleteReason != "" {
+		resp["incomplete_details"] = map[string]any{"reason": incompleteReason}
+	}
+	if usage != nil {
+		resp["usage"] = map[string]any{
+			"input_tokens":          usage.InputTokens,
+			"input_tokens_details":  map[string]any{"cached_tokens": usage.CacheReadInputTokens},
+			"output_tokens":         usage.OutputTokens,
+			"output_tokens_details": map[string]any{"reasoning_tokens": usage.ReasoningTokens},
+			"total_tokens":          usage.InputTokens + usage.OutputTokens,
+		}
+	}
+	return resp
+}
+
+// chatCompatMessageItem logprobs 为 nil 时不输出 logprobs 字段（客户端未请求）
+func chatCompatMessageItem(id, status, text string, logprobs []any) map[string]any {
+	return map[string]any{
+		"id":      "msg_" + id,
+		"type":    "message",
+		"status":  status,
+		"role":    "assistant",
+		"content": []any{chatCompatOutputText(text, logprobs)},
+	}
+}
+
+func chatCompatOutputText(text string, logprobs []any) map[string]any {
+	part := map[string]any{"type": "output_text", "text": text, "annotations": []any{}}
+	if logprobs != nil {
+		part["logprobs"] = logprobs
+	}
+	return part
+}
+
+// chatCompatLogprobs 提取 choice.logprobs.content（与 Responses output_text.logprobs 结构一致）；
+// 未请求时返回 nil，请求了但上游未提供时返回空数组
+func chatCompatLogprobs(choice gjson.Result, requested bool) ([]any, bool) {
+	if !requested {
+		return nil, false
+	}
+	content := choice.Get("logprobs.content")
+	if !content.IsArray() {
+		return []any{}, false
+	}
+	logprobs, _ := content.Value().([]any)
+	if logprobs == nil {
+		logprobs = []any{}
+	}
+	return logprobs, true
+}
+
+func chatCompatFunctionCallItem(id string, index int, status, callID, name, arguments string) map[string]any {
+	return map[string]any{
+		"id":        "fc_" + id + "_" + strconv.Itoa(index),
+		"type":      "function_call",
+		"status":    status,
+		"call_id":   callID,
+		"name":      name,
+		"arguments": arguments,
+	}
+}
+
+// chatCompletionToResponses 将非流式 chat.completion 转为 Responses 响应对象；
+// logprobs 为客户端是否请求了 logprobs，返回值 emulated 表示上游未提供而以空数组代替
+func chatCompletionToResponses(completion []byte, responseID, model string, createdAt int64, logprobs bool, overrides map[string]map[string]string) (resp map[string]any, usage OpenAIUsage, emulated bool) {
+	parsed := gjson.ParseBytes(completion)
+	usage = chatCompletionUsage(parsed.Get("usage"))
+	message := parsed.Get("choices.0.message")
+
+	var output []any
+	if text := message.Get("content").String(); text != "" {
+		tokenLogprobs, provided := chatCompatLogprobs(parsed.Get("choices.0"), logprobs)
+		emulated = logprobs && !provided
+		output = append(output, chatCompatMessageItem(responseID, "completed", text, tokenLogprobs))
+	}
+	for i, call := range message.Get("tool_calls").Array() {
+		output = append(output, chatCompatFunctionCallItem(responseID, i, "completed",
+			call.Get("id").String(), call.Get("function.name").String(), call.Get("function.arguments").String()))
+	}
+	status, incompleteReason := chatFinishStatus(overrides, parsed.Get("choices.0.finish_reason").String())
+	return chatCompatResponseObject(responseID, model, createdAt, status, incompleteReason, output, &usage), usage, emulated
+}
+
+type chatCompatToolCall struct {
+	outputIndex int
+	callID      string
+	name        string
+	arguments   strings.Builder
+}
+
+// chatCompatStream 将 chat.completion.chunk 流转换为 Responses 流式事件
+type chatCompatStream struct {
+	responseID string
+	model      string
+	createdAt  int64
+	emit       func(event string, data map[string]any)
+
+	nextOutput   int
+	textIndex    int
+	text         strings.Builder
+	toolCalls    map[int64]*chatCompatToolCall
+	toolOrder    []int64
+	finishReason string
+	usage        OpenAIUsage
+	// logprobs 客户端请求了 logprobs：逐段透传上游 logprobs，缺失时以空数组代替
+	logprobs     bool
+	textLogprobs []any
+	// finishReasonOverrides gateway.finish_reason_overrides
+	finishReasonOverrides map[string]map[string]string
+}
+
+func newChatCompatStream(responseID, model string, createdAt int64, overrides map[string]map[string]string, emit func(event string, data map[string]any)) *chatCompatStream {
+	return &chatCompatStream{
+		responseID:            responseID,
+		model:                 model,
+		createdAt:             createdAt,
+		emit:                  emit,
+		textIndex:             -1,
+		toolCalls:             make(map[int64]*chatCompatToolCall),
+		finishReasonOverrides: overrides,
+	}
+}
+
+func (st *chatCompatStream) start() {
+	st.emit("response.created", map[string]any{"response": chatCompatResponseObject(st.responseID, st.model, st.createdAt, "in_progress", "", nil, nil)})
+}
+
+// consume 处理一个 chunk，返回是否产生了输出内容（用于首 token 计时）
+func (st *chatCompatStream) consume(chunk gjson.Result) bool {
+	if usage := chunk.Get("usage"); usage.IsObject() {
+		st.usage = chatCompletionUsage(usage)
+	}
+	produced := false
+	for _, choice := range chunk.Get("choices").Array() {
+		delta := choice.Get("delta")
+		if text := delta.Get("content").String(); text != "" {
+			tokenLogprobs, _ := chatCompatLogprobs(choice, st.logprobs)
+			st.appendText(text, tokenLogprobs)
+			produced = true
+		}
+		for _, call := range delta.Get("tool_calls").Array() {
+			st.appendToolCall(call)
+			produced = true
+		}
+		if reason := choice.Get("finish_reason").String(); reason != "" {
+			st.finishReason = reason
+		}
+	}
+	return produced
+}
+
+func (st *chatCompatStream) appendText(delta string, logprobs []any) {
+	itemID := "msg_" + st.responseID
+	if st.textIndex < 0 {
+		st.textIndex = st.nextOutput
+		st.nextOutput++
+		st.emit("response.output_item.added", map[string]any{
+			"output_index": st.textIndex,
+			"item":         map[string]any{"id": itemID, "type": "message", "status": "in_progress", "role": "assistant", "content": []any{}},
+		})
+		st.emit("response.content_part.added", map[string]any{
+			"item_id":       itemID,
+			"output_index":  st.textIndex,
+			"content_index": 0,
+			"part":          chatCompatOutputText("", st.partLogprobs()),
+		})
+	}
+	st.text.WriteString(delta)
+	event := map[string]any{
+		"item_id":       itemID,
+		"output_index":  st.textIndex,
+		"content_index": 0,
+		"delta":         delta,
+	}
+	if logprobs != nil {
+		event["logprobs"] = logprobs
+		st.textLogprobs = append(st.textLogprobs, logprobs...)
+	}
+	st.emit("response.output_text.delta", event)
+}
+
+// partLogprobs 已累计的 logprobs；未请求时为 nil
+func (st *chatCompatStream) partLogprobs() []any {
+	if !st.logprobs {
+		return nil
+	}
+	if st.textLogprobs == nil {
+		return []any{}
+	}
+	return st.textLogprobs
+}
+
+func (st *chatCompatStream) appendToolCall(call gjson.Result) {
+	index := call.Get("index").Int()
+	tc, ok := st.toolCalls[index]
+	if !ok {
+		tc = &chatCompatToolCall{outputIndex: st.nextOutput, callID: call.Get("id").String(), name: call.Get("function.name").String()}
+		st.nextOutput++
+		st.toolCalls[index] = tc
+		st.toolOrder = append(st.toolOrder, index)
+		st.emit("response.output_item.added", map[string]any{
+			"output_index": tc.outputIndex,
+			"item":         chatCompatFunctionCallItem(st.responseID, tc.outputIndex, "in_progress", tc.callID, tc.name, ""),
+		})
+	}
+	if args := call.Get("function.arguments").String(); args != "" {
+		tc.arguments.WriteString(args)
+		st.emit("response.function_call_arguments.delta", map[string]any{
+			"item_id":      "fc_" + st.responseID + "_" + strconv.Itoa(tc.outputIndex),
+			"output_index": tc.outputIndex,
+			"delta":        args,
+		})
+	}
+}
+
+// finish 关闭所有输出项并发送 response.completed / response.incomplete
+func (st *chatCompatStream) finish() {
+	output := make([]any, st.nextOutput)
+	if st.textIndex >= 0 {
+		itemID := "msg_" + st.responseID
+		text := st.text.String()
+		st.emit("response.output_text.done", map[string]any{"item_id": itemID, "output_index": st.textIndex, "content_index": 0, "text": text})
+		st.emit("response.content_part.done", map[string]any{
+			"item_id":       itemID,
+			"output_index":  st.textIndex,
+			"content_index": 0,
+			"part":          chatCompatOutputText(text, st.partLogprobs()),
+		})
+		item := chatCompatMessageItem(st.responseID, "completed", text, st.partLogprobs())
+		st.emit("response.output_item.done", map[string]any{"output_index": st.textIndex, "item": item})
+		output[st.textIndex] = item
+	}
+	for _, index := range st.toolOrder {
+		tc := st.toolCalls[index]
+		item := chatCompatFunctionCallItem(st.responseID, tc.outputIndex, "completed", tc.callID, tc.name, tc.arguments.String())
+		st.emit("response.function_call_arguments.done", map[string]any{
+			"item_id":      item["id"],
+			"output_index": tc.outputIndex,
+			"arguments":    tc.arguments.String(),
+		})
+		st.emit("response.output_item.done", map[string]any{"output_index": tc.outputIndex, "item": item})
+		output[tc.outputIndex] = item
+	}
+
+	status, incompleteReason := chatFinishStatus(st.finishReasonOverrides, st.finishReason)
+	final := chatCompatResponseObject(st.responseID, st.model, st.createdAt, status, incompleteReason, output, &st.usage)
+	if status == "completed" {
+		st.emit("response.completed", map[string]any{"response": final})
+	} else {
+		st.emit("response.incomplete", map[string]any{"response": final})
+	}
+}
+
+// readChatCompletionStream 逐个读取 chat.completion.chunk；onChunk 返回 false 时停止读取
+func readChatCompletionStream(r io.Reader, maxLineSize int, onChunk func(chunk gjson.Result) bool) error {
+	scanner := bufio.NewScanner(r)
+	scanner.Buffer(make([]byte, 0, 64*1024), maxLineSize)
+	for scanner.Scan() {
+		data, ok := extractOpenAISSEDataLine(scanner.Text())
+		if !ok || data == "" {
+			continue
+		}
+		if data == "[DONE]" {
+			return nil
+		}
+		chunk := gjson.Parse(data)
+		if errObj := chunk.Get("error"); errObj.Exists() {
+			return fmt.Errorf("upstream stream error: %s", errObj.Get("message").String())
+		}
+		if !onChunk(chunk) {
+			return nil
+		}
+	}
+	return scanner.Err()
+}
+
+// relayChatCompletionAsResponses 将上游 Chat Completions 响应以 Responses 格式写回客户端
+// logprobs 为客户端是否请求了 logprobs；非流式响应中上游未提供时设置 LogprobsHeader: emulated
+func (s *OpenAIGatewayService) relayChatCompletionAsResponses(c *gin.Context, resp *http.Response, model string, stream, logprobs bool, startTime time.Time) (*OpenAIForwardResult, error) {
+	responseID := randomHex(12)
+	createdAt := startTime.Unix()
+	result := &OpenAIForwardResult{
+		RequestID: resp.Header.Get("x-request-id"),
+		Model:     model,
+		Stream:    stream,
+	}
+	if result.RequestID == "" {
+		result.RequestID = responseID
+	}
+
+	if !stream {
+		raw, err := readUpstreamResponseBodyLimited(resp.Body, resolveUpstreamResponseReadLimit(s.cfg))
+		if err != nil {
+			writeChatGPTWebError(c, http.StatusBadGateway, "upstream_error", "Failed to read upstream response")
+			return nil, err
+		}
+		out, usage, emulated := chatCompletionToResponses(raw, responseID, model, createdAt, logprobs, finishReasonOverrides(s.cfg))
+		if emulated {
+			c.Header(LogprobsHeader, LogprobsEmulated)
+		}
+		c.JSON(http.StatusOK, out)
+		result.Usage = usage
+		result.Duration = time.Since(startTime)
+		return result, nil
+	}
+
+	c.Header("Content-Type", "text/event-stream")
+	c.Header("Cache-Control", "no-cache")
+	c.Header("Connection", "keep-alive")
+	c.Header("X-Accel-Buffering", "no")
+	c.Status(http.StatusOK)
+
+	flusher, ok := c.Writer.(http.Flusher)
+	if !ok {
+		return nil, errors.New("streaming not supported")
+	}
+	cancelOnDisconnect := s.cfg != nil && s.cfg.Gateway.CancelUpstreamOnClientDisconnect
+	clientDisconnected := false
+	maxLineSize := defaultMaxLineSize
+	if s.cfg != nil && s.cfg.Gateway.MaxLineSize > 0 {
+		maxLineSize = s.cfg.Gateway.MaxLineSize
+	}
+
+	seq := 0
+	st := newChatCompatStream(responseID, model, createdAt, finishReasonOverrides(s.cfg), func(event string, data map[string]any) {
+		if clientDisconnected {
+			return
+		}
+		data["type"] = event
+		data["sequence_number"] = seq
+		seq++
+		writeSSE(c.Writer, event, data)
+	})
+	st.logprobs = logprobs
+	st.start()
+	flusher.Flush()
+
+	err := readChatCompletionStream(resp.Body, maxLineSize, func(chunk gjson.Result) bool {
+		if st.consume(chunk) && result.FirstTokenMs == nil {
+			ms := int(time.Since(startTime).Milliseconds())
+			result.FirstTokenMs = &ms
+		}
+		if clientDisconnected {
+			// 继续读取上游以完成计费，除非配置了断开即取消
+			return !cancelOnDisconnect
+		}
+		flusher.Flush()
+		if c.Request != nil && c.Request.Context().Err() != nil {
+			clientDisconnected = true
+		}
+		return true
+	})
+	result.Usage = st.usage
+	result.Duration = time.Since(startTime)
+	if err != nil && !clientDisconnected {
+		st.emit("error", map[string]any{"code": "upstream_error", "message": "Upstream stream ended with an error"})
+		flusher.Flush()
+		return nil, err
+	}
+	st.finish()
+	flusher.Flush()
+	return result, nil
+}
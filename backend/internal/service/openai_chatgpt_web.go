@@ -0,0 +1,665 @@
+package service
+
+import (
+	"bufio"
+	"bytes"
+	"context"
+	"crypto/sha3"
+	"encoding/base64"
+	"encoding/hex"
+	"encoding/json"
+	"errors"
+	"fmt"
+	"io"
+	"net/http"
+	"regexp"
+	"strconv"
+	"strings"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/pkg/claude"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/logger"
+	"github.com/gin-gonic/gin"
+	"github.com/google/uuid"
+	"github.com/tidwall/gjson"
+)
+
+const (
+	chatGPTWebDefaultBaseURL   = "https://chatgpt.com"
+	chatGPTWebDefaultUserAgent = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/131.0.0.0 Safari/537.36"
+
+	// chatGPTWebPoWMaxAttempts 工作量证明的最大尝试次数；正常难度下几千次以内即可命中
+	chatGPTWebPoWMaxAttempts = 500000
+
+	chatGPTWebCleanupTimeout = 10 * time.Second
+)
+
+// chatGPTWebModelSlugs API 模型名与网页端模型标识不一致的映射，其余模型名原样使用
+var chatGPTWebModelSlugs = map[string]string{
+	"chatgpt-4o-latest": "gpt-4o",
+	"gpt-4.1":           "gpt-4-1",
+	"gpt-4.1-mini":      "gpt-4-1-mini",
+	"gpt-4.5":           "gpt-4-5",
+	"gpt-4.5-preview":   "gpt-4-5",
+}
+
+var chatGPTWebDateSuffix = regexp.MustCompile(`-\d{4}-\d{2}-\d{2}$`)
+
+// errChatGPTWebChallengeRequired 网页端要求 Turnstile / Arkose 人机验证，当前账号无法自动通过
+var errChatGPTWebChallengeRequired = errors.New("chatgpt web session requires an interactive challenge")
+
+// chatGPTWebModelSlug 将请求模型转换为网页端模型标识（去掉日期快照后缀）
+func chatGPTWebModelSlug(model string) string {
+	model = strings.ToLower(strings.TrimSpace(model))
+	if model == "" {
+		return "auto"
+	}
+	model = chatGPTWebDateSuffix.ReplaceAllString(model, "")
+	if slug, ok := chatGPTWebModelSlugs[model]; ok {
+		return slug
+	}
+	return model
+}
+
+// buildChatGPTWebPrompt 将 Responses 请求压平为网页对话的单条用户消息。
+// 网页端每轮只接受一条消息，多轮历史以 User/Assistant 转录的形式写入同一条 prompt。
+func buildChatGPTWebPrompt(body []byte) (string, error) {
+	if tools := gjson.GetBytes(body, "tools"); tools.IsArray() && len(tools.Array()) > 0 {
+		return "", errors.New("tools are not supported by ChatGPT web session accounts")
+	}
+
+	instructions := []string{}
+	if v := strings.TrimSpace(gjson.GetBytes(body, "instructions").String()); v != "" {
+		instructions = append(instructions, v)
+	}
+
+	input := gjson.GetBytes(body, "input")
+	if input.Type == gjson.String {
+		if len(instructions) == 0 {
+			return input.String(), nil
+		}
+		return instructions[0] + "\n\nUser: " + input.String(), nil
+	}
+	if !input.IsArray() || len(input.Array()) == 0 {
+		return "", errors.New("input: at least one message is required")
+	}
+
+	type turn struct {
+		role string
+		text string
+	}
+	var turns []turn
+	for _, item := range input.Array() {
+		if itemType := item.Get("type").String(); itemType != "" && itemType != "message" {
+			return "", fmt.Errorf("input item type %q is not supported by ChatGPT web session accounts", itemType)
+		}
+		text, err := chatGPTWebContentText(item.Get("content"))
+		if err != nil {
+			return "", err
+		}
+		switch role := item.Get("role").String(); role {
+		case "system", "developer":
+			instructions = append(instructions, text)
+		default:
+			turns = append(turns, turn{role: role, text: text})
+		}
+	}
+	if len(turns) == 0 {
+		return "", errors.New("input: at least one user message is required")
+	}
+
+	if len(instructions) == 0 && len(turns) == 1 && turns[0].role == "user" {
+		return turns[0].text, nil
+	}
+
+	var b strings.Builder
+	for _, v := range instructions {
+		b.WriteString(v)
+		b.WriteString("\n\n")
+	}
+	for _, t := range turns {
+		if t.role == "assistant" {
+			b.WriteString("Assistant: ")
+		} else {
+			b.WriteString("User: ")
+		}
+		b.WriteString(t.text)
+		b.WriteString("\n\n")
+	}
+	return strings.TrimSpace(b.String()), nil
+}
+
+func chatGPTWebContentText(content gjson.Result) (string, error) {
+	if content.Type == gjson.String {
+		return content.String(), nil
+	}
+	var (
+		parts []string
+		err   error
+	)
+	content.ForEach(func(_, part gjson.Result) bool {
+		switch partType := part.Get("type").String(); partType {
+		case "input_text", "output_text", "text":
+			parts = append(parts, part.Get("text").String())
+		default:
+			err = fmt.Errorf("content part type %q is not supported by ChatGPT web session accounts", partType)
+			return false
+		}
+		return true
+	})
+	return strings.Join(parts, "\n"), err
+}
+
+// solveChatGPTProofOfWork 求解 sentinel 工作量证明：寻找使 sha3-512(seed + answer) 的十六进制前缀不大于 difficulty 的浏览器指纹配置
+func solveChatGPTProofOfWork(seed, difficulty, userAgent string, now time.Time) (string, bool) {
+	difficulty = strings.ToLower(strings.TrimSpace(difficulty))
+	config := []any{
+		3008,
+		now.UTC().Format("Mon Jan 02 2006 15:04:05") + " GMT+0000 (Coordinated Universal Time)",
+		4294705152,
+		0,
+		userAgent,
+		"",
+		"",
+		"en-US",
+		"en-US,en",
+		0,
+		"webdriver−false",
+		"location",
+		"window",
+		1000.0,
+		uuid.NewString(),
+		"",
+		8,
+		float64(now.UnixMilli()),
+	}
+	for attempt := 0; attempt < chatGPTWebPoWMaxAttempts; attempt++ {
+		config[3] = attempt
+		config[9] = attempt >> 1
+		payload, err := json.Marshal(config)
+		if err != nil {
+			return "", false
+		}
+		answer := base64.StdEncoding.EncodeToString(payload)
+		sum := sha3.Sum512([]byte(seed + answer))
+		digest := hex.EncodeToString(sum[:])
+		if digest[:min(len(difficulty), len(digest))] <= difficulty {
+			return "gAAAAAB" + answer, true
+		}
+	}
+	return "", false
+}
+
+// chatGPTWebStreamState 解析 backend-api 的 SSE 输出，兼容整段快照与 delta 编码两种格式
+type chatGPTWebStreamState struct {
+	conversationID string
+	finishType     string
+	text           string
+	assistant      bool
+}
+
+func (st *chatGPTWebStreamState) applyMessage(msg gjson.Result) string {
+	st.assistant = msg.Get("author.role").String() == "assistant" && msg.Get("content.content_type").String() == "text"
+	if reason := msg.Get("metadata.finish_details.type").String(); reason != "" && st.assistant {
+		st.finishType = reason
+	}
+	if !st.assistant {
+		return ""
+	}
+	full := msg.Get("content.parts.0").String()
+	if !strings.HasPrefix(full, st.text) {
+		// 新的助手消息（例如推理模型先输出思考再输出正文），从头累计
+		st.text = ""
+	}
+	delta := full[len(st.text):]
+	st.text = full
+	return delta
+}
+
+func (st *chatGPTWebStreamState) appendText(delta string) string {
+	if !st.assistant {
+		return ""
+	}
+	st.text += delta
+	return delta
+}
+
+func (st *chatGPTWebStreamState) apply(event gjson.Result) string {
+	if id := event.Get("conversation_id").String(); id != "" {
+		st.conversationID = id
+	}
+	if msg := event.Get("message"); msg.IsObject() {
+		return st.applyMessage(msg)
+	}
+
+	v := event.Get("v")
+	switch {
+	case v.IsObject():
+		if id := v.Get("conversation_id").String(); id != "" {
+			st.conversationID = id
+		}
+		if msg := v.Get("message"); msg.IsObject() {
+			return st.applyMessage(msg)
+		}
+	case v.Type == gjson.String:
+		if p := event.Get("p").String(); p == "" || p == "/message/content/parts/0" {
+			if o := event.Get("o").String(); o == "" || o == "append" {
+				return st.appendText(v.String())
+			}
+		}
+	case v.IsArray():
+		var delta strings.Builder
+		v.ForEach(func(_, op gjson.Result) bool {
+			switch op.Get("p").String() {
+			case "/message/content/parts/0":
+				if op.Get("o").String() == "append" {
+					delta.WriteString(st.appendText(op.Get("v").String()))
+				}
+			case "/message/metadata/finish_details":
+				if reason := op.Get("v.type").String(); reason != "" && st.assistant {
+					st.finishType = reason
+				}
+			}
+			return true
+		})
+		return delta.String()
+	}
+	return ""
+}
+
+// readChatGPTWebEvents 逐条解析网页对话输出，onText 返回 false 时停止读取
+func readChatGPTWebEvents(r io.Reader, maxLineSize int, onText func(delta string) bool) (*chatGPTWebStreamState, error) {
+	scanner := bufio.NewScanner(r)
+	scanner.Buffer(make([]byte, 0, 64*1024), maxLineSize)
+
+	st := &chatGPTWebStreamState{}
+	for scanner.Scan() {
+		line := strings.TrimSpace(scanner.Text())
+		if !strings.HasPrefix(line, "data:") {
+			continue
+		}
+		data := strings.TrimSpace(strings.TrimPrefix(line, "data:"))
+		if data == "[DONE]" {
+			break
+		}
+		if data == "" || !gjson.Valid(data) {
+			continue
+		}
+		event := gjson.Parse(data)
+		if e := event.Get("error"); e.Exists() && e.Type != gjson.Null && e.String() != "" {
+			return st, fmt.Errorf("chatgpt stream error: %s", e.String())
+		}
+		if delta := st.apply(event); delta != "" && !onText(delta) {
+			return st, nil
+		}
+	}
+	if err := scanner.Err(); err != nil {
+		return st, fmt.Errorf("stream read error: %w", err)
+	}
+	return st, nil
+}
+
+func chatGPTWebStatus(finishType string) (status string, incompleteReason string) {
+	if finishType == "max_tokens" {
+		return "incomplete", "max_output_tokens"
+	}
+	return "completed", ""
+}
+
+func chatGPTWebResponseObject(id, model string, createdAt int64, status, incompleteReason, text string, usage *OpenAIUsage) map[string]any {
+	output := []any{}
+	if status != "in_progress" {
+		output = append(output, map[string]any{
+			"id":     "msg_" + id,
+			"type":   "message",
+			"status": "completed",
+			"role":   "assistant",
+			"content": []any{map[string]any{
+				"type":        "output_text",
+				"text":        text,
+				"annotations": []any{},
+			}},
+		})
+	}
+	resp := map[string]any{
+		"id":         "resp_" + id,
+		"object":     "response",
+		"created_at": createdAt,
+		"status":     status,
+		"model":      model,
+		"output":     output,
+	}
+	if incompleteReason != "" {
+		resp["incomplete_details"] = map[string]any{"reason": incompleteReason}
+	}
+	if usage != nil {
+		resp["usage"] = map[string]any{
+			"input_tokens":  usage.InputTokens,
+			"output_tokens": usage.OutputTokens,
+			"total_tokens":  usage.InputTokens + usage.OutputTokens,
+		}
+	}
+	return resp
+}
+
+func writeChatGPTWebError(c *gin.Context, status int, errType, message string) {
+	c.JSON(status, gin.H{
+		"error": gin.H{
+			"type":    errType,
+			"message": message,
+		},
+	})
+}
+
+func (s *OpenAIGatewayService) chatGPTWebBaseURL(account *Account) (string, error) {
+	raw := strings.TrimSpace(account.GetCredential("base_url"))
+	if raw == "" {
+		return chatGPTWebDefaultBaseURL, nil
+	}
+	validated, err := s.validateUpstreamBaseURL(raw)
+	if err != nil {
+		return "", err
+	}
+	return strings.TrimRight(validated, "/"), nil
+}
+
+func chatGPTWebUserAgent(account *Account) string {
+	if ua := strings.TrimSpace(account.GetCredential("user_agent")); ua != "" {
+		return ua
+	}
+	return chatGPTWebDefaultUserAgent
+}
+
+func (s *OpenAIGatewayService) newChatGPTWebRequest(ctx context.Context, account *Account, method, baseURL, path string, body any) (*http.Request, error) {
+	var reader io.Reader
+	if body != nil {
+		payload, err := json.Marshal(body)
+		if err != nil {
+			return nil, err
+		}
+		reader = bytes.NewReader(payload)
+	}
+	req, err := http.NewRequestWithContext(ctx, method, baseURL+path, reader)
+	if err != nil {
+		return nil, err
+	}
+
+	// 设备 ID 需在同一账号的请求间保持稳定，未配置时按账号 ID 派生
+	deviceID := strings.TrimSpace(account.GetCredential("device_id"))
+	if deviceID == "" {
+		deviceID = uuid.NewSHA1(uuid.NameSpaceOID, []byte("sub2api-chatgpt-web-"+strconv.FormatInt(account.ID, 10))).String()
+	}
+	req.Header.Set("Authorization", "Bearer "+account.GetCredential("access_token"))
+	if accountID := strings.TrimSpace(account.GetCredential("chatgpt_account_id")); accountID != "" {
+		req.Header.Set("chatgpt-account-id", accountID)
+	}
+	req.Header.Set("User-Agent", chatGPTWebUserAgent(account))
+	req.Header.Set("Origin", baseURL)
+	req.Header.Set("Referer", baseURL+"/")
+	req.Header.Set("oai-device-id", deviceID)
+	req.Header.Set("oai-language", "en-US")
+	if body != nil {
+		req.Header.Set("Content-Type", "application/json")
+	}
+	return req, nil
+}
+
+// doChatGPTWebRequest 发送网页接口请求；上游错误与 OpenAI 账号走同一套故障转移 / 限流处理
+func (s *OpenAIGatewayService) doChatGPTWebRequest(ctx context.Context, c *gin.Context, account *Account, req *http.Request, proxyURL string, requestBody []byte) (*http.Response, error) {
+	return s.doOpenAIUpstreamRequest(ctx, c, account, req, proxyURL, requestBody, "ChatGPTWeb")
+}
+
+// chatGPTWebSentinelHeaders 获取对话请求所需的 sentinel 令牌，并在要求时附带工作量证明
+func (s *OpenAIGatewayService) chatGPTWebSentinelHeaders(ctx context.Context, c *gin.Context, account *Account, baseURL, proxyURL string, requestBody []byte) (http.Header, error) {
+	req, err := s.newChatGPTWebRequest(ctx, account, http.MethodPost, baseURL, "/backend-api/sentinel/chat-requirements", map[string]any{})
+	if err != nil {
+		return nil, err
+	}
+	resp, err := s.doChatGPTWebRequest(ctx, c, account, req, proxyURL, requestBody)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = resp.Body.Close() }()
+
+	raw, err := io.ReadAll(io.LimitReader(resp.Body, 1<<20))
+	if err != nil {
+		return nil, fmt.Errorf("read chat requirements: %w", err)
+	}
+	requirements := gjson.ParseBytes(raw)
+	if requirements.Get("turnstile.required").Bool() || requirements.Get("arkose.required").Bool() {
+		return nil, errChatGPTWebChallengeRequired
+	}
+
+	headers := http.Header{}
+	if token := requirements.Get("token").String(); token != "" {
+		headers.Set("openai-sentinel-chat-requirements-token", token)
+	}
+	if requirements.Get("proofofwork.required").Bool() {
+		proof, ok := solveChatGPTProofOfWork(
+			requirements.Get("proofofwork.seed").String(),
+			requirements.Get("proofofwork.difficulty").String(),
+			chatGPTWebUserAgent(account),
+			time.Now(),
+		)
+		if !ok {
+			return nil, errors.New("chatgpt proof of work not solved within attempt limit")
+		}
+		headers.Set("openai-sentinel-proof-token", proof)
+	}
+	return headers, nil
+}
+
+// forwardChatGPTWeb 通过 ChatGPT 网页会话（backend-api）转发 Responses 请求。
+// 对话以临时对话（不写入历史、不参与训练）创建，结束后再隐藏。
+func (s *OpenAIGatewayService) forwardChatGPTWeb(ctx context.Context, c *gin.Context, account *Account, body []byte, startTime time.Time) (*OpenAIForwardResult, error) {
+	prompt, err := buildChatGPTWebPrompt(body)
+	if err != nil {
+		writeChatGPTWebError(c, http.StatusBadRequest, "invalid_request_error", err.Error())
+		return nil, err
+	}
+	if strings.TrimSpace(account.GetCredential("access_token")) == "" {
+		return nil, fmt.Errorf("chatgpt web session account %d has no access_token", account.ID)
+	}
+
+	reqModel := gjson.GetBytes(body, "model").String()
+	reqStream := gjson.GetBytes(body, "stream").Bool()
+	baseURL, err := s.chatGPTWebBaseURL(account)
+	if err != nil {
+		return nil, err
+	}
+	proxyURL := ""
+	if account.ProxyID != nil && account.Proxy != nil {
+		proxyURL = account.Proxy.URL()
+	}
+
+	sentinel, err := s.chatGPTWebSentinelHeaders(ctx, c, account, baseURL, proxyURL, body)
+	if errors.Is(err, errChatGPTWebChallengeRequired) {
+		logger.LegacyPrintf("service.openai_gateway", "[ChatGPTWeb] challenge required, failover: account=%d", account.ID)
+		s.rateLimitService.ReportChallenge(ctx, account, &UpstreamChallenge{Kind: ChallengeKindChatGPTSentinel, StatusCode: http.StatusForbidden})
+		return nil, &UpstreamFailoverError{StatusCode: http.StatusForbidden, ResponseBody: []byte(`{"error":{"message":"interactive challenge required"}}`)}
+	}
+	if err != nil {
+		return nil, err
+	}
+
+	mappedModel := chatGPTWebModelSlug(account.GetMappedModel(reqModel))
+	conversation := map[string]any{
+		"action": "next",
+		"messages": []any{map[string]any{
+			"id":       uuid.NewString(),
+			"author":   map[string]any{"role": "user"},
+			"content":  map[string]any{"content_type": "text", "parts": []string{prompt}},
+			"metadata": map[string]any{},
+		}},
+		"parent_message_id":             uuid.NewString(),
+		"model":                         mappedModel,
+		"history_and_training_disabled": true,
+		"conversation_mode":             map[string]any{"kind": "primary_assistant"},
+		"timezone_offset_min":           0,
+		"websocket_request_id":          uuid.NewString(),
+	}
+	convReq, err := s.newChatGPTWebRequest(ctx, account, http.MethodPost, baseURL, "/backend-api/conversation", conversation)
+	if err != nil {
+		return nil, err
+	}
+	convReq.Header.Set("Accept", "text/event-stream")
+	for key, values := range sentinel {
+		for _, v := range values {
+			convReq.Header.Add(key, v)
+		}
+	}
+	setOpsUpstreamRequestBody(c, body)
+
+	resp, err := s.doChatGPTWebRequest(ctx, c, account, convReq, proxyURL, body)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = resp.Body.Close() }()
+
+	// 网页接口不返回 token 用量，按本地估算计费
+	usage := OpenAIUsage{InputTokens: estimateOpenAIResponsesInputTokens(body)}
+	maxLineSize := defaultMaxLineSize
+	if s.cfg != nil && s.cfg.Gateway.MaxLineSize > 0 {
+		maxLineSize = s.cfg.Gateway.MaxLineSize
+	}
+
+	responseID := randomHex(12)
+	createdAt := startTime.Unix()
+	// 网页接口不返回用量，按本地分词器估算
+	result := &OpenAIForwardResult{
+		RequestID:      responseID,
+		Model:          reqModel,
+		Stream:         reqStream,
+		UsageEstimated: true,
+	}
+
+	var st *chatGPTWebStreamState
+	if reqStream {
+		st, err = s.streamChatGPTWebResponse(c, resp.Body, maxLineSize, responseID, reqModel, createdAt, &usage, startTime, result)
+	} else {
+		st, err = readChatGPTWebEvents(resp.Body, maxLineSize, func(string) bool { return true })
+		if err == nil {
+			usage.OutputTokens = claude.EstimateTextTokens(st.text)
+			status, incompleteReason := chatGPTWebStatus(st.finishType)
+			c.JSON(http.StatusOK, chatGPTWebResponseObject(responseID, reqModel, createdAt, status, incompleteReason, st.text, &usage))
+		} else {
+			writeChatGPTWebError(c, http.StatusBadGateway, "upstream_error", "Upstream stream ended with an error")
+		}
+	}
+	if st != nil && st.conversationID != "" {
+		defer s.hideChatGPTWebConversation(account, baseURL, st.conversationID, proxyURL)
+	}
+	if err != nil {
+		return nil, err
+	}
+
+	usage.OutputTokens = claude.EstimateTextTokens(st.text)
+	result.Usage = usage
+	result.Duration = time.Since(startTime)
+	return result, nil
+}
+
+func (s *OpenAIGatewayService) streamChatGPTWebResponse(c *gin.Context, body io.Reader, maxLineSize int, responseID, model string, createdAt int64, usage *OpenAIUsage, startTime time.Time, result *OpenAIForwardResult) (*chatGPTWebStreamState, error) {
+	c.Header("Content-Type", "text/event-stream")
+	c.Header("Cache-Control", "no-cache")
+	c.Header("Connection", "keep-alive")
+	c.Header("X-Accel-Buffering", "no")
+	c.Status(http.StatusOK)
+
+	flusher, ok := c.Writer.(http.Flusher)
+	if !ok {
+		return nil, errors.New("streaming not supported")
+	}
+	cancelOnDisconnect := s.cfg != nil && s.cfg.Gateway.CancelUpstreamOnClientDisconnect
+	clientDisconnected := false
+
+	seq := 0
+	emit := func(event string, data map[string]any) {
+		data["type"] = event
+		data["sequence_number"] = seq
+		seq++
+		writeSSE(c.Writer, event, data)
+	}
+	itemID := "msg_" + responseID
+	emit("response.created", map[string]any{"response": chatGPTWebResponseObject(responseID, model, createdAt, "in_progress", "", "", nil)})
+	emit("response.output_item.added", map[string]any{
+		"output_index": 0,
+		"item":         map[string]any{"id": itemID, "type": "message", "status": "in_progress", "role": "assistant", "content": []any{}},
+	})
+	emit("response.content_part.added", map[string]any{
+		"item_id":       itemID,
+		"output_index":  0,
+		"content_index": 0,
+		"part":          map[string]any{"type": "output_text", "text": "", "annotations": []any{}},
+	})
+	flusher.Flush()
+
+	st, err := readChatGPTWebEvents(body, maxLineSize, func(delta string) bool {
+		if result.FirstTokenMs == nil {
+			ms := int(time.Since(startTime).Milliseconds())
+			result.FirstTokenMs = &ms
+		}
+		if clientDisconnected {
+			// 继续读取上游以完成计费，除非配置了断开即取消
+			return !cancelOnDisconnect
+		}
+		emit("response.output_text.delta", map[string]any{
+			"item_id":       itemID,
+			"output_index":  0,
+			"content_index": 0,
+			"delta":         delta,
+		})
+		flusher.Flush()
+		if c.Request != nil && c.Request.Context().Err() != nil {
+			clientDisconnected = true
+		}
+		return true
+	})
+	if clientDisconnected {
+		return st, nil
+	}
+	if err != nil {
+		emit("error", map[string]any{"code": "upstream_error", "message": "Upstream stream ended with an error"})
+		flusher.Flush()
+		return st, err
+	}
+
+	usage.OutputTokens = claude.EstimateTextTokens(st.text)
+	status, incompleteReason := chatGPTWebStatus(st.finishType)
+	emit("response.output_text.done", map[string]any{"item_id": itemID, "output_index": 0, "content_index": 0, "text": st.text})
+	emit("response.content_part.done", map[string]any{
+		"item_id":       itemID,
+		"output_index":  0,
+		"content_index": 0,
+		"part":          map[string]any{"type": "output_text", "text": st.text, "annotations": []any{}},
+	})
+	final := chatGPTWebResponseObject(responseID, model, createdAt, status, incompleteReason, st.text, usage)
+	emit("response.output_item.done", map[string]any{"output_index": 0, "item": final["output"].([]any)[0]})
+	if status == "completed" {
+		emit("response.completed", map[string]any{"response": final})
+	} else {
+		emit("response.incomplete", map[string]any{"response": final})
+	}
+	flusher.Flush()
+	return st, nil
+}
+
+// hideChatGPTWebConversation 隐藏对话，避免出现在网页端历史中；失败只记录日志
+func (s *OpenAIGatewayService) hideChatGPTWebConversation(account *Account, baseURL, conversationID, proxyURL string) {
+	ctx, cancel := context.WithTimeout(context.Background(), chatGPTWebCleanupTimeout)
+	defer cancel()
+
+	req, err := s.newChatGPTWebRequest(ctx, account, http.MethodPatch, baseURL, "/backend-api/conversation/"+conversationID, map[string]any{"is_visible": false})
+	if err != nil {
+		return
+	}
+	resp, err := s.httpUpstream.DoWithTLS(withAccountTLSProfile(req, account), proxyURL, account.ID, account.Concurrency, account.IsTLSFingerprintEnabled())
+	if err != nil {
+		logger.LegacyPrintf("service.openai_gateway", "[ChatGPTWeb] hide conversation failed: account=%d err=%s", account.ID, sanitizeUpstreamErrorMessage(err.Error()))
+		return
+	}
+	_ = resp.Body.Close()
+	if resp.StatusCode >= 400 {
+		logger.LegacyPrintf("service.openai_gateway", "[ChatGPTWeb] hide conversation failed: account=%d status=%d", account.ID, resp.StatusCode)
+	}
+}
@@ -0,0 +1,207 @@
+package service
+
+import (
+	"context"
+	"crypto/sha3"
+	"encoding/base64"
+	"encoding/hex"
+	"encoding/json"
+	"net/http"
+	"net/http/httptest"
+	"strings"
+	"testing"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/gin-gonic/gin"
+	"github.com/stretchr/testify/require"
+	"github.com/tidwall/gjson"
+)
+
+func newChatGPTWebAccountForTest() *Account {
+	return &Account{
+		ID:          401,
+		Name:        "chatgpt-web",
+		Platform:    PlatformOpenAI,
+		Type:        AccountTypeWebSession,
+		Concurrency: 1,
+		Credentials: map[string]any{
+			"access_token":       "web-access-token",
+			"chatgpt_account_id": "team-123",
+		},
+		Status:      StatusActive,
+		Schedulable: true,
+	}
+}
+
+func TestChatGPTWebModelSlug(t *testing.T) {
+	require.Equal(t, "gpt-4o", chatGPTWebModelSlug("gpt-4o-2024-08-06"))
+	require.Equal(t, "gpt-4-1", chatGPTWebModelSlug("GPT-4.1"))
+	require.Equal(t, "o3-mini", chatGPTWebModelSlug("o3-mini"))
+	require.Equal(t, "auto", chatGPTWebModelSlug(""))
+}
+
+func TestBuildChatGPTWebPrompt(t *testing.T) {
+	prompt, err := buildChatGPTWebPrompt([]byte(`{"input":"hello"}`))
+	require.NoError(t, err)
+	require.Equal(t, "hello", prompt)
+
+	prompt, err = buildChatGPTWebPrompt([]byte(`{
+		"instructions":"Be brief.",
+		"input":[
+			{"role":"developer","content":"Answer in English."},
+			{"type":"message","role":"user","content":[{"type":"input_text","text":"2+2?"}]},
+			{"role":"assistant","content":[{"type":"output_text","text":"4"}]},
+			{"role":"user","content":"and 3+3?"}
+		]}`))
+	require.NoError(t, err)
+	require.Equal(t, "Be brief.\n\nAnswer in English.\n\nUser: 2+2?\n\nAssistant: 4\n\nUser: and 3+3?", prompt)
+
+	_, err = buildChatGPTWebPrompt([]byte(`{"input":[{"type":"function_call_output","output":"x"}]}`))
+	require.ErrorContains(t, err, "function_call_output")
+	_, err = buildChatGPTWebPrompt([]byte(`{"tools":[{"type":"function"}],"input":"hi"}`))
+	require.ErrorContains(t, err, "tools are not supported")
+}
+
+func TestSolveChatGPTProofOfWork(t *testing.T) {
+	proof, ok := solveChatGPTProofOfWork("0.42", "0fff", "test-agent", time.Unix(1700000000, 0))
+	require.True(t, ok)
+	require.True(t, strings.HasPrefix(proof, "gAAAAAB"))
+
+	answer := strings.TrimPrefix(proof, "gAAAAAB")
+	sum := sha3.Sum512([]byte("0.42" + answer))
+	require.LessOrEqual(t, hex.EncodeToString(sum[:])[:4], "0fff")
+
+	raw, err := base64.StdEncoding.DecodeString(answer)
+	require.NoError(t, err)
+	var fields []any
+	require.NoError(t, json.Unmarshal(raw, &fields))
+	require.Equal(t, "test-agent", fields[4])
+}
+
+func TestReadChatGPTWebEvents(t *testing.T) {
+	collect := func(stream string) (string, *chatGPTWebStreamState) {
+		var out strings.Builder
+		st, err := readChatGPTWebEvents(strings.NewReader(stream), 1<<20, func(delta string) bool {
+			out.WriteString(delta)
+			return true
+		})
+		require.NoError(t, err)
+		return out.String(), st
+	}
+
+	// 整段快照格式：parts 为累计文本
+	snapshot := `data: {"message":{"author":{"role":"user"},"content":{"content_type":"text","parts":["hi"]}},"conversation_id":"conv-1"}` + "\n\n" +
+		`data: {"message":{"author":{"role":"assistant"},"content":{"content_type":"text","parts":["Hel"]}},"conversation_id":"conv-1"}` + "\n\n" +
+		`data: {"message":{"author":{"role":"assistant"},"content":{"content_type":"text","parts":["Hello"]},"metadata":{"finish_details":{"type":"stop"}}},"conversation_id":"conv-1"}` + "\n\n" +
+		"data: [DONE]\n\n"
+	text, st := collect(snapshot)
+	require.Equal(t, "Hello", text)
+	require.Equal(t, "conv-1", st.conversationID)
+	require.Equal(t, "stop", st.finishType)
+
+	// delta 编码格式
+	delta := `data: {"v":{"message":{"author":{"role":"assistant"},"content":{"content_type":"text","parts":[""]}},"conversation_id":"conv-2"},"c":0}` + "\n\n" +
+		`data: {"p":"/message/content/parts/0","o":"append","v":"Hi"}` + "\n\n" +
+		`data: {"v":" there"}` + "\n\n" +
+		`data: {"p":"","o":"patch","v":[{"p":"/message/content/parts/0","o":"append","v":"!"},{"p":"/message/metadata/finish_details","o":"replace","v":{"type":"max_tokens"}}]}` + "\n\n" +
+		"data: [DONE]\n\n"
+	text, st = collect(delta)
+	require.Equal(t, "Hi there!", text)
+	require.Equal(t, "conv-2", st.conversationID)
+	status, reason := chatGPTWebStatus(st.finishType)
+	require.Equal(t, "incomplete", status)
+	require.Equal(t, "max_output_tokens", reason)
+}
+
+func TestOpenAIGatewayService_ForwardChatGPTWeb(t *testing.T) {
+	gin.SetMode(gin.TestMode)
+	stream := `data: {"message":{"author":{"role":"assistant"},"content":{"content_type":"text","parts":["Hello"]}},"conversation_id":"conv-9"}` + "\n\n" +
+		`data: {"message":{"author":{"role":"assistant"},"content":{"content_type":"text","parts":["Hello world"]},"metadata":{"finish_details":{"type":"stop"}}},"conversation_id":"conv-9"}` + "\n\n" +
+		"data: [DONE]\n\n"
+	upstream := &claudeWebUpstreamStub{respond: func(req *http.Request) *http.Response {
+		switch req.URL.Path {
+		case "/backend-api/sentinel/chat-requirements":
+			return claudeWebTestResponse(http.StatusOK, `{"token":"req-token","proofofwork":{"required":true,"seed":"0.1","difficulty":"ff"}}`)
+		case "/backend-api/conversation":
+			return claudeWebTestResponse(http.StatusOK, stream)
+		default:
+			return claudeWebTestResponse(http.StatusOK, `{"success":true}`)
+		}
+	}}
+	svc := &OpenAIGatewayService{httpUpstream: upstream, cfg: &config.Config{}}
+
+	rec := httptest.NewRecorder()
+	c, _ := gin.CreateTestContext(rec)
+	c.Request = httptest.NewRequest(http.MethodPost, "/v1/responses", nil)
+	body := []byte(`{"model":"gpt-4o-2024-08-06","input":"hi"}`)
+
+	result, err := svc.Forward(context.Background(), c, newChatGPTWebAccountForTest(), body)
+	require.NoError(t, err)
+	require.Equal(t, "gpt-4o-2024-08-06", result.Model)
+	require.Positive(t, result.Usage.OutputTokens)
+
+	require.Equal(t, http.StatusOK, rec.Code)
+	require.Equal(t, "completed", gjson.Get(rec.Body.String(), "status").String())
+	require.Equal(t, "Hello world", gjson.Get(rec.Body.String(), "output.0.content.0.text").String())
+
+	require.Equal(t, []string{
+		"POST /backend-api/sentinel/chat-requirements",
+		"POST /backend-api/conversation",
+		"PATCH /backend-api/conversation/conv-9",
+	}, upstream.requests)
+	convHeaders := upstream.headers["POST /backend-api/conversation"]
+	require.Equal(t, "Bearer web-access-token", convHeaders.Get("Authorization"))
+	require.Equal(t, "team-123", convHeaders.Get("chatgpt-account-id"))
+	require.Equal(t, "req-token", convHeaders.Get("openai-sentinel-chat-requirements-token"))
+	require.True(t, strings.HasPrefix(convHeaders.Get("openai-sentinel-proof-token"), "gAAAAAB"))
+
+	conv := gjson.ParseBytes(upstream.bodies["POST /backend-api/conversation"])
+	require.Equal(t, "gpt-4o", conv.Get("model").String())
+	require.True(t, conv.Get("history_and_training_disabled").Bool())
+	require.Equal(t, "hi", conv.Get("messages.0.content.parts.0").String())
+}
+
+func TestOpenAIGatewayService_ForwardChatGPTWeb_StreamEvents(t *testing.T) {
+	gin.SetMode(gin.TestMode)
+	upstream := &claudeWebUpstreamStub{respond: func(req *http.Request) *http.Response {
+		if req.URL.Path == "/backend-api/conversation" {
+			return claudeWebTestResponse(http.StatusOK, `data: {"message":{"author":{"role":"assistant"},"content":{"content_type":"text","parts":["Yo"]}}}`+"\n\ndata: [DONE]\n\n")
+		}
+		return claudeWebTestResponse(http.StatusOK, `{"token":"t"}`)
+	}}
+	svc := &OpenAIGatewayService{httpUpstream: upstream, cfg: &config.Config{}}
+
+	rec := httptest.NewRecorder()
+	c, _ := gin.CreateTestContext(rec)
+	c.Request = httptest.NewRequest(http.MethodPost, "/v1/responses", nil)
+
+	result, err := svc.Forward(context.Background(), c, newChatGPTWebAccountForTest(), []byte(`{"model":"o3","stream":true,"input":"hi"}`))
+	require.NoError(t, err)
+	require.True(t, result.Stream)
+	require.NotNil(t, result.FirstTokenMs)
+
+	out := rec.Body.String()
+	for _, event := range []string{"response.created", "response.output_text.delta", "response.output_text.done", "response.completed"} {
+		require.Contains(t, out, "event: "+event+"\n")
+	}
+	require.Contains(t, out, `"delta":"Yo"`)
+	require.Len(t, upstream.requests, 2, "no conversation id means nothing to hide")
+}
+
+func TestOpenAIGatewayService_ForwardChatGPTWeb_ChallengeFailsOver(t *testing.T) {
+	gin.SetMode(gin.TestMode)
+	upstream := &claudeWebUpstreamStub{respond: func(req *http.Request) *http.Response {
+		return claudeWebTestResponse(http.StatusOK, `{"token":"t","turnstile":{"required":true}}`)
+	}}
+	svc := &OpenAIGatewayService{httpUpstream: upstream, cfg: &config.Config{}}
+
+	rec := httptest.NewRecorder()
+	c, _ := gin.CreateTestContext(rec)
+	c.Request = httptest.NewRequest(http.MethodPost, "/v1/responses", nil)
+
+	_, err := svc.Forward(context.Background(), c, newChatGPTWebAccountForTest(), []byte(`{"model":"gpt-4o","input":"hi"}`))
+	var failover *UpstreamFailoverError
+	require.ErrorAs(t, err, &failover)
+	require.Len(t, upstream.requests, 1)
+}
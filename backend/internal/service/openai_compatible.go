@@ -0,0 +1,158 @@
+package service
+
+import (
+	"bytes"
+	"context"
+	"encoding/json"
+	"errors"
+	"fmt"
+	"io"
+	"net/http"
+	"strings"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/pkg/logger"
+	"github.com/gin-gonic/gin"
+	"github.com/tidwall/gjson"
+)
+
+// OpenAI 兼容上游的实现类型（credentials.provider），用于确定默认能力
+const (
+	OpenAICompatibleProviderOllama   = "ollama"
+	OpenAICompatibleProviderLlamaCpp = "llamacpp"
+)
+
+// openAICompatibleUnsupportedFeature 返回请求用到、但账号未声明支持的能力（tools / vision），均支持时返回空
+func openAICompatibleUnsupportedFeature(account *Account, chatBody []byte) string {
+	if !account.OpenAICompatibleSupportsTools() && len(gjson.GetBytes(chatBody, "tools").Array()) > 0 {
+		return "tools"
+	}
+	if !account.OpenAICompatibleSupportsVision() && chatRequestHasImage(chatBody) {
+		return "vision"
+	}
+	return ""
+}
+
+// buildOpenAIChatCompletionsURL 与 OpenAI SDK 的 base_url 约定一致：
+// 以 /v1（或 OpenRouter 的 /api/v1 等版本路径）结尾时直接拼接 /chat/completions，未带版本路径时补全 /v1。
+func buildOpenAIChatCompletionsURL(base string) string {
+	normalized := strings.TrimRight(strings.TrimSpace(base), "/")
+	if strings.HasSuffix(normalized, "/chat/completions") {
+		return normalized
+	}
+	if strings.HasSuffix(normalized, "/v1") {
+		return normalized + "/chat/completions"
+	}
+	return normalized + "/v1/chat/completions"
+}
+
+// doOpenAIUpstreamRequest 发送上游请求；上游错误与 OpenAI 账号走同一套故障转移 / 限流处理
+func (s *OpenAIGatewayService) doOpenAIUpstreamRequest(ctx context.Context, c *gin.Context, account *Account, req *http.Request, proxyURL string, requestBody []byte, logTag string) (*http.Response, error) {
+	resp, err := s.httpUpstream.DoWithTLS(withAccountTLSProfile(req, account), proxyURL, account.ID, account.Concurrency, account.IsTLSFingerprintEnabled())
+	if err != nil {
+		if resp != nil && resp.Body != nil {
+			_ = resp.Body.Close()
+		}
+		safeErr := sanitizeUpstreamErrorMessage(err.Error())
+		setOpsUpstreamError(c, 0, safeErr, "")
+		appendOpsUpstreamError(c, OpsUpstreamErrorEvent{
+			Platform:    account.Platform,
+			AccountID:   account.ID,
+			AccountName: account.Name,
+			Kind:        "request_error",
+			Message:     safeErr,
+		})
+		writeUpstreamRequestError(c, UpstreamErrorFormatOpenAI, err)
+		return nil, fmt.Errorf("upstream request failed: %s", safeErr)
+	}
+	if resp.StatusCode < 400 {
+		return resp, nil
+	}
+
+	respBody, _ := io.ReadAll(io.LimitReader(resp.Body, 2<<20))
+	_ = resp.Body.Close()
+	resp.Body = io.NopCloser(bytes.NewReader(respBody))
+
+	if s.shouldFailoverUpstreamError(resp.StatusCode) {
+		logger.LegacyPrintf("service.openai_gateway", "[%s] Upstream error (failover): Account=%d(%s) Status=%d Body=%s",
+			logTag, account.ID, account.Name, resp.StatusCode, truncateString(string(respBody), 1000))
+
+		s.handleFailoverSideEffects(ctx, resp, account)
+		appendOpsUpstreamError(c, OpsUpstreamErrorEvent{
+			Platform:           account.Platform,
+			AccountID:          account.ID,
+			AccountName:        account.Name,
+			UpstreamStatusCode: resp.StatusCode,
+			Kind:               "failover",
+			Message:            extractUpstreamErrorMessage(respBody),
+		})
+		return nil, &UpstreamFailoverError{StatusCode: resp.StatusCode, ResponseBody: respBody}
+	}
+
+	if _, err := s.handleErrorResponse(ctx, resp, c, account, requestBody); err != nil {
+		return nil, err
+	}
+	return nil, fmt.Errorf("upstream error: %d", resp.StatusCode)
+}
+
+// forwardOpenAICompatible 将 Responses 请求转换为 Chat Completions，转发到通用 OpenAI 兼容上游。
+// 凭证：base_url（必填）、api_key（本地服务可为空）、provider（ollama / llamacpp，可选）、model_mapping（可选）；
+// 能力开关：extra.supports_tools、extra.supports_vision。
+func (s *OpenAIGatewayService) forwardOpenAICompatible(ctx context.Context, c *gin.Context, account *Account, body []byte, startTime time.Time) (*OpenAIForwardResult, error) {
+	baseURL := account.GetCredential("base_url")
+	if baseURL == "" {
+		writeChatGPTWebError(c, http.StatusBadGateway, "upstream_error", "Upstream account is missing base_url")
+		return nil, errors.New("openai compatible account is missing base_url")
+	}
+	validatedURL, err := s.validateUpstreamBaseURL(baseURL)
+	if err != nil {
+		writeChatGPTWebError(c, http.StatusBadGateway, "upstream_error", "Invalid upstream base_url")
+		return nil, err
+	}
+
+	reqModel := gjson.GetBytes(body, "model").String()
+	reqStream := gjson.GetBytes(body, "stream").Bool()
+	chatBody, err := convertResponsesToChatCompletions(body, account.GetMappedModel(reqModel))
+	if err != nil {
+		writeChatGPTWebError(c, http.StatusBadRequest, "invalid_request_error", err.Error())
+		return nil, err
+	}
+	// 能力不匹配时切换到其他账号，不影响本账号的调度状态
+	if feature := openAICompatibleUnsupportedFeature(account, chatBody); feature != "" {
+		message := fmt.Sprintf("%s is not supported by this upstream", feature)
+		logger.LegacyPrintf("service.openai_gateway", "[OpenAICompatible] capability mismatch (failover): account=%d feature=%s", account.ID, feature)
+		respBody, _ := json.Marshal(map[string]any{"error": map[string]any{"type": "invalid_request_error", "message": message}})
+		return nil, &UpstreamFailoverError{StatusCode: http.StatusBadRequest, ResponseBody: respBody}
+	}
+
+	req, err := http.NewRequestWithContext(ctx, http.MethodPost, buildOpenAIChatCompletionsURL(validatedURL), bytes.NewReader(chatBody))
+	if err != nil {
+		return nil, err
+	}
+	if apiKey := account.GetCredential("api_key"); apiKey != "" {
+		req.Header.Set("Authorization", "Bearer "+apiKey)
+	}
+	req.Header.Set("Content-Type", "application/json")
+	if reqStream {
+		req.Header.Set("Accept", "text/event-stream")
+	} else {
+		req.Header.Set("Accept", "application/json")
+	}
+	if ua := account.GetOpenAIUserAgent(); ua != "" {
+		req.Header.Set("User-Agent", ua)
+	}
+	setOpsUpstreamRequestBody(c, chatBody)
+
+	proxyURL := ""
+	if account.ProxyID != nil && account.Proxy != nil {
+		proxyURL = account.Proxy.URL()
+	}
+	resp, err := s.doOpenAIUpstreamRequest(ctx, c, account, req, proxyURL, body, "OpenAICompatible")
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = resp.Body.Close() }()
+
+	logprobs, _ := responsesLogprobsRequested(gjson.ParseBytes(body))
+	return s.relayChatCompletionAsResponses(c, resp, reqModel, reqStream, logprobs, startTime)
+}
@@ -0,0 +1,141 @@
+package service
+
+import (
+	"context"
+	"net/http"
+	"net/http/httptest"
+	"testing"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/gin-gonic/gin"
+	"github.com/stretchr/testify/require"
+	"github.com/tidwall/gjson"
+)
+
+func newOpenAICompatibleAccountForTest(baseURL string) *Account {
+	return &Account{
+		ID:          601,
+		Name:        "openrouter",
+		Platform:    PlatformOpenAI,
+		Type:        AccountTypeUpstream,
+		Concurrency: 1,
+		Credentials: map[string]any{
+			"base_url":      baseURL,
+			"api_key":       "sk-or-test",
+			"model_mapping": map[string]any{"gpt-4.1": "openai/gpt-4.1"},
+		},
+		Status:      StatusActive,
+		Schedulable: true,
+	}
+}
+
+func newOpenAICompatibleGatewayForTest(upstream HTTPUpstream) (*OpenAIGatewayService, *claudeWebAccountRepoStub) {
+	repo := &claudeWebAccountRepoStub{}
+	svc := &OpenAIGatewayService{
+		httpUpstream:     upstream,
+		cfg:              &config.Config{},
+		rateLimitService: &RateLimitService{accountRepo: repo},
+	}
+	return svc, repo
+}
+
+func TestBuildOpenAIChatCompletionsURL(t *testing.T) {
+	require.Equal(t, "https://openrouter.ai/api/v1/chat/completions", buildOpenAIChatCompletionsURL("https://openrouter.ai/api/v1/"))
+	require.Equal(t, "http://10.0.0.5:8000/v1/chat/completions", buildOpenAIChatCompletionsURL("http://10.0.0.5:8000"))
+	require.Equal(t, "https://gw.example.com/v1/chat/completions", buildOpenAIChatCompletionsURL("https://gw.example.com/v1/chat/completions"))
+}
+
+func TestOpenAIGatewayService_ForwardOpenAICompatible_Stream(t *testing.T) {
+	gin.SetMode(gin.TestMode)
+	upstream := &claudeWebUpstreamStub{respond: func(*http.Request) *http.Response {
+		return claudeWebTestResponse(http.StatusOK, copilotTestChatStream)
+	}}
+	svc, _ := newOpenAICompatibleGatewayForTest(upstream)
+
+	rec := httptest.NewRecorder()
+	c, _ := gin.CreateTestContext(rec)
+	c.Request = httptest.NewRequest(http.MethodPost, "/v1/responses", nil)
+
+	result, err := svc.Forward(context.Background(), c, newOpenAICompatibleAccountForTest("https://openrouter.ai/api/v1"), []byte(`{"model":"gpt-4.1","stream":true,"input":"hi"}`))
+	require.NoError(t, err)
+	require.Equal(t, "gpt-4.1", result.Model)
+	require.Equal(t, 12, result.Usage.InputTokens)
+	require.Equal(t, 7, result.Usage.OutputTokens)
+	require.Contains(t, rec.Body.String(), "event: response.completed\n")
+
+	require.Equal(t, []string{"POST /api/v1/chat/completions"}, upstream.requests)
+	require.Equal(t, "Bearer sk-or-test", upstream.headers["POST /api/v1/chat/completions"].Get("Authorization"))
+	require.Equal(t, "openai/gpt-4.1", gjson.GetBytes(upstream.bodies["POST /api/v1/chat/completions"], "model").String())
+}
+
+func TestOpenAIGatewayService_ForwardOpenAICompatible_UpstreamErrorFailsOver(t *testing.T) {
+	gin.SetMode(gin.TestMode)
+	upstream := &claudeWebUpstreamStub{respond: func(*http.Request) *http.Response {
+		return claudeWebTestResponse(http.StatusUnauthorized, `{"error":{"message":"invalid api key"}}`)
+	}}
+	svc, repo := newOpenAICompatibleGatewayForTest(upstream)
+
+	rec := httptest.NewRecorder()
+	c, _ := gin.CreateTestContext(rec)
+	c.Request = httptest.NewRequest(http.MethodPost, "/v1/responses", nil)
+
+	_, err := svc.Forward(context.Background(), c, newOpenAICompatibleAccountForTest("https://openrouter.ai/api/v1"), []byte(`{"model":"gpt-4.1","input":"hi"}`))
+	var failover *UpstreamFailoverError
+	require.ErrorAs(t, err, &failover)
+	require.Equal(t, http.StatusUnauthorized, failover.StatusCode)
+	require.NotEmpty(t, repo.errorMsg, "a rejected api key takes the account out of rotation")
+}
+
+func TestOpenAIGatewayService_ForwardOpenAICompatible_RejectsInsecureBaseURL(t *testing.T) {
+	gin.SetMode(gin.TestMode)
+	upstream := &claudeWebUpstreamStub{}
+	svc, _ := newOpenAICompatibleGatewayForTest(upstream)
+
+	rec := httptest.NewRecorder()
+	c, _ := gin.CreateTestContext(rec)
+	c.Request = httptest.NewRequest(http.MethodPost, "/v1/responses", nil)
+
+	_, err := svc.Forward(context.Background(), c, newOpenAICompatibleAccountForTest("http://10.0.0.5:8000/v1"), []byte(`{"model":"gpt-4.1","input":"hi"}`))
+	require.Error(t, err)
+	require.Equal(t, http.StatusBadGateway, rec.Code)
+	require.Empty(t, upstream.requests)
+}
+
+func TestAccount_OpenAICompatibleCapabilities(t *testing.T) {
+	account := newOpenAICompatibleAccountForTest("https://openrouter.ai/api/v1")
+	require.False(t, account.IsLocalModelUpstream())
+	require.True(t, account.OpenAICompatibleSupportsTools())
+	require.True(t, account.OpenAICompatibleSupportsVision())
+
+	account.Credentials["provider"] = "Ollama"
+	require.True(t, account.IsLocalModelUpstream())
+	require.True(t, account.OpenAICompatibleSupportsTools())
+	require.False(t, account.OpenAICompatibleSupportsVision())
+
+	account.Credentials["provider"] = OpenAICompatibleProviderLlamaCpp
+	require.False(t, account.OpenAICompatibleSupportsTools())
+
+	account.Extra = map[string]any{"supports_tools": true, "supports_vision": true}
+	require.True(t, account.OpenAICompatibleSupportsTools())
+	require.True(t, account.OpenAICompatibleSupportsVision())
+}
+
+func TestOpenAIGatewayService_ForwardOpenAICompatible_CapabilityMismatchFailsOver(t *testing.T) {
+	gin.SetMode(gin.TestMode)
+	upstream := &claudeWebUpstreamStub{}
+	svc, repo := newOpenAICompatibleGatewayForTest(upstream)
+	account := newOpenAICompatibleAccountForTest("https://llama.internal.example.com/v1")
+	account.Credentials["provider"] = OpenAICompatibleProviderLlamaCpp
+
+	rec := httptest.NewRecorder()
+	c, _ := gin.CreateTestContext(rec)
+	c.Request = httptest.NewRequest(http.MethodPost, "/v1/responses", nil)
+
+	_, err := svc.Forward(context.Background(), c, account, []byte(`{"model":"gpt-4.1","input":"hi","tools":[{"type":"function","name":"lookup","parameters":{"type":"object"}}]}`))
+	var failover *UpstreamFailoverError
+	require.ErrorAs(t, err, &failover)
+	require.Equal(t, http.StatusBadRequest, failover.StatusCode)
+	require.Contains(t, string(failover.ResponseBody), "tools is not supported")
+	require.Empty(t, upstream.requests)
+	require.Empty(t, repo.errorMsg, "capability mismatch must not disable the account")
+}
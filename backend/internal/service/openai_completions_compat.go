@@ -0,0 +1,524 @@
+package service
+
+import (
+	"bytes"
+	"encoding/json"
+	"errors"
+	"fmt"
+	"strings"
+	"unicode/utf8"
+
+	"github.com/tidwall/gjson"
+)
+
+// 本文件负责旧版 Completions API（/v1/completions）与 Anthropic Messages API 之间的互转：
+// 文本提示词包装为单轮对话交给 /v1/messages 的调度链路，响应再转换回 text_completion 格式。
+
+const (
+	// completionDefaultMaxTokens 与 OpenAI 一致，未指定 max_tokens 时为 16
+	completionDefaultMaxTokens = 16
+	// completionMaxStops OpenAI 最多接受 4 个停止序列
+	completionMaxStops = 4
+
+	completionSystemPrompt       = "You are a raw text completion engine. Continue the user's text exactly where it ends. Output only the continuation, with no preamble, explanation, quotes or formatting."
+	completionInsertSystemPrompt = "You are a raw text completion engine. The user provides the text before and after a gap in <prefix> and <suffix> tags. Output only the text that belongs in the gap, with no tags, preamble, explanation, quotes or formatting."
+)
+
+// CompletionsFailedChoicesHeader n > 1 扇出时部分请求失败，响应中缺失的 choice index（逗号分隔）
+const CompletionsFailedChoicesHeader = "X-Sub2API-Failed-Choices"
+
+// CompletionChoiceBody n > 1 扇出时一次成功的非流式 Messages 响应及其 choice index
+type CompletionChoiceBody struct {
+	Index int
+	Body  []byte
+}
+
+// CompletionRequest Completions 请求中影响响应转换的字段
+type CompletionRequest struct {
+	Model        string
+	Prompt       string
+	Echo         bool
+	Stream       bool
+	IncludeUsage bool
+	// N 候选数；大于 1 时由网关并发发起 N 次 Messages 请求后合并
+	N int
+	// Logprobs 请求了 logprobs；Messages API 不提供，响应中返回 emulated 空结构
+	Logprobs bool
+	// LocalStops 由网关在输出中截断的停止序列（Messages API 不接受纯空白的 stop_sequences，如常见的 "\n"）
+	LocalStops []string
+	// SystemFingerprint 实际处理请求的后端指纹（取自 Messages 响应头 X-Sub2API-System-Fingerprint）
+	SystemFingerprint string
+
+	// finishReasonOverrides gateway.finish_reason_overrides
+	finishReasonOverrides map[string]map[string]string
+}
+
+// ConvertCompletionRequest 将 Completions 请求体转换为 Messages 请求体；maxChoices 为 n 的上限，
+// overrides 为 gateway.finish_reason_overrides
+func ConvertCompletionRequest(body []byte, maxChoices int, overrides map[string]map[string]string) (*CompletionRequest, []byte, *RequestSchemaError) {
+	if !gjson.ValidBytes(body) {
+		return nil, nil, &RequestSchemaError{Code: "invalid_type", Message: "Failed to parse request body"}
+	}
+	parsed := gjson.ParseBytes(body)
+	req := &CompletionRequest{
+		Model:        parsed.Get("model").String(),
+		Echo:         parsed.Get("echo").Bool(),
+		Stream:       parsed.Get("stream").Bool(),
+		IncludeUsage: parsed.Get("stream_options.include_usage").Bool(),
+
+		finishReasonOverrides: overrides,
+	}
+	if req.Model == "" {
+		return nil, nil, &RequestSchemaError{Param: "model", Code: "missing_required_parameter", Message: "model is required"}
+	}
+	prompt, schemaErr := parseCompletionPrompt(parsed.Get("prompt"))
+	if schemaErr != nil {
+		return nil, nil, schemaErr
+	}
+	req.Prompt = prompt
+	suffix := parsed.Get("suffix").String()
+	if suffix != "" && req.Echo {
+		return nil, nil, &RequestSchemaError{Param: "echo", Code: "invalid_value", Message: "echo is not supported together with suffix"}
+	}
+	req.N = 1
+	if v := parsed.Get("n"); v.Exists() && v.Type != gjson.Null {
+		if v.Type != gjson.Number || v.Int() < 1 || v.Int() > int64(maxChoices) {
+			return nil, nil, &RequestSchemaError{Param: "n", Code: "invalid_value", Message: fmt.Sprintf("n must be an integer between 1 and %d", maxChoices)}
+		}
+		req.N = int(v.Int())
+	}
+	// best_of 需要对候选结果打分，Messages API 不提供，仅接受与 n 相同的取值
+	if v := parsed.Get("best_of"); v.Exists() && v.Type != gjson.Null && v.Int() != int64(req.N) {
+		return nil, nil, &RequestSchemaError{Param: "best_of", Code: "invalid_value", Message: "best_of other than n is not supported"}
+	}
+	if v := parsed.Get("logprobs"); v.Exists() && v.Type != gjson.Null {
+		if v.Type != gjson.Number || v.Int() < 0 || v.Int() > completionMaxLogprobs {
+			return nil, nil, &RequestSchemaError{Param: "logprobs", Code: "invalid_value", Message: fmt.Sprintf("logprobs must be an integer between 0 and %d", completionMaxLogprobs)}
+		}
+		req.Logprobs = true
+	}
+
+	maxTokens := int64(completionDefaultMaxTokens)
+	if v := parsed.Get("max_tokens"); v.Exists() && v.Type != gjson.Null {
+		if v.Type != gjson.Number || v.Int() < 1 {
+			return nil, nil, &RequestSchemaError{Param: "max_tokens", Code: "invalid_value", Message: "max_tokens must be greater than or equal to 1"}
+		}
+		maxTokens = v.Int()
+	}
+
+	out := map[string]any{
+		"model":      req.Model,
+		"max_tokens": maxTokens,
+		"system":     completionSystemPrompt,
+	}
+	content := prompt
+	if suffix != "" {
+		out["system"] = completionInsertSystemPrompt
+		content = "<prefix>" + prompt + "</prefix>\n<suffix>" + suffix + "</suffix>"
+	}
+	out["messages"] = []any{map[string]any{"role": "user", "content": content}}
+	if req.Stream {
+		out["stream"] = true
+	}
+	// Completions 的 temperature 取值 0~2，Messages API 为 0~1
+	if v := parsed.Get("temperature"); v.Type == gjson.Number {
+		out["temperature"] = min(v.Float(), 1)
+	}
+	if v := parsed.Get("top_p"); v.Type == gjson.Number {
+		out["top_p"] = v.Float()
+	}
+	// seed 由 Messages 按上游能力透传或移除（移除时在 X-Sub2API-Adjusted-Params 中报告）
+	if v := parsed.Get("seed"); v.Exists() && v.Type != gjson.Null {
+		if v.Type != gjson.Number || v.Float() != float64(v.Int()) {
+			return nil, nil, &RequestSchemaError{Param: "seed", Code: "invalid_type", Message: "seed must be an integer"}
+		}
+		out["seed"] = v.Int()
+	}
+
+	stops, schemaErr := parseCompletionStops(parsed.Get("stop"))
+	if schemaErr != nil {
+		return nil, nil, schemaErr
+	}
+	var upstreamStops []string
+	for _, stop := range stops {
+		if strings.TrimSpace(stop) == "" {
+			req.LocalStops = append(req.LocalStops, stop)
+		} else {
+			upstreamStops = append(upstreamStops, stop)
+		}
+	}
+	if len(upstreamStops) > 0 {
+		out["stop_sequences"] = upstreamStops
+	}
+
+	converted, err := json.Marshal(out)
+	if err != nil {
+		return nil, nil, &RequestSchemaError{Code: "invalid_value", Message: "Failed to encode request"}
+	}
+	return req, converted, nil
+}
+
+// parseCompletionPrompt 接受字符串或仅含一个字符串的数组；多提示词批量与 token 数组提示词不支持
+func parseCompletionPrompt(v gjson.Result) (string, *RequestSchemaError) {
+	switch {
+	case !v.Exists() || v.Type == gjson.Null:
+		return "", &RequestSchemaError{Param: "prompt", Code: "missing_required_parameter", Message: "prompt is required"}
+	case v.Type == gjson.String:
+		if v.Str == "" {
+			return "", &RequestSchemaError{Param: "prompt", Code: "invalid_value", Message: "prompt must not be empty"}
+		}
+		return v.Str, nil
+	case v.IsArray():
+		items := v.Array()
+		if len(items) == 1 && items[0].Type == gjson.String {
+			return parseCompletionPrompt(items[0])
+		}
+		for _, item := range items {
+			if item.Type != gjson.String {
+				return "", &RequestSchemaError{Param: "prompt", Code: "invalid_type", Message: "token array prompts are not supported, send the prompt as a string"}
+			}
+		}
+		return "", &RequestSchemaError{Param: "prompt", Code: "invalid_value", Message: "only a single prompt per request is supported"}
+	}
+	return "", &RequestSchemaError{Param: "prompt", Code: "invalid_type", Message: "prompt must be a string or an array"}
+}
+
+func parseCompletionStops(v gjson.Result) ([]string, *RequestSchemaError) {
+	switch {
+	case !v.Exists() || v.Type == gjson.Null:
+		return nil, nil
+	case v.Type == gjson.String:
+		return []string{v.Str}, nil
+	case v.IsArray():
+		var stops []string
+		for _, item := range v.Array() {
+			if item.Type != gjson.String {
+				return nil, &RequestSchemaError{Param: "stop", Code: "invalid_type", Message: "stop must be a string or an array of strings"}
+			}
+			if item.Str != "" {
+				stops = append(stops, item.Str)
+			}
+		}
+		if len(stops) > completionMaxStops {
+			return nil, &RequestSchemaError{Param: "stop", Code: "invalid_value", Message: fmt.Sprintf("stop accepts at most %d sequences", completionMaxStops)}
+		}
+		return stops, nil
+	}
+	return nil, &RequestSchemaError{Param: "stop", Code: "invalid_type", Message: "stop must be a string or an array of strings"}
+}
+
+// finishReason 将 Messages API 的 stop_reason 映射为 finish_reason
+func (r *CompletionRequest) finishReason(stopReason string) string {
+	return NormalizeFinishReason(r.finishReasonOverrides, FinishReasonSourceAnthropic, stopReason)
+}
+
+type completionChoice struct {
+	Text         string  `json:"text"`
+	Index        int     `json:"index"`
+	Logprobs     any     `json:"logprobs"`
+	FinishReason *string `json:"finish_reason"`
+}
+
+type completionUsage struct {
+	PromptTokens     int64 `json:"prompt_tokens"`
+	CompletionTokens int64 `json:"completion_tokens"`
+	TotalTokens      int64 `json:"total_tokens"`
+}
+
+type completionObject struct {
+	ID                string             `json:"id"`
+	Object            string             `json:"object"`
+	Created           int64              `json:"created"`
+	Model             string             `json:"model"`
+	SystemFingerprint string             `json:"system_fingerprint,omitempty"`
+	Choices           []completionChoice `json:"choices"`
+	Usage             *completionUsage   `json:"usage,omitempty"`
+}
+
+// messagesCompletionUsage 输入 token 包含缓存创建与命中部分
+func messagesCompletionUsage(usage gjson.Result) *completionUsage {
+	prompt := usage.Get("input_tokens").Int() + usage.Get("cache_creation_input_tokens").Int() + usage.Get("cache_read_input_tokens").Int()
+	completion := usage.Get("output_tokens").Int()
+	return &completionUsage{PromptTokens: prompt, CompletionTokens: completion, TotalTokens: prompt + completion}
+}
+
+// truncateAtStop 在最早出现的停止序列处截断
+func truncateAtStop(text string, stops []string) (string, bool) {
+	cut := -1
+	for _, stop := range stops {
+		if i := strings.Index(text, stop); i >= 0 && (cut < 0 || i < cut) {
+			cut = i
+		}
+	}
+	if cut < 0 {
+		return text, false
+	}
+	return text[:cut], true
+}
+
+// MessagesToCompletion 将非流式 Messages 响应转换为 text_completion 对象
+func (r *CompletionRequest) MessagesToCompletion(body []byte, created int64) ([]byte, error) {
+	return r.MergeCompletions([]CompletionChoiceBody{{Body: body}}, created, false)
+}
+
+// MergeCompletions 将多次非流式 Messages 响应合并为一个 text_completion 对象（choice 使用各自的 index，usage 为各次之和）；
+// stream 为 true 时以 Completions 流式 chunk 输出合并结果
+func (r *CompletionRequest) MergeCompletions(choices []CompletionChoiceBody, created int64, stream bool) ([]byte, error) {
+	merged := completionObject{Object: "text_completion", Created: created, Model: r.Model, SystemFingerprint: r.SystemFingerprint, Usage: &completionUsage{}}
+	for i, choice := range choices {
+		if !gjson.ValidBytes(choice.Body) {
+			return nil, errors.New("invalid messages response")
+		}
+		parsed := gjson.ParseBytes(choice.Body)
+		if i == 0 {
+			merged.ID = "cmpl-" + parsed.Get("id").String()
+		}
+		merged.Choices = append(merged.Choices, r.messagesChoice(parsed, choice.Index))
+		usage := messagesCompletionUsage(parsed.Get("usage"))
+		merged.Usage.PromptTokens += usage.PromptTokens
+		merged.Usage.CompletionTokens += usage.CompletionTokens
+		merged.Usage.TotalTokens += usage.TotalTokens
+	}
+	if !stream {
+		return json.Marshal(merged)
+	}
+
+	var out bytes.Buffer
+	writeChunk := func(choices []completionChoice, usage *completionUsage) {
+		writeCompletionData(&out, completionObject{ID: merged.ID, Object: merged.Object, Created: created, Model: r.Model, SystemFingerprint: merged.SystemFingerprint, Choices: choices, Usage: usage})
+	}
+	for _, choice := range merged.Choices {
+		if choice.Text != "" {
+			writeChunk([]completionChoice{{Text: choice.Text, Index: choice.Index, Logprobs: choice.Logprobs}}, nil)
+		}
+		writeChunk([]completionChoice{{Index: choice.Index, Logprobs: choice.Logprobs, FinishReason: choice.FinishReason}}, nil)
+	}
+	if r.IncludeUsage {
+		writeChunk([]completionChoice{}, merged.Usage)
+	}
+	_, _ = out.WriteString("data: [DONE]\n\n")
+	return out.Bytes(), nil
+}
+
+func (r *CompletionRequest) messagesChoice(parsed gjson.Result, index int) completionChoice {
+	var text strings.Builder
+	for _, block := range parsed.Get("content").Array() {
+		if block.Get("type").String() == "text" {
+			_, _ = text.WriteString(block.Get("text").String())
+		}
+	}
+	output := text.String()
+	finish := r.finishReason(parsed.Get("stop_reason").String())
+	if truncated, stopped := truncateAtStop(output, r.LocalStops); stopped {
+		output, finish = truncated, "stop"
+	}
+	if r.Echo {
+		output = r.Prompt + output
+	}
+	return completionChoice{Text: output, Index: index, Logprobs: r.choiceLogprobs(), FinishReason: &finish}
+}
+
+func (r *CompletionRequest) choiceLogprobs() any {
+	if !r.Logprobs {
+		return nil
+	}
+	return emulatedCompletionLogprobs()
+}
+
+// CompletionStream 将 /v1/messages 的 SSE 事件流转换为 Completions 流式 chunk
+type CompletionStream struct {
+	req     *CompletionRequest
+	created int64
+
+	buf     []byte
+	id      string
+	usage   map[string]int64
+	finish  string
+	pending string
+	stopped bool
+	done    bool
+}
+
+// NewStream 创建流式转换器
+func (r *CompletionRequest) NewStream(created int64) *CompletionStream {
+	return &CompletionStream{req: r, created: created, usage: make(map[string]int64)}
+}
+
+// Done 是否已输出结束标记（[DONE] 或错误）
+func (s *CompletionStream) Done() bool {
+	return s.done
+}
+
+// Write 接收一段上游 SSE 数据，返回需要写给客户端的数据
+func (s *CompletionStream) Write(p []byte) []byte {
+	if s.done {
+		return nil
+	}
+	s.buf = append(s.buf, p...)
+	var out bytes.Buffer
+	for !s.done {
+		i, sep := bytes.Index(s.buf, []byte("\n\n")), 2
+		if j := bytes.Index(s.buf, []byte("\r\n\r\n")); j >= 0 && (i < 0 || j < i) {
+			i, sep = j, 4
+		}
+		if i < 0 {
+			break
+		}
+		event := s.buf[:i]
+		s.buf = s.buf[i+sep:]
+		s.handleEvent(event, &out)
+	}
+	return out.Bytes()
+}
+
+// Abort 上游在 message_stop 之前结束时输出错误 chunk
+func (s *CompletionStream) Abort(message string) []byte {
+	if s.done {
+		return nil
+	}
+	var out bytes.Buffer
+	s.writeError(&out, "api_error", message)
+	return out.Bytes()
+}
+
+func (s *CompletionStream) handleEvent(raw []byte, out *bytes.Buffer) {
+	var data []byte
+	for _, line := range bytes.Split(raw, []byte("\n")) {
+		line = bytes.TrimRight(line, "\r")
+		if rest, ok := bytes.CutPrefix(line, []byte("data:")); ok {
+			data = append(data, bytes.TrimSpace(rest)...)
+		}
+	}
+	if len(data) == 0 || !gjson.ValidBytes(data) {
+		return
+	}
+	event := gjson.ParseBytes(data)
+	switch event.Get("type").String() {
+	case "ping":
+		// 保持连接（客户端 SSE 解析会忽略注释行）
+		_, _ = out.WriteString(": ping\n\n")
+	case "message_start":
+		s.id = event.Get("message.id").String()
+		s.mergeUsage(event.Get("message.usage"))
+		if s.req.Echo {
+			s.writeChunk(out, s.req.Prompt, nil)
+		}
+	case "content_block_delta":
+		if event.Get("delta.type").String() == "text_delta" {
+			s.appendText(out, event.Get("delta.text").String())
+		}
+	case "message_delta":
+		if reason := event.Get("delta.stop_reason").String(); reason != "" && !s.stopped {
+			s.finish = s.req.finishReason(reason)
+		}
+		s.mergeUsage(event.Get("usage"))
+	case "message_stop":
+		if s.pending != "" {
+			s.writeChunk(out, s.pending, nil)
+			s.pending = ""
+		}
+		finish := s.finish
+		if finish == "" {
+			finish = "stop"
+		}
+		s.writeChunk(out, "", &finish)
+		if s.req.IncludeUsage {
+			writeCompletionData(out, completionObject{
+				ID:                s.completionID(),
+				Object:            "text_completion",
+				Created:           s.created,
+				Model:             s.req.Model,
+				SystemFingerprint: s.req.SystemFingerprint,
+				Choices:           []completionChoice{},
+				Usage:             s.completionUsage(),
+			})
+		}
+		_, _ = out.WriteString("data: [DONE]\n\n")
+		s.done = true
+	case "error":
+		errType := event.Get("error.type").String()
+		if errType == "" {
+			errType = "api_error"
+		}
+		message := event.Get("error.message").String()
+		if message == "" {
+			message = "upstream stream error"
+		}
+		s.writeError(out, errType, message)
+	}
+}
+
+// appendText 输出文本增量；存在本地停止序列时保留可能构成停止序列前缀的尾部，命中后丢弃其余输出
+func (s *CompletionStream) appendText(out *bytes.Buffer, delta string) {
+	if s.stopped || delta == "" {
+		return
+	}
+	if len(s.req.LocalStops) == 0 {
+		s.writeChunk(out, delta, nil)
+		return
+	}
+	s.pending += delta
+	if truncated, stopped := truncateAtStop(s.pending, s.req.LocalStops); stopped {
+		s.stopped, s.finish, s.pending = true, "stop", ""
+		if truncated != "" {
+			s.writeChunk(out, truncated, nil)
+		}
+		return
+	}
+	hold := 0
+	for _, stop := range s.req.LocalStops {
+		hold = max(hold, len(stop)-1)
+	}
+	cut := max(len(s.pending)-hold, 0)
+	for cut > 0 && cut < len(s.pending) && !utf8.RuneStart(s.pending[cut]) {
+		cut--
+	}
+	if cut > 0 {
+		s.writeChunk(out, s.pending[:cut], nil)
+		s.pending = s.pending[cut:]
+	}
+}
+
+func (s *CompletionStream) mergeUsage(usage gjson.Result) {
+	for _, key := range []string{"input_tokens", "output_tokens", "cache_creation_input_tokens", "cache_read_input_tokens"} {
+		if v := usage.Get(key).Int(); v > 0 {
+			s.usage[key] = v
+		}
+	}
+}
+
+func (s *CompletionStream) completionUsage() *completionUsage {
+	prompt := s.usage["input_tokens"] + s.usage["cache_creation_input_tokens"] + s.usage["cache_read_input_tokens"]
+	completion := s.usage["output_tokens"]
+	return &completionUsage{PromptTokens: prompt, CompletionTokens: completion, TotalTokens: prompt + completion}
+}
+
+func (s *CompletionStream) completionID() string {
+	return "cmpl-" + s.id
+}
+
+func (s *CompletionStream) writeChunk(out *bytes.Buffer, text string, finish *string) {
+	writeCompletionData(out, completionObject{
+		ID:                s.completionID(),
+		Object:            "text_completion",
+		Created:           s.created,
+		Model:             s.req.Model,
+		SystemFingerprint: s.req.SystemFingerprint,
+		Choices:           []completionChoice{{Text: text, Logprobs: s.req.choiceLogprobs(), FinishReason: finish}},
+	})
+}
+
+func (s *CompletionStream) writeError(out *bytes.Buffer, errType, message string) {
+	writeCompletionData(out, map[string]any{"error": map[string]any{"type": errType, "message": message}})
+	s.done = true
+}
+
+func writeCompletionData(out *bytes.Buffer, v any) {
+	data, err := json.Marshal(v)
+	if err != nil {
+		return
+	}
+	_, _ = out.WriteString("data: ")
+	_, _ = out.Write(data)
+	_, _ = out.WriteString("\n\n")
+}
@@ -0,0 +1,217 @@
+//go:build unit
+
+package service
+
+import (
+	"strings"
+	"testing"
+
+	"github.com/stretchr/testify/require"
+	"github.com/tidwall/gjson"
+)
+
+func TestConvertCompletionRequest(t *testing.T) {
+	req, body, schemaErr := ConvertCompletionRequest([]byte(`{"model":"claude-haiku","prompt":["Once upon"],"temperature":1.5,"stop":["\n","END"],"stream":true,"stream_options":{"include_usage":true}}`), 1, nil)
+	require.Nil(t, schemaErr)
+	require.Equal(t, "Once upon", req.Prompt)
+	require.True(t, req.Stream)
+	require.True(t, req.IncludeUsage)
+	require.Equal(t, []string{"\n"}, req.LocalStops)
+
+	parsed := gjson.ParseBytes(body)
+	require.Equal(t, "claude-haiku", parsed.Get("model").String())
+	require.Equal(t, int64(completionDefaultMaxTokens), parsed.Get("max_tokens").Int())
+	require.Equal(t, completionSystemPrompt, parsed.Get("system").String())
+	require.Equal(t, "user", parsed.Get("messages.0.role").String())
+	require.Equal(t, "Once upon", parsed.Get("messages.0.content").String())
+	require.Equal(t, 1.0, parsed.Get("temperature").Float())
+	require.Equal(t, `["END"]`, parsed.Get("stop_sequences").Raw)
+	require.True(t, parsed.Get("stream").Bool())
+
+	// suffix 使用插入式提示词
+	_, body, schemaErr = ConvertCompletionRequest([]byte(`{"model":"m","prompt":"def f(","suffix":"\n    return x","max_tokens":64}`), 1, nil)
+	require.Nil(t, schemaErr)
+	parsed = gjson.ParseBytes(body)
+	require.Equal(t, completionInsertSystemPrompt, parsed.Get("system").String())
+	require.Equal(t, "<prefix>def f(</prefix>\n<suffix>\n    return x</suffix>", parsed.Get("messages.0.content").String())
+	require.Equal(t, int64(64), parsed.Get("max_tokens").Int())
+
+	// n 不超过上限时接受，best_of 与 n 相同时接受
+	req, _, schemaErr = ConvertCompletionRequest([]byte(`{"model":"m","prompt":"x","n":3,"best_of":3}`), 4, nil)
+	require.Nil(t, schemaErr)
+	require.Equal(t, 3, req.N)
+
+	// seed 交由 Messages 按上游能力透传或移除
+	_, body, schemaErr = ConvertCompletionRequest([]byte(`{"model":"m","prompt":"x","seed":123}`), 1, nil)
+	require.Nil(t, schemaErr)
+	require.Equal(t, int64(123), gjson.GetBytes(body, "seed").Int())
+
+	for _, tc := range []struct {
+		body  string
+		param string
+	}{
+		{`{"prompt":"x"}`, "model"},
+		{`{"model":"m"}`, "prompt"},
+		{`{"model":"m","prompt":[1,2,3]}`, "prompt"},
+		{`{"model":"m","prompt":["a","b"]}`, "prompt"},
+		{`{"model":"m","prompt":"x","n":5}`, "n"},
+		{`{"model":"m","prompt":"x","n":0}`, "n"},
+		{`{"model":"m","prompt":"x","n":2,"best_of":3}`, "best_of"},
+		{`{"model":"m","prompt":"x","logprobs":6}`, "logprobs"},
+		{`{"model":"m","prompt":"x","suffix":"y","echo":true}`, "echo"},
+		{`{"model":"m","prompt":"x","stop":["a","b","c","d","e"]}`, "stop"},
+		{`{"model":"m","prompt":"x","max_tokens":0}`, "max_tokens"},
+		{`{"model":"m","prompt":"x","seed":"abc"}`, "seed"},
+	} {
+		_, _, schemaErr := ConvertCompletionRequest([]byte(tc.body), 4, nil)
+		require.NotNil(t, schemaErr, tc.body)
+		require.Equal(t, tc.param, schemaErr.Param, tc.body)
+	}
+}
+
+func TestCompletionRequest_MessagesToCompletion(t *testing.T) {
+	req := &CompletionRequest{Model: "gpt-3.5-turbo-instruct", Prompt: "Say: ", Echo: true, LocalStops: []string{"\n"}}
+	out, err := req.MessagesToCompletion([]byte(`{"id":"msg_1","content":[{"type":"text","text":"hello\nworld"}],"stop_reason":"max_tokens","usage":{"input_tokens":5,"cache_read_input_tokens":3,"output_tokens":4}}`), 100)
+	require.NoError(t, err)
+	parsed := gjson.ParseBytes(out)
+	require.Equal(t, "cmpl-msg_1", parsed.Get("id").String())
+	require.Equal(t, "text_completion", parsed.Get("object").String())
+	require.Equal(t, "gpt-3.5-turbo-instruct", parsed.Get("model").String())
+	require.Equal(t, "Say: hello", parsed.Get("choices.0.text").String())
+	// 本地停止序列命中时 finish_reason 为 stop
+	require.Equal(t, "stop", parsed.Get("choices.0.finish_reason").String())
+	require.Equal(t, gjson.Null, parsed.Get("choices.0.logprobs").Type)
+	require.Equal(t, int64(8), parsed.Get("usage.prompt_tokens").Int())
+	require.Equal(t, int64(12), parsed.Get("usage.total_tokens").Int())
+
+	req = &CompletionRequest{Model: "m"}
+	out, err = req.MessagesToCompletion([]byte(`{"id":"msg_2","content":[{"type":"text","text":"abc"}],"stop_reason":"max_tokens","usage":{}}`), 100)
+	require.NoError(t, err)
+	require.Equal(t, "length", gjson.GetBytes(out, "choices.0.finish_reason").String())
+
+	// 请求 logprobs 时返回标记为 emulated 的空结构
+	req = &CompletionRequest{Model: "m", Logprobs: true}
+	out, err = req.MessagesToCompletion([]byte(`{"id":"msg_3","content":[{"type":"text","text":"abc"}],"stop_reason":"end_turn"}`), 100)
+	require.NoError(t, err)
+	require.True(t, gjson.GetBytes(out, "choices.0.logprobs.emulated").Bool())
+	require.Equal(t, "[]", gjson.GetBytes(out, "choices.0.logprobs.tokens").Raw)
+	require.Equal(t, "[]", gjson.GetBytes(out, "choices.0.logprobs.token_logprobs").Raw)
+
+	_, err = req.MessagesToCompletion([]byte(`not json`), 100)
+	require.Error(t, err)
+}
+
+func TestCompletionRequest_MergeCompletions(t *testing.T) {
+	bodies := []CompletionChoiceBody{
+		{Index: 0, Body: []byte(`{"id":"msg_1","content":[{"type":"text","text":"one"}],"stop_reason":"end_turn","usage":{"input_tokens":5,"output_tokens":1}}`)},
+		{Index: 1, Body: []byte(`{"id":"msg_2","content":[{"type":"text","text":"two"}],"stop_reason":"max_tokens","usage":{"input_tokens":5,"output_tokens":2}}`)},
+	}
+	req := &CompletionRequest{Model: "m", N: 2, IncludeUsage: true, SystemFingerprint: "fp_0123456789"}
+	out, err := req.MergeCompletions(bodies, 100, false)
+	require.NoError(t, err)
+	parsed := gjson.ParseBytes(out)
+	require.Equal(t, "cmpl-msg_1", parsed.Get("id").String())
+	require.Equal(t, "fp_0123456789", parsed.Get("system_fingerprint").String())
+	require.Equal(t, `["one","two"]`, parsed.Get("choices.#.text").Raw)
+	require.Equal(t, `[0,1]`, parsed.Get("choices.#.index").Raw)
+	require.Equal(t, `["stop","length"]`, parsed.Get("choices.#.finish_reason").Raw)
+	// usage 为各次请求之和
+	require.Equal(t, int64(10), parsed.Get("usage.prompt_tokens").Int())
+	require.Equal(t, int64(13), parsed.Get("usage.total_tokens").Int())
+
+	out, err = req.MergeCompletions(bodies, 100, true)
+	require.NoError(t, err)
+	texts, finishes := completionStreamTexts(t, string(out))
+	require.Equal(t, []string{"one", "two"}, texts)
+	require.Equal(t, []string{"stop", "length"}, finishes)
+	require.Contains(t, string(out), `"index":1`)
+	require.Contains(t, string(out), `"usage":{"prompt_tokens":10,"completion_tokens":3,"total_tokens":13}`)
+	require.True(t, strings.HasSuffix(string(out), "data: [DONE]\n\n"))
+
+	// 部分 choice 失败时保留成功 choice 的原 index
+	out, err = req.MergeCompletions(bodies[1:], 100, false)
+	require.NoError(t, err)
+	require.Equal(t, `[1]`, gjson.GetBytes(out, "choices.#.index").Raw)
+	require.Equal(t, int64(7), gjson.GetBytes(out, "usage.total_tokens").Int())
+
+	_, err = req.MergeCompletions([]CompletionChoiceBody{bodies[0], {Index: 1, Body: []byte(`not json`)}}, 100, false)
+	require.Error(t, err)
+}
+
+func messagesSSEEvent(data string) string {
+	return "event: x\ndata: " + data + "\n\n"
+}
+
+func completionStreamTexts(t *testing.T, out string) ([]string, []string) {
+	t.Helper()
+	var texts, finishes []string
+	for _, line := range strings.Split(out, "\n") {
+		data, ok := strings.CutPrefix(line, "data: ")
+		if !ok || data == "[DONE]" {
+			continue
+		}
+		chunk := gjson.Parse(data)
+		for _, choice := range chunk.Get("choices").Array() {
+			if text := choice.Get("text").String(); text != "" {
+				texts = append(texts, text)
+			}
+			if finish := choice.Get("finish_reason").String(); finish != "" {
+				finishes = append(finishes, finish)
+			}
+		}
+	}
+	return texts, finishes
+}
+
+func TestCompletionStream(t *testing.T) {
+	req := &CompletionRequest{Model: "m", Prompt: "P:", Echo: true, IncludeUsage: true}
+	stream := req.NewStream(100)
+	input := messagesSSEEvent(`{"type":"message_start","message":{"id":"msg_1","usage":{"input_tokens":7}}}`) +
+		messagesSSEEvent(`{"type":"ping"}`) +
+		messagesSSEEvent(`{"type":"content_block_delta","delta":{"type":"text_delta","text":"ab"}}`) +
+		messagesSSEEvent(`{"type":"content_block_delta","delta":{"type":"text_delta","text":"cd"}}`) +
+		messagesSSEEvent(`{"type":"message_delta","delta":{"stop_reason":"end_turn"},"usage":{"output_tokens":2}}`) +
+		messagesSSEEvent(`{"type":"message_stop"}`)
+
+	// 任意切分输入结果一致
+	var out strings.Builder
+	for i := 0; i < len(input); i += 7 {
+		_, _ = out.Write(stream.Write([]byte(input[i:min(i+7, len(input))])))
+	}
+	require.True(t, stream.Done())
+	require.Nil(t, stream.Abort("x"))
+
+	texts, finishes := completionStreamTexts(t, out.String())
+	require.Equal(t, []string{"P:", "ab", "cd"}, texts)
+	require.Equal(t, []string{"stop"}, finishes)
+	require.Contains(t, out.String(), ": ping\n\n")
+	require.Contains(t, out.String(), `"usage":{"prompt_tokens":7,"completion_tokens":2,"total_tokens":9}`)
+	require.True(t, strings.HasSuffix(out.String(), "data: [DONE]\n\n"))
+}
+
+func TestCompletionStream_LocalStop(t *testing.T) {
+	req := &CompletionRequest{Model: "m", LocalStops: []string{"\n\n"}}
+	stream := req.NewStream(100)
+	out := string(stream.Write([]byte(
+		messagesSSEEvent(`{"type":"message_start","message":{"id":"msg_1"}}`) +
+			messagesSSEEvent(`{"type":"content_block_delta","delta":{"type":"text_delta","text":"line one\n"}}`) +
+			messagesSSEEvent(`{"type":"content_block_delta","delta":{"type":"text_delta","text":"\nline two"}}`) +
+			messagesSSEEvent(`{"type":"message_delta","delta":{"stop_reason":"max_tokens"}}`) +
+			messagesSSEEvent(`{"type":"message_stop"}`),
+	)))
+	texts, finishes := completionStreamTexts(t, out)
+	require.Equal(t, "line one", strings.Join(texts, ""))
+	require.Equal(t, []string{"stop"}, finishes)
+}
+
+func TestCompletionStream_ErrorAndAbort(t *testing.T) {
+	req := &CompletionRequest{Model: "m"}
+	stream := req.NewStream(100)
+	out := string(stream.Write([]byte(messagesSSEEvent(`{"type":"error","error":{"type":"overloaded_error","message":"Overloaded"}}`))))
+	require.Contains(t, out, `"error":{"message":"Overloaded","type":"overloaded_error"}`)
+	require.True(t, stream.Done())
+
+	stream = req.NewStream(100)
+	out = string(stream.Abort("stream ended before completion"))
+	require.Contains(t, out, "stream ended before completion")
+}
@@ -0,0 +1,167 @@
+package service
+
+import (
+	"bytes"
+	"context"
+	"encoding/json"
+	"errors"
+	"fmt"
+	"io"
+	"net/http"
+	"strings"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/pkg/copilot"
+	infraerrors "github.com/Wei-Shaw/sub2api/internal/pkg/errors"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/logger"
+	"github.com/gin-gonic/gin"
+	"github.com/google/uuid"
+	"github.com/tidwall/gjson"
+)
+
+// chatRequestHasImage 判断 Chat Completions 请求是否包含图片，Copilot 要求此类请求显式声明
+func chatRequestHasImage(chatBody []byte) bool {
+	for _, msg := range gjson.GetBytes(chatBody, "messages").Array() {
+		for _, part := range msg.Get("content").Array() {
+			if part.Get("type").String() == "image_url" {
+				return true
+			}
+		}
+	}
+	return false
+}
+
+// copilotTokenFailover GitHub token 失效或订阅取消时停止调度该账号，并切换到其他账号
+func (s *OpenAIGatewayService) copilotTokenFailover(ctx context.Context, c *gin.Context, account *Account, err error) error {
+	status := infraerrors.Code(err)
+	message := infraerrors.Message(err)
+	body, _ := json.Marshal(map[string]any{"error": map[string]any{"message": message}})
+	if infraerrors.IsUnauthorized(err) || infraerrors.IsForbidden(err) {
+		if s.rateLimitService != nil {
+			s.rateLimitService.HandleUpstreamError(ctx, account, status, http.Header{}, body)
+		}
+	} else {
+		status = http.StatusBadGateway
+	}
+	logger.LegacyPrintf("service.openai_gateway", "[Copilot] token exchange failed (failover): account=%d status=%d err=%s",
+		account.ID, status, sanitizeUpstreamErrorMessage(message))
+	appendOpsUpstreamError(c, OpsUpstreamErrorEvent{
+		Platform:           account.Platform,
+		AccountID:          account.ID,
+		AccountName:        account.Name,
+		UpstreamStatusCode: status,
+		Kind:               "failover",
+		Message:            sanitizeUpstreamErrorMessage(message),
+	})
+	return &UpstreamFailoverError{StatusCode: status, ResponseBody: body}
+}
+
+// doCopilotRequest 发送 chat/completions 请求。
+// 401 说明缓存的短期 Copilot token 已失效，清除缓存后切换账号，不禁用账号；其余错误与 OpenAI 账号处理一致。
+func (s *OpenAIGatewayService) doCopilotRequest(ctx context.Context, c *gin.Context, account *Account, req *http.Request, proxyURL string, requestBody []byte) (*http.Response, error) {
+	resp, err := s.httpUpstream.DoWithTLS(withAccountTLSProfile(req, account), proxyURL, account.ID, account.Concurrency, account.IsTLSFingerprintEnabled())
+	if err != nil {
+		if resp != nil && resp.Body != nil {
+			_ = resp.Body.Close()
+		}
+		safeErr := sanitizeUpstreamErrorMessage(err.Error())
+		setOpsUpstreamError(c, 0, safeErr, "")
+		appendOpsUpstreamError(c, OpsUpstreamErrorEvent{
+			Platform:    account.Platform,
+			AccountID:   account.ID,
+			AccountName: account.Name,
+			Kind:        "request_error",
+			Message:     safeErr,
+		})
+		writeUpstreamRequestError(c, UpstreamErrorFormatOpenAI, err)
+		return nil, fmt.Errorf("upstream request failed: %s", safeErr)
+	}
+	if resp.StatusCode < 400 {
+		return resp, nil
+	}
+
+	respBody, _ := io.ReadAll(io.LimitReader(resp.Body, 2<<20))
+	_ = resp.Body.Close()
+	resp.Body = io.NopCloser(bytes.NewReader(respBody))
+
+	if resp.StatusCode == http.StatusUnauthorized {
+		s.copilotService.InvalidateSessionToken(ctx, account)
+		appendOpsUpstreamError(c, OpsUpstreamErrorEvent{
+			Platform:           account.Platform,
+			AccountID:          account.ID,
+			AccountName:        account.Name,
+			UpstreamStatusCode: resp.StatusCode,
+			Kind:               "failover",
+			Message:            extractUpstreamErrorMessage(respBody),
+		})
+		return nil, &UpstreamFailoverError{StatusCode: resp.StatusCode, ResponseBody: respBody}
+	}
+	if s.shouldFailoverUpstreamError(resp.StatusCode) {
+		logger.LegacyPrintf("service.openai_gateway", "[Copilot] Upstream error (failover): Account=%d(%s) Status=%d Body=%s",
+			account.ID, account.Name, resp.StatusCode, truncateString(string(respBody), 1000))
+
+		s.handleFailoverSideEffects(ctx, resp, account)
+		appendOpsUpstreamError(c, OpsUpstreamErrorEvent{
+			Platform:           account.Platform,
+			AccountID:          account.ID,
+			AccountName:        account.Name,
+			UpstreamStatusCode: resp.StatusCode,
+			Kind:               "failover",
+			Message:            extractUpstreamErrorMessage(respBody),
+		})
+		return nil, &UpstreamFailoverError{StatusCode: resp.StatusCode, ResponseBody: respBody}
+	}
+
+	if _, err := s.handleErrorResponse(ctx, resp, c, account, requestBody); err != nil {
+		return nil, err
+	}
+	return nil, fmt.Errorf("upstream error: %d", resp.StatusCode)
+}
+
+// forwardCopilot 将 Responses 请求转换为 Chat Completions，通过 Copilot 订阅转发
+func (s *OpenAIGatewayService) forwardCopilot(ctx context.Context, c *gin.Context, account *Account, body []byte, startTime time.Time) (*OpenAIForwardResult, error) {
+	if s.copilotService == nil {
+		return nil, errors.New("copilot service is not configured")
+	}
+
+	reqModel := gjson.GetBytes(body, "model").String()
+	reqStream := gjson.GetBytes(body, "stream").Bool()
+	chatBody, err := convertResponsesToChatCompletions(body, account.GetMappedModel(reqModel))
+	if err != nil {
+		writeChatGPTWebError(c, http.StatusBadRequest, "invalid_request_error", err.Error())
+		return nil, err
+	}
+
+	token, err := s.copilotService.GetSessionToken(ctx, account)
+	if err != nil {
+		return nil, s.copilotTokenFailover(ctx, c, account, err)
+	}
+
+	req, err := http.NewRequestWithContext(ctx, http.MethodPost, strings.TrimRight(token.APIBaseURL, "/")+"/chat/completions", bytes.NewReader(chatBody))
+	if err != nil {
+		return nil, err
+	}
+	copilot.SetClientHeaders(req.Header)
+	req.Header.Set("Authorization", "Bearer "+token.Token)
+	req.Header.Set("Content-Type", "application/json")
+	req.Header.Set("Openai-Intent", "conversation-panel")
+	req.Header.Set("X-Request-Id", uuid.NewString())
+	if reqStream {
+		req.Header.Set("Accept", "text/event-stream")
+	} else {
+		req.Header.Set("Accept", "application/json")
+	}
+	if chatRequestHasImage(chatBody) {
+		req.Header.Set("Copilot-Vision-Request", "true")
+	}
+	setOpsUpstreamRequestBody(c, chatBody)
+
+	resp, err := s.doCopilotRequest(ctx, c, account, req, copilotAccountProxyURL(account), body)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = resp.Body.Close() }()
+
+	logprobs, _ := responsesLogprobsRequested(gjson.ParseBytes(body))
+	return s.relayChatCompletionAsResponses(c, resp, reqModel, reqStream, logprobs, startTime)
+}
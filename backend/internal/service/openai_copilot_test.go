@@ -0,0 +1,355 @@
+package service
+
+import (
+	"context"
+	"net/http"
+	"net/http/httptest"
+	"strings"
+	"testing"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/copilot"
+	infraerrors "github.com/Wei-Shaw/sub2api/internal/pkg/errors"
+	"github.com/gin-gonic/gin"
+	"github.com/stretchr/testify/require"
+	"github.com/tidwall/gjson"
+)
+
+type copilotAuthClientStub struct {
+	exchanges   int
+	exchangeErr error
+	poll        *copilot.AccessTokenResponse
+}
+
+func (s *copilotAuthClientStub) RequestDeviceCode(context.Context, string) (*copilot.DeviceCodeResponse, error) {
+	return &copilot.DeviceCodeResponse{DeviceCode: "dc-1", UserCode: "ABCD-1234", VerificationURI: "https://github.com/login/device", ExpiresIn: 900, Interval: 5}, nil
+}
+
+func (s *copilotAuthClientStub) PollAccessToken(context.Context, string, string) (*copilot.AccessTokenResponse, error) {
+	return s.poll, nil
+}
+
+func (s *copilotAuthClientStub) RefreshAccessToken(context.Context, string, string) (*copilot.AccessTokenResponse, error) {
+	return &copilot.AccessTokenResponse{AccessToken: "ghu_new", RefreshToken: "ghr_new", ExpiresIn: 28800}, nil
+}
+
+func (s *copilotAuthClientStub) ExchangeToken(context.Context, string, string) (*copilot.Token, error) {
+	s.exchanges++
+	if s.exchangeErr != nil {
+		return nil, s.exchangeErr
+	}
+	token := &copilot.Token{Token: "tid=1", ExpiresAt: time.Now().Add(30 * time.Minute).Unix(), SKU: "copilot_for_business_seat"}
+	token.Endpoints.API = "https://api.business.githubcopilot.com"
+	return token, nil
+}
+
+func (s *copilotAuthClientStub) ListModels(context.Context, string, string, string) ([]copilot.Model, error) {
+	return []copilot.Model{{ID: "gpt-4.1"}}, nil
+}
+
+type copilotTokenCacheStub struct {
+	GeminiTokenCache
+	tokens map[string]string
+}
+
+func (c *copilotTokenCacheStub) GetAccessToken(_ context.Context, key string) (string, error) {
+	return c.tokens[key], nil
+}
+
+func (c *copilotTokenCacheStub) SetAccessToken(_ context.Context, key, token string, _ time.Duration) error {
+	c.tokens[key] = token
+	return nil
+}
+
+func (c *copilotTokenCacheStub) DeleteAccessToken(_ context.Context, key string) error {
+	delete(c.tokens, key)
+	return nil
+}
+
+func newCopilotAccountForTest() *Account {
+	return &Account{
+		ID:          501,
+		Name:        "copilot",
+		Platform:    PlatformOpenAI,
+		Type:        AccountTypeCopilot,
+		Concurrency: 1,
+		Credentials: map[string]any{"github_token": "ghu_abc"},
+		Status:      StatusActive,
+		Schedulable: true,
+	}
+}
+
+func newCopilotGatewayForTest(upstream HTTPUpstream, auth *copilotAuthClientStub) (*OpenAIGatewayService, *claudeWebAccountRepoStub, *copilotTokenCacheStub) {
+	repo := &claudeWebAccountRepoStub{}
+	cache := &copilotTokenCacheStub{tokens: map[string]string{}}
+	svc := &OpenAIGatewayService{
+		httpUpstream:     upstream,
+		cfg:              &config.Config{},
+		rateLimitService: &RateLimitService{accountRepo: repo},
+		copilotService:   NewCopilotService(auth, nil, repo, cache),
+	}
+	return svc, repo, cache
+}
+
+const copilotTestChatStream = `data: {"id":"c1","choices":[{"index":0,"delta":{"role":"assistant","content":"Hel"}}]}
+
+data: {"id":"c1","choices":[{"index":0,"delta":{"content":"lo"}}]}
+
+data: {"id":"c1","choices":[{"index":0,"delta":{"tool_calls":[{"index":0,"id":"call_1","type":"function","function":{"name":"lookup","arguments":"{\"q\":"}}]}}]}
+
+data: {"id":"c1","choices":[{"index":0,"delta":{"tool_calls":[{"index":0,"function":{"arguments":"\"x\"}"}}]},"finish_reason":"tool_calls"}]}
+
+data: {"id":"c1","choices":[],"usage":{"prompt_tokens":12,"completion_tokens":7,"prompt_tokens_details":{"cached_tokens":4}}}
+
+data: [DONE]
+
+`
+
+func TestConvertResponsesToChatCompletions(t *testing.T) {
+	out, err := convertResponsesToChatCompletions([]byte(`{
+		"model":"gpt-4.1","instructions":"Be brief.","stream":true,"max_output_tokens":256,"seed":42,
+		"reasoning":{"effort":"low"},
+		"text":{"format":{"type":"json_schema","name":"answer","schema":{"type":"object"},"strict":true}},
+		"tools":[{"type":"function","name":"lookup","description":"Look up","parameters":{"type":"object"}}],
+		"tool_choice":{"type":"function","name":"lookup"},
+		"input":[
+			{"role":"user","content":[{"type":"input_text","text":"What is this?"},{"type":"input_image","image_url":"data:image/png;base64,AAAA","detail":"low"}]},
+			{"type":"reasoning","summary":[]},
+			{"type":"function_call","call_id":"call_1","name":"lookup","arguments":"{}"},
+			{"type":"function_call","call_id":"call_2","name":"lookup","arguments":"{\"q\":1}"},
+			{"type":"function_call_output","call_id":"call_1","output":"found"},
+			{"role":"assistant","content":[{"type":"output_text","text":"It is a PNG."}]}
+		]}`), "gpt-4.1-mapped")
+	require.NoError(t, err)
+
+	req := gjson.ParseBytes(out)
+	require.Equal(t, "gpt-4.1-mapped", req.Get("model").String())
+	require.True(t, req.Get("stream_options.include_usage").Bool())
+	require.Equal(t, int64(256), req.Get("max_tokens").Int())
+	require.Equal(t, int64(42), req.Get("seed").Int())
+	require.Equal(t, "low", req.Get("reasoning_effort").String())
+	require.Equal(t, "answer", req.Get("response_format.json_schema.name").String())
+	require.Equal(t, "lookup", req.Get("tools.0.function.name").String())
+	require.Equal(t, "lookup", req.Get("tool_choice.function.name").String())
+
+	messages := req.Get("messages").Array()
+	require.Len(t, messages, 5)
+	require.Equal(t, "Be brief.", messages[0].Get("content").String())
+	require.Equal(t, "image_url", messages[1].Get("content.1.type").String())
+	require.Equal(t, "low", messages[1].Get("content.1.image_url.detail").String())
+	require.Len(t, messages[2].Get("tool_calls").Array(), 2, "consecutive calls share one assistant message")
+	require.Equal(t, "tool", messages[3].Get("role").String())
+	require.Equal(t, "call_1", messages[3].Get("tool_call_id").String())
+	require.Equal(t, "It is a PNG.", messages[4].Get("content").String())
+	require.True(t, chatRequestHasImage(out))
+
+	_, err = convertResponsesToChatCompletions([]byte(`{"input":"hi","tools":[{"type":"web_search"}]}`), "gpt-4.1")
+	require.ErrorContains(t, err, "web_search")
+	_, err = convertResponsesToChatCompletions([]byte(`{"input":"hi","previous_response_id":"resp_1"}`), "gpt-4.1")
+	require.ErrorContains(t, err, "previous_response_id")
+}
+
+func TestChatCompletionToResponses(t *testing.T) {
+	out, usage, _ := chatCompletionToResponses([]byte(`{"choices":[{"message":{"role":"assistant","content":"Hi"},"finish_reason":"length"}],
+		"usage":{"prompt_tokens":10,"completion_tokens":3,"completion_tokens_details":{"reasoning_tokens":1}}}`), "abc", "gpt-4.1", 1700000000, false, nil)
+	require.Equal(t, 10, usage.InputTokens)
+	require.Equal(t, 1, usage.ReasoningTokens)
+	require.Equal(t, "incomplete", out["status"])
+	require.Equal(t, map[string]any{"reason": "max_output_tokens"}, out["incomplete_details"])
+	require.Equal(t, "Hi", out["output"].([]any)[0].(map[string]any)["content"].([]any)[0].(map[string]any)["text"])
+	require.NotContains(t, out["output"].([]any)[0].(map[string]any)["content"].([]any)[0], "logprobs")
+}
+
+func TestChatCompletionToResponses_Logprobs(t *testing.T) {
+	chatBody, err := convertResponsesToChatCompletions([]byte(`{"input":"hi","top_logprobs":2}`), "gpt-4.1")
+	require.NoError(t, err)
+	require.True(t, gjson.GetBytes(chatBody, "logprobs").Bool())
+	require.Equal(t, int64(2), gjson.GetBytes(chatBody, "top_logprobs").Int())
+	chatBody, err = convertResponsesToChatCompletions([]byte(`{"input":"hi","include":["message.output_text.logprobs"]}`), "gpt-4.1")
+	require.NoError(t, err)
+	require.True(t, gjson.GetBytes(chatBody, "logprobs").Bool())
+	require.False(t, gjson.GetBytes(chatBody, "top_logprobs").Exists())
+
+	// 上游返回 logprobs 时透传
+	out, _, emulated := chatCompletionToResponses([]byte(`{"choices":[{"message":{"content":"Hi"},"logprobs":{"content":[{"token":"Hi","logprob":-0.1,"bytes":[72,105],"top_logprobs":[]}]},"finish_reason":"stop"}]}`), "abc", "gpt-4.1", 1700000000, true, nil)
+	require.False(t, emulated)
+	part := out["output"].([]any)[0].(map[string]any)["content"].([]any)[0].(map[string]any)
+	require.Len(t, part["logprobs"], 1)
+	require.Equal(t, "Hi", part["logprobs"].([]any)[0].(map[string]any)["token"])
+
+	// 上游未提供时以空数组代替并标记 emulated
+	out, _, emulated = chatCompletionToResponses([]byte(`{"choices":[{"message":{"content":"Hi"},"finish_reason":"stop"}]}`), "abc", "gpt-4.1", 1700000000, true, nil)
+	require.True(t, emulated)
+	part = out["output"].([]any)[0].(map[string]any)["content"].([]any)[0].(map[string]any)
+	require.Equal(t, []any{}, part["logprobs"])
+}
+
+func TestChatCompatStream_Logprobs(t *testing.T) {
+	var events []map[string]any
+	st := newChatCompatStream("abc", "gpt-4.1", 1700000000, nil, func(event string, data map[string]any) {
+		data["type"] = event
+		events = append(events, data)
+	})
+	st.logprobs = true
+	st.consume(gjson.Parse(`{"choices":[{"delta":{"content":"He"},"logprobs":{"content":[{"token":"He","logprob":-0.5}]}}]}`))
+	st.consume(gjson.Parse(`{"choices":[{"delta":{"content":"y"},"finish_reason":"stop"}]}`))
+	st.finish()
+
+	var deltas []map[string]any
+	for _, e := range events {
+		if e["type"] == "response.output_text.delta" {
+			deltas = append(deltas, e)
+		}
+	}
+	require.Len(t, deltas, 2)
+	require.Len(t, deltas[0]["logprobs"], 1)
+	require.Equal(t, []any{}, deltas[1]["logprobs"])
+	done := events[len(events)-1]["response"].(map[string]any)
+	part := done["output"].([]any)[0].(map[string]any)["content"].([]any)[0].(map[string]any)
+	require.Len(t, part["logprobs"], 1)
+}
+
+func TestOpenAIGatewayService_ForwardCopilot_Stream(t *testing.T) {
+	gin.SetMode(gin.TestMode)
+	upstream := &claudeWebUpstreamStub{respond: func(*http.Request) *http.Response {
+		return claudeWebTestResponse(http.StatusOK, copilotTestChatStream)
+	}}
+	auth := &copilotAuthClientStub{}
+	svc, repo, cache := newCopilotGatewayForTest(upstream, auth)
+	account := newCopilotAccountForTest()
+
+	rec := httptest.NewRecorder()
+	c, _ := gin.CreateTestContext(rec)
+	c.Request = httptest.NewRequest(http.MethodPost, "/v1/responses", nil)
+
+	result, err := svc.Forward(context.Background(), c, account, []byte(`{"model":"gpt-4.1","stream":true,"input":"hi"}`))
+	require.NoError(t, err)
+	require.True(t, result.Stream)
+	require.NotNil(t, result.FirstTokenMs)
+	require.Equal(t, 12, result.Usage.InputTokens)
+	require.Equal(t, 4, result.Usage.CacheReadInputTokens)
+
+	out := rec.Body.String()
+	for _, event := range []string{"response.created", "response.output_text.delta", "response.function_call_arguments.delta", "response.output_item.done", "response.completed"} {
+		require.Contains(t, out, "event: "+event+"\n")
+	}
+	require.Contains(t, out, `"arguments":"{\"q\":\"x\"}"`)
+
+	require.Equal(t, []string{"POST /chat/completions"}, upstream.requests)
+	headers := upstream.headers["POST /chat/completions"]
+	require.Equal(t, "Bearer tid=1", headers.Get("Authorization"))
+	require.Equal(t, copilot.IntegrationID, headers.Get("Copilot-Integration-Id"))
+	require.Equal(t, "https://api.business.githubcopilot.com", repo.extra["copilot_api_base"])
+	require.Equal(t, "tid=1", cache.tokens[CopilotTokenCacheKey(account)])
+	require.True(t, gjson.GetBytes(upstream.bodies["POST /chat/completions"], "stream").Bool())
+
+	// 缓存命中时不再交换 token
+	rec = httptest.NewRecorder()
+	c, _ = gin.CreateTestContext(rec)
+	c.Request = httptest.NewRequest(http.MethodPost, "/v1/responses", nil)
+	_, err = svc.Forward(context.Background(), c, account, []byte(`{"model":"gpt-4.1","stream":true,"input":"hi"}`))
+	require.NoError(t, err)
+	require.Equal(t, 1, auth.exchanges)
+}
+
+func TestOpenAIGatewayService_ForwardCopilot_NonStream(t *testing.T) {
+	gin.SetMode(gin.TestMode)
+	upstream := &claudeWebUpstreamStub{respond: func(*http.Request) *http.Response {
+		return claudeWebTestResponse(http.StatusOK, `{"id":"c1","choices":[{"message":{"role":"assistant","content":"Hello"},"finish_reason":"stop"}],"usage":{"prompt_tokens":5,"completion_tokens":2}}`)
+	}}
+	svc, _, _ := newCopilotGatewayForTest(upstream, &copilotAuthClientStub{})
+
+	rec := httptest.NewRecorder()
+	c, _ := gin.CreateTestContext(rec)
+	c.Request = httptest.NewRequest(http.MethodPost, "/v1/responses", nil)
+
+	result, err := svc.Forward(context.Background(), c, newCopilotAccountForTest(), []byte(`{"model":"gpt-4.1","input":"hi"}`))
+	require.NoError(t, err)
+	require.False(t, result.Stream)
+	require.Equal(t, 2, result.Usage.OutputTokens)
+	require.Equal(t, "completed", gjson.Get(rec.Body.String(), "status").String())
+	require.Equal(t, "Hello", gjson.Get(rec.Body.String(), "output.0.content.0.text").String())
+	require.True(t, strings.HasPrefix(gjson.Get(rec.Body.String(), "id").String(), "resp_"))
+}
+
+func TestOpenAIGatewayService_ForwardCopilot_StaleTokenFailsOver(t *testing.T) {
+	gin.SetMode(gin.TestMode)
+	upstream := &claudeWebUpstreamStub{respond: func(*http.Request) *http.Response {
+		return claudeWebTestResponse(http.StatusUnauthorized, `{"error":{"message":"token expired"}}`)
+	}}
+	svc, repo, cache := newCopilotGatewayForTest(upstream, &copilotAuthClientStub{})
+	account := newCopilotAccountForTest()
+
+	rec := httptest.NewRecorder()
+	c, _ := gin.CreateTestContext(rec)
+	c.Request = httptest.NewRequest(http.MethodPost, "/v1/responses", nil)
+
+	_, err := svc.Forward(context.Background(), c, account, []byte(`{"model":"gpt-4.1","input":"hi"}`))
+	var failover *UpstreamFailoverError
+	require.ErrorAs(t, err, &failover)
+	require.Equal(t, http.StatusUnauthorized, failover.StatusCode)
+	require.Empty(t, cache.tokens, "stale copilot token must be dropped")
+	require.Empty(t, repo.errorMsg, "a stale session token does not disable the account")
+}
+
+func TestOpenAIGatewayService_ForwardCopilot_RevokedGitHubToken(t *testing.T) {
+	gin.SetMode(gin.TestMode)
+	upstream := &claudeWebUpstreamStub{respond: func(*http.Request) *http.Response {
+		return claudeWebTestResponse(http.StatusOK, "{}")
+	}}
+	auth := &copilotAuthClientStub{exchangeErr: infraerrors.Unauthorized("COPILOT_GITHUB_TOKEN_INVALID", "github token rejected")}
+	svc, repo, _ := newCopilotGatewayForTest(upstream, auth)
+
+	rec := httptest.NewRecorder()
+	c, _ := gin.CreateTestContext(rec)
+	c.Request = httptest.NewRequest(http.MethodPost, "/v1/responses", nil)
+
+	_, err := svc.Forward(context.Background(), c, newCopilotAccountForTest(), []byte(`{"model":"gpt-4.1","input":"hi"}`))
+	var failover *UpstreamFailoverError
+	require.ErrorAs(t, err, &failover)
+	require.Equal(t, http.StatusUnauthorized, failover.StatusCode)
+	require.Contains(t, repo.errorMsg, "github token rejected")
+	require.Empty(t, upstream.requests)
+}
+
+func TestCopilotService_DeviceFlow(t *testing.T) {
+	auth := &copilotAuthClientStub{poll: &copilot.AccessTokenResponse{Error: copilot.ErrAuthorizationPending}}
+	svc := NewCopilotService(auth, nil, nil, nil)
+
+	started, err := svc.StartDeviceAuth(context.Background(), nil)
+	require.NoError(t, err)
+	require.Equal(t, "ABCD-1234", started.UserCode)
+
+	polled, err := svc.PollDeviceAuth(context.Background(), started.SessionID)
+	require.NoError(t, err)
+	require.Equal(t, CopilotDeviceStatusPending, polled.Status)
+
+	auth.poll = &copilot.AccessTokenResponse{AccessToken: "ghu_abc", RefreshToken: "ghr_abc", ExpiresIn: 28800}
+	polled, err = svc.PollDeviceAuth(context.Background(), started.SessionID)
+	require.NoError(t, err)
+	require.Equal(t, CopilotDeviceStatusAuthorized, polled.Status)
+	require.Equal(t, "copilot_for_business_seat", polled.SKU)
+	require.Equal(t, "ghu_abc", polled.Credentials["github_token"])
+	require.Equal(t, "ghr_abc", polled.Credentials["github_refresh_token"])
+	require.NotEmpty(t, polled.Credentials["github_token_expires_at"])
+
+	_, err = svc.PollDeviceAuth(context.Background(), started.SessionID)
+	require.True(t, infraerrors.IsNotFound(err), "session is consumed once authorized")
+}
+
+func TestCopilotTokenRefresher(t *testing.T) {
+	refresher := NewCopilotTokenRefresher(NewCopilotService(&copilotAuthClientStub{}, nil, nil, nil))
+	account := newCopilotAccountForTest()
+	require.False(t, refresher.CanRefresh(account), "non-expiring github tokens need no refresh")
+
+	account.Credentials["github_refresh_token"] = "ghr_old"
+	account.Credentials["github_token_expires_at"] = time.Now().Add(time.Hour).Format(time.RFC3339)
+	require.True(t, refresher.CanRefresh(account))
+	require.True(t, refresher.NeedsRefresh(account, 2*time.Hour))
+
+	creds, err := refresher.Refresh(context.Background(), account)
+	require.NoError(t, err)
+	require.Equal(t, "ghu_new", creds["github_token"])
+	require.Equal(t, "ghr_new", creds["github_refresh_token"])
+}
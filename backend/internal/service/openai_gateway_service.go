@@ -18,6 +18,7 @@ import (
 	"time"
 
 	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/claude"
 	"github.com/Wei-Shaw/sub2api/internal/pkg/logger"
 	"github.com/Wei-Shaw/sub2api/internal/pkg/openai"
 	"github.com/Wei-Shaw/sub2api/internal/util/responseheaders"
@@ -185,6 +186,8 @@ type OpenAIUsage struct {
 	OutputTokens             int `json:"output_tokens"`
 	CacheCreationInputTokens int `json:"cache_creation_input_tokens,omitempty"`
 	CacheReadInputTokens     int `json:"cache_read_input_tokens,omitempty"`
+	// ReasoningTokens 来自 output_tokens_details.reasoning_tokens（已包含在 OutputTokens 内）
+	ReasoningTokens int `json:"reasoning_tokens,omitempty"`
 }
 
 // OpenAIForwardResult represents the result of forwarding
@@ -198,6 +201,8 @@ type OpenAIForwardResult struct {
 	Stream          bool
 	Duration        time.Duration
 	FirstTokenMs    *int
+	// UsageEstimated 上游未返回用量（网页会话），Usage 为本地分词器估算值
+	UsageEstimated bool
 }
 
 // OpenAIGatewayService handles OpenAI API gateway operations
@@ -218,6 +223,13 @@ type OpenAIGatewayService struct {
 	deferredService     *DeferredService
 	openAITokenProvider *OpenAITokenProvider
 	toolCorrector       *CodexToolCorrector
+	latencyTracker      *AccountLatencyTracker
+	quotaTracker        *AccountQuotaBudgetTracker
+	liveUsage           *LiveUsageHub
+	copilotService      *CopilotService
+	accountShards       *AccountShardService
+	eventExport         *EventExportService
+	usageAnalytics      *UsageAnalyticsService
 }
 
 // NewOpenAIGatewayService creates a new OpenAIGatewayService
@@ -236,6 +248,13 @@ func NewOpenAIGatewayService(
 	httpUpstream HTTPUpstream,
 	deferredService *DeferredService,
 	openAITokenProvider *OpenAITokenProvider,
+	latencyTracker *AccountLatencyTracker,
+	quotaTracker *AccountQuotaBudgetTracker,
+	liveUsage *LiveUsageHub,
+	copilotService *CopilotService,
+	accountShards *AccountShardService,
+	eventExport *EventExportService,
+	usageAnalytics *UsageAnalyticsService,
 ) *OpenAIGatewayService {
 	return &OpenAIGatewayService{
 		accountRepo:         accountRepo,
@@ -254,6 +273,13 @@ func NewOpenAIGatewayService(
 		deferredService:     deferredService,
 		openAITokenProvider: openAITokenProvider,
 		toolCorrector:       NewCodexToolCorrector(),
+		latencyTracker:      latencyTracker,
+		quotaTracker:        quotaTracker,
+		liveUsage:           liveUsage,
+		copilotService:      copilotService,
+		accountShards:       accountShards,
+		eventExport:         eventExport,
+		usageAnalytics:      usageAnalytics,
 	}
 }
 
@@ -751,7 +777,8 @@ func (s *OpenAIGatewayService) SelectAccountWithLoadAwareness(ctx context.Contex
 					_ = s.cache.DeleteSessionAccountID(ctx, derefGroupID(groupID), "openai:"+sessionHash)
 				}
 				if !clearSticky && account.IsSchedulable() && account.IsOpenAI() &&
-					(requestedModel == "" || account.IsModelSupported(requestedModel)) {
+					(requestedModel == "" || account.IsModelSupported(requestedModel)) &&
+					s.quotaTracker.Level(ctx, account) != AccountQuotaExhausted {
 					result, err := s.tryAcquireAccountSlot(ctx, accountID, account.Concurrency)
 					if err == nil && result.Acquired {
 						_ = s.cache.RefreshSessionTTL(ctx, derefGroupID(groupID), "openai:"+sessionHash, openaiStickySessionTTL)
@@ -781,6 +808,7 @@ func (s *OpenAIGatewayService) SelectAccountWithLoadAwareness(ctx context.Contex
 
 	// ============ Layer 2: Load-aware selection ============
 	candidates := make([]*Account, 0, len(accounts))
+	var quotaLowCandidates []*Account
 	for i := range accounts {
 		acc := &accounts[i]
 		if isExcluded(acc.ID) {
@@ -795,8 +823,19 @@ func (s *OpenAIGatewayService) SelectAccountWithLoadAwareness(ctx context.Contex
 		if requestedModel != "" && !acc.IsModelSupported(requestedModel) {
 			continue
 		}
+		// Quota budget: skip exhausted accounts, keep near-exhaustion ones as a last resort.
+		switch s.quotaTracker.Level(ctx, acc) {
+		case AccountQuotaExhausted:
+			continue
+		case AccountQuotaLow:
+			quotaLowCandidates = append(quotaLowCandidates, acc)
+			continue
+		}
 		candidates = append(candidates, acc)
 	}
+	if len(candidates) == 0 {
+		candidates = quotaLowCandidates
+	}
 
 	if len(candidates) == 0 {
 		return nil, errors.New("no available accounts")
@@ -843,11 +882,19 @@ func (s *OpenAIGatewayService) SelectAccountWithLoadAwareness(ctx context.Contex
 		}
 
 		if len(available) > 0 {
+			// latency 策略：同优先级内优先延迟在最快账号容差范围内的账号
+			var latencyPreferred map[int64]bool
+			if cfg.SelectionStrategy == "latency" {
+				latencyPreferred = latencyPreferredIDs(available, s.latencyTracker)
+			}
 			sort.SliceStable(available, func(i, j int) bool {
 				a, b := available[i], available[j]
 				if a.account.Priority != b.account.Priority {
 					return a.account.Priority < b.account.Priority
 				}
+				if latencyPreferred != nil && latencyPreferred[a.account.ID] != latencyPreferred[b.account.ID] {
+					return latencyPreferred[a.account.ID]
+				}
 				if a.loadInfo.LoadRate != b.loadInfo.LoadRate {
 					return a.loadInfo.LoadRate < b.loadInfo.LoadRate
 				}
@@ -897,7 +944,16 @@ func (s *OpenAIGatewayService) SelectAccountWithLoadAwareness(ctx context.Contex
 	return nil, errors.New("no available accounts")
 }
 
+// listSchedulableAccounts 列出候选账号，并按账号分片路由与隔离状态过滤
 func (s *OpenAIGatewayService) listSchedulableAccounts(ctx context.Context, groupID *int64) ([]Account, error) {
+	accounts, err := s.listAllSchedulableAccounts(ctx, groupID)
+	if err != nil {
+		return nil, err
+	}
+	return s.accountShards.FilterAccounts(ctx, groupID, accounts), nil
+}
+
+func (s *OpenAIGatewayService) listAllSchedulableAccounts(ctx context.Context, groupID *int64) ([]Account, error) {
 	if s.schedulerSnapshot != nil {
 		accounts, _, err := s.schedulerSnapshot.ListSchedulableAccounts(ctx, groupID, PlatformOpenAI, false)
 		return accounts, err
@@ -925,6 +981,9 @@ func (s *OpenAIGatewayService) tryAcquireAccountSlot(ctx context.Context, accoun
 }
 
 func (s *OpenAIGatewayService) getSchedulableAccount(ctx context.Context, accountID int64) (*Account, error) {
+	if !s.accountShards.AllowsAccount(ctx, accountID) {
+		return nil, ErrAccountShardExcluded
+	}
 	if s.schedulerSnapshot != nil {
 		return s.schedulerSnapshot.GetAccount(ctx, accountID)
 	}
@@ -991,6 +1050,15 @@ func (s *OpenAIGatewayService) handleFailoverSideEffects(ctx context.Context, re
 // Forward forwards request to OpenAI API
 func (s *OpenAIGatewayService) Forward(ctx context.Context, c *gin.Context, account *Account, body []byte) (*OpenAIForwardResult, error) {
 	startTime := time.Now()
+	if account.IsChatGPTWebSession() {
+		return s.forwardChatGPTWeb(ctx, c, account, body, startTime)
+	}
+	if account.IsCopilot() {
+		return s.forwardCopilot(ctx, c, account, body, startTime)
+	}
+	if account.IsOpenAICompatible() {
+		return s.forwardOpenAICompatible(ctx, c, account, body, startTime)
+	}
 
 	restrictionResult := s.detectCodexClientRestriction(c, account)
 	apiKeyID := getAPIKeyIDFromContext(c)
@@ -1066,6 +1134,32 @@ func (s *OpenAIGatewayService) Forward(ctx context.Context, c *gin.Context, acco
 		}
 	}
 
+	// 跨格式推理参数转换（reasoning_effort / thinking → reasoning.effort），并按 API Key 策略处理推理摘要。
+	policy := APIKeyPolicyFromContext(ctx)
+	if applyOpenAIReasoningTranslation(reqBody, policy.ReasoningMode) {
+		bodyModified = true
+	}
+
+	// 按 API Key / 分组策略注入系统提示词模板（在默认 instructions 注入之后，避免被覆盖）
+	if applyOpenAISystemPrompt(reqBody, policy.SystemPrompt, account.Type != AccountTypeOAuth) {
+		bodyModified = true
+	}
+
+	// 按 API Key / 分组策略附加 metadata（仅 OpenAI API Key 账号；Codex 上游不接受自定义 metadata）
+	if account.Type == AccountTypeAPIKey && account.Platform == PlatformOpenAI && applyUpstreamMetadata(reqBody, policy.UpstreamTags) {
+		bodyModified = true
+	}
+
+	// 按上游能力规范化采样参数；Codex（OAuth）上游不接受任何采样参数
+	samplingCaps := samplingCapsOpenAIResponses
+	if account.Type == AccountTypeOAuth {
+		samplingCaps = samplingCapsCodex
+	}
+	if adjusted := normalizeOpenAISamplingParams(reqBody, samplingCaps); len(adjusted) > 0 {
+		setSamplingAdjustedHeader(c, adjusted)
+		bodyModified = true
+	}
+
 	// 规范化 reasoning.effort 参数（minimal -> none），与上游允许值对齐。
 	if reasoning, ok := reqBody["reasoning"].(map[string]any); ok {
 		if effort, ok := reasoning["effort"].(string); ok && effort == "minimal" {
@@ -1180,12 +1274,7 @@ func (s *OpenAIGatewayService) Forward(ctx context.Context, c *gin.Context, acco
 			Kind:               "request_error",
 			Message:            safeErr,
 		})
-		c.JSON(http.StatusBadGateway, gin.H{
-			"error": gin.H{
-				"type":    "upstream_error",
-				"message": "Upstream request failed",
-			},
-		})
+		writeUpstreamRequestError(c, UpstreamErrorFormatOpenAI, err)
 		return nil, fmt.Errorf("upstream request failed: %s", safeErr)
 	}
 	defer func() { _ = resp.Body.Close() }()
@@ -1368,12 +1457,7 @@ func (s *OpenAIGatewayService) forwardOpenAIPassthrough(
 			Kind:               "request_error",
 			Message:            safeErr,
 		})
-		c.JSON(http.StatusBadGateway, gin.H{
-			"error": gin.H{
-				"type":    "upstream_error",
-				"message": "Upstream request failed",
-			},
-		})
+		writeUpstreamRequestError(c, UpstreamErrorFormatOpenAI, err)
 		return nil, fmt.Errorf("upstream request failed: %s", safeErr)
 	}
 	defer func() { _ = resp.Body.Close() }()
@@ -1766,12 +1850,16 @@ func (s *OpenAIGatewayService) handleNonStreamingResponsePassthrough(
 				InputTokenDetails struct {
 					CachedTokens int `json:"cached_tokens"`
 				} `json:"input_tokens_details"`
+				OutputTokenDetails struct {
+					ReasoningTokens int `json:"reasoning_tokens"`
+				} `json:"output_tokens_details"`
 			} `json:"usage"`
 		}
 		if json.Unmarshal(body, &response) == nil {
 			usage.InputTokens = response.Usage.InputTokens
 			usage.OutputTokens = response.Usage.OutputTokens
 			usage.CacheReadInputTokens = response.Usage.InputTokenDetails.CachedTokens
+			usage.ReasoningTokens = response.Usage.OutputTokenDetails.ReasoningTokens
 			usageParsed = true
 		}
 	}
@@ -2027,39 +2115,12 @@ func (s *OpenAIGatewayService) handleErrorResponse(
 		return nil, &UpstreamFailoverError{StatusCode: resp.StatusCode, ResponseBody: body}
 	}
 
-	// Return appropriate error response
-	var errType, errMsg string
-	var statusCode int
-
-	switch resp.StatusCode {
-	case 401:
-		statusCode = http.StatusBadGateway
-		errType = "upstream_error"
-		errMsg = "Upstream authentication failed, please contact administrator"
-	case 402:
-		statusCode = http.StatusBadGateway
-		errType = "upstream_error"
-		errMsg = "Upstream payment required: insufficient balance or billing issue"
-	case 403:
-		statusCode = http.StatusBadGateway
-		errType = "upstream_error"
-		errMsg = "Upstream access forbidden, please contact administrator"
-	case 429:
-		statusCode = http.StatusTooManyRequests
-		errType = "rate_limit_error"
-		errMsg = "Upstream rate limit exceeded, please retry later"
-	default:
-		statusCode = http.StatusBadGateway
-		errType = "upstream_error"
-		errMsg = "Upstream request failed"
-	}
-
-	c.JSON(statusCode, gin.H{
-		"error": gin.H{
-			"type":    errType,
-			"message": errMsg,
-		},
-	})
+	// Return error response mapped through the unified upstream error taxonomy
+	class := ClassifyUpstreamError(resp.StatusCode, body)
+	mapped := MapUpstreamError(class, UpstreamErrorFormatOpenAI)
+	setAccountRetryAfterHeaders(ctx, c, s.accountRepo, mapped.Status, account.ID)
+	writeUpstreamError(c, UpstreamErrorFormatOpenAI, mapped)
+	s.liveUsage.publishUpstreamError(ctx, c, account, mapped.Status, class)
 
 	if upstreamMsg == "" {
 		return nil, fmt.Errorf("upstream error: %d", resp.StatusCode)
@@ -2152,28 +2213,24 @@ func (s *OpenAIGatewayService) handleStreamingResponse(ctx context.Context, resp
 		intervalCh = intervalTicker.C
 	}
 
-	keepaliveInterval := time.Duration(0)
-	if s.cfg != nil && s.cfg.Gateway.StreamKeepaliveInterval > 0 {
-		keepaliveInterval = time.Duration(s.cfg.Gateway.StreamKeepaliveInterval) * time.Second
-	}
 	// 下游 keepalive 仅用于防止代理空闲断开
-	var keepaliveTicker *time.Ticker
-	if keepaliveInterval > 0 {
-		keepaliveTicker = time.NewTicker(keepaliveInterval)
-		defer keepaliveTicker.Stop()
-	}
-	var keepaliveCh <-chan time.Time
-	if keepaliveTicker != nil {
-		keepaliveCh = keepaliveTicker.C
-	}
-	// 记录上次收到上游数据的时间，用于控制 keepalive 发送频率
-	lastDataAt := time.Now()
+	keepalive := newSSEKeepalive(s.cfg)
+	defer keepalive.Stop()
 
 	// 仅发送一次错误事件，避免多次写入导致协议混乱。
 	// 注意：OpenAI `/v1/responses` streaming 事件必须符合 OpenAI Responses schema；
 	// 否则下游 SDK（例如 OpenCode）会因为类型校验失败而报错。
 	errorEventSent := false
-	clientDisconnected := false // 客户端断开后继续 drain 上游以收集 usage
+	clientDisconnected := false // 客户端断开后未启用断开即取消上游时继续 drain 上游以收集 usage
+	cancelOnDisconnect := s.cfg != nil && s.cfg.Gateway.CancelUpstreamOnClientDisconnect
+	// streamedOutputTokens 已输出内容的估算 token 数，上游未返回最终 usage 时用于计费
+	streamedOutputTokens := 0
+	disconnectResult := func() *openaiStreamingResult {
+		if usage.OutputTokens == 0 {
+			usage.OutputTokens = streamedOutputTokens
+		}
+		return &openaiStreamingResult{usage: usage, firstTokenMs: firstTokenMs}
+	}
 	sendErrorEvent := func(reason string) {
 		if errorEventSent || clientDisconnected {
 			return
@@ -2207,27 +2264,35 @@ func (s *OpenAIGatewayService) handleStreamingResponse(ctx context.Context, resp
 				// /v1/responses 的 SSE 事件必须符合 OpenAI 协议；这里不注入自定义 error event，避免下游 SDK 解析失败。
 				if errors.Is(ev.err, context.Canceled) || errors.Is(ev.err, context.DeadlineExceeded) {
 					logger.LegacyPrintf("service.openai_gateway", "Context canceled during streaming, returning collected usage")
-					return &openaiStreamingResult{usage: usage, firstTokenMs: firstTokenMs}, nil
+					return disconnectResult(), nil
 				}
 				// 客户端已断开时，上游出错仅影响体验，不影响计费；返回已收集 usage
 				if clientDisconnected {
 					logger.LegacyPrintf("service.openai_gateway", "Upstream read error after client disconnect: %v, returning collected usage", ev.err)
-					return &openaiStreamingResult{usage: usage, firstTokenMs: firstTokenMs}, nil
+					return disconnectResult(), nil
 				}
 				if errors.Is(ev.err, bufio.ErrTooLong) {
 					logger.LegacyPrintf("service.openai_gateway", "SSE line too long: account=%d max_size=%d error=%v", account.ID, maxLineSize, ev.err)
 					sendErrorEvent("response_too_large")
 					return &openaiStreamingResult{usage: usage, firstTokenMs: firstTokenMs}, ev.err
 				}
+				if timeoutErr, ok := AsUpstreamTimeoutError(ev.err); ok {
+					sendErrorEvent(timeoutErr.Code())
+					return &openaiStreamingResult{usage: usage, firstTokenMs: firstTokenMs}, timeoutErr
+				}
 				sendErrorEvent("stream_read_error")
 				return &openaiStreamingResult{usage: usage, firstTokenMs: firstTokenMs}, fmt.Errorf("stream read error: %w", ev.err)
 			}
 
 			line := ev.line
-			lastDataAt = time.Now()
 
 			// Extract data from SSE line (supports both "data: " and "data:" formats)
 			if data, ok := extractOpenAISSEDataLine(line); ok {
+				// 函数调用参数不是合法 JSON（被截断等）时原样转发，并将调用项标记为 incomplete
+				if marked, changed := markInvalidToolCallArguments(data); changed {
+					data = marked
+					line = "data: " + marked
+				}
 
 				// Replace model in response if needed
 				if needModelReplace {
@@ -2244,9 +2309,14 @@ func (s *OpenAIGatewayService) handleStreamingResponse(ctx context.Context, resp
 				if !clientDisconnected {
 					if _, err := fmt.Fprintf(w, "%s\n", line); err != nil {
 						clientDisconnected = true
+						if cancelOnDisconnect {
+							logger.LegacyPrintf("service.openai_gateway", "Client disconnected during streaming, canceling upstream: account=%d", account.ID)
+							return disconnectResult(), nil
+						}
 						logger.LegacyPrintf("service.openai_gateway", "Client disconnected during streaming, continuing to drain upstream for billing")
 					} else {
 						flusher.Flush()
+						keepalive.TouchLine(line)
 					}
 				}
 
@@ -2256,14 +2326,20 @@ func (s *OpenAIGatewayService) handleStreamingResponse(ctx context.Context, resp
 					firstTokenMs = &ms
 				}
 				s.parseSSEUsage(data, usage)
+				streamedOutputTokens += estimateOpenAIStreamDeltaTokens(data)
 			} else {
 				// Forward non-data lines as-is
 				if !clientDisconnected {
 					if _, err := fmt.Fprintf(w, "%s\n", line); err != nil {
 						clientDisconnected = true
+						if cancelOnDisconnect {
+							logger.LegacyPrintf("service.openai_gateway", "Client disconnected during streaming, canceling upstream: account=%d", account.ID)
+							return disconnectResult(), nil
+						}
 						logger.LegacyPrintf("service.openai_gateway", "Client disconnected during streaming, continuing to drain upstream for billing")
 					} else {
 						flusher.Flush()
+						keepalive.TouchLine(line)
 					}
 				}
 			}
@@ -2285,24 +2361,41 @@ func (s *OpenAIGatewayService) handleStreamingResponse(ctx context.Context, resp
 			sendErrorEvent("stream_timeout")
 			return &openaiStreamingResult{usage: usage, firstTokenMs: firstTokenMs}, fmt.Errorf("stream data interval timeout")
 
-		case <-keepaliveCh:
-			if clientDisconnected {
-				continue
-			}
-			if time.Since(lastDataAt) < keepaliveInterval {
+		case <-keepalive.C():
+			if clientDisconnected || !keepalive.Due() {
 				continue
 			}
-			if _, err := fmt.Fprint(w, ":\n\n"); err != nil {
+			if _, err := fmt.Fprint(w, sseKeepalivePing); err != nil {
 				clientDisconnected = true
+				if cancelOnDisconnect {
+					logger.LegacyPrintf("service.openai_gateway", "Client disconnected during streaming, canceling upstream: account=%d", account.ID)
+					return disconnectResult(), nil
+				}
 				logger.LegacyPrintf("service.openai_gateway", "Client disconnected during streaming, continuing to drain upstream for billing")
 				continue
 			}
 			flusher.Flush()
+			keepalive.Touch()
 		}
 	}
 
 }
 
+// estimateOpenAIStreamDeltaTokens 估算 Responses API `*.delta` 事件输出内容的 token 数
+func estimateOpenAIStreamDeltaTokens(data string) int {
+	if !strings.Contains(data, `.delta"`) {
+		return 0
+	}
+	if !strings.HasSuffix(gjson.Get(data, "type").String(), ".delta") {
+		return 0
+	}
+	delta := gjson.Get(data, "delta")
+	if delta.Type != gjson.String {
+		return 0
+	}
+	return claude.EstimateTextTokens(delta.String())
+}
+
 // extractOpenAISSEDataLine 低开销提取 SSE `data:` 行内容。
 // 兼容 `data: xxx` 与 `data:xxx` 两种格式。
 func extractOpenAISSEDataLine(line string) (string, bool) {
@@ -2378,6 +2471,7 @@ func (s *OpenAIGatewayService) parseSSEUsage(data string, usage *OpenAIUsage) {
 	usage.InputTokens = int(gjson.Get(data, "response.usage.input_tokens").Int())
 	usage.OutputTokens = int(gjson.Get(data, "response.usage.output_tokens").Int())
 	usage.CacheReadInputTokens = int(gjson.Get(data, "response.usage.input_tokens_details.cached_tokens").Int())
+	usage.ReasoningTokens = int(gjson.Get(data, "response.usage.output_tokens_details.reasoning_tokens").Int())
 }
 
 func (s *OpenAIGatewayService) handleNonStreamingResponse(ctx context.Context, resp *http.Response, c *gin.Context, account *Account, originalModel, mappedModel string) (*OpenAIUsage, error) {
@@ -2411,6 +2505,9 @@ func (s *OpenAIGatewayService) handleNonStreamingResponse(ctx context.Context, r
 			InputTokenDetails struct {
 				CachedTokens int `json:"cached_tokens"`
 			} `json:"input_tokens_details"`
+			OutputTokenDetails struct {
+				ReasoningTokens int `json:"reasoning_tokens"`
+			} `json:"output_tokens_details"`
 		} `json:"usage"`
 	}
 	if err := json.Unmarshal(body, &response); err != nil {
@@ -2421,6 +2518,7 @@ func (s *OpenAIGatewayService) handleNonStreamingResponse(ctx context.Context, r
 		InputTokens:          response.Usage.InputTokens,
 		OutputTokens:         response.Usage.OutputTokens,
 		CacheReadInputTokens: response.Usage.InputTokenDetails.CachedTokens,
+		ReasoningTokens:      response.Usage.OutputTokenDetails.ReasoningTokens,
 	}
 
 	// Replace model in response if needed
@@ -2460,12 +2558,16 @@ func (s *OpenAIGatewayService) handleOAuthSSEToJSON(resp *http.Response, c *gin.
 				InputTokenDetails struct {
 					CachedTokens int `json:"cached_tokens"`
 				} `json:"input_tokens_details"`
+				OutputTokenDetails struct {
+					ReasoningTokens int `json:"reasoning_tokens"`
+				} `json:"output_tokens_details"`
 			} `json:"usage"`
 		}
 		if err := json.Unmarshal(finalResponse, &response); err == nil {
 			usage.InputTokens = response.Usage.InputTokens
 			usage.OutputTokens = response.Usage.OutputTokens
 			usage.CacheReadInputTokens = response.Usage.InputTokenDetails.CachedTokens
+			usage.ReasoningTokens = response.Usage.OutputTokenDetails.ReasoningTokens
 		}
 		body = finalResponse
 		if originalModel != mappedModel {
@@ -2596,24 +2698,31 @@ func (s *OpenAIGatewayService) replaceModelInResponseBody(body []byte, fromModel
 
 // OpenAIRecordUsageInput input for recording usage
 type OpenAIRecordUsageInput struct {
-	Result        *OpenAIForwardResult
-	APIKey        *APIKey
-	User          *User
-	Account       *Account
-	Subscription  *UserSubscription
-	UserAgent     string // 请求的 User-Agent
-	IPAddress     string // 请求的客户端 IP 地址
-	APIKeyService APIKeyQuotaUpdater
+	Result         *OpenAIForwardResult
+	APIKey         *APIKey
+	User           *User
+	Account        *Account
+	Subscription   *UserSubscription
+	UserAgent      string // 请求的 User-Agent
+	IPAddress      string // 请求的客户端 IP 地址
+	PromptFirewall string // 提示词防火墙处理结果摘要（未命中为空）
+	APIKeyService  APIKeyQuotaUpdater
+	CreditHold     *CreditHold // 处理器认领的额度预占，扣费后释放
 }
 
 // RecordUsage records usage and deducts balance
 func (s *OpenAIGatewayService) RecordUsage(ctx context.Context, input *OpenAIRecordUsageInput) error {
+	// 余额与配额扣减写入后释放预占（提前返回时同样释放）
+	defer input.CreditHold.Settle(ctx)
+
 	result := input.Result
 	apiKey := input.APIKey
 	user := input.User
 	account := input.Account
 	subscription := input.Subscription
 
+	s.latencyTracker.Record(account.ID, result.FirstTokenMs, result.Duration)
+
 	// 计算实际的新输入token（减去缓存读取的token）
 	// 因为 input_tokens 包含了 cache_read_tokens，而缓存读取的token不应按输入价格计费
 	actualInputTokens := result.Usage.InputTokens - result.Usage.CacheReadInputTokens
@@ -2628,6 +2737,9 @@ func (s *OpenAIGatewayService) RecordUsage(ctx context.Context, input *OpenAIRec
 		CacheCreationTokens: result.Usage.CacheCreationInputTokens,
 		CacheReadTokens:     result.Usage.CacheReadInputTokens,
 	}
+	if result.UsageEstimated {
+		tokens = s.billingService.ReconcileEstimatedUsage(tokens)
+	}
 
 	// Get rate multiplier
 	multiplier := s.cfg.Default.RateMultiplier
@@ -2650,6 +2762,7 @@ func (s *OpenAIGatewayService) RecordUsage(ctx context.Context, input *OpenAIRec
 	// Create usage log
 	durationMs := int(result.Duration.Milliseconds())
 	accountRateMultiplier := account.BillingRateMultiplier()
+	systemFingerprint := SystemFingerprint(account, result.Model)
 	usageLog := &UsageLog{
 		UserID:                user.ID,
 		APIKeyID:              apiKey.ID,
@@ -2661,6 +2774,7 @@ func (s *OpenAIGatewayService) RecordUsage(ctx context.Context, input *OpenAIRec
 		OutputTokens:          result.Usage.OutputTokens,
 		CacheCreationTokens:   result.Usage.CacheCreationInputTokens,
 		CacheReadTokens:       result.Usage.CacheReadInputTokens,
+		ReasoningTokens:       result.Usage.ReasoningTokens,
 		InputCost:             cost.InputCost,
 		OutputCost:            cost.OutputCost,
 		CacheCreationCost:     cost.CacheCreationCost,
@@ -2673,6 +2787,8 @@ func (s *OpenAIGatewayService) RecordUsage(ctx context.Context, input *OpenAIRec
 		Stream:                result.Stream,
 		DurationMs:            &durationMs,
 		FirstTokenMs:          result.FirstTokenMs,
+		SystemFingerprint:     &systemFingerprint,
+		UsageEstimated:        result.UsageEstimated,
 		CreatedAt:             time.Now(),
 	}
 
@@ -2680,6 +2796,9 @@ func (s *OpenAIGatewayService) RecordUsage(ctx context.Context, input *OpenAIRec
 	if input.UserAgent != "" {
 		usageLog.UserAgent = &input.UserAgent
 	}
+	if input.PromptFirewall != "" {
+		usageLog.PromptFirewall = &input.PromptFirewall
+	}
 
 	// 添加 IPAddress
 	if input.IPAddress != "" {
@@ -2694,6 +2813,11 @@ func (s *OpenAIGatewayService) RecordUsage(ctx context.Context, input *OpenAIRec
 	}
 
 	inserted, err := s.usageLogRepo.Create(ctx, usageLog)
+	s.liveUsage.publishUsageLog(usageLog, account, apiKey)
+	if inserted {
+		s.eventExport.RecordUsage(ctx, usageLog)
+		s.usageAnalytics.Record(usageLog)
+	}
 	if s.cfg != nil && s.cfg.RunMode == config.RunModeSimple {
 		logger.LegacyPrintf("service.openai_gateway", "[SIMPLE MODE] Usage recorded (not billed): user=%d, tokens=%d", usageLog.UserID, usageLog.TotalTokens())
 		s.deferredService.ScheduleLastUsedUpdate(account.ID)
@@ -2711,7 +2835,7 @@ func (s *OpenAIGatewayService) RecordUsage(ctx context.Context, input *OpenAIRec
 	} else {
 		if shouldBill && cost.ActualCost > 0 {
 			_ = s.userRepo.DeductBalance(ctx, user.ID, cost.ActualCost)
-			s.billingCacheService.QueueDeductBalance(user.ID, cost.ActualCost)
+			deductBalanceCache(ctx, s.billingCacheService, input.CreditHold, user.ID, cost.ActualCost)
 		}
 	}
 
@@ -2722,6 +2846,11 @@ func (s *OpenAIGatewayService) RecordUsage(ctx context.Context, input *OpenAIRec
 		}
 	}
 
+	// 消费硬上限计数（余额 / 订阅模式均按实际费用累计）
+	if shouldBill {
+		s.billingCacheService.RecordSpend(ctx, apiKey, cost.ActualCost)
+	}
+
 	// Schedule batch update for account last_used_at
 	s.deferredService.ScheduleLastUsedUpdate(account.ID)
 
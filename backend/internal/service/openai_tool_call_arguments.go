@@ -0,0 +1,69 @@
+package service
+
+import (
+	"encoding/json"
+	"strconv"
+	"strings"
+
+	"github.com/Wei-Shaw/sub2api/internal/pkg/logger"
+	"github.com/tidwall/gjson"
+	"github.com/tidwall/sjson"
+)
+
+// markInvalidToolCallArguments 校验 Responses 流中函数调用的完整参数（response.output_item.done 与最终响应的 output）。
+//
+// 部分上游会截断或重复发送参数。参数不是合法 JSON 时不做修复（补齐后的参数并非模型生成，客户端会据此直接执行工具），
+// 而是原样转发并将该函数调用项的 status 置为 incomplete，由客户端决定重试或报错。
+// 返回更新后的事件；未做修改时返回 false。
+func markInvalidToolCallArguments(data string) (string, bool) {
+	if !strings.Contains(data, "function_call") || !gjson.Valid(data) {
+		return data, false
+	}
+
+	switch eventType := gjson.Get(data, "type").String(); eventType {
+	case "response.function_call_arguments.done":
+		// 该事件没有状态字段，随后的 output_item.done 会被标记
+		if arguments := gjson.Get(data, "arguments"); !validToolCallArguments(arguments.String()) {
+			logInvalidToolCallArguments(eventType, arguments.String())
+		}
+		return data, false
+	case "response.output_item.done":
+		return markInvalidFunctionCallItem(data, "item", eventType)
+	case "response.completed", "response.incomplete", "response.failed":
+		changed := false
+		for i := range gjson.Get(data, "response.output").Array() {
+			var marked bool
+			data, marked = markInvalidFunctionCallItem(data, "response.output."+strconv.Itoa(i), eventType)
+			changed = changed || marked
+		}
+		return data, changed
+	}
+	return data, false
+}
+
+func markInvalidFunctionCallItem(data, path, eventType string) (string, bool) {
+	item := gjson.Get(data, path)
+	if item.Get("type").String() != "function_call" {
+		return data, false
+	}
+	arguments := item.Get("arguments").String()
+	if validToolCallArguments(arguments) {
+		return data, false
+	}
+	logInvalidToolCallArguments(eventType, arguments)
+	updated, err := sjson.Set(data, path+".status", "incomplete")
+	if err != nil {
+		return data, false
+	}
+	return updated, true
+}
+
+// validToolCallArguments 空参数视为无参数调用
+func validToolCallArguments(arguments string) bool {
+	trimmed := strings.TrimSpace(arguments)
+	return trimmed == "" || json.Valid([]byte(trimmed))
+}
+
+func logInvalidToolCallArguments(eventType, arguments string) {
+	logger.LegacyPrintf("service.openai_gateway", "Tool call arguments are not valid JSON, forwarding unchanged: event=%s len=%d", eventType, len(arguments))
+}
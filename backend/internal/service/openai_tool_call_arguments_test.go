@@ -0,0 +1,68 @@
+package service
+
+import (
+	"testing"
+
+	"github.com/stretchr/testify/require"
+	"github.com/tidwall/gjson"
+)
+
+func TestValidToolCallArguments(t *testing.T) {
+	tests := []struct {
+		name  string
+		input string
+		want  bool
+	}{
+		{name: "valid", input: `{"path":"a.go"}`, want: true},
+		{name: "empty", input: "  ", want: true},
+		{name: "duplicated", input: `{"a":1}{"a":1}`, want: false},
+		{name: "truncated string", input: `{"path":"/etc/pa`, want: false},
+		{name: "truncated nested", input: `{"items":[1,2,`, want: false},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			require.Equal(t, tt.want, validToolCallArguments(tt.input))
+		})
+	}
+}
+
+func TestMarkInvalidToolCallArguments_DoesNotRewriteTruncatedArguments(t *testing.T) {
+	truncated := `{"path":"/etc/pa`
+
+	data := `{"type":"response.output_item.done","output_index":0,"item":{"id":"fc_1","type":"function_call","status":"completed","name":"read","arguments":"{\"path\":\"/etc/pa"}}`
+	out, changed := markInvalidToolCallArguments(data)
+	require.True(t, changed)
+	require.Equal(t, truncated, gjson.Get(out, "item.arguments").String())
+	require.Equal(t, "incomplete", gjson.Get(out, "item.status").String())
+
+	// 参数完成事件没有状态字段，原样转发
+	done := `{"type":"response.function_call_arguments.done","item_id":"fc_1","arguments":"{\"path\":\"/etc/pa"}`
+	out, changed = markInvalidToolCallArguments(done)
+	require.False(t, changed)
+	require.Equal(t, done, out)
+
+	completed := `{"type":"response.completed","response":{"status":"completed","output":[` +
+		`{"type":"message","content":[]},` +
+		`{"type":"function_call","status":"completed","name":"ok","arguments":"{\"a\":1}"},` +
+		`{"type":"function_call","status":"completed","name":"dup","arguments":"{\"a\":1}{\"a\":1}"}]}}`
+	out, changed = markInvalidToolCallArguments(completed)
+	require.True(t, changed)
+	require.Equal(t, "completed", gjson.Get(out, "response.output.1.status").String())
+	require.Equal(t, "incomplete", gjson.Get(out, "response.output.2.status").String())
+	require.Equal(t, `{"a":1}{"a":1}`, gjson.Get(out, "response.output.2.arguments").String())
+}
+
+func TestMarkInvalidToolCallArguments_PassesThroughValidEvents(t *testing.T) {
+	for _, data := range []string{
+		`{"type":"response.output_item.done","item":{"type":"function_call","status":"completed","arguments":"{\"a\":\"b\"}"}}`,
+		`{"type":"response.output_item.done","item":{"type":"function_call","status":"completed","arguments":""}}`,
+		`{"type":"response.output_item.done","item":{"type":"message","content":[]}}`,
+		`{"type":"response.function_call_arguments.delta","delta":"{\"a"}`,
+		`{"id":"c1","choices":[{"index":0,"delta":{"tool_calls":[{"index":0,"function":{"arguments":"{\"pa"}}]}}]}`,
+		"[DONE]",
+	} {
+		out, changed := markInvalidToolCallArguments(data)
+		require.False(t, changed, data)
+		require.Equal(t, data, out)
+	}
+}
@@ -35,6 +35,8 @@ type OpsAlertEvaluatorService struct {
 	opsService   *OpsService
 	opsRepo      OpsRepository
 	emailService *EmailService
+	discord      DiscordWebhookClient
+	eventExport  *EventExportService
 
 	redisClient *redis.Client
 	cfg         *config.Config
@@ -65,6 +67,7 @@ func NewOpsAlertEvaluatorService(
 	opsService *OpsService,
 	opsRepo OpsRepository,
 	emailService *EmailService,
+	discord DiscordWebhookClient,
 	redisClient *redis.Client,
 	cfg *config.Config,
 ) *OpsAlertEvaluatorService {
@@ -72,6 +75,7 @@ func NewOpsAlertEvaluatorService(
 		opsService:   opsService,
 		opsRepo:      opsRepo,
 		emailService: emailService,
+		discord:      discord,
 		redisClient:  redisClient,
 		cfg:          cfg,
 		instanceID:   uuid.NewString(),
@@ -80,6 +84,11 @@ func NewOpsAlertEvaluatorService(
 	}
 }
 
+// SetEventExporter 告警触发 / 恢复时导出告警事件；需要在 Start() 之前调用
+func (s *OpsAlertEvaluatorService) SetEventExporter(eventExport *EventExportService) {
+	s.eventExport = eventExport
+}
+
 func (s *OpsAlertEvaluatorService) Start() {
 	if s == nil {
 		return
@@ -292,6 +301,8 @@ func (s *OpsAlertEvaluatorService) evaluateOnce(interval time.Duration) {
 				if s.maybeSendAlertEmail(ctx, runtimeCfg, rule, created) {
 					emailsSent++
 				}
+				s.maybeSendAlertDiscord(ctx, runtimeCfg, rule, created)
+				s.eventExport.RecordAlert(ctx, created)
 			}
 			continue
 		}
@@ -303,6 +314,10 @@ func (s *OpsAlertEvaluatorService) evaluateOnce(interval time.Duration) {
 				logger.LegacyPrintf("service.ops_alert_evaluator", "[OpsAlertEvaluator] resolve event failed (event=%d): %v", activeEvent.ID, err)
 			} else {
 				eventsResolved++
+				activeEvent.Status = OpsAlertStatusResolved
+				activeEvent.ResolvedAt = &resolvedAt
+				s.maybeSendAlertDiscord(ctx, runtimeCfg, rule, activeEvent)
+				s.eventExport.RecordAlert(ctx, activeEvent)
 			}
 		}
 	}
@@ -657,6 +672,42 @@ func (s *OpsAlertEvaluatorService) maybeSendAlertEmail(ctx context.Context, runt
 	return anySent
 }
 
+// maybeSendAlertDiscord 推送告警触发/恢复到 Discord；失败只记录日志，不影响评估流程
+func (s *OpsAlertEvaluatorService) maybeSendAlertDiscord(ctx context.Context, runtimeCfg *OpsAlertRuntimeSettings, rule *OpsAlertRule, event *OpsAlertEvent) bool {
+	if s == nil || s.discord == nil || s.opsService == nil || event == nil || rule == nil {
+		return false
+	}
+	if !rule.NotifyDiscord {
+		return false
+	}
+
+	discordCfg, err := s.opsService.getDiscordNotificationConfig(ctx)
+	if err != nil || discordCfg == nil || !discordCfg.Enabled || discordCfg.WebhookURL == "" {
+		return false
+	}
+	resolved := event.Status != OpsAlertStatusFiring
+	if resolved && !discordCfg.IncludeResolvedAlerts {
+		return false
+	}
+	if !shouldSendOpsAlertEmailByMinSeverity(strings.TrimSpace(discordCfg.MinSeverity), strings.TrimSpace(rule.Severity)) {
+		return false
+	}
+	if runtimeCfg != nil && runtimeCfg.Silencing.Enabled {
+		if isOpsAlertSilenced(time.Now().UTC(), rule, event, runtimeCfg.Silencing) {
+			return false
+		}
+	}
+
+	// 仅在 critical 告警触发时提及角色，恢复消息不打扰
+	mention := !resolved && opsEmailSeverityForOps(rule.Severity) == "critical"
+	msg := newOpsDiscordMessage(discordCfg, mention, buildOpsAlertDiscordEmbed(rule, event))
+	if err := s.discord.Execute(ctx, discordCfg.WebhookURL, msg); err != nil {
+		logger.LegacyPrintf("service.ops_alert_evaluator", "[OpsAlertEvaluator] discord notify failed (rule=%d event=%d): %v", rule.ID, event.ID, err)
+		return false
+	}
+	return true
+}
+
 func buildOpsAlertEmailBody(rule *OpsAlertRule, event *OpsAlertEvent) string {
 	if rule == nil || event == nil {
 		return ""
@@ -394,3 +394,30 @@ func (s *OpsService) GetUserConcurrencyStats(ctx context.Context) (map[int64]*Us
 
 	return result, &collectedAt, nil
 }
+
+// GetFairShareStats returns per-user fair-share scheduling stats for account wait queues (this instance only).
+func (s *OpsService) GetFairShareStats(ctx context.Context) ([]FairShareTenantStats, error) {
+	if err := s.RequireMonitoringEnabled(ctx); err != nil {
+		return nil, err
+	}
+	if s.concurrencyService == nil {
+		return []FairShareTenantStats{}, nil
+	}
+	return s.concurrencyService.FairShareStats(), nil
+}
+
+// GetUpstreamPoolStats returns upstream HTTP client cache and connection stats of this instance.
+// ok=false when the HTTPUpstream implementation does not expose pool stats.
+func (s *OpsService) GetUpstreamPoolStats(ctx context.Context) (UpstreamPoolStats, bool, error) {
+	if err := s.RequireMonitoringEnabled(ctx); err != nil {
+		return UpstreamPoolStats{}, false, err
+	}
+	if s.gatewayService == nil {
+		return UpstreamPoolStats{}, false, nil
+	}
+	provider, ok := s.gatewayService.httpUpstream.(UpstreamPoolStatsProvider)
+	if !ok {
+		return UpstreamPoolStats{}, false, nil
+	}
+	return provider.PoolStats(), true, nil
+}
@@ -0,0 +1,166 @@
+package service
+
+import (
+	"context"
+	"errors"
+	"fmt"
+	"net/url"
+	"strings"
+	"time"
+)
+
+// Discord embed 颜色
+const (
+	discordColorCritical = 0xE74C3C
+	discordColorWarning  = 0xF39C12
+	discordColorInfo     = 0x3498DB
+	discordColorResolved = 0x2ECC71
+)
+
+// DiscordEmbedField embed 中的键值字段
+type DiscordEmbedField struct {
+	Name   string `json:"name"`
+	Value  string `json:"value"`
+	Inline bool   `json:"inline,omitempty"`
+}
+
+// DiscordEmbedFooter embed 页脚
+type DiscordEmbedFooter struct {
+	Text string `json:"text"`
+}
+
+// DiscordEmbed Discord 富文本消息
+type DiscordEmbed struct {
+	Title       string              `json:"title,omitempty"`
+	Description string              `json:"description,omitempty"`
+	Color       int                 `json:"color,omitempty"`
+	Timestamp   string              `json:"timestamp,omitempty"`
+	Fields      []DiscordEmbedField `json:"fields,omitempty"`
+	Footer      *DiscordEmbedFooter `json:"footer,omitempty"`
+}
+
+// DiscordAllowedMentions 限制消息可以提及的对象，避免规则名称等内容中的 @everyone 生效
+type DiscordAllowedMentions struct {
+	Parse []string `json:"parse"`
+	Roles []string `json:"roles,omitempty"`
+}
+
+// DiscordWebhookMessage Webhook 请求体
+type DiscordWebhookMessage struct {
+	Content         string                  `json:"content,omitempty"`
+	Username        string                  `json:"username,omitempty"`
+	Embeds          []DiscordEmbed          `json:"embeds"`
+	AllowedMentions *DiscordAllowedMentions `json:"allowed_mentions"`
+}
+
+// DiscordWebhookClient 调用 Discord Webhook
+type DiscordWebhookClient interface {
+	Execute(ctx context.Context, webhookURL string, msg *DiscordWebhookMessage) error
+}
+
+var discordWebhookHosts = map[string]bool{
+	"discord.com":        true,
+	"discordapp.com":     true,
+	"ptb.discord.com":    true,
+	"canary.discord.com": true,
+}
+
+// validateDiscordWebhookURL 只允许 Discord 官方域名下的 Webhook 地址，避免被用来请求任意地址
+func validateDiscordWebhookURL(raw string) error {
+	u, err := url.Parse(raw)
+	if err != nil || u.Scheme != "https" || !discordWebhookHosts[strings.ToLower(u.Hostname())] ||
+		u.Port() != "" || u.User != nil || !strings.HasPrefix(u.Path, "/api/webhooks/") {
+		return errors.New("webhook_url must be a Discord webhook URL (https://discord.com/api/webhooks/...)")
+	}
+	return nil
+}
+
+// newOpsDiscordMessage 按配置填充用户名与允许提及的角色
+func newOpsDiscordMessage(cfg *OpsDiscordNotificationConfig, mention bool, embed DiscordEmbed) *DiscordWebhookMessage {
+	msg := &DiscordWebhookMessage{
+		Username:        cfg.Username,
+		Embeds:          []DiscordEmbed{embed},
+		AllowedMentions: &DiscordAllowedMentions{Parse: []string{}},
+	}
+	if mention && cfg.MentionRoleID != "" {
+		msg.Content = "<@&" + cfg.MentionRoleID + ">"
+		msg.AllowedMentions.Roles = []string{cfg.MentionRoleID}
+	}
+	return msg
+}
+
+func buildOpsAlertDiscordEmbed(rule *OpsAlertRule, event *OpsAlertEvent) DiscordEmbed {
+	value := "-"
+	threshold := fmt.Sprintf("%.2f", rule.Threshold)
+	if event.MetricValue != nil {
+		value = fmt.Sprintf("%.2f", *event.MetricValue)
+	}
+	if event.ThresholdValue != nil {
+		threshold = fmt.Sprintf("%.2f", *event.ThresholdValue)
+	}
+
+	embed := DiscordEmbed{
+		Title:       truncateString(fmt.Sprintf("[%s] %s", strings.TrimSpace(rule.Severity), strings.TrimSpace(rule.Name)), 256),
+		Description: truncateString(event.Description, 4096),
+		Timestamp:   event.FiredAt.UTC().Format(time.RFC3339),
+		Fields: []DiscordEmbedField{
+			{Name: "Status", Value: event.Status, Inline: true},
+			{Name: "Metric", Value: truncateString(fmt.Sprintf("%s %s %s", rule.MetricType, rule.Operator, threshold), 1024), Inline: true},
+			{Name: "Value", Value: value, Inline: true},
+		},
+		Footer: &DiscordEmbedFooter{Text: fmt.Sprintf("rule #%d · event #%d", rule.ID, event.ID)},
+	}
+	switch {
+	case event.Status != OpsAlertStatusFiring:
+		embed.Title = truncateString("Resolved: "+embed.Title, 256)
+		embed.Color = discordColorResolved
+		if event.ResolvedAt != nil {
+			embed.Timestamp = event.ResolvedAt.UTC().Format(time.RFC3339)
+		}
+	case opsEmailSeverityForOps(rule.Severity) == "critical":
+		embed.Color = discordColorCritical
+	case opsEmailSeverityForOps(rule.Severity) == "warning":
+		embed.Color = discordColorWarning
+	default:
+		embed.Color = discordColorInfo
+	}
+	return embed
+}
+
+func buildOpsSummaryDiscordEmbed(title string, start, end time.Time, overview *OpsDashboardOverview) DiscordEmbed {
+	embed := DiscordEmbed{
+		Title:     title,
+		Color:     discordColorInfo,
+		Timestamp: end.UTC().Format(time.RFC3339),
+		Footer:    &DiscordEmbedFooter{Text: fmt.Sprintf("%s ~ %s (UTC)", start.UTC().Format("2006-01-02 15:04"), end.UTC().Format("2006-01-02 15:04"))},
+	}
+	if overview == nil {
+		embed.Description = "No data."
+		return embed
+	}
+
+	latency := "-"
+	if overview.Duration.P50 != nil && overview.Duration.P99 != nil {
+		latency = fmt.Sprintf("p50 %dms / p99 %dms", *overview.Duration.P50, *overview.Duration.P99)
+	}
+	ttft := "-"
+	if overview.TTFT.P50 != nil && overview.TTFT.P99 != nil {
+		ttft = fmt.Sprintf("p50 %dms / p99 %dms", *overview.TTFT.P50, *overview.TTFT.P99)
+	}
+	embed.Fields = []DiscordEmbedField{
+		{Name: "Requests", Value: fmt.Sprintf("%d", overview.RequestCountTotal), Inline: true},
+		{Name: "Success", Value: fmt.Sprintf("%d", overview.SuccessCount), Inline: true},
+		{Name: "Errors (SLA)", Value: fmt.Sprintf("%d", overview.ErrorCountSLA), Inline: true},
+		{Name: "SLA", Value: fmt.Sprintf("%.2f%%", overview.SLA*100), Inline: true},
+		{Name: "Error rate", Value: fmt.Sprintf("%.2f%%", overview.ErrorRate*100), Inline: true},
+		{Name: "Upstream 429 / 529", Value: fmt.Sprintf("%d / %d", overview.Upstream429Count, overview.Upstream529Count), Inline: true},
+		{Name: "Tokens", Value: fmt.Sprintf("%d", overview.TokenConsumed), Inline: true},
+		{Name: "Peak QPS / TPS", Value: fmt.Sprintf("%.1f / %.1f", overview.QPS.Peak, overview.TPS.Peak), Inline: true},
+		{Name: "Latency", Value: latency, Inline: true},
+		{Name: "TTFT", Value: ttft, Inline: true},
+	}
+	if overview.SLA < 0.99 && overview.RequestCountTotal > 0 {
+		embed.Color = discordColorWarning
+	}
+	return embed
+}
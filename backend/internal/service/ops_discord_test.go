@@ -0,0 +1,120 @@
+//go:build unit
+
+package service
+
+import (
+	"context"
+	"testing"
+	"time"
+
+	"github.com/stretchr/testify/require"
+)
+
+type discordWebhookClientStub struct {
+	urls []string
+	sent []*DiscordWebhookMessage
+}
+
+func (c *discordWebhookClientStub) Execute(_ context.Context, webhookURL string, msg *DiscordWebhookMessage) error {
+	c.urls = append(c.urls, webhookURL)
+	c.sent = append(c.sent, msg)
+	return nil
+}
+
+const testOpsDiscordWebhook = "https://discord.com/api/webhooks/1/token"
+
+func TestValidateDiscordWebhookURL(t *testing.T) {
+	require.NoError(t, validateDiscordWebhookURL(testOpsDiscordWebhook))
+	require.NoError(t, validateDiscordWebhookURL("https://canary.discord.com/api/webhooks/1/token"))
+
+	for _, raw := range []string{
+		"http://discord.com/api/webhooks/1/token",
+		"https://discord.com.evil.test/api/webhooks/1/token",
+		"https://discord.com:8443/api/webhooks/1/token",
+		"https://user@discord.com/api/webhooks/1/token",
+		"https://discord.com/api/users/@me",
+		"not a url",
+	} {
+		require.Error(t, validateDiscordWebhookURL(raw), raw)
+	}
+}
+
+func TestOpsService_DiscordConfigKeepsWebhookAndMasks(t *testing.T) {
+	ctx := context.Background()
+	svc := &OpsService{settingRepo: newRuntimeSettingRepoStub()}
+
+	updated, err := svc.UpdateDiscordNotificationConfig(ctx, &OpsDiscordNotificationConfigUpdateRequest{
+		OpsDiscordNotificationConfig: OpsDiscordNotificationConfig{Enabled: true, WebhookURL: testOpsDiscordWebhook, MentionRoleID: "42"},
+	})
+	require.NoError(t, err)
+	require.Empty(t, updated.WebhookURL)
+	require.True(t, updated.WebhookConfigured)
+
+	// 留空的 webhook_url 表示沿用已保存的地址
+	_, err = svc.UpdateDiscordNotificationConfig(ctx, &OpsDiscordNotificationConfigUpdateRequest{
+		OpsDiscordNotificationConfig: OpsDiscordNotificationConfig{Enabled: true, MinSeverity: "warning"},
+	})
+	require.NoError(t, err)
+	stored, err := svc.getDiscordNotificationConfig(ctx)
+	require.NoError(t, err)
+	require.Equal(t, testOpsDiscordWebhook, stored.WebhookURL)
+	require.Equal(t, "warning", stored.MinSeverity)
+
+	_, err = svc.UpdateDiscordNotificationConfig(ctx, &OpsDiscordNotificationConfigUpdateRequest{
+		OpsDiscordNotificationConfig: OpsDiscordNotificationConfig{Enabled: true},
+		ClearWebhook:                 true,
+	})
+	require.ErrorContains(t, err, "webhook_url is required")
+
+	_, err = svc.UpdateDiscordNotificationConfig(ctx, &OpsDiscordNotificationConfigUpdateRequest{
+		OpsDiscordNotificationConfig: OpsDiscordNotificationConfig{MentionRoleID: "@everyone"},
+	})
+	require.ErrorContains(t, err, "mention_role_id")
+}
+
+func TestOpsAlertEvaluator_SendsDiscordAlerts(t *testing.T) {
+	ctx := context.Background()
+	opsService := &OpsService{settingRepo: newRuntimeSettingRepoStub()}
+	_, err := opsService.UpdateDiscordNotificationConfig(ctx, &OpsDiscordNotificationConfigUpdateRequest{
+		OpsDiscordNotificationConfig: OpsDiscordNotificationConfig{Enabled: true, WebhookURL: testOpsDiscordWebhook, MentionRoleID: "42"},
+	})
+	require.NoError(t, err)
+
+	client := &discordWebhookClientStub{}
+	evaluator := &OpsAlertEvaluatorService{opsService: opsService, discord: client}
+	rule := &OpsAlertRule{ID: 3, Name: "@everyone errors", Severity: "P0", MetricType: "error_rate", Operator: ">", Threshold: 5, NotifyDiscord: true}
+	event := &OpsAlertEvent{ID: 9, RuleID: 3, Status: OpsAlertStatusFiring, Description: "error rate high", MetricValue: float64Ptr(7.5), FiredAt: time.Now()}
+
+	require.True(t, evaluator.maybeSendAlertDiscord(ctx, nil, rule, event))
+	require.Equal(t, []string{testOpsDiscordWebhook}, client.urls)
+	msg := client.sent[0]
+	require.Equal(t, "<@&42>", msg.Content)
+	require.Empty(t, msg.AllowedMentions.Parse)
+	require.Equal(t, []string{"42"}, msg.AllowedMentions.Roles)
+	require.Equal(t, discordColorCritical, msg.Embeds[0].Color)
+	require.Contains(t, msg.Embeds[0].Title, "@everyone errors")
+	require.Equal(t, "7.50", msg.Embeds[0].Fields[2].Value)
+
+	// 默认不推送恢复消息
+	resolvedAt := time.Now()
+	event.Status = OpsAlertStatusResolved
+	event.ResolvedAt = &resolvedAt
+	require.False(t, evaluator.maybeSendAlertDiscord(ctx, nil, rule, event))
+
+	rule.NotifyDiscord = false
+	event.Status = OpsAlertStatusFiring
+	require.False(t, evaluator.maybeSendAlertDiscord(ctx, nil, rule, event))
+	require.Len(t, client.sent, 1)
+}
+
+func TestBuildOpsAlertDiscordEmbed_Resolved(t *testing.T) {
+	resolvedAt := time.Date(2026, 1, 2, 3, 4, 5, 0, time.UTC)
+	embed := buildOpsAlertDiscordEmbed(
+		&OpsAlertRule{ID: 1, Name: "latency", Severity: "P2", MetricType: "p99_latency_ms", Operator: ">", Threshold: 3000},
+		&OpsAlertEvent{ID: 2, Status: OpsAlertStatusResolved, ResolvedAt: &resolvedAt},
+	)
+	require.Equal(t, "Resolved: [P2] latency", embed.Title)
+	require.Equal(t, discordColorResolved, embed.Color)
+	require.Equal(t, "2026-01-02T03:04:05Z", embed.Timestamp)
+	require.Equal(t, "p99_latency_ms > 3000.00", embed.Fields[1].Value)
+}
@@ -39,6 +39,7 @@ type OpsScheduledReportService struct {
 	opsService   *OpsService
 	userService  *UserService
 	emailService *EmailService
+	discord      DiscordWebhookClient
 	redisClient  *redis.Client
 	cfg          *config.Config
 
@@ -59,6 +60,7 @@ func NewOpsScheduledReportService(
 	opsService *OpsService,
 	userService *UserService,
 	emailService *EmailService,
+	discord DiscordWebhookClient,
 	redisClient *redis.Client,
 	cfg *config.Config,
 ) *OpsScheduledReportService {
@@ -74,6 +76,7 @@ func NewOpsScheduledReportService(
 		opsService:   opsService,
 		userService:  userService,
 		emailService: emailService,
+		discord:      discord,
 		redisClient:  redisClient,
 		cfg:          cfg,
 
@@ -202,9 +205,15 @@ func (s *OpsScheduledReportService) runOnce() {
 	s.recordHeartbeatSuccess(runAt, time.Since(startedAt), result)
 }
 
+const (
+	opsScheduledReportChannelEmail   = "email"
+	opsScheduledReportChannelDiscord = "discord"
+)
+
 type opsScheduledReport struct {
 	Name       string
 	ReportType string
+	Channel    string
 	Schedule   string
 	Enabled    bool
 
@@ -227,29 +236,39 @@ func (s *OpsScheduledReportService) listScheduledReports(ctx context.Context, no
 		ctx = context.Background()
 	}
 
-	emailCfg, err := s.opsService.GetEmailNotificationConfig(ctx)
-	if err != nil || emailCfg == nil {
-		return nil
-	}
-	if !emailCfg.Report.Enabled {
-		return nil
-	}
-
-	recipients := normalizeEmails(emailCfg.Report.Recipients)
-
 	type reportDef struct {
 		enabled   bool
 		name      string
 		kind      string
+		channel   string
 		timeRange time.Duration
 		schedule  string
 	}
 
-	defs := []reportDef{
-		{enabled: emailCfg.Report.DailySummaryEnabled, name: "日报", kind: "daily_summary", timeRange: 24 * time.Hour, schedule: emailCfg.Report.DailySummarySchedule},
-		{enabled: emailCfg.Report.WeeklySummaryEnabled, name: "周报", kind: "weekly_summary", timeRange: 7 * 24 * time.Hour, schedule: emailCfg.Report.WeeklySummarySchedule},
-		{enabled: emailCfg.Report.ErrorDigestEnabled, name: "错误摘要", kind: "error_digest", timeRange: 24 * time.Hour, schedule: emailCfg.Report.ErrorDigestSchedule},
-		{enabled: emailCfg.Report.AccountHealthEnabled, name: "账号健康", kind: "account_health", timeRange: 24 * time.Hour, schedule: emailCfg.Report.AccountHealthSchedule},
+	var (
+		defs       []reportDef
+		recipients []string
+		report     OpsEmailReportConfig
+	)
+
+	emailCfg, err := s.opsService.GetEmailNotificationConfig(ctx)
+	if err == nil && emailCfg != nil && emailCfg.Report.Enabled {
+		report = emailCfg.Report
+		recipients = normalizeEmails(report.Recipients)
+		defs = append(defs,
+			reportDef{enabled: report.DailySummaryEnabled, name: "日报", kind: "daily_summary", channel: opsScheduledReportChannelEmail, timeRange: 24 * time.Hour, schedule: report.DailySummarySchedule},
+			reportDef{enabled: report.WeeklySummaryEnabled, name: "周报", kind: "weekly_summary", channel: opsScheduledReportChannelEmail, timeRange: 7 * 24 * time.Hour, schedule: report.WeeklySummarySchedule},
+			reportDef{enabled: report.ErrorDigestEnabled, name: "错误摘要", kind: "error_digest", channel: opsScheduledReportChannelEmail, timeRange: 24 * time.Hour, schedule: report.ErrorDigestSchedule},
+			reportDef{enabled: report.AccountHealthEnabled, name: "账号健康", kind: "account_health", channel: opsScheduledReportChannelEmail, timeRange: 24 * time.Hour, schedule: report.AccountHealthSchedule},
+		)
+	}
+
+	// Discord 日报独立于邮件报表开关，使用单独的 last_run 记录
+	if s.discord != nil {
+		discordCfg, err := s.opsService.getDiscordNotificationConfig(ctx)
+		if err == nil && discordCfg != nil && discordCfg.Enabled && discordCfg.WebhookURL != "" {
+			defs = append(defs, reportDef{enabled: discordCfg.DailySummaryEnabled, name: "Daily Summary", kind: "discord_daily_summary", channel: opsScheduledReportChannelDiscord, timeRange: 24 * time.Hour, schedule: discordCfg.DailySummarySchedule})
+		}
 	}
 
 	out := make([]*opsScheduledReport, 0, len(defs))
@@ -287,6 +306,7 @@ func (s *OpsScheduledReportService) listScheduledReports(ctx context.Context, no
 		out = append(out, &opsScheduledReport{
 			Name:       d.name,
 			ReportType: d.kind,
+			Channel:    d.channel,
 			Schedule:   spec,
 			Enabled:    true,
 
@@ -294,8 +314,8 @@ func (s *OpsScheduledReportService) listScheduledReports(ctx context.Context, no
 
 			Recipients: recipients,
 
-			ErrorDigestMinCount:             emailCfg.Report.ErrorDigestMinCount,
-			AccountHealthErrorRateThreshold: emailCfg.Report.AccountHealthErrorRateThreshold,
+			ErrorDigestMinCount:             report.ErrorDigestMinCount,
+			AccountHealthErrorRateThreshold: report.AccountHealthErrorRateThreshold,
 
 			LastRunAt: lastRunPtr,
 			NextRunAt: next,
@@ -316,6 +336,10 @@ func (s *OpsScheduledReportService) runReport(ctx context.Context, report *opsSc
 	// Mark as "run" up-front so a broken SMTP config doesn't spam retries every minute.
 	s.setLastRunAt(ctx, report.ReportType, now)
 
+	if report.Channel == opsScheduledReportChannelDiscord {
+		return s.runDiscordReport(ctx, report, now)
+	}
+
 	content, err := s.generateReportHTML(ctx, report, now)
 	if err != nil {
 		return 0, err
@@ -353,6 +377,54 @@ func (s *OpsScheduledReportService) runReport(ctx context.Context, report *opsSc
 	return attempts, nil
 }
 
+func (s *OpsScheduledReportService) runDiscordReport(ctx context.Context, report *opsScheduledReport, now time.Time) (int, error) {
+	if s.discord == nil {
+		return 0, nil
+	}
+	discordCfg, err := s.opsService.getDiscordNotificationConfig(ctx)
+	if err != nil {
+		return 0, err
+	}
+	if discordCfg == nil || discordCfg.WebhookURL == "" {
+		return 0, nil
+	}
+
+	end := now.UTC()
+	start := end.Add(-report.TimeRange)
+	overview, err := s.loadSummaryOverview(ctx, start, end)
+	if err != nil {
+		return 0, err
+	}
+
+	msg := newOpsDiscordMessage(discordCfg, false, buildOpsSummaryDiscordEmbed(report.Name, start, end, overview))
+	if err := s.discord.Execute(ctx, discordCfg.WebhookURL, msg); err != nil {
+		// Best-effort like email delivery: the run is already marked, so a bad webhook won't retry every minute.
+		log.Printf("[OpsScheduledReport] discord summary failed: %v", err)
+	}
+	return 1, nil
+}
+
+func (s *OpsScheduledReportService) loadSummaryOverview(ctx context.Context, start, end time.Time) (*OpsDashboardOverview, error) {
+	overview, err := s.opsService.GetDashboardOverview(ctx, &OpsDashboardFilter{
+		StartTime: start,
+		EndTime:   end,
+		Platform:  "",
+		GroupID:   nil,
+		QueryMode: OpsQueryModeAuto,
+	})
+	if err != nil {
+		// If pre-aggregation isn't ready but the report is requested, fall back to raw.
+		overview, err = s.opsService.GetDashboardOverview(ctx, &OpsDashboardFilter{
+			StartTime: start,
+			EndTime:   end,
+			Platform:  "",
+			GroupID:   nil,
+			QueryMode: OpsQueryModeRaw,
+		})
+	}
+	return overview, err
+}
+
 func (s *OpsScheduledReportService) generateReportHTML(ctx context.Context, report *opsScheduledReport, now time.Time) (string, error) {
 	if s == nil || s.opsService == nil || report == nil {
 		return "", fmt.Errorf("service not initialized")
@@ -366,27 +438,9 @@ func (s *OpsScheduledReportService) generateReportHTML(ctx context.Context, repo
 
 	switch strings.TrimSpace(report.ReportType) {
 	case "daily_summary", "weekly_summary":
-		overview, err := s.opsService.GetDashboardOverview(ctx, &OpsDashboardFilter{
-			StartTime: start,
-			EndTime:   end,
-			Platform:  "",
-			GroupID:   nil,
-			QueryMode: OpsQueryModeAuto,
-		})
+		overview, err := s.loadSummaryOverview(ctx, start, end)
 		if err != nil {
-			// If pre-aggregation isn't ready but the report is requested, fall back to raw.
-			if strings.TrimSpace(report.ReportType) == "daily_summary" || strings.TrimSpace(report.ReportType) == "weekly_summary" {
-				overview, err = s.opsService.GetDashboardOverview(ctx, &OpsDashboardFilter{
-					StartTime: start,
-					EndTime:   end,
-					Platform:  "",
-					GroupID:   nil,
-					QueryMode: OpsQueryModeRaw,
-				})
-			}
-			if err != nil {
-				return "", err
-			}
+			return "", err
 		}
 		return buildOpsSummaryEmailHTML(report.Name, start, end, overview), nil
 	case "error_digest":
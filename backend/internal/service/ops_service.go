@@ -54,6 +54,7 @@ type OpsService struct {
 	geminiCompatService       *GeminiMessagesCompatService
 	antigravityGatewayService *AntigravityGatewayService
 	systemLogSink             *OpsSystemLogSink
+	logMasking                *LogMaskingProfiles
 }
 
 func NewOpsService(
@@ -68,6 +69,7 @@ func NewOpsService(
 	geminiCompatService *GeminiMessagesCompatService,
 	antigravityGatewayService *AntigravityGatewayService,
 	systemLogSink *OpsSystemLogSink,
+	logMasking *LogMaskingProfiles,
 ) *OpsService {
 	svc := &OpsService{
 		opsRepo:     opsRepo,
@@ -83,11 +85,20 @@ func NewOpsService(
 		geminiCompatService:       geminiCompatService,
 		antigravityGatewayService: antigravityGatewayService,
 		systemLogSink:             systemLogSink,
+		logMasking:                logMasking,
 	}
 	svc.applyRuntimeLogConfigOnStartup(context.Background())
 	return svc
 }
 
+// LogMasking 返回日志脱敏配置档（错误日志脱敏与管理后台预览使用）；未配置时返回 nil
+func (s *OpsService) LogMasking() *LogMaskingProfiles {
+	if s == nil {
+		return nil
+	}
+	return s.logMasking
+}
+
 func (s *OpsService) RequireMonitoringEnabled(ctx context.Context) error {
 	if s.IsMonitoringEnabled(ctx) {
 		return nil
@@ -191,6 +191,141 @@ func validateOpsEmailNotificationConfig(cfg *OpsEmailNotificationConfig) error {
 	return nil
 }
 
+// =========================
+// Discord notification config
+// =========================
+
+// GetDiscordNotificationConfig returns the Discord config for the admin UI (webhook URL masked).
+func (s *OpsService) GetDiscordNotificationConfig(ctx context.Context) (*OpsDiscordNotificationConfig, error) {
+	cfg, err := s.getDiscordNotificationConfig(ctx)
+	if err != nil {
+		return nil, err
+	}
+	return maskOpsDiscordNotificationConfig(cfg), nil
+}
+
+// getDiscordNotificationConfig returns the stored config including the webhook URL.
+func (s *OpsService) getDiscordNotificationConfig(ctx context.Context) (*OpsDiscordNotificationConfig, error) {
+	defaultCfg := defaultOpsDiscordNotificationConfig()
+	if s == nil || s.settingRepo == nil {
+		return defaultCfg, nil
+	}
+	if ctx == nil {
+		ctx = context.Background()
+	}
+
+	raw, err := s.settingRepo.GetValue(ctx, SettingKeyOpsDiscordNotificationConfig)
+	if err != nil {
+		if errors.Is(err, ErrSettingNotFound) {
+			return defaultCfg, nil
+		}
+		return nil, err
+	}
+
+	cfg := &OpsDiscordNotificationConfig{}
+	if err := json.Unmarshal([]byte(raw), cfg); err != nil {
+		return defaultCfg, nil
+	}
+	normalizeOpsDiscordNotificationConfig(cfg)
+	return cfg, nil
+}
+
+func (s *OpsService) UpdateDiscordNotificationConfig(ctx context.Context, req *OpsDiscordNotificationConfigUpdateRequest) (*OpsDiscordNotificationConfig, error) {
+	if s == nil || s.settingRepo == nil {
+		return nil, errors.New("setting repository not initialized")
+	}
+	if ctx == nil {
+		ctx = context.Background()
+	}
+	if req == nil {
+		return nil, errors.New("invalid request")
+	}
+
+	current, err := s.getDiscordNotificationConfig(ctx)
+	if err != nil {
+		return nil, err
+	}
+
+	cfg := req.OpsDiscordNotificationConfig
+	cfg.WebhookURL = strings.TrimSpace(cfg.WebhookURL)
+	if cfg.WebhookURL == "" && !req.ClearWebhook {
+		cfg.WebhookURL = current.WebhookURL
+	}
+	normalizeOpsDiscordNotificationConfig(&cfg)
+	if err := validateOpsDiscordNotificationConfig(&cfg); err != nil {
+		return nil, err
+	}
+
+	raw, err := json.Marshal(cfg)
+	if err != nil {
+		return nil, err
+	}
+	if err := s.settingRepo.Set(ctx, SettingKeyOpsDiscordNotificationConfig, string(raw)); err != nil {
+		return nil, err
+	}
+	return maskOpsDiscordNotificationConfig(&cfg), nil
+}
+
+func defaultOpsDiscordNotificationConfig() *OpsDiscordNotificationConfig {
+	return &OpsDiscordNotificationConfig{
+		Enabled:              false,
+		MinSeverity:          "",
+		DailySummaryEnabled:  false,
+		DailySummarySchedule: "0 9 * * *",
+	}
+}
+
+func normalizeOpsDiscordNotificationConfig(cfg *OpsDiscordNotificationConfig) {
+	if cfg == nil {
+		return
+	}
+	cfg.WebhookURL = strings.TrimSpace(cfg.WebhookURL)
+	cfg.WebhookConfigured = cfg.WebhookURL != ""
+	cfg.Username = strings.TrimSpace(cfg.Username)
+	cfg.MinSeverity = strings.TrimSpace(cfg.MinSeverity)
+	cfg.MentionRoleID = strings.TrimSpace(cfg.MentionRoleID)
+	cfg.DailySummarySchedule = strings.TrimSpace(cfg.DailySummarySchedule)
+	if cfg.DailySummarySchedule == "" {
+		cfg.DailySummarySchedule = "0 9 * * *"
+	}
+}
+
+func maskOpsDiscordNotificationConfig(cfg *OpsDiscordNotificationConfig) *OpsDiscordNotificationConfig {
+	masked := *cfg
+	masked.WebhookURL = ""
+	return &masked
+}
+
+func validateOpsDiscordNotificationConfig(cfg *OpsDiscordNotificationConfig) error {
+	if cfg == nil {
+		return errors.New("invalid config")
+	}
+	if cfg.WebhookURL != "" {
+		if err := validateDiscordWebhookURL(cfg.WebhookURL); err != nil {
+			return err
+		}
+	} else if cfg.Enabled {
+		return errors.New("webhook_url is required when Discord notifications are enabled")
+	}
+	switch cfg.MinSeverity {
+	case "", "critical", "warning", "info":
+	default:
+		return errors.New("min_severity must be one of: critical, warning, info, or empty")
+	}
+	if len(cfg.Username) > 80 {
+		return errors.New("username must be at most 80 characters")
+	}
+	for _, r := range cfg.MentionRoleID {
+		if r < '0' || r > '9' {
+			return errors.New("mention_role_id must be a numeric Discord role ID")
+		}
+	}
+	if _, err := opsScheduledReportCronParser.Parse(cfg.DailySummarySchedule); err != nil {
+		return errors.New("daily_summary_schedule must be a valid 5-field cron expression")
+	}
+	return nil
+}
+
 // =========================
 // Alert runtime settings
 // =========================
@@ -38,6 +38,28 @@ type OpsEmailNotificationConfigUpdateRequest struct {
 	Report *OpsEmailReportConfig `json:"report"`
 }
 
+// OpsDiscordNotificationConfig Discord Webhook 推送：告警事件（按规则 notify_discord 开启）与每日用量摘要。
+// WebhookURL 只保存在服务端，查询接口返回时置空，以 WebhookConfigured 表示是否已配置。
+type OpsDiscordNotificationConfig struct {
+	Enabled           bool   `json:"enabled"`
+	WebhookURL        string `json:"webhook_url,omitempty"`
+	WebhookConfigured bool   `json:"webhook_configured"`
+	// Username 覆盖 Webhook 默认显示的名称
+	Username              string `json:"username"`
+	MinSeverity           string `json:"min_severity"`
+	IncludeResolvedAlerts bool   `json:"include_resolved_alerts"`
+	// MentionRoleID critical 级别告警时提及的 Discord 角色 ID
+	MentionRoleID        string `json:"mention_role_id"`
+	DailySummaryEnabled  bool   `json:"daily_summary_enabled"`
+	DailySummarySchedule string `json:"daily_summary_schedule"`
+}
+
+// OpsDiscordNotificationConfigUpdateRequest WebhookURL 为空时保留已保存的地址，ClearWebhook 用于清除。
+type OpsDiscordNotificationConfigUpdateRequest struct {
+	OpsDiscordNotificationConfig
+	ClearWebhook bool `json:"clear_webhook"`
+}
+
 type OpsDistributedLockSettings struct {
 	Enabled    bool   `json:"enabled"`
 	Key        string `json:"key"`
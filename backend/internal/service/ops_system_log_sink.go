@@ -26,7 +26,8 @@ type OpsSystemLogSinkHealth struct {
 }
 
 type OpsSystemLogSink struct {
-	opsRepo OpsRepository
+	opsRepo    OpsRepository
+	logMasking *LogMaskingProfiles
 
 	queue chan *logger.LogEvent
 
@@ -45,10 +46,11 @@ type OpsSystemLogSink struct {
 	lastError atomic.Value
 }
 
-func NewOpsSystemLogSink(opsRepo OpsRepository) *OpsSystemLogSink {
+func NewOpsSystemLogSink(opsRepo OpsRepository, logMasking *LogMaskingProfiles) *OpsSystemLogSink {
 	ctx, cancel := context.WithCancel(context.Background())
 	s := &OpsSystemLogSink{
 		opsRepo:       opsRepo,
+		logMasking:    logMasking,
 		queue:         make(chan *logger.LogEvent, 5000),
 		batchSize:     200,
 		flushInterval: time.Second,
@@ -183,6 +185,8 @@ func (s *OpsSystemLogSink) run() {
 
 func (s *OpsSystemLogSink) flushBatch(baseCtx context.Context, batch []*logger.LogEvent) (int, error) {
 	inputs := make([]*OpsInsertSystemLogInput, 0, len(batch))
+	// 系统日志无法关联请求的 API Key / 分组，按默认脱敏配置档处理
+	masker := s.logMasking.Default()
 	for _, event := range batch {
 		if event == nil {
 			continue
@@ -209,8 +213,8 @@ func (s *OpsSystemLogSink) flushBatch(baseCtx context.Context, batch []*logger.L
 		accountID := asInt64Ptr(fields["account_id"])
 
 		// 统一脱敏后写入索引。
-		message := logredact.RedactText(strings.TrimSpace(event.Message))
-		redactedExtra := logredact.RedactMap(fields)
+		message := masker.Mask(logredact.RedactText(strings.TrimSpace(event.Message)))
+		redactedExtra := masker.MaskValue(logredact.RedactMap(fields))
 		extraJSONBytes, _ := json.Marshal(redactedExtra)
 		extraJSON := string(extraJSONBytes)
 		if strings.TrimSpace(extraJSON) == "" {
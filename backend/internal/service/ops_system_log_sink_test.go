@@ -139,7 +139,7 @@ func TestOpsSystemLogSink_StartStopAndFlushSuccess(t *testing.T) {
 		},
 	}
 
-	sink := NewOpsSystemLogSink(repo)
+	sink := NewOpsSystemLogSink(repo, nil)
 	sink.batchSize = 1
 	sink.flushInterval = 10 * time.Millisecond
 	sink.Start()
@@ -195,7 +195,7 @@ func TestOpsSystemLogSink_FlushFailureUpdatesHealth(t *testing.T) {
 			return 0, errors.New("db unavailable")
 		},
 	}
-	sink := NewOpsSystemLogSink(repo)
+	sink := NewOpsSystemLogSink(repo, nil)
 	sink.batchSize = 1
 	sink.flushInterval = 10 * time.Millisecond
 	sink.Start()
@@ -237,7 +237,7 @@ func TestOpsSystemLogSink_StopFlushUsesActiveContextAndDrainsQueue(t *testing.T)
 		},
 	}
 
-	sink := NewOpsSystemLogSink(repo)
+	sink := NewOpsSystemLogSink(repo, nil)
 	sink.batchSize = 200
 	sink.flushInterval = time.Hour
 	sink.Start()
@@ -0,0 +1,425 @@
+package service
+
+import (
+	"context"
+	"crypto/hmac"
+	"crypto/sha256"
+	"encoding/hex"
+	"encoding/json"
+	"fmt"
+	"math"
+	"net/http"
+	"strconv"
+	"strings"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	infraerrors "github.com/Wei-Shaw/sub2api/internal/pkg/errors"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/logger"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/pagination"
+)
+
+// PaymentProviderStripe 目前唯一的支付渠道
+const PaymentProviderStripe = "stripe"
+
+// 支付订单状态
+const (
+	PaymentStatusPending           = "pending"
+	PaymentStatusPaid              = "paid"
+	PaymentStatusPartiallyRefunded = "partially_refunded"
+	PaymentStatusRefunded          = "refunded"
+	PaymentStatusExpired           = "expired"
+)
+
+var (
+	ErrPaymentsDisabled        = infraerrors.NotFound("PAYMENTS_DISABLED", "online payments are not enabled")
+	ErrPaymentInvalidAmount    = infraerrors.BadRequest("PAYMENT_INVALID_AMOUNT", "payment amount is out of the allowed range")
+	ErrPaymentSignatureInvalid = infraerrors.BadRequest("PAYMENT_SIGNATURE_INVALID", "invalid webhook signature")
+	ErrPaymentOrderNotFound    = infraerrors.NotFound("PAYMENT_ORDER_NOT_FOUND", "payment order not found")
+	ErrPaymentProviderFailure  = infraerrors.ServiceUnavailable("PAYMENT_PROVIDER_ERROR", "payment provider is unavailable, please try again later")
+	ErrPaymentWebhookMalformed = infraerrors.BadRequest("PAYMENT_WEBHOOK_MALFORMED", "malformed webhook payload")
+	errPaymentSignatureExpired = infraerrors.BadRequest("PAYMENT_SIGNATURE_EXPIRED", "webhook signature timestamp is outside the tolerance")
+)
+
+// PaymentOrder 在线支付充值订单
+type PaymentOrder struct {
+	ID              int64      `json:"id"`
+	UserID          int64      `json:"user_id"`
+	Provider        string     `json:"provider"`
+	SessionID       string     `json:"session_id"`
+	PaymentIntentID string     `json:"payment_intent_id,omitempty"`
+	Currency        string     `json:"currency"`
+	AmountMinor     int64      `json:"amount_minor"`
+	CreditAmount    float64    `json:"credit_amount"`
+	RefundedMinor   int64      `json:"refunded_minor"`
+	RefundedCredit  float64    `json:"refunded_credit"`
+	Status          string     `json:"status"`
+	CheckoutURL     string     `json:"checkout_url,omitempty"`
+	CreatedAt       time.Time  `json:"created_at"`
+	UpdatedAt       time.Time  `json:"updated_at"`
+	PaidAt          *time.Time `json:"paid_at,omitempty"`
+}
+
+// StripeCheckoutParams 创建 Checkout Session 的参数
+type StripeCheckoutParams struct {
+	UserID      int64
+	Email       string
+	Currency    string
+	AmountMinor int64
+	ProductName string
+	SuccessURL  string
+	CancelURL   string
+}
+
+// StripeCheckoutSession Stripe 返回的 Checkout Session
+type StripeCheckoutSession struct {
+	ID  string `json:"id"`
+	URL string `json:"url"`
+}
+
+// StripeClient Stripe API 客户端
+type StripeClient interface {
+	CreateCheckoutSession(ctx context.Context, params StripeCheckoutParams) (*StripeCheckoutSession, error)
+}
+
+// PaymentRepository 支付订单数据访问
+type PaymentRepository interface {
+	Create(ctx context.Context, order *PaymentOrder) error
+	GetBySessionID(ctx context.Context, provider, sessionID string) (*PaymentOrder, error)
+	// List 分页列出订单；userID 为 0、status 为空时不筛选
+	List(ctx context.Context, userID int64, status string, params pagination.PaginationParams) ([]PaymentOrder, *pagination.PaginationResult, error)
+	// MarkPaid 在同一事务中将 pending/expired 订单置为 paid、增加用户余额并写入余额记录（ledgerCode 为记录编号）；
+	// 订单已入账时返回 false，不重复入账
+	MarkPaid(ctx context.Context, provider, sessionID, paymentIntentID, ledgerCode string) (*PaymentOrder, bool, error)
+	// ApplyRefund 按累计退款金额（最小单位）在同一事务中扣回对应比例的余额并写入余额记录；
+	// 返回本次扣回的余额，累计退款未增加时返回 0
+	ApplyRefund(ctx context.Context, provider, paymentIntentID string, refundedMinor int64, ledgerCode string) (*PaymentOrder, float64, error)
+	// MarkExpired 将仍为 pending 的订单置为 expired
+	MarkExpired(ctx context.Context, provider, sessionID string) error
+}
+
+// PaymentService 在线支付充值：创建 Stripe Checkout 订单，校验 Webhook 签名后为账户余额入账或按退款扣回。
+// 入账以订单状态的条件更新实现幂等，重复投递或并发投递的 Webhook 只会入账一次。
+type PaymentService struct {
+	repo                 PaymentRepository
+	userRepo             UserRepository
+	stripe               StripeClient
+	billingCacheService  *BillingCacheService
+	authCacheInvalidator APIKeyAuthCacheInvalidator
+	cfg                  *config.Config
+	now                  func() time.Time
+}
+
+// NewPaymentService 创建支付服务
+func NewPaymentService(
+	repo PaymentRepository,
+	userRepo UserRepository,
+	stripe StripeClient,
+	billingCacheService *BillingCacheService,
+	authCacheInvalidator APIKeyAuthCacheInvalidator,
+	cfg *config.Config,
+) *PaymentService {
+	return &PaymentService{
+		repo:                 repo,
+		userRepo:             userRepo,
+		stripe:               stripe,
+		billingCacheService:  billingCacheService,
+		authCacheInvalidator: authCacheInvalidator,
+		cfg:                  cfg,
+		now:                  time.Now,
+	}
+}
+
+// Enabled 是否启用 Stripe 充值
+func (s *PaymentService) Enabled() bool {
+	return s != nil && s.cfg != nil && s.cfg.Payments.Stripe.Enabled
+}
+
+// CreateCheckout 为用户创建充值订单并返回 Stripe 支付地址；amount 为收款币种金额
+func (s *PaymentService) CreateCheckout(ctx context.Context, userID int64, amount float64) (*PaymentOrder, error) {
+	if !s.Enabled() {
+		return nil, ErrPaymentsDisabled
+	}
+	cfg := s.cfg.Payments.Stripe
+	currency := strings.ToLower(cfg.Currency)
+	if math.IsNaN(amount) || amount < cfg.MinAmount || amount > cfg.MaxAmount {
+		return nil, infraerrors.Newf(http.StatusBadRequest, ErrPaymentInvalidAmount.Reason,
+			"amount must be between %s and %s", formatPaymentAmount(paymentAmountMinor(cfg.MinAmount, currency), currency),
+			formatPaymentAmount(paymentAmountMinor(cfg.MaxAmount, currency), currency))
+	}
+	user, err := s.userRepo.GetByID(ctx, userID)
+	if err != nil {
+		return nil, err
+	}
+
+	amountMinor := paymentAmountMinor(amount, currency)
+	session, err := s.stripe.CreateCheckoutSession(ctx, StripeCheckoutParams{
+		UserID:      user.ID,
+		Email:       user.Email,
+		Currency:    currency,
+		AmountMinor: amountMinor,
+		ProductName: fmt.Sprintf("Account credit (%s)", formatPaymentAmount(amountMinor, currency)),
+		SuccessURL:  cfg.SuccessURL,
+		CancelURL:   cfg.CancelURL,
+	})
+	if err != nil {
+		logger.LegacyPrintf("service.payment", "[Payment] create stripe checkout for user %d failed: %v", userID, err)
+		return nil, ErrPaymentProviderFailure
+	}
+
+	order := &PaymentOrder{
+		UserID:       user.ID,
+		Provider:     PaymentProviderStripe,
+		SessionID:    session.ID,
+		Currency:     currency,
+		AmountMinor:  amountMinor,
+		CreditAmount: paymentCredit(amountMinor, currency, cfg.CreditsPerUnit),
+		Status:       PaymentStatusPending,
+		CheckoutURL:  session.URL,
+	}
+	if err := s.repo.Create(ctx, order); err != nil {
+		return nil, fmt.Errorf("create payment order: %w", err)
+	}
+	return order, nil
+}
+
+// ListUserOrders 列出用户自己的充值订单
+func (s *PaymentService) ListUserOrders(ctx context.Context, userID int64, params pagination.PaginationParams) ([]PaymentOrder, *pagination.PaginationResult, error) {
+	return s.repo.List(ctx, userID, "", params)
+}
+
+// ListOrders 管理端列出充值订单
+func (s *PaymentService) ListOrders(ctx context.Context, userID int64, status string, params pagination.PaginationParams) ([]PaymentOrder, *pagination.PaginationResult, error) {
+	return s.repo.List(ctx, userID, strings.TrimSpace(status), params)
+}
+
+// stripeEvent Webhook 事件中用到的字段
+type stripeEvent struct {
+	ID   string `json:"id"`
+	Type string `json:"type"`
+	Data struct {
+		Object json.RawMessage `json:"object"`
+	} `json:"data"`
+}
+
+type stripeCheckoutSessionObject struct {
+	ID            string `json:"id"`
+	PaymentStatus string `json:"payment_status"`
+	PaymentIntent string `json:"payment_intent"`
+	AmountTotal   int64  `json:"amount_total"`
+	Currency      string `json:"currency"`
+}
+
+type stripeChargeObject struct {
+	PaymentIntent  string `json:"payment_intent"`
+	AmountRefunded int64  `json:"amount_refunded"`
+}
+
+// HandleStripeWebhook 校验签名并处理 Stripe 事件；未关注的事件类型直接忽略。
+// 返回错误时 Stripe 会按退避策略重试投递，因此重试也无法恢复的业务异常（如金额不符）只记审计日志并确认接收。
+func (s *PaymentService) HandleStripeWebhook(ctx context.Context, payload []byte, signatureHeader string) error {
+	if !s.Enabled() {
+		return ErrPaymentsDisabled
+	}
+	cfg := s.cfg.Payments.Stripe
+	tolerance := time.Duration(cfg.WebhookToleranceSeconds) * time.Second
+	if err := VerifyStripeSignature(payload, signatureHeader, cfg.WebhookSecret, tolerance, s.now()); err != nil {
+		return err
+	}
+
+	var event stripeEvent
+	if err := json.Unmarshal(payload, &event); err != nil {
+		return ErrPaymentWebhookMalformed
+	}
+	switch event.Type {
+	case "checkout.session.completed", "checkout.session.async_payment_succeeded":
+		var session stripeCheckoutSessionObject
+		if err := json.Unmarshal(event.Data.Object, &session); err != nil {
+			return ErrPaymentWebhookMalformed
+		}
+		// 延迟支付方式（如银行转账）在 completed 时仍为 unpaid，等待 async_payment_succeeded
+		if session.PaymentStatus != "paid" {
+			return nil
+		}
+		return s.credit(ctx, event.ID, &session)
+	case "checkout.session.expired", "checkout.session.async_payment_failed":
+		var session stripeCheckoutSessionObject
+		if err := json.Unmarshal(event.Data.Object, &session); err != nil {
+			return ErrPaymentWebhookMalformed
+		}
+		return s.repo.MarkExpired(ctx, PaymentProviderStripe, session.ID)
+	case "charge.refunded":
+		var charge stripeChargeObject
+		if err := json.Unmarshal(event.Data.Object, &charge); err != nil {
+			return ErrPaymentWebhookMalformed
+		}
+		return s.refund(ctx, event.ID, &charge)
+	default:
+		return nil
+	}
+}
+
+func (s *PaymentService) credit(ctx context.Context, eventID string, session *stripeCheckoutSessionObject) error {
+	order, err := s.repo.GetBySessionID(ctx, PaymentProviderStripe, session.ID)
+	if err != nil {
+		if infraerrors.IsNotFound(err) {
+			// 非本系统创建的 Checkout Session（同一 Stripe 账号的其他业务），忽略
+			logger.LegacyPrintf("service.payment", "[Payment] ignore event %s for unknown checkout session %s", eventID, session.ID)
+			return nil
+		}
+		return err
+	}
+	if session.AmountTotal != order.AmountMinor || !strings.EqualFold(session.Currency, order.Currency) {
+		// 同一事件重试投递结果不变，不入账并确认接收，由人工按审计日志核对处理
+		logger.LegacyPrintf("service.payment", "[Payment] AUDIT amount mismatch, not credited: order=%d user=%d session=%s paid=%d %s expected=%d %s event=%s",
+			order.ID, order.UserID, session.ID, session.AmountTotal, session.Currency, order.AmountMinor, order.Currency, eventID)
+		return nil
+	}
+
+	code, err := GenerateRedeemCode()
+	if err != nil {
+		return err
+	}
+	order, credited, err := s.repo.MarkPaid(ctx, PaymentProviderStripe, session.ID, session.PaymentIntent, code)
+	if err != nil {
+		return fmt.Errorf("credit payment order: %w", err)
+	}
+	if !credited {
+		return nil
+	}
+	logger.LegacyPrintf("service.payment", "[Payment] AUDIT credited order=%d user=%d credit=%.4f event=%s",
+		order.ID, order.UserID, order.CreditAmount, eventID)
+	s.invalidateBalance(ctx, order.UserID)
+	return nil
+}
+
+func (s *PaymentService) refund(ctx context.Context, eventID string, charge *stripeChargeObject) error {
+	if charge.PaymentIntent == "" {
+		return nil
+	}
+	code, err := GenerateRedeemCode()
+	if err != nil {
+		return err
+	}
+	order, deducted, err := s.repo.ApplyRefund(ctx, PaymentProviderStripe, charge.PaymentIntent, charge.AmountRefunded, code)
+	if err != nil {
+		if infraerrors.IsNotFound(err) {
+			return nil
+		}
+		return fmt.Errorf("apply payment refund: %w", err)
+	}
+	if deducted <= 0 {
+		return nil
+	}
+	logger.LegacyPrintf("service.payment", "[Payment] AUDIT refunded order=%d user=%d deducted=%.4f refunded_minor=%d event=%s",
+		order.ID, order.UserID, deducted, order.RefundedMinor, eventID)
+	s.invalidateBalance(ctx, order.UserID)
+	return nil
+}
+
+func (s *PaymentService) invalidateBalance(ctx context.Context, userID int64) {
+	if s.authCacheInvalidator != nil {
+		s.authCacheInvalidator.InvalidateAuthCacheByUserID(ctx, userID)
+	}
+	if s.billingCacheService == nil {
+		return
+	}
+	go func() {
+		cacheCtx, cancel := context.WithTimeout(context.Background(), 5*time.Second)
+		defer cancel()
+		if err := s.billingCacheService.InvalidateUserBalance(cacheCtx, userID); err != nil {
+			logger.LegacyPrintf("service.payment", "invalidate user balance cache failed: user_id=%d err=%v", userID, err)
+		}
+	}()
+}
+
+// PaymentRefundCredit 按累计退款比例计算应扣回的余额（不超过入账金额）
+func PaymentRefundCredit(order *PaymentOrder, refundedMinor int64) float64 {
+	if order.AmountMinor <= 0 {
+		return 0
+	}
+	refundedMinor = min(max(refundedMinor, 0), order.AmountMinor)
+	return order.CreditAmount * float64(refundedMinor) / float64(order.AmountMinor)
+}
+
+func paymentCredit(amountMinor int64, currency string, creditsPerUnit float64) float64 {
+	return float64(amountMinor) / math.Pow10(paymentCurrencyExponent(currency)) * creditsPerUnit
+}
+
+// paymentCurrencyExponents 非两位小数币种的最小单位位数（见 Stripe 文档 Zero-decimal / Three-decimal currencies），
+// 未列出的币种按两位小数处理
+var paymentCurrencyExponents = map[string]int{
+	"bif": 0, "clp": 0, "djf": 0, "gnf": 0, "jpy": 0, "kmf": 0, "krw": 0, "mga": 0,
+	"pyg": 0, "rwf": 0, "ugx": 0, "vnd": 0, "vuv": 0, "xaf": 0, "xof": 0, "xpf": 0,
+	"bhd": 3, "jod": 3, "kwd": 3, "omr": 3, "tnd": 3,
+}
+
+func paymentCurrencyExponent(currency string) int {
+	if exp, ok := paymentCurrencyExponents[strings.ToLower(currency)]; ok {
+		return exp
+	}
+	return 2
+}
+
+// paymentAmountMinor 收款币种金额换算为 Stripe 使用的最小单位
+func paymentAmountMinor(amount float64, currency string) int64 {
+	exp := paymentCurrencyExponent(currency)
+	if exp == 3 {
+		// Stripe 要求三位小数币种的金额末位为 0
+		return int64(math.Round(amount*100)) * 10
+	}
+	return int64(math.Round(amount * math.Pow10(exp)))
+}
+
+// formatPaymentAmount 按币种小数位格式化最小单位金额，例如 2501 usd -> "25.01 USD"、1000 jpy -> "1000 JPY"
+func formatPaymentAmount(amountMinor int64, currency string) string {
+	exp := paymentCurrencyExponent(currency)
+	return strconv.FormatFloat(float64(amountMinor)/math.Pow10(exp), 'f', exp, 64) + " " + strings.ToUpper(currency)
+}
+
+// VerifyStripeSignature 校验 Stripe-Signature 头（t=时间戳,v1=签名,...）：
+// 签名为 HMAC-SHA256(secret, "t.payload")，任一 v1 签名匹配且时间戳在容差内即通过
+func VerifyStripeSignature(payload []byte, header, secret string, tolerance time.Duration, now time.Time) error {
+	var (
+		timestamp  int64
+		signatures []string
+	)
+	for _, part := range strings.Split(header, ",") {
+		key, value, ok := strings.Cut(strings.TrimSpace(part), "=")
+		if !ok {
+			continue
+		}
+		switch key {
+		case "t":
+			ts, err := strconv.ParseInt(value, 10, 64)
+			if err != nil {
+				return ErrPaymentSignatureInvalid
+			}
+			timestamp = ts
+		case "v1":
+			signatures = append(signatures, value)
+		}
+	}
+	if timestamp == 0 {
+		return ErrPaymentSignatureInvalid
+	}
+	if len(signatures) == 0 {
+		return ErrPaymentSignatureInvalid
+	}
+	if tolerance > 0 && now.Sub(time.Unix(timestamp, 0)).Abs() > tolerance {
+		return errPaymentSignatureExpired
+	}
+
+	mac := hmac.New(sha256.New, []byte(secret))
+	mac.Write([]byte(strconv.FormatInt(timestamp, 10)))
+	mac.Write([]byte("."))
+	mac.Write(payload)
+	expected := mac.Sum(nil)
+	for _, sig := range signatures {
+		decoded, err := hex.DecodeString(sig)
+		if err == nil && hmac.Equal(decoded, expected) {
+			return nil
+		}
+	}
+	return ErrPaymentSignatureInvalid
+}
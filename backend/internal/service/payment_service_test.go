@@ -0,0 +1,300 @@
+//go:build unit
+
+package service
+
+import (
+	"context"
+	"crypto/hmac"
+	"crypto/sha256"
+	"encoding/hex"
+	"fmt"
+	"testing"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/pagination"
+	"github.com/stretchr/testify/require"
+)
+
+const testStripeWebhookSecret = "whsec_test"
+
+type stripeClientStub struct {
+	params []StripeCheckoutParams
+	err    error
+}
+
+func (s *stripeClientStub) CreateCheckoutSession(_ context.Context, params StripeCheckoutParams) (*StripeCheckoutSession, error) {
+	if s.err != nil {
+		return nil, s.err
+	}
+	s.params = append(s.params, params)
+	id := fmt.Sprintf("cs_test_%d", len(s.params))
+	return &StripeCheckoutSession{ID: id, URL: "https://checkout.stripe.com/c/pay/" + id}, nil
+}
+
+// paymentRepoStub 内存实现，balances 记录各用户的入账合计
+type paymentRepoStub struct {
+	orders   map[string]*PaymentOrder
+	balances map[int64]float64
+	ledger   []string
+}
+
+func newPaymentRepoStub() *paymentRepoStub {
+	return &paymentRepoStub{orders: map[string]*PaymentOrder{}, balances: map[int64]float64{}}
+}
+
+func (r *paymentRepoStub) Create(_ context.Context, order *PaymentOrder) error {
+	order.ID = int64(len(r.orders) + 1)
+	r.orders[order.SessionID] = order
+	return nil
+}
+
+func (r *paymentRepoStub) GetBySessionID(_ context.Context, _, sessionID string) (*PaymentOrder, error) {
+	order, ok := r.orders[sessionID]
+	if !ok {
+		return nil, ErrPaymentOrderNotFound
+	}
+	return order, nil
+}
+
+func (r *paymentRepoStub) List(context.Context, int64, string, pagination.PaginationParams) ([]PaymentOrder, *pagination.PaginationResult, error) {
+	panic("unexpected List call")
+}
+
+func (r *paymentRepoStub) MarkPaid(_ context.Context, _, sessionID, paymentIntentID, ledgerCode string) (*PaymentOrder, bool, error) {
+	order, ok := r.orders[sessionID]
+	if !ok {
+		return nil, false, ErrPaymentOrderNotFound
+	}
+	if order.Status != PaymentStatusPending && order.Status != PaymentStatusExpired {
+		return order, false, nil
+	}
+	order.Status = PaymentStatusPaid
+	order.PaymentIntentID = paymentIntentID
+	r.balances[order.UserID] += order.CreditAmount
+	r.ledger = append(r.ledger, ledgerCode)
+	return order, true, nil
+}
+
+func (r *paymentRepoStub) ApplyRefund(_ context.Context, _, paymentIntentID string, refundedMinor int64, ledgerCode string) (*PaymentOrder, float64, error) {
+	for _, order := range r.orders {
+		if order.PaymentIntentID != paymentIntentID || order.Status == PaymentStatusPending || order.Status == PaymentStatusExpired {
+			continue
+		}
+		if refundedMinor <= order.RefundedMinor {
+			return order, 0, nil
+		}
+		credit := PaymentRefundCredit(order, refundedMinor)
+		deducted := credit - order.RefundedCredit
+		order.RefundedMinor, order.RefundedCredit = refundedMinor, credit
+		order.Status = PaymentStatusPartiallyRefunded
+		if refundedMinor >= order.AmountMinor {
+			order.Status = PaymentStatusRefunded
+		}
+		r.balances[order.UserID] -= deducted
+		r.ledger = append(r.ledger, ledgerCode)
+		return order, deducted, nil
+	}
+	return nil, 0, ErrPaymentOrderNotFound
+}
+
+func (r *paymentRepoStub) MarkExpired(_ context.Context, _, sessionID string) error {
+	if order, ok := r.orders[sessionID]; ok && order.Status == PaymentStatusPending {
+		order.Status = PaymentStatusExpired
+	}
+	return nil
+}
+
+func newPaymentServiceForTest() (*PaymentService, *paymentRepoStub, *stripeClientStub, *authCacheInvalidatorStub) {
+	cfg := &config.Config{}
+	cfg.Payments.Stripe = config.StripeConfig{
+		Enabled:                 true,
+		SecretKey:               "sk_test",
+		WebhookSecret:           testStripeWebhookSecret,
+		Currency:                "usd",
+		CreditsPerUnit:          1,
+		MinAmount:               5,
+		MaxAmount:               1000,
+		SuccessURL:              "https://example.com/ok",
+		CancelURL:               "https://example.com/cancel",
+		WebhookToleranceSeconds: 300,
+	}
+	repo := newPaymentRepoStub()
+	stripe := &stripeClientStub{}
+	invalidator := &authCacheInvalidatorStub{}
+	users := &userRepoStub{user: &User{ID: 9, Email: "owner@example.com"}}
+	return NewPaymentService(repo, users, stripe, nil, invalidator, cfg), repo, stripe, invalidator
+}
+
+func signStripePayload(payload string, ts time.Time, secret string) string {
+	mac := hmac.New(sha256.New, []byte(secret))
+	_, _ = fmt.Fprintf(mac, "%d.%s", ts.Unix(), payload)
+	return fmt.Sprintf("t=%d,v1=%s", ts.Unix(), hex.EncodeToString(mac.Sum(nil)))
+}
+
+func sendStripeEvent(t *testing.T, svc *PaymentService, payload string) error {
+	t.Helper()
+	return svc.HandleStripeWebhook(context.Background(), []byte(payload), signStripePayload(payload, time.Now(), testStripeWebhookSecret))
+}
+
+func TestVerifyStripeSignature(t *testing.T) {
+	payload := []byte(`{"id":"evt_1"}`)
+	now := time.Unix(1760000000, 0)
+	header := signStripePayload(string(payload), now, "whsec_x")
+
+	require.NoError(t, VerifyStripeSignature(payload, header, "whsec_x", 5*time.Minute, now))
+	// 多个 v1 签名（密钥轮换期间）任一匹配即可
+	require.NoError(t, VerifyStripeSignature(payload, header+",v1=deadbeef", "whsec_x", 5*time.Minute, now))
+
+	require.ErrorIs(t, VerifyStripeSignature(payload, header, "whsec_other", 5*time.Minute, now), ErrPaymentSignatureInvalid)
+	require.ErrorIs(t, VerifyStripeSignature([]byte(`{"id":"evt_2"}`), header, "whsec_x", 5*time.Minute, now), ErrPaymentSignatureInvalid)
+	require.ErrorIs(t, VerifyStripeSignature(payload, "", "whsec_x", 5*time.Minute, now), ErrPaymentSignatureInvalid)
+	require.ErrorIs(t, VerifyStripeSignature(payload, "t=1760000000", "whsec_x", 5*time.Minute, now), ErrPaymentSignatureInvalid)
+	require.ErrorIs(t, VerifyStripeSignature(payload, header, "whsec_x", 5*time.Minute, now.Add(10*time.Minute)), errPaymentSignatureExpired)
+}
+
+func TestPaymentService_CreateCheckout(t *testing.T) {
+	svc, repo, stripe, _ := newPaymentServiceForTest()
+	ctx := context.Background()
+
+	_, err := svc.CreateCheckout(ctx, 9, 1)
+	require.ErrorIs(t, err, ErrPaymentInvalidAmount)
+	_, err = svc.CreateCheckout(ctx, 9, 5000)
+	require.ErrorIs(t, err, ErrPaymentInvalidAmount)
+
+	svc.cfg.Payments.Stripe.CreditsPerUnit = 0.5
+	order, err := svc.CreateCheckout(ctx, 9, 25.01)
+	require.NoError(t, err)
+	require.Equal(t, "cs_test_1", order.SessionID)
+	require.Equal(t, int64(2501), order.AmountMinor)
+	require.InDelta(t, 12.505, order.CreditAmount, 1e-9)
+	require.Equal(t, PaymentStatusPending, order.Status)
+	require.Same(t, order, repo.orders["cs_test_1"])
+	require.Equal(t, "owner@example.com", stripe.params[0].Email)
+	require.Equal(t, "Account credit (25.01 USD)", stripe.params[0].ProductName)
+
+	stripe.err = fmt.Errorf("boom")
+	_, err = svc.CreateCheckout(ctx, 9, 10)
+	require.ErrorIs(t, err, ErrPaymentProviderFailure)
+
+	svc.cfg.Payments.Stripe.Enabled = false
+	_, err = svc.CreateCheckout(ctx, 9, 10)
+	require.ErrorIs(t, err, ErrPaymentsDisabled)
+}
+
+func TestPaymentService_CreateCheckoutZeroDecimalCurrency(t *testing.T) {
+	svc, _, stripe, _ := newPaymentServiceForTest()
+	svc.cfg.Payments.Stripe.Currency = "JPY"
+	svc.cfg.Payments.Stripe.CreditsPerUnit = 0.0067
+	svc.cfg.Payments.Stripe.MinAmount = 500
+	svc.cfg.Payments.Stripe.MaxAmount = 100000
+
+	order, err := svc.CreateCheckout(context.Background(), 9, 1500)
+	require.NoError(t, err)
+	require.Equal(t, "jpy", order.Currency)
+	require.Equal(t, int64(1500), order.AmountMinor)
+	require.InDelta(t, 10.05, order.CreditAmount, 1e-9)
+	require.Equal(t, "Account credit (1500 JPY)", stripe.params[0].ProductName)
+
+	_, err = svc.CreateCheckout(context.Background(), 9, 100)
+	require.ErrorContains(t, err, "amount must be between 500 JPY and 100000 JPY")
+}
+
+func TestPaymentAmountMinor(t *testing.T) {
+	tests := []struct {
+		currency  string
+		amount    float64
+		wantMinor int64
+		wantText  string
+	}{
+		{"usd", 25.01, 2501, "25.01 USD"},
+		{"EUR", 10, 1000, "10.00 EUR"},
+		{"jpy", 1500, 1500, "1500 JPY"},
+		{"krw", 10000.4, 10000, "10000 KRW"},
+		{"kwd", 12.34, 12340, "12.340 KWD"},
+	}
+	for _, tt := range tests {
+		t.Run(tt.currency, func(t *testing.T) {
+			minor := paymentAmountMinor(tt.amount, tt.currency)
+			require.Equal(t, tt.wantMinor, minor)
+			require.Equal(t, tt.wantText, formatPaymentAmount(minor, tt.currency))
+		})
+	}
+}
+
+func TestPaymentService_WebhookCreditsOnce(t *testing.T) {
+	svc, repo, _, invalidator := newPaymentServiceForTest()
+	order, err := svc.CreateCheckout(context.Background(), 9, 20)
+	require.NoError(t, err)
+
+	completed := `{"id":"evt_1","type":"checkout.session.completed","data":{"object":{"id":"cs_test_1","payment_status":"paid","payment_intent":"pi_1","amount_total":2000,"currency":"usd"}}}`
+	require.NoError(t, sendStripeEvent(t, svc, completed))
+	require.NoError(t, sendStripeEvent(t, svc, completed))
+	require.InDelta(t, 20, repo.balances[9], 1e-9)
+	require.Len(t, repo.ledger, 1)
+	require.Equal(t, PaymentStatusPaid, order.Status)
+	require.Equal(t, "pi_1", order.PaymentIntentID)
+	require.Equal(t, []int64{9}, invalidator.userIDs)
+
+	// 未知会话（同一 Stripe 账号的其他业务）忽略
+	unknown := `{"id":"evt_2","type":"checkout.session.completed","data":{"object":{"id":"cs_other","payment_status":"paid","amount_total":100,"currency":"usd"}}}`
+	require.NoError(t, sendStripeEvent(t, svc, unknown))
+
+	// 签名错误
+	err = svc.HandleStripeWebhook(context.Background(), []byte(completed), signStripePayload(completed, time.Now(), "whsec_wrong"))
+	require.ErrorIs(t, err, ErrPaymentSignatureInvalid)
+}
+
+func TestPaymentService_WebhookSkipsAmountMismatchAndUnpaid(t *testing.T) {
+	svc, repo, _, _ := newPaymentServiceForTest()
+	_, err := svc.CreateCheckout(context.Background(), 9, 20)
+	require.NoError(t, err)
+
+	unpaid := `{"id":"evt_1","type":"checkout.session.completed","data":{"object":{"id":"cs_test_1","payment_status":"unpaid","amount_total":2000,"currency":"usd"}}}`
+	require.NoError(t, sendStripeEvent(t, svc, unpaid))
+	require.Zero(t, repo.balances[9])
+
+	mismatch := `{"id":"evt_2","type":"checkout.session.async_payment_succeeded","data":{"object":{"id":"cs_test_1","payment_status":"paid","amount_total":200,"currency":"usd"}}}`
+	// 金额不符不入账，但确认接收，避免 Stripe 无限重试
+	require.NoError(t, sendStripeEvent(t, svc, mismatch))
+	require.Zero(t, repo.balances[9])
+	require.Equal(t, PaymentStatusPending, repo.orders["cs_test_1"].Status)
+
+	wrongCurrency := `{"id":"evt_3","type":"checkout.session.completed","data":{"object":{"id":"cs_test_1","payment_status":"paid","amount_total":2000,"currency":"eur"}}}`
+	require.NoError(t, sendStripeEvent(t, svc, wrongCurrency))
+	require.Zero(t, repo.balances[9])
+}
+
+func TestPaymentService_WebhookRefunds(t *testing.T) {
+	svc, repo, _, _ := newPaymentServiceForTest()
+	order, err := svc.CreateCheckout(context.Background(), 9, 40)
+	require.NoError(t, err)
+	require.NoError(t, sendStripeEvent(t, svc,
+		`{"id":"evt_1","type":"checkout.session.completed","data":{"object":{"id":"cs_test_1","payment_status":"paid","payment_intent":"pi_1","amount_total":4000,"currency":"usd"}}}`))
+
+	partial := `{"id":"evt_2","type":"charge.refunded","data":{"object":{"payment_intent":"pi_1","amount_refunded":1000}}}`
+	require.NoError(t, sendStripeEvent(t, svc, partial))
+	require.NoError(t, sendStripeEvent(t, svc, partial))
+	require.InDelta(t, 30, repo.balances[9], 1e-9)
+	require.Equal(t, PaymentStatusPartiallyRefunded, order.Status)
+
+	full := `{"id":"evt_3","type":"charge.refunded","data":{"object":{"payment_intent":"pi_1","amount_refunded":4000}}}`
+	require.NoError(t, sendStripeEvent(t, svc, full))
+	require.InDelta(t, 0, repo.balances[9], 1e-9)
+	require.Equal(t, PaymentStatusRefunded, order.Status)
+	require.Len(t, repo.ledger, 3)
+
+	// 非本系统的支付
+	require.NoError(t, sendStripeEvent(t, svc, `{"id":"evt_4","type":"charge.refunded","data":{"object":{"payment_intent":"pi_other","amount_refunded":100}}}`))
+}
+
+func TestPaymentService_WebhookExpires(t *testing.T) {
+	svc, repo, _, _ := newPaymentServiceForTest()
+	order, err := svc.CreateCheckout(context.Background(), 9, 20)
+	require.NoError(t, err)
+
+	require.NoError(t, sendStripeEvent(t, svc, `{"id":"evt_1","type":"checkout.session.expired","data":{"object":{"id":"cs_test_1"}}}`))
+	require.Equal(t, PaymentStatusExpired, order.Status)
+	require.NoError(t, sendStripeEvent(t, svc, `{"id":"evt_2","type":"customer.created","data":{"object":{}}}`))
+	require.Zero(t, repo.balances[9])
+}
@@ -0,0 +1,350 @@
+package service
+
+import (
+	"context"
+	"fmt"
+	"net/http"
+	"sync/atomic"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/claude"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/ctxkey"
+	infraerrors "github.com/Wei-Shaw/sub2api/internal/pkg/errors"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/logger"
+	"github.com/google/uuid"
+	"github.com/tidwall/gjson"
+)
+
+// 预占对象
+const (
+	// CreditScopeUser 用户余额（账户级预付费余额）
+	CreditScopeUser = "user"
+	// CreditScopeAPIKey API Key 额度（quota - quota_used）
+	CreditScopeAPIKey = "api_key"
+)
+
+var (
+	ErrInsufficientCredit = infraerrors.New(http.StatusPaymentRequired, "INSUFFICIENT_CREDIT", "insufficient prepaid credit for the estimated cost of this request")
+	ErrInvalidCreditTopUp = infraerrors.BadRequest("INVALID_CREDIT_TOP_UP", "top-up amount must be positive")
+)
+
+// CreditHoldTarget 预占对象及其当前可用额度
+type CreditHoldTarget struct {
+	ScopeType string
+	ScopeID   int64
+	Available float64
+}
+
+// CreditHoldCache 额度预占存储（Redis）。每个对象维护一组带过期时间的预占，过期预占在读写时清理。
+type CreditHoldCache interface {
+	// Reserve 在所有对象的 可用额度 - 已预占 >= amount 时原子写入预占；
+	// 成功返回 -1，否则返回首个额度不足对象的下标及其已预占合计
+	Reserve(ctx context.Context, targets []CreditHoldTarget, holdID string, amount float64, expiresAt time.Time) (int, float64, error)
+	// Release 调整预占过期时间；expiresAt 为零值时立即删除
+	Release(ctx context.Context, targets []CreditHoldTarget, holdID string, expiresAt time.Time) error
+	// Held 返回对象当前未过期的预占合计
+	Held(ctx context.Context, scopeType string, scopeID int64) (float64, error)
+}
+
+// CreditHold 单个请求的额度预占
+type CreditHold struct {
+	ID      string
+	Amount  float64
+	targets []CreditHoldTarget
+
+	service *PrepaidCreditService
+	// claimed 由记录用量的任务认领后，预占随实际扣费一并释放，中间件不再释放
+	claimed atomic.Bool
+}
+
+// WithCreditHolds 将请求的额度预占写入 context
+func WithCreditHolds(ctx context.Context, holds []*CreditHold) context.Context {
+	if len(holds) == 0 {
+		return ctx
+	}
+	return context.WithValue(ctx, ctxkey.CreditHold, holds)
+}
+
+// ClaimCreditHold 认领 context 中一份未被认领的额度预占，交由 RecordUsage 在扣费后释放。
+// 每份预占只能被认领一次（n > 1 扇出时每次调用各认领一份），未预占或已全部认领时返回 nil。
+func ClaimCreditHold(ctx context.Context) *CreditHold {
+	holds, _ := ctx.Value(ctxkey.CreditHold).([]*CreditHold)
+	for _, hold := range holds {
+		if hold.claimed.CompareAndSwap(false, true) {
+			return hold
+		}
+	}
+	return nil
+}
+
+// Claimed 预占是否已被记录用量的任务认领
+func (h *CreditHold) Claimed() bool {
+	return h != nil && h.claimed.Load()
+}
+
+// Settle 实际费用已扣除（或确认无需扣除）后释放预占
+func (h *CreditHold) Settle(ctx context.Context) {
+	if h == nil {
+		return
+	}
+	h.service.Release(ctx, h)
+}
+
+// APIKeyCredit API Key 预付费额度概览
+type APIKeyCredit struct {
+	APIKeyID  int64   `json:"api_key_id"`
+	Quota     float64 `json:"quota"`
+	QuotaUsed float64 `json:"quota_used"`
+	Held      float64 `json:"held"`
+	// Available 可用额度（quota - quota_used - held），-1 表示不限额
+	Available float64 `json:"available"`
+}
+
+// PrepaidCreditService 预付费额度：请求前按预估费用原子预占用户余额与 API Key 额度，
+// 可用额度（余额 - 进行中请求的预占）不足时直接拒绝。
+//
+// 实际费用仍由 RecordUsage 按用量扣除：处理器认领预占后交给 RecordUsage，扣费写入后在同一步骤释放预占，
+// 不存在预占与实际费用同时计入的窗口；未进入记账的请求（失败、无用量）由中间件在请求结束时释放。
+type PrepaidCreditService struct {
+	cache                CreditHoldCache
+	billingCache         *BillingCacheService
+	billingService       *BillingService
+	apiKeyRepo           APIKeyRepository
+	authCacheInvalidator APIKeyAuthCacheInvalidator
+	cfg                  *config.Config
+}
+
+// NewPrepaidCreditService 创建预付费额度服务
+func NewPrepaidCreditService(
+	cache CreditHoldCache,
+	billingCache *BillingCacheService,
+	billingService *BillingService,
+	apiKeyRepo APIKeyRepository,
+	authCacheInvalidator APIKeyAuthCacheInvalidator,
+	cfg *config.Config,
+) *PrepaidCreditService {
+	return &PrepaidCreditService{
+		cache:                cache,
+		billingCache:         billingCache,
+		billingService:       billingService,
+		apiKeyRepo:           apiKeyRepo,
+		authCacheInvalidator: authCacheInvalidator,
+		cfg:                  cfg,
+	}
+}
+
+// Enabled 是否启用请求前预占
+func (s *PrepaidCreditService) Enabled() bool {
+	return s != nil && s.cfg != nil && s.cfg.Billing.PrepaidHold.Enabled && s.cfg.RunMode != config.RunModeSimple
+}
+
+// EstimateCost 按请求体预估费用：输入按文本内容估算 token（图片、PDF 等附件按固定值计，不按 base64 字节数），
+// 输出取请求的 max_tokens，未指定时取配置默认值（不超过模型输出上限）。模型无定价时返回 0。
+func (s *PrepaidCreditService) EstimateCost(apiKey *APIKey, model string, body []byte) float64 {
+	if s.billingService == nil || model == "" {
+		return 0
+	}
+	inputTokens := estimatePrepaidInputTokens(body)
+	outputTokens := 0
+	for _, path := range []string{"max_tokens", "max_output_tokens", "max_completion_tokens", "generationConfig.maxOutputTokens"} {
+		if v := gjson.GetBytes(body, path); v.Exists() && v.Int() > 0 {
+			outputTokens = int(v.Int())
+			break
+		}
+	}
+	if outputTokens == 0 {
+		outputTokens = s.cfg.Billing.PrepaidHold.DefaultOutputTokens
+		if limit := s.billingService.GetModelTokenLimits(model).MaxOutput; limit > 0 {
+			outputTokens = min(outputTokens, limit)
+		}
+	}
+
+	multiplier := s.cfg.Default.RateMultiplier
+	if apiKey != nil && apiKey.Group != nil {
+		multiplier = apiKey.Group.RateMultiplier
+	}
+	cost, err := s.billingService.CalculateCost(model, UsageTokens{InputTokens: inputTokens, OutputTokens: outputTokens}, multiplier)
+	if err != nil {
+		return 0
+	}
+	return cost.ActualCost
+}
+
+// Reserve 预占请求的预估费用，每次上游调用一份预占（/v1/completions 的 n > 1 扇出为 n 份，全部预占成功才放行），
+// 每份随对应调用的扣费释放；返回空表示无需预占（未启用、无法预估或无预付费对象）。
+// 订阅模式下不预占用户余额，设置了额度的 API Key 始终预占。预占存储不可用时放行，由原有余额检查兜底。
+func (s *PrepaidCreditService) Reserve(ctx context.Context, apiKey *APIKey, subscription *UserSubscription, model string, body []byte) ([]*CreditHold, error) {
+	if !s.Enabled() || apiKey == nil {
+		return nil, nil
+	}
+	amount := s.EstimateCost(apiKey, model, body)
+	if amount <= 0 {
+		return nil, nil
+	}
+
+	targets := make([]CreditHoldTarget, 0, 2)
+	isSubscriptionMode := apiKey.Group != nil && apiKey.Group.IsSubscriptionType() && subscription != nil
+	if !isSubscriptionMode && s.billingCache != nil {
+		balance, err := s.billingCache.GetUserBalance(ctx, apiKey.UserID)
+		if err != nil {
+			logger.LegacyPrintf("service.prepaid_credit", "[PrepaidCredit] read balance failed for user %d: %v", apiKey.UserID, err)
+		} else {
+			targets = append(targets, CreditHoldTarget{ScopeType: CreditScopeUser, ScopeID: apiKey.UserID, Available: balance})
+		}
+	}
+	// API Key 总额度处于演练模式时超额照常放行，不预占
+	if apiKey.Quota > 0 && !s.cfg.Billing.LimitDryRun.APIKeyQuota {
+		// 认证缓存中的 quota_used 可能滞后，预占前读取最新值
+		quota, quotaUsed := apiKey.Quota, apiKey.QuotaUsed
+		if fresh, err := s.apiKeyRepo.GetByID(ctx, apiKey.ID); err == nil {
+			quota, quotaUsed = fresh.Quota, fresh.QuotaUsed
+		}
+		targets = append(targets, CreditHoldTarget{ScopeType: CreditScopeAPIKey, ScopeID: apiKey.ID, Available: quota - quotaUsed})
+	}
+	if len(targets) == 0 {
+		return nil, nil
+	}
+
+	shares := s.creditHoldShares(body)
+	expiresAt := time.Now().Add(time.Duration(s.cfg.Billing.PrepaidHold.HoldTTLSeconds) * time.Second)
+	holds := make([]*CreditHold, 0, shares)
+	for range shares {
+		hold := &CreditHold{ID: uuid.NewString(), Amount: amount, targets: targets, service: s}
+		failed, held, err := s.cache.Reserve(ctx, targets, hold.ID, amount, expiresAt)
+		if err != nil {
+			logger.LegacyPrintf("service.prepaid_credit", "[PrepaidCredit] reserve failed for api key %d: %v", apiKey.ID, err)
+			s.releaseAll(ctx, holds)
+			return nil, nil
+		}
+		if failed >= 0 && failed < len(targets) {
+			// 已写入的份额计入 held，提示中按整个请求的预估费用展示
+			s.releaseAll(ctx, holds)
+			return nil, insufficientCredit(targets[failed], held-amount*float64(len(holds)), amount*float64(shares))
+		}
+		holds = append(holds, hold)
+	}
+	return holds, nil
+}
+
+// creditHoldShares 请求的上游调用次数：/v1/completions 的 n > 1 由网关扇出为 n 次调用；
+// 超过 gateway.completions_max_choices 的请求会被拒绝，按 1 次计
+func (s *PrepaidCreditService) creditHoldShares(body []byte) int {
+	n := int(gjson.GetBytes(body, "n").Int())
+	if n <= 1 || n > s.cfg.Gateway.CompletionsMaxChoices {
+		return 1
+	}
+	return n
+}
+
+func (s *PrepaidCreditService) releaseAll(ctx context.Context, holds []*CreditHold) {
+	for _, hold := range holds {
+		s.Release(ctx, hold)
+	}
+}
+
+// Release 立即释放预占
+func (s *PrepaidCreditService) Release(ctx context.Context, hold *CreditHold) {
+	if s == nil || hold == nil {
+		return
+	}
+	if err := s.cache.Release(ctx, hold.targets, hold.ID, time.Time{}); err != nil {
+		logger.LegacyPrintf("service.prepaid_credit", "[PrepaidCredit] release hold %s failed: %v", hold.ID, err)
+	}
+}
+
+// GetAPIKeyCredit 返回 API Key 的额度、已用、预占与可用额度
+func (s *PrepaidCreditService) GetAPIKeyCredit(ctx context.Context, apiKeyID int64) (*APIKeyCredit, error) {
+	apiKey, err := s.apiKeyRepo.GetByID(ctx, apiKeyID)
+	if err != nil {
+		return nil, err
+	}
+	credit := &APIKeyCredit{APIKeyID: apiKey.ID, Quota: apiKey.Quota, QuotaUsed: apiKey.QuotaUsed, Available: -1}
+	if apiKey.Quota > 0 {
+		if s.cache != nil {
+			if held, err := s.cache.Held(ctx, CreditScopeAPIKey, apiKey.ID); err == nil {
+				credit.Held = held
+			}
+		}
+		credit.Available = max(apiKey.Quota-apiKey.QuotaUsed-credit.Held, 0)
+	}
+	return credit, nil
+}
+
+// TopUpAPIKey 为 API Key 充值预付费额度：在剩余额度基础上增加 amount（未设置额度的 Key 从已用金额起算），
+// 因额度耗尽被停用的 Key 自动恢复
+func (s *PrepaidCreditService) TopUpAPIKey(ctx context.Context, apiKeyID int64, amount float64, operatorID int64) (*APIKeyCredit, error) {
+	if amount <= 0 {
+		return nil, ErrInvalidCreditTopUp
+	}
+	apiKey, err := s.apiKeyRepo.GetByID(ctx, apiKeyID)
+	if err != nil {
+		return nil, err
+	}
+	// 充值在单条 SQL 内完成，不回写 quota_used，避免覆盖并发请求的扣费
+	if err := s.apiKeyRepo.AddQuota(ctx, apiKeyID, amount); err != nil {
+		return nil, fmt.Errorf("top up api key quota: %w", err)
+	}
+	if s.authCacheInvalidator != nil {
+		s.authCacheInvalidator.InvalidateAuthCacheByKey(ctx, apiKey.Key)
+	}
+	credit, err := s.GetAPIKeyCredit(ctx, apiKeyID)
+	if err != nil {
+		return nil, err
+	}
+	logger.LegacyPrintf("service.prepaid_credit", "AUDIT api key credit topped up: api_key=%d amount=%.4f quota=%.4f by=%d",
+		apiKeyID, amount, credit.Quota, operatorID)
+	return credit, nil
+}
+
+// estimatePrepaidInputTokens 按请求格式估算输入 token，复用 count_tokens / max_tokens 策略的本地估算
+func estimatePrepaidInputTokens(body []byte) int {
+	switch {
+	case gjson.GetBytes(body, "contents").Exists():
+		return estimateGeminiCountTokens(body)
+	case gjson.GetBytes(body, "input").Exists() || gjson.GetBytes(body, "instructions").Exists():
+		return estimateOpenAIResponsesInputTokens(body)
+	case gjson.GetBytes(body, "prompt").Exists():
+		// 旧版 Completions：prompt 为字符串或字符串数组
+		tokens := 0
+		prompt := gjson.GetBytes(body, "prompt")
+		if prompt.IsArray() {
+			prompt.ForEach(func(_, p gjson.Result) bool {
+				tokens += claude.EstimateTextTokens(p.String())
+				return true
+			})
+		} else {
+			tokens = claude.EstimateTextTokens(prompt.String())
+		}
+		return tokens
+	default:
+		// Anthropic Messages 与 Chat Completions（messages 结构一致）
+		tokens, _ := EstimateClaudeInputTokens(body)
+		return tokens
+	}
+}
+
+// deductBalanceCache 扣减余额缓存：持有预占的请求同步扣减，随后释放的预占不会出现余额尚未扣减的窗口；
+// 其余请求沿用异步扣减
+func deductBalanceCache(ctx context.Context, billingCache *BillingCacheService, hold *CreditHold, userID int64, amount float64) {
+	if billingCache == nil {
+		return
+	}
+	if hold == nil {
+		billingCache.QueueDeductBalance(userID, amount)
+		return
+	}
+	if err := billingCache.DeductBalanceCache(ctx, userID, amount); err != nil {
+		logger.LegacyPrintf("service.prepaid_credit", "[PrepaidCredit] deduct balance cache failed for user %d: %v", userID, err)
+	}
+}
+
+func insufficientCredit(target CreditHoldTarget, held, amount float64) error {
+	what := "account balance"
+	if target.ScopeType == CreditScopeAPIKey {
+		what = "API key credit"
+	}
+	return infraerrors.Newf(http.StatusPaymentRequired, ErrInsufficientCredit.Reason,
+		"insufficient %s: available $%.4f ($%.4f reserved by in-flight requests), estimated cost of this request $%.4f",
+		what, max(target.Available-held, 0), held, amount)
+}
@@ -0,0 +1,299 @@
+//go:build unit
+
+package service
+
+import (
+	"context"
+	"fmt"
+	"net/http"
+	"strings"
+	"testing"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	infraerrors "github.com/Wei-Shaw/sub2api/internal/pkg/errors"
+	"github.com/stretchr/testify/require"
+)
+
+type creditHoldEntry struct {
+	amount    float64
+	expiresAt time.Time
+}
+
+type creditHoldCacheStub struct {
+	holds map[string]map[string]creditHoldEntry
+}
+
+func newCreditHoldCacheStub() *creditHoldCacheStub {
+	return &creditHoldCacheStub{holds: map[string]map[string]creditHoldEntry{}}
+}
+
+func (c *creditHoldCacheStub) held(key string) float64 {
+	var total float64
+	for _, e := range c.holds[key] {
+		if e.expiresAt.After(time.Now()) {
+			total += e.amount
+		}
+	}
+	return total
+}
+
+func (c *creditHoldCacheStub) Reserve(_ context.Context, targets []CreditHoldTarget, holdID string, amount float64, expiresAt time.Time) (int, float64, error) {
+	for i, t := range targets {
+		held := c.held(spendingCapLocalKey(t.ScopeType, t.ScopeID))
+		if t.Available-held < amount {
+			return i, held, nil
+		}
+	}
+	for _, t := range targets {
+		key := spendingCapLocalKey(t.ScopeType, t.ScopeID)
+		if c.holds[key] == nil {
+			c.holds[key] = map[string]creditHoldEntry{}
+		}
+		c.holds[key][holdID] = creditHoldEntry{amount: amount, expiresAt: expiresAt}
+	}
+	return -1, 0, nil
+}
+
+func (c *creditHoldCacheStub) Release(_ context.Context, targets []CreditHoldTarget, holdID string, expiresAt time.Time) error {
+	for _, t := range targets {
+		key := spendingCapLocalKey(t.ScopeType, t.ScopeID)
+		if expiresAt.IsZero() {
+			delete(c.holds[key], holdID)
+			continue
+		}
+		if e, ok := c.holds[key][holdID]; ok {
+			e.expiresAt = expiresAt
+			c.holds[key][holdID] = e
+		}
+	}
+	return nil
+}
+
+func (c *creditHoldCacheStub) Held(_ context.Context, scopeType string, scopeID int64) (float64, error) {
+	return c.held(spendingCapLocalKey(scopeType, scopeID)), nil
+}
+
+// creditAPIKeyRepoStub 按 AddQuota 的 SQL 语义模拟充值；未覆盖 Update，调用时 panic
+type creditAPIKeyRepoStub struct {
+	*apiKeyRepoStub
+	topUps []float64
+}
+
+func (s *creditAPIKeyRepoStub) AddQuota(_ context.Context, _ int64, amount float64) error {
+	s.topUps = append(s.topUps, amount)
+	s.apiKey.Quota = max(s.apiKey.Quota, s.apiKey.QuotaUsed) + amount
+	if s.apiKey.Status == StatusAPIKeyQuotaExhausted {
+		s.apiKey.Status = StatusActive
+	}
+	return nil
+}
+
+func newPrepaidCreditServiceForTest(apiKey *APIKey) (*PrepaidCreditService, *creditHoldCacheStub, *creditAPIKeyRepoStub, *authCacheInvalidatorStub) {
+	cfg := &config.Config{}
+	cfg.Default.RateMultiplier = 1
+	cfg.Billing.PrepaidHold = config.PrepaidHoldConfig{Enabled: true, DefaultOutputTokens: 4096, HoldTTLSeconds: 900}
+	cfg.Gateway.CompletionsMaxChoices = 8
+	cache := newCreditHoldCacheStub()
+	repo := &creditAPIKeyRepoStub{apiKeyRepoStub: &apiKeyRepoStub{apiKey: apiKey}}
+	invalidator := &authCacheInvalidatorStub{}
+	svc := NewPrepaidCreditService(cache, nil, NewBillingService(cfg, nil), repo, invalidator, cfg)
+	return svc, cache, repo, invalidator
+}
+
+func TestPrepaidCreditService_EstimateCost(t *testing.T) {
+	svc, _, _, _ := newPrepaidCreditServiceForTest(nil)
+
+	small := svc.EstimateCost(nil, "claude-sonnet-4", []byte(`{"model":"claude-sonnet-4","max_tokens":100}`))
+	large := svc.EstimateCost(nil, "claude-sonnet-4", []byte(`{"model":"claude-sonnet-4","max_tokens":10000}`))
+	defaulted := svc.EstimateCost(nil, "claude-sonnet-4", []byte(`{"model":"claude-sonnet-4"}`))
+	require.Greater(t, small, 0.0)
+	require.Greater(t, large, small)
+	require.Greater(t, defaulted, small)
+
+	doubled := svc.EstimateCost(&APIKey{Group: &Group{RateMultiplier: 2}}, "claude-sonnet-4", []byte(`{"model":"claude-sonnet-4","max_tokens":100}`))
+	require.InDelta(t, small*2, doubled, 1e-12)
+
+	require.Zero(t, svc.EstimateCost(nil, "", []byte(`{}`)))
+}
+
+func TestPrepaidCreditService_EstimateCostIgnoresAttachmentPayloads(t *testing.T) {
+	svc, _, _, _ := newPrepaidCreditServiceForTest(nil)
+	payload := strings.Repeat("QUJDRA==", 512*1024) // 4MB base64
+
+	text := svc.EstimateCost(nil, "claude-sonnet-4", []byte(`{"model":"claude-sonnet-4","max_tokens":100,"messages":[{"role":"user","content":"hi"}]}`))
+	attachmentCost, err := svc.billingService.CalculateCost("claude-sonnet-4", UsageTokens{InputTokens: 2 * countTokensAttachmentEstimate}, 1)
+	require.NoError(t, err)
+
+	for _, body := range []string{
+		`{"model":"claude-sonnet-4","max_tokens":100,"messages":[{"role":"user","content":[{"type":"text","text":"hi"},{"type":"image","source":{"type":"base64","media_type":"image/png","data":"` + payload + `"}}]}]}`,
+		`{"model":"claude-sonnet-4","max_tokens":100,"messages":[{"role":"user","content":[{"type":"text","text":"hi"},{"type":"document","source":{"type":"base64","media_type":"application/pdf","data":"` + payload + `"}}]}]}`,
+		`{"model":"claude-sonnet-4","max_tokens":100,"messages":[{"role":"user","content":[{"type":"text","text":"hi"},{"type":"image_url","image_url":{"url":"data:image/png;base64,` + payload + `"}}]}]}`,
+		`{"model":"claude-sonnet-4","max_output_tokens":100,"input":[{"role":"user","content":[{"type":"input_text","text":"hi"},{"type":"input_file","file_data":"` + payload + `"}]}]}`,
+		`{"model":"claude-sonnet-4","generationConfig":{"maxOutputTokens":100},"contents":[{"role":"user","parts":[{"text":"hi"},{"inlineData":{"mimeType":"application/pdf","data":"` + payload + `"}}]}]}`,
+	} {
+		estimate := svc.EstimateCost(nil, "claude-sonnet-4", []byte(body))
+		require.Greater(t, estimate, 0.0)
+		require.Less(t, estimate, text+attachmentCost.ActualCost, body[:80])
+	}
+}
+
+func TestPrepaidCreditService_ReserveAgainstAPIKeyCredit(t *testing.T) {
+	ctx := context.Background()
+	body := []byte(`{"model":"claude-sonnet-4","max_tokens":1000}`)
+	probe, _, _, _ := newPrepaidCreditServiceForTest(nil)
+	estimate := probe.EstimateCost(nil, "claude-sonnet-4", body)
+
+	apiKey := &APIKey{ID: 5, UserID: 9, Key: "sk-test", Quota: estimate * 2.5}
+	svc, cache, _, _ := newPrepaidCreditServiceForTest(apiKey)
+
+	holds, err := svc.Reserve(ctx, apiKey, nil, "claude-sonnet-4", body)
+	require.NoError(t, err)
+	require.Len(t, holds, 1)
+	first := holds[0]
+	require.InDelta(t, estimate, first.Amount, 1e-12)
+
+	holds, err = svc.Reserve(ctx, apiKey, nil, "claude-sonnet-4", body)
+	require.NoError(t, err)
+	require.Len(t, holds, 1)
+
+	_, err = svc.Reserve(ctx, apiKey, nil, "claude-sonnet-4", body)
+	require.ErrorIs(t, err, ErrInsufficientCredit)
+	require.Equal(t, http.StatusPaymentRequired, infraerrors.Code(err))
+	require.Contains(t, infraerrors.Message(err), "API key credit")
+
+	// 释放即删除预占，不保留宽限期
+	svc.Release(ctx, first)
+	require.InDelta(t, estimate, cache.held(spendingCapLocalKey(CreditScopeAPIKey, 5)), 1e-12)
+	require.NotContains(t, cache.holds[spendingCapLocalKey(CreditScopeAPIKey, 5)], first.ID)
+	holds, err = svc.Reserve(ctx, apiKey, nil, "claude-sonnet-4", body)
+	require.NoError(t, err)
+	require.Len(t, holds, 1)
+}
+
+func TestPrepaidCreditService_ReservePerChoice(t *testing.T) {
+	ctx := context.Background()
+	single := []byte(`{"model":"claude-sonnet-4","prompt":"hi","max_tokens":1000}`)
+	fanOut := []byte(`{"model":"claude-sonnet-4","prompt":"hi","max_tokens":1000,"n":3}`)
+	probe, _, _, _ := newPrepaidCreditServiceForTest(nil)
+	estimate := probe.EstimateCost(nil, "claude-sonnet-4", single)
+
+	// 额度够两次调用：n=3 整体拒绝，不留下部分预占
+	apiKey := &APIKey{ID: 5, UserID: 9, Key: "sk-test", Quota: estimate * 2.5}
+	svc, cache, _, _ := newPrepaidCreditServiceForTest(apiKey)
+	_, err := svc.Reserve(ctx, apiKey, nil, "claude-sonnet-4", fanOut)
+	require.ErrorIs(t, err, ErrInsufficientCredit)
+	require.Contains(t, infraerrors.Message(err), fmt.Sprintf("$%.4f", estimate*3))
+	require.Zero(t, cache.held(spendingCapLocalKey(CreditScopeAPIKey, 5)))
+
+	apiKey.Quota = estimate * 3.5
+	holds, err := svc.Reserve(ctx, apiKey, nil, "claude-sonnet-4", fanOut)
+	require.NoError(t, err)
+	require.Len(t, holds, 3)
+	require.InDelta(t, estimate*3, cache.held(spendingCapLocalKey(CreditScopeAPIKey, 5)), 1e-12)
+
+	// 每次调用认领一份，各自随扣费释放
+	ctx = WithCreditHolds(ctx, holds)
+	for i := range holds {
+		hold := ClaimCreditHold(ctx)
+		require.Same(t, holds[i], hold)
+		hold.Settle(ctx)
+		require.InDelta(t, estimate*float64(len(holds)-i-1), cache.held(spendingCapLocalKey(CreditScopeAPIKey, 5)), 1e-12)
+	}
+	require.Nil(t, ClaimCreditHold(ctx))
+
+	// 超过 completions_max_choices 的请求会被拒绝，按一次调用预占
+	svc.cfg.Gateway.CompletionsMaxChoices = 2
+	holds, err = svc.Reserve(context.Background(), apiKey, nil, "claude-sonnet-4", fanOut)
+	require.NoError(t, err)
+	require.Len(t, holds, 1)
+}
+
+func TestPrepaidCreditService_BackToBackNearLimit(t *testing.T) {
+	body := []byte(`{"model":"claude-sonnet-4","max_tokens":1000}`)
+	probe, _, _, _ := newPrepaidCreditServiceForTest(nil)
+	estimate := probe.EstimateCost(nil, "claude-sonnet-4", body)
+
+	// 额度只够一个请求的预估费用，但实际费用扣除后仍够下一个请求
+	apiKey := &APIKey{ID: 5, UserID: 9, Key: "sk-test", Quota: estimate * 1.5}
+	svc, cache, repo, _ := newPrepaidCreditServiceForTest(apiKey)
+
+	holds, err := svc.Reserve(context.Background(), apiKey, nil, "claude-sonnet-4", body)
+	require.NoError(t, err)
+	require.Len(t, holds, 1)
+	hold := holds[0]
+	ctx := WithCreditHolds(context.Background(), holds)
+
+	_, err = svc.Reserve(ctx, apiKey, nil, "claude-sonnet-4", body)
+	require.ErrorIs(t, err, ErrInsufficientCredit)
+
+	// 处理器认领预占，每个预占只能认领一次
+	claimed := ClaimCreditHold(ctx)
+	require.Same(t, hold, claimed)
+	require.True(t, hold.Claimed())
+	require.Nil(t, ClaimCreditHold(ctx))
+
+	// 记录用量：写入实际费用后在同一步骤释放预占，不再与实际费用重复计入
+	repo.apiKey.QuotaUsed += estimate * 0.4
+	claimed.Settle(ctx)
+	require.Zero(t, cache.held(spendingCapLocalKey(CreditScopeAPIKey, 5)))
+
+	next, err := svc.Reserve(ctx, apiKey, nil, "claude-sonnet-4", body)
+	require.NoError(t, err)
+	require.Len(t, next, 1)
+
+	// 未预占时 Settle 为空操作
+	var none *CreditHold
+	none.Settle(ctx)
+	require.Nil(t, ClaimCreditHold(context.Background()))
+}
+
+func TestPrepaidCreditService_ReserveSkipsWhenNotApplicable(t *testing.T) {
+	ctx := context.Background()
+	body := []byte(`{"model":"claude-sonnet-4","max_tokens":1000}`)
+
+	// 未设置额度且无余额缓存：无预付费对象
+	unlimited := &APIKey{ID: 5, UserID: 9}
+	svc, _, _, _ := newPrepaidCreditServiceForTest(unlimited)
+	holds, err := svc.Reserve(ctx, unlimited, nil, "claude-sonnet-4", body)
+	require.NoError(t, err)
+	require.Empty(t, holds)
+
+	// 无法定价的模型不预占
+	limited := &APIKey{ID: 6, UserID: 9, Quota: 0.000001}
+	svc, _, _, _ = newPrepaidCreditServiceForTest(limited)
+	holds, err = svc.Reserve(ctx, limited, nil, "", body)
+	require.NoError(t, err)
+	require.Empty(t, holds)
+
+	// 未启用
+	svc.cfg.Billing.PrepaidHold.Enabled = false
+	holds, err = svc.Reserve(ctx, limited, nil, "claude-sonnet-4", body)
+	require.NoError(t, err)
+	require.Empty(t, holds)
+}
+
+func TestPrepaidCreditService_TopUpAPIKey(t *testing.T) {
+	ctx := context.Background()
+	apiKey := &APIKey{ID: 5, UserID: 9, Key: "sk-test", Quota: 10, QuotaUsed: 12, Status: StatusAPIKeyQuotaExhausted}
+	svc, _, repo, invalidator := newPrepaidCreditServiceForTest(apiKey)
+
+	_, err := svc.TopUpAPIKey(ctx, 5, 0, 1)
+	require.ErrorIs(t, err, ErrInvalidCreditTopUp)
+
+	credit, err := svc.TopUpAPIKey(ctx, 5, 5, 1)
+	require.NoError(t, err)
+	require.Equal(t, []float64{5}, repo.topUps)
+	require.Equal(t, StatusActive, repo.apiKey.Status)
+	require.InDelta(t, 12, repo.apiKey.QuotaUsed, 1e-9)
+	require.InDelta(t, 17, credit.Quota, 1e-9)
+	require.InDelta(t, 5, credit.Available, 1e-9)
+	require.Equal(t, []string{"sk-test"}, invalidator.keys)
+
+	// 未设置额度（不限额）的 Key 从已用金额起算
+	repo.apiKey = &APIKey{ID: 5, Key: "sk-test", QuotaUsed: 3, Status: StatusActive}
+	credit, err = svc.TopUpAPIKey(ctx, 5, 2, 1)
+	require.NoError(t, err)
+	require.InDelta(t, 5, credit.Quota, 1e-9)
+	require.InDelta(t, 2, credit.Available, 1e-9)
+}
@@ -37,6 +37,8 @@ type LiteLLMModelPricing struct {
 	Mode                                string  `json:"mode"`
 	SupportsPromptCaching               bool    `json:"supports_prompt_caching"`
 	OutputCostPerImage                  float64 `json:"output_cost_per_image"` // 图片生成模型每张图片价格
+	MaxInputTokens                      int     `json:"max_input_tokens"`      // 上下文窗口（输入 token 上限），0 表示未知
+	MaxOutputTokens                     int     `json:"max_output_tokens"`     // 单次输出 token 上限，0 表示未知
 }
 
 // PricingRemoteClient 远程价格数据获取接口
@@ -56,6 +58,8 @@ type LiteLLMRawEntry struct {
 	Mode                                string   `json:"mode"`
 	SupportsPromptCaching               bool     `json:"supports_prompt_caching"`
 	OutputCostPerImage                  *float64 `json:"output_cost_per_image"`
+	MaxInputTokens                      *float64 `json:"max_input_tokens"`
+	MaxOutputTokens                     *float64 `json:"max_output_tokens"`
 }
 
 // PricingService 动态价格服务
@@ -330,6 +334,12 @@ func (s *PricingService) parsePricingData(body []byte) (map[string]*LiteLLMModel
 		if entry.OutputCostPerImage != nil {
 			pricing.OutputCostPerImage = *entry.OutputCostPerImage
 		}
+		if entry.MaxInputTokens != nil {
+			pricing.MaxInputTokens = int(*entry.MaxInputTokens)
+		}
+		if entry.MaxOutputTokens != nil {
+			pricing.MaxOutputTokens = int(*entry.MaxOutputTokens)
+		}
 
 		result[modelName] = pricing
 	}
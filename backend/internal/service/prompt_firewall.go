@@ -0,0 +1,361 @@
+package service
+
+import (
+	"fmt"
+	"regexp"
+	"strconv"
+	"strings"
+	"sync"
+	"unicode/utf8"
+
+	infraerrors "github.com/Wei-Shaw/sub2api/internal/pkg/errors"
+	"github.com/tidwall/gjson"
+	"github.com/tidwall/sjson"
+)
+
+// 提示词防火墙命中后的处理动作，严重程度：block > sanitize > flag
+const (
+	// PromptFirewallActionBlock 拒绝请求（400），不转发上游
+	PromptFirewallActionBlock = "block"
+	// PromptFirewallActionFlag 放行请求，仅在使用记录中标记
+	PromptFirewallActionFlag = "flag"
+	// PromptFirewallActionSanitize 将命中的片段替换为 Replacement 后放行
+	PromptFirewallActionSanitize = "sanitize"
+)
+
+// 提示词防火墙规则类型
+const (
+	// PromptFirewallRuleKeyword 关键词（不区分大小写的子串匹配）
+	PromptFirewallRuleKeyword = "keyword"
+	// PromptFirewallRuleRegex Go RE2 正则表达式
+	PromptFirewallRuleRegex = "regex"
+)
+
+// 请求体格式，决定防火墙检查哪些字段
+const (
+	PromptFormatAnthropic       = "anthropic"
+	PromptFormatOpenAIResponses = "openai_responses"
+)
+
+const (
+	promptFirewallMaxRules          = 50
+	promptFirewallMaxPatternLen     = 1024
+	promptFirewallDefaultRedaction  = "[REDACTED]"
+	promptFirewallMaxCharsRuleName  = "max_chars"
+	promptFirewallJailbreakRuleName = "jailbreak"
+	// promptFirewallSummaryMaxLen 与 usage_logs.prompt_firewall 列宽一致
+	promptFirewallSummaryMaxLen = 255
+)
+
+var (
+	ErrInvalidPromptFirewallRule   = infraerrors.BadRequest("INVALID_PROMPT_FIREWALL_RULE", "invalid prompt firewall rule")
+	ErrInvalidPromptFirewallAction = infraerrors.BadRequest("INVALID_PROMPT_FIREWALL_ACTION", "prompt firewall action must be 'block', 'flag' or 'sanitize'")
+	ErrTooManyPromptFirewallRules  = infraerrors.BadRequest("TOO_MANY_PROMPT_FIREWALL_RULES", "too many prompt firewall rules")
+)
+
+// promptJailbreakPatterns 内置越狱提示词启发式规则，仅覆盖常见英文/中文话术，误报时可关闭 jailbreak_action
+var promptJailbreakPatterns = []*regexp.Regexp{
+	regexp.MustCompile(`(?i)\b(ignore|disregard|forget)\s+(all\s+)?(the\s+)?(previous|prior|above|earlier)\s+(instructions|prompts|rules|directions)`),
+	regexp.MustCompile(`(?i)\bdo\s+anything\s+now\b|\bDAN\s+mode\b|\byou\s+are\s+(now\s+)?DAN\b`),
+	regexp.MustCompile(`(?i)\b(enable|enter|activate)\s+developer\s+mode\b|\bdeveloper\s+mode\s+(enabled|output)\b`),
+	regexp.MustCompile(`(?i)\b(reveal|print|show|repeat|output)\s+(me\s+)?(your|the)\s+(system\s+prompt|hidden\s+instructions|initial\s+instructions)`),
+	regexp.MustCompile(`(?i)\bpretend\s+(that\s+)?(you\s+)?(have|there\s+are)\s+no\s+(restrictions|rules|guidelines|filters)`),
+	regexp.MustCompile(`忽略(之前|以上|上面|先前)的?(所有)?(指令|提示|规则)`),
+}
+
+// PromptFirewallRule 单条检查规则
+type PromptFirewallRule struct {
+	// Name 规则名称，出现在使用记录与日志中；为空时按序号生成
+	Name string `json:"name"`
+	// Type 规则类型：keyword（默认）/ regex
+	Type    string `json:"type"`
+	Pattern string `json:"pattern"`
+	// Action 命中后的动作：block（默认）/ flag / sanitize
+	Action string `json:"action"`
+	// Replacement sanitize 时的替换文本，默认 [REDACTED]
+	Replacement string `json:"replacement,omitempty"`
+}
+
+// PromptFirewallPolicy 入站提示词检查配置（APIKeyPolicy.Firewall）
+//
+// 仅检查 system / instructions 与用户消息中的文本，不检查助手消息、工具结果及图片等非文本内容。
+type PromptFirewallPolicy struct {
+	Rules []PromptFirewallRule `json:"rules,omitempty"`
+	// MaxChars 检查文本总字符数上限，0 表示不限制
+	MaxChars int `json:"max_chars,omitempty"`
+	// MaxCharsAction 超出 MaxChars 时的动作：block（默认）/ flag
+	MaxCharsAction string `json:"max_chars_action,omitempty"`
+	// JailbreakAction 内置越狱话术检测的动作，为空表示关闭
+	JailbreakAction string `json:"jailbreak_action,omitempty"`
+
+	compileOnce sync.Once
+	compiled    []*regexp.Regexp
+}
+
+// PromptFirewallDecision 一次检查的结果
+type PromptFirewallDecision struct {
+	// Action 命中规则中最严重的动作
+	Action string
+	// Rules 命中的规则名称（按命中顺序去重）
+	Rules []string
+	// Body Action 为 sanitize 时为改写后的请求体，否则为原请求体
+	Body []byte
+}
+
+// Summary 返回写入使用记录的摘要，如 "flag:pii_email,jailbreak"
+func (d *PromptFirewallDecision) Summary() string {
+	if d == nil {
+		return ""
+	}
+	summary := d.Action + ":" + strings.Join(d.Rules, ",")
+	if len(summary) > promptFirewallSummaryMaxLen {
+		summary = summary[:promptFirewallSummaryMaxLen]
+	}
+	return summary
+}
+
+// normalize 校验并规范化防火墙配置；返回 false 表示配置为空，调用方应置为 nil
+func (f *PromptFirewallPolicy) normalize() (bool, error) {
+	if len(f.Rules) > promptFirewallMaxRules {
+		return false, ErrTooManyPromptFirewallRules
+	}
+	for i := range f.Rules {
+		rule := &f.Rules[i]
+		rule.Name = strings.TrimSpace(rule.Name)
+		if rule.Name == "" {
+			rule.Name = "rule_" + strconv.Itoa(i+1)
+		}
+		rule.Type = strings.ToLower(strings.TrimSpace(rule.Type))
+		if rule.Type == "" {
+			rule.Type = PromptFirewallRuleKeyword
+		}
+		if rule.Pattern == "" || len(rule.Pattern) > promptFirewallMaxPatternLen {
+			return false, ErrInvalidPromptFirewallRule.WithMetadata(map[string]string{"rule": rule.Name})
+		}
+		switch rule.Type {
+		case PromptFirewallRuleKeyword:
+		case PromptFirewallRuleRegex:
+			if _, err := regexp.Compile(rule.Pattern); err != nil {
+				return false, ErrInvalidPromptFirewallRule.WithMetadata(map[string]string{"rule": rule.Name, "error": err.Error()})
+			}
+		default:
+			return false, ErrInvalidPromptFirewallRule.WithMetadata(map[string]string{"rule": rule.Name})
+		}
+		action, err := normalizePromptFirewallAction(rule.Action, PromptFirewallActionBlock)
+		if err != nil {
+			return false, err
+		}
+		rule.Action = action
+	}
+
+	if f.MaxChars < 0 {
+		f.MaxChars = 0
+	}
+	f.MaxCharsAction = strings.ToLower(strings.TrimSpace(f.MaxCharsAction))
+	switch f.MaxCharsAction {
+	case "":
+		if f.MaxChars > 0 {
+			f.MaxCharsAction = PromptFirewallActionBlock
+		}
+	case PromptFirewallActionBlock, PromptFirewallActionFlag:
+	default:
+		// 超长文本无法通过替换处理
+		return false, ErrInvalidPromptFirewallAction
+	}
+
+	jailbreak, err := normalizePromptFirewallAction(f.JailbreakAction, "")
+	if err != nil {
+		return false, err
+	}
+	f.JailbreakAction = jailbreak
+
+	return len(f.Rules) > 0 || f.MaxChars > 0 || f.JailbreakAction != "", nil
+}
+
+func normalizePromptFirewallAction(action, fallback string) (string, error) {
+	action = strings.ToLower(strings.TrimSpace(action))
+	switch action {
+	case "":
+		return fallback, nil
+	case PromptFirewallActionBlock, PromptFirewallActionFlag, PromptFirewallActionSanitize:
+		return action, nil
+	default:
+		return "", ErrInvalidPromptFirewallAction
+	}
+}
+
+// compiledRules 返回与 Rules 一一对应的正则；非法规则（未经 normalize 的历史数据）对应 nil 并被跳过
+func (f *PromptFirewallPolicy) compiledRules() []*regexp.Regexp {
+	f.compileOnce.Do(func() {
+		f.compiled = make([]*regexp.Regexp, len(f.Rules))
+		for i, rule := range f.Rules {
+			pattern := rule.Pattern
+			if rule.Type != PromptFirewallRuleRegex {
+				pattern = "(?i)" + regexp.QuoteMeta(pattern)
+			}
+			if re, err := regexp.Compile(pattern); err == nil {
+				f.compiled[i] = re
+			}
+		}
+	})
+	return f.compiled
+}
+
+// promptSegment 请求体中一段待检查的文本及其 JSON 路径
+type promptSegment struct {
+	path string
+	text string
+}
+
+// Inspect 检查请求体中的提示词文本；未命中任何规则时返回 nil
+func (f *PromptFirewallPolicy) Inspect(body []byte, format string) *PromptFirewallDecision {
+	if f == nil {
+		return nil
+	}
+	segments := collectPromptSegments(body, format)
+	if len(segments) == 0 {
+		return nil
+	}
+
+	decision := &PromptFirewallDecision{Body: body}
+	hit := func(name, action string) {
+		for _, existing := range decision.Rules {
+			if existing == name {
+				decision.escalate(action)
+				return
+			}
+		}
+		decision.Rules = append(decision.Rules, name)
+		decision.escalate(action)
+	}
+
+	if f.MaxChars > 0 {
+		total := 0
+		for _, seg := range segments {
+			total += utf8.RuneCountInString(seg.text)
+		}
+		if total > f.MaxChars {
+			hit(promptFirewallMaxCharsRuleName, f.MaxCharsAction)
+		}
+	}
+
+	sanitized := make([]string, len(segments))
+	for i, seg := range segments {
+		sanitized[i] = seg.text
+	}
+	check := func(name, action, replacement string, re *regexp.Regexp) {
+		for i := range sanitized {
+			if !re.MatchString(sanitized[i]) {
+				continue
+			}
+			hit(name, action)
+			if action == PromptFirewallActionSanitize {
+				if replacement == "" {
+					replacement = promptFirewallDefaultRedaction
+				}
+				sanitized[i] = re.ReplaceAllLiteralString(sanitized[i], replacement)
+			}
+		}
+	}
+
+	compiled := f.compiledRules()
+	for i, rule := range f.Rules {
+		if i < len(compiled) && compiled[i] != nil {
+			check(rule.Name, rule.Action, rule.Replacement, compiled[i])
+		}
+	}
+	if f.JailbreakAction != "" {
+		for _, re := range promptJailbreakPatterns {
+			check(promptFirewallJailbreakRuleName, f.JailbreakAction, "", re)
+		}
+	}
+
+	if decision.Action == "" {
+		return nil
+	}
+	if decision.Action == PromptFirewallActionSanitize {
+		out := body
+		for i, seg := range segments {
+			if sanitized[i] == seg.text {
+				continue
+			}
+			if next, err := sjson.SetBytes(out, seg.path, sanitized[i]); err == nil {
+				out = next
+			}
+		}
+		decision.Body = out
+	}
+	return decision
+}
+
+func (d *PromptFirewallDecision) escalate(action string) {
+	if promptFirewallSeverity(action) > promptFirewallSeverity(d.Action) {
+		d.Action = action
+	}
+}
+
+func promptFirewallSeverity(action string) int {
+	switch action {
+	case PromptFirewallActionBlock:
+		return 3
+	case PromptFirewallActionSanitize:
+		return 2
+	case PromptFirewallActionFlag:
+		return 1
+	default:
+		return 0
+	}
+}
+
+// collectPromptSegments 按请求格式收集 system / instructions 与用户消息中的文本
+func collectPromptSegments(body []byte, format string) []promptSegment {
+	var segments []promptSegment
+	addText := func(path string, value gjson.Result) {
+		if value.Type == gjson.String && value.String() != "" {
+			segments = append(segments, promptSegment{path: path, text: value.String()})
+		}
+	}
+	// addContent 处理字符串或内容块数组形式的 content
+	addContent := func(path string, content gjson.Result, textTypes ...string) {
+		if content.Type == gjson.String {
+			addText(path, content)
+			return
+		}
+		if !content.IsArray() {
+			return
+		}
+		for i, part := range content.Array() {
+			partType := part.Get("type").String()
+			for _, t := range textTypes {
+				if partType == t {
+					addText(fmt.Sprintf("%s.%d.text", path, i), part.Get("text"))
+					break
+				}
+			}
+		}
+	}
+
+	switch format {
+	case PromptFormatAnthropic:
+		addContent("system", gjson.GetBytes(body, "system"), "text")
+		for i, msg := range gjson.GetBytes(body, "messages").Array() {
+			if msg.Get("role").String() == "user" {
+				addContent(fmt.Sprintf("messages.%d.content", i), msg.Get("content"), "text")
+			}
+		}
+	case PromptFormatOpenAIResponses:
+		addText("instructions", gjson.GetBytes(body, "instructions"))
+		input := gjson.GetBytes(body, "input")
+		if input.Type == gjson.String {
+			addText("input", input)
+			break
+		}
+		for i, item := range input.Array() {
+			switch item.Get("role").String() {
+			case "user", "system", "developer":
+				addContent(fmt.Sprintf("input.%d.content", i), item.Get("content"), "input_text", "text")
+			}
+		}
+	}
+	return segments
+}
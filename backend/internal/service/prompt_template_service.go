@@ -0,0 +1,478 @@
+package service
+
+import (
+	"bytes"
+	"context"
+	"encoding/json"
+	"net/http"
+	"regexp"
+	"strings"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	infraerrors "github.com/Wei-Shaw/sub2api/internal/pkg/errors"
+	"github.com/google/uuid"
+	"github.com/tidwall/gjson"
+	"github.com/tidwall/sjson"
+)
+
+const (
+	// PromptTemplateIDPrefix 模板 ID 前缀；模板名称不允许使用该前缀，因此 template_id 可同时按 ID 或名称查找
+	PromptTemplateIDPrefix = "tmpl_"
+
+	promptTemplateMaxVariables      = 50
+	promptTemplateMaxDescriptionLen = 1000
+)
+
+var (
+	ErrPromptTemplateDisabled   = infraerrors.BadRequest("PROMPT_TEMPLATE_DISABLED", "prompt templates are not enabled")
+	ErrPromptTemplateNotFound   = infraerrors.NotFound("PROMPT_TEMPLATE_NOT_FOUND", "prompt template not found")
+	ErrPromptTemplateNameExists = infraerrors.Conflict("PROMPT_TEMPLATE_NAME_EXISTS", "a prompt template with this name already exists")
+	ErrPromptTemplateLimit      = infraerrors.BadRequest("PROMPT_TEMPLATE_LIMIT_EXCEEDED", "prompt template limit reached")
+	ErrPromptTemplateTooLarge   = infraerrors.New(http.StatusRequestEntityTooLarge, "PROMPT_TEMPLATE_TOO_LARGE", "prompt template is too large")
+)
+
+var (
+	promptTemplateNamePattern     = regexp.MustCompile(`^[A-Za-z0-9][A-Za-z0-9._-]{0,127}$`)
+	promptTemplateVariablePattern = regexp.MustCompile(`^[A-Za-z_][A-Za-z0-9_]{0,63}$`)
+	// promptTemplatePlaceholder 匹配 {{name}} 与 {{ name }}
+	promptTemplatePlaceholder = regexp.MustCompile(`\{\{\s*([A-Za-z_][A-Za-z0-9_]*)\s*\}\}`)
+)
+
+// PromptTemplate 用户的命名提示词模板
+type PromptTemplate struct {
+	ID            string    `json:"id"`
+	UserID        int64     `json:"-"`
+	Name          string    `json:"name"`
+	Description   string    `json:"description"`
+	LatestVersion int       `json:"latest_version"`
+	CreatedAt     time.Time `json:"created_at"`
+	UpdatedAt     time.Time `json:"updated_at"`
+}
+
+// PromptTemplateMessage 模板中的一条消息，content 可含 {{变量}} 占位符
+type PromptTemplateMessage struct {
+	Role    string `json:"role"`
+	Content string `json:"content"`
+}
+
+// PromptTemplateVariable 模板变量声明；Default 为 nil 时请求必须提供该变量
+type PromptTemplateVariable struct {
+	Name        string  `json:"name"`
+	Description string  `json:"description,omitempty"`
+	Default     *string `json:"default,omitempty"`
+}
+
+// PromptTemplateVersion 模板的一个版本，创建后不可修改
+type PromptTemplateVersion struct {
+	Version   int                      `json:"version"`
+	System    string                   `json:"system,omitempty"`
+	Messages  []PromptTemplateMessage  `json:"messages,omitempty"`
+	Variables []PromptTemplateVariable `json:"variables,omitempty"`
+	CreatedAt time.Time                `json:"created_at"`
+}
+
+// PromptTemplateInput 创建模板或发布新版本的参数；发布新版本时忽略 Name，Description 为 nil 表示不修改
+type PromptTemplateInput struct {
+	Name        string
+	Description *string
+	System      string
+	Messages    []PromptTemplateMessage
+	Variables   []PromptTemplateVariable
+}
+
+// PromptTemplateRepository 提示词模板存储
+type PromptTemplateRepository interface {
+	// Create 创建模板及其第 1 个版本，同名模板已存在时返回 ErrPromptTemplateNameExists
+	Create(ctx context.Context, tmpl *PromptTemplate, version *PromptTemplateVersion) error
+	// AddVersion 以 latest_version + 1 发布新版本并返回更新后的模板（version.Version 同时回填）；
+	// description 为 nil 时不修改
+	AddVersion(ctx context.Context, userID int64, ref string, description *string, version *PromptTemplateVersion) (*PromptTemplate, error)
+	// Get 按 ID 或名称查询模板，不存在时返回 ErrPromptTemplateNotFound
+	Get(ctx context.Context, userID int64, ref string) (*PromptTemplate, error)
+	// GetVersion 按 ID 或名称查询模板的指定版本（version <= 0 表示最新版本），不存在时返回 ErrPromptTemplateNotFound
+	GetVersion(ctx context.Context, userID int64, ref string, version int) (*PromptTemplateVersion, error)
+	// List 按最近更新时间列出用户的模板
+	List(ctx context.Context, userID int64) ([]*PromptTemplate, error)
+	// ListVersions 按版本号倒序列出模板的全部版本，模板不存在时返回 ErrPromptTemplateNotFound
+	ListVersions(ctx context.Context, userID int64, ref string) ([]*PromptTemplateVersion, error)
+	Count(ctx context.Context, userID int64) (int, error)
+	// Delete 删除模板及其全部版本，不存在时返回 ErrPromptTemplateNotFound
+	Delete(ctx context.Context, userID int64, ref string) error
+}
+
+// PromptTemplateService 管理提示词模板，并在网关请求中按 template_id 渲染模板
+type PromptTemplateService struct {
+	repo PromptTemplateRepository
+	cfg  config.PromptTemplatesConfig
+}
+
+// NewPromptTemplateService 创建提示词模板服务
+func NewPromptTemplateService(repo PromptTemplateRepository, cfg *config.Config) *PromptTemplateService {
+	s := &PromptTemplateService{repo: repo}
+	if cfg != nil {
+		s.cfg = cfg.PromptTemplates
+	}
+	return s
+}
+
+// Enabled 是否启用提示词模板
+func (s *PromptTemplateService) Enabled() bool {
+	return s != nil && s.cfg.Enabled && s.repo != nil
+}
+
+// Create 创建模板（版本 1）
+func (s *PromptTemplateService) Create(ctx context.Context, userID int64, in *PromptTemplateInput) (*PromptTemplate, *PromptTemplateVersion, error) {
+	if !s.Enabled() {
+		return nil, nil, ErrPromptTemplateDisabled
+	}
+	name := strings.TrimSpace(in.Name)
+	if !promptTemplateNamePattern.MatchString(name) || strings.HasPrefix(name, PromptTemplateIDPrefix) {
+		return nil, nil, infraerrors.Newf(http.StatusBadRequest, "PROMPT_TEMPLATE_INVALID",
+			"name must be 1-128 letters, digits, '.', '_' or '-' and must not start with %q", PromptTemplateIDPrefix)
+	}
+	description := ""
+	if in.Description != nil {
+		description = strings.TrimSpace(*in.Description)
+	}
+	if err := validatePromptTemplateDescription(description); err != nil {
+		return nil, nil, err
+	}
+	version, err := s.buildVersion(in)
+	if err != nil {
+		return nil, nil, err
+	}
+
+	count, err := s.repo.Count(ctx, userID)
+	if err != nil {
+		return nil, nil, err
+	}
+	if count >= s.cfg.MaxTemplatesPerUser {
+		return nil, nil, ErrPromptTemplateLimit
+	}
+
+	now := time.Now().UTC()
+	version.Version = 1
+	version.CreatedAt = now
+	tmpl := &PromptTemplate{
+		ID:            PromptTemplateIDPrefix + strings.ReplaceAll(uuid.NewString(), "-", ""),
+		UserID:        userID,
+		Name:          name,
+		Description:   description,
+		LatestVersion: 1,
+		CreatedAt:     now,
+		UpdatedAt:     now,
+	}
+	if err := s.repo.Create(ctx, tmpl, version); err != nil {
+		return nil, nil, err
+	}
+	return tmpl, version, nil
+}
+
+// Update 发布模板的新版本，历史版本保留且仍可按 template_version 引用
+func (s *PromptTemplateService) Update(ctx context.Context, userID int64, ref string, in *PromptTemplateInput) (*PromptTemplate, *PromptTemplateVersion, error) {
+	if !s.Enabled() {
+		return nil, nil, ErrPromptTemplateDisabled
+	}
+	var description *string
+	if in.Description != nil {
+		d := strings.TrimSpace(*in.Description)
+		if err := validatePromptTemplateDescription(d); err != nil {
+			return nil, nil, err
+		}
+		description = &d
+	}
+	version, err := s.buildVersion(in)
+	if err != nil {
+		return nil, nil, err
+	}
+	version.CreatedAt = time.Now().UTC()
+	tmpl, err := s.repo.AddVersion(ctx, userID, ref, description, version)
+	if err != nil {
+		return nil, nil, err
+	}
+	return tmpl, version, nil
+}
+
+// Get 查询模板及其指定版本（version <= 0 表示最新版本）
+func (s *PromptTemplateService) Get(ctx context.Context, userID int64, ref string, version int) (*PromptTemplate, *PromptTemplateVersion, error) {
+	if !s.Enabled() {
+		return nil, nil, ErrPromptTemplateDisabled
+	}
+	tmpl, err := s.repo.Get(ctx, userID, ref)
+	if err != nil {
+		return nil, nil, err
+	}
+	v, err := s.repo.GetVersion(ctx, userID, tmpl.ID, version)
+	if err != nil {
+		return nil, nil, err
+	}
+	return tmpl, v, nil
+}
+
+// List 列出用户的模板
+func (s *PromptTemplateService) List(ctx context.Context, userID int64) ([]*PromptTemplate, error) {
+	if !s.Enabled() {
+		return nil, ErrPromptTemplateDisabled
+	}
+	return s.repo.List(ctx, userID)
+}
+
+// Versions 列出模板的全部版本（最新在前）
+func (s *PromptTemplateService) Versions(ctx context.Context, userID int64, ref string) ([]*PromptTemplateVersion, error) {
+	if !s.Enabled() {
+		return nil, ErrPromptTemplateDisabled
+	}
+	return s.repo.ListVersions(ctx, userID, ref)
+}
+
+// Delete 删除模板及其全部版本
+func (s *PromptTemplateService) Delete(ctx context.Context, userID int64, ref string) error {
+	if !s.Enabled() {
+		return ErrPromptTemplateDisabled
+	}
+	return s.repo.Delete(ctx, userID, ref)
+}
+
+func validatePromptTemplateDescription(description string) error {
+	if len([]rune(description)) > promptTemplateMaxDescriptionLen {
+		return infraerrors.Newf(http.StatusBadRequest, "PROMPT_TEMPLATE_INVALID", "description must be at most %d characters", promptTemplateMaxDescriptionLen)
+	}
+	return nil
+}
+
+// buildVersion 校验模板内容：消息角色只能是 user / assistant，变量名合法且不重复，
+// 占位符引用的变量必须已声明。
+func (s *PromptTemplateService) buildVersion(in *PromptTemplateInput) (*PromptTemplateVersion, error) {
+	invalid := func(format string, args ...any) error {
+		return infraerrors.Newf(http.StatusBadRequest, "PROMPT_TEMPLATE_INVALID", format, args...)
+	}
+	if strings.TrimSpace(in.System) == "" && len(in.Messages) == 0 {
+		return nil, invalid("template must define a system prompt or at least one message")
+	}
+	for i, msg := range in.Messages {
+		if msg.Role != "user" && msg.Role != "assistant" {
+			return nil, invalid("messages[%d].role must be \"user\" or \"assistant\"", i)
+		}
+		if strings.TrimSpace(msg.Content) == "" {
+			return nil, invalid("messages[%d].content is required", i)
+		}
+	}
+	if len(in.Variables) > promptTemplateMaxVariables {
+		return nil, invalid("template may declare at most %d variables", promptTemplateMaxVariables)
+	}
+	declared := make(map[string]bool, len(in.Variables))
+	for i, v := range in.Variables {
+		if !promptTemplateVariablePattern.MatchString(v.Name) {
+			return nil, invalid("variables[%d].name %q is not a valid identifier", i, v.Name)
+		}
+		if declared[v.Name] {
+			return nil, invalid("variable %q is declared more than once", v.Name)
+		}
+		declared[v.Name] = true
+	}
+	texts := []string{in.System}
+	for _, msg := range in.Messages {
+		texts = append(texts, msg.Content)
+	}
+	for _, text := range texts {
+		for _, m := range promptTemplatePlaceholder.FindAllStringSubmatch(text, -1) {
+			if !declared[m[1]] {
+				return nil, invalid("placeholder {{%s}} refers to an undeclared variable", m[1])
+			}
+		}
+	}
+
+	version := &PromptTemplateVersion{
+		System:    in.System,
+		Messages:  in.Messages,
+		Variables: in.Variables,
+	}
+	if raw, err := json.Marshal(version); err == nil && len(raw) > s.cfg.MaxTemplateBytes {
+		return nil, ErrPromptTemplateTooLarge
+	}
+	return version, nil
+}
+
+// Apply 请求携带 template_id 时渲染模板：system 拼接到请求 system / instructions 之前，
+// 模板消息插入到 messages / input 之前，并移除 template_* 字段。
+//
+// template_version 缺省时使用最新版本；template_variables 的值可以是字符串、数字或布尔值。
+// 未携带 template_id 时原样返回请求体。
+func (s *PromptTemplateService) Apply(ctx context.Context, format string, userID int64, body []byte) ([]byte, error) {
+	if !bytes.Contains(body, []byte(`"template_id"`)) {
+		return body, nil
+	}
+	ref := gjson.GetBytes(body, "template_id")
+	if !ref.Exists() {
+		return body, nil
+	}
+	if !s.Enabled() {
+		return body, ErrPromptTemplateDisabled
+	}
+	if ref.Type != gjson.String || strings.TrimSpace(ref.String()) == "" {
+		return body, infraerrors.BadRequest("PROMPT_TEMPLATE_INVALID_REQUEST", "template_id must be a non-empty string")
+	}
+
+	versionNum := 0
+	if v := gjson.GetBytes(body, "template_version"); v.Exists() && v.Type != gjson.Null {
+		if v.Type != gjson.Number || v.Int() <= 0 || float64(v.Int()) != v.Float() {
+			return body, infraerrors.BadRequest("PROMPT_TEMPLATE_INVALID_REQUEST", "template_version must be a positive integer")
+		}
+		versionNum = int(v.Int())
+	}
+	values := map[string]string{}
+	if vars := gjson.GetBytes(body, "template_variables"); vars.Exists() && vars.Type != gjson.Null {
+		if !vars.IsObject() {
+			return body, infraerrors.BadRequest("PROMPT_TEMPLATE_INVALID_REQUEST", "template_variables must be an object")
+		}
+		var err error
+		vars.ForEach(func(key, value gjson.Result) bool {
+			switch value.Type {
+			case gjson.String:
+				values[key.String()] = value.String()
+			case gjson.Number, gjson.True, gjson.False:
+				values[key.String()] = value.Raw
+			default:
+				err = infraerrors.Newf(http.StatusBadRequest, "PROMPT_TEMPLATE_INVALID_REQUEST", "template_variables.%s must be a string, number or boolean", key.String())
+				return false
+			}
+			return true
+		})
+		if err != nil {
+			return body, err
+		}
+	}
+
+	version, err := s.repo.GetVersion(ctx, userID, ref.String(), versionNum)
+	if err != nil {
+		if infraerrors.IsNotFound(err) {
+			if versionNum > 0 {
+				return body, infraerrors.Newf(http.StatusNotFound, "PROMPT_TEMPLATE_NOT_FOUND", "version %d of prompt template %q not found", versionNum, ref.String())
+			}
+			return body, infraerrors.Newf(http.StatusNotFound, "PROMPT_TEMPLATE_NOT_FOUND", "prompt template %q not found", ref.String())
+		}
+		return body, err
+	}
+	resolved, err := resolvePromptTemplateVariables(version.Variables, values)
+	if err != nil {
+		return body, err
+	}
+	render := func(text string) string {
+		return promptTemplatePlaceholder.ReplaceAllStringFunc(text, func(m string) string {
+			return resolved[promptTemplatePlaceholder.FindStringSubmatch(m)[1]]
+		})
+	}
+	system := render(version.System)
+	messages := make([]PromptTemplateMessage, len(version.Messages))
+	for i, msg := range version.Messages {
+		messages[i] = PromptTemplateMessage{Role: msg.Role, Content: render(msg.Content)}
+	}
+
+	for _, field := range []string{"template_id", "template_version", "template_variables"} {
+		if body, err = sjson.DeleteBytes(body, field); err != nil {
+			return body, err
+		}
+	}
+	switch format {
+	case PromptFormatAnthropic:
+		return applyAnthropicPromptTemplate(body, system, messages)
+	case PromptFormatOpenAIResponses:
+		return applyResponsesPromptTemplate(body, system, messages)
+	}
+	return body, nil
+}
+
+// resolvePromptTemplateVariables 合并请求变量与默认值；缺少必填变量或传入未声明的变量时返回错误
+func resolvePromptTemplateVariables(declared []PromptTemplateVariable, values map[string]string) (map[string]string, error) {
+	known := make(map[string]bool, len(declared))
+	resolved := make(map[string]string, len(declared))
+	var missing []string
+	for _, v := range declared {
+		known[v.Name] = true
+		switch value, ok := values[v.Name]; {
+		case ok:
+			resolved[v.Name] = value
+		case v.Default != nil:
+			resolved[v.Name] = *v.Default
+		default:
+			missing = append(missing, v.Name)
+		}
+	}
+	if len(missing) > 0 {
+		return nil, infraerrors.Newf(http.StatusBadRequest, "PROMPT_TEMPLATE_VARIABLE_MISSING", "missing template variables: %s", strings.Join(missing, ", "))
+	}
+	for name := range values {
+		if !known[name] {
+			return nil, infraerrors.Newf(http.StatusBadRequest, "PROMPT_TEMPLATE_VARIABLE_UNKNOWN", "template does not declare variable %q", name)
+		}
+	}
+	return resolved, nil
+}
+
+func applyAnthropicPromptTemplate(body []byte, system string, messages []PromptTemplateMessage) ([]byte, error) {
+	if system != "" {
+		if next, ok := ApplyClaudeSystemPrompt(body, &SystemPromptTemplate{Mode: SystemPromptModePrepend, Template: system}); ok {
+			body = next
+		}
+	}
+	if len(messages) == 0 {
+		return body, nil
+	}
+	items := make([][]byte, 0, len(messages))
+	for _, msg := range messages {
+		raw, err := json.Marshal(msg)
+		if err != nil {
+			return body, err
+		}
+		items = append(items, raw)
+	}
+	return prependPromptTemplateItems(body, "messages", items)
+}
+
+func applyResponsesPromptTemplate(body []byte, system string, messages []PromptTemplateMessage) ([]byte, error) {
+	var err error
+	if system != "" {
+		instructions := joinSystemPrompt(gjson.GetBytes(body, "instructions").String(), &SystemPromptTemplate{Mode: SystemPromptModePrepend, Template: system})
+		if body, err = sjson.SetBytes(body, "instructions", instructions); err != nil {
+			return body, err
+		}
+	}
+	if len(messages) == 0 {
+		return body, nil
+	}
+	// 字符串形式的 input 等价于一条 user 消息
+	if input := gjson.GetBytes(body, "input"); input.Type == gjson.String {
+		raw, _ := json.Marshal([]map[string]string{{"type": "message", "role": "user", "content": input.String()}})
+		if body, err = sjson.SetRawBytes(body, "input", raw); err != nil {
+			return body, err
+		}
+	}
+	items := make([][]byte, 0, len(messages))
+	for _, msg := range messages {
+		raw, err := json.Marshal(map[string]string{"type": "message", "role": msg.Role, "content": msg.Content})
+		if err != nil {
+			return body, err
+		}
+		items = append(items, raw)
+	}
+	return prependPromptTemplateItems(body, "input", items)
+}
+
+// prependPromptTemplateItems 把 items 插入到 path 数组之前（字段缺失时新建数组）
+func prependPromptTemplateItems(body []byte, path string, items [][]byte) ([]byte, error) {
+	existing := gjson.GetBytes(body, path)
+	if existing.Exists() && existing.Type != gjson.Null && !existing.IsArray() {
+		return body, infraerrors.Newf(http.StatusBadRequest, "PROMPT_TEMPLATE_INVALID_REQUEST", "%s must be an array when using a prompt template", path)
+	}
+	var buf bytes.Buffer
+	buf.WriteByte('[')
+	buf.Write(bytes.Join(items, []byte(",")))
+	existing.ForEach(func(_, item gjson.Result) bool {
+		buf.WriteByte(',')
+		buf.WriteString(item.Raw)
+		return true
+	})
+	buf.WriteByte(']')
+	return sjson.SetRawBytes(body, path, buf.Bytes())
+}
@@ -0,0 +1,238 @@
+//go:build unit
+
+package service
+
+import (
+	"context"
+	"errors"
+	"net/http"
+	"testing"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	infraerrors "github.com/Wei-Shaw/sub2api/internal/pkg/errors"
+	"github.com/stretchr/testify/require"
+	"github.com/tidwall/gjson"
+)
+
+type promptTemplateRepoStub struct {
+	templates map[string]*PromptTemplate
+	versions  map[string][]*PromptTemplateVersion
+}
+
+func newPromptTemplateRepoStub() *promptTemplateRepoStub {
+	return &promptTemplateRepoStub{templates: map[string]*PromptTemplate{}, versions: map[string][]*PromptTemplateVersion{}}
+}
+
+func (r *promptTemplateRepoStub) find(userID int64, ref string) *PromptTemplate {
+	for _, tmpl := range r.templates {
+		if tmpl.UserID == userID && (tmpl.ID == ref || tmpl.Name == ref) {
+			return tmpl
+		}
+	}
+	return nil
+}
+
+func (r *promptTemplateRepoStub) Create(_ context.Context, tmpl *PromptTemplate, version *PromptTemplateVersion) error {
+	if r.find(tmpl.UserID, tmpl.Name) != nil {
+		return ErrPromptTemplateNameExists
+	}
+	r.templates[tmpl.ID] = tmpl
+	r.versions[tmpl.ID] = []*PromptTemplateVersion{version}
+	return nil
+}
+
+func (r *promptTemplateRepoStub) AddVersion(_ context.Context, userID int64, ref string, description *string, version *PromptTemplateVersion) (*PromptTemplate, error) {
+	tmpl := r.find(userID, ref)
+	if tmpl == nil {
+		return nil, ErrPromptTemplateNotFound
+	}
+	tmpl.LatestVersion++
+	if description != nil {
+		tmpl.Description = *description
+	}
+	version.Version = tmpl.LatestVersion
+	r.versions[tmpl.ID] = append(r.versions[tmpl.ID], version)
+	return tmpl, nil
+}
+
+func (r *promptTemplateRepoStub) Get(_ context.Context, userID int64, ref string) (*PromptTemplate, error) {
+	if tmpl := r.find(userID, ref); tmpl != nil {
+		return tmpl, nil
+	}
+	return nil, ErrPromptTemplateNotFound
+}
+
+func (r *promptTemplateRepoStub) GetVersion(_ context.Context, userID int64, ref string, version int) (*PromptTemplateVersion, error) {
+	tmpl := r.find(userID, ref)
+	if tmpl == nil {
+		return nil, ErrPromptTemplateNotFound
+	}
+	if version <= 0 {
+		version = tmpl.LatestVersion
+	}
+	for _, v := range r.versions[tmpl.ID] {
+		if v.Version == version {
+			return v, nil
+		}
+	}
+	return nil, ErrPromptTemplateNotFound
+}
+
+func (r *promptTemplateRepoStub) List(_ context.Context, userID int64) ([]*PromptTemplate, error) {
+	out := make([]*PromptTemplate, 0)
+	for _, tmpl := range r.templates {
+		if tmpl.UserID == userID {
+			out = append(out, tmpl)
+		}
+	}
+	return out, nil
+}
+
+func (r *promptTemplateRepoStub) ListVersions(_ context.Context, userID int64, ref string) ([]*PromptTemplateVersion, error) {
+	tmpl := r.find(userID, ref)
+	if tmpl == nil {
+		return nil, ErrPromptTemplateNotFound
+	}
+	return r.versions[tmpl.ID], nil
+}
+
+func (r *promptTemplateRepoStub) Count(_ context.Context, userID int64) (int, error) {
+	list, _ := r.List(context.Background(), userID)
+	return len(list), nil
+}
+
+func (r *promptTemplateRepoStub) Delete(_ context.Context, userID int64, ref string) error {
+	tmpl := r.find(userID, ref)
+	if tmpl == nil {
+		return ErrPromptTemplateNotFound
+	}
+	delete(r.templates, tmpl.ID)
+	delete(r.versions, tmpl.ID)
+	return nil
+}
+
+func newTestPromptTemplateService(repo PromptTemplateRepository) *PromptTemplateService {
+	return NewPromptTemplateService(repo, &config.Config{PromptTemplates: config.PromptTemplatesConfig{
+		Enabled:             true,
+		MaxTemplatesPerUser: 2,
+		MaxTemplateBytes:    4096,
+	}})
+}
+
+func TestPromptTemplateService_CreateValidation(t *testing.T) {
+	svc := newTestPromptTemplateService(newPromptTemplateRepoStub())
+	ctx := context.Background()
+	lang := "English"
+
+	cases := []struct {
+		name string
+		in   PromptTemplateInput
+	}{
+		{"empty", PromptTemplateInput{Name: "empty"}},
+		{"bad name", PromptTemplateInput{Name: "has space", System: "x"}},
+		{"id prefix", PromptTemplateInput{Name: PromptTemplateIDPrefix + "x", System: "x"}},
+		{"system role", PromptTemplateInput{Name: "role", Messages: []PromptTemplateMessage{{Role: "system", Content: "x"}}}},
+		{"undeclared", PromptTemplateInput{Name: "undeclared", System: "Reply in {{ lang }}"}},
+		{"duplicate var", PromptTemplateInput{Name: "dup", System: "{{lang}}", Variables: []PromptTemplateVariable{{Name: "lang"}, {Name: "lang"}}}},
+	}
+	for _, tc := range cases {
+		_, _, err := svc.Create(ctx, 1, &tc.in)
+		require.Error(t, err, tc.name)
+		require.Equal(t, http.StatusBadRequest, infraerrors.Code(err), tc.name)
+	}
+
+	in := &PromptTemplateInput{Name: "translator", System: "Reply in {{ lang }}", Variables: []PromptTemplateVariable{{Name: "lang", Default: &lang}}}
+	tmpl, version, err := svc.Create(ctx, 1, in)
+	require.NoError(t, err)
+	require.Equal(t, 1, tmpl.LatestVersion)
+	require.Equal(t, 1, version.Version)
+
+	_, _, err = svc.Create(ctx, 1, in)
+	require.True(t, errors.Is(err, ErrPromptTemplateNameExists))
+
+	_, _, err = svc.Create(ctx, 1, &PromptTemplateInput{Name: "second", System: "x"})
+	require.NoError(t, err)
+	_, _, err = svc.Create(ctx, 1, &PromptTemplateInput{Name: "third", System: "x"})
+	require.True(t, errors.Is(err, ErrPromptTemplateLimit))
+}
+
+func TestPromptTemplateService_ApplyAnthropic(t *testing.T) {
+	svc := newTestPromptTemplateService(newPromptTemplateRepoStub())
+	ctx := context.Background()
+	tone := "friendly"
+	tmpl, _, err := svc.Create(ctx, 1, &PromptTemplateInput{
+		Name:   "support",
+		System: "You support {{product}} in a {{ tone }} tone.",
+		Messages: []PromptTemplateMessage{
+			{Role: "user", Content: "My order is {{order}}."},
+			{Role: "assistant", Content: "Got it."},
+		},
+		Variables: []PromptTemplateVariable{{Name: "product"}, {Name: "tone", Default: &tone}, {Name: "order"}},
+	})
+	require.NoError(t, err)
+
+	body := []byte(`{"model":"claude-sonnet-4-5","system":"Be brief.","template_id":"support","template_variables":{"product":"Acme","order":1234},"messages":[{"role":"user","content":"Where is it?"}]}`)
+	out, err := svc.Apply(ctx, PromptFormatAnthropic, 1, body)
+	require.NoError(t, err)
+	require.False(t, gjson.GetBytes(out, "template_id").Exists())
+	require.False(t, gjson.GetBytes(out, "template_variables").Exists())
+	require.Equal(t, "You support Acme in a friendly tone.\n\nBe brief.", gjson.GetBytes(out, "system").String())
+	msgs := gjson.GetBytes(out, "messages").Array()
+	require.Len(t, msgs, 3)
+	require.Equal(t, "My order is 1234.", msgs[0].Get("content").String())
+	require.Equal(t, "assistant", msgs[1].Get("role").String())
+	require.Equal(t, "Where is it?", msgs[2].Get("content").String())
+
+	// 旧版本仍可按版本号引用
+	_, v2, err := svc.Update(ctx, 1, tmpl.ID, &PromptTemplateInput{System: "v2 for {{product}}", Variables: []PromptTemplateVariable{{Name: "product"}}})
+	require.NoError(t, err)
+	require.Equal(t, 2, v2.Version)
+	out, err = svc.Apply(ctx, PromptFormatAnthropic, 1, []byte(`{"template_id":"support","template_variables":{"product":"Acme"},"messages":[]}`))
+	require.NoError(t, err)
+	require.Equal(t, "v2 for Acme", gjson.GetBytes(out, "system").String())
+	out, err = svc.Apply(ctx, PromptFormatAnthropic, 1, []byte(`{"template_id":"support","template_version":1,"template_variables":{"product":"Acme","order":"7"},"messages":[]}`))
+	require.NoError(t, err)
+	require.Len(t, gjson.GetBytes(out, "messages").Array(), 2)
+
+	_, err = svc.Apply(ctx, PromptFormatAnthropic, 1, []byte(`{"template_id":"support","template_version":1,"template_variables":{"product":"Acme"}}`))
+	require.Equal(t, "PROMPT_TEMPLATE_VARIABLE_MISSING", infraerrors.Reason(err))
+	_, err = svc.Apply(ctx, PromptFormatAnthropic, 1, []byte(`{"template_id":"support","template_variables":{"product":"Acme","extra":"x"}}`))
+	require.Equal(t, "PROMPT_TEMPLATE_VARIABLE_UNKNOWN", infraerrors.Reason(err))
+	_, err = svc.Apply(ctx, PromptFormatAnthropic, 1, []byte(`{"template_id":"support","template_version":9}`))
+	require.True(t, infraerrors.IsNotFound(err))
+	_, err = svc.Apply(ctx, PromptFormatAnthropic, 2, []byte(`{"template_id":"support"}`))
+	require.True(t, infraerrors.IsNotFound(err))
+}
+
+func TestPromptTemplateService_ApplyResponses(t *testing.T) {
+	svc := newTestPromptTemplateService(newPromptTemplateRepoStub())
+	ctx := context.Background()
+	_, _, err := svc.Create(ctx, 1, &PromptTemplateInput{
+		Name:      "coder",
+		System:    "You write {{lang}}.",
+		Messages:  []PromptTemplateMessage{{Role: "user", Content: "Use tabs."}},
+		Variables: []PromptTemplateVariable{{Name: "lang"}},
+	})
+	require.NoError(t, err)
+
+	out, err := svc.Apply(ctx, PromptFormatOpenAIResponses, 1, []byte(`{"model":"gpt-5","template_id":"coder","template_variables":{"lang":"Go"},"input":"Write a parser"}`))
+	require.NoError(t, err)
+	require.Equal(t, "You write Go.", gjson.GetBytes(out, "instructions").String())
+	input := gjson.GetBytes(out, "input").Array()
+	require.Len(t, input, 2)
+	require.Equal(t, "Use tabs.", input[0].Get("content").String())
+	require.Equal(t, "Write a parser", input[1].Get("content").String())
+	require.Equal(t, "user", input[1].Get("role").String())
+}
+
+func TestPromptTemplateService_ApplyPassthroughAndDisabled(t *testing.T) {
+	body := []byte(`{"model":"claude-sonnet-4-5","messages":[]}`)
+	svc := newTestPromptTemplateService(newPromptTemplateRepoStub())
+	out, err := svc.Apply(context.Background(), PromptFormatAnthropic, 1, body)
+	require.NoError(t, err)
+	require.Equal(t, body, out)
+
+	disabled := NewPromptTemplateService(newPromptTemplateRepoStub(), &config.Config{})
+	_, err = disabled.Apply(context.Background(), PromptFormatAnthropic, 1, []byte(`{"template_id":"x"}`))
+	require.True(t, errors.Is(err, ErrPromptTemplateDisabled))
+}
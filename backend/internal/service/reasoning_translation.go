@@ -0,0 +1,273 @@
+package service
+
+import (
+	"strings"
+
+	"github.com/Wei-Shaw/sub2api/internal/pkg/claude"
+	"github.com/tidwall/gjson"
+	"github.com/tidwall/sjson"
+)
+
+// 推理参数跨格式映射：OpenAI reasoning effort ↔ Anthropic thinking.budget_tokens ↔ Gemini thinkingBudget
+const (
+	// reasoningBudgetMinimal Anthropic budget_tokens 允许的最小值
+	reasoningBudgetMinimal = 1024
+	reasoningBudgetLow     = 4096
+	reasoningBudgetMedium  = 16384
+	reasoningBudgetHigh    = 32768
+	reasoningBudgetXHigh   = 65536
+)
+
+// ReasoningEffortToBudget 返回 effort 档位对应的 thinking 预算；none 或无法识别时返回 0
+func ReasoningEffortToBudget(effort string) int {
+	value := strings.NewReplacer("-", "", "_", "", " ", "").Replace(strings.ToLower(strings.TrimSpace(effort)))
+	switch value {
+	case "minimal":
+		return reasoningBudgetMinimal
+	case "low":
+		return reasoningBudgetLow
+	case "medium":
+		return reasoningBudgetMedium
+	case "high":
+		return reasoningBudgetHigh
+	case "xhigh", "extrahigh":
+		return reasoningBudgetXHigh
+	default:
+		return 0
+	}
+}
+
+// ReasoningBudgetToEffort 按预算区间映射为 OpenAI reasoning effort；预算 <= 0 时返回空串
+func ReasoningBudgetToEffort(budget int) string {
+	switch {
+	case budget <= 0:
+		return ""
+	case budget <= reasoningBudgetLow:
+		return "low"
+	case budget <= reasoningBudgetMedium:
+		return "medium"
+	default:
+		return "high"
+	}
+}
+
+// TranslateClaudeReasoningParams 将 Anthropic Messages 请求中的 OpenAI 风格推理参数
+// （reasoning_effort / reasoning.effort）转换为 thinking 配置。
+//
+// 上游会拒绝这些未知字段，因此无论是否转换都会删除；请求已显式携带 thinking 时以 thinking 为准。
+// 开启 thinking 时上游不允许自定义 temperature / top_k，一并移除。
+func TranslateClaudeReasoningParams(body []byte) []byte {
+	flat := gjson.GetBytes(body, "reasoning_effort")
+	nested := gjson.GetBytes(body, "reasoning")
+	if !flat.Exists() && !nested.Exists() {
+		return body
+	}
+	effort := flat.String()
+	if effort == "" {
+		effort = nested.Get("effort").String()
+	}
+
+	out := body
+	for _, path := range []string{"reasoning_effort", "reasoning"} {
+		if next, err := sjson.DeleteBytes(out, path); err == nil {
+			out = next
+		}
+	}
+	if gjson.GetBytes(out, "thinking").Exists() {
+		return out
+	}
+
+	budget := ReasoningEffortToBudget(effort)
+	if budget == 0 {
+		return out
+	}
+	// budget_tokens 必须小于 max_tokens
+	if maxTokens := int(gjson.GetBytes(out, "max_tokens").Int()); maxTokens > 0 && budget >= maxTokens {
+		budget = maxTokens - 1
+		if budget < reasoningBudgetMinimal {
+			return out
+		}
+	}
+	next, err := sjson.SetBytes(out, "thinking", map[string]any{"type": "enabled", "budget_tokens": budget})
+	if err != nil {
+		return out
+	}
+	out = next
+	for _, path := range []string{"temperature", "top_k"} {
+		if next, err := sjson.DeleteBytes(out, path); err == nil {
+			out = next
+		}
+	}
+	return out
+}
+
+// applyOpenAIReasoningTranslation 规范化 Responses API 请求中的推理参数，返回是否修改了请求体
+//
+//   - 顶层 reasoning_effort（Chat Completions 写法）与 Anthropic thinking 转换为 reasoning.effort，已有 reasoning.effort 时以其为准
+//   - ReasoningModeExpose：请求上游返回推理摘要（reasoning.summary=auto）
+//   - ReasoningModeStrip：移除 reasoning.summary，上游不再返回推理文本
+func applyOpenAIReasoningTranslation(reqBody map[string]any, reasoningMode string) bool {
+	modified := false
+	effort := ""
+	if v, ok := reqBody["reasoning_effort"]; ok {
+		effort, _ = v.(string)
+		delete(reqBody, "reasoning_effort")
+		modified = true
+	}
+	if v, ok := reqBody["thinking"]; ok {
+		if thinking, ok := v.(map[string]any); ok && effort == "" {
+			switch thinking["type"] {
+			case "enabled":
+				budget, _ := asInt(thinking["budget_tokens"])
+				effort = ReasoningBudgetToEffort(budget)
+			case "adaptive":
+				effort = "medium"
+			}
+		}
+		delete(reqBody, "thinking")
+		modified = true
+	}
+
+	reasoning, _ := reqBody["reasoning"].(map[string]any)
+	if effort = strings.TrimSpace(effort); effort != "" {
+		if reasoning == nil {
+			reasoning = make(map[string]any)
+			reqBody["reasoning"] = reasoning
+		}
+		if _, exists := reasoning["effort"]; !exists {
+			reasoning["effort"] = effort
+		}
+	}
+	if reasoning == nil {
+		return modified
+	}
+
+	switch reasoningMode {
+	case ReasoningModeExpose:
+		if _, exists := reasoning["summary"]; !exists {
+			reasoning["summary"] = "auto"
+			modified = true
+		}
+	case ReasoningModeStrip:
+		if _, exists := reasoning["summary"]; exists {
+			delete(reasoning, "summary")
+			modified = true
+		}
+	}
+	return modified
+}
+
+// convertClaudeThinkingToGeminiConfig 将 Anthropic thinking 配置转换为 Gemini generationConfig.thinkingConfig
+//
+// 不开启 includeThoughts：兼容层不会把 thought 片段转换为 thinking 块，避免推理内容混入正文。
+func convertClaudeThinkingToGeminiConfig(thinking any) map[string]any {
+	tm, ok := thinking.(map[string]any)
+	if !ok {
+		return nil
+	}
+	switch tm["type"] {
+	case "enabled":
+		budget, ok := asInt(tm["budget_tokens"])
+		if !ok || budget <= 0 {
+			return nil
+		}
+		return map[string]any{"thinkingBudget": budget}
+	case "adaptive":
+		// -1 表示由模型动态决定思考预算
+		return map[string]any{"thinkingBudget": -1}
+	default:
+		return nil
+	}
+}
+
+// estimateClaudeThinkingTokens 估算非流式响应中 thinking 块的 token 数（上游 usage 未单独给出推理 token）
+func estimateClaudeThinkingTokens(body []byte) int {
+	tokens := 0
+	gjson.GetBytes(body, "content").ForEach(func(_, block gjson.Result) bool {
+		if block.Get("type").String() == "thinking" {
+			tokens += claude.EstimateTextTokens(block.Get("thinking").String())
+		}
+		return true
+	})
+	return tokens
+}
+
+// stripClaudeThinkingBlocks 移除非流式响应中的 thinking / redacted_thinking 内容块
+func stripClaudeThinkingBlocks(body []byte) []byte {
+	content := gjson.GetBytes(body, "content")
+	if !content.IsArray() {
+		return body
+	}
+	kept := make([]string, 0, len(content.Array()))
+	stripped := false
+	content.ForEach(func(_, block gjson.Result) bool {
+		if isClaudeThinkingBlockType(block.Get("type").String()) {
+			stripped = true
+			return true
+		}
+		kept = append(kept, block.Raw)
+		return true
+	})
+	if !stripped {
+		return body
+	}
+	next, err := sjson.SetRawBytes(body, "content", []byte("["+strings.Join(kept, ",")+"]"))
+	if err != nil {
+		return body
+	}
+	return next
+}
+
+func isClaudeThinkingBlockType(blockType string) bool {
+	return blockType == "thinking" || blockType == "redacted_thinking"
+}
+
+// clampReasoningTokens 推理 token 为估算值时不应超过上游报告的输出 token
+func clampReasoningTokens(usage *ClaudeUsage) {
+	if usage != nil && usage.OutputTokens > 0 && usage.ReasoningTokens > usage.OutputTokens {
+		usage.ReasoningTokens = usage.OutputTokens
+	}
+}
+
+// claudeThinkingStreamStripper 在 SSE 流中丢弃 thinking 内容块的事件，并重新编号后续内容块的 index，
+// 保证客户端看到的 index 连续。
+type claudeThinkingStreamStripper struct {
+	dropped map[int]bool
+	remap   map[int]int
+	removed int
+}
+
+func newClaudeThinkingStreamStripper() *claudeThinkingStreamStripper {
+	return &claudeThinkingStreamStripper{dropped: make(map[int]bool), remap: make(map[int]int)}
+}
+
+// filter 处理一个已解析的 SSE 事件；返回 false 表示该事件应被丢弃，返回 true 时可能已就地修改 index
+func (st *claudeThinkingStreamStripper) filter(eventType string, event map[string]any) bool {
+	switch eventType {
+	case "content_block_start", "content_block_delta", "content_block_stop":
+	default:
+		return true
+	}
+	index, ok := asInt(event["index"])
+	if !ok {
+		return true
+	}
+
+	if eventType == "content_block_start" {
+		block, _ := event["content_block"].(map[string]any)
+		blockType, _ := block["type"].(string)
+		if isClaudeThinkingBlockType(blockType) {
+			st.dropped[index] = true
+			st.removed++
+			return false
+		}
+		st.remap[index] = index - st.removed
+	}
+	if st.dropped[index] {
+		return false
+	}
+	if mapped, ok := st.remap[index]; ok && mapped != index {
+		event["index"] = mapped
+	}
+	return true
+}
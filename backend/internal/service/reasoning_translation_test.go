@@ -0,0 +1,172 @@
+//go:build unit
+
+package service
+
+import (
+	"context"
+	"testing"
+
+	"github.com/stretchr/testify/require"
+	"github.com/tidwall/gjson"
+)
+
+func TestReasoningEffortBudgetMapping(t *testing.T) {
+	require.Equal(t, 1024, ReasoningEffortToBudget("minimal"))
+	require.Equal(t, 4096, ReasoningEffortToBudget("LOW"))
+	require.Equal(t, 16384, ReasoningEffortToBudget("medium"))
+	require.Equal(t, 32768, ReasoningEffortToBudget("high"))
+	require.Equal(t, 65536, ReasoningEffortToBudget("x-high"))
+	require.Equal(t, 0, ReasoningEffortToBudget("none"))
+	require.Equal(t, 0, ReasoningEffortToBudget("bogus"))
+
+	require.Equal(t, "", ReasoningBudgetToEffort(0))
+	require.Equal(t, "low", ReasoningBudgetToEffort(1024))
+	require.Equal(t, "low", ReasoningBudgetToEffort(4096))
+	require.Equal(t, "medium", ReasoningBudgetToEffort(10000))
+	require.Equal(t, "high", ReasoningBudgetToEffort(40000))
+
+	// 双向映射在档位边界上保持一致
+	for _, effort := range []string{"low", "medium", "high"} {
+		require.Equal(t, effort, ReasoningBudgetToEffort(ReasoningEffortToBudget(effort)))
+	}
+}
+
+func TestTranslateClaudeReasoningParams(t *testing.T) {
+	t.Run("no reasoning fields keeps body", func(t *testing.T) {
+		body := []byte(`{"model":"claude-sonnet-4-5","max_tokens":1024}`)
+		require.Equal(t, body, TranslateClaudeReasoningParams(body))
+	})
+
+	t.Run("flat reasoning_effort becomes thinking", func(t *testing.T) {
+		body := []byte(`{"model":"claude-sonnet-4-5","max_tokens":64000,"temperature":0.2,"reasoning_effort":"medium"}`)
+		out := TranslateClaudeReasoningParams(body)
+		require.False(t, gjson.GetBytes(out, "reasoning_effort").Exists())
+		require.False(t, gjson.GetBytes(out, "temperature").Exists())
+		require.Equal(t, "enabled", gjson.GetBytes(out, "thinking.type").String())
+		require.Equal(t, int64(16384), gjson.GetBytes(out, "thinking.budget_tokens").Int())
+	})
+
+	t.Run("budget clamped below max_tokens", func(t *testing.T) {
+		body := []byte(`{"max_tokens":8000,"reasoning":{"effort":"high"}}`)
+		out := TranslateClaudeReasoningParams(body)
+		require.False(t, gjson.GetBytes(out, "reasoning").Exists())
+		require.Equal(t, int64(7999), gjson.GetBytes(out, "thinking.budget_tokens").Int())
+	})
+
+	t.Run("max_tokens too small disables thinking", func(t *testing.T) {
+		out := TranslateClaudeReasoningParams([]byte(`{"max_tokens":512,"reasoning_effort":"high"}`))
+		require.False(t, gjson.GetBytes(out, "thinking").Exists())
+		require.False(t, gjson.GetBytes(out, "reasoning_effort").Exists())
+	})
+
+	t.Run("explicit thinking wins", func(t *testing.T) {
+		body := []byte(`{"max_tokens":4096,"thinking":{"type":"enabled","budget_tokens":2048},"reasoning_effort":"high"}`)
+		out := TranslateClaudeReasoningParams(body)
+		require.False(t, gjson.GetBytes(out, "reasoning_effort").Exists())
+		require.Equal(t, int64(2048), gjson.GetBytes(out, "thinking.budget_tokens").Int())
+	})
+}
+
+func TestApplyOpenAIReasoningTranslation(t *testing.T) {
+	t.Run("thinking budget maps to effort", func(t *testing.T) {
+		reqBody := map[string]any{
+			"model":    "gpt-5",
+			"thinking": map[string]any{"type": "enabled", "budget_tokens": float64(12000)},
+		}
+		require.True(t, applyOpenAIReasoningTranslation(reqBody, ReasoningModePassthrough))
+		require.NotContains(t, reqBody, "thinking")
+		require.Equal(t, map[string]any{"effort": "medium"}, reqBody["reasoning"])
+	})
+
+	t.Run("flat effort does not override nested", func(t *testing.T) {
+		reqBody := map[string]any{
+			"reasoning_effort": "high",
+			"reasoning":        map[string]any{"effort": "low"},
+		}
+		require.True(t, applyOpenAIReasoningTranslation(reqBody, ReasoningModePassthrough))
+		require.NotContains(t, reqBody, "reasoning_effort")
+		require.Equal(t, "low", reqBody["reasoning"].(map[string]any)["effort"])
+	})
+
+	t.Run("expose requests summary", func(t *testing.T) {
+		reqBody := map[string]any{"reasoning": map[string]any{"effort": "high"}}
+		require.True(t, applyOpenAIReasoningTranslation(reqBody, ReasoningModeExpose))
+		require.Equal(t, "auto", reqBody["reasoning"].(map[string]any)["summary"])
+	})
+
+	t.Run("strip removes summary", func(t *testing.T) {
+		reqBody := map[string]any{"reasoning": map[string]any{"effort": "high", "summary": "detailed"}}
+		require.True(t, applyOpenAIReasoningTranslation(reqBody, ReasoningModeStrip))
+		require.NotContains(t, reqBody["reasoning"], "summary")
+	})
+
+	t.Run("no reasoning untouched", func(t *testing.T) {
+		reqBody := map[string]any{"model": "gpt-4.1"}
+		require.False(t, applyOpenAIReasoningTranslation(reqBody, ReasoningModeExpose))
+		require.NotContains(t, reqBody, "reasoning")
+	})
+}
+
+func TestConvertClaudeGenerationConfig_Thinking(t *testing.T) {
+	out := convertClaudeGenerationConfig(map[string]any{
+		"max_tokens": float64(8192),
+		"thinking":   map[string]any{"type": "enabled", "budget_tokens": float64(4096)},
+	})
+	require.Equal(t, map[string]any{"thinkingBudget": 4096}, out["thinkingConfig"])
+
+	out = convertClaudeGenerationConfig(map[string]any{"thinking": map[string]any{"type": "adaptive"}})
+	require.Equal(t, map[string]any{"thinkingBudget": -1}, out["thinkingConfig"])
+
+	out = convertClaudeGenerationConfig(map[string]any{"thinking": map[string]any{"type": "disabled"}})
+	require.Nil(t, out)
+}
+
+func TestStripClaudeThinkingBlocks(t *testing.T) {
+	body := []byte(`{"content":[{"type":"thinking","thinking":"Let me think about it.","signature":"sig"},{"type":"redacted_thinking","data":"xx"},{"type":"text","text":"Hi"}],"usage":{"output_tokens":20}}`)
+	require.Positive(t, estimateClaudeThinkingTokens(body))
+
+	out := stripClaudeThinkingBlocks(body)
+	content := gjson.GetBytes(out, "content").Array()
+	require.Len(t, content, 1)
+	require.Equal(t, "text", content[0].Get("type").String())
+	require.Equal(t, int64(20), gjson.GetBytes(out, "usage.output_tokens").Int())
+
+	plain := []byte(`{"content":[{"type":"text","text":"Hi"}]}`)
+	require.Equal(t, plain, stripClaudeThinkingBlocks(plain))
+}
+
+func TestClaudeThinkingStreamStripper(t *testing.T) {
+	st := newClaudeThinkingStreamStripper()
+	events := []map[string]any{
+		{"type": "content_block_start", "index": float64(0), "content_block": map[string]any{"type": "thinking"}},
+		{"type": "content_block_delta", "index": float64(0), "delta": map[string]any{"type": "thinking_delta"}},
+		{"type": "content_block_stop", "index": float64(0)},
+		{"type": "content_block_start", "index": float64(1), "content_block": map[string]any{"type": "text"}},
+		{"type": "content_block_delta", "index": float64(1), "delta": map[string]any{"type": "text_delta"}},
+		{"type": "content_block_stop", "index": float64(1)},
+		{"type": "message_delta"},
+	}
+	kept := make([]map[string]any, 0)
+	for _, ev := range events {
+		if st.filter(ev["type"].(string), ev) {
+			kept = append(kept, ev)
+		}
+	}
+	require.Len(t, kept, 4)
+	for _, ev := range kept[:3] {
+		require.Equal(t, 0, ev["index"])
+	}
+	require.Equal(t, "message_delta", kept[3]["type"])
+}
+
+func TestAPIKeyPolicyContext(t *testing.T) {
+	require.Equal(t, ReasoningModePassthrough, APIKeyPolicyFromContext(context.Background()).ReasoningMode)
+
+	ctx := WithAPIKeyPolicy(context.Background(), &APIKeyPolicy{ReasoningMode: ReasoningModeStrip})
+	require.Equal(t, ReasoningModeStrip, APIKeyPolicyFromContext(ctx).ReasoningMode)
+
+	policy := &APIKeyPolicy{ReasoningMode: " Expose "}
+	require.NoError(t, policy.Normalize())
+	require.Equal(t, ReasoningModeExpose, policy.ReasoningMode)
+	require.ErrorIs(t, (&APIKeyPolicy{ReasoningMode: "hide"}).Normalize(), ErrInvalidReasoningMode)
+}
@@ -0,0 +1,293 @@
+package service
+
+import (
+	"context"
+	"fmt"
+	"net/http"
+	"sync"
+	"sync/atomic"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/logger"
+)
+
+const (
+	// RequestLogErrorClassKey gin 上下文键：网关映射上游错误时写入错误分类，供请求日志中间件读取
+	RequestLogErrorClassKey = "request_log_error_class"
+
+	requestLogFlushInterval   = time.Second
+	requestLogWriteTimeout    = 10 * time.Second
+	requestLogPurgeBatchSize  = 5000
+	requestLogDefaultPageSize = 50
+	requestLogMaxPageSize     = 500
+)
+
+// RequestLogEntry 单个网关请求的日志记录
+type RequestLogEntry struct {
+	ID              int64     `json:"id"`
+	CreatedAt       time.Time `json:"created_at"`
+	TraceID         string    `json:"trace_id,omitempty"`
+	ClientRequestID string    `json:"client_request_id,omitempty"`
+	UserID          *int64    `json:"user_id,omitempty"`
+	APIKeyID        *int64    `json:"api_key_id,omitempty"`
+	AccountID       *int64    `json:"account_id,omitempty"`
+	GroupID         *int64    `json:"group_id,omitempty"`
+	Platform        string    `json:"platform,omitempty"`
+	Model           string    `json:"model,omitempty"`
+	Method          string    `json:"method"`
+	Path            string    `json:"path"`
+	Stream          bool      `json:"stream"`
+	StatusCode      int       `json:"status_code"`
+	ErrorClass      string    `json:"error_class,omitempty"`
+	DurationMs      int       `json:"duration_ms"`
+	ClientIP        string    `json:"client_ip,omitempty"`
+}
+
+// RequestLogFilter 请求日志检索条件，零值字段表示不限制
+type RequestLogFilter struct {
+	StartTime   time.Time
+	EndTime     time.Time
+	APIKeyID    *int64
+	UserID      *int64
+	AccountID   *int64
+	Model       string
+	StatusCodes []int
+	// ErrorsOnly 仅返回状态码 >= 400 的请求
+	ErrorsOnly bool
+	ErrorClass string
+	// TraceID 同时匹配 trace_id（X-Request-ID）与客户端请求 ID
+	TraceID  string
+	Page     int
+	PageSize int
+}
+
+// RequestLogRepository 请求日志存储
+type RequestLogRepository interface {
+	BatchInsert(ctx context.Context, entries []*RequestLogEntry) error
+	// Search 按 created_at 倒序分页检索，返回当前页与总数
+	Search(ctx context.Context, filter RequestLogFilter) ([]*RequestLogEntry, int64, error)
+	// DeleteBefore 删除 cutoff 之前的记录，单次最多 limit 条，返回删除数量
+	DeleteBefore(ctx context.Context, cutoff time.Time, limit int) (int64, error)
+}
+
+// RequestLogService 异步批量写入网关请求日志，并提供保留期内的检索与过期清除。
+// Record 不阻塞请求路径：队列满时丢弃并计数。
+type RequestLogService struct {
+	repo RequestLogRepository
+	cfg  *config.Config
+	now  func() time.Time
+
+	queue   chan *RequestLogEntry
+	dropped atomic.Int64
+
+	startOnce sync.Once
+	stopOnce  sync.Once
+	stopCh    chan struct{}
+	wg        sync.WaitGroup
+}
+
+// NewRequestLogService 创建请求日志服务（需调用 Start 启动写入协程）
+func NewRequestLogService(repo RequestLogRepository, cfg *config.Config) *RequestLogService {
+	queueSize := 8192
+	if cfg != nil && cfg.RequestLog.QueueSize > 0 {
+		queueSize = cfg.RequestLog.QueueSize
+	}
+	return &RequestLogService{
+		repo:   repo,
+		cfg:    cfg,
+		now:    time.Now,
+		queue:  make(chan *RequestLogEntry, queueSize),
+		stopCh: make(chan struct{}),
+	}
+}
+
+func (s *RequestLogService) enabled() bool {
+	return s != nil && s.repo != nil && s.cfg != nil && s.cfg.RequestLog.Enabled
+}
+
+func (s *RequestLogService) batchSize() int {
+	if s.cfg != nil && s.cfg.RequestLog.BatchSize > 0 {
+		return s.cfg.RequestLog.BatchSize
+	}
+	return 200
+}
+
+func (s *RequestLogService) retention() time.Duration {
+	days := 7
+	if s.cfg != nil && s.cfg.RequestLog.RetentionDays > 0 {
+		days = s.cfg.RequestLog.RetentionDays
+	}
+	return time.Duration(days) * 24 * time.Hour
+}
+
+// Record 入队一条请求日志，对 nil 安全
+func (s *RequestLogService) Record(entry *RequestLogEntry) {
+	if !s.enabled() || entry == nil {
+		return
+	}
+	if entry.CreatedAt.IsZero() {
+		entry.CreatedAt = s.now()
+	}
+	select {
+	case s.queue <- entry:
+	default:
+		if n := s.dropped.Add(1); n == 1 || n%1000 == 0 {
+			logger.LegacyPrintf("service.request_log", "[RequestLog] queue full, dropped=%d", n)
+		}
+	}
+}
+
+// Dropped 返回因队列已满丢弃的记录数
+func (s *RequestLogService) Dropped() int64 {
+	if s == nil {
+		return 0
+	}
+	return s.dropped.Load()
+}
+
+// Start 启动写入协程：每秒或攒满一批时写入
+func (s *RequestLogService) Start() {
+	if !s.enabled() {
+		return
+	}
+	s.startOnce.Do(func() {
+		s.wg.Add(1)
+		go s.run()
+	})
+}
+
+// Stop 停止写入协程，并写出队列中剩余的记录
+func (s *RequestLogService) Stop() {
+	if s == nil {
+		return
+	}
+	s.stopOnce.Do(func() {
+		close(s.stopCh)
+	})
+	s.wg.Wait()
+}
+
+func (s *RequestLogService) run() {
+	defer s.wg.Done()
+	ticker := time.NewTicker(requestLogFlushInterval)
+	defer ticker.Stop()
+
+	size := s.batchSize()
+	batch := make([]*RequestLogEntry, 0, size)
+	flush := func() {
+		if len(batch) == 0 {
+			return
+		}
+		ctx, cancel := context.WithTimeout(context.Background(), requestLogWriteTimeout)
+		if err := s.repo.BatchInsert(ctx, batch); err != nil {
+			logger.LegacyPrintf("service.request_log", "[RequestLog] batch insert failed: rows=%d err=%v", len(batch), err)
+		}
+		cancel()
+		batch = make([]*RequestLogEntry, 0, size)
+	}
+
+	for {
+		select {
+		case entry := <-s.queue:
+			batch = append(batch, entry)
+			if len(batch) >= size {
+				flush()
+			}
+		case <-ticker.C:
+			flush()
+		case <-s.stopCh:
+			for {
+				select {
+				case entry := <-s.queue:
+					batch = append(batch, entry)
+					if len(batch) >= size {
+						flush()
+					}
+				default:
+					flush()
+					return
+				}
+			}
+		}
+	}
+}
+
+// Search 检索保留期内的请求日志；起始时间早于保留期时截断到保留期起点
+func (s *RequestLogService) Search(ctx context.Context, filter RequestLogFilter) ([]*RequestLogEntry, int64, error) {
+	if s == nil || s.repo == nil {
+		return nil, 0, fmt.Errorf("request log service not initialized")
+	}
+	now := s.now()
+	if filter.EndTime.IsZero() {
+		filter.EndTime = now
+	}
+	if cutoff := now.Add(-s.retention()); filter.StartTime.Before(cutoff) {
+		filter.StartTime = cutoff
+	}
+	if filter.Page <= 0 {
+		filter.Page = 1
+	}
+	if filter.PageSize <= 0 {
+		filter.PageSize = requestLogDefaultPageSize
+	}
+	filter.PageSize = min(filter.PageSize, requestLogMaxPageSize)
+	if !filter.StartTime.Before(filter.EndTime) {
+		return []*RequestLogEntry{}, 0, nil
+	}
+	items, total, err := s.repo.Search(ctx, filter)
+	if err != nil {
+		return nil, 0, fmt.Errorf("search request logs: %w", err)
+	}
+	return items, total, nil
+}
+
+// PurgeExpired 分批删除超过保留期的请求日志（供 request_log_purge 定时任务调用）
+func (s *RequestLogService) PurgeExpired(ctx context.Context) error {
+	if s == nil || s.repo == nil {
+		return nil
+	}
+	cutoff := s.now().Add(-s.retention())
+	var total int64
+	for {
+		n, err := s.repo.DeleteBefore(ctx, cutoff, requestLogPurgeBatchSize)
+		if err != nil {
+			return fmt.Errorf("purge request logs: %w", err)
+		}
+		total += n
+		if n < requestLogPurgeBatchSize {
+			break
+		}
+		if err := ctx.Err(); err != nil {
+			return err
+		}
+	}
+	if total > 0 {
+		logger.LegacyPrintf("service.request_log", "[RequestLog] purged %d rows before %s", total, cutoff.UTC().Format(time.RFC3339))
+	}
+	return nil
+}
+
+// RequestLogErrorClassForStatus 未经上游错误映射的失败请求，按状态码推导网关侧错误分类
+func RequestLogErrorClassForStatus(status int) string {
+	switch {
+	case status < 400:
+		return ""
+	case status == http.StatusBadRequest:
+		return "invalid_request"
+	case status == http.StatusUnauthorized:
+		return "unauthorized"
+	case status == http.StatusForbidden:
+		return "forbidden"
+	case status == http.StatusNotFound:
+		return "not_found"
+	case status == http.StatusRequestEntityTooLarge:
+		return "request_too_large"
+	case status == http.StatusTooManyRequests:
+		return "rate_limited"
+	case status >= 500:
+		return "server_error"
+	default:
+		return "client_error"
+	}
+}
@@ -0,0 +1,147 @@
+//go:build unit
+
+package service
+
+import (
+	"context"
+	"net/http"
+	"sync"
+	"testing"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/stretchr/testify/require"
+)
+
+type requestLogRepoStub struct {
+	mu         sync.Mutex
+	batches    [][]*RequestLogEntry
+	lastFilter RequestLogFilter
+	deleteRuns []int64
+	cutoffs    []time.Time
+}
+
+func (s *requestLogRepoStub) BatchInsert(_ context.Context, entries []*RequestLogEntry) error {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+	s.batches = append(s.batches, append([]*RequestLogEntry(nil), entries...))
+	return nil
+}
+
+func (s *requestLogRepoStub) Search(_ context.Context, filter RequestLogFilter) ([]*RequestLogEntry, int64, error) {
+	s.lastFilter = filter
+	return []*RequestLogEntry{{ID: 1}}, 1, nil
+}
+
+func (s *requestLogRepoStub) DeleteBefore(_ context.Context, cutoff time.Time, _ int) (int64, error) {
+	s.cutoffs = append(s.cutoffs, cutoff)
+	if len(s.deleteRuns) == 0 {
+		return 0, nil
+	}
+	n := s.deleteRuns[0]
+	s.deleteRuns = s.deleteRuns[1:]
+	return n, nil
+}
+
+func (s *requestLogRepoStub) rows() int {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+	n := 0
+	for _, b := range s.batches {
+		n += len(b)
+	}
+	return n
+}
+
+func newRequestLogTestConfig(queueSize, batchSize int) *config.Config {
+	return &config.Config{RequestLog: config.RequestLogConfig{
+		Enabled:       true,
+		RetentionDays: 7,
+		QueueSize:     queueSize,
+		BatchSize:     batchSize,
+	}}
+}
+
+func TestRequestLogService_BatchesAndFlushesOnStop(t *testing.T) {
+	repo := &requestLogRepoStub{}
+	svc := NewRequestLogService(repo, newRequestLogTestConfig(100, 2))
+	svc.Start()
+
+	for i := 0; i < 5; i++ {
+		svc.Record(&RequestLogEntry{StatusCode: http.StatusOK})
+	}
+	svc.Stop()
+	svc.Stop()
+
+	require.Equal(t, 5, repo.rows())
+	for _, b := range repo.batches {
+		require.LessOrEqual(t, len(b), 2)
+		require.False(t, b[0].CreatedAt.IsZero())
+	}
+}
+
+func TestRequestLogService_DropsWhenQueueFull(t *testing.T) {
+	repo := &requestLogRepoStub{}
+	// 未启动写入协程，队列不会被消费
+	svc := NewRequestLogService(repo, newRequestLogTestConfig(2, 10))
+	for i := 0; i < 5; i++ {
+		svc.Record(&RequestLogEntry{})
+	}
+	require.Equal(t, int64(3), svc.Dropped())
+
+	disabled := NewRequestLogService(repo, &config.Config{})
+	disabled.Record(&RequestLogEntry{})
+	require.Equal(t, int64(0), disabled.Dropped())
+
+	var nilSvc *RequestLogService
+	nilSvc.Record(&RequestLogEntry{})
+	nilSvc.Stop()
+}
+
+func TestRequestLogService_SearchClampsRangeAndPageSize(t *testing.T) {
+	now := time.Date(2026, 3, 10, 12, 0, 0, 0, time.UTC)
+	repo := &requestLogRepoStub{}
+	svc := NewRequestLogService(repo, newRequestLogTestConfig(10, 10))
+	svc.now = func() time.Time { return now }
+
+	_, total, err := svc.Search(context.Background(), RequestLogFilter{
+		StartTime: now.Add(-30 * 24 * time.Hour),
+		PageSize:  10000,
+	})
+	require.NoError(t, err)
+	require.Equal(t, int64(1), total)
+	require.Equal(t, now.Add(-7*24*time.Hour), repo.lastFilter.StartTime)
+	require.Equal(t, now, repo.lastFilter.EndTime)
+	require.Equal(t, 1, repo.lastFilter.Page)
+	require.Equal(t, requestLogMaxPageSize, repo.lastFilter.PageSize)
+
+	// 整个区间都在保留期之前：不查询数据库
+	repo.lastFilter = RequestLogFilter{}
+	items, total, err := svc.Search(context.Background(), RequestLogFilter{
+		StartTime: now.Add(-20 * 24 * time.Hour),
+		EndTime:   now.Add(-10 * 24 * time.Hour),
+	})
+	require.NoError(t, err)
+	require.Empty(t, items)
+	require.Zero(t, total)
+	require.Zero(t, repo.lastFilter.PageSize)
+}
+
+func TestRequestLogService_PurgeExpiredLoopsUntilShortBatch(t *testing.T) {
+	now := time.Date(2026, 3, 10, 12, 0, 0, 0, time.UTC)
+	repo := &requestLogRepoStub{deleteRuns: []int64{requestLogPurgeBatchSize, requestLogPurgeBatchSize, 12}}
+	svc := NewRequestLogService(repo, newRequestLogTestConfig(10, 10))
+	svc.now = func() time.Time { return now }
+
+	require.NoError(t, svc.PurgeExpired(context.Background()))
+	require.Len(t, repo.cutoffs, 3)
+	require.Equal(t, now.Add(-7*24*time.Hour), repo.cutoffs[0])
+}
+
+func TestRequestLogErrorClassForStatus(t *testing.T) {
+	require.Equal(t, "", RequestLogErrorClassForStatus(http.StatusOK))
+	require.Equal(t, "unauthorized", RequestLogErrorClassForStatus(http.StatusUnauthorized))
+	require.Equal(t, "rate_limited", RequestLogErrorClassForStatus(http.StatusTooManyRequests))
+	require.Equal(t, "server_error", RequestLogErrorClassForStatus(http.StatusBadGateway))
+	require.Equal(t, "client_error", RequestLogErrorClassForStatus(http.StatusConflict))
+}
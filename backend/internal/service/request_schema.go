@@ -0,0 +1,314 @@
+package service
+
+import (
+	"fmt"
+	"slices"
+	"strconv"
+	"strings"
+
+	"github.com/tidwall/gjson"
+)
+
+// 请求体结构校验模式（gateway.request_validation）
+const (
+	// RequestValidationOff 不做结构校验，仅由后续解析与上游报错
+	RequestValidationOff = "off"
+	// RequestValidationLenient 校验已知字段的类型与取值，忽略未知字段
+	RequestValidationLenient = "lenient"
+	// RequestValidationStrict 在 lenient 基础上拒绝未知字段（仅请求顶层与消息对象）
+	RequestValidationStrict = "strict"
+)
+
+// RequestSchemaError 请求体字段级结构错误
+type RequestSchemaError struct {
+	// Param 出错字段路径，如 messages[2].content；请求体本身出错时为空
+	Param string
+	// Code invalid_type / invalid_value / missing_required_parameter / unknown_parameter
+	Code    string
+	Message string
+}
+
+func (e *RequestSchemaError) Error() string {
+	return e.Message
+}
+
+type schemaKind uint8
+
+const (
+	schemaString schemaKind = 1 << iota
+	schemaNumber
+	schemaInteger
+	schemaBoolean
+	schemaObject
+	schemaArray
+)
+
+// schemaNode 字段结构描述；kinds 为 0 表示任意类型，fields 为 nil 的对象不检查其字段。
+// 值为 null 的可选字段视为未提供。
+type schemaNode struct {
+	kinds    schemaKind
+	fields   map[string]*schemaNode
+	required []string
+	items    *schemaNode
+	enum     []string
+	positive bool
+	// closed 严格模式下拒绝 fields 中未声明的字段
+	closed bool
+}
+
+// RequestSchema 一种请求体（如 Anthropic Messages）的结构描述
+type RequestSchema struct {
+	root *schemaNode
+}
+
+var (
+	schemaAnyValue        = &schemaNode{}
+	schemaAnyString       = &schemaNode{kinds: schemaString}
+	schemaAnyNumber       = &schemaNode{kinds: schemaNumber}
+	schemaAnyInteger      = &schemaNode{kinds: schemaInteger}
+	schemaAnyBoolean      = &schemaNode{kinds: schemaBoolean}
+	schemaAnyObject       = &schemaNode{kinds: schemaObject}
+	schemaAnyArray        = &schemaNode{kinds: schemaArray}
+	schemaPositiveInteger = &schemaNode{kinds: schemaInteger, positive: true}
+	schemaStringArray     = &schemaNode{kinds: schemaArray, items: schemaAnyString}
+	schemaTypedObject     = &schemaNode{kinds: schemaObject, required: []string{"type"}, fields: map[string]*schemaNode{"type": schemaAnyString}}
+)
+
+// withGatewayExtensions 补充网关自身消费的扩展字段（服务端会话、提示词模板），这些字段在转发前会被移除
+func withGatewayExtensions(fields map[string]*schemaNode) map[string]*schemaNode {
+	fields["conversation_id"] = schemaAnyString
+	fields["template_id"] = schemaAnyString
+	fields["template_version"] = schemaPositiveInteger
+	fields["template_variables"] = schemaAnyObject
+	return fields
+}
+
+// AnthropicMessagesSchema /v1/messages 与 /v1/messages/count_tokens 请求体
+var AnthropicMessagesSchema = &RequestSchema{root: &schemaNode{
+	kinds:    schemaObject,
+	closed:   true,
+	required: []string{"model"},
+	fields: withGatewayExtensions(map[string]*schemaNode{
+		"model": schemaAnyString,
+		"messages": {kinds: schemaArray, items: &schemaNode{
+			kinds:    schemaObject,
+			closed:   true,
+			required: []string{"role", "content"},
+			fields: map[string]*schemaNode{
+				"role":    {kinds: schemaString, enum: []string{"user", "assistant"}},
+				"content": {kinds: schemaString | schemaArray, items: schemaTypedObject},
+			},
+		}},
+		"system":             {kinds: schemaString | schemaArray, items: schemaTypedObject},
+		"max_tokens":         schemaPositiveInteger,
+		"stream":             schemaAnyBoolean,
+		"temperature":        schemaAnyNumber,
+		"top_p":              schemaAnyNumber,
+		"top_k":              schemaAnyInteger,
+		"stop_sequences":     schemaStringArray,
+		"metadata":           {kinds: schemaObject, fields: map[string]*schemaNode{"user_id": schemaAnyString}},
+		"tools":              {kinds: schemaArray, items: &schemaNode{kinds: schemaObject, fields: map[string]*schemaNode{"name": schemaAnyString, "description": schemaAnyString, "input_schema": schemaAnyObject, "type": schemaAnyString}}},
+		"tool_choice":        schemaTypedObject,
+		"thinking":           {kinds: schemaObject, required: []string{"type"}, fields: map[string]*schemaNode{"type": schemaAnyString, "budget_tokens": schemaPositiveInteger}},
+		"service_tier":       schemaAnyString,
+		"container":          schemaAnyValue,
+		"mcp_servers":        schemaAnyArray,
+		"context_management": schemaAnyObject,
+		"output_format":      schemaAnyObject,
+		"output_config":      schemaAnyObject,
+		// OpenAI 风格推理参数，由网关转换为 thinking
+		"reasoning_effort": schemaAnyString,
+		"reasoning":        schemaAnyObject,
+		// 确定性采样种子：上游支持时透传（Gemini），否则移除并在 X-Sub2API-Adjusted-Params 中报告
+		"seed": schemaAnyInteger,
+	}),
+}}
+
+// OpenAIResponsesSchema /v1/responses 请求体
+var OpenAIResponsesSchema = &RequestSchema{root: &schemaNode{
+	kinds:    schemaObject,
+	closed:   true,
+	required: []string{"model"},
+	fields: withGatewayExtensions(map[string]*schemaNode{
+		"model": schemaAnyString,
+		"input": {kinds: schemaString | schemaArray, items: &schemaNode{
+			kinds: schemaObject,
+			fields: map[string]*schemaNode{
+				"type":    schemaAnyString,
+				"role":    schemaAnyString,
+				"content": {kinds: schemaString | schemaArray, items: schemaTypedObject},
+			},
+		}},
+		"instructions":           schemaAnyString,
+		"stream":                 schemaAnyBoolean,
+		"stream_options":         schemaAnyObject,
+		"store":                  schemaAnyBoolean,
+		"background":             schemaAnyBoolean,
+		"parallel_tool_calls":    schemaAnyBoolean,
+		"max_output_tokens":      schemaPositiveInteger,
+		"max_tool_calls":         schemaPositiveInteger,
+		"temperature":            schemaAnyNumber,
+		"top_p":                  schemaAnyNumber,
+		"top_logprobs":           schemaAnyInteger,
+		"seed":                   schemaAnyInteger,
+		"tools":                  {kinds: schemaArray, items: schemaTypedObject},
+		"tool_choice":            {kinds: schemaString | schemaObject},
+		"reasoning":              {kinds: schemaObject, fields: map[string]*schemaNode{"effort": schemaAnyString, "summary": schemaAnyString}},
+		"text":                   schemaAnyObject,
+		"include":                schemaStringArray,
+		"metadata":               schemaAnyObject,
+		"previous_response_id":   schemaAnyString,
+		"conversation":           {kinds: schemaString | schemaObject},
+		"prompt":                 schemaAnyObject,
+		"prompt_cache_key":       schemaAnyString,
+		"prompt_cache_retention": schemaAnyString,
+		"safety_identifier":      schemaAnyString,
+		"service_tier":           schemaAnyString,
+		"truncation":             schemaAnyString,
+		"user":                   schemaAnyString,
+	}),
+}}
+
+var geminiContentSchema = &schemaNode{
+	kinds:  schemaObject,
+	closed: true,
+	fields: map[string]*schemaNode{
+		"role":  schemaAnyString,
+		"parts": {kinds: schemaArray, items: schemaAnyObject},
+	},
+}
+
+// GeminiGenerateContentSchema generateContent / streamGenerateContent 请求体（同时接受 camelCase 与 snake_case 字段名）
+var GeminiGenerateContentSchema = &RequestSchema{root: &schemaNode{
+	kinds:  schemaObject,
+	closed: true,
+	fields: map[string]*schemaNode{
+		"contents":           {kinds: schemaArray, items: geminiContentSchema},
+		"systemInstruction":  geminiContentSchema,
+		"system_instruction": geminiContentSchema,
+		"generationConfig":   schemaAnyObject,
+		"generation_config":  schemaAnyObject,
+		"safetySettings":     {kinds: schemaArray, items: schemaAnyObject},
+		"safety_settings":    {kinds: schemaArray, items: schemaAnyObject},
+		"tools":              {kinds: schemaArray, items: schemaAnyObject},
+		"toolConfig":         schemaAnyObject,
+		"tool_config":        schemaAnyObject,
+		"cachedContent":      schemaAnyString,
+		"cached_content":     schemaAnyString,
+		"labels":             schemaAnyObject,
+		"model":              schemaAnyString,
+	},
+}}
+
+// Validate 按模式校验请求体，返回第一个字段级错误；非法 JSON 不在此处报错，交由后续解析处理
+func (s *RequestSchema) Validate(body []byte, mode string) *RequestSchemaError {
+	if s == nil || mode == RequestValidationOff || !gjson.ValidBytes(body) {
+		return nil
+	}
+	return s.root.validate(gjson.ParseBytes(body), "", mode == RequestValidationStrict)
+}
+
+func (n *schemaNode) validate(v gjson.Result, path string, strict bool) *RequestSchemaError {
+	if n.kinds != 0 && !n.accepts(v) {
+		return &RequestSchemaError{Param: path, Code: "invalid_type", Message: fmt.Sprintf("%s must be %s", schemaDisplayPath(path), n.describe())}
+	}
+	if len(n.enum) > 0 && v.Type == gjson.String && !slices.Contains(n.enum, v.Str) {
+		return &RequestSchemaError{Param: path, Code: "invalid_value", Message: fmt.Sprintf("%s must be one of: %s", schemaDisplayPath(path), strings.Join(n.enum, ", "))}
+	}
+	if n.positive && v.Type == gjson.Number && v.Float() < 1 {
+		return &RequestSchemaError{Param: path, Code: "invalid_value", Message: fmt.Sprintf("%s must be greater than or equal to 1", schemaDisplayPath(path))}
+	}
+
+	var err *RequestSchemaError
+	switch {
+	case v.IsObject():
+		for _, name := range n.required {
+			if field := v.Get(name); !field.Exists() || field.Type == gjson.Null {
+				fieldPath := joinSchemaPath(path, name)
+				return &RequestSchemaError{Param: fieldPath, Code: "missing_required_parameter", Message: fmt.Sprintf("%s is required", fieldPath)}
+			}
+		}
+		if n.fields == nil {
+			return nil
+		}
+		v.ForEach(func(key, value gjson.Result) bool {
+			child, ok := n.fields[key.Str]
+			if !ok {
+				if strict && n.closed {
+					fieldPath := joinSchemaPath(path, key.Str)
+					err = &RequestSchemaError{Param: fieldPath, Code: "unknown_parameter", Message: fmt.Sprintf("Unrecognized request argument supplied: %s", fieldPath)}
+					return false
+				}
+				return true
+			}
+			if value.Type == gjson.Null {
+				return true
+			}
+			err = child.validate(value, joinSchemaPath(path, key.Str), strict)
+			return err == nil
+		})
+	case v.IsArray() && n.items != nil:
+		i := 0
+		v.ForEach(func(_, item gjson.Result) bool {
+			err = n.items.validate(item, path+"["+strconv.Itoa(i)+"]", strict)
+			i++
+			return err == nil
+		})
+	}
+	return err
+}
+
+func (n *schemaNode) accepts(v gjson.Result) bool {
+	switch v.Type {
+	case gjson.String:
+		return n.kinds&schemaString != 0
+	case gjson.Number:
+		if n.kinds&schemaNumber != 0 {
+			return true
+		}
+		return n.kinds&schemaInteger != 0 && v.Float() == float64(int64(v.Float()))
+	case gjson.True, gjson.False:
+		return n.kinds&schemaBoolean != 0
+	case gjson.JSON:
+		if v.IsObject() {
+			return n.kinds&schemaObject != 0
+		}
+		return n.kinds&schemaArray != 0
+	}
+	return false
+}
+
+func (n *schemaNode) describe() string {
+	names := []struct {
+		kind schemaKind
+		name string
+	}{
+		{schemaString, "a string"},
+		{schemaNumber, "a number"},
+		{schemaInteger, "an integer"},
+		{schemaBoolean, "a boolean"},
+		{schemaObject, "an object"},
+		{schemaArray, "an array"},
+	}
+	parts := make([]string, 0, 2)
+	for _, item := range names {
+		if n.kinds&item.kind != 0 {
+			parts = append(parts, item.name)
+		}
+	}
+	return strings.Join(parts, " or ")
+}
+
+func joinSchemaPath(path, key string) string {
+	if path == "" {
+		return key
+	}
+	return path + "." + key
+}
+
+func schemaDisplayPath(path string) string {
+	if path == "" {
+		return "request body"
+	}
+	return path
+}
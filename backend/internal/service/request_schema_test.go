@@ -0,0 +1,148 @@
+//go:build unit
+
+package service
+
+import (
+	"testing"
+
+	"github.com/stretchr/testify/require"
+)
+
+func TestAnthropicMessagesSchema_Validate(t *testing.T) {
+	cases := []struct {
+		name  string
+		body  string
+		mode  string
+		param string
+		code  string
+		msg   string
+	}{
+		{
+			name: "valid",
+			body: `{"model":"claude-sonnet-4","max_tokens":1024,"system":[{"type":"text","text":"hi"}],"messages":[{"role":"user","content":"hello"},{"role":"assistant","content":[{"type":"text","text":"x"}]}],"metadata":null,"unknown":1}`,
+			mode: RequestValidationLenient,
+		},
+		{
+			name:  "content type",
+			body:  `{"model":"m","messages":[{"role":"user","content":"a"},{"role":"assistant","content":"b"},{"role":"user","content":42}]}`,
+			mode:  RequestValidationLenient,
+			param: "messages[2].content",
+			code:  "invalid_type",
+			msg:   "messages[2].content must be a string or an array",
+		},
+		{
+			name:  "missing model",
+			body:  `{"messages":[]}`,
+			mode:  RequestValidationLenient,
+			param: "model",
+			code:  "missing_required_parameter",
+			msg:   "model is required",
+		},
+		{
+			name:  "role enum",
+			body:  `{"model":"m","messages":[{"role":"system","content":"x"}]}`,
+			mode:  RequestValidationLenient,
+			param: "messages[0].role",
+			code:  "invalid_value",
+			msg:   "messages[0].role must be one of: user, assistant",
+		},
+		{
+			name:  "block without type",
+			body:  `{"model":"m","messages":[{"role":"user","content":[{"text":"x"}]}]}`,
+			mode:  RequestValidationLenient,
+			param: "messages[0].content[0].type",
+			code:  "missing_required_parameter",
+		},
+		{
+			name:  "max_tokens integer",
+			body:  `{"model":"m","max_tokens":1.5}`,
+			mode:  RequestValidationLenient,
+			param: "max_tokens",
+			code:  "invalid_type",
+			msg:   "max_tokens must be an integer",
+		},
+		{
+			name:  "max_tokens positive",
+			body:  `{"model":"m","max_tokens":0}`,
+			mode:  RequestValidationLenient,
+			param: "max_tokens",
+			code:  "invalid_value",
+		},
+		{
+			name:  "strict unknown top-level",
+			body:  `{"model":"m","foo":1}`,
+			mode:  RequestValidationStrict,
+			param: "foo",
+			code:  "unknown_parameter",
+			msg:   "Unrecognized request argument supplied: foo",
+		},
+		{
+			name:  "strict unknown message field",
+			body:  `{"model":"m","messages":[{"role":"user","content":"x","name":"bob"}]}`,
+			mode:  RequestValidationStrict,
+			param: "messages[0].name",
+			code:  "unknown_parameter",
+		},
+		{
+			name: "strict allows gateway extensions and open blocks",
+			body: `{"model":"m","template_id":"t","template_variables":{"a":"b"},"conversation_id":"c","reasoning_effort":"high","messages":[{"role":"user","content":[{"type":"image","source":{},"cache_control":{"type":"ephemeral"}}]}]}`,
+			mode: RequestValidationStrict,
+		},
+		{
+			name:  "root must be object",
+			body:  `[1]`,
+			mode:  RequestValidationLenient,
+			param: "",
+			code:  "invalid_type",
+			msg:   "request body must be an object",
+		},
+		{
+			name: "off",
+			body: `{"messages":1}`,
+			mode: RequestValidationOff,
+		},
+		{
+			name: "invalid json left to parser",
+			body: `{"model":`,
+			mode: RequestValidationStrict,
+		},
+	}
+	for _, tc := range cases {
+		t.Run(tc.name, func(t *testing.T) {
+			err := AnthropicMessagesSchema.Validate([]byte(tc.body), tc.mode)
+			if tc.code == "" {
+				require.Nil(t, err)
+				return
+			}
+			require.NotNil(t, err)
+			require.Equal(t, tc.param, err.Param)
+			require.Equal(t, tc.code, err.Code)
+			if tc.msg != "" {
+				require.Equal(t, tc.msg, err.Message)
+			}
+		})
+	}
+}
+
+func TestOpenAIResponsesSchema_Validate(t *testing.T) {
+	body := `{"model":"gpt-5","instructions":null,"input":[{"type":"message","role":"user","content":[{"type":"input_text","text":"hi"}]},{"type":"function_call_output","call_id":"c","output":"ok"}],"reasoning":{"effort":"high","summary":"auto"},"tools":[{"type":"function","name":"f"}],"tool_choice":"auto","stream":true}`
+	require.Nil(t, OpenAIResponsesSchema.Validate([]byte(body), RequestValidationStrict))
+
+	err := OpenAIResponsesSchema.Validate([]byte(`{"model":"gpt-5","input":[{"role":"user","content":7}]}`), RequestValidationLenient)
+	require.NotNil(t, err)
+	require.Equal(t, "input[0].content", err.Param)
+	require.Equal(t, "input[0].content must be a string or an array", err.Message)
+
+	err = OpenAIResponsesSchema.Validate([]byte(`{"model":"gpt-5","stream":"yes"}`), RequestValidationLenient)
+	require.NotNil(t, err)
+	require.Equal(t, "stream must be a boolean", err.Message)
+}
+
+func TestGeminiGenerateContentSchema_Validate(t *testing.T) {
+	body := `{"contents":[{"role":"user","parts":[{"text":"hi"}]}],"system_instruction":{"parts":[{"text":"be brief"}]},"generationConfig":{"temperature":0.2}}`
+	require.Nil(t, GeminiGenerateContentSchema.Validate([]byte(body), RequestValidationStrict))
+
+	err := GeminiGenerateContentSchema.Validate([]byte(`{"contents":[{"role":"user","parts":"hi"}]}`), RequestValidationLenient)
+	require.NotNil(t, err)
+	require.Equal(t, "contents[0].parts must be an array", err.Message)
+}
@@ -0,0 +1,831 @@
+package service
+
+import (
+	"context"
+	"fmt"
+	"log"
+	"net/http"
+	"net/textproto"
+	"slices"
+	"sort"
+	"strings"
+	"sync"
+	"time"
+	"unicode/utf8"
+
+	infraerrors "github.com/Wei-Shaw/sub2api/internal/pkg/errors"
+	"github.com/tidwall/gjson"
+	"github.com/tidwall/sjson"
+)
+
+const (
+	// routingRuleCacheTTL 规则、标签与目标分组的本地缓存时间；本实例修改立即生效，其他实例最迟在该时间后生效
+	routingRuleCacheTTL        = 10 * time.Second
+	routingRuleMaxNameLen      = 100
+	routingRuleMaxListItems    = 50
+	routingRuleMaxPatternLen   = 200
+	routingRuleMaxFallbacks    = 10
+	routingRuleMaxOverrides    = 50
+	routingRuleMaxOverridePath = 128
+	apiKeyMaxTags              = 32
+	apiKeyTagMaxLen            = 64
+)
+
+var (
+	ErrRoutingRuleNotFound = infraerrors.NotFound("ROUTING_RULE_NOT_FOUND", "routing rule not found")
+	ErrInvalidAPIKeyTags   = infraerrors.BadRequest("INVALID_API_KEY_TAGS", "at most 32 tags of up to 64 characters (letters, digits, '-', '_', '.', ':') are allowed")
+)
+
+// routingPromptFields 统计提示词长度时读取的顶层字段（Anthropic / OpenAI Responses / Gemini）
+var routingPromptFields = []string{"system", "messages", "instructions", "input", "prompt", "systemInstruction", "contents"}
+
+// RoutingConditions 路由规则匹配条件；未设置的条件不参与匹配，已设置的条件须同时满足
+type RoutingConditions struct {
+	// Models 请求模型匹配模式（支持末尾 * 通配），命中任一即可
+	Models []string `json:"models,omitempty"`
+	// GroupIDs API Key 当前所属分组，命中任一即可
+	GroupIDs []int64 `json:"group_ids,omitempty"`
+	// KeyTags API Key 标签，带有任一标签即可
+	KeyTags []string `json:"key_tags,omitempty"`
+	// MinPromptChars / MaxPromptChars 提示词文本字符数范围（含边界），0 表示不限
+	MinPromptChars int                `json:"min_prompt_chars,omitempty"`
+	MaxPromptChars int                `json:"max_prompt_chars,omitempty"`
+	TimeWindow     *RoutingTimeWindow `json:"time_window,omitempty"`
+	// Headers 请求头条件，须全部满足
+	Headers []RoutingHeaderMatch `json:"headers,omitempty"`
+}
+
+// RoutingTimeWindow 生效时间段 [Start, End)；Start > End 表示跨越午夜（如 22:00-06:00），Start == End 表示全天
+type RoutingTimeWindow struct {
+	Start string `json:"start"`
+	End   string `json:"end"`
+	// Weekdays 生效的星期（0=周日 … 6=周六），为空表示每天；跨午夜的时间段按开始的那一天判断
+	Weekdays []int `json:"weekdays,omitempty"`
+	// Timezone IANA 时区名（如 Asia/Shanghai），为空时使用 UTC
+	Timezone string `json:"timezone,omitempty"`
+}
+
+// RoutingHeaderMatch 请求头条件：Values 为空时请求头存在即可，否则任一值命中任一模式（大小写不敏感，支持末尾 * 通配）
+type RoutingHeaderMatch struct {
+	Name   string   `json:"name"`
+	Values []string `json:"values,omitempty"`
+}
+
+// RoutingRule 声明式路由规则：命中后切换分组（可带降级分组链）并覆盖请求参数
+type RoutingRule struct {
+	ID          int64             `json:"id"`
+	Name        string            `json:"name"`
+	Description string            `json:"description"`
+	Priority    int               `json:"priority"`
+	Enabled     bool              `json:"enabled"`
+	Conditions  RoutingConditions `json:"conditions"`
+	// TargetGroupID 命中后使用的分组，nil 表示沿用 API Key 原分组
+	TargetGroupID *int64 `json:"target_group_id"`
+	// FallbackGroupIDs 目标分组不可用或没有可调度账号时依次尝试的分组
+	FallbackGroupIDs []int64 `json:"fallback_group_ids"`
+	// ParamOverrides 请求体参数覆盖，键为点分隔的 JSON 路径（如 thinking.budget_tokens），值为 null 表示删除该字段
+	ParamOverrides map[string]any `json:"param_overrides"`
+	CreatedAt      time.Time      `json:"created_at"`
+	UpdatedAt      time.Time      `json:"updated_at"`
+}
+
+// RoutingRuleRepository 路由规则与 API Key 标签存储
+type RoutingRuleRepository interface {
+	List(ctx context.Context) ([]*RoutingRule, error)
+	GetByID(ctx context.Context, id int64) (*RoutingRule, error)
+	Create(ctx context.Context, rule *RoutingRule) error
+	Update(ctx context.Context, rule *RoutingRule) error
+	Delete(ctx context.Context, id int64) error
+
+	// GetKeyTags 返回 API Key 的标签（按字母序）
+	GetKeyTags(ctx context.Context, apiKeyID int64) ([]string, error)
+	// SetKeyTags 整体替换 API Key 的标签；API Key 不存在时返回 ErrAPIKeyNotFound
+	SetKeyTags(ctx context.Context, apiKeyID int64, tags []string) error
+	// ListKeyTagsByTags 返回带有任一指定标签的 API Key 及其（属于指定范围内的）标签
+	ListKeyTagsByTags(ctx context.Context, tags []string) (map[int64][]string, error)
+}
+
+// RoutingAccountLister 列出分组内的可调度账号（由调度快照实现），用于跳过降级链中没有可用账号的分组
+type RoutingAccountLister interface {
+	ListSchedulableAccounts(ctx context.Context, groupID *int64, platform string, hasForcePlatform bool) ([]Account, bool, error)
+}
+
+// RoutingRequest 路由匹配输入
+type RoutingRequest struct {
+	APIKeyID int64
+	// KeyTags 非 nil 时直接使用（规则试算），否则按 APIKeyID 查询标签
+	KeyTags []string
+	// Group API Key 当前分组，nil 表示未绑定分组
+	Group       *Group
+	Model       string
+	PromptChars int
+	Header      http.Header
+	Now         time.Time
+}
+
+// RoutingSkippedGroup 降级链中被跳过的分组
+type RoutingSkippedGroup struct {
+	GroupID int64  `json:"group_id"`
+	Reason  string `json:"reason"`
+}
+
+// RoutingDecision 路由结果
+type RoutingDecision struct {
+	Rule *RoutingRule `json:"rule"`
+	// GroupID 最终使用的分组；Group 为 nil 时表示沿用 API Key 原分组
+	GroupID        *int64                `json:"group_id"`
+	Group          *Group                `json:"-"`
+	Skipped        []RoutingSkippedGroup `json:"skipped,omitempty"`
+	ParamOverrides map[string]any        `json:"param_overrides,omitempty"`
+}
+
+// RoutingEvaluateInput 规则试算输入（管理后台调试用）
+type RoutingEvaluateInput struct {
+	GroupID     *int64            `json:"group_id"`
+	KeyTags     []string          `json:"key_tags"`
+	Model       string            `json:"model"`
+	PromptChars int               `json:"prompt_chars"`
+	Headers     map[string]string `json:"headers"`
+	Time        *time.Time        `json:"time"`
+}
+
+// routingRuleState 路由使用的规则快照
+type routingRuleState struct {
+	rules []*RoutingRule
+	// keyTags API Key ID -> 标签（仅包含规则中引用到的标签）
+	keyTags map[int64][]string
+	// groups 规则引用的目标 / 降级分组，加载失败的分组不在其中
+	groups    map[int64]*Group
+	locations map[string]*time.Location
+}
+
+// RoutingRuleService 声明式路由规则：按模型、API Key 标签、提示词长度、时间段与请求头匹配请求，
+// 命中的第一条规则决定请求使用的分组（含降级分组链）与参数覆盖。
+//
+// 分组切换只在同平台的非订阅分组之间进行：订阅额度绑定在分组上，订阅分组的 API Key 仅应用参数覆盖。
+// 切换后的分组同时用于调度与计费。
+type RoutingRuleService struct {
+	repo      RoutingRuleRepository
+	groupRepo GroupRepository
+	accounts  RoutingAccountLister
+
+	stateMu       sync.RWMutex
+	state         *routingRuleState
+	stateLoadedAt time.Time
+}
+
+// NewRoutingRuleService 创建路由规则服务
+func NewRoutingRuleService(repo RoutingRuleRepository, groupRepo GroupRepository, schedulerSnapshot *SchedulerSnapshotService) *RoutingRuleService {
+	s := &RoutingRuleService{repo: repo, groupRepo: groupRepo}
+	if schedulerSnapshot != nil {
+		s.accounts = schedulerSnapshot
+	}
+	return s
+}
+
+// List 列出全部规则（按匹配顺序）
+func (s *RoutingRuleService) List(ctx context.Context) ([]*RoutingRule, error) {
+	return s.repo.List(ctx)
+}
+
+// GetByID 获取规则
+func (s *RoutingRuleService) GetByID(ctx context.Context, id int64) (*RoutingRule, error) {
+	return s.repo.GetByID(ctx, id)
+}
+
+// Create 创建规则
+func (s *RoutingRuleService) Create(ctx context.Context, rule *RoutingRule) error {
+	if err := s.validateRule(ctx, rule); err != nil {
+		return err
+	}
+	if err := s.repo.Create(ctx, rule); err != nil {
+		return err
+	}
+	s.invalidate()
+	return nil
+}
+
+// Update 更新规则
+func (s *RoutingRuleService) Update(ctx context.Context, rule *RoutingRule) error {
+	if err := s.validateRule(ctx, rule); err != nil {
+		return err
+	}
+	if err := s.repo.Update(ctx, rule); err != nil {
+		return err
+	}
+	s.invalidate()
+	return nil
+}
+
+// Delete 删除规则
+func (s *RoutingRuleService) Delete(ctx context.Context, id int64) error {
+	if err := s.repo.Delete(ctx, id); err != nil {
+		return err
+	}
+	s.invalidate()
+	return nil
+}
+
+// GetKeyTags 获取 API Key 标签
+func (s *RoutingRuleService) GetKeyTags(ctx context.Context, apiKeyID int64) ([]string, error) {
+	return s.repo.GetKeyTags(ctx, apiKeyID)
+}
+
+// SetKeyTags 整体替换 API Key 标签，返回规范化后的标签
+func (s *RoutingRuleService) SetKeyTags(ctx context.Context, apiKeyID int64, tags []string) ([]string, error) {
+	normalized, ok := normalizeAPIKeyTags(tags)
+	if !ok || len(normalized) > apiKeyMaxTags {
+		return nil, ErrInvalidAPIKeyTags
+	}
+	if err := s.repo.SetKeyTags(ctx, apiKeyID, normalized); err != nil {
+		return nil, err
+	}
+	s.invalidate()
+	return normalized, nil
+}
+
+// Active 是否存在启用的规则；网关在没有规则时可跳过读取请求体
+func (s *RoutingRuleService) Active(ctx context.Context) bool {
+	return s != nil && s.loadState(ctx) != nil
+}
+
+// Route 按规则为请求选择分组与参数覆盖；未命中任何规则时返回 nil
+func (s *RoutingRuleService) Route(ctx context.Context, req *RoutingRequest) *RoutingDecision {
+	if s == nil || req == nil {
+		return nil
+	}
+	state := s.loadState(ctx)
+	if state == nil {
+		return nil
+	}
+	rule := state.match(req)
+	if rule == nil {
+		return nil
+	}
+	decision := &RoutingDecision{Rule: rule, ParamOverrides: rule.ParamOverrides}
+	s.resolveGroup(ctx, state, req.Group, rule, decision)
+	return decision
+}
+
+// Evaluate 按给定的请求特征试算规则（不发出请求），返回 nil 表示没有命中任何规则
+func (s *RoutingRuleService) Evaluate(ctx context.Context, in *RoutingEvaluateInput) (*RoutingDecision, error) {
+	req := &RoutingRequest{
+		KeyTags:     []string{},
+		Model:       strings.TrimSpace(in.Model),
+		PromptChars: in.PromptChars,
+		Header:      make(http.Header, len(in.Headers)),
+		Now:         time.Now(),
+	}
+	if tags, ok := normalizeAPIKeyTags(in.KeyTags); ok {
+		req.KeyTags = tags
+	}
+	for name, value := range in.Headers {
+		req.Header.Set(name, value)
+	}
+	if in.Time != nil {
+		req.Now = *in.Time
+	}
+	if in.GroupID != nil && *in.GroupID > 0 {
+		group, err := s.groupRepo.GetByIDLite(ctx, *in.GroupID)
+		if err != nil {
+			return nil, err
+		}
+		req.Group = group
+	}
+	return s.Route(ctx, req), nil
+}
+
+// resolveGroup 依次检查目标分组与降级分组，选出第一个可用且有可调度账号的分组
+func (s *RoutingRuleService) resolveGroup(ctx context.Context, state *routingRuleState, current *Group, rule *RoutingRule, decision *RoutingDecision) {
+	chain := routingGroupChain(current, rule)
+	if len(chain) == 0 {
+		return
+	}
+	if current != nil && current.IsSubscriptionType() {
+		decision.Skipped = append(decision.Skipped, RoutingSkippedGroup{GroupID: current.ID, Reason: "subscription group keys are not rerouted"})
+		return
+	}
+	platform := PlatformAnthropic
+	if current != nil {
+		platform = current.Platform
+	}
+
+	var firstUsable *Group
+	for _, id := range chain {
+		group := state.groups[id]
+		if current != nil && id == current.ID {
+			group = current
+		}
+		reason := ""
+		switch {
+		case group == nil:
+			reason = "group not found"
+		case !group.IsActive():
+			reason = "group is not active"
+		case group.IsSubscriptionType():
+			reason = "subscription groups cannot be routing targets"
+		case group.Platform != platform:
+			reason = fmt.Sprintf("group platform %s does not match %s", group.Platform, platform)
+		case !s.hasSchedulableAccounts(ctx, group):
+			reason = "no schedulable accounts"
+			if firstUsable == nil {
+				firstUsable = group
+			}
+		default:
+			decision.useGroup(current, group)
+			return
+		}
+		decision.Skipped = append(decision.Skipped, RoutingSkippedGroup{GroupID: id, Reason: reason})
+	}
+	// 链上分组都没有可调度账号时使用第一个可用分组，由调度返回原有的无可用账号错误
+	if firstUsable != nil {
+		decision.useGroup(current, firstUsable)
+	}
+}
+
+func (d *RoutingDecision) useGroup(current, group *Group) {
+	id := group.ID
+	d.GroupID = &id
+	if current == nil || current.ID != group.ID {
+		d.Group = group
+	}
+}
+
+// routingGroupChain 规则的分组链：目标分组（未设置时为 API Key 原分组）后接降级分组，已去重
+func routingGroupChain(current *Group, rule *RoutingRule) []int64 {
+	if rule.TargetGroupID == nil && len(rule.FallbackGroupIDs) == 0 {
+		return nil
+	}
+	chain := make([]int64, 0, len(rule.FallbackGroupIDs)+1)
+	switch {
+	case rule.TargetGroupID != nil:
+		chain = append(chain, *rule.TargetGroupID)
+	case current != nil:
+		chain = append(chain, current.ID)
+	}
+	for _, id := range rule.FallbackGroupIDs {
+		if !slices.Contains(chain, id) {
+			chain = append(chain, id)
+		}
+	}
+	return chain
+}
+
+func (s *RoutingRuleService) hasSchedulableAccounts(ctx context.Context, group *Group) bool {
+	if s.accounts == nil {
+		return true
+	}
+	groupID := group.ID
+	accounts, _, err := s.accounts.ListSchedulableAccounts(ctx, &groupID, group.Platform, false)
+	if err != nil {
+		// 快照读取失败时不跳过分组，交由调度处理
+		return true
+	}
+	return len(accounts) > 0
+}
+
+// match 返回第一条命中的规则；规则按 priority 升序、同优先级按 ID 升序排列
+func (st *routingRuleState) match(req *RoutingRequest) *RoutingRule {
+	for _, rule := range st.rules {
+		if st.matches(&rule.Conditions, req) {
+			return rule
+		}
+	}
+	return nil
+}
+
+func (st *routingRuleState) matches(c *RoutingConditions, req *RoutingRequest) bool {
+	if len(c.Models) > 0 && !slices.ContainsFunc(c.Models, func(pattern string) bool { return matchModelPattern(pattern, req.Model) }) {
+		return false
+	}
+	if len(c.GroupIDs) > 0 && (req.Group == nil || !slices.Contains(c.GroupIDs, req.Group.ID)) {
+		return false
+	}
+	if len(c.KeyTags) > 0 {
+		tags := req.KeyTags
+		if tags == nil {
+			tags = st.keyTags[req.APIKeyID]
+		}
+		if !slices.ContainsFunc(c.KeyTags, func(tag string) bool { return slices.Contains(tags, tag) }) {
+			return false
+		}
+	}
+	if c.MinPromptChars > 0 && req.PromptChars < c.MinPromptChars {
+		return false
+	}
+	if c.MaxPromptChars > 0 && req.PromptChars > c.MaxPromptChars {
+		return false
+	}
+	if c.TimeWindow != nil && !c.TimeWindow.contains(req.Now, st.location(c.TimeWindow.Timezone)) {
+		return false
+	}
+	for i := range c.Headers {
+		if !c.Headers[i].matches(req.Header) {
+			return false
+		}
+	}
+	return true
+}
+
+func (st *routingRuleState) location(name string) *time.Location {
+	if loc, ok := st.locations[name]; ok {
+		return loc
+	}
+	return time.UTC
+}
+
+func (w *RoutingTimeWindow) contains(now time.Time, loc *time.Location) bool {
+	start, _ := parseRoutingClock(w.Start)
+	end, _ := parseRoutingClock(w.End)
+	t := now.In(loc)
+	minute := t.Hour()*60 + t.Minute()
+	day := t.Weekday()
+	switch {
+	case start == end:
+	case start < end:
+		if minute < start || minute >= end {
+			return false
+		}
+	default:
+		if minute < start && minute >= end {
+			return false
+		}
+		// 跨午夜时间段的后半段属于前一天开始的时间段
+		if minute < end {
+			day = (day + 6) % 7
+		}
+	}
+	return len(w.Weekdays) == 0 || slices.Contains(w.Weekdays, int(day))
+}
+
+func (m *RoutingHeaderMatch) matches(header http.Header) bool {
+	values := header.Values(m.Name)
+	if len(values) == 0 {
+		return false
+	}
+	if len(m.Values) == 0 {
+		return true
+	}
+	for _, value := range values {
+		value = strings.ToLower(strings.TrimSpace(value))
+		for _, pattern := range m.Values {
+			if matchModelPattern(strings.ToLower(pattern), value) {
+				return true
+			}
+		}
+	}
+	return false
+}
+
+// parseRoutingClock 解析 HH:MM，返回距零点的分钟数
+func parseRoutingClock(s string) (int, error) {
+	t, err := time.Parse("15:04", strings.TrimSpace(s))
+	if err != nil {
+		return 0, err
+	}
+	return t.Hour()*60 + t.Minute(), nil
+}
+
+func (s *RoutingRuleService) invalidate() {
+	s.stateMu.Lock()
+	s.stateLoadedAt = time.Time{}
+	s.stateMu.Unlock()
+}
+
+// loadState 返回规则快照（带短时缓存）；加载失败时沿用上一份快照，首次加载失败则不做路由
+func (s *RoutingRuleService) loadState(ctx context.Context) *routingRuleState {
+	s.stateMu.RLock()
+	if !s.stateLoadedAt.IsZero() && time.Since(s.stateLoadedAt) < routingRuleCacheTTL {
+		state := s.state
+		s.stateMu.RUnlock()
+		return state
+	}
+	s.stateMu.RUnlock()
+
+	state, err := s.buildState(ctx)
+	s.stateMu.Lock()
+	defer s.stateMu.Unlock()
+	if err != nil {
+		log.Printf("[RoutingRule] load rules failed: %v", err)
+		state = s.state
+	}
+	s.state = state
+	s.stateLoadedAt = time.Now()
+	return state
+}
+
+func (s *RoutingRuleService) buildState(ctx context.Context) (*routingRuleState, error) {
+	rules, err := s.repo.List(ctx)
+	if err != nil {
+		return nil, err
+	}
+	state := &routingRuleState{
+		groups:    make(map[int64]*Group),
+		locations: make(map[string]*time.Location),
+	}
+	tagSet := make(map[string]struct{})
+	for _, rule := range rules {
+		if rule == nil || !rule.Enabled {
+			continue
+		}
+		state.rules = append(state.rules, rule)
+		for _, tag := range rule.Conditions.KeyTags {
+			tagSet[tag] = struct{}{}
+		}
+		if tw := rule.Conditions.TimeWindow; tw != nil && tw.Timezone != "" {
+			if _, ok := state.locations[tw.Timezone]; !ok {
+				if loc, err := time.LoadLocation(tw.Timezone); err == nil {
+					state.locations[tw.Timezone] = loc
+				}
+			}
+		}
+		for _, id := range routingGroupChain(nil, rule) {
+			if _, ok := state.groups[id]; ok {
+				continue
+			}
+			group, err := s.groupRepo.GetByIDLite(ctx, id)
+			if err != nil {
+				log.Printf("[RoutingRule] rule %d: load group %d failed: %v", rule.ID, id, err)
+				continue
+			}
+			state.groups[id] = group
+		}
+	}
+	if len(state.rules) == 0 {
+		return nil, nil
+	}
+	sort.SliceStable(state.rules, func(i, j int) bool {
+		if state.rules[i].Priority != state.rules[j].Priority {
+			return state.rules[i].Priority < state.rules[j].Priority
+		}
+		return state.rules[i].ID < state.rules[j].ID
+	})
+	if len(tagSet) > 0 {
+		tags := make([]string, 0, len(tagSet))
+		for tag := range tagSet {
+			tags = append(tags, tag)
+		}
+		sort.Strings(tags)
+		if state.keyTags, err = s.repo.ListKeyTagsByTags(ctx, tags); err != nil {
+			return nil, err
+		}
+	}
+	return state, nil
+}
+
+// validateRule 校验并规范化规则，目标与降级分组须存在
+func (s *RoutingRuleService) validateRule(ctx context.Context, rule *RoutingRule) error {
+	if err := normalizeRoutingRule(rule); err != nil {
+		return err
+	}
+	for _, id := range routingGroupChain(nil, rule) {
+		if _, err := s.groupRepo.GetByIDLite(ctx, id); err != nil {
+			if infraerrors.IsNotFound(err) {
+				return invalidRoutingRule("group %d not found", id)
+			}
+			return err
+		}
+	}
+	return nil
+}
+
+func invalidRoutingRule(format string, a ...any) error {
+	return infraerrors.Newf(http.StatusBadRequest, "INVALID_ROUTING_RULE", format, a...)
+}
+
+func normalizeRoutingRule(rule *RoutingRule) error {
+	if rule == nil {
+		return invalidRoutingRule("rule is required")
+	}
+	rule.Name = strings.TrimSpace(rule.Name)
+	rule.Description = strings.TrimSpace(rule.Description)
+	if rule.Name == "" || len(rule.Name) > routingRuleMaxNameLen {
+		return invalidRoutingRule("name is required and must not exceed %d characters", routingRuleMaxNameLen)
+	}
+	if err := normalizeRoutingConditions(&rule.Conditions); err != nil {
+		return err
+	}
+
+	if rule.TargetGroupID != nil && *rule.TargetGroupID <= 0 {
+		rule.TargetGroupID = nil
+	}
+	fallbacks := make([]int64, 0, len(rule.FallbackGroupIDs))
+	for _, id := range rule.FallbackGroupIDs {
+		if id <= 0 || slices.Contains(fallbacks, id) || (rule.TargetGroupID != nil && id == *rule.TargetGroupID) {
+			continue
+		}
+		fallbacks = append(fallbacks, id)
+	}
+	if len(fallbacks) > routingRuleMaxFallbacks {
+		return invalidRoutingRule("at most %d fallback groups are allowed", routingRuleMaxFallbacks)
+	}
+	rule.FallbackGroupIDs = fallbacks
+
+	if rule.ParamOverrides == nil {
+		rule.ParamOverrides = map[string]any{}
+	}
+	if len(rule.ParamOverrides) > routingRuleMaxOverrides {
+		return invalidRoutingRule("at most %d param overrides are allowed", routingRuleMaxOverrides)
+	}
+	for path := range rule.ParamOverrides {
+		if !isValidRoutingOverridePath(path) {
+			return invalidRoutingRule("invalid param override path %q", path)
+		}
+	}
+
+	if rule.TargetGroupID == nil && len(rule.FallbackGroupIDs) == 0 && len(rule.ParamOverrides) == 0 {
+		return invalidRoutingRule("rule must set target_group_id, fallback_group_ids or param_overrides")
+	}
+	return nil
+}
+
+func normalizeRoutingConditions(c *RoutingConditions) error {
+	models, err := normalizeRoutingList(c.Models, "models", func(v string) string { return v })
+	if err != nil {
+		return err
+	}
+	c.Models = models
+
+	groupIDs := make([]int64, 0, len(c.GroupIDs))
+	for _, id := range c.GroupIDs {
+		if id > 0 && !slices.Contains(groupIDs, id) {
+			groupIDs = append(groupIDs, id)
+		}
+	}
+	if len(groupIDs) > routingRuleMaxListItems {
+		return invalidRoutingRule("at most %d group_ids are allowed", routingRuleMaxListItems)
+	}
+	c.GroupIDs = groupIDs
+
+	tags, ok := normalizeAPIKeyTags(c.KeyTags)
+	if !ok || len(tags) > routingRuleMaxListItems {
+		return invalidRoutingRule("key_tags must be valid tags (letters, digits, '-', '_', '.', ':')")
+	}
+	c.KeyTags = tags
+
+	if c.MinPromptChars < 0 || c.MaxPromptChars < 0 || (c.MaxPromptChars > 0 && c.MinPromptChars > c.MaxPromptChars) {
+		return invalidRoutingRule("prompt char range is invalid")
+	}
+
+	if tw := c.TimeWindow; tw != nil {
+		if _, err := parseRoutingClock(tw.Start); err != nil {
+			return invalidRoutingRule("time_window.start must be HH:MM")
+		}
+		if _, err := parseRoutingClock(tw.End); err != nil {
+			return invalidRoutingRule("time_window.end must be HH:MM")
+		}
+		tw.Start = strings.TrimSpace(tw.Start)
+		tw.End = strings.TrimSpace(tw.End)
+		tw.Timezone = strings.TrimSpace(tw.Timezone)
+		if tw.Timezone != "" {
+			if _, err := time.LoadLocation(tw.Timezone); err != nil {
+				return invalidRoutingRule("unknown time_window.timezone %q", tw.Timezone)
+			}
+		}
+		weekdays := make([]int, 0, len(tw.Weekdays))
+		for _, d := range tw.Weekdays {
+			if d < 0 || d > 6 {
+				return invalidRoutingRule("time_window.weekdays must be between 0 (Sunday) and 6 (Saturday)")
+			}
+			if !slices.Contains(weekdays, d) {
+				weekdays = append(weekdays, d)
+			}
+		}
+		slices.Sort(weekdays)
+		tw.Weekdays = weekdays
+	}
+
+	if len(c.Headers) > routingRuleMaxListItems {
+		return invalidRoutingRule("at most %d header conditions are allowed", routingRuleMaxListItems)
+	}
+	for i := range c.Headers {
+		h := &c.Headers[i]
+		h.Name = textproto.CanonicalMIMEHeaderKey(strings.TrimSpace(h.Name))
+		if h.Name == "" || strings.ContainsAny(h.Name, " \t:") {
+			return invalidRoutingRule("header name is required")
+		}
+		values, err := normalizeRoutingList(h.Values, "header values", strings.ToLower)
+		if err != nil {
+			return err
+		}
+		h.Values = values
+	}
+	return nil
+}
+
+// normalizeRoutingList 去除空白与重复项并限制数量与长度
+func normalizeRoutingList(items []string, field string, transform func(string) string) ([]string, error) {
+	out := make([]string, 0, len(items))
+	for _, item := range items {
+		item = transform(strings.TrimSpace(item))
+		if item == "" || slices.Contains(out, item) {
+			continue
+		}
+		if len(item) > routingRuleMaxPatternLen {
+			return nil, invalidRoutingRule("%s entries must not exceed %d characters", field, routingRuleMaxPatternLen)
+		}
+		out = append(out, item)
+	}
+	if len(out) > routingRuleMaxListItems {
+		return nil, invalidRoutingRule("at most %d %s are allowed", routingRuleMaxListItems, field)
+	}
+	return out, nil
+}
+
+// isValidRoutingOverridePath 参数覆盖路径仅允许点分隔的字段名；model 由模型映射 / 金丝雀路由负责，不允许覆盖
+func isValidRoutingOverridePath(path string) bool {
+	if path == "" || len(path) > routingRuleMaxOverridePath || path == "model" {
+		return false
+	}
+	for _, segment := range strings.Split(path, ".") {
+		if segment == "" {
+			return false
+		}
+		for _, r := range segment {
+			if !isRoutingIdentRune(r) {
+				return false
+			}
+		}
+	}
+	return true
+}
+
+func isRoutingIdentRune(r rune) bool {
+	return r >= 'a' && r <= 'z' || r >= 'A' && r <= 'Z' || r >= '0' && r <= '9' || r == '_' || r == '-'
+}
+
+// normalizeAPIKeyTags 标签统一为小写并去重排序；包含非法字符时返回 false
+func normalizeAPIKeyTags(tags []string) ([]string, bool) {
+	out := make([]string, 0, len(tags))
+	for _, tag := range tags {
+		tag = strings.ToLower(strings.TrimSpace(tag))
+		if tag == "" {
+			continue
+		}
+		if len(tag) > apiKeyTagMaxLen {
+			return nil, false
+		}
+		for _, r := range tag {
+			if !isRoutingIdentRune(r) && r != '.' && r != ':' {
+				return nil, false
+			}
+		}
+		if !slices.Contains(out, tag) {
+			out = append(out, tag)
+		}
+	}
+	sort.Strings(out)
+	return out, true
+}
+
+// ApplyRoutingParamOverrides 将参数覆盖写入 JSON 请求体（按路径字母序），值为 nil 时删除对应字段
+func ApplyRoutingParamOverrides(body []byte, overrides map[string]any) ([]byte, error) {
+	if len(overrides) == 0 || !gjson.ValidBytes(body) {
+		return body, nil
+	}
+	paths := make([]string, 0, len(overrides))
+	for path := range overrides {
+		paths = append(paths, path)
+	}
+	sort.Strings(paths)
+	out := body
+	for _, path := range paths {
+		var err error
+		if value := overrides[path]; value == nil {
+			out, err = sjson.DeleteBytes(out, path)
+		} else {
+			out, err = sjson.SetBytes(out, path, value)
+		}
+		if err != nil {
+			return body, err
+		}
+	}
+	return out, nil
+}
+
+// RoutingPromptChars 统计请求体中提示词文本的字符数（不含图片等非文本内容）
+func RoutingPromptChars(body []byte) int {
+	root := gjson.ParseBytes(body)
+	n := 0
+	for _, field := range routingPromptFields {
+		n += countRoutingPromptChars(root.Get(field))
+	}
+	return n
+}
+
+func countRoutingPromptChars(v gjson.Result) int {
+	switch {
+	case v.Type == gjson.String:
+		return utf8.RuneCountInString(v.Str)
+	case v.IsArray():
+		n := 0
+		v.ForEach(func(_, item gjson.Result) bool {
+			n += countRoutingPromptChars(item)
+			return true
+		})
+		return n
+	case v.IsObject():
+		return countRoutingPromptChars(v.Get("text")) + countRoutingPromptChars(v.Get("content")) + countRoutingPromptChars(v.Get("parts"))
+	}
+	return 0
+}
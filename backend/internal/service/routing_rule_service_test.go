@@ -0,0 +1,208 @@
+//go:build unit
+
+package service
+
+import (
+	"context"
+	"net/http"
+	"testing"
+	"time"
+
+	"github.com/stretchr/testify/require"
+)
+
+type routingRuleRepoStub struct {
+	RoutingRuleRepository
+	rules   []*RoutingRule
+	keyTags map[int64][]string
+}
+
+func (r *routingRuleRepoStub) List(ctx context.Context) ([]*RoutingRule, error) {
+	return r.rules, nil
+}
+
+func (r *routingRuleRepoStub) Create(ctx context.Context, rule *RoutingRule) error {
+	rule.ID = int64(len(r.rules) + 1)
+	r.rules = append(r.rules, rule)
+	return nil
+}
+
+func (r *routingRuleRepoStub) ListKeyTagsByTags(ctx context.Context, tags []string) (map[int64][]string, error) {
+	return r.keyTags, nil
+}
+
+type routingGroupRepoStub struct {
+	GroupRepository
+	groups map[int64]*Group
+}
+
+func (r *routingGroupRepoStub) GetByIDLite(ctx context.Context, id int64) (*Group, error) {
+	if g, ok := r.groups[id]; ok {
+		return g, nil
+	}
+	return nil, ErrGroupNotFound
+}
+
+type routingAccountListerStub struct {
+	accounts map[int64]int
+}
+
+func (l *routingAccountListerStub) ListSchedulableAccounts(ctx context.Context, groupID *int64, platform string, hasForcePlatform bool) ([]Account, bool, error) {
+	return make([]Account, l.accounts[*groupID]), false, nil
+}
+
+func routingTestGroup(id int64, platform string) *Group {
+	return &Group{ID: id, Platform: platform, Status: StatusActive, SubscriptionType: SubscriptionTypeStandard, Hydrated: true}
+}
+
+func newRoutingRuleTestService(rules ...*RoutingRule) (*RoutingRuleService, *routingAccountListerStub) {
+	groups := map[int64]*Group{
+		1: routingTestGroup(1, PlatformAnthropic),
+		2: routingTestGroup(2, PlatformAnthropic),
+		3: routingTestGroup(3, PlatformAnthropic),
+		4: routingTestGroup(4, PlatformOpenAI),
+	}
+	repo := &routingRuleRepoStub{rules: rules, keyTags: map[int64][]string{7: {"batch"}}}
+	svc := NewRoutingRuleService(repo, &routingGroupRepoStub{groups: groups}, nil)
+	lister := &routingAccountListerStub{accounts: map[int64]int{1: 1, 2: 1, 3: 1, 4: 1}}
+	svc.accounts = lister
+	return svc, lister
+}
+
+func routingInt64(v int64) *int64 { return &v }
+
+func TestRoutingRuleService_MatchConditions(t *testing.T) {
+	svc, _ := newRoutingRuleTestService(
+		&RoutingRule{ID: 1, Priority: 10, Enabled: true, Conditions: RoutingConditions{Models: []string{"claude-opus-*"}}, TargetGroupID: routingInt64(2)},
+		&RoutingRule{ID: 2, Priority: 0, Enabled: true, Conditions: RoutingConditions{KeyTags: []string{"batch"}}, TargetGroupID: routingInt64(3)},
+		&RoutingRule{ID: 3, Priority: 20, Enabled: true, Conditions: RoutingConditions{MinPromptChars: 1000}, ParamOverrides: map[string]any{"max_tokens": 1024}},
+		&RoutingRule{ID: 4, Priority: -1, Enabled: false, Conditions: RoutingConditions{}, TargetGroupID: routingInt64(3)},
+		&RoutingRule{ID: 5, Priority: 30, Enabled: true, Conditions: RoutingConditions{Headers: []RoutingHeaderMatch{{Name: "X-Client", Values: []string{"cli-*"}}}}, TargetGroupID: routingInt64(2)},
+	)
+	ctx := context.Background()
+	current := routingTestGroup(1, PlatformAnthropic)
+
+	// 标签规则优先级最高（禁用的规则不参与匹配）
+	d := svc.Route(ctx, &RoutingRequest{APIKeyID: 7, Group: current, Model: "claude-opus-4"})
+	require.NotNil(t, d)
+	require.Equal(t, int64(2), d.Rule.ID)
+	require.Equal(t, int64(3), *d.GroupID)
+	require.Equal(t, int64(3), d.Group.ID)
+
+	d = svc.Route(ctx, &RoutingRequest{APIKeyID: 8, Group: current, Model: "claude-opus-4"})
+	require.NotNil(t, d)
+	require.Equal(t, int64(1), d.Rule.ID)
+	require.Equal(t, int64(2), *d.GroupID)
+
+	// 仅参数覆盖的规则不切换分组
+	d = svc.Route(ctx, &RoutingRequest{APIKeyID: 8, Group: current, Model: "claude-sonnet-4", PromptChars: 1000})
+	require.NotNil(t, d)
+	require.Equal(t, int64(3), d.Rule.ID)
+	require.Nil(t, d.GroupID)
+	require.Nil(t, d.Group)
+	require.Equal(t, map[string]any{"max_tokens": 1024}, d.ParamOverrides)
+
+	header := http.Header{}
+	header.Set("x-client", "CLI-2.0")
+	d = svc.Route(ctx, &RoutingRequest{APIKeyID: 8, Group: current, Model: "claude-sonnet-4", Header: header})
+	require.NotNil(t, d)
+	require.Equal(t, int64(5), d.Rule.ID)
+
+	require.Nil(t, svc.Route(ctx, &RoutingRequest{APIKeyID: 8, Group: current, Model: "claude-sonnet-4", PromptChars: 10}))
+}
+
+func TestRoutingRuleService_FallbackChain(t *testing.T) {
+	svc, lister := newRoutingRuleTestService(
+		&RoutingRule{ID: 1, Enabled: true, TargetGroupID: routingInt64(2), FallbackGroupIDs: []int64{4, 3}},
+	)
+	ctx := context.Background()
+	current := routingTestGroup(1, PlatformAnthropic)
+
+	// 目标分组无可调度账号，跳过不同平台的分组后降级到 3
+	lister.accounts[2] = 0
+	d := svc.Route(ctx, &RoutingRequest{Group: current})
+	require.NotNil(t, d)
+	require.Equal(t, int64(3), *d.GroupID)
+	require.Len(t, d.Skipped, 2)
+	require.Equal(t, int64(2), d.Skipped[0].GroupID)
+	require.Equal(t, int64(4), d.Skipped[1].GroupID)
+
+	// 全部没有可调度账号时使用链上第一个可用分组
+	lister.accounts[3] = 0
+	d = svc.Route(ctx, &RoutingRequest{Group: current})
+	require.NotNil(t, d)
+	require.Equal(t, int64(2), *d.GroupID)
+
+	// 订阅分组的 Key 不切换分组
+	sub := routingTestGroup(1, PlatformAnthropic)
+	sub.SubscriptionType = SubscriptionTypeSubscription
+	d = svc.Route(ctx, &RoutingRequest{Group: sub})
+	require.NotNil(t, d)
+	require.Nil(t, d.GroupID)
+	require.Nil(t, d.Group)
+}
+
+func TestRoutingTimeWindow_Contains(t *testing.T) {
+	w := &RoutingTimeWindow{Start: "22:00", End: "06:00", Weekdays: []int{5}}
+	// 周五 23:00 与周六 02:00 都属于周五开始的时间段
+	require.True(t, w.contains(time.Date(2026, 10, 16, 23, 0, 0, 0, time.UTC), time.UTC))
+	require.True(t, w.contains(time.Date(2026, 10, 17, 2, 0, 0, 0, time.UTC), time.UTC))
+	require.False(t, w.contains(time.Date(2026, 10, 17, 23, 0, 0, 0, time.UTC), time.UTC))
+	require.False(t, w.contains(time.Date(2026, 10, 16, 12, 0, 0, 0, time.UTC), time.UTC))
+
+	day := &RoutingTimeWindow{Start: "09:00", End: "18:00"}
+	require.True(t, day.contains(time.Date(2026, 10, 16, 9, 0, 0, 0, time.UTC), time.UTC))
+	require.False(t, day.contains(time.Date(2026, 10, 16, 18, 0, 0, 0, time.UTC), time.UTC))
+}
+
+func TestRoutingRuleService_CreateValidation(t *testing.T) {
+	svc, _ := newRoutingRuleTestService()
+	ctx := context.Background()
+
+	require.Error(t, svc.Create(ctx, &RoutingRule{Name: "noop", Enabled: true}))
+	require.Error(t, svc.Create(ctx, &RoutingRule{Name: "model", ParamOverrides: map[string]any{"model": "x"}}))
+	require.Error(t, svc.Create(ctx, &RoutingRule{Name: "bad path", ParamOverrides: map[string]any{"a..b": 1}}))
+	require.Error(t, svc.Create(ctx, &RoutingRule{Name: "missing group", TargetGroupID: routingInt64(99)}))
+	require.Error(t, svc.Create(ctx, &RoutingRule{Name: "bad window", TargetGroupID: routingInt64(2), Conditions: RoutingConditions{TimeWindow: &RoutingTimeWindow{Start: "25:00", End: "06:00"}}}))
+
+	rule := &RoutingRule{
+		Name:             " long prompts ",
+		TargetGroupID:    routingInt64(2),
+		FallbackGroupIDs: []int64{2, 3, 3, 0},
+		Conditions:       RoutingConditions{KeyTags: []string{"Batch", "batch"}, Headers: []RoutingHeaderMatch{{Name: "x-client", Values: []string{"CLI"}}}},
+	}
+	require.NoError(t, svc.Create(ctx, rule))
+	require.Equal(t, "long prompts", rule.Name)
+	require.Equal(t, []int64{3}, rule.FallbackGroupIDs)
+	require.Equal(t, []string{"batch"}, rule.Conditions.KeyTags)
+	require.Equal(t, "X-Client", rule.Conditions.Headers[0].Name)
+	require.Equal(t, []string{"cli"}, rule.Conditions.Headers[0].Values)
+}
+
+func TestApplyRoutingParamOverrides(t *testing.T) {
+	body := []byte(`{"model":"claude-sonnet-4","max_tokens":8192,"temperature":1}`)
+	out, err := ApplyRoutingParamOverrides(body, map[string]any{
+		"max_tokens":             1024,
+		"temperature":            nil,
+		"thinking.budget_tokens": 2048,
+	})
+	require.NoError(t, err)
+	require.JSONEq(t, `{"model":"claude-sonnet-4","max_tokens":1024,"thinking":{"budget_tokens":2048}}`, string(out))
+
+	// 非 JSON 请求体保持不变
+	out, err = ApplyRoutingParamOverrides([]byte("not json"), map[string]any{"max_tokens": 1})
+	require.NoError(t, err)
+	require.Equal(t, "not json", string(out))
+}
+
+func TestRoutingPromptChars(t *testing.T) {
+	body := []byte(`{
+		"system": [{"type":"text","text":"你好"}],
+		"messages": [
+			{"role":"user","content":"hello"},
+			{"role":"user","content":[{"type":"text","text":"abc"},{"type":"image","source":{"data":"AAAA"}}]}
+		]
+	}`)
+	require.Equal(t, 10, RoutingPromptChars(body))
+	require.Equal(t, 3, RoutingPromptChars([]byte(`{"contents":[{"parts":[{"text":"abc"}]}]}`)))
+}
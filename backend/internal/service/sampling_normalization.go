@@ -0,0 +1,240 @@
+package service
+
+import (
+	"strings"
+
+	"github.com/gin-gonic/gin"
+	"github.com/tidwall/gjson"
+	"github.com/tidwall/sjson"
+)
+
+// SamplingAdjustedHeader 响应头：列出网关为适配上游而调整过的采样参数，如 "temperature=clamped, top_k=dropped"
+const SamplingAdjustedHeader = "X-Sub2API-Adjusted-Params"
+
+// 采样参数的调整动作
+const (
+	samplingActionDropped   = "dropped"
+	samplingActionClamped   = "clamped"
+	samplingActionRenamed   = "renamed"
+	samplingActionTruncated = "truncated"
+	samplingActionFiltered  = "filtered"
+	samplingActionConverted = "converted"
+)
+
+// samplingCapabilities 上游对采样参数的支持情况（参数名按入站请求格式：temperature / top_p / top_k / seed / stop / stop_sequences）
+type samplingCapabilities struct {
+	// temperatureMax temperature 上限，0 表示不支持 temperature
+	temperatureMax float64
+	topP           bool
+	topK           bool
+	// stopField 上游接受的停止序列字段名，空表示不支持；stop / stop_sequences 中的另一个会被重命名为该字段
+	stopField string
+	// stopMax 停止序列最大条数，0 表示不限制
+	stopMax int
+	// seed 上游是否支持确定性采样种子
+	seed bool
+}
+
+var (
+	// samplingCapsAnthropic Anthropic Messages：temperature 0~1，停止序列字段为 stop_sequences，不支持 seed
+	samplingCapsAnthropic = samplingCapabilities{temperatureMax: 1, topP: true, topK: true, stopField: "stop_sequences"}
+	// samplingCapsGemini 转换为 Gemini generationConfig 前的 Claude 格式请求：temperature 0~2，stopSequences 最多 5 条，支持 seed
+	samplingCapsGemini = samplingCapabilities{temperatureMax: 2, topP: true, topK: true, stopField: "stop_sequences", stopMax: 5, seed: true}
+	// samplingCapsOpenAIResponses OpenAI Responses API：不支持 top_k、seed 与停止序列
+	samplingCapsOpenAIResponses = samplingCapabilities{temperatureMax: 2, topP: true}
+	// samplingCapsCodex Codex（OAuth）上游不接受任何采样参数
+	samplingCapsCodex = samplingCapabilities{}
+)
+
+// samplingParamStore 抽象请求体的读写，使 []byte（Claude 路径，保持字段顺序）与 map（OpenAI 路径）共用同一套规则
+type samplingParamStore interface {
+	get(key string) (any, bool)
+	set(key string, value any)
+	del(key string)
+}
+
+type samplingBodyStore struct{ body []byte }
+
+func (s *samplingBodyStore) get(key string) (any, bool) {
+	r := gjson.GetBytes(s.body, key)
+	if !r.Exists() {
+		return nil, false
+	}
+	return r.Value(), true
+}
+
+func (s *samplingBodyStore) set(key string, value any) {
+	if next, err := sjson.SetBytes(s.body, key, value); err == nil {
+		s.body = next
+	}
+}
+
+func (s *samplingBodyStore) del(key string) {
+	if next, err := sjson.DeleteBytes(s.body, key); err == nil {
+		s.body = next
+	}
+}
+
+type samplingMapStore map[string]any
+
+func (m samplingMapStore) get(key string) (any, bool) {
+	v, ok := m[key]
+	return v, ok
+}
+
+func (m samplingMapStore) set(key string, value any) { m[key] = value }
+
+func (m samplingMapStore) del(key string) { delete(m, key) }
+
+// normalizeClaudeSamplingParams 按上游能力调整 Claude 格式请求体中的采样参数，返回新请求体与调整记录
+func normalizeClaudeSamplingParams(body []byte, caps samplingCapabilities) ([]byte, []string) {
+	present := false
+	for _, r := range gjson.GetManyBytes(body, "temperature", "top_p", "top_k", "seed", "stop", "stop_sequences") {
+		if r.Exists() {
+			present = true
+			break
+		}
+	}
+	if !present {
+		return body, nil
+	}
+	store := &samplingBodyStore{body: body}
+	adjusted := normalizeSamplingParams(store, caps)
+	return store.body, adjusted
+}
+
+// normalizeOpenAISamplingParams 按上游能力调整 Responses API 请求中的采样参数，返回调整记录
+func normalizeOpenAISamplingParams(reqBody map[string]any, caps samplingCapabilities) []string {
+	return normalizeSamplingParams(samplingMapStore(reqBody), caps)
+}
+
+// normalizeSamplingParams 按固定顺序处理各参数，保证相同输入得到相同输出与调整记录
+func normalizeSamplingParams(store samplingParamStore, caps samplingCapabilities) []string {
+	var adjusted []string
+	record := func(param, action string) {
+		adjusted = append(adjusted, param+"="+action)
+	}
+
+	numeric := []struct {
+		key       string
+		supported bool
+		upper     float64
+	}{
+		{"temperature", caps.temperatureMax > 0, caps.temperatureMax},
+		{"top_p", caps.topP, 1},
+	}
+	for _, p := range numeric {
+		value, ok := store.get(p.key)
+		if !ok {
+			continue
+		}
+		v, isNumber := value.(float64)
+		if !p.supported || !isNumber {
+			store.del(p.key)
+			record(p.key, samplingActionDropped)
+			continue
+		}
+		if clamped := min(max(v, 0), p.upper); clamped != v {
+			store.set(p.key, clamped)
+			record(p.key, samplingActionClamped)
+		}
+	}
+
+	if value, ok := store.get("top_k"); ok {
+		k, isNumber := asInt(value)
+		switch {
+		case !caps.topK || !isNumber:
+			store.del("top_k")
+			record("top_k", samplingActionDropped)
+		case k < 1:
+			store.set("top_k", 1)
+			record("top_k", samplingActionClamped)
+		case isNonIntegerFloat(value):
+			// 非整数 top_k 向下取整
+			store.set("top_k", k)
+			record("top_k", samplingActionClamped)
+		}
+	}
+
+	if value, ok := store.get("seed"); ok {
+		if _, isNumber := asInt(value); !caps.seed || !isNumber || isNonIntegerFloat(value) {
+			store.del("seed")
+			record("seed", samplingActionDropped)
+		}
+	}
+
+	for _, key := range []string{"stop", "stop_sequences"} {
+		value, ok := store.get(key)
+		if !ok {
+			continue
+		}
+		if caps.stopField == "" {
+			store.del(key)
+			record(key, samplingActionDropped)
+			continue
+		}
+		if key != caps.stopField {
+			store.del(key)
+			if _, exists := store.get(caps.stopField); exists {
+				// 同时携带两种写法时以上游字段为准
+				record(key, samplingActionDropped)
+				continue
+			}
+			record(key, samplingActionRenamed)
+		}
+		sequences, action := normalizeStopSequences(value, caps.stopMax)
+		switch {
+		case len(sequences) == 0:
+			store.del(caps.stopField)
+			record(caps.stopField, samplingActionDropped)
+		case action != "" || key != caps.stopField:
+			store.set(caps.stopField, sequences)
+			if action != "" {
+				record(caps.stopField, action)
+			}
+		}
+	}
+	return adjusted
+}
+
+func isNonIntegerFloat(value any) bool {
+	f, ok := value.(float64)
+	return ok && f != float64(int64(f))
+}
+
+// normalizeStopSequences 将字符串或数组形式的停止序列规范化为字符串数组，移除空白项并按上限截断
+func normalizeStopSequences(value any, limit int) ([]any, string) {
+	var items []any
+	switch v := value.(type) {
+	case string:
+		items = []any{v}
+	case []any:
+		items = v
+	}
+	out := make([]any, 0, len(items))
+	action := ""
+	for _, item := range items {
+		s, ok := item.(string)
+		if !ok || strings.TrimSpace(s) == "" {
+			action = samplingActionFiltered
+			continue
+		}
+		out = append(out, s)
+	}
+	if _, isString := value.(string); isString && action == "" {
+		action = samplingActionConverted
+	}
+	if limit > 0 && len(out) > limit {
+		out = out[:limit]
+		action = samplingActionTruncated
+	}
+	return out, action
+}
+
+// setSamplingAdjustedHeader 在响应头中报告采样参数调整情况
+func setSamplingAdjustedHeader(c *gin.Context, adjusted []string) {
+	if c == nil || len(adjusted) == 0 {
+		return
+	}
+	c.Header(SamplingAdjustedHeader, strings.Join(adjusted, ", "))
+}
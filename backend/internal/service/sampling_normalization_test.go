@@ -0,0 +1,106 @@
+//go:build unit
+
+package service
+
+import (
+	"net/http/httptest"
+	"testing"
+
+	"github.com/gin-gonic/gin"
+	"github.com/stretchr/testify/require"
+	"github.com/tidwall/gjson"
+)
+
+func TestNormalizeClaudeSamplingParams_Anthropic(t *testing.T) {
+	t.Run("untouched body keeps bytes", func(t *testing.T) {
+		body := []byte(`{"model":"claude-sonnet-4-5","temperature":0.7,"stop_sequences":["END"]}`)
+		out, adjusted := normalizeClaudeSamplingParams(body, samplingCapsAnthropic)
+		require.Empty(t, adjusted)
+		require.Equal(t, body, out)
+	})
+
+	t.Run("clamp and rename", func(t *testing.T) {
+		body := []byte(`{"model":"m","temperature":1.5,"top_p":-0.1,"top_k":0,"stop":"###"}`)
+		out, adjusted := normalizeClaudeSamplingParams(body, samplingCapsAnthropic)
+		require.Equal(t, []string{
+			"temperature=clamped",
+			"top_p=clamped",
+			"top_k=clamped",
+			"stop=renamed",
+			"stop_sequences=converted",
+		}, adjusted)
+		require.Equal(t, float64(1), gjson.GetBytes(out, "temperature").Float())
+		require.Equal(t, float64(0), gjson.GetBytes(out, "top_p").Float())
+		require.Equal(t, int64(1), gjson.GetBytes(out, "top_k").Int())
+		require.False(t, gjson.GetBytes(out, "stop").Exists())
+		require.Equal(t, `["###"]`, gjson.GetBytes(out, "stop_sequences").Raw)
+	})
+
+	t.Run("stop dropped when stop_sequences present", func(t *testing.T) {
+		out, adjusted := normalizeClaudeSamplingParams([]byte(`{"stop":["a"],"stop_sequences":["b"," "]}`), samplingCapsAnthropic)
+		require.Equal(t, []string{"stop=dropped", "stop_sequences=filtered"}, adjusted)
+		require.Equal(t, `["b"]`, gjson.GetBytes(out, "stop_sequences").Raw)
+	})
+
+	t.Run("invalid types dropped", func(t *testing.T) {
+		out, adjusted := normalizeClaudeSamplingParams([]byte(`{"temperature":"hot","top_k":2.5}`), samplingCapsAnthropic)
+		require.Equal(t, []string{"temperature=dropped", "top_k=clamped"}, adjusted)
+		require.False(t, gjson.GetBytes(out, "temperature").Exists())
+		require.Equal(t, int64(2), gjson.GetBytes(out, "top_k").Int())
+	})
+}
+
+func TestNormalizeClaudeSamplingParams_Gemini(t *testing.T) {
+	body := []byte(`{"temperature":1.8,"stop_sequences":["1","2","3","4","5","6"]}`)
+	out, adjusted := normalizeClaudeSamplingParams(body, samplingCapsGemini)
+	require.Equal(t, []string{"stop_sequences=truncated"}, adjusted)
+	require.Equal(t, 1.8, gjson.GetBytes(out, "temperature").Float())
+	require.Len(t, gjson.GetBytes(out, "stop_sequences").Array(), 5)
+
+	cfg := convertClaudeGenerationConfig(map[string]any{"top_k": float64(40), "seed": float64(7)})
+	require.Equal(t, 40, cfg["topK"])
+	require.Equal(t, 7, cfg["seed"])
+}
+
+func TestNormalizeSamplingParams_Seed(t *testing.T) {
+	// Anthropic 不支持 seed：移除并报告
+	out, adjusted := normalizeClaudeSamplingParams([]byte(`{"model":"m","seed":42}`), samplingCapsAnthropic)
+	require.Equal(t, []string{"seed=dropped"}, adjusted)
+	require.False(t, gjson.GetBytes(out, "seed").Exists())
+
+	// Gemini 支持 seed：原样保留，非整数移除
+	body := []byte(`{"model":"m","seed":42}`)
+	out, adjusted = normalizeClaudeSamplingParams(body, samplingCapsGemini)
+	require.Empty(t, adjusted)
+	require.Equal(t, body, out)
+	_, adjusted = normalizeClaudeSamplingParams([]byte(`{"seed":1.5}`), samplingCapsGemini)
+	require.Equal(t, []string{"seed=dropped"}, adjusted)
+
+	reqBody := map[string]any{"seed": float64(42)}
+	require.Equal(t, []string{"seed=dropped"}, normalizeOpenAISamplingParams(reqBody, samplingCapsOpenAIResponses))
+	require.Empty(t, reqBody)
+}
+
+func TestNormalizeOpenAISamplingParams(t *testing.T) {
+	reqBody := map[string]any{"temperature": 2.5, "top_p": 0.9, "top_k": float64(20), "stop": []any{"x"}}
+	adjusted := normalizeOpenAISamplingParams(reqBody, samplingCapsOpenAIResponses)
+	require.Equal(t, []string{"temperature=clamped", "top_k=dropped", "stop=dropped"}, adjusted)
+	require.Equal(t, map[string]any{"temperature": 2.0, "top_p": 0.9}, reqBody)
+
+	reqBody = map[string]any{"temperature": 0.2, "top_p": 0.9}
+	adjusted = normalizeOpenAISamplingParams(reqBody, samplingCapsCodex)
+	require.Equal(t, []string{"temperature=dropped", "top_p=dropped"}, adjusted)
+	require.Empty(t, reqBody)
+}
+
+func TestSetSamplingAdjustedHeader(t *testing.T) {
+	gin.SetMode(gin.TestMode)
+	rec := httptest.NewRecorder()
+	c, _ := gin.CreateTestContext(rec)
+
+	setSamplingAdjustedHeader(c, nil)
+	require.Empty(t, rec.Header().Get(SamplingAdjustedHeader))
+
+	setSamplingAdjustedHeader(c, []string{"temperature=clamped", "top_k=dropped"})
+	require.Equal(t, "temperature=clamped, top_k=dropped", rec.Header().Get(SamplingAdjustedHeader))
+}
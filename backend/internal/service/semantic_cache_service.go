@@ -0,0 +1,288 @@
+package service
+
+import (
+	"bytes"
+	"context"
+	"crypto/sha256"
+	"encoding/hex"
+	"encoding/json"
+	"fmt"
+	"log"
+	"net/http"
+	"strconv"
+	"strings"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/gin-gonic/gin"
+	"github.com/tidwall/gjson"
+	"github.com/tidwall/sjson"
+)
+
+// 语义缓存的请求类型（不同协议的缓存互不命中）
+const (
+	SemanticCacheTypeMessages = "messages"
+	SemanticCacheTypeOpenAI   = "openai_responses"
+)
+
+// SemanticCacheHeader 响应头：hit 表示由语义缓存返回，miss 表示已查询但未命中
+const SemanticCacheHeader = "X-Semantic-Cache"
+
+// SemanticCacheEntry 一条缓存的问答
+type SemanticCacheEntry struct {
+	ID          int64
+	APIKeyID    int64
+	RequestType string
+	Model       string
+	// ContextHash 除最后一条用户消息外的请求内容（system、历史消息、参数、工具等）的哈希，只有完全一致才可复用
+	ContextHash string
+	// PromptHash 最后一条用户消息的哈希，用于跳过向量计算的精确命中
+	PromptHash string
+	Prompt     string
+	Embedding  []float32
+	Response   []byte
+	HitCount   int64
+	CreatedAt  time.Time
+	ExpiresAt  time.Time
+}
+
+// SemanticCacheScope 缓存查找范围
+type SemanticCacheScope struct {
+	APIKeyID    int64
+	RequestType string
+	Model       string
+	ContextHash string
+}
+
+// SemanticCacheRepository 语义缓存存储（pgvector）
+type SemanticCacheRepository interface {
+	// FindExact 按提问哈希精确查找未过期条目，未找到返回 nil, nil
+	FindExact(ctx context.Context, scope SemanticCacheScope, promptHash string) (*SemanticCacheEntry, error)
+	// FindNearest 查找余弦相似度最高的未过期条目及其相似度，未找到返回 nil, 0, nil
+	FindNearest(ctx context.Context, scope SemanticCacheScope, embedding []float32) (*SemanticCacheEntry, float64, error)
+	Create(ctx context.Context, entry *SemanticCacheEntry) error
+	IncrementHit(ctx context.Context, id int64) error
+	// DeleteExpired 删除某个 API Key 下已过期的条目
+	DeleteExpired(ctx context.Context, apiKeyID int64) (int64, error)
+}
+
+// SemanticCacheEmbedder 调用 OpenAI 兼容的 embeddings 接口
+type SemanticCacheEmbedder interface {
+	Embed(ctx context.Context, endpoint, apiKey, model, input string) ([]float32, error)
+}
+
+// SemanticCacheService 语义响应缓存：对定向开启的 API Key，最后一条用户消息与已缓存问题足够相似、
+// 且其余上下文完全一致时，直接返回缓存的响应。仅处理非流式请求；命中不经过上游，也不计费。
+type SemanticCacheService struct {
+	repo         SemanticCacheRepository
+	embedder     SemanticCacheEmbedder
+	featureFlags *FeatureFlagService
+	cfg          config.SemanticCacheConfig
+}
+
+// NewSemanticCacheService creates a new SemanticCacheService
+func NewSemanticCacheService(repo SemanticCacheRepository, embedder SemanticCacheEmbedder, featureFlags *FeatureFlagService, cfg *config.Config) *SemanticCacheService {
+	return &SemanticCacheService{
+		repo:         repo,
+		embedder:     embedder,
+		featureFlags: featureFlags,
+		cfg:          cfg.SemanticCache,
+	}
+}
+
+// SemanticCacheSession 一次未命中的查询；主请求成功后调用 Complete 写入缓存
+type SemanticCacheSession struct {
+	svc        *SemanticCacheService
+	ctx        context.Context
+	scope      SemanticCacheScope
+	prompt     string
+	promptHash string
+	embedding  []float32
+	capture    *trafficMirrorCaptureWriter
+}
+
+// Lookup 查询语义缓存。命中时已写出响应并返回 (nil, true)；
+// 未命中且可缓存时包装响应写入器并返回会话，其余情况返回 (nil, false)。
+//
+// 需在转发前调用；查询失败（向量服务或数据库异常）按未命中处理，不影响正常转发。
+func (s *SemanticCacheService) Lookup(c *gin.Context, reqType string, apiKey *APIKey, model string, stream bool, body []byte) (*SemanticCacheSession, bool) {
+	if s == nil || !s.cfg.Enabled || s.repo == nil || s.embedder == nil || c == nil || c.Request == nil || apiKey == nil || stream {
+		return nil, false
+	}
+	if !s.featureFlags.IsEnabled(FeatureFlagSemanticCache, FeatureFlagSubjectFromAPIKey(apiKey)) {
+		return nil, false
+	}
+	prompt, contextHash, ok := extractSemanticCachePrompt(reqType, body)
+	if !ok || len([]rune(prompt)) > s.cfg.MaxPromptChars {
+		return nil, false
+	}
+
+	ctx, cancel := context.WithTimeout(c.Request.Context(), time.Duration(s.cfg.TimeoutSeconds)*time.Second)
+	defer cancel()
+	scope := SemanticCacheScope{APIKeyID: apiKey.ID, RequestType: reqType, Model: model, ContextHash: contextHash}
+	promptHash := semanticCacheHash([]byte(prompt))
+
+	entry, err := s.repo.FindExact(ctx, scope, promptHash)
+	if err != nil {
+		log.Printf("[SemanticCache] exact lookup failed for api key %d: %v", apiKey.ID, err)
+		return nil, false
+	}
+	if entry != nil {
+		s.serve(c, entry, 1)
+		return nil, true
+	}
+
+	embedding, err := s.embedder.Embed(ctx, s.cfg.EmbeddingURL, s.cfg.EmbeddingAPIKey, s.cfg.EmbeddingModel, prompt)
+	if err != nil {
+		log.Printf("[SemanticCache] embedding failed for api key %d: %v", apiKey.ID, err)
+		return nil, false
+	}
+	entry, similarity, err := s.repo.FindNearest(ctx, scope, embedding)
+	if err != nil {
+		log.Printf("[SemanticCache] nearest lookup failed for api key %d: %v", apiKey.ID, err)
+		return nil, false
+	}
+	if entry != nil && similarity >= s.cfg.SimilarityThreshold {
+		s.serve(c, entry, similarity)
+		return nil, true
+	}
+
+	c.Header(SemanticCacheHeader, "miss")
+	capture := &trafficMirrorCaptureWriter{ResponseWriter: c.Writer, limit: s.cfg.MaxResponseBytes}
+	c.Writer = capture
+	return &SemanticCacheSession{
+		svc:        s,
+		ctx:        context.WithoutCancel(c.Request.Context()),
+		scope:      scope,
+		prompt:     prompt,
+		promptHash: promptHash,
+		embedding:  embedding,
+		capture:    capture,
+	}, false
+}
+
+func (s *SemanticCacheService) serve(c *gin.Context, entry *SemanticCacheEntry, similarity float64) {
+	c.Header(SemanticCacheHeader, "hit")
+	c.Header(SemanticCacheHeader+"-Similarity", strconv.FormatFloat(similarity, 'f', 4, 64))
+	c.Data(http.StatusOK, "application/json", entry.Response)
+
+	ctx := context.WithoutCancel(c.Request.Context())
+	go func() {
+		if err := s.repo.IncrementHit(ctx, entry.ID); err != nil {
+			log.Printf("[SemanticCache] increment hit failed for entry %d: %v", entry.ID, err)
+		}
+	}()
+}
+
+// Complete 主请求成功（200 且响应完整）后异步写入缓存；session 为 nil 时为空操作
+func (m *SemanticCacheSession) Complete() {
+	if m == nil || m.capture.Status() != http.StatusOK || m.capture.truncated() {
+		return
+	}
+	response := bytes.TrimSpace(m.capture.buf.Bytes())
+	if !json.Valid(response) {
+		return
+	}
+	entry := &SemanticCacheEntry{
+		APIKeyID:    m.scope.APIKeyID,
+		RequestType: m.scope.RequestType,
+		Model:       m.scope.Model,
+		ContextHash: m.scope.ContextHash,
+		PromptHash:  m.promptHash,
+		Prompt:      m.prompt,
+		Embedding:   m.embedding,
+		Response:    append([]byte(nil), response...),
+		ExpiresAt:   time.Now().Add(time.Duration(m.svc.cfg.TTLHours) * time.Hour),
+	}
+	go func() {
+		ctx, cancel := context.WithTimeout(m.ctx, 30*time.Second)
+		defer cancel()
+		if _, err := m.svc.repo.DeleteExpired(ctx, entry.APIKeyID); err != nil {
+			log.Printf("[SemanticCache] delete expired failed for api key %d: %v", entry.APIKeyID, err)
+		}
+		if err := m.svc.repo.Create(ctx, entry); err != nil {
+			log.Printf("[SemanticCache] store entry failed for api key %d: %v", entry.APIKeyID, err)
+		}
+	}()
+}
+
+// extractSemanticCachePrompt 取出最后一条用户消息的文本作为提问，其余内容（去掉 stream 等与结果无关的字段）计算上下文哈希。
+// 最后一条消息不是纯文本的用户消息（如含图片、工具结果）时不缓存。
+func extractSemanticCachePrompt(reqType string, body []byte) (string, string, bool) {
+	var (
+		prompt string
+		path   string
+	)
+	switch reqType {
+	case SemanticCacheTypeMessages:
+		messages := gjson.GetBytes(body, "messages").Array()
+		if len(messages) == 0 {
+			return "", "", false
+		}
+		last := messages[len(messages)-1]
+		if last.Get("role").String() != "user" {
+			return "", "", false
+		}
+		text, ok := semanticCacheText(last.Get("content"), "text")
+		if !ok {
+			return "", "", false
+		}
+		prompt, path = text, fmt.Sprintf("messages.%d.content", len(messages)-1)
+	case SemanticCacheTypeOpenAI:
+		input := gjson.GetBytes(body, "input")
+		if input.Type == gjson.String {
+			prompt, path = input.String(), "input"
+			break
+		}
+		items := input.Array()
+		if len(items) == 0 {
+			return "", "", false
+		}
+		last := items[len(items)-1]
+		if last.Get("role").String() != "user" {
+			return "", "", false
+		}
+		text, ok := semanticCacheText(last.Get("content"), "input_text")
+		if !ok {
+			return "", "", false
+		}
+		prompt, path = text, fmt.Sprintf("input.%d.content", len(items)-1)
+	default:
+		return "", "", false
+	}
+	if strings.TrimSpace(prompt) == "" {
+		return "", "", false
+	}
+
+	rest, err := sjson.SetBytes(body, path, "")
+	if err != nil {
+		return "", "", false
+	}
+	for _, field := range []string{"stream", "stream_options", "metadata"} {
+		rest, _ = sjson.DeleteBytes(rest, field)
+	}
+	return prompt, semanticCacheHash(rest), true
+}
+
+// semanticCacheText 提取纯文本内容：字符串，或仅由 textType 块组成的数组
+func semanticCacheText(content gjson.Result, textType string) (string, bool) {
+	if content.Type == gjson.String {
+		return content.String(), true
+	}
+	if !content.IsArray() {
+		return "", false
+	}
+	parts := make([]string, 0, 1)
+	for _, block := range content.Array() {
+		if block.Get("type").String() != textType {
+			return "", false
+		}
+		parts = append(parts, block.Get("text").String())
+	}
+	return strings.Join(parts, "\n"), len(parts) > 0
+}
+
+func semanticCacheHash(data []byte) string {
+	sum := sha256.Sum256(data)
+	return hex.EncodeToString(sum[:])
+}
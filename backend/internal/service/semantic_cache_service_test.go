@@ -0,0 +1,198 @@
+//go:build unit
+
+package service
+
+import (
+	"context"
+	"net/http"
+	"net/http/httptest"
+	"strings"
+	"sync"
+	"testing"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/gin-gonic/gin"
+	"github.com/stretchr/testify/require"
+)
+
+type semanticCacheRepoStub struct {
+	mu         sync.Mutex
+	entries    []*SemanticCacheEntry
+	similarity float64
+	hits       int
+}
+
+func (r *semanticCacheRepoStub) FindExact(_ context.Context, scope SemanticCacheScope, promptHash string) (*SemanticCacheEntry, error) {
+	r.mu.Lock()
+	defer r.mu.Unlock()
+	for _, e := range r.entries {
+		if e.ContextHash == scope.ContextHash && e.PromptHash == promptHash {
+			return e, nil
+		}
+	}
+	return nil, nil
+}
+
+func (r *semanticCacheRepoStub) FindNearest(_ context.Context, scope SemanticCacheScope, _ []float32) (*SemanticCacheEntry, float64, error) {
+	r.mu.Lock()
+	defer r.mu.Unlock()
+	for _, e := range r.entries {
+		if e.ContextHash == scope.ContextHash {
+			return e, r.similarity, nil
+		}
+	}
+	return nil, 0, nil
+}
+
+func (r *semanticCacheRepoStub) Create(_ context.Context, entry *SemanticCacheEntry) error {
+	r.mu.Lock()
+	defer r.mu.Unlock()
+	entry.ID = int64(len(r.entries) + 1)
+	r.entries = append(r.entries, entry)
+	return nil
+}
+
+func (r *semanticCacheRepoStub) IncrementHit(_ context.Context, _ int64) error {
+	r.mu.Lock()
+	defer r.mu.Unlock()
+	r.hits++
+	return nil
+}
+
+func (r *semanticCacheRepoStub) DeleteExpired(context.Context, int64) (int64, error) {
+	return 0, nil
+}
+
+func (r *semanticCacheRepoStub) count() int {
+	r.mu.Lock()
+	defer r.mu.Unlock()
+	return len(r.entries)
+}
+
+type semanticCacheEmbedderStub struct{ calls int }
+
+func (e *semanticCacheEmbedderStub) Embed(context.Context, string, string, string, string) ([]float32, error) {
+	e.calls++
+	return []float32{0.1, 0.2, 0.3}, nil
+}
+
+func newSemanticCacheServiceForTest(t *testing.T) (*SemanticCacheService, *semanticCacheRepoStub, *semanticCacheEmbedderStub) {
+	t.Helper()
+	flags := NewFeatureFlagService(&featureFlagRepoStub{flags: map[string]*FeatureFlag{}}, nil, &config.Config{})
+	require.NoError(t, flags.Upsert(context.Background(), &FeatureFlag{Key: FeatureFlagSemanticCache, Enabled: true, APIKeyIDs: []int64{7}}))
+	repo := &semanticCacheRepoStub{similarity: 0.97}
+	embedder := &semanticCacheEmbedderStub{}
+	cfg := &config.Config{SemanticCache: config.SemanticCacheConfig{
+		Enabled:             true,
+		EmbeddingURL:        "https://embeddings.internal/v1/embeddings",
+		EmbeddingModel:      "text-embedding-3-small",
+		TimeoutSeconds:      5,
+		SimilarityThreshold: 0.95,
+		TTLHours:            1,
+		MaxPromptChars:      1000,
+		MaxResponseBytes:    1024,
+	}}
+	return NewSemanticCacheService(repo, embedder, flags, cfg), repo, embedder
+}
+
+func newSemanticCacheContext(body string) (*gin.Context, *httptest.ResponseRecorder) {
+	rec := httptest.NewRecorder()
+	c, _ := gin.CreateTestContext(rec)
+	c.Request = httptest.NewRequest(http.MethodPost, "/v1/messages", strings.NewReader(body))
+	return c, rec
+}
+
+func TestExtractSemanticCachePrompt(t *testing.T) {
+	prompt, hashA, ok := extractSemanticCachePrompt(SemanticCacheTypeMessages, []byte(`{"model":"m","system":"s","messages":[{"role":"user","content":[{"type":"text","text":"hello"}]}],"stream":false}`))
+	require.True(t, ok)
+	require.Equal(t, "hello", prompt)
+
+	// 提问与 stream 字段不影响上下文哈希
+	_, hashB, ok := extractSemanticCachePrompt(SemanticCacheTypeMessages, []byte(`{"model":"m","system":"s","messages":[{"role":"user","content":[{"type":"text","text":"hi there"}]}]}`))
+	require.True(t, ok)
+	require.Equal(t, hashA, hashB)
+
+	_, hashC, ok := extractSemanticCachePrompt(SemanticCacheTypeMessages, []byte(`{"model":"m","system":"other","messages":[{"role":"user","content":"hello"}]}`))
+	require.True(t, ok)
+	require.NotEqual(t, hashA, hashC)
+
+	_, _, ok = extractSemanticCachePrompt(SemanticCacheTypeMessages, []byte(`{"messages":[{"role":"user","content":[{"type":"image","source":{}}]}]}`))
+	require.False(t, ok)
+	_, _, ok = extractSemanticCachePrompt(SemanticCacheTypeMessages, []byte(`{"messages":[{"role":"assistant","content":"x"}]}`))
+	require.False(t, ok)
+
+	prompt, _, ok = extractSemanticCachePrompt(SemanticCacheTypeOpenAI, []byte(`{"model":"m","input":"what is go"}`))
+	require.True(t, ok)
+	require.Equal(t, "what is go", prompt)
+	prompt, _, ok = extractSemanticCachePrompt(SemanticCacheTypeOpenAI, []byte(`{"model":"m","input":[{"role":"user","content":[{"type":"input_text","text":"a"},{"type":"input_text","text":"b"}]}]}`))
+	require.True(t, ok)
+	require.Equal(t, "a\nb", prompt)
+}
+
+func TestSemanticCacheService_MissStoreThenHit(t *testing.T) {
+	svc, repo, embedder := newSemanticCacheServiceForTest(t)
+	apiKey := &APIKey{ID: 7}
+	body := []byte(`{"model":"claude","messages":[{"role":"user","content":"hello"}]}`)
+
+	c, rec := newSemanticCacheContext(string(body))
+	session, served := svc.Lookup(c, SemanticCacheTypeMessages, apiKey, "claude", false, body)
+	require.False(t, served)
+	require.NotNil(t, session)
+	c.JSON(http.StatusOK, gin.H{"content": "world"})
+	session.Complete()
+	require.Equal(t, "miss", rec.Header().Get(SemanticCacheHeader))
+	require.Eventually(t, func() bool { return repo.count() == 1 }, 2*time.Second, 10*time.Millisecond)
+
+	// 相同提问精确命中，无需计算向量
+	c, rec = newSemanticCacheContext(string(body))
+	session, served = svc.Lookup(c, SemanticCacheTypeMessages, apiKey, "claude", false, body)
+	require.True(t, served)
+	require.Nil(t, session)
+	require.Equal(t, "hit", rec.Header().Get(SemanticCacheHeader))
+	require.JSONEq(t, `{"content":"world"}`, rec.Body.String())
+	require.Equal(t, 1, embedder.calls)
+
+	// 相似提问按向量命中
+	similar := []byte(`{"model":"claude","messages":[{"role":"user","content":"hello!"}]}`)
+	c, rec = newSemanticCacheContext(string(similar))
+	_, served = svc.Lookup(c, SemanticCacheTypeMessages, apiKey, "claude", false, similar)
+	require.True(t, served)
+	require.Equal(t, "0.9700", rec.Header().Get(SemanticCacheHeader+"-Similarity"))
+
+	// 低于阈值不命中
+	repo.mu.Lock()
+	repo.similarity = 0.5
+	repo.mu.Unlock()
+	c, _ = newSemanticCacheContext(string(similar))
+	_, served = svc.Lookup(c, SemanticCacheTypeMessages, apiKey, "claude", false, similar)
+	require.False(t, served)
+}
+
+func TestSemanticCacheService_Skips(t *testing.T) {
+	svc, repo, _ := newSemanticCacheServiceForTest(t)
+	body := []byte(`{"model":"claude","messages":[{"role":"user","content":"hello"}]}`)
+
+	// 流式请求与未定向开启的 API Key 不查询
+	c, _ := newSemanticCacheContext(string(body))
+	session, served := svc.Lookup(c, SemanticCacheTypeMessages, &APIKey{ID: 7}, "claude", true, body)
+	require.False(t, served)
+	require.Nil(t, session)
+	session, served = svc.Lookup(c, SemanticCacheTypeMessages, &APIKey{ID: 8}, "claude", false, body)
+	require.False(t, served)
+	require.Nil(t, session)
+
+	// 非 200 响应不写入缓存
+	session, _ = svc.Lookup(c, SemanticCacheTypeMessages, &APIKey{ID: 7}, "claude", false, body)
+	require.NotNil(t, session)
+	c.JSON(http.StatusBadGateway, gin.H{"error": "upstream"})
+	session.Complete()
+	time.Sleep(50 * time.Millisecond)
+	require.Equal(t, 0, repo.count())
+
+	var nilSvc *SemanticCacheService
+	session, served = nilSvc.Lookup(c, SemanticCacheTypeMessages, &APIKey{ID: 7}, "claude", false, body)
+	require.False(t, served)
+	require.Nil(t, session)
+	session.Complete()
+}
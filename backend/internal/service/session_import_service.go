@@ -0,0 +1,366 @@
+package service
+
+import (
+	"context"
+	"encoding/json"
+	"fmt"
+	"io"
+	"net/http"
+	"sort"
+	"strings"
+	"time"
+
+	infraerrors "github.com/Wei-Shaw/sub2api/internal/pkg/errors"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/sessionimport"
+)
+
+const (
+	sessionImportProbeTimeout  = 20 * time.Second
+	sessionImportProbeMaxBytes = 8 << 20
+
+	// chatGPTWebSessionCookie next-auth 会话 Cookie；过长时浏览器会拆分为 .0 / .1 ... 多段
+	chatGPTWebSessionCookie = "__Secure-next-auth.session-token"
+)
+
+var (
+	ErrSessionImportUnrecognized = infraerrors.BadRequest("SESSION_IMPORT_UNRECOGNIZED", "no supported web session found in the export; specify platform explicitly if the export covers several sites")
+	ErrSessionImportAmbiguous    = infraerrors.BadRequest("SESSION_IMPORT_AMBIGUOUS", "the export contains sessions for several platforms; specify platform")
+)
+
+// SessionImportInput 从浏览器导出内容创建网页会话账号。
+// 账号没有独立的标签字段，分组绑定（GroupIDs）即用于归类与调度范围。
+type SessionImportInput struct {
+	Platform    string // anthropic / openai / gemini；为空时按导出内容自动识别
+	Content     string // HAR、JSON Cookie 数组、Netscape cookies.txt 或原始 Cookie 头
+	Name        string
+	Notes       *string
+	ProxyID     *int64
+	GroupIDs    []int64
+	Concurrency int
+	Priority    int
+	SkipProbe   bool // 跳过探测请求，直接创建
+	DryRun      bool // 只解析与探测，不创建账号
+}
+
+// SessionImportResult 导入结果；凭证只返回字段名，不回显内容
+type SessionImportResult struct {
+	Platform       string   `json:"platform"`
+	Type           string   `json:"type"`
+	Format         string   `json:"format"`
+	CredentialKeys []string `json:"credential_keys"`
+	Probed         bool     `json:"probed"`
+	ProbeDetail    string   `json:"probe_detail,omitempty"`
+	Account        *Account `json:"-"`
+}
+
+// SessionImportService 从浏览器 Cookie / HAR 中提取网页会话凭证，探测有效后创建账号
+type SessionImportService struct {
+	adminService AdminService
+	httpUpstream HTTPUpstream
+}
+
+// NewSessionImportService 创建会话导入服务
+func NewSessionImportService(adminService AdminService, httpUpstream HTTPUpstream) *SessionImportService {
+	return &SessionImportService{adminService: adminService, httpUpstream: httpUpstream}
+}
+
+// sessionImportExtractor 各平台网页会话的识别与凭证提取
+type sessionImportExtractor struct {
+	platform string
+	domain   string
+	extract  func(capture *sessionimport.Capture) map[string]any
+}
+
+var sessionImportExtractors = []sessionImportExtractor{
+	{platform: PlatformAnthropic, domain: "claude.ai", extract: extractClaudeWebCredentials},
+	{platform: PlatformOpenAI, domain: "chatgpt.com", extract: extractChatGPTWebCredentials},
+	{platform: PlatformGemini, domain: "google.com", extract: extractGeminiWebCredentials},
+}
+
+func extractClaudeWebCredentials(capture *sessionimport.Capture) map[string]any {
+	sessionKey := capture.Cookie("claude.ai", "sessionKey")
+	if sessionKey == "" {
+		return nil
+	}
+	creds := map[string]any{"session_key": sessionKey}
+	if org := capture.Cookie("claude.ai", "lastActiveOrg"); org != "" {
+		creds["org_uuid"] = org
+	}
+	return creds
+}
+
+func extractChatGPTWebCredentials(capture *sessionimport.Capture) map[string]any {
+	creds := map[string]any{}
+	if token := capture.BearerToken("chatgpt.com"); token != "" {
+		creds["access_token"] = token
+	}
+	sessionToken := capture.Cookie("chatgpt.com", chatGPTWebSessionCookie)
+	if sessionToken == "" {
+		for i := 0; ; i++ {
+			part := capture.Cookie("chatgpt.com", fmt.Sprintf("%s.%d", chatGPTWebSessionCookie, i))
+			if part == "" {
+				break
+			}
+			sessionToken += part
+		}
+	}
+	if sessionToken != "" {
+		creds["session_token"] = sessionToken
+	}
+	if len(creds) == 0 {
+		return nil
+	}
+	if deviceID := capture.Cookie("chatgpt.com", "oai-did"); deviceID != "" {
+		creds["device_id"] = deviceID
+	}
+	return creds
+}
+
+func extractGeminiWebCredentials(capture *sessionimport.Capture) map[string]any {
+	psid := capture.Cookie("google.com", "__Secure-1PSID")
+	if psid == "" {
+		return nil
+	}
+	creds := map[string]any{"secure_1psid": psid}
+	if psidts := capture.Cookie("google.com", "__Secure-1PSIDTS"); psidts != "" {
+		creds["secure_1psidts"] = psidts
+	}
+	return creds
+}
+
+// extractSessionCredentials 按平台提取凭证；未指定平台时要求导出内容只匹配一个平台
+func extractSessionCredentials(capture *sessionimport.Capture, platform string) (string, map[string]any, error) {
+	var (
+		matchedPlatform string
+		matchedCreds    map[string]any
+	)
+	for _, ex := range sessionImportExtractors {
+		if platform != "" && platform != ex.platform {
+			continue
+		}
+		// 原始 Cookie 头没有域名信息，必须显式指定平台
+		if platform == "" && !capture.HasDomain(ex.domain) {
+			continue
+		}
+		creds := ex.extract(capture)
+		if creds == nil {
+			continue
+		}
+		if matchedCreds != nil {
+			return "", nil, ErrSessionImportAmbiguous
+		}
+		matchedPlatform, matchedCreds = ex.platform, creds
+	}
+	if matchedCreds == nil {
+		return "", nil, ErrSessionImportUnrecognized
+	}
+	if capture.UserAgent != "" {
+		matchedCreds["user_agent"] = capture.UserAgent
+	}
+	return matchedPlatform, matchedCreds, nil
+}
+
+// Import 解析导出内容、探测会话有效性，并创建账号（DryRun 时不创建）
+func (s *SessionImportService) Import(ctx context.Context, input *SessionImportInput) (*SessionImportResult, error) {
+	capture, err := sessionimport.Parse([]byte(input.Content))
+	if err != nil {
+		return nil, infraerrors.BadRequest("SESSION_IMPORT_INVALID", err.Error())
+	}
+	platform, creds, err := extractSessionCredentials(capture, input.Platform)
+	if err != nil {
+		return nil, err
+	}
+
+	result := &SessionImportResult{
+		Platform: platform,
+		Type:     AccountTypeWebSession,
+		Format:   string(capture.Format),
+	}
+
+	if !input.SkipProbe {
+		proxyURL := ""
+		if input.ProxyID != nil {
+			proxy, err := s.adminService.GetProxy(ctx, *input.ProxyID)
+			if err != nil {
+				return nil, err
+			}
+			proxyURL = proxy.URL()
+		}
+		probeCtx, cancel := context.WithTimeout(ctx, sessionImportProbeTimeout)
+		detail, err := s.probe(probeCtx, platform, creds, proxyURL)
+		cancel()
+		if err != nil {
+			return nil, infraerrors.BadRequest("SESSION_IMPORT_PROBE_FAILED", "session probe failed: "+sanitizeUpstreamErrorMessage(err.Error()))
+		}
+		result.Probed = true
+		result.ProbeDetail = detail
+	}
+
+	// ChatGPT 只有 session_token 时，探测阶段已换取 access_token
+	if platform == PlatformOpenAI && creds["access_token"] == nil {
+		return nil, infraerrors.BadRequest("SESSION_IMPORT_NO_ACCESS_TOKEN", "chatgpt export has no access token; include a HAR with backend-api requests or enable the probe to exchange the session cookie")
+	}
+
+	for key := range creds {
+		result.CredentialKeys = append(result.CredentialKeys, key)
+	}
+	sort.Strings(result.CredentialKeys)
+	if input.DryRun {
+		return result, nil
+	}
+
+	name := strings.TrimSpace(input.Name)
+	if name == "" {
+		name = fmt.Sprintf("%s-web-%s", platform, time.Now().Format("20060102-150405"))
+	}
+	concurrency := input.Concurrency
+	if concurrency <= 0 {
+		concurrency = 1
+	}
+	account, err := s.adminService.CreateAccount(ctx, &CreateAccountInput{
+		Name:        name,
+		Notes:       input.Notes,
+		Platform:    platform,
+		Type:        AccountTypeWebSession,
+		Credentials: creds,
+		ProxyID:     input.ProxyID,
+		Concurrency: concurrency,
+		Priority:    input.Priority,
+		GroupIDs:    input.GroupIDs,
+	})
+	if err != nil {
+		return nil, err
+	}
+	result.Account = account
+	return result, nil
+}
+
+// probe 用导入的凭证发送一次只读请求，返回可读的会话描述
+func (s *SessionImportService) probe(ctx context.Context, platform string, creds map[string]any, proxyURL string) (string, error) {
+	account := &Account{Platform: platform, Type: AccountTypeWebSession, Credentials: creds, Concurrency: 1}
+	switch platform {
+	case PlatformAnthropic:
+		return s.probeClaudeWeb(ctx, account, proxyURL)
+	case PlatformOpenAI:
+		return s.probeChatGPTWeb(ctx, account, proxyURL)
+	case PlatformGemini:
+		return s.probeGeminiWeb(ctx, account, proxyURL)
+	}
+	return "", fmt.Errorf("unsupported platform: %s", platform)
+}
+
+func (s *SessionImportService) probeGet(req *http.Request, proxyURL string) ([]byte, error) {
+	resp, err := s.httpUpstream.Do(req, proxyURL, 0, 1)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = resp.Body.Close() }()
+	body, err := io.ReadAll(io.LimitReader(resp.Body, sessionImportProbeMaxBytes))
+	if err != nil {
+		return nil, err
+	}
+	if resp.StatusCode != http.StatusOK {
+		return nil, fmt.Errorf("%s returned %d", req.URL.Host, resp.StatusCode)
+	}
+	return body, nil
+}
+
+// probeClaudeWeb 查询组织列表；未从 Cookie 中得到组织时顺带写入 org_uuid
+func (s *SessionImportService) probeClaudeWeb(ctx context.Context, account *Account, proxyURL string) (string, error) {
+	req, err := http.NewRequestWithContext(ctx, http.MethodGet, claudeWebDefaultBaseURL+"/api/organizations", nil)
+	if err != nil {
+		return "", err
+	}
+	userAgent := account.GetCredential("user_agent")
+	if userAgent == "" {
+		userAgent = claudeWebDefaultUserAgent
+	}
+	req.Header.Set("User-Agent", userAgent)
+	req.Header.Set("Cookie", "sessionKey="+account.GetCredential("session_key"))
+	req.Header.Set("anthropic-client-platform", "web_claude_ai")
+
+	body, err := s.probeGet(req, proxyURL)
+	if err != nil {
+		return "", err
+	}
+	var orgs []struct {
+		UUID string `json:"uuid"`
+		Name string `json:"name"`
+	}
+	if err := json.Unmarshal(body, &orgs); err != nil {
+		return "", fmt.Errorf("decode organizations: %w", err)
+	}
+	if len(orgs) == 0 {
+		return "", fmt.Errorf("claude.ai session has no organizations")
+	}
+	if account.GetCredential("org_uuid") == "" {
+		account.Credentials["org_uuid"] = orgs[0].UUID
+	}
+	return fmt.Sprintf("claude.ai organization %s", orgs[0].Name), nil
+}
+
+// probeChatGPTWeb 有 session_token 时通过 /api/auth/session 换取（刷新）access_token，否则直接校验 access_token
+func (s *SessionImportService) probeChatGPTWeb(ctx context.Context, account *Account, proxyURL string) (string, error) {
+	if sessionToken := account.GetCredential("session_token"); sessionToken != "" {
+		req, err := http.NewRequestWithContext(ctx, http.MethodGet, chatGPTWebDefaultBaseURL+"/api/auth/session", nil)
+		if err != nil {
+			return "", err
+		}
+		req.Header.Set("User-Agent", chatGPTWebUserAgent(account))
+		req.Header.Set("Cookie", chatGPTWebSessionCookie+"="+sessionToken)
+		body, err := s.probeGet(req, proxyURL)
+		if err != nil {
+			return "", err
+		}
+		var session struct {
+			AccessToken string `json:"accessToken"`
+			Expires     string `json:"expires"`
+			User        struct {
+				Email string `json:"email"`
+			} `json:"user"`
+		}
+		if err := json.Unmarshal(body, &session); err != nil {
+			return "", fmt.Errorf("decode auth session: %w", err)
+		}
+		if session.AccessToken == "" {
+			return "", fmt.Errorf("chatgpt session cookie is expired")
+		}
+		account.Credentials["access_token"] = session.AccessToken
+		if session.Expires != "" {
+			account.Credentials["session_expires_at"] = session.Expires
+		}
+		return fmt.Sprintf("chatgpt.com user %s", session.User.Email), nil
+	}
+
+	req, err := http.NewRequestWithContext(ctx, http.MethodGet, chatGPTWebDefaultBaseURL+"/backend-api/me", nil)
+	if err != nil {
+		return "", err
+	}
+	req.Header.Set("User-Agent", chatGPTWebUserAgent(account))
+	req.Header.Set("Authorization", "Bearer "+account.GetCredential("access_token"))
+	body, err := s.probeGet(req, proxyURL)
+	if err != nil {
+		return "", err
+	}
+	var me struct {
+		Email string `json:"email"`
+	}
+	_ = json.Unmarshal(body, &me)
+	return fmt.Sprintf("chatgpt.com user %s", me.Email), nil
+}
+
+// probeGeminiWeb 拉取 /app 页面并解析会话参数
+func (s *SessionImportService) probeGeminiWeb(ctx context.Context, account *Account, proxyURL string) (string, error) {
+	req, err := newGeminiWebRequest(ctx, account, http.MethodGet, geminiWebBaseURL+"/app", nil, "")
+	if err != nil {
+		return "", err
+	}
+	page, err := s.probeGet(req, proxyURL)
+	if err != nil {
+		return "", err
+	}
+	if _, err := parseGeminiWebSession(page); err != nil {
+		return "", err
+	}
+	return "gemini.google.com session", nil
+}
@@ -0,0 +1,92 @@
+//go:build unit
+
+package service
+
+import (
+	"context"
+	"net/http"
+	"testing"
+
+	infraerrors "github.com/Wei-Shaw/sub2api/internal/pkg/errors"
+	"github.com/stretchr/testify/require"
+)
+
+type sessionImportAdminStub struct {
+	AdminService
+	created *CreateAccountInput
+}
+
+func (s *sessionImportAdminStub) CreateAccount(_ context.Context, input *CreateAccountInput) (*Account, error) {
+	s.created = input
+	return &Account{ID: 77, Name: input.Name, Platform: input.Platform, Type: input.Type, Credentials: input.Credentials}, nil
+}
+
+func (s *sessionImportAdminStub) GetProxy(_ context.Context, id int64) (*Proxy, error) {
+	return &Proxy{ID: id, Protocol: "http", Host: "127.0.0.1", Port: 3128}, nil
+}
+
+func TestSessionImportService_ImportClaudeCookies(t *testing.T) {
+	upstream := &claudeWebUpstreamStub{respond: func(*http.Request) *http.Response {
+		return claudeWebTestResponse(http.StatusOK, `[{"uuid":"org-uuid-1","name":"Personal"}]`)
+	}}
+	admin := &sessionImportAdminStub{}
+	svc := NewSessionImportService(admin, upstream)
+	proxyID := int64(3)
+
+	result, err := svc.Import(context.Background(), &SessionImportInput{
+		Content:  `[{"domain":".claude.ai","name":"sessionKey","value":"sk-ant-sid01-abc"}]`,
+		Name:     "claude-1",
+		ProxyID:  &proxyID,
+		GroupIDs: []int64{5},
+	})
+	require.NoError(t, err)
+	require.Equal(t, PlatformAnthropic, result.Platform)
+	require.True(t, result.Probed)
+	require.Equal(t, []string{"org_uuid", "session_key"}, result.CredentialKeys)
+	require.Equal(t, []string{"GET /api/organizations"}, upstream.requests)
+	require.Equal(t, "sessionKey=sk-ant-sid01-abc", upstream.headers["GET /api/organizations"].Get("Cookie"))
+
+	require.NotNil(t, admin.created)
+	require.Equal(t, AccountTypeWebSession, admin.created.Type)
+	require.Equal(t, []int64{5}, admin.created.GroupIDs)
+	require.Equal(t, &proxyID, admin.created.ProxyID)
+	require.Equal(t, "org-uuid-1", admin.created.Credentials["org_uuid"])
+	require.Equal(t, int64(77), result.Account.ID)
+}
+
+func TestSessionImportService_ChatGPTSessionExchange(t *testing.T) {
+	upstream := &claudeWebUpstreamStub{respond: func(*http.Request) *http.Response {
+		return claudeWebTestResponse(http.StatusOK, `{"accessToken":"eyJ.access","expires":"2026-11-01T00:00:00Z","user":{"email":"a@example.com"}}`)
+	}}
+	admin := &sessionImportAdminStub{}
+	svc := NewSessionImportService(admin, upstream)
+
+	result, err := svc.Import(context.Background(), &SessionImportInput{
+		Platform: PlatformOpenAI,
+		Content:  "__Secure-next-auth.session-token.0=part1; __Secure-next-auth.session-token.1=part2",
+		DryRun:   true,
+	})
+	require.NoError(t, err)
+	require.Contains(t, result.ProbeDetail, "a@example.com")
+	require.Contains(t, result.CredentialKeys, "access_token")
+	require.Equal(t, "__Secure-next-auth.session-token=part1part2", upstream.headers["GET /api/auth/session"].Get("Cookie"))
+	require.Nil(t, admin.created, "dry run must not create the account")
+}
+
+func TestSessionImportService_ProbeFailureAndDetection(t *testing.T) {
+	upstream := &claudeWebUpstreamStub{respond: func(*http.Request) *http.Response {
+		return claudeWebTestResponse(http.StatusForbidden, `{"error":"invalid session"}`)
+	}}
+	admin := &sessionImportAdminStub{}
+	svc := NewSessionImportService(admin, upstream)
+
+	_, err := svc.Import(context.Background(), &SessionImportInput{Content: `[{"domain":"claude.ai","name":"sessionKey","value":"expired"}]`})
+	require.Equal(t, "SESSION_IMPORT_PROBE_FAILED", infraerrors.Reason(err))
+	require.Nil(t, admin.created)
+
+	_, err = svc.Import(context.Background(), &SessionImportInput{Content: "sessionKey=abc"})
+	require.ErrorIs(t, err, ErrSessionImportUnrecognized)
+
+	_, err = svc.Import(context.Background(), &SessionImportInput{Content: `[{"domain":"claude.ai","name":"sessionKey","value":"a"},{"domain":".google.com","name":"__Secure-1PSID","value":"b"}]`, SkipProbe: true})
+	require.ErrorIs(t, err, ErrSessionImportAmbiguous)
+}
@@ -0,0 +1,130 @@
+package service
+
+import (
+	"context"
+	"errors"
+	"fmt"
+	"slices"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+)
+
+// ErrAccountReloginRequired 外部刷新服务无法自动续期（需要验证码、二次验证或重新登录），账号需人工处理
+var ErrAccountReloginRequired = errors.New("manual re-login required")
+
+// 外部刷新服务返回的状态
+const (
+	SessionRefreshStatusRefreshed  = "refreshed"
+	SessionRefreshStatusUnchanged  = "unchanged"
+	SessionRefreshStatusNeedsLogin = "needs_login"
+)
+
+// sessionRefreshedAtKey 最近一次由外部服务刷新成功的时间，用于按固定间隔刷新没有过期时间的会话
+const sessionRefreshedAtKey = "session_refreshed_at"
+
+// SessionRefreshHookRequest 发送给外部刷新服务的请求
+type SessionRefreshHookRequest struct {
+	AccountID   int64          `json:"account_id"`
+	Name        string         `json:"name"`
+	Platform    string         `json:"platform"`
+	Type        string         `json:"type"`
+	Credentials map[string]any `json:"credentials"`
+	ProxyURL    string         `json:"proxy_url,omitempty"`
+}
+
+// SessionRefreshHookResponse 外部刷新服务的响应；Credentials 只需包含有变化的字段
+type SessionRefreshHookResponse struct {
+	Status      string         `json:"status"`
+	Credentials map[string]any `json:"credentials,omitempty"`
+	Message     string         `json:"message,omitempty"`
+}
+
+// SessionRefreshHookClient 调用外部会话刷新服务
+type SessionRefreshHookClient interface {
+	Refresh(ctx context.Context, endpoint, authToken string, req *SessionRefreshHookRequest) (*SessionRefreshHookResponse, error)
+}
+
+// SessionRefreshHookRefresher 通过外部服务（无头浏览器等）刷新网页会话凭证。
+// 注册在内置刷新器之后，只处理内置刷新器不认领的账号。
+type SessionRefreshHookRefresher struct {
+	client SessionRefreshHookClient
+	cfg    config.SessionRefreshHookConfig
+}
+
+func NewSessionRefreshHookRefresher(client SessionRefreshHookClient, cfg config.SessionRefreshHookConfig) *SessionRefreshHookRefresher {
+	return &SessionRefreshHookRefresher{client: client, cfg: cfg}
+}
+
+func (r *SessionRefreshHookRefresher) CanRefresh(account *Account) bool {
+	if r.client == nil || !r.cfg.Enabled || r.cfg.URL == "" {
+		return false
+	}
+	if disabled, ok := account.Extra["session_hook_disabled"].(bool); ok && disabled {
+		return false
+	}
+	return slices.Contains(r.cfg.AccountTypes, account.Type)
+}
+
+// NeedsRefresh 凭证带有过期时间（session_expires_at / expires_at）时按刷新窗口判断，否则按固定间隔
+func (r *SessionRefreshHookRefresher) NeedsRefresh(account *Account, refreshWindow time.Duration) bool {
+	expiresAt := account.GetCredentialAsTime("session_expires_at")
+	if expiresAt == nil {
+		expiresAt = account.GetCredentialAsTime("expires_at")
+	}
+	if expiresAt != nil {
+		return time.Until(*expiresAt) < refreshWindow
+	}
+	if r.cfg.IntervalHours <= 0 {
+		return false
+	}
+	refreshedAt := account.GetCredentialAsTime(sessionRefreshedAtKey)
+	if refreshedAt == nil {
+		return true
+	}
+	return time.Since(*refreshedAt) >= time.Duration(r.cfg.IntervalHours*float64(time.Hour))
+}
+
+func (r *SessionRefreshHookRefresher) Refresh(ctx context.Context, account *Account) (map[string]any, error) {
+	timeout := time.Duration(r.cfg.TimeoutSeconds) * time.Second
+	if timeout <= 0 {
+		timeout = 2 * time.Minute
+	}
+	ctx, cancel := context.WithTimeout(ctx, timeout)
+	defer cancel()
+
+	req := &SessionRefreshHookRequest{
+		AccountID:   account.ID,
+		Name:        account.Name,
+		Platform:    account.Platform,
+		Type:        account.Type,
+		Credentials: account.Credentials,
+	}
+	if account.ProxyID != nil && account.Proxy != nil {
+		req.ProxyURL = account.Proxy.URL()
+	}
+	resp, err := r.client.Refresh(ctx, r.cfg.URL, r.cfg.AuthToken, req)
+	if err != nil {
+		return nil, fmt.Errorf("session refresh hook: %w", err)
+	}
+
+	switch resp.Status {
+	case SessionRefreshStatusRefreshed, SessionRefreshStatusUnchanged:
+		newCredentials := make(map[string]any, len(account.Credentials)+len(resp.Credentials)+1)
+		for k, v := range account.Credentials {
+			newCredentials[k] = v
+		}
+		for k, v := range resp.Credentials {
+			newCredentials[k] = v
+		}
+		newCredentials[sessionRefreshedAtKey] = time.Now().Format(time.RFC3339)
+		return newCredentials, nil
+	case SessionRefreshStatusNeedsLogin:
+		if resp.Message == "" {
+			return nil, ErrAccountReloginRequired
+		}
+		return nil, fmt.Errorf("%w: %s", ErrAccountReloginRequired, resp.Message)
+	default:
+		return nil, fmt.Errorf("session refresh hook returned unknown status %q", resp.Status)
+	}
+}
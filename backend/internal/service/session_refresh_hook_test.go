@@ -0,0 +1,89 @@
+//go:build unit
+
+package service
+
+import (
+	"context"
+	"testing"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/stretchr/testify/require"
+)
+
+type sessionRefreshHookClientStub struct {
+	resp *SessionRefreshHookResponse
+	last *SessionRefreshHookRequest
+}
+
+func (s *sessionRefreshHookClientStub) Refresh(_ context.Context, _, _ string, req *SessionRefreshHookRequest) (*SessionRefreshHookResponse, error) {
+	s.last = req
+	return s.resp, nil
+}
+
+func newSessionRefreshHookConfigForTest() config.SessionRefreshHookConfig {
+	return config.SessionRefreshHookConfig{
+		Enabled:        true,
+		URL:            "https://refresher.internal/refresh",
+		TimeoutSeconds: 30,
+		IntervalHours:  12,
+		AccountTypes:   []string{AccountTypeWebSession},
+	}
+}
+
+func TestSessionRefreshHookRefresher_CanAndNeedsRefresh(t *testing.T) {
+	refresher := NewSessionRefreshHookRefresher(&sessionRefreshHookClientStub{}, newSessionRefreshHookConfigForTest())
+	account := &Account{ID: 9, Platform: PlatformGemini, Type: AccountTypeWebSession, Credentials: map[string]any{"secure_1psid": "g.a000"}}
+
+	require.True(t, refresher.CanRefresh(account))
+	require.False(t, refresher.CanRefresh(&Account{Type: AccountTypeAPIKey}))
+	require.True(t, refresher.NeedsRefresh(account, time.Hour), "never refreshed")
+
+	account.Credentials[sessionRefreshedAtKey] = time.Now().Add(-time.Hour).Format(time.RFC3339)
+	require.False(t, refresher.NeedsRefresh(account, time.Hour))
+
+	account.Credentials["session_expires_at"] = time.Now().Add(30 * time.Minute).Format(time.RFC3339)
+	require.True(t, refresher.NeedsRefresh(account, time.Hour), "expiry takes precedence over the interval")
+
+	account.Extra = map[string]any{"session_hook_disabled": true}
+	require.False(t, refresher.CanRefresh(account))
+}
+
+func TestSessionRefreshHookRefresher_Refresh(t *testing.T) {
+	client := &sessionRefreshHookClientStub{resp: &SessionRefreshHookResponse{
+		Status:      SessionRefreshStatusRefreshed,
+		Credentials: map[string]any{"secure_1psidts": "sidts-new"},
+	}}
+	refresher := NewSessionRefreshHookRefresher(client, newSessionRefreshHookConfigForTest())
+	account := &Account{ID: 9, Platform: PlatformGemini, Type: AccountTypeWebSession, Credentials: map[string]any{"secure_1psid": "g.a000", "secure_1psidts": "sidts-old"}}
+
+	creds, err := refresher.Refresh(context.Background(), account)
+	require.NoError(t, err)
+	require.Equal(t, "g.a000", creds["secure_1psid"])
+	require.Equal(t, "sidts-new", creds["secure_1psidts"])
+	require.NotEmpty(t, creds[sessionRefreshedAtKey])
+	require.Equal(t, int64(9), client.last.AccountID)
+
+	client.resp = &SessionRefreshHookResponse{Status: SessionRefreshStatusNeedsLogin, Message: "2FA prompt"}
+	_, err = refresher.Refresh(context.Background(), account)
+	require.ErrorIs(t, err, ErrAccountReloginRequired)
+	require.Contains(t, err.Error(), "2FA prompt")
+}
+
+func TestTokenRefreshService_RefreshWithRetry_ReloginRequiredSkipsRetries(t *testing.T) {
+	repo := &tokenRefreshAccountRepo{}
+	cfg := &config.Config{
+		TokenRefresh: config.TokenRefreshConfig{
+			MaxRetries:          3,
+			RetryBackoffSeconds: 0,
+		},
+	}
+	service := NewTokenRefreshService(repo, nil, nil, nil, nil, nil, nil, nil, cfg)
+	account := &Account{ID: 9, Platform: PlatformOpenAI, Type: AccountTypeWebSession}
+	refresher := &tokenRefresherStub{err: ErrAccountReloginRequired}
+
+	err := service.refreshWithRetry(context.Background(), account, refresher)
+	require.ErrorIs(t, err, ErrAccountReloginRequired)
+	require.Equal(t, 1, repo.setErrorCalls)
+	require.Equal(t, 0, repo.updateCalls)
+}
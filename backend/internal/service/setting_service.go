@@ -336,6 +336,11 @@ func (s *SettingService) IsTotpEncryptionKeyConfigured() bool {
 	return s.cfg.Totp.EncryptionKeyConfigured
 }
 
+// IsAdminTotpRequired 检查部署配置是否强制管理员启用 2FA
+func (s *SettingService) IsAdminTotpRequired() bool {
+	return s != nil && s.cfg != nil && s.cfg.Totp.RequireForAdmins
+}
+
 // GetSiteName 获取网站名称
 func (s *SettingService) GetSiteName(ctx context.Context) string {
 	value, err := s.settingRepo.GetValue(ctx, SettingKeySiteName)
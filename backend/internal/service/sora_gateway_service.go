@@ -759,6 +759,14 @@ func firstMediaURL(urls []string) string {
 	return urls[0]
 }
 
+// MediaStorage 返回媒体存储（可能为 nil）
+func (s *SoraGatewayService) MediaStorage() *SoraMediaStorage {
+	if s == nil {
+		return nil
+	}
+	return s.mediaStorage
+}
+
 func (s *SoraGatewayService) buildSoraMediaURL(path string, rawQuery string) string {
 	if path == "" {
 		return path
@@ -1,6 +1,7 @@
 package service
 
 import (
+	"context"
 	"os"
 	"path/filepath"
 	"strings"
@@ -14,10 +15,11 @@ import (
 
 var soraCleanupCronParser = cron.NewParser(cron.Minute | cron.Hour | cron.Dom | cron.Month | cron.Dow)
 
-// SoraMediaCleanupService 定期清理本地媒体文件
+// SoraMediaCleanupService 定期清理过期媒体文件（本地目录或对象存储）
 type SoraMediaCleanupService struct {
 	storage *SoraMediaStorage
 	cfg     *config.Config
+	leader  *LeaderElector
 
 	cron *cron.Cron
 
@@ -32,6 +34,11 @@ func NewSoraMediaCleanupService(storage *SoraMediaStorage, cfg *config.Config) *
 	}
 }
 
+// SetLeaderElector 多实例部署时仅由 leader 执行；需要在 Start() 之前调用
+func (s *SoraMediaCleanupService) SetLeaderElector(leader *LeaderElector) {
+	s.leader = leader
+}
+
 func (s *SoraMediaCleanupService) Start() {
 	if s == nil || s.cfg == nil {
 		return
@@ -94,6 +101,14 @@ func (s *SoraMediaCleanupService) runCleanup() {
 		return
 	}
 	cutoff := time.Now().AddDate(0, 0, -retention)
+	if s.storage.UsesObjectStorage() {
+		// 对象存储为各实例共享，仅由 leader 清理；本地目录仍由各实例各自清理
+		if !s.leader.IsLeader() {
+			return
+		}
+		s.cleanupObjects(cutoff)
+		return
+	}
 	deleted := 0
 
 	roots := []string{s.storage.ImageRoot(), s.storage.VideoRoot()}
@@ -118,3 +133,30 @@ func (s *SoraMediaCleanupService) runCleanup() {
 	}
 	logger.LegacyPrintf("service.sora_media_cleanup", "[SoraCleanup] cleanup finished, deleted=%d", deleted)
 }
+
+func (s *SoraMediaCleanupService) cleanupObjects(cutoff time.Time) {
+	ctx, cancel := context.WithTimeout(context.Background(), 30*time.Minute)
+	defer cancel()
+
+	objects := s.storage.ObjectStorage()
+	// 先收集再删除，避免边列举边删除影响翻页
+	var expired []string
+	err := objects.List(ctx, soraObjectKeyPrefix+"/", func(obj StoredObjectInfo) error {
+		if !obj.LastModified.IsZero() && obj.LastModified.Before(cutoff) {
+			expired = append(expired, obj.Key)
+		}
+		return nil
+	})
+	if err != nil {
+		logger.LegacyPrintf("service.sora_media_cleanup", "[SoraCleanup] list objects failed: %v", err)
+	}
+	deleted := 0
+	for _, key := range expired {
+		if err := objects.Delete(ctx, key); err != nil {
+			logger.LegacyPrintf("service.sora_media_cleanup", "[SoraCleanup] delete object %s failed: %v", key, err)
+			continue
+		}
+		deleted++
+	}
+	logger.LegacyPrintf("service.sora_media_cleanup", "[SoraCleanup] object cleanup finished, deleted=%d", deleted)
+}
@@ -21,11 +21,14 @@ import (
 
 const (
 	soraStorageDefaultRoot = "/app/data/sora"
+	// soraObjectKeyPrefix 对象存储模式下媒体的 key 前缀（相对于 storage.prefix）
+	soraObjectKeyPrefix = "sora"
 )
 
-// SoraMediaStorage 负责下载并落地 Sora 媒体
+// SoraMediaStorage 负责下载并落地 Sora 媒体（本地磁盘或对象存储）
 type SoraMediaStorage struct {
 	cfg                *config.Config
+	objects            ObjectStorage
 	root               string
 	imageRoot          string
 	videoRoot          string
@@ -48,11 +51,51 @@ func NewSoraMediaStorage(cfg *config.Config) *SoraMediaStorage {
 	return storage
 }
 
+// SetObjectStorage 注入对象存储（sora.storage.type=s3 时使用）
+func (s *SoraMediaStorage) SetObjectStorage(objects ObjectStorage) {
+	if s == nil {
+		return
+	}
+	s.objects = objects
+}
+
 func (s *SoraMediaStorage) Enabled() bool {
 	if s == nil || s.cfg == nil {
 		return false
 	}
-	return strings.ToLower(strings.TrimSpace(s.cfg.Sora.Storage.Type)) == "local"
+	switch strings.ToLower(strings.TrimSpace(s.cfg.Sora.Storage.Type)) {
+	case "local":
+		return true
+	case "s3":
+		return s.objects != nil
+	default:
+		return false
+	}
+}
+
+// UsesObjectStorage 媒体是否写入对象存储（而非本地磁盘）
+func (s *SoraMediaStorage) UsesObjectStorage() bool {
+	return s.Enabled() && strings.ToLower(strings.TrimSpace(s.cfg.Sora.Storage.Type)) == "s3"
+}
+
+// ObjectStorage 返回对象存储模式下使用的存储
+func (s *SoraMediaStorage) ObjectStorage() ObjectStorage {
+	if s == nil {
+		return nil
+	}
+	return s.objects
+}
+
+// OpenObject 在对象存储模式下读取媒体；relative 为 StoreFromURLs 返回的相对路径
+func (s *SoraMediaStorage) OpenObject(ctx context.Context, relative string) (*StoredObject, error) {
+	if !s.UsesObjectStorage() {
+		return nil, ErrObjectNotFound
+	}
+	return s.objects.Get(ctx, soraObjectKey(relative))
+}
+
+func soraObjectKey(relative string) string {
+	return path.Join(soraObjectKeyPrefix, path.Clean("/"+relative))
 }
 
 func (s *SoraMediaStorage) Root() string {
@@ -118,11 +161,15 @@ func (s *SoraMediaStorage) refreshConfig() {
 	s.sem = make(chan struct{}, maxConcurrent)
 }
 
-// EnsureLocalDirs 创建并校验本地目录
+// EnsureLocalDirs 创建并校验本地目录（对象存储模式无需本地目录）
 func (s *SoraMediaStorage) EnsureLocalDirs() error {
 	if s == nil || !s.Enabled() {
 		return nil
 	}
+	if s.UsesObjectStorage() {
+		s.ready = true
+		return nil
+	}
 	if err := os.MkdirAll(s.imageRoot, 0o755); err != nil {
 		return fmt.Errorf("create image dir: %w", err)
 	}
@@ -169,7 +216,7 @@ func (s *SoraMediaStorage) downloadAndStore(ctx context.Context, mediaType, rawU
 	if mediaType == "video" {
 		root = s.videoRoot
 	}
-	if root == "" {
+	if root == "" && !s.UsesObjectStorage() {
 		return "", errors.New("storage root not configured")
 	}
 
@@ -223,6 +270,9 @@ func (s *SoraMediaStorage) downloadOnce(ctx context.Context, root, mediaType, ra
 	if s.maxDownloadBytes > 0 && resp.ContentLength > s.maxDownloadBytes {
 		return "", fmt.Errorf("download size exceeds limit: %d", resp.ContentLength)
 	}
+	if s.UsesObjectStorage() {
+		return s.storeObject(ctx, resp, mediaType, ext)
+	}
 
 	storageRoot, err := os.OpenRoot(root)
 	if err != nil {
@@ -261,6 +311,32 @@ func (s *SoraMediaStorage) downloadOnce(ctx context.Context, root, mediaType, ra
 	return relative, nil
 }
 
+// storeObject 将下载内容流式写入对象存储，返回与本地模式一致的相对路径
+func (s *SoraMediaStorage) storeObject(ctx context.Context, resp *http.Response, mediaType, ext string) (string, error) {
+	relative := path.Join("/", mediaType, time.Now().Format("2006/01/02"), uuid.NewString()+ext)
+	contentType := resp.Header.Get("Content-Type")
+	if contentType == "" {
+		contentType = mime.TypeByExtension(ext)
+	}
+	w := s.objects.NewWriter(ctx, soraObjectKey(relative), contentType)
+	written, err := io.Copy(w, io.LimitReader(resp.Body, s.maxDownloadBytes+1))
+	if err != nil {
+		w.Abort()
+		return "", err
+	}
+	if s.maxDownloadBytes > 0 && written > s.maxDownloadBytes {
+		w.Abort()
+		return "", fmt.Errorf("download size exceeds limit: %d", written)
+	}
+	if err := w.Close(); err != nil {
+		return "", err
+	}
+	if s.debug {
+		log.Printf("[SoraStorage] 已上传 %s -> %s", sanitizeMediaLogURL(resp.Request.URL.String()), relative)
+	}
+	return relative, nil
+}
+
 func (s *SoraMediaStorage) acquire(ctx context.Context) (func(), error) {
 	if s.sem == nil {
 		return func() {}, nil
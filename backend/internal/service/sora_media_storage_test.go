@@ -3,13 +3,16 @@
 package service
 
 import (
+	"bytes"
 	"context"
+	"io"
 	"net/http"
 	"net/http/httptest"
 	"os"
 	"path/filepath"
 	"strings"
 	"testing"
+	"time"
 
 	"github.com/Wei-Shaw/sub2api/internal/config"
 	"github.com/stretchr/testify/require"
@@ -117,3 +120,96 @@ func TestRemovePartialDownload(t *testing.T) {
 	require.Error(t, err)
 	require.True(t, os.IsNotExist(err))
 }
+
+type memoryObjectStorage struct {
+	objects  map[string][]byte
+	modified map[string]time.Time
+}
+
+func newMemoryObjectStorage() *memoryObjectStorage {
+	return &memoryObjectStorage{objects: map[string][]byte{}, modified: map[string]time.Time{}}
+}
+
+type memoryObjectWriter struct {
+	storage *memoryObjectStorage
+	key     string
+	buf     bytes.Buffer
+}
+
+func (w *memoryObjectWriter) Write(p []byte) (int, error) { return w.buf.Write(p) }
+
+func (w *memoryObjectWriter) Close() error {
+	w.storage.objects[w.key] = w.buf.Bytes()
+	w.storage.modified[w.key] = time.Now()
+	return nil
+}
+
+func (w *memoryObjectWriter) Abort() {}
+
+func (m *memoryObjectStorage) Bucket() string { return "mem" }
+
+func (m *memoryObjectStorage) FullKey(key string) string { return key }
+
+func (m *memoryObjectStorage) NewWriter(_ context.Context, key, _ string) ObjectWriter {
+	return &memoryObjectWriter{storage: m, key: key}
+}
+
+func (m *memoryObjectStorage) Get(_ context.Context, key string) (*StoredObject, error) {
+	data, ok := m.objects[key]
+	if !ok {
+		return nil, ErrObjectNotFound
+	}
+	return &StoredObject{Body: io.NopCloser(bytes.NewReader(data)), Size: int64(len(data))}, nil
+}
+
+func (m *memoryObjectStorage) Delete(_ context.Context, key string) error {
+	delete(m.objects, key)
+	return nil
+}
+
+func (m *memoryObjectStorage) List(_ context.Context, prefix string, fn func(StoredObjectInfo) error) error {
+	for key, data := range m.objects {
+		if strings.HasPrefix(key, prefix) {
+			if err := fn(StoredObjectInfo{Key: key, Size: int64(len(data)), LastModified: m.modified[key]}); err != nil {
+				return err
+			}
+		}
+	}
+	return nil
+}
+
+func TestSoraMediaStorage_StoreToObjectStorage(t *testing.T) {
+	server := httptest.NewServer(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		w.Header().Set("Content-Type", "video/mp4")
+		_, _ = w.Write([]byte("video-data"))
+	}))
+	defer server.Close()
+
+	cfg := &config.Config{
+		Sora: config.SoraConfig{
+			Storage: config.SoraStorageConfig{Type: "s3", MaxConcurrentDownloads: 1},
+		},
+	}
+	storage := NewSoraMediaStorage(cfg)
+	require.False(t, storage.Enabled())
+
+	objects := newMemoryObjectStorage()
+	storage.SetObjectStorage(objects)
+	require.True(t, storage.Enabled())
+	require.True(t, storage.UsesObjectStorage())
+
+	urls, err := storage.StoreFromURLs(context.Background(), "video", []string{server.URL + "/v.mp4"})
+	require.NoError(t, err)
+	require.Len(t, urls, 1)
+	require.True(t, strings.HasPrefix(urls[0], "/video/"))
+	require.Contains(t, objects.objects, "sora"+urls[0])
+
+	obj, err := storage.OpenObject(context.Background(), urls[0])
+	require.NoError(t, err)
+	data, err := io.ReadAll(obj.Body)
+	require.NoError(t, err)
+	require.Equal(t, "video-data", string(data))
+
+	_, err = storage.OpenObject(context.Background(), "/video/../../etc/passwd")
+	require.ErrorIs(t, err, ErrObjectNotFound)
+}
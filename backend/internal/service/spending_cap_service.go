@@ -0,0 +1,599 @@
+package service
+
+import (
+	"context"
+	"fmt"
+	"net/http"
+	"strconv"
+	"sync"
+	"time"
+
+	infraerrors "github.com/Wei-Shaw/sub2api/internal/pkg/errors"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/logger"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/pagination"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/timezone"
+)
+
+// 消费上限作用范围
+const (
+	SpendingCapScopeAPIKey = "api_key"
+	// SpendingCapScopeUser 用户（账户）级上限，汇总该用户所有 API Key 的消费
+	SpendingCapScopeUser = "user"
+	// SpendingCapScopeTeam 团队共享预算，汇总团队成员 API Key 的消费
+	SpendingCapScopeTeam = "team"
+	// SpendingCapScopeTeamMember 团队成员子额度（scope_id 为 API Key ID），由团队所有者设置，与管理员设置的 api_key 上限互不覆盖
+	SpendingCapScopeTeamMember = "team_member"
+)
+
+// 限额演练模式作用范围（billing.limit_dry_run），与消费上限的演练计数共用存储
+const (
+	// LimitDryRunScopeSubscription 分组订阅日 / 周 / 月限额，scope_id 为订阅 ID
+	LimitDryRunScopeSubscription = "subscription"
+	// LimitDryRunScopeAPIKeyQuota API Key 总额度，scope_id 为 API Key ID，不分周期
+	LimitDryRunScopeAPIKeyQuota = "api_key_quota"
+)
+
+// 消费上限统计周期，按服务端时区切分
+const (
+	SpendingCapPeriodDaily   = "daily"
+	SpendingCapPeriodWeekly  = "weekly"
+	SpendingCapPeriodMonthly = "monthly"
+)
+
+// spendingCapLocalTTL 上限配置的内存缓存时间；管理员解除暂停后其他实例最多延迟该时长放行
+const spendingCapLocalTTL = 30 * time.Second
+
+// spendingCapDryRunLogEvery 演练模式下每个周期首次本应拒绝时记录日志，此后每累计该数量再记录一次
+const spendingCapDryRunLogEvery = 100
+
+// limitDryRunTotalTTL 不分周期的限额（API Key 总额度）演练计数保留时间，自最近一次本应拒绝起算
+const limitDryRunTotalTTL = 30 * 24 * time.Hour
+
+var (
+	ErrSpendingCapNotFound = infraerrors.NotFound("SPENDING_CAP_NOT_FOUND", "spending cap not found")
+	ErrSpendingCapInvalid  = infraerrors.BadRequest("SPENDING_CAP_INVALID", "scope must be api_key/user/team/team_member, period must be daily/weekly/monthly and limit_usd must be positive")
+	ErrSpendingCapExceeded = infraerrors.Forbidden("SPENDING_CAP_EXCEEDED", "spending cap reached")
+)
+
+// SpendingCap API Key / 用户的周期消费硬上限
+type SpendingCap struct {
+	ScopeType string  `json:"scope_type"`
+	ScopeID   int64   `json:"scope_id"`
+	Period    string  `json:"period"`
+	LimitUSD  float64 `json:"limit_usd"`
+	// DryRun 演练模式：照常统计消费并记录本应拒绝的请求（日志与计数），但不拒绝、不暂停、不通知，
+	// 用于上线新上限前按真实流量评估影响
+	DryRun bool `json:"dry_run"`
+	// SuspendedAt 达到上限自动暂停的时间；SuspendedPeriodStart 为暂停所在周期，进入新周期后自动解除
+	SuspendedAt          *time.Time `json:"suspended_at,omitempty"`
+	SuspendedPeriodStart *time.Time `json:"suspended_period_start,omitempty"`
+	// ExemptPeriodStart 管理员手动解除暂停的周期，该周期内不再强制上限
+	ExemptPeriodStart *time.Time `json:"exempt_period_start,omitempty"`
+	UpdatedBy         *int64     `json:"updated_by,omitempty"`
+	CreatedAt         time.Time  `json:"created_at"`
+	UpdatedAt         time.Time  `json:"updated_at"`
+}
+
+// PeriodStartAt 返回 t 所在统计周期的起点
+func (c *SpendingCap) PeriodStartAt(t time.Time) time.Time {
+	switch c.Period {
+	case SpendingCapPeriodWeekly:
+		return timezone.StartOfWeek(t)
+	case SpendingCapPeriodMonthly:
+		return timezone.StartOfMonth(t)
+	default:
+		return timezone.StartOfDay(t)
+	}
+}
+
+// SuspendedIn 是否在指定周期内处于暂停状态
+func (c *SpendingCap) SuspendedIn(periodStart time.Time) bool {
+	return c.SuspendedAt != nil && c.SuspendedPeriodStart != nil && c.SuspendedPeriodStart.Equal(periodStart)
+}
+
+// ExemptIn 管理员是否已豁免指定周期
+func (c *SpendingCap) ExemptIn(periodStart time.Time) bool {
+	return c.ExemptPeriodStart != nil && c.ExemptPeriodStart.Equal(periodStart)
+}
+
+// SpendingCapStatus 上限配置及当前周期消费
+type SpendingCapStatus struct {
+	SpendingCap
+	PeriodStart time.Time `json:"period_start"`
+	SpentUSD    float64   `json:"spent_usd"`
+	Suspended   bool      `json:"suspended"`
+	// DryRunRejections 演练模式下当前周期本应拒绝的请求数（Redis 计数，多实例汇总）
+	DryRunRejections     int64      `json:"dry_run_rejections,omitempty"`
+	DryRunLastRejectedAt *time.Time `json:"dry_run_last_rejected_at,omitempty"`
+}
+
+// SpendingCapRepository 消费上限存储
+type SpendingCapRepository interface {
+	// Get 未配置时返回 nil, nil
+	Get(ctx context.Context, scopeType string, scopeID int64) (*SpendingCap, error)
+	List(ctx context.Context, scopeType string, suspendedOnly bool, params pagination.PaginationParams) ([]SpendingCap, *pagination.PaginationResult, error)
+	// Upsert 写入配置并清除暂停 / 豁免状态
+	Upsert(ctx context.Context, c *SpendingCap) error
+	Delete(ctx context.Context, scopeType string, scopeID int64) error
+	// MarkSuspended 标记暂停；同一周期已暂停或已豁免时返回 false，用于多实例下只通知一次
+	MarkSuspended(ctx context.Context, scopeType string, scopeID int64, periodStart, at time.Time) (bool, error)
+	// Lift 解除暂停并豁免 periodStart 所在周期
+	Lift(ctx context.Context, scopeType string, scopeID int64, periodStart time.Time) error
+	// SumSpend 计费账本（usage_logs.actual_cost）中 since 之后的消费合计
+	SumSpend(ctx context.Context, scopeType string, scopeID int64, since time.Time) (float64, error)
+	// GetTeamID 返回 API Key 所属团队，未加入团队时返回 0
+	GetTeamID(ctx context.Context, apiKeyID int64) (int64, error)
+}
+
+// SpendingCapCache 周期消费计数（Redis），请求完成后立即累加，供下一次请求前同步检查
+type SpendingCapCache interface {
+	GetSpend(ctx context.Context, scopeType string, scopeID int64, periodStart time.Time) (float64, bool, error)
+	// InitSpend 计数不存在时写入初始值，返回是否写入
+	InitSpend(ctx context.Context, scopeType string, scopeID int64, periodStart time.Time, value float64, ttl time.Duration) (bool, error)
+	// AddSpend 计数存在时原子累加并返回新值；不存在时返回 false
+	AddSpend(ctx context.Context, scopeType string, scopeID int64, periodStart time.Time, amount float64, ttl time.Duration) (float64, bool, error)
+	// IncrDryRun 演练模式本应拒绝次数 +1 并记录时间，返回累加后的次数
+	IncrDryRun(ctx context.Context, scopeType string, scopeID int64, periodStart, at time.Time, ttl time.Duration) (int64, error)
+	// GetDryRun 返回周期内本应拒绝的次数及最近一次时间，不存在时返回 0
+	GetDryRun(ctx context.Context, scopeType string, scopeID int64, periodStart time.Time) (int64, time.Time, error)
+	ClearDryRun(ctx context.Context, scopeType string, scopeID int64, periodStarts ...time.Time) error
+}
+
+type spendingCapCacheEntry struct {
+	cap       *SpendingCap
+	expiresAt time.Time
+}
+
+type spendingCapTeamEntry struct {
+	teamID    int64
+	expiresAt time.Time
+}
+
+// SpendingCapService 消费硬上限：达到上限时自动暂停 API Key / 用户并发送通知，
+// 直到进入新周期或管理员解除。
+//
+// 每次请求前读取 Redis 中的周期消费计数，请求计费后立即累加，
+// 超额量最多为上限触发时仍在处理中的请求费用。
+type SpendingCapService struct {
+	repo          SpendingCapRepository
+	cache         SpendingCapCache
+	apiKeyRepo    APIKeyRepository
+	userRepo      UserRepository
+	notifications *NotificationService
+	now           func() time.Time
+
+	mu    sync.RWMutex
+	local map[string]spendingCapCacheEntry
+	teams map[int64]spendingCapTeamEntry
+}
+
+// NewSpendingCapService 创建消费上限服务
+func NewSpendingCapService(repo SpendingCapRepository, cache SpendingCapCache, apiKeyRepo APIKeyRepository, userRepo UserRepository, notifications *NotificationService) *SpendingCapService {
+	return &SpendingCapService{
+		repo:          repo,
+		cache:         cache,
+		apiKeyRepo:    apiKeyRepo,
+		userRepo:      userRepo,
+		notifications: notifications,
+		now:           timezone.Now,
+		local:         make(map[string]spendingCapCacheEntry),
+		teams:         make(map[int64]spendingCapTeamEntry),
+	}
+}
+
+// Check 请求前检查 API Key、所属团队及所属用户是否已达到消费上限；演练模式的上限只记录不拒绝
+func (s *SpendingCapService) Check(ctx context.Context, apiKey *APIKey) error {
+	if s == nil || apiKey == nil {
+		return nil
+	}
+	now := s.now()
+	for _, c := range s.capsFor(ctx, apiKey) {
+		start := c.PeriodStartAt(now)
+		if c.ExemptIn(start) {
+			continue
+		}
+		if c.SuspendedIn(start) && !c.DryRun {
+			return spendingCapExceeded(c, start)
+		}
+		spent, err := s.currentSpend(ctx, c, start)
+		if err != nil {
+			logger.LegacyPrintf("service.spending_cap", "[SpendingCap] read spend failed for %s %d: %v", c.ScopeType, c.ScopeID, err)
+			continue
+		}
+		if spent < c.LimitUSD {
+			continue
+		}
+		if c.DryRun {
+			s.recordDryRun(ctx, c.ScopeType, c.ScopeID, c.Period, start, spendingCapCounterTTL(c, start), apiKey.ID, spent, c.LimitUSD)
+			continue
+		}
+		s.suspend(ctx, c, apiKey, start, spent)
+		return spendingCapExceeded(c, start)
+	}
+	return nil
+}
+
+// RecordSpend 请求计费后累加周期消费，越过上限时立即暂停
+func (s *SpendingCapService) RecordSpend(ctx context.Context, apiKey *APIKey, cost float64) {
+	if s == nil || apiKey == nil || cost <= 0 {
+		return
+	}
+	now := s.now()
+	for _, c := range s.capsFor(ctx, apiKey) {
+		start := c.PeriodStartAt(now)
+		spent, err := s.addSpend(ctx, c, start, cost)
+		if err != nil {
+			logger.LegacyPrintf("service.spending_cap", "[SpendingCap] record spend failed for %s %d: %v", c.ScopeType, c.ScopeID, err)
+			continue
+		}
+		if spent >= c.LimitUSD && !c.DryRun && !c.ExemptIn(start) && !c.SuspendedIn(start) {
+			s.suspend(ctx, c, apiKey, start, spent)
+		}
+	}
+}
+
+// Get 返回上限配置及当前周期消费
+func (s *SpendingCapService) Get(ctx context.Context, scopeType string, scopeID int64) (*SpendingCapStatus, error) {
+	c, err := s.repo.Get(ctx, scopeType, scopeID)
+	if err != nil {
+		return nil, err
+	}
+	if c == nil {
+		return nil, ErrSpendingCapNotFound
+	}
+	return s.status(ctx, c), nil
+}
+
+// List 分页列出上限配置及当前周期消费
+func (s *SpendingCapService) List(ctx context.Context, scopeType string, suspendedOnly bool, params pagination.PaginationParams) ([]SpendingCapStatus, *pagination.PaginationResult, error) {
+	caps, page, err := s.repo.List(ctx, scopeType, suspendedOnly, params)
+	if err != nil {
+		return nil, nil, err
+	}
+	out := make([]SpendingCapStatus, 0, len(caps))
+	for i := range caps {
+		out = append(out, *s.status(ctx, &caps[i]))
+	}
+	return out, page, nil
+}
+
+// Upsert 创建或更新上限；同时清除暂停状态，按新上限重新判定
+func (s *SpendingCapService) Upsert(ctx context.Context, c *SpendingCap) (*SpendingCapStatus, error) {
+	if err := validateSpendingCap(c); err != nil {
+		return nil, err
+	}
+	switch c.ScopeType {
+	case SpendingCapScopeAPIKey:
+		if _, err := s.apiKeyRepo.GetByID(ctx, c.ScopeID); err != nil {
+			return nil, err
+		}
+	case SpendingCapScopeUser:
+		if _, err := s.userRepo.GetByID(ctx, c.ScopeID); err != nil {
+			return nil, err
+		}
+	}
+	if err := s.repo.Upsert(ctx, c); err != nil {
+		return nil, err
+	}
+	s.invalidate(c.ScopeType, c.ScopeID)
+	s.clearDryRun(ctx, c.ScopeType, c.ScopeID)
+	logger.LegacyPrintf("service.spending_cap", "AUDIT spending cap updated: %s=%d period=%s limit=%.4f dry_run=%t by=%s",
+		c.ScopeType, c.ScopeID, c.Period, c.LimitUSD, c.DryRun, formatOptionalID(c.UpdatedBy))
+	return s.status(ctx, c), nil
+}
+
+// Delete 删除上限配置
+func (s *SpendingCapService) Delete(ctx context.Context, scopeType string, scopeID int64) error {
+	if err := s.repo.Delete(ctx, scopeType, scopeID); err != nil {
+		return err
+	}
+	s.invalidate(scopeType, scopeID)
+	s.clearDryRun(ctx, scopeType, scopeID)
+	logger.LegacyPrintf("service.spending_cap", "AUDIT spending cap deleted: %s=%d", scopeType, scopeID)
+	return nil
+}
+
+// Lift 管理员解除暂停：当前周期内不再强制上限，进入新周期后恢复
+func (s *SpendingCapService) Lift(ctx context.Context, scopeType string, scopeID int64, operatorID int64) (*SpendingCapStatus, error) {
+	c, err := s.repo.Get(ctx, scopeType, scopeID)
+	if err != nil {
+		return nil, err
+	}
+	if c == nil {
+		return nil, ErrSpendingCapNotFound
+	}
+	start := c.PeriodStartAt(s.now())
+	if err := s.repo.Lift(ctx, scopeType, scopeID, start); err != nil {
+		return nil, err
+	}
+	s.invalidate(scopeType, scopeID)
+	logger.LegacyPrintf("service.spending_cap", "AUDIT spending cap suspension lifted: %s=%d period_start=%s by=%d",
+		scopeType, scopeID, start.Format(time.RFC3339), operatorID)
+	c.SuspendedAt, c.SuspendedPeriodStart, c.ExemptPeriodStart = nil, nil, &start
+	return s.status(ctx, c), nil
+}
+
+func (s *SpendingCapService) status(ctx context.Context, c *SpendingCap) *SpendingCapStatus {
+	start := c.PeriodStartAt(s.now())
+	st := &SpendingCapStatus{SpendingCap: *c, PeriodStart: start, Suspended: c.SuspendedIn(start)}
+	if spent, err := s.currentSpend(ctx, c, start); err == nil {
+		st.SpentUSD = spent
+	}
+	if c.DryRun && s.cache != nil {
+		count, last, err := s.cache.GetDryRun(ctx, c.ScopeType, c.ScopeID, start)
+		if err == nil && count > 0 {
+			st.DryRunRejections = count
+			st.DryRunLastRejectedAt = &last
+		}
+	}
+	return st
+}
+
+// RecordLimitDryRun 记录演练模式下分组订阅限额 / API Key 总额度本应拒绝的请求；
+// period 为空表示不分周期的总额度
+func (s *SpendingCapService) RecordLimitDryRun(ctx context.Context, scopeType string, scopeID int64, period string, spent, limit float64) {
+	if s == nil {
+		return
+	}
+	if period == "" {
+		s.recordDryRun(ctx, scopeType, scopeID, "total", time.Unix(0, 0), limitDryRunTotalTTL, 0, spent, limit)
+		return
+	}
+	c := &SpendingCap{ScopeType: scopeType, ScopeID: scopeID, Period: period}
+	start := c.PeriodStartAt(s.now())
+	s.recordDryRun(ctx, scopeType, scopeID, period, start, spendingCapCounterTTL(c, start), 0, spent, limit)
+}
+
+// recordDryRun 记录演练模式本应拒绝的请求：累加 Redis 中本周期计数（多实例共享），
+// 每个周期首次及此后每 spendingCapDryRunLogEvery 次记录一条日志；Redis 不可用时只记日志。
+// apiKeyID 为 0 表示限额不按 API Key 归属（如分组订阅）
+func (s *SpendingCapService) recordDryRun(ctx context.Context, scopeType string, scopeID int64, period string, start time.Time, ttl time.Duration, apiKeyID int64, spent, limit float64) {
+	var count int64
+	if s.cache != nil {
+		n, err := s.cache.IncrDryRun(ctx, scopeType, scopeID, start, time.Now(), ttl)
+		if err != nil {
+			logger.LegacyPrintf("service.spending_cap", "[SpendingCap] record dry-run rejection failed for %s %d: %v", scopeType, scopeID, err)
+		}
+		count = n
+	}
+	if count <= 1 || count%spendingCapDryRunLogEvery == 0 {
+		apiKey := "-"
+		if apiKeyID > 0 {
+			apiKey = strconv.FormatInt(apiKeyID, 10)
+		}
+		logger.LegacyPrintf("service.spending_cap", "[SpendingCap] dry-run would reject: %s=%d api_key=%s spent=%.4f limit=%.4f period=%s period_start=%s rejections=%d",
+			scopeType, scopeID, apiKey, spent, limit, period, start.Format(time.RFC3339), count)
+	}
+}
+
+// clearDryRun 上限配置变更后清除当前各周期的演练计数，按新配置重新统计
+func (s *SpendingCapService) clearDryRun(ctx context.Context, scopeType string, scopeID int64) {
+	if s.cache == nil {
+		return
+	}
+	now := s.now()
+	starts := make([]time.Time, 0, 3)
+	for _, period := range []string{SpendingCapPeriodDaily, SpendingCapPeriodWeekly, SpendingCapPeriodMonthly} {
+		starts = append(starts, (&SpendingCap{Period: period}).PeriodStartAt(now))
+	}
+	if err := s.cache.ClearDryRun(ctx, scopeType, scopeID, starts...); err != nil {
+		logger.LegacyPrintf("service.spending_cap", "[SpendingCap] clear dry-run rejections failed for %s %d: %v", scopeType, scopeID, err)
+	}
+}
+
+// capsFor 返回 API Key、所属团队及所属用户的上限配置（内存缓存，未配置的也缓存）
+func (s *SpendingCapService) capsFor(ctx context.Context, apiKey *APIKey) []*SpendingCap {
+	caps := make([]*SpendingCap, 0, 4)
+	if c := s.cached(ctx, SpendingCapScopeAPIKey, apiKey.ID); c != nil {
+		caps = append(caps, c)
+	}
+	if teamID := s.teamIDFor(ctx, apiKey.ID); teamID > 0 {
+		if c := s.cached(ctx, SpendingCapScopeTeamMember, apiKey.ID); c != nil {
+			caps = append(caps, c)
+		}
+		if c := s.cached(ctx, SpendingCapScopeTeam, teamID); c != nil {
+			caps = append(caps, c)
+		}
+	}
+	if c := s.cached(ctx, SpendingCapScopeUser, apiKey.UserID); c != nil {
+		caps = append(caps, c)
+	}
+	return caps
+}
+
+// teamIDFor 返回 API Key 所属团队（内存缓存，未加入团队的也缓存）
+func (s *SpendingCapService) teamIDFor(ctx context.Context, apiKeyID int64) int64 {
+	now := time.Now()
+	s.mu.RLock()
+	entry, ok := s.teams[apiKeyID]
+	s.mu.RUnlock()
+	if ok && now.Before(entry.expiresAt) {
+		return entry.teamID
+	}
+
+	teamID, err := s.repo.GetTeamID(ctx, apiKeyID)
+	if err != nil {
+		logger.LegacyPrintf("service.spending_cap", "[SpendingCap] load team of api key %d failed: %v", apiKeyID, err)
+		return entry.teamID
+	}
+	s.mu.Lock()
+	s.teams[apiKeyID] = spendingCapTeamEntry{teamID: teamID, expiresAt: now.Add(spendingCapLocalTTL)}
+	s.mu.Unlock()
+	return teamID
+}
+
+// InvalidateTeamMembership 团队成员变更后清除本实例缓存；其他实例最多延迟 spendingCapLocalTTL 生效
+func (s *SpendingCapService) InvalidateTeamMembership(apiKeyID int64) {
+	if s == nil {
+		return
+	}
+	s.mu.Lock()
+	delete(s.teams, apiKeyID)
+	delete(s.local, spendingCapLocalKey(SpendingCapScopeTeamMember, apiKeyID))
+	s.mu.Unlock()
+}
+
+func (s *SpendingCapService) cached(ctx context.Context, scopeType string, scopeID int64) *SpendingCap {
+	if scopeID <= 0 {
+		return nil
+	}
+	key := spendingCapLocalKey(scopeType, scopeID)
+	now := time.Now()
+	s.mu.RLock()
+	entry, ok := s.local[key]
+	s.mu.RUnlock()
+	if ok && now.Before(entry.expiresAt) {
+		return entry.cap
+	}
+
+	c, err := s.repo.Get(ctx, scopeType, scopeID)
+	if err != nil {
+		logger.LegacyPrintf("service.spending_cap", "[SpendingCap] load %s %d failed: %v", scopeType, scopeID, err)
+		// 读取失败时沿用旧配置，避免存储抖动导致上限失效
+		return entry.cap
+	}
+	s.mu.Lock()
+	s.local[key] = spendingCapCacheEntry{cap: c, expiresAt: now.Add(spendingCapLocalTTL)}
+	s.mu.Unlock()
+	return c
+}
+
+func (s *SpendingCapService) invalidate(scopeType string, scopeID int64) {
+	s.mu.Lock()
+	delete(s.local, spendingCapLocalKey(scopeType, scopeID))
+	s.mu.Unlock()
+}
+
+// currentSpend 读取周期消费；计数缺失时以账本初始化，Redis 不可用时直接查询账本
+func (s *SpendingCapService) currentSpend(ctx context.Context, c *SpendingCap, start time.Time) (float64, error) {
+	if s.cache != nil {
+		spent, ok, err := s.cache.GetSpend(ctx, c.ScopeType, c.ScopeID, start)
+		if err == nil && ok {
+			return spent, nil
+		}
+		if err != nil {
+			logger.LegacyPrintf("service.spending_cap", "[SpendingCap] cache read failed, falling back to ledger: %v", err)
+		}
+	}
+	spent, err := s.repo.SumSpend(ctx, c.ScopeType, c.ScopeID, start)
+	if err != nil {
+		return 0, err
+	}
+	if s.cache != nil {
+		_, _ = s.cache.InitSpend(ctx, c.ScopeType, c.ScopeID, start, spent, spendingCapCounterTTL(c, start))
+	}
+	return spent, nil
+}
+
+// addSpend 累加周期消费。计数缺失时以账本初始化（账本已包含本次请求，不再重复累加）
+func (s *SpendingCapService) addSpend(ctx context.Context, c *SpendingCap, start time.Time, cost float64) (float64, error) {
+	if s.cache == nil {
+		return s.repo.SumSpend(ctx, c.ScopeType, c.ScopeID, start)
+	}
+	ttl := spendingCapCounterTTL(c, start)
+	spent, ok, err := s.cache.AddSpend(ctx, c.ScopeType, c.ScopeID, start, cost, ttl)
+	if err != nil || ok {
+		return spent, err
+	}
+	seeded, err := s.repo.SumSpend(ctx, c.ScopeType, c.ScopeID, start)
+	if err != nil {
+		return 0, err
+	}
+	created, err := s.cache.InitSpend(ctx, c.ScopeType, c.ScopeID, start, seeded, ttl)
+	if err != nil || created {
+		return seeded, err
+	}
+	// 并发请求已完成初始化，按正常路径累加
+	spent, _, err = s.cache.AddSpend(ctx, c.ScopeType, c.ScopeID, start, cost, ttl)
+	return spent, err
+}
+
+// suspend 标记暂停并通知 API Key 所属用户；多实例下由首个标记成功的实例发送通知
+func (s *SpendingCapService) suspend(ctx context.Context, c *SpendingCap, apiKey *APIKey, start time.Time, spent float64) {
+	marked, err := s.repo.MarkSuspended(ctx, c.ScopeType, c.ScopeID, start, time.Now())
+	s.invalidate(c.ScopeType, c.ScopeID)
+	if err != nil {
+		logger.LegacyPrintf("service.spending_cap", "[SpendingCap] mark suspended failed for %s %d: %v", c.ScopeType, c.ScopeID, err)
+		return
+	}
+	if !marked {
+		return
+	}
+	logger.LegacyPrintf("service.spending_cap", "[SpendingCap] %s %d suspended: spent=%.4f limit=%.4f period=%s",
+		c.ScopeType, c.ScopeID, spent, c.LimitUSD, c.Period)
+
+	if apiKey.User == nil || apiKey.User.Email == "" {
+		return
+	}
+	target := "Your account"
+	switch c.ScopeType {
+	case SpendingCapScopeAPIKey, SpendingCapScopeTeamMember:
+		target = fmt.Sprintf("API key %q", apiKey.Name)
+	case SpendingCapScopeTeam:
+		target = fmt.Sprintf("Team #%d", c.ScopeID)
+	}
+	err = s.notifications.Notify(ctx, Notification{
+		Kind:      NotificationKindQuotaAlert,
+		UserID:    apiKey.User.ID,
+		Email:     apiKey.User.Email,
+		DedupeKey: fmt.Sprintf("%s:%s:%d:%d", NotificationKindQuotaAlert, c.ScopeType, c.ScopeID, start.Unix()),
+		Data: map[string]any{
+			"Target":   target,
+			"Period":   c.Period,
+			"LimitUSD": c.LimitUSD,
+			"SpentUSD": spent,
+			"Since":    start.Format("2006-01-02 15:04 MST"),
+		},
+	})
+	if err != nil {
+		logger.LegacyPrintf("service.spending_cap", "[SpendingCap] enqueue notification failed: %v", err)
+	}
+}
+
+// spendingCapCounterTTL 计数保留到周期结束后一天
+func spendingCapCounterTTL(c *SpendingCap, start time.Time) time.Duration {
+	var end time.Time
+	switch c.Period {
+	case SpendingCapPeriodWeekly:
+		end = start.AddDate(0, 0, 7)
+	case SpendingCapPeriodMonthly:
+		end = start.AddDate(0, 1, 0)
+	default:
+		end = start.AddDate(0, 0, 1)
+	}
+	return time.Until(end) + 24*time.Hour
+}
+
+func spendingCapExceeded(c *SpendingCap, start time.Time) error {
+	return infraerrors.Newf(http.StatusForbidden, ErrSpendingCapExceeded.Reason,
+		"%s spending cap of $%.2f reached for the period starting %s; requests are suspended until the period resets or an admin lifts the suspension",
+		c.Period, c.LimitUSD, start.Format(time.RFC3339))
+}
+
+func validateSpendingCap(c *SpendingCap) error {
+	if c == nil || c.ScopeID <= 0 || c.LimitUSD <= 0 {
+		return ErrSpendingCapInvalid
+	}
+	switch c.ScopeType {
+	case SpendingCapScopeAPIKey, SpendingCapScopeUser, SpendingCapScopeTeam, SpendingCapScopeTeamMember:
+	default:
+		return ErrSpendingCapInvalid
+	}
+	switch c.Period {
+	case SpendingCapPeriodDaily, SpendingCapPeriodWeekly, SpendingCapPeriodMonthly:
+	default:
+		return ErrSpendingCapInvalid
+	}
+	return nil
+}
+
+func spendingCapLocalKey(scopeType string, scopeID int64) string {
+	return scopeType + ":" + strconv.FormatInt(scopeID, 10)
+}
+
+func formatOptionalID(id *int64) string {
+	if id == nil {
+		return "-"
+	}
+	return strconv.FormatInt(*id, 10)
+}
@@ -0,0 +1,381 @@
+//go:build unit
+
+package service
+
+import (
+	"context"
+	"errors"
+	"fmt"
+	"testing"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/pagination"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/timezone"
+	"github.com/stretchr/testify/require"
+)
+
+type spendingCapLedgerEntry struct {
+	apiKeyID int64
+	userID   int64
+	cost     float64
+	at       time.Time
+}
+
+type spendingCapRepoStub struct {
+	caps      map[string]*SpendingCap
+	ledger    []spendingCapLedgerEntry
+	teams     map[int64]int64 // api key -> team
+	sumCalls  int
+	markCalls int
+}
+
+func newSpendingCapRepoStub() *spendingCapRepoStub {
+	return &spendingCapRepoStub{caps: map[string]*SpendingCap{}, teams: map[int64]int64{}}
+}
+
+func (r *spendingCapRepoStub) Get(_ context.Context, scopeType string, scopeID int64) (*SpendingCap, error) {
+	c, ok := r.caps[spendingCapLocalKey(scopeType, scopeID)]
+	if !ok {
+		return nil, nil
+	}
+	cp := *c
+	return &cp, nil
+}
+
+func (r *spendingCapRepoStub) List(context.Context, string, bool, pagination.PaginationParams) ([]SpendingCap, *pagination.PaginationResult, error) {
+	return nil, nil, errors.New("not implemented")
+}
+
+func (r *spendingCapRepoStub) Upsert(_ context.Context, c *SpendingCap) error {
+	cp := *c
+	r.caps[spendingCapLocalKey(c.ScopeType, c.ScopeID)] = &cp
+	return nil
+}
+
+func (r *spendingCapRepoStub) Delete(_ context.Context, scopeType string, scopeID int64) error {
+	delete(r.caps, spendingCapLocalKey(scopeType, scopeID))
+	return nil
+}
+
+func (r *spendingCapRepoStub) MarkSuspended(_ context.Context, scopeType string, scopeID int64, periodStart, at time.Time) (bool, error) {
+	r.markCalls++
+	c, ok := r.caps[spendingCapLocalKey(scopeType, scopeID)]
+	if !ok || c.SuspendedIn(periodStart) || c.ExemptIn(periodStart) {
+		return false, nil
+	}
+	c.SuspendedAt, c.SuspendedPeriodStart = &at, &periodStart
+	return true, nil
+}
+
+func (r *spendingCapRepoStub) Lift(_ context.Context, scopeType string, scopeID int64, periodStart time.Time) error {
+	c, ok := r.caps[spendingCapLocalKey(scopeType, scopeID)]
+	if !ok {
+		return ErrSpendingCapNotFound
+	}
+	c.SuspendedAt, c.SuspendedPeriodStart, c.ExemptPeriodStart = nil, nil, &periodStart
+	return nil
+}
+
+func (r *spendingCapRepoStub) SumSpend(_ context.Context, scopeType string, scopeID int64, since time.Time) (float64, error) {
+	r.sumCalls++
+	var total float64
+	for _, e := range r.ledger {
+		id := e.apiKeyID
+		switch scopeType {
+		case SpendingCapScopeUser:
+			id = e.userID
+		case SpendingCapScopeTeam:
+			id = r.teams[e.apiKeyID]
+		}
+		if id == scopeID && !e.at.Before(since) {
+			total += e.cost
+		}
+	}
+	return total, nil
+}
+
+func (r *spendingCapRepoStub) GetTeamID(_ context.Context, apiKeyID int64) (int64, error) {
+	return r.teams[apiKeyID], nil
+}
+
+type spendingCapCacheStub struct {
+	values     map[string]float64
+	rejections map[string]int64
+	lastAt     map[string]time.Time
+}
+
+func newSpendingCapCacheStub() *spendingCapCacheStub {
+	return &spendingCapCacheStub{values: map[string]float64{}, rejections: map[string]int64{}, lastAt: map[string]time.Time{}}
+}
+
+func spendingCapCacheStubKey(scopeType string, scopeID int64, periodStart time.Time) string {
+	return fmt.Sprintf("%s:%d:%d", scopeType, scopeID, periodStart.Unix())
+}
+
+func (c *spendingCapCacheStub) GetSpend(_ context.Context, scopeType string, scopeID int64, periodStart time.Time) (float64, bool, error) {
+	v, ok := c.values[spendingCapCacheStubKey(scopeType, scopeID, periodStart)]
+	return v, ok, nil
+}
+
+func (c *spendingCapCacheStub) InitSpend(_ context.Context, scopeType string, scopeID int64, periodStart time.Time, value float64, _ time.Duration) (bool, error) {
+	key := spendingCapCacheStubKey(scopeType, scopeID, periodStart)
+	if _, ok := c.values[key]; ok {
+		return false, nil
+	}
+	c.values[key] = value
+	return true, nil
+}
+
+func (c *spendingCapCacheStub) AddSpend(_ context.Context, scopeType string, scopeID int64, periodStart time.Time, amount float64, _ time.Duration) (float64, bool, error) {
+	key := spendingCapCacheStubKey(scopeType, scopeID, periodStart)
+	v, ok := c.values[key]
+	if !ok {
+		return 0, false, nil
+	}
+	c.values[key] = v + amount
+	return v + amount, true, nil
+}
+
+func (c *spendingCapCacheStub) IncrDryRun(_ context.Context, scopeType string, scopeID int64, periodStart, at time.Time, _ time.Duration) (int64, error) {
+	key := spendingCapCacheStubKey(scopeType, scopeID, periodStart)
+	c.rejections[key]++
+	c.lastAt[key] = at
+	return c.rejections[key], nil
+}
+
+func (c *spendingCapCacheStub) GetDryRun(_ context.Context, scopeType string, scopeID int64, periodStart time.Time) (int64, time.Time, error) {
+	key := spendingCapCacheStubKey(scopeType, scopeID, periodStart)
+	return c.rejections[key], c.lastAt[key], nil
+}
+
+func (c *spendingCapCacheStub) ClearDryRun(_ context.Context, scopeType string, scopeID int64, periodStarts ...time.Time) error {
+	for _, start := range periodStarts {
+		key := spendingCapCacheStubKey(scopeType, scopeID, start)
+		delete(c.rejections, key)
+		delete(c.lastAt, key)
+	}
+	return nil
+}
+
+func newSpendingCapServiceForTest(now time.Time) (*SpendingCapService, *spendingCapRepoStub, *spendingCapCacheStub) {
+	repo := newSpendingCapRepoStub()
+	cache := newSpendingCapCacheStub()
+	svc := NewSpendingCapService(repo, cache, nil, nil, nil)
+	svc.now = func() time.Time { return now }
+	return svc, repo, cache
+}
+
+// chargeSpendingCap 模拟计费流程：先写入使用记录（账本），再累加周期计数
+func chargeSpendingCap(svc *SpendingCapService, repo *spendingCapRepoStub, apiKey *APIKey, cost float64) {
+	repo.ledger = append(repo.ledger, spendingCapLedgerEntry{apiKeyID: apiKey.ID, userID: apiKey.UserID, cost: cost, at: svc.now()})
+	svc.RecordSpend(context.Background(), apiKey, cost)
+}
+
+func TestSpendingCapService_SuspendsWhenLimitReached(t *testing.T) {
+	ctx := context.Background()
+	now := time.Date(2026, 3, 10, 12, 0, 0, 0, time.Local)
+	svc, repo, _ := newSpendingCapServiceForTest(now)
+	repo.caps[spendingCapLocalKey(SpendingCapScopeAPIKey, 7)] = &SpendingCap{ScopeType: SpendingCapScopeAPIKey, ScopeID: 7, Period: SpendingCapPeriodDaily, LimitUSD: 1}
+	apiKey := &APIKey{ID: 7, UserID: 3}
+
+	require.NoError(t, svc.Check(ctx, apiKey))
+	chargeSpendingCap(svc, repo, apiKey, 0.6)
+	require.NoError(t, svc.Check(ctx, apiKey))
+
+	chargeSpendingCap(svc, repo, apiKey, 0.5)
+	require.Equal(t, 1, repo.markCalls)
+	require.NotNil(t, repo.caps[spendingCapLocalKey(SpendingCapScopeAPIKey, 7)].SuspendedAt)
+
+	err := svc.Check(ctx, apiKey)
+	require.ErrorIs(t, err, ErrSpendingCapExceeded)
+	require.Equal(t, 1, repo.markCalls, "already suspended caps should not be marked again")
+}
+
+func TestSpendingCapService_DryRunRecordsWithoutEnforcing(t *testing.T) {
+	ctx := context.Background()
+	now := time.Date(2026, 3, 10, 12, 0, 0, 0, time.Local)
+	svc, repo, cache := newSpendingCapServiceForTest(now)
+	repo.caps[spendingCapLocalKey(SpendingCapScopeAPIKey, 7)] = &SpendingCap{ScopeType: SpendingCapScopeAPIKey, ScopeID: 7, Period: SpendingCapPeriodDaily, LimitUSD: 1, DryRun: true}
+	apiKey := &APIKey{ID: 7, UserID: 3}
+
+	chargeSpendingCap(svc, repo, apiKey, 1.5)
+	require.NoError(t, svc.Check(ctx, apiKey))
+	require.NoError(t, svc.Check(ctx, apiKey))
+	require.Zero(t, repo.markCalls, "dry-run caps must not suspend")
+
+	status, err := svc.Get(ctx, SpendingCapScopeAPIKey, 7)
+	require.NoError(t, err)
+	require.False(t, status.Suspended)
+	require.Equal(t, int64(2), status.DryRunRejections)
+	require.NotNil(t, status.DryRunLastRejectedAt)
+
+	// 计数存储于共享缓存，其他实例读取到相同结果
+	other := NewSpendingCapService(repo, cache, nil, nil, nil)
+	other.now = svc.now
+	status, err = other.Get(ctx, SpendingCapScopeAPIKey, 7)
+	require.NoError(t, err)
+	require.Equal(t, int64(2), status.DryRunRejections)
+
+	// 新周期重新计数
+	svc.now = func() time.Time { return now.AddDate(0, 0, 1) }
+	status, err = svc.Get(ctx, SpendingCapScopeAPIKey, 7)
+	require.NoError(t, err)
+	require.Zero(t, status.DryRunRejections)
+
+	// 关闭演练模式后按同一上限强制执行
+	svc.now = func() time.Time { return now }
+	repo.caps[spendingCapLocalKey(SpendingCapScopeAPIKey, 7)].DryRun = false
+	svc.invalidate(SpendingCapScopeAPIKey, 7)
+	require.ErrorIs(t, svc.Check(ctx, apiKey), ErrSpendingCapExceeded)
+	require.Equal(t, 1, repo.markCalls)
+}
+
+func TestSpendingCapService_UserScopeAggregatesKeys(t *testing.T) {
+	ctx := context.Background()
+	now := time.Date(2026, 3, 10, 12, 0, 0, 0, time.Local)
+	svc, repo, _ := newSpendingCapServiceForTest(now)
+	repo.caps[spendingCapLocalKey(SpendingCapScopeUser, 3)] = &SpendingCap{ScopeType: SpendingCapScopeUser, ScopeID: 3, Period: SpendingCapPeriodMonthly, LimitUSD: 2}
+
+	chargeSpendingCap(svc, repo, &APIKey{ID: 7, UserID: 3}, 1)
+	chargeSpendingCap(svc, repo, &APIKey{ID: 8, UserID: 3}, 1)
+
+	require.ErrorIs(t, svc.Check(ctx, &APIKey{ID: 9, UserID: 3}), ErrSpendingCapExceeded)
+	require.NoError(t, svc.Check(ctx, &APIKey{ID: 10, UserID: 4}))
+}
+
+func TestSpendingCapService_TeamBudgetAndMemberLimit(t *testing.T) {
+	ctx := context.Background()
+	now := time.Date(2026, 3, 10, 12, 0, 0, 0, time.Local)
+	svc, repo, _ := newSpendingCapServiceForTest(now)
+	repo.teams[7], repo.teams[8] = 5, 5
+	repo.caps[spendingCapLocalKey(SpendingCapScopeTeam, 5)] = &SpendingCap{ScopeType: SpendingCapScopeTeam, ScopeID: 5, Period: SpendingCapPeriodMonthly, LimitUSD: 3}
+	repo.caps[spendingCapLocalKey(SpendingCapScopeTeamMember, 7)] = &SpendingCap{ScopeType: SpendingCapScopeTeamMember, ScopeID: 7, Period: SpendingCapPeriodMonthly, LimitUSD: 1}
+	alice, bob, outsider := &APIKey{ID: 7, UserID: 3}, &APIKey{ID: 8, UserID: 3}, &APIKey{ID: 9, UserID: 3}
+
+	// 成员子额度只限制该成员
+	chargeSpendingCap(svc, repo, alice, 1)
+	require.ErrorIs(t, svc.Check(ctx, alice), ErrSpendingCapExceeded)
+	require.NoError(t, svc.Check(ctx, bob))
+
+	// 团队预算由成员共享，团队外的 Key 不受影响
+	chargeSpendingCap(svc, repo, bob, 2)
+	require.ErrorIs(t, svc.Check(ctx, bob), ErrSpendingCapExceeded)
+	require.NoError(t, svc.Check(ctx, outsider))
+
+	// 移出团队后不再受团队预算约束
+	delete(repo.teams, 8)
+	svc.InvalidateTeamMembership(8)
+	require.NoError(t, svc.Check(ctx, bob))
+}
+
+func TestSpendingCapService_LiftExemptsCurrentPeriod(t *testing.T) {
+	ctx := context.Background()
+	now := time.Date(2026, 3, 10, 12, 0, 0, 0, time.Local)
+	svc, repo, _ := newSpendingCapServiceForTest(now)
+	repo.caps[spendingCapLocalKey(SpendingCapScopeAPIKey, 7)] = &SpendingCap{ScopeType: SpendingCapScopeAPIKey, ScopeID: 7, Period: SpendingCapPeriodDaily, LimitUSD: 1}
+	apiKey := &APIKey{ID: 7, UserID: 3}
+
+	chargeSpendingCap(svc, repo, apiKey, 2)
+	require.ErrorIs(t, svc.Check(ctx, apiKey), ErrSpendingCapExceeded)
+
+	status, err := svc.Lift(ctx, SpendingCapScopeAPIKey, 7, 1)
+	require.NoError(t, err)
+	require.False(t, status.Suspended)
+	require.Equal(t, 2.0, status.SpentUSD)
+
+	chargeSpendingCap(svc, repo, apiKey, 1)
+	require.NoError(t, svc.Check(ctx, apiKey))
+	require.Equal(t, 1, repo.markCalls)
+
+	// 进入下一周期后恢复强制上限
+	svc.now = func() time.Time { return now.AddDate(0, 0, 1) }
+	require.NoError(t, svc.Check(ctx, apiKey))
+	chargeSpendingCap(svc, repo, apiKey, 1)
+	require.ErrorIs(t, svc.Check(ctx, apiKey), ErrSpendingCapExceeded)
+}
+
+func TestSpendingCapService_PeriodResetClearsSuspension(t *testing.T) {
+	ctx := context.Background()
+	now := time.Date(2026, 3, 10, 12, 0, 0, 0, time.Local)
+	svc, repo, _ := newSpendingCapServiceForTest(now)
+	repo.caps[spendingCapLocalKey(SpendingCapScopeAPIKey, 7)] = &SpendingCap{ScopeType: SpendingCapScopeAPIKey, ScopeID: 7, Period: SpendingCapPeriodDaily, LimitUSD: 1}
+	apiKey := &APIKey{ID: 7, UserID: 3}
+
+	chargeSpendingCap(svc, repo, apiKey, 1)
+	require.ErrorIs(t, svc.Check(ctx, apiKey), ErrSpendingCapExceeded)
+
+	svc.now = func() time.Time { return now.AddDate(0, 0, 1) }
+	require.NoError(t, svc.Check(ctx, apiKey))
+}
+
+func TestSpendingCapService_SeedsFromLedgerWithoutDoubleCounting(t *testing.T) {
+	ctx := context.Background()
+	now := time.Date(2026, 3, 10, 12, 0, 0, 0, time.Local)
+	svc, repo, cache := newSpendingCapServiceForTest(now)
+	c := &SpendingCap{ScopeType: SpendingCapScopeAPIKey, ScopeID: 7, Period: SpendingCapPeriodDaily, LimitUSD: 10}
+	repo.caps[spendingCapLocalKey(SpendingCapScopeAPIKey, 7)] = c
+	repo.ledger = append(repo.ledger, spendingCapLedgerEntry{apiKeyID: 7, userID: 3, cost: 4, at: now.Add(-time.Hour)})
+
+	// 计数缺失时以账本初始化，账本已包含本次请求，不应再重复累加
+	chargeSpendingCap(svc, repo, &APIKey{ID: 7, UserID: 3}, 0.5)
+
+	spent, ok, err := cache.GetSpend(ctx, SpendingCapScopeAPIKey, 7, c.PeriodStartAt(now))
+	require.NoError(t, err)
+	require.True(t, ok)
+	require.Equal(t, 4.5, spent)
+	require.Equal(t, 1, repo.sumCalls)
+}
+
+func TestSpendingCapService_UpsertValidates(t *testing.T) {
+	svc, _, _ := newSpendingCapServiceForTest(time.Now())
+	cases := []*SpendingCap{
+		{ScopeType: "org", ScopeID: 1, Period: SpendingCapPeriodDaily, LimitUSD: 1},
+		{ScopeType: SpendingCapScopeUser, ScopeID: 0, Period: SpendingCapPeriodDaily, LimitUSD: 1},
+		{ScopeType: SpendingCapScopeUser, ScopeID: 1, Period: "hourly", LimitUSD: 1},
+		{ScopeType: SpendingCapScopeUser, ScopeID: 1, Period: SpendingCapPeriodDaily, LimitUSD: 0},
+	}
+	for _, c := range cases {
+		_, err := svc.Upsert(context.Background(), c)
+		require.ErrorIs(t, err, ErrSpendingCapInvalid)
+	}
+}
+
+func TestSpendingCapService_RecordLimitDryRun(t *testing.T) {
+	ctx := context.Background()
+	now := time.Date(2026, 3, 10, 12, 0, 0, 0, time.Local)
+	svc, _, cache := newSpendingCapServiceForTest(now)
+
+	svc.RecordLimitDryRun(ctx, LimitDryRunScopeSubscription, 5, SpendingCapPeriodWeekly, 12, 10)
+	svc.RecordLimitDryRun(ctx, LimitDryRunScopeSubscription, 5, SpendingCapPeriodWeekly, 13, 10)
+	count, _, err := cache.GetDryRun(ctx, LimitDryRunScopeSubscription, 5, timezone.StartOfWeek(now))
+	require.NoError(t, err)
+	require.Equal(t, int64(2), count)
+
+	// 总额度不分周期
+	svc.RecordLimitDryRun(ctx, LimitDryRunScopeAPIKeyQuota, 7, "", 5, 5)
+	count, _, err = cache.GetDryRun(ctx, LimitDryRunScopeAPIKeyQuota, 7, time.Unix(0, 0))
+	require.NoError(t, err)
+	require.Equal(t, int64(1), count)
+}
+
+func TestSubscriptionService_CheckUsageLimitsDryRun(t *testing.T) {
+	ctx := context.Background()
+	now := time.Date(2026, 3, 10, 12, 0, 0, 0, time.Local)
+	caps, _, cache := newSpendingCapServiceForTest(now)
+	cfg := &config.Config{}
+	billing := NewBillingCacheService(nil, nil, nil, cfg, caps)
+	t.Cleanup(billing.Stop)
+	subs := &SubscriptionService{billingCacheService: billing}
+
+	limit := 10.0
+	group := &Group{ID: 1, DailyLimitUSD: &limit}
+	sub := &UserSubscription{ID: 5, DailyUsageUSD: 12}
+	require.ErrorIs(t, subs.CheckUsageLimits(ctx, sub, group, 0), ErrDailyLimitExceeded)
+
+	cfg.Billing.LimitDryRun.SubscriptionLimits = true
+	require.NoError(t, subs.CheckUsageLimits(ctx, sub, group, 0))
+	count, _, err := cache.GetDryRun(ctx, LimitDryRunScopeSubscription, 5, timezone.StartOfDay(now))
+	require.NoError(t, err)
+	require.Equal(t, int64(1), count)
+}
@@ -0,0 +1,66 @@
+package service
+
+import (
+	"strings"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+)
+
+// sseKeepalivePing 下游 keepalive 使用的 SSE 注释，客户端按 SSE 规范忽略
+const sseKeepalivePing = ": ping\n\n"
+
+// sseKeepalive 流式响应的下游 keepalive。
+//
+// 距上次向客户端写入超过 gateway.stream_keepalive_interval 时发送 SSE 注释，
+// 避免上游长时间无输出（思考模型推理阶段、隐藏推理内容等）时被中间代理按空闲超时断开。
+// 只在 SSE 事件边界发送：注释自带的空行会提前结束尚未写完的事件。
+type sseKeepalive struct {
+	interval  time.Duration
+	ticker    *time.Ticker
+	lastWrite time.Time
+	// inEvent 已写出事件的部分行但尚未写出结束空行
+	inEvent bool
+}
+
+// newSSEKeepalive 按配置创建 keepalive；间隔为 0 时禁用（C 返回 nil channel）
+func newSSEKeepalive(cfg *config.Config) *sseKeepalive {
+	k := &sseKeepalive{lastWrite: time.Now()}
+	if cfg != nil && cfg.Gateway.StreamKeepaliveInterval > 0 {
+		k.interval = time.Duration(cfg.Gateway.StreamKeepaliveInterval) * time.Second
+		k.ticker = time.NewTicker(k.interval)
+	}
+	return k
+}
+
+// C 检查是否需要发送 keepalive 的定时信号
+func (k *sseKeepalive) C() <-chan time.Time {
+	if k.ticker == nil {
+		return nil
+	}
+	return k.ticker.C
+}
+
+// Stop 停止定时器
+func (k *sseKeepalive) Stop() {
+	if k.ticker != nil {
+		k.ticker.Stop()
+	}
+}
+
+// Touch 记录向客户端写出了完整事件（含 keepalive 本身）
+func (k *sseKeepalive) Touch() {
+	k.lastWrite = time.Now()
+	k.inEvent = false
+}
+
+// TouchLine 记录向客户端逐行写出了一行（不含换行符），空行表示事件结束
+func (k *sseKeepalive) TouchLine(line string) {
+	k.lastWrite = time.Now()
+	k.inEvent = strings.TrimSpace(line) != ""
+}
+
+// Due 是否应发送 keepalive：位于事件边界且距上次写入已超过间隔
+func (k *sseKeepalive) Due() bool {
+	return k.ticker != nil && !k.inEvent && time.Since(k.lastWrite) >= k.interval
+}
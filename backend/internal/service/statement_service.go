@@ -0,0 +1,512 @@
+package service
+
+import (
+	"bytes"
+	"context"
+	"encoding/csv"
+	"encoding/json"
+	"fmt"
+	"strconv"
+	"strings"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	infraerrors "github.com/Wei-Shaw/sub2api/internal/pkg/errors"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/logger"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/pagination"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/pdf"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/timezone"
+)
+
+// 账单文件格式
+const (
+	StatementFormatJSON = "json"
+	StatementFormatCSV  = "csv"
+	StatementFormatPDF  = "pdf"
+)
+
+// StatementFormats 每份账单生成的全部格式
+var StatementFormats = []string{StatementFormatJSON, StatementFormatCSV, StatementFormatPDF}
+
+var statementContentTypes = map[string]string{
+	StatementFormatJSON: "application/json",
+	StatementFormatCSV:  "text/csv; charset=utf-8",
+	StatementFormatPDF:  "application/pdf",
+}
+
+// statementMonthTimeout 整月批量生成的超时时间
+const statementMonthTimeout = time.Hour
+
+var (
+	ErrStatementNotFound        = infraerrors.NotFound("STATEMENT_NOT_FOUND", "statement not found")
+	ErrStatementInvalidPeriod   = infraerrors.BadRequest("STATEMENT_INVALID_PERIOD", "month must be a past or current month in YYYY-MM format")
+	ErrStatementInvalidFormat   = infraerrors.BadRequest("STATEMENT_INVALID_FORMAT", "format must be json, csv or pdf")
+	ErrStatementStorageDisabled = infraerrors.BadRequest("STATEMENT_STORAGE_DISABLED", "object storage is required for statements")
+)
+
+// StatementModelLine 账单中单个模型的用量与费用
+type StatementModelLine struct {
+	Model               string  `json:"model"`
+	Requests            int64   `json:"requests"`
+	InputTokens         int64   `json:"input_tokens"`
+	OutputTokens        int64   `json:"output_tokens"`
+	CacheCreationTokens int64   `json:"cache_creation_tokens"`
+	CacheReadTokens     int64   `json:"cache_read_tokens"`
+	TotalCost           float64 `json:"total_cost"`
+	ActualCost          float64 `json:"actual_cost"`
+}
+
+// StatementCredit 账单期内到账的一笔充值（余额卡密或管理员正向调整）
+type StatementCredit struct {
+	Type      string    `json:"type"`
+	Amount    float64   `json:"amount"`
+	AppliedAt time.Time `json:"applied_at"`
+}
+
+// Statement 用户（账户）的月度账单
+type Statement struct {
+	ID             int64     `json:"id"`
+	UserID         int64     `json:"user_id"`
+	UserEmail      string    `json:"user_email,omitempty"`
+	Username       string    `json:"username,omitempty"`
+	PeriodStart    time.Time `json:"period_start"`
+	PeriodEnd      time.Time `json:"period_end"`
+	RequestCount   int64     `json:"request_count"`
+	TotalCost      float64   `json:"total_cost"`
+	ActualCost     float64   `json:"actual_cost"`
+	CreditsApplied float64   `json:"credits_applied"`
+	// Models / Credits 明细只存在于账单文件中，列表与详情接口不返回
+	Models      []StatementModelLine `json:"models,omitempty"`
+	Credits     []StatementCredit    `json:"credits,omitempty"`
+	ObjectKeys  map[string]string    `json:"object_keys,omitempty"`
+	GeneratedAt time.Time            `json:"generated_at"`
+}
+
+// Month 账单月份（YYYY-MM）
+func (s *Statement) Month() string {
+	return s.PeriodStart.In(timezone.Location()).Format("2006-01")
+}
+
+// StatementRepository 月度账单数据访问
+type StatementRepository interface {
+	// ListActiveUserIDs 返回 [start, end) 内有使用记录或充值的用户
+	ListActiveUserIDs(ctx context.Context, start, end time.Time) ([]int64, error)
+	// UsageByModel 按模型汇总用户在 [start, end) 内的使用记录
+	UsageByModel(ctx context.Context, userID int64, start, end time.Time) ([]StatementModelLine, error)
+	// Credits 返回用户在 [start, end) 内到账的充值
+	Credits(ctx context.Context, userID int64, start, end time.Time) ([]StatementCredit, error)
+	// Upsert 按 (user_id, period_start) 写入汇总（已存在时覆盖），回填 ID
+	Upsert(ctx context.Context, st *Statement) error
+	GetByID(ctx context.Context, id int64) (*Statement, error)
+	// List 分页列出账单；userID 为 0、periodStart 为 nil 时不筛选
+	List(ctx context.Context, userID int64, periodStart *time.Time, params pagination.PaginationParams) ([]Statement, *pagination.PaginationResult, error)
+}
+
+// StatementService 月度账单：按用户汇总当月各模型用量、费用与到账充值，
+// 渲染为 JSON / CSV / PDF 写入对象存储，并在数据库记录汇总以便管理端查询与下载。
+// 同一用户同一月份重复生成时覆盖原有文件与汇总。
+type StatementService struct {
+	repo          StatementRepository
+	userRepo      UserRepository
+	storage       ObjectStorage
+	notifications *NotificationService
+	cfg           *config.Config
+	now           func() time.Time
+}
+
+// NewStatementService 创建月度账单服务；storage 为 nil 时不支持生成
+func NewStatementService(repo StatementRepository, userRepo UserRepository, storage ObjectStorage, notifications *NotificationService, cfg *config.Config) *StatementService {
+	return &StatementService{repo: repo, userRepo: userRepo, storage: storage, notifications: notifications, cfg: cfg, now: time.Now}
+}
+
+// StorageEnabled 是否配置了对象存储
+func (s *StatementService) StorageEnabled() bool {
+	return s != nil && s.storage != nil
+}
+
+// ParseStatementMonth 解析 YYYY-MM 为该月第一天零点（按配置时区）
+func ParseStatementMonth(month string) (time.Time, error) {
+	start, err := timezone.ParseInLocation("2006-01", strings.TrimSpace(month))
+	if err != nil {
+		return time.Time{}, ErrStatementInvalidPeriod
+	}
+	return start, nil
+}
+
+// Generate 生成（或重新生成）用户在 periodStart 所在月份的账单
+func (s *StatementService) Generate(ctx context.Context, userID int64, periodStart time.Time) (*Statement, error) {
+	if s.storage == nil {
+		return nil, ErrStatementStorageDisabled
+	}
+	start, end, err := s.period(periodStart)
+	if err != nil {
+		return nil, err
+	}
+	user, err := s.userRepo.GetByID(ctx, userID)
+	if err != nil {
+		return nil, err
+	}
+	models, err := s.repo.UsageByModel(ctx, userID, start, end)
+	if err != nil {
+		return nil, fmt.Errorf("load statement usage: %w", err)
+	}
+	credits, err := s.repo.Credits(ctx, userID, start, end)
+	if err != nil {
+		return nil, fmt.Errorf("load statement credits: %w", err)
+	}
+
+	st := &Statement{
+		UserID:      user.ID,
+		UserEmail:   user.Email,
+		Username:    user.Username,
+		PeriodStart: start,
+		PeriodEnd:   end,
+		Models:      models,
+		Credits:     credits,
+		ObjectKeys:  make(map[string]string, len(StatementFormats)),
+		GeneratedAt: s.now(),
+	}
+	for _, m := range models {
+		st.RequestCount += m.Requests
+		st.TotalCost += m.TotalCost
+		st.ActualCost += m.ActualCost
+	}
+	for _, c := range credits {
+		st.CreditsApplied += c.Amount
+	}
+
+	for _, format := range StatementFormats {
+		data, err := s.render(st, format)
+		if err != nil {
+			return nil, fmt.Errorf("render %s statement: %w", format, err)
+		}
+		key := s.objectKey(st, format)
+		w := s.storage.NewWriter(ctx, key, statementContentTypes[format])
+		if _, err := w.Write(data); err != nil {
+			w.Abort()
+			return nil, fmt.Errorf("upload %s statement: %w", format, err)
+		}
+		if err := w.Close(); err != nil {
+			return nil, fmt.Errorf("upload %s statement: %w", format, err)
+		}
+		st.ObjectKeys[format] = key
+	}
+	if err := s.repo.Upsert(ctx, st); err != nil {
+		return nil, fmt.Errorf("save statement: %w", err)
+	}
+	return st, nil
+}
+
+// GenerateMonth 为 periodStart 所在月份内有用量或充值的所有用户生成账单，
+// 单个用户失败不影响其他用户；返回成功生成的数量
+func (s *StatementService) GenerateMonth(ctx context.Context, periodStart time.Time) (int, error) {
+	if s.storage == nil {
+		return 0, ErrStatementStorageDisabled
+	}
+	start, end, err := s.period(periodStart)
+	if err != nil {
+		return 0, err
+	}
+	userIDs, err := s.repo.ListActiveUserIDs(ctx, start, end)
+	if err != nil {
+		return 0, fmt.Errorf("list statement users: %w", err)
+	}
+	generated, failed := 0, 0
+	for _, userID := range userIDs {
+		if err := ctx.Err(); err != nil {
+			return generated, err
+		}
+		st, err := s.Generate(ctx, userID, start)
+		if err != nil {
+			failed++
+			logger.LegacyPrintf("service.statement", "[Statement] generate %s for user %d failed: %v", start.Format("2006-01"), userID, err)
+			continue
+		}
+		generated++
+		s.notifyReady(ctx, st)
+	}
+	logger.LegacyPrintf("service.statement", "[Statement] month=%s generated=%d failed=%d", start.Format("2006-01"), generated, failed)
+	if failed > 0 {
+		return generated, fmt.Errorf("%d of %d statements failed", failed, len(userIDs))
+	}
+	return generated, nil
+}
+
+// notifyReady 通知用户月度账单已生成；重新生成同一月份时不重复通知
+func (s *StatementService) notifyReady(ctx context.Context, st *Statement) {
+	err := s.notifications.Notify(ctx, Notification{
+		Kind:      NotificationKindStatement,
+		UserID:    st.UserID,
+		Email:     st.UserEmail,
+		DedupeKey: fmt.Sprintf("%s:%d:%s", NotificationKindStatement, st.UserID, st.Month()),
+		Data: map[string]any{
+			"Month":      st.Month(),
+			"Requests":   st.RequestCount,
+			"ActualCost": st.ActualCost,
+			"Credits":    st.CreditsApplied,
+		},
+	})
+	if err != nil {
+		logger.LegacyPrintf("service.statement", "[Statement] notify user %d of %s statement failed: %v", st.UserID, st.Month(), err)
+	}
+}
+
+// GenerateMonthAsync 在后台生成整月账单（管理端手动触发），参数校验失败时同步返回错误
+func (s *StatementService) GenerateMonthAsync(periodStart time.Time) error {
+	if s.storage == nil {
+		return ErrStatementStorageDisabled
+	}
+	start, _, err := s.period(periodStart)
+	if err != nil {
+		return err
+	}
+	go func() {
+		ctx, cancel := context.WithTimeout(context.Background(), statementMonthTimeout)
+		defer cancel()
+		_, _ = s.GenerateMonth(ctx, start)
+	}()
+	return nil
+}
+
+// GeneratePreviousMonth 生成上一自然月的账单（定时任务入口）
+func (s *StatementService) GeneratePreviousMonth(ctx context.Context) error {
+	_, err := s.GenerateMonth(ctx, timezone.StartOfMonth(s.now()).AddDate(0, -1, 0))
+	return err
+}
+
+// Get 查询账单汇总
+func (s *StatementService) Get(ctx context.Context, id int64) (*Statement, error) {
+	return s.repo.GetByID(ctx, id)
+}
+
+// List 分页列出账单；month 为空时不按月份筛选
+func (s *StatementService) List(ctx context.Context, userID int64, month string, params pagination.PaginationParams) ([]Statement, *pagination.PaginationResult, error) {
+	var periodStart *time.Time
+	if strings.TrimSpace(month) != "" {
+		start, err := ParseStatementMonth(month)
+		if err != nil {
+			return nil, nil, err
+		}
+		periodStart = &start
+	}
+	return s.repo.List(ctx, userID, periodStart, params)
+}
+
+// Open 打开账单文件，调用方负责关闭 Body；返回对象 key 供下载文件名使用
+func (s *StatementService) Open(ctx context.Context, id int64, format string) (string, *StoredObject, error) {
+	format = strings.ToLower(strings.TrimSpace(format))
+	if _, ok := statementContentTypes[format]; !ok {
+		return "", nil, ErrStatementInvalidFormat
+	}
+	st, err := s.repo.GetByID(ctx, id)
+	if err != nil {
+		return "", nil, err
+	}
+	if s.storage == nil {
+		return "", nil, ErrStatementStorageDisabled
+	}
+	key := st.ObjectKeys[format]
+	if key == "" {
+		return "", nil, ErrStatementNotFound
+	}
+	obj, err := s.storage.Get(ctx, key)
+	if err != nil {
+		return "", nil, err
+	}
+	if obj.ContentType == "" {
+		obj.ContentType = statementContentTypes[format]
+	}
+	return key, obj, nil
+}
+
+// period 规整为自然月 [start, end)，拒绝尚未开始的月份
+func (s *StatementService) period(t time.Time) (time.Time, time.Time, error) {
+	start := timezone.StartOfMonth(t)
+	if start.After(s.now()) {
+		return time.Time{}, time.Time{}, ErrStatementInvalidPeriod
+	}
+	return start, start.AddDate(0, 1, 0), nil
+}
+
+func (s *StatementService) objectKey(st *Statement, format string) string {
+	prefix := ""
+	if s.cfg != nil {
+		prefix = s.cfg.Statements.StoragePrefix
+	}
+	return prefix + st.Month() + "/user_" + strconv.FormatInt(st.UserID, 10) + "." + format
+}
+
+func (s *StatementService) issuer() string {
+	if s.cfg != nil && s.cfg.Statements.IssuerName != "" {
+		return s.cfg.Statements.IssuerName
+	}
+	return "Sub2API"
+}
+
+func (s *StatementService) render(st *Statement, format string) ([]byte, error) {
+	switch format {
+	case StatementFormatJSON:
+		return renderStatementJSON(st)
+	case StatementFormatCSV:
+		return renderStatementCSV(st)
+	case StatementFormatPDF:
+		return renderStatementPDF(st, s.issuer())
+	default:
+		return nil, ErrStatementInvalidFormat
+	}
+}
+
+func renderStatementJSON(st *Statement) ([]byte, error) {
+	doc := *st
+	doc.ObjectKeys = nil
+	if doc.Models == nil {
+		doc.Models = []StatementModelLine{}
+	}
+	if doc.Credits == nil {
+		doc.Credits = []StatementCredit{}
+	}
+	// 文件在写入数据库前生成，不包含账单 ID
+	return json.MarshalIndent(struct {
+		Month string `json:"month"`
+		ID    int64  `json:"-"`
+		Statement
+	}{Month: st.Month(), Statement: doc}, "", "  ")
+}
+
+// renderStatementCSV 输出三段：汇总、按模型明细、充值明细，段之间以空行分隔
+func renderStatementCSV(st *Statement) ([]byte, error) {
+	var buf bytes.Buffer
+	w := csv.NewWriter(&buf)
+	rows := [][]string{
+		{"month", "user_id", "email", "requests", "total_cost", "actual_cost", "credits_applied"},
+		{st.Month(), strconv.FormatInt(st.UserID, 10), escapeCSVFormula(st.UserEmail), strconv.FormatInt(st.RequestCount, 10),
+			formatStatementAmount(st.TotalCost), formatStatementAmount(st.ActualCost), formatStatementAmount(st.CreditsApplied)},
+		{},
+		{"model", "requests", "input_tokens", "output_tokens", "cache_creation_tokens", "cache_read_tokens", "total_cost", "actual_cost"},
+	}
+	for _, m := range st.Models {
+		rows = append(rows, []string{
+			escapeCSVFormula(m.Model),
+			strconv.FormatInt(m.Requests, 10),
+			strconv.FormatInt(m.InputTokens, 10),
+			strconv.FormatInt(m.OutputTokens, 10),
+			strconv.FormatInt(m.CacheCreationTokens, 10),
+			strconv.FormatInt(m.CacheReadTokens, 10),
+			formatStatementAmount(m.TotalCost),
+			formatStatementAmount(m.ActualCost),
+		})
+	}
+	rows = append(rows, []string{}, []string{"credit_type", "amount", "applied_at"})
+	for _, c := range st.Credits {
+		rows = append(rows, []string{c.Type, formatStatementAmount(c.Amount), c.AppliedAt.UTC().Format(time.RFC3339)})
+	}
+	if err := w.WriteAll(rows); err != nil {
+		return nil, err
+	}
+	return buf.Bytes(), nil
+}
+
+// PDF 版面（pt）
+const (
+	statementMarginX      = 48.0
+	statementTop          = 64.0
+	statementBottom       = 780.0
+	statementLineHeight   = 14.0
+	statementBodySize     = 9.0
+	statementModelColumn  = 30 // 模型名最多显示的字符数
+	statementRightEdge    = pdf.PageWidth - statementMarginX
+	statementTokensColumn = 300.0
+	statementCostColumn   = 420.0
+)
+
+// renderStatementPDF 渲染单列表格账单，超出一页时自动分页并重复表头
+func renderStatementPDF(st *Statement, issuer string) ([]byte, error) {
+	doc := pdf.New()
+	var page *pdf.Page
+	y := 0.0
+
+	newPage := func() {
+		page = doc.AddPage()
+		page.Text(statementMarginX, statementTop, 16, pdf.HelveticaBold, issuer+" Statement")
+		page.Text(statementMarginX, statementTop+18, 10, pdf.Helvetica, "Period: "+st.Month()+
+			"   ("+st.PeriodStart.Format("2006-01-02")+" - "+st.PeriodEnd.AddDate(0, 0, -1).Format("2006-01-02")+")")
+		page.TextRight(statementRightEdge, statementTop+18, 8, fmt.Sprintf("page %d", doc.PageCount()))
+		page.Line(statementMarginX, statementTop+26, statementRightEdge, statementTop+26, 0.5)
+		y = statementTop + 44
+	}
+	ensure := func(lines int) {
+		if page == nil || y+float64(lines)*statementLineHeight > statementBottom {
+			newPage()
+		}
+	}
+	section := func(title string) {
+		ensure(3)
+		y += 6
+		page.Text(statementMarginX, y, 11, pdf.HelveticaBold, title)
+		y += statementLineHeight
+	}
+	row := func(font pdf.Font, label string, cols ...string) {
+		page.Text(statementMarginX, y, statementBodySize, font, label)
+		edges := []float64{statementTokensColumn, statementCostColumn, statementRightEdge}
+		for i, col := range cols {
+			page.TextRight(edges[len(edges)-len(cols)+i], y, statementBodySize, col)
+		}
+		y += statementLineHeight
+	}
+
+	ensure(8)
+	account := st.UserEmail
+	if st.Username != "" {
+		account = st.Username + " <" + st.UserEmail + ">"
+	}
+	page.Text(statementMarginX, y, 10, pdf.Helvetica, fmt.Sprintf("Account: #%d %s", st.UserID, account))
+	y += statementLineHeight * 1.5
+
+	section("Summary")
+	row(pdf.Helvetica, "Requests", strconv.FormatInt(st.RequestCount, 10))
+	row(pdf.Helvetica, "Standard cost (USD)", formatStatementAmount(st.TotalCost))
+	row(pdf.HelveticaBold, "Amount charged (USD)", formatStatementAmount(st.ActualCost))
+	row(pdf.Helvetica, "Credits applied (USD)", formatStatementAmount(st.CreditsApplied))
+
+	section("Usage by model")
+	header := func() { row(pdf.HelveticaBold, "Model", "Requests", "Tokens", "Charged (USD)") }
+	header()
+	if len(st.Models) == 0 {
+		row(pdf.Helvetica, "No usage in this period")
+	}
+	for _, m := range st.Models {
+		if y+statementLineHeight > statementBottom {
+			newPage()
+			header()
+		}
+		name := m.Model
+		if r := []rune(name); len(r) > statementModelColumn {
+			name = string(r[:statementModelColumn-3]) + "..."
+		}
+		tokens := m.InputTokens + m.OutputTokens + m.CacheCreationTokens + m.CacheReadTokens
+		row(pdf.Helvetica, name, strconv.FormatInt(m.Requests, 10), strconv.FormatInt(tokens, 10), formatStatementAmount(m.ActualCost))
+	}
+
+	section("Credits")
+	if len(st.Credits) == 0 {
+		row(pdf.Helvetica, "No credits in this period")
+	}
+	for _, c := range st.Credits {
+		ensure(1)
+		row(pdf.Helvetica, c.AppliedAt.In(timezone.Location()).Format("2006-01-02 15:04")+"  "+c.Type, formatStatementAmount(c.Amount))
+	}
+
+	ensure(2)
+	y += statementLineHeight
+	page.Text(statementMarginX, y, 8, pdf.Helvetica, "Generated "+st.GeneratedAt.UTC().Format(time.RFC3339)+". Amounts are in USD.")
+
+	var buf bytes.Buffer
+	if _, err := doc.WriteTo(&buf); err != nil {
+		return nil, err
+	}
+	return buf.Bytes(), nil
+}
+
+func formatStatementAmount(v float64) string {
+	return strconv.FormatFloat(v, 'f', 4, 64)
+}
@@ -0,0 +1,182 @@
+//go:build unit
+
+package service
+
+import (
+	"bytes"
+	"context"
+	"encoding/csv"
+	"encoding/json"
+	"io"
+	"strings"
+	"testing"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/pagination"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/timezone"
+	"github.com/stretchr/testify/require"
+)
+
+type statementRepoStub struct {
+	activeUsers []int64
+	models      []StatementModelLine
+	credits     []StatementCredit
+	saved       []*Statement
+	usageRange  [2]time.Time
+}
+
+func (s *statementRepoStub) ListActiveUserIDs(_ context.Context, _, _ time.Time) ([]int64, error) {
+	return s.activeUsers, nil
+}
+
+func (s *statementRepoStub) UsageByModel(_ context.Context, _ int64, start, end time.Time) ([]StatementModelLine, error) {
+	s.usageRange = [2]time.Time{start, end}
+	return s.models, nil
+}
+
+func (s *statementRepoStub) Credits(_ context.Context, _ int64, _, _ time.Time) ([]StatementCredit, error) {
+	return s.credits, nil
+}
+
+func (s *statementRepoStub) Upsert(_ context.Context, st *Statement) error {
+	st.ID = int64(len(s.saved) + 1)
+	s.saved = append(s.saved, st)
+	return nil
+}
+
+func (s *statementRepoStub) GetByID(_ context.Context, id int64) (*Statement, error) {
+	if id <= 0 || int(id) > len(s.saved) {
+		return nil, ErrStatementNotFound
+	}
+	return s.saved[id-1], nil
+}
+
+func (s *statementRepoStub) List(context.Context, int64, *time.Time, pagination.PaginationParams) ([]Statement, *pagination.PaginationResult, error) {
+	panic("unexpected List call")
+}
+
+func newStatementServiceForTest(storage ObjectStorage) (*StatementService, *statementRepoStub) {
+	cfg := &config.Config{}
+	cfg.Statements = config.StatementsConfig{StoragePrefix: "statements/", IssuerName: "Acme"}
+	repo := &statementRepoStub{
+		models: []StatementModelLine{
+			{Model: "claude-sonnet-4", Requests: 10, InputTokens: 1000, OutputTokens: 500, TotalCost: 2, ActualCost: 1.5},
+			{Model: "=cmd|' /C calc'!A0", Requests: 2, InputTokens: 10, OutputTokens: 5, TotalCost: 0.5, ActualCost: 0.25},
+		},
+		credits: []StatementCredit{{Type: RedeemTypeBalance, Amount: 20, AppliedAt: time.Date(2026, 9, 3, 8, 0, 0, 0, time.UTC)}},
+	}
+	users := &userRepoStub{user: &User{ID: 7, Email: "ops@example.com", Username: "ops"}}
+	svc := NewStatementService(repo, users, storage, nil, cfg)
+	svc.now = func() time.Time { return time.Date(2026, 10, 15, 12, 0, 0, 0, timezone.Location()) }
+	return svc, repo
+}
+
+func TestParseStatementMonth(t *testing.T) {
+	start, err := ParseStatementMonth(" 2026-09 ")
+	require.NoError(t, err)
+	require.Equal(t, time.Date(2026, 9, 1, 0, 0, 0, 0, timezone.Location()), start)
+
+	for _, v := range []string{"", "2026-9", "2026-13", "09/2026"} {
+		_, err := ParseStatementMonth(v)
+		require.ErrorIs(t, err, ErrStatementInvalidPeriod, v)
+	}
+}
+
+func TestStatementService_Generate(t *testing.T) {
+	storage := newMemoryObjectStorage()
+	svc, repo := newStatementServiceForTest(storage)
+	ctx := context.Background()
+
+	st, err := svc.Generate(ctx, 7, time.Date(2026, 9, 17, 0, 0, 0, 0, timezone.Location()))
+	require.NoError(t, err)
+	require.Equal(t, int64(1), st.ID)
+	require.Equal(t, "2026-09", st.Month())
+	require.Equal(t, time.Date(2026, 9, 1, 0, 0, 0, 0, timezone.Location()), repo.usageRange[0])
+	require.Equal(t, time.Date(2026, 10, 1, 0, 0, 0, 0, timezone.Location()), repo.usageRange[1])
+	require.Equal(t, int64(12), st.RequestCount)
+	require.InDelta(t, 2.5, st.TotalCost, 1e-9)
+	require.InDelta(t, 1.75, st.ActualCost, 1e-9)
+	require.InDelta(t, 20, st.CreditsApplied, 1e-9)
+	require.Equal(t, map[string]string{
+		StatementFormatJSON: "statements/2026-09/user_7.json",
+		StatementFormatCSV:  "statements/2026-09/user_7.csv",
+		StatementFormatPDF:  "statements/2026-09/user_7.pdf",
+	}, st.ObjectKeys)
+
+	var doc map[string]any
+	require.NoError(t, json.Unmarshal(storage.objects["statements/2026-09/user_7.json"], &doc))
+	require.Equal(t, "2026-09", doc["month"])
+	require.Len(t, doc["models"], 2)
+	require.Len(t, doc["credits"], 1)
+	require.NotContains(t, doc, "object_keys")
+	require.NotContains(t, doc, "id")
+
+	// 各段列数不同；空行分隔的段落在读取时被跳过
+	r := csv.NewReader(bytes.NewReader(storage.objects["statements/2026-09/user_7.csv"]))
+	r.FieldsPerRecord = -1
+	rows, err := r.ReadAll()
+	require.NoError(t, err)
+	require.Equal(t, []string{"2026-09", "7", "ops@example.com", "12", "2.5000", "1.7500", "20.0000"}, rows[1])
+	require.Equal(t, "'=cmd|' /C calc'!A0", rows[4][0], "model names must be neutralised against CSV formula injection")
+
+	pdfData := string(storage.objects["statements/2026-09/user_7.pdf"])
+	require.True(t, strings.HasPrefix(pdfData, "%PDF-1.4"))
+	require.Contains(t, pdfData, "(Acme Statement) Tj")
+	require.Contains(t, pdfData, "(claude-sonnet-4) Tj")
+
+	key, obj, err := svc.Open(ctx, st.ID, "CSV")
+	require.NoError(t, err)
+	require.Equal(t, "statements/2026-09/user_7.csv", key)
+	require.Equal(t, "text/csv; charset=utf-8", obj.ContentType)
+	data, err := io.ReadAll(obj.Body)
+	require.NoError(t, err)
+	require.Equal(t, storage.objects[key], data)
+
+	_, _, err = svc.Open(ctx, st.ID, "xlsx")
+	require.ErrorIs(t, err, ErrStatementInvalidFormat)
+	_, _, err = svc.Open(ctx, 99, StatementFormatPDF)
+	require.ErrorIs(t, err, ErrStatementNotFound)
+}
+
+func TestStatementService_GenerateValidation(t *testing.T) {
+	ctx := context.Background()
+
+	disabled, _ := newStatementServiceForTest(nil)
+	_, err := disabled.Generate(ctx, 7, time.Date(2026, 9, 1, 0, 0, 0, 0, timezone.Location()))
+	require.ErrorIs(t, err, ErrStatementStorageDisabled)
+	require.False(t, disabled.StorageEnabled())
+
+	svc, _ := newStatementServiceForTest(newMemoryObjectStorage())
+	_, err = svc.Generate(ctx, 7, time.Date(2026, 11, 1, 0, 0, 0, 0, timezone.Location()))
+	require.ErrorIs(t, err, ErrStatementInvalidPeriod)
+}
+
+func TestStatementService_GeneratePreviousMonth(t *testing.T) {
+	storage := newMemoryObjectStorage()
+	svc, repo := newStatementServiceForTest(storage)
+	repo.activeUsers = []int64{7, 8}
+
+	require.NoError(t, svc.GeneratePreviousMonth(context.Background()))
+	require.Len(t, repo.saved, 2)
+	for _, st := range repo.saved {
+		require.Equal(t, "2026-09", st.Month())
+	}
+}
+
+func TestRenderStatementPDF_Paginates(t *testing.T) {
+	st := &Statement{
+		UserID:      7,
+		PeriodStart: time.Date(2026, 9, 1, 0, 0, 0, 0, time.UTC),
+		PeriodEnd:   time.Date(2026, 10, 1, 0, 0, 0, 0, time.UTC),
+	}
+	for i := 0; i < 120; i++ {
+		st.Models = append(st.Models, StatementModelLine{Model: "model-with-a-rather-long-name-that-gets-truncated", Requests: 1})
+	}
+	data, err := renderStatementPDF(st, "Acme")
+	require.NoError(t, err)
+	out := string(data)
+	require.Contains(t, out, "/Count 3 ")
+	require.Contains(t, out, "(page 2) Tj")
+	require.Contains(t, out, "(model-with-a-rather-long-na...) Tj")
+}
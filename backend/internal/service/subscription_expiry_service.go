@@ -11,6 +11,7 @@ import (
 type SubscriptionExpiryService struct {
 	userSubRepo UserSubscriptionRepository
 	interval    time.Duration
+	leader      *LeaderElector
 	stopCh      chan struct{}
 	stopOnce    sync.Once
 	wg          sync.WaitGroup
@@ -24,6 +25,11 @@ func NewSubscriptionExpiryService(userSubRepo UserSubscriptionRepository, interv
 	}
 }
 
+// SetLeaderElector 多实例部署时仅由 leader 执行；需要在 Start() 之前调用
+func (s *SubscriptionExpiryService) SetLeaderElector(leader *LeaderElector) {
+	s.leader = leader
+}
+
 func (s *SubscriptionExpiryService) Start() {
 	if s == nil || s.userSubRepo == nil || s.interval <= 0 {
 		return
@@ -57,6 +63,9 @@ func (s *SubscriptionExpiryService) Stop() {
 }
 
 func (s *SubscriptionExpiryService) runOnce() {
+	if !s.leader.IsLeader() {
+		return
+	}
 	ctx, cancel := context.WithTimeout(context.Background(), 10*time.Second)
 	defer cancel()
 
@@ -743,24 +743,33 @@ func (s *SubscriptionService) CheckAndResetWindows(ctx context.Context, sub *Use
 }
 
 // CheckUsageLimits 检查使用限额（返回错误如果超限）
-// 用于中间件的快速预检查，additionalCost 通常为 0
+// 用于中间件的快速预检查，additionalCost 通常为 0；分组订阅限额处于演练模式时超限只记录不拒绝
 func (s *SubscriptionService) CheckUsageLimits(ctx context.Context, sub *UserSubscription, group *Group, additionalCost float64) error {
+	period, spent, limit, err := exceededUsageLimit(sub, group, additionalCost)
+	if err != nil && s.billingCacheService.LimitDryRun(ctx, LimitDryRunScopeSubscription, sub.ID, period, spent, limit) {
+		return nil
+	}
+	return err
+}
+
+// exceededUsageLimit 依次检查日 / 周 / 月限额，返回首个超限的周期、用量及限额
+func exceededUsageLimit(sub *UserSubscription, group *Group, additionalCost float64) (period string, spent, limit float64, err error) {
 	if !sub.CheckDailyLimit(group, additionalCost) {
-		return ErrDailyLimitExceeded
+		return SpendingCapPeriodDaily, sub.DailyUsageUSD + additionalCost, *group.DailyLimitUSD, ErrDailyLimitExceeded
 	}
 	if !sub.CheckWeeklyLimit(group, additionalCost) {
-		return ErrWeeklyLimitExceeded
+		return SpendingCapPeriodWeekly, sub.WeeklyUsageUSD + additionalCost, *group.WeeklyLimitUSD, ErrWeeklyLimitExceeded
 	}
 	if !sub.CheckMonthlyLimit(group, additionalCost) {
-		return ErrMonthlyLimitExceeded
+		return SpendingCapPeriodMonthly, sub.MonthlyUsageUSD + additionalCost, *group.MonthlyLimitUSD, ErrMonthlyLimitExceeded
 	}
-	return nil
+	return "", 0, 0, nil
 }
 
 // ValidateAndCheckLimits 合并验证+限额检查（中间件热路径专用）
-// 仅做内存检查，不触发 DB 写入。窗口重置的 DB 写入由 DoWindowMaintenance 异步完成。
+// 仅做内存检查，不触发 DB 写入（限额演练模式超限时只写 Redis 计数）。窗口重置的 DB 写入由 DoWindowMaintenance 异步完成。
 // 返回 needsMaintenance 表示是否需要异步执行窗口维护。
-func (s *SubscriptionService) ValidateAndCheckLimits(sub *UserSubscription, group *Group) (needsMaintenance bool, err error) {
+func (s *SubscriptionService) ValidateAndCheckLimits(ctx context.Context, sub *UserSubscription, group *Group) (needsMaintenance bool, err error) {
 	// 1. 验证订阅状态
 	if sub.Status == SubscriptionStatusExpired {
 		return false, ErrSubscriptionExpired
@@ -791,17 +800,7 @@ func (s *SubscriptionService) ValidateAndCheckLimits(sub *UserSubscription, grou
 	}
 
 	// 3. 检查用量限额
-	if !sub.CheckDailyLimit(group, 0) {
-		return needsMaintenance, ErrDailyLimitExceeded
-	}
-	if !sub.CheckWeeklyLimit(group, 0) {
-		return needsMaintenance, ErrWeeklyLimitExceeded
-	}
-	if !sub.CheckMonthlyLimit(group, 0) {
-		return needsMaintenance, ErrMonthlyLimitExceeded
-	}
-
-	return needsMaintenance, nil
+	return needsMaintenance, s.CheckUsageLimits(ctx, sub, group, 0)
 }
 
 // DoWindowMaintenance 异步执行窗口维护（激活+重置）
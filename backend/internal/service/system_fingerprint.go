@@ -0,0 +1,23 @@
+package service
+
+import (
+	"crypto/sha256"
+	"encoding/hex"
+)
+
+// SystemFingerprintHeader 响应头：本次请求实际使用的上游、模型与网关适配器版本的稳定指纹，取值变化说明后端发生了变化
+const SystemFingerprintHeader = "X-Sub2API-System-Fingerprint"
+
+// systemFingerprintAdapterVersion 网关协议适配器版本，请求 / 响应转换逻辑发生影响输出的变化时递增
+const systemFingerprintAdapterVersion = "1"
+
+// SystemFingerprint 由上游（平台 + 账号类型）、映射后的上游模型与适配器版本生成 system_fingerprint（fp_ + 10 位十六进制）。
+// 相同后端得到相同指纹，不包含账号 ID，同一上游的多个账号之间切换不会改变指纹。
+func SystemFingerprint(account *Account, requestedModel string) string {
+	if account == nil {
+		return ""
+	}
+	source := account.Platform + "\x00" + account.Type + "\x00" + account.GetMappedModel(requestedModel) + "\x00" + systemFingerprintAdapterVersion
+	sum := sha256.Sum256([]byte(source))
+	return "fp_" + hex.EncodeToString(sum[:5])
+}
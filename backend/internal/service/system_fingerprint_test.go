@@ -0,0 +1,28 @@
+//go:build unit
+
+package service
+
+import (
+	"testing"
+
+	"github.com/stretchr/testify/require"
+)
+
+func TestSystemFingerprint(t *testing.T) {
+	account := &Account{ID: 1, Platform: PlatformAnthropic, Type: AccountTypeAPIKey}
+	fingerprint := SystemFingerprint(account, "claude-sonnet-4-5")
+	require.Regexp(t, `^fp_[0-9a-f]{10}$`, fingerprint)
+
+	// 同一上游的不同账号指纹相同
+	require.Equal(t, fingerprint, SystemFingerprint(&Account{ID: 2, Platform: PlatformAnthropic, Type: AccountTypeAPIKey}, "claude-sonnet-4-5"))
+	// 模型、账号类型或平台变化时指纹变化
+	require.NotEqual(t, fingerprint, SystemFingerprint(account, "claude-haiku-4-5"))
+	require.NotEqual(t, fingerprint, SystemFingerprint(&Account{Platform: PlatformAnthropic, Type: AccountTypeOAuth}, "claude-sonnet-4-5"))
+	require.NotEqual(t, fingerprint, SystemFingerprint(&Account{Platform: PlatformGemini, Type: AccountTypeAPIKey}, "claude-sonnet-4-5"))
+
+	// 按映射后的上游模型计算
+	mapped := &Account{Platform: PlatformAnthropic, Type: AccountTypeAPIKey, Credentials: map[string]any{"model_mapping": map[string]any{"alias": "claude-sonnet-4-5"}}}
+	require.Equal(t, fingerprint, SystemFingerprint(mapped, "alias"))
+
+	require.Empty(t, SystemFingerprint(nil, "m"))
+}
@@ -0,0 +1,130 @@
+package service
+
+import (
+	"encoding/json"
+	"strings"
+
+	"github.com/tidwall/gjson"
+	"github.com/tidwall/sjson"
+)
+
+// ApplyClaudeSystemPrompt 按策略模板修改 Anthropic Messages 请求的 system 字段，返回新请求体及是否修改
+//
+// system 为字符串时按文本拼接；为内容块数组时以独立 text 块插入，保留客户端块上的 cache_control。
+func ApplyClaudeSystemPrompt(body []byte, sp *SystemPromptTemplate) ([]byte, bool) {
+	if sp == nil || strings.TrimSpace(sp.Template) == "" {
+		return body, false
+	}
+	system := gjson.GetBytes(body, "system")
+
+	var value any
+	switch {
+	case sp.Mode == SystemPromptModeReplace || !system.Exists() || system.Type == gjson.Null:
+		value = sp.Template
+	case system.Type == gjson.String:
+		value = joinSystemPrompt(system.String(), sp)
+	case system.IsArray():
+		block, err := json.Marshal(map[string]any{"type": "text", "text": sp.Template})
+		if err != nil {
+			return body, false
+		}
+		blocks := make([]string, 0, len(system.Array())+1)
+		if sp.Mode != SystemPromptModeAppend {
+			blocks = append(blocks, string(block))
+		}
+		system.ForEach(func(_, item gjson.Result) bool {
+			blocks = append(blocks, item.Raw)
+			return true
+		})
+		if sp.Mode == SystemPromptModeAppend {
+			blocks = append(blocks, string(block))
+		}
+		next, err := sjson.SetRawBytes(body, "system", []byte("["+strings.Join(blocks, ",")+"]"))
+		if err != nil {
+			return body, false
+		}
+		return next, true
+	default:
+		return body, false
+	}
+
+	next, err := sjson.SetBytes(body, "system", value)
+	if err != nil {
+		return body, false
+	}
+	return next, true
+}
+
+// applyOpenAISystemPrompt 按策略模板修改 Responses API 请求，返回是否修改
+//
+// useInstructions 为 true 时直接改写 instructions 字段；OAuth（Codex）账号的 instructions 由上游协议约束，
+// 此时以 developer 消息写入 input，replace 模式会移除客户端的 system / developer 消息。
+func applyOpenAISystemPrompt(reqBody map[string]any, sp *SystemPromptTemplate, useInstructions bool) bool {
+	if sp == nil || strings.TrimSpace(sp.Template) == "" {
+		return false
+	}
+	if useInstructions {
+		existing, _ := reqBody["instructions"].(string)
+		if sp.Mode == SystemPromptModeReplace || strings.TrimSpace(existing) == "" {
+			reqBody["instructions"] = sp.Template
+		} else {
+			reqBody["instructions"] = joinSystemPrompt(existing, sp)
+		}
+		return true
+	}
+
+	var input []any
+	switch v := reqBody["input"].(type) {
+	case []any:
+		input = v
+	case string:
+		input = []any{map[string]any{"type": "message", "role": "user", "content": v}}
+	}
+
+	developer := map[string]any{"type": "message", "role": "developer", "content": sp.Template}
+	out := make([]any, 0, len(input)+1)
+	switch sp.Mode {
+	case SystemPromptModeAppend:
+		// 插入到客户端最后一条 system / developer 消息之后，保持指令位于对话之前
+		insertAt := 0
+		for i, item := range input {
+			if isOpenAIInstructionMessage(item) {
+				insertAt = i + 1
+			}
+		}
+		out = append(out, input[:insertAt]...)
+		out = append(out, developer)
+		out = append(out, input[insertAt:]...)
+	case SystemPromptModeReplace:
+		out = append(out, developer)
+		for _, item := range input {
+			if !isOpenAIInstructionMessage(item) {
+				out = append(out, item)
+			}
+		}
+	default:
+		out = append(out, developer)
+		out = append(out, input...)
+	}
+	reqBody["input"] = out
+	return true
+}
+
+func isOpenAIInstructionMessage(item any) bool {
+	m, ok := item.(map[string]any)
+	if !ok {
+		return false
+	}
+	role, _ := m["role"].(string)
+	return role == "system" || role == "developer"
+}
+
+func joinSystemPrompt(existing string, sp *SystemPromptTemplate) string {
+	if strings.TrimSpace(existing) == "" {
+		return sp.Template
+	}
+	if sp.Mode == SystemPromptModeAppend {
+		return existing + "\n\n" + sp.Template
+	}
+	return sp.Template + "\n\n" + existing
+}
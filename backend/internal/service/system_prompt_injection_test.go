@@ -0,0 +1,136 @@
+//go:build unit
+
+package service
+
+import (
+	"testing"
+	"time"
+
+	"github.com/stretchr/testify/require"
+	"github.com/tidwall/gjson"
+)
+
+func TestApplyClaudeSystemPrompt(t *testing.T) {
+	tpl := func(mode string) *SystemPromptTemplate {
+		return &SystemPromptTemplate{Mode: mode, Template: "Follow company policy."}
+	}
+
+	t.Run("nil template keeps body", func(t *testing.T) {
+		body := []byte(`{"system":"hi"}`)
+		out, ok := ApplyClaudeSystemPrompt(body, nil)
+		require.False(t, ok)
+		require.Equal(t, body, out)
+	})
+
+	t.Run("missing system", func(t *testing.T) {
+		out, ok := ApplyClaudeSystemPrompt([]byte(`{"messages":[]}`), tpl(SystemPromptModeAppend))
+		require.True(t, ok)
+		require.Equal(t, "Follow company policy.", gjson.GetBytes(out, "system").String())
+	})
+
+	t.Run("string prepend and append", func(t *testing.T) {
+		out, _ := ApplyClaudeSystemPrompt([]byte(`{"system":"Be brief."}`), tpl(SystemPromptModePrepend))
+		require.Equal(t, "Follow company policy.\n\nBe brief.", gjson.GetBytes(out, "system").String())
+
+		out, _ = ApplyClaudeSystemPrompt([]byte(`{"system":"Be brief."}`), tpl(SystemPromptModeAppend))
+		require.Equal(t, "Be brief.\n\nFollow company policy.", gjson.GetBytes(out, "system").String())
+	})
+
+	t.Run("array keeps client blocks", func(t *testing.T) {
+		body := []byte(`{"system":[{"type":"text","text":"Be brief.","cache_control":{"type":"ephemeral"}}]}`)
+		out, ok := ApplyClaudeSystemPrompt(body, tpl(SystemPromptModePrepend))
+		require.True(t, ok)
+		blocks := gjson.GetBytes(out, "system").Array()
+		require.Len(t, blocks, 2)
+		require.Equal(t, "Follow company policy.", blocks[0].Get("text").String())
+		require.Equal(t, "ephemeral", blocks[1].Get("cache_control.type").String())
+
+		out, _ = ApplyClaudeSystemPrompt(body, tpl(SystemPromptModeAppend))
+		require.Equal(t, "Follow company policy.", gjson.GetBytes(out, "system.1.text").String())
+	})
+
+	t.Run("replace", func(t *testing.T) {
+		out, _ := ApplyClaudeSystemPrompt([]byte(`{"system":[{"type":"text","text":"x"}]}`), tpl(SystemPromptModeReplace))
+		require.Equal(t, "Follow company policy.", gjson.GetBytes(out, "system").String())
+	})
+}
+
+func TestApplyOpenAISystemPrompt(t *testing.T) {
+	sp := &SystemPromptTemplate{Mode: SystemPromptModePrepend, Template: "Policy"}
+
+	t.Run("instructions", func(t *testing.T) {
+		reqBody := map[string]any{"instructions": "Client"}
+		require.True(t, applyOpenAISystemPrompt(reqBody, sp, true))
+		require.Equal(t, "Policy\n\nClient", reqBody["instructions"])
+
+		reqBody = map[string]any{"instructions": "Client"}
+		applyOpenAISystemPrompt(reqBody, &SystemPromptTemplate{Mode: SystemPromptModeReplace, Template: "Policy"}, true)
+		require.Equal(t, "Policy", reqBody["instructions"])
+	})
+
+	t.Run("string input becomes messages", func(t *testing.T) {
+		reqBody := map[string]any{"input": "hello"}
+		require.True(t, applyOpenAISystemPrompt(reqBody, sp, false))
+		input := reqBody["input"].([]any)
+		require.Len(t, input, 2)
+		require.Equal(t, "developer", input[0].(map[string]any)["role"])
+		require.Equal(t, "hello", input[1].(map[string]any)["content"])
+	})
+
+	t.Run("append after client instructions", func(t *testing.T) {
+		reqBody := map[string]any{"input": []any{
+			map[string]any{"role": "developer", "content": "client rules"},
+			map[string]any{"role": "user", "content": "hi"},
+		}}
+		applyOpenAISystemPrompt(reqBody, &SystemPromptTemplate{Mode: SystemPromptModeAppend, Template: "Policy"}, false)
+		input := reqBody["input"].([]any)
+		require.Len(t, input, 3)
+		require.Equal(t, "Policy", input[1].(map[string]any)["content"])
+	})
+
+	t.Run("replace drops client instructions", func(t *testing.T) {
+		reqBody := map[string]any{"input": []any{
+			map[string]any{"role": "system", "content": "client rules"},
+			map[string]any{"role": "user", "content": "hi"},
+		}}
+		applyOpenAISystemPrompt(reqBody, &SystemPromptTemplate{Mode: SystemPromptModeReplace, Template: "Policy"}, false)
+		input := reqBody["input"].([]any)
+		require.Len(t, input, 2)
+		require.Equal(t, "Policy", input[0].(map[string]any)["content"])
+		require.Equal(t, "user", input[1].(map[string]any)["role"])
+	})
+}
+
+func TestAPIKeyPolicy_MergeAndRender(t *testing.T) {
+	group := &APIKeyPolicy{
+		ReasoningMode: ReasoningModeStrip,
+		SystemPrompt:  &SystemPromptTemplate{Mode: SystemPromptModePrepend, Template: "Group {{group_name}}"},
+	}
+	merged := mergeAPIKeyPolicy(group, &APIKeyPolicy{})
+	require.Equal(t, ReasoningModeStrip, merged.ReasoningMode)
+	require.Equal(t, "Group {{group_name}}", merged.SystemPrompt.Template)
+
+	merged = mergeAPIKeyPolicy(group, &APIKeyPolicy{
+		SystemPrompt: &SystemPromptTemplate{Mode: SystemPromptModeAppend, Template: "Key {{key_name}} on {{date}}"},
+	})
+	require.Equal(t, SystemPromptModeAppend, merged.SystemPrompt.Mode)
+
+	apiKey := &APIKey{Name: "ci-bot", Group: &Group{Name: "acme"}}
+	rendered := merged.ForRequest(apiKey, time.Date(2026, 3, 1, 8, 0, 0, 0, time.UTC))
+	require.Equal(t, "Key ci-bot on 2026-03-01", rendered.SystemPrompt.Template)
+	// 渲染不修改缓存中的原始策略
+	require.Equal(t, "Key {{key_name}} on {{date}}", merged.SystemPrompt.Template)
+}
+
+func TestAPIKeyPolicy_NormalizeSystemPrompt(t *testing.T) {
+	policy := &APIKeyPolicy{SystemPrompt: &SystemPromptTemplate{Template: "x"}}
+	require.NoError(t, policy.Normalize())
+	require.Equal(t, SystemPromptModePrepend, policy.SystemPrompt.Mode)
+
+	policy = &APIKeyPolicy{SystemPrompt: &SystemPromptTemplate{Mode: "prepend", Template: "  "}}
+	require.NoError(t, policy.Normalize())
+	require.Nil(t, policy.SystemPrompt)
+
+	policy = &APIKeyPolicy{SystemPrompt: &SystemPromptTemplate{Mode: "wrap", Template: "x"}}
+	require.ErrorIs(t, policy.Normalize(), ErrInvalidSystemPromptMode)
+}
@@ -0,0 +1,365 @@
+package service
+
+import (
+	"context"
+	"fmt"
+	"strings"
+	"time"
+
+	infraerrors "github.com/Wei-Shaw/sub2api/internal/pkg/errors"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/logger"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/pagination"
+)
+
+var (
+	ErrTeamNotFound       = infraerrors.NotFound("TEAM_NOT_FOUND", "team not found")
+	ErrTeamInvalid        = infraerrors.BadRequest("TEAM_INVALID", "name is required (max 100 characters) and period must be daily/weekly/monthly")
+	ErrTeamKeyNotOwned    = infraerrors.BadRequest("TEAM_KEY_NOT_OWNED", "api key must belong to the team owner")
+	ErrTeamKeyInOtherTeam = infraerrors.Conflict("TEAM_KEY_IN_OTHER_TEAM", "api key already belongs to another team")
+	ErrTeamMemberNotFound = infraerrors.NotFound("TEAM_MEMBER_NOT_FOUND", "api key is not a member of this team")
+	ErrTeamUsageRange     = infraerrors.BadRequest("TEAM_USAGE_RANGE_INVALID", "end must be after start and the range must not exceed 366 days")
+)
+
+const teamUsageMaxRange = 366 * 24 * time.Hour
+
+// Team 同一账户下共享预算的 API Key 团队；消费仍从团队所属账户余额扣除
+type Team struct {
+	ID     int64  `json:"id"`
+	UserID int64  `json:"user_id"`
+	Name   string `json:"name"`
+	// Period 团队预算与成员子额度的统计周期
+	Period    string    `json:"period"`
+	CreatedAt time.Time `json:"created_at"`
+	UpdatedAt time.Time `json:"updated_at"`
+}
+
+// TeamMember 团队成员 API Key
+type TeamMember struct {
+	APIKeyID   int64     `json:"api_key_id"`
+	APIKeyName string    `json:"api_key_name"`
+	MemberName string    `json:"member_name"`
+	JoinedAt   time.Time `json:"joined_at"`
+	// Limit 成员子额度及本周期消费，未设置时为空
+	Limit *SpendingCapStatus `json:"limit,omitempty"`
+}
+
+// TeamDetail 团队详情：成员、团队预算及本周期消费
+type TeamDetail struct {
+	Team
+	Budget  *SpendingCapStatus `json:"budget,omitempty"`
+	Members []TeamMember       `json:"members"`
+}
+
+// TeamUsageLine 按成员归属的用量
+type TeamUsageLine struct {
+	APIKeyID     int64   `json:"api_key_id"`
+	APIKeyName   string  `json:"api_key_name"`
+	MemberName   string  `json:"member_name"`
+	Requests     int64   `json:"requests"`
+	InputTokens  int64   `json:"input_tokens"`
+	OutputTokens int64   `json:"output_tokens"`
+	ActualCost   float64 `json:"actual_cost"`
+}
+
+// TeamUsageReport 团队用量报表
+type TeamUsageReport struct {
+	TeamID        int64           `json:"team_id"`
+	Start         time.Time       `json:"start"`
+	End           time.Time       `json:"end"`
+	Members       []TeamUsageLine `json:"members"`
+	TotalRequests int64           `json:"total_requests"`
+	TotalCost     float64         `json:"total_cost"`
+}
+
+// TeamRepository 团队与成员存储
+type TeamRepository interface {
+	Create(ctx context.Context, team *Team) error
+	GetByID(ctx context.Context, id int64) (*Team, error)
+	// List userID 为 0 时列出所有团队
+	List(ctx context.Context, userID int64, params pagination.PaginationParams) ([]Team, *pagination.PaginationResult, error)
+	Update(ctx context.Context, team *Team) error
+	Delete(ctx context.Context, id int64) error
+	ListMembers(ctx context.Context, teamID int64) ([]TeamMember, error)
+	// UpsertMember 加入团队或修改成员名称；Key 已属于其他团队时返回 ErrTeamKeyInOtherTeam
+	UpsertMember(ctx context.Context, teamID, apiKeyID int64, memberName string) error
+	RemoveMember(ctx context.Context, teamID, apiKeyID int64) error
+	// UsageByMember 当前成员在 [start, end) 内的用量，成员无用量时也返回
+	UsageByMember(ctx context.Context, teamID int64, start, end time.Time) ([]TeamUsageLine, error)
+}
+
+// UpsertTeamMemberInput 成员参数；LimitUSD 为 nil 时不修改子额度，为 0 时移除子额度
+type UpsertTeamMemberInput struct {
+	MemberName string
+	LimitUSD   *float64
+}
+
+// TeamService 团队 API Key：多个 Key 共享团队预算，可为每个成员设置子额度并按成员统计用量。
+// 预算与子额度以 spending_caps（team / team_member）实现，复用消费上限的暂停与通知流程。
+type TeamService struct {
+	repo         TeamRepository
+	apiKeyRepo   APIKeyRepository
+	spendingCaps *SpendingCapService
+}
+
+// NewTeamService 创建团队服务
+func NewTeamService(repo TeamRepository, apiKeyRepo APIKeyRepository, spendingCaps *SpendingCapService) *TeamService {
+	return &TeamService{repo: repo, apiKeyRepo: apiKeyRepo, spendingCaps: spendingCaps}
+}
+
+// Create 创建团队
+func (s *TeamService) Create(ctx context.Context, userID int64, name, period string) (*Team, error) {
+	team := &Team{UserID: userID, Name: strings.TrimSpace(name), Period: period}
+	if team.Period == "" {
+		team.Period = SpendingCapPeriodMonthly
+	}
+	if err := validateTeam(team); err != nil {
+		return nil, err
+	}
+	if err := s.repo.Create(ctx, team); err != nil {
+		return nil, fmt.Errorf("create team: %w", err)
+	}
+	return team, nil
+}
+
+// List 列出团队；userID 为 0 时列出所有团队（管理员）
+func (s *TeamService) List(ctx context.Context, userID int64, params pagination.PaginationParams) ([]Team, *pagination.PaginationResult, error) {
+	return s.repo.List(ctx, userID, params)
+}
+
+// Get 返回团队详情；ownerID 非 0 时校验团队归属
+func (s *TeamService) Get(ctx context.Context, ownerID, teamID int64) (*TeamDetail, error) {
+	team, err := s.get(ctx, ownerID, teamID)
+	if err != nil {
+		return nil, err
+	}
+	members, err := s.repo.ListMembers(ctx, team.ID)
+	if err != nil {
+		return nil, fmt.Errorf("list team members: %w", err)
+	}
+	detail := &TeamDetail{Team: *team, Members: members}
+	detail.Budget, err = s.capStatus(ctx, SpendingCapScopeTeam, team.ID)
+	if err != nil {
+		return nil, err
+	}
+	for i := range detail.Members {
+		detail.Members[i].Limit, err = s.capStatus(ctx, SpendingCapScopeTeamMember, detail.Members[i].APIKeyID)
+		if err != nil {
+			return nil, err
+		}
+	}
+	return detail, nil
+}
+
+// Update 修改团队名称或统计周期；周期变化时团队预算与成员子额度一并切换
+func (s *TeamService) Update(ctx context.Context, ownerID, teamID int64, name *string, period *string) (*Team, error) {
+	team, err := s.get(ctx, ownerID, teamID)
+	if err != nil {
+		return nil, err
+	}
+	oldPeriod := team.Period
+	if name != nil {
+		team.Name = strings.TrimSpace(*name)
+	}
+	if period != nil {
+		team.Period = *period
+	}
+	if err := validateTeam(team); err != nil {
+		return nil, err
+	}
+	if err := s.repo.Update(ctx, team); err != nil {
+		return nil, fmt.Errorf("update team: %w", err)
+	}
+	if team.Period != oldPeriod {
+		if err := s.applyTeamPeriod(ctx, team, ownerID); err != nil {
+			return nil, err
+		}
+	}
+	return team, nil
+}
+
+// Delete 删除团队及其预算与成员子额度，成员 Key 本身保留
+func (s *TeamService) Delete(ctx context.Context, ownerID, teamID int64) error {
+	team, err := s.get(ctx, ownerID, teamID)
+	if err != nil {
+		return err
+	}
+	members, err := s.repo.ListMembers(ctx, team.ID)
+	if err != nil {
+		return fmt.Errorf("list team members: %w", err)
+	}
+	if err := s.repo.Delete(ctx, team.ID); err != nil {
+		return fmt.Errorf("delete team: %w", err)
+	}
+	for _, m := range members {
+		s.deleteCap(ctx, SpendingCapScopeTeamMember, m.APIKeyID)
+		s.spendingCaps.InvalidateTeamMembership(m.APIKeyID)
+	}
+	s.deleteCap(ctx, SpendingCapScopeTeam, team.ID)
+	return nil
+}
+
+// SetBudget 设置团队共享预算；limitUSD 为 0 时移除预算
+func (s *TeamService) SetBudget(ctx context.Context, ownerID, teamID int64, limitUSD float64, operatorID int64) (*SpendingCapStatus, error) {
+	team, err := s.get(ctx, ownerID, teamID)
+	if err != nil {
+		return nil, err
+	}
+	if limitUSD == 0 {
+		s.deleteCap(ctx, SpendingCapScopeTeam, team.ID)
+		return nil, nil
+	}
+	return s.spendingCaps.Upsert(ctx, &SpendingCap{
+		ScopeType: SpendingCapScopeTeam,
+		ScopeID:   team.ID,
+		Period:    team.Period,
+		LimitUSD:  limitUSD,
+		UpdatedBy: &operatorID,
+	})
+}
+
+// UpsertMember 将账户下的 API Key 加入团队（或修改成员名称、子额度）
+func (s *TeamService) UpsertMember(ctx context.Context, ownerID, teamID, apiKeyID int64, input UpsertTeamMemberInput, operatorID int64) (*TeamMember, error) {
+	team, err := s.get(ctx, ownerID, teamID)
+	if err != nil {
+		return nil, err
+	}
+	apiKey, err := s.apiKeyRepo.GetByID(ctx, apiKeyID)
+	if err != nil {
+		return nil, err
+	}
+	if apiKey.UserID != team.UserID {
+		return nil, ErrTeamKeyNotOwned
+	}
+	memberName := strings.TrimSpace(input.MemberName)
+	if len([]rune(memberName)) > 100 {
+		return nil, infraerrors.BadRequest("TEAM_MEMBER_NAME_TOO_LONG", "member_name must not exceed 100 characters")
+	}
+	if err := s.repo.UpsertMember(ctx, team.ID, apiKey.ID, memberName); err != nil {
+		return nil, err
+	}
+	s.spendingCaps.InvalidateTeamMembership(apiKey.ID)
+
+	member := &TeamMember{APIKeyID: apiKey.ID, APIKeyName: apiKey.Name, MemberName: memberName, JoinedAt: time.Now()}
+	if input.LimitUSD != nil {
+		if *input.LimitUSD == 0 {
+			s.deleteCap(ctx, SpendingCapScopeTeamMember, apiKey.ID)
+		} else {
+			member.Limit, err = s.spendingCaps.Upsert(ctx, &SpendingCap{
+				ScopeType: SpendingCapScopeTeamMember,
+				ScopeID:   apiKey.ID,
+				Period:    team.Period,
+				LimitUSD:  *input.LimitUSD,
+				UpdatedBy: &operatorID,
+			})
+			if err != nil {
+				return nil, err
+			}
+		}
+	}
+	logger.LegacyPrintf("service.team", "AUDIT team member updated: team=%d api_key=%d by=%d", team.ID, apiKey.ID, operatorID)
+	return member, nil
+}
+
+// RemoveMember 将 API Key 移出团队并删除其子额度
+func (s *TeamService) RemoveMember(ctx context.Context, ownerID, teamID, apiKeyID int64) error {
+	team, err := s.get(ctx, ownerID, teamID)
+	if err != nil {
+		return err
+	}
+	if err := s.repo.RemoveMember(ctx, team.ID, apiKeyID); err != nil {
+		return err
+	}
+	s.deleteCap(ctx, SpendingCapScopeTeamMember, apiKeyID)
+	s.spendingCaps.InvalidateTeamMembership(apiKeyID)
+	return nil
+}
+
+// Usage 按成员统计团队在 [start, end) 内的用量
+func (s *TeamService) Usage(ctx context.Context, ownerID, teamID int64, start, end time.Time) (*TeamUsageReport, error) {
+	if !end.After(start) || end.Sub(start) > teamUsageMaxRange {
+		return nil, ErrTeamUsageRange
+	}
+	team, err := s.get(ctx, ownerID, teamID)
+	if err != nil {
+		return nil, err
+	}
+	lines, err := s.repo.UsageByMember(ctx, team.ID, start, end)
+	if err != nil {
+		return nil, fmt.Errorf("team usage: %w", err)
+	}
+	report := &TeamUsageReport{TeamID: team.ID, Start: start, End: end, Members: lines}
+	for _, l := range lines {
+		report.TotalRequests += l.Requests
+		report.TotalCost += l.ActualCost
+	}
+	return report, nil
+}
+
+func (s *TeamService) get(ctx context.Context, ownerID, teamID int64) (*Team, error) {
+	team, err := s.repo.GetByID(ctx, teamID)
+	if err != nil {
+		return nil, err
+	}
+	// 不区分“不存在”与“无权访问”，避免泄露其他账户的团队
+	if ownerID != 0 && team.UserID != ownerID {
+		return nil, ErrTeamNotFound
+	}
+	return team, nil
+}
+
+func (s *TeamService) capStatus(ctx context.Context, scopeType string, scopeID int64) (*SpendingCapStatus, error) {
+	status, err := s.spendingCaps.Get(ctx, scopeType, scopeID)
+	if err != nil {
+		if infraerrors.IsNotFound(err) {
+			return nil, nil
+		}
+		return nil, err
+	}
+	return status, nil
+}
+
+// applyTeamPeriod 按团队新周期重写预算与成员子额度（同时清除旧周期的暂停状态）
+func (s *TeamService) applyTeamPeriod(ctx context.Context, team *Team, operatorID int64) error {
+	members, err := s.repo.ListMembers(ctx, team.ID)
+	if err != nil {
+		return fmt.Errorf("list team members: %w", err)
+	}
+	if err := s.setCapPeriod(ctx, SpendingCapScopeTeam, team.ID, team.Period, operatorID); err != nil {
+		return err
+	}
+	for _, m := range members {
+		if err := s.setCapPeriod(ctx, SpendingCapScopeTeamMember, m.APIKeyID, team.Period, operatorID); err != nil {
+			return err
+		}
+	}
+	return nil
+}
+
+func (s *TeamService) setCapPeriod(ctx context.Context, scopeType string, scopeID int64, period string, operatorID int64) error {
+	status, err := s.capStatus(ctx, scopeType, scopeID)
+	if err != nil || status == nil {
+		return err
+	}
+	c := status.SpendingCap
+	c.Period, c.UpdatedBy = period, &operatorID
+	_, err = s.spendingCaps.Upsert(ctx, &c)
+	return err
+}
+
+func (s *TeamService) deleteCap(ctx context.Context, scopeType string, scopeID int64) {
+	if err := s.spendingCaps.Delete(ctx, scopeType, scopeID); err != nil && !infraerrors.IsNotFound(err) {
+		logger.LegacyPrintf("service.team", "[Team] delete %s cap %d failed: %v", scopeType, scopeID, err)
+	}
+}
+
+func validateTeam(team *Team) error {
+	if team.Name == "" || len([]rune(team.Name)) > 100 {
+		return ErrTeamInvalid
+	}
+	switch team.Period {
+	case SpendingCapPeriodDaily, SpendingCapPeriodWeekly, SpendingCapPeriodMonthly:
+		return nil
+	default:
+		return ErrTeamInvalid
+	}
+}
@@ -0,0 +1,211 @@
+//go:build unit
+
+package service
+
+import (
+	"context"
+	"testing"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/pkg/pagination"
+	"github.com/stretchr/testify/require"
+)
+
+type teamRepoStub struct {
+	teams   map[int64]*Team
+	members map[int64]*TeamMember
+	teamOf  map[int64]int64 // api key -> team，与 spendingCapRepoStub.teams 共享
+	nextID  int64
+}
+
+func (r *teamRepoStub) Create(_ context.Context, team *Team) error {
+	r.nextID++
+	team.ID = r.nextID
+	cp := *team
+	r.teams[team.ID] = &cp
+	return nil
+}
+
+func (r *teamRepoStub) GetByID(_ context.Context, id int64) (*Team, error) {
+	team, ok := r.teams[id]
+	if !ok {
+		return nil, ErrTeamNotFound
+	}
+	cp := *team
+	return &cp, nil
+}
+
+func (r *teamRepoStub) List(context.Context, int64, pagination.PaginationParams) ([]Team, *pagination.PaginationResult, error) {
+	panic("unexpected List call")
+}
+
+func (r *teamRepoStub) Update(_ context.Context, team *Team) error {
+	cp := *team
+	r.teams[team.ID] = &cp
+	return nil
+}
+
+func (r *teamRepoStub) Delete(_ context.Context, id int64) error {
+	delete(r.teams, id)
+	for keyID, teamID := range r.teamOf {
+		if teamID == id {
+			delete(r.teamOf, keyID)
+			delete(r.members, keyID)
+		}
+	}
+	return nil
+}
+
+func (r *teamRepoStub) ListMembers(_ context.Context, teamID int64) ([]TeamMember, error) {
+	out := make([]TeamMember, 0)
+	for keyID, id := range r.teamOf {
+		if id == teamID {
+			out = append(out, *r.members[keyID])
+		}
+	}
+	return out, nil
+}
+
+func (r *teamRepoStub) UpsertMember(_ context.Context, teamID, apiKeyID int64, memberName string) error {
+	if current, ok := r.teamOf[apiKeyID]; ok && current != teamID {
+		return ErrTeamKeyInOtherTeam
+	}
+	r.teamOf[apiKeyID] = teamID
+	r.members[apiKeyID] = &TeamMember{APIKeyID: apiKeyID, MemberName: memberName}
+	return nil
+}
+
+func (r *teamRepoStub) RemoveMember(_ context.Context, teamID, apiKeyID int64) error {
+	if r.teamOf[apiKeyID] != teamID {
+		return ErrTeamMemberNotFound
+	}
+	delete(r.teamOf, apiKeyID)
+	delete(r.members, apiKeyID)
+	return nil
+}
+
+func (r *teamRepoStub) UsageByMember(context.Context, int64, time.Time, time.Time) ([]TeamUsageLine, error) {
+	return []TeamUsageLine{
+		{APIKeyID: 11, Requests: 3, ActualCost: 1.5},
+		{APIKeyID: 12, Requests: 1, ActualCost: 0.25},
+	}, nil
+}
+
+func newTeamServiceForTest(now time.Time) (*TeamService, *SpendingCapService, *spendingCapRepoStub) {
+	caps, capRepo, _ := newSpendingCapServiceForTest(now)
+	repo := &teamRepoStub{teams: map[int64]*Team{}, members: map[int64]*TeamMember{}, teamOf: capRepo.teams}
+	apiKeys := &rotationAPIKeyRepoStub{keys: map[int64]*APIKey{
+		11: {ID: 11, UserID: 1, Name: "ci"},
+		12: {ID: 12, UserID: 1, Name: "alice"},
+		21: {ID: 21, UserID: 2, Name: "other"},
+	}}
+	return NewTeamService(repo, apiKeys, caps), caps, capRepo
+}
+
+func TestTeamService_MembersShareBudget(t *testing.T) {
+	now := time.Date(2026, 5, 20, 10, 0, 0, 0, time.UTC)
+	svc, caps, capRepo := newTeamServiceForTest(now)
+	ctx := context.Background()
+
+	_, err := svc.Create(ctx, 1, "  ", "")
+	require.ErrorIs(t, err, ErrTeamInvalid)
+	team, err := svc.Create(ctx, 1, "Platform", "")
+	require.NoError(t, err)
+	require.Equal(t, SpendingCapPeriodMonthly, team.Period)
+
+	// 只能加入团队所属账户的 Key
+	_, err = svc.UpsertMember(ctx, 1, team.ID, 21, UpsertTeamMemberInput{}, 1)
+	require.ErrorIs(t, err, ErrTeamKeyNotOwned)
+	// 其他账户看不到该团队
+	_, err = svc.UpsertMember(ctx, 2, team.ID, 21, UpsertTeamMemberInput{}, 2)
+	require.ErrorIs(t, err, ErrTeamNotFound)
+
+	limit := 2.0
+	_, err = svc.UpsertMember(ctx, 1, team.ID, 11, UpsertTeamMemberInput{MemberName: "CI"}, 1)
+	require.NoError(t, err)
+	member, err := svc.UpsertMember(ctx, 1, team.ID, 12, UpsertTeamMemberInput{MemberName: "Alice", LimitUSD: &limit}, 1)
+	require.NoError(t, err)
+	require.Equal(t, 2.0, member.Limit.LimitUSD)
+	require.Equal(t, SpendingCapPeriodMonthly, member.Limit.Period)
+
+	_, err = svc.SetBudget(ctx, 1, team.ID, 5, 1)
+	require.NoError(t, err)
+
+	// 成员子额度先于团队预算触发
+	alice := &APIKey{ID: 12, UserID: 1}
+	ci := &APIKey{ID: 11, UserID: 1}
+	capRepo.ledger = append(capRepo.ledger, spendingCapLedgerEntry{apiKeyID: 12, userID: 1, cost: 2, at: now})
+	caps.RecordSpend(ctx, alice, 2)
+	require.ErrorIs(t, caps.Check(ctx, alice), ErrSpendingCapExceeded)
+	require.NoError(t, caps.Check(ctx, ci))
+
+	// 团队预算由全部成员共享
+	capRepo.ledger = append(capRepo.ledger, spendingCapLedgerEntry{apiKeyID: 11, userID: 1, cost: 3, at: now})
+	caps.RecordSpend(ctx, ci, 3)
+	require.ErrorIs(t, caps.Check(ctx, ci), ErrSpendingCapExceeded)
+
+	detail, err := svc.Get(ctx, 1, team.ID)
+	require.NoError(t, err)
+	require.Len(t, detail.Members, 2)
+	require.Equal(t, 5.0, detail.Budget.SpentUSD)
+	require.True(t, detail.Budget.Suspended)
+
+	// 移出团队后不再受团队预算和子额度约束
+	require.NoError(t, svc.RemoveMember(ctx, 1, team.ID, 12))
+	require.NoError(t, caps.Check(ctx, alice))
+	require.ErrorIs(t, svc.RemoveMember(ctx, 1, team.ID, 12), ErrTeamMemberNotFound)
+}
+
+func TestTeamService_MemberCannotJoinTwoTeams(t *testing.T) {
+	svc, _, _ := newTeamServiceForTest(time.Now())
+	ctx := context.Background()
+
+	first, err := svc.Create(ctx, 1, "First", SpendingCapPeriodDaily)
+	require.NoError(t, err)
+	second, err := svc.Create(ctx, 1, "Second", SpendingCapPeriodDaily)
+	require.NoError(t, err)
+
+	_, err = svc.UpsertMember(ctx, 1, first.ID, 11, UpsertTeamMemberInput{}, 1)
+	require.NoError(t, err)
+	_, err = svc.UpsertMember(ctx, 1, second.ID, 11, UpsertTeamMemberInput{}, 1)
+	require.ErrorIs(t, err, ErrTeamKeyInOtherTeam)
+}
+
+func TestTeamService_PeriodChangeAndDelete(t *testing.T) {
+	svc, _, capRepo := newTeamServiceForTest(time.Now())
+	ctx := context.Background()
+
+	team, err := svc.Create(ctx, 1, "Platform", SpendingCapPeriodMonthly)
+	require.NoError(t, err)
+	limit := 1.0
+	_, err = svc.UpsertMember(ctx, 1, team.ID, 11, UpsertTeamMemberInput{LimitUSD: &limit}, 1)
+	require.NoError(t, err)
+	_, err = svc.SetBudget(ctx, 1, team.ID, 10, 1)
+	require.NoError(t, err)
+
+	weekly := SpendingCapPeriodWeekly
+	_, err = svc.Update(ctx, 1, team.ID, nil, &weekly)
+	require.NoError(t, err)
+	require.Equal(t, SpendingCapPeriodWeekly, capRepo.caps[spendingCapLocalKey(SpendingCapScopeTeam, team.ID)].Period)
+	require.Equal(t, SpendingCapPeriodWeekly, capRepo.caps[spendingCapLocalKey(SpendingCapScopeTeamMember, 11)].Period)
+
+	require.NoError(t, svc.Delete(ctx, 1, team.ID))
+	require.Empty(t, capRepo.caps)
+	require.Empty(t, capRepo.teams)
+}
+
+func TestTeamService_Usage(t *testing.T) {
+	svc, _, _ := newTeamServiceForTest(time.Now())
+	ctx := context.Background()
+	team, err := svc.Create(ctx, 1, "Platform", "")
+	require.NoError(t, err)
+
+	start := time.Date(2026, 5, 1, 0, 0, 0, 0, time.UTC)
+	_, err = svc.Usage(ctx, 1, team.ID, start, start)
+	require.ErrorIs(t, err, ErrTeamUsageRange)
+
+	report, err := svc.Usage(ctx, 1, team.ID, start, start.AddDate(0, 1, 0))
+	require.NoError(t, err)
+	require.Equal(t, int64(4), report.TotalRequests)
+	require.InDelta(t, 1.75, report.TotalCost, 1e-9)
+}
@@ -0,0 +1,435 @@
+package service
+
+import (
+	"context"
+	"fmt"
+	"html"
+	"sort"
+	"strconv"
+	"strings"
+	"sync"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	infraerrors "github.com/Wei-Shaw/sub2api/internal/pkg/errors"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/logger"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/pagination"
+	"github.com/google/uuid"
+)
+
+// telegramPollerLock 跨实例的长轮询锁：同一 Bot 同时只能有一个 getUpdates 请求，否则 Telegram 返回 409
+const telegramPollerLock = "telegram_poller"
+
+// TelegramUpdate 收到的消息（仅保留命令处理需要的字段）
+type TelegramUpdate struct {
+	UpdateID int64
+	ChatID   int64
+	From     string
+	Text     string
+}
+
+// TelegramClient Telegram Bot API 客户端
+type TelegramClient interface {
+	// GetUpdates 长轮询拉取 offset 之后的消息，同时确认 offset 之前的消息
+	GetUpdates(ctx context.Context, offset int64, timeout time.Duration) ([]TelegramUpdate, error)
+	// SendMessage 发送 HTML 格式的消息
+	SendMessage(ctx context.Context, chatID int64, text string) error
+}
+
+// TelegramStateCache 多实例共享的机器人状态
+type TelegramStateCache interface {
+	// MarkAlerted 记录告警已推送；冷却期内已推送过时返回 false
+	MarkAlerted(ctx context.Context, key string, cooldown time.Duration) (bool, error)
+	GetUpdateOffset(ctx context.Context) (int64, error)
+	SetUpdateOffset(ctx context.Context, offset int64) error
+}
+
+// platformAccountHealth 单个平台的账号可用情况（不含手动暂停调度的账号）
+type platformAccountHealth struct {
+	Platform       string
+	Total          int
+	Available      int
+	RateLimited    int
+	Overloaded     int
+	Errored        int
+	QuotaExhausted int
+}
+
+// TelegramService Telegram 运维机器人：推送严重告警，并处理白名单会话中的命令。
+// 告警由定时任务检查，冷却状态与消息 offset 存放在 Redis 中，多实例部署时不会重复推送或重复执行命令。
+type TelegramService struct {
+	cfg          config.TelegramConfig
+	client       TelegramClient
+	cache        TelegramStateCache
+	locker       CronJobLocker
+	accountRepo  AccountRepository
+	adminService AdminService
+	dashboard    *DashboardService
+	quotaTracker *AccountQuotaBudgetTracker
+	allowed      map[int64]bool
+	instanceID   string
+
+	stopOnce sync.Once
+	stopCh   chan struct{}
+	wg       sync.WaitGroup
+}
+
+// NewTelegramService 创建 Telegram 机器人服务
+func NewTelegramService(
+	client TelegramClient,
+	cache TelegramStateCache,
+	locker CronJobLocker,
+	accountRepo AccountRepository,
+	adminService AdminService,
+	dashboard *DashboardService,
+	quotaTracker *AccountQuotaBudgetTracker,
+	cfg *config.Config,
+) *TelegramService {
+	tg := cfg.Notifications.Telegram
+	allowed := make(map[int64]bool, len(tg.ChatIDs))
+	for _, id := range tg.ChatIDs {
+		allowed[id] = true
+	}
+	return &TelegramService{
+		cfg:          tg,
+		client:       client,
+		cache:        cache,
+		locker:       locker,
+		accountRepo:  accountRepo,
+		adminService: adminService,
+		dashboard:    dashboard,
+		quotaTracker: quotaTracker,
+		allowed:      allowed,
+		instanceID:   uuid.NewString(),
+		stopCh:       make(chan struct{}),
+	}
+}
+
+// Enabled 是否已配置机器人
+func (s *TelegramService) Enabled() bool {
+	return s != nil && s.cfg.Enabled && s.cfg.BotToken != "" && len(s.allowed) > 0
+}
+
+// Start 启动命令轮询
+func (s *TelegramService) Start() {
+	if !s.Enabled() {
+		return
+	}
+	s.wg.Add(1)
+	go s.pollLoop()
+	logger.LegacyPrintf("service.telegram", "[Telegram] command polling started for %d chat(s)", len(s.allowed))
+}
+
+// Stop 停止命令轮询
+func (s *TelegramService) Stop() {
+	if s == nil {
+		return
+	}
+	s.stopOnce.Do(func() { close(s.stopCh) })
+	s.wg.Wait()
+}
+
+func (s *TelegramService) pollLoop() {
+	defer s.wg.Done()
+	timeout := time.Duration(s.cfg.PollTimeoutSeconds) * time.Second
+	for {
+		wait, err := s.pollOnce(timeout)
+		select {
+		case <-s.stopCh:
+			return
+		default:
+		}
+		if err != nil {
+			logger.LegacyPrintf("service.telegram", "[Telegram] poll failed: %v", err)
+		}
+		select {
+		case <-s.stopCh:
+			return
+		case <-time.After(wait):
+		}
+	}
+}
+
+// pollOnce 持锁执行一次 getUpdates 并处理命令，返回下一次轮询前的等待时间
+func (s *TelegramService) pollOnce(timeout time.Duration) (time.Duration, error) {
+	ctx, cancel := context.WithCancel(context.Background())
+	defer cancel()
+	go func() {
+		select {
+		case <-s.stopCh:
+			cancel()
+		case <-ctx.Done():
+		}
+	}()
+
+	ok, err := s.locker.TryLock(ctx, telegramPollerLock, s.instanceID, timeout+time.Minute)
+	if err != nil {
+		return 5 * time.Second, fmt.Errorf("acquire poller lock: %w", err)
+	}
+	if !ok {
+		// 其他实例正在轮询
+		return timeout, nil
+	}
+	defer func() {
+		unlockCtx, unlockCancel := context.WithTimeout(context.Background(), 3*time.Second)
+		defer unlockCancel()
+		_ = s.locker.Unlock(unlockCtx, telegramPollerLock, s.instanceID)
+	}()
+
+	offset, err := s.cache.GetUpdateOffset(ctx)
+	if err != nil {
+		return 5 * time.Second, fmt.Errorf("load update offset: %w", err)
+	}
+	updates, err := s.client.GetUpdates(ctx, offset, timeout)
+	if err != nil {
+		return 5 * time.Second, err
+	}
+	for _, u := range updates {
+		// 先推进 offset 再执行命令：宁可漏执行也不重复执行（如重复停用账号）
+		if err := s.cache.SetUpdateOffset(ctx, u.UpdateID+1); err != nil {
+			return 5 * time.Second, fmt.Errorf("save update offset: %w", err)
+		}
+		s.handleUpdate(ctx, u)
+	}
+	return 0, nil
+}
+
+func (s *TelegramService) handleUpdate(ctx context.Context, u TelegramUpdate) {
+	if !s.allowed[u.ChatID] {
+		logger.LegacyPrintf("service.telegram", "[Telegram] ignored message from chat %d (not in chat_ids)", u.ChatID)
+		return
+	}
+	reply := s.HandleCommand(ctx, u.ChatID, u.From, u.Text)
+	if reply == "" {
+		return
+	}
+	if err := s.client.SendMessage(ctx, u.ChatID, reply); err != nil {
+		logger.LegacyPrintf("service.telegram", "[Telegram] reply to chat %d failed: %v", u.ChatID, err)
+	}
+}
+
+// HandleCommand 执行白名单会话中的命令并返回回复（HTML）；非命令消息返回空字符串
+func (s *TelegramService) HandleCommand(ctx context.Context, chatID int64, from, text string) string {
+	fields := strings.Fields(text)
+	if len(fields) == 0 || !strings.HasPrefix(fields[0], "/") {
+		return ""
+	}
+	// 群组中的命令形如 /status@my_bot
+	command, _, _ := strings.Cut(strings.ToLower(fields[0]), "@")
+	args := fields[1:]
+
+	switch command {
+	case "/status":
+		return s.statusReply(ctx)
+	case "/usage":
+		return s.usageReply(ctx)
+	case "/disable", "/enable":
+		if len(args) != 1 {
+			return fmt.Sprintf("Usage: %s &lt;account id&gt;", command)
+		}
+		id, err := strconv.ParseInt(strings.TrimPrefix(args[0], "#"), 10, 64)
+		if err != nil || id <= 0 {
+			return "Invalid account ID."
+		}
+		return s.setSchedulableReply(ctx, chatID, from, id, command == "/enable")
+	case "/start", "/help":
+		return strings.Join([]string{
+			"/status - account availability by platform",
+			"/usage - today's usage",
+			"/disable &lt;id&gt; - stop scheduling an account",
+			"/enable &lt;id&gt; - resume scheduling an account",
+		}, "\n")
+	default:
+		return "Unknown command. Send /help for the command list."
+	}
+}
+
+func (s *TelegramService) statusReply(ctx context.Context) string {
+	health, _, err := s.collectAccountHealth(ctx, false)
+	if err != nil {
+		return "Failed to load accounts: " + html.EscapeString(infraerrors.Message(err))
+	}
+	if len(health) == 0 {
+		return "No accounts configured."
+	}
+	lines := []string{"<b>Account status</b>"}
+	for _, h := range health {
+		line := fmt.Sprintf("%s: %d/%d available", html.EscapeString(h.Platform), h.Available, h.Total)
+		var details []string
+		for _, d := range []struct {
+			n     int
+			label string
+		}{
+			{h.RateLimited, "rate limited"},
+			{h.Overloaded, "overloaded"},
+			{h.Errored, "error"},
+			{h.QuotaExhausted, "quota exhausted"},
+		} {
+			if d.n > 0 {
+				details = append(details, fmt.Sprintf("%d %s", d.n, d.label))
+			}
+		}
+		if len(details) > 0 {
+			line += " (" + strings.Join(details, ", ") + ")"
+		}
+		if h.Available == 0 {
+			line = "⚠️ " + line
+		}
+		lines = append(lines, line)
+	}
+	return strings.Join(lines, "\n")
+}
+
+func (s *TelegramService) usageReply(ctx context.Context) string {
+	stats, err := s.dashboard.GetDashboardStats(ctx)
+	if err != nil {
+		return "Failed to load usage: " + html.EscapeString(infraerrors.Message(err))
+	}
+	return fmt.Sprintf("<b>Usage today</b>\nRequests: %d\nTokens: %d\nCharged: $%.2f (standard $%.2f)\nActive users: %d\nRPM / TPM: %d / %d",
+		stats.TodayRequests, stats.TodayTokens, stats.TodayActualCost, stats.TodayCost, stats.ActiveUsers, stats.Rpm, stats.Tpm)
+}
+
+func (s *TelegramService) setSchedulableReply(ctx context.Context, chatID int64, from string, id int64, schedulable bool) string {
+	account, err := s.adminService.SetAccountSchedulable(ctx, id, schedulable)
+	if err != nil {
+		return fmt.Sprintf("Failed to update account %d: %s", id, html.EscapeString(infraerrors.Message(err)))
+	}
+	logger.LegacyPrintf("service.telegram", "[Telegram] chat %d (%s) set account %d schedulable=%v", chatID, from, id, schedulable)
+	if schedulable {
+		return fmt.Sprintf("Account #%d <b>%s</b> is schedulable again.", id, html.EscapeString(account.Name))
+	}
+	return fmt.Sprintf("Account #%d <b>%s</b> will no longer be scheduled. Send /enable %d to resume.", id, html.EscapeString(account.Name), id)
+}
+
+// CheckAlerts 检查严重告警并推送（定时任务入口）：某平台没有可调度账号、账号配额耗尽
+func (s *TelegramService) CheckAlerts(ctx context.Context) error {
+	if !s.Enabled() {
+		return nil
+	}
+	health, exhausted, err := s.collectAccountHealth(ctx, true)
+	if err != nil {
+		return fmt.Errorf("collect account health: %w", err)
+	}
+	for _, h := range health {
+		if h.Available > 0 {
+			continue
+		}
+		text := fmt.Sprintf("🚨 <b>All %s accounts are unavailable</b>\n%d account(s): %d rate limited, %d overloaded, %d error, %d quota exhausted.",
+			html.EscapeString(h.Platform), h.Total, h.RateLimited, h.Overloaded, h.Errored, h.QuotaExhausted)
+		s.alert(ctx, "unavailable:"+h.Platform, text)
+	}
+
+	var fresh []string
+	for _, a := range exhausted {
+		// 每个账号单独冷却，新耗尽的账号合并为一条消息
+		if s.markAlerted(ctx, "quota_exhausted:"+strconv.FormatInt(a.ID, 10)) {
+			fresh = append(fresh, fmt.Sprintf("#%d %s (%s)", a.ID, html.EscapeString(a.Name), html.EscapeString(a.Platform)))
+		}
+	}
+	if len(fresh) > 0 {
+		s.broadcast(ctx, "⚠️ <b>Upstream quota exhausted</b>\n"+strings.Join(fresh, "\n"))
+	}
+	return nil
+}
+
+func (s *TelegramService) alert(ctx context.Context, key, text string) {
+	if s.markAlerted(ctx, key) {
+		s.broadcast(ctx, text)
+	}
+}
+
+func (s *TelegramService) markAlerted(ctx context.Context, key string) bool {
+	ok, err := s.cache.MarkAlerted(ctx, key, time.Duration(s.cfg.AlertCooldownMinutes)*time.Minute)
+	if err != nil {
+		// Redis 不可用时仍推送，宁可重复也不漏报
+		logger.LegacyPrintf("service.telegram", "[Telegram] alert cooldown check failed: %v", err)
+		return true
+	}
+	return ok
+}
+
+func (s *TelegramService) broadcast(ctx context.Context, text string) {
+	for chatID := range s.allowed {
+		if err := s.client.SendMessage(ctx, chatID, text); err != nil {
+			logger.LegacyPrintf("service.telegram", "[Telegram] send alert to chat %d failed: %v", chatID, err)
+		}
+	}
+}
+
+// collectAccountHealth 按平台统计账号可用情况；withExhausted 时同时返回配额耗尽的账号
+func (s *TelegramService) collectAccountHealth(ctx context.Context, withExhausted bool) ([]*platformAccountHealth, []Account, error) {
+	active, err := s.accountRepo.ListActive(ctx)
+	if err != nil {
+		return nil, nil, err
+	}
+	errored, err := s.listErroredAccounts(ctx)
+	if err != nil {
+		return nil, nil, err
+	}
+
+	byPlatform := make(map[string]*platformAccountHealth)
+	get := func(platform string) *platformAccountHealth {
+		h := byPlatform[platform]
+		if h == nil {
+			h = &platformAccountHealth{Platform: platform}
+			byPlatform[platform] = h
+		}
+		return h
+	}
+	var exhausted []Account
+	for i := range active {
+		a := &active[i]
+		if !a.Schedulable {
+			// 手动暂停的账号不计入
+			continue
+		}
+		h := get(a.Platform)
+		h.Total++
+		switch {
+		case a.IsRateLimited():
+			h.RateLimited++
+		case a.IsOverloaded():
+			h.Overloaded++
+		case !a.IsSchedulable():
+			// 临时不可调度或已过期自动暂停
+		case s.quotaTracker.Level(ctx, a) == AccountQuotaExhausted:
+			h.QuotaExhausted++
+			if withExhausted {
+				exhausted = append(exhausted, *a)
+			}
+		default:
+			h.Available++
+		}
+	}
+	for i := range errored {
+		if !errored[i].Schedulable {
+			continue
+		}
+		h := get(errored[i].Platform)
+		h.Total++
+		h.Errored++
+	}
+
+	out := make([]*platformAccountHealth, 0, len(byPlatform))
+	for _, h := range byPlatform {
+		out = append(out, h)
+	}
+	sort.Slice(out, func(i, j int) bool { return out[i].Platform < out[j].Platform })
+	return out, exhausted, nil
+}
+
+func (s *TelegramService) listErroredAccounts(ctx context.Context) ([]Account, error) {
+	var out []Account
+	params := pagination.PaginationParams{Page: 1, PageSize: 100}
+	for {
+		accounts, result, err := s.accountRepo.ListWithFilters(ctx, params, "", "", StatusError, "", 0)
+		if err != nil {
+			return nil, err
+		}
+		out = append(out, accounts...)
+		if result == nil || params.Page >= result.Pages {
+			return out, nil
+		}
+		params.Page++
+	}
+}
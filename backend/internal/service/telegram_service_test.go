@@ -0,0 +1,176 @@
+//go:build unit
+
+package service
+
+import (
+	"context"
+	"testing"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/pagination"
+	"github.com/stretchr/testify/require"
+)
+
+type telegramSentMessage struct {
+	chatID int64
+	text   string
+}
+
+type telegramClientStub struct {
+	updates []TelegramUpdate
+	offsets []int64
+	sent    []telegramSentMessage
+}
+
+func (c *telegramClientStub) GetUpdates(_ context.Context, offset int64, _ time.Duration) ([]TelegramUpdate, error) {
+	c.offsets = append(c.offsets, offset)
+	updates := c.updates
+	c.updates = nil
+	return updates, nil
+}
+
+func (c *telegramClientStub) SendMessage(_ context.Context, chatID int64, text string) error {
+	c.sent = append(c.sent, telegramSentMessage{chatID: chatID, text: text})
+	return nil
+}
+
+type telegramStateCacheStub struct {
+	alerted map[string]bool
+	offset  int64
+}
+
+func (c *telegramStateCacheStub) MarkAlerted(_ context.Context, key string, _ time.Duration) (bool, error) {
+	if c.alerted[key] {
+		return false, nil
+	}
+	c.alerted[key] = true
+	return true, nil
+}
+
+func (c *telegramStateCacheStub) GetUpdateOffset(context.Context) (int64, error) {
+	return c.offset, nil
+}
+
+func (c *telegramStateCacheStub) SetUpdateOffset(_ context.Context, offset int64) error {
+	c.offset = offset
+	return nil
+}
+
+type telegramAccountRepoStub struct {
+	AccountRepository
+	active  []Account
+	errored []Account
+}
+
+func (r *telegramAccountRepoStub) ListActive(context.Context) ([]Account, error) {
+	return r.active, nil
+}
+
+func (r *telegramAccountRepoStub) ListWithFilters(_ context.Context, params pagination.PaginationParams, _, _, status, _ string, _ int64) ([]Account, *pagination.PaginationResult, error) {
+	if status != StatusError {
+		panic("unexpected status filter " + status)
+	}
+	return r.errored, &pagination.PaginationResult{Total: int64(len(r.errored)), Page: params.Page, PageSize: params.PageSize, Pages: 1}, nil
+}
+
+type telegramAdminServiceStub struct {
+	AdminService
+	accounts map[int64]*Account
+}
+
+func (s *telegramAdminServiceStub) SetAccountSchedulable(_ context.Context, id int64, schedulable bool) (*Account, error) {
+	account, ok := s.accounts[id]
+	if !ok {
+		return nil, ErrAccountNotFound
+	}
+	account.Schedulable = schedulable
+	return account, nil
+}
+
+func newTelegramServiceForTest(repo *telegramAccountRepoStub, admin *telegramAdminServiceStub) (*TelegramService, *telegramClientStub, *telegramStateCacheStub) {
+	client := &telegramClientStub{}
+	cache := &telegramStateCacheStub{alerted: map[string]bool{}}
+	cfg := &config.Config{}
+	cfg.Notifications.Telegram = config.TelegramConfig{
+		Enabled:              true,
+		BotToken:             "123:abc",
+		ChatIDs:              []int64{42},
+		AlertCooldownMinutes: 30,
+		PollTimeoutSeconds:   25,
+	}
+	svc := NewTelegramService(client, cache, &cronJobLockerStub{held: map[string]string{}}, repo, admin, nil, nil, cfg)
+	return svc, client, cache
+}
+
+func TestTelegramService_Commands(t *testing.T) {
+	limited := time.Now().Add(time.Hour)
+	repo := &telegramAccountRepoStub{
+		active: []Account{
+			{ID: 1, Name: "a1", Platform: PlatformAnthropic, Status: StatusActive, Schedulable: true},
+			{ID: 2, Name: "a2", Platform: PlatformAnthropic, Status: StatusActive, Schedulable: true, RateLimitResetAt: &limited},
+			{ID: 3, Name: "paused", Platform: PlatformOpenAI, Status: StatusActive, Schedulable: false},
+		},
+		errored: []Account{{ID: 4, Name: "o1", Platform: PlatformOpenAI, Status: StatusError, Schedulable: true}},
+	}
+	admin := &telegramAdminServiceStub{accounts: map[int64]*Account{1: {ID: 1, Name: "<a1>", Schedulable: true}}}
+	svc, _, _ := newTelegramServiceForTest(repo, admin)
+	ctx := context.Background()
+
+	status := svc.HandleCommand(ctx, 42, "ops", "/status@sub2api_bot")
+	require.Contains(t, status, "anthropic: 1/2 available (1 rate limited)")
+	require.Contains(t, status, "⚠️ openai: 0/1 available (1 error)")
+
+	reply := svc.HandleCommand(ctx, 42, "ops", "/disable 1")
+	require.Contains(t, reply, "&lt;a1&gt;")
+	require.False(t, admin.accounts[1].Schedulable)
+	require.Contains(t, svc.HandleCommand(ctx, 42, "ops", "/enable #1"), "schedulable again")
+	require.True(t, admin.accounts[1].Schedulable)
+
+	require.Equal(t, "Invalid account ID.", svc.HandleCommand(ctx, 42, "ops", "/disable abc"))
+	require.Contains(t, svc.HandleCommand(ctx, 42, "ops", "/disable"), "Usage: /disable")
+	require.Contains(t, svc.HandleCommand(ctx, 42, "ops", "/disable 99"), "account not found")
+	require.Contains(t, svc.HandleCommand(ctx, 42, "ops", "/reboot"), "Unknown command")
+	require.Empty(t, svc.HandleCommand(ctx, 42, "ops", "hello"))
+}
+
+func TestTelegramService_PollOnlyAnswersAllowlistedChats(t *testing.T) {
+	admin := &telegramAdminServiceStub{accounts: map[int64]*Account{7: {ID: 7, Name: "acc", Schedulable: true}}}
+	svc, client, cache := newTelegramServiceForTest(&telegramAccountRepoStub{}, admin)
+	cache.offset = 100
+	client.updates = []TelegramUpdate{
+		{UpdateID: 100, ChatID: 999, Text: "/disable 7"},
+		{UpdateID: 101, ChatID: 42, Text: "/help"},
+	}
+
+	wait, err := svc.pollOnce(time.Second)
+	require.NoError(t, err)
+	require.Zero(t, wait)
+	require.Equal(t, []int64{100}, client.offsets)
+	require.Equal(t, int64(102), cache.offset)
+	require.True(t, admin.accounts[7].Schedulable, "commands from unknown chats must be ignored")
+	require.Len(t, client.sent, 1)
+	require.Equal(t, int64(42), client.sent[0].chatID)
+	require.Contains(t, client.sent[0].text, "/status")
+}
+
+func TestTelegramService_CheckAlertsWithCooldown(t *testing.T) {
+	limited := time.Now().Add(time.Hour)
+	repo := &telegramAccountRepoStub{
+		active: []Account{
+			{ID: 1, Platform: PlatformGemini, Status: StatusActive, Schedulable: true, RateLimitResetAt: &limited},
+			{ID: 2, Platform: PlatformAnthropic, Status: StatusActive, Schedulable: true},
+		},
+	}
+	svc, client, cache := newTelegramServiceForTest(repo, &telegramAdminServiceStub{})
+	ctx := context.Background()
+
+	require.NoError(t, svc.CheckAlerts(ctx))
+	require.Len(t, client.sent, 1)
+	require.Contains(t, client.sent[0].text, "All gemini accounts are unavailable")
+	require.True(t, cache.alerted["unavailable:gemini"])
+
+	// 冷却期内不重复推送
+	require.NoError(t, svc.CheckAlerts(ctx))
+	require.Len(t, client.sent, 1)
+}
@@ -2,6 +2,7 @@ package service
 
 import (
 	"context"
+	"errors"
 	"fmt"
 	"log/slog"
 	"strings"
@@ -19,6 +20,7 @@ type TokenRefreshService struct {
 	cfg              *config.TokenRefreshConfig
 	cacheInvalidator TokenCacheInvalidator
 	schedulerCache   SchedulerCache // 用于同步更新调度器缓存，解决 token 刷新后缓存不一致问题
+	leader           *LeaderElector
 
 	stopCh chan struct{}
 	wg     sync.WaitGroup
@@ -31,6 +33,7 @@ func NewTokenRefreshService(
 	openaiOAuthService *OpenAIOAuthService,
 	geminiOAuthService *GeminiOAuthService,
 	antigravityOAuthService *AntigravityOAuthService,
+	copilotService *CopilotService,
 	cacheInvalidator TokenCacheInvalidator,
 	schedulerCache SchedulerCache,
 	cfg *config.Config,
@@ -52,11 +55,17 @@ func NewTokenRefreshService(
 		openAIRefresher,
 		NewGeminiTokenRefresher(geminiOAuthService),
 		NewAntigravityTokenRefresher(antigravityOAuthService),
+		NewCopilotTokenRefresher(copilotService),
 	}
 
 	return s
 }
 
+// SetLeaderElector 多实例部署时仅由 leader 执行；需要在 Start() 之前调用
+func (s *TokenRefreshService) SetLeaderElector(leader *LeaderElector) {
+	s.leader = leader
+}
+
 // SetSoraAccountRepo 设置 Sora 账号扩展表仓储
 // 用于在 OpenAI Token 刷新时同步更新 sora_accounts 表
 // 需要在 Start() 之前调用
@@ -69,6 +78,15 @@ func (s *TokenRefreshService) SetSoraAccountRepo(repo SoraAccountRepository) {
 	}
 }
 
+// SetSessionRefreshHookClient 注册外部会话刷新服务（无头浏览器等）
+// 排在内置刷新器之后，只处理内置刷新器不认领的账号；需要在 Start() 之前调用
+func (s *TokenRefreshService) SetSessionRefreshHookClient(client SessionRefreshHookClient) {
+	if client == nil || !s.cfg.SessionHook.Enabled {
+		return
+	}
+	s.refreshers = append(s.refreshers, NewSessionRefreshHookRefresher(client, s.cfg.SessionHook))
+}
+
 // Start 启动后台刷新服务
 func (s *TokenRefreshService) Start() {
 	if !s.cfg.Enabled {
@@ -120,6 +138,9 @@ func (s *TokenRefreshService) refreshLoop() {
 
 // processRefresh 执行一次刷新检查
 func (s *TokenRefreshService) processRefresh() {
+	if !s.leader.IsLeader() {
+		return
+	}
 	ctx := context.Background()
 
 	// 计算刷新窗口
@@ -269,6 +290,22 @@ func (s *TokenRefreshService) refreshWithRetry(ctx context.Context, account *Acc
 			return err
 		}
 
+		// 外部刷新服务要求人工重新登录：重试无意义，直接标记 error 停止调度
+		if errors.Is(err, ErrAccountReloginRequired) {
+			if setErr := s.accountRepo.SetError(ctx, account.ID, err.Error()); setErr != nil {
+				slog.Error("token_refresh.set_error_status_failed",
+					"account_id", account.ID,
+					"error", setErr,
+				)
+			}
+			slog.Warn("token_refresh.relogin_required",
+				"account_id", account.ID,
+				"account_name", account.Name,
+				"error", err,
+			)
+			return err
+		}
+
 		lastErr = err
 		slog.Warn("token_refresh.retry_attempt_failed",
 			"account_id", account.ID,
@@ -0,0 +1,122 @@
+//go:build unit
+
+package service
+
+import (
+	"context"
+	"strings"
+	"testing"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/stretchr/testify/require"
+)
+
+type totpBackupCodeRepoStub struct {
+	codes map[string]bool // hash -> used
+}
+
+func (r *totpBackupCodeRepoStub) ReplaceCodes(ctx context.Context, userID int64, codeHashes []string) error {
+	r.codes = make(map[string]bool, len(codeHashes))
+	for _, h := range codeHashes {
+		r.codes[h] = false
+	}
+	return nil
+}
+
+func (r *totpBackupCodeRepoStub) ConsumeCode(ctx context.Context, userID int64, codeHash string) (bool, error) {
+	used, ok := r.codes[codeHash]
+	if !ok || used {
+		return false, nil
+	}
+	r.codes[codeHash] = true
+	return true, nil
+}
+
+func (r *totpBackupCodeRepoStub) CountRemaining(ctx context.Context, userID int64) (int, error) {
+	n := 0
+	for _, used := range r.codes {
+		if !used {
+			n++
+		}
+	}
+	return n, nil
+}
+
+func (r *totpBackupCodeRepoStub) DeleteByUserID(ctx context.Context, userID int64) error {
+	r.codes = nil
+	return nil
+}
+
+type totpAttemptsCacheStub struct {
+	TotpCache
+	attempts int
+}
+
+func (c *totpAttemptsCacheStub) GetVerifyAttempts(ctx context.Context, userID int64) (int, error) {
+	return c.attempts, nil
+}
+
+func (c *totpAttemptsCacheStub) IncrementVerifyAttempts(ctx context.Context, userID int64) (int, error) {
+	c.attempts++
+	return c.attempts, nil
+}
+
+func (c *totpAttemptsCacheStub) ClearVerifyAttempts(ctx context.Context, userID int64) error {
+	c.attempts = 0
+	return nil
+}
+
+func (c *totpAttemptsCacheStub) SetSetupSession(ctx context.Context, userID int64, session *TotpSetupSession, ttl time.Duration) error {
+	return nil
+}
+
+func TestTotpService_BackupCodesAreSingleUse(t *testing.T) {
+	repo := &totpBackupCodeRepoStub{}
+	cache := &totpAttemptsCacheStub{}
+	svc := NewTotpService(nil, nil, cache, repo, nil, nil, nil)
+
+	codes, err := svc.replaceBackupCodes(context.Background(), 1)
+	require.NoError(t, err)
+	require.Len(t, codes, totpBackupCodeCount)
+	for _, code := range codes {
+		require.Len(t, code, 11)
+		require.Equal(t, "-", code[5:6])
+	}
+
+	// 大小写与连字符不敏感
+	input := strings.ToUpper(strings.ReplaceAll(codes[0], "-", " "))
+	require.NoError(t, svc.VerifyBackupCode(context.Background(), 1, input))
+	require.ErrorIs(t, svc.VerifyBackupCode(context.Background(), 1, codes[0]), ErrTotpInvalidBackup)
+	require.Equal(t, 1, cache.attempts)
+
+	remaining, err := repo.CountRemaining(context.Background(), 1)
+	require.NoError(t, err)
+	require.Equal(t, totpBackupCodeCount-1, remaining)
+}
+
+func TestTotpService_BackupCodeRateLimited(t *testing.T) {
+	repo := &totpBackupCodeRepoStub{}
+	cache := &totpAttemptsCacheStub{attempts: maxTotpAttempts}
+	svc := NewTotpService(nil, nil, cache, repo, nil, nil, nil)
+
+	codes, err := svc.replaceBackupCodes(context.Background(), 1)
+	require.NoError(t, err)
+	require.ErrorIs(t, svc.VerifyBackupCode(context.Background(), 1, codes[0]), ErrTotpTooManyAttempts)
+}
+
+func TestTotpService_RequiredForAdmins(t *testing.T) {
+	settingSvc := NewSettingService(&settingRepoStub{values: map[string]string{}}, &config.Config{
+		Totp: config.TotpConfig{RequireForAdmins: true},
+	})
+	svc := NewTotpService(nil, nil, nil, nil, settingSvc, nil, nil)
+
+	admin := &User{ID: 1, Role: RoleAdmin, TotpEnabled: true}
+	user := &User{ID: 2, Role: RoleUser, TotpEnabled: true}
+
+	// 全局 TOTP 开关关闭时，管理员仍需 2FA；普通用户不需要
+	require.True(t, svc.RequiresLogin2FA(context.Background(), admin))
+	require.False(t, svc.RequiresLogin2FA(context.Background(), user))
+	require.True(t, svc.isRequiredForUser(admin))
+	require.False(t, svc.isRequiredForUser(user))
+}
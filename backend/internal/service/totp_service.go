@@ -7,7 +7,9 @@ import (
 	"encoding/hex"
 	"fmt"
 	"log/slog"
+	"strings"
 	"time"
+	"unicode"
 
 	"github.com/pquerna/otp/totp"
 
@@ -23,8 +25,21 @@ var (
 	ErrTotpTooManyAttempts = infraerrors.TooManyRequests("TOTP_TOO_MANY_ATTEMPTS", "too many verification attempts, please try again later")
 	ErrVerifyCodeRequired  = infraerrors.BadRequest("VERIFY_CODE_REQUIRED", "email verification code is required")
 	ErrPasswordRequired    = infraerrors.BadRequest("PASSWORD_REQUIRED", "password is required")
+	ErrTotpInvalidBackup   = infraerrors.BadRequest("TOTP_INVALID_BACKUP_CODE", "invalid or already used backup code")
+	ErrTotpRequired        = infraerrors.Forbidden("TOTP_REQUIRED", "two-factor authentication is mandatory for admin accounts")
 )
 
+// TotpBackupCodeRepository 管理 TOTP 备用恢复码（仅存储哈希）
+type TotpBackupCodeRepository interface {
+	// ReplaceCodes 替换用户的全部备用码
+	ReplaceCodes(ctx context.Context, userID int64, codeHashes []string) error
+	// ConsumeCode 消费一个未使用的备用码，返回是否成功
+	ConsumeCode(ctx context.Context, userID int64, codeHash string) (bool, error)
+	// CountRemaining 返回剩余未使用的备用码数量
+	CountRemaining(ctx context.Context, userID int64) (int, error)
+	DeleteByUserID(ctx context.Context, userID int64) error
+}
+
 // TotpCache defines cache operations for TOTP service
 type TotpCache interface {
 	// Setup session methods
@@ -65,9 +80,11 @@ type TotpLoginSession struct {
 
 // TotpStatus represents the TOTP status for a user
 type TotpStatus struct {
-	Enabled        bool       `json:"enabled"`
-	EnabledAt      *time.Time `json:"enabled_at,omitempty"`
-	FeatureEnabled bool       `json:"feature_enabled"`
+	Enabled              bool       `json:"enabled"`
+	EnabledAt            *time.Time `json:"enabled_at,omitempty"`
+	FeatureEnabled       bool       `json:"feature_enabled"`
+	Required             bool       `json:"required"` // 管理员强制 2FA 时为 true
+	BackupCodesRemaining int        `json:"backup_codes_remaining"`
 }
 
 // TotpSetupResponse represents the response for initiating TOTP setup
@@ -84,6 +101,10 @@ const (
 	totpAttemptsTTL = 15 * time.Minute
 	maxTotpAttempts = 5
 	totpIssuer      = "Sub2API"
+
+	totpBackupCodeCount = 10
+	// 备用码字符集：去掉易混淆的 0/1/i/l/o
+	totpBackupCodeAlphabet = "abcdefghjkmnpqrstuvwxyz23456789"
 )
 
 // TotpService handles TOTP operations
@@ -91,6 +112,7 @@ type TotpService struct {
 	userRepo          UserRepository
 	encryptor         SecretEncryptor
 	cache             TotpCache
+	backupCodeRepo    TotpBackupCodeRepository
 	settingService    *SettingService
 	emailService      *EmailService
 	emailQueueService *EmailQueueService
@@ -101,6 +123,7 @@ func NewTotpService(
 	userRepo UserRepository,
 	encryptor SecretEncryptor,
 	cache TotpCache,
+	backupCodeRepo TotpBackupCodeRepository,
 	settingService *SettingService,
 	emailService *EmailService,
 	emailQueueService *EmailQueueService,
@@ -109,42 +132,66 @@ func NewTotpService(
 		userRepo:          userRepo,
 		encryptor:         encryptor,
 		cache:             cache,
+		backupCodeRepo:    backupCodeRepo,
 		settingService:    settingService,
 		emailService:      emailService,
 		emailQueueService: emailQueueService,
 	}
 }
 
+// isRequiredForUser 部署配置要求后台账号必须启用 2FA
+func (s *TotpService) isRequiredForUser(user *User) bool {
+	return user != nil && user.IsStaff() && s.settingService.IsAdminTotpRequired()
+}
+
+// isAvailableForUser 判断用户是否可以使用 TOTP
+// 全局开关关闭时，强制 2FA 的管理员仍然可以（且必须）使用
+func (s *TotpService) isAvailableForUser(ctx context.Context, user *User) bool {
+	return s.settingService.IsTotpEnabled(ctx) || s.isRequiredForUser(user)
+}
+
+// RequiresLogin2FA 判断用户登录时是否需要进行 2FA 校验
+func (s *TotpService) RequiresLogin2FA(ctx context.Context, user *User) bool {
+	return user != nil && user.TotpEnabled && s.isAvailableForUser(ctx, user)
+}
+
 // GetStatus returns the TOTP status for a user
 func (s *TotpService) GetStatus(ctx context.Context, userID int64) (*TotpStatus, error) {
-	featureEnabled := s.settingService.IsTotpEnabled(ctx)
-
 	user, err := s.userRepo.GetByID(ctx, userID)
 	if err != nil {
 		return nil, fmt.Errorf("get user: %w", err)
 	}
 
-	return &TotpStatus{
+	status := &TotpStatus{
 		Enabled:        user.TotpEnabled,
 		EnabledAt:      user.TotpEnabledAt,
-		FeatureEnabled: featureEnabled,
-	}, nil
+		FeatureEnabled: s.isAvailableForUser(ctx, user),
+		Required:       s.isRequiredForUser(user),
+	}
+	if user.TotpEnabled && s.backupCodeRepo != nil {
+		remaining, err := s.backupCodeRepo.CountRemaining(ctx, userID)
+		if err != nil {
+			return nil, fmt.Errorf("count backup codes: %w", err)
+		}
+		status.BackupCodesRemaining = remaining
+	}
+	return status, nil
 }
 
 // InitiateSetup starts the TOTP setup process
 // If email verification is enabled, emailCode is required; otherwise password is required
 func (s *TotpService) InitiateSetup(ctx context.Context, userID int64, emailCode, password string) (*TotpSetupResponse, error) {
-	// Check if TOTP feature is enabled globally
-	if !s.settingService.IsTotpEnabled(ctx) {
-		return nil, ErrTotpNotEnabled
-	}
-
 	// Get user and check if TOTP is already enabled
 	user, err := s.userRepo.GetByID(ctx, userID)
 	if err != nil {
 		return nil, fmt.Errorf("get user: %w", err)
 	}
 
+	// Check if TOTP feature is available for this user
+	if !s.isAvailableForUser(ctx, user) {
+		return nil, ErrTotpNotEnabled
+	}
+
 	if user.TotpEnabled {
 		return nil, ErrTotpAlreadyEnabled
 	}
@@ -203,30 +250,36 @@ func (s *TotpService) InitiateSetup(ctx context.Context, userID int64, emailCode
 }
 
 // CompleteSetup completes the TOTP setup by verifying the code
-func (s *TotpService) CompleteSetup(ctx context.Context, userID int64, totpCode, setupToken string) error {
-	// Check if TOTP feature is enabled globally
-	if !s.settingService.IsTotpEnabled(ctx) {
-		return ErrTotpNotEnabled
+// 成功后返回一次性展示的备用恢复码
+func (s *TotpService) CompleteSetup(ctx context.Context, userID int64, totpCode, setupToken string) ([]string, error) {
+	user, err := s.userRepo.GetByID(ctx, userID)
+	if err != nil {
+		return nil, fmt.Errorf("get user: %w", err)
+	}
+
+	// Check if TOTP feature is available for this user
+	if !s.isAvailableForUser(ctx, user) {
+		return nil, ErrTotpNotEnabled
 	}
 
 	// Get the setup session
 	session, err := s.cache.GetSetupSession(ctx, userID)
 	if err != nil {
-		return ErrTotpSetupExpired
+		return nil, ErrTotpSetupExpired
 	}
 
 	if session == nil {
-		return ErrTotpSetupExpired
+		return nil, ErrTotpSetupExpired
 	}
 
 	// Verify the setup token (constant-time comparison)
 	if subtle.ConstantTimeCompare([]byte(session.SetupToken), []byte(setupToken)) != 1 {
-		return ErrTotpSetupExpired
+		return nil, ErrTotpSetupExpired
 	}
 
 	// Verify the TOTP code
 	if !totp.Validate(totpCode, session.Secret) {
-		return ErrTotpInvalidCode
+		return nil, ErrTotpInvalidCode
 	}
 
 	setupSecretPrefix := "N/A"
@@ -241,7 +294,7 @@ func (s *TotpService) CompleteSetup(ctx context.Context, userID int64, totpCode,
 	// Encrypt the secret
 	encryptedSecret, err := s.encryptor.Encrypt(session.Secret)
 	if err != nil {
-		return fmt.Errorf("encrypt totp secret: %w", err)
+		return nil, fmt.Errorf("encrypt totp secret: %w", err)
 	}
 
 	slog.Debug("totp_complete_setup_encrypted",
@@ -269,20 +322,113 @@ func (s *TotpService) CompleteSetup(ctx context.Context, userID int64, totpCode,
 
 	// Update user with encrypted TOTP secret
 	if err := s.userRepo.UpdateTotpSecret(ctx, userID, &encryptedSecret); err != nil {
-		return fmt.Errorf("update totp secret: %w", err)
+		return nil, fmt.Errorf("update totp secret: %w", err)
+	}
+
+	// Generate backup codes before enabling, so a failure does not leave 2FA without recovery codes
+	backupCodes, err := s.replaceBackupCodes(ctx, userID)
+	if err != nil {
+		return nil, err
 	}
 
 	// Enable TOTP for the user
 	if err := s.userRepo.EnableTotp(ctx, userID); err != nil {
-		return fmt.Errorf("enable totp: %w", err)
+		return nil, fmt.Errorf("enable totp: %w", err)
 	}
 
 	// Clean up the setup session
 	_ = s.cache.DeleteSetupSession(ctx, userID)
 
+	return backupCodes, nil
+}
+
+// RegenerateBackupCodes 重新生成备用码（需要当前 TOTP 验证码），旧备用码全部失效
+func (s *TotpService) RegenerateBackupCodes(ctx context.Context, userID int64, totpCode string) ([]string, error) {
+	if err := s.VerifyCode(ctx, userID, totpCode); err != nil {
+		return nil, err
+	}
+	return s.replaceBackupCodes(ctx, userID)
+}
+
+// VerifyBackupCode 使用备用码完成 2FA 校验（每个备用码仅可使用一次）
+func (s *TotpService) VerifyBackupCode(ctx context.Context, userID int64, code string) error {
+	attempts, err := s.cache.GetVerifyAttempts(ctx, userID)
+	if err == nil && attempts >= maxTotpAttempts {
+		return ErrTotpTooManyAttempts
+	}
+	if s.backupCodeRepo == nil {
+		return ErrTotpInvalidBackup
+	}
+
+	normalized := normalizeBackupCode(code)
+	if normalized == "" {
+		_, _ = s.cache.IncrementVerifyAttempts(ctx, userID)
+		return ErrTotpInvalidBackup
+	}
+
+	ok, err := s.backupCodeRepo.ConsumeCode(ctx, userID, hashToken(normalized))
+	if err != nil {
+		return infraerrors.InternalServer("TOTP_VERIFY_ERROR", "failed to verify backup code")
+	}
+	if !ok {
+		_, _ = s.cache.IncrementVerifyAttempts(ctx, userID)
+		return ErrTotpInvalidBackup
+	}
+
+	_ = s.cache.ClearVerifyAttempts(ctx, userID)
+	slog.Info("totp_backup_code_used", "user_id", userID)
 	return nil
 }
 
+// replaceBackupCodes 生成新的备用码并替换旧备用码，返回明文（仅展示一次）
+func (s *TotpService) replaceBackupCodes(ctx context.Context, userID int64) ([]string, error) {
+	if s.backupCodeRepo == nil {
+		return []string{}, nil
+	}
+	codes := make([]string, 0, totpBackupCodeCount)
+	hashes := make([]string, 0, totpBackupCodeCount)
+	for i := 0; i < totpBackupCodeCount; i++ {
+		code, err := generateBackupCode()
+		if err != nil {
+			return nil, fmt.Errorf("generate backup code: %w", err)
+		}
+		codes = append(codes, code)
+		hashes = append(hashes, hashToken(normalizeBackupCode(code)))
+	}
+	if err := s.backupCodeRepo.ReplaceCodes(ctx, userID, hashes); err != nil {
+		return nil, fmt.Errorf("store backup codes: %w", err)
+	}
+	return codes, nil
+}
+
+// generateBackupCode 生成形如 xxxxx-xxxxx 的备用码
+func generateBackupCode() (string, error) {
+	const length = 10
+	b := make([]byte, length)
+	if _, err := rand.Read(b); err != nil {
+		return "", err
+	}
+	out := make([]byte, 0, length+1)
+	for i, v := range b {
+		if i == length/2 {
+			out = append(out, '-')
+		}
+		out = append(out, totpBackupCodeAlphabet[int(v)%len(totpBackupCodeAlphabet)])
+	}
+	return string(out), nil
+}
+
+// normalizeBackupCode 忽略大小写、空格与连字符
+func normalizeBackupCode(code string) string {
+	code = strings.ToLower(code)
+	return strings.Map(func(r rune) rune {
+		if r == '-' || unicode.IsSpace(r) {
+			return -1
+		}
+		return r
+	}, code)
+}
+
 // Disable disables TOTP for a user
 // If email verification is enabled, emailCode is required; otherwise password is required
 func (s *TotpService) Disable(ctx context.Context, userID int64, emailCode, password string) error {
@@ -315,11 +461,22 @@ func (s *TotpService) Disable(ctx context.Context, userID int64, emailCode, pass
 		}
 	}
 
+	// 强制 2FA 的管理员不允许自行关闭
+	if s.isRequiredForUser(user) {
+		return ErrTotpRequired
+	}
+
 	// Disable TOTP
 	if err := s.userRepo.DisableTotp(ctx, userID); err != nil {
 		return fmt.Errorf("disable totp: %w", err)
 	}
 
+	if s.backupCodeRepo != nil {
+		if err := s.backupCodeRepo.DeleteByUserID(ctx, userID); err != nil {
+			slog.Warn("totp_delete_backup_codes_failed", "user_id", userID, "error", err)
+		}
+	}
+
 	return nil
 }
 
@@ -0,0 +1,140 @@
+package service
+
+import (
+	"strings"
+	"time"
+
+	"github.com/tidwall/gjson"
+)
+
+// TrafficMirrorRuleReport 单条规则在时间窗口内的主/镜像对比汇总
+type TrafficMirrorRuleReport struct {
+	RuleID          int64  `json:"rule_id"`
+	RuleName        string `json:"rule_name"`
+	TargetAccountID int64  `json:"target_account_id"`
+	TargetModel     string `json:"target_model"`
+
+	Samples      int64 `json:"samples"`
+	MirrorErrors int64 `json:"mirror_errors"`
+	// MirrorStatusCodes 镜像请求状态码分布（0 表示未收到上游响应）
+	MirrorStatusCodes map[int]int64 `json:"mirror_status_codes"`
+
+	PrimaryLatencyP50Ms float64 `json:"primary_latency_p50_ms"`
+	PrimaryLatencyP95Ms float64 `json:"primary_latency_p95_ms"`
+	MirrorLatencyP50Ms  float64 `json:"mirror_latency_p50_ms"`
+	MirrorLatencyP95Ms  float64 `json:"mirror_latency_p95_ms"`
+
+	// Compared 两侧输出均可解析的样本数，以下匹配数与平均输出 token 均基于这部分样本
+	Compared               int64   `json:"compared"`
+	OutputMatches          int64   `json:"output_matches"`
+	StopReasonMatches      int64   `json:"stop_reason_matches"`
+	AvgPrimaryOutputTokens float64 `json:"avg_primary_output_tokens"`
+	AvgMirrorOutputTokens  float64 `json:"avg_mirror_output_tokens"`
+
+	MirrorErrorRate     float64 `json:"mirror_error_rate"`
+	OutputMatchRate     float64 `json:"output_match_rate"`
+	StopReasonMatchRate float64 `json:"stop_reason_match_rate"`
+
+	FirstSampleAt *time.Time `json:"first_sample_at,omitempty"`
+	LastSampleAt  *time.Time `json:"last_sample_at,omitempty"`
+}
+
+func (r *TrafficMirrorRuleReport) fillRates() {
+	if r.Samples > 0 {
+		r.MirrorErrorRate = float64(r.MirrorErrors) / float64(r.Samples)
+	}
+	if r.Compared > 0 {
+		r.OutputMatchRate = float64(r.OutputMatches) / float64(r.Compared)
+		r.StopReasonMatchRate = float64(r.StopReasonMatches) / float64(r.Compared)
+	}
+}
+
+// trafficMirrorOutput 从捕获的输出中提取的可比较特征
+type trafficMirrorOutput struct {
+	// shape 内容块/输出项类型序列，工具调用附带工具名，如 "thinking,text,tool_use:get_weather"
+	shape        string
+	stopReason   string
+	outputTokens int64
+}
+
+// compareTrafficMirrorSample 解析主请求与镜像请求的输出并填充对比字段；
+// 镜像失败或任一输出无法解析（如被截断）时 OutputMatch 保持为 nil
+func compareTrafficMirrorSample(sample *TrafficMirrorSample) {
+	primary, primaryOK := parseTrafficMirrorOutput(sample.PrimaryResponse)
+	sample.PrimaryStopReason = primary.stopReason
+	sample.PrimaryOutputTokens = primary.outputTokens
+	if sample.MirrorError != nil {
+		return
+	}
+	mirror, mirrorOK := parseTrafficMirrorOutput(sample.MirrorResponse)
+	sample.MirrorStopReason = mirror.stopReason
+	sample.MirrorOutputTokens = mirror.outputTokens
+	if primaryOK && mirrorOK {
+		match := primary.shape == mirror.shape && primary.stopReason == mirror.stopReason
+		sample.OutputMatch = &match
+	}
+}
+
+// parseTrafficMirrorOutput 解析 Anthropic Messages / OpenAI Responses 输出（JSON 或 SSE），
+// 未能取得停止原因时返回 false
+func parseTrafficMirrorOutput(raw string) (trafficMirrorOutput, bool) {
+	raw = strings.TrimSpace(raw)
+	var out trafficMirrorOutput
+	if !strings.HasPrefix(raw, "event:") && !strings.HasPrefix(raw, "data:") {
+		if !gjson.Valid(raw) {
+			return out, false
+		}
+		parseTrafficMirrorJSON(&out, gjson.Parse(raw))
+		return out, out.stopReason != ""
+	}
+
+	var shape []string
+	for _, line := range strings.Split(raw, "\n") {
+		data, ok := strings.CutPrefix(strings.TrimSpace(line), "data:")
+		if !ok {
+			continue
+		}
+		event := gjson.Parse(strings.TrimSpace(data))
+		switch event.Get("type").String() {
+		case "content_block_start":
+			block := event.Get("content_block")
+			shape = append(shape, trafficMirrorShapeItem(block.Get("type").String(), block.Get("name").String()))
+		case "message_delta":
+			out.stopReason = event.Get("delta.stop_reason").String()
+			out.outputTokens = event.Get("usage.output_tokens").Int()
+		case "response.completed", "response.incomplete", "response.failed":
+			// Responses 流的终止事件携带完整响应
+			parseTrafficMirrorJSON(&out, event.Get("response"))
+			return out, out.stopReason != ""
+		}
+	}
+	out.shape = strings.Join(shape, ",")
+	return out, out.stopReason != ""
+}
+
+func parseTrafficMirrorJSON(out *trafficMirrorOutput, doc gjson.Result) {
+	var shape []string
+	if content := doc.Get("content"); content.IsArray() {
+		for _, block := range content.Array() {
+			shape = append(shape, trafficMirrorShapeItem(block.Get("type").String(), block.Get("name").String()))
+		}
+		out.stopReason = doc.Get("stop_reason").String()
+	} else if output := doc.Get("output"); output.IsArray() {
+		for _, item := range output.Array() {
+			shape = append(shape, trafficMirrorShapeItem(item.Get("type").String(), item.Get("name").String()))
+		}
+		out.stopReason = doc.Get("status").String()
+		if reason := doc.Get("incomplete_details.reason").String(); reason != "" {
+			out.stopReason += ":" + reason
+		}
+	}
+	out.shape = strings.Join(shape, ",")
+	out.outputTokens = doc.Get("usage.output_tokens").Int()
+}
+
+func trafficMirrorShapeItem(itemType, name string) string {
+	if name == "" {
+		return itemType
+	}
+	return itemType + ":" + name
+}
@@ -0,0 +1,514 @@
+package service
+
+import (
+	"bytes"
+	"context"
+	"fmt"
+	"log"
+	"math/rand/v2"
+	"net/http"
+	"strings"
+	"sync"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/domain"
+	infraerrors "github.com/Wei-Shaw/sub2api/internal/pkg/errors"
+	"github.com/gin-gonic/gin"
+	"github.com/tidwall/sjson"
+)
+
+// 镜像请求类型
+const (
+	TrafficMirrorTypeMessages = "messages"
+	TrafficMirrorTypeOpenAI   = "openai_responses"
+)
+
+const (
+	trafficMirrorRulesCacheTTL  = 30 * time.Second
+	trafficMirrorCaptureLimit   = 64 * 1024
+	trafficMirrorTimeout        = 5 * time.Minute
+	trafficMirrorMaxConcurrency = 4
+	trafficMirrorMaxNameLen     = 100
+	trafficMirrorMaxPatternLen  = 200
+)
+
+var (
+	ErrTrafficMirrorRuleNotFound       = infraerrors.NotFound("TRAFFIC_MIRROR_RULE_NOT_FOUND", "traffic mirror rule not found")
+	ErrTrafficMirrorSampleNotFound     = infraerrors.NotFound("TRAFFIC_MIRROR_SAMPLE_NOT_FOUND", "traffic mirror sample not found")
+	ErrInvalidTrafficMirrorRule        = infraerrors.BadRequest("INVALID_TRAFFIC_MIRROR_RULE", "name and target account are required and sample_rate must be within (0, 1]")
+	ErrInvalidTrafficMirrorReportRange = infraerrors.BadRequest("INVALID_TRAFFIC_MIRROR_REPORT_RANGE", "start_time must be before end_time")
+)
+
+// TrafficMirrorRule 流量镜像规则：命中的请求按比例抽样，异步复制到目标账号/模型
+type TrafficMirrorRule struct {
+	ID      int64  `json:"id"`
+	Name    string `json:"name"`
+	Enabled bool   `json:"enabled"`
+	// GroupID 限定分组，nil 表示所有分组
+	GroupID *int64 `json:"group_id,omitempty"`
+	// ModelPattern 模型名或以 * 结尾的前缀，空表示所有模型
+	ModelPattern    string    `json:"model_pattern"`
+	SampleRate      float64   `json:"sample_rate"`
+	TargetAccountID int64     `json:"target_account_id"`
+	TargetModel     string    `json:"target_model"`
+	CreatedAt       time.Time `json:"created_at"`
+	UpdatedAt       time.Time `json:"updated_at"`
+}
+
+// TrafficMirrorSample 一次镜像的对比样本；输出超出上限时截断
+type TrafficMirrorSample struct {
+	ID               int64  `json:"id"`
+	RuleID           int64  `json:"rule_id"`
+	RequestType      string `json:"request_type"`
+	APIKeyID         *int64 `json:"api_key_id,omitempty"`
+	GroupID          *int64 `json:"group_id,omitempty"`
+	Model            string `json:"model"`
+	PrimaryAccountID int64  `json:"primary_account_id"`
+	PrimaryLatencyMs int64  `json:"primary_latency_ms"`
+	// PrimaryResponse / MirrorResponse 仅在详情接口返回
+	PrimaryResponse     string  `json:"primary_response,omitempty"`
+	PrimaryTruncated    bool    `json:"primary_truncated"`
+	PrimaryStopReason   string  `json:"primary_stop_reason"`
+	PrimaryOutputTokens int64   `json:"primary_output_tokens"`
+	MirrorAccountID     int64   `json:"mirror_account_id"`
+	MirrorModel         string  `json:"mirror_model"`
+	MirrorStatusCode    int     `json:"mirror_status_code"`
+	MirrorLatencyMs     int64   `json:"mirror_latency_ms"`
+	MirrorResponse      string  `json:"mirror_response,omitempty"`
+	MirrorTruncated     bool    `json:"mirror_truncated"`
+	MirrorError         *string `json:"mirror_error,omitempty"`
+	MirrorStopReason    string  `json:"mirror_stop_reason"`
+	MirrorOutputTokens  int64   `json:"mirror_output_tokens"`
+	// OutputMatch 主/镜像输出结构（内容块类型与工具调用）及停止原因是否一致，任一输出无法解析时为 nil
+	OutputMatch *bool     `json:"output_match,omitempty"`
+	CreatedAt   time.Time `json:"created_at"`
+}
+
+// TrafficMirrorRepository 流量镜像规则与样本存储
+type TrafficMirrorRepository interface {
+	ListRules(ctx context.Context) ([]*TrafficMirrorRule, error)
+	GetRule(ctx context.Context, id int64) (*TrafficMirrorRule, error)
+	CreateRule(ctx context.Context, rule *TrafficMirrorRule) error
+	UpdateRule(ctx context.Context, rule *TrafficMirrorRule) error
+	DeleteRule(ctx context.Context, id int64) error
+
+	CreateSample(ctx context.Context, sample *TrafficMirrorSample) error
+	// ListSamples 按时间倒序分页列出样本（不含输出内容），ruleID 为 0 表示不过滤
+	ListSamples(ctx context.Context, ruleID int64, page, pageSize int) ([]*TrafficMirrorSample, int64, error)
+	GetSample(ctx context.Context, id int64) (*TrafficMirrorSample, error)
+	// Report 按规则聚合时间窗口内的样本，ruleID 为 0 表示所有规则
+	Report(ctx context.Context, ruleID int64, start, end time.Time) ([]*TrafficMirrorRuleReport, error)
+}
+
+// TrafficMirrorService 流量镜像：抽中的请求在主请求成功后异步复制到目标账号，不影响客户端响应
+type TrafficMirrorService struct {
+	repo                      TrafficMirrorRepository
+	accountRepo               AccountRepository
+	gatewayService            *GatewayService
+	openAIGatewayService      *OpenAIGatewayService
+	geminiCompatService       *GeminiMessagesCompatService
+	antigravityGatewayService *AntigravityGatewayService
+	featureFlags              *FeatureFlagService
+
+	rulesMu       sync.RWMutex
+	rules         []*TrafficMirrorRule
+	rulesLoadedAt time.Time
+
+	// sem 限制同时执行的镜像请求数，满时直接丢弃本次镜像
+	sem chan struct{}
+}
+
+// NewTrafficMirrorService creates a new TrafficMirrorService
+func NewTrafficMirrorService(
+	repo TrafficMirrorRepository,
+	accountRepo AccountRepository,
+	gatewayService *GatewayService,
+	openAIGatewayService *OpenAIGatewayService,
+	geminiCompatService *GeminiMessagesCompatService,
+	antigravityGatewayService *AntigravityGatewayService,
+	featureFlags *FeatureFlagService,
+) *TrafficMirrorService {
+	return &TrafficMirrorService{
+		repo:                      repo,
+		accountRepo:               accountRepo,
+		gatewayService:            gatewayService,
+		openAIGatewayService:      openAIGatewayService,
+		geminiCompatService:       geminiCompatService,
+		antigravityGatewayService: antigravityGatewayService,
+		featureFlags:              featureFlags,
+		sem:                       make(chan struct{}, trafficMirrorMaxConcurrency),
+	}
+}
+
+// ListRules 列出全部规则
+func (s *TrafficMirrorService) ListRules(ctx context.Context) ([]*TrafficMirrorRule, error) {
+	return s.repo.ListRules(ctx)
+}
+
+// GetRule 获取规则
+func (s *TrafficMirrorService) GetRule(ctx context.Context, id int64) (*TrafficMirrorRule, error) {
+	return s.repo.GetRule(ctx, id)
+}
+
+// CreateRule 创建规则
+func (s *TrafficMirrorService) CreateRule(ctx context.Context, rule *TrafficMirrorRule) error {
+	if err := s.validateRule(ctx, rule); err != nil {
+		return err
+	}
+	if err := s.repo.CreateRule(ctx, rule); err != nil {
+		return err
+	}
+	s.invalidateRules()
+	return nil
+}
+
+// UpdateRule 更新规则
+func (s *TrafficMirrorService) UpdateRule(ctx context.Context, rule *TrafficMirrorRule) error {
+	if err := s.validateRule(ctx, rule); err != nil {
+		return err
+	}
+	if err := s.repo.UpdateRule(ctx, rule); err != nil {
+		return err
+	}
+	s.invalidateRules()
+	return nil
+}
+
+// DeleteRule 删除规则（同时删除其样本）
+func (s *TrafficMirrorService) DeleteRule(ctx context.Context, id int64) error {
+	if err := s.repo.DeleteRule(ctx, id); err != nil {
+		return err
+	}
+	s.invalidateRules()
+	return nil
+}
+
+// ListSamples 分页列出样本
+func (s *TrafficMirrorService) ListSamples(ctx context.Context, ruleID int64, page, pageSize int) ([]*TrafficMirrorSample, int64, error) {
+	return s.repo.ListSamples(ctx, ruleID, page, pageSize)
+}
+
+// GetSample 获取样本详情（含主请求与镜像请求输出）
+func (s *TrafficMirrorService) GetSample(ctx context.Context, id int64) (*TrafficMirrorSample, error) {
+	return s.repo.GetSample(ctx, id)
+}
+
+// Report 按规则汇总时间窗口内主请求与镜像请求的对比结果，ruleID 为 0 表示所有规则
+func (s *TrafficMirrorService) Report(ctx context.Context, ruleID int64, start, end time.Time) ([]*TrafficMirrorRuleReport, error) {
+	if !start.Before(end) {
+		return nil, ErrInvalidTrafficMirrorReportRange
+	}
+	reports, err := s.repo.Report(ctx, ruleID, start, end)
+	if err != nil {
+		return nil, err
+	}
+	for _, r := range reports {
+		r.fillRates()
+	}
+	return reports, nil
+}
+
+func (s *TrafficMirrorService) validateRule(ctx context.Context, rule *TrafficMirrorRule) error {
+	if err := normalizeTrafficMirrorRule(rule); err != nil {
+		return err
+	}
+	if _, err := s.accountRepo.GetByID(ctx, rule.TargetAccountID); err != nil {
+		return ErrAccountNotFound
+	}
+	return nil
+}
+
+// normalizeTrafficMirrorRule 校验并规范化规则字段
+func normalizeTrafficMirrorRule(rule *TrafficMirrorRule) error {
+	if rule == nil {
+		return ErrInvalidTrafficMirrorRule
+	}
+	rule.Name = strings.TrimSpace(rule.Name)
+	rule.ModelPattern = strings.ToLower(strings.TrimSpace(rule.ModelPattern))
+	rule.TargetModel = strings.TrimSpace(rule.TargetModel)
+	if rule.ModelPattern == "*" {
+		rule.ModelPattern = ""
+	}
+	if rule.GroupID != nil && *rule.GroupID <= 0 {
+		rule.GroupID = nil
+	}
+	if rule.Name == "" || len(rule.Name) > trafficMirrorMaxNameLen ||
+		len(rule.ModelPattern) > trafficMirrorMaxPatternLen || len(rule.TargetModel) > trafficMirrorMaxPatternLen ||
+		rule.TargetAccountID <= 0 || !(rule.SampleRate > 0 && rule.SampleRate <= 1) {
+		return ErrInvalidTrafficMirrorRule
+	}
+	return nil
+}
+
+// matches 判断规则是否适用于请求（不含抽样）
+func (r *TrafficMirrorRule) matches(groupID *int64, model string) bool {
+	if !r.Enabled {
+		return false
+	}
+	if r.GroupID != nil && (groupID == nil || *groupID != *r.GroupID) {
+		return false
+	}
+	if r.ModelPattern == "" {
+		return true
+	}
+	model = strings.ToLower(strings.TrimSpace(model))
+	if prefix, ok := strings.CutSuffix(r.ModelPattern, "*"); ok {
+		return strings.HasPrefix(model, prefix)
+	}
+	return model == r.ModelPattern
+}
+
+func (s *TrafficMirrorService) invalidateRules() {
+	s.rulesMu.Lock()
+	s.rulesLoadedAt = time.Time{}
+	s.rulesMu.Unlock()
+}
+
+// activeRules 返回已启用的规则（按 ID 升序，带短时缓存）
+func (s *TrafficMirrorService) activeRules(ctx context.Context) []*TrafficMirrorRule {
+	s.rulesMu.RLock()
+	if !s.rulesLoadedAt.IsZero() && time.Since(s.rulesLoadedAt) < trafficMirrorRulesCacheTTL {
+		rules := s.rules
+		s.rulesMu.RUnlock()
+		return rules
+	}
+	s.rulesMu.RUnlock()
+
+	all, err := s.repo.ListRules(ctx)
+	if err != nil {
+		log.Printf("[TrafficMirror] load rules failed: %v", err)
+		all = nil
+	}
+	rules := make([]*TrafficMirrorRule, 0, len(all))
+	for _, rule := range all {
+		if rule != nil && rule.Enabled {
+			rules = append(rules, rule)
+		}
+	}
+
+	s.rulesMu.Lock()
+	s.rules = rules
+	s.rulesLoadedAt = time.Now()
+	s.rulesMu.Unlock()
+	return rules
+}
+
+// pickTrafficMirrorRule 返回第一条匹配且被抽中的规则
+func pickTrafficMirrorRule(rules []*TrafficMirrorRule, groupID *int64, model string, sample func() float64) *TrafficMirrorRule {
+	for _, rule := range rules {
+		if rule.matches(groupID, model) {
+			if sample() < rule.SampleRate {
+				return rule
+			}
+			return nil
+		}
+	}
+	return nil
+}
+
+// TrafficMirrorSession 单个被抽中请求的镜像会话
+type TrafficMirrorSession struct {
+	svc      *TrafficMirrorService
+	rule     *TrafficMirrorRule
+	reqType  string
+	ctx      context.Context
+	path     string
+	headers  http.Header
+	body     []byte
+	model    string
+	apiKeyID *int64
+	groupID  *int64
+	capture  *trafficMirrorCaptureWriter
+}
+
+// Start 按规则对请求抽样；抽中时包装响应写入器以捕获主请求输出并返回会话，否则返回 nil。
+//
+// 需在转发前调用；body 为即将转发的请求体（会复制一份用于镜像）。
+func (s *TrafficMirrorService) Start(c *gin.Context, reqType string, apiKey *APIKey, model string, body []byte) *TrafficMirrorSession {
+	if s == nil || c == nil || c.Request == nil {
+		return nil
+	}
+	var groupID *int64
+	var apiKeyID *int64
+	if apiKey != nil {
+		groupID = apiKey.GroupID
+		id := apiKey.ID
+		apiKeyID = &id
+	}
+	if !s.featureFlags.IsEnabled(FeatureFlagTrafficMirror, FeatureFlagSubjectFromAPIKey(apiKey)) {
+		return nil
+	}
+	rule := pickTrafficMirrorRule(s.activeRules(c.Request.Context()), groupID, model, rand.Float64)
+	if rule == nil {
+		return nil
+	}
+
+	headers := http.Header{}
+	for key, values := range c.Request.Header {
+		lower := strings.ToLower(key)
+		if opsRetryRequestHeaderAllowlist[lower] || lower == "user-agent" {
+			headers[key] = append([]string(nil), values...)
+		}
+	}
+	capture := &trafficMirrorCaptureWriter{ResponseWriter: c.Writer, limit: trafficMirrorCaptureLimit}
+	c.Writer = capture
+
+	return &TrafficMirrorSession{
+		svc:      s,
+		rule:     rule,
+		reqType:  reqType,
+		ctx:      context.WithoutCancel(c.Request.Context()),
+		path:     c.Request.URL.Path,
+		headers:  headers,
+		body:     append([]byte(nil), body...),
+		model:    model,
+		apiKeyID: apiKeyID,
+		groupID:  groupID,
+		capture:  capture,
+	}
+}
+
+// Complete 主请求成功后提交异步镜像；session 为 nil 时为空操作。并发已满时丢弃本次镜像。
+func (m *TrafficMirrorSession) Complete(primaryAccountID int64, primaryDuration time.Duration) {
+	if m == nil {
+		return
+	}
+	sample := &TrafficMirrorSample{
+		RuleID:           m.rule.ID,
+		RequestType:      m.reqType,
+		APIKeyID:         m.apiKeyID,
+		GroupID:          m.groupID,
+		Model:            m.model,
+		PrimaryAccountID: primaryAccountID,
+		PrimaryLatencyMs: primaryDuration.Milliseconds(),
+		PrimaryResponse:  string(bytes.TrimSpace(m.capture.buf.Bytes())),
+		PrimaryTruncated: m.capture.truncated(),
+	}
+
+	select {
+	case m.svc.sem <- struct{}{}:
+	default:
+		log.Printf("[TrafficMirror] concurrency limit reached, dropping mirror for rule %d", m.rule.ID)
+		return
+	}
+	go func() {
+		defer func() { <-m.svc.sem }()
+		defer func() {
+			if r := recover(); r != nil {
+				log.Printf("[TrafficMirror] mirror panic for rule %d: %v", m.rule.ID, r)
+			}
+		}()
+		m.svc.runMirror(m, sample)
+	}()
+}
+
+// runMirror 执行镜像请求并保存样本
+func (s *TrafficMirrorService) runMirror(m *TrafficMirrorSession, sample *TrafficMirrorSample) {
+	ctx, cancel := context.WithTimeout(m.ctx, trafficMirrorTimeout)
+	defer cancel()
+
+	sample.MirrorAccountID = m.rule.TargetAccountID
+	sample.MirrorModel = m.model
+	body := m.body
+	if m.rule.TargetModel != "" {
+		sample.MirrorModel = m.rule.TargetModel
+		if next, err := sjson.SetBytes(body, "model", m.rule.TargetModel); err == nil {
+			body = next
+		}
+	}
+
+	start := time.Now()
+	statusCode, response, truncated, err := s.forward(ctx, m, body)
+	sample.MirrorLatencyMs = time.Since(start).Milliseconds()
+	sample.MirrorStatusCode = statusCode
+	sample.MirrorResponse = response
+	sample.MirrorTruncated = truncated
+	if err == nil && statusCode >= 400 {
+		err = fmt.Errorf("upstream returned status %d", statusCode)
+	}
+	if err != nil {
+		msg := err.Error()
+		sample.MirrorError = &msg
+	}
+	compareTrafficMirrorSample(sample)
+
+	if err := s.repo.CreateSample(ctx, sample); err != nil {
+		log.Printf("[TrafficMirror] save sample failed for rule %d: %v", m.rule.ID, err)
+	}
+}
+
+// forward 在合成的 gin 上下文中将请求转发到目标账号，返回状态码与捕获的输出
+func (s *TrafficMirrorService) forward(ctx context.Context, m *TrafficMirrorSession, body []byte) (int, string, bool, error) {
+	account, err := s.accountRepo.GetByID(ctx, m.rule.TargetAccountID)
+	if err != nil {
+		return 0, "", false, fmt.Errorf("load target account: %w", err)
+	}
+	if !account.IsSchedulable() {
+		return 0, "", false, fmt.Errorf("target account %d is not schedulable", account.ID)
+	}
+
+	w := newLimitedResponseWriter(trafficMirrorCaptureLimit)
+	c, _ := gin.CreateTestContext(w)
+	req, _ := http.NewRequestWithContext(ctx, http.MethodPost, "http://localhost"+m.path, bytes.NewReader(nil))
+	for key, values := range m.headers {
+		req.Header[key] = values
+	}
+	req.Header.Set("content-type", "application/json")
+	c.Request = req
+
+	switch m.reqType {
+	case TrafficMirrorTypeOpenAI:
+		if account.Platform != PlatformOpenAI {
+			return 0, "", false, fmt.Errorf("target account platform %s does not support %s requests", account.Platform, m.reqType)
+		}
+		_, err = s.openAIGatewayService.Forward(ctx, c, account, body)
+	case TrafficMirrorTypeMessages:
+		switch account.Platform {
+		case PlatformAntigravity:
+			_, err = s.antigravityGatewayService.Forward(ctx, c, account, body, false)
+		case PlatformGemini:
+			_, err = s.geminiCompatService.Forward(ctx, c, account, body)
+		case PlatformAnthropic:
+			parsedReq, parseErr := ParseGatewayRequest(body, domain.PlatformAnthropic)
+			if parseErr != nil {
+				return 0, "", false, fmt.Errorf("parse request body: %w", parseErr)
+			}
+			_, err = s.gatewayService.Forward(ctx, c, account, parsedReq)
+		default:
+			return 0, "", false, fmt.Errorf("target account platform %s does not support %s requests", account.Platform, m.reqType)
+		}
+	default:
+		return 0, "", false, fmt.Errorf("unsupported mirror request type %s", m.reqType)
+	}
+
+	return c.Writer.Status(), string(bytes.TrimSpace(w.bodyBytes())), w.truncated(), err
+}
+
+// trafficMirrorCaptureWriter 透传写入客户端的同时捕获主请求输出（超出上限部分丢弃）
+type trafficMirrorCaptureWriter struct {
+	gin.ResponseWriter
+	limit int
+	total int
+	buf   bytes.Buffer
+}
+
+func (w *trafficMirrorCaptureWriter) Write(b []byte) (int, error) {
+	w.capture(b)
+	return w.ResponseWriter.Write(b)
+}
+
+func (w *trafficMirrorCaptureWriter) WriteString(s string) (int, error) {
+	w.capture([]byte(s))
+	return w.ResponseWriter.WriteString(s)
+}
+
+func (w *trafficMirrorCaptureWriter) capture(b []byte) {
+	w.total += len(b)
+	if remaining := w.limit - w.buf.Len(); remaining > 0 {
+		_, _ = w.buf.Write(b[:min(len(b), remaining)])
+	}
+}
+
+func (w *trafficMirrorCaptureWriter) truncated() bool {
+	return w.total > w.limit
+}
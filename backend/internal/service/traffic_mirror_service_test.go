@@ -0,0 +1,144 @@
+//go:build unit
+
+package service
+
+import (
+	"context"
+	"net/http"
+	"net/http/httptest"
+	"strings"
+	"testing"
+	"time"
+
+	"github.com/gin-gonic/gin"
+	"github.com/stretchr/testify/require"
+)
+
+type trafficMirrorRepoStub struct {
+	TrafficMirrorRepository
+	rules []*TrafficMirrorRule
+}
+
+func (s *trafficMirrorRepoStub) ListRules(context.Context) ([]*TrafficMirrorRule, error) {
+	return s.rules, nil
+}
+
+func TestNormalizeTrafficMirrorRule(t *testing.T) {
+	groupID := int64(0)
+	rule := &TrafficMirrorRule{Name: " ab ", ModelPattern: " * ", SampleRate: 0.1, TargetAccountID: 7, GroupID: &groupID}
+	require.NoError(t, normalizeTrafficMirrorRule(rule))
+	require.Equal(t, "ab", rule.Name)
+	require.Empty(t, rule.ModelPattern)
+	require.Nil(t, rule.GroupID)
+
+	for _, bad := range []*TrafficMirrorRule{
+		nil,
+		{Name: "", SampleRate: 0.1, TargetAccountID: 1},
+		{Name: "x", SampleRate: 0, TargetAccountID: 1},
+		{Name: "x", SampleRate: 1.5, TargetAccountID: 1},
+		{Name: "x", SampleRate: 0.5},
+	} {
+		require.ErrorIs(t, normalizeTrafficMirrorRule(bad), ErrInvalidTrafficMirrorRule)
+	}
+}
+
+func TestPickTrafficMirrorRule(t *testing.T) {
+	groupA := int64(1)
+	groupB := int64(2)
+	rules := []*TrafficMirrorRule{
+		{ID: 1, Enabled: false, SampleRate: 1},
+		{ID: 2, Enabled: true, GroupID: &groupA, ModelPattern: "claude-opus-*", SampleRate: 0.5},
+		{ID: 3, Enabled: true, ModelPattern: "gpt-5", SampleRate: 1},
+	}
+	always := func() float64 { return 0 }
+	never := func() float64 { return 0.99 }
+
+	require.Equal(t, int64(2), pickTrafficMirrorRule(rules, &groupA, "Claude-Opus-4-5", always).ID)
+	require.Nil(t, pickTrafficMirrorRule(rules, &groupA, "claude-opus-4-5", never))
+	require.Nil(t, pickTrafficMirrorRule(rules, &groupB, "claude-opus-4-5", always))
+	require.Nil(t, pickTrafficMirrorRule(rules, nil, "claude-opus-4-5", always))
+	require.Equal(t, int64(3), pickTrafficMirrorRule(rules, nil, "gpt-5", never).ID)
+	require.Nil(t, pickTrafficMirrorRule(rules, nil, "gpt-5-mini", always))
+}
+
+func TestTrafficMirrorService_StartCapturesPrimaryOutput(t *testing.T) {
+	gin.SetMode(gin.TestMode)
+	svc := NewTrafficMirrorService(&trafficMirrorRepoStub{rules: []*TrafficMirrorRule{
+		{ID: 9, Enabled: true, ModelPattern: "m", SampleRate: 1, TargetAccountID: 3},
+	}}, nil, nil, nil, nil, nil, nil)
+
+	newContext := func() (*gin.Context, *httptest.ResponseRecorder) {
+		rec := httptest.NewRecorder()
+		c, _ := gin.CreateTestContext(rec)
+		c.Request = httptest.NewRequest(http.MethodPost, "/v1/messages", nil)
+		c.Request.Header.Set("Anthropic-Version", "2023-06-01")
+		c.Request.Header.Set("Authorization", "Bearer secret")
+		return c, rec
+	}
+
+	c, _ := newContext()
+	require.Nil(t, svc.Start(c, TrafficMirrorTypeMessages, &APIKey{ID: 1}, "other", []byte(`{}`)))
+
+	c, rec := newContext()
+	body := []byte(`{"model":"m"}`)
+	session := svc.Start(c, TrafficMirrorTypeMessages, &APIKey{ID: 1}, "m", body)
+	require.NotNil(t, session)
+	require.Equal(t, int64(9), session.rule.ID)
+	require.Equal(t, "/v1/messages", session.path)
+	require.Equal(t, "2023-06-01", session.headers.Get("anthropic-version"))
+	require.Empty(t, session.headers.Get("authorization"))
+
+	body[2] = 'x'
+	require.Equal(t, `{"model":"m"}`, string(session.body))
+
+	_, _ = c.Writer.WriteString(strings.Repeat("a", trafficMirrorCaptureLimit))
+	_, _ = c.Writer.Write([]byte("tail"))
+	require.Equal(t, trafficMirrorCaptureLimit+4, rec.Body.Len())
+	require.Equal(t, trafficMirrorCaptureLimit, session.capture.buf.Len())
+	require.True(t, session.capture.truncated())
+
+	var nilSession *TrafficMirrorSession
+	nilSession.Complete(1, time.Second)
+	var nilService *TrafficMirrorService
+	require.Nil(t, nilService.Start(c, TrafficMirrorTypeMessages, nil, "m", body))
+}
+
+func TestCompareTrafficMirrorSample(t *testing.T) {
+	anthropicJSON := `{"type":"message","content":[{"type":"text","text":"hi"},{"type":"tool_use","name":"get_weather","input":{}}],"stop_reason":"tool_use","usage":{"input_tokens":10,"output_tokens":12}}`
+	anthropicSSE := "event: message_start\ndata: {\"type\":\"message_start\",\"message\":{\"usage\":{\"input_tokens\":10}}}\n\n" +
+		"event: content_block_start\ndata: {\"type\":\"content_block_start\",\"index\":0,\"content_block\":{\"type\":\"text\",\"text\":\"\"}}\n\n" +
+		"event: content_block_start\ndata: {\"type\":\"content_block_start\",\"index\":1,\"content_block\":{\"type\":\"tool_use\",\"name\":\"get_weather\"}}\n\n" +
+		"event: message_delta\ndata: {\"type\":\"message_delta\",\"delta\":{\"stop_reason\":\"tool_use\"},\"usage\":{\"output_tokens\":20}}\n\n"
+
+	sample := &TrafficMirrorSample{PrimaryResponse: anthropicJSON, MirrorResponse: anthropicSSE}
+	compareTrafficMirrorSample(sample)
+	require.NotNil(t, sample.OutputMatch)
+	require.True(t, *sample.OutputMatch)
+	require.Equal(t, "tool_use", sample.PrimaryStopReason)
+	require.Equal(t, int64(12), sample.PrimaryOutputTokens)
+	require.Equal(t, int64(20), sample.MirrorOutputTokens)
+
+	sample = &TrafficMirrorSample{PrimaryResponse: anthropicJSON, MirrorResponse: `{"content":[{"type":"text","text":"x"}],"stop_reason":"end_turn"}`}
+	compareTrafficMirrorSample(sample)
+	require.NotNil(t, sample.OutputMatch)
+	require.False(t, *sample.OutputMatch)
+	require.Equal(t, "end_turn", sample.MirrorStopReason)
+
+	responsesSSE := "event: response.created\ndata: {\"type\":\"response.created\"}\n\n" +
+		"event: response.completed\ndata: {\"type\":\"response.completed\",\"response\":{\"status\":\"incomplete\",\"incomplete_details\":{\"reason\":\"max_output_tokens\"},\"output\":[{\"type\":\"reasoning\"},{\"type\":\"message\"}],\"usage\":{\"output_tokens\":64}}}\n\n"
+	out, ok := parseTrafficMirrorOutput(responsesSSE)
+	require.True(t, ok)
+	require.Equal(t, "reasoning,message", out.shape)
+	require.Equal(t, "incomplete:max_output_tokens", out.stopReason)
+	require.Equal(t, int64(64), out.outputTokens)
+
+	// 截断的输出与失败的镜像不参与对比
+	sample = &TrafficMirrorSample{PrimaryResponse: anthropicSSE[:80], MirrorResponse: anthropicJSON}
+	compareTrafficMirrorSample(sample)
+	require.Nil(t, sample.OutputMatch)
+	errMsg := "upstream returned status 529"
+	sample = &TrafficMirrorSample{PrimaryResponse: anthropicJSON, MirrorResponse: `{"type":"error"}`, MirrorError: &errMsg}
+	compareTrafficMirrorSample(sample)
+	require.Nil(t, sample.OutputMatch)
+	require.Equal(t, "tool_use", sample.PrimaryStopReason)
+}
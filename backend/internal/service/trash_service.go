@@ -0,0 +1,216 @@
+package service
+
+import (
+	"context"
+	"errors"
+	"fmt"
+	"sync"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	infraerrors "github.com/Wei-Shaw/sub2api/internal/pkg/errors"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/logger"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/pagination"
+)
+
+var (
+	ErrTrashItemNotFound = infraerrors.NotFound("TRASH_ITEM_NOT_FOUND", "item not found in trash")
+)
+
+// TrashedAPIKey 回收站中的 API Key
+type TrashedAPIKey struct {
+	ID        int64      `json:"id"`
+	UserID    int64      `json:"user_id"`
+	UserEmail string     `json:"user_email"`
+	Name      string     `json:"name"`
+	GroupID   *int64     `json:"group_id,omitempty"`
+	DeletedAt time.Time  `json:"deleted_at"`
+	PurgeAt   *time.Time `json:"purge_at,omitempty"`
+}
+
+// TrashedAccount 回收站中的上游账号
+type TrashedAccount struct {
+	ID        int64      `json:"id"`
+	Name      string     `json:"name"`
+	Platform  string     `json:"platform"`
+	Type      string     `json:"type"`
+	GroupIDs  []int64    `json:"group_ids"`
+	DeletedAt time.Time  `json:"deleted_at"`
+	PurgeAt   *time.Time `json:"purge_at,omitempty"`
+}
+
+// TrashRepository 访问已软删除（尚未清除）的 API Key 与账号
+type TrashRepository interface {
+	ListAPIKeys(ctx context.Context, params pagination.PaginationParams, search string) ([]TrashedAPIKey, *pagination.PaginationResult, error)
+	ListAccounts(ctx context.Context, params pagination.PaginationParams, search string) ([]TrashedAccount, *pagination.PaginationResult, error)
+	// RestoreAPIKey 恢复 API Key，返回 Key 值用于清理认证缓存；不存在或已清除时返回 ErrTrashItemNotFound
+	RestoreAPIKey(ctx context.Context, id int64) (string, error)
+	// RestoreAccount 恢复账号及其删除时的分组绑定；不存在或已清除时返回 ErrTrashItemNotFound
+	RestoreAccount(ctx context.Context, id int64) error
+	// PurgeAPIKeys 清除 before 之前删除的 API Key，返回清除数量
+	PurgeAPIKeys(ctx context.Context, before time.Time) (int64, error)
+	// PurgeAccounts 清除 before 之前删除的账号，返回清除数量
+	PurgeAccounts(ctx context.Context, before time.Time) (int64, error)
+}
+
+// TrashService 回收站：列出/恢复已删除的 API Key 与账号，并定期清除超过保留期的记录
+type TrashService struct {
+	repo          TrashRepository
+	apiKeyService *APIKeyService
+	cfg           *config.Config
+	leader        *LeaderElector
+
+	stopCh   chan struct{}
+	stopOnce sync.Once
+	wg       sync.WaitGroup
+}
+
+// NewTrashService 创建回收站服务
+func NewTrashService(repo TrashRepository, apiKeyService *APIKeyService, cfg *config.Config) *TrashService {
+	return &TrashService{
+		repo:          repo,
+		apiKeyService: apiKeyService,
+		cfg:           cfg,
+		stopCh:        make(chan struct{}),
+	}
+}
+
+// SetLeaderElector 多实例部署时仅由 leader 执行；需要在 Start() 之前调用
+func (s *TrashService) SetLeaderElector(leader *LeaderElector) {
+	s.leader = leader
+}
+
+func (s *TrashService) retention() time.Duration {
+	if s.cfg == nil {
+		return 0
+	}
+	return time.Duration(s.cfg.Trash.RetentionDays) * 24 * time.Hour
+}
+
+func (s *TrashService) purgeAt(deletedAt time.Time) *time.Time {
+	retention := s.retention()
+	if retention <= 0 {
+		return nil
+	}
+	t := deletedAt.Add(retention)
+	return &t
+}
+
+// ListAPIKeys 列出回收站中的 API Key
+func (s *TrashService) ListAPIKeys(ctx context.Context, params pagination.PaginationParams, search string) ([]TrashedAPIKey, *pagination.PaginationResult, error) {
+	items, result, err := s.repo.ListAPIKeys(ctx, params, search)
+	if err != nil {
+		return nil, nil, fmt.Errorf("list trashed api keys: %w", err)
+	}
+	for i := range items {
+		items[i].PurgeAt = s.purgeAt(items[i].DeletedAt)
+	}
+	return items, result, nil
+}
+
+// ListAccounts 列出回收站中的账号
+func (s *TrashService) ListAccounts(ctx context.Context, params pagination.PaginationParams, search string) ([]TrashedAccount, *pagination.PaginationResult, error) {
+	items, result, err := s.repo.ListAccounts(ctx, params, search)
+	if err != nil {
+		return nil, nil, fmt.Errorf("list trashed accounts: %w", err)
+	}
+	for i := range items {
+		items[i].PurgeAt = s.purgeAt(items[i].DeletedAt)
+	}
+	return items, result, nil
+}
+
+// RestoreAPIKey 从回收站恢复 API Key
+func (s *TrashService) RestoreAPIKey(ctx context.Context, id int64) error {
+	key, err := s.repo.RestoreAPIKey(ctx, id)
+	if err != nil {
+		return err
+	}
+	// 删除期间认证缓存可能记录了"Key 不存在"，恢复后需立即失效
+	if s.apiKeyService != nil {
+		s.apiKeyService.InvalidateAuthCacheByKey(ctx, key)
+	}
+	return nil
+}
+
+// RestoreAccount 从回收站恢复账号
+func (s *TrashService) RestoreAccount(ctx context.Context, id int64) error {
+	return s.repo.RestoreAccount(ctx, id)
+}
+
+// Start 启动后台清除任务（retention_days=0 时不启动）
+func (s *TrashService) Start() {
+	if s == nil || s.repo == nil || s.cfg == nil {
+		return
+	}
+	if s.retention() <= 0 {
+		logger.LegacyPrintf("service.trash", "[Trash] purge not started (retention_days=0)")
+		return
+	}
+	interval := time.Duration(s.cfg.Trash.PurgeIntervalMinutes) * time.Minute
+	if interval <= 0 {
+		interval = time.Hour
+	}
+
+	s.wg.Add(1)
+	go func() {
+		defer s.wg.Done()
+		ticker := time.NewTicker(interval)
+		defer ticker.Stop()
+
+		s.runOnce()
+		for {
+			select {
+			case <-ticker.C:
+				s.runOnce()
+			case <-s.stopCh:
+				return
+			}
+		}
+	}()
+}
+
+// Stop 停止后台清除任务
+func (s *TrashService) Stop() {
+	if s == nil {
+		return
+	}
+	s.stopOnce.Do(func() {
+		close(s.stopCh)
+	})
+	s.wg.Wait()
+}
+
+func (s *TrashService) runOnce() {
+	if !s.leader.IsLeader() {
+		return
+	}
+	ctx, cancel := context.WithTimeout(context.Background(), time.Minute)
+	defer cancel()
+	if err := s.PurgeExpired(ctx); err != nil {
+		logger.LegacyPrintf("service.trash", "[Trash] %v", err)
+	}
+}
+
+// PurgeExpired 清除超过保留期的 API Key 与账号（retention_days=0 时不执行）
+func (s *TrashService) PurgeExpired(ctx context.Context) error {
+	if s == nil || s.repo == nil || s.retention() <= 0 {
+		return nil
+	}
+	before := time.Now().Add(-s.retention())
+	var errs []error
+	keys, err := s.repo.PurgeAPIKeys(ctx, before)
+	if err != nil {
+		errs = append(errs, fmt.Errorf("purge api keys failed: %w", err))
+	} else if keys > 0 {
+		logger.LegacyPrintf("service.trash", "[Trash] purged %d api keys deleted before %s", keys, before.Format(time.RFC3339))
+	}
+
+	accounts, err := s.repo.PurgeAccounts(ctx, before)
+	if err != nil {
+		errs = append(errs, fmt.Errorf("purge accounts failed: %w", err))
+	} else if accounts > 0 {
+		logger.LegacyPrintf("service.trash", "[Trash] purged %d accounts deleted before %s", accounts, before.Format(time.RFC3339))
+	}
+	return errors.Join(errs...)
+}
@@ -0,0 +1,98 @@
+//go:build unit
+
+package service
+
+import (
+	"context"
+	"testing"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/pagination"
+	"github.com/stretchr/testify/require"
+)
+
+type trashRepoStub struct {
+	keys          []TrashedAPIKey
+	restoredKeys  map[int64]string
+	purgeBefore   []time.Time
+	purgedKeys    int64
+	purgedAccount int64
+}
+
+func (r *trashRepoStub) ListAPIKeys(ctx context.Context, params pagination.PaginationParams, search string) ([]TrashedAPIKey, *pagination.PaginationResult, error) {
+	return r.keys, &pagination.PaginationResult{Total: int64(len(r.keys))}, nil
+}
+
+func (r *trashRepoStub) ListAccounts(ctx context.Context, params pagination.PaginationParams, search string) ([]TrashedAccount, *pagination.PaginationResult, error) {
+	return nil, &pagination.PaginationResult{}, nil
+}
+
+func (r *trashRepoStub) RestoreAPIKey(ctx context.Context, id int64) (string, error) {
+	key, ok := r.restoredKeys[id]
+	if !ok {
+		return "", ErrTrashItemNotFound
+	}
+	return key, nil
+}
+
+func (r *trashRepoStub) RestoreAccount(ctx context.Context, id int64) error {
+	return ErrTrashItemNotFound
+}
+
+func (r *trashRepoStub) PurgeAPIKeys(ctx context.Context, before time.Time) (int64, error) {
+	r.purgeBefore = append(r.purgeBefore, before)
+	return r.purgedKeys, nil
+}
+
+func (r *trashRepoStub) PurgeAccounts(ctx context.Context, before time.Time) (int64, error) {
+	r.purgeBefore = append(r.purgeBefore, before)
+	return r.purgedAccount, nil
+}
+
+func TestTrashService_ListComputesPurgeTime(t *testing.T) {
+	deletedAt := time.Date(2026, 1, 1, 0, 0, 0, 0, time.UTC)
+	repo := &trashRepoStub{keys: []TrashedAPIKey{{ID: 1, DeletedAt: deletedAt}}}
+	svc := NewTrashService(repo, nil, &config.Config{Trash: config.TrashConfig{RetentionDays: 7}})
+
+	items, _, err := svc.ListAPIKeys(context.Background(), pagination.DefaultPagination(), "")
+	require.NoError(t, err)
+	require.Len(t, items, 1)
+	require.NotNil(t, items[0].PurgeAt)
+	require.Equal(t, deletedAt.Add(7*24*time.Hour), *items[0].PurgeAt)
+
+	// retention_days=0 表示永久保留
+	svc = NewTrashService(repo, nil, &config.Config{})
+	items, _, err = svc.ListAPIKeys(context.Background(), pagination.DefaultPagination(), "")
+	require.NoError(t, err)
+	require.Nil(t, items[0].PurgeAt)
+}
+
+func TestTrashService_Restore(t *testing.T) {
+	repo := &trashRepoStub{restoredKeys: map[int64]string{1: "sk-restored"}}
+	svc := NewTrashService(repo, nil, &config.Config{Trash: config.TrashConfig{RetentionDays: 30}})
+
+	require.NoError(t, svc.RestoreAPIKey(context.Background(), 1))
+	require.ErrorIs(t, svc.RestoreAPIKey(context.Background(), 2), ErrTrashItemNotFound)
+	require.ErrorIs(t, svc.RestoreAccount(context.Background(), 1), ErrTrashItemNotFound)
+}
+
+func TestTrashService_RunOncePurgesExpired(t *testing.T) {
+	repo := &trashRepoStub{purgedKeys: 2, purgedAccount: 1}
+	svc := NewTrashService(repo, nil, &config.Config{Trash: config.TrashConfig{RetentionDays: 30, PurgeIntervalMinutes: 60}})
+
+	svc.runOnce()
+	require.Len(t, repo.purgeBefore, 2)
+	for _, before := range repo.purgeBefore {
+		require.WithinDuration(t, time.Now().Add(-30*24*time.Hour), before, time.Minute)
+	}
+}
+
+func TestTrashService_StartSkippedWithoutRetention(t *testing.T) {
+	repo := &trashRepoStub{}
+	svc := NewTrashService(repo, nil, &config.Config{Trash: config.TrashConfig{PurgeIntervalMinutes: 60}})
+
+	svc.Start()
+	svc.Stop()
+	require.Empty(t, repo.purgeBefore)
+}
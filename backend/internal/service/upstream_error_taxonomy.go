@@ -0,0 +1,216 @@
+package service
+
+import (
+	"context"
+	"errors"
+	"net"
+	"net/http"
+	"strings"
+
+	"github.com/gin-gonic/gin"
+	"github.com/tidwall/gjson"
+)
+
+// UpstreamErrorClass 上游错误的统一分类，与具体平台的错误格式无关
+type UpstreamErrorClass string
+
+const (
+	UpstreamErrorAuthExpired      UpstreamErrorClass = "auth_expired"
+	UpstreamErrorPermissionDenied UpstreamErrorClass = "permission_denied"
+	UpstreamErrorQuotaExhausted   UpstreamErrorClass = "quota_exhausted"
+	UpstreamErrorRateLimited      UpstreamErrorClass = "rate_limited"
+	UpstreamErrorContentFiltered  UpstreamErrorClass = "content_filtered"
+	UpstreamErrorOverloaded       UpstreamErrorClass = "overloaded"
+	UpstreamErrorServerError      UpstreamErrorClass = "server_error"
+	UpstreamErrorTimeout          UpstreamErrorClass = "timeout"
+	UpstreamErrorNetwork          UpstreamErrorClass = "network"
+	UpstreamErrorUnknown          UpstreamErrorClass = "unknown"
+)
+
+// UpstreamErrorFormat 客户端期望的错误响应格式
+type UpstreamErrorFormat int
+
+const (
+	// UpstreamErrorFormatAnthropic {"type":"error","error":{"type":"...","message":"..."}}
+	UpstreamErrorFormatAnthropic UpstreamErrorFormat = iota
+	// UpstreamErrorFormatOpenAI {"error":{"type":"...","message":"...","code":"..."}}
+	UpstreamErrorFormatOpenAI
+)
+
+// UpstreamErrorResponse 分类映射后返回给客户端的错误
+type UpstreamErrorResponse struct {
+	Status  int
+	Type    string
+	Message string
+	Code    string
+}
+
+var (
+	contentFilterMarkers = []string{
+		"content_filter",
+		"content_policy",
+		"content filtering policy",
+		"prohibited_content",
+		"responsible ai policy",
+		"blocked by safety",
+	}
+	quotaExhaustedMarkers = []string{
+		"insufficient_quota",
+		"billing_hard_limit",
+		"billing_not_active",
+		"exceeded your current quota",
+		"credit balance is too low",
+		"insufficient balance",
+	}
+	authExpiredMarkers = []string{
+		"token expired",
+		"token has expired",
+		"invalid_grant",
+		"invalid_api_key",
+		"authentication_error",
+	}
+)
+
+// ClassifyUpstreamError 根据上游 HTTP 状态码与响应体将错误归入统一分类。
+// 响应体中的错误码/消息优先于状态码：例如 429 insufficient_quota 归为额度耗尽而不是限流，
+// 400 content_filter 归为内容过滤。
+func ClassifyUpstreamError(statusCode int, body []byte) UpstreamErrorClass {
+	hint := upstreamErrorHint(body)
+	switch {
+	case containsAnyMarker(hint, contentFilterMarkers):
+		return UpstreamErrorContentFiltered
+	case statusCode == http.StatusPaymentRequired || containsAnyMarker(hint, quotaExhaustedMarkers):
+		return UpstreamErrorQuotaExhausted
+	case statusCode == http.StatusUnauthorized || containsAnyMarker(hint, authExpiredMarkers):
+		return UpstreamErrorAuthExpired
+	case statusCode == http.StatusForbidden:
+		return UpstreamErrorPermissionDenied
+	case statusCode == http.StatusTooManyRequests:
+		return UpstreamErrorRateLimited
+	case statusCode == 529 || strings.Contains(hint, "overloaded"):
+		return UpstreamErrorOverloaded
+	case statusCode == http.StatusRequestTimeout || statusCode == http.StatusGatewayTimeout:
+		return UpstreamErrorTimeout
+	case statusCode >= 500:
+		return UpstreamErrorServerError
+	}
+	return UpstreamErrorUnknown
+}
+
+// ClassifyUpstreamRequestError 对未拿到上游响应的请求错误（连接失败、超时等）分类
+func ClassifyUpstreamRequestError(err error) UpstreamErrorClass {
+	if err == nil {
+		return UpstreamErrorUnknown
+	}
+	if _, ok := AsUpstreamTimeoutError(err); ok {
+		return UpstreamErrorTimeout
+	}
+	if errors.Is(err, context.DeadlineExceeded) {
+		return UpstreamErrorTimeout
+	}
+	var netErr net.Error
+	if errors.As(err, &netErr) && netErr.Timeout() {
+		return UpstreamErrorTimeout
+	}
+	return UpstreamErrorNetwork
+}
+
+// MapUpstreamError 将分类映射为客户端状态码、错误类型与消息。
+// 状态码在两种格式间保持一致，不回显上游的详细错误信息。
+func MapUpstreamError(class UpstreamErrorClass, format UpstreamErrorFormat) UpstreamErrorResponse {
+	resp := UpstreamErrorResponse{Status: http.StatusBadGateway, Type: "upstream_error", Code: string(class)}
+	switch class {
+	case UpstreamErrorAuthExpired:
+		resp.Message = "Upstream authentication failed, please contact administrator"
+	case UpstreamErrorPermissionDenied:
+		resp.Message = "Upstream access forbidden, please contact administrator"
+	case UpstreamErrorQuotaExhausted:
+		resp.Status = http.StatusTooManyRequests
+		resp.Type = "rate_limit_error"
+		resp.Message = "Upstream quota exhausted, please retry later"
+	case UpstreamErrorRateLimited:
+		resp.Status = http.StatusTooManyRequests
+		resp.Type = "rate_limit_error"
+		resp.Message = "Upstream rate limit exceeded, please retry later"
+	case UpstreamErrorContentFiltered:
+		resp.Status = http.StatusBadRequest
+		resp.Type = "invalid_request_error"
+		resp.Message = "Request was rejected by the upstream content filter"
+	case UpstreamErrorOverloaded:
+		resp.Status = http.StatusServiceUnavailable
+		if format == UpstreamErrorFormatAnthropic {
+			resp.Type = "overloaded_error"
+		}
+		resp.Message = "Upstream service overloaded, please retry later"
+	case UpstreamErrorServerError:
+		resp.Message = "Upstream service temporarily unavailable"
+	case UpstreamErrorTimeout:
+		resp.Status = http.StatusGatewayTimeout
+		resp.Type = "timeout_error"
+		resp.Message = "Upstream request timed out"
+	default:
+		resp.Message = "Upstream request failed"
+	}
+	return resp
+}
+
+// writeUpstreamError 按客户端格式写出映射后的错误
+func writeUpstreamError(c *gin.Context, format UpstreamErrorFormat, resp UpstreamErrorResponse) {
+	c.Set(RequestLogErrorClassKey, resp.Code)
+	if format == UpstreamErrorFormatOpenAI {
+		c.JSON(resp.Status, gin.H{
+			"error": gin.H{
+				"type":    resp.Type,
+				"message": resp.Message,
+				"code":    resp.Code,
+			},
+		})
+		return
+	}
+	c.JSON(resp.Status, gin.H{
+		"type": "error",
+		"error": gin.H{
+			"type":    resp.Type,
+			"message": resp.Message,
+		},
+	})
+}
+
+// writeUpstreamRequestError 写出未拿到上游响应时的错误；分阶段超时保留其具体阶段与时长
+func writeUpstreamRequestError(c *gin.Context, format UpstreamErrorFormat, err error) {
+	if timeoutErr, ok := AsUpstreamTimeoutError(err); ok {
+		if format == UpstreamErrorFormatOpenAI {
+			writeUpstreamTimeoutOpenAIError(c, timeoutErr)
+		} else {
+			writeUpstreamTimeoutClaudeError(c, timeoutErr)
+		}
+		return
+	}
+	writeUpstreamError(c, format, MapUpstreamError(ClassifyUpstreamRequestError(err), format))
+}
+
+// upstreamErrorHint 提取响应体中用于分类的错误码、类型、状态与消息（小写）
+func upstreamErrorHint(body []byte) string {
+	if len(body) == 0 || !gjson.ValidBytes(body) {
+		return strings.ToLower(string(body))
+	}
+	parts := make([]string, 0, 5)
+	for _, path := range []string{"error.code", "error.type", "error.status", "error.message", "message"} {
+		if v := gjson.GetBytes(body, path).String(); v != "" {
+			parts = append(parts, v)
+		}
+	}
+	return strings.ToLower(strings.Join(parts, " "))
+}
+
+func containsAnyMarker(s string, markers []string) bool {
+	if s == "" {
+		return false
+	}
+	for _, marker := range markers {
+		if strings.Contains(s, marker) {
+			return true
+		}
+	}
+	return false
+}
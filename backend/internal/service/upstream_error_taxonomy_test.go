@@ -0,0 +1,104 @@
+//go:build unit
+
+package service
+
+import (
+	"context"
+	"encoding/json"
+	"errors"
+	"net/http"
+	"net/http/httptest"
+	"testing"
+	"time"
+
+	"github.com/gin-gonic/gin"
+	"github.com/stretchr/testify/require"
+)
+
+func TestClassifyUpstreamError(t *testing.T) {
+	tests := []struct {
+		name   string
+		status int
+		body   string
+		want   UpstreamErrorClass
+	}{
+		{name: "401", status: 401, body: `{"error":{"message":"invalid x-api-key"}}`, want: UpstreamErrorAuthExpired},
+		{name: "expired token on 400", status: 400, body: `{"error":{"message":"OAuth token has expired"}}`, want: UpstreamErrorAuthExpired},
+		{name: "403", status: 403, body: `{"type":"error","error":{"type":"permission_error","message":"no access"}}`, want: UpstreamErrorPermissionDenied},
+		{name: "openai insufficient quota", status: 429, body: `{"error":{"type":"insufficient_quota","code":"insufficient_quota","message":"You exceeded your current quota"}}`, want: UpstreamErrorQuotaExhausted},
+		{name: "anthropic credit balance", status: 400, body: `{"type":"error","error":{"type":"invalid_request_error","message":"Your credit balance is too low"}}`, want: UpstreamErrorQuotaExhausted},
+		{name: "402", status: 402, want: UpstreamErrorQuotaExhausted},
+		{name: "429", status: 429, body: `{"error":{"type":"rate_limit_error","message":"slow down"}}`, want: UpstreamErrorRateLimited},
+		{name: "content filter", status: 400, body: `{"error":{"code":"content_filter","message":"blocked"}}`, want: UpstreamErrorContentFiltered},
+		{name: "529", status: 529, want: UpstreamErrorOverloaded},
+		{name: "503 overloaded", status: 503, body: `{"type":"error","error":{"type":"overloaded_error","message":"Overloaded"}}`, want: UpstreamErrorOverloaded},
+		{name: "504", status: 504, want: UpstreamErrorTimeout},
+		{name: "500", status: 500, body: `<html>Internal Server Error</html>`, want: UpstreamErrorServerError},
+		{name: "422", status: 422, body: `{"error":{"message":"Invalid schema"}}`, want: UpstreamErrorUnknown},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			require.Equal(t, tt.want, ClassifyUpstreamError(tt.status, []byte(tt.body)))
+		})
+	}
+}
+
+func TestClassifyUpstreamRequestError(t *testing.T) {
+	require.Equal(t, UpstreamErrorTimeout, ClassifyUpstreamRequestError(&UpstreamTimeoutError{Phase: UpstreamTimeoutPhaseConnect, Timeout: time.Second}))
+	require.Equal(t, UpstreamErrorTimeout, ClassifyUpstreamRequestError(context.DeadlineExceeded))
+	require.Equal(t, UpstreamErrorNetwork, ClassifyUpstreamRequestError(errors.New("connection refused")))
+}
+
+func TestMapUpstreamError_ConsistentStatusAcrossFormats(t *testing.T) {
+	classes := []UpstreamErrorClass{
+		UpstreamErrorAuthExpired, UpstreamErrorPermissionDenied, UpstreamErrorQuotaExhausted,
+		UpstreamErrorRateLimited, UpstreamErrorContentFiltered, UpstreamErrorOverloaded,
+		UpstreamErrorServerError, UpstreamErrorTimeout, UpstreamErrorNetwork, UpstreamErrorUnknown,
+	}
+	for _, class := range classes {
+		anthropic := MapUpstreamError(class, UpstreamErrorFormatAnthropic)
+		openai := MapUpstreamError(class, UpstreamErrorFormatOpenAI)
+		require.Equal(t, anthropic.Status, openai.Status, class)
+		require.NotEmpty(t, anthropic.Message, class)
+	}
+
+	overloaded := MapUpstreamError(UpstreamErrorOverloaded, UpstreamErrorFormatAnthropic)
+	require.Equal(t, http.StatusServiceUnavailable, overloaded.Status)
+	require.Equal(t, "overloaded_error", overloaded.Type)
+	require.Equal(t, "upstream_error", MapUpstreamError(UpstreamErrorOverloaded, UpstreamErrorFormatOpenAI).Type)
+}
+
+func TestWriteUpstreamError_Formats(t *testing.T) {
+	gin.SetMode(gin.TestMode)
+	mapped := MapUpstreamError(UpstreamErrorQuotaExhausted, UpstreamErrorFormatOpenAI)
+
+	rec := httptest.NewRecorder()
+	c, _ := gin.CreateTestContext(rec)
+	writeUpstreamError(c, UpstreamErrorFormatOpenAI, mapped)
+	require.Equal(t, http.StatusTooManyRequests, rec.Code)
+	var openaiBody struct {
+		Error struct {
+			Type string `json:"type"`
+			Code string `json:"code"`
+		} `json:"error"`
+	}
+	require.NoError(t, json.Unmarshal(rec.Body.Bytes(), &openaiBody))
+	require.Equal(t, "rate_limit_error", openaiBody.Error.Type)
+	require.Equal(t, "quota_exhausted", openaiBody.Error.Code)
+
+	rec = httptest.NewRecorder()
+	c, _ = gin.CreateTestContext(rec)
+	writeUpstreamRequestError(c, UpstreamErrorFormatAnthropic, errors.New("dial tcp: connection refused"))
+	require.Equal(t, http.StatusBadGateway, rec.Code)
+	var anthropicBody struct {
+		Type  string `json:"type"`
+		Error struct {
+			Type    string `json:"type"`
+			Message string `json:"message"`
+		} `json:"error"`
+	}
+	require.NoError(t, json.Unmarshal(rec.Body.Bytes(), &anthropicBody))
+	require.Equal(t, "error", anthropicBody.Type)
+	require.Equal(t, "upstream_error", anthropicBody.Error.Type)
+	require.Equal(t, "Upstream request failed", anthropicBody.Error.Message)
+}
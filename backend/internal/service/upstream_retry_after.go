@@ -0,0 +1,141 @@
+package service
+
+import (
+	"context"
+	"math"
+	"net/http"
+	"strconv"
+	"strings"
+	"time"
+
+	"github.com/gin-gonic/gin"
+)
+
+// maxGatewayRetryAfter 返回给客户端的 Retry-After 上限，避免长周期窗口（如 7d）让客户端长时间放弃重试
+const maxGatewayRetryAfter = time.Hour
+
+// upstreamResetDurationHeaders OpenAI 风格的重置时长头（如 "1s"、"6m0s"）
+var upstreamResetDurationHeaders = []string{
+	"x-ratelimit-reset-requests",
+	"x-ratelimit-reset-tokens",
+}
+
+// upstreamResetTimestampHeaders Anthropic 风格的重置时间头（RFC3339）
+var upstreamResetTimestampHeaders = []string{
+	"anthropic-ratelimit-requests-reset",
+	"anthropic-ratelimit-tokens-reset",
+	"anthropic-ratelimit-input-tokens-reset",
+	"anthropic-ratelimit-output-tokens-reset",
+}
+
+// parseUpstreamRetryAfter 从上游限流响应头解析需等待的时长。
+// 优先使用 Retry-After（秒或 HTTP-date）与 retry-after-ms；没有时取各维度重置头中最晚的一个，
+// 因为任一维度未恢复时请求仍会被拒绝。
+func parseUpstreamRetryAfter(headers http.Header, now time.Time) (time.Duration, bool) {
+	if headers == nil {
+		return 0, false
+	}
+	if v := strings.TrimSpace(headers.Get("retry-after-ms")); v != "" {
+		if ms, err := strconv.ParseFloat(v, 64); err == nil && ms > 0 {
+			return time.Duration(ms * float64(time.Millisecond)), true
+		}
+	}
+	if v := strings.TrimSpace(headers.Get("Retry-After")); v != "" {
+		if secs, err := strconv.ParseFloat(v, 64); err == nil {
+			if secs > 0 {
+				return time.Duration(secs * float64(time.Second)), true
+			}
+		} else if at, err := http.ParseTime(v); err == nil && at.After(now) {
+			return at.Sub(now), true
+		}
+	}
+
+	var longest time.Duration
+	for _, key := range upstreamResetDurationHeaders {
+		if d, err := time.ParseDuration(strings.TrimSpace(headers.Get(key))); err == nil && d > longest {
+			longest = d
+		}
+	}
+	for _, key := range upstreamResetTimestampHeaders {
+		if at, err := time.Parse(time.RFC3339, strings.TrimSpace(headers.Get(key))); err == nil && at.Sub(now) > longest {
+			longest = at.Sub(now)
+		}
+	}
+	return longest, longest > 0
+}
+
+// estimateGatewayRetryAfter 根据网关自身记录的账号限流/过载/临时不可调度窗口，
+// 估算最早有账号恢复可用的等待时长。任一账号已可调度或无法查询时返回 false（不建议等待时长）。
+func estimateGatewayRetryAfter(ctx context.Context, accountRepo AccountRepository, accountIDs map[int64]struct{}) (time.Duration, bool) {
+	if accountRepo == nil || len(accountIDs) == 0 {
+		return 0, false
+	}
+	ids := make([]int64, 0, len(accountIDs))
+	for id := range accountIDs {
+		ids = append(ids, id)
+	}
+	accounts, err := accountRepo.GetByIDs(ctx, ids)
+	if err != nil || len(accounts) == 0 {
+		return 0, false
+	}
+
+	now := time.Now()
+	soonest := time.Duration(0)
+	for _, account := range accounts {
+		if account == nil {
+			continue
+		}
+		var until time.Time
+		for _, t := range []*time.Time{account.RateLimitResetAt, account.OverloadUntil, account.TempUnschedulableUntil} {
+			if t != nil && t.After(until) {
+				until = *t
+			}
+		}
+		if !until.After(now) {
+			return 0, false
+		}
+		if wait := until.Sub(now); soonest == 0 || wait < soonest {
+			soonest = wait
+		}
+	}
+	if soonest <= 0 {
+		return 0, false
+	}
+	return min(soonest, maxGatewayRetryAfter), true
+}
+
+// SetGatewayRetryAfterHeaders 写入网关视角的 Retry-After（秒，向上取整）与 X-RateLimit-Reset-Requests
+func SetGatewayRetryAfterHeaders(h http.Header, retryAfter time.Duration) {
+	if retryAfter <= 0 {
+		return
+	}
+	secs := int(math.Ceil(retryAfter.Seconds()))
+	h.Set("Retry-After", strconv.Itoa(secs))
+	h.Set("X-RateLimit-Reset-Requests", strconv.Itoa(secs)+"s")
+}
+
+// setAccountRetryAfterHeaders 直接返回限流/过载类错误（未换号）时，按该账号在网关中的限流窗口设置 Retry-After
+func setAccountRetryAfterHeaders(ctx context.Context, c *gin.Context, accountRepo AccountRepository, status int, accountID int64) {
+	if status != http.StatusTooManyRequests && status != http.StatusServiceUnavailable {
+		return
+	}
+	if wait, ok := estimateGatewayRetryAfter(ctx, accountRepo, map[int64]struct{}{accountID: {}}); ok {
+		SetGatewayRetryAfterHeaders(c.Writer.Header(), wait)
+	}
+}
+
+// RetryAfter 估算失败账号集合中最早恢复可用的等待时长，用于失败响应的 Retry-After
+func (s *GatewayService) RetryAfter(ctx context.Context, accountIDs map[int64]struct{}) (time.Duration, bool) {
+	if s == nil {
+		return 0, false
+	}
+	return estimateGatewayRetryAfter(ctx, s.accountRepo, accountIDs)
+}
+
+// RetryAfter 估算失败账号集合中最早恢复可用的等待时长，用于失败响应的 Retry-After
+func (s *OpenAIGatewayService) RetryAfter(ctx context.Context, accountIDs map[int64]struct{}) (time.Duration, bool) {
+	if s == nil {
+		return 0, false
+	}
+	return estimateGatewayRetryAfter(ctx, s.accountRepo, accountIDs)
+}
@@ -0,0 +1,79 @@
+//go:build unit
+
+package service
+
+import (
+	"context"
+	"net/http"
+	"testing"
+	"time"
+
+	"github.com/stretchr/testify/require"
+)
+
+func TestParseUpstreamRetryAfter(t *testing.T) {
+	now := time.Date(2026, 3, 1, 10, 0, 0, 0, time.UTC)
+	tests := []struct {
+		name    string
+		headers http.Header
+		want    time.Duration
+		ok      bool
+	}{
+		{name: "seconds", headers: http.Header{"Retry-After": {"30"}}, want: 30 * time.Second, ok: true},
+		{name: "http date", headers: http.Header{"Retry-After": {now.Add(2 * time.Minute).Format(http.TimeFormat)}}, want: 2 * time.Minute, ok: true},
+		{name: "milliseconds preferred", headers: http.Header{"Retry-After": {"30"}, "Retry-After-Ms": {"1500"}}, want: 1500 * time.Millisecond, ok: true},
+		{
+			name:    "openai reset headers use the longest",
+			headers: http.Header{"X-Ratelimit-Reset-Requests": {"1s"}, "X-Ratelimit-Reset-Tokens": {"6m0s"}},
+			want:    6 * time.Minute,
+			ok:      true,
+		},
+		{
+			name:    "anthropic reset timestamp",
+			headers: http.Header{"Anthropic-Ratelimit-Tokens-Reset": {now.Add(45 * time.Second).Format(time.RFC3339)}},
+			want:    45 * time.Second,
+			ok:      true,
+		},
+		{name: "past date", headers: http.Header{"Retry-After": {now.Add(-time.Minute).Format(http.TimeFormat)}}},
+		{name: "missing", headers: http.Header{}},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			got, ok := parseUpstreamRetryAfter(tt.headers, now)
+			require.Equal(t, tt.ok, ok)
+			require.Equal(t, tt.want, got)
+		})
+	}
+}
+
+func TestEstimateGatewayRetryAfter(t *testing.T) {
+	now := time.Now()
+	soon := now.Add(20 * time.Second)
+	later := now.Add(5 * time.Minute)
+	weekly := now.Add(7 * 24 * time.Hour)
+	repo := &mockAccountRepoForPlatform{accountsByID: map[int64]*Account{
+		1: {ID: 1, RateLimitResetAt: &later},
+		2: {ID: 2, OverloadUntil: &soon},
+		3: {ID: 3},
+		4: {ID: 4, RateLimitResetAt: &weekly},
+	}}
+
+	wait, ok := estimateGatewayRetryAfter(context.Background(), repo, map[int64]struct{}{1: {}, 2: {}})
+	require.True(t, ok)
+	require.InDelta(t, (20 * time.Second).Seconds(), wait.Seconds(), 1)
+
+	// 有账号已可调度时不给出等待时长
+	_, ok = estimateGatewayRetryAfter(context.Background(), repo, map[int64]struct{}{1: {}, 3: {}})
+	require.False(t, ok)
+
+	wait, ok = estimateGatewayRetryAfter(context.Background(), repo, map[int64]struct{}{4: {}})
+	require.True(t, ok)
+	require.Equal(t, maxGatewayRetryAfter, wait)
+}
+
+func TestSetGatewayRetryAfterHeaders(t *testing.T) {
+	h := http.Header{}
+	SetGatewayRetryAfterHeaders(h, 1500*time.Millisecond)
+	require.Equal(t, "2", h.Get("Retry-After"))
+	require.Equal(t, "2s", h.Get("X-RateLimit-Reset-Requests"))
+}
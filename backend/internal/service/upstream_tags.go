@@ -0,0 +1,228 @@
+package service
+
+import (
+	"net/http"
+	"regexp"
+	"sort"
+	"strings"
+
+	infraerrors "github.com/Wei-Shaw/sub2api/internal/pkg/errors"
+)
+
+const (
+	// upstreamTagMaxHeaders 单个策略允许的自定义请求头数量上限
+	upstreamTagMaxHeaders = 16
+	// upstreamTagMaxHeaderValueLen 自定义请求头值最大长度（字节）
+	upstreamTagMaxHeaderValueLen = 256
+	// upstreamTagMaxMetadata metadata 键数量上限（与 OpenAI Responses API 一致）
+	upstreamTagMaxMetadata = 16
+	// upstreamTagMaxMetadataKeyLen / upstreamTagMaxMetadataValueLen 与 OpenAI Responses API 的 metadata 限制一致
+	upstreamTagMaxMetadataKeyLen   = 64
+	upstreamTagMaxMetadataValueLen = 512
+)
+
+var (
+	ErrUpstreamHeaderNotAllowed = infraerrors.BadRequest("UPSTREAM_HEADER_NOT_ALLOWED", "upstream header is not in gateway.upstream_header_allowlist or is reserved")
+	ErrInvalidUpstreamHeader    = infraerrors.BadRequest("INVALID_UPSTREAM_HEADER", "upstream header name or value is invalid or too long")
+	ErrInvalidUpstreamMetadata  = infraerrors.BadRequest("INVALID_UPSTREAM_METADATA", "upstream metadata has too many keys or a key/value is too long")
+)
+
+var upstreamHeaderNamePattern = regexp.MustCompile(`^[a-z0-9][a-z0-9-]*$`)
+
+// reservedUpstreamHeaders 认证、路由与协议相关的请求头，即使出现在白名单中也不允许由策略设置
+var reservedUpstreamHeaders = map[string]struct{}{
+	"authorization":      {},
+	"cookie":             {},
+	"host":               {},
+	"content-length":     {},
+	"content-type":       {},
+	"content-encoding":   {},
+	"transfer-encoding":  {},
+	"accept-encoding":    {},
+	"connection":         {},
+	"keep-alive":         {},
+	"te":                 {},
+	"trailer":            {},
+	"upgrade":            {},
+	"user-agent":         {},
+	"x-api-key":          {},
+	"x-real-ip":          {},
+	"chatgpt-account-id": {},
+	"originator":         {},
+	"version":            {},
+}
+
+// reservedUpstreamHeaderPrefixes 保留的请求头前缀（上游协议头、SDK 指纹头、代理 / 转发头）
+var reservedUpstreamHeaderPrefixes = []string{
+	"anthropic-", "openai-", "x-stainless-", "x-goog-", "proxy-", "sec-", "x-forwarded-", "cf-",
+}
+
+// UpstreamTagPolicy 附加到上游请求的自定义请求头与 metadata（APIKeyPolicy.UpstreamTags），
+// 用于为代理方自身的流量统计打标
+type UpstreamTagPolicy struct {
+	// Headers 请求头名称须匹配 gateway.upstream_header_allowlist 且不属于保留请求头；覆盖客户端传入的同名请求头
+	Headers map[string]string `json:"headers,omitempty"`
+	// Metadata 写入 OpenAI Responses 请求的 metadata（仅 OpenAI API Key 账号，透传模式除外），覆盖客户端同名字段
+	Metadata map[string]string `json:"metadata,omitempty"`
+}
+
+// normalize 校验并规范化上游标记策略；返回 false 表示配置为空，调用方应置为 nil
+func (p *UpstreamTagPolicy) normalize() (bool, error) {
+	if len(p.Headers) > upstreamTagMaxHeaders {
+		return false, ErrInvalidUpstreamHeader
+	}
+	headers := make(map[string]string, len(p.Headers))
+	for name, value := range p.Headers {
+		name = strings.ToLower(strings.TrimSpace(name))
+		value = strings.TrimSpace(value)
+		if !upstreamHeaderNamePattern.MatchString(name) || value == "" || len(value) > upstreamTagMaxHeaderValueLen || strings.ContainsAny(value, "\r\n\x00") {
+			return false, ErrInvalidUpstreamHeader.WithMetadata(map[string]string{"header": name})
+		}
+		if upstreamHeaderReserved(name) {
+			return false, ErrUpstreamHeaderNotAllowed.WithMetadata(map[string]string{"header": name})
+		}
+		if _, dup := headers[name]; dup {
+			return false, ErrInvalidUpstreamHeader.WithMetadata(map[string]string{"header": name})
+		}
+		headers[name] = value
+	}
+
+	if len(p.Metadata) > upstreamTagMaxMetadata {
+		return false, ErrInvalidUpstreamMetadata
+	}
+	metadata := make(map[string]string, len(p.Metadata))
+	for key, value := range p.Metadata {
+		key = strings.TrimSpace(key)
+		if key == "" || len(key) > upstreamTagMaxMetadataKeyLen || len(value) > upstreamTagMaxMetadataValueLen {
+			return false, ErrInvalidUpstreamMetadata.WithMetadata(map[string]string{"key": key})
+		}
+		if _, dup := metadata[key]; dup {
+			return false, ErrInvalidUpstreamMetadata.WithMetadata(map[string]string{"key": key})
+		}
+		metadata[key] = value
+	}
+
+	if len(headers) == 0 && len(metadata) == 0 {
+		return false, nil
+	}
+	p.Headers, p.Metadata = nil, nil
+	if len(headers) > 0 {
+		p.Headers = headers
+	}
+	if len(metadata) > 0 {
+		p.Metadata = metadata
+	}
+	return true, nil
+}
+
+// checkAllowlist 校验请求头均在白名单中；白名单来自配置且可能收紧，因此由保存策略的服务校验而非 normalize
+func (p *UpstreamTagPolicy) checkAllowlist(allowlist *UpstreamHeaderAllowlist) error {
+	if p == nil {
+		return nil
+	}
+	for name := range p.Headers {
+		if !allowlist.Allowed(name) {
+			return ErrUpstreamHeaderNotAllowed.WithMetadata(map[string]string{"header": name})
+		}
+	}
+	return nil
+}
+
+// UpstreamHeaderAllowlist 策略可设置的上游请求头白名单（gateway.upstream_header_allowlist）
+type UpstreamHeaderAllowlist struct {
+	exact    map[string]struct{}
+	prefixes []string
+}
+
+// NewUpstreamHeaderAllowlist 创建白名单；条目不区分大小写，以 * 结尾表示前缀匹配
+func NewUpstreamHeaderAllowlist(entries []string) *UpstreamHeaderAllowlist {
+	a := &UpstreamHeaderAllowlist{exact: make(map[string]struct{}, len(entries))}
+	for _, entry := range entries {
+		entry = strings.ToLower(strings.TrimSpace(entry))
+		if prefix, ok := strings.CutSuffix(entry, "*"); ok {
+			if prefix != "" {
+				a.prefixes = append(a.prefixes, prefix)
+			}
+			continue
+		}
+		if entry != "" {
+			a.exact[entry] = struct{}{}
+		}
+	}
+	return a
+}
+
+// upstreamHeaderReserved 判断请求头名称（小写）是否为保留请求头
+func upstreamHeaderReserved(name string) bool {
+	if _, reserved := reservedUpstreamHeaders[name]; reserved {
+		return true
+	}
+	for _, prefix := range reservedUpstreamHeaderPrefixes {
+		if strings.HasPrefix(name, prefix) {
+			return true
+		}
+	}
+	return false
+}
+
+// Allowed 判断请求头名称（小写）是否在白名单中且不属于保留请求头；nil 白名单不允许任何请求头
+func (a *UpstreamHeaderAllowlist) Allowed(name string) bool {
+	if a == nil || upstreamHeaderReserved(name) {
+		return false
+	}
+	if _, ok := a.exact[name]; ok {
+		return true
+	}
+	for _, prefix := range a.prefixes {
+		if strings.HasPrefix(name, prefix) {
+			return true
+		}
+	}
+	return false
+}
+
+// ApplyUpstreamHeaderPolicy 将请求上下文中策略配置的自定义请求头写入上游请求。
+// 运行时按当前白名单再次过滤，白名单收紧后已保存策略中不再允许的请求头会被跳过。
+func ApplyUpstreamHeaderPolicy(req *http.Request, allowlist *UpstreamHeaderAllowlist) {
+	if req == nil {
+		return
+	}
+	tags := APIKeyPolicyFromContext(req.Context()).UpstreamTags
+	if tags == nil {
+		return
+	}
+	for name, value := range tags.Headers {
+		if allowlist.Allowed(name) {
+			req.Header.Set(name, value)
+		}
+	}
+}
+
+// applyUpstreamMetadata 将策略中的 metadata 合并到 OpenAI Responses 请求体，返回是否修改了请求体。
+// 合并后超过上游键数量上限时，按键名顺序丢弃客户端自带的字段。
+func applyUpstreamMetadata(reqBody map[string]any, tags *UpstreamTagPolicy) bool {
+	if tags == nil || len(tags.Metadata) == 0 {
+		return false
+	}
+	metadata, _ := reqBody["metadata"].(map[string]any)
+	if metadata == nil {
+		metadata = make(map[string]any, len(tags.Metadata))
+	}
+	for key, value := range tags.Metadata {
+		metadata[key] = value
+	}
+	if overflow := len(metadata) - upstreamTagMaxMetadata; overflow > 0 {
+		clientKeys := make([]string, 0, len(metadata))
+		for key := range metadata {
+			if _, ok := tags.Metadata[key]; !ok {
+				clientKeys = append(clientKeys, key)
+			}
+		}
+		sort.Strings(clientKeys)
+		for _, key := range clientKeys[len(clientKeys)-overflow:] {
+			delete(metadata, key)
+		}
+	}
+	reqBody["metadata"] = metadata
+	return true
+}
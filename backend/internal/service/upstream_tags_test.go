@@ -0,0 +1,102 @@
+//go:build unit
+
+package service
+
+import (
+	"context"
+	"fmt"
+	"net/http"
+	"testing"
+
+	"github.com/stretchr/testify/require"
+)
+
+func TestUpstreamTagPolicy_Normalize(t *testing.T) {
+	svc := &APIKeyPolicyService{headerAllowlist: NewUpstreamHeaderAllowlist([]string{"X-Reseller-*", "x-tenant-id"})}
+
+	policy := &APIKeyPolicy{UpstreamTags: &UpstreamTagPolicy{
+		Headers:  map[string]string{" X-Reseller-Customer ": " acme ", "x-tenant-id": "t1"},
+		Metadata: map[string]string{" reseller ": "acme"},
+	}}
+	require.NoError(t, svc.normalize(policy))
+	require.Equal(t, map[string]string{"x-reseller-customer": "acme", "x-tenant-id": "t1"}, policy.UpstreamTags.Headers)
+	require.Equal(t, map[string]string{"reseller": "acme"}, policy.UpstreamTags.Metadata)
+
+	policy = &APIKeyPolicy{UpstreamTags: &UpstreamTagPolicy{Headers: map[string]string{}}}
+	require.NoError(t, svc.normalize(policy))
+	require.Nil(t, policy.UpstreamTags)
+
+	// 未配置白名单时不允许任何自定义请求头
+	noAllowlist := &APIKeyPolicyService{}
+	require.ErrorIs(t, noAllowlist.normalize(&APIKeyPolicy{UpstreamTags: &UpstreamTagPolicy{Headers: map[string]string{"x-tenant-id": "t1"}}}), ErrUpstreamHeaderNotAllowed)
+
+	tests := []struct {
+		name string
+		tags *UpstreamTagPolicy
+		want error
+	}{
+		{"not in allowlist", &UpstreamTagPolicy{Headers: map[string]string{"x-other": "v"}}, ErrUpstreamHeaderNotAllowed},
+		{"reserved exact name", &UpstreamTagPolicy{Headers: map[string]string{"Authorization": "Bearer x"}}, ErrUpstreamHeaderNotAllowed},
+		{"reserved prefix", &UpstreamTagPolicy{Headers: map[string]string{"anthropic-beta": "v"}}, ErrUpstreamHeaderNotAllowed},
+		{"header injection", &UpstreamTagPolicy{Headers: map[string]string{"x-tenant-id": "a\r\nx-api-key: b"}}, ErrInvalidUpstreamHeader},
+		{"invalid header name", &UpstreamTagPolicy{Headers: map[string]string{"x tenant": "v"}}, ErrInvalidUpstreamHeader},
+		{"empty header value", &UpstreamTagPolicy{Headers: map[string]string{"x-tenant-id": " "}}, ErrInvalidUpstreamHeader},
+		{"duplicate after normalize", &UpstreamTagPolicy{Headers: map[string]string{"x-tenant-id": "a", "X-Tenant-ID": "b"}}, ErrInvalidUpstreamHeader},
+		{"empty metadata key", &UpstreamTagPolicy{Metadata: map[string]string{" ": "v"}}, ErrInvalidUpstreamMetadata},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			require.ErrorIs(t, svc.normalize(&APIKeyPolicy{UpstreamTags: tt.tags}), tt.want)
+		})
+	}
+
+	tooMany := make(map[string]string, upstreamTagMaxMetadata+1)
+	for i := range upstreamTagMaxMetadata + 1 {
+		tooMany[fmt.Sprintf("k%d", i)] = "v"
+	}
+	require.ErrorIs(t, svc.normalize(&APIKeyPolicy{UpstreamTags: &UpstreamTagPolicy{Metadata: tooMany}}), ErrInvalidUpstreamMetadata)
+}
+
+func TestApplyUpstreamHeaderPolicy(t *testing.T) {
+	allowlist := NewUpstreamHeaderAllowlist([]string{"x-reseller-*"})
+
+	tags := &UpstreamTagPolicy{Headers: map[string]string{"x-reseller-customer": "acme", "x-tenant-id": "t1"}}
+	ctx := WithAPIKeyPolicy(context.Background(), &APIKeyPolicy{UpstreamTags: tags})
+	req, err := http.NewRequestWithContext(ctx, http.MethodPost, "https://api.example.com/v1/messages", nil)
+	require.NoError(t, err)
+	req.Header.Set("X-Reseller-Customer", "client-value")
+
+	ApplyUpstreamHeaderPolicy(req, allowlist)
+	require.Equal(t, "acme", req.Header.Get("X-Reseller-Customer"))
+	// 白名单收紧后，已保存策略中不再允许的请求头被跳过
+	require.Empty(t, req.Header.Get("X-Tenant-Id"))
+
+	// 未携带策略的请求（如后台任务）不受影响
+	plain, err := http.NewRequest(http.MethodGet, "https://api.example.com/v1/models", nil)
+	require.NoError(t, err)
+	ApplyUpstreamHeaderPolicy(plain, allowlist)
+	require.Empty(t, plain.Header)
+}
+
+func TestApplyUpstreamMetadata(t *testing.T) {
+	require.False(t, applyUpstreamMetadata(map[string]any{}, nil))
+
+	tags := &UpstreamTagPolicy{Metadata: map[string]string{"reseller": "acme", "tier": "gold"}}
+	reqBody := map[string]any{"metadata": map[string]any{"tier": "free", "trace": "abc"}}
+	require.True(t, applyUpstreamMetadata(reqBody, tags))
+	require.Equal(t, map[string]any{"reseller": "acme", "tier": "gold", "trace": "abc"}, reqBody["metadata"])
+
+	// 合并后超出上限时丢弃客户端字段，策略字段始终保留
+	clientMetadata := make(map[string]any, upstreamTagMaxMetadata)
+	for i := range upstreamTagMaxMetadata {
+		clientMetadata[fmt.Sprintf("c%02d", i)] = "v"
+	}
+	reqBody = map[string]any{"metadata": clientMetadata}
+	require.True(t, applyUpstreamMetadata(reqBody, tags))
+	merged := reqBody["metadata"].(map[string]any)
+	require.Len(t, merged, upstreamTagMaxMetadata)
+	require.Equal(t, "acme", merged["reseller"])
+	require.Equal(t, "gold", merged["tier"])
+	require.Contains(t, merged, "c00")
+	require.NotContains(t, merged, "c15")
+}
@@ -0,0 +1,241 @@
+package service
+
+import (
+	"context"
+	"errors"
+	"fmt"
+	"io"
+	"net/http"
+	"net/http/httptrace"
+	"strings"
+	"sync"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/gin-gonic/gin"
+)
+
+// 上游超时阶段
+const (
+	UpstreamTimeoutPhaseConnect    = "connect"
+	UpstreamTimeoutPhaseFirstToken = "first_token"
+	UpstreamTimeoutPhaseTotal      = "total"
+)
+
+// UpstreamTimeouts 单次上游请求的分阶段超时，零值表示不限制
+//   - Connect: 从发出请求到建立（或复用）上游连接，含代理与 TLS 握手
+//   - FirstToken: 从发出请求到读到首个响应体字节
+//   - Total: 整个请求，含流式读取
+type UpstreamTimeouts struct {
+	Connect    time.Duration
+	FirstToken time.Duration
+	Total      time.Duration
+}
+
+// IsZero 是否未设置任何超时
+func (t UpstreamTimeouts) IsZero() bool {
+	return t.Connect <= 0 && t.FirstToken <= 0 && t.Total <= 0
+}
+
+// UpstreamTimeoutError 上游请求在某一阶段超时
+type UpstreamTimeoutError struct {
+	Phase   string
+	Timeout time.Duration
+}
+
+func (e *UpstreamTimeoutError) Error() string {
+	return fmt.Sprintf("upstream %s timeout after %s", strings.ReplaceAll(e.Phase, "_", " "), e.Timeout)
+}
+
+// Code 返回对外错误码，如 upstream_first_token_timeout
+func (e *UpstreamTimeoutError) Code() string {
+	return "upstream_" + e.Phase + "_timeout"
+}
+
+// AsUpstreamTimeoutError 判断错误是否为上游分阶段超时
+func AsUpstreamTimeoutError(err error) (*UpstreamTimeoutError, bool) {
+	var timeoutErr *UpstreamTimeoutError
+	if errors.As(err, &timeoutErr) {
+		return timeoutErr, true
+	}
+	return nil, false
+}
+
+type upstreamTimeoutsKey struct{}
+
+// WithUpstreamTimeouts 将本次请求的上游超时写入 context，由 HTTPUpstream 实现读取并生效
+func WithUpstreamTimeouts(ctx context.Context, timeouts UpstreamTimeouts) context.Context {
+	if timeouts.IsZero() {
+		return ctx
+	}
+	return context.WithValue(ctx, upstreamTimeoutsKey{}, timeouts)
+}
+
+// UpstreamTimeoutsFromContext 读取 context 中的上游超时，未设置时返回零值
+func UpstreamTimeoutsFromContext(ctx context.Context) UpstreamTimeouts {
+	if ctx == nil {
+		return UpstreamTimeouts{}
+	}
+	timeouts, _ := ctx.Value(upstreamTimeoutsKey{}).(UpstreamTimeouts)
+	return timeouts
+}
+
+// ResolveUpstreamTimeouts 按平台与模型解析上游超时：以 gateway.upstream_timeouts 的默认值为基础，
+// 取第一条匹配的规则覆盖其中非 0 的字段
+func ResolveUpstreamTimeouts(cfg *config.Config, platform, model string) UpstreamTimeouts {
+	if cfg == nil {
+		return UpstreamTimeouts{}
+	}
+	tc := cfg.Gateway.UpstreamTimeouts
+	connect, firstToken, total := tc.ConnectSeconds, tc.FirstTokenSeconds, tc.TotalSeconds
+	for _, rule := range tc.Rules {
+		if !upstreamTimeoutRuleMatches(rule, platform, model) {
+			continue
+		}
+		if rule.ConnectSeconds > 0 {
+			connect = rule.ConnectSeconds
+		}
+		if rule.FirstTokenSeconds > 0 {
+			firstToken = rule.FirstTokenSeconds
+		}
+		if rule.TotalSeconds > 0 {
+			total = rule.TotalSeconds
+		}
+		break
+	}
+	return UpstreamTimeouts{
+		Connect:    time.Duration(connect) * time.Second,
+		FirstToken: time.Duration(firstToken) * time.Second,
+		Total:      time.Duration(total) * time.Second,
+	}
+}
+
+func upstreamTimeoutRuleMatches(rule config.GatewayUpstreamTimeoutRule, platform, model string) bool {
+	if rule.Platform != "" && !strings.EqualFold(rule.Platform, platform) {
+		return false
+	}
+	if len(rule.Models) == 0 {
+		return true
+	}
+	model = strings.ToLower(strings.TrimSpace(model))
+	for _, pattern := range rule.Models {
+		if matchWildcard(strings.ToLower(strings.TrimSpace(pattern)), model) {
+			return true
+		}
+	}
+	return false
+}
+
+// DoWithUpstreamTimeouts 按请求 context 中的上游超时执行请求（供 HTTPUpstream 实现调用）。
+// 超时后取消上游请求，并以 *UpstreamTimeoutError 作为请求错误或响应体读取错误返回。
+func DoWithUpstreamTimeouts(req *http.Request, do func(*http.Request) (*http.Response, error)) (*http.Response, error) {
+	timeouts := UpstreamTimeoutsFromContext(req.Context())
+	if timeouts.IsZero() {
+		return do(req)
+	}
+
+	ctx, cancel := context.WithCancelCause(req.Context())
+	guard := &upstreamTimeoutGuard{cancel: cancel}
+	guard.total = guard.arm(UpstreamTimeoutPhaseTotal, timeouts.Total)
+	guard.firstToken = guard.arm(UpstreamTimeoutPhaseFirstToken, timeouts.FirstToken)
+	if connectTimer := guard.arm(UpstreamTimeoutPhaseConnect, timeouts.Connect); connectTimer != nil {
+		defer connectTimer.Stop()
+		ctx = httptrace.WithClientTrace(ctx, &httptrace.ClientTrace{
+			GotConn: func(httptrace.GotConnInfo) { connectTimer.Stop() },
+		})
+	}
+
+	resp, err := do(req.WithContext(ctx))
+	if err != nil {
+		guard.stop()
+		if timeoutErr, ok := AsUpstreamTimeoutError(context.Cause(ctx)); ok {
+			return nil, timeoutErr
+		}
+		return nil, err
+	}
+	resp.Body = &upstreamTimeoutBody{ReadCloser: resp.Body, ctx: ctx, guard: guard}
+	return resp, nil
+}
+
+type upstreamTimeoutGuard struct {
+	cancel     context.CancelCauseFunc
+	firstToken *time.Timer
+	total      *time.Timer
+	once       sync.Once
+}
+
+func (g *upstreamTimeoutGuard) arm(phase string, timeout time.Duration) *time.Timer {
+	if timeout <= 0 {
+		return nil
+	}
+	return time.AfterFunc(timeout, func() {
+		g.cancel(&UpstreamTimeoutError{Phase: phase, Timeout: timeout})
+	})
+}
+
+func (g *upstreamTimeoutGuard) gotFirstByte() {
+	if g.firstToken != nil {
+		g.firstToken.Stop()
+	}
+}
+
+func (g *upstreamTimeoutGuard) stop() {
+	g.once.Do(func() {
+		g.gotFirstByte()
+		if g.total != nil {
+			g.total.Stop()
+		}
+		g.cancel(context.Canceled)
+	})
+}
+
+// upstreamTimeoutBody 响应体包装：首字节到达后停止首 token 计时，超时导致的读取错误替换为 *UpstreamTimeoutError
+type upstreamTimeoutBody struct {
+	io.ReadCloser
+	ctx       context.Context
+	guard     *upstreamTimeoutGuard
+	firstRead bool
+}
+
+func (b *upstreamTimeoutBody) Read(p []byte) (int, error) {
+	n, err := b.ReadCloser.Read(p)
+	if n > 0 && !b.firstRead {
+		b.firstRead = true
+		b.guard.gotFirstByte()
+	}
+	if err != nil && !errors.Is(err, io.EOF) {
+		if timeoutErr, ok := AsUpstreamTimeoutError(context.Cause(b.ctx)); ok {
+			return n, timeoutErr
+		}
+	}
+	return n, err
+}
+
+func (b *upstreamTimeoutBody) Close() error {
+	err := b.ReadCloser.Close()
+	b.guard.stop()
+	return err
+}
+
+// writeUpstreamTimeoutOpenAIError 以 OpenAI 错误对象返回上游超时（504）
+func writeUpstreamTimeoutOpenAIError(c *gin.Context, timeoutErr *UpstreamTimeoutError) {
+	c.JSON(http.StatusGatewayTimeout, gin.H{
+		"error": gin.H{
+			"message": timeoutErr.Error(),
+			"type":    "timeout_error",
+			"param":   nil,
+			"code":    timeoutErr.Code(),
+		},
+	})
+}
+
+// writeUpstreamTimeoutClaudeError 以 Anthropic 错误格式返回上游超时（504）
+func writeUpstreamTimeoutClaudeError(c *gin.Context, timeoutErr *UpstreamTimeoutError) {
+	c.JSON(http.StatusGatewayTimeout, gin.H{
+		"type": "error",
+		"error": gin.H{
+			"type":    "timeout_error",
+			"message": timeoutErr.Error(),
+		},
+	})
+}
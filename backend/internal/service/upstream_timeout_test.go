@@ -0,0 +1,129 @@
+//go:build unit
+
+package service
+
+import (
+	"context"
+	"io"
+	"net/http"
+	"net/http/httptest"
+	"testing"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/stretchr/testify/require"
+)
+
+func TestResolveUpstreamTimeouts(t *testing.T) {
+	cfg := &config.Config{}
+	cfg.Gateway.UpstreamTimeouts = config.GatewayUpstreamTimeoutsConfig{
+		ConnectSeconds: 30,
+		Rules: []config.GatewayUpstreamTimeoutRule{
+			{Platform: PlatformAnthropic, Models: []string{"claude-opus-*"}, FirstTokenSeconds: 600},
+			{Models: []string{"gpt-4o-mini*"}, ConnectSeconds: 5, FirstTokenSeconds: 20, TotalSeconds: 120},
+		},
+	}
+
+	require.Equal(t, UpstreamTimeouts{Connect: 30 * time.Second, FirstToken: 600 * time.Second},
+		ResolveUpstreamTimeouts(cfg, PlatformAnthropic, "Claude-Opus-4-1"))
+	require.Equal(t, UpstreamTimeouts{Connect: 30 * time.Second},
+		ResolveUpstreamTimeouts(cfg, PlatformAntigravity, "claude-opus-4-1"))
+	require.Equal(t, UpstreamTimeouts{Connect: 5 * time.Second, FirstToken: 20 * time.Second, Total: 120 * time.Second},
+		ResolveUpstreamTimeouts(cfg, PlatformOpenAI, "gpt-4o-mini-2024-07-18"))
+	require.True(t, ResolveUpstreamTimeouts(nil, PlatformOpenAI, "gpt-4o").IsZero())
+}
+
+func TestDoWithUpstreamTimeouts_FirstTokenTimeout(t *testing.T) {
+	release := make(chan struct{})
+	srv := httptest.NewServer(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		w.WriteHeader(http.StatusOK)
+		w.(http.Flusher).Flush()
+		select {
+		case <-release:
+		case <-r.Context().Done():
+		}
+	}))
+	defer srv.Close()
+	defer close(release)
+
+	ctx := WithUpstreamTimeouts(context.Background(), UpstreamTimeouts{FirstToken: 50 * time.Millisecond})
+	req, err := http.NewRequestWithContext(ctx, http.MethodGet, srv.URL, nil)
+	require.NoError(t, err)
+
+	resp, err := DoWithUpstreamTimeouts(req, srv.Client().Do)
+	require.NoError(t, err)
+	defer func() { _ = resp.Body.Close() }()
+
+	_, err = io.ReadAll(resp.Body)
+	timeoutErr, ok := AsUpstreamTimeoutError(err)
+	require.True(t, ok, "unexpected error: %v", err)
+	require.Equal(t, "upstream_first_token_timeout", timeoutErr.Code())
+}
+
+func TestDoWithUpstreamTimeouts_FirstTokenStopsAfterFirstByte(t *testing.T) {
+	srv := httptest.NewServer(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		_, _ = w.Write([]byte("data: a\n\n"))
+		w.(http.Flusher).Flush()
+		time.Sleep(100 * time.Millisecond)
+		_, _ = w.Write([]byte("data: b\n\n"))
+	}))
+	defer srv.Close()
+
+	ctx := WithUpstreamTimeouts(context.Background(), UpstreamTimeouts{FirstToken: 50 * time.Millisecond})
+	req, err := http.NewRequestWithContext(ctx, http.MethodGet, srv.URL, nil)
+	require.NoError(t, err)
+
+	resp, err := DoWithUpstreamTimeouts(req, srv.Client().Do)
+	require.NoError(t, err)
+	defer func() { _ = resp.Body.Close() }()
+
+	body, err := io.ReadAll(resp.Body)
+	require.NoError(t, err)
+	require.Equal(t, "data: a\n\ndata: b\n\n", string(body))
+}
+
+func TestDoWithUpstreamTimeouts_TotalTimeout(t *testing.T) {
+	srv := httptest.NewServer(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		for {
+			if _, err := w.Write([]byte("data: x\n\n")); err != nil {
+				return
+			}
+			w.(http.Flusher).Flush()
+			select {
+			case <-time.After(10 * time.Millisecond):
+			case <-r.Context().Done():
+				return
+			}
+		}
+	}))
+	defer srv.Close()
+
+	ctx := WithUpstreamTimeouts(context.Background(), UpstreamTimeouts{Total: 80 * time.Millisecond})
+	req, err := http.NewRequestWithContext(ctx, http.MethodGet, srv.URL, nil)
+	require.NoError(t, err)
+
+	resp, err := DoWithUpstreamTimeouts(req, srv.Client().Do)
+	require.NoError(t, err)
+	defer func() { _ = resp.Body.Close() }()
+
+	_, err = io.ReadAll(resp.Body)
+	timeoutErr, ok := AsUpstreamTimeoutError(err)
+	require.True(t, ok, "unexpected error: %v", err)
+	require.Equal(t, UpstreamTimeoutPhaseTotal, timeoutErr.Phase)
+	require.Equal(t, "upstream total timeout after 80ms", timeoutErr.Error())
+}
+
+func TestDoWithUpstreamTimeouts_NoTimeoutsPassesThrough(t *testing.T) {
+	req, err := http.NewRequest(http.MethodGet, "http://example.invalid", nil)
+	require.NoError(t, err)
+
+	var got *http.Request
+	resp := &http.Response{StatusCode: http.StatusOK, Body: io.NopCloser(nil)}
+	out, err := DoWithUpstreamTimeouts(req, func(r *http.Request) (*http.Response, error) {
+		got = r
+		return resp, nil
+	})
+	require.NoError(t, err)
+	require.Same(t, req, got)
+	require.Same(t, resp, out)
+}
@@ -0,0 +1,332 @@
+package service
+
+import (
+	"context"
+	"encoding/json"
+	"fmt"
+	"sync"
+	"sync/atomic"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/logger"
+	"github.com/google/uuid"
+)
+
+// JobTopicUsageAnalytics 用量明细批次写入分析存储的主题
+const JobTopicUsageAnalytics = "usage_analytics"
+
+const (
+	// usageAnalyticsMaxBackoff 写入失败后的最长重试间隔
+	usageAnalyticsMaxBackoff = time.Minute
+	// usageAnalyticsStopTimeout 停止时最后一次写入的超时
+	usageAnalyticsStopTimeout = 10 * time.Second
+)
+
+// UsageAnalyticsRow 写入分析存储的一行用量明细（列名与 ClickHouse usage_events 表一致）
+type UsageAnalyticsRow struct {
+	UsageLogID          int64     `json:"usage_log_id"`
+	RequestID           string    `json:"request_id"`
+	UserID              int64     `json:"user_id"`
+	APIKeyID            int64     `json:"api_key_id"`
+	AccountID           int64     `json:"account_id"`
+	GroupID             int64     `json:"group_id"`
+	SubscriptionID      int64     `json:"subscription_id"`
+	Model               string    `json:"model"`
+	BillingType         int8      `json:"billing_type"`
+	Stream              bool      `json:"stream"`
+	InputTokens         int       `json:"input_tokens"`
+	OutputTokens        int       `json:"output_tokens"`
+	CacheCreationTokens int       `json:"cache_creation_tokens"`
+	CacheReadTokens     int       `json:"cache_read_tokens"`
+	ReasoningTokens     int       `json:"reasoning_tokens"`
+	ImageCount          int       `json:"image_count"`
+	InputCost           float64   `json:"input_cost"`
+	OutputCost          float64   `json:"output_cost"`
+	CacheCreationCost   float64   `json:"cache_creation_cost"`
+	CacheReadCost       float64   `json:"cache_read_cost"`
+	TotalCost           float64   `json:"total_cost"`
+	ActualCost          float64   `json:"actual_cost"`
+	RateMultiplier      float64   `json:"rate_multiplier"`
+	DurationMs          *int      `json:"duration_ms"`
+	FirstTokenMs        *int      `json:"first_token_ms"`
+	CreatedAt           time.Time `json:"created_at"`
+}
+
+// UsageAnalyticsStore 列式分析存储（ClickHouse）
+type UsageAnalyticsStore interface {
+	// Migrate 创建数据库并执行尚未应用的表结构迁移
+	Migrate(ctx context.Context) error
+	// InsertUsage 批量写入；dedupToken 在同一批次的重试间保持不变，用于服务端去重
+	InsertUsage(ctx context.Context, dedupToken string, rows []*UsageAnalyticsRow) error
+}
+
+// usageAnalyticsBatch 一批用量明细（同时作为任务队列载荷）
+type usageAnalyticsBatch struct {
+	Token string               `json:"token"`
+	Rows  []*UsageAnalyticsRow `json:"rows"`
+}
+
+// UsageAnalyticsService 将用量明细异步写入列式分析存储。
+//
+// 网关记录用量后非阻塞地把行放入进程内队列，后台协程按条数或时间攒批插入；
+// 写入失败的批次保留在内存中按指数退避重试（重试沿用去重 token，不会重复写入），
+// 累积超过 max_pending_rows 时丢弃最早的批次。Postgres 中的 usage_logs 不受影响。
+//
+// 启用持久化任务队列时，攒好的批次改为入队，由队列消费者写入（失败按队列语义重试，
+// 实例重启不丢失已入队的批次）；入队失败的批次仍在内存中退避重试。
+type UsageAnalyticsService struct {
+	store    UsageAnalyticsStore
+	cfg      config.ClickHouseConfig
+	jobQueue *JobQueueService
+
+	// migrateMu 保护 migrated：未启用队列时仅由后台协程访问，启用后由队列消费者访问
+	migrateMu sync.Mutex
+	migrated  bool
+
+	rows    chan *UsageAnalyticsRow
+	dropped atomic.Int64
+
+	// 以下字段仅由后台协程访问
+	batch       []*UsageAnalyticsRow
+	pending     []*usageAnalyticsBatch
+	pendingRows int
+	failures    int
+	retryAt     time.Time
+
+	stopCh    chan struct{}
+	startOnce sync.Once
+	stopOnce  sync.Once
+	wg        sync.WaitGroup
+}
+
+// NewUsageAnalyticsService 创建用量分析写入服务
+func NewUsageAnalyticsService(store UsageAnalyticsStore, cfg *config.Config) *UsageAnalyticsService {
+	s := &UsageAnalyticsService{store: store, stopCh: make(chan struct{})}
+	if cfg != nil {
+		s.cfg = cfg.ClickHouse
+	}
+	if s.Enabled() {
+		s.rows = make(chan *UsageAnalyticsRow, s.cfg.BufferSize)
+	}
+	return s
+}
+
+// Enabled 是否启用用量分析写入
+func (s *UsageAnalyticsService) Enabled() bool {
+	return s != nil && s.cfg.Enabled && s.store != nil
+}
+
+// SetJobQueue 注入任务队列：批次改为入队，由负责后台任务的进程（embedded/worker）写入分析存储
+func (s *UsageAnalyticsService) SetJobQueue(jobQueue *JobQueueService) {
+	if !s.Enabled() || !jobQueue.Enabled() {
+		return
+	}
+	s.jobQueue = jobQueue
+	jobQueue.RegisterHandler(JobTopicUsageAnalytics, 1, time.Duration(s.cfg.TimeoutSeconds)*time.Second, s.handleJob)
+}
+
+// Record 提交一条用量明细；队列写满时丢弃并计数，不阻塞调用方
+func (s *UsageAnalyticsService) Record(log *UsageLog) {
+	if log == nil || !s.Enabled() || s.rows == nil {
+		return
+	}
+	row := &UsageAnalyticsRow{
+		UsageLogID:          log.ID,
+		RequestID:           log.RequestID,
+		UserID:              log.UserID,
+		APIKeyID:            log.APIKeyID,
+		AccountID:           log.AccountID,
+		Model:               log.Model,
+		BillingType:         log.BillingType,
+		Stream:              log.Stream,
+		InputTokens:         log.InputTokens,
+		OutputTokens:        log.OutputTokens,
+		CacheCreationTokens: log.CacheCreationTokens,
+		CacheReadTokens:     log.CacheReadTokens,
+		ReasoningTokens:     log.ReasoningTokens,
+		ImageCount:          log.ImageCount,
+		InputCost:           log.InputCost,
+		OutputCost:          log.OutputCost,
+		CacheCreationCost:   log.CacheCreationCost,
+		CacheReadCost:       log.CacheReadCost,
+		TotalCost:           log.TotalCost,
+		ActualCost:          log.ActualCost,
+		RateMultiplier:      log.RateMultiplier,
+		DurationMs:          log.DurationMs,
+		FirstTokenMs:        log.FirstTokenMs,
+		CreatedAt:           log.CreatedAt.UTC(),
+	}
+	if log.GroupID != nil {
+		row.GroupID = *log.GroupID
+	}
+	if log.SubscriptionID != nil {
+		row.SubscriptionID = *log.SubscriptionID
+	}
+	select {
+	case s.rows <- row:
+	default:
+		s.dropped.Add(1)
+	}
+}
+
+// Start 启动后台写入协程
+func (s *UsageAnalyticsService) Start() {
+	if !s.Enabled() {
+		return
+	}
+	s.startOnce.Do(func() {
+		s.wg.Add(1)
+		go s.run()
+		logger.LegacyPrintf("service.usage_analytics", "[UsageAnalytics] Started (batch_size=%d flush_interval=%dms)", s.cfg.BatchSize, s.cfg.FlushIntervalMs)
+	})
+}
+
+// Stop 停止写入协程，并对队列与待重试批次做最后一次写入
+func (s *UsageAnalyticsService) Stop() {
+	if s == nil {
+		return
+	}
+	s.stopOnce.Do(func() { close(s.stopCh) })
+	s.wg.Wait()
+}
+
+func (s *UsageAnalyticsService) run() {
+	defer s.wg.Done()
+	ticker := time.NewTicker(time.Duration(s.cfg.FlushIntervalMs) * time.Millisecond)
+	defer ticker.Stop()
+
+	for {
+		select {
+		case <-s.stopCh:
+			s.shutdown()
+			return
+		case row := <-s.rows:
+			s.batch = append(s.batch, row)
+			if len(s.batch) >= s.cfg.BatchSize {
+				s.seal()
+				s.flush(time.Now())
+			}
+		case <-ticker.C:
+			if dropped := s.dropped.Swap(0); dropped > 0 {
+				logger.LegacyPrintf("service.usage_analytics", "[UsageAnalytics] Queue full, dropped %d rows", dropped)
+			}
+			s.seal()
+			s.flush(time.Now())
+		}
+	}
+}
+
+func (s *UsageAnalyticsService) shutdown() {
+	for drained := false; !drained; {
+		select {
+		case row := <-s.rows:
+			s.batch = append(s.batch, row)
+			if len(s.batch) >= s.cfg.BatchSize {
+				s.seal()
+			}
+		default:
+			drained = true
+		}
+	}
+	s.seal()
+	// 停止时忽略退避，只做一次尝试
+	s.retryAt = time.Time{}
+	s.flushWithTimeout(time.Now(), usageAnalyticsStopTimeout)
+	if s.pendingRows > 0 {
+		logger.LegacyPrintf("service.usage_analytics", "[UsageAnalytics] Stopped with %d unwritten rows", s.pendingRows)
+	}
+}
+
+// seal 把当前批次转入待写入队列；超出 max_pending_rows 时丢弃最早的批次
+func (s *UsageAnalyticsService) seal() {
+	if len(s.batch) > 0 {
+		s.pending = append(s.pending, &usageAnalyticsBatch{Token: uuid.NewString(), Rows: s.batch})
+		s.pendingRows += len(s.batch)
+		s.batch = nil
+	}
+	for s.pendingRows > s.cfg.MaxPendingRows && len(s.pending) > 1 {
+		oldest := s.pending[0]
+		s.pending = s.pending[1:]
+		s.pendingRows -= len(oldest.Rows)
+		logger.LegacyPrintf("service.usage_analytics", "[UsageAnalytics] Pending buffer full, dropped batch of %d rows", len(oldest.Rows))
+	}
+}
+
+func (s *UsageAnalyticsService) flush(now time.Time) {
+	s.flushWithTimeout(now, time.Duration(s.cfg.TimeoutSeconds)*time.Second)
+}
+
+// flushWithTimeout 按顺序写入（或入队）待写入批次，遇到失败即停止并进入退避
+func (s *UsageAnalyticsService) flushWithTimeout(now time.Time, timeout time.Duration) {
+	if len(s.pending) == 0 || now.Before(s.retryAt) {
+		return
+	}
+	for len(s.pending) > 0 {
+		batch := s.pending[0]
+		ctx, cancel := context.WithTimeout(context.Background(), timeout)
+		op, err := s.write(ctx, batch)
+		cancel()
+		if err != nil {
+			s.backoff(now, op, err)
+			return
+		}
+		s.pending = s.pending[1:]
+		s.pendingRows -= len(batch.Rows)
+		s.failures = 0
+	}
+	s.pending = nil
+}
+
+// write 启用任务队列时把批次入队，否则直接写入分析存储；返回失败的操作名
+func (s *UsageAnalyticsService) write(ctx context.Context, batch *usageAnalyticsBatch) (string, error) {
+	if s.jobQueue != nil {
+		_, err := s.jobQueue.Enqueue(ctx, JobTopicUsageAnalytics, batch)
+		return "enqueue", err
+	}
+	if err := s.migrate(ctx); err != nil {
+		return "migrate", err
+	}
+	return "insert", s.store.InsertUsage(ctx, batch.Token, batch.Rows)
+}
+
+// migrate 首次写入前执行表结构迁移
+func (s *UsageAnalyticsService) migrate(ctx context.Context) error {
+	s.migrateMu.Lock()
+	defer s.migrateMu.Unlock()
+	if s.migrated {
+		return nil
+	}
+	if err := s.store.Migrate(ctx); err != nil {
+		return err
+	}
+	s.migrated = true
+	return nil
+}
+
+// handleJob 写入一批用量明细；返回错误时任务由队列重新投递（去重 token 不变，不会重复写入）
+func (s *UsageAnalyticsService) handleJob(ctx context.Context, job *QueuedJob) error {
+	var batch usageAnalyticsBatch
+	if err := json.Unmarshal(job.Payload, &batch); err != nil {
+		// 载荷损坏无法通过重试恢复，直接确认丢弃
+		logger.LegacyPrintf("service.usage_analytics", "[UsageAnalytics] Drop malformed batch %s: %v", job.ID, err)
+		return nil
+	}
+	if len(batch.Rows) == 0 {
+		return nil
+	}
+	if err := s.migrate(ctx); err != nil {
+		return fmt.Errorf("migrate usage analytics store: %w", err)
+	}
+	if err := s.store.InsertUsage(ctx, batch.Token, batch.Rows); err != nil {
+		return fmt.Errorf("insert usage batch (%d rows): %w", len(batch.Rows), err)
+	}
+	return nil
+}
+
+func (s *UsageAnalyticsService) backoff(now time.Time, op string, err error) {
+	s.failures++
+	delay := time.Second << min(s.failures, 6)
+	s.retryAt = now.Add(min(delay, usageAnalyticsMaxBackoff))
+	logger.LegacyPrintf("service.usage_analytics", "[UsageAnalytics] %s failed (pending=%d rows, retry in %s): %v", op, s.pendingRows, min(delay, usageAnalyticsMaxBackoff), err)
+}
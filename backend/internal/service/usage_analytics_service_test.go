@@ -0,0 +1,142 @@
+//go:build unit
+
+package service
+
+import (
+	"context"
+	"errors"
+	"testing"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/stretchr/testify/require"
+)
+
+type usageAnalyticsStoreStub struct {
+	migrateCalls int
+	tokens       []string
+	rows         [][]*UsageAnalyticsRow
+	err          error
+}
+
+func (s *usageAnalyticsStoreStub) Migrate(ctx context.Context) error {
+	s.migrateCalls++
+	return nil
+}
+
+func (s *usageAnalyticsStoreStub) InsertUsage(ctx context.Context, dedupToken string, rows []*UsageAnalyticsRow) error {
+	s.tokens = append(s.tokens, dedupToken)
+	if s.err != nil {
+		return s.err
+	}
+	s.rows = append(s.rows, rows)
+	return nil
+}
+
+func newUsageAnalyticsTestService(store UsageAnalyticsStore, maxPending int) *UsageAnalyticsService {
+	return NewUsageAnalyticsService(store, &config.Config{ClickHouse: config.ClickHouseConfig{
+		Enabled:         true,
+		BatchSize:       2,
+		FlushIntervalMs: 60000,
+		BufferSize:      16,
+		MaxPendingRows:  maxPending,
+		TimeoutSeconds:  5,
+	}})
+}
+
+func TestUsageAnalyticsService_RecordAndFlushOnStop(t *testing.T) {
+	store := &usageAnalyticsStoreStub{}
+	svc := newUsageAnalyticsTestService(store, 100)
+	groupID := int64(3)
+
+	svc.Record(&UsageLog{ID: 1, UserID: 7, GroupID: &groupID, Model: "claude-sonnet-4", InputTokens: 10, CreatedAt: time.Now()})
+	svc.Record(&UsageLog{ID: 2, UserID: 7, Model: "claude-sonnet-4"})
+	svc.Record(&UsageLog{ID: 3, UserID: 8, Model: "gpt-5"})
+	svc.Start()
+	svc.Stop()
+
+	require.Equal(t, 1, store.migrateCalls)
+	var ids []int64
+	for _, batch := range store.rows {
+		require.LessOrEqual(t, len(batch), 2)
+		for _, row := range batch {
+			ids = append(ids, row.UsageLogID)
+		}
+	}
+	require.Equal(t, []int64{1, 2, 3}, ids)
+	require.Equal(t, int64(3), store.rows[0][0].GroupID)
+	require.Equal(t, 10, store.rows[0][0].InputTokens)
+}
+
+func TestUsageAnalyticsService_RetryReusesDedupToken(t *testing.T) {
+	store := &usageAnalyticsStoreStub{err: errors.New("connection refused")}
+	svc := newUsageAnalyticsTestService(store, 100)
+	now := time.Now()
+
+	svc.batch = []*UsageAnalyticsRow{{UsageLogID: 1}}
+	svc.seal()
+	svc.flush(now)
+	require.Equal(t, 1, svc.pendingRows)
+	require.True(t, svc.retryAt.After(now))
+
+	// 退避期内不重试
+	svc.flush(now)
+	require.Len(t, store.tokens, 1)
+
+	store.err = nil
+	svc.flush(svc.retryAt)
+	require.Len(t, store.tokens, 2)
+	require.Equal(t, store.tokens[0], store.tokens[1])
+	require.Zero(t, svc.pendingRows)
+	require.Empty(t, svc.pending)
+}
+
+func TestUsageAnalyticsService_PendingOverflowDropsOldest(t *testing.T) {
+	svc := newUsageAnalyticsTestService(&usageAnalyticsStoreStub{}, 2)
+
+	for i := int64(1); i <= 3; i++ {
+		svc.batch = []*UsageAnalyticsRow{{UsageLogID: i}}
+		svc.seal()
+	}
+	require.Equal(t, 2, svc.pendingRows)
+	require.Len(t, svc.pending, 2)
+	require.Equal(t, int64(2), svc.pending[0].Rows[0].UsageLogID)
+}
+
+func TestUsageAnalyticsService_DisabledIsNoop(t *testing.T) {
+	svc := NewUsageAnalyticsService(&usageAnalyticsStoreStub{}, &config.Config{})
+	require.False(t, svc.Enabled())
+	svc.Record(&UsageLog{ID: 1})
+	svc.Start()
+	svc.Stop()
+
+	var nilSvc *UsageAnalyticsService
+	nilSvc.Record(&UsageLog{ID: 1})
+	nilSvc.Stop()
+}
+
+func TestUsageAnalyticsService_WritesThroughJobQueue(t *testing.T) {
+	store := &usageAnalyticsStoreStub{}
+	queue := newJobQueueStub()
+	svc := newUsageAnalyticsTestService(store, 100)
+	svc.SetJobQueue(newTestJobQueueService(queue))
+
+	svc.Record(&UsageLog{ID: 1, UserID: 7, Model: "claude-sonnet-4"})
+	svc.Record(&UsageLog{ID: 2, UserID: 7, Model: "claude-sonnet-4"})
+	svc.Start()
+	svc.Stop()
+
+	require.Empty(t, store.rows, "rows are written by the queue consumer")
+	require.Len(t, queue.enqueued[JobTopicUsageAnalytics], 1)
+
+	job := &QueuedJob{ID: "1-0", Payload: queue.enqueued[JobTopicUsageAnalytics][0]}
+	require.NoError(t, svc.handleJob(context.Background(), job))
+	require.Equal(t, 1, store.migrateCalls)
+	require.Len(t, store.rows, 1)
+	require.Len(t, store.rows[0], 2)
+
+	// 重新投递沿用同一去重 token
+	store.err = errors.New("clickhouse down")
+	require.Error(t, svc.handleJob(context.Background(), job))
+	require.Equal(t, store.tokens[0], store.tokens[1])
+}
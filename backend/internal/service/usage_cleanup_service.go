@@ -30,6 +30,7 @@ type UsageCleanupService struct {
 	cfg         *config.Config
 
 	running   int32
+	leader    *LeaderElector
 	startOnce sync.Once
 	stopOnce  sync.Once
 
@@ -49,6 +50,11 @@ func NewUsageCleanupService(repo UsageCleanupRepository, timingWheel *TimingWhee
 	}
 }
 
+// SetLeaderElector 多实例部署时仅由 leader 执行；需要在 Start() 之前调用
+func (s *UsageCleanupService) SetLeaderElector(leader *LeaderElector) {
+	s.leader = leader
+}
+
 func describeUsageCleanupFilters(filters UsageCleanupFilters) string {
 	var parts []string
 	parts = append(parts, "start="+filters.StartTime.UTC().Format(time.RFC3339))
@@ -153,7 +159,7 @@ func (s *UsageCleanupService) CreateTask(ctx context.Context, filters UsageClean
 
 func (s *UsageCleanupService) runOnce() {
 	svc := s
-	if svc == nil {
+	if svc == nil || !svc.leader.IsLeader() {
 		return
 	}
 	if !atomic.CompareAndSwapInt32(&svc.running, 0, 1) {
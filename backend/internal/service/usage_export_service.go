@@ -0,0 +1,345 @@
+package service
+
+import (
+	"context"
+	"encoding/csv"
+	"fmt"
+	"io"
+	"strconv"
+	"strings"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	infraerrors "github.com/Wei-Shaw/sub2api/internal/pkg/errors"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/pagination"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/parquet"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/timezone"
+	"github.com/Wei-Shaw/sub2api/internal/pkg/usagestats"
+)
+
+// 导出格式
+const (
+	UsageExportFormatCSV     = "csv"
+	UsageExportFormatParquet = "parquet"
+)
+
+var (
+	ErrUsageExportInvalidFormat   = infraerrors.BadRequest("USAGE_EXPORT_INVALID_FORMAT", "format must be csv or parquet")
+	ErrUsageExportRangeRequired   = infraerrors.BadRequest("USAGE_EXPORT_RANGE_REQUIRED", "start_date and end_date are required")
+	ErrUsageExportRangeTooLarge   = infraerrors.BadRequest("USAGE_EXPORT_RANGE_TOO_LARGE", "export range exceeds usage_export.max_range_days")
+	ErrUsageExportStorageDisabled = infraerrors.BadRequest("USAGE_EXPORT_STORAGE_DISABLED", "object storage is not configured")
+)
+
+// UsageExportResult 上传到对象存储的导出结果
+type UsageExportResult struct {
+	Bucket string `json:"bucket"`
+	Key    string `json:"key"`
+	Rows   int64  `json:"rows"`
+	Format string `json:"format"`
+}
+
+type usageExportColumn struct {
+	name     string
+	typ      parquet.Type
+	optional bool
+	value    func(l *UsageLog) any
+}
+
+func optionalInt(v *int) any {
+	if v == nil {
+		return nil
+	}
+	return int64(*v)
+}
+
+func optionalInt64(v *int64) any {
+	if v == nil {
+		return nil
+	}
+	return *v
+}
+
+// usageExportColumns 导出列（CSV 表头与 Parquet schema 共用）
+var usageExportColumns = []usageExportColumn{
+	{"id", parquet.Int64, false, func(l *UsageLog) any { return l.ID }},
+	{"created_at", parquet.TimestampMillis, false, func(l *UsageLog) any { return l.CreatedAt }},
+	{"request_id", parquet.String, false, func(l *UsageLog) any { return l.RequestID }},
+	{"user_id", parquet.Int64, false, func(l *UsageLog) any { return l.UserID }},
+	{"user_email", parquet.String, true, func(l *UsageLog) any {
+		if l.User == nil {
+			return nil
+		}
+		return l.User.Email
+	}},
+	{"api_key_id", parquet.Int64, false, func(l *UsageLog) any { return l.APIKeyID }},
+	{"api_key_name", parquet.String, true, func(l *UsageLog) any {
+		if l.APIKey == nil {
+			return nil
+		}
+		return l.APIKey.Name
+	}},
+	{"account_id", parquet.Int64, false, func(l *UsageLog) any { return l.AccountID }},
+	{"account_name", parquet.String, true, func(l *UsageLog) any {
+		if l.Account == nil {
+			return nil
+		}
+		return l.Account.Name
+	}},
+	{"group_id", parquet.Int64, true, func(l *UsageLog) any { return optionalInt64(l.GroupID) }},
+	{"group_name", parquet.String, true, func(l *UsageLog) any {
+		if l.Group == nil {
+			return nil
+		}
+		return l.Group.Name
+	}},
+	{"model", parquet.String, false, func(l *UsageLog) any { return l.Model }},
+	{"input_tokens", parquet.Int64, false, func(l *UsageLog) any { return l.InputTokens }},
+	{"output_tokens", parquet.Int64, false, func(l *UsageLog) any { return l.OutputTokens }},
+	{"cache_creation_tokens", parquet.Int64, false, func(l *UsageLog) any { return l.CacheCreationTokens }},
+	{"cache_read_tokens", parquet.Int64, false, func(l *UsageLog) any { return l.CacheReadTokens }},
+	{"reasoning_tokens", parquet.Int64, false, func(l *UsageLog) any { return l.ReasoningTokens }},
+	{"total_tokens", parquet.Int64, false, func(l *UsageLog) any { return l.TotalTokens() }},
+	{"input_cost", parquet.Double, false, func(l *UsageLog) any { return l.InputCost }},
+	{"output_cost", parquet.Double, false, func(l *UsageLog) any { return l.OutputCost }},
+	{"cache_creation_cost", parquet.Double, false, func(l *UsageLog) any { return l.CacheCreationCost }},
+	{"cache_read_cost", parquet.Double, false, func(l *UsageLog) any { return l.CacheReadCost }},
+	{"total_cost", parquet.Double, false, func(l *UsageLog) any { return l.TotalCost }},
+	{"actual_cost", parquet.Double, false, func(l *UsageLog) any { return l.ActualCost }},
+	{"rate_multiplier", parquet.Double, false, func(l *UsageLog) any { return l.RateMultiplier }},
+	{"billing_type", parquet.Int64, false, func(l *UsageLog) any { return int64(l.BillingType) }},
+	{"stream", parquet.Bool, false, func(l *UsageLog) any { return l.Stream }},
+	{"duration_ms", parquet.Int64, true, func(l *UsageLog) any { return optionalInt(l.DurationMs) }},
+	{"first_token_ms", parquet.Int64, true, func(l *UsageLog) any { return optionalInt(l.FirstTokenMs) }},
+}
+
+// usageExportEncoder 按格式逐行写出
+type usageExportEncoder interface {
+	WriteRow(l *UsageLog) error
+	Close() error
+}
+
+type csvUsageEncoder struct {
+	w      *csv.Writer
+	record []string
+}
+
+func newCSVUsageEncoder(w io.Writer) (*csvUsageEncoder, error) {
+	enc := &csvUsageEncoder{w: csv.NewWriter(w), record: make([]string, len(usageExportColumns))}
+	for i, col := range usageExportColumns {
+		enc.record[i] = col.name
+	}
+	if err := enc.w.Write(enc.record); err != nil {
+		return nil, err
+	}
+	return enc, nil
+}
+
+func (e *csvUsageEncoder) WriteRow(l *UsageLog) error {
+	for i, col := range usageExportColumns {
+		e.record[i] = formatCSVValue(col.value(l))
+	}
+	return e.w.Write(e.record)
+}
+
+func (e *csvUsageEncoder) Close() error {
+	e.w.Flush()
+	return e.w.Error()
+}
+
+func formatCSVValue(v any) string {
+	switch x := v.(type) {
+	case nil:
+		return ""
+	case string:
+		return escapeCSVFormula(x)
+	case int:
+		return strconv.Itoa(x)
+	case int64:
+		return strconv.FormatInt(x, 10)
+	case float64:
+		return strconv.FormatFloat(x, 'f', -1, 64)
+	case bool:
+		return strconv.FormatBool(x)
+	case time.Time:
+		return x.In(timezone.Location()).Format(time.RFC3339)
+	default:
+		return fmt.Sprint(x)
+	}
+}
+
+// escapeCSVFormula 防止用户可控的文本（Key 名称、请求 ID 等）在表格软件中被当作公式执行
+func escapeCSVFormula(s string) string {
+	if s != "" && strings.ContainsRune("=+-@\t\r", rune(s[0])) {
+		return "'" + s
+	}
+	return s
+}
+
+type parquetUsageEncoder struct {
+	w   *parquet.Writer
+	row []any
+}
+
+func newParquetUsageEncoder(w io.Writer, rowGroupSize int) (*parquetUsageEncoder, error) {
+	columns := make([]parquet.Column, len(usageExportColumns))
+	for i, col := range usageExportColumns {
+		columns[i] = parquet.Column{Name: col.name, Type: col.typ, Optional: col.optional}
+	}
+	pw, err := parquet.NewWriter(w, columns, rowGroupSize)
+	if err != nil {
+		return nil, err
+	}
+	return &parquetUsageEncoder{w: pw, row: make([]any, len(usageExportColumns))}, nil
+}
+
+func (e *parquetUsageEncoder) WriteRow(l *UsageLog) error {
+	for i, col := range usageExportColumns {
+		e.row[i] = col.value(l)
+	}
+	return e.w.Write(e.row)
+}
+
+func (e *parquetUsageEncoder) Close() error {
+	return e.w.Close()
+}
+
+// UsageExportService 将使用记录按过滤条件流式导出为 CSV / Parquet，可直接上传到对象存储
+//
+// 数据按 ID 分批（keyset）读取并逐批写出，内存占用与批大小相关，与导出总行数无关。
+type UsageExportService struct {
+	repo    AdminListRepository
+	cfg     config.UsageExportConfig
+	storage ObjectStorage
+}
+
+// NewUsageExportService 创建使用记录导出服务；storage 为 nil 时不支持上传
+func NewUsageExportService(repo AdminListRepository, storage ObjectStorage, cfg *config.Config) *UsageExportService {
+	s := &UsageExportService{repo: repo, storage: storage}
+	if cfg != nil {
+		s.cfg = cfg.UsageExport
+	}
+	if s.cfg.BatchSize <= 0 {
+		s.cfg.BatchSize = 5000
+	}
+	return s
+}
+
+// StorageEnabled 是否配置了对象存储
+func (s *UsageExportService) StorageEnabled() bool {
+	return s.storage != nil
+}
+
+// NormalizeUsageExportFormat 校验并规范化导出格式（默认 csv）
+func NormalizeUsageExportFormat(format string) (string, error) {
+	switch strings.ToLower(strings.TrimSpace(format)) {
+	case "", UsageExportFormatCSV:
+		return UsageExportFormatCSV, nil
+	case UsageExportFormatParquet:
+		return UsageExportFormatParquet, nil
+	default:
+		return "", ErrUsageExportInvalidFormat
+	}
+}
+
+// UsageExportContentType 返回导出格式对应的 Content-Type
+func UsageExportContentType(format string) string {
+	if format == UsageExportFormatParquet {
+		return "application/vnd.apache.parquet"
+	}
+	return "text/csv; charset=utf-8"
+}
+
+// UsageExportFileName 生成导出文件名，例如 usage_20260101_20260131.parquet
+func UsageExportFileName(filters usagestats.UsageLogFilters, format string) string {
+	name := "usage"
+	loc := timezone.Location()
+	if filters.StartTime != nil {
+		name += "_" + filters.StartTime.In(loc).Format("20060102")
+	}
+	if filters.EndTime != nil {
+		name += "_" + filters.EndTime.In(loc).Format("20060102")
+	}
+	return name + "." + format
+}
+
+// ValidateRange 导出必须指定时间范围，且不超过 max_range_days
+func (s *UsageExportService) ValidateRange(filters usagestats.UsageLogFilters) error {
+	if filters.StartTime == nil || filters.EndTime == nil {
+		return ErrUsageExportRangeRequired
+	}
+	if filters.EndTime.Sub(*filters.StartTime) > time.Duration(s.cfg.MaxRangeDays)*24*time.Hour {
+		return ErrUsageExportRangeTooLarge
+	}
+	return nil
+}
+
+// Export 将符合条件的使用记录写入 w，返回写出的行数
+func (s *UsageExportService) Export(ctx context.Context, filters usagestats.UsageLogFilters, format string, w io.Writer) (int64, error) {
+	if err := s.ValidateRange(filters); err != nil {
+		return 0, err
+	}
+
+	var (
+		enc usageExportEncoder
+		err error
+	)
+	switch format {
+	case UsageExportFormatCSV:
+		enc, err = newCSVUsageEncoder(w)
+	case UsageExportFormatParquet:
+		enc, err = newParquetUsageEncoder(w, s.cfg.BatchSize)
+	default:
+		return 0, ErrUsageExportInvalidFormat
+	}
+	if err != nil {
+		return 0, err
+	}
+
+	var rows int64
+	q := CursorQuery{SortBy: "id", Limit: s.cfg.BatchSize}
+	for {
+		if err := ctx.Err(); err != nil {
+			return rows, err
+		}
+		logs, err := s.repo.ListUsageLogsByCursor(ctx, filters, q)
+		if err != nil {
+			return rows, fmt.Errorf("load usage logs: %w", err)
+		}
+		for i := range logs {
+			if err := enc.WriteRow(&logs[i]); err != nil {
+				return rows, err
+			}
+		}
+		rows += int64(len(logs))
+		if len(logs) < q.Limit {
+			break
+		}
+		q.After = &pagination.Cursor{SortBy: "id", SortOrder: "asc", ID: logs[len(logs)-1].ID}
+	}
+	if err := enc.Close(); err != nil {
+		return rows, err
+	}
+	return rows, nil
+}
+
+// ExportToStorage 导出并以分片上传方式直接写入对象存储
+func (s *UsageExportService) ExportToStorage(ctx context.Context, filters usagestats.UsageLogFilters, format string) (*UsageExportResult, error) {
+	if s.storage == nil {
+		return nil, ErrUsageExportStorageDisabled
+	}
+	if err := s.ValidateRange(filters); err != nil {
+		return nil, err
+	}
+
+	key := s.cfg.StoragePrefix + time.Now().In(timezone.Location()).Format("20060102-150405") + "_" + UsageExportFileName(filters, format)
+	w := s.storage.NewWriter(ctx, key, UsageExportContentType(format))
+	rows, err := s.Export(ctx, filters, format, w)
+	if err != nil {
+		w.Abort()
+		return nil, err
+	}
+	if err := w.Close(); err != nil {
+		return nil, fmt.Errorf("upload usage export: %w", err)
+	}
+	return &UsageExportResult{Bucket: s.storage.Bucket(), Key: s.storage.FullKey(key), Rows: rows, Format: format}, nil
+}
@@ -25,6 +25,8 @@ type UsageLog struct {
 	OutputTokens        int
 	CacheCreationTokens int
 	CacheReadTokens     int
+	// ReasoningTokens 输出中用于推理/思考的 token 数（已包含在 OutputTokens 内，仅用于统计展示）
+	ReasoningTokens int
 
 	CacheCreation5mTokens int `gorm:"column:cache_creation_5m_tokens"`
 	CacheCreation1hTokens int `gorm:"column:cache_creation_1h_tokens"`
@@ -49,6 +51,15 @@ type UsageLog struct {
 	// Cache TTL Override 标记（管理员强制替换了缓存 TTL 计费）
 	CacheTTLOverridden bool
 
+	// PromptFirewall 提示词防火墙处理结果（如 "flag:jailbreak"），nil 表示未命中
+	PromptFirewall *string
+
+	// SystemFingerprint 实际使用的上游 + 模型 + 适配器版本指纹（见 SystemFingerprint），nil 表示历史数据
+	SystemFingerprint *string
+
+	// UsageEstimated 上游未返回用量，token 数为本地分词器估算值（计费时应用 billing.estimated_usage_margin）
+	UsageEstimated bool
+
 	// 图片生成字段
 	ImageCount int
 	ImageSize  *string
@@ -38,6 +38,11 @@ func (u *User) IsAdmin() bool {
 	return u.Role == RoleAdmin
 }
 
+// IsStaff 是否为后台账号（超级管理员或细分后台角色）
+func (u *User) IsStaff() bool {
+	return IsAdminRole(u.Role)
+}
+
 func (u *User) IsActive() bool {
 	return u.Status == StatusActive
 }
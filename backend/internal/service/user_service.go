@@ -65,6 +65,7 @@ type UserService struct {
 	userRepo             UserRepository
 	authCacheInvalidator APIKeyAuthCacheInvalidator
 	billingCache         BillingCache
+	adminUsers           *AdminUserService
 }
 
 // NewUserService 创建用户服务实例
@@ -76,6 +77,11 @@ func NewUserService(userRepo UserRepository, authCacheInvalidator APIKeyAuthCach
 	}
 }
 
+// SetAdminUserService 注入后台账号服务，后台账号修改密码时执行密码策略并记录改密时间
+func (s *UserService) SetAdminUserService(adminUsers *AdminUserService) {
+	s.adminUsers = adminUsers
+}
+
 // GetFirstAdmin 获取首个管理员用户（用于 Admin API Key 认证）
 func (s *UserService) GetFirstAdmin(ctx context.Context) (*User, error) {
 	admin, err := s.userRepo.GetFirstAdmin(ctx)
@@ -146,6 +152,13 @@ func (s *UserService) ChangePassword(ctx context.Context, userID int64, req Chan
 		return ErrPasswordIncorrect
 	}
 
+	staff := user.IsStaff() && s.adminUsers != nil
+	if staff {
+		if err := s.adminUsers.ValidateNewPassword(user, req.NewPassword); err != nil {
+			return err
+		}
+	}
+
 	if err := user.SetPassword(req.NewPassword); err != nil {
 		return fmt.Errorf("set password: %w", err)
 	}
@@ -158,6 +171,12 @@ func (s *UserService) ChangePassword(ctx context.Context, userID int64, req Chan
 		return fmt.Errorf("update user: %w", err)
 	}
 
+	if staff {
+		if err := s.adminUsers.RecordPasswordChange(ctx, user.ID); err != nil {
+			return fmt.Errorf("record password change: %w", err)
+		}
+	}
+
 	return nil
 }
 
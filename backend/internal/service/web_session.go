@@ -0,0 +1,245 @@
+package service
+
+import (
+	"context"
+	"crypto/rand"
+	"encoding/hex"
+	"errors"
+	"fmt"
+	"net/http"
+	"net/url"
+	"strings"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	infraerrors "github.com/Wei-Shaw/sub2api/internal/pkg/errors"
+)
+
+var (
+	// ErrWebSessionNotFound 会话不存在（已过期或已被撤销），由存储层返回
+	ErrWebSessionNotFound = errors.New("web session not found")
+
+	ErrWebSessionInvalid  = infraerrors.Unauthorized("WEB_SESSION_INVALID", "session is invalid or has been revoked")
+	ErrWebSessionDisabled = infraerrors.Forbidden("WEB_SESSION_DISABLED", "web sessions are disabled")
+)
+
+// webSessionTouchInterval 滑动续期的最小间隔，避免每个请求都写 Redis
+const webSessionTouchInterval = time.Minute
+
+// WebSession 管理后台 Cookie 会话（存储在 Redis 中，不保存原始 Token）
+type WebSession struct {
+	ID           string    `json:"id"` // Token 的 SHA256 哈希
+	UserID       int64     `json:"user_id"`
+	TokenVersion int64     `json:"token_version"` // 用户改密后旧会话失效
+	IPAddress    string    `json:"ip_address"`
+	UserAgent    string    `json:"user_agent"`
+	CreatedAt    time.Time `json:"created_at"`
+	LastSeenAt   time.Time `json:"last_seen_at"`
+	ExpiresAt    time.Time `json:"expires_at"` // 绝对过期时间
+}
+
+// WebSessionStore 管理会话的持久化
+//
+// Key 格式:
+//   - web_session:{session_id}        -> WebSession (JSON)
+//   - user_web_sessions:{user_id}     -> Set<session_id>
+type WebSessionStore interface {
+	// SaveSession 写入会话并设置 TTL（同时维护用户会话集合）
+	SaveSession(ctx context.Context, session *WebSession, ttl time.Duration) error
+	// GetSession 返回 (nil, ErrWebSessionNotFound) 如果会话不存在
+	GetSession(ctx context.Context, sessionID string) (*WebSession, error)
+	DeleteSession(ctx context.Context, userID int64, sessionID string) error
+	ListUserSessions(ctx context.Context, userID int64) ([]*WebSession, error)
+	DeleteUserSessions(ctx context.Context, userID int64) error
+}
+
+// WebSessionCookieOptions 会话 Cookie 的属性
+type WebSessionCookieOptions struct {
+	Name     string
+	Domain   string
+	Secure   bool
+	SameSite http.SameSite
+	MaxAge   int // 秒
+}
+
+// WebSessionService 基于 Redis 的管理后台会话服务
+// 会话支持滑动过期（idle timeout）与绝对过期（absolute timeout），并可在服务端即时撤销。
+type WebSessionService struct {
+	store WebSessionStore
+	cfg   *config.Config
+}
+
+// NewWebSessionService 创建会话服务
+func NewWebSessionService(store WebSessionStore, cfg *config.Config) *WebSessionService {
+	return &WebSessionService{store: store, cfg: cfg}
+}
+
+// Enabled 是否启用 Cookie 会话
+func (s *WebSessionService) Enabled() bool {
+	return s != nil && s.store != nil && s.cfg != nil && s.cfg.WebSession.Enabled
+}
+
+func (s *WebSessionService) idleTimeout() time.Duration {
+	return time.Duration(s.cfg.WebSession.IdleTimeoutMinutes) * time.Minute
+}
+
+func (s *WebSessionService) absoluteTimeout() time.Duration {
+	return time.Duration(s.cfg.WebSession.AbsoluteTimeoutHours) * time.Hour
+}
+
+// CookieOptions 返回会话 Cookie 属性
+func (s *WebSessionService) CookieOptions() WebSessionCookieOptions {
+	opts := WebSessionCookieOptions{
+		Name:     s.cfg.WebSession.CookieName,
+		Domain:   s.cfg.WebSession.CookieDomain,
+		Secure:   s.cfg.WebSession.Secure,
+		SameSite: http.SameSiteStrictMode,
+		MaxAge:   int(s.absoluteTimeout().Seconds()),
+	}
+	// same_site=none 在配置校验阶段拒绝（跨站请求会携带 Cookie）
+	if s.cfg.WebSession.SameSite == "lax" {
+		opts.SameSite = http.SameSiteLaxMode
+	}
+	return opts
+}
+
+// TrustedOrigin 判断 Cookie 会话写请求的来源（Origin，缺失时取 Referer）是否可信，用于防御 CSRF：
+// 与请求 Host 同源，或与 server.frontend_url / cors.allowed_origins 中的来源一致。来源为空或无法解析时不可信。
+func (s *WebSessionService) TrustedOrigin(origin, requestHost string) bool {
+	u, err := url.Parse(strings.TrimSpace(origin))
+	if err != nil || (u.Scheme != "http" && u.Scheme != "https") || u.Host == "" {
+		return false
+	}
+	if requestHost != "" && strings.EqualFold(u.Host, requestHost) {
+		return true
+	}
+	if s == nil || s.cfg == nil {
+		return false
+	}
+	normalized := strings.ToLower(u.Scheme + "://" + u.Host)
+	trusted := append([]string{s.cfg.Server.FrontendURL}, s.cfg.CORS.AllowedOrigins...)
+	for _, candidate := range trusted {
+		c, err := url.Parse(strings.TrimSpace(candidate))
+		if err != nil || c.Host == "" {
+			// 通配符 "*" 等不视为可信来源
+			continue
+		}
+		if strings.ToLower(c.Scheme+"://"+c.Host) == normalized {
+			return true
+		}
+	}
+	return false
+}
+
+// Create 为用户创建新会话，返回需要写入 Cookie 的原始 Token
+func (s *WebSessionService) Create(ctx context.Context, user *User, ipAddress, userAgent string) (string, *WebSession, error) {
+	if !s.Enabled() {
+		return "", nil, ErrWebSessionDisabled
+	}
+	if user == nil {
+		return "", nil, fmt.Errorf("user is nil")
+	}
+
+	buf := make([]byte, 32)
+	if _, err := rand.Read(buf); err != nil {
+		return "", nil, fmt.Errorf("generate session token: %w", err)
+	}
+	token := hex.EncodeToString(buf)
+
+	now := time.Now()
+	session := &WebSession{
+		ID:           hashToken(token),
+		UserID:       user.ID,
+		TokenVersion: user.TokenVersion,
+		IPAddress:    ipAddress,
+		UserAgent:    truncateString(userAgent, 256),
+		CreatedAt:    now,
+		LastSeenAt:   now,
+		ExpiresAt:    now.Add(s.absoluteTimeout()),
+	}
+	if err := s.store.SaveSession(ctx, session, s.slidingTTL(session, now)); err != nil {
+		return "", nil, fmt.Errorf("save web session: %w", err)
+	}
+	return token, session, nil
+}
+
+// Authenticate 校验 Cookie 中的 Token，并在需要时滑动续期
+func (s *WebSessionService) Authenticate(ctx context.Context, token string) (*WebSession, error) {
+	if !s.Enabled() {
+		return nil, ErrWebSessionDisabled
+	}
+	if token == "" {
+		return nil, ErrWebSessionInvalid
+	}
+
+	session, err := s.store.GetSession(ctx, hashToken(token))
+	if err != nil {
+		if errors.Is(err, ErrWebSessionNotFound) {
+			return nil, ErrWebSessionInvalid
+		}
+		return nil, err
+	}
+
+	now := time.Now()
+	if !now.Before(session.ExpiresAt) {
+		_ = s.store.DeleteSession(ctx, session.UserID, session.ID)
+		return nil, ErrWebSessionInvalid
+	}
+
+	if now.Sub(session.LastSeenAt) >= webSessionTouchInterval {
+		session.LastSeenAt = now
+		if err := s.store.SaveSession(ctx, session, s.slidingTTL(session, now)); err != nil {
+			// 续期失败不影响本次请求
+			return session, nil
+		}
+	}
+	return session, nil
+}
+
+// slidingTTL 计算滑动 TTL：不超过 idle timeout，也不超过绝对过期时间
+func (s *WebSessionService) slidingTTL(session *WebSession, now time.Time) time.Duration {
+	ttl := s.idleTimeout()
+	if remaining := session.ExpiresAt.Sub(now); remaining < ttl {
+		ttl = remaining
+	}
+	return ttl
+}
+
+// Revoke 撤销 Token 对应的会话（用于登出）
+func (s *WebSessionService) Revoke(ctx context.Context, token string) error {
+	if !s.Enabled() || token == "" {
+		return nil
+	}
+	session, err := s.store.GetSession(ctx, hashToken(token))
+	if err != nil {
+		if errors.Is(err, ErrWebSessionNotFound) {
+			return nil
+		}
+		return err
+	}
+	return s.store.DeleteSession(ctx, session.UserID, session.ID)
+}
+
+// RevokeByID 撤销用户的指定会话
+func (s *WebSessionService) RevokeByID(ctx context.Context, userID int64, sessionID string) error {
+	if !s.Enabled() {
+		return ErrWebSessionDisabled
+	}
+	return s.store.DeleteSession(ctx, userID, sessionID)
+}
+
+// RevokeAllForUser 撤销用户的全部会话（例如运维人员离职时）
+func (s *WebSessionService) RevokeAllForUser(ctx context.Context, userID int64) error {
+	if !s.Enabled() {
+		return nil
+	}
+	return s.store.DeleteUserSessions(ctx, userID)
+}
+
+// ListForUser 列出用户当前的有效会话
+func (s *WebSessionService) ListForUser(ctx context.Context, userID int64) ([]*WebSession, error) {
+	if !s.Enabled() {
+		return []*WebSession{}, nil
+	}
+	return s.store.ListUserSessions(ctx, userID)
+}
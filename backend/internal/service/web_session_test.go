@@ -0,0 +1,189 @@
+//go:build unit
+
+package service
+
+import (
+	"context"
+	"net/http"
+	"testing"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/stretchr/testify/require"
+)
+
+type webSessionStoreStub struct {
+	sessions map[string]*WebSession
+	ttls     map[string]time.Duration
+}
+
+func newWebSessionStoreStub() *webSessionStoreStub {
+	return &webSessionStoreStub{
+		sessions: map[string]*WebSession{},
+		ttls:     map[string]time.Duration{},
+	}
+}
+
+func (s *webSessionStoreStub) SaveSession(ctx context.Context, session *WebSession, ttl time.Duration) error {
+	cp := *session
+	s.sessions[session.ID] = &cp
+	s.ttls[session.ID] = ttl
+	return nil
+}
+
+func (s *webSessionStoreStub) GetSession(ctx context.Context, sessionID string) (*WebSession, error) {
+	session, ok := s.sessions[sessionID]
+	if !ok {
+		return nil, ErrWebSessionNotFound
+	}
+	cp := *session
+	return &cp, nil
+}
+
+func (s *webSessionStoreStub) DeleteSession(ctx context.Context, userID int64, sessionID string) error {
+	delete(s.sessions, sessionID)
+	return nil
+}
+
+func (s *webSessionStoreStub) ListUserSessions(ctx context.Context, userID int64) ([]*WebSession, error) {
+	out := make([]*WebSession, 0)
+	for _, session := range s.sessions {
+		if session.UserID == userID {
+			out = append(out, session)
+		}
+	}
+	return out, nil
+}
+
+func (s *webSessionStoreStub) DeleteUserSessions(ctx context.Context, userID int64) error {
+	for id, session := range s.sessions {
+		if session.UserID == userID {
+			delete(s.sessions, id)
+		}
+	}
+	return nil
+}
+
+func newTestWebSessionService(store WebSessionStore) *WebSessionService {
+	cfg := &config.Config{
+		WebSession: config.WebSessionConfig{
+			Enabled:              true,
+			CookieName:           "sub2api_session",
+			Secure:               true,
+			SameSite:             "lax",
+			IdleTimeoutMinutes:   30,
+			AbsoluteTimeoutHours: 24,
+		},
+	}
+	return NewWebSessionService(store, cfg)
+}
+
+func TestWebSessionService_CreateAndAuthenticate(t *testing.T) {
+	store := newWebSessionStoreStub()
+	svc := newTestWebSessionService(store)
+
+	token, session, err := svc.Create(context.Background(), &User{ID: 7, TokenVersion: 3}, "1.2.3.4", "ua")
+	require.NoError(t, err)
+	require.NotEmpty(t, token)
+	require.NotEqual(t, token, session.ID, "raw token must not be stored")
+	require.Equal(t, 30*time.Minute, store.ttls[session.ID])
+
+	got, err := svc.Authenticate(context.Background(), token)
+	require.NoError(t, err)
+	require.Equal(t, int64(7), got.UserID)
+	require.Equal(t, int64(3), got.TokenVersion)
+}
+
+func TestWebSessionService_SlidingExpirationCappedByAbsolute(t *testing.T) {
+	store := newWebSessionStoreStub()
+	svc := newTestWebSessionService(store)
+
+	token, session, err := svc.Create(context.Background(), &User{ID: 1}, "", "")
+	require.NoError(t, err)
+
+	// 模拟会话已空闲一段时间，且距离绝对过期只剩 10 分钟
+	stored := store.sessions[session.ID]
+	stored.LastSeenAt = time.Now().Add(-5 * time.Minute)
+	stored.ExpiresAt = time.Now().Add(10 * time.Minute)
+
+	_, err = svc.Authenticate(context.Background(), token)
+	require.NoError(t, err)
+	require.LessOrEqual(t, store.ttls[session.ID], 10*time.Minute)
+	require.WithinDuration(t, time.Now(), store.sessions[session.ID].LastSeenAt, time.Second)
+}
+
+func TestWebSessionService_ExpiredSessionRejected(t *testing.T) {
+	store := newWebSessionStoreStub()
+	svc := newTestWebSessionService(store)
+
+	token, session, err := svc.Create(context.Background(), &User{ID: 1}, "", "")
+	require.NoError(t, err)
+	store.sessions[session.ID].ExpiresAt = time.Now().Add(-time.Second)
+
+	_, err = svc.Authenticate(context.Background(), token)
+	require.ErrorIs(t, err, ErrWebSessionInvalid)
+	require.NotContains(t, store.sessions, session.ID)
+}
+
+func TestWebSessionService_Revoke(t *testing.T) {
+	store := newWebSessionStoreStub()
+	svc := newTestWebSessionService(store)
+
+	tokenA, _, err := svc.Create(context.Background(), &User{ID: 1}, "", "")
+	require.NoError(t, err)
+	tokenB, _, err := svc.Create(context.Background(), &User{ID: 1}, "", "")
+	require.NoError(t, err)
+	tokenC, _, err := svc.Create(context.Background(), &User{ID: 2}, "", "")
+	require.NoError(t, err)
+
+	require.NoError(t, svc.Revoke(context.Background(), tokenA))
+	_, err = svc.Authenticate(context.Background(), tokenA)
+	require.ErrorIs(t, err, ErrWebSessionInvalid)
+
+	require.NoError(t, svc.RevokeAllForUser(context.Background(), 1))
+	_, err = svc.Authenticate(context.Background(), tokenB)
+	require.ErrorIs(t, err, ErrWebSessionInvalid)
+
+	_, err = svc.Authenticate(context.Background(), tokenC)
+	require.NoError(t, err)
+}
+
+func TestWebSessionService_DisabledAndCookieOptions(t *testing.T) {
+	var nilSvc *WebSessionService
+	require.False(t, nilSvc.Enabled())
+
+	svc := newTestWebSessionService(newWebSessionStoreStub())
+	opts := svc.CookieOptions()
+	require.Equal(t, "sub2api_session", opts.Name)
+	require.Equal(t, http.SameSiteLaxMode, opts.SameSite)
+	require.True(t, opts.Secure)
+	require.Equal(t, 24*3600, opts.MaxAge)
+
+	svc.cfg.WebSession.Enabled = false
+	_, _, err := svc.Create(context.Background(), &User{ID: 1}, "", "")
+	require.ErrorIs(t, err, ErrWebSessionDisabled)
+}
+
+func TestWebSessionService_TrustedOrigin(t *testing.T) {
+	svc := newTestWebSessionService(newWebSessionStoreStub())
+	svc.cfg.Server.FrontendURL = "https://console.example.com/app"
+	svc.cfg.CORS.AllowedOrigins = []string{"*", "https://admin.example.com"}
+
+	cases := []struct {
+		origin string
+		want   bool
+	}{
+		{origin: "https://api.example.com", want: true},
+		{origin: "https://API.example.com/settings?tab=1", want: true},
+		{origin: "https://console.example.com", want: true},
+		{origin: "https://admin.example.com", want: true},
+		{origin: "https://evil.example.com", want: false},
+		{origin: "https://api.example.com.evil.com", want: false},
+		{origin: "null", want: false},
+		{origin: "", want: false},
+		{origin: "javascript:alert(1)", want: false},
+	}
+	for _, tc := range cases {
+		require.Equal(t, tc.want, svc.TrustedOrigin(tc.origin, "api.example.com"), tc.origin)
+	}
+}
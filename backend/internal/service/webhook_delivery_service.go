@@ -0,0 +1,72 @@
+package service
+
+import (
+	"context"
+	"encoding/json"
+	"fmt"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/pkg/logger"
+)
+
+// JobTopicWebhook 出站 Webhook（运维告警 / 定时报告的 Discord 通知）投递主题
+const JobTopicWebhook = "webhook"
+
+// webhookDeliveryTimeout 单次 Webhook 投递超时
+const webhookDeliveryTimeout = 30 * time.Second
+
+// webhookJob Webhook 投递任务载荷
+type webhookJob struct {
+	URL     string                 `json:"url"`
+	Message *DiscordWebhookMessage `json:"message"`
+}
+
+// WebhookDeliveryService 出站 Webhook 投递。
+//
+// 启用持久化任务队列时，Execute 只负责入队，由队列消费者调用 Webhook（失败按至少一次语义重试，
+// 超过次数进入死信）；否则直接同步调用，与未接入队列前一致。
+type WebhookDeliveryService struct {
+	client   DiscordWebhookClient
+	jobQueue *JobQueueService
+}
+
+// NewWebhookDeliveryService 创建出站 Webhook 投递服务
+func NewWebhookDeliveryService(client DiscordWebhookClient, jobQueue *JobQueueService) *WebhookDeliveryService {
+	s := &WebhookDeliveryService{client: client}
+	if jobQueue.Enabled() {
+		s.jobQueue = jobQueue
+		jobQueue.RegisterHandler(JobTopicWebhook, 1, webhookDeliveryTimeout, s.handleJob)
+	}
+	return s
+}
+
+// Execute 投递一条 Webhook 消息；入队失败时退回同步调用
+func (s *WebhookDeliveryService) Execute(ctx context.Context, webhookURL string, msg *DiscordWebhookMessage) error {
+	if s.jobQueue != nil {
+		_, err := s.jobQueue.Enqueue(ctx, JobTopicWebhook, webhookJob{URL: webhookURL, Message: msg})
+		if err == nil {
+			return nil
+		}
+		logger.LegacyPrintf("service.webhook_delivery", "[WebhookDelivery] Enqueue failed, delivering in-process: %v", err)
+	}
+	return s.client.Execute(ctx, webhookURL, msg)
+}
+
+// handleJob 调用 Webhook；返回错误时任务由队列重新投递
+func (s *WebhookDeliveryService) handleJob(ctx context.Context, job *QueuedJob) error {
+	var payload webhookJob
+	if err := json.Unmarshal(job.Payload, &payload); err != nil || payload.Message == nil {
+		// 载荷损坏无法通过重试恢复，直接确认丢弃
+		logger.LegacyPrintf("service.webhook_delivery", "[WebhookDelivery] Drop malformed job %s: %v", job.ID, err)
+		return nil
+	}
+	// 队列中的地址同样只允许 Discord 官方域名
+	if err := validateDiscordWebhookURL(payload.URL); err != nil {
+		logger.LegacyPrintf("service.webhook_delivery", "[WebhookDelivery] Drop job %s: %v", job.ID, err)
+		return nil
+	}
+	if err := s.client.Execute(ctx, payload.URL, payload.Message); err != nil {
+		return fmt.Errorf("deliver webhook: %w", err)
+	}
+	return nil
+}
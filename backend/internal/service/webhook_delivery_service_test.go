@@ -0,0 +1,40 @@
+//go:build unit
+
+package service
+
+import (
+	"context"
+	"testing"
+
+	"github.com/Wei-Shaw/sub2api/internal/config"
+	"github.com/stretchr/testify/require"
+)
+
+func TestWebhookDeliveryService_DirectWhenJobQueueDisabled(t *testing.T) {
+	client := &discordWebhookClientStub{}
+	queue := newJobQueueStub()
+	svc := NewWebhookDeliveryService(client, NewJobQueueService(queue, &config.Config{}))
+
+	require.NoError(t, svc.Execute(context.Background(), testOpsDiscordWebhook, &DiscordWebhookMessage{Content: "hi"}))
+	require.Equal(t, []string{testOpsDiscordWebhook}, client.urls)
+	require.Empty(t, queue.enqueued[JobTopicWebhook])
+}
+
+func TestWebhookDeliveryService_EnqueuesAndDeliversFromJobQueue(t *testing.T) {
+	ctx := context.Background()
+	client := &discordWebhookClientStub{}
+	queue := newJobQueueStub()
+	svc := NewWebhookDeliveryService(client, newTestJobQueueService(queue))
+
+	require.NoError(t, svc.Execute(ctx, testOpsDiscordWebhook, &DiscordWebhookMessage{Content: "hi"}))
+	require.Empty(t, client.urls, "delivery must happen in the queue consumer")
+	require.Len(t, queue.enqueued[JobTopicWebhook], 1)
+
+	require.NoError(t, svc.handleJob(ctx, &QueuedJob{ID: "1-0", Payload: queue.enqueued[JobTopicWebhook][0]}))
+	require.Equal(t, []string{testOpsDiscordWebhook}, client.urls)
+	require.Equal(t, "hi", client.sent[0].Content)
+
+	// 非 Discord 地址的任务直接确认丢弃
+	require.NoError(t, svc.handleJob(ctx, &QueuedJob{ID: "2-0", Payload: []byte(`{"url":"https://evil.test/hook","message":{"content":"x"}}`)}))
+	require.Len(t, client.urls, 1)
+}
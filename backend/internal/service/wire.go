@@ -33,8 +33,35 @@ func ProvideUpdateService(cache UpdateCache, githubClient GitHubReleaseClient, b
 }
 
 // ProvideEmailQueueService creates EmailQueueService with default worker count
-func ProvideEmailQueueService(emailService *EmailService) *EmailQueueService {
-	return NewEmailQueueService(emailService, 3)
+func ProvideEmailQueueService(emailService *EmailService, jobQueue *JobQueueService) *EmailQueueService {
+	return NewEmailQueueServiceWithJobQueue(emailService, jobQueue, 3)
+}
+
+// ProvideJobQueueService 创建并启动持久化任务队列
+func ProvideJobQueueService(queue JobQueue, cfg *config.Config) *JobQueueService {
+	svc := NewJobQueueService(queue, cfg)
+	// API 副本只负责入队，由 worker 消费
+	startBackgroundJob(cfg, "JobQueueService", svc.Start)
+	return svc
+}
+
+// startBackgroundJob 仅在当前进程负责后台任务时启动（worker.mode=api 时交给独立 worker 进程）
+func startBackgroundJob(cfg *config.Config, name string, start func()) {
+	if cfg != nil && !cfg.Worker.RunsBackgroundJobs() {
+		logger.LegacyPrintf("service.worker", "[Worker] %s not started in this process (worker.mode=%s)", name, cfg.Worker.Mode)
+		return
+	}
+	start()
+}
+
+// ProvideLeaderElector 创建后台单例任务选主器；仅在运行后台任务的进程中参与选举
+func ProvideLeaderElector(lock LeaderLock, cfg *config.Config) *LeaderElector {
+	if cfg != nil && !cfg.Worker.RunsBackgroundJobs() {
+		return nil
+	}
+	elector := NewLeaderElector(lock, cfg)
+	elector.Start()
+	return elector
 }
 
 // ProvideTokenRefreshService creates and starts TokenRefreshService
@@ -45,41 +72,209 @@ func ProvideTokenRefreshService(
 	openaiOAuthService *OpenAIOAuthService,
 	geminiOAuthService *GeminiOAuthService,
 	antigravityOAuthService *AntigravityOAuthService,
+	copilotService *CopilotService,
+	sessionHookClient SessionRefreshHookClient,
 	cacheInvalidator TokenCacheInvalidator,
 	schedulerCache SchedulerCache,
+	leader *LeaderElector,
 	cfg *config.Config,
 ) *TokenRefreshService {
-	svc := NewTokenRefreshService(accountRepo, oauthService, openaiOAuthService, geminiOAuthService, antigravityOAuthService, cacheInvalidator, schedulerCache, cfg)
+	svc := NewTokenRefreshService(accountRepo, oauthService, openaiOAuthService, geminiOAuthService, antigravityOAuthService, copilotService, cacheInvalidator, schedulerCache, cfg)
 	// 注入 Sora 账号扩展表仓储，用于 OpenAI Token 刷新时同步 sora_accounts 表
 	svc.SetSoraAccountRepo(soraAccountRepo)
-	svc.Start()
+	svc.SetSessionRefreshHookClient(sessionHookClient)
+	svc.SetLeaderElector(leader)
+	startBackgroundJob(cfg, "TokenRefreshService", svc.Start)
 	return svc
 }
 
 // ProvideDashboardAggregationService 创建并启动仪表盘聚合服务
-func ProvideDashboardAggregationService(repo DashboardAggregationRepository, timingWheel *TimingWheelService, cfg *config.Config) *DashboardAggregationService {
+func ProvideDashboardAggregationService(repo DashboardAggregationRepository, timingWheel *TimingWheelService, jobQueue *JobQueueService, leader *LeaderElector, cfg *config.Config) *DashboardAggregationService {
 	svc := NewDashboardAggregationService(repo, timingWheel, cfg)
-	svc.Start()
+	svc.SetJobQueue(jobQueue)
+	svc.SetLeaderElector(leader)
+	startBackgroundJob(cfg, "DashboardAggregationService", svc.Start)
 	return svc
 }
 
 // ProvideUsageCleanupService 创建并启动使用记录清理任务服务
-func ProvideUsageCleanupService(repo UsageCleanupRepository, timingWheel *TimingWheelService, dashboardAgg *DashboardAggregationService, cfg *config.Config) *UsageCleanupService {
+func ProvideUsageCleanupService(repo UsageCleanupRepository, timingWheel *TimingWheelService, dashboardAgg *DashboardAggregationService, leader *LeaderElector, cfg *config.Config) *UsageCleanupService {
 	svc := NewUsageCleanupService(repo, timingWheel, dashboardAgg, cfg)
-	svc.Start()
+	svc.SetLeaderElector(leader)
+	startBackgroundJob(cfg, "UsageCleanupService", svc.Start)
 	return svc
 }
 
 // ProvideAccountExpiryService creates and starts AccountExpiryService.
-func ProvideAccountExpiryService(accountRepo AccountRepository) *AccountExpiryService {
+func ProvideAccountExpiryService(accountRepo AccountRepository, leader *LeaderElector, cfg *config.Config) *AccountExpiryService {
 	svc := NewAccountExpiryService(accountRepo, time.Minute)
-	svc.Start()
+	svc.SetLeaderElector(leader)
+	startBackgroundJob(cfg, "AccountExpiryService", svc.Start)
 	return svc
 }
 
 // ProvideSubscriptionExpiryService creates and starts SubscriptionExpiryService.
-func ProvideSubscriptionExpiryService(userSubRepo UserSubscriptionRepository) *SubscriptionExpiryService {
+func ProvideSubscriptionExpiryService(userSubRepo UserSubscriptionRepository, leader *LeaderElector, cfg *config.Config) *SubscriptionExpiryService {
 	svc := NewSubscriptionExpiryService(userSubRepo, time.Minute)
+	svc.SetLeaderElector(leader)
+	startBackgroundJob(cfg, "SubscriptionExpiryService", svc.Start)
+	return svc
+}
+
+// ProvideTrashService creates TrashService and starts the purge job.
+func ProvideTrashService(repo TrashRepository, apiKeyService *APIKeyService, leader *LeaderElector, cfg *config.Config) *TrashService {
+	svc := NewTrashService(repo, apiKeyService, cfg)
+	svc.SetLeaderElector(leader)
+	startBackgroundJob(cfg, "TrashService", svc.Start)
+	return svc
+}
+
+// ProvideNotificationService 创建通知服务，并由邮件队列回写发送结果
+func ProvideNotificationService(repo NotificationRepository, settingRepo SettingRepository, emailQueue *EmailQueueService, cfg *config.Config) *NotificationService {
+	svc := NewNotificationService(repo, settingRepo, emailQueue, cfg)
+	emailQueue.SetSendRecorder(svc)
+	return svc
+}
+
+// ProvideTelegramService 创建 Telegram 机器人并启动命令轮询（告警检查由定时任务驱动）
+func ProvideTelegramService(
+	client TelegramClient,
+	cache TelegramStateCache,
+	locker CronJobLocker,
+	accountRepo AccountRepository,
+	adminService AdminService,
+	dashboard *DashboardService,
+	quotaTracker *AccountQuotaBudgetTracker,
+	cfg *config.Config,
+) *TelegramService {
+	svc := NewTelegramService(client, cache, locker, accountRepo, adminService, dashboard, quotaTracker, cfg)
+	startBackgroundJob(cfg, "TelegramService", svc.Start)
+	return svc
+}
+
+// ProvideCronJobService 创建定时任务调度服务并注册内置任务
+func ProvideCronJobService(
+	repo CronJobStateRepository,
+	locker CronJobLocker,
+	dashboardAgg *DashboardAggregationService,
+	trash *TrashService,
+	requestLog *RequestLogService,
+	statements *StatementService,
+	notifications *NotificationService,
+	telegram *TelegramService,
+	conversations *ConversationService,
+	attachments *AttachmentService,
+	cfg *config.Config,
+) *CronJobService {
+	svc := NewCronJobService(repo, locker, cfg)
+	builtins := []CronJob{
+		{
+			Name:        "dashboard_recompute",
+			Description: "重新计算前一天的仪表盘预聚合与用量汇总，修正迟到的使用记录",
+			Schedule:    "30 3 * * *",
+			Timeout:     defaultDashboardAggregationBackfillTimeout,
+			Run:         dashboardAgg.RecomputePreviousDay,
+		},
+		{
+			Name:        "trash_purge",
+			Description: "清除回收站中超过保留期的 API Key 与账号（默认由回收站自身的间隔任务执行）",
+			Schedule:    "0 4 * * *",
+			Disabled:    true,
+			Timeout:     10 * time.Minute,
+			Run:         trash.PurgeExpired,
+		},
+		{
+			Name:        "request_log_purge",
+			Description: "清除超过保留天数的网关请求日志",
+			Schedule:    "20 * * * *",
+			Timeout:     10 * time.Minute,
+			Run:         requestLog.PurgeExpired,
+		},
+		{
+			Name:        "monthly_statements",
+			Description: "生成上一自然月的用户月度账单（JSON / CSV / PDF），需要配置对象存储",
+			Schedule:    "0 6 1 * *",
+			Disabled:    !statements.StorageEnabled(),
+			Timeout:     statementMonthTimeout,
+			Run:         statements.GeneratePreviousMonth,
+		},
+		{
+			Name:        "api_key_expiry_warnings",
+			Description: "提醒用户即将到期的 API Key（notifications.key_expiry_warn_days）",
+			Schedule:    "15 * * * *",
+			Disabled:    cfg.Notifications.KeyExpiryWarnDays <= 0,
+			Timeout:     10 * time.Minute,
+			Run:         notifications.WarnExpiringAPIKeys,
+		},
+		{
+			Name:        "notification_log_purge",
+			Description: "清除超过保留天数的通知邮件发送记录",
+			Schedule:    "40 4 * * *",
+			Timeout:     10 * time.Minute,
+			Run:         notifications.PurgeExpiredLogs,
+		},
+		{
+			Name:        "telegram_alerts",
+			Description: "检查严重告警（平台账号全部不可用、上游配额耗尽）并推送到 Telegram",
+			Schedule:    "* * * * *",
+			Disabled:    !telegram.Enabled(),
+			Timeout:     time.Minute,
+			Run:         telegram.CheckAlerts,
+		},
+		{
+			Name:        "conversation_purge",
+			Description: "清除超过保留天数未更新的服务端会话",
+			Schedule:    "50 * * * *",
+			Disabled:    !cfg.Conversation.Enabled,
+			Timeout:     10 * time.Minute,
+			Run:         conversations.PurgeExpired,
+		},
+		{
+			Name:        "attachment_purge",
+			Description: "清除超过保留时间的上传文件及其存储对象",
+			Schedule:    "55 * * * *",
+			Disabled:    !cfg.Attachments.Enabled || !cfg.Storage.Enabled,
+			Timeout:     10 * time.Minute,
+			Run:         attachments.PurgeExpired,
+		},
+	}
+	for _, job := range builtins {
+		if err := svc.Register(job); err != nil {
+			logger.LegacyPrintf("service.cron_job", "[CronJob] register %s failed: %v", job.Name, err)
+		}
+	}
+	startBackgroundJob(cfg, "CronJobService", svc.Start)
+	return svc
+}
+
+// ProvideRequestLogService creates RequestLogService and starts its async writer.
+// 写入协程不受 background job 开关影响：请求日志由每个处理请求的实例写出。
+func ProvideRequestLogService(repo RequestLogRepository, cfg *config.Config) *RequestLogService {
+	svc := NewRequestLogService(repo, cfg)
+	svc.Start()
+	return svc
+}
+
+// ProvideAnalyticsTeeService 创建请求摘要旁路转发服务并开始收集事件。
+// 事件收集不受 background job 开关影响：事件只在处理请求的实例上产生。
+func ProvideAnalyticsTeeService(hub *LiveUsageHub, jobQueue *JobQueueService, sink AnalyticsSink, cfg *config.Config) *AnalyticsTeeService {
+	svc := NewAnalyticsTeeService(hub, jobQueue, sink, cfg)
+	svc.Start()
+	return svc
+}
+
+// ProvideEventExportService 创建事件导出服务；outbox 转发任务随后台任务运行
+func ProvideEventExportService(repo EventOutboxRepository, publisher EventPublisher, cfg *config.Config) *EventExportService {
+	svc := NewEventExportService(repo, publisher, cfg)
+	startBackgroundJob(cfg, "EventExportService", svc.Start)
+	return svc
+}
+
+// ProvideUsageAnalyticsService 创建用量分析写入服务并启动写入协程。
+// 写入协程不受 background job 开关影响：用量明细由每个处理请求的实例写出。
+func ProvideUsageAnalyticsService(store UsageAnalyticsStore, jobQueue *JobQueueService, cfg *config.Config) *UsageAnalyticsService {
+	svc := NewUsageAnalyticsService(store, cfg)
+	svc.SetJobQueue(jobQueue)
 	svc.Start()
 	return svc
 }
@@ -102,10 +297,17 @@ func ProvideDeferredService(accountRepo AccountRepository, timingWheel *TimingWh
 }
 
 // ProvideConcurrencyService creates ConcurrencyService and starts slot cleanup worker.
-func ProvideConcurrencyService(cache ConcurrencyCache, accountRepo AccountRepository, cfg *config.Config) *ConcurrencyService {
+func ProvideConcurrencyService(cache ConcurrencyCache, accountRepo AccountRepository, cfg *config.Config, stateLocker AccountStateLocker) *ConcurrencyService {
 	svc := NewConcurrencyService(cache)
+	svc.SetAccountStateLocker(stateLocker)
 	if cfg != nil {
 		svc.StartSlotCleanupWorker(accountRepo, cfg.Gateway.Scheduling.SlotCleanupInterval)
+		if cfg.Concurrency.FairShare {
+			svc.EnableFairShare()
+		}
+		if cfg.Concurrency.AdmissionMaxWait > 0 {
+			svc.EnableAdmissionControl(time.Duration(cfg.Concurrency.AdmissionMaxWait) * time.Second)
+		}
 	}
 	return svc
 }
@@ -133,11 +335,15 @@ func ProvideRateLimitService(
 	timeoutCounterCache TimeoutCounterCache,
 	settingService *SettingService,
 	tokenCacheInvalidator TokenCacheInvalidator,
+	challengeHookService *ChallengeHookService,
+	stateLocker AccountStateLocker,
 ) *RateLimitService {
 	svc := NewRateLimitService(accountRepo, usageRepo, cfg, geminiQuotaService, tempUnschedCache)
 	svc.SetTimeoutCounterCache(timeoutCounterCache)
 	svc.SetSettingService(settingService)
 	svc.SetTokenCacheInvalidator(tokenCacheInvalidator)
+	svc.SetChallengeHookService(challengeHookService)
+	svc.SetAccountStateLocker(stateLocker)
 	return svc
 }
 
@@ -165,7 +371,7 @@ func ProvideOpsAggregationService(
 	cfg *config.Config,
 ) *OpsAggregationService {
 	svc := NewOpsAggregationService(opsRepo, settingRepo, db, redisClient, cfg)
-	svc.Start()
+	startBackgroundJob(cfg, "OpsAggregationService", svc.Start)
 	return svc
 }
 
@@ -174,11 +380,14 @@ func ProvideOpsAlertEvaluatorService(
 	opsService *OpsService,
 	opsRepo OpsRepository,
 	emailService *EmailService,
+	webhooks *WebhookDeliveryService,
 	redisClient *redis.Client,
+	eventExport *EventExportService,
 	cfg *config.Config,
 ) *OpsAlertEvaluatorService {
-	svc := NewOpsAlertEvaluatorService(opsService, opsRepo, emailService, redisClient, cfg)
-	svc.Start()
+	svc := NewOpsAlertEvaluatorService(opsService, opsRepo, emailService, webhooks, redisClient, cfg)
+	svc.SetEventExporter(eventExport)
+	startBackgroundJob(cfg, "OpsAlertEvaluatorService", svc.Start)
 	return svc
 }
 
@@ -190,20 +399,22 @@ func ProvideOpsCleanupService(
 	cfg *config.Config,
 ) *OpsCleanupService {
 	svc := NewOpsCleanupService(opsRepo, db, redisClient, cfg)
-	svc.Start()
+	startBackgroundJob(cfg, "OpsCleanupService", svc.Start)
 	return svc
 }
 
-func ProvideOpsSystemLogSink(opsRepo OpsRepository) *OpsSystemLogSink {
-	sink := NewOpsSystemLogSink(opsRepo)
+func ProvideOpsSystemLogSink(opsRepo OpsRepository, logStream *LogStreamHub, logMasking *LogMaskingProfiles) *OpsSystemLogSink {
+	sink := NewOpsSystemLogSink(opsRepo, logMasking)
 	sink.Start()
-	logger.SetSink(sink)
+	logger.SetSink(logger.MultiSink{sink, logStream})
 	return sink
 }
 
 // ProvideSoraMediaStorage 初始化 Sora 媒体存储
-func ProvideSoraMediaStorage(cfg *config.Config) *SoraMediaStorage {
-	return NewSoraMediaStorage(cfg)
+func ProvideSoraMediaStorage(cfg *config.Config, objects ObjectStorage) *SoraMediaStorage {
+	storage := NewSoraMediaStorage(cfg)
+	storage.SetObjectStorage(objects)
+	return storage
 }
 
 func ProvideSoraSDKClient(
@@ -219,8 +430,9 @@ func ProvideSoraSDKClient(
 }
 
 // ProvideSoraMediaCleanupService 创建并启动 Sora 媒体清理服务
-func ProvideSoraMediaCleanupService(storage *SoraMediaStorage, cfg *config.Config) *SoraMediaCleanupService {
+func ProvideSoraMediaCleanupService(storage *SoraMediaStorage, leader *LeaderElector, cfg *config.Config) *SoraMediaCleanupService {
 	svc := NewSoraMediaCleanupService(storage, cfg)
+	svc.SetLeaderElector(leader)
 	svc.Start()
 	return svc
 }
@@ -258,9 +470,10 @@ func ProvideSystemOperationLockService(repo IdempotencyRepository, cfg *config.C
 	return NewSystemOperationLockService(repo, buildIdempotencyConfig(cfg))
 }
 
-func ProvideIdempotencyCleanupService(repo IdempotencyRepository, cfg *config.Config) *IdempotencyCleanupService {
+func ProvideIdempotencyCleanupService(repo IdempotencyRepository, leader *LeaderElector, cfg *config.Config) *IdempotencyCleanupService {
 	svc := NewIdempotencyCleanupService(repo, cfg)
-	svc.Start()
+	svc.SetLeaderElector(leader)
+	startBackgroundJob(cfg, "IdempotencyCleanupService", svc.Start)
 	return svc
 }
 
@@ -269,14 +482,25 @@ func ProvideOpsScheduledReportService(
 	opsService *OpsService,
 	userService *UserService,
 	emailService *EmailService,
+	webhooks *WebhookDeliveryService,
 	redisClient *redis.Client,
 	cfg *config.Config,
 ) *OpsScheduledReportService {
-	svc := NewOpsScheduledReportService(opsService, userService, emailService, redisClient, cfg)
-	svc.Start()
+	svc := NewOpsScheduledReportService(opsService, userService, emailService, webhooks, redisClient, cfg)
+	startBackgroundJob(cfg, "OpsScheduledReportService", svc.Start)
 	return svc
 }
 
+// ProvideUpstreamHeaderAllowlist 从 gateway.upstream_header_allowlist 创建策略请求头白名单
+func ProvideUpstreamHeaderAllowlist(cfg *config.Config) *UpstreamHeaderAllowlist {
+	return NewUpstreamHeaderAllowlist(cfg.Gateway.UpstreamHeaderAllowlist)
+}
+
+// ProvideLogMaskingProfiles 编译 log_masking 配置档
+func ProvideLogMaskingProfiles(cfg *config.Config) (*LogMaskingProfiles, error) {
+	return NewLogMaskingProfiles(cfg.LogMasking)
+}
+
 // ProvideAPIKeyAuthCacheInvalidator 提供 API Key 认证缓存失效能力
 func ProvideAPIKeyAuthCacheInvalidator(apiKeyService *APIKeyService) APIKeyAuthCacheInvalidator {
 	// Start Pub/Sub subscriber for L1 cache invalidation across instances
@@ -284,11 +508,24 @@ func ProvideAPIKeyAuthCacheInvalidator(apiKeyService *APIKeyService) APIKeyAuthC
 	return apiKeyService
 }
 
+// ProvideUserService 创建用户服务并注入后台账号服务（后台账号改密时执行密码策略）
+func ProvideUserService(
+	userRepo UserRepository,
+	authCacheInvalidator APIKeyAuthCacheInvalidator,
+	billingCache BillingCache,
+	adminUsers *AdminUserService,
+) *UserService {
+	svc := NewUserService(userRepo, authCacheInvalidator, billingCache)
+	svc.SetAdminUserService(adminUsers)
+	return svc
+}
+
 // ProviderSet is the Wire provider set for all services
 var ProviderSet = wire.NewSet(
 	// Core services
 	NewAuthService,
-	NewUserService,
+	ProvideUserService,
+	NewAdminUserService,
 	NewAPIKeyService,
 	ProvideAPIKeyAuthCacheInvalidator,
 	NewGroupService,
@@ -312,6 +549,8 @@ var ProviderSet = wire.NewSet(
 	NewOpenAIGatewayService,
 	NewOAuthService,
 	NewOpenAIOAuthService,
+	NewCopilotService,
+	NewSessionImportService,
 	NewGeminiOAuthService,
 	NewGeminiQuotaService,
 	NewCompositeTokenCacheInvalidator,
@@ -324,6 +563,7 @@ var ProviderSet = wire.NewSet(
 	NewClaudeTokenProvider,
 	NewAntigravityGatewayService,
 	ProvideRateLimitService,
+	NewChallengeHookService,
 	NewAccountUsageService,
 	NewAccountTestService,
 	NewSettingService,
@@ -336,6 +576,10 @@ var ProviderSet = wire.NewSet(
 	ProvideOpsScheduledReportService,
 	NewEmailService,
 	ProvideEmailQueueService,
+	ProvideNotificationService,
+	ProvideTelegramService,
+	ProvideJobQueueService,
+	NewWebhookDeliveryService,
 	NewTurnstileService,
 	NewSubscriptionService,
 	ProvideConcurrencyService,
@@ -344,9 +588,17 @@ var ProviderSet = wire.NewSet(
 	NewIdentityService,
 	NewCRSSyncService,
 	ProvideUpdateService,
+	ProvideLeaderElector,
 	ProvideTokenRefreshService,
 	ProvideAccountExpiryService,
 	ProvideSubscriptionExpiryService,
+	ProvideTrashService,
+	ProvideCronJobService,
+	ProvideRequestLogService,
+	ProvideAnalyticsTeeService,
+	ProvideEventExportService,
+	ProvideUsageAnalyticsService,
+	NewDataSubjectService,
 	ProvideTimingWheelService,
 	ProvideDashboardAggregationService,
 	ProvideUsageCleanupService,
@@ -355,7 +607,35 @@ var ProviderSet = wire.NewSet(
 	NewUserAttributeService,
 	NewUsageCache,
 	NewTotpService,
+	NewWebSessionService,
+	NewAPIKeyRotationService,
+	ProvideUpstreamHeaderAllowlist,
+	ProvideLogMaskingProfiles,
+	NewAPIKeyPolicyService,
+	NewAdminListService,
+	NewUsageExportService,
 	NewErrorPassthroughService,
+	NewFeatureFlagService,
+	NewMaintenanceService,
+	NewSpendingCapService,
+	NewPrepaidCreditService,
+	NewStatementService,
+	NewPaymentService,
+	NewTeamService,
+	NewTrafficMirrorService,
+	NewSemanticCacheService,
+	NewModerationService,
+	NewConversationService,
+	NewAttachmentService,
+	NewPromptTemplateService,
+	NewModelCanaryService,
+	NewAccountShardService,
+	NewRoutingRuleService,
+	NewAccountLatencyTracker,
+	NewAccountQuotaBudgetTracker,
+	NewLiveUsageHub,
+	NewLogStreamHub,
+	NewAccountHealthService,
 	NewDigestSessionStore,
 	ProvideIdempotencyCoordinator,
 	ProvideSystemOperationLockService,
@@ -0,0 +1,169 @@
+// Package standalone 单用户轻量模式：API Key 与上游账号来自配置文件，使用记录写入本地 JSONL，
+// 不依赖 PostgreSQL / Redis。只提供透明转发（不做计费、额度、并发控制与 OAuth 账号），
+// 适合个人在 VPS 上自用；多用户或需要计费时请使用完整部署。
+package standalone
+
+import (
+	"errors"
+	"fmt"
+	"net/url"
+	"os"
+	"strings"
+
+	"gopkg.in/yaml.v3"
+)
+
+const (
+	PlatformAnthropic = "anthropic"
+	PlatformOpenAI    = "openai"
+
+	defaultListen       = "127.0.0.1:8080"
+	defaultUsageLog     = "usage.jsonl"
+	defaultMaxBodyBytes = 32 << 20
+	// minKeyLength 网关 API Key 最短长度，避免配置弱口令
+	minKeyLength = 16
+)
+
+// defaultBaseURLs 各平台未配置 base_url 时使用的官方地址
+var defaultBaseURLs = map[string]string{
+	PlatformAnthropic: "https://api.anthropic.com",
+	PlatformOpenAI:    "https://api.openai.com",
+}
+
+// Config 独立模式配置文件（YAML）
+type Config struct {
+	// Listen 监听地址，默认 127.0.0.1:8080
+	Listen string `yaml:"listen"`
+	// UsageLog 使用记录 JSONL 文件路径（追加写入），默认 ./usage.jsonl
+	UsageLog string `yaml:"usage_log"`
+	// MaxBodyBytes 请求体大小上限，默认 32MB
+	MaxBodyBytes int64           `yaml:"max_body_bytes"`
+	Accounts     []AccountConfig `yaml:"accounts"`
+	Keys         []KeyConfig     `yaml:"keys"`
+}
+
+// AccountConfig 上游 API Key 账号
+type AccountConfig struct {
+	Name string `yaml:"name"`
+	// Platform anthropic（/v1/messages）或 openai（/v1/responses、/v1/chat/completions）
+	Platform string `yaml:"platform"`
+	// BaseURL 为空时使用平台官方地址
+	BaseURL string `yaml:"base_url"`
+	// APIKey 支持 ${ENV} 引用环境变量
+	APIKey string `yaml:"api_key"`
+	// ModelMapping 请求模型 -> 上游模型；非空时账号只服务映射中列出的模型
+	ModelMapping map[string]string `yaml:"model_mapping"`
+}
+
+// KeyConfig 网关 API Key
+type KeyConfig struct {
+	// Name 写入使用记录，便于区分不同客户端
+	Name string `yaml:"name"`
+	// Key 支持 ${ENV} 引用环境变量
+	Key string `yaml:"key"`
+	// Models 允许请求的模型，空表示不限制
+	Models []string `yaml:"models"`
+	// Accounts 可用账号名（按顺序故障转移），空表示全部账号
+	Accounts []string `yaml:"accounts"`
+}
+
+// LoadConfig 读取并校验配置文件，未知字段视为错误
+func LoadConfig(path string) (*Config, error) {
+	f, err := os.Open(path)
+	if err != nil {
+		return nil, err
+	}
+	defer func() { _ = f.Close() }()
+
+	var cfg Config
+	dec := yaml.NewDecoder(f)
+	dec.KnownFields(true)
+	if err := dec.Decode(&cfg); err != nil {
+		return nil, fmt.Errorf("parse %s: %w", path, err)
+	}
+	if err := cfg.Normalize(); err != nil {
+		return nil, err
+	}
+	return &cfg, nil
+}
+
+// Normalize 填充默认值、展开环境变量并校验
+func (c *Config) Normalize() error {
+	c.Listen = strings.TrimSpace(c.Listen)
+	if c.Listen == "" {
+		c.Listen = defaultListen
+	}
+	c.UsageLog = strings.TrimSpace(c.UsageLog)
+	if c.UsageLog == "" {
+		c.UsageLog = defaultUsageLog
+	}
+	if c.MaxBodyBytes < 0 {
+		return errors.New("max_body_bytes must be non-negative")
+	}
+	if c.MaxBodyBytes == 0 {
+		c.MaxBodyBytes = defaultMaxBodyBytes
+	}
+
+	if len(c.Accounts) == 0 {
+		return errors.New("at least one account is required")
+	}
+	accountNames := make(map[string]struct{}, len(c.Accounts))
+	for i := range c.Accounts {
+		a := &c.Accounts[i]
+		a.Name = strings.TrimSpace(a.Name)
+		a.Platform = strings.ToLower(strings.TrimSpace(a.Platform))
+		a.APIKey = strings.TrimSpace(os.ExpandEnv(a.APIKey))
+		if a.Name == "" {
+			return fmt.Errorf("accounts[%d]: name is required", i)
+		}
+		if _, dup := accountNames[a.Name]; dup {
+			return fmt.Errorf("accounts[%d]: duplicate name %q", i, a.Name)
+		}
+		accountNames[a.Name] = struct{}{}
+		defaultBaseURL, ok := defaultBaseURLs[a.Platform]
+		if !ok {
+			return fmt.Errorf("account %q: platform must be anthropic or openai", a.Name)
+		}
+		if a.APIKey == "" {
+			return fmt.Errorf("account %q: api_key is required", a.Name)
+		}
+		a.BaseURL = strings.TrimRight(strings.TrimSpace(a.BaseURL), "/")
+		if a.BaseURL == "" {
+			a.BaseURL = defaultBaseURL
+		}
+		if u, err := url.Parse(a.BaseURL); err != nil || (u.Scheme != "http" && u.Scheme != "https") || u.Host == "" {
+			return fmt.Errorf("account %q: base_url must be an http(s) URL", a.Name)
+		}
+		for from, to := range a.ModelMapping {
+			if strings.TrimSpace(from) == "" || strings.TrimSpace(to) == "" {
+				return fmt.Errorf("account %q: model_mapping entries must be non-empty", a.Name)
+			}
+		}
+	}
+
+	if len(c.Keys) == 0 {
+		return errors.New("at least one key is required")
+	}
+	keyValues := make(map[string]struct{}, len(c.Keys))
+	for i := range c.Keys {
+		k := &c.Keys[i]
+		k.Name = strings.TrimSpace(k.Name)
+		k.Key = strings.TrimSpace(os.ExpandEnv(k.Key))
+		if k.Name == "" {
+			k.Name = fmt.Sprintf("key-%d", i+1)
+		}
+		if len(k.Key) < minKeyLength {
+			return fmt.Errorf("key %q: key must be at least %d characters", k.Name, minKeyLength)
+		}
+		if _, dup := keyValues[k.Key]; dup {
+			return fmt.Errorf("key %q: duplicate key value", k.Name)
+		}
+		keyValues[k.Key] = struct{}{}
+		for _, name := range k.Accounts {
+			if _, ok := accountNames[name]; !ok {
+				return fmt.Errorf("key %q: unknown account %q", k.Name, name)
+			}
+		}
+	}
+	return nil
+}
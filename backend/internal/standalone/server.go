@@ -0,0 +1,341 @@
+package standalone
+
+import (
+	"bufio"
+	"bytes"
+	"errors"
+	"fmt"
+	"io"
+	"net/http"
+	"sort"
+	"strings"
+	"time"
+
+	"github.com/Wei-Shaw/sub2api/internal/pkg/logger"
+	"github.com/Wei-Shaw/sub2api/internal/server/middleware"
+	"github.com/Wei-Shaw/sub2api/internal/server/routes"
+	"github.com/gin-gonic/gin"
+	"github.com/tidwall/gjson"
+	"github.com/tidwall/sjson"
+)
+
+const (
+	defaultAnthropicVersion = "2023-06-01"
+	// maxResponseBodyBytes 非流式响应体读取上限
+	maxResponseBodyBytes = 64 << 20
+	// upstreamResponseHeaderTimeout 等待上游响应头的超时（长上下文首包可能较慢）
+	upstreamResponseHeaderTimeout = 10 * time.Minute
+
+	ctxKeyAPIKey = "standalone_api_key"
+)
+
+// forwardedRequestHeaders 透传给上游的客户端请求头；认证头由账号凭证替换
+var forwardedRequestHeaders = []string{"Content-Type", "Accept", "User-Agent", "Anthropic-Version", "Anthropic-Beta", "OpenAI-Beta"}
+
+// skippedResponseHeaders 不回传给客户端的上游响应头（逐跳头，长度由本地写入决定）
+var skippedResponseHeaders = map[string]struct{}{
+	"Connection":        {},
+	"Keep-Alive":        {},
+	"Proxy-Connection":  {},
+	"Transfer-Encoding": {},
+	"Content-Length":    {},
+	"Te":                {},
+	"Trailer":           {},
+	"Upgrade":           {},
+}
+
+// apiKey 网关 API Key 与其可用账号（按配置顺序故障转移）
+type apiKey struct {
+	name     string
+	models   map[string]struct{}
+	accounts []*AccountConfig
+}
+
+func (k *apiKey) allowsModel(model string) bool {
+	if len(k.models) == 0 {
+		return true
+	}
+	_, ok := k.models[model]
+	return ok
+}
+
+func accountServes(a *AccountConfig, model string) bool {
+	if len(a.ModelMapping) == 0 {
+		return true
+	}
+	_, ok := a.ModelMapping[model]
+	return ok
+}
+
+// Server 独立模式网关：认证、模型映射、按账号顺序故障转移与使用记录
+type Server struct {
+	cfg    *Config
+	usage  *UsageLog
+	client *http.Client
+	keys   map[string]*apiKey
+}
+
+// NewServer 创建独立模式网关；client 为 nil 时使用默认上游客户端。cfg 须已通过 Normalize 校验。
+func NewServer(cfg *Config, usage *UsageLog, client *http.Client) *Server {
+	if client == nil {
+		client = &http.Client{Transport: &http.Transport{
+			Proxy:                 http.ProxyFromEnvironment,
+			ForceAttemptHTTP2:     true,
+			MaxIdleConnsPerHost:   16,
+			IdleConnTimeout:       90 * time.Second,
+			TLSHandshakeTimeout:   10 * time.Second,
+			ResponseHeaderTimeout: upstreamResponseHeaderTimeout,
+		}}
+	}
+	accounts := make(map[string]*AccountConfig, len(cfg.Accounts))
+	all := make([]*AccountConfig, 0, len(cfg.Accounts))
+	for i := range cfg.Accounts {
+		accounts[cfg.Accounts[i].Name] = &cfg.Accounts[i]
+		all = append(all, &cfg.Accounts[i])
+	}
+	keys := make(map[string]*apiKey, len(cfg.Keys))
+	for _, k := range cfg.Keys {
+		key := &apiKey{name: k.Name, models: make(map[string]struct{}, len(k.Models)), accounts: all}
+		for _, model := range k.Models {
+			key.models[model] = struct{}{}
+		}
+		if len(k.Accounts) > 0 {
+			key.accounts = make([]*AccountConfig, 0, len(k.Accounts))
+			for _, name := range k.Accounts {
+				key.accounts = append(key.accounts, accounts[name])
+			}
+		}
+		keys[k.Key] = key
+	}
+	return &Server{cfg: cfg, usage: usage, client: client, keys: keys}
+}
+
+// Handler 返回 HTTP 路由
+func (s *Server) Handler() http.Handler {
+	r := gin.New()
+	r.Use(middleware.Recovery())
+	routes.RegisterCommonRoutes(r)
+
+	v1 := r.Group("/v1", middleware.RequestBodyLimit(s.cfg.MaxBodyBytes), s.authenticate)
+	v1.GET("/models", s.models)
+	v1.POST("/messages", s.proxy(PlatformAnthropic, true))
+	v1.POST("/messages/count_tokens", s.proxy(PlatformAnthropic, false))
+	v1.POST("/responses", s.proxy(PlatformOpenAI, true))
+	v1.POST("/chat/completions", s.proxy(PlatformOpenAI, true))
+	return r
+}
+
+// platformForPath 按端点选择错误响应格式
+func platformForPath(path string) string {
+	if strings.HasPrefix(path, "/v1/messages") {
+		return PlatformAnthropic
+	}
+	return PlatformOpenAI
+}
+
+func writeError(c *gin.Context, platform string, status int, errType, message string) {
+	if platform == PlatformOpenAI {
+		c.AbortWithStatusJSON(status, gin.H{"error": gin.H{"type": errType, "message": message}})
+		return
+	}
+	c.AbortWithStatusJSON(status, gin.H{"type": "error", "error": gin.H{"type": errType, "message": message}})
+}
+
+// authenticate 支持 x-api-key 与 Authorization: Bearer
+func (s *Server) authenticate(c *gin.Context) {
+	token := strings.TrimSpace(c.GetHeader("x-api-key"))
+	if token == "" {
+		if bearer, ok := strings.CutPrefix(c.GetHeader("Authorization"), "Bearer "); ok {
+			token = strings.TrimSpace(bearer)
+		}
+	}
+	key, ok := s.keys[token]
+	if token == "" || !ok {
+		writeError(c, platformForPath(c.Request.URL.Path), http.StatusUnauthorized, "authentication_error", "Invalid API key")
+		return
+	}
+	c.Set(ctxKeyAPIKey, key)
+	c.Next()
+}
+
+// models 列出当前 Key 可用的模型：Key 配置的 models，未配置时为可用账号 model_mapping 中的模型
+func (s *Server) models(c *gin.Context) {
+	key := c.MustGet(ctxKeyAPIKey).(*apiKey)
+	seen := make(map[string]struct{})
+	for model := range key.models {
+		seen[model] = struct{}{}
+	}
+	if len(seen) == 0 {
+		for _, account := range key.accounts {
+			for model := range account.ModelMapping {
+				seen[model] = struct{}{}
+			}
+		}
+	}
+	ids := make([]string, 0, len(seen))
+	for model := range seen {
+		ids = append(ids, model)
+	}
+	sort.Strings(ids)
+	data := make([]gin.H, 0, len(ids))
+	for _, id := range ids {
+		data = append(data, gin.H{"id": id, "object": "model", "type": "model"})
+	}
+	c.JSON(http.StatusOK, gin.H{"object": "list", "data": data})
+}
+
+// proxy 透明转发到 platform 平台的账号：上游返回 429 / 5xx 或连接失败时依次尝试下一个账号。
+// recordUsage 为 false 的端点（count_tokens）不写使用记录。
+func (s *Server) proxy(platform string, recordUsage bool) gin.HandlerFunc {
+	return func(c *gin.Context) {
+		key := c.MustGet(ctxKeyAPIKey).(*apiKey)
+		body, err := io.ReadAll(c.Request.Body)
+		if err != nil {
+			var maxErr *http.MaxBytesError
+			if errors.As(err, &maxErr) {
+				writeError(c, platform, http.StatusRequestEntityTooLarge, "invalid_request_error", middleware.BodyTooLargeMessage(maxErr.Limit))
+				return
+			}
+			writeError(c, platform, http.StatusBadRequest, "invalid_request_error", "Failed to read request body")
+			return
+		}
+		model := gjson.GetBytes(body, "model").String()
+		if model == "" {
+			writeError(c, platform, http.StatusBadRequest, "invalid_request_error", "model is required")
+			return
+		}
+		if !key.allowsModel(model) {
+			writeError(c, platform, http.StatusForbidden, "permission_error", fmt.Sprintf("Model %q is not allowed for this API key", model))
+			return
+		}
+		candidates := make([]*AccountConfig, 0, len(key.accounts))
+		for _, account := range key.accounts {
+			if account.Platform == platform && accountServes(account, model) {
+				candidates = append(candidates, account)
+			}
+		}
+		if len(candidates) == 0 {
+			writeError(c, platform, http.StatusServiceUnavailable, "api_error", fmt.Sprintf("No available accounts for model %q", model))
+			return
+		}
+
+		start := time.Now()
+		rec := &UsageRecord{
+			Time:     start.UTC(),
+			Key:      key.name,
+			Endpoint: c.Request.URL.Path,
+			Model:    model,
+			Stream:   gjson.GetBytes(body, "stream").Bool(),
+		}
+		var resp *http.Response
+		for i, account := range candidates {
+			rec.Account, rec.UpstreamModel = account.Name, ""
+			upstreamBody := body
+			if mapped := account.ModelMapping[model]; mapped != "" && mapped != model {
+				if upstreamBody, err = sjson.SetBytes(body, "model", mapped); err != nil {
+					break
+				}
+				rec.UpstreamModel = mapped
+			}
+			resp, err = s.send(c, account, upstreamBody)
+			if err != nil || i == len(candidates)-1 || !retryableStatus(resp.StatusCode) {
+				break
+			}
+			_ = resp.Body.Close()
+			resp = nil
+		}
+		if err != nil || resp == nil {
+			rec.Status = http.StatusBadGateway
+			if err != nil {
+				rec.Error = err.Error()
+			}
+			writeError(c, platform, http.StatusBadGateway, "api_error", "Upstream request failed")
+		} else {
+			defer func() { _ = resp.Body.Close() }()
+			rec.Status = resp.StatusCode
+			for name, values := range resp.Header {
+				if _, skip := skippedResponseHeaders[name]; !skip {
+					c.Writer.Header()[name] = values
+				}
+			}
+			c.Status(resp.StatusCode)
+			if strings.HasPrefix(resp.Header.Get("Content-Type"), "text/event-stream") {
+				err = streamResponse(c.Writer, resp.Body, rec)
+			} else {
+				err = copyResponse(c.Writer, resp.Body, rec)
+			}
+			if err != nil {
+				rec.Error = err.Error()
+			}
+		}
+		rec.DurationMs = time.Since(start).Milliseconds()
+		if recordUsage {
+			if err := s.usage.Write(rec); err != nil {
+				logger.LegacyPrintf("standalone", "[Standalone] write usage log failed: %v", err)
+			}
+		}
+	}
+}
+
+func retryableStatus(status int) bool {
+	return status == http.StatusTooManyRequests || status >= http.StatusInternalServerError
+}
+
+// send 以账号凭证向上游发送请求，保留客户端的查询参数（如 Claude Code 的 ?beta=true）
+func (s *Server) send(c *gin.Context, account *AccountConfig, body []byte) (*http.Response, error) {
+	target := account.BaseURL + c.Request.URL.Path
+	if c.Request.URL.RawQuery != "" {
+		target += "?" + c.Request.URL.RawQuery
+	}
+	req, err := http.NewRequestWithContext(c.Request.Context(), http.MethodPost, target, bytes.NewReader(body))
+	if err != nil {
+		return nil, err
+	}
+	for _, name := range forwardedRequestHeaders {
+		if value := c.GetHeader(name); value != "" {
+			req.Header.Set(name, value)
+		}
+	}
+	if account.Platform == PlatformAnthropic {
+		req.Header.Set("x-api-key", account.APIKey)
+		if req.Header.Get("Anthropic-Version") == "" {
+			req.Header.Set("Anthropic-Version", defaultAnthropicVersion)
+		}
+	} else {
+		req.Header.Set("Authorization", "Bearer "+account.APIKey)
+	}
+	return s.client.Do(req)
+}
+
+// streamResponse 按行转发 SSE 并从 data 事件中累计用量，每行写出后立即刷新
+func streamResponse(w gin.ResponseWriter, body io.Reader, rec *UsageRecord) error {
+	reader := bufio.NewReaderSize(body, 64<<10)
+	for {
+		line, err := reader.ReadBytes('\n')
+		if len(line) > 0 {
+			if data, ok := bytes.CutPrefix(line, []byte("data:")); ok {
+				mergeUsage(rec, data)
+			}
+			if _, werr := w.Write(line); werr != nil {
+				return werr
+			}
+			w.Flush()
+		}
+		if err != nil {
+			if errors.Is(err, io.EOF) {
+				return nil
+			}
+			return err
+		}
+	}
+}
+
+func copyResponse(w gin.ResponseWriter, body io.Reader, rec *UsageRecord) error {
+	data, err := io.ReadAll(io.LimitReader(body, maxResponseBodyBytes))
+	if err != nil {
+		return err
+	}
+	mergeUsage(rec, data)
+	_, err = w.Write(data)
+	return err
+}
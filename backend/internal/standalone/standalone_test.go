@@ -0,0 +1,211 @@
+package standalone
+
+import (
+	"bufio"
+	"encoding/json"
+	"io"
+	"net/http"
+	"net/http/httptest"
+	"os"
+	"path/filepath"
+	"strings"
+	"testing"
+
+	"github.com/gin-gonic/gin"
+	"github.com/stretchr/testify/require"
+)
+
+const testKey = "sk-standalone-test-key"
+
+func TestConfigNormalize(t *testing.T) {
+	t.Setenv("STANDALONE_TEST_UPSTREAM_KEY", "upstream-secret")
+	cfg := &Config{
+		Accounts: []AccountConfig{{Name: "a", Platform: " Anthropic ", APIKey: "${STANDALONE_TEST_UPSTREAM_KEY}", BaseURL: "https://proxy.example.com/"}},
+		Keys:     []KeyConfig{{Key: testKey}},
+	}
+	require.NoError(t, cfg.Normalize())
+	require.Equal(t, defaultListen, cfg.Listen)
+	require.Equal(t, defaultUsageLog, cfg.UsageLog)
+	require.Equal(t, int64(defaultMaxBodyBytes), cfg.MaxBodyBytes)
+	require.Equal(t, PlatformAnthropic, cfg.Accounts[0].Platform)
+	require.Equal(t, "upstream-secret", cfg.Accounts[0].APIKey)
+	require.Equal(t, "https://proxy.example.com", cfg.Accounts[0].BaseURL)
+	require.Equal(t, "key-1", cfg.Keys[0].Name)
+
+	tests := []struct {
+		name    string
+		mutate  func(c *Config)
+		wantErr string
+	}{
+		{"no accounts", func(c *Config) { c.Accounts = nil }, "at least one account"},
+		{"unknown platform", func(c *Config) { c.Accounts[0].Platform = "gemini" }, "platform"},
+		{"missing api key", func(c *Config) { c.Accounts[0].APIKey = "${STANDALONE_TEST_UNSET}" }, "api_key"},
+		{"duplicate account", func(c *Config) { c.Accounts = append(c.Accounts, c.Accounts[0]) }, "duplicate name"},
+		{"bad base url", func(c *Config) { c.Accounts[0].BaseURL = "ftp://example.com" }, "base_url"},
+		{"empty mapping", func(c *Config) { c.Accounts[0].ModelMapping = map[string]string{"a": " "} }, "model_mapping"},
+		{"no keys", func(c *Config) { c.Keys = nil }, "at least one key"},
+		{"short key", func(c *Config) { c.Keys[0].Key = "sk-short" }, "at least"},
+		{"duplicate key", func(c *Config) { c.Keys = append(c.Keys, KeyConfig{Key: testKey}) }, "duplicate key"},
+		{"unknown account", func(c *Config) { c.Keys[0].Accounts = []string{"missing"} }, "unknown account"},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			c := &Config{
+				Accounts: []AccountConfig{{Name: "a", Platform: "openai", APIKey: "k"}},
+				Keys:     []KeyConfig{{Key: testKey}},
+			}
+			tt.mutate(c)
+			err := c.Normalize()
+			require.Error(t, err)
+			require.Contains(t, err.Error(), tt.wantErr)
+		})
+	}
+}
+
+func TestLoadConfig_RejectsUnknownFields(t *testing.T) {
+	path := filepath.Join(t.TempDir(), "standalone.yaml")
+	require.NoError(t, os.WriteFile(path, []byte("accounts: []\nkeyz: []\n"), 0o600))
+	_, err := LoadConfig(path)
+	require.Error(t, err)
+	require.Contains(t, err.Error(), "keyz")
+}
+
+func TestMergeUsage(t *testing.T) {
+	tests := []struct {
+		name     string
+		payloads []string
+		want     UsageRecord
+	}{
+		{
+			name:     "anthropic response",
+			payloads: []string{`{"type":"message","usage":{"input_tokens":10,"output_tokens":5,"cache_creation_input_tokens":2,"cache_read_input_tokens":3}}`},
+			want:     UsageRecord{InputTokens: 10, OutputTokens: 5, CacheCreationTokens: 2, CacheReadTokens: 3},
+		},
+		{
+			name: "anthropic stream",
+			payloads: []string{
+				`{"type":"message_start","message":{"usage":{"input_tokens":10,"output_tokens":1,"cache_read_input_tokens":4}}}`,
+				`{"type":"content_block_delta","delta":{"text":"hi"}}`,
+				`{"type":"message_delta","usage":{"output_tokens":7}}`,
+			},
+			want: UsageRecord{InputTokens: 10, OutputTokens: 7, CacheReadTokens: 4},
+		},
+		{
+			name: "openai responses stream",
+			payloads: []string{
+				`{"type":"response.created","response":{"usage":null}}`,
+				`{"type":"response.completed","response":{"usage":{"input_tokens":20,"output_tokens":8,"input_tokens_details":{"cached_tokens":6}}}}`,
+			},
+			want: UsageRecord{InputTokens: 20, OutputTokens: 8, CacheReadTokens: 6},
+		},
+		{
+			name:     "chat completions",
+			payloads: []string{`{"choices":[],"usage":{"prompt_tokens":12,"completion_tokens":4,"prompt_tokens_details":{"cached_tokens":2}}}`, ` [DONE]`},
+			want:     UsageRecord{InputTokens: 12, OutputTokens: 4, CacheReadTokens: 2},
+		},
+		{
+			name:     "no usage",
+			payloads: []string{`{"error":{"type":"overloaded_error"}}`, `not json`},
+		},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			var rec UsageRecord
+			for _, payload := range tt.payloads {
+				mergeUsage(&rec, []byte(payload))
+			}
+			require.Equal(t, tt.want, rec)
+		})
+	}
+}
+
+func readUsageLog(t *testing.T, path string) []UsageRecord {
+	t.Helper()
+	f, err := os.Open(path)
+	require.NoError(t, err)
+	defer func() { _ = f.Close() }()
+	var records []UsageRecord
+	scanner := bufio.NewScanner(f)
+	for scanner.Scan() {
+		var rec UsageRecord
+		require.NoError(t, json.Unmarshal(scanner.Bytes(), &rec))
+		records = append(records, rec)
+	}
+	return records
+}
+
+func TestServer_ProxyFailoverAndUsage(t *testing.T) {
+	gin.SetMode(gin.TestMode)
+
+	overloaded := httptest.NewServer(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		w.WriteHeader(529)
+		_, _ = w.Write([]byte(`{"type":"error","error":{"type":"overloaded_error"}}`))
+	}))
+	defer overloaded.Close()
+
+	var gotBody, gotKey, gotVersion, gotQuery string
+	upstream := httptest.NewServer(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		body, _ := io.ReadAll(r.Body)
+		gotBody, gotKey, gotVersion, gotQuery = string(body), r.Header.Get("x-api-key"), r.Header.Get("anthropic-version"), r.URL.RawQuery
+		w.Header().Set("Content-Type", "text/event-stream")
+		_, _ = io.WriteString(w, "event: message_start\ndata: {\"type\":\"message_start\",\"message\":{\"usage\":{\"input_tokens\":9,\"output_tokens\":1}}}\n\n")
+		_, _ = io.WriteString(w, "event: message_delta\ndata: {\"type\":\"message_delta\",\"usage\":{\"output_tokens\":4}}\n\n")
+	}))
+	defer upstream.Close()
+
+	usagePath := filepath.Join(t.TempDir(), "usage.jsonl")
+	cfg := &Config{
+		UsageLog: usagePath,
+		Accounts: []AccountConfig{
+			{Name: "busy", Platform: PlatformAnthropic, BaseURL: overloaded.URL, APIKey: "busy-key"},
+			{Name: "main", Platform: PlatformAnthropic, BaseURL: upstream.URL, APIKey: "main-key", ModelMapping: map[string]string{"claude-alias": "claude-real"}},
+			{Name: "oai", Platform: PlatformOpenAI, BaseURL: upstream.URL, APIKey: "oai-key"},
+		},
+		Keys: []KeyConfig{{Name: "me", Key: testKey, Models: []string{"claude-alias"}}},
+	}
+	require.NoError(t, cfg.Normalize())
+	usageLog, err := OpenUsageLog(usagePath)
+	require.NoError(t, err)
+	handler := NewServer(cfg, usageLog, upstream.Client()).Handler()
+
+	do := func(path, key, body string) *httptest.ResponseRecorder {
+		req := httptest.NewRequest(http.MethodPost, path, strings.NewReader(body))
+		req.Header.Set("Content-Type", "application/json")
+		if key != "" {
+			req.Header.Set("Authorization", "Bearer "+key)
+		}
+		w := httptest.NewRecorder()
+		handler.ServeHTTP(w, req)
+		return w
+	}
+
+	w := do("/v1/messages?beta=true", testKey, `{"model":"claude-alias","stream":true}`)
+	require.Equal(t, http.StatusOK, w.Code)
+	require.Contains(t, w.Body.String(), "message_delta")
+	require.JSONEq(t, `{"model":"claude-real","stream":true}`, gotBody)
+	require.Equal(t, "main-key", gotKey)
+	require.Equal(t, defaultAnthropicVersion, gotVersion)
+	require.Equal(t, "beta=true", gotQuery)
+
+	// 认证失败与模型不在白名单时按端点格式返回错误，且不写使用记录
+	w = do("/v1/messages", "sk-wrong-key-0000000", `{"model":"claude-alias"}`)
+	require.Equal(t, http.StatusUnauthorized, w.Code)
+	require.JSONEq(t, `{"type":"error","error":{"type":"authentication_error","message":"Invalid API key"}}`, w.Body.String())
+	w = do("/v1/responses", testKey, `{"model":"gpt-5"}`)
+	require.Equal(t, http.StatusForbidden, w.Code)
+	require.NotContains(t, w.Body.String(), `"type":"error"`)
+
+	require.NoError(t, usageLog.Close())
+	records := readUsageLog(t, usagePath)
+	require.Len(t, records, 1)
+	rec := records[0]
+	require.Equal(t, "me", rec.Key)
+	require.Equal(t, "main", rec.Account)
+	require.Equal(t, "/v1/messages", rec.Endpoint)
+	require.Equal(t, "claude-alias", rec.Model)
+	require.Equal(t, "claude-real", rec.UpstreamModel)
+	require.True(t, rec.Stream)
+	require.Equal(t, http.StatusOK, rec.Status)
+	require.Equal(t, int64(9), rec.InputTokens)
+	require.Equal(t, int64(4), rec.OutputTokens)
+}
@@ -0,0 +1,96 @@
+package standalone
+
+import (
+	"bytes"
+	"encoding/json"
+	"os"
+	"sync"
+	"time"
+
+	"github.com/tidwall/gjson"
+)
+
+// UsageRecord 单次转发的使用记录（JSONL 一行）
+type UsageRecord struct {
+	Time                time.Time `json:"time"`
+	Key                 string    `json:"key"`
+	Account             string    `json:"account"`
+	Endpoint            string    `json:"endpoint"`
+	Model               string    `json:"model"`
+	UpstreamModel       string    `json:"upstream_model,omitempty"`
+	Stream              bool      `json:"stream"`
+	Status              int       `json:"status"`
+	InputTokens         int64     `json:"input_tokens"`
+	OutputTokens        int64     `json:"output_tokens"`
+	CacheCreationTokens int64     `json:"cache_creation_tokens"`
+	CacheReadTokens     int64     `json:"cache_read_tokens"`
+	DurationMs          int64     `json:"duration_ms"`
+	Error               string    `json:"error,omitempty"`
+}
+
+// UsageLog 以追加方式写入 JSONL 使用记录，并发安全
+type UsageLog struct {
+	mu  sync.Mutex
+	f   *os.File
+	enc *json.Encoder
+}
+
+// OpenUsageLog 打开（不存在时创建）使用记录文件
+func OpenUsageLog(path string) (*UsageLog, error) {
+	f, err := os.OpenFile(path, os.O_CREATE|os.O_APPEND|os.O_WRONLY, 0o600)
+	if err != nil {
+		return nil, err
+	}
+	return &UsageLog{f: f, enc: json.NewEncoder(f)}, nil
+}
+
+// Write 追加一条记录
+func (l *UsageLog) Write(rec *UsageRecord) error {
+	l.mu.Lock()
+	defer l.mu.Unlock()
+	return l.enc.Encode(rec)
+}
+
+// Close 关闭文件
+func (l *UsageLog) Close() error {
+	l.mu.Lock()
+	defer l.mu.Unlock()
+	return l.f.Close()
+}
+
+// mergeUsage 从响应体或单个 SSE data 事件中提取 token 用量，已出现的字段以后出现的为准：
+//   - Anthropic：响应体 usage；流式 message_start 的 message.usage 与 message_delta 的 usage
+//   - OpenAI Responses：响应体 usage；流式 response.completed / incomplete / failed 的 response.usage
+//   - OpenAI Chat Completions：响应体 usage；流式末尾携带 usage 的 chunk（需客户端开启 stream_options.include_usage）
+func mergeUsage(rec *UsageRecord, payload []byte) {
+	payload = bytes.TrimSpace(payload)
+	if len(payload) == 0 || payload[0] != '{' {
+		return
+	}
+	event := gjson.ParseBytes(payload)
+	var usage gjson.Result
+	switch event.Get("type").String() {
+	case "message_start":
+		usage = event.Get("message.usage")
+	case "response.completed", "response.incomplete", "response.failed":
+		usage = event.Get("response.usage")
+	default:
+		usage = event.Get("usage")
+	}
+	if !usage.IsObject() {
+		return
+	}
+	setUsageField(&rec.InputTokens, usage, "input_tokens", "prompt_tokens")
+	setUsageField(&rec.OutputTokens, usage, "output_tokens", "completion_tokens")
+	setUsageField(&rec.CacheCreationTokens, usage, "cache_creation_input_tokens")
+	setUsageField(&rec.CacheReadTokens, usage, "cache_read_input_tokens", "input_tokens_details.cached_tokens", "prompt_tokens_details.cached_tokens")
+}
+
+func setUsageField(dst *int64, usage gjson.Result, paths ...string) {
+	for _, path := range paths {
+		if v := usage.Get(path); v.Exists() {
+			*dst = v.Int()
+			return
+		}
+	}
+}
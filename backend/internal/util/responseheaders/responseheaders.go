@@ -12,26 +12,22 @@ import (
 //   - content-length: 由 ResponseWriter 根据实际写入数据自动设置
 //   - transfer-encoding: 由 HTTP 库根据需要自动添加/移除
 //   - connection: 由 HTTP 库管理连接复用
+//
+// 上游的 retry-after / x-ratelimit-* 只反映单个上游账号的状态，默认不透传；
+// 网关在限流响应中按自身的账号调度状态另行设置这些头。
 var defaultAllowed = map[string]struct{}{
-	"content-type":                   {},
-	"content-encoding":               {},
-	"content-language":               {},
-	"cache-control":                  {},
-	"etag":                           {},
-	"last-modified":                  {},
-	"expires":                        {},
-	"vary":                           {},
-	"date":                           {},
-	"x-request-id":                   {},
-	"x-ratelimit-limit-requests":     {},
-	"x-ratelimit-limit-tokens":       {},
-	"x-ratelimit-remaining-requests": {},
-	"x-ratelimit-remaining-tokens":   {},
-	"x-ratelimit-reset-requests":     {},
-	"x-ratelimit-reset-tokens":       {},
-	"retry-after":                    {},
-	"location":                       {},
-	"www-authenticate":               {},
+	"content-type":     {},
+	"content-encoding": {},
+	"content-language": {},
+	"cache-control":    {},
+	"etag":             {},
+	"last-modified":    {},
+	"expires":          {},
+	"vary":             {},
+	"date":             {},
+	"x-request-id":     {},
+	"location":         {},
+	"www-authenticate": {},
 }
 
 // hopByHopHeaders 是跳过的 hop-by-hop 头部，这些头部由 HTTP 库自动处理
@@ -89,7 +89,9 @@ func (s *FrontendServer) Middleware() gin.HandlerFunc {
 			strings.HasPrefix(path, "/antigravity/") ||
 			strings.HasPrefix(path, "/setup/") ||
 			path == "/health" ||
-			path == "/responses" {
+			path == "/responses" ||
+			path == "/openapi.json" ||
+			path == "/docs" {
 			c.Next()
 			return
 		}
@@ -213,7 +215,9 @@ func ServeEmbeddedFrontend() gin.HandlerFunc {
 			strings.HasPrefix(path, "/antigravity/") ||
 			strings.HasPrefix(path, "/setup/") ||
 			path == "/health" ||
-			path == "/responses" {
+			path == "/responses" ||
+			path == "/openapi.json" ||
+			path == "/docs" {
 			c.Next()
 			return
 		}
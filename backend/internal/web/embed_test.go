@@ -367,6 +367,8 @@ func TestFrontendServer_Middleware(t *testing.T) {
 			"/setup/init",
 			"/health",
 			"/responses",
+			"/openapi.json",
+			"/docs",
 		}
 
 		for _, path := range apiPaths {
@@ -543,6 +545,8 @@ func TestServeEmbeddedFrontend(t *testing.T) {
 			"/setup/init",
 			"/health",
 			"/responses",
+			"/openapi.json",
+			"/docs",
 		}
 
 		for _, path := range apiPaths {
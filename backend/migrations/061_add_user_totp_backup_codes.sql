@@ -0,0 +1,16 @@
+-- TOTP 备用恢复码表
+-- 每个备用码仅可使用一次，只保存 SHA256 哈希
+CREATE TABLE IF NOT EXISTS user_totp_backup_codes (
+    id         BIGSERIAL PRIMARY KEY,
+    user_id    BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    code_hash  VARCHAR(64) NOT NULL,
+    used_at    TIMESTAMPTZ DEFAULT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_user_totp_backup_codes_user_hash
+    ON user_totp_backup_codes(user_id, code_hash);
+
+COMMENT ON TABLE user_totp_backup_codes IS 'TOTP 双因素认证备用恢复码';
+COMMENT ON COLUMN user_totp_backup_codes.code_hash IS '备用码 SHA256 哈希';
+COMMENT ON COLUMN user_totp_backup_codes.used_at IS '使用时间（NULL 表示未使用）';
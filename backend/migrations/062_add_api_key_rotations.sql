@@ -0,0 +1,20 @@
+-- API Key 轮换记录表
+-- 轮换时签发新 Key，旧 Key 在宽限期内仍然有效（通过 api_keys.expires_at 自动过期）
+CREATE TABLE IF NOT EXISTS api_key_rotations (
+    id             BIGSERIAL PRIMARY KEY,
+    user_id        BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    old_api_key_id BIGINT NOT NULL REFERENCES api_keys(id) ON DELETE CASCADE,
+    new_api_key_id BIGINT NOT NULL REFERENCES api_keys(id) ON DELETE CASCADE,
+    grace_until    TIMESTAMPTZ NOT NULL,
+    created_at     TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_api_key_rotations_old_key
+    ON api_key_rotations(old_api_key_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_api_key_rotations_new_key
+    ON api_key_rotations(new_api_key_id);
+
+COMMENT ON TABLE api_key_rotations IS 'API Key 轮换记录';
+COMMENT ON COLUMN api_key_rotations.old_api_key_id IS '被轮换的旧 Key';
+COMMENT ON COLUMN api_key_rotations.new_api_key_id IS '替换后的新 Key';
+COMMENT ON COLUMN api_key_rotations.grace_until IS '旧 Key 宽限期截止时间';
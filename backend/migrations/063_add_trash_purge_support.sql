@@ -0,0 +1,25 @@
+-- 回收站支持：软删除的 API Key / 上游账号可在保留期内恢复，过期后由后台任务清除
+--
+-- 1) 账号删除时会移除 account_groups 绑定，这里保存一份快照用于恢复
+CREATE TABLE IF NOT EXISTS trashed_account_groups (
+    account_id BIGINT NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    group_id   BIGINT NOT NULL REFERENCES groups(id) ON DELETE CASCADE,
+    priority   INT NOT NULL DEFAULT 50,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (account_id, group_id)
+);
+
+COMMENT ON TABLE trashed_account_groups IS '已删除账号的分组绑定快照（用于从回收站恢复）';
+
+-- 2) 仍被 usage_logs 引用的记录无法物理删除（外键级联会删掉历史用量），
+--    清除时改为抹掉敏感字段并标记 purged_at，之后不再出现在回收站中
+ALTER TABLE api_keys ADD COLUMN IF NOT EXISTS purged_at TIMESTAMPTZ;
+ALTER TABLE accounts ADD COLUMN IF NOT EXISTS purged_at TIMESTAMPTZ;
+
+COMMENT ON COLUMN api_keys.purged_at IS '回收站清除时间（敏感字段已抹除，不可恢复）';
+COMMENT ON COLUMN accounts.purged_at IS '回收站清除时间（凭证已抹除，不可恢复）';
+
+CREATE INDEX IF NOT EXISTS idx_api_keys_trash
+    ON api_keys(deleted_at) WHERE deleted_at IS NOT NULL AND purged_at IS NULL;
+CREATE INDEX IF NOT EXISTS idx_accounts_trash
+    ON accounts(deleted_at) WHERE deleted_at IS NOT NULL AND purged_at IS NULL;
@@ -0,0 +1,54 @@
+-- Per-dimension usage rollups (api_key_id, account_id, model) at hourly/daily granularity.
+-- Maintained by the dashboard aggregation job alongside usage_dashboard_hourly/daily,
+-- so key/account/model breakdowns no longer scan raw usage_logs.
+
+CREATE TABLE IF NOT EXISTS usage_rollup_hourly (
+    bucket_start TIMESTAMPTZ NOT NULL,
+    api_key_id BIGINT NOT NULL,
+    account_id BIGINT NOT NULL,
+    model VARCHAR(100) NOT NULL,
+    total_requests BIGINT NOT NULL DEFAULT 0,
+    input_tokens BIGINT NOT NULL DEFAULT 0,
+    output_tokens BIGINT NOT NULL DEFAULT 0,
+    cache_creation_tokens BIGINT NOT NULL DEFAULT 0,
+    cache_read_tokens BIGINT NOT NULL DEFAULT 0,
+    total_cost DECIMAL(20, 10) NOT NULL DEFAULT 0,
+    actual_cost DECIMAL(20, 10) NOT NULL DEFAULT 0,
+    account_cost DECIMAL(20, 10) NOT NULL DEFAULT 0,
+    total_duration_ms BIGINT NOT NULL DEFAULT 0,
+    computed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (bucket_start, api_key_id, account_id, model)
+);
+
+CREATE INDEX IF NOT EXISTS idx_usage_rollup_hourly_api_key
+    ON usage_rollup_hourly (api_key_id, bucket_start);
+CREATE INDEX IF NOT EXISTS idx_usage_rollup_hourly_account
+    ON usage_rollup_hourly (account_id, bucket_start);
+
+COMMENT ON TABLE usage_rollup_hourly IS 'Hourly usage rollup per api key, account and model.';
+COMMENT ON COLUMN usage_rollup_hourly.account_cost IS 'SUM(total_cost * account_rate_multiplier), i.e. cost from the account perspective.';
+
+CREATE TABLE IF NOT EXISTS usage_rollup_daily (
+    bucket_date DATE NOT NULL,
+    api_key_id BIGINT NOT NULL,
+    account_id BIGINT NOT NULL,
+    model VARCHAR(100) NOT NULL,
+    total_requests BIGINT NOT NULL DEFAULT 0,
+    input_tokens BIGINT NOT NULL DEFAULT 0,
+    output_tokens BIGINT NOT NULL DEFAULT 0,
+    cache_creation_tokens BIGINT NOT NULL DEFAULT 0,
+    cache_read_tokens BIGINT NOT NULL DEFAULT 0,
+    total_cost DECIMAL(20, 10) NOT NULL DEFAULT 0,
+    actual_cost DECIMAL(20, 10) NOT NULL DEFAULT 0,
+    account_cost DECIMAL(20, 10) NOT NULL DEFAULT 0,
+    total_duration_ms BIGINT NOT NULL DEFAULT 0,
+    computed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (bucket_date, api_key_id, account_id, model)
+);
+
+CREATE INDEX IF NOT EXISTS idx_usage_rollup_daily_api_key
+    ON usage_rollup_daily (api_key_id, bucket_date);
+CREATE INDEX IF NOT EXISTS idx_usage_rollup_daily_account
+    ON usage_rollup_daily (account_id, bucket_date);
+
+COMMENT ON TABLE usage_rollup_daily IS 'Daily usage rollup per api key, account and model (dates in the server timezone).';
@@ -0,0 +1,19 @@
+-- 定时任务执行状态：记录每个 cron 任务最近一次调度/执行结果，
+-- 用于多副本去重（同一调度时间只执行一次）与重启后补跑错过的调度
+CREATE TABLE IF NOT EXISTS cron_job_runs (
+    name              VARCHAR(100) PRIMARY KEY,
+    last_scheduled_at TIMESTAMPTZ,
+    last_started_at   TIMESTAMPTZ,
+    last_finished_at  TIMESTAMPTZ,
+    last_status       VARCHAR(20) NOT NULL DEFAULT '',
+    last_error        TEXT NOT NULL DEFAULT '',
+    last_duration_ms  BIGINT NOT NULL DEFAULT 0,
+    last_instance     VARCHAR(100) NOT NULL DEFAULT '',
+    run_count         BIGINT NOT NULL DEFAULT 0,
+    failure_count     BIGINT NOT NULL DEFAULT 0,
+    updated_at        TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+COMMENT ON TABLE cron_job_runs IS '定时任务最近一次执行状态';
+COMMENT ON COLUMN cron_job_runs.last_scheduled_at IS '最近一次执行对应的调度时间（手动触发时为触发时间）';
+COMMENT ON COLUMN cron_job_runs.last_status IS 'running / succeeded / failed';
@@ -0,0 +1,5 @@
+-- 使用记录单独统计推理（thinking / reasoning）token
+-- 推理 token 已计入 output_tokens，此列仅用于分析展示，不参与计费
+ALTER TABLE usage_logs ADD COLUMN IF NOT EXISTS reasoning_tokens INTEGER NOT NULL DEFAULT 0;
+
+COMMENT ON COLUMN usage_logs.reasoning_tokens IS '推理 token 数（已包含在 output_tokens 中）';
@@ -0,0 +1,9 @@
+-- API Key 级网关策略（推理内容处理等），以 JSONB 存储便于扩展
+CREATE TABLE IF NOT EXISTS api_key_policies (
+    api_key_id BIGINT PRIMARY KEY REFERENCES api_keys(id) ON DELETE CASCADE,
+    policy     JSONB NOT NULL DEFAULT '{}'::jsonb,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+COMMENT ON TABLE api_key_policies IS 'API Key 级网关策略';
+COMMENT ON COLUMN api_key_policies.policy IS '策略内容（JSON），字段见 service.APIKeyPolicy';
@@ -0,0 +1,9 @@
+-- 分组级网关策略（系统提示词模板等），字段与 api_key_policies 一致；API Key 级策略优先
+CREATE TABLE IF NOT EXISTS group_policies (
+    group_id   BIGINT PRIMARY KEY REFERENCES groups(id) ON DELETE CASCADE,
+    policy     JSONB NOT NULL DEFAULT '{}'::jsonb,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+COMMENT ON TABLE group_policies IS '分组级网关策略，作为该分组下 API Key 的默认策略';
+COMMENT ON COLUMN group_policies.policy IS '策略内容（JSON），字段见 service.APIKeyPolicy';
@@ -0,0 +1,4 @@
+-- 提示词防火墙对请求的处理结果（flag / sanitize 及命中规则），被拦截的请求不产生使用记录，见 ops_error_logs
+ALTER TABLE usage_logs ADD COLUMN IF NOT EXISTS prompt_firewall VARCHAR(255);
+
+COMMENT ON COLUMN usage_logs.prompt_firewall IS '提示词防火墙结果，格式：<action>:<rule>[,<rule>...]，NULL 表示未命中';
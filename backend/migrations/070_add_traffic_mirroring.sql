@@ -0,0 +1,44 @@
+-- 流量镜像：按规则抽样将请求异步复制到备选账号/模型，保存主请求与镜像请求的输出供离线对比
+CREATE TABLE IF NOT EXISTS traffic_mirror_rules (
+    id                BIGSERIAL PRIMARY KEY,
+    name              VARCHAR(100) NOT NULL,
+    enabled           BOOLEAN NOT NULL DEFAULT TRUE,
+    group_id          BIGINT REFERENCES groups(id) ON DELETE CASCADE,
+    model_pattern     VARCHAR(200) NOT NULL DEFAULT '',
+    sample_rate       DOUBLE PRECISION NOT NULL DEFAULT 0,
+    target_account_id BIGINT NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    target_model      VARCHAR(200) NOT NULL DEFAULT '',
+    created_at        TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at        TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+COMMENT ON TABLE traffic_mirror_rules IS '流量镜像规则';
+COMMENT ON COLUMN traffic_mirror_rules.group_id IS '限定分组，NULL 表示所有分组';
+COMMENT ON COLUMN traffic_mirror_rules.model_pattern IS '模型名或以 * 结尾的前缀，空表示所有模型';
+COMMENT ON COLUMN traffic_mirror_rules.sample_rate IS '抽样比例 0~1';
+COMMENT ON COLUMN traffic_mirror_rules.target_model IS '镜像请求使用的模型，空表示沿用原请求模型';
+
+CREATE TABLE IF NOT EXISTS traffic_mirror_samples (
+    id                 BIGSERIAL PRIMARY KEY,
+    rule_id            BIGINT NOT NULL REFERENCES traffic_mirror_rules(id) ON DELETE CASCADE,
+    request_type       VARCHAR(32) NOT NULL,
+    api_key_id         BIGINT,
+    group_id           BIGINT,
+    model              VARCHAR(200) NOT NULL DEFAULT '',
+    primary_account_id BIGINT,
+    primary_latency_ms INT,
+    primary_response   TEXT NOT NULL DEFAULT '',
+    primary_truncated  BOOLEAN NOT NULL DEFAULT FALSE,
+    mirror_account_id  BIGINT,
+    mirror_model       VARCHAR(200) NOT NULL DEFAULT '',
+    mirror_status_code INT,
+    mirror_latency_ms  INT,
+    mirror_response    TEXT NOT NULL DEFAULT '',
+    mirror_truncated   BOOLEAN NOT NULL DEFAULT FALSE,
+    mirror_error       TEXT,
+    created_at         TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_traffic_mirror_samples_rule_created ON traffic_mirror_samples (rule_id, created_at DESC);
+
+COMMENT ON TABLE traffic_mirror_samples IS '流量镜像样本：主请求与镜像请求的输出（超出上限时截断）';
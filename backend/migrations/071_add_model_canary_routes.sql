@@ -0,0 +1,15 @@
+-- 金丝雀路由：模型别名按权重分流到多个目标模型，权重可通过管理接口在运行时调整
+CREATE TABLE IF NOT EXISTS model_canary_routes (
+    id         BIGSERIAL PRIMARY KEY,
+    alias      VARCHAR(200) NOT NULL,
+    enabled    BOOLEAN NOT NULL DEFAULT TRUE,
+    targets    JSONB NOT NULL DEFAULT '[]'::jsonb,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_model_canary_routes_alias ON model_canary_routes (alias);
+
+COMMENT ON TABLE model_canary_routes IS '模型金丝雀路由';
+COMMENT ON COLUMN model_canary_routes.alias IS '客户端请求的模型名（小写）';
+COMMENT ON COLUMN model_canary_routes.targets IS '目标列表 [{name, model, weight}]，按权重比例分流';
@@ -0,0 +1,25 @@
+-- Per-account hourly health rollups (latency percentiles, success/error counts, throughput).
+-- Maintained by the dashboard aggregation job alongside usage_rollup_hourly so operators can
+-- spot degrading upstream accounts without scanning raw usage_logs / ops_error_logs.
+
+CREATE TABLE IF NOT EXISTS account_health_rollup_hourly (
+    bucket_start TIMESTAMPTZ NOT NULL,
+    account_id BIGINT NOT NULL,
+    success_count BIGINT NOT NULL DEFAULT 0,
+    error_count BIGINT NOT NULL DEFAULT 0,
+    output_tokens BIGINT NOT NULL DEFAULT 0,
+    duration_p50_ms INT,
+    duration_p95_ms INT,
+    duration_p99_ms INT,
+    first_token_p50_ms INT,
+    first_token_p95_ms INT,
+    first_token_p99_ms INT,
+    computed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (bucket_start, account_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_account_health_rollup_hourly_account
+    ON account_health_rollup_hourly (account_id, bucket_start);
+
+COMMENT ON TABLE account_health_rollup_hourly IS 'Hourly per-account health rollup: latency percentiles from usage_logs, upstream errors from ops_error_logs.';
+COMMENT ON COLUMN account_health_rollup_hourly.error_count IS 'Provider-owned, non business-limited errors attributed to the account (count_tokens excluded).';
@@ -0,0 +1,43 @@
+-- Searchable gateway request logs (one row per client request, success or failure).
+-- Written asynchronously in batches by the gateway; rows older than request_log.retention_days
+-- are purged hourly by the request_log_purge cron job, so the table stays bounded.
+
+CREATE TABLE IF NOT EXISTS request_logs (
+    id BIGSERIAL PRIMARY KEY,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    trace_id VARCHAR(64),
+    client_request_id VARCHAR(64),
+    user_id BIGINT,
+    api_key_id BIGINT,
+    account_id BIGINT,
+    group_id BIGINT,
+    platform VARCHAR(32),
+    model VARCHAR(128),
+    method VARCHAR(16) NOT NULL DEFAULT '',
+    path VARCHAR(256) NOT NULL DEFAULT '',
+    stream BOOLEAN NOT NULL DEFAULT FALSE,
+    status_code INT NOT NULL,
+    error_class VARCHAR(64),
+    duration_ms INT NOT NULL DEFAULT 0,
+    client_ip VARCHAR(64)
+);
+
+CREATE INDEX IF NOT EXISTS idx_request_logs_created_at
+    ON request_logs (created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_request_logs_api_key_created_at
+    ON request_logs (api_key_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_request_logs_model_created_at
+    ON request_logs (model, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_request_logs_status_created_at
+    ON request_logs (status_code, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_request_logs_error_class_created_at
+    ON request_logs (error_class, created_at DESC)
+    WHERE error_class IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_request_logs_trace_id
+    ON request_logs (trace_id);
+CREATE INDEX IF NOT EXISTS idx_request_logs_client_request_id
+    ON request_logs (client_request_id);
+
+COMMENT ON TABLE request_logs IS 'Per-request gateway log for admin search; bounded by request_log.retention_days.';
+COMMENT ON COLUMN request_logs.trace_id IS 'X-Request-ID returned to the client and attached to server logs.';
+COMMENT ON COLUMN request_logs.error_class IS 'Unified upstream error class (auth_expired, rate_limited, ...) or a gateway-side class derived from the status code.';
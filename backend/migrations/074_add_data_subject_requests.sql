@@ -0,0 +1,30 @@
+-- 数据主体（用户 / API Key）导出与永久删除请求
+-- 请求记录本身即为审计记录：删除任务不会清除本表，report 记录每张表导出/删除的行数。
+
+CREATE TABLE IF NOT EXISTS data_subject_requests (
+    id            BIGSERIAL PRIMARY KEY,
+    kind          VARCHAR(16) NOT NULL,
+    subject_type  VARCHAR(16) NOT NULL,
+    subject_id    BIGINT NOT NULL,
+    status        VARCHAR(16) NOT NULL DEFAULT 'pending',
+    reason        TEXT NOT NULL DEFAULT '',
+    requested_by  BIGINT REFERENCES users(id) ON DELETE SET NULL,
+    report        JSONB,
+    export_key    VARCHAR(512),
+    error_message TEXT,
+    created_at    TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    started_at    TIMESTAMPTZ,
+    finished_at   TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_data_subject_requests_created_at
+    ON data_subject_requests (created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_data_subject_requests_subject
+    ON data_subject_requests (subject_type, subject_id, status);
+
+COMMENT ON TABLE data_subject_requests IS '数据主体导出/删除请求及审计记录';
+COMMENT ON COLUMN data_subject_requests.kind IS 'export | delete';
+COMMENT ON COLUMN data_subject_requests.subject_type IS 'user | api_key';
+COMMENT ON COLUMN data_subject_requests.status IS 'pending | running | succeeded | failed';
+COMMENT ON COLUMN data_subject_requests.report IS '按表统计的导出/删除行数';
+COMMENT ON COLUMN data_subject_requests.export_key IS '导出文件在对象存储中的相对 key';
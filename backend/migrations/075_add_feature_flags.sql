@@ -0,0 +1,24 @@
+-- 运行时功能开关：按部署环境 / 用户 / 分组 / API Key 灰度开启高风险功能，无需重新部署。
+-- 未在本表中配置的开关使用代码内置默认值。
+
+CREATE TABLE IF NOT EXISTS feature_flags (
+    key             VARCHAR(100) PRIMARY KEY,
+    description     TEXT NOT NULL DEFAULT '',
+    enabled         BOOLEAN NOT NULL DEFAULT FALSE,
+    rollout_percent INT NOT NULL DEFAULT 100 CHECK (rollout_percent BETWEEN 0 AND 100),
+    environments    TEXT[] NOT NULL DEFAULT '{}',
+    user_ids        BIGINT[] NOT NULL DEFAULT '{}',
+    group_ids       BIGINT[] NOT NULL DEFAULT '{}',
+    api_key_ids     BIGINT[] NOT NULL DEFAULT '{}',
+    updated_by      BIGINT REFERENCES users(id) ON DELETE SET NULL,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at      TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+COMMENT ON TABLE feature_flags IS '运行时功能开关';
+COMMENT ON COLUMN feature_flags.enabled IS '总开关，false 时对所有请求关闭';
+COMMENT ON COLUMN feature_flags.rollout_percent IS '未命中定向列表时按主体哈希放量的百分比';
+COMMENT ON COLUMN feature_flags.environments IS '仅在这些部署环境（log.env）生效，空表示不限';
+COMMENT ON COLUMN feature_flags.user_ids IS '定向开启的用户';
+COMMENT ON COLUMN feature_flags.group_ids IS '定向开启的分组';
+COMMENT ON COLUMN feature_flags.api_key_ids IS '定向开启的 API Key';
@@ -0,0 +1,22 @@
+-- 消费硬上限：API Key / 用户在统计周期内的消费达到上限后自动暂停，
+-- 直到进入新周期或管理员手动解除。周期消费计数保存在 Redis，缺失时以 usage_logs 重建。
+
+CREATE TABLE IF NOT EXISTS spending_caps (
+    scope_type             VARCHAR(20) NOT NULL CHECK (scope_type IN ('api_key', 'user')),
+    scope_id               BIGINT NOT NULL,
+    period                 VARCHAR(20) NOT NULL CHECK (period IN ('daily', 'weekly', 'monthly')),
+    limit_usd              DECIMAL(20, 8) NOT NULL CHECK (limit_usd > 0),
+    suspended_at           TIMESTAMPTZ,
+    suspended_period_start TIMESTAMPTZ,
+    exempt_period_start    TIMESTAMPTZ,
+    updated_by             BIGINT REFERENCES users(id) ON DELETE SET NULL,
+    created_at             TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at             TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (scope_type, scope_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_spending_caps_suspended ON spending_caps (suspended_at) WHERE suspended_at IS NOT NULL;
+
+COMMENT ON TABLE spending_caps IS 'API Key / 用户周期消费硬上限';
+COMMENT ON COLUMN spending_caps.suspended_period_start IS '自动暂停所在周期的起点，进入新周期后暂停自动失效';
+COMMENT ON COLUMN spending_caps.exempt_period_start IS '管理员解除暂停的周期起点，该周期内不再强制上限';
@@ -0,0 +1,22 @@
+-- 月度账单：按用户（账户）汇总每月的模型用量、费用与充值，
+-- JSON / CSV / PDF 文件写入对象存储，本表只记录汇总与对象 key。
+
+CREATE TABLE IF NOT EXISTS billing_statements (
+    id              BIGSERIAL PRIMARY KEY,
+    user_id         BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    period_start    TIMESTAMPTZ NOT NULL,
+    period_end      TIMESTAMPTZ NOT NULL,
+    request_count   BIGINT NOT NULL DEFAULT 0,
+    total_cost      DECIMAL(20, 10) NOT NULL DEFAULT 0,
+    actual_cost     DECIMAL(20, 10) NOT NULL DEFAULT 0,
+    credits_applied DECIMAL(20, 8) NOT NULL DEFAULT 0,
+    object_keys     JSONB NOT NULL DEFAULT '{}'::jsonb,
+    generated_at    TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (user_id, period_start)
+);
+
+CREATE INDEX IF NOT EXISTS idx_billing_statements_period ON billing_statements (period_start);
+
+COMMENT ON TABLE billing_statements IS '月度账单汇总（文件存放在对象存储）';
+COMMENT ON COLUMN billing_statements.credits_applied IS '当月到账的充值（余额卡密与管理员正向调整）';
+COMMENT ON COLUMN billing_statements.object_keys IS '各格式文件的对象存储相对 key，如 {"json": "...", "csv": "...", "pdf": "..."}';
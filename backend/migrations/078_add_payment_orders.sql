@@ -0,0 +1,30 @@
+-- 在线支付订单（Stripe Checkout 自助充值）。
+-- 入账与退款在同一事务内更新订单状态、用户余额并写入 redeem_codes 余额记录；
+-- 订单状态的条件更新保证重复的 Webhook 回调只入账一次。
+
+CREATE TABLE IF NOT EXISTS payment_orders (
+    id                 BIGSERIAL PRIMARY KEY,
+    user_id            BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    provider           VARCHAR(20) NOT NULL DEFAULT 'stripe',
+    session_id         VARCHAR(255) NOT NULL,
+    payment_intent_id  VARCHAR(255),
+    currency           VARCHAR(3) NOT NULL,
+    amount_minor       BIGINT NOT NULL,
+    credit_amount      DECIMAL(20, 8) NOT NULL,
+    refunded_minor     BIGINT NOT NULL DEFAULT 0,
+    refunded_credit    DECIMAL(20, 8) NOT NULL DEFAULT 0,
+    status             VARCHAR(20) NOT NULL DEFAULT 'pending',
+    checkout_url       TEXT NOT NULL DEFAULT '',
+    created_at         TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at         TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    paid_at            TIMESTAMPTZ,
+    UNIQUE (provider, session_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_payment_orders_user ON payment_orders (user_id, created_at DESC);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_payment_orders_intent ON payment_orders (provider, payment_intent_id) WHERE payment_intent_id IS NOT NULL;
+
+COMMENT ON TABLE payment_orders IS '在线支付充值订单';
+COMMENT ON COLUMN payment_orders.amount_minor IS '支付金额（币种最小单位，如美分）';
+COMMENT ON COLUMN payment_orders.credit_amount IS '支付成功后入账的余额（USD）';
+COMMENT ON COLUMN payment_orders.status IS 'pending / paid / partially_refunded / refunded / expired';
@@ -0,0 +1,13 @@
+-- 后台账号密码状态：强制改密标记与最近一次改密时间，用于密码轮换策略。
+-- 角色本身仍保存在 users.role（admin / billing / accounts / auditor）。
+
+CREATE TABLE IF NOT EXISTS admin_user_credentials (
+    user_id              BIGINT PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    must_change_password BOOLEAN NOT NULL DEFAULT FALSE,
+    password_changed_at  TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    created_at           TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at           TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+COMMENT ON TABLE admin_user_credentials IS '后台账号密码状态';
+COMMENT ON COLUMN admin_user_credentials.must_change_password IS '新建或重置密码后需先修改密码才能进入管理后台';
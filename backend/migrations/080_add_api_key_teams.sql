@@ -0,0 +1,32 @@
+-- 团队：同一账户下的多个 API Key 组成团队，共享团队预算（spending_caps.scope_type = 'team'），
+-- 每个成员 Key 可设置子额度（scope_type = 'team_member'，scope_id 为 API Key ID）。
+-- 计费仍从团队所属账户余额扣除；用量按成员归属统计。
+
+CREATE TABLE IF NOT EXISTS api_key_teams (
+    id         BIGSERIAL PRIMARY KEY,
+    user_id    BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name       VARCHAR(100) NOT NULL,
+    period     VARCHAR(20) NOT NULL DEFAULT 'monthly' CHECK (period IN ('daily', 'weekly', 'monthly')),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_api_key_teams_user_id ON api_key_teams (user_id);
+
+-- 每个 API Key 最多属于一个团队
+CREATE TABLE IF NOT EXISTS api_key_team_members (
+    api_key_id  BIGINT PRIMARY KEY REFERENCES api_keys(id) ON DELETE CASCADE,
+    team_id     BIGINT NOT NULL REFERENCES api_key_teams(id) ON DELETE CASCADE,
+    member_name VARCHAR(100) NOT NULL DEFAULT '',
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_api_key_team_members_team_id ON api_key_team_members (team_id);
+
+ALTER TABLE spending_caps DROP CONSTRAINT IF EXISTS spending_caps_scope_type_check;
+ALTER TABLE spending_caps ADD CONSTRAINT spending_caps_scope_type_check
+    CHECK (scope_type IN ('api_key', 'user', 'team', 'team_member'));
+
+COMMENT ON TABLE api_key_teams IS 'API Key 团队（共享预算）';
+COMMENT ON COLUMN api_key_teams.period IS '团队预算与成员子额度的统计周期';
+COMMENT ON TABLE api_key_team_members IS '团队成员 API Key 及其归属的成员名称';
@@ -0,0 +1,36 @@
+-- 邮件通知：按用户、按通知类型的退订偏好，以及用于排查投递问题的发送记录。
+
+-- 仅记录用户修改过的类型；没有记录时默认接收
+CREATE TABLE IF NOT EXISTS notification_preferences (
+    user_id       BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    kind          VARCHAR(40) NOT NULL,
+    email_enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    updated_at    TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, kind)
+);
+
+CREATE TABLE IF NOT EXISTS notification_send_logs (
+    id         BIGSERIAL PRIMARY KEY,
+    user_id    BIGINT,
+    recipient  VARCHAR(255) NOT NULL,
+    kind       VARCHAR(40) NOT NULL,
+    subject    VARCHAR(255) NOT NULL DEFAULT '',
+    status     VARCHAR(20) NOT NULL CHECK (status IN ('queued', 'sent', 'failed', 'skipped')),
+    error      TEXT NOT NULL DEFAULT '',
+    attempts   INT NOT NULL DEFAULT 0,
+    dedupe_key VARCHAR(200),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    sent_at    TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_notification_send_logs_created_at ON notification_send_logs (created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_notification_send_logs_recipient ON notification_send_logs (recipient, created_at DESC);
+-- 同一事件（如同一 Key 的同一到期时间）只通知一次，多实例下同样生效
+CREATE UNIQUE INDEX IF NOT EXISTS idx_notification_send_logs_dedupe_key
+    ON notification_send_logs (dedupe_key) WHERE dedupe_key IS NOT NULL;
+
+COMMENT ON TABLE notification_preferences IS '用户邮件通知偏好（按通知类型）';
+COMMENT ON TABLE notification_send_logs IS '通知邮件发送记录';
+COMMENT ON COLUMN notification_send_logs.status IS 'queued: 已入队; sent: 已发送; failed: 发送失败（持久化队列会重试）; skipped: 收件人已退订';
+COMMENT ON COLUMN notification_send_logs.dedupe_key IS '事件去重键，为空时不去重';
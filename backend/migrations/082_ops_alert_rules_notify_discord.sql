@@ -0,0 +1,5 @@
+-- 告警规则可单独选择是否推送到 Discord（Webhook 地址等配置保存在 settings 表中）
+ALTER TABLE ops_alert_rules
+    ADD COLUMN IF NOT EXISTS notify_discord BOOLEAN NOT NULL DEFAULT FALSE;
+
+COMMENT ON COLUMN ops_alert_rules.notify_discord IS '触发 / 恢复时推送到 Discord Webhook';
@@ -0,0 +1,42 @@
+-- 语义响应缓存：按提问向量做近邻查找，复用相似问题的非流式响应
+--
+-- 依赖 pgvector 扩展。扩展不可用（未安装或无权限）时跳过建表，不阻塞迁移；
+-- 此时开启 semantic_cache 只会在日志中记录查询失败，请求按未命中正常转发。
+
+DO $$
+BEGIN
+  BEGIN
+    CREATE EXTENSION IF NOT EXISTS vector;
+  EXCEPTION WHEN OTHERS THEN
+    RAISE NOTICE 'vector extension not created: %', SQLERRM;
+  END;
+
+  IF EXISTS (SELECT 1 FROM pg_extension WHERE extname = 'vector') THEN
+    -- 向量不限定维度，更换 embedding 模型时无需改表（维度不同的旧条目不会被 <=> 比较，应随 TTL 过期）
+    EXECUTE 'CREATE TABLE IF NOT EXISTS semantic_cache_entries (
+        id           BIGSERIAL PRIMARY KEY,
+        api_key_id   BIGINT NOT NULL,
+        request_type VARCHAR(32) NOT NULL,
+        model        VARCHAR(100) NOT NULL,
+        context_hash VARCHAR(64) NOT NULL,
+        prompt_hash  VARCHAR(64) NOT NULL,
+        prompt       TEXT NOT NULL,
+        embedding    vector NOT NULL,
+        dimensions   INT NOT NULL,
+        response     TEXT NOT NULL,
+        hit_count    BIGINT NOT NULL DEFAULT 0,
+        created_at   TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+        expires_at   TIMESTAMPTZ NOT NULL
+    )';
+
+    EXECUTE 'CREATE INDEX IF NOT EXISTS idx_semantic_cache_entries_scope
+             ON semantic_cache_entries (api_key_id, request_type, model, context_hash)';
+    EXECUTE 'CREATE INDEX IF NOT EXISTS idx_semantic_cache_entries_expires_at
+             ON semantic_cache_entries (api_key_id, expires_at)';
+
+    EXECUTE 'COMMENT ON TABLE semantic_cache_entries IS ''语义响应缓存条目（按 API Key、协议、模型与上下文隔离）''';
+    EXECUTE 'COMMENT ON COLUMN semantic_cache_entries.context_hash IS ''除最后一条用户消息外的请求内容哈希''';
+    EXECUTE 'COMMENT ON COLUMN semantic_cache_entries.prompt_hash IS ''最后一条用户消息的哈希，用于精确命中''';
+    EXECUTE 'COMMENT ON COLUMN semantic_cache_entries.dimensions IS ''embedding 维度，近邻查找只比较同维度向量''';
+  END IF;
+END $$;
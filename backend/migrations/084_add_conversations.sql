@@ -0,0 +1,33 @@
+-- 服务端会话持久化：客户端携带 conversation_id 时由网关补全历史消息
+-- 会话 ID 由客户端指定，在同一用户下唯一
+
+CREATE TABLE IF NOT EXISTS conversations (
+    user_id       BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    id            VARCHAR(128) NOT NULL,
+    api_key_id    BIGINT,
+    model         VARCHAR(100) NOT NULL DEFAULT '',
+    system        JSONB,
+    message_count INT NOT NULL DEFAULT 0,
+    created_at    TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at    TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at    TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (user_id, id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_conversations_expires_at ON conversations (expires_at);
+
+CREATE TABLE IF NOT EXISTS conversation_messages (
+    user_id         BIGINT NOT NULL,
+    conversation_id VARCHAR(128) NOT NULL,
+    seq             INT NOT NULL,
+    role            VARCHAR(16) NOT NULL,
+    content         JSONB NOT NULL,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, conversation_id, seq),
+    FOREIGN KEY (user_id, conversation_id) REFERENCES conversations (user_id, id) ON DELETE CASCADE
+);
+
+COMMENT ON TABLE conversations IS '服务端持久化会话（按用户隔离，ID 由客户端指定）';
+COMMENT ON COLUMN conversations.system IS '最近一次请求的 system 字段，后续请求未携带时沿用';
+COMMENT ON COLUMN conversations.expires_at IS '最后一次更新时间 + conversation.ttl_days';
+COMMENT ON TABLE conversation_messages IS '会话消息（Anthropic Messages 格式），seq 从 1 递增';
@@ -0,0 +1,22 @@
+-- 会话分支：从任意一条历史消息分叉出新会话，分支只保存分叉点之后的消息，
+-- 分叉点及之前的历史沿 parent 链从祖先会话读取（seq 在整条链上连续）
+
+ALTER TABLE conversations
+    ADD COLUMN IF NOT EXISTS parent_id VARCHAR(128),
+    ADD COLUMN IF NOT EXISTS parent_seq INT;
+
+DO $$
+BEGIN
+  IF NOT EXISTS (SELECT 1 FROM pg_constraint WHERE conname = 'fk_conversations_parent') THEN
+    -- 删除会话时一并删除其所有分支（分支依赖祖先的历史消息）
+    ALTER TABLE conversations
+        ADD CONSTRAINT fk_conversations_parent FOREIGN KEY (user_id, parent_id)
+        REFERENCES conversations (user_id, id) ON DELETE CASCADE;
+  END IF;
+END $$;
+
+CREATE INDEX IF NOT EXISTS idx_conversations_parent ON conversations (user_id, parent_id) WHERE parent_id IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_conversations_user_updated ON conversations (user_id, updated_at DESC) WHERE parent_id IS NULL;
+
+COMMENT ON COLUMN conversations.parent_id IS '分叉来源会话 ID（为空表示根会话）';
+COMMENT ON COLUMN conversations.parent_seq IS '分叉点：继承来源会话 seq <= parent_seq 的消息';
@@ -0,0 +1,21 @@
+-- 多模态附件：通过 /v1/files 上传的文件，内容存储在对象存储，消息中按 ID 引用
+
+CREATE TABLE IF NOT EXISTS gateway_files (
+    id          VARCHAR(64) PRIMARY KEY,
+    user_id     BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    api_key_id  BIGINT,
+    filename    VARCHAR(255) NOT NULL DEFAULT '',
+    media_type  VARCHAR(100) NOT NULL,
+    size_bytes  BIGINT NOT NULL,
+    sha256      CHAR(64) NOT NULL,
+    object_key  VARCHAR(512) NOT NULL,
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at  TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_gateway_files_user_created ON gateway_files (user_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_gateway_files_expires_at ON gateway_files (expires_at);
+
+COMMENT ON TABLE gateway_files IS '用户上传的多模态附件（图片 / PDF / 文本），转发时内联为 base64';
+COMMENT ON COLUMN gateway_files.object_key IS '对象存储中的相对 key（不含部署前缀）';
+COMMENT ON COLUMN gateway_files.expires_at IS '上传时间 + attachments.ttl_hours';
@@ -0,0 +1,30 @@
+-- 提示词模板：用户维护的命名模板，每次修改生成不可变的新版本，请求中按 template_id 引用
+
+CREATE TABLE IF NOT EXISTS prompt_templates (
+    id              VARCHAR(64) PRIMARY KEY,
+    user_id         BIGINT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name            VARCHAR(128) NOT NULL,
+    description     TEXT NOT NULL DEFAULT '',
+    latest_version  INT NOT NULL DEFAULT 1,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at      TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (user_id, name)
+);
+
+CREATE INDEX IF NOT EXISTS idx_prompt_templates_user_updated ON prompt_templates (user_id, updated_at DESC);
+
+CREATE TABLE IF NOT EXISTS prompt_template_versions (
+    template_id  VARCHAR(64) NOT NULL REFERENCES prompt_templates(id) ON DELETE CASCADE,
+    version      INT NOT NULL,
+    system       TEXT NOT NULL DEFAULT '',
+    messages     JSONB NOT NULL DEFAULT '[]',
+    variables    JSONB NOT NULL DEFAULT '[]',
+    created_at   TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (template_id, version)
+);
+
+COMMENT ON TABLE prompt_templates IS '用户的命名提示词模板，请求中以 template_id（ID 或名称）引用';
+COMMENT ON COLUMN prompt_templates.latest_version IS '最新版本号，未指定 template_version 时使用';
+COMMENT ON TABLE prompt_template_versions IS '提示词模板的历史版本（不可修改）';
+COMMENT ON COLUMN prompt_template_versions.messages IS '[{"role":"user|assistant","content":"..."}]，内容可含 {{变量}} 占位符';
+COMMENT ON COLUMN prompt_template_versions.variables IS '[{"name":"...","description":"...","default":"..."}]，default 缺省表示必填';
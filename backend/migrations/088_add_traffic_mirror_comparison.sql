@@ -0,0 +1,12 @@
+-- 流量镜像对比：保存主请求与镜像请求输出的可比较特征，供对比报告按规则聚合
+
+ALTER TABLE traffic_mirror_samples
+    ADD COLUMN IF NOT EXISTS primary_stop_reason   VARCHAR(64) NOT NULL DEFAULT '',
+    ADD COLUMN IF NOT EXISTS primary_output_tokens INT NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS mirror_stop_reason    VARCHAR(64) NOT NULL DEFAULT '',
+    ADD COLUMN IF NOT EXISTS mirror_output_tokens  INT NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS output_match          BOOLEAN;
+
+CREATE INDEX IF NOT EXISTS idx_traffic_mirror_samples_created ON traffic_mirror_samples (created_at);
+
+COMMENT ON COLUMN traffic_mirror_samples.output_match IS '主/镜像输出结构（内容块类型与工具调用）及停止原因是否一致，任一输出无法解析时为 NULL';
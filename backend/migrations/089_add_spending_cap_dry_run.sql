@@ -0,0 +1,5 @@
+-- 消费上限演练模式：照常统计消费并记录本应拒绝的请求，但不拒绝、不暂停，用于上线前按真实流量调整上限
+
+ALTER TABLE spending_caps ADD COLUMN IF NOT EXISTS dry_run BOOLEAN NOT NULL DEFAULT FALSE;
+
+COMMENT ON COLUMN spending_caps.dry_run IS '演练模式：只记录本应拒绝的请求（日志与计数），不拒绝、不暂停、不通知';
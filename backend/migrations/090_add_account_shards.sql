@@ -0,0 +1,46 @@
+-- 账号分片：按出口地域 / 风险等级等将账号池划分为命名分片，路由规则把 API Key / 分组 / 模型的请求
+-- 限定到指定分片；分片被标记隔离后其账号不再参与调度，其余账号池不受影响。
+
+CREATE TABLE IF NOT EXISTS account_shards (
+    id              BIGSERIAL PRIMARY KEY,
+    name            VARCHAR(64) NOT NULL,
+    description     TEXT NOT NULL DEFAULT '',
+    isolated        BOOLEAN NOT NULL DEFAULT FALSE,
+    isolated_reason TEXT NOT NULL DEFAULT '',
+    isolated_at     TIMESTAMPTZ,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at      TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_account_shards_name ON account_shards (name);
+
+-- 每个账号最多属于一个分片
+CREATE TABLE IF NOT EXISTS account_shard_members (
+    account_id BIGINT PRIMARY KEY REFERENCES accounts(id) ON DELETE CASCADE,
+    shard_id   BIGINT NOT NULL REFERENCES account_shards(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_account_shard_members_shard ON account_shard_members (shard_id);
+
+CREATE TABLE IF NOT EXISTS account_shard_rules (
+    id            BIGSERIAL PRIMARY KEY,
+    shard_id      BIGINT NOT NULL REFERENCES account_shards(id) ON DELETE CASCADE,
+    priority      INT NOT NULL DEFAULT 0,
+    api_key_id    BIGINT,
+    group_id      BIGINT,
+    model_pattern VARCHAR(200) NOT NULL DEFAULT '',
+    enabled       BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at    TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at    TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_account_shard_rules_shard ON account_shard_rules (shard_id);
+
+COMMENT ON TABLE account_shards IS '账号分片（按出口地域 / 风险等级划分的账号池）';
+COMMENT ON COLUMN account_shards.isolated IS '隔离：分片内账号不参与任何调度，路由到该分片的请求直接失败';
+COMMENT ON TABLE account_shard_members IS '账号所属分片，未分配的账号属于默认池';
+COMMENT ON TABLE account_shard_rules IS '分片路由规则，按 priority 升序匹配第一条命中的规则';
+COMMENT ON COLUMN account_shard_rules.api_key_id IS '限定 API Key，NULL 表示不限';
+COMMENT ON COLUMN account_shard_rules.group_id IS '限定分组，NULL 表示不限';
+COMMENT ON COLUMN account_shard_rules.model_pattern IS '请求模型匹配模式，支持末尾 * 通配，空表示不限';
@@ -0,0 +1,20 @@
+-- 事件导出 outbox：用量、审计、告警事件先落库，再由后台转发任务批量发布到 Kafka / NATS。
+-- 发布成功后标记 published_at，失败按 attempts 指数退避重试，保证至少一次投递。
+
+CREATE TABLE IF NOT EXISTS event_outbox (
+    id              BIGSERIAL PRIMARY KEY,
+    event_type      VARCHAR(32) NOT NULL,
+    event_key       VARCHAR(128) NOT NULL DEFAULT '',
+    payload         JSONB NOT NULL,
+    attempts        INT NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_error      TEXT,
+    published_at    TIMESTAMPTZ,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- 待发布事件的认领顺序
+CREATE INDEX IF NOT EXISTS idx_event_outbox_pending ON event_outbox (next_attempt_at, id) WHERE published_at IS NULL;
+
+-- 已发布事件的过期清理
+CREATE INDEX IF NOT EXISTS idx_event_outbox_published_at ON event_outbox (published_at) WHERE published_at IS NOT NULL;
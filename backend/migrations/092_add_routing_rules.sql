@@ -0,0 +1,35 @@
+-- 声明式路由规则：按模型、API Key 标签、提示词长度、时间段与请求头匹配请求，
+-- 决定使用的分组、降级分组链与请求参数覆盖。
+
+CREATE TABLE IF NOT EXISTS routing_rules (
+    id                 BIGSERIAL PRIMARY KEY,
+    name               VARCHAR(100) NOT NULL,
+    description        TEXT NOT NULL DEFAULT '',
+    priority           INT NOT NULL DEFAULT 0,
+    enabled            BOOLEAN NOT NULL DEFAULT TRUE,
+    conditions         JSONB NOT NULL DEFAULT '{}'::jsonb,
+    target_group_id    BIGINT,
+    fallback_group_ids BIGINT[] NOT NULL DEFAULT '{}',
+    param_overrides    JSONB NOT NULL DEFAULT '{}'::jsonb,
+    created_at         TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at         TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_routing_rules_priority ON routing_rules (priority, id);
+
+-- API Key 标签，供路由规则的 key_tags 条件匹配
+CREATE TABLE IF NOT EXISTS api_key_tags (
+    api_key_id BIGINT NOT NULL REFERENCES api_keys(id) ON DELETE CASCADE,
+    tag        VARCHAR(64) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (api_key_id, tag)
+);
+
+CREATE INDEX IF NOT EXISTS idx_api_key_tags_tag ON api_key_tags (tag);
+
+COMMENT ON TABLE routing_rules IS '声明式路由规则，按 priority 升序匹配第一条命中的规则';
+COMMENT ON COLUMN routing_rules.conditions IS '匹配条件（models / group_ids / key_tags / min_prompt_chars / max_prompt_chars / time_window / headers），各条件同时满足才命中';
+COMMENT ON COLUMN routing_rules.target_group_id IS '命中后使用的分组，NULL 表示保持 API Key 原分组';
+COMMENT ON COLUMN routing_rules.fallback_group_ids IS '降级分组链：目标分组不可用或无可调度账号时依次尝试';
+COMMENT ON COLUMN routing_rules.param_overrides IS '请求体参数覆盖（键为 JSON 路径，值为 null 表示删除该字段）';
+COMMENT ON TABLE api_key_tags IS 'API Key 标签';
@@ -0,0 +1,4 @@
+-- 实际使用的上游（平台 + 账号类型）、模型与网关适配器版本的稳定指纹，客户端据此识别后端变化
+ALTER TABLE usage_logs ADD COLUMN IF NOT EXISTS system_fingerprint VARCHAR(32);
+
+COMMENT ON COLUMN usage_logs.system_fingerprint IS 'system_fingerprint，格式：fp_<10 位十六进制>，NULL 表示历史数据';
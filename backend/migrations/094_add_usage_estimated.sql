@@ -0,0 +1,4 @@
+-- 上游未返回用量（网页会话）时按本地分词器估算 token，计费时应用 billing.estimated_usage_margin
+ALTER TABLE usage_logs ADD COLUMN IF NOT EXISTS usage_estimated BOOLEAN NOT NULL DEFAULT FALSE;
+
+COMMENT ON COLUMN usage_logs.usage_estimated IS '用量是否为本地估算值（上游未返回 usage）';
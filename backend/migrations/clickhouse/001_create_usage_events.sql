@@ -0,0 +1,35 @@
+-- 用量明细（与 Postgres usage_logs 一一对应，仅用于分析查询）
+-- 重试的批次携带相同的 insert_deduplication_token，由 non_replicated_deduplication_window 去重
+CREATE TABLE IF NOT EXISTS usage_events
+(
+    usage_log_id          Int64,
+    request_id            String,
+    user_id               Int64,
+    api_key_id            Int64,
+    account_id            Int64,
+    group_id              Int64,
+    subscription_id       Int64,
+    model                 LowCardinality(String),
+    billing_type          UInt8,
+    stream                Bool,
+    input_tokens          UInt32,
+    output_tokens         UInt32,
+    cache_creation_tokens UInt32,
+    cache_read_tokens     UInt32,
+    reasoning_tokens      UInt32,
+    image_count           UInt32,
+    input_cost            Float64,
+    output_cost           Float64,
+    cache_creation_cost   Float64,
+    cache_read_cost       Float64,
+    total_cost            Float64,
+    actual_cost           Float64,
+    rate_multiplier       Float64,
+    duration_ms           Nullable(UInt32),
+    first_token_ms        Nullable(UInt32),
+    created_at            DateTime64(3, 'UTC')
+)
+ENGINE = MergeTree
+PARTITION BY toYYYYMM(created_at)
+ORDER BY (user_id, created_at, usage_log_id)
+SETTINGS non_replicated_deduplication_window = 1000;
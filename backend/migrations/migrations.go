@@ -32,3 +32,10 @@ import "embed"
 //
 //go:embed *.sql
 var FS embed.FS
+
+// ClickHouseFS 包含 clickhouse/ 下的 ClickHouse 表结构迁移文件（命名规范同上）。
+//
+// ClickHouse HTTP 接口每次请求只能执行一条语句，迁移文件中的多条语句以行尾分号分隔。
+//
+//go:embed clickhouse/*.sql
+var ClickHouseFS embed.FS